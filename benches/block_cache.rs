@@ -0,0 +1,138 @@
+//! Block cache throughput benchmark.
+//!
+//! Measures concurrent cached reads against a single slide's block cache with
+//! one shard (the previous single-lock design) and with the default shard
+//! count, plus cold reads that exercise fetch coalescing.
+//!
+//! Run with:
+//!
+//! ```text
+//! cargo bench --bench block_cache
+//! ```
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use wsi_streamer::error::IoError;
+use wsi_streamer::io::{BlockCache, RangeReader, DEFAULT_BLOCK_CACHE_SHARDS};
+
+const BLOCK_SIZE: usize = 64 * 1024;
+const BLOCKS: usize = 256;
+const TASKS: usize = 64;
+const READS_PER_TASK: usize = 20_000;
+
+/// In-memory reader with a fixed per-request latency.
+struct MemoryReader {
+    data: Bytes,
+    latency: Duration,
+    reads: AtomicUsize,
+}
+
+#[async_trait]
+impl RangeReader for MemoryReader {
+    async fn read_exact_at(&self, offset: u64, len: usize) -> Result<Bytes, IoError> {
+        self.reads.fetch_add(1, Ordering::Relaxed);
+        if !self.latency.is_zero() {
+            tokio::time::sleep(self.latency).await;
+        }
+        Ok(self.data.slice(offset as usize..offset as usize + len))
+    }
+
+    fn size(&self) -> u64 {
+        self.data.len() as u64
+    }
+
+    fn identifier(&self) -> &str {
+        "memory://bench"
+    }
+}
+
+fn reader(latency: Duration) -> MemoryReader {
+    MemoryReader {
+        data: Bytes::from(vec![0u8; BLOCK_SIZE * BLOCKS]),
+        latency,
+        reads: AtomicUsize::new(0),
+    }
+}
+
+/// Concurrent small reads over a fully cached slide.
+async fn warm_reads(shards: usize) -> f64 {
+    let cache = Arc::new(BlockCache::with_shards(
+        reader(Duration::ZERO),
+        BLOCK_SIZE,
+        BLOCKS,
+        shards,
+    ));
+    cache
+        .read_exact_at(0, BLOCK_SIZE * BLOCKS)
+        .await
+        .expect("warm-up read");
+
+    let start = Instant::now();
+    let mut handles = Vec::with_capacity(TASKS);
+    for task in 0..TASKS {
+        let cache = cache.clone();
+        handles.push(tokio::spawn(async move {
+            for i in 0..READS_PER_TASK {
+                let block = (task * 31 + i * 7) % BLOCKS;
+                let offset = (block * BLOCK_SIZE + 128) as u64;
+                cache.read_exact_at(offset, 4096).await.expect("read");
+            }
+        }));
+    }
+    for handle in handles {
+        handle.await.expect("task");
+    }
+
+    (TASKS * READS_PER_TASK) as f64 / start.elapsed().as_secs_f64()
+}
+
+/// Concurrent reads of the same cold blocks; returns underlying fetches.
+async fn cold_reads(shards: usize) -> usize {
+    let cache = Arc::new(BlockCache::with_shards(
+        reader(Duration::from_millis(20)),
+        BLOCK_SIZE,
+        BLOCKS,
+        shards,
+    ));
+
+    let mut handles = Vec::with_capacity(TASKS);
+    for _ in 0..TASKS {
+        let cache = cache.clone();
+        handles.push(tokio::spawn(async move {
+            for block in 0..8 {
+                let offset = (block * BLOCK_SIZE) as u64;
+                cache.read_exact_at(offset, 1024).await.expect("read");
+            }
+        }));
+    }
+    for handle in handles {
+        handle.await.expect("task");
+    }
+
+    cache.stats().fetches as usize
+}
+
+#[tokio::main(flavor = "multi_thread")]
+async fn main() {
+    println!("block_cache: {} tasks x {} reads", TASKS, READS_PER_TASK);
+
+    for shards in [1, DEFAULT_BLOCK_CACHE_SHARDS] {
+        let throughput = warm_reads(shards).await;
+        println!(
+            "  warm reads, {:>2} shard(s): {:>12.0} reads/s",
+            shards, throughput
+        );
+    }
+
+    for shards in [1, DEFAULT_BLOCK_CACHE_SHARDS] {
+        let fetches = cold_reads(shards).await;
+        println!(
+            "  cold reads, {:>2} shard(s): {} fetches for 8 blocks",
+            shards, fetches
+        );
+    }
+}
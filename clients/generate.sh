@@ -0,0 +1,58 @@
+#!/bin/bash
+#
+# Generate the TypeScript and Python clients from openapi.json.
+#
+# Generated code is not committed; it is rebuilt here and by the release
+# workflow. The signing helpers in each client are handwritten.
+#
+# Prerequisites:
+#   - Node.js 20+ (npx)
+#   - Python 3.9+ with openapi-python-client (pip install openapi-python-client)
+#
+# Usage:
+#   ./clients/generate.sh            # both clients
+#   ./clients/generate.sh typescript
+#   ./clients/generate.sh python
+#
+
+set -euo pipefail
+
+CLIENTS_DIR="$(cd "$(dirname "$0")" && pwd)"
+SPEC="$CLIENTS_DIR/openapi.json"
+VERSION=$(grep -m1 '^version' "$CLIENTS_DIR/../Cargo.toml" | sed 's/version = "\(.*\)"/\1/')
+TARGETS="${1:-typescript python}"
+
+SPEC_VERSION=$(python3 -c "import json,sys; print(json.load(open(sys.argv[1]))['info']['version'])" "$SPEC")
+if [ "$SPEC_VERSION" != "$VERSION" ]; then
+    echo "openapi.json version ($SPEC_VERSION) does not match Cargo.toml ($VERSION)" >&2
+    exit 1
+fi
+
+for target in $TARGETS; do
+    case "$target" in
+        typescript)
+            echo "==> Generating TypeScript client $VERSION"
+            cd "$CLIENTS_DIR/typescript"
+            rm -rf src/generated
+            npm pkg set version="$VERSION"
+            npm install --no-audit --no-fund
+            npx openapi-ts
+            npm run build
+            ;;
+        python)
+            echo "==> Generating Python client $VERSION"
+            cd "$CLIENTS_DIR/python"
+            rm -rf wsi_streamer_client/generated
+            openapi-python-client generate \
+                --path "$SPEC" \
+                --meta none \
+                --output-path wsi_streamer_client/generated \
+                --overwrite
+            sed -i.bak "s/^version = .*/version = \"$VERSION\"/" pyproject.toml && rm pyproject.toml.bak
+            ;;
+        *)
+            echo "Unknown target: $target (expected typescript or python)" >&2
+            exit 1
+            ;;
+    esac
+done
@@ -0,0 +1,30 @@
+import json
+from pathlib import Path
+
+from wsi_streamer_client.signing import sign_path, sign_url, viewer_token
+
+# Shared with the Rust and TypeScript test suites
+VECTORS = json.loads((Path(__file__).parents[2] / "signing_vectors.json").read_text())
+
+
+def test_signatures_match_server():
+    for v in VECTORS["signatures"]:
+        params = dict(v["params"])
+        assert sign_path(VECTORS["secret"], v["path"], v["expiry"], params) == v["signature"]
+
+
+def test_viewer_tokens_match_server():
+    for v in VECTORS["viewer_tokens"]:
+        assert viewer_token(VECTORS["secret"], v["slide_id"], v["expiry"]) == v["token"]
+
+
+def test_sign_url_appends_exp_and_sig():
+    v = VECTORS["signatures"][1]
+    url = sign_url(
+        VECTORS["secret"],
+        "http://localhost:3000/",
+        v["path"],
+        params=dict(v["params"]),
+        now=v["expiry"] - 3600,
+    )
+    assert url == f"http://localhost:3000{v['path']}?quality=80&exp={v['expiry']}&sig={v['signature']}"
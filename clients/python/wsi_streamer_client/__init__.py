@@ -0,0 +1,30 @@
+"""Typed client for WSI Streamer.
+
+The HTTP client in :mod:`wsi_streamer_client.generated` is generated from
+``clients/openapi.json``; :mod:`wsi_streamer_client.signing` is handwritten.
+"""
+
+from .signing import (
+    SigningAuth,
+    canonical_query,
+    encode_slide_id,
+    sign_path,
+    sign_url,
+    viewer_token,
+)
+
+try:
+    from .generated import AuthenticatedClient, Client
+except ImportError:  # generated code not built (source checkout)
+    pass
+
+__all__ = [
+    "AuthenticatedClient",
+    "Client",
+    "SigningAuth",
+    "canonical_query",
+    "encode_slide_id",
+    "sign_path",
+    "sign_url",
+    "viewer_token",
+]
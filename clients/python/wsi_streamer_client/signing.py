@@ -0,0 +1,95 @@
+"""Signed URL helpers for WSI Streamer.
+
+Mirrors the server's signing scheme (``src/server/auth.rs``)::
+
+    signature = hex(HMAC-SHA256(secret, "{path}?{canonical_query}"))
+
+where the canonical query is every query parameter except ``sig``, ``exp``
+included, sorted by key then value and joined as ``key=value`` without
+percent-encoding. ``path`` is the request path exactly as sent (slide IDs
+percent-encoded).
+"""
+
+from __future__ import annotations
+
+import hashlib
+import hmac
+import time
+from typing import Mapping, Optional, Union
+from urllib.parse import parse_qsl, quote, urlencode, urlsplit
+
+import httpx
+
+QueryValue = Union[str, int]
+
+
+def _hmac_hex(secret: str, message: str) -> str:
+    return hmac.new(secret.encode(), message.encode(), hashlib.sha256).hexdigest()
+
+
+def canonical_query(params: Mapping[str, QueryValue], expiry: int) -> str:
+    """Canonical query string signed by the server."""
+    pairs = [(k, str(v)) for k, v in params.items() if k not in ("sig", "exp")]
+    pairs.append(("exp", str(expiry)))
+    pairs.sort()
+    return "&".join(f"{k}={v}" for k, v in pairs)
+
+
+def encode_slide_id(slide_id: str) -> str:
+    """Encode a slide ID as a single path segment."""
+    return quote(slide_id, safe="")
+
+
+def sign_path(
+    secret: str,
+    path: str,
+    expiry: int,
+    params: Optional[Mapping[str, QueryValue]] = None,
+) -> str:
+    """Compute the signature of a path at a fixed expiry."""
+    return _hmac_hex(secret, f"{path}?{canonical_query(params or {}, expiry)}")
+
+
+def sign_url(
+    secret: str,
+    base_url: str,
+    path: str,
+    ttl_seconds: int = 3600,
+    params: Optional[Mapping[str, QueryValue]] = None,
+    now: Optional[int] = None,
+) -> str:
+    """Build a signed URL.
+
+    ``path`` must already be percent-encoded (see :func:`encode_slide_id`).
+    """
+    params = dict(params or {})
+    expiry = (int(time.time()) if now is None else now) + ttl_seconds
+    sig = sign_path(secret, path, expiry, params)
+    query = urlencode([*((k, str(v)) for k, v in params.items()), ("exp", expiry), ("sig", sig)])
+    return f"{base_url.rstrip('/')}{path}?{query}"
+
+
+def viewer_token(secret: str, slide_id: str, expiry: int) -> str:
+    """Compute a viewer token authorizing every tile, DZI and IIIF request
+    for one slide. Append it as ``?vt={token}&exp={expiry}``."""
+    return _hmac_hex(secret, f"viewer:{slide_id}:{expiry}")
+
+
+class SigningAuth(httpx.Auth):
+    """httpx auth that signs every request path before sending it.
+
+    Pass it to the generated client via
+    ``Client(base_url=..., httpx_args={"auth": SigningAuth(secret)})``.
+    """
+
+    def __init__(self, secret: str, ttl_seconds: int = 3600) -> None:
+        self.secret = secret
+        self.ttl_seconds = ttl_seconds
+
+    def auth_flow(self, request: httpx.Request):
+        url = urlsplit(str(request.url))
+        params = {k: v for k, v in parse_qsl(url.query, keep_blank_values=True)}
+        origin = f"{url.scheme}://{url.netloc}"
+        signed = sign_url(self.secret, origin, url.path, self.ttl_seconds, params)
+        request.url = httpx.URL(signed)
+        yield request
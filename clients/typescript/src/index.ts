@@ -0,0 +1,10 @@
+export * from "./generated/index.js";
+export {
+  canonicalQuery,
+  encodeSlideId,
+  signPath,
+  signUrl,
+  signingFetch,
+  viewerToken,
+} from "./signing.js";
+export type { QueryParams, SignedUrlOptions } from "./signing.js";
@@ -0,0 +1,35 @@
+import assert from "node:assert/strict";
+import { readFileSync } from "node:fs";
+import { test } from "node:test";
+
+import { signPath, signUrl, viewerToken } from "./signing.js";
+
+// Shared with the Rust and Python test suites
+const vectors = JSON.parse(
+  readFileSync(new URL("../../signing_vectors.json", import.meta.url), "utf8"),
+);
+
+test("signatures match the server", async () => {
+  for (const v of vectors.signatures) {
+    const params = Object.fromEntries(v.params);
+    assert.equal(await signPath(vectors.secret, v.path, v.expiry, params), v.signature);
+  }
+});
+
+test("viewer tokens match the server", async () => {
+  for (const v of vectors.viewer_tokens) {
+    assert.equal(await viewerToken(vectors.secret, v.slide_id, v.expiry), v.token);
+  }
+});
+
+test("signUrl appends exp and sig", async () => {
+  const v = vectors.signatures[1];
+  const url = await signUrl(vectors.secret, "http://localhost:3000/", v.path, {
+    params: Object.fromEntries(v.params),
+    now: v.expiry - 3600,
+  });
+  assert.equal(
+    url,
+    `http://localhost:3000${v.path}?quality=80&exp=${v.expiry}&sig=${v.signature}`,
+  );
+});
@@ -0,0 +1,132 @@
+/**
+ * Signed URL helpers for WSI Streamer.
+ *
+ * Mirrors the server's signing scheme (`src/server/auth.rs`):
+ *
+ *     signature = hex(HMAC-SHA256(secret, "{path}?{canonical_query}"))
+ *
+ * where the canonical query is every query parameter except `sig`, `exp`
+ * included, sorted by key then value and joined as `key=value` without
+ * percent-encoding. `path` is the request path exactly as sent (slide IDs
+ * percent-encoded).
+ *
+ * Uses Web Crypto, so it runs in browsers and Node.js 20+. Only sign on a
+ * trusted backend: the secret must never reach the browser.
+ */
+
+export type QueryParams = Record<string, string | number>;
+
+export interface SignedUrlOptions {
+  /** Signature lifetime in seconds (default: 3600) */
+  ttlSeconds?: number;
+  /** Extra query parameters to bind into the signature (e.g. `quality`) */
+  params?: QueryParams;
+  /** Current time in Unix seconds (default: now) */
+  now?: number;
+}
+
+const encoder = new TextEncoder();
+
+async function hmacSha256Hex(secret: string, message: string): Promise<string> {
+  const key = await crypto.subtle.importKey(
+    "raw",
+    encoder.encode(secret),
+    { name: "HMAC", hash: "SHA-256" },
+    false,
+    ["sign"],
+  );
+  const mac = await crypto.subtle.sign("HMAC", key, encoder.encode(message));
+  return Array.from(new Uint8Array(mac), (b) => b.toString(16).padStart(2, "0")).join("");
+}
+
+function compare(a: string, b: string): number {
+  return a < b ? -1 : a > b ? 1 : 0;
+}
+
+/** Canonical query string signed by the server. */
+export function canonicalQuery(params: QueryParams, expiry: number): string {
+  const pairs: [string, string][] = Object.entries(params)
+    .filter(([key]) => key !== "sig" && key !== "exp")
+    .map(([key, value]) => [key, String(value)]);
+  pairs.push(["exp", String(expiry)]);
+  pairs.sort((a, b) => compare(a[0], b[0]) || compare(a[1], b[1]));
+  return pairs.map(([key, value]) => `${key}=${value}`).join("&");
+}
+
+/** Encode a slide ID as a single path segment. */
+export function encodeSlideId(slideId: string): string {
+  return encodeURIComponent(slideId);
+}
+
+/** Compute the signature of a path at a fixed expiry. */
+export async function signPath(
+  secret: string,
+  path: string,
+  expiry: number,
+  params: QueryParams = {},
+): Promise<string> {
+  return hmacSha256Hex(secret, `${path}?${canonicalQuery(params, expiry)}`);
+}
+
+/**
+ * Build a signed URL.
+ *
+ * `path` must already be percent-encoded (see {@link encodeSlideId}).
+ */
+export async function signUrl(
+  secret: string,
+  baseUrl: string,
+  path: string,
+  options: SignedUrlOptions = {},
+): Promise<string> {
+  const now = options.now ?? Math.floor(Date.now() / 1000);
+  const expiry = now + (options.ttlSeconds ?? 3600);
+  const params = options.params ?? {};
+  const sig = await signPath(secret, path, expiry, params);
+
+  const query = new URLSearchParams();
+  for (const [key, value] of Object.entries(params)) {
+    query.append(key, String(value));
+  }
+  query.append("exp", String(expiry));
+  query.append("sig", sig);
+  return `${baseUrl.replace(/\/$/, "")}${path}?${query.toString()}`;
+}
+
+/**
+ * Compute a viewer token authorizing every tile, DZI and IIIF request for
+ * one slide. Append it as `?vt={token}&exp={expiry}`.
+ */
+export async function viewerToken(
+  secret: string,
+  slideId: string,
+  expiry: number,
+): Promise<string> {
+  return hmacSha256Hex(secret, `viewer:${slideId}:${expiry}`);
+}
+
+/**
+ * A `fetch` wrapper that signs every request path before sending it.
+ *
+ * Pass it as the `fetch` option of the generated client so every call is
+ * signed transparently.
+ */
+export function signingFetch(
+  secret: string,
+  options: { ttlSeconds?: number; fetch?: typeof fetch } = {},
+): typeof fetch {
+  const inner = options.fetch ?? fetch;
+  return async (input, init) => {
+    const request = new Request(input, init);
+    const url = new URL(request.url);
+    const params: QueryParams = {};
+    url.searchParams.forEach((value, key) => {
+      params[key] = value;
+    });
+    const signed = await signUrl(secret, url.origin, url.pathname, {
+      ttlSeconds: options.ttlSeconds,
+      params,
+    });
+    return inner(new Request(signed, request));
+  };
+}
@@ -0,0 +1,189 @@
+//! Build capabilities report.
+//!
+//! Heavy codec and integration dependencies sit behind individual Cargo
+//! features, so a given binary may not support every source compression or
+//! extension. [`capabilities`] reports what this build was compiled with,
+//! along with the endpoints declared in the route table. It backs the
+//! `GET /capabilities` endpoint and the `wsi-streamer capabilities` command.
+//!
+//! # Cargo Features
+//!
+//! | Feature | Enables | Default |
+//! |---------|---------|---------|
+//! | `jpeg2000` | JPEG 2000 (Aperio 33003) source tiles via OpenJPEG | yes |
+//! | `webp` | Lossy WebP tile output via libwebp | yes |
+//! | `inference` | HTTP inference sidecar tile transformer | no |
+//! | `aws-secrets` | `secretsmanager://` and `ssm://` secret references | no |
+//! | `gcs` | Google Cloud Storage slide backend (`--storage=gcs`) | no |
+//! | `azure` | Azure Blob Storage slide backend (`--storage=azure`) | no |
+//! | `http` | HTTP(S) URL slide backend (`--storage=http`) | no |
+//! | `otel` | OTLP span export (`--otel-endpoint`) | no |
+//! | `full` | All of the above | no |
+//!
+//! A minimal build is `cargo build --no-default-features`; a full one is
+//! `cargo build --features full`.
+
+use serde::Serialize;
+
+use crate::format::tiff::Compression;
+use crate::format::SlideFormat;
+use crate::server::route_table::{endpoints, EndpointSummary};
+use crate::tile::OutputFormat;
+
+/// A Cargo feature and whether this build includes it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FeatureStatus {
+    /// Cargo feature name
+    pub name: &'static str,
+
+    /// Whether the feature was compiled in
+    pub enabled: bool,
+
+    /// What the feature provides
+    pub description: &'static str,
+}
+
+/// Capabilities of this build.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Capabilities {
+    /// Crate version
+    pub version: &'static str,
+
+    /// Optional Cargo features
+    pub features: Vec<FeatureStatus>,
+
+    /// Slide container formats that can be opened
+    pub formats: Vec<&'static str>,
+
+    /// Source tile compressions that can be decoded
+    pub compressions: Vec<&'static str>,
+
+    /// Output tile encodings
+    pub outputs: Vec<&'static str>,
+
+    /// HTTP endpoints, from the route table
+    pub endpoints: Vec<EndpointSummary>,
+}
+
+impl Capabilities {
+    /// Check whether a named feature is enabled in this build.
+    pub fn has_feature(&self, name: &str) -> bool {
+        self.features.iter().any(|f| f.name == name && f.enabled)
+    }
+}
+
+/// Report the capabilities of this build.
+pub fn capabilities() -> Capabilities {
+    let features = vec![
+        FeatureStatus {
+            name: "jpeg2000",
+            enabled: cfg!(feature = "jpeg2000"),
+            description: "JPEG 2000 source tiles",
+        },
+        FeatureStatus {
+            name: "webp",
+            enabled: cfg!(feature = "webp"),
+            description: "WebP tile output",
+        },
+        FeatureStatus {
+            name: "tls",
+            enabled: cfg!(feature = "tls"),
+            description: "HTTPS listener",
+        },
+        FeatureStatus {
+            name: "inference",
+            enabled: cfg!(feature = "inference"),
+            description: "HTTP inference sidecar tile transformer",
+        },
+        FeatureStatus {
+            name: "aws-secrets",
+            enabled: cfg!(feature = "aws-secrets"),
+            description: "AWS Secrets Manager and SSM secret references",
+        },
+        FeatureStatus {
+            name: "gcs",
+            enabled: cfg!(feature = "gcs"),
+            description: "Google Cloud Storage slide backend",
+        },
+        FeatureStatus {
+            name: "azure",
+            enabled: cfg!(feature = "azure"),
+            description: "Azure Blob Storage slide backend",
+        },
+        FeatureStatus {
+            name: "http",
+            enabled: cfg!(feature = "http"),
+            description: "HTTP(S) URL slide backend",
+        },
+        FeatureStatus {
+            name: "otel",
+            enabled: cfg!(feature = "otel"),
+            description: "OpenTelemetry span export over OTLP",
+        },
+    ];
+
+    let compressions = [
+        Compression::None,
+        Compression::Lzw,
+        Compression::OldJpeg,
+        Compression::Jpeg,
+        Compression::Deflate,
+        Compression::AdobeDeflate,
+        Compression::Jpeg2000,
+    ]
+    .into_iter()
+    .filter(|c| c.is_supported())
+    .map(Compression::name)
+    .collect();
+
+    Capabilities {
+        version: env!("CARGO_PKG_VERSION"),
+        features,
+        formats: [SlideFormat::AperioSvs, SlideFormat::GenericTiff]
+            .iter()
+            .map(SlideFormat::name)
+            .collect(),
+        compressions,
+        outputs: OutputFormat::ALL
+            .into_iter()
+            .filter(OutputFormat::is_supported)
+            .map(|format| format.name())
+            .collect(),
+        endpoints: endpoints(),
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capabilities_reflect_build() {
+        let caps = capabilities();
+
+        assert_eq!(caps.version, env!("CARGO_PKG_VERSION"));
+        assert!(caps.compressions.contains(&"JPEG"));
+        assert_eq!(
+            caps.compressions.contains(&"JPEG 2000"),
+            cfg!(feature = "jpeg2000")
+        );
+        assert_eq!(caps.has_feature("jpeg2000"), cfg!(feature = "jpeg2000"));
+        assert_eq!(caps.has_feature("webp"), cfg!(feature = "webp"));
+        assert!(caps.outputs.contains(&"png"));
+        assert_eq!(caps.outputs.contains(&"webp"), cfg!(feature = "webp"));
+        assert_eq!(caps.has_feature("inference"), cfg!(feature = "inference"));
+        assert_eq!(caps.has_feature("gcs"), cfg!(feature = "gcs"));
+        assert_eq!(caps.has_feature("azure"), cfg!(feature = "azure"));
+        assert_eq!(caps.has_feature("http"), cfg!(feature = "http"));
+        assert_eq!(caps.has_feature("otel"), cfg!(feature = "otel"));
+        assert!(!caps.has_feature("unknown"));
+        assert!(caps
+            .endpoints
+            .iter()
+            .any(|e| e.method == "GET" && e.path == "/tiles/{slide_id}/{level}/{x}/{y}.jpg"));
+    }
+}
@@ -10,6 +10,10 @@
 //! - `serve` (default): Start the tile server
 //! - `sign`: Generate signed URLs for authentication
 //! - `check`: Validate configuration and test S3 connectivity
+//! - `plan`: Estimate cache sizes and S3 load for a target workload
+//! - `capabilities`: Print the codecs and optional features compiled in
+//! - `manifest`: Write a checksum manifest for a local slide file
+//! - `inspect`: Dump the TIFF structure and tags of a local slide file
 //!
 //! # Example
 //!
@@ -21,31 +25,78 @@
 //!     Cli::Serve(config) => { /* start server */ }
 //!     Cli::Sign(config) => { /* generate signed URL */ }
 //!     Cli::Check(config) => { /* validate config */ }
+//!     Cli::Plan(config) => { /* print capacity plan */ }
+//!     Cli::Capabilities => { /* print build capabilities */ }
 //! }
 //! ```
 //!
 //! # Environment Variables
 //!
-//! All configuration options can be set via environment variables with the `WSI_` prefix:
+//! All serve options can be set via environment variables with the `WSI_` prefix,
+//! or the long-form `WSI_STREAMER_` prefix (e.g. `WSI_STREAMER_PORT`), which takes
+//! precedence. This allows container deployments to configure the server without
+//! command-line arguments, keeping secrets out of `ps` output. List options
+//! (`WSI_CORS_ORIGINS`, `WSI_TENANT_QUOTAS`, `WSI_PRELOAD_INTO_MEMORY`,
+//! `WSI_WARM_SLIDES`) accept comma- or newline-separated
+//! items or a JSON array of strings.
+//!
 //!
 //! - `WSI_HOST` - Server bind address (default: 0.0.0.0)
 //! - `WSI_PORT` - Server port (default: 3000)
 //! - `WSI_S3_BUCKET` - S3 bucket name
 //! - `WSI_S3_ENDPOINT` - Custom S3 endpoint for S3-compatible services
 //! - `WSI_S3_REGION` - AWS region (default: us-east-1)
-//! - `WSI_AUTH_SECRET` - HMAC secret for signed URLs
+//! - `WSI_S3_FAILOVER_REGION` / `WSI_S3_FAILOVER_ENDPOINT` / `WSI_S3_FAILOVER_BUCKET` -
+//!   Secondary bucket used when the primary is unavailable
+//! - `WSI_AUTH_SECRET` - HMAC secret for signed URLs (literal or secret reference)
+//! - `WSI_AUTH_SECRET_FILE` - File containing the HMAC secret
 //! - `WSI_AUTH_ENABLED` - Enable authentication (default: false)
+//! - `WSI_PRELOAD_INTO_MEMORY` - Slides to serve from memory without a storage backend
+//! - `WSI_WARM_SLIDES` - Slides (IDs or glob patterns) to open and warm at startup
 //! - `WSI_CACHE_SLIDES` - Max slides to cache (default: 100)
 //! - `WSI_CACHE_BLOCKS` - Max blocks per slide (default: 100)
 //! - `WSI_CACHE_TILES` - Tile cache size in bytes (default: 100MB)
 //! - `WSI_JPEG_QUALITY` - Default JPEG quality (default: 80)
 //! - `WSI_CACHE_MAX_AGE` - HTTP cache max-age seconds (default: 3600)
-
-use clap::{Args, Parser, Subcommand, ValueEnum};
+//! - `WSI_RATE_LIMIT` - Requests per second per client (default: 0 = unlimited)
+//! - `WSI_SLO_AVAILABILITY` / `WSI_SLO_LATENCY` - Tile request SLO targets
+//!   (default: 0.999 / 0.99)
+
+use clap::error::{ContextKind, ContextValue};
+use clap::parser::ValueSource;
+use clap::{Arg, Args, CommandFactory, Parser, Subcommand, ValueEnum};
+use serde_json::{Map, Value};
 use std::fmt;
-
-use crate::io::DEFAULT_BLOCK_SIZE;
-use crate::tile::{DEFAULT_JPEG_QUALITY, DEFAULT_TILE_CACHE_CAPACITY};
+use std::path::{Path, PathBuf};
+
+use crate::error::SecretError;
+use crate::format::tiff::{
+    PyramidDetectOptions, TiffLimits, DEFAULT_DOWNSAMPLE_TOLERANCE, DEFAULT_MAX_ARRAY_BYTES,
+    DEFAULT_MAX_IFD_ENTRIES, DEFAULT_MAX_JPEG_TABLES_BYTES, DEFAULT_MAX_TILE_COUNT,
+};
+use crate::io::{
+    S3RetryPolicy, DEFAULT_BLOCK_SIZE, DEFAULT_S3_MAX_ATTEMPTS, DEFAULT_S3_RETRY_BASE_DELAY,
+};
+use crate::plan::CapacityInputs;
+use crate::replay::ReplayOptions;
+use crate::secrets::{read_secret_file, resolve_secret};
+use crate::server::auth::AccessClaims;
+use crate::server::{
+    BandwidthConfig, RateLimitConfig, RateLimitKey, DEFAULT_AVAILABILITY_TARGET,
+    DEFAULT_LATENCY_TARGET, DEFAULT_SECRET_ROTATION_GRACE,
+};
+use crate::slide::{parse_color, SparseTiles};
+use crate::slide::{
+    AdaptiveCacheConfig, DEFAULT_COLD_BLOCK_CACHE_CAPACITY, DEFAULT_FAILOVER_COOLDOWN,
+    DEFAULT_HOT_BLOCK_CACHE_CAPACITY, DEFAULT_MANIFEST_BLOCK_SIZE, DEFAULT_MAX_CONCURRENT_OPENS,
+    DEFAULT_NEGATIVE_CACHE_TTL, DEFAULT_QUARANTINE_THRESHOLD, DEFAULT_READAHEAD_TILES,
+    DEFAULT_WARM_CONCURRENCY, MANIFEST_SUFFIX, TENANT_SEPARATOR,
+};
+use crate::telemetry::DEFAULT_OTEL_SAMPLE_RATE;
+use crate::tile::{
+    CacheAdmission, ResampleFilter, TenantQuota, Watermark, WatermarkCorner, DEFAULT_JPEG_QUALITY,
+    DEFAULT_TILE_CACHE_CAPACITY, DEFAULT_TILE_CACHE_SHARDS, DEFAULT_WATERMARK_OPACITY,
+};
 
 // =============================================================================
 // Default Values
@@ -57,6 +108,9 @@ pub const DEFAULT_HOST: &str = "0.0.0.0";
 /// Default server port.
 pub const DEFAULT_PORT: u16 = 3000;
 
+/// Default admin listener host (loopback only).
+pub const DEFAULT_ADMIN_HOST: &str = "127.0.0.1";
+
 /// Default AWS region.
 pub const DEFAULT_REGION: &str = "us-east-1";
 
@@ -66,12 +120,280 @@ pub const DEFAULT_SLIDE_CACHE_CAPACITY: usize = 100;
 /// Default number of blocks to cache per slide.
 pub const DEFAULT_BLOCK_CACHE_CAPACITY: usize = 100;
 
+/// Default time a slide open may wait for a free slot, in milliseconds.
+pub const DEFAULT_OPEN_QUEUE_TIMEOUT_MS: u64 = 30_000;
+
+/// Default time a tile request may wait for a generation slot, in milliseconds.
+pub const DEFAULT_TILE_QUEUE_TIMEOUT_MS: u64 = 30_000;
+
+/// Default interval between storage endpoint health checks, in seconds.
+pub const DEFAULT_S3_HEALTH_CHECK_INTERVAL_SECS: u64 = 10;
+
+/// Default interval between checks of cached slides for replaced files, in seconds.
+pub const DEFAULT_SLIDE_REVALIDATE_INTERVAL_SECS: u64 = 300;
+
+/// Default number of retries for transient HTTP source failures.
+pub const DEFAULT_HTTP_RETRIES: u32 = 3;
+
 /// Default HTTP cache max-age in seconds (1 hour).
 pub const DEFAULT_CACHE_MAX_AGE: u32 = 3600;
 
+/// Default interval between checks of the configuration and secret files
+/// for changes, in seconds.
+pub const DEFAULT_CONFIG_WATCH_INTERVAL_SECS: u64 = 10;
+
+/// Serve options a running server reloads from its configuration file.
+pub const RELOADABLE_OPTIONS: [&str; 5] = [
+    "auth_secret",
+    "auth_secret_file",
+    "cors_origins",
+    "cache_max_age",
+    "verbose",
+];
+
 /// Default TTL for signed URLs in seconds (1 hour).
 pub const DEFAULT_SIGN_TTL: u64 = 3600;
 
+/// Default timeout for inference sidecar requests in milliseconds.
+pub const DEFAULT_INFERENCE_TIMEOUT_MS: u64 = 5000;
+
+/// Default latency SLO threshold for tile requests in milliseconds.
+pub const DEFAULT_SLO_LATENCY_THRESHOLD_MS: u64 = 500;
+
+// =============================================================================
+// Environment Aliases
+// =============================================================================
+
+/// Prefix of the long-form environment variables (`WSI_STREAMER_PORT`, ...).
+pub const ENV_ALIAS_PREFIX: &str = "WSI_STREAMER_";
+
+/// Prefix of the environment variables read by the CLI parser.
+pub const ENV_PREFIX: &str = "WSI_";
+
+/// Environment variables holding comma-separated lists.
+const LIST_ENV_VARS: [&str; 7] = [
+    "WSI_CORS_ORIGINS",
+    "WSI_TENANT_QUOTAS",
+    "WSI_PRELOAD_INTO_MEMORY",
+    "WSI_WARM_SLIDES",
+    "WSI_BANDWIDTH_KEY_LIMITS",
+    "WSI_TENANT_BUCKETS",
+    "WSI_TENANT_CACHE_SLIDES",
+];
+
+/// Normalize a list value to the comma-separated form the CLI parser expects.
+///
+/// Accepts a JSON array of strings (`["a", "b"]`), or items separated by
+/// commas and/or newlines, as produced by YAML block scalars in Helm values.
+pub fn normalize_list_env(value: &str) -> Result<String, String> {
+    let trimmed = value.trim();
+    let items: Vec<String> = if trimmed.starts_with('[') {
+        serde_json::from_str(trimmed)
+            .map_err(|e| format!("Invalid JSON list '{}': {}", trimmed, e))?
+    } else {
+        trimmed
+            .split([',', '\n'])
+            .map(|item| item.trim().to_string())
+            .filter(|item| !item.is_empty())
+            .collect()
+    };
+    Ok(items.join(","))
+}
+
+/// Resolve environment variables into the `WSI_*` names the CLI reads.
+///
+/// `WSI_STREAMER_*` variables are mapped to their `WSI_*` equivalent and
+/// take precedence over it, and list variables are normalized with
+/// [`normalize_list_env`]. Returns the variables to set.
+pub fn resolve_env_aliases(
+    vars: impl IntoIterator<Item = (String, String)>,
+) -> Result<Vec<(String, String)>, String> {
+    let mut resolved: Vec<(String, String)> = Vec::new();
+    let mut aliased = std::collections::HashSet::new();
+
+    for (name, value) in vars {
+        let (target, is_alias) = match name.strip_prefix(ENV_ALIAS_PREFIX) {
+            Some(rest) if !rest.is_empty() => (format!("{}{}", ENV_PREFIX, rest), true),
+            _ if name.starts_with(ENV_PREFIX) => (name, false),
+            _ => continue,
+        };
+        // Aliases override the short form regardless of iteration order
+        if !is_alias && aliased.contains(&target) {
+            continue;
+        }
+
+        let value = if LIST_ENV_VARS.contains(&target.as_str()) {
+            normalize_list_env(&value).map_err(|e| format!("{}: {}", target, e))?
+        } else if is_alias {
+            value
+        } else {
+            // Short-form scalars are already what the parser reads
+            continue;
+        };
+
+        if is_alias {
+            aliased.insert(target.clone());
+        }
+        resolved.retain(|(n, _)| *n != target);
+        resolved.push((target, value));
+    }
+
+    Ok(resolved)
+}
+
+/// Apply [`resolve_env_aliases`] to the process environment.
+///
+/// Must run before the CLI is parsed and before the async runtime (or any
+/// other thread) is started: mutating the environment while other threads
+/// may read it is unsound.
+pub fn apply_env_aliases() -> Result<(), String> {
+    for (name, value) in resolve_env_aliases(std::env::vars())? {
+        std::env::set_var(name, value);
+    }
+    Ok(())
+}
+
+// =============================================================================
+// Configuration Files
+// =============================================================================
+
+/// Parse a configuration file into its top-level keys.
+///
+/// `.yaml` and `.yml` files are read as YAML, anything else as TOML.
+fn parse_config_file(path: &Path, contents: &str) -> Result<Map<String, Value>, String> {
+    let is_yaml = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("yaml") || ext.eq_ignore_ascii_case("yml"));
+    let value: Value = if is_yaml {
+        serde_yaml::from_str(contents).map_err(|e| e.to_string())?
+    } else {
+        toml::from_str(contents).map_err(|e| e.to_string())?
+    };
+    match value {
+        Value::Object(table) => Ok(table),
+        Value::Null => Ok(Map::new()),
+        _ => Err("expected a table of options".to_string()),
+    }
+}
+
+/// Convert a scalar config file value to its command-line form.
+fn config_scalar(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+/// Resolve a configuration file into the `WSI_*` variables the CLI reads.
+///
+/// Keys are `serve` option names, with underscores or dashes
+/// (`cache_slides` or `cache-slides`); list options take an array. Each
+/// value is checked with its option's parser, so errors name the key
+/// rather than the environment variable. Returns the variables to set.
+pub fn resolve_config_file(path: &Path, contents: &str) -> Result<Vec<(String, String)>, String> {
+    Ok(resolve_config_entries(path, contents)?
+        .into_iter()
+        .map(|entry| (entry.env, entry.value))
+        .collect())
+}
+
+/// A configuration file value for one option.
+struct ConfigEntry {
+    /// Option name, with underscores
+    id: String,
+
+    /// Variable the option is read from
+    env: String,
+
+    /// Value in command-line form, list items joined with commas
+    value: String,
+}
+
+fn resolve_config_entries(path: &Path, contents: &str) -> Result<Vec<ConfigEntry>, String> {
+    let at = |message: String| format!("{}: {}", path.display(), message);
+    let table = parse_config_file(path, contents).map_err(at)?;
+    let command = Cli::command();
+
+    let mut resolved = Vec::new();
+    for (key, value) in &table {
+        let id = key.replace('-', "_");
+        let arg = command
+            .get_arguments()
+            .find(|arg| arg.get_id().as_str() == id)
+            .ok_or_else(|| at(format!("unknown key `{}`", key)))?;
+        let env = match arg.get_env().and_then(|env| env.to_str()) {
+            Some(env) if id != "config" => env,
+            _ => return Err(at(format!("`{}` can't be set in a config file", key))),
+        };
+
+        let items = match value {
+            Value::Null => continue,
+            Value::Array(items) if arg.get_value_delimiter().is_some() => items
+                .iter()
+                .map(config_scalar)
+                .collect::<Option<Vec<_>>>()
+                .ok_or_else(|| at(format!("`{}` must be a list of values", key)))?,
+            value => vec![config_scalar(value)
+                .ok_or_else(|| at(format!("`{}` must be a single value", key)))?],
+        };
+        // Run each value through the option's parser on its own, so the
+        // environment and other options can't affect the check
+        let check = clap::Command::new("config")
+            .no_binary_name(true)
+            .arg(Arg::new("value").value_parser(arg.get_value_parser().clone()));
+        for item in &items {
+            check.clone().try_get_matches_from([item]).map_err(|e| {
+                let reason = match (
+                    std::error::Error::source(&e),
+                    e.get(ContextKind::ValidValue),
+                ) {
+                    (Some(source), _) => source.to_string(),
+                    (None, Some(ContextValue::Strings(valid))) => {
+                        format!("expected one of {}", valid.join(", "))
+                    }
+                    (None, _) => e.kind().to_string(),
+                };
+                at(format!(
+                    "invalid value `{}` for `{}`: {}",
+                    item, key, reason
+                ))
+            })?;
+        }
+        resolved.push(ConfigEntry {
+            env: env.to_string(),
+            value: items.join(","),
+            id,
+        });
+    }
+
+    Ok(resolved)
+}
+
+/// Read a configuration file and apply it to the process environment.
+///
+/// Variables already set take precedence over the file, and command-line
+/// flags over both, once the CLI is parsed again. Like
+/// [`apply_env_aliases`], must run before the async runtime is started.
+/// Returns the variables that were set.
+pub fn apply_config_file(path: &Path) -> Result<Vec<String>, String> {
+    let contents = read_config_file(path)?;
+    let mut applied = Vec::new();
+    for (name, value) in resolve_config_file(path, &contents)? {
+        if std::env::var_os(&name).is_none() {
+            std::env::set_var(&name, value);
+            applied.push(name);
+        }
+    }
+    Ok(applied)
+}
+
+fn read_config_file(path: &Path) -> Result<String, String> {
+    std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+}
+
 // =============================================================================
 // CLI Structure
 // =============================================================================
@@ -103,6 +425,15 @@ EXAMPLES:
 
     # Generate a signed URL for a tile
     wsi-streamer sign --path /tiles/slide.svs/0/0/0.jpg --secret $SECRET
+
+    # Estimate cache sizes for 200 concurrent viewers
+    wsi-streamer plan --slides 5000 --avg-slide-size 2000000000 --concurrency 200
+
+    # Show which codecs this build supports
+    wsi-streamer capabilities
+
+    # Reproduce a bug report from a browser HAR export
+    wsi-streamer replay session.har --target http://localhost:3000
 ")]
 pub struct Cli {
     #[command(subcommand)]
@@ -117,9 +448,19 @@ impl Cli {
     pub fn into_command(self) -> Command {
         self.command.unwrap_or(Command::Serve(self.serve))
     }
+
+    /// Configuration file given to the `serve` command, if any.
+    pub fn config_file(&self) -> Option<&Path> {
+        match &self.command {
+            Some(Command::Serve(config)) => config.config.as_deref(),
+            None => self.serve.config.as_deref(),
+            Some(_) => None,
+        }
+    }
 }
 
 #[derive(Subcommand, Debug, Clone)]
+#[allow(clippy::large_enum_variant)]
 pub enum Command {
     /// Start the tile server (default command)
     Serve(ServeConfig),
@@ -129,20 +470,134 @@ pub enum Command {
 
     /// Validate configuration and test S3 connectivity
     Check(CheckConfig),
+
+    /// Estimate cache sizes and S3 load for a target workload
+    Plan(PlanConfig),
+
+    /// Print the codecs and optional features compiled into this build
+    Capabilities,
+
+    /// Write a checksum manifest for a local slide file
+    Manifest(ManifestConfig),
+
+    /// Dump the IFDs and tags of a local slide file as JSON
+    Inspect(InspectConfig),
+
+    /// Re-issue requests captured in a HAR file or access log
+    Replay(ReplayConfig),
 }
 
 // =============================================================================
 // Serve Configuration
 // =============================================================================
 
+/// Object storage holding the slides.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum StorageBackend {
+    /// Amazon S3 or S3-compatible storage (default)
+    #[default]
+    S3,
+    /// Google Cloud Storage (requires the `gcs` feature)
+    Gcs,
+    /// Azure Blob Storage (requires the `azure` feature)
+    Azure,
+    /// Any web server supporting range requests (requires the `http` feature)
+    Http,
+}
+
+impl StorageBackend {
+    /// URI scheme naming a bucket or container of this backend.
+    pub fn scheme(&self) -> &'static str {
+        match self {
+            StorageBackend::S3 => "s3",
+            StorageBackend::Gcs => "gs",
+            StorageBackend::Azure => "az",
+            StorageBackend::Http => "https",
+        }
+    }
+
+    /// Whether this build includes the backend.
+    pub fn is_supported(&self) -> bool {
+        match self {
+            StorageBackend::S3 => true,
+            StorageBackend::Gcs => cfg!(feature = "gcs"),
+            StorageBackend::Azure => cfg!(feature = "azure"),
+            StorageBackend::Http => cfg!(feature = "http"),
+        }
+    }
+}
+
+impl fmt::Display for StorageBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StorageBackend::S3 => write!(f, "s3"),
+            StorageBackend::Gcs => write!(f, "gcs"),
+            StorageBackend::Azure => write!(f, "azure"),
+            StorageBackend::Http => write!(f, "http"),
+        }
+    }
+}
+
+/// How pyramid levels of TIFF-based slides are identified.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum PyramidDetection {
+    /// Tiled images with power-of-two downsamples, skipping labels (default)
+    #[default]
+    Strict,
+    /// Every tiled image is a level
+    Permissive,
+}
+
+/// What to serve for tiles missing from sparse TIFFs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum SparseTileMode {
+    /// A solid tile of `--sparse-tile-color` (default)
+    #[default]
+    Background,
+    /// A 404 error
+    Error,
+}
+
+/// Format of log output.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum LogFormat {
+    /// Compact human-readable lines (default)
+    #[default]
+    Text,
+    /// One JSON object per line
+    Json,
+}
+
 /// Configuration for the `serve` command (tile server).
 #[derive(Args, Debug, Clone)]
 pub struct ServeConfig {
-    /// S3 bucket URI (e.g., s3://my-bucket) or just the bucket name.
-    /// Alternative to --s3-bucket flag.
-    #[arg(value_name = "S3_URI")]
+    /// Bucket URI (e.g., s3://my-bucket, gs://my-bucket, az://my-container)
+    /// or just the bucket name. Alternative to --s3-bucket flag.
+    /// With storage=http, the base URL of the slides.
+    #[arg(value_name = "S3_URI", env = "WSI_S3_URI")]
     pub s3_uri: Option<String>,
 
+    /// TOML or YAML file setting any of these options by name.
+    ///
+    /// Command-line flags and environment variables take precedence over
+    /// the file.
+    #[arg(long, env = "WSI_CONFIG")]
+    pub config: Option<PathBuf>,
+
+    /// Seconds between checks of the configuration and secret files for
+    /// changes to reload (0 disables; `SIGHUP` and `POST /admin/reload`
+    /// still reload).
+    #[arg(
+        long,
+        default_value_t = DEFAULT_CONFIG_WATCH_INTERVAL_SECS,
+        env = "WSI_CONFIG_WATCH_INTERVAL_SECS"
+    )]
+    pub config_watch_interval_secs: u64,
+
+    /// Variables set from the configuration file at startup.
+    #[arg(skip)]
+    pub config_file_vars: Vec<String>,
+
     // =========================================================================
     // Server Configuration
     // =========================================================================
@@ -154,12 +609,52 @@ pub struct ServeConfig {
     #[arg(short, long, default_value_t = DEFAULT_PORT, env = "WSI_PORT")]
     pub port: u16,
 
+    /// Serve admin, debug and metrics endpoints on a separate port.
+    ///
+    /// When set, `/admin/*`, `/debug/*` and `/metrics` are only served on
+    /// this port (bound to `--admin-host`) and removed from the main
+    /// listener, so they can stay on an internal network.
+    #[arg(long, env = "WSI_ADMIN_PORT")]
+    pub admin_port: Option<u16>,
+
+    /// Host address to bind the admin listener to.
+    #[arg(long, default_value = DEFAULT_ADMIN_HOST, env = "WSI_ADMIN_HOST")]
+    pub admin_host: String,
+
+    // =========================================================================
+    // TLS Configuration
+    // =========================================================================
+    /// PEM certificate chain for serving HTTPS on the main port.
+    ///
+    /// Requires --tls-key and the `tls` feature. The admin listener stays
+    /// plain HTTP.
+    #[arg(long, env = "WSI_TLS_CERT")]
+    pub tls_cert: Option<PathBuf>,
+
+    /// PEM private key (PKCS#8, PKCS#1 or SEC1) matching --tls-cert.
+    #[arg(long, env = "WSI_TLS_KEY")]
+    pub tls_key: Option<PathBuf>,
+
+    /// Port answering plain HTTP with a redirect to HTTPS (e.g. 80).
+    #[arg(long, env = "WSI_TLS_REDIRECT_PORT")]
+    pub tls_redirect_port: Option<u16>,
+
+    // =========================================================================
+    // Storage Configuration
+    // =========================================================================
+    /// Object storage holding the slides: s3, gcs, azure or http.
+    ///
+    /// GCS, Azure Blob Storage and HTTP require the `gcs`, `azure` and
+    /// `http` features. Failover and `check` are S3-only.
+    #[arg(long, value_enum, default_value = "s3", env = "WSI_STORAGE")]
+    pub storage: StorageBackend,
+
     // =========================================================================
     // S3 Configuration
     // =========================================================================
-    /// S3 bucket name containing the slide files.
+    /// Bucket (S3, GCS) or container (Azure) name containing the slide files.
     /// Can also be provided as a positional argument (s3://bucket).
-    #[arg(long, env = "WSI_S3_BUCKET")]
+    #[arg(long, visible_alias = "bucket", env = "WSI_S3_BUCKET")]
     pub s3_bucket: Option<String>,
 
     /// Custom S3 endpoint URL for S3-compatible services (MinIO, etc.).
@@ -172,15 +667,157 @@ pub struct ServeConfig {
     #[arg(long, default_value = DEFAULT_REGION, env = "WSI_S3_REGION")]
     pub s3_region: String,
 
+    /// AWS region of the failover (secondary) bucket.
+    ///
+    /// Setting any failover option enables failover; reads move to the
+    /// secondary when the primary keeps failing.
+    #[arg(long, env = "WSI_S3_FAILOVER_REGION")]
+    pub s3_failover_region: Option<String>,
+
+    /// Custom S3 endpoint URL for the failover bucket.
+    #[arg(long, env = "WSI_S3_FAILOVER_ENDPOINT")]
+    pub s3_failover_endpoint: Option<String>,
+
+    /// Failover bucket name (defaults to the primary bucket name).
+    #[arg(long, env = "WSI_S3_FAILOVER_BUCKET")]
+    pub s3_failover_bucket: Option<String>,
+
+    /// Seconds a failing endpoint is skipped before it is retried.
+    #[arg(
+        long,
+        default_value_t = DEFAULT_FAILOVER_COOLDOWN.as_secs(),
+        env = "WSI_S3_FAILOVER_COOLDOWN_SECS"
+    )]
+    pub s3_failover_cooldown_secs: u64,
+
+    /// Seconds between active health checks of storage endpoints.
+    #[arg(
+        long,
+        default_value_t = DEFAULT_S3_HEALTH_CHECK_INTERVAL_SECS,
+        env = "WSI_S3_HEALTH_CHECK_INTERVAL_SECS"
+    )]
+    pub s3_health_check_interval_secs: u64,
+
+    /// Attempts per S3 range read, including the first (1 disables retries).
+    ///
+    /// Throttling, 5xx responses, timeouts and dropped connections are
+    /// retried with exponential backoff and jitter.
+    #[arg(long, default_value_t = DEFAULT_S3_MAX_ATTEMPTS, env = "WSI_S3_MAX_ATTEMPTS")]
+    pub s3_max_attempts: u32,
+
+    /// Backoff before the first S3 read retry in milliseconds, doubled for
+    /// each further retry.
+    #[arg(
+        long,
+        default_value_t = DEFAULT_S3_RETRY_BASE_DELAY.as_millis() as u64,
+        env = "WSI_S3_RETRY_BASE_DELAY_MS"
+    )]
+    pub s3_retry_base_delay_ms: u64,
+
+    /// Per-tenant buckets as tenant=bucket (comma-separated).
+    ///
+    /// Slide IDs starting with `{tenant}/` are read from the tenant's
+    /// bucket, through the same endpoint and credentials; other slide IDs
+    /// are read from the main bucket.
+    #[arg(long, env = "WSI_TENANT_BUCKETS", value_delimiter = ',')]
+    pub tenant_buckets: Option<Vec<String>>,
+
+    // =========================================================================
+    // GCS Configuration
+    // =========================================================================
+    /// Custom GCS endpoint URL, for emulators such as fake-gcs-server.
+    #[arg(long, env = "WSI_GCS_ENDPOINT")]
+    pub gcs_endpoint: Option<String>,
+
+    /// OAuth 2.0 access token for GCS requests.
+    ///
+    /// Accepts a literal value or a secret reference resolved at startup.
+    /// Without a token (or --gcs-metadata-auth), requests are anonymous.
+    #[arg(long, env = "WSI_GCS_TOKEN")]
+    pub gcs_token: Option<String>,
+
+    /// Authorize GCS requests with the service account of the instance,
+    /// using tokens from the GCE metadata server (Compute Engine, GKE,
+    /// Cloud Run).
+    #[arg(long, default_value_t = false, env = "WSI_GCS_METADATA_AUTH")]
+    pub gcs_metadata_auth: bool,
+
+    // =========================================================================
+    // Azure Configuration
+    // =========================================================================
+    /// Azure Storage account name.
+    #[arg(long, env = "WSI_AZURE_ACCOUNT")]
+    pub azure_account: Option<String>,
+
+    /// Custom blob endpoint URL (e.g., Azurite); defaults to
+    /// https://{account}.blob.core.windows.net.
+    #[arg(long, env = "WSI_AZURE_ENDPOINT")]
+    pub azure_endpoint: Option<String>,
+
+    /// Shared access signature (SAS) token for the container.
+    ///
+    /// Accepts a literal value or a secret reference resolved at startup.
+    /// Without a token, requests are anonymous (public containers).
+    #[arg(long, env = "WSI_AZURE_SAS_TOKEN")]
+    pub azure_sas_token: Option<String>,
+
+    // =========================================================================
+    // HTTP Configuration
+    // =========================================================================
+    /// Base URL of the slides for storage=http (e.g., https://files.example.org/wsi).
+    ///
+    /// Slide IDs are appended to this URL. Can also be provided as the
+    /// positional argument.
+    #[arg(long, env = "WSI_HTTP_BASE_URL")]
+    pub http_base_url: Option<String>,
+
+    /// Retries for HTTP reads failing with connection errors, timeouts,
+    /// throttling or server errors (0 disables retries).
+    #[arg(long, default_value_t = DEFAULT_HTTP_RETRIES, env = "WSI_HTTP_RETRIES")]
+    pub http_retries: u32,
+
+    // =========================================================================
+    // Preload Configuration
+    // =========================================================================
+    /// Slides to load fully into memory at startup (comma-separated).
+    ///
+    /// Each entry is a local file path (served under its file name) or, if
+    /// no such file exists, a key in the S3 bucket. Only these slides are
+    /// served, from memory, without a storage backend afterwards; the
+    /// bucket is then optional. Meant for demos and air-gapped review
+    /// stations.
+    #[arg(long, env = "WSI_PRELOAD_INTO_MEMORY", value_delimiter = ',')]
+    pub preload_into_memory: Option<Vec<String>>,
+
+    /// Slides to open and warm at startup (comma-separated IDs or glob
+    /// patterns such as `cases/2024-*/*.svs`).
+    ///
+    /// Each slide's pyramid and tile offsets are parsed and the tiles of its
+    /// lowest-resolution level read into the block cache before the server
+    /// starts listening, so the first viewer doesn't pay for the open.
+    #[arg(long, env = "WSI_WARM_SLIDES", value_delimiter = ',')]
+    pub warm_slides: Option<Vec<String>>,
+
+    /// Slides warmed at once at startup.
+    #[arg(long, default_value_t = DEFAULT_WARM_CONCURRENCY, env = "WSI_WARM_CONCURRENCY")]
+    pub warm_concurrency: usize,
+
     // =========================================================================
     // Authentication Configuration
     // =========================================================================
     /// Secret key for HMAC-SHA256 signed URL authentication.
     ///
-    /// Required when authentication is enabled.
+    /// Required when authentication is enabled. Accepts a literal value or a
+    /// `file://`, `secretsmanager://` or `ssm://` reference resolved at startup.
     #[arg(long, env = "WSI_AUTH_SECRET")]
     pub auth_secret: Option<String>,
 
+    /// File containing the HMAC secret key.
+    ///
+    /// Keeps the secret out of process arguments and the environment.
+    #[arg(long, env = "WSI_AUTH_SECRET_FILE", conflicts_with = "auth_secret")]
+    pub auth_secret_file: Option<PathBuf>,
+
     /// Enable signed URL authentication.
     ///
     /// When disabled (default), all tile requests are allowed without authentication.
@@ -188,6 +825,13 @@ pub struct ServeConfig {
     #[arg(long, default_value_t = false, env = "WSI_AUTH_ENABLED")]
     pub auth_enabled: bool,
 
+    /// Seconds a replaced secret keeps verifying after a reload rotates it.
+    ///
+    /// Lets URLs signed just before a rotation finish loading; after it,
+    /// the old secret is rejected, so rotating a leaked secret revokes it.
+    #[arg(long, default_value_t = DEFAULT_SECRET_ROTATION_GRACE.as_secs(), env = "WSI_AUTH_SECRET_GRACE_SECS")]
+    pub auth_secret_grace_secs: u64,
+
     // =========================================================================
     // Cache Configuration
     // =========================================================================
@@ -195,18 +839,203 @@ pub struct ServeConfig {
     #[arg(long, default_value_t = DEFAULT_SLIDE_CACHE_CAPACITY, env = "WSI_CACHE_SLIDES")]
     pub cache_slides: usize,
 
+    /// Per-tenant slide cache quotas as tenant=max_slides (comma-separated).
+    ///
+    /// A tenant at its quota evicts its own least recently used slide to
+    /// open another, so one lab can't push the others out of the cache.
+    #[arg(long, env = "WSI_TENANT_CACHE_SLIDES", value_delimiter = ',')]
+    pub tenant_cache_slides: Option<Vec<String>>,
+
+    /// Maximum number of slides opened concurrently.
+    ///
+    /// Further opens queue for a free slot.
+    #[arg(long, default_value_t = DEFAULT_MAX_CONCURRENT_OPENS, env = "WSI_MAX_CONCURRENT_OPENS")]
+    pub max_concurrent_opens: usize,
+
+    /// Time a queued slide open may wait before failing with 503, in milliseconds.
+    #[arg(long, default_value_t = DEFAULT_OPEN_QUEUE_TIMEOUT_MS, env = "WSI_OPEN_QUEUE_TIMEOUT_MS")]
+    pub open_queue_timeout_ms: u64,
+
+    /// Decode failures or panics within 10 minutes that quarantine a slide.
+    ///
+    /// Quarantined slides return 423 until released via
+    /// `POST /admin/quarantine/{slide_id}/release`. 0 disables quarantining.
+    #[arg(long, default_value_t = DEFAULT_QUARANTINE_THRESHOLD, env = "WSI_QUARANTINE_THRESHOLD")]
+    pub quarantine_threshold: u32,
+
+    /// Seconds a slide that is missing or fails to parse is remembered (0 = off).
+    ///
+    /// Requests for it fail with the same error without reaching storage
+    /// until the TTL expires or the slide is invalidated.
+    #[arg(long, default_value_t = DEFAULT_NEGATIVE_CACHE_TTL.as_secs(), env = "WSI_NEGATIVE_CACHE_TTL")]
+    pub negative_cache_ttl: u64,
+
+    /// How pyramid levels of SVS and TIFF slides are identified.
+    ///
+    /// `strict` keeps tiled images whose downsample is close to a power of
+    /// two and skips label-like images; `permissive` accepts every tiled
+    /// image, for vendor files with other ratios (e.g. 3x).
+    #[arg(
+        long,
+        value_enum,
+        default_value = "strict",
+        env = "WSI_PYRAMID_DETECTION"
+    )]
+    pub pyramid_detection: PyramidDetection,
+
+    /// Relative tolerance around power-of-two downsamples in strict pyramid
+    /// detection (0.2 = ±20%).
+    #[arg(long, default_value_t = DEFAULT_DOWNSAMPLE_TOLERANCE, env = "WSI_PYRAMID_DOWNSAMPLE_TOLERANCE")]
+    pub pyramid_downsample_tolerance: f64,
+
+    /// Maximum number of entries in one TIFF IFD; files declaring more are
+    /// rejected as corrupt.
+    #[arg(long, default_value_t = DEFAULT_MAX_IFD_ENTRIES, env = "WSI_TIFF_MAX_IFD_ENTRIES")]
+    pub tiff_max_ifd_entries: u64,
+
+    /// Maximum size in bytes of one TIFF tag array read from the file.
+    #[arg(long, default_value_t = DEFAULT_MAX_ARRAY_BYTES, env = "WSI_TIFF_MAX_ARRAY_BYTES")]
+    pub tiff_max_array_bytes: u64,
+
+    /// Maximum number of tiles (or strips) in one TIFF level.
+    #[arg(long, default_value_t = DEFAULT_MAX_TILE_COUNT, env = "WSI_TIFF_MAX_TILE_COUNT")]
+    pub tiff_max_tile_count: u64,
+
+    /// Maximum size in bytes of a level's JPEGTables.
+    #[arg(
+        long,
+        default_value_t = DEFAULT_MAX_JPEG_TABLES_BYTES,
+        env = "WSI_TIFF_MAX_JPEG_TABLES_BYTES"
+    )]
+    pub tiff_max_jpeg_tables_bytes: u64,
+
+    /// What to serve for tiles missing from sparse TIFFs (TileOffsets or
+    /// TileByteCounts of 0).
+    ///
+    /// `background` serves a solid tile of `--sparse-tile-color`; `error`
+    /// fails the request with 404.
+    #[arg(
+        long,
+        value_enum,
+        default_value = "background",
+        env = "WSI_SPARSE_TILES"
+    )]
+    pub sparse_tiles: SparseTileMode,
+
+    /// Color of background tiles served for missing tiles, as #RRGGBB.
+    #[arg(long, default_value = "#ffffff", env = "WSI_SPARSE_TILE_COLOR")]
+    pub sparse_tile_color: String,
+
+    /// Seconds between checks of open slides for replaced files (0 = off).
+    ///
+    /// Each check costs one metadata request per open slide; slides whose
+    /// ETag changed are reopened on their next request. Reads are pinned to
+    /// the ETag seen at open either way, so a replaced file is never mixed
+    /// with stale metadata.
+    #[arg(
+        long,
+        default_value_t = DEFAULT_SLIDE_REVALIDATE_INTERVAL_SECS,
+        env = "WSI_SLIDE_REVALIDATE_INTERVAL"
+    )]
+    pub slide_revalidate_interval: u64,
+
+    /// Verify slides against their checksum manifest (`{slide}.sha256`) when opened.
+    ///
+    /// Checks the file size and the blocks covering the header and a few
+    /// tiles. Results appear in `GET /slides/{slide_id}`; mismatches are
+    /// logged but don't block serving.
+    #[arg(long, default_value_t = false, env = "WSI_VERIFY_CHECKSUMS")]
+    pub verify_checksums: bool,
+
+    /// Verify every block read from storage against the slide's checksum
+    /// manifest block digests.
+    ///
+    /// Corrupted blocks fail the request with `data_corrupted` instead of
+    /// being served. Verified slides cache blocks of the manifest's block
+    /// size.
+    #[arg(long, default_value_t = false, env = "WSI_VERIFY_READS")]
+    pub verify_reads: bool,
+
+    /// Add custom response headers from each slide's `{slide}.headers.json` sidecar.
+    ///
+    /// The sidecar is a JSON object of header names to values, added to the
+    /// slide's tile and metadata responses. Costs one extra GET per slide open.
+    #[arg(long, default_value_t = false, env = "WSI_SLIDE_HEADERS")]
+    pub slide_headers: bool,
+
     /// Maximum number of blocks to cache per slide (256KB each).
     #[arg(long, default_value_t = DEFAULT_BLOCK_CACHE_CAPACITY, env = "WSI_CACHE_BLOCKS")]
     pub cache_blocks: usize,
 
+    /// Size each slide's block cache by how heavily it is accessed.
+    ///
+    /// Slides are classified every 10 seconds: hot slides get
+    /// `--cache-blocks-hot` blocks and stay in the slide cache in preference
+    /// to others, slides idle for 10 minutes shrink to `--cache-blocks-cold`,
+    /// and the rest get `--cache-blocks`. See `GET /admin/cache`.
+    #[arg(long, default_value_t = false, env = "WSI_ADAPTIVE_CACHE")]
+    pub adaptive_cache: bool,
+
+    /// Block cache budget for hot slides with `--adaptive-cache`.
+    #[arg(long, default_value_t = DEFAULT_HOT_BLOCK_CACHE_CAPACITY, env = "WSI_CACHE_BLOCKS_HOT")]
+    pub cache_blocks_hot: usize,
+
+    /// Block cache budget for cold slides with `--adaptive-cache`.
+    #[arg(long, default_value_t = DEFAULT_COLD_BLOCK_CACHE_CAPACITY, env = "WSI_CACHE_BLOCKS_COLD")]
+    pub cache_blocks_cold: usize,
+
     /// Maximum tile cache size in bytes (default: 100MB).
     #[arg(long, default_value_t = DEFAULT_TILE_CACHE_CAPACITY, env = "WSI_CACHE_TILES")]
     pub cache_tiles: usize,
 
+    /// Number of independently locked tile cache shards.
+    ///
+    /// More shards reduce lock contention at high request rates; each shard
+    /// holds an even share of the tile cache capacity.
+    #[arg(long, default_value_t = DEFAULT_TILE_CACHE_SHARDS, env = "WSI_CACHE_TILE_SHARDS")]
+    pub cache_tile_shards: usize,
+
+    /// Seconds after which cached tiles are stale (0 = never).
+    ///
+    /// Stale tiles are regenerated, or refreshed in the background when
+    /// `--stale-while-revalidate` is set.
+    #[arg(long, default_value_t = 0, env = "WSI_CACHE_TILE_TTL")]
+    pub cache_tile_ttl: u64,
+
+    /// Tile cache admission policy (lru, tinylfu).
+    ///
+    /// With `tinylfu` a new tile only evicts the least recently used one if
+    /// it has been requested more often recently, so bulk scans can't flush
+    /// the tiles other clients keep using.
+    #[arg(long, default_value_t = CacheAdmission::TinyLfu, env = "WSI_CACHE_TILE_ADMISSION")]
+    pub cache_tile_admission: CacheAdmission,
+
+    /// Memory budget in bytes across the tile cache, slide block caches and
+    /// parsed slide metadata (0 = unlimited).
+    ///
+    /// Checked every second; when exceeded, each cache evicts a share of
+    /// the excess proportional to its size, closing least recently used
+    /// slides to free metadata.
+    #[arg(long, default_value_t = 0, env = "WSI_MAX_MEMORY")]
+    pub max_memory: usize,
+
+    /// Serve stale cached tiles immediately and refresh them in the
+    /// background.
+    ///
+    /// Tiles are stale once their slide is invalidated or they outlive
+    /// `--cache-tile-ttl`. Keeps tile latency flat during invalidation storms.
+    #[arg(long, default_value_t = false, env = "WSI_STALE_WHILE_REVALIDATE")]
+    pub stale_while_revalidate: bool,
+
     /// Block size in bytes for the block cache.
     #[arg(long, default_value_t = DEFAULT_BLOCK_SIZE, env = "WSI_BLOCK_SIZE")]
     pub block_size: usize,
 
+    /// Tiles fetched into the block cache ahead of a sequential (row-major)
+    /// tile scan, such as bulk patch extraction (0 = off).
+    #[arg(long, default_value_t = DEFAULT_READAHEAD_TILES, env = "WSI_READAHEAD_TILES")]
+    pub readahead_tiles: usize,
+
     // =========================================================================
     // Tile Configuration
     // =========================================================================
@@ -214,6 +1043,178 @@ pub struct ServeConfig {
     #[arg(long, default_value_t = DEFAULT_JPEG_QUALITY, env = "WSI_JPEG_QUALITY")]
     pub jpeg_quality: u8,
 
+    /// Trim padded edge tiles to the true level dimensions.
+    ///
+    /// JPEG tiles already at the requested quality are cropped losslessly.
+    #[arg(long, default_value_t = false, env = "WSI_CROP_EDGE_TILES")]
+    pub crop_edge_tiles: bool,
+
+    /// Serve levels with giant native tiles as a virtual grid of this size.
+    ///
+    /// Levels whose native tile size is an exact multiple of this value
+    /// (e.g. 1024 or 2048 for 256) are cut into smaller tiles; each native
+    /// tile is decoded once and all of its sub-tiles are cached together.
+    /// 0 serves every level on its native grid.
+    #[arg(long, default_value_t = 0, env = "WSI_RETILE_SIZE")]
+    pub retile_size: u32,
+
+    /// Render tiles deterministically for golden-image regression tests.
+    ///
+    /// Disables source passthrough and enables `GET /debug/tiles/.../hash`.
+    #[arg(long, default_value_t = false, env = "WSI_DETERMINISTIC")]
+    pub deterministic: bool,
+
+    /// Serve JPEG tiles unchanged when already quantized at the requested quality.
+    ///
+    /// Only tiles whose quantization tables are exactly the standard tables
+    /// scaled to `quality` are reused; all others are re-encoded.
+    #[arg(long, default_value_t = false, env = "WSI_REUSE_SOURCE_TILES")]
+    pub reuse_source_tiles: bool,
+
+    /// Report phase timings in `Server-Timing` response headers.
+    ///
+    /// Breaks each request down into storage metadata, IFD parse, storage
+    /// reads, tile fetch, decode and encode time. For debugging: the
+    /// timings are visible to every client.
+    #[arg(long, default_value_t = false, env = "WSI_SERVER_TIMING")]
+    pub server_timing: bool,
+
+    /// Serve tissue masks at `GET /slides/{slide_id}/mask.png`.
+    ///
+    /// A slide's mask is computed from its overview on first request; read-ahead
+    /// then skips the slide's background tiles.
+    #[arg(long, default_value_t = false, env = "WSI_TISSUE_MASKS")]
+    pub tissue_masks: bool,
+
+    /// Embed the slide's ICC color profile in every JPEG tile.
+    ///
+    /// Color-managed viewers then render the scanner's colors; each tile
+    /// grows by the profile's size.
+    #[arg(long, default_value_t = false, env = "WSI_EMBED_ICC_PROFILES")]
+    pub embed_icc_profiles: bool,
+
+    /// Text burned into a corner of watermarked tiles.
+    ///
+    /// Tiles are watermarked when a signed access claim asks for it
+    /// (`watermark=on`), or always with --watermark-all.
+    #[arg(long, env = "WSI_WATERMARK_TEXT", conflicts_with = "watermark_image")]
+    pub watermark_text: Option<String>,
+
+    /// PNG logo burned into a corner of watermarked tiles, instead of text.
+    #[arg(long, env = "WSI_WATERMARK_IMAGE")]
+    pub watermark_image: Option<PathBuf>,
+
+    /// Opacity of the watermark (0.0-1.0).
+    #[arg(long, default_value_t = DEFAULT_WATERMARK_OPACITY, env = "WSI_WATERMARK_OPACITY")]
+    pub watermark_opacity: f32,
+
+    /// Tile corner of the watermark (top-left, top-right, bottom-left,
+    /// bottom-right).
+    #[arg(long, default_value_t = WatermarkCorner::BottomRight, env = "WSI_WATERMARK_CORNER")]
+    pub watermark_corner: WatermarkCorner,
+
+    /// Watermark every tile unless signed access claims turn it off
+    /// (`watermark=off`).
+    #[arg(long, default_value_t = false, env = "WSI_WATERMARK_ALL")]
+    pub watermark_all: bool,
+
+    /// Default resampling filter for regions, thumbnails and sprite sheets
+    /// (nearest, bilinear, lanczos3).
+    ///
+    /// Requests can override it with `filter=`.
+    #[arg(long, default_value_t = ResampleFilter::default(), env = "WSI_RESAMPLE_FILTER")]
+    pub resample_filter: ResampleFilter,
+
+    // =========================================================================
+    // Tile Scheduling Configuration
+    // =========================================================================
+    /// Maximum number of tiles generated concurrently (0 = unbounded).
+    ///
+    /// When set, cache misses wait for a slot in weighted fair queues per
+    /// tenant (or per slide), so one client can't monopolize encoding.
+    #[arg(long, default_value_t = 0, env = "WSI_MAX_CONCURRENT_TILES")]
+    pub max_concurrent_tiles: usize,
+
+    /// Time a tile request may wait for a generation slot before failing
+    /// with 503, in milliseconds.
+    #[arg(long, default_value_t = DEFAULT_TILE_QUEUE_TIMEOUT_MS, env = "WSI_TILE_QUEUE_TIMEOUT_MS")]
+    pub tile_queue_timeout_ms: u64,
+
+    /// Time budget for generating a tile on a cache miss, in milliseconds
+    /// (0 = unbounded).
+    ///
+    /// Covers waiting for a generation slot, slide reads and decoding.
+    /// Requests exceeding it fail with 504.
+    #[arg(long, default_value_t = 0, env = "WSI_TILE_TIMEOUT_MS")]
+    pub tile_timeout_ms: u64,
+
+    /// Tiles decoded and encoded at once on the blocking thread pool
+    /// (0 = one per CPU).
+    ///
+    /// Keeps CPU-heavy re-encoding off the async runtime.
+    #[arg(long, default_value_t = 0, env = "WSI_ENCODE_WORKERS")]
+    pub encode_workers: usize,
+
+    /// Request header naming the tenant of a tile request.
+    ///
+    /// Must be set by a trusted gateway. Requests without it are queued per
+    /// slide.
+    #[arg(long, env = "WSI_TENANT_HEADER")]
+    pub tenant_header: Option<String>,
+
+    /// Tiles a single queue may generate at once (0 = no limit).
+    ///
+    /// Applies to tenants without an explicit quota and to per-slide queues.
+    #[arg(long, default_value_t = 0, env = "WSI_TENANT_MAX_IN_FLIGHT")]
+    pub tenant_max_in_flight: usize,
+
+    /// Per-tenant quotas as name=weight[:max_in_flight] (comma-separated).
+    ///
+    /// Weights set each tenant's share of generation slots when queues
+    /// compete (default 1).
+    #[arg(long, env = "WSI_TENANT_QUOTAS", value_delimiter = ',')]
+    pub tenant_quotas: Option<Vec<String>>,
+
+    // =========================================================================
+    // Bandwidth Configuration
+    // =========================================================================
+    /// Response bytes per second allowed per client connection (0 = unlimited).
+    ///
+    /// Keeps a bulk export client from saturating an uplink shared with
+    /// interactive viewers.
+    #[arg(long, default_value_t = 0, env = "WSI_BANDWIDTH_LIMIT")]
+    pub bandwidth_limit: u64,
+
+    /// Request header carrying the API key used to select a per-key limit.
+    #[arg(long, env = "WSI_BANDWIDTH_KEY_HEADER")]
+    pub bandwidth_key_header: Option<String>,
+
+    /// Per-key limits as key=bytes_per_sec (comma-separated, 0 = unlimited).
+    ///
+    /// Keys without an entry use --bandwidth-limit.
+    #[arg(long, env = "WSI_BANDWIDTH_KEY_LIMITS", value_delimiter = ',')]
+    pub bandwidth_key_limits: Option<Vec<String>>,
+
+    // =========================================================================
+    // Rate Limit Configuration
+    // =========================================================================
+    /// Requests per second allowed per client (0 = unlimited).
+    ///
+    /// Clients over the limit get 429 with a Retry-After header, so one
+    /// misbehaving viewer can't exhaust the storage request quota.
+    #[arg(long, default_value_t = 0, env = "WSI_RATE_LIMIT")]
+    pub rate_limit: u64,
+
+    /// Requests a client may make at once before being limited
+    /// (0 = one second's worth).
+    #[arg(long, default_value_t = 0, env = "WSI_RATE_LIMIT_BURST")]
+    pub rate_limit_burst: u64,
+
+    /// How clients are identified: ip, or subject (the share token, viewer
+    /// token or access claims of a signed URL, falling back to the IP).
+    #[arg(long, default_value = "ip", env = "WSI_RATE_LIMIT_KEY")]
+    pub rate_limit_key: RateLimitKey,
+
     /// HTTP Cache-Control max-age in seconds.
     #[arg(long, default_value_t = DEFAULT_CACHE_MAX_AGE, env = "WSI_CACHE_MAX_AGE")]
     pub cache_max_age: u32,
@@ -227,24 +1228,75 @@ pub struct ServeConfig {
     #[arg(long, env = "WSI_CORS_ORIGINS", value_delimiter = ',')]
     pub cors_origins: Option<Vec<String>>,
 
+    // =========================================================================
+    // SLO Configuration
+    // =========================================================================
+    /// Fraction of tile requests that must not fail with a server error.
+    #[arg(long, default_value_t = DEFAULT_AVAILABILITY_TARGET, env = "WSI_SLO_AVAILABILITY")]
+    pub slo_availability: f64,
+
+    /// Fraction of tile requests that must complete within the latency threshold.
+    #[arg(long, default_value_t = DEFAULT_LATENCY_TARGET, env = "WSI_SLO_LATENCY")]
+    pub slo_latency: f64,
+
+    /// Latency threshold for the tile latency SLO, in milliseconds.
+    #[arg(long, default_value_t = DEFAULT_SLO_LATENCY_THRESHOLD_MS, env = "WSI_SLO_LATENCY_THRESHOLD_MS")]
+    pub slo_latency_threshold_ms: u64,
+
+    // =========================================================================
+    // Inference Configuration
+    // =========================================================================
+    /// URL of an inference sidecar receiving tiles requested with `assist=true`.
+    ///
+    /// Requires the `inference` feature.
+    #[arg(long, env = "WSI_INFERENCE_URL")]
+    pub inference_url: Option<String>,
+
+    /// Timeout for inference sidecar requests in milliseconds.
+    #[arg(long, default_value_t = DEFAULT_INFERENCE_TIMEOUT_MS, env = "WSI_INFERENCE_TIMEOUT_MS")]
+    pub inference_timeout_ms: u64,
+
     // =========================================================================
     // Logging Configuration
     // =========================================================================
     /// Enable verbose logging (debug level).
-    #[arg(short, long, default_value_t = false)]
+    #[arg(short, long, default_value_t = false, env = "WSI_VERBOSE")]
     pub verbose: bool,
 
+    /// Log output format.
+    ///
+    /// `json` writes one JSON object per line, with the request ID and tile
+    /// fields of the enclosing spans, for log aggregators.
+    #[arg(long, value_enum, default_value = "text", env = "WSI_LOG_FORMAT")]
+    pub log_format: LogFormat,
+
     /// Disable request tracing.
-    #[arg(long, default_value_t = false)]
+    #[arg(long, default_value_t = false, env = "WSI_NO_TRACING")]
     pub no_tracing: bool,
+
+    /// Disable gzip compression of JSON responses (e.g. when a load
+    /// balancer compresses them already).
+    #[arg(long, default_value_t = false, env = "WSI_NO_COMPRESSION")]
+    pub no_compression: bool,
+
+    /// OTLP/HTTP collector to export request spans to
+    /// (e.g. `http://otel-collector:4318`).
+    ///
+    /// Requires the `otel` feature.
+    #[arg(long, env = "WSI_OTEL_ENDPOINT")]
+    pub otel_endpoint: Option<String>,
+
+    /// Share of request traces exported to the OTLP collector (0.0-1.0).
+    #[arg(long, default_value_t = DEFAULT_OTEL_SAMPLE_RATE, env = "WSI_OTEL_SAMPLE_RATE")]
+    pub otel_sample_rate: f64,
 }
 
 impl ServeConfig {
     /// Resolve the S3 bucket name from either the positional URI or --s3-bucket flag.
     pub fn resolve_bucket(&self) -> Result<String, String> {
-        // First try the positional S3 URI
+        // First try the positional bucket URI
         if let Some(ref uri) = self.s3_uri {
-            return parse_s3_uri(uri);
+            return parse_bucket_uri(uri, self.storage.scheme());
         }
 
         // Fall back to --s3-bucket flag
@@ -255,22 +1307,198 @@ impl ServeConfig {
             return Ok(bucket.clone());
         }
 
-        Err(
-            "S3 bucket is required. Use: wsi-streamer s3://bucket-name or --s3-bucket=name"
-                .to_string(),
-        )
+        Err(format!(
+            "Bucket is required. Use: wsi-streamer {}://bucket-name or --s3-bucket=name",
+            self.storage.scheme()
+        ))
+    }
+
+    /// Resolve the base URL for storage=http from either the positional URI
+    /// or --http-base-url.
+    pub fn resolve_http_base_url(&self) -> Result<String, String> {
+        let url = match (&self.s3_uri, &self.http_base_url) {
+            (Some(uri), _) if uri.starts_with("http://") || uri.starts_with("https://") => uri,
+            (_, Some(url)) => url,
+            _ => {
+                return Err(
+                    "storage=http requires a base URL. Use: wsi-streamer https://host/path \
+                    --storage=http or --http-base-url=https://host/path"
+                        .to_string(),
+                )
+            }
+        };
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            return Err(format!(
+                "Invalid base URL '{}'. Expected http:// or https://",
+                url
+            ));
+        }
+        Ok(url.trim_end_matches('/').to_string())
+    }
+
+    /// Build the retry policy for S3 range reads.
+    pub fn s3_retry_policy(&self) -> S3RetryPolicy {
+        S3RetryPolicy::new(self.s3_max_attempts).with_base_delay(std::time::Duration::from_millis(
+            self.s3_retry_base_delay_ms,
+        ))
+    }
+
+    /// Build the pyramid detection heuristics.
+    pub fn pyramid_detect_options(&self) -> PyramidDetectOptions {
+        let options = match self.pyramid_detection {
+            PyramidDetection::Strict => PyramidDetectOptions::strict(),
+            PyramidDetection::Permissive => PyramidDetectOptions::permissive(),
+        };
+        options
+            .with_downsample_tolerance(self.pyramid_downsample_tolerance)
+            .with_limits(TiffLimits {
+                max_ifd_entries: self.tiff_max_ifd_entries,
+                max_array_bytes: self.tiff_max_array_bytes,
+                max_tile_count: self.tiff_max_tile_count,
+                max_jpeg_tables_bytes: self.tiff_max_jpeg_tables_bytes,
+            })
+    }
+
+    /// Build the policy for tiles missing from sparse TIFFs.
+    ///
+    /// Assumes the configuration was validated; an invalid color falls back
+    /// to white.
+    pub fn sparse_tiles(&self) -> SparseTiles {
+        match self.sparse_tiles {
+            SparseTileMode::Background => SparseTiles::Background(
+                parse_color(&self.sparse_tile_color).unwrap_or([255, 255, 255]),
+            ),
+            SparseTileMode::Error => SparseTiles::Error,
+        }
+    }
+
+    /// Build the tile watermark, if one is configured.
+    ///
+    /// Reads the logo file of `--watermark-image`.
+    pub fn watermark(&self) -> Result<Option<Watermark>, String> {
+        let watermark = match (&self.watermark_text, &self.watermark_image) {
+            (Some(text), _) => Watermark::from_text(text),
+            (None, Some(path)) => {
+                let data = std::fs::read(path)
+                    .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+                Watermark::from_png(&data).map_err(|e| e.to_string())?
+            }
+            (None, None) => return Ok(None),
+        };
+        Ok(Some(
+            watermark
+                .with_opacity(self.watermark_opacity)
+                .with_corner(self.watermark_corner),
+        ))
+    }
+
+    /// Build the adaptive block cache budgets, if `--adaptive-cache` is set.
+    pub fn adaptive_cache_config(&self) -> Option<AdaptiveCacheConfig> {
+        self.adaptive_cache.then(|| {
+            AdaptiveCacheConfig::new(self.cache_blocks)
+                .with_hot_blocks(self.cache_blocks_hot)
+                .with_cold_blocks(self.cache_blocks_cold)
+        })
+    }
+
+    /// Parse the per-tenant quotas.
+    pub fn parse_tenant_quotas(&self) -> Result<Vec<(String, TenantQuota)>, String> {
+        self.tenant_quotas
+            .iter()
+            .flatten()
+            .map(|spec| TenantQuota::parse_spec(spec))
+            .collect()
+    }
+
+    /// Parse the per-tenant buckets.
+    pub fn parse_tenant_buckets(&self) -> Result<Vec<(String, String)>, String> {
+        let buckets = parse_tenant_specs(self.tenant_buckets.as_deref(), |bucket| {
+            parse_bucket_uri(bucket, self.storage.scheme())
+        })?;
+        if !buckets.is_empty() && self.storage != StorageBackend::S3 {
+            return Err("tenant_buckets requires storage=s3".to_string());
+        }
+        if !buckets.is_empty() && self.has_failover() {
+            return Err("tenant_buckets can't be combined with S3 failover".to_string());
+        }
+        Ok(buckets)
+    }
+
+    /// Parse the per-tenant slide cache quotas.
+    pub fn parse_tenant_cache_slides(&self) -> Result<Vec<(String, usize)>, String> {
+        parse_tenant_specs(self.tenant_cache_slides.as_deref(), |max| {
+            max.parse::<usize>()
+                .ok()
+                .filter(|&max| max > 0)
+                .ok_or_else(|| format!("Invalid slide quota '{}': expected a positive number", max))
+        })
+    }
+
+    /// Build the per-client request rate limits.
+    pub fn rate_limit_config(&self) -> RateLimitConfig {
+        RateLimitConfig::new(self.rate_limit)
+            .with_burst(self.rate_limit_burst)
+            .with_key(self.rate_limit_key)
+    }
+
+    /// Build the per-connection bandwidth limits.
+    pub fn bandwidth_config(&self) -> Result<BandwidthConfig, String> {
+        let mut bandwidth = BandwidthConfig::new(self.bandwidth_limit);
+        if let Some(ref header) = self.bandwidth_key_header {
+            bandwidth = bandwidth.with_key_header(header.clone());
+        }
+        for spec in self.bandwidth_key_limits.iter().flatten() {
+            let (key, rate) = BandwidthConfig::parse_key_rate(spec)?;
+            bandwidth = bandwidth.with_key_rate(key, rate);
+        }
+        if !bandwidth.key_rates.is_empty() && bandwidth.key_header.is_none() {
+            return Err("bandwidth_key_limits requires bandwidth_key_header".to_string());
+        }
+        Ok(bandwidth)
     }
 
     /// Validate the configuration and return an error message if invalid.
     pub fn validate(&self) -> Result<(), String> {
-        // Resolve and validate bucket
-        self.resolve_bucket()?;
+        // Resolve and validate bucket (optional when slides are preloaded)
+        if self.storage == StorageBackend::Http {
+            if !self.preloads_into_memory() {
+                self.resolve_http_base_url()?;
+            }
+        } else if !self.preloads_into_memory() || self.s3_uri.is_some() || self.s3_bucket.is_some()
+        {
+            self.resolve_bucket()?;
+        }
 
         // Check auth secret is provided when auth is enabled
-        if self.auth_enabled && self.auth_secret.is_none() {
-            return Err("Authentication is enabled but no secret provided. \
-                Set --auth-secret or WSI_AUTH_SECRET, or disable auth with --auth-enabled=false"
-                .to_string());
+        if self.auth_enabled && self.auth_secret.is_none() && self.auth_secret_file.is_none() {
+            return Err(
+                "Authentication is enabled but no secret provided. \
+                Set --auth-secret or --auth-secret-file, or disable auth with --auth-enabled=false"
+                    .to_string(),
+            );
+        }
+
+        // The admin listener needs its own port
+        if self.admin_port == Some(self.port) {
+            return Err("admin_port must differ from port".to_string());
+        }
+
+        // Validate TLS settings
+        if self.tls_cert.is_some() != self.tls_key.is_some() {
+            return Err("tls_cert and tls_key must be set together".to_string());
+        }
+        if self.has_tls() && !cfg!(feature = "tls") {
+            return Err(
+                "tls_cert requires wsi-streamer to be built with the `tls` feature".to_string(),
+            );
+        }
+        if let Some(redirect_port) = self.tls_redirect_port {
+            if !self.has_tls() {
+                return Err("tls_redirect_port requires tls_cert and tls_key".to_string());
+            }
+            if redirect_port == self.port || self.admin_port == Some(redirect_port) {
+                return Err("tls_redirect_port must differ from port and admin_port".to_string());
+            }
         }
 
         // Validate cache sizes
@@ -283,17 +1511,138 @@ impl ServeConfig {
         if self.cache_tiles == 0 {
             return Err("cache_tiles must be greater than 0".to_string());
         }
+        if self.adaptive_cache && (self.cache_blocks_hot == 0 || self.cache_blocks_cold == 0) {
+            return Err(
+                "cache_blocks_hot and cache_blocks_cold must be greater than 0".to_string(),
+            );
+        }
+        if self.max_concurrent_opens == 0 {
+            return Err("max_concurrent_opens must be greater than 0".to_string());
+        }
+        if !(self.pyramid_downsample_tolerance > 0.0 && self.pyramid_downsample_tolerance < 1.0) {
+            return Err("pyramid_downsample_tolerance must be between 0 and 1".to_string());
+        }
+        if self.tiff_max_ifd_entries == 0
+            || self.tiff_max_array_bytes == 0
+            || self.tiff_max_tile_count == 0
+            || self.tiff_max_jpeg_tables_bytes == 0
+        {
+            return Err("tiff_max_* limits must be greater than 0".to_string());
+        }
+        parse_color(&self.sparse_tile_color).map_err(|e| format!("sparse_tile_color: {}", e))?;
+        if !(0.0..=1.0).contains(&self.watermark_opacity) {
+            return Err("watermark_opacity must be between 0 and 1".to_string());
+        }
+        if self.watermark_text.as_deref() == Some("") {
+            return Err("watermark_text cannot be empty".to_string());
+        }
+        if self.watermark_all && self.watermark_text.is_none() && self.watermark_image.is_none() {
+            return Err("watermark_all requires watermark_text or watermark_image".to_string());
+        }
+        if self.cache_tile_shards == 0 {
+            return Err("cache_tile_shards must be greater than 0".to_string());
+        }
+
+        // Validate failover settings
+        if self.has_failover() {
+            if self.s3_failover_bucket.as_deref() == Some("") {
+                return Err("s3_failover_bucket cannot be empty".to_string());
+            }
+            if self.s3_failover_cooldown_secs == 0 {
+                return Err("s3_failover_cooldown_secs must be greater than 0".to_string());
+            }
+            if self.s3_health_check_interval_secs == 0 {
+                return Err("s3_health_check_interval_secs must be greater than 0".to_string());
+            }
+        }
+
+        // Validate SLO targets
+        for (name, target) in [
+            ("slo_availability", self.slo_availability),
+            ("slo_latency", self.slo_latency),
+        ] {
+            if !(target > 0.0 && target < 1.0) {
+                return Err(format!("{} must be between 0 and 1 (exclusive)", name));
+            }
+        }
+        if self.slo_latency_threshold_ms == 0 {
+            return Err("slo_latency_threshold_ms must be greater than 0".to_string());
+        }
+
+        // Validate tile scheduling
+        if self.max_concurrent_tiles > 0 && self.tile_queue_timeout_ms == 0 {
+            return Err("tile_queue_timeout_ms must be greater than 0".to_string());
+        }
+        self.parse_tenant_quotas()?;
+        self.parse_tenant_buckets()?;
+        self.parse_tenant_cache_slides()?;
+
+        // Validate bandwidth limits
+        self.bandwidth_config()?;
 
         // Validate JPEG quality
         if self.jpeg_quality == 0 || self.jpeg_quality > 100 {
             return Err("jpeg_quality must be between 1 and 100".to_string());
         }
 
+        // Validate retiling
+        if self.retile_size != 0 && self.retile_size < 64 {
+            return Err("retile_size must be 0 (disabled) or at least 64".to_string());
+        }
+
         // Validate block size (must be reasonable)
         if self.block_size < 1024 || self.block_size > 16 * 1024 * 1024 {
             return Err("block_size must be between 1KB and 16MB".to_string());
         }
 
+        // Validate S3 retries
+        if self.s3_max_attempts == 0 {
+            return Err("s3_max_attempts must be at least 1".to_string());
+        }
+
+        // Validate storage backend settings
+        if !self.storage.is_supported() {
+            return Err(format!(
+                "storage={} requires wsi-streamer to be built with the `{}` feature",
+                self.storage, self.storage
+            ));
+        }
+        if self.storage != StorageBackend::S3 && self.has_failover() {
+            return Err("S3 failover options require storage=s3".to_string());
+        }
+        if self.gcs_token.is_some() && self.gcs_metadata_auth {
+            return Err("Set either gcs_token or gcs_metadata_auth, not both".to_string());
+        }
+        if self.storage == StorageBackend::Azure
+            && self.azure_account.is_none()
+            && self.azure_endpoint.is_none()
+        {
+            return Err("storage=azure requires --azure-account or --azure-endpoint".to_string());
+        }
+
+        // Validate inference sidecar settings
+        if self.inference_url.is_some() {
+            if !cfg!(feature = "inference") {
+                return Err("inference_url requires wsi-streamer to be built with the \
+                    `inference` feature"
+                    .to_string());
+            }
+            if self.inference_timeout_ms == 0 {
+                return Err("inference_timeout_ms must be greater than 0".to_string());
+            }
+        }
+
+        // Validate span export settings
+        if self.otel_endpoint.is_some() && !cfg!(feature = "otel") {
+            return Err(
+                "otel_endpoint requires wsi-streamer to be built with the `otel` feature"
+                    .to_string(),
+            );
+        }
+        if !(0.0..=1.0).contains(&self.otel_sample_rate) {
+            return Err("otel_sample_rate must be between 0.0 and 1.0".to_string());
+        }
+
         Ok(())
     }
 
@@ -302,6 +1651,119 @@ impl ServeConfig {
         format!("{}:{}", self.host, self.port)
     }
 
+    /// Whether the main listener serves HTTPS.
+    pub fn has_tls(&self) -> bool {
+        self.tls_cert.is_some() && self.tls_key.is_some()
+    }
+
+    /// Get the HTTP to HTTPS redirect listener bind address, if enabled.
+    pub fn tls_redirect_address(&self) -> Option<String> {
+        self.tls_redirect_port
+            .map(|port| format!("{}:{}", self.host, port))
+    }
+
+    /// Whether slides are served from memory (`--preload-into-memory`).
+    pub fn preloads_into_memory(&self) -> bool {
+        self.preload_into_memory
+            .as_ref()
+            .is_some_and(|slides| !slides.is_empty())
+    }
+
+    /// Get the admin listener bind address as "host:port", if enabled.
+    pub fn admin_bind_address(&self) -> Option<String> {
+        self.admin_port
+            .map(|port| format!("{}:{}", self.admin_host, port))
+    }
+
+    /// Resolve `--auth-secret-file` and secret references in `--auth-secret`
+    /// into the literal secret.
+    ///
+    /// Called once at startup, after [`validate`](Self::validate).
+    pub async fn resolve_secrets(&mut self) -> Result<(), SecretError> {
+        if let Some(ref path) = self.auth_secret_file {
+            self.auth_secret = Some(read_secret_file(path)?);
+        } else if let Some(ref reference) = self.auth_secret {
+            self.auth_secret = Some(resolve_secret(reference).await?);
+        }
+        if let Some(ref reference) = self.gcs_token {
+            self.gcs_token = Some(resolve_secret(reference).await?);
+        }
+        if let Some(ref reference) = self.azure_sas_token {
+            self.azure_sas_token = Some(resolve_secret(reference).await?);
+        }
+        Ok(())
+    }
+
+    /// Reloadable options given as command-line flags or variables, which
+    /// take precedence over the configuration file and so never reload.
+    ///
+    /// Reads the process arguments, so it only applies to the configuration
+    /// the process was started with.
+    pub fn pinned_options(&self) -> Vec<&'static str> {
+        let command = Cli::command();
+        let Ok(matches) = command.clone().try_get_matches() else {
+            return RELOADABLE_OPTIONS.to_vec();
+        };
+        let matches = matches.subcommand_matches("serve").unwrap_or(&matches);
+
+        RELOADABLE_OPTIONS
+            .into_iter()
+            .filter(|id| match matches.value_source(id) {
+                Some(ValueSource::CommandLine) => true,
+                // Variables set from the file at startup aren't pinned
+                Some(ValueSource::EnvVariable) => command
+                    .get_arguments()
+                    .find(|arg| arg.get_id() == id)
+                    .and_then(|arg| arg.get_env())
+                    .and_then(|env| env.to_str())
+                    .map_or(true, |env| {
+                        !self.config_file_vars.iter().any(|var| var == env)
+                    }),
+                _ => false,
+            })
+            .collect()
+    }
+
+    /// Apply the reloadable options set in the configuration file.
+    ///
+    /// Options in `pinned` keep their value, as do options the file no
+    /// longer sets. Secrets are read again by [`resolve_secrets`](Self::resolve_secrets).
+    pub fn reload_from_file(&mut self, pinned: &[&str]) -> Result<(), String> {
+        let Some(path) = self.config.clone() else {
+            return Ok(());
+        };
+        let contents = read_config_file(&path)?;
+        for ConfigEntry { id, value, .. } in resolve_config_entries(&path, &contents)? {
+            if !RELOADABLE_OPTIONS.contains(&id.as_str()) || pinned.contains(&id.as_str()) {
+                continue;
+            }
+            // Checked by the option's parser when the file was resolved
+            let invalid = || format!("{}: invalid value `{}` for `{}`", path.display(), value, id);
+            match id.as_str() {
+                "auth_secret" => {
+                    self.auth_secret = Some(value);
+                    if !pinned.contains(&"auth_secret_file") {
+                        self.auth_secret_file = None;
+                    }
+                }
+                "auth_secret_file" => self.auth_secret_file = Some(PathBuf::from(value)),
+                "cors_origins" => {
+                    self.cors_origins = Some(
+                        value
+                            .split(',')
+                            .filter(|origin| !origin.is_empty())
+                            .map(str::to_string)
+                            .collect(),
+                    )
+                }
+                "cache_max_age" => self.cache_max_age = value.parse().map_err(|_| invalid())?,
+                "verbose" => self.verbose = value.parse().map_err(|_| invalid())?,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
     /// Get the auth secret, returning empty string if not set.
     pub fn auth_secret_or_empty(&self) -> &str {
         self.auth_secret.as_deref().unwrap_or("")
@@ -312,6 +1774,27 @@ impl ServeConfig {
         self.resolve_bucket()
             .expect("bucket should be validated before calling this method")
     }
+
+    /// Whether a failover bucket is configured.
+    pub fn has_failover(&self) -> bool {
+        self.s3_failover_region.is_some()
+            || self.s3_failover_endpoint.is_some()
+            || self.s3_failover_bucket.is_some()
+    }
+
+    /// Get the failover bucket name, defaulting to the primary bucket.
+    pub fn failover_bucket(&self) -> String {
+        self.s3_failover_bucket
+            .clone()
+            .unwrap_or_else(|| self.bucket())
+    }
+
+    /// Get the failover region, defaulting to the primary region.
+    pub fn failover_region(&self) -> &str {
+        self.s3_failover_region
+            .as_deref()
+            .unwrap_or(&self.s3_region)
+    }
 }
 
 // =============================================================================
@@ -364,6 +1847,12 @@ pub struct SignConfig {
     #[arg(short = 'P', long, value_delimiter = ',')]
     pub params: Option<Vec<String>>,
 
+    /// Access claims to sign instead of the path
+    /// (e.g., "slide=lab-a/*;min_level=1;endpoints=tiles,metadata").
+    /// The signed URL then works for every request the claims allow.
+    #[arg(long)]
+    pub claims: Option<String>,
+
     /// Output format: url (default), json, or signature
     #[arg(short, long, default_value = "url")]
     pub format: SignOutputFormat,
@@ -409,6 +1898,10 @@ impl SignConfig {
         // Validate params format
         self.parse_params()?;
 
+        if let Some(ref claims) = self.claims {
+            AccessClaims::parse(claims).map_err(|e| e.to_string())?;
+        }
+
         Ok(())
     }
 }
@@ -470,28 +1963,165 @@ impl CheckConfig {
     }
 }
 
+// =============================================================================
+// Plan Configuration
+// =============================================================================
+
+/// Output format for the plan command.
+#[derive(Debug, Clone, Copy, Default, ValueEnum)]
+pub enum PlanOutputFormat {
+    /// Human-readable report (default)
+    #[default]
+    Text,
+    /// JSON report
+    Json,
+}
+
+impl fmt::Display for PlanOutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlanOutputFormat::Text => write!(f, "text"),
+            PlanOutputFormat::Json => write!(f, "json"),
+        }
+    }
+}
+
+/// Configuration for the `plan` command.
+#[derive(Args, Debug, Clone)]
+pub struct PlanConfig {
+    /// Number of slides in the bucket
+    #[arg(long)]
+    pub slides: u64,
+
+    /// Average slide size in bytes
+    #[arg(long)]
+    pub avg_slide_size: u64,
+
+    /// Target number of concurrently active viewers
+    #[arg(long)]
+    pub concurrency: u64,
+
+    /// Average compressed source tile size in bytes
+    #[arg(long, default_value_t = 40 * 1024)]
+    pub avg_tile_size: u64,
+
+    /// Average served (re-encoded) tile size in bytes
+    #[arg(long, default_value_t = 30 * 1024)]
+    pub avg_served_tile_size: u64,
+
+    /// Tile requests per second per active viewer
+    #[arg(long, default_value_t = 10.0)]
+    pub tiles_per_viewer: f64,
+
+    /// Distinct slides opened per minute across all viewers
+    #[arg(long, default_value_t = 10.0)]
+    pub opens_per_minute: f64,
+
+    /// Typical S3 GET latency in milliseconds
+    #[arg(long, default_value_t = 50)]
+    pub s3_latency_ms: u64,
+
+    /// Output format: text (default) or json
+    #[arg(short, long, default_value = "text")]
+    pub format: PlanOutputFormat,
+}
+
+impl PlanConfig {
+    /// Build the capacity model inputs.
+    pub fn inputs(&self) -> CapacityInputs {
+        CapacityInputs {
+            slides: self.slides,
+            avg_slide_bytes: self.avg_slide_size,
+            avg_source_tile_bytes: self.avg_tile_size,
+            avg_served_tile_bytes: self.avg_served_tile_size,
+            concurrency: self.concurrency,
+            tiles_per_viewer_per_sec: self.tiles_per_viewer,
+            slide_opens_per_minute: self.opens_per_minute,
+            s3_latency_ms: self.s3_latency_ms,
+        }
+    }
+
+    /// Validate the plan configuration.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.slides == 0 {
+            return Err("Slide count must be greater than 0".to_string());
+        }
+
+        if self.concurrency == 0 {
+            return Err("Concurrency must be greater than 0".to_string());
+        }
+
+        if self.avg_tile_size == 0 || self.avg_served_tile_size == 0 {
+            return Err("Tile sizes must be greater than 0".to_string());
+        }
+
+        if self.avg_slide_size < self.avg_tile_size {
+            return Err("Average slide size must be at least the average tile size".to_string());
+        }
+
+        if !(self.tiles_per_viewer.is_finite() && self.tiles_per_viewer > 0.0) {
+            return Err("Tiles per viewer must be greater than 0".to_string());
+        }
+
+        if !(self.opens_per_minute.is_finite() && self.opens_per_minute >= 0.0) {
+            return Err("Opens per minute cannot be negative".to_string());
+        }
+
+        Ok(())
+    }
+}
+
 // =============================================================================
 // Helper Functions
 // =============================================================================
 
 /// Parse an S3 URI (s3://bucket-name or s3://bucket-name/prefix) and return the bucket name.
 fn parse_s3_uri(uri: &str) -> Result<String, String> {
-    // Handle both s3:// prefix and plain bucket names
+    parse_bucket_uri(uri, "s3")
+}
+
+/// Parse a bucket URI with the given scheme (e.g. gs://bucket-name) or a
+/// plain bucket name, and return the bucket name.
+/// Parse `tenant=value` specs, rejecting invalid or repeated tenant names.
+fn parse_tenant_specs<T>(
+    specs: Option<&[String]>,
+    parse_value: impl Fn(&str) -> Result<T, String>,
+) -> Result<Vec<(String, T)>, String> {
+    let mut parsed: Vec<(String, T)> = Vec::new();
+    for spec in specs.unwrap_or_default() {
+        let (tenant, value) = spec
+            .split_once('=')
+            .ok_or_else(|| format!("Invalid tenant spec '{}': expected tenant=value", spec))?;
+        let tenant = tenant.trim();
+        if tenant.is_empty() || tenant.contains(TENANT_SEPARATOR) {
+            return Err(format!("Invalid tenant name in '{}'", spec));
+        }
+        if parsed.iter().any(|(t, _)| t == tenant) {
+            return Err(format!("Tenant '{}' is configured twice", tenant));
+        }
+        parsed.push((tenant.to_string(), parse_value(value.trim())?));
+    }
+    Ok(parsed)
+}
+
+fn parse_bucket_uri(uri: &str, scheme: &str) -> Result<String, String> {
+    // Handle both scheme:// prefix and plain bucket names
     let uri = uri.trim();
+    let prefix = format!("{}://", scheme);
 
-    if let Some(path) = uri.strip_prefix("s3://") {
+    if let Some(path) = uri.strip_prefix(&prefix) {
         let bucket = path.split('/').next().unwrap_or("");
         if bucket.is_empty() {
             return Err(format!(
-                "Invalid S3 URI '{}'. Expected format: s3://bucket-name",
-                uri
+                "Invalid bucket URI '{}'. Expected format: {}bucket-name",
+                uri, prefix
             ));
         }
         Ok(bucket.to_string())
     } else if uri.contains("://") {
         Err(format!(
-            "Invalid URI scheme in '{}'. Expected s3:// or plain bucket name",
-            uri
+            "Invalid URI scheme in '{}'. Expected {} or plain bucket name",
+            uri, prefix
         ))
     } else {
         // Plain bucket name
@@ -502,6 +2132,111 @@ fn parse_s3_uri(uri: &str) -> Result<String, String> {
     }
 }
 
+// =============================================================================
+// Manifest Configuration
+// =============================================================================
+
+/// Configuration for the `manifest` command.
+#[derive(Args, Debug, Clone)]
+pub struct ManifestConfig {
+    /// Slide file to digest
+    #[arg(value_name = "FILE")]
+    pub file: PathBuf,
+
+    /// Size of each digested block in bytes
+    #[arg(long, default_value_t = DEFAULT_MANIFEST_BLOCK_SIZE)]
+    pub block_size: u64,
+
+    /// Output path (default: FILE.sha256)
+    #[arg(long, short)]
+    pub output: Option<PathBuf>,
+}
+
+impl ManifestConfig {
+    /// Path the manifest is written to.
+    pub fn output_path(&self) -> PathBuf {
+        self.output.clone().unwrap_or_else(|| {
+            let mut path = self.file.clone().into_os_string();
+            path.push(MANIFEST_SUFFIX);
+            PathBuf::from(path)
+        })
+    }
+}
+
+// =============================================================================
+// Inspect Configuration
+// =============================================================================
+
+/// Configuration for the `inspect` command.
+#[derive(Args, Debug, Clone)]
+pub struct InspectConfig {
+    /// Slide file to inspect
+    #[arg(value_name = "FILE")]
+    pub file: PathBuf,
+}
+
+// =============================================================================
+// Replay Configuration
+// =============================================================================
+
+/// Default server requests are replayed against.
+pub const DEFAULT_REPLAY_TARGET: &str = "http://127.0.0.1:3000";
+
+/// Default per-request timeout for replayed requests in seconds.
+pub const DEFAULT_REPLAY_TIMEOUT_SECS: u64 = 30;
+
+/// Configuration for the `replay` command.
+#[derive(Args, Debug, Clone)]
+pub struct ReplayConfig {
+    /// HAR file or access log (Common/Combined Log Format) to replay
+    #[arg(value_name = "FILE")]
+    pub file: PathBuf,
+
+    /// Server to send requests to (plain HTTP)
+    #[arg(long, default_value = DEFAULT_REPLAY_TARGET)]
+    pub target: String,
+
+    /// Playback speed relative to the capture (2 = twice as fast, 0 = no delays)
+    #[arg(long, default_value_t = 1.0)]
+    pub speed: f64,
+
+    /// Maximum requests in flight (0 = unlimited)
+    #[arg(long, default_value_t = 0)]
+    pub max_concurrency: usize,
+
+    /// Per-request timeout in seconds
+    #[arg(long, default_value_t = DEFAULT_REPLAY_TIMEOUT_SECS)]
+    pub timeout_secs: u64,
+
+    /// Print every request, not only failures and status mismatches
+    #[arg(short, long)]
+    pub verbose: bool,
+}
+
+impl ReplayConfig {
+    /// Build the replay pacing options.
+    pub fn options(&self) -> ReplayOptions {
+        ReplayOptions {
+            speed: self.speed,
+            max_concurrency: self.max_concurrency,
+            timeout: std::time::Duration::from_secs(self.timeout_secs),
+        }
+    }
+
+    /// Validate the replay configuration.
+    pub fn validate(&self) -> Result<(), String> {
+        if !(self.speed.is_finite() && self.speed >= 0.0) {
+            return Err("Replay speed cannot be negative".to_string());
+        }
+
+        if self.timeout_secs == 0 {
+            return Err("Replay timeout must be greater than 0".to_string());
+        }
+
+        Ok(())
+    }
+}
+
 // =============================================================================
 // Legacy Compatibility
 // =============================================================================
@@ -521,56 +2256,507 @@ mod tests {
     fn test_serve_config() -> ServeConfig {
         ServeConfig {
             s3_uri: None,
+            config: None,
+            config_watch_interval_secs: DEFAULT_CONFIG_WATCH_INTERVAL_SECS,
+            config_file_vars: Vec::new(),
             host: "127.0.0.1".to_string(),
             port: 8080,
+            admin_port: None,
+            tls_cert: None,
+            tls_key: None,
+            tls_redirect_port: None,
+            admin_host: DEFAULT_ADMIN_HOST.to_string(),
+            storage: StorageBackend::S3,
             s3_bucket: Some("test-bucket".to_string()),
             s3_endpoint: None,
             s3_region: "us-west-2".to_string(),
+            s3_failover_region: None,
+            s3_failover_endpoint: None,
+            s3_failover_bucket: None,
+            s3_failover_cooldown_secs: DEFAULT_FAILOVER_COOLDOWN.as_secs(),
+            s3_health_check_interval_secs: DEFAULT_S3_HEALTH_CHECK_INTERVAL_SECS,
+            s3_max_attempts: DEFAULT_S3_MAX_ATTEMPTS,
+            s3_retry_base_delay_ms: DEFAULT_S3_RETRY_BASE_DELAY.as_millis() as u64,
+            gcs_endpoint: None,
+            gcs_token: None,
+            gcs_metadata_auth: false,
+            azure_account: None,
+            azure_endpoint: None,
+            azure_sas_token: None,
+            http_base_url: None,
+            http_retries: DEFAULT_HTTP_RETRIES,
+            preload_into_memory: None,
+            warm_slides: None,
+            warm_concurrency: DEFAULT_WARM_CONCURRENCY,
             auth_secret: Some("test-secret".to_string()),
+            auth_secret_file: None,
             auth_enabled: true,
+            auth_secret_grace_secs: DEFAULT_SECRET_ROTATION_GRACE.as_secs(),
             cache_slides: 50,
+            max_concurrent_opens: DEFAULT_MAX_CONCURRENT_OPENS,
+            open_queue_timeout_ms: DEFAULT_OPEN_QUEUE_TIMEOUT_MS,
+            quarantine_threshold: DEFAULT_QUARANTINE_THRESHOLD,
+            negative_cache_ttl: DEFAULT_NEGATIVE_CACHE_TTL.as_secs(),
+            pyramid_detection: PyramidDetection::Strict,
+            pyramid_downsample_tolerance: DEFAULT_DOWNSAMPLE_TOLERANCE,
+            tiff_max_ifd_entries: DEFAULT_MAX_IFD_ENTRIES,
+            tiff_max_array_bytes: DEFAULT_MAX_ARRAY_BYTES,
+            tiff_max_tile_count: DEFAULT_MAX_TILE_COUNT,
+            tiff_max_jpeg_tables_bytes: DEFAULT_MAX_JPEG_TABLES_BYTES,
+            sparse_tiles: SparseTileMode::Background,
+            sparse_tile_color: "#ffffff".to_string(),
+            slide_revalidate_interval: DEFAULT_SLIDE_REVALIDATE_INTERVAL_SECS,
+            verify_checksums: false,
+            verify_reads: false,
+            slide_headers: false,
             cache_blocks: 100,
+            adaptive_cache: false,
+            cache_blocks_hot: DEFAULT_HOT_BLOCK_CACHE_CAPACITY,
+            cache_blocks_cold: DEFAULT_COLD_BLOCK_CACHE_CAPACITY,
             cache_tiles: 500,
+            cache_tile_shards: DEFAULT_TILE_CACHE_SHARDS,
+            cache_tile_ttl: 0,
+            cache_tile_admission: CacheAdmission::TinyLfu,
+            max_memory: 0,
+            stale_while_revalidate: false,
             block_size: DEFAULT_BLOCK_SIZE,
+            readahead_tiles: DEFAULT_READAHEAD_TILES,
             jpeg_quality: 85,
+            crop_edge_tiles: false,
+            retile_size: 0,
+            deterministic: false,
+            reuse_source_tiles: false,
+            server_timing: false,
+            tissue_masks: false,
+            embed_icc_profiles: false,
+            watermark_text: None,
+            watermark_image: None,
+            watermark_opacity: DEFAULT_WATERMARK_OPACITY,
+            watermark_corner: WatermarkCorner::BottomRight,
+            watermark_all: false,
+            resample_filter: ResampleFilter::default(),
+            max_concurrent_tiles: 0,
+            tile_queue_timeout_ms: DEFAULT_TILE_QUEUE_TIMEOUT_MS,
+            tile_timeout_ms: 0,
+            encode_workers: 0,
+            tenant_header: None,
+            tenant_max_in_flight: 0,
+            tenant_quotas: None,
+            tenant_buckets: None,
+            tenant_cache_slides: None,
+            bandwidth_limit: 0,
+            bandwidth_key_header: None,
+            bandwidth_key_limits: None,
+            rate_limit: 0,
+            rate_limit_burst: 0,
+            rate_limit_key: RateLimitKey::Ip,
             cache_max_age: 7200,
             cors_origins: None,
+            slo_availability: DEFAULT_AVAILABILITY_TARGET,
+            slo_latency: DEFAULT_LATENCY_TARGET,
+            slo_latency_threshold_ms: DEFAULT_SLO_LATENCY_THRESHOLD_MS,
+            inference_url: None,
+            inference_timeout_ms: DEFAULT_INFERENCE_TIMEOUT_MS,
             verbose: false,
+            log_format: LogFormat::Text,
             no_tracing: false,
+            no_compression: false,
+            otel_endpoint: None,
+            otel_sample_rate: DEFAULT_OTEL_SAMPLE_RATE,
         }
     }
 
-    #[test]
-    fn test_valid_config() {
-        let config = test_serve_config();
-        assert!(config.validate().is_ok());
+    fn env(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
     }
 
     #[test]
-    fn test_missing_auth_secret() {
-        let mut config = test_serve_config();
-        config.auth_secret = None;
-        config.auth_enabled = true;
+    fn test_resolve_env_aliases() {
+        let resolved = resolve_env_aliases(env(&[
+            ("WSI_STREAMER_PORT", "8080"),
+            ("WSI_PORT", "9000"),
+            ("WSI_HOST", "127.0.0.1"),
+            ("WSI_STREAMER_AUTH_SECRET", "s3cret"),
+            ("HOME", "/root"),
+        ]))
+        .unwrap();
 
-        let result = config.validate();
-        assert!(result.is_err());
-        assert!(result.unwrap_err().contains("secret"));
+        assert_eq!(
+            resolved,
+            env(&[("WSI_PORT", "8080"), ("WSI_AUTH_SECRET", "s3cret")])
+        );
     }
 
     #[test]
-    fn test_auth_disabled_no_secret_ok() {
-        let mut config = test_serve_config();
-        config.auth_secret = None;
-        config.auth_enabled = false;
+    fn test_resolve_env_alias_lists() {
+        let resolved = resolve_env_aliases(env(&[
+            (
+                "WSI_STREAMER_CORS_ORIGINS",
+                r#"["https://a.example", "https://b.example"]"#,
+            ),
+            ("WSI_TENANT_QUOTAS", "acme=2\nbeta=1:4\n"),
+            (
+                "WSI_STREAMER_BANDWIDTH_KEY_LIMITS",
+                r#"["k1=1000", "k2=0"]"#,
+            ),
+        ]))
+        .unwrap();
 
-        assert!(config.validate().is_ok());
+        assert_eq!(
+            resolved,
+            env(&[
+                ("WSI_CORS_ORIGINS", "https://a.example,https://b.example"),
+                ("WSI_TENANT_QUOTAS", "acme=2,beta=1:4"),
+                ("WSI_BANDWIDTH_KEY_LIMITS", "k1=1000,k2=0"),
+            ])
+        );
+
+        assert!(resolve_env_aliases(env(&[("WSI_CORS_ORIGINS", "[not json")])).is_err());
     }
 
     #[test]
-    fn test_empty_bucket() {
-        let mut config = test_serve_config();
-        config.s3_bucket = Some(String::new());
-        config.s3_uri = None;
+    fn test_list_env_vars_cover_delimited_options() {
+        use clap::CommandFactory;
+
+        let cli = Cli::command();
+        let commands = std::iter::once(&cli).chain(cli.get_subcommands());
+        for arg in commands.flat_map(|command| command.get_arguments()) {
+            if let (Some(env), Some(_)) = (arg.get_env(), arg.get_value_delimiter()) {
+                let env = env.to_str().unwrap();
+                assert!(LIST_ENV_VARS.contains(&env), "{} is not normalized", env);
+            }
+        }
+    }
+
+    #[test]
+    fn test_resolve_config_file_toml() {
+        let contents = r#"
+            s3_uri = "s3://slides"
+            port = 8080
+            cache-slides = 250
+            auth_enabled = true
+            cors_origins = ["https://a.example", "https://b.example"]
+            slo_availability = 0.995
+        "#;
+        let resolved = resolve_config_file(Path::new("wsi.toml"), contents).unwrap();
+        assert_eq!(
+            resolved,
+            env(&[
+                ("WSI_AUTH_ENABLED", "true"),
+                ("WSI_CACHE_SLIDES", "250"),
+                ("WSI_CORS_ORIGINS", "https://a.example,https://b.example"),
+                ("WSI_PORT", "8080"),
+                ("WSI_S3_URI", "s3://slides"),
+                ("WSI_SLO_AVAILABILITY", "0.995"),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_resolve_config_file_yaml() {
+        let contents = "storage: gcs\nwarm_slides:\n  - demo.svs\n  - cases/*.svs\n";
+        let resolved = resolve_config_file(Path::new("wsi.yaml"), contents).unwrap();
+        assert_eq!(
+            resolved,
+            env(&[
+                ("WSI_STORAGE", "gcs"),
+                ("WSI_WARM_SLIDES", "demo.svs,cases/*.svs"),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_config_file_errors_name_the_key() {
+        let path = Path::new("wsi.toml");
+        let err = resolve_config_file(path, "cache_slide = 10").unwrap_err();
+        assert_eq!(err, "wsi.toml: unknown key `cache_slide`");
+
+        let err = resolve_config_file(path, "port = \"http\"").unwrap_err();
+        assert_eq!(
+            err,
+            "wsi.toml: invalid value `http` for `port`: invalid digit found in string"
+        );
+
+        let err = resolve_config_file(path, "storage = \"ftp\"").unwrap_err();
+        assert_eq!(
+            err,
+            "wsi.toml: invalid value `ftp` for `storage`: expected one of s3, gcs, azure, http"
+        );
+
+        let err = resolve_config_file(path, "port = [1, 2]").unwrap_err();
+        assert_eq!(err, "wsi.toml: `port` must be a single value");
+
+        let err = resolve_config_file(path, "config = \"other.toml\"").unwrap_err();
+        assert_eq!(err, "wsi.toml: `config` can't be set in a config file");
+
+        assert!(resolve_config_file(path, "port = ").is_err());
+    }
+
+    #[test]
+    fn test_reload_from_file() {
+        let path = std::env::temp_dir().join(format!("wsi-reload-{}.toml", std::process::id()));
+        std::fs::write(
+            &path,
+            r#"
+                port = 9000
+                auth_secret = "rotated"
+                cors_origins = ["https://a.example"]
+                cache_max_age = 60
+                verbose = true
+            "#,
+        )
+        .unwrap();
+
+        let mut config = test_serve_config();
+        config.config = Some(path.clone());
+        config.reload_from_file(&["cache_max_age"]).unwrap();
+
+        // Only reloadable options that aren't pinned change
+        assert_eq!(config.port, 8080);
+        assert_eq!(config.auth_secret.as_deref(), Some("rotated"));
+        assert_eq!(
+            config.cors_origins,
+            Some(vec!["https://a.example".to_string()])
+        );
+        assert_eq!(config.cache_max_age, 7200);
+        assert!(config.verbose);
+
+        std::fs::write(&path, "cache_max_age = -1").unwrap();
+        assert!(config.reload_from_file(&[]).is_err());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_valid_config() {
+        let config = test_serve_config();
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_tenant_quotas() {
+        let mut config = test_serve_config();
+        config.tenant_quotas = Some(vec!["acme=4:16".to_string(), "beta=2".to_string()]);
+        let quotas = config.parse_tenant_quotas().unwrap();
+        assert_eq!(quotas[0], ("acme".to_string(), TenantQuota::new(4, 16)));
+        assert_eq!(quotas[1], ("beta".to_string(), TenantQuota::new(2, 0)));
+        assert!(config.validate().is_ok());
+
+        config.tenant_quotas = Some(vec!["acme".to_string()]);
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_tenant_buckets() {
+        let mut config = test_serve_config();
+        config.tenant_buckets = Some(vec![
+            "lab-a=s3://bucket-a".to_string(),
+            "lab-b=bucket-b".to_string(),
+        ]);
+        config.tenant_cache_slides = Some(vec!["lab-a=20".to_string()]);
+        assert_eq!(
+            config.parse_tenant_buckets().unwrap(),
+            [
+                ("lab-a".to_string(), "bucket-a".to_string()),
+                ("lab-b".to_string(), "bucket-b".to_string())
+            ]
+        );
+        assert_eq!(
+            config.parse_tenant_cache_slides().unwrap(),
+            [("lab-a".to_string(), 20)]
+        );
+        assert!(config.validate().is_ok());
+
+        config.tenant_cache_slides = Some(vec!["lab-a=0".to_string()]);
+        assert!(config.validate().is_err());
+        config.tenant_cache_slides = None;
+
+        for invalid in ["lab-a", "=bucket", "lab/a=bucket", "lab-c=gs://bucket"] {
+            config.tenant_buckets = Some(vec!["lab-b=bucket-b".to_string(), invalid.to_string()]);
+            assert!(config.validate().is_err(), "{}", invalid);
+        }
+
+        config.tenant_buckets = Some(vec!["lab-a=bucket-a".to_string()]);
+        config.s3_failover_region = Some("eu-west-1".to_string());
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_rate_limit_config() {
+        let mut config = test_serve_config();
+        assert!(!config.rate_limit_config().is_enabled());
+
+        config.rate_limit = 20;
+        config.rate_limit_key = "subject".parse().unwrap();
+        let rate_limit = config.rate_limit_config();
+        assert!(rate_limit.is_enabled());
+        assert_eq!(rate_limit.effective_burst(), 20);
+        assert_eq!(rate_limit.key, RateLimitKey::Subject);
+    }
+
+    #[test]
+    fn test_bandwidth_config() {
+        let mut config = test_serve_config();
+        assert!(!config.bandwidth_config().unwrap().is_enabled());
+
+        config.bandwidth_limit = 1_048_576;
+        config.bandwidth_key_limits = Some(vec!["export=262144".to_string()]);
+        assert!(config.validate().is_err());
+
+        config.bandwidth_key_header = Some("X-Api-Key".to_string());
+        let bandwidth = config.bandwidth_config().unwrap();
+        assert_eq!(bandwidth.default_rate, 1_048_576);
+        assert_eq!(bandwidth.key_rates.get("export"), Some(&262_144));
+        assert!(config.validate().is_ok());
+
+        config.bandwidth_key_limits = Some(vec!["export".to_string()]);
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_failover_config() {
+        let mut config = test_serve_config();
+        assert!(!config.has_failover());
+
+        config.s3_failover_region = Some("eu-west-1".to_string());
+        assert!(config.has_failover());
+        assert!(config.validate().is_ok());
+        assert_eq!(config.failover_bucket(), "test-bucket");
+        assert_eq!(config.failover_region(), "eu-west-1");
+
+        config.s3_failover_bucket = Some("test-bucket-replica".to_string());
+        assert_eq!(config.failover_bucket(), "test-bucket-replica");
+
+        config.s3_failover_cooldown_secs = 0;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_otel_settings() {
+        let mut config = test_serve_config();
+        config.otel_endpoint = Some("http://localhost:4318".to_string());
+        assert_eq!(config.validate().is_ok(), cfg!(feature = "otel"));
+
+        config.otel_endpoint = None;
+        config.otel_sample_rate = 1.5;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_inference_url_requires_feature() {
+        let mut config = test_serve_config();
+        config.inference_url = Some("http://localhost:9100/infer".to_string());
+        assert_eq!(config.validate().is_ok(), cfg!(feature = "inference"));
+
+        config.inference_timeout_ms = 0;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_s3_retry_policy() {
+        let mut config = test_serve_config();
+        let policy = config.s3_retry_policy();
+        assert_eq!(policy.max_attempts, DEFAULT_S3_MAX_ATTEMPTS);
+        assert_eq!(policy.base_delay, DEFAULT_S3_RETRY_BASE_DELAY);
+
+        config.s3_max_attempts = 0;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_storage_backend() {
+        let mut config = test_serve_config();
+        config.storage = StorageBackend::Gcs;
+        config.s3_bucket = None;
+        config.s3_uri = Some("gs://gcs-bucket/prefix".to_string());
+        assert_eq!(config.resolve_bucket().unwrap(), "gcs-bucket");
+        assert_eq!(config.validate().is_ok(), cfg!(feature = "gcs"));
+
+        // The URI scheme must match the backend
+        config.s3_uri = Some("s3://gcs-bucket".to_string());
+        assert!(config.resolve_bucket().is_err());
+
+        config.s3_uri = None;
+        config.s3_bucket = Some("gcs-bucket".to_string());
+        config.gcs_token = Some("token".to_string());
+        config.gcs_metadata_auth = true;
+        assert!(config.validate().is_err());
+
+        // Azure needs an account or endpoint
+        let mut config = test_serve_config();
+        config.storage = StorageBackend::Azure;
+        assert!(config.validate().is_err());
+        config.azure_account = Some("lab".to_string());
+        assert_eq!(config.validate().is_ok(), cfg!(feature = "azure"));
+
+        // Failover is S3-only
+        config.s3_failover_region = Some("eu-west-1".to_string());
+        assert!(config.validate().is_err());
+
+        // HTTP takes a base URL instead of a bucket
+        let mut config = test_serve_config();
+        config.storage = StorageBackend::Http;
+        assert!(config.validate().is_err());
+        config.s3_uri = Some("https://files.test/wsi/".to_string());
+        assert_eq!(
+            config.resolve_http_base_url().unwrap(),
+            "https://files.test/wsi"
+        );
+        assert_eq!(config.validate().is_ok(), cfg!(feature = "http"));
+        config.s3_uri = None;
+        config.http_base_url = Some("ftp://files.test".to_string());
+        assert!(config.resolve_http_base_url().is_err());
+    }
+
+    #[test]
+    fn test_missing_auth_secret() {
+        let mut config = test_serve_config();
+        config.auth_secret = None;
+        config.auth_enabled = true;
+
+        let result = config.validate();
+        assert!(result.is_err());
+        assert!(result.unwrap_err().contains("secret"));
+    }
+
+    #[tokio::test]
+    async fn test_auth_secret_file() {
+        let path = std::env::temp_dir().join(format!("wsi-auth-secret-{}", std::process::id()));
+        std::fs::write(&path, "from-file\n").unwrap();
+
+        let mut config = test_serve_config();
+        config.auth_secret = None;
+        config.auth_secret_file = Some(path.clone());
+        assert!(config.validate().is_ok());
+
+        config.resolve_secrets().await.unwrap();
+        assert_eq!(config.auth_secret_or_empty(), "from-file");
+
+        config.auth_secret_file = None;
+        config.auth_secret = Some(format!("file://{}", path.display()));
+        config.resolve_secrets().await.unwrap();
+        assert_eq!(config.auth_secret_or_empty(), "from-file");
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_auth_disabled_no_secret_ok() {
+        let mut config = test_serve_config();
+        config.auth_secret = None;
+        config.auth_enabled = false;
+
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_empty_bucket() {
+        let mut config = test_serve_config();
+        config.s3_bucket = Some(String::new());
+        config.s3_uri = None;
 
         let result = config.validate();
         assert!(result.is_err());
@@ -587,6 +2773,24 @@ mod tests {
         assert!(result.unwrap_err().contains("bucket"));
     }
 
+    #[test]
+    fn test_preload_makes_bucket_optional() {
+        let mut config = test_serve_config();
+        config.s3_bucket = None;
+        config.s3_uri = None;
+        config.preload_into_memory = Some(vec![]);
+        assert!(!config.preloads_into_memory());
+        assert!(config.validate().is_err());
+
+        config.preload_into_memory = Some(vec!["demo.svs".to_string()]);
+        assert!(config.preloads_into_memory());
+        assert!(config.validate().is_ok());
+
+        // A bucket that is given must still be valid
+        config.s3_bucket = Some(String::new());
+        assert!(config.validate().is_err());
+    }
+
     #[test]
     fn test_s3_uri_parsing() {
         // Valid S3 URIs
@@ -625,6 +2829,33 @@ mod tests {
         let mut config = test_serve_config();
         config.cache_tiles = 0;
         assert!(config.validate().is_err());
+
+        let mut config = test_serve_config();
+        config.max_concurrent_opens = 0;
+        assert!(config.validate().is_err());
+
+        let mut config = test_serve_config();
+        config.cache_tile_shards = 0;
+        assert!(config.validate().is_err());
+
+        let mut config = test_serve_config();
+        config.cache_blocks_cold = 0;
+        assert!(config.validate().is_ok());
+        config.adaptive_cache = true;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_adaptive_cache_config() {
+        let mut config = test_serve_config();
+        assert!(config.adaptive_cache_config().is_none());
+
+        config.adaptive_cache = true;
+        config.cache_blocks_hot = 800;
+        let adaptive = config.adaptive_cache_config().unwrap();
+        assert_eq!(adaptive.hot_blocks, 800);
+        assert_eq!(adaptive.warm_blocks, 100);
+        assert_eq!(adaptive.cold_blocks, DEFAULT_COLD_BLOCK_CACHE_CAPACITY);
     }
 
     #[test]
@@ -638,6 +2869,117 @@ mod tests {
         assert!(config.validate().is_err());
     }
 
+    #[test]
+    fn test_admin_listener() {
+        let mut config = test_serve_config();
+        assert_eq!(config.admin_bind_address(), None);
+
+        config.admin_port = Some(9090);
+        assert!(config.validate().is_ok());
+        assert_eq!(
+            config.admin_bind_address().as_deref(),
+            Some("127.0.0.1:9090")
+        );
+
+        config.admin_port = Some(config.port);
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_pyramid_detect_options() {
+        let mut config = test_serve_config();
+        assert_eq!(
+            config.pyramid_detect_options(),
+            PyramidDetectOptions::strict()
+        );
+
+        config.pyramid_detection = PyramidDetection::Permissive;
+        config.pyramid_downsample_tolerance = 0.3;
+        let options = config.pyramid_detect_options();
+        assert!(options.permissive);
+        assert_eq!(options.downsample_tolerance, 0.3);
+        assert!(config.validate().is_ok());
+
+        config.pyramid_downsample_tolerance = 1.0;
+        assert!(config.validate().is_err());
+        config.pyramid_downsample_tolerance = 0.3;
+
+        config.tiff_max_ifd_entries = 100;
+        assert_eq!(config.pyramid_detect_options().limits.max_ifd_entries, 100);
+        config.tiff_max_tile_count = 0;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_sparse_tiles() {
+        let mut config = test_serve_config();
+        assert_eq!(
+            config.sparse_tiles(),
+            SparseTiles::Background([255, 255, 255])
+        );
+
+        config.sparse_tile_color = "#F0F0F0".to_string();
+        assert!(config.validate().is_ok());
+        assert_eq!(
+            config.sparse_tiles(),
+            SparseTiles::Background([240, 240, 240])
+        );
+
+        config.sparse_tiles = SparseTileMode::Error;
+        assert_eq!(config.sparse_tiles(), SparseTiles::Error);
+
+        config.sparse_tile_color = "white".to_string();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_tls_settings() {
+        let mut config = test_serve_config();
+        assert!(!config.has_tls());
+
+        config.tls_redirect_port = Some(8081);
+        assert!(config.validate().is_err());
+
+        config.tls_cert = Some(PathBuf::from("/etc/wsi/cert.pem"));
+        assert!(config.validate().is_err());
+
+        config.tls_key = Some(PathBuf::from("/etc/wsi/key.pem"));
+        assert!(config.has_tls());
+        assert_eq!(config.validate().is_ok(), cfg!(feature = "tls"));
+        assert_eq!(
+            config.tls_redirect_address().as_deref(),
+            Some("127.0.0.1:8081")
+        );
+
+        config.tls_redirect_port = Some(config.port);
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_retile_size_validation() {
+        let mut config = test_serve_config();
+        config.retile_size = 32;
+        assert!(config.validate().is_err());
+
+        config.retile_size = 256;
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_invalid_slo_targets() {
+        let mut config = test_serve_config();
+        config.slo_availability = 1.0;
+        assert!(config.validate().is_err());
+
+        let mut config = test_serve_config();
+        config.slo_latency = 0.0;
+        assert!(config.validate().is_err());
+
+        let mut config = test_serve_config();
+        config.slo_latency_threshold_ms = 0;
+        assert!(config.validate().is_err());
+    }
+
     #[test]
     fn test_bind_address() {
         let config = test_serve_config();
@@ -673,6 +3015,7 @@ mod tests {
             ttl: 3600,
             base_url: None,
             params: Some(vec!["quality=90".to_string(), "format=jpg".to_string()]),
+            claims: None,
             format: SignOutputFormat::Url,
         };
 
@@ -690,12 +3033,30 @@ mod tests {
             ttl: 3600,
             base_url: None,
             params: Some(vec!["invalid_param".to_string()]),
+            claims: None,
             format: SignOutputFormat::Url,
         };
 
         assert!(config.parse_params().is_err());
     }
 
+    #[test]
+    fn test_sign_config_claims() {
+        let mut config = SignConfig {
+            path: "/slides/lab-a%2Fs1.svs".to_string(),
+            secret: "secret".to_string(),
+            ttl: 3600,
+            base_url: None,
+            params: None,
+            claims: Some("slide=lab-a/*;min_level=1".to_string()),
+            format: SignOutputFormat::Url,
+        };
+        assert!(config.validate().is_ok());
+
+        config.claims = Some("slide=lab-a/*;levels=1".to_string());
+        assert!(config.validate().is_err());
+    }
+
     #[test]
     fn test_check_config_resolve_bucket() {
         let config = CheckConfig {
@@ -710,4 +3071,95 @@ mod tests {
 
         assert_eq!(config.resolve_bucket().unwrap(), "check-bucket");
     }
+
+    fn test_plan_config() -> PlanConfig {
+        PlanConfig {
+            slides: 1000,
+            avg_slide_size: 1024 * 1024 * 1024,
+            concurrency: 50,
+            avg_tile_size: 40 * 1024,
+            avg_served_tile_size: 30 * 1024,
+            tiles_per_viewer: 10.0,
+            opens_per_minute: 10.0,
+            s3_latency_ms: 50,
+            format: PlanOutputFormat::Text,
+        }
+    }
+
+    #[test]
+    fn test_plan_config_validation() {
+        assert!(test_plan_config().validate().is_ok());
+
+        let mut config = test_plan_config();
+        config.concurrency = 0;
+        assert!(config.validate().is_err());
+
+        let mut config = test_plan_config();
+        config.avg_slide_size = 1;
+        assert!(config.validate().is_err());
+
+        let mut config = test_plan_config();
+        config.tiles_per_viewer = 0.0;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_plan_config_inputs() {
+        let inputs = test_plan_config().inputs();
+        assert_eq!(inputs.concurrency, 50);
+        assert_eq!(inputs.avg_source_tile_bytes, 40 * 1024);
+    }
+
+    #[test]
+    fn test_replay_config() {
+        let cli = Cli::try_parse_from([
+            "wsi-streamer",
+            "replay",
+            "access.log",
+            "--speed",
+            "2",
+            "--max-concurrency",
+            "6",
+        ])
+        .unwrap();
+        let Some(Command::Replay(config)) = cli.command else {
+            panic!("expected replay command");
+        };
+        assert_eq!(config.file, PathBuf::from("access.log"));
+        assert_eq!(config.target, DEFAULT_REPLAY_TARGET);
+        assert!(config.validate().is_ok());
+
+        let options = config.options();
+        assert_eq!(options.speed, 2.0);
+        assert_eq!(options.max_concurrency, 6);
+
+        let mut config = config;
+        config.speed = -1.0;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_inspect_config() {
+        let cli = Cli::try_parse_from(["wsi-streamer", "inspect", "slide.svs"]).unwrap();
+        let Some(Command::Inspect(config)) = cli.command else {
+            panic!("expected inspect command");
+        };
+        assert_eq!(config.file, PathBuf::from("slide.svs"));
+    }
+
+    #[test]
+    fn test_manifest_output_path() {
+        let mut config = ManifestConfig {
+            file: PathBuf::from("/data/slide.svs"),
+            block_size: DEFAULT_MANIFEST_BLOCK_SIZE,
+            output: None,
+        };
+        assert_eq!(
+            config.output_path(),
+            PathBuf::from("/data/slide.svs.sha256")
+        );
+
+        config.output = Some(PathBuf::from("/tmp/out.sha256"));
+        assert_eq!(config.output_path(), PathBuf::from("/tmp/out.sha256"));
+    }
 }
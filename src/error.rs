@@ -1,5 +1,41 @@
+//! Error types.
+//!
+//! Every error that can reach an HTTP response implements [`ErrorCode`],
+//! which gives each variant a stable machine-readable code, the HTTP status
+//! it maps to and whether retrying may succeed. Handlers build responses
+//! from it instead of matching on variants themselves, so new endpoints
+//! report errors the same way as existing ones.
+
+use http::StatusCode;
 use thiserror::Error;
 
+use crate::format::tiff::ValidationError;
+
+// =============================================================================
+// Error Codes
+// =============================================================================
+
+/// Stable classification of an error for API responses.
+pub trait ErrorCode {
+    /// Machine-readable code, reported as `error` in JSON error bodies.
+    ///
+    /// Codes are part of the API and never change for a given condition.
+    fn code(&self) -> &'static str;
+
+    /// HTTP status the error maps to.
+    fn status(&self) -> StatusCode;
+
+    /// Whether the same request may succeed if retried later.
+    ///
+    /// True for storage outages and overload; false for errors caused by
+    /// the request or the slide itself.
+    fn is_retryable(&self) -> bool;
+}
+
+// =============================================================================
+// Error Types
+// =============================================================================
+
 /// I/O errors that can occur when reading from remote storage
 #[derive(Debug, Clone, Error)]
 pub enum IoError {
@@ -22,6 +58,22 @@ pub enum IoError {
     /// Object not found
     #[error("Object not found: {0}")]
     NotFound(String),
+
+    /// Object key is not a valid slide identifier (should map to HTTP 400)
+    #[error("Invalid slide id: {0}")]
+    InvalidKey(String),
+
+    /// Object was replaced since it was opened (its ETag no longer matches)
+    #[error("Object changed since it was opened: {0}")]
+    ObjectChanged(String),
+
+    /// Bytes read from storage don't match their checksum
+    #[error("Checksum mismatch in {identifier}: {len} bytes at offset {offset}")]
+    Corrupted {
+        identifier: String,
+        offset: u64,
+        len: u64,
+    },
 }
 
 /// Errors related to format detection and validation
@@ -38,6 +90,14 @@ pub enum FormatError {
     /// File format is not supported (should map to HTTP 415)
     #[error("Unsupported format: {reason}")]
     UnsupportedFormat { reason: String },
+
+    /// Too many slides are being opened; the open timed out waiting for a slot
+    #[error("Timed out after {waited_ms}ms waiting to open slide {slide_id}")]
+    OpenQueueTimeout { slide_id: String, waited_ms: u64 },
+
+    /// Slide was quarantined after repeated failures (should map to HTTP 423)
+    #[error("Slide is quarantined after repeated failures: {slide_id}")]
+    Quarantined { slide_id: String },
 }
 
 /// Errors that can occur when parsing TIFF files
@@ -76,16 +136,45 @@ pub enum TiffError {
     InvalidTagValue { tag: &'static str, message: String },
 
     /// Unsupported compression scheme
-    #[error("Unsupported compression: {0} (only JPEG and JPEG 2000 are supported)")]
+    #[error("Unsupported compression: {0} (supported: JPEG, JPEG 2000, LZW, Deflate)")]
     UnsupportedCompression(String),
 
     /// File uses strips instead of tiles
     #[error("Unsupported organization: file uses strips instead of tiles")]
     StripOrganization,
 
+    /// Tile is not stored in the file (TileOffsets or TileByteCounts is 0)
+    #[error("Tile ({tile_x}, {tile_y}) at level {level} is missing from the file (sparse TIFF)")]
+    SparseTile {
+        level: usize,
+        tile_x: u32,
+        tile_y: u32,
+    },
+
+    /// File declares a structure larger than a parser safety limit allows
+    #[error("TIFF exceeds {limit}: {value} > {max}")]
+    LimitExceeded {
+        limit: &'static str,
+        value: u64,
+        max: u64,
+    },
+
     /// Unknown field type in IFD entry
     #[error("Unknown field type: {0}")]
     UnknownFieldType(u16),
+
+    /// Slide rejected by validation; lists every failed constraint
+    #[error("Unsupported slide: {}", join_violations(.0))]
+    Unsupported(Vec<ValidationError>),
+}
+
+/// Join validation errors into a single message.
+fn join_violations(errors: &[ValidationError]) -> String {
+    errors
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
 }
 
 /// Errors that can occur when processing tiles
@@ -128,4 +217,514 @@ pub enum TileError {
     /// Invalid quality parameter
     #[error("Invalid quality: {quality} (must be 1-100)")]
     InvalidQuality { quality: u8 },
+
+    /// Brightness, contrast or gamma outside its accepted range
+    #[error("Invalid {parameter}: {message}")]
+    InvalidAdjustment {
+        parameter: &'static str,
+        message: String,
+    },
+
+    /// Requested region is empty, outside the slide or too large to render
+    #[error("Invalid region: {message}")]
+    InvalidRegion { message: String },
+
+    /// Requested output format is not compiled into this build
+    #[error("Unsupported output format: {format}")]
+    UnsupportedOutputFormat { format: String },
+
+    /// External tile transformer (e.g. inference service) failed
+    #[error("Tile transform failed: {message}")]
+    TransformError { message: String },
+
+    /// The service is temporarily at capacity (should map to HTTP 503)
+    #[error("Service overloaded: {message}")]
+    Overloaded { message: String },
+    /// Slide was quarantined after repeated failures (should map to HTTP 423)
+    #[error("Slide is quarantined after repeated failures: {slide_id}")]
+    SlideQuarantined { slide_id: String },
+
+    /// Tile was not generated within the request budget (should map to HTTP 504)
+    #[error("Tile generation exceeded its {budget_ms}ms budget")]
+    Timeout { budget_ms: u64 },
+}
+
+/// Errors that can occur when serving IIIF Image API requests
+#[derive(Debug, Clone, Error)]
+pub enum IiifError {
+    /// Malformed region, size, rotation, quality or format parameter
+    #[error("Invalid IIIF {parameter}: {message}")]
+    InvalidParameter {
+        parameter: &'static str,
+        message: String,
+    },
+
+    /// Well-formed request using a feature this server does not implement
+    #[error("Unsupported IIIF {parameter}: {message}")]
+    Unsupported {
+        parameter: &'static str,
+        message: String,
+    },
+
+    /// Error rendering the image
+    #[error(transparent)]
+    Tile(#[from] TileError),
+}
+
+/// Errors that can occur when saving or loading viewer states
+#[derive(Debug, Clone, Error)]
+pub enum ViewError {
+    /// View name is empty, too long or contains unsupported characters
+    #[error("Invalid view name: {name}")]
+    InvalidName { name: String },
+
+    /// View state is inconsistent with the slide
+    #[error("Invalid view state: {message}")]
+    InvalidState { message: String },
+
+    /// No view with this name is saved for the slide
+    #[error("View {name} not found for slide {slide_id}")]
+    NotFound { slide_id: String, name: String },
+
+    /// The slide could not be opened
+    #[error("Slide error: {0}")]
+    Slide(#[from] FormatError),
+
+    /// Error reading or writing the stored view
+    #[error("I/O error: {0}")]
+    Io(#[from] IoError),
+}
+
+/// Errors that can occur when saving or loading slide annotations
+#[derive(Debug, Clone, Error)]
+pub enum AnnotationError {
+    /// Body is not GeoJSON the overlay renderer understands
+    #[error("Invalid GeoJSON: {message}")]
+    InvalidGeoJson { message: String },
+
+    /// Annotation set exceeds the vertex limit
+    #[error("Too many annotation vertices: {count} (max {max})")]
+    TooLarge { count: usize, max: usize },
+
+    /// No annotations are stored for the slide
+    #[error("No annotations stored for slide {slide_id}")]
+    NotFound { slide_id: String },
+
+    /// The slide could not be opened
+    #[error("Slide error: {0}")]
+    Slide(#[from] FormatError),
+
+    /// Error reading or writing the stored annotations
+    #[error("I/O error: {0}")]
+    Io(#[from] IoError),
+}
+
+/// Errors that can occur when resolving a secret reference
+#[derive(Debug, Clone, Error)]
+pub enum SecretError {
+    /// The secret source could not be read
+    #[error("Failed to read secret from {origin}: {message}")]
+    Read { origin: String, message: String },
+
+    /// The secret source exists but holds an empty value
+    #[error("Secret from {origin} is empty")]
+    Empty { origin: String },
+
+    /// JSON secret does not contain the requested field
+    #[error("Secret from {origin} has no field {key}")]
+    MissingKey { origin: String, key: String },
+
+    /// Reference scheme needs a feature not compiled into this build
+    #[error("{scheme}:// secret references require the aws-secrets feature")]
+    Unsupported { scheme: &'static str },
+}
+
+/// Errors that can occur when loading the TLS certificate and key
+#[derive(Debug, Clone, Error)]
+pub enum TlsError {
+    /// The certificate chain could not be read or parsed
+    #[error("Failed to load TLS certificate {path}: {message}")]
+    Certificate { path: String, message: String },
+
+    /// The private key could not be read or parsed
+    #[error("Failed to load TLS private key {path}: {message}")]
+    PrivateKey { path: String, message: String },
+
+    /// The certificate and key don't form a usable configuration
+    #[error("Invalid TLS configuration: {0}")]
+    Config(String),
+}
+
+/// Errors that can occur when loading or replaying a request log
+#[derive(Debug, Clone, Error)]
+pub enum ReplayError {
+    /// The request log could not be read
+    #[error("Failed to read request log: {0}")]
+    Read(String),
+
+    /// The request log is not valid HAR
+    #[error("Failed to parse HAR: {0}")]
+    Parse(String),
+
+    /// The replay target URL is unusable
+    #[error("Invalid replay target {0}")]
+    InvalidTarget(String),
+}
+
+// =============================================================================
+// Error Code Mapping
+// =============================================================================
+
+impl ErrorCode for IoError {
+    fn code(&self) -> &'static str {
+        match self {
+            IoError::NotFound(_) => "not_found",
+            IoError::InvalidKey(_) => "invalid_slide_id",
+            IoError::S3(_) => "storage_error",
+            IoError::Connection(_) => "connection_error",
+            IoError::RangeOutOfBounds { .. } => "io_error",
+            IoError::ObjectChanged(_) => "object_changed",
+            IoError::Corrupted { .. } => "data_corrupted",
+        }
+    }
+
+    fn status(&self) -> StatusCode {
+        match self {
+            IoError::NotFound(_) => StatusCode::NOT_FOUND,
+            IoError::InvalidKey(_) => StatusCode::BAD_REQUEST,
+            IoError::Connection(_) => StatusCode::BAD_GATEWAY,
+            IoError::ObjectChanged(_) => StatusCode::SERVICE_UNAVAILABLE,
+            IoError::S3(_) | IoError::RangeOutOfBounds { .. } | IoError::Corrupted { .. } => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        }
+    }
+
+    fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            IoError::S3(_) | IoError::Connection(_) | IoError::ObjectChanged(_)
+        )
+    }
+}
+
+impl ErrorCode for TiffError {
+    fn code(&self) -> &'static str {
+        match self {
+            TiffError::Io(err) => err.code(),
+            TiffError::SparseTile { .. } => "missing_tile",
+            _ => "unsupported_format",
+        }
+    }
+
+    fn status(&self) -> StatusCode {
+        match self {
+            TiffError::Io(err) => err.status(),
+            TiffError::SparseTile { .. } => StatusCode::NOT_FOUND,
+            _ => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+        }
+    }
+
+    fn is_retryable(&self) -> bool {
+        match self {
+            TiffError::Io(err) => err.is_retryable(),
+            _ => false,
+        }
+    }
+}
+
+impl ErrorCode for FormatError {
+    fn code(&self) -> &'static str {
+        match self {
+            FormatError::Io(err) => err.code(),
+            FormatError::Tiff(err) => err.code(),
+            FormatError::UnsupportedFormat { .. } => "unsupported_format",
+            FormatError::OpenQueueTimeout { .. } => "overloaded",
+            FormatError::Quarantined { .. } => "slide_quarantined",
+        }
+    }
+
+    fn status(&self) -> StatusCode {
+        match self {
+            FormatError::Io(err) => err.status(),
+            FormatError::Tiff(err) => err.status(),
+            FormatError::UnsupportedFormat { .. } => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            FormatError::OpenQueueTimeout { .. } => StatusCode::SERVICE_UNAVAILABLE,
+            FormatError::Quarantined { .. } => StatusCode::LOCKED,
+        }
+    }
+
+    fn is_retryable(&self) -> bool {
+        match self {
+            FormatError::Io(err) => err.is_retryable(),
+            FormatError::Tiff(err) => err.is_retryable(),
+            FormatError::OpenQueueTimeout { .. } => true,
+            FormatError::UnsupportedFormat { .. } | FormatError::Quarantined { .. } => false,
+        }
+    }
+}
+
+impl ErrorCode for TileError {
+    fn code(&self) -> &'static str {
+        match self {
+            TileError::Io(err) => err.code(),
+            TileError::Slide(err) => err.code(),
+            TileError::DecodeError { .. } => "decode_error",
+            TileError::EncodeError { .. } => "encode_error",
+            TileError::InvalidLevel { .. } => "invalid_level",
+            TileError::TileOutOfBounds { .. } => "tile_out_of_bounds",
+            TileError::SlideNotFound { .. } => "not_found",
+            TileError::InvalidQuality { .. } => "invalid_quality",
+            TileError::InvalidAdjustment { .. } => "invalid_adjustment",
+            TileError::InvalidRegion { .. } => "invalid_region",
+            TileError::UnsupportedOutputFormat { .. } => "unsupported_output_format",
+            TileError::TransformError { .. } => "transform_error",
+            TileError::Overloaded { .. } => "overloaded",
+            TileError::SlideQuarantined { .. } => "slide_quarantined",
+            TileError::Timeout { .. } => "timeout",
+        }
+    }
+
+    fn status(&self) -> StatusCode {
+        match self {
+            TileError::Io(err) => err.status(),
+            TileError::Slide(err) => err.status(),
+            TileError::DecodeError { .. } | TileError::EncodeError { .. } => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+            TileError::InvalidLevel { .. }
+            | TileError::TileOutOfBounds { .. }
+            | TileError::InvalidQuality { .. }
+            | TileError::InvalidAdjustment { .. }
+            | TileError::InvalidRegion { .. } => StatusCode::BAD_REQUEST,
+            TileError::SlideNotFound { .. } => StatusCode::NOT_FOUND,
+            TileError::UnsupportedOutputFormat { .. } => StatusCode::NOT_ACCEPTABLE,
+            TileError::TransformError { .. } => StatusCode::BAD_GATEWAY,
+            TileError::Overloaded { .. } => StatusCode::SERVICE_UNAVAILABLE,
+            TileError::SlideQuarantined { .. } => StatusCode::LOCKED,
+            TileError::Timeout { .. } => StatusCode::GATEWAY_TIMEOUT,
+        }
+    }
+
+    fn is_retryable(&self) -> bool {
+        match self {
+            TileError::Io(err) => err.is_retryable(),
+            TileError::Slide(err) => err.is_retryable(),
+            TileError::TransformError { .. }
+            | TileError::Overloaded { .. }
+            | TileError::Timeout { .. } => true,
+            _ => false,
+        }
+    }
+}
+
+impl ErrorCode for IiifError {
+    fn code(&self) -> &'static str {
+        match self {
+            IiifError::InvalidParameter { .. } => "invalid_iiif_parameter",
+            IiifError::Unsupported { .. } => "not_implemented",
+            IiifError::Tile(err) => err.code(),
+        }
+    }
+
+    fn status(&self) -> StatusCode {
+        match self {
+            IiifError::InvalidParameter { .. } => StatusCode::BAD_REQUEST,
+            IiifError::Unsupported { .. } => StatusCode::NOT_IMPLEMENTED,
+            IiifError::Tile(err) => err.status(),
+        }
+    }
+
+    fn is_retryable(&self) -> bool {
+        match self {
+            IiifError::Tile(err) => err.is_retryable(),
+            _ => false,
+        }
+    }
+}
+
+impl ErrorCode for ViewError {
+    fn code(&self) -> &'static str {
+        match self {
+            ViewError::InvalidName { .. } => "invalid_view_name",
+            ViewError::InvalidState { .. } => "invalid_view_state",
+            ViewError::NotFound { .. } => "not_found",
+            ViewError::Slide(err) => err.code(),
+            ViewError::Io(err) => err.code(),
+        }
+    }
+
+    fn status(&self) -> StatusCode {
+        match self {
+            ViewError::InvalidName { .. } | ViewError::InvalidState { .. } => {
+                StatusCode::BAD_REQUEST
+            }
+            ViewError::NotFound { .. } => StatusCode::NOT_FOUND,
+            ViewError::Slide(err) => err.status(),
+            ViewError::Io(err) => err.status(),
+        }
+    }
+
+    fn is_retryable(&self) -> bool {
+        match self {
+            ViewError::Slide(err) => err.is_retryable(),
+            ViewError::Io(err) => err.is_retryable(),
+            _ => false,
+        }
+    }
+}
+
+impl ErrorCode for AnnotationError {
+    fn code(&self) -> &'static str {
+        match self {
+            AnnotationError::InvalidGeoJson { .. } => "invalid_annotations",
+            AnnotationError::TooLarge { .. } => "annotations_too_large",
+            AnnotationError::NotFound { .. } => "not_found",
+            AnnotationError::Slide(err) => err.code(),
+            AnnotationError::Io(err) => err.code(),
+        }
+    }
+
+    fn status(&self) -> StatusCode {
+        match self {
+            AnnotationError::InvalidGeoJson { .. } => StatusCode::BAD_REQUEST,
+            AnnotationError::TooLarge { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+            AnnotationError::NotFound { .. } => StatusCode::NOT_FOUND,
+            AnnotationError::Slide(err) => err.status(),
+            AnnotationError::Io(err) => err.status(),
+        }
+    }
+
+    fn is_retryable(&self) -> bool {
+        match self {
+            AnnotationError::Slide(err) => err.is_retryable(),
+            AnnotationError::Io(err) => err.is_retryable(),
+            _ => false,
+        }
+    }
+}
+
+// =============================================================================
+// Conversions
+// =============================================================================
+
+impl TileError {
+    /// Convert an error from opening a slide.
+    ///
+    /// A missing object becomes [`TileError::SlideNotFound`] for the
+    /// requested slide, and open-queue timeouts become
+    /// [`TileError::Overloaded`].
+    pub fn from_open(slide_id: &str, err: FormatError) -> Self {
+        match err {
+            FormatError::Io(IoError::NotFound(_)) => TileError::SlideNotFound {
+                slide_id: slide_id.to_string(),
+            },
+            FormatError::Io(io_err) => TileError::Io(io_err),
+            FormatError::Tiff(tiff_err) => TileError::Slide(tiff_err),
+            FormatError::UnsupportedFormat { reason } => {
+                TileError::Slide(TiffError::InvalidTagValue {
+                    tag: "Format",
+                    message: reason,
+                })
+            }
+            err @ FormatError::OpenQueueTimeout { .. } => TileError::Overloaded {
+                message: err.to_string(),
+            },
+            FormatError::Quarantined { slide_id } => TileError::SlideQuarantined { slide_id },
+        }
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_io_error_codes() {
+        let not_found = IoError::NotFound("slide.svs".to_string());
+        assert_eq!(not_found.code(), "not_found");
+        assert_eq!(not_found.status(), StatusCode::NOT_FOUND);
+        assert!(!not_found.is_retryable());
+
+        let connection = IoError::Connection("reset".to_string());
+        assert_eq!(connection.code(), "connection_error");
+        assert_eq!(connection.status(), StatusCode::BAD_GATEWAY);
+        assert!(connection.is_retryable());
+
+        assert!(IoError::S3("throttled".to_string()).is_retryable());
+
+        let changed = IoError::ObjectChanged("s3://bucket/slide.svs".to_string());
+        assert_eq!(changed.code(), "object_changed");
+        assert_eq!(changed.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert!(changed.is_retryable());
+    }
+
+    #[test]
+    fn test_wrapped_errors_keep_code() {
+        // The same storage failure is classified the same way however it
+        // reaches the handler
+        let io = IoError::Connection("reset".to_string());
+        let wrapped: [&dyn ErrorCode; 5] = [
+            &TileError::Io(io.clone()),
+            &TileError::Slide(TiffError::Io(io.clone())),
+            &FormatError::Tiff(TiffError::Io(io.clone())),
+            &ViewError::Io(io.clone()),
+            &AnnotationError::Io(io.clone()),
+        ];
+        for err in wrapped {
+            assert_eq!(err.code(), io.code());
+            assert_eq!(err.status(), io.status());
+            assert!(err.is_retryable());
+        }
+    }
+
+    #[test]
+    fn test_slide_errors_not_retryable() {
+        let err = TileError::Slide(TiffError::StripOrganization);
+        assert_eq!(err.code(), "unsupported_format");
+        assert_eq!(err.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        assert!(!err.is_retryable());
+
+        let err = TileError::InvalidQuality { quality: 0 };
+        assert_eq!(err.status(), StatusCode::BAD_REQUEST);
+        assert!(!err.is_retryable());
+
+        let err = FormatError::OpenQueueTimeout {
+            slide_id: "slide.svs".to_string(),
+            waited_ms: 100,
+        };
+        assert_eq!(err.code(), "overloaded");
+        assert!(err.is_retryable());
+    }
+
+    #[test]
+    fn test_from_open() {
+        let err = TileError::from_open(
+            "slide.svs",
+            FormatError::Io(IoError::NotFound("s3://bucket/slide.svs".to_string())),
+        );
+        assert!(
+            matches!(err, TileError::SlideNotFound { ref slide_id } if slide_id == "slide.svs")
+        );
+
+        let err = TileError::from_open(
+            "slide.svs",
+            FormatError::UnsupportedFormat {
+                reason: "not a TIFF file".to_string(),
+            },
+        );
+        assert_eq!(err.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+        let err = TileError::from_open(
+            "slide.svs",
+            FormatError::Quarantined {
+                slide_id: "slide.svs".to_string(),
+            },
+        );
+        assert_eq!(err.code(), "slide_quarantined");
+    }
 }
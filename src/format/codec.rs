@@ -0,0 +1,466 @@
+//! Raw pixel codecs for LZW- and Deflate-compressed TIFF tiles.
+//!
+//! JPEG and JPEG 2000 tiles are self-describing streams that the tile encoder
+//! can decode directly. LZW and Deflate tiles are not: they are compressed
+//! pixel buffers whose layout (dimensions, samples per pixel, predictor,
+//! photometric interpretation) lives in the IFD. Readers therefore wrap such
+//! tiles with their layout in a small self-describing container, and the tile
+//! encoder decodes the container with [`decode_raw_tile`] before re-encoding
+//! as JPEG. Tiles cut from strip-organized levels are wrapped the same way,
+//! as uncompressed RGB pixels.
+//!
+//! # Supported Layouts
+//!
+//! - 8 bits per sample, chunky (contiguous) planar configuration
+//! - 1 sample (grayscale), 3 samples (RGB) or 4 samples (RGB + extra sample,
+//!   which is dropped)
+//! - No predictor or horizontal differencing (predictor 2)
+//! - MinIsWhite, MinIsBlack or RGB photometric interpretation
+//!
+//! Other layouts are rejected as an unsupported compression.
+
+use std::io::Read;
+
+use bytes::{BufMut, Bytes, BytesMut};
+use image::{DynamicImage, GrayImage, RgbImage};
+
+use crate::error::{TiffError, TileError};
+
+use super::tiff::{ByteOrder, Compression, PyramidLevel, TiffTag};
+
+/// Magic bytes identifying a wrapped raw tile.
+///
+/// The leading NUL can't start a JPEG or JPEG 2000 stream, so wrapped tiles
+/// are never mistaken for either.
+pub const RAW_TILE_MAGIC: [u8; 8] = *b"\0WSIRAW\x01";
+
+/// Size of the wrapped raw tile header in bytes.
+const RAW_TILE_HEADER_SIZE: usize = 24;
+
+/// TIFF predictor: none.
+const PREDICTOR_NONE: u16 = 1;
+
+/// TIFF predictor: horizontal differencing.
+const PREDICTOR_HORIZONTAL: u16 = 2;
+
+/// Photometric interpretation: 0 is white.
+const PHOTOMETRIC_MIN_IS_WHITE: u16 = 0;
+
+/// Photometric interpretation: 0 is black.
+const PHOTOMETRIC_MIN_IS_BLACK: u16 = 1;
+
+/// Photometric interpretation: RGB.
+const PHOTOMETRIC_RGB: u16 = 2;
+
+// =============================================================================
+// Raw Tile Layout
+// =============================================================================
+
+/// Pixel layout of an LZW- or Deflate-compressed tile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RawTileLayout {
+    /// Compression scheme (LZW, Deflate or Adobe Deflate)
+    pub compression: Compression,
+
+    /// Tile width in pixels
+    pub width: u32,
+
+    /// Tile height in pixels
+    pub height: u32,
+
+    /// Samples per pixel (1, 3 or 4)
+    pub samples_per_pixel: u16,
+
+    /// TIFF predictor (1 = none, 2 = horizontal differencing)
+    pub predictor: u16,
+
+    /// TIFF photometric interpretation
+    pub photometric: u16,
+}
+
+impl RawTileLayout {
+    /// Get the raw tile layout of a pyramid level.
+    ///
+    /// Returns `Ok(None)` for levels whose tiles are self-describing streams
+    /// (JPEG, JPEG 2000).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TiffError::UnsupportedCompression`] if the level is LZW or
+    /// Deflate compressed with a pixel layout this module can't decode.
+    pub fn from_level(
+        level: &PyramidLevel,
+        byte_order: ByteOrder,
+    ) -> Result<Option<Self>, TiffError> {
+        let compression = match Compression::from_u16(level.compression) {
+            Some(c @ (Compression::Lzw | Compression::Deflate | Compression::AdobeDeflate)) => c,
+            _ => return Ok(None),
+        };
+
+        let ifd = &level.ifd;
+        let unsupported = |detail: String| {
+            TiffError::UnsupportedCompression(format!("{} ({})", compression.name(), detail))
+        };
+
+        // Multi-valued BitsPerSample isn't stored inline; the decoded size
+        // check catches anything other than 8 bits per sample.
+        if let Some(bits) = ifd.get_u16(TiffTag::BitsPerSample, byte_order) {
+            if bits != 8 {
+                return Err(unsupported(format!("{} bits per sample", bits)));
+            }
+        }
+
+        let planar = ifd
+            .get_u16(TiffTag::PlanarConfiguration, byte_order)
+            .unwrap_or(1);
+        if planar != 1 {
+            return Err(unsupported("separate planes".to_string()));
+        }
+
+        let samples_per_pixel = ifd
+            .get_u16(TiffTag::SamplesPerPixel, byte_order)
+            .unwrap_or(1);
+        if !matches!(samples_per_pixel, 1 | 3 | 4) {
+            return Err(unsupported(format!(
+                "{} samples per pixel",
+                samples_per_pixel
+            )));
+        }
+
+        let predictor = ifd
+            .get_u16(TiffTag::Predictor, byte_order)
+            .unwrap_or(PREDICTOR_NONE);
+        if predictor != PREDICTOR_NONE && predictor != PREDICTOR_HORIZONTAL {
+            return Err(unsupported(format!("predictor {}", predictor)));
+        }
+
+        let default_photometric = if samples_per_pixel == 1 {
+            PHOTOMETRIC_MIN_IS_BLACK
+        } else {
+            PHOTOMETRIC_RGB
+        };
+        let photometric = ifd
+            .get_u16(TiffTag::PhotometricInterpretation, byte_order)
+            .unwrap_or(default_photometric);
+        let photometric_ok = match samples_per_pixel {
+            1 => matches!(
+                photometric,
+                PHOTOMETRIC_MIN_IS_WHITE | PHOTOMETRIC_MIN_IS_BLACK
+            ),
+            _ => photometric == PHOTOMETRIC_RGB,
+        };
+        if !photometric_ok {
+            return Err(unsupported(format!(
+                "photometric interpretation {}",
+                photometric
+            )));
+        }
+
+        Ok(Some(RawTileLayout {
+            compression,
+            width: level.tile_width,
+            height: level.tile_height,
+            samples_per_pixel,
+            predictor,
+            photometric,
+        }))
+    }
+
+    /// Layout of an uncompressed RGB tile.
+    pub fn uncompressed_rgb(width: u32, height: u32) -> Self {
+        RawTileLayout {
+            compression: Compression::None,
+            width,
+            height,
+            samples_per_pixel: 3,
+            predictor: PREDICTOR_NONE,
+            photometric: PHOTOMETRIC_RGB,
+        }
+    }
+
+    /// Size of the decompressed tile in bytes.
+    pub fn decoded_len(&self) -> usize {
+        self.width as usize * self.height as usize * self.samples_per_pixel as usize
+    }
+
+    /// Wrap compressed tile bytes with this layout for the tile encoder.
+    pub fn wrap(&self, compressed: &[u8]) -> Bytes {
+        let mut out = BytesMut::with_capacity(RAW_TILE_HEADER_SIZE + compressed.len());
+        out.put_slice(&RAW_TILE_MAGIC);
+        out.put_u16_le(self.compression as u16);
+        out.put_u16_le(self.samples_per_pixel);
+        out.put_u16_le(self.predictor);
+        out.put_u16_le(self.photometric);
+        out.put_u32_le(self.width);
+        out.put_u32_le(self.height);
+        out.put_slice(compressed);
+        out.freeze()
+    }
+
+    /// Split a wrapped tile into its layout and compressed payload.
+    ///
+    /// Returns `None` if the data is not a wrapped raw tile.
+    pub fn from_wrapped(data: &[u8]) -> Option<(Self, &[u8])> {
+        if !is_raw_tile(data) {
+            return None;
+        }
+        let u16_at = |at: usize| u16::from_le_bytes([data[at], data[at + 1]]);
+        let u32_at =
+            |at: usize| u32::from_le_bytes([data[at], data[at + 1], data[at + 2], data[at + 3]]);
+
+        let layout = RawTileLayout {
+            compression: Compression::from_u16(u16_at(8))?,
+            samples_per_pixel: u16_at(10),
+            predictor: u16_at(12),
+            photometric: u16_at(14),
+            width: u32_at(16),
+            height: u32_at(20),
+        };
+        Some((layout, &data[RAW_TILE_HEADER_SIZE..]))
+    }
+}
+
+// =============================================================================
+// Decoding
+// =============================================================================
+
+/// Check whether tile data is a wrapped raw tile.
+pub fn is_raw_tile(data: &[u8]) -> bool {
+    data.len() >= RAW_TILE_HEADER_SIZE && data[..8] == RAW_TILE_MAGIC
+}
+
+/// Decompress LZW, Deflate or uncompressed tile data.
+pub fn decompress(compression: Compression, data: &[u8]) -> Result<Vec<u8>, TileError> {
+    match compression {
+        Compression::None => Ok(data.to_vec()),
+        Compression::Lzw => weezl::decode::Decoder::with_tiff_size_switch(weezl::BitOrder::Msb, 8)
+            .decode(data)
+            .map_err(|e| TileError::DecodeError {
+                message: format!("LZW decode error: {}", e),
+            }),
+        Compression::Deflate | Compression::AdobeDeflate => {
+            let mut out = Vec::new();
+            flate2::read::ZlibDecoder::new(data)
+                .read_to_end(&mut out)
+                .map_err(|e| TileError::DecodeError {
+                    message: format!("Deflate decode error: {}", e),
+                })?;
+            Ok(out)
+        }
+        other => Err(TileError::DecodeError {
+            message: format!("{} is not a raw pixel compression", other.name()),
+        }),
+    }
+}
+
+/// Undo horizontal differencing (TIFF predictor 2) in place.
+fn undo_horizontal_predictor(pixels: &mut [u8], width: usize, samples: usize) {
+    let row_len = width * samples;
+    if row_len == 0 {
+        return;
+    }
+    for row in pixels.chunks_exact_mut(row_len) {
+        for i in samples..row_len {
+            row[i] = row[i].wrapping_add(row[i - samples]);
+        }
+    }
+}
+
+/// Decode a wrapped raw tile to an image.
+///
+/// # Errors
+///
+/// Returns [`TileError::DecodeError`] if the data is not a wrapped raw tile,
+/// decompression fails, or the decompressed buffer is smaller than the
+/// layout requires.
+pub fn decode_raw_tile(data: &[u8]) -> Result<DynamicImage, TileError> {
+    let (layout, payload) =
+        RawTileLayout::from_wrapped(data).ok_or_else(|| TileError::DecodeError {
+            message: "Invalid raw tile header".to_string(),
+        })?;
+
+    let mut pixels = decompress(layout.compression, payload)?;
+    let expected = layout.decoded_len();
+    if pixels.len() < expected {
+        return Err(TileError::DecodeError {
+            message: format!(
+                "{} tile decoded to {} bytes, expected {} ({}x{}x{})",
+                layout.compression.name(),
+                pixels.len(),
+                expected,
+                layout.width,
+                layout.height,
+                layout.samples_per_pixel
+            ),
+        });
+    }
+    pixels.truncate(expected);
+
+    let samples = layout.samples_per_pixel as usize;
+    if layout.predictor == PREDICTOR_HORIZONTAL {
+        undo_horizontal_predictor(&mut pixels, layout.width as usize, samples);
+    }
+
+    let image = match samples {
+        1 => {
+            if layout.photometric == PHOTOMETRIC_MIN_IS_WHITE {
+                pixels.iter_mut().for_each(|p| *p = 255 - *p);
+            }
+            GrayImage::from_raw(layout.width, layout.height, pixels).map(DynamicImage::ImageLuma8)
+        }
+        3 => RgbImage::from_raw(layout.width, layout.height, pixels).map(DynamicImage::ImageRgb8),
+        _ => {
+            let rgb: Vec<u8> = pixels
+                .chunks_exact(samples)
+                .flat_map(|p| [p[0], p[1], p[2]])
+                .collect();
+            RgbImage::from_raw(layout.width, layout.height, rgb).map(DynamicImage::ImageRgb8)
+        }
+    };
+
+    image.ok_or_else(|| TileError::DecodeError {
+        message: format!(
+            "Failed to create image from raw tile: {}x{}",
+            layout.width, layout.height
+        ),
+    })
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn deflate(data: &[u8]) -> Vec<u8> {
+        let mut encoder =
+            flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn lzw(data: &[u8]) -> Vec<u8> {
+        weezl::encode::Encoder::with_tiff_size_switch(weezl::BitOrder::Msb, 8)
+            .encode(data)
+            .unwrap()
+    }
+
+    fn layout(compression: Compression, samples: u16, predictor: u16) -> RawTileLayout {
+        RawTileLayout {
+            compression,
+            width: 4,
+            height: 2,
+            samples_per_pixel: samples,
+            predictor,
+            photometric: if samples == 1 {
+                PHOTOMETRIC_MIN_IS_BLACK
+            } else {
+                PHOTOMETRIC_RGB
+            },
+        }
+    }
+
+    fn rgb_pixels() -> Vec<u8> {
+        (0..4 * 2 * 3).map(|i| (i * 10) as u8).collect()
+    }
+
+    #[test]
+    fn test_wrap_roundtrip() {
+        let layout = layout(Compression::Lzw, 3, PREDICTOR_HORIZONTAL);
+        let wrapped = layout.wrap(b"payload");
+
+        assert!(is_raw_tile(&wrapped));
+        let (parsed, payload) = RawTileLayout::from_wrapped(&wrapped).unwrap();
+        assert_eq!(parsed, layout);
+        assert_eq!(payload, b"payload");
+    }
+
+    #[test]
+    fn test_raw_tile_not_confused_with_jpeg() {
+        assert!(!is_raw_tile(&[0xFF, 0xD8, 0xFF, 0xE0]));
+        assert!(!is_raw_tile(&RAW_TILE_MAGIC));
+    }
+
+    #[test]
+    fn test_decode_deflate_rgb() {
+        let pixels = rgb_pixels();
+        let wrapped = layout(Compression::Deflate, 3, PREDICTOR_NONE).wrap(&deflate(&pixels));
+
+        let image = decode_raw_tile(&wrapped).unwrap().into_rgb8();
+        assert_eq!(image.dimensions(), (4, 2));
+        assert_eq!(image.into_raw(), pixels);
+    }
+
+    #[test]
+    fn test_decode_lzw_rgb() {
+        let pixels = rgb_pixels();
+        let wrapped = layout(Compression::Lzw, 3, PREDICTOR_NONE).wrap(&lzw(&pixels));
+
+        let image = decode_raw_tile(&wrapped).unwrap().into_rgb8();
+        assert_eq!(image.into_raw(), pixels);
+    }
+
+    #[test]
+    fn test_decode_horizontal_predictor() {
+        let pixels = rgb_pixels();
+
+        // Apply horizontal differencing per row, as a TIFF writer would
+        let mut differenced = pixels.clone();
+        for row in differenced.chunks_exact_mut(4 * 3) {
+            for i in (3..row.len()).rev() {
+                row[i] = row[i].wrapping_sub(row[i - 3]);
+            }
+        }
+
+        let wrapped =
+            layout(Compression::AdobeDeflate, 3, PREDICTOR_HORIZONTAL).wrap(&deflate(&differenced));
+        let image = decode_raw_tile(&wrapped).unwrap().into_rgb8();
+        assert_eq!(image.into_raw(), pixels);
+    }
+
+    #[test]
+    fn test_decode_rgba_drops_extra_sample() {
+        let rgba: Vec<u8> = (0..4 * 2).flat_map(|i| [i, i, i, 255]).collect();
+        let wrapped = layout(Compression::Deflate, 4, PREDICTOR_NONE).wrap(&deflate(&rgba));
+
+        let image = decode_raw_tile(&wrapped).unwrap().into_rgb8();
+        assert_eq!(image.get_pixel(1, 0).0, [1, 1, 1]);
+    }
+
+    #[test]
+    fn test_decode_min_is_white() {
+        let mut layout = layout(Compression::Deflate, 1, PREDICTOR_NONE);
+        layout.photometric = PHOTOMETRIC_MIN_IS_WHITE;
+        let wrapped = layout.wrap(&deflate(&[0u8; 8]));
+
+        let image = decode_raw_tile(&wrapped).unwrap().into_luma8();
+        assert!(image.pixels().all(|p| p.0 == [255]));
+    }
+
+    #[test]
+    fn test_decode_uncompressed_rgb() {
+        let pixels = rgb_pixels();
+        let wrapped = RawTileLayout::uncompressed_rgb(4, 2).wrap(&pixels);
+
+        let image = decode_raw_tile(&wrapped).unwrap().into_rgb8();
+        assert_eq!(image.into_raw(), pixels);
+    }
+
+    #[test]
+    fn test_decode_short_buffer_fails() {
+        let wrapped = layout(Compression::Deflate, 3, PREDICTOR_NONE).wrap(&deflate(&[0u8; 5]));
+        assert!(matches!(
+            decode_raw_tile(&wrapped),
+            Err(TileError::DecodeError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_decode_corrupt_payload_fails() {
+        let wrapped = layout(Compression::Deflate, 3, PREDICTOR_NONE).wrap(b"not zlib");
+        assert!(decode_raw_tile(&wrapped).is_err());
+    }
+}
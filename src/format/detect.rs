@@ -6,12 +6,25 @@
 //! - **Aperio SVS**: TIFF-based format identified by "Aperio" string in ImageDescription
 //! - **Generic Pyramidal TIFF**: Standard tiled TIFF with multiple resolution levels
 //!
+//! Other formats (Philips iSyntax, multi-file formats such as MIRAX) can be
+//! added without forking by implementing [`FormatPlugin`] and registering it
+//! with [`SlideRegistry::with_format_plugin`](crate::slide::SlideRegistry::with_format_plugin).
+//!
 //! Unsupported formats return an error that should map to HTTP 415 Unsupported Media Type.
 
-use crate::error::FormatError;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+
+use crate::error::{FormatError, IoError, TiffError};
 use crate::io::RangeReader;
+use crate::slide::LevelInfo;
 
-use super::tiff::{ByteOrder, Ifd, TiffHeader, TiffTag, BIGTIFF_HEADER_SIZE, TIFF_HEADER_SIZE};
+use super::tiff::{
+    ByteOrder, Ifd, TiffHeader, TiffLimits, TiffTag, BIGTIFF_HEADER_SIZE, TIFF_HEADER_SIZE,
+};
 
 // =============================================================================
 // SlideFormat
@@ -28,6 +41,9 @@ pub enum SlideFormat {
 
     /// Generic pyramidal TIFF (standard tiled TIFF with multiple resolutions)
     GenericTiff,
+
+    /// Format provided by a registered [`FormatPlugin`], by plugin name
+    Plugin(&'static str),
 }
 
 impl SlideFormat {
@@ -36,6 +52,7 @@ impl SlideFormat {
         match self {
             SlideFormat::AperioSvs => "Aperio SVS",
             SlideFormat::GenericTiff => "Generic Pyramidal TIFF",
+            SlideFormat::Plugin(name) => name,
         }
     }
 }
@@ -110,6 +127,7 @@ async fn detect_format_from_first_ifd<R: RangeReader>(
     } else {
         header.byte_order.read_u16(&count_bytes) as u64
     };
+    TiffLimits::DEFAULT.check_ifd_entries(entry_count)?;
 
     // Read the full IFD
     let ifd_size = Ifd::calculate_size(entry_count, header);
@@ -194,6 +212,108 @@ pub fn is_tiff_header(bytes: &[u8]) -> bool {
     version == 42 || version == 43
 }
 
+// =============================================================================
+// Format Plugins
+// =============================================================================
+
+/// Access to other objects in the slide's storage backend.
+///
+/// Multi-file formats use this to open companion files (index files, data
+/// files) next to the slide named by the request.
+#[async_trait]
+pub trait SlideFiles: Send + Sync {
+    /// Open a range reader for another object in the same storage backend.
+    async fn open(&self, key: &str) -> Result<Arc<dyn RangeReader>, IoError>;
+}
+
+/// An opened slide in a plugin-provided format.
+///
+/// This is the object-safe counterpart of
+/// [`SlideReader`](crate::slide::SlideReader): `read_tile` takes the slide's
+/// primary reader as a trait object. Companion readers opened through
+/// [`SlideFiles`] can be kept in the implementation.
+#[async_trait]
+pub trait PluginSlide: Send + Sync {
+    /// Get the number of pyramid levels (level 0 = full resolution).
+    fn level_count(&self) -> usize;
+
+    /// Get complete information about a level.
+    ///
+    /// Returns `None` if level is out of range.
+    fn level_info(&self, level: usize) -> Option<LevelInfo>;
+
+    /// Get the level 0 resolution in microns per pixel, if known.
+    fn mpp(&self) -> Option<f64> {
+        None
+    }
+
+    /// Get format-specific slide properties.
+    ///
+    /// These are added to the standard `openslide.*` properties, which
+    /// they may override (e.g. `openslide.objective-power`).
+    fn properties(&self) -> BTreeMap<String, String> {
+        BTreeMap::new()
+    }
+
+    /// Find the level with the smallest downsample that is at least the
+    /// requested factor, falling back to the lowest resolution.
+    fn best_level_for_downsample(&self, downsample: f64) -> Option<usize> {
+        (0..self.level_count())
+            .filter_map(|level| Some((level, self.level_info(level)?.downsample)))
+            .filter(|(_, ds)| *ds >= downsample * 0.99)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(level, _)| level)
+            .or_else(|| self.level_count().checked_sub(1))
+    }
+
+    /// Read a tile.
+    ///
+    /// Returns a complete JPEG or JPEG 2000 codestream, or raw pixels
+    /// wrapped with [`RawTileLayout::wrap`](super::codec::RawTileLayout::wrap).
+    async fn read_tile(
+        &self,
+        reader: &dyn RangeReader,
+        level: usize,
+        tile_x: u32,
+        tile_y: u32,
+    ) -> Result<Bytes, TiffError>;
+}
+
+/// A slide format provided outside this crate.
+///
+/// Registered plugins are tried in registration order before the built-in
+/// TIFF detection, so a plugin can also take over TIFF variants.
+///
+/// # Example
+///
+/// ```ignore
+/// use std::sync::Arc;
+/// use wsi_streamer::format::FormatPlugin;
+/// use wsi_streamer::slide::SlideRegistry;
+///
+/// let registry = SlideRegistry::new(source)
+///     .with_format_plugin(Arc::new(ISyntaxPlugin::default()));
+/// ```
+#[async_trait]
+pub trait FormatPlugin: Send + Sync {
+    /// Human-readable format name (e.g., "Philips iSyntax").
+    fn name(&self) -> &'static str;
+
+    /// Whether this plugin handles the slide.
+    ///
+    /// Implementations should decide from the slide ID and a few header
+    /// bytes; reads go through the slide's block cache.
+    async fn detect(&self, slide_id: &str, reader: &dyn RangeReader) -> Result<bool, FormatError>;
+
+    /// Open a slide this plugin detected.
+    async fn open(
+        &self,
+        slide_id: &str,
+        reader: &dyn RangeReader,
+        files: &dyn SlideFiles,
+    ) -> Result<Box<dyn PluginSlide>, FormatError>;
+}
+
 // =============================================================================
 // Tests
 // =============================================================================
@@ -337,5 +457,9 @@ mod tests {
     fn test_slide_format_name() {
         assert_eq!(SlideFormat::AperioSvs.name(), "Aperio SVS");
         assert_eq!(SlideFormat::GenericTiff.name(), "Generic Pyramidal TIFF");
+        assert_eq!(
+            SlideFormat::Plugin("Philips iSyntax").name(),
+            "Philips iSyntax"
+        );
     }
 }
@@ -1,35 +1,51 @@
 //! Generic pyramidal TIFF reader.
 //!
 //! This module provides support for reading standard pyramidal TIFF files
-//! that use tiled organization with JPEG compression.
+//! that use tiled organization with JPEG, JPEG 2000, LZW or Deflate compression.
 //!
 //! # Supported Files
 //!
 //! This reader supports TIFF files that:
-//! - Use tiled organization (not strips)
-//! - Use JPEG or JPEG 2000 compression (compression tag = 7 or 33003)
+//! - Use tiled organization, or JPEG strips of at most
+//!   [`MAX_STRIP_PIXELS`](super::tiff::MAX_STRIP_PIXELS) each
+//! - Use JPEG, JPEG 2000, LZW or Deflate compression (compression tag = 7,
+//!   33003, 5, 8 or 32946)
 //! - Have multiple resolution levels (pyramid structure)
 //!
+//! Strip-organized levels are served as a grid of 256x256 tiles by a
+//! [`StripReader`], which decodes each strip once and crops tiles from it.
+//!
 //! # Unsupported Files
 //!
 //! Files that don't meet these requirements return an error that can be
 //! mapped to HTTP 415 Unsupported Media Type:
-//! - Strip-based TIFFs
-//! - Non-JPEG/JPEG 2000 compression (LZW, Deflate, etc.)
+//! - TIFFs with only larger or non-JPEG strips
+//! - Other compressions (old-style JPEG, uncompressed, etc.), or LZW/Deflate
+//!   with a pixel layout [`super::codec`] can't decode
 //! - Single-level TIFFs without pyramid structure
 
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
 use async_trait::async_trait;
 use bytes::Bytes;
+use image::{imageops, DynamicImage, ImageFormat, Rgb, RgbImage};
+use lru::LruCache;
 
 use crate::error::TiffError;
 use crate::io::RangeReader;
 use crate::slide::SlideReader;
 
+use super::codec::RawTileLayout;
 use super::jpeg::prepare_tile_jpeg;
 use super::tiff::{
-    validate_pyramid, PyramidLevel, TiffHeader, TiffPyramid, TileData, ValidationResult,
+    validate_pyramid, PyramidDetectOptions, PyramidLevel, TiffHeader, TiffMetadata, TiffPyramid,
+    TileData, ValidationResult,
 };
 
+/// Budget of decoded strips cached per reader, in bytes of RGB pixels.
+pub const STRIP_CACHE_BYTES: usize = 128 * 1024 * 1024;
+
 // =============================================================================
 // Generic TIFF Level Data
 // =============================================================================
@@ -46,7 +62,13 @@ pub struct GenericTiffLevelData {
 
 impl GenericTiffLevelData {
     /// Get the offset and size for a specific tile.
+    ///
+    /// Returns None for strip-organized levels, whose tiles aren't stored
+    /// in the file.
     pub fn get_tile_location(&self, tile_x: u32, tile_y: u32) -> Option<(u64, u64)> {
+        if self.level.is_stripped() {
+            return None;
+        }
         let tile_index = self.level.tile_index(tile_x, tile_y)?;
         self.tile_data.get_tile_location(tile_index)
     }
@@ -73,8 +95,14 @@ pub struct GenericTiffReader {
     /// Level data including tile offsets and optional JPEGTables
     levels: Vec<GenericTiffLevelData>,
 
+    /// Descriptive tags of the full-resolution level
+    tiff_metadata: TiffMetadata,
+
     /// Validation warnings (non-fatal issues)
     warnings: Vec<String>,
+
+    /// Decoded strips of strip-organized levels
+    strips: StripReader,
 }
 
 impl GenericTiffReader {
@@ -88,11 +116,21 @@ impl GenericTiffReader {
     /// Returns an error if:
     /// - The file is not a valid TIFF
     /// - The file uses strip organization (not tiles)
-    /// - The file uses unsupported compression (not JPEG)
+    /// - The file uses unsupported compression
     /// - No pyramid levels are found
     pub async fn open<R: RangeReader>(reader: &R) -> Result<Self, TiffError> {
+        Self::open_with_options(reader, &PyramidDetectOptions::default()).await
+    }
+
+    /// Open a generic pyramidal TIFF file, identifying pyramid levels with
+    /// the given heuristics.
+    pub async fn open_with_options<R: RangeReader>(
+        reader: &R,
+        options: &PyramidDetectOptions,
+    ) -> Result<Self, TiffError> {
         // Parse the TIFF pyramid structure
-        let pyramid = TiffPyramid::parse(reader).await?;
+        let options = options.with_stripped_levels(true);
+        let pyramid = TiffPyramid::parse_with_options(reader, &options).await?;
 
         // Validate the pyramid meets our requirements
         let validation = validate_pyramid(&pyramid);
@@ -106,17 +144,22 @@ impl GenericTiffReader {
         // Load tile data for each pyramid level
         let mut levels = Vec::with_capacity(pyramid.levels.len());
         for level in &pyramid.levels {
-            let tile_data = TileData::load(reader, level, &pyramid.header).await?;
+            let tile_data =
+                TileData::load_with_limits(reader, level, &pyramid.header, &options.limits).await?;
             levels.push(GenericTiffLevelData {
                 level: level.clone(),
                 tile_data,
             });
         }
 
+        let tiff_metadata = TiffMetadata::read_pyramid(reader, &pyramid).await;
+
         Ok(GenericTiffReader {
             pyramid,
             levels,
+            tiff_metadata,
             warnings,
+            strips: StripReader::new(),
         })
     }
 
@@ -128,7 +171,8 @@ impl GenericTiffReader {
         reader: &R,
     ) -> Result<(Self, ValidationResult), TiffError> {
         // Parse the TIFF pyramid structure
-        let pyramid = TiffPyramid::parse(reader).await?;
+        let options = PyramidDetectOptions::default().with_stripped_levels(true);
+        let pyramid = TiffPyramid::parse_with_options(reader, &options).await?;
 
         // Validate the pyramid
         let validation = validate_pyramid(&pyramid);
@@ -139,17 +183,22 @@ impl GenericTiffReader {
         // Load tile data for each pyramid level
         let mut levels = Vec::with_capacity(pyramid.levels.len());
         for level in &pyramid.levels {
-            let tile_data = TileData::load(reader, level, &pyramid.header).await?;
+            let tile_data =
+                TileData::load_with_limits(reader, level, &pyramid.header, &options.limits).await?;
             levels.push(GenericTiffLevelData {
                 level: level.clone(),
                 tile_data,
             });
         }
 
+        let tiff_metadata = TiffMetadata::read_pyramid(reader, &pyramid).await;
+
         let reader = GenericTiffReader {
             pyramid,
             levels,
+            tiff_metadata,
             warnings: validation.warnings.clone(),
+            strips: StripReader::new(),
         };
 
         Ok((reader, validation))
@@ -167,6 +216,11 @@ impl GenericTiffReader {
         &self.warnings
     }
 
+    /// Get the descriptive TIFF tags of the full-resolution level.
+    pub fn tiff_metadata(&self) -> &TiffMetadata {
+        &self.tiff_metadata
+    }
+
     /// Get the number of pyramid levels.
     pub fn level_count(&self) -> usize {
         self.levels.len()
@@ -211,6 +265,7 @@ impl GenericTiffReader {
     /// Read raw tile data from the file.
     ///
     /// This reads the raw bytes from the file without any processing.
+    /// Strip-organized levels have no raw tiles and return an error.
     pub async fn read_raw_tile<R: RangeReader>(
         &self,
         reader: &R,
@@ -234,14 +289,25 @@ impl GenericTiffReader {
                     ),
                 })?;
 
+        // Sparse TIFFs leave unwritten tiles at offset or size 0
+        if offset == 0 || size == 0 {
+            return Err(TiffError::SparseTile {
+                level,
+                tile_x,
+                tile_y,
+            });
+        }
+
         let data = reader.read_exact_at(offset, size as usize).await?;
         Ok(data)
     }
 
-    /// Read a tile and prepare it for JPEG decoding.
+    /// Read a tile and prepare it for decoding.
     ///
     /// This reads the tile data and merges it with JPEGTables if the tile
     /// contains an abbreviated JPEG stream (rare for generic TIFF but handled).
+    /// LZW and Deflate tiles are wrapped with their pixel layout (see
+    /// [`super::codec`]).
     ///
     /// # Arguments
     /// * `reader` - Range reader for the file
@@ -250,7 +316,8 @@ impl GenericTiffReader {
     /// * `tile_y` - Tile Y coordinate
     ///
     /// # Returns
-    /// Complete JPEG data ready for decoding.
+    /// Complete JPEG, JPEG 2000 or wrapped raw tile data ready for decoding.
+    /// Tiles of strip-organized levels are wrapped uncompressed RGB pixels.
     pub async fn read_tile<R: RangeReader>(
         &self,
         reader: &R,
@@ -258,15 +325,29 @@ impl GenericTiffReader {
         tile_x: u32,
         tile_y: u32,
     ) -> Result<Bytes, TiffError> {
-        // Read raw tile data
-        let raw_data = self.read_raw_tile(reader, level, tile_x, tile_y).await?;
-
-        // Get JPEGTables for this level (may not be present in generic TIFF)
         let level_data = self.levels.get(level).ok_or(TiffError::InvalidTagValue {
             tag: "level",
             message: format!("level {} out of range", level),
         })?;
 
+        // Tiles of strip-organized levels are cut from the decoded strips
+        if level_data.level.is_stripped() {
+            return self
+                .strips
+                .read_tile(reader, level, level_data, tile_x, tile_y)
+                .await;
+        }
+
+        // Read raw tile data
+        let raw_data = self.read_raw_tile(reader, level, tile_x, tile_y).await?;
+
+        // LZW/Deflate tiles are wrapped with their pixel layout for decoding
+        if let Some(layout) =
+            RawTileLayout::from_level(&level_data.level, self.header().byte_order)?
+        {
+            return Ok(layout.wrap(&raw_data));
+        }
+
         let tables = level_data.jpeg_tables();
 
         // Prepare the JPEG data (merge tables if needed)
@@ -285,6 +366,193 @@ impl GenericTiffReader {
     }
 }
 
+// =============================================================================
+// Strip Reader
+// =============================================================================
+
+/// Cuts tiles out of strip-organized levels.
+///
+/// Each strip is decoded whole and kept in an LRU cache bounded by
+/// [`STRIP_CACHE_BYTES`], so the other tiles of a strip row are cropped
+/// without reading or decoding it again.
+pub struct StripReader {
+    cache: Mutex<StripCache>,
+}
+
+/// Decoded strips, keyed by `(level, strip index)`.
+struct StripCache {
+    strips: LruCache<(usize, u32), Arc<RgbImage>>,
+
+    /// Total size of the cached strips in bytes
+    bytes: usize,
+}
+
+impl StripCache {
+    fn insert(&mut self, key: (usize, u32), pixels: Arc<RgbImage>) {
+        let size = pixels.as_raw().len();
+        if size > STRIP_CACHE_BYTES {
+            return;
+        }
+        if let Some(previous) = self.strips.put(key, pixels) {
+            self.bytes -= previous.as_raw().len();
+        }
+        self.bytes += size;
+        while self.bytes > STRIP_CACHE_BYTES {
+            match self.strips.pop_lru() {
+                Some((_, evicted)) => self.bytes -= evicted.as_raw().len(),
+                None => break,
+            }
+        }
+    }
+}
+
+impl StripReader {
+    /// Create a reader with an empty strip cache.
+    pub fn new() -> Self {
+        StripReader {
+            cache: Mutex::new(StripCache {
+                strips: LruCache::unbounded(),
+                bytes: 0,
+            }),
+        }
+    }
+
+    /// Get the number of bytes of decoded strips currently cached.
+    pub fn cached_bytes(&self) -> usize {
+        self.lock().bytes
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, StripCache> {
+        self.cache.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Read a tile of a strip-organized level.
+    ///
+    /// Returns the tile as uncompressed RGB pixels wrapped for the tile
+    /// encoder (see [`super::codec`]). Edge tiles are padded with white to
+    /// the full tile size, like the padded edge tiles of tiled levels.
+    pub async fn read_tile<R: RangeReader>(
+        &self,
+        reader: &R,
+        level: usize,
+        level_data: &GenericTiffLevelData,
+        tile_x: u32,
+        tile_y: u32,
+    ) -> Result<Bytes, TiffError> {
+        let info = &level_data.level;
+        let strip_rows = info.strip_rows.ok_or(TiffError::InvalidTagValue {
+            tag: "RowsPerStrip",
+            message: format!("level {} is not strip-organized", level),
+        })?;
+        let (width, height) =
+            info.tile_dimensions(tile_x, tile_y)
+                .ok_or(TiffError::InvalidTagValue {
+                    tag: "tile",
+                    message: format!(
+                        "tile ({}, {}) out of range for level {}",
+                        tile_x, tile_y, level
+                    ),
+                })?;
+        let x = tile_x * info.tile_width;
+        let y = tile_y * info.tile_height;
+
+        let mut tile = RgbImage::from_pixel(info.tile_width, info.tile_height, Rgb([255; 3]));
+        for strip in y / strip_rows..=(y + height - 1) / strip_rows {
+            let Some(pixels) = self.strip(reader, level, level_data, strip).await? else {
+                return Err(TiffError::SparseTile {
+                    level,
+                    tile_x,
+                    tile_y,
+                });
+            };
+
+            // Rows of the strip inside the tile, clipped to what decoded
+            let strip_y = strip * strip_rows;
+            let top = y.max(strip_y);
+            let bottom = (y + height).min(strip_y + pixels.height());
+            if bottom <= top || x >= pixels.width() {
+                continue;
+            }
+            let rows = imageops::crop_imm(
+                pixels.as_ref(),
+                x,
+                top - strip_y,
+                width.min(pixels.width() - x),
+                bottom - top,
+            )
+            .to_image();
+            imageops::replace(&mut tile, &rows, 0, (top - y) as i64);
+        }
+
+        let layout = RawTileLayout::uncompressed_rgb(tile.width(), tile.height());
+        Ok(layout.wrap(tile.as_raw()))
+    }
+
+    /// Get a decoded strip, reading and decoding it on a cache miss.
+    ///
+    /// Returns None for strips missing from a sparse TIFF.
+    async fn strip<R: RangeReader>(
+        &self,
+        reader: &R,
+        level: usize,
+        level_data: &GenericTiffLevelData,
+        strip: u32,
+    ) -> Result<Option<Arc<RgbImage>>, TiffError> {
+        let key = (level, strip);
+        if let Some(pixels) = self.lock().strips.get(&key) {
+            return Ok(Some(Arc::clone(pixels)));
+        }
+
+        let (offset, size) =
+            level_data
+                .tile_data
+                .get_tile_location(strip)
+                .ok_or(TiffError::InvalidTagValue {
+                    tag: "StripOffsets",
+                    message: format!("strip {} out of range for level {}", strip, level),
+                })?;
+        if offset == 0 || size == 0 {
+            return Ok(None);
+        }
+        let data = reader.read_exact_at(offset, size as usize).await?;
+        let jpeg = prepare_tile_jpeg(level_data.jpeg_tables().map(|t| t.as_ref()), &data);
+
+        let decoded = tokio::task::spawn_blocking(move || {
+            image::load_from_memory_with_format(&jpeg, ImageFormat::Jpeg)
+                .map(DynamicImage::into_rgb8)
+                .map_err(|e| e.to_string())
+        })
+        .await
+        .unwrap_or_else(|e| Err(e.to_string()));
+        let pixels = Arc::new(decoded.map_err(|message| TiffError::InvalidTagValue {
+            tag: "StripOffsets",
+            message: format!(
+                "strip {} of level {} failed to decode: {}",
+                strip, level, message
+            ),
+        })?);
+
+        self.lock().insert(key, Arc::clone(&pixels));
+        Ok(Some(pixels))
+    }
+}
+
+impl Default for StripReader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for StripReader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let cache = self.lock();
+        f.debug_struct("StripReader")
+            .field("strips", &cache.strips.len())
+            .field("bytes", &cache.bytes)
+            .finish()
+    }
+}
+
 // =============================================================================
 // SlideReader Implementation
 // =============================================================================
@@ -344,8 +612,9 @@ impl SlideReader for GenericTiffReader {
 mod tests {
     use super::*;
     use crate::error::IoError;
+    use crate::format::codec::decode_raw_tile;
     use crate::format::tiff::{FieldType, Ifd, IfdEntry, TiffTag};
-    use crate::io::RangeReader;
+    use crate::io::{MemoryRangeReader, RangeReader};
     use async_trait::async_trait;
     use std::collections::HashMap;
 
@@ -450,6 +719,7 @@ mod tests {
                 is_inline: false,
             }),
             jpeg_tables_entry: None,
+            strip_rows: None,
         };
 
         let tile_data = TileData {
@@ -482,6 +752,96 @@ mod tests {
         assert_eq!(level_data.get_tile_location(0, 10), None);
     }
 
+    /// 600x300 JPEG TIFF in strips of 128 rows: red, green, then blue.
+    fn stripped_tiff() -> Vec<u8> {
+        let strips: Vec<Vec<u8>> = [([255, 0, 0], 128), ([0, 255, 0], 128), ([0, 0, 255], 44)]
+            .into_iter()
+            .map(|(color, rows)| {
+                let strip = RgbImage::from_pixel(600, rows, Rgb(color));
+                let mut jpeg = Vec::new();
+                image::codecs::jpeg::JpegEncoder::new_with_quality(&mut jpeg, 95)
+                    .encode_image(&strip)
+                    .unwrap();
+                jpeg
+            })
+            .collect();
+
+        let entries: [(u16, u16, u32, u32); 7] = [
+            (256, 3, 1, 600), // ImageWidth
+            (257, 3, 1, 300), // ImageLength
+            (259, 3, 1, 7),   // Compression
+            (262, 3, 1, 2),   // PhotometricInterpretation
+            (273, 4, 3, 200), // StripOffsets (offset)
+            (278, 3, 1, 128), // RowsPerStrip
+            (279, 4, 3, 212), // StripByteCounts (offset)
+        ];
+        let mut data = b"II*\0".to_vec();
+        data.extend_from_slice(&8u32.to_le_bytes());
+        data.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+        for (tag, field_type, count, value) in entries {
+            data.extend_from_slice(&tag.to_le_bytes());
+            data.extend_from_slice(&field_type.to_le_bytes());
+            data.extend_from_slice(&count.to_le_bytes());
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data.resize(200, 0);
+
+        let mut offset = 224;
+        for strip in &strips {
+            data.extend_from_slice(&(offset as u32).to_le_bytes());
+            offset += strip.len();
+        }
+        for strip in &strips {
+            data.extend_from_slice(&(strip.len() as u32).to_le_bytes());
+        }
+        for strip in &strips {
+            data.extend_from_slice(strip);
+        }
+        data
+    }
+
+    fn assert_color(tile: &RgbImage, x: u32, y: u32, expected: [u8; 3]) {
+        let pixel = tile.get_pixel(x, y).0;
+        let close = pixel.iter().zip(expected).all(|(&a, b)| a.abs_diff(b) <= 8);
+        assert!(
+            close,
+            "pixel ({}, {}) is {:?}, expected {:?}",
+            x, y, pixel, expected
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stripped_level_is_retiled() {
+        let reader = MemoryRangeReader::new("stripped.tif", Bytes::from(stripped_tiff()));
+        let tiff = GenericTiffReader::open(&reader).await.unwrap();
+
+        assert_eq!(tiff.level_count(), 1);
+        assert_eq!(tiff.tile_size(0), Some((256, 256)));
+        assert_eq!(tiff.tile_count(0), Some((3, 2)));
+        assert_eq!(tiff.get_level(0).unwrap().get_tile_location(0, 0), None);
+
+        // The first tile spans the red and green strips
+        let tile = tiff.read_tile(&reader, 0, 0, 0).await.unwrap();
+        let tile = decode_raw_tile(&tile).unwrap().into_rgb8();
+        assert_eq!(tile.dimensions(), (256, 256));
+        assert_color(&tile, 10, 10, [255, 0, 0]);
+        assert_color(&tile, 10, 200, [0, 255, 0]);
+        let cached = tiff.strips.cached_bytes();
+        assert_eq!(cached, 2 * 600 * 128 * 3);
+
+        // The corner tile is padded with white past the image edge
+        let tile = tiff.read_tile(&reader, 0, 2, 1).await.unwrap();
+        let tile = decode_raw_tile(&tile).unwrap().into_rgb8();
+        assert_color(&tile, 10, 10, [0, 0, 255]);
+        assert_color(&tile, 100, 10, [255, 255, 255]);
+        assert_color(&tile, 10, 100, [255, 255, 255]);
+
+        // Neighbouring tiles reuse the decoded strips
+        tiff.read_tile(&reader, 0, 1, 0).await.unwrap();
+        assert_eq!(tiff.strips.cached_bytes(), cached + 600 * 44 * 3);
+    }
+
     #[test]
     fn test_jpeg_tables_none() {
         let level_data = make_mock_level();
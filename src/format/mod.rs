@@ -11,19 +11,26 @@
 //! - **Aperio SVS**: Identified by "Aperio" marker in ImageDescription
 //! - **Generic Pyramidal TIFF**: Standard tiled TIFF with pyramid structure
 //!
+//! Further formats can be registered at runtime through [`detect::FormatPlugin`].
+//!
 //! # Reading Slides
 //!
 //! - Use [`svs::SvsReader`] for Aperio SVS files
 //! - Use [`generic_tiff::GenericTiffReader`] for standard pyramidal TIFF files
 //! - Both readers handle JPEGTables merging automatically when needed
+//! - LZW and Deflate tiles are decoded to raw pixels by [`codec`]
 
+pub mod codec;
 pub mod detect;
 pub mod generic_tiff;
 pub mod jpeg;
 pub mod svs;
 pub mod tiff;
 
-pub use detect::{detect_format, is_tiff_header, SlideFormat};
-pub use generic_tiff::{GenericTiffLevelData, GenericTiffReader};
+pub use codec::{decode_raw_tile, is_raw_tile, RawTileLayout};
+pub use detect::{
+    detect_format, is_tiff_header, FormatPlugin, PluginSlide, SlideFiles, SlideFormat,
+};
+pub use generic_tiff::{GenericTiffLevelData, GenericTiffReader, StripReader};
 pub use jpeg::{is_abbreviated_stream, is_complete_stream, merge_jpeg_tables, prepare_tile_jpeg};
 pub use svs::{SvsLevelData, SvsMetadata, SvsReader};
@@ -24,6 +24,7 @@
 //! - Microns per pixel (MPP)
 //! - Objective magnification
 //! - Scanner information
+//! - Acquisition date, time and stage position
 
 use async_trait::async_trait;
 use bytes::Bytes;
@@ -33,9 +34,11 @@ use crate::error::TiffError;
 use crate::io::RangeReader;
 use crate::slide::SlideReader;
 
+use super::codec::RawTileLayout;
 use super::jpeg::prepare_tile_jpeg;
 use super::tiff::{
-    validate_pyramid, PyramidLevel, TiffHeader, TiffPyramid, TiffTag, TileData, ValueReader,
+    validate_pyramid, PyramidDetectOptions, PyramidLevel, TiffHeader, TiffMetadata, TiffPyramid,
+    TileData,
 };
 
 // =============================================================================
@@ -44,8 +47,17 @@ use super::tiff::{
 
 /// Parsed metadata from an SVS file.
 ///
-/// SVS files store metadata in the ImageDescription tag as a pipe-separated
-/// string with key=value pairs.
+/// SVS files store metadata in the ImageDescription tag: a header line
+/// naming the Aperio library, then pipe-separated `key = value` pairs:
+///
+/// ```text
+/// Aperio Image Library v10.0.50
+/// 16000x17597 [0,100 15374x17497] (256x256) JPEG/RGB Q=30|AppMag = 20|MPP = 0.4990|...
+/// ```
+///
+/// Well-known keys are parsed into typed fields, converted to the units
+/// named in their documentation; every pair is also kept verbatim in
+/// `properties`.
 #[derive(Debug, Clone, Default)]
 pub struct SvsMetadata {
     /// Microns per pixel (resolution)
@@ -60,6 +72,69 @@ pub struct SvsMetadata {
     /// Full ImageDescription string
     pub image_description: Option<String>,
 
+    /// Aperio Image Library version (e.g., "v10.0.50")
+    pub library_version: Option<String>,
+
+    /// Tile compression and color space from the header (e.g., "JPEG/RGB")
+    pub compression: Option<String>,
+
+    /// JPEG quality from the header (`Q=30`)
+    pub jpeg_quality: Option<u8>,
+
+    /// Compression quality recorded by newer scanners (`Compression Quality`)
+    pub compression_quality: Option<u8>,
+
+    /// Full-resolution width as scanned (`OriginalWidth`)
+    pub original_width: Option<u32>,
+
+    /// Full-resolution height as scanned (`OriginalHeight`)
+    pub original_height: Option<u32>,
+
+    /// Width of the scan stripes in pixels (`StripeWidth`)
+    pub stripe_width: Option<u32>,
+
+    /// Scanner identifier (`ScanScope ID`)
+    pub scanner_id: Option<String>,
+
+    /// Name of the file as written by the scanner (`Filename`)
+    pub filename: Option<String>,
+
+    /// Scanner-assigned image identifier (`ImageID`)
+    pub image_id: Option<String>,
+
+    /// Acquisition date as recorded (`Date`, MM/DD/YY)
+    pub date: Option<String>,
+
+    /// Acquisition time as recorded (`Time`, HH:MM:SS)
+    pub time: Option<String>,
+
+    /// Time zone of the acquisition time (`Time Zone`, e.g., "GMT-0800")
+    pub time_zone: Option<String>,
+
+    /// Operator who ran the scan (`User`)
+    pub user: Option<String>,
+
+    /// Image filter level applied by the scanner (`Filtered`)
+    pub filtered: Option<u32>,
+
+    /// Scan parameter set (`Parmset`)
+    pub parmset: Option<String>,
+
+    /// Stage position of the image's left edge, in millimeters (`Left`)
+    pub left_mm: Option<f64>,
+
+    /// Stage position of the image's top edge, in millimeters (`Top`)
+    pub top_mm: Option<f64>,
+
+    /// Focus offset, in millimeters (`Focus Offset`)
+    pub focus_offset_mm: Option<f64>,
+
+    /// Camera exposure time, in microseconds (`Exposure Time`)
+    pub exposure_time_us: Option<f64>,
+
+    /// Name of the embedded ICC profile (`ICC Profile`)
+    pub icc_profile: Option<String>,
+
     /// Additional key-value pairs from ImageDescription
     pub properties: HashMap<String, String>,
 }
@@ -74,7 +149,9 @@ impl SvsMetadata {
     /// ```
     ///
     /// The first line identifies the format, subsequent parts are pipe-separated
-    /// with key=value pairs.
+    /// with key=value pairs. Keys are matched case-insensitively, since
+    /// scanner versions disagree on spelling (`Originalheight`). Values that
+    /// fail to parse, or carry an unknown unit, leave their typed field unset.
     pub fn parse(description: &str) -> Self {
         let mut metadata = SvsMetadata {
             image_description: Some(description.to_string()),
@@ -86,39 +163,158 @@ impl SvsMetadata {
             metadata.vendor = Some("Aperio".to_string());
         }
 
-        // Parse pipe-separated key=value pairs
-        for part in description.split('|') {
-            let part = part.trim();
+        let mut parts = description.split('|').map(str::trim).peekable();
+
+        // The header isn't a key=value pair, although it contains `Q=..`
+        if let Some(header) = parts.next_if(|part| part.starts_with("Aperio")) {
+            metadata.parse_header(header);
+        }
 
+        // Parse pipe-separated key=value pairs
+        for part in parts {
             // Try to parse as key=value
-            if let Some(eq_pos) = part.find('=') {
-                let key = part[..eq_pos].trim();
-                let value = part[eq_pos + 1..].trim();
+            if let Some((key, value)) = part.split_once('=') {
+                let key = key.trim();
+                let value = value.trim();
 
                 // Store in properties
                 metadata
                     .properties
                     .insert(key.to_string(), value.to_string());
 
-                // Parse known keys
-                match key {
-                    "MPP" => {
-                        if let Ok(mpp) = value.parse::<f64>() {
-                            metadata.mpp = Some(mpp);
-                        }
-                    }
-                    "AppMag" => {
-                        if let Ok(mag) = value.parse::<f64>() {
-                            metadata.magnification = Some(mag);
-                        }
-                    }
-                    _ => {}
-                }
+                metadata.parse_field(key, value);
             }
         }
 
         metadata
     }
+
+    /// Parse the header: library version, then the image summary line.
+    fn parse_header(&mut self, header: &str) {
+        let mut lines = header.lines();
+        self.library_version = lines
+            .next()
+            .and_then(|line| line.split_whitespace().find(|word| word.starts_with('v')))
+            .map(str::to_string);
+
+        for word in lines.flat_map(str::split_whitespace) {
+            if let Some(quality) = word.strip_prefix("Q=") {
+                self.jpeg_quality = quality.parse().ok();
+            } else if word.contains('/') {
+                self.compression = Some(word.to_string());
+            }
+        }
+    }
+
+    /// Parse a known key into its typed field.
+    fn parse_field(&mut self, key: &str, value: &str) {
+        let text = || (!value.is_empty()).then(|| value.to_string());
+        match key.to_ascii_lowercase().as_str() {
+            "mpp" => self.mpp = parse_measure(value, MICRONS),
+            "appmag" => self.magnification = parse_measure(value, MAGNIFICATION),
+            "compression quality" => self.compression_quality = value.parse().ok(),
+            "originalwidth" => self.original_width = value.parse().ok(),
+            "originalheight" => self.original_height = value.parse().ok(),
+            "stripewidth" => self.stripe_width = value.parse().ok(),
+            "scanscope id" => self.scanner_id = text(),
+            "filename" => self.filename = text(),
+            "imageid" => self.image_id = text(),
+            "date" => self.date = text(),
+            "time" => self.time = text(),
+            "time zone" => self.time_zone = text(),
+            "user" => self.user = text(),
+            "filtered" => self.filtered = value.parse().ok(),
+            "parmset" => self.parmset = text(),
+            "left" => self.left_mm = parse_measure(value, MILLIMETERS),
+            "top" => self.top_mm = parse_measure(value, MILLIMETERS),
+            "focus offset" => self.focus_offset_mm = parse_measure(value, MILLIMETERS),
+            "exposure time" => self.exposure_time_us = parse_measure(value, MICROSECONDS),
+            "icc profile" => self.icc_profile = text(),
+            _ => {}
+        }
+    }
+
+    /// Get the acquisition date and time in ISO 8601 form
+    /// (`2009-12-29T09:59:15-08:00`), if the slide records them.
+    ///
+    /// Aperio writes two-digit years, which are taken to be 20xx. The
+    /// offset is omitted when `Time Zone` is absent or not a GMT offset.
+    pub fn acquired_at(&self) -> Option<String> {
+        let mut date = self.date.as_deref()?.split('/');
+        let month: u8 = date.next()?.trim().parse().ok()?;
+        let day: u8 = date.next()?.trim().parse().ok()?;
+        let year: u16 = date.next()?.trim().parse().ok()?;
+        if date.next().is_some() || !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+            return None;
+        }
+        let year = if year < 100 { 2000 + year } else { year };
+
+        let mut time = self.time.as_deref()?.split(':');
+        let hour: u8 = time.next()?.trim().parse().ok()?;
+        let minute: u8 = time.next()?.trim().parse().ok()?;
+        let second: u8 = time.next().map_or(Some(0), |s| s.trim().parse().ok())?;
+        if hour > 23 || minute > 59 || second > 60 {
+            return None;
+        }
+
+        let offset = self
+            .time_zone
+            .as_deref()
+            .and_then(parse_gmt_offset)
+            .unwrap_or_default();
+        Some(format!(
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}{}",
+            year, month, day, hour, minute, second, offset
+        ))
+    }
+}
+
+/// Units accepted for resolutions, with their size in microns.
+const MICRONS: &[(&str, f64)] = &[
+    ("", 1.0),
+    ("um", 1.0),
+    ("µm", 1.0),
+    ("μm", 1.0),
+    ("microns", 1.0),
+    ("nm", 0.001),
+];
+
+/// Units accepted for stage positions, with their size in millimeters.
+const MILLIMETERS: &[(&str, f64)] = &[("", 1.0), ("mm", 1.0), ("um", 0.001), ("µm", 0.001)];
+
+/// Units accepted for exposure times, with their size in microseconds.
+const MICROSECONDS: &[(&str, f64)] = &[("", 1.0), ("us", 1.0), ("µs", 1.0), ("ms", 1000.0)];
+
+/// Suffixes accepted for magnifications (`40`, `40x`).
+const MAGNIFICATION: &[(&str, f64)] = &[("", 1.0), ("x", 1.0), ("X", 1.0)];
+
+/// Parse a number with an optional unit suffix, converting it with the
+/// factor of the matching unit.
+fn parse_measure(value: &str, units: &[(&str, f64)]) -> Option<f64> {
+    let value = value.trim();
+    let number = value.trim_end_matches(|c: char| c.is_alphabetic());
+    let unit = &value[number.len()..];
+    let number: f64 = number.trim().parse().ok()?;
+    let (_, factor) = units.iter().find(|(name, _)| *name == unit)?;
+    Some(number * factor).filter(|n| n.is_finite())
+}
+
+/// Convert an Aperio time zone (`GMT-0800`, `GMT+05:30`, `GMT`) to an
+/// ISO 8601 offset.
+fn parse_gmt_offset(zone: &str) -> Option<String> {
+    let offset = zone.trim().strip_prefix("GMT")?;
+    if offset.is_empty() {
+        return Some("Z".to_string());
+    }
+    let (sign, digits) = offset.split_at(1);
+    if sign != "+" && sign != "-" {
+        return None;
+    }
+    let digits: String = digits.chars().filter(|c| *c != ':').collect();
+    if digits.len() != 4 || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    Some(format!("{}{}:{}", sign, &digits[..2], &digits[2..]))
 }
 
 // =============================================================================
@@ -169,6 +365,12 @@ pub struct SvsReader {
 
     /// Parsed SVS metadata
     metadata: SvsMetadata,
+
+    /// Descriptive TIFF tags of the full-resolution level
+    tiff_metadata: TiffMetadata,
+
+    /// Validation warnings (non-fatal issues)
+    warnings: Vec<String>,
 }
 
 impl SvsReader {
@@ -177,8 +379,17 @@ impl SvsReader {
     /// This reads the TIFF structure, identifies pyramid levels,
     /// loads tile offset arrays, and caches JPEGTables for each level.
     pub async fn open<R: RangeReader>(reader: &R) -> Result<Self, TiffError> {
+        Self::open_with_options(reader, &PyramidDetectOptions::default()).await
+    }
+
+    /// Open an SVS file, identifying pyramid levels with the given
+    /// heuristics.
+    pub async fn open_with_options<R: RangeReader>(
+        reader: &R,
+        options: &PyramidDetectOptions,
+    ) -> Result<Self, TiffError> {
         // Parse the TIFF pyramid structure
-        let pyramid = TiffPyramid::parse(reader).await?;
+        let pyramid = TiffPyramid::parse_with_options(reader, options).await?;
 
         // Validate the pyramid meets our requirements
         let validation = validate_pyramid(&pyramid);
@@ -186,10 +397,14 @@ impl SvsReader {
             return Err(validation.into_result().unwrap_err());
         }
 
+        // Store warnings for later inspection
+        let warnings = validation.warnings;
+
         // Load tile data for each pyramid level
         let mut levels = Vec::with_capacity(pyramid.levels.len());
         for level in &pyramid.levels {
-            let tile_data = TileData::load(reader, level, &pyramid.header).await?;
+            let tile_data =
+                TileData::load_with_limits(reader, level, &pyramid.header, &options.limits).await?;
             levels.push(SvsLevelData {
                 level: level.clone(),
                 tile_data,
@@ -197,49 +412,44 @@ impl SvsReader {
         }
 
         // Parse metadata from first IFD's ImageDescription
-        let metadata = Self::parse_metadata(reader, &pyramid).await?;
+        let tiff_metadata = TiffMetadata::read_pyramid(reader, &pyramid).await;
+        let metadata = tiff_metadata
+            .image_description
+            .as_deref()
+            .map(SvsMetadata::parse)
+            .unwrap_or_default();
 
         Ok(SvsReader {
             pyramid,
             levels,
             metadata,
+            tiff_metadata,
+            warnings,
         })
     }
 
-    /// Parse SVS metadata from the first pyramid level's ImageDescription.
-    async fn parse_metadata<R: RangeReader>(
-        reader: &R,
-        pyramid: &TiffPyramid,
-    ) -> Result<SvsMetadata, TiffError> {
-        // Get the first pyramid level's IFD
-        let first_level = match pyramid.levels.first() {
-            Some(level) => level,
-            None => return Ok(SvsMetadata::default()),
-        };
-
-        // Check for ImageDescription tag
-        let entry = match first_level.ifd.get_entry_by_tag(TiffTag::ImageDescription) {
-            Some(e) => e,
-            None => return Ok(SvsMetadata::default()),
-        };
-
-        // Read the ImageDescription
-        let value_reader = ValueReader::new(reader, &pyramid.header);
-        let description = value_reader.read_string(entry).await?;
-
-        Ok(SvsMetadata::parse(&description))
-    }
-
     /// Get the TIFF header.
     pub fn header(&self) -> &TiffHeader {
         &self.pyramid.header
     }
 
+    /// Get validation warnings from file open.
+    ///
+    /// Warnings indicate non-fatal issues like unusual tile dimensions.
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
     /// Get the parsed SVS metadata.
     pub fn metadata(&self) -> &SvsMetadata {
         &self.metadata
     }
 
+    /// Get the descriptive TIFF tags of the full-resolution level.
+    pub fn tiff_metadata(&self) -> &TiffMetadata {
+        &self.tiff_metadata
+    }
+
     /// Get the number of pyramid levels.
     pub fn level_count(&self) -> usize {
         self.levels.len()
@@ -308,6 +518,15 @@ impl SvsReader {
                     ),
                 })?;
 
+        // Sparse TIFFs leave unwritten tiles at offset or size 0
+        if offset == 0 || size == 0 {
+            return Err(TiffError::SparseTile {
+                level,
+                tile_x,
+                tile_y,
+            });
+        }
+
         let data = reader.read_exact_at(offset, size as usize).await?;
         Ok(data)
     }
@@ -315,7 +534,9 @@ impl SvsReader {
     /// Read a tile and prepare it for JPEG decoding.
     ///
     /// This reads the tile data and merges it with JPEGTables if the tile
-    /// contains an abbreviated JPEG stream (common in SVS files).
+    /// contains an abbreviated JPEG stream (common in SVS files). LZW and
+    /// Deflate tiles are wrapped with their pixel layout (see
+    /// [`super::codec`]).
     ///
     /// # Arguments
     /// * `reader` - Range reader for the file
@@ -341,6 +562,13 @@ impl SvsReader {
             message: format!("level {} out of range", level),
         })?;
 
+        // LZW/Deflate tiles are wrapped with their pixel layout for decoding
+        if let Some(layout) =
+            RawTileLayout::from_level(&level_data.level, self.header().byte_order)?
+        {
+            return Ok(layout.wrap(&raw_data));
+        }
+
         let tables = level_data.jpeg_tables();
 
         // Prepare the JPEG data (merge tables if needed)
@@ -508,4 +736,91 @@ mod tests {
         assert!((metadata.mpp.unwrap() - 0.5).abs() < 0.001);
         assert!((metadata.magnification.unwrap() - 40.0).abs() < 0.1);
     }
+
+    #[test]
+    fn test_parse_metadata_full_block() {
+        let description = "Aperio Image Library v10.0.50\r\n\
+            16000x17597 [0,100 15374x17497] (256x256) JPEG/RGB Q=30|\
+            AppMag = 20|StripeWidth = 1000|ScanScope ID = CPAPERIOCS|Filename = CMU-1|\
+            Date = 12/29/09|Time = 09:59:15|Time Zone = GMT-0800|User = b414003d|\
+            Parmset = USM Filter|MPP = 0.4990|Left = 25.691574|Top = 23.449873|\
+            Focus Offset = 0.000000|ImageID = 1004486|OriginalWidth = 46920|\
+            Originalheight = 33014|Filtered = 5|Exposure Time = 109|\
+            Compression Quality = 91|ICC Profile = ScanScope v1";
+
+        let metadata = SvsMetadata::parse(description);
+
+        assert_eq!(metadata.library_version.as_deref(), Some("v10.0.50"));
+        assert_eq!(metadata.compression.as_deref(), Some("JPEG/RGB"));
+        assert_eq!(metadata.jpeg_quality, Some(30));
+        assert_eq!(metadata.compression_quality, Some(91));
+        assert_eq!(metadata.original_width, Some(46920));
+        assert_eq!(metadata.original_height, Some(33014));
+        assert_eq!(metadata.stripe_width, Some(1000));
+        assert_eq!(metadata.scanner_id.as_deref(), Some("CPAPERIOCS"));
+        assert_eq!(metadata.filename.as_deref(), Some("CMU-1"));
+        assert_eq!(metadata.image_id.as_deref(), Some("1004486"));
+        assert_eq!(metadata.user.as_deref(), Some("b414003d"));
+        assert_eq!(metadata.parmset.as_deref(), Some("USM Filter"));
+        assert_eq!(metadata.filtered, Some(5));
+        assert_eq!(metadata.icc_profile.as_deref(), Some("ScanScope v1"));
+        assert!((metadata.mpp.unwrap() - 0.499).abs() < 1e-9);
+        assert!((metadata.left_mm.unwrap() - 25.691574).abs() < 1e-9);
+        assert!((metadata.top_mm.unwrap() - 23.449873).abs() < 1e-9);
+        assert_eq!(metadata.focus_offset_mm, Some(0.0));
+        assert_eq!(metadata.exposure_time_us, Some(109.0));
+        assert_eq!(
+            metadata.acquired_at().as_deref(),
+            Some("2009-12-29T09:59:15-08:00")
+        );
+
+        assert_eq!(metadata.properties.len(), 20);
+        assert!(metadata
+            .properties
+            .keys()
+            .all(|key| !key.contains("Aperio")));
+        assert_eq!(
+            metadata.properties.get("Originalheight"),
+            Some(&"33014".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_metadata_units() {
+        let metadata =
+            SvsMetadata::parse("Aperio Image Library|MPP = 250 nm|AppMag = 40x|Left = 1500um");
+        assert!((metadata.mpp.unwrap() - 0.25).abs() < 1e-9);
+        assert_eq!(metadata.magnification, Some(40.0));
+        assert!((metadata.left_mm.unwrap() - 1.5).abs() < 1e-9);
+
+        // Unknown units are rejected rather than misread
+        let metadata = SvsMetadata::parse("Aperio Image Library|MPP = 0.5 in|Top = 3 furlongs");
+        assert!(metadata.mpp.is_none());
+        assert!(metadata.top_mm.is_none());
+    }
+
+    #[test]
+    fn test_acquired_at() {
+        let mut metadata = SvsMetadata {
+            date: Some("03/07/2021".to_string()),
+            time: Some("14:05:00".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            metadata.acquired_at().as_deref(),
+            Some("2021-03-07T14:05:00")
+        );
+
+        metadata.time_zone = Some("GMT+05:30".to_string());
+        assert_eq!(
+            metadata.acquired_at().as_deref(),
+            Some("2021-03-07T14:05:00+05:30")
+        );
+
+        metadata.time_zone = Some("GMT".to_string());
+        assert!(metadata.acquired_at().unwrap().ends_with('Z'));
+
+        metadata.date = Some("13/07/21".to_string());
+        assert!(metadata.acquired_at().is_none());
+    }
 }
@@ -0,0 +1,293 @@
+//! Tag dump of a TIFF file.
+//!
+//! [`TiffPyramid::describe`] lists every IFD the parser found with all of its
+//! tags, the way `tiffinfo` would, so a vendor file that fails validation can
+//! be debugged without external tools. Small values are decoded; large
+//! arrays (tile offsets) and binary blobs (JPEGTables) are summarized by
+//! their size.
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::io::RangeReader;
+
+use super::parser::{ByteOrder, Ifd, IfdEntry, TiffHeader};
+use super::pyramid::{PyramidLayout, TiffPyramid};
+use super::tags::{FieldType, TiffTag};
+use super::values::ValueReader;
+
+/// Most numeric values decoded per tag; longer arrays are only counted.
+pub const MAX_DESCRIBED_VALUES: u64 = 64;
+
+/// Largest ASCII value decoded, in bytes.
+pub const MAX_DESCRIBED_TEXT: u64 = 1024 * 1024;
+
+/// Tag dump of a TIFF file.
+#[derive(Debug, Clone, Serialize)]
+pub struct TiffDescription {
+    /// "little-endian" or "big-endian"
+    pub byte_order: &'static str,
+
+    /// Whether the file is a BigTIFF
+    pub bigtiff: bool,
+
+    /// Where the pyramid levels were found ("ifd-chain", "sub-ifds", "leica-scn")
+    pub layout: &'static str,
+
+    /// Every IFD, in file order (SubIFDs last)
+    pub ifds: Vec<IfdDescription>,
+}
+
+/// One IFD of a [`TiffDescription`].
+#[derive(Debug, Clone, Serialize)]
+pub struct IfdDescription {
+    /// Index in the IFD chain
+    pub index: usize,
+
+    /// Pyramid level stored in this IFD, absent for label, macro and
+    /// other associated images
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub level: Option<usize>,
+
+    /// Tags, in file order
+    pub tags: Vec<TagDescription>,
+}
+
+/// One tag of an [`IfdDescription`].
+#[derive(Debug, Clone, Serialize)]
+pub struct TagDescription {
+    /// Numeric tag ID
+    pub id: u16,
+
+    /// Tag name, absent for tags the parser doesn't know
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    /// Field type name, or its number when unsupported
+    #[serde(rename = "type")]
+    pub field_type: String,
+
+    /// Number of values
+    pub count: u64,
+
+    /// Size of the value in bytes, absent for unsupported field types
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub byte_size: Option<u64>,
+
+    /// Decoded value: a string for ASCII, a number or array of numbers
+    /// otherwise. Absent when too large to decode or binary.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<Value>,
+
+    /// Why the value couldn't be read
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl TiffPyramid {
+    /// Dump every IFD and tag of the file.
+    ///
+    /// `reader` must be the reader the pyramid was parsed from; it is used
+    /// to fetch values stored outside the IFDs. A value that can't be read
+    /// is reported on its tag rather than failing the dump.
+    pub async fn describe<R: RangeReader>(&self, reader: &R) -> TiffDescription {
+        let values = ValueReader::new(reader, &self.header);
+
+        let mut ifds: Vec<(usize, Option<usize>, &Ifd)> = self
+            .levels
+            .iter()
+            .map(|level| (level.ifd_index, Some(level.level_index), &level.ifd))
+            .chain(
+                self.other_ifds
+                    .iter()
+                    .map(|(index, ifd)| (*index, None, ifd)),
+            )
+            .collect();
+        ifds.sort_by_key(|(index, _, _)| *index);
+
+        let mut described = Vec::with_capacity(ifds.len());
+        for (index, level, ifd) in ifds {
+            let mut tags = Vec::with_capacity(ifd.entries.len());
+            for entry in &ifd.entries {
+                tags.push(describe_entry(&values, &self.header, entry).await);
+            }
+            described.push(IfdDescription { index, level, tags });
+        }
+
+        TiffDescription {
+            byte_order: match self.header.byte_order {
+                ByteOrder::LittleEndian => "little-endian",
+                ByteOrder::BigEndian => "big-endian",
+            },
+            bigtiff: self.header.is_bigtiff,
+            layout: match self.layout {
+                PyramidLayout::IfdChain => "ifd-chain",
+                PyramidLayout::SubIfds => "sub-ifds",
+                PyramidLayout::LeicaScn => "leica-scn",
+            },
+            ifds: described,
+        }
+    }
+}
+
+/// Describe one IFD entry, decoding its value when small enough.
+async fn describe_entry<R: RangeReader>(
+    values: &ValueReader<'_, R>,
+    header: &TiffHeader,
+    entry: &IfdEntry,
+) -> TagDescription {
+    let mut description = TagDescription {
+        id: entry.tag_id,
+        name: TiffTag::from_u16(entry.tag_id).map(|tag| format!("{:?}", tag)),
+        field_type: match entry.field_type {
+            Some(field_type) => format!("{:?}", field_type),
+            None => entry.field_type_raw.to_string(),
+        },
+        count: entry.count,
+        byte_size: entry.value_byte_size(),
+        value: None,
+        error: None,
+    };
+
+    let decodable = match entry.field_type {
+        Some(FieldType::Ascii) => description.byte_size <= Some(MAX_DESCRIBED_TEXT),
+        Some(FieldType::Undefined) | None => false,
+        Some(_) => entry.count <= MAX_DESCRIBED_VALUES,
+    };
+    if !decodable {
+        return description;
+    }
+
+    let value = match entry.field_type {
+        Some(FieldType::Ascii) => values.read_string(entry).await.map(Value::String),
+        _ => values
+            .read_bytes(entry)
+            .await
+            .map(|bytes| decode_numbers(&bytes, entry, header.byte_order)),
+    };
+    match value {
+        Ok(value) => description.value = Some(value),
+        Err(e) => description.error = Some(e.to_string()),
+    }
+    description
+}
+
+/// Decode the numeric values of an entry: a single number, or an array.
+fn decode_numbers(bytes: &[u8], entry: &IfdEntry, byte_order: ByteOrder) -> Value {
+    let Some(field_type) = entry.field_type else {
+        return Value::Null;
+    };
+    let numbers: Vec<Value> = bytes
+        .chunks_exact(field_type.size_in_bytes())
+        .map(|value| match field_type {
+            FieldType::Byte | FieldType::Ascii | FieldType::Undefined => Value::from(value[0]),
+            FieldType::Short => Value::from(byte_order.read_u16(value)),
+            FieldType::Long | FieldType::Ifd => Value::from(byte_order.read_u32(value)),
+            FieldType::Long8 | FieldType::Ifd8 => Value::from(byte_order.read_u64(value)),
+            FieldType::Rational => {
+                let numerator = byte_order.read_u32(&value[..4]);
+                let denominator = byte_order.read_u32(&value[4..]);
+                if denominator == 0 {
+                    Value::Null
+                } else {
+                    Value::from(numerator as f64 / denominator as f64)
+                }
+            }
+        })
+        .collect();
+
+    match <[Value; 1]>::try_from(numbers) {
+        Ok([number]) => number,
+        Err(numbers) => Value::Array(numbers),
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::MemoryRangeReader;
+    use bytes::Bytes;
+
+    /// Little-endian TIFF with one 2048x1024 tiled IFD, an ImageDescription
+    /// stored out of line, a resolution and a 4-byte JPEGTables blob.
+    fn tiff() -> Vec<u8> {
+        let description = b"Aperio Image Library v1.0\0";
+        let entries: [(u16, u16, u32, u32); 11] = [
+            (256, 3, 1, 2048),   // ImageWidth
+            (257, 3, 1, 1024),   // ImageLength
+            (259, 3, 1, 7),      // Compression
+            (270, 2, 26, 200),   // ImageDescription (offset)
+            (282, 5, 1, 232),    // XResolution (offset)
+            (322, 3, 1, 1024),   // TileWidth
+            (323, 3, 1, 512),    // TileLength
+            (324, 4, 4, 240),    // TileOffsets (offset)
+            (325, 4, 4, 256),    // TileByteCounts (offset)
+            (347, 7, 4, 0xd8ff), // JPEGTables (inline)
+            (65000, 3, 1, 1),    // Unknown private tag
+        ];
+
+        let mut data = b"II*\0".to_vec();
+        data.extend_from_slice(&8u32.to_le_bytes());
+        data.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+        for (tag, field_type, count, value) in entries {
+            data.extend_from_slice(&tag.to_le_bytes());
+            data.extend_from_slice(&field_type.to_le_bytes());
+            data.extend_from_slice(&count.to_le_bytes());
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data.resize(200, 0);
+        data.extend_from_slice(description);
+        data.resize(232, 0);
+        data.extend_from_slice(&40_000u32.to_le_bytes());
+        data.extend_from_slice(&1u32.to_le_bytes());
+        for offset in [1000u32, 2000, 3000, 4000] {
+            data.extend_from_slice(&offset.to_le_bytes());
+        }
+        for _ in 0..4 {
+            data.extend_from_slice(&100u32.to_le_bytes());
+        }
+        data
+    }
+
+    #[tokio::test]
+    async fn test_describe() {
+        let reader = MemoryRangeReader::new("test.tif", Bytes::from(tiff()));
+        let pyramid = TiffPyramid::parse(&reader).await.unwrap();
+        let description = pyramid.describe(&reader).await;
+
+        assert_eq!(description.byte_order, "little-endian");
+        assert!(!description.bigtiff);
+        assert_eq!(description.layout, "ifd-chain");
+        assert_eq!(description.ifds.len(), 1);
+        assert_eq!(description.ifds[0].level, Some(0));
+
+        let tags = &description.ifds[0].tags;
+        let tag = |id: u16| tags.iter().find(|tag| tag.id == id).unwrap();
+        assert_eq!(tags.len(), 11);
+        assert_eq!(tag(256).name.as_deref(), Some("ImageWidth"));
+        assert_eq!(tag(256).value, Some(Value::from(2048)));
+        assert_eq!(
+            tag(270).value,
+            Some(Value::from("Aperio Image Library v1.0"))
+        );
+        assert_eq!(tag(282).field_type, "Rational");
+        assert_eq!(tag(282).value, Some(Value::from(40_000.0)));
+        assert_eq!(
+            tag(324).value,
+            Some(serde_json::json!([1000, 2000, 3000, 4000]))
+        );
+
+        // Binary blobs are summarized by size
+        assert_eq!(tag(347).byte_size, Some(4));
+        assert!(tag(347).value.is_none());
+
+        // Unknown tags are listed without a name
+        assert!(tag(65000).name.is_none());
+        assert_eq!(tag(65000).value, Some(Value::from(1)));
+    }
+}
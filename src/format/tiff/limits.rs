@@ -0,0 +1,84 @@
+//! Safety limits for TIFF parsing.
+//!
+//! Counts and sizes in a TIFF file are attacker-controlled: a corrupt or
+//! malicious file can declare an IFD with billions of entries or a tag
+//! array of several gigabytes. The parser checks every such value against
+//! [`TiffLimits`] before reading or allocating anything, and fails with
+//! [`TiffError::LimitExceeded`] instead.
+//!
+//! The defaults are far above what real slides use: a 200,000 x 200,000
+//! pixel level in 256x256 tiles has about 610,000 tiles, whose offsets take
+//! under 5 MB.
+
+use crate::error::TiffError;
+
+/// Default maximum number of entries in one IFD.
+pub const DEFAULT_MAX_IFD_ENTRIES: u64 = 4096;
+
+/// Default maximum size of one out-of-line tag value, in bytes.
+pub const DEFAULT_MAX_ARRAY_BYTES: u64 = 256 * 1024 * 1024;
+
+/// Default maximum number of tiles (or strips) in one level.
+pub const DEFAULT_MAX_TILE_COUNT: u64 = 16 * 1024 * 1024;
+
+/// Default maximum size of a JPEGTables value, in bytes.
+pub const DEFAULT_MAX_JPEG_TABLES_BYTES: u64 = 1024 * 1024;
+
+/// Hard limits on what a TIFF file may make the parser read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TiffLimits {
+    /// Maximum number of entries in one IFD
+    pub max_ifd_entries: u64,
+
+    /// Maximum size of one out-of-line tag value (arrays, strings), in bytes
+    pub max_array_bytes: u64,
+
+    /// Maximum number of tiles (or strips) in one level
+    pub max_tile_count: u64,
+
+    /// Maximum size of a JPEGTables value, in bytes
+    pub max_jpeg_tables_bytes: u64,
+}
+
+impl TiffLimits {
+    /// The default limits.
+    pub const DEFAULT: Self = Self {
+        max_ifd_entries: DEFAULT_MAX_IFD_ENTRIES,
+        max_array_bytes: DEFAULT_MAX_ARRAY_BYTES,
+        max_tile_count: DEFAULT_MAX_TILE_COUNT,
+        max_jpeg_tables_bytes: DEFAULT_MAX_JPEG_TABLES_BYTES,
+    };
+
+    /// Check the entry count of an IFD.
+    pub fn check_ifd_entries(&self, entries: u64) -> Result<(), TiffError> {
+        check("max_ifd_entries", entries, self.max_ifd_entries)
+    }
+
+    /// Check the size of an out-of-line tag value.
+    pub fn check_array_bytes(&self, bytes: u64) -> Result<(), TiffError> {
+        check("max_array_bytes", bytes, self.max_array_bytes)
+    }
+
+    /// Check the number of tiles (or strips) of a level.
+    pub fn check_tile_count(&self, tiles: u64) -> Result<(), TiffError> {
+        check("max_tile_count", tiles, self.max_tile_count)
+    }
+
+    /// Check the size of a JPEGTables value.
+    pub fn check_jpeg_tables_bytes(&self, bytes: u64) -> Result<(), TiffError> {
+        check("max_jpeg_tables_bytes", bytes, self.max_jpeg_tables_bytes)
+    }
+}
+
+impl Default for TiffLimits {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+fn check(limit: &'static str, value: u64, max: u64) -> Result<(), TiffError> {
+    if value > max {
+        return Err(TiffError::LimitExceeded { limit, value, max });
+    }
+    Ok(())
+}
@@ -0,0 +1,161 @@
+//! Descriptive TIFF tags of a slide.
+//!
+//! Besides the pyramid structure, the first pyramid level's IFD usually
+//! records who wrote the file and at what resolution. These tags are
+//! optional; a tag that is missing or malformed is simply absent from
+//! [`TiffMetadata`] rather than failing the slide.
+
+use bytes::Bytes;
+
+use crate::io::RangeReader;
+
+use super::orientation::Orientation;
+use super::parser::{Ifd, TiffHeader};
+use super::pyramid::TiffPyramid;
+use super::tags::TiffTag;
+use super::values::ValueReader;
+
+/// `ResolutionUnit` value for centimeters.
+pub const RESOLUTION_UNIT_CENTIMETER: u16 = 3;
+
+/// Largest ICC profile kept; larger profiles are ignored.
+pub const MAX_ICC_PROFILE_BYTES: usize = 1024 * 1024;
+
+/// Descriptive tags of a TIFF image.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TiffMetadata {
+    /// ImageDescription tag
+    pub image_description: Option<String>,
+
+    /// Make tag (scanner manufacturer)
+    pub make: Option<String>,
+
+    /// Model tag (scanner model)
+    pub model: Option<String>,
+
+    /// Software tag
+    pub software: Option<String>,
+
+    /// DateTime tag
+    pub date_time: Option<String>,
+
+    /// Pixels per resolution unit horizontally
+    pub x_resolution: Option<f64>,
+
+    /// Pixels per resolution unit vertically
+    pub y_resolution: Option<f64>,
+
+    /// Resolution unit (1 = none, 2 = inch, 3 = centimeter)
+    pub resolution_unit: Option<u16>,
+
+    /// Orientation tag (upright when missing or invalid)
+    pub orientation: Orientation,
+
+    /// Embedded ICC color profile
+    pub icc_profile: Option<Bytes>,
+}
+
+impl TiffMetadata {
+    /// Read the descriptive tags of an IFD.
+    pub async fn read<R: RangeReader>(reader: &R, header: &TiffHeader, ifd: &Ifd) -> Self {
+        let values = ValueReader::new(reader, header);
+        Self {
+            image_description: read_string_tag(&values, ifd, TiffTag::ImageDescription).await,
+            make: read_string_tag(&values, ifd, TiffTag::Make).await,
+            model: read_string_tag(&values, ifd, TiffTag::Model).await,
+            software: read_string_tag(&values, ifd, TiffTag::Software).await,
+            date_time: read_string_tag(&values, ifd, TiffTag::DateTime).await,
+            x_resolution: read_rational_tag(&values, ifd, TiffTag::XResolution).await,
+            y_resolution: read_rational_tag(&values, ifd, TiffTag::YResolution).await,
+            resolution_unit: ifd.get_u16(TiffTag::ResolutionUnit, header.byte_order),
+            orientation: ifd
+                .get_u16(TiffTag::Orientation, header.byte_order)
+                .and_then(Orientation::from_value)
+                .unwrap_or_default(),
+            icc_profile: read_icc_profile(&values, ifd).await,
+        }
+    }
+
+    /// Read the descriptive tags of a pyramid's full-resolution level.
+    pub async fn read_pyramid<R: RangeReader>(reader: &R, pyramid: &TiffPyramid) -> Self {
+        match pyramid.levels.first() {
+            Some(level) => Self::read(reader, &pyramid.header, &level.ifd).await,
+            None => Self::default(),
+        }
+    }
+
+    /// Get the resolution name used in property maps ("none", "inch" or
+    /// "centimeter").
+    pub fn resolution_unit_name(&self) -> Option<&'static str> {
+        match self.resolution_unit? {
+            1 => Some("none"),
+            2 => Some("inch"),
+            3 => Some("centimeter"),
+            _ => None,
+        }
+    }
+
+    /// Get the resolution in microns per pixel `(x, y)`.
+    ///
+    /// Only centimeter resolutions are trusted: inch resolutions are often
+    /// a writer's 72 dpi default rather than a calibration.
+    pub fn mpp(&self) -> Option<(f64, f64)> {
+        if self.resolution_unit != Some(RESOLUTION_UNIT_CENTIMETER) {
+            return None;
+        }
+        let to_mpp = |resolution: f64| (resolution > 0.0).then(|| 10_000.0 / resolution);
+        Some((to_mpp(self.x_resolution?)?, to_mpp(self.y_resolution?)?))
+    }
+}
+
+async fn read_string_tag<R: RangeReader>(
+    values: &ValueReader<'_, R>,
+    ifd: &Ifd,
+    tag: TiffTag,
+) -> Option<String> {
+    values.read_string(ifd.get_entry_by_tag(tag)?).await.ok()
+}
+
+async fn read_icc_profile<R: RangeReader>(values: &ValueReader<'_, R>, ifd: &Ifd) -> Option<Bytes> {
+    let entry = ifd.get_entry_by_tag(TiffTag::IccProfile)?;
+    if entry.value_byte_size()? > MAX_ICC_PROFILE_BYTES as u64 {
+        return None;
+    }
+    values.read_raw_bytes(entry).await.ok()
+}
+
+async fn read_rational_tag<R: RangeReader>(
+    values: &ValueReader<'_, R>,
+    ifd: &Ifd,
+    tag: TiffTag,
+) -> Option<f64> {
+    values.read_rational(ifd.get_entry_by_tag(tag)?).await.ok()
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mpp_from_centimeter_resolution() {
+        let mut metadata = TiffMetadata {
+            x_resolution: Some(40_000.0),
+            y_resolution: Some(20_000.0),
+            resolution_unit: Some(RESOLUTION_UNIT_CENTIMETER),
+            ..Default::default()
+        };
+        assert_eq!(metadata.mpp(), Some((0.25, 0.5)));
+        assert_eq!(metadata.resolution_unit_name(), Some("centimeter"));
+
+        metadata.resolution_unit = Some(2);
+        assert_eq!(metadata.mpp(), None);
+
+        metadata.resolution_unit = Some(RESOLUTION_UNIT_CENTIMETER);
+        metadata.x_resolution = Some(0.0);
+        assert_eq!(metadata.mpp(), None);
+    }
+}
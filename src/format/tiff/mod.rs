@@ -17,14 +17,30 @@
 //! - **Inline vs offset values**: Small values are stored inline in the IFD entry,
 //!   larger values are stored at an offset pointed to by the entry.
 
+mod describe;
+mod limits;
+mod metadata;
+mod orientation;
 mod parser;
 mod pyramid;
 mod tags;
 mod validation;
 mod values;
 
+pub use describe::{
+    IfdDescription, TagDescription, TiffDescription, MAX_DESCRIBED_TEXT, MAX_DESCRIBED_VALUES,
+};
+pub use limits::{
+    TiffLimits, DEFAULT_MAX_ARRAY_BYTES, DEFAULT_MAX_IFD_ENTRIES, DEFAULT_MAX_JPEG_TABLES_BYTES,
+    DEFAULT_MAX_TILE_COUNT,
+};
+pub use metadata::{TiffMetadata, MAX_ICC_PROFILE_BYTES, RESOLUTION_UNIT_CENTIMETER};
+pub use orientation::Orientation;
 pub use parser::{ByteOrder, Ifd, IfdEntry, TiffHeader, BIGTIFF_HEADER_SIZE, TIFF_HEADER_SIZE};
-pub use pyramid::{PyramidLevel, TiffPyramid, TileData};
+pub use pyramid::{
+    PyramidDetectOptions, PyramidLayout, PyramidLevel, TiffPyramid, TileData,
+    DEFAULT_DOWNSAMPLE_TOLERANCE, MAX_STRIP_PIXELS, STRIP_TILE_SIZE,
+};
 pub use tags::{Compression, FieldType, TiffTag};
 pub use validation::{
     check_compression, check_tile_tags, check_tiled, validate_ifd, validate_ifd_strict,
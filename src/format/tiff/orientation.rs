@@ -0,0 +1,190 @@
+//! TIFF Orientation tag (274).
+//!
+//! The Orientation tag says how the stored rows and columns map onto the
+//! displayed image. Nearly every slide is stored upright ([`Orientation::Normal`]),
+//! but some converters and scanners write rotated or mirrored images and
+//! rely on the tag to display them. Values 5-8 transpose the image, so its
+//! displayed width is its stored height. The same values are used by the
+//! EXIF orientation of JPEG label and macro images.
+
+use image::DynamicImage;
+
+/// Orientation of stored pixels relative to the upright image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Orientation {
+    /// Upright (1)
+    #[default]
+    Normal,
+    /// Mirrored horizontally (2)
+    FlipHorizontal,
+    /// Rotated 180° (3)
+    Rotate180,
+    /// Mirrored vertically (4)
+    FlipVertical,
+    /// Mirrored across the main diagonal (5)
+    Transpose,
+    /// Needs a 90° clockwise rotation (6)
+    Rotate90,
+    /// Mirrored across the anti-diagonal (7)
+    Transverse,
+    /// Needs a 270° clockwise rotation (8)
+    Rotate270,
+}
+
+impl Orientation {
+    /// Parse an EXIF / TIFF orientation value.
+    pub fn from_value(value: u16) -> Option<Self> {
+        match value {
+            1 => Some(Orientation::Normal),
+            2 => Some(Orientation::FlipHorizontal),
+            3 => Some(Orientation::Rotate180),
+            4 => Some(Orientation::FlipVertical),
+            5 => Some(Orientation::Transpose),
+            6 => Some(Orientation::Rotate90),
+            7 => Some(Orientation::Transverse),
+            8 => Some(Orientation::Rotate270),
+            _ => None,
+        }
+    }
+
+    /// Whether the image is stored upright.
+    pub fn is_upright(self) -> bool {
+        self == Orientation::Normal
+    }
+
+    /// Whether the width and height are swapped once applied.
+    pub fn swaps_dimensions(self) -> bool {
+        matches!(
+            self,
+            Orientation::Transpose
+                | Orientation::Rotate90
+                | Orientation::Transverse
+                | Orientation::Rotate270
+        )
+    }
+
+    /// Displayed size of a stored `width` x `height` area.
+    pub fn display_size(self, width: u32, height: u32) -> (u32, u32) {
+        if self.swaps_dimensions() {
+            (height, width)
+        } else {
+            (width, height)
+        }
+    }
+
+    /// Stored position of a displayed pixel of a `width` x `height`
+    /// displayed image.
+    pub fn to_stored(self, x: u32, y: u32, width: u32, height: u32) -> (u32, u32) {
+        let (right, bottom) = (width - 1 - x, height - 1 - y);
+        match self {
+            Orientation::Normal => (x, y),
+            Orientation::FlipHorizontal => (right, y),
+            Orientation::Rotate180 => (right, bottom),
+            Orientation::FlipVertical => (x, bottom),
+            Orientation::Transpose => (y, x),
+            Orientation::Rotate90 => (y, right),
+            Orientation::Transverse => (bottom, right),
+            Orientation::Rotate270 => (bottom, x),
+        }
+    }
+
+    /// Stored rectangle `(x, y, width, height)` under a displayed rectangle
+    /// of a `width` x `height` displayed image.
+    pub fn to_stored_rect(
+        self,
+        rect: (u32, u32, u32, u32),
+        width: u32,
+        height: u32,
+    ) -> (u32, u32, u32, u32) {
+        let (x, y, w, h) = rect;
+        let (x0, y0) = self.to_stored(x, y, width, height);
+        let (x1, y1) = self.to_stored(x + w - 1, y + h - 1, width, height);
+        (
+            x0.min(x1),
+            y0.min(y1),
+            x0.abs_diff(x1) + 1,
+            y0.abs_diff(y1) + 1,
+        )
+    }
+
+    /// Transform stored pixels into the upright image.
+    pub fn apply(self, img: DynamicImage) -> DynamicImage {
+        match self {
+            Orientation::Normal => img,
+            Orientation::FlipHorizontal => img.fliph(),
+            Orientation::Rotate180 => img.rotate180(),
+            Orientation::FlipVertical => img.flipv(),
+            Orientation::Transpose => img.rotate90().fliph(),
+            Orientation::Rotate90 => img.rotate90(),
+            Orientation::Transverse => img.rotate270().fliph(),
+            Orientation::Rotate270 => img.rotate270(),
+        }
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgb, RgbImage};
+
+    const ALL: [Orientation; 8] = [
+        Orientation::Normal,
+        Orientation::FlipHorizontal,
+        Orientation::Rotate180,
+        Orientation::FlipVertical,
+        Orientation::Transpose,
+        Orientation::Rotate90,
+        Orientation::Transverse,
+        Orientation::Rotate270,
+    ];
+
+    #[test]
+    fn test_tag_values() {
+        for (value, orientation) in (1..=8).zip(ALL) {
+            assert_eq!(Orientation::from_value(value), Some(orientation));
+        }
+        assert_eq!(Orientation::from_value(0), None);
+        assert_eq!(Orientation::from_value(9), None);
+        assert_eq!(Orientation::Rotate90.display_size(30, 20), (20, 30));
+        assert_eq!(Orientation::Rotate180.display_size(30, 20), (30, 20));
+    }
+
+    #[test]
+    fn test_to_stored_rect() {
+        // Display is 3 wide and 5 tall for a transposed 5x3 image
+        assert_eq!(
+            Orientation::Rotate90.to_stored_rect((1, 0, 2, 2), 3, 5),
+            (0, 0, 2, 2)
+        );
+        assert_eq!(
+            Orientation::Rotate180.to_stored_rect((0, 0, 2, 1), 5, 3),
+            (3, 2, 2, 1)
+        );
+        assert_eq!(
+            Orientation::Normal.to_stored_rect((1, 2, 3, 1), 5, 3),
+            (1, 2, 3, 1)
+        );
+    }
+
+    #[test]
+    fn test_apply_matches_to_stored() {
+        // Every stored pixel has a distinct color
+        let stored = RgbImage::from_fn(5, 3, |x, y| Rgb([x as u8, y as u8, 0]));
+        for orientation in ALL {
+            let displayed = orientation
+                .apply(DynamicImage::ImageRgb8(stored.clone()))
+                .into_rgb8();
+            let (width, height) = orientation.display_size(5, 3);
+            assert_eq!(displayed.dimensions(), (width, height), "{:?}", orientation);
+
+            for (x, y, pixel) in displayed.enumerate_pixels() {
+                let (sx, sy) = orientation.to_stored(x, y, width, height);
+                assert_eq!(pixel, stored.get_pixel(sx, sy), "{:?}", orientation);
+            }
+        }
+    }
+}
@@ -26,6 +26,7 @@ use std::collections::HashMap;
 use crate::error::TiffError;
 use crate::io::{read_u16_be, read_u16_le, read_u32_be, read_u32_le, read_u64_be, read_u64_le};
 
+use super::limits::TiffLimits;
 use super::tags::{FieldType, TiffTag};
 
 // =============================================================================
@@ -433,7 +434,7 @@ impl IfdEntry {
     /// Calculate total byte size of the value data.
     pub fn value_byte_size(&self) -> Option<u64> {
         self.field_type
-            .map(|ft| ft.size_in_bytes() as u64 * self.count)
+            .map(|ft| (ft.size_in_bytes() as u64).saturating_mul(self.count))
     }
 }
 
@@ -475,6 +476,16 @@ impl Ifd {
     /// # Errors
     /// Returns an error if the bytes are too short for the declared entry count.
     pub fn parse(bytes: &[u8], header: &TiffHeader) -> Result<Self, TiffError> {
+        Self::parse_with_limits(bytes, header, &TiffLimits::DEFAULT)
+    }
+
+    /// Parse an IFD from raw bytes, rejecting IFDs with more entries than
+    /// `limits` allows.
+    pub fn parse_with_limits(
+        bytes: &[u8],
+        header: &TiffHeader,
+        limits: &TiffLimits,
+    ) -> Result<Self, TiffError> {
         let byte_order = header.byte_order;
         let count_size = header.ifd_count_size();
         let entry_size = header.ifd_entry_size();
@@ -493,6 +504,7 @@ impl Ifd {
         } else {
             byte_order.read_u16(&bytes[0..2]) as u64
         };
+        limits.check_ifd_entries(entry_count)?;
 
         // Calculate required size
         let entries_start = count_size;
@@ -544,9 +556,9 @@ impl Ifd {
     /// * `entry_count` - Number of entries in the IFD
     /// * `header` - The TIFF header
     pub fn calculate_size(entry_count: u64, header: &TiffHeader) -> usize {
-        header.ifd_count_size()
-            + (entry_count as usize * header.ifd_entry_size())
-            + header.ifd_next_offset_size()
+        (entry_count as usize)
+            .saturating_mul(header.ifd_entry_size())
+            .saturating_add(header.ifd_count_size() + header.ifd_next_offset_size())
     }
 
     /// Get an entry by its tag ID.
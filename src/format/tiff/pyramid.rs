@@ -23,14 +23,23 @@
 //! - Label: Small, often square-ish, may not be tiled
 //! - Macro: Medium-sized, different aspect ratio than pyramid
 //! - Thumbnail: Very small, may lack tile structure
+//!
+//! # Pyramid Layouts
+//!
+//! Levels are not always chained top-level IFDs:
+//! - **OME-TIFF** (Bio-Formats, QuPath exports) stores reduced resolutions in
+//!   the SubIFDs (tag 330) of the full resolution IFD
+//! - **Leica SCN** lists the IFD of every resolution of each image in the
+//!   collection XML held in the first ImageDescription
 
 use bytes::Bytes;
 
 use crate::error::TiffError;
 use crate::io::RangeReader;
 
+use super::limits::TiffLimits;
 use super::parser::{ByteOrder, Ifd, IfdEntry, TiffHeader, BIGTIFF_HEADER_SIZE};
-use super::tags::TiffTag;
+use super::tags::{Compression, TiffTag};
 use super::values::ValueReader;
 
 // =============================================================================
@@ -47,6 +56,122 @@ const MIN_PYRAMID_DIMENSION: u32 = 256;
 /// Maximum size for a label image (pixels)
 const MAX_LABEL_DIMENSION: u32 = 2000;
 
+/// Size of the virtual tiles cut from strip-organized levels (pixels)
+pub const STRIP_TILE_SIZE: u32 = 256;
+
+/// Largest strip (width x rows per strip) of a strip-organized level, in
+/// pixels. Every strip is decoded whole, so levels stored in fewer, larger
+/// strips are left out of the pyramid.
+pub const MAX_STRIP_PIXELS: u64 = 16 * 1024 * 1024;
+
+/// Default tolerance around power-of-two downsample factors
+pub const DEFAULT_DOWNSAMPLE_TOLERANCE: f64 = 0.2;
+
+/// Maximum number of SubIFDs followed from the base IFD (safety limit)
+const MAX_SUB_IFDS: usize = 32;
+
+/// Maximum ImageDescription size read when looking for SCN collection XML
+const MAX_DESCRIPTION_BYTES: u64 = 1024 * 1024;
+
+/// Namespace marker of the Leica SCN collection XML
+const SCN_NAMESPACE: &str = "leica-microsystems.com/scn";
+
+// =============================================================================
+// PyramidDetectOptions
+// =============================================================================
+
+/// Heuristics deciding which IFDs are pyramid levels.
+///
+/// The defaults (strict mode) keep tiled images of at least
+/// `min_dimension` pixels that don't look like labels and whose downsample
+/// is within `downsample_tolerance` of a power of two. Some vendors write
+/// other ratios (e.g. 3x); [`PyramidDetectOptions::permissive`] accepts every
+/// tiled IFD with tile data as a level instead.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PyramidDetectOptions {
+    /// Accept every tiled IFD with tile data, ignoring the other settings
+    pub permissive: bool,
+
+    /// Minimum width and height of a level, in pixels
+    pub min_dimension: u32,
+
+    /// Skip small, square-ish images as likely slide labels
+    pub exclude_labels: bool,
+
+    /// Relative tolerance around power-of-two downsamples (0.2 = ±20%)
+    pub downsample_tolerance: f64,
+
+    /// Also consider strip-organized JPEG images of at most
+    /// [`MAX_STRIP_PIXELS`] per strip, for readers that retile them
+    pub stripped_levels: bool,
+
+    /// Hard limits on IFD, tag array and tile table sizes
+    pub limits: TiffLimits,
+}
+
+impl PyramidDetectOptions {
+    /// Strict detection (the default).
+    pub const fn strict() -> Self {
+        Self {
+            permissive: false,
+            min_dimension: MIN_PYRAMID_DIMENSION,
+            exclude_labels: true,
+            downsample_tolerance: DEFAULT_DOWNSAMPLE_TOLERANCE,
+            stripped_levels: false,
+            limits: TiffLimits::DEFAULT,
+        }
+    }
+
+    /// Permissive detection: every tiled IFD is a level.
+    pub const fn permissive() -> Self {
+        Self {
+            permissive: true,
+            ..Self::strict()
+        }
+    }
+
+    /// Set the tolerance around power-of-two downsamples.
+    pub const fn with_downsample_tolerance(mut self, tolerance: f64) -> Self {
+        self.downsample_tolerance = tolerance;
+        self
+    }
+
+    /// Set whether strip-organized images may be levels.
+    pub const fn with_stripped_levels(mut self, enabled: bool) -> Self {
+        self.stripped_levels = enabled;
+        self
+    }
+
+    /// Set the parsing safety limits.
+    pub const fn with_limits(mut self, limits: TiffLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+}
+
+impl Default for PyramidDetectOptions {
+    fn default() -> Self {
+        Self::strict()
+    }
+}
+
+// =============================================================================
+// PyramidLayout
+// =============================================================================
+
+/// Where the levels of a pyramid are stored in the file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PyramidLayout {
+    /// Levels are top-level IFDs in the main chain (SVS, generic pyramidal TIFF)
+    IfdChain,
+
+    /// Reduced resolutions are SubIFDs of the full resolution IFD (OME-TIFF)
+    SubIfds,
+
+    /// Levels are the IFDs listed for the main image in the SCN collection XML
+    LeicaScn,
+}
+
 // =============================================================================
 // PyramidLevel
 // =============================================================================
@@ -62,6 +187,8 @@ pub struct PyramidLevel {
     pub level_index: usize,
 
     /// Index of the IFD in the file's IFD chain
+    ///
+    /// SubIFDs are numbered after the last IFD of the main chain.
     pub ifd_index: usize,
 
     /// Image width in pixels
@@ -102,6 +229,13 @@ pub struct PyramidLevel {
 
     /// JPEGTables entry for this level (if present)
     pub jpeg_tables_entry: Option<IfdEntry>,
+
+    /// Rows per strip, for strip-organized levels
+    ///
+    /// Such levels are exposed as a grid of [`STRIP_TILE_SIZE`] tiles cut
+    /// from the decoded strips; the tile offset and byte count entries then
+    /// hold StripOffsets and StripByteCounts.
+    pub strip_rows: Option<u32>,
 }
 
 impl PyramidLevel {
@@ -110,8 +244,8 @@ impl PyramidLevel {
     /// Returns None if the IFD doesn't have the required tile tags.
     fn from_ifd(ifd: Ifd, ifd_index: usize, byte_order: ByteOrder) -> Option<Self> {
         // Must have tile dimensions
-        let tile_width = ifd.tile_width(byte_order)?;
-        let tile_height = ifd.tile_height(byte_order)?;
+        let tile_width = ifd.tile_width(byte_order).filter(|&w| w > 0)?;
+        let tile_height = ifd.tile_height(byte_order).filter(|&h| h > 0)?;
 
         // Must have image dimensions
         let width = ifd.image_width(byte_order)?;
@@ -123,7 +257,7 @@ impl PyramidLevel {
         // Calculate tile counts
         let tiles_x = width.div_ceil(tile_width);
         let tiles_y = height.div_ceil(tile_height);
-        let tile_count = tiles_x * tiles_y;
+        let tile_count = tiles_x.checked_mul(tiles_y)?;
 
         // Get entries for tile offsets and byte counts
         let tile_offsets_entry = ifd.get_entry_by_tag(TiffTag::TileOffsets).cloned();
@@ -148,9 +282,63 @@ impl PyramidLevel {
             tile_offsets_entry,
             tile_byte_counts_entry,
             jpeg_tables_entry,
+            strip_rows: None,
+        })
+    }
+
+    /// Create a PyramidLevel from a strip-organized JPEG IFD.
+    ///
+    /// Returns None if the IFD is tiled, not JPEG compressed, or has strips
+    /// larger than [`MAX_STRIP_PIXELS`].
+    fn from_stripped_ifd(ifd: Ifd, ifd_index: usize, byte_order: ByteOrder) -> Option<Self> {
+        if ifd.is_tiled() || !ifd.is_stripped() {
+            return None;
+        }
+        if ifd.compression(byte_order) != Some(Compression::Jpeg as u16) {
+            return None;
+        }
+
+        let width = ifd.image_width(byte_order).filter(|&w| w > 0)?;
+        let height = ifd.image_height(byte_order).filter(|&h| h > 0)?;
+
+        // RowsPerStrip defaults to the whole image
+        let strip_rows = ifd
+            .get_u32(TiffTag::RowsPerStrip, byte_order)
+            .unwrap_or(height)
+            .clamp(1, height);
+        if width as u64 * strip_rows as u64 > MAX_STRIP_PIXELS {
+            return None;
+        }
+
+        let tiles_x = width.div_ceil(STRIP_TILE_SIZE);
+        let tiles_y = height.div_ceil(STRIP_TILE_SIZE);
+        let tile_count = tiles_x.checked_mul(tiles_y)?;
+
+        Some(PyramidLevel {
+            level_index: 0,
+            ifd_index,
+            width,
+            height,
+            tile_width: STRIP_TILE_SIZE,
+            tile_height: STRIP_TILE_SIZE,
+            tiles_x,
+            tiles_y,
+            tile_count,
+            downsample: 1.0,
+            compression: Compression::Jpeg as u16,
+            tile_offsets_entry: ifd.get_entry_by_tag(TiffTag::StripOffsets).cloned(),
+            tile_byte_counts_entry: ifd.get_entry_by_tag(TiffTag::StripByteCounts).cloned(),
+            jpeg_tables_entry: ifd.get_entry_by_tag(TiffTag::JpegTables).cloned(),
+            ifd,
+            strip_rows: Some(strip_rows),
         })
     }
 
+    /// Check if this level is strip-organized.
+    pub fn is_stripped(&self) -> bool {
+        self.strip_rows.is_some()
+    }
+
     /// Check if this level has valid tile offset and byte count entries.
     pub fn has_tile_data(&self) -> bool {
         self.tile_offsets_entry.is_some() && self.tile_byte_counts_entry.is_some()
@@ -220,6 +408,9 @@ pub struct TiffPyramid {
 
     /// IFDs that were identified as non-pyramid images (label, macro, etc.)
     pub other_ifds: Vec<(usize, Ifd)>,
+
+    /// Where the pyramid levels were found
+    pub layout: PyramidLayout,
 }
 
 impl TiffPyramid {
@@ -227,41 +418,78 @@ impl TiffPyramid {
     ///
     /// This reads all IFDs from the file, identifies which ones belong to the
     /// image pyramid, and sorts them by resolution.
+    ///
+    /// Leica SCN collection XML takes precedence, then SubIFDs of the largest
+    /// IFD; otherwise levels are picked from the main IFD chain.
     pub async fn parse<R: RangeReader>(reader: &R) -> Result<Self, TiffError> {
+        Self::parse_with_options(reader, &PyramidDetectOptions::default()).await
+    }
+
+    /// Parse a TIFF file, identifying pyramid levels with the given
+    /// heuristics.
+    pub async fn parse_with_options<R: RangeReader>(
+        reader: &R,
+        options: &PyramidDetectOptions,
+    ) -> Result<Self, TiffError> {
         // Read and parse header
         let header_bytes = reader.read_exact_at(0, BIGTIFF_HEADER_SIZE).await?;
         let header = TiffHeader::parse(&header_bytes, reader.size())?;
 
         // Parse all IFDs
-        let ifds = Self::parse_all_ifds(reader, &header).await?;
+        let mut ifds = Self::parse_all_ifds(reader, &header, &options.limits).await?;
+
+        // Leica SCN: the collection XML names the IFDs of each resolution
+        if let Some(xml) = Self::read_description(reader, &header, ifds.first()).await? {
+            if xml.contains(SCN_NAMESPACE) {
+                if let Some(members) = scn_main_image_ifds(&xml) {
+                    if members.iter().all(|&index| index < ifds.len()) {
+                        return Self::build_pyramid(
+                            header,
+                            ifds,
+                            PyramidLayout::LeicaScn,
+                            Some(&members),
+                            options,
+                        );
+                    }
+                }
+            }
+        }
+
+        // OME-TIFF: reduced resolutions hang off the base IFD as SubIFDs
+        if let Some(base) = Self::sub_ifd_base(&ifds, header.byte_order) {
+            let sub_ifds =
+                Self::parse_sub_ifds(reader, &header, &ifds[base], &options.limits).await?;
+            if !sub_ifds.is_empty() {
+                let mut members = vec![base];
+                for ifd in sub_ifds {
+                    members.push(ifds.len());
+                    ifds.push(ifd);
+                }
+                return Self::build_pyramid(
+                    header,
+                    ifds,
+                    PyramidLayout::SubIfds,
+                    Some(&members),
+                    options,
+                );
+            }
+        }
 
         // Identify pyramid levels
-        Self::build_pyramid(header, ifds)
+        Self::build_pyramid(header, ifds, PyramidLayout::IfdChain, None, options)
     }
 
     /// Parse all IFDs in the file following the next-IFD chain.
     async fn parse_all_ifds<R: RangeReader>(
         reader: &R,
         header: &TiffHeader,
+        limits: &TiffLimits,
     ) -> Result<Vec<Ifd>, TiffError> {
         let mut ifds = Vec::new();
         let mut offset = header.first_ifd_offset;
 
         while offset != 0 && ifds.len() < MAX_IFDS {
-            // First, read just enough to get the entry count
-            let count_size = header.ifd_count_size();
-            let count_bytes = reader.read_exact_at(offset, count_size).await?;
-
-            let entry_count = if header.is_bigtiff {
-                header.byte_order.read_u64(&count_bytes)
-            } else {
-                header.byte_order.read_u16(&count_bytes) as u64
-            };
-
-            // Now read the full IFD
-            let ifd_size = Ifd::calculate_size(entry_count, header);
-            let ifd_bytes = reader.read_exact_at(offset, ifd_size).await?;
-            let ifd = Ifd::parse(&ifd_bytes, header)?;
+            let ifd = Self::read_ifd(reader, header, offset, limits).await?;
 
             let next_offset = ifd.next_ifd_offset;
             ifds.push(ifd);
@@ -272,18 +500,128 @@ impl TiffPyramid {
         Ok(ifds)
     }
 
+    /// Read and parse a single IFD at the given offset.
+    async fn read_ifd<R: RangeReader>(
+        reader: &R,
+        header: &TiffHeader,
+        offset: u64,
+        limits: &TiffLimits,
+    ) -> Result<Ifd, TiffError> {
+        // First, read just enough to get the entry count
+        let count_size = header.ifd_count_size();
+        let count_bytes = reader.read_exact_at(offset, count_size).await?;
+
+        let entry_count = if header.is_bigtiff {
+            header.byte_order.read_u64(&count_bytes)
+        } else {
+            header.byte_order.read_u16(&count_bytes) as u64
+        };
+        limits.check_ifd_entries(entry_count)?;
+
+        // Now read the full IFD
+        let ifd_size = Ifd::calculate_size(entry_count, header);
+        let ifd_bytes = reader.read_exact_at(offset, ifd_size).await?;
+        Ifd::parse_with_limits(&ifd_bytes, header, limits)
+    }
+
+    /// Parse the SubIFDs referenced by an IFD's SubIFDs tag.
+    async fn parse_sub_ifds<R: RangeReader>(
+        reader: &R,
+        header: &TiffHeader,
+        parent: &Ifd,
+        limits: &TiffLimits,
+    ) -> Result<Vec<Ifd>, TiffError> {
+        let entry = match parent.get_entry_by_tag(TiffTag::SubIfds) {
+            Some(entry) => entry,
+            None => return Ok(Vec::new()),
+        };
+
+        let offsets = ValueReader::new(reader, header)
+            .with_limits(*limits)
+            .read_u64_array(entry)
+            .await?;
+
+        let mut sub_ifds = Vec::new();
+        for offset in offsets.into_iter().filter(|&o| o != 0).take(MAX_SUB_IFDS) {
+            sub_ifds.push(Self::read_ifd(reader, header, offset, limits).await?);
+        }
+
+        Ok(sub_ifds)
+    }
+
+    /// Pick the largest IFD of the main chain that carries SubIFDs.
+    fn sub_ifd_base(ifds: &[Ifd], byte_order: ByteOrder) -> Option<usize> {
+        ifds.iter()
+            .enumerate()
+            .filter(|(_, ifd)| ifd.get_entry_by_tag(TiffTag::SubIfds).is_some())
+            .max_by_key(|(_, ifd)| {
+                let width = ifd.image_width(byte_order).unwrap_or(0) as u64;
+                let height = ifd.image_height(byte_order).unwrap_or(0) as u64;
+                width * height
+            })
+            .map(|(index, _)| index)
+    }
+
+    /// Read the ImageDescription of an IFD, if present and reasonably sized.
+    async fn read_description<R: RangeReader>(
+        reader: &R,
+        header: &TiffHeader,
+        ifd: Option<&Ifd>,
+    ) -> Result<Option<String>, TiffError> {
+        let entry = match ifd.and_then(|ifd| ifd.get_entry_by_tag(TiffTag::ImageDescription)) {
+            Some(entry) => entry,
+            None => return Ok(None),
+        };
+
+        if entry.count > MAX_DESCRIPTION_BYTES {
+            return Ok(None);
+        }
+
+        match ValueReader::new(reader, header).read_string(entry).await {
+            Ok(description) => Ok(Some(description)),
+            // A malformed description should not make the pyramid unreadable
+            Err(TiffError::InvalidTagValue { .. }) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
     /// Build the pyramid structure from parsed IFDs.
-    fn build_pyramid(header: TiffHeader, ifds: Vec<Ifd>) -> Result<Self, TiffError> {
+    ///
+    /// When `members` is given, only those IFDs may become pyramid levels.
+    fn build_pyramid(
+        header: TiffHeader,
+        ifds: Vec<Ifd>,
+        layout: PyramidLayout,
+        members: Option<&[usize]>,
+        options: &PyramidDetectOptions,
+    ) -> Result<Self, TiffError> {
         let byte_order = header.byte_order;
 
         let mut pyramid_candidates: Vec<PyramidLevel> = Vec::new();
         let mut other_ifds: Vec<(usize, Ifd)> = Vec::new();
 
         for (ifd_index, ifd) in ifds.into_iter().enumerate() {
+            if members.is_some_and(|members| !members.contains(&ifd_index)) {
+                other_ifds.push((ifd_index, ifd));
+                continue;
+            }
+
             // Try to create a pyramid level from this IFD
-            if let Some(level) = PyramidLevel::from_ifd(ifd.clone(), ifd_index, byte_order) {
-                // Check if this looks like a pyramid level
-                if Self::is_pyramid_candidate(&level) {
+            let level = match PyramidLevel::from_ifd(ifd.clone(), ifd_index, byte_order) {
+                None if options.stripped_levels => {
+                    PyramidLevel::from_stripped_ifd(ifd.clone(), ifd_index, byte_order)
+                }
+                level => level,
+            };
+            if let Some(level) = level {
+                // Check if this looks like a pyramid level; IFDs named by the
+                // layout only need tile data
+                let is_level = if members.is_some() || options.permissive {
+                    level.has_tile_data()
+                } else {
+                    Self::is_pyramid_candidate(&level, options)
+                };
+                if is_level {
                     pyramid_candidates.push(level);
                 } else {
                     other_ifds.push((ifd_index, ifd));
@@ -301,20 +639,28 @@ impl TiffPyramid {
             area_b.cmp(&area_a)
         });
 
-        // Filter to keep only levels that form a consistent pyramid
-        let levels = Self::filter_pyramid_levels(pyramid_candidates);
+        // Filter to keep only levels that form a consistent pyramid; the
+        // rest stay listed as other images
+        let (levels, rejected) = Self::filter_pyramid_levels(pyramid_candidates, options);
+        other_ifds.extend(
+            rejected
+                .into_iter()
+                .map(|level| (level.ifd_index, level.ifd)),
+        );
+        other_ifds.sort_by_key(|(ifd_index, _)| *ifd_index);
 
         Ok(TiffPyramid {
             header,
             levels,
             other_ifds,
+            layout,
         })
     }
 
     /// Check if a level looks like a pyramid candidate (vs label/macro).
-    fn is_pyramid_candidate(level: &PyramidLevel) -> bool {
+    fn is_pyramid_candidate(level: &PyramidLevel, options: &PyramidDetectOptions) -> bool {
         // Must have minimum dimensions
-        if level.width < MIN_PYRAMID_DIMENSION || level.height < MIN_PYRAMID_DIMENSION {
+        if level.width < options.min_dimension || level.height < options.min_dimension {
             return false;
         }
 
@@ -324,7 +670,10 @@ impl TiffPyramid {
         }
 
         // Exclude likely label images (small and square-ish)
-        if level.width <= MAX_LABEL_DIMENSION && level.height <= MAX_LABEL_DIMENSION {
+        if options.exclude_labels
+            && level.width <= MAX_LABEL_DIMENSION
+            && level.height <= MAX_LABEL_DIMENSION
+        {
             let aspect_ratio = level.width as f64 / level.height as f64;
             // Labels are often square or nearly square
             if aspect_ratio > 0.5 && aspect_ratio < 2.0 {
@@ -339,9 +688,14 @@ impl TiffPyramid {
     }
 
     /// Filter candidates to keep only levels that form a consistent pyramid.
-    fn filter_pyramid_levels(candidates: Vec<PyramidLevel>) -> Vec<PyramidLevel> {
+    ///
+    /// Returns the levels and the rejected candidates.
+    fn filter_pyramid_levels(
+        candidates: Vec<PyramidLevel>,
+        options: &PyramidDetectOptions,
+    ) -> (Vec<PyramidLevel>, Vec<PyramidLevel>) {
         if candidates.is_empty() {
-            return candidates;
+            return (candidates, Vec::new());
         }
 
         // The largest image is always level 0
@@ -349,6 +703,7 @@ impl TiffPyramid {
         let base_height = candidates[0].height as f64;
 
         let mut levels = Vec::new();
+        let mut rejected = Vec::new();
 
         for (idx, mut level) in candidates.into_iter().enumerate() {
             // Calculate downsample factor
@@ -360,18 +715,22 @@ impl TiffPyramid {
 
             // Check if this level has a reasonable downsample factor
             // Pyramid levels typically have power-of-2 or power-of-4 downsamples
-            if Self::is_valid_downsample(downsample, idx) {
+            if options.permissive
+                || Self::is_valid_downsample(downsample, idx, options.downsample_tolerance)
+            {
                 level.level_index = levels.len();
                 level.downsample = downsample;
                 levels.push(level);
+            } else {
+                rejected.push(level);
             }
         }
 
-        levels
+        (levels, rejected)
     }
 
     /// Check if a downsample factor is valid for pyramid level.
-    fn is_valid_downsample(downsample: f64, level_idx: usize) -> bool {
+    fn is_valid_downsample(downsample: f64, level_idx: usize, tolerance: f64) -> bool {
         if level_idx == 0 {
             // First level should have downsample ~1.0
             return (downsample - 1.0).abs() < 0.1;
@@ -391,8 +750,7 @@ impl TiffPyramid {
         let expected = 2.0_f64.powf(rounded);
         let ratio = downsample / expected;
 
-        // Allow 20% tolerance
-        ratio > 0.8 && ratio < 1.2
+        ratio > 1.0 - tolerance && ratio < 1.0 + tolerance
     }
 
     /// Get the number of pyramid levels.
@@ -428,11 +786,108 @@ impl TiffPyramid {
     }
 }
 
+// =============================================================================
+// Leica SCN Collection XML
+// =============================================================================
+
+/// Return the IFD indices of the main image in a Leica SCN collection, from
+/// highest to lowest resolution.
+///
+/// A collection holds a macro overview plus one or more scanned regions, each
+/// an `<image>` whose `<pixels>` list one `<dimension>` per resolution. The
+/// macro is the image whose `<view>` spans the whole collection; of the
+/// others, the one with the largest full resolution is the main image. For
+/// fluorescence scans only the first channel and focal plane are used.
+fn scn_main_image_ifds(xml: &str) -> Option<Vec<usize>> {
+    let collection_size = xml.split("<collection").nth(1).map(|tag| {
+        let tag = tag.split('>').next().unwrap_or(tag);
+        (xml_attr(tag, "sizeX"), xml_attr(tag, "sizeY"))
+    });
+    let mut best: Option<(u64, Vec<usize>)> = None;
+
+    for image in xml.split("<image").skip(1) {
+        // Skip elements that merely share the prefix (e.g. <imageX>)
+        if !image.starts_with(|c: char| c.is_whitespace() || c == '>') {
+            continue;
+        }
+        let image = image.split("</image>").next().unwrap_or(image);
+
+        // Skip the macro overview
+        let view_size = image.split("<view").nth(1).map(|tag| {
+            let tag = tag.split('>').next().unwrap_or(tag);
+            (xml_attr(tag, "sizeX"), xml_attr(tag, "sizeY"))
+        });
+        if view_size.is_some() && view_size == collection_size {
+            continue;
+        }
+
+        // (resolution, area, ifd) for the first channel and focal plane
+        let mut dimensions: Vec<(u32, u64, usize)> = Vec::new();
+        for tag in image.split("<dimension").skip(1) {
+            let tag = tag.split('>').next().unwrap_or(tag);
+            let plane = |name| xml_attr(tag, name).map_or(Some(0), |v| v.parse::<u32>().ok());
+            if plane("c") != Some(0) || plane("z") != Some(0) {
+                continue;
+            }
+
+            let (Some(width), Some(height), Some(ifd)) = (
+                xml_attr(tag, "sizeX").and_then(|v| v.parse::<u64>().ok()),
+                xml_attr(tag, "sizeY").and_then(|v| v.parse::<u64>().ok()),
+                xml_attr(tag, "ifd").and_then(|v| v.parse::<usize>().ok()),
+            ) else {
+                continue;
+            };
+            let resolution = plane("r").unwrap_or(0);
+            dimensions.push((resolution, width * height, ifd));
+        }
+
+        dimensions.sort_by_key(|&(resolution, _, _)| resolution);
+        let Some(&(_, area, _)) = dimensions.first() else {
+            continue;
+        };
+
+        if best
+            .as_ref()
+            .is_some_and(|(best_area, _)| *best_area >= area)
+        {
+            continue;
+        }
+        let ifds = dimensions.into_iter().map(|(_, _, ifd)| ifd).collect();
+        best = Some((area, ifds));
+    }
+
+    best.map(|(_, ifds)| ifds)
+}
+
+/// Read an attribute value from the inside of an XML start tag.
+fn xml_attr<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let mut rest = tag;
+    while let Some(pos) = rest.find(name) {
+        let before = rest[..pos].chars().next_back();
+        let after = &rest[pos + name.len()..];
+        if before.is_some_and(char::is_whitespace) {
+            if let Some(value) = after.trim_start().strip_prefix('=') {
+                let value = value.trim_start();
+                let quote = value.chars().next()?;
+                if quote == '"' || quote == '\'' {
+                    let value = &value[1..];
+                    return value.find(quote).map(|end| &value[..end]);
+                }
+            }
+        }
+        rest = after;
+    }
+    None
+}
+
 // =============================================================================
 // Tile Data Loading
 // =============================================================================
 
 /// Loaded tile data for a pyramid level.
+///
+/// For strip-organized levels, the offsets and byte counts are those of the
+/// strips.
 #[derive(Debug, Clone)]
 pub struct TileData {
     /// Byte offset of each tile in the file
@@ -452,10 +907,22 @@ impl TileData {
         level: &PyramidLevel,
         header: &TiffHeader,
     ) -> Result<Self, TiffError> {
-        let value_reader = ValueReader::new(reader, header);
+        Self::load_with_limits(reader, level, header, &TiffLimits::DEFAULT).await
+    }
+
+    /// Load tile data for a pyramid level, enforcing the tile count and
+    /// JPEGTables size limits before anything is read.
+    pub async fn load_with_limits<R: RangeReader>(
+        reader: &R,
+        level: &PyramidLevel,
+        header: &TiffHeader,
+        limits: &TiffLimits,
+    ) -> Result<Self, TiffError> {
+        let value_reader = ValueReader::new(reader, header).with_limits(*limits);
 
         // Load tile offsets
         let offsets = if let Some(ref entry) = level.tile_offsets_entry {
+            limits.check_tile_count(entry.count)?;
             value_reader.read_u64_array(entry).await?
         } else {
             return Err(TiffError::MissingTag("TileOffsets"));
@@ -463,6 +930,7 @@ impl TileData {
 
         // Load tile byte counts
         let byte_counts = if let Some(ref entry) = level.tile_byte_counts_entry {
+            limits.check_tile_count(entry.count)?;
             value_reader.read_u64_array(entry).await?
         } else {
             return Err(TiffError::MissingTag("TileByteCounts"));
@@ -470,6 +938,7 @@ impl TileData {
 
         // Load JPEGTables if present
         let jpeg_tables = if let Some(ref entry) = level.jpeg_tables_entry {
+            limits.check_jpeg_tables_bytes(entry.value_byte_size().unwrap_or(entry.count))?;
             Some(value_reader.read_raw_bytes(entry).await?)
         } else {
             None
@@ -499,6 +968,8 @@ impl TileData {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::error::IoError;
+    use async_trait::async_trait;
 
     fn make_tiff_header() -> TiffHeader {
         TiffHeader {
@@ -530,6 +1001,7 @@ mod tests {
             tile_offsets_entry: None,
             tile_byte_counts_entry: None,
             jpeg_tables_entry: None,
+            strip_rows: None,
         };
 
         // Valid indices
@@ -561,6 +1033,7 @@ mod tests {
             tile_offsets_entry: None,
             tile_byte_counts_entry: None,
             jpeg_tables_entry: None,
+            strip_rows: None,
         };
 
         // Full tiles
@@ -582,28 +1055,37 @@ mod tests {
 
     #[test]
     fn test_is_valid_downsample() {
+        let valid = |downsample, level_idx| {
+            TiffPyramid::is_valid_downsample(downsample, level_idx, DEFAULT_DOWNSAMPLE_TOLERANCE)
+        };
+
         // Level 0 should be ~1.0
-        assert!(TiffPyramid::is_valid_downsample(1.0, 0));
-        assert!(TiffPyramid::is_valid_downsample(1.05, 0));
-        assert!(!TiffPyramid::is_valid_downsample(2.0, 0));
+        assert!(valid(1.0, 0));
+        assert!(valid(1.05, 0));
+        assert!(!valid(2.0, 0));
 
         // Level 1+ should be powers of 2
-        assert!(TiffPyramid::is_valid_downsample(2.0, 1));
-        assert!(TiffPyramid::is_valid_downsample(4.0, 2));
-        assert!(TiffPyramid::is_valid_downsample(8.0, 3));
-        assert!(TiffPyramid::is_valid_downsample(16.0, 4));
+        assert!(valid(2.0, 1));
+        assert!(valid(4.0, 2));
+        assert!(valid(8.0, 3));
+        assert!(valid(16.0, 4));
 
         // Allow some tolerance
-        assert!(TiffPyramid::is_valid_downsample(2.1, 1));
-        assert!(TiffPyramid::is_valid_downsample(3.9, 2));
+        assert!(valid(2.1, 1));
+        assert!(valid(3.9, 2));
 
         // Reject values too far off
-        assert!(!TiffPyramid::is_valid_downsample(1.5, 1)); // Not close to 2
-        assert!(!TiffPyramid::is_valid_downsample(3.0, 2)); // Not close to 4
+        assert!(!valid(1.5, 1)); // Not close to 2
+        assert!(!valid(3.0, 2)); // Not close to 4
+
+        // A wider tolerance accepts 3x-downsampled levels
+        assert!(TiffPyramid::is_valid_downsample(3.0, 2, 0.3));
     }
 
     #[test]
     fn test_is_pyramid_candidate() {
+        let strict = PyramidDetectOptions::strict();
+
         // Large enough, has tile data
         let good_level = PyramidLevel {
             level_index: 0,
@@ -621,8 +1103,9 @@ mod tests {
             tile_offsets_entry: Some(create_mock_entry()),
             tile_byte_counts_entry: Some(create_mock_entry()),
             jpeg_tables_entry: None,
+            strip_rows: None,
         };
-        assert!(TiffPyramid::is_pyramid_candidate(&good_level));
+        assert!(TiffPyramid::is_pyramid_candidate(&good_level, &strict));
 
         // Too small
         let small_level = PyramidLevel {
@@ -630,14 +1113,14 @@ mod tests {
             height: 100,
             ..good_level.clone()
         };
-        assert!(!TiffPyramid::is_pyramid_candidate(&small_level));
+        assert!(!TiffPyramid::is_pyramid_candidate(&small_level, &strict));
 
         // No tile data
         let no_tiles = PyramidLevel {
             tile_offsets_entry: None,
             ..good_level.clone()
         };
-        assert!(!TiffPyramid::is_pyramid_candidate(&no_tiles));
+        assert!(!TiffPyramid::is_pyramid_candidate(&no_tiles, &strict));
 
         // Label-like (small and square)
         let label_like = PyramidLevel {
@@ -648,7 +1131,13 @@ mod tests {
             tile_count: 4,
             ..good_level.clone()
         };
-        assert!(!TiffPyramid::is_pyramid_candidate(&label_like));
+        assert!(!TiffPyramid::is_pyramid_candidate(&label_like, &strict));
+
+        let keep_labels = PyramidDetectOptions {
+            exclude_labels: false,
+            ..strict
+        };
+        assert!(TiffPyramid::is_pyramid_candidate(&label_like, &keep_labels));
     }
 
     #[test]
@@ -662,6 +1151,7 @@ mod tests {
                 create_level_with_downsample(2, 16.0, 625, 500),
             ],
             other_ifds: vec![],
+            layout: PyramidLayout::IfdChain,
         };
 
         // Exact matches
@@ -701,10 +1191,476 @@ mod tests {
         );
     }
 
+    // -------------------------------------------------------------------------
+    // Pyramid layout tests
+    // -------------------------------------------------------------------------
+
+    const SCN_XML: &str = r#"<?xml version="1.0"?>
+<scn xmlns="http://www.leica-microsystems.com/scn/2010/10/01">
+  <collection name="slide" sizeX="20000000" sizeY="60000000">
+    <image name="macro">
+      <view sizeX="20000000" sizeY="60000000" offsetX="0" offsetY="0"/>
+      <pixels sizeX="1616" sizeY="4668">
+        <dimension sizeX="1616" sizeY="4668" r="0" ifd="0"/>
+      </pixels>
+    </image>
+    <image name="region">
+      <view sizeX="500000" sizeY="500000" offsetX="1000000" offsetY="2000000"/>
+      <pixels sizeX="1024" sizeY="1024">
+        <dimension sizeX="1024" sizeY="1024" r="0" c="0" ifd="1"/>
+        <dimension sizeX="1024" sizeY="1024" r="0" c="1" ifd="4"/>
+        <dimension sizeX="256" sizeY="256" r="2" c="0" ifd="3"/>
+        <dimension sizeX="512" sizeY="512" r="1" c="0" ifd="2"/>
+      </pixels>
+    </image>
+  </collection>
+</scn>"#;
+
+    #[test]
+    fn test_scn_main_image_ifds() {
+        // Macro is skipped, second channel is ignored, levels ordered by r
+        assert_eq!(scn_main_image_ifds(SCN_XML), Some(vec![1, 2, 3]));
+
+        assert_eq!(scn_main_image_ifds("<scn><collection/></scn>"), None);
+    }
+
+    #[test]
+    fn test_xml_attr() {
+        let tag = r#" sizeX="1024" sizeY='512' r = "2" ifd="7"/"#;
+        assert_eq!(xml_attr(tag, "sizeX"), Some("1024"));
+        assert_eq!(xml_attr(tag, "sizeY"), Some("512"));
+        assert_eq!(xml_attr(tag, "r"), Some("2"));
+        assert_eq!(xml_attr(tag, "ifd"), Some("7"));
+        assert_eq!(xml_attr(tag, "X"), None);
+        assert_eq!(xml_attr(tag, "c"), None);
+    }
+
+    #[tokio::test]
+    async fn test_parse_sub_ifd_pyramid() {
+        let mut tiff = TestTiff::new();
+
+        // Reduced resolutions only reachable through the base IFD's SubIFDs
+        let sub1 = tiff.push_level(512, 512, &[]);
+        let sub2 = tiff.push_level(256, 256, &[]);
+        let sub_offsets = tiff.push_u32s(&[sub1, sub2]);
+
+        // Second series in the main chain (e.g. a macro image)
+        let macro_entries = tiff.level_entries(1200, 600, &[]);
+        let macro_ifd = tiff.push_ifd(&macro_entries, 0);
+
+        let base_entries = tiff.level_entries(1024, 1024, &[(330, 13, 2, sub_offsets)]);
+        let base = tiff.push_ifd(&base_entries, macro_ifd);
+        tiff.set_first_ifd(base);
+
+        let pyramid = TiffPyramid::parse(&tiff.reader()).await.unwrap();
+
+        assert_eq!(pyramid.layout, PyramidLayout::SubIfds);
+        assert_eq!(pyramid.level_count(), 3);
+        assert_eq!(pyramid.levels[0].width, 1024);
+        assert_eq!(pyramid.levels[0].ifd_index, 0);
+        assert_eq!(pyramid.levels[1].width, 512);
+        assert_eq!(pyramid.levels[1].ifd_index, 2);
+        assert_eq!(pyramid.levels[2].width, 256);
+        assert_eq!(pyramid.levels[2].downsample, 4.0);
+
+        // The macro series is not part of the pyramid
+        assert_eq!(pyramid.other_ifds.len(), 1);
+        assert_eq!(pyramid.other_ifds[0].0, 1);
+    }
+
+    #[tokio::test]
+    async fn test_parse_scn_pyramid() {
+        let mut tiff = TestTiff::new();
+
+        let xml = tiff.push(format!("{SCN_XML}\0").as_bytes());
+        let xml_len = SCN_XML.len() as u32 + 1;
+
+        // IFD chain: macro, region levels (r=0, 1, 2), second channel
+        let channel = tiff.push_level(1024, 1024, &[]);
+        let mut next = channel;
+        for size in [256, 512, 1024] {
+            let entries = tiff.level_entries(size, size, &[]);
+            next = tiff.push_ifd(&entries, next);
+        }
+        let macro_entries = tiff.level_entries(1616, 4668, &[(270, 2, xml_len, xml)]);
+        let first = tiff.push_ifd(&macro_entries, next);
+        tiff.set_first_ifd(first);
+
+        let pyramid = TiffPyramid::parse(&tiff.reader()).await.unwrap();
+
+        // The macro is the largest tiled image but not a pyramid level
+        assert_eq!(pyramid.layout, PyramidLayout::LeicaScn);
+        assert_eq!(pyramid.level_count(), 3);
+        let indices: Vec<usize> = pyramid.levels.iter().map(|l| l.ifd_index).collect();
+        assert_eq!(indices, vec![1, 2, 3]);
+        assert_eq!(pyramid.dimensions(), Some((1024, 1024)));
+    }
+
+    #[tokio::test]
+    async fn test_parse_ifd_chain_pyramid() {
+        let mut tiff = TestTiff::new();
+
+        let level1_entries = tiff.level_entries(1024, 1024, &[]);
+        let level1 = tiff.push_ifd(&level1_entries, 0);
+        let base_entries = tiff.level_entries(2048, 2048, &[]);
+        let base = tiff.push_ifd(&base_entries, level1);
+        tiff.set_first_ifd(base);
+
+        let pyramid = TiffPyramid::parse(&tiff.reader()).await.unwrap();
+
+        assert_eq!(pyramid.layout, PyramidLayout::IfdChain);
+        assert_eq!(pyramid.level_count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_stripped_levels() {
+        let mut tiff = TestTiff::new();
+
+        // A single 8192x8192 strip is too large to decode whole
+        let oversized = tiff.push_ifd(
+            &[
+                (256, 4, 1, 8192),
+                (257, 4, 1, 8192),
+                (259, 3, 1, 7),
+                (273, 4, 1, 0),
+                (279, 4, 1, 1),
+            ],
+            0,
+        );
+
+        // 2048x1024 JPEG in 16 strips of 64 rows, with a tiled level below
+        let level1_entries = tiff.level_entries(1024, 512, &[]);
+        let level1 = tiff.push_ifd(&level1_entries, oversized);
+        let offsets = tiff.push_u32s(&[0; 16]);
+        let counts = tiff.push_u32s(&[1; 16]);
+        let base = tiff.push_ifd(
+            &[
+                (256, 4, 1, 2048),
+                (257, 4, 1, 1024),
+                (259, 3, 1, 7),
+                (273, 4, 16, offsets),
+                (278, 3, 1, 64),
+                (279, 4, 16, counts),
+            ],
+            level1,
+        );
+        tiff.set_first_ifd(base);
+        let reader = tiff.reader();
+
+        // Strips are only considered when asked for
+        let pyramid = TiffPyramid::parse(&reader).await.unwrap();
+        assert_eq!(pyramid.level_count(), 1);
+        assert_eq!(pyramid.other_ifds.len(), 2);
+
+        let options = PyramidDetectOptions::strict().with_stripped_levels(true);
+        let pyramid = TiffPyramid::parse_with_options(&reader, &options)
+            .await
+            .unwrap();
+        assert_eq!(pyramid.level_count(), 2);
+
+        let base = &pyramid.levels[0];
+        assert!(base.is_stripped());
+        assert_eq!(base.strip_rows, Some(64));
+        assert_eq!((base.tile_width, base.tile_height), (256, 256));
+        assert_eq!((base.tiles_x, base.tiles_y), (8, 4));
+        assert!(!pyramid.levels[1].is_stripped());
+        assert_eq!(pyramid.other_ifds.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_permissive_detection_keeps_3x_levels() {
+        let mut tiff = TestTiff::new();
+
+        let level1_entries = tiff.level_entries(1024, 1024, &[]);
+        let level1 = tiff.push_ifd(&level1_entries, 0);
+        let base_entries = tiff.level_entries(3072, 3072, &[]);
+        let base = tiff.push_ifd(&base_entries, level1);
+        tiff.set_first_ifd(base);
+        let reader = tiff.reader();
+
+        // Strict mode drops the 3x level
+        let strict = TiffPyramid::parse(&reader).await.unwrap();
+        assert_eq!(strict.level_count(), 1);
+        assert_eq!(strict.other_ifds.len(), 1);
+
+        let permissive =
+            TiffPyramid::parse_with_options(&reader, &PyramidDetectOptions::permissive())
+                .await
+                .unwrap();
+        assert_eq!(permissive.level_count(), 2);
+        assert_eq!(permissive.levels[1].downsample, 3.0);
+
+        let tolerant = PyramidDetectOptions::strict().with_downsample_tolerance(0.3);
+        let tolerant = TiffPyramid::parse_with_options(&reader, &tolerant)
+            .await
+            .unwrap();
+        assert_eq!(tolerant.level_count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_limits_reject_oversized_structures() {
+        let mut tiff = TestTiff::new();
+        let tables = tiff.push(&[0; 64]);
+        let base = tiff.push_level(2048, 2048, &[(347, 7, 64, tables)]);
+        tiff.set_first_ifd(base);
+        let reader = tiff.reader();
+
+        let limited = |limits: TiffLimits| PyramidDetectOptions::default().with_limits(limits);
+
+        // The IFD has 8 entries
+        let options = limited(TiffLimits {
+            max_ifd_entries: 7,
+            ..TiffLimits::DEFAULT
+        });
+        let err = TiffPyramid::parse_with_options(&reader, &options)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            TiffError::LimitExceeded {
+                limit: "max_ifd_entries",
+                value: 8,
+                max: 7
+            }
+        ));
+
+        let pyramid = TiffPyramid::parse(&reader).await.unwrap();
+        let level = &pyramid.levels[0];
+        assert!(TileData::load(&reader, level, &pyramid.header)
+            .await
+            .is_ok());
+
+        // The level has 64 tiles and 64 bytes of JPEGTables
+        for (limits, name) in [
+            (
+                TiffLimits {
+                    max_tile_count: 63,
+                    ..TiffLimits::DEFAULT
+                },
+                "max_tile_count",
+            ),
+            (
+                TiffLimits {
+                    max_jpeg_tables_bytes: 63,
+                    ..TiffLimits::DEFAULT
+                },
+                "max_jpeg_tables_bytes",
+            ),
+            (
+                TiffLimits {
+                    max_array_bytes: 255,
+                    ..TiffLimits::DEFAULT
+                },
+                "max_array_bytes",
+            ),
+        ] {
+            let err = TileData::load_with_limits(&reader, level, &pyramid.header, &limits)
+                .await
+                .unwrap_err();
+            assert!(
+                matches!(err, TiffError::LimitExceeded { limit, .. } if limit == name),
+                "{}: {:?}",
+                name,
+                err
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_huge_declared_arrays_are_not_read() {
+        let mut tiff = TestTiff::new();
+        let data = tiff.push(&[0; 16]);
+        // A 1024x1024 level whose arrays claim a billion entries
+        let base = tiff.push_ifd(
+            &[
+                (256, 4, 1, 1024),
+                (257, 4, 1, 1024),
+                (259, 3, 1, 7),
+                (322, 4, 1, 256),
+                (323, 4, 1, 256),
+                (324, 4, 1 << 30, data),
+                (325, 4, 1 << 30, data),
+                (347, 7, u32::MAX, data),
+            ],
+            0,
+        );
+        tiff.set_first_ifd(base);
+        let reader = tiff.reader();
+
+        let pyramid = TiffPyramid::parse(&reader).await.unwrap();
+        let err = TileData::load(&reader, &pyramid.levels[0], &pyramid.header)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            TiffError::LimitExceeded {
+                limit: "max_tile_count",
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_mutated_files_never_panic() {
+        let mut tiff = TestTiff::new();
+        let level1 = tiff.push_level(1024, 1024, &[]);
+        let base_entries = tiff.level_entries(2048, 2048, &[]);
+        let base = tiff.push_ifd(&base_entries, level1);
+        tiff.set_first_ifd(base);
+        let original = tiff.data;
+
+        // Deterministic xorshift so failures are reproducible
+        let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+
+        for _ in 0..2000 {
+            let mut data = original.clone();
+            for _ in 0..1 + next() % 8 {
+                let at = (next() % data.len() as u64) as usize;
+                data[at] = match next() % 4 {
+                    0 => 0,
+                    1 => 0xff,
+                    _ => next() as u8,
+                };
+            }
+
+            let reader = MemoryReader { data };
+            let options = PyramidDetectOptions::permissive().with_stripped_levels(true);
+            if let Ok(pyramid) = TiffPyramid::parse_with_options(&reader, &options).await {
+                for level in &pyramid.levels {
+                    let _ = TileData::load(&reader, level, &pyramid.header).await;
+                }
+            }
+        }
+    }
+
     // -------------------------------------------------------------------------
     // Helper functions for tests
     // -------------------------------------------------------------------------
 
+    /// In-memory reader over a test file.
+    struct MemoryReader {
+        data: Vec<u8>,
+    }
+
+    #[async_trait]
+    impl RangeReader for MemoryReader {
+        async fn read_exact_at(&self, offset: u64, len: usize) -> Result<Bytes, IoError> {
+            let start = offset as usize;
+            let end = start.saturating_add(len);
+            if end > self.data.len() {
+                return Err(IoError::RangeOutOfBounds {
+                    offset,
+                    requested: len as u64,
+                    size: self.data.len() as u64,
+                });
+            }
+            Ok(Bytes::copy_from_slice(&self.data[start..end]))
+        }
+
+        fn size(&self) -> u64 {
+            self.data.len() as u64
+        }
+
+        fn identifier(&self) -> &str {
+            "memory://pyramid"
+        }
+    }
+
+    /// Minimal little-endian classic TIFF writer for layout tests.
+    ///
+    /// Entries are `(tag, type, count, value)` where `value` is either the
+    /// inline value or the offset of data pushed earlier.
+    struct TestTiff {
+        data: Vec<u8>,
+    }
+
+    impl TestTiff {
+        const TILE_SIZE: u32 = 256;
+
+        fn new() -> Self {
+            let mut data = vec![b'I', b'I', 42, 0, 0, 0, 0, 0];
+            // Keep the file above the BigTIFF header size read up front
+            data.resize(16, 0);
+            Self { data }
+        }
+
+        fn push(&mut self, bytes: &[u8]) -> u32 {
+            let offset = self.data.len() as u32;
+            self.data.extend_from_slice(bytes);
+            offset
+        }
+
+        fn push_u32s(&mut self, values: &[u32]) -> u32 {
+            let bytes: Vec<u8> = values.iter().flat_map(|v| v.to_le_bytes()).collect();
+            self.push(&bytes)
+        }
+
+        fn push_ifd(&mut self, entries: &[(u16, u16, u32, u32)], next: u32) -> u32 {
+            let mut entries = entries.to_vec();
+            entries.sort_by_key(|e| e.0);
+
+            let mut bytes = (entries.len() as u16).to_le_bytes().to_vec();
+            for (tag, field_type, count, value) in entries {
+                bytes.extend_from_slice(&tag.to_le_bytes());
+                bytes.extend_from_slice(&field_type.to_le_bytes());
+                bytes.extend_from_slice(&count.to_le_bytes());
+                bytes.extend_from_slice(&value.to_le_bytes());
+            }
+            bytes.extend_from_slice(&next.to_le_bytes());
+            self.push(&bytes)
+        }
+
+        /// Entries of a tiled JPEG image whose tiles all point at one byte.
+        fn level_entries(
+            &mut self,
+            width: u32,
+            height: u32,
+            extra: &[(u16, u16, u32, u32)],
+        ) -> Vec<(u16, u16, u32, u32)> {
+            let tiles = width.div_ceil(Self::TILE_SIZE) * height.div_ceil(Self::TILE_SIZE);
+            let tile_data = self.push(&[0]);
+            let (offsets, counts) = if tiles == 1 {
+                (tile_data, 1)
+            } else {
+                (
+                    self.push_u32s(&vec![tile_data; tiles as usize]),
+                    self.push_u32s(&vec![1; tiles as usize]),
+                )
+            };
+
+            let mut entries = vec![
+                (256, 4, 1, width),
+                (257, 4, 1, height),
+                (259, 3, 1, 7),
+                (322, 4, 1, Self::TILE_SIZE),
+                (323, 4, 1, Self::TILE_SIZE),
+                (324, 4, tiles, offsets),
+                (325, 4, tiles, counts),
+            ];
+            entries.extend_from_slice(extra);
+            entries
+        }
+
+        fn push_level(&mut self, width: u32, height: u32, extra: &[(u16, u16, u32, u32)]) -> u32 {
+            let entries = self.level_entries(width, height, extra);
+            self.push_ifd(&entries, 0)
+        }
+
+        fn set_first_ifd(&mut self, offset: u32) {
+            self.data[4..8].copy_from_slice(&offset.to_le_bytes());
+        }
+
+        fn reader(self) -> MemoryReader {
+            MemoryReader { data: self.data }
+        }
+    }
+
     fn create_mock_ifd() -> Ifd {
         Ifd::empty()
     }
@@ -742,6 +1698,7 @@ mod tests {
             tile_offsets_entry: Some(create_mock_entry()),
             tile_byte_counts_entry: Some(create_mock_entry()),
             jpeg_tables_entry: None,
+            strip_rows: None,
         }
     }
 }
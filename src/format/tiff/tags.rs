@@ -17,7 +17,7 @@
 /// - Reading arrays of values correctly
 ///
 /// Note: We only define types actually used in WSI files. TIFF supports
-/// additional types (RATIONAL, FLOAT, etc.) that are not needed here.
+/// additional types (SRATIONAL, FLOAT, etc.) that are not needed here.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(u16)]
 pub enum FieldType {
@@ -33,9 +33,18 @@ pub enum FieldType {
     /// Unsigned 32-bit integer (4 bytes)
     Long = 4,
 
+    /// Two unsigned 32-bit integers, numerator and denominator (8 bytes)
+    Rational = 5,
+
     /// Unsigned 64-bit integer (8 bytes) - BigTIFF only
     Long8 = 16,
 
+    /// 32-bit IFD offset (4 bytes), used by SubIFDs
+    Ifd = 13,
+
+    /// 64-bit IFD offset (8 bytes) - BigTIFF only, used by SubIFDs
+    Ifd8 = 18,
+
     /// Undefined byte data (1 byte per element)
     Undefined = 7,
 }
@@ -53,7 +62,10 @@ impl FieldType {
             FieldType::Ascii => 1,
             FieldType::Short => 2,
             FieldType::Long => 4,
+            FieldType::Rational => 8,
             FieldType::Long8 => 8,
+            FieldType::Ifd => 4,
+            FieldType::Ifd8 => 8,
             FieldType::Undefined => 1,
         }
     }
@@ -67,8 +79,11 @@ impl FieldType {
             2 => Some(FieldType::Ascii),
             3 => Some(FieldType::Short),
             4 => Some(FieldType::Long),
+            5 => Some(FieldType::Rational),
             7 => Some(FieldType::Undefined),
+            13 => Some(FieldType::Ifd),
             16 => Some(FieldType::Long8),
+            18 => Some(FieldType::Ifd8),
             _ => None,
         }
     }
@@ -93,7 +108,7 @@ impl FieldType {
     /// `true` if the total value size fits in the inline value field.
     #[inline]
     pub fn fits_inline(self, count: u64, is_bigtiff: bool) -> bool {
-        let total_size = self.size_in_bytes() as u64 * count;
+        let total_size = (self.size_in_bytes() as u64).saturating_mul(count);
         let threshold = if is_bigtiff {
             Self::INLINE_THRESHOLD_BIGTIFF as u64
         } else {
@@ -141,12 +156,24 @@ pub enum TiffTag {
     /// Description string (contains metadata in SVS files)
     ImageDescription = 270,
 
+    /// Scanner manufacturer
+    Make = 271,
+
+    /// Scanner model
+    Model = 272,
+
+    /// How stored rows and columns map onto the displayed image
+    Orientation = 274,
+
     /// Number of components per pixel (e.g., 3 for RGB)
     SamplesPerPixel = 277,
 
     /// How components are organized (chunky vs planar)
     PlanarConfiguration = 284,
 
+    /// Prediction scheme applied before LZW/Deflate compression
+    Predictor = 317,
+
     // -------------------------------------------------------------------------
     // Strip Organization (used to detect unsupported files)
     // -------------------------------------------------------------------------
@@ -197,6 +224,21 @@ pub enum TiffTag {
 
     /// Unit of resolution (1=none, 2=inch, 3=centimeter)
     ResolutionUnit = 296,
+
+    /// Software that wrote the file
+    Software = 305,
+
+    /// Creation date and time ("YYYY:MM:DD HH:MM:SS")
+    DateTime = 306,
+
+    /// Embedded ICC color profile of the scanner
+    IccProfile = 34675,
+
+    // -------------------------------------------------------------------------
+    // Pyramid Structure
+    // -------------------------------------------------------------------------
+    /// Offsets of child IFDs holding reduced resolutions (OME-TIFF pyramids)
+    SubIfds = 330,
 }
 
 impl TiffTag {
@@ -212,7 +254,10 @@ impl TiffTag {
             259 => Some(TiffTag::Compression),
             262 => Some(TiffTag::PhotometricInterpretation),
             270 => Some(TiffTag::ImageDescription),
+            271 => Some(TiffTag::Make),
+            272 => Some(TiffTag::Model),
             273 => Some(TiffTag::StripOffsets),
+            274 => Some(TiffTag::Orientation),
             277 => Some(TiffTag::SamplesPerPixel),
             278 => Some(TiffTag::RowsPerStrip),
             279 => Some(TiffTag::StripByteCounts),
@@ -220,12 +265,17 @@ impl TiffTag {
             283 => Some(TiffTag::YResolution),
             284 => Some(TiffTag::PlanarConfiguration),
             296 => Some(TiffTag::ResolutionUnit),
+            305 => Some(TiffTag::Software),
+            306 => Some(TiffTag::DateTime),
+            317 => Some(TiffTag::Predictor),
             322 => Some(TiffTag::TileWidth),
             323 => Some(TiffTag::TileLength),
             324 => Some(TiffTag::TileOffsets),
             325 => Some(TiffTag::TileByteCounts),
+            330 => Some(TiffTag::SubIfds),
             347 => Some(TiffTag::JpegTables),
             530 => Some(TiffTag::YCbCrSubSampling),
+            34675 => Some(TiffTag::IccProfile),
             _ => None,
         }
     }
@@ -243,15 +293,16 @@ impl TiffTag {
 
 /// TIFF compression scheme identifiers.
 ///
-/// We support JPEG (value 7) and JPEG 2000 (value 33003) compression.
-/// Other compression schemes will result in HTTP 415 Unsupported Media Type.
+/// We support JPEG (value 7), JPEG 2000 (value 33003), LZW (value 5) and
+/// Deflate (values 8 and 32946) compression. Other compression schemes will
+/// result in HTTP 415 Unsupported Media Type.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(u16)]
 pub enum Compression {
     /// No compression
     None = 1,
 
-    /// LZW compression (not supported)
+    /// LZW compression (supported, decoded to raw pixels)
     Lzw = 5,
 
     /// "Old-style" JPEG (not supported, rarely used)
@@ -260,13 +311,13 @@ pub enum Compression {
     /// JPEG compression (supported)
     Jpeg = 7,
 
-    /// Deflate/zlib compression (not supported)
+    /// Deflate/zlib compression (supported, decoded to raw pixels)
     Deflate = 8,
 
-    /// Adobe Deflate (not supported)
+    /// Adobe Deflate (supported, decoded to raw pixels)
     AdobeDeflate = 32946,
 
-    /// JPEG 2000 (supported)
+    /// JPEG 2000 (supported with the `jpeg2000` feature)
     Jpeg2000 = 33003,
 }
 
@@ -288,9 +339,15 @@ impl Compression {
     }
 
     /// Check if this compression scheme is supported.
+    ///
+    /// JPEG 2000 is only supported in builds with the `jpeg2000` feature.
+    /// LZW and Deflate tiles are decoded by [`crate::format::codec`].
     #[inline]
     pub const fn is_supported(self) -> bool {
-        matches!(self, Compression::Jpeg | Compression::Jpeg2000)
+        matches!(
+            self,
+            Compression::Jpeg | Compression::Lzw | Compression::Deflate | Compression::AdobeDeflate
+        ) || (cfg!(feature = "jpeg2000") && matches!(self, Compression::Jpeg2000))
     }
 
     /// Get a human-readable name for the compression scheme.
@@ -325,8 +382,11 @@ mod tests {
         assert_eq!(FieldType::Ascii.size_in_bytes(), 1);
         assert_eq!(FieldType::Short.size_in_bytes(), 2);
         assert_eq!(FieldType::Long.size_in_bytes(), 4);
+        assert_eq!(FieldType::Rational.size_in_bytes(), 8);
         assert_eq!(FieldType::Long8.size_in_bytes(), 8);
         assert_eq!(FieldType::Undefined.size_in_bytes(), 1);
+        assert_eq!(FieldType::Ifd.size_in_bytes(), 4);
+        assert_eq!(FieldType::Ifd8.size_in_bytes(), 8);
     }
 
     #[test]
@@ -335,8 +395,11 @@ mod tests {
         assert_eq!(FieldType::from_u16(2), Some(FieldType::Ascii));
         assert_eq!(FieldType::from_u16(3), Some(FieldType::Short));
         assert_eq!(FieldType::from_u16(4), Some(FieldType::Long));
+        assert_eq!(FieldType::from_u16(5), Some(FieldType::Rational));
         assert_eq!(FieldType::from_u16(7), Some(FieldType::Undefined));
         assert_eq!(FieldType::from_u16(16), Some(FieldType::Long8));
+        assert_eq!(FieldType::from_u16(13), Some(FieldType::Ifd));
+        assert_eq!(FieldType::from_u16(18), Some(FieldType::Ifd8));
         // Unknown types
         assert_eq!(FieldType::from_u16(0), None);
         assert_eq!(FieldType::from_u16(99), None);
@@ -391,9 +454,15 @@ mod tests {
         assert_eq!(TiffTag::from_u16(323), Some(TiffTag::TileLength));
         assert_eq!(TiffTag::from_u16(324), Some(TiffTag::TileOffsets));
         assert_eq!(TiffTag::from_u16(325), Some(TiffTag::TileByteCounts));
+        assert_eq!(TiffTag::from_u16(330), Some(TiffTag::SubIfds));
 
         // JPEG tables
         assert_eq!(TiffTag::from_u16(347), Some(TiffTag::JpegTables));
+        assert_eq!(TiffTag::from_u16(317), Some(TiffTag::Predictor));
+
+        // Descriptive metadata
+        assert_eq!(TiffTag::from_u16(271), Some(TiffTag::Make));
+        assert_eq!(TiffTag::from_u16(306), Some(TiffTag::DateTime));
 
         // Strip tags (for detection)
         assert_eq!(TiffTag::from_u16(273), Some(TiffTag::StripOffsets));
@@ -427,10 +496,15 @@ mod tests {
     #[test]
     fn test_compression_is_supported() {
         assert!(Compression::Jpeg.is_supported());
-        assert!(Compression::Jpeg2000.is_supported());
+        assert_eq!(
+            Compression::Jpeg2000.is_supported(),
+            cfg!(feature = "jpeg2000")
+        );
+        assert!(Compression::Lzw.is_supported());
+        assert!(Compression::Deflate.is_supported());
+        assert!(Compression::AdobeDeflate.is_supported());
         assert!(!Compression::None.is_supported());
-        assert!(!Compression::Lzw.is_supported());
-        assert!(!Compression::Deflate.is_supported());
+        assert!(!Compression::OldJpeg.is_supported());
     }
 
     #[test]
@@ -8,14 +8,19 @@
 //!
 //! The following constraints define what slides are supported:
 //! - **Organization**: Tiled only (no strips)
-//! - **Compression**: JPEG or JPEG 2000 (no LZW, Deflate)
+//! - **Compression**: JPEG, JPEG 2000, or 8-bit LZW/Deflate
 //! - **Format**: Standard TIFF or BigTIFF
 //! - **Structure**: Must have tile offsets and byte counts tags
 //!
 //! Files outside this subset return appropriate errors that can be mapped
-//! to HTTP 415 Unsupported Media Type.
+//! to HTTP 415 Unsupported Media Type. A rejected pyramid reports every
+//! failed constraint ([`TiffError::Unsupported`]), each with a remediation
+//! hint, so the response tells the user exactly what to fix.
+
+use std::fmt;
 
 use crate::error::TiffError;
+use crate::format::codec::RawTileLayout;
 
 use super::parser::{ByteOrder, Ifd};
 use super::pyramid::{PyramidLevel, TiffPyramid};
@@ -70,12 +75,13 @@ impl ValidationResult {
 
     /// Convert to a TiffError if invalid.
     ///
-    /// Returns the first error as a TiffError, or Ok(()) if valid.
+    /// Returns [`TiffError::Unsupported`] listing every error, or Ok(()) if
+    /// valid.
     pub fn into_result(self) -> Result<(), TiffError> {
         if self.is_valid {
             Ok(())
         } else {
-            Err(self.errors.into_iter().next().unwrap().into())
+            Err(TiffError::Unsupported(self.errors))
         }
     }
 }
@@ -130,6 +136,102 @@ pub enum ValidationError {
     },
 }
 
+impl ValidationError {
+    /// Short identifier of the failed constraint (e.g. "strip_organization").
+    pub fn constraint(&self) -> &'static str {
+        match self {
+            ValidationError::MissingTag { .. } => "missing_tag",
+            ValidationError::StripOrganization { .. } => "strip_organization",
+            ValidationError::UnsupportedCompression { .. } => "unsupported_compression",
+            ValidationError::MissingTileTags { .. } => "missing_tile_tags",
+            ValidationError::NoPyramidLevels => "no_pyramid_levels",
+            ValidationError::InvalidTileDimensions { .. } => "invalid_tile_dimensions",
+        }
+    }
+
+    /// Index of the offending IFD, if the error concerns a single IFD.
+    pub fn ifd_index(&self) -> Option<usize> {
+        match self {
+            ValidationError::MissingTag { ifd_index, .. }
+            | ValidationError::StripOrganization { ifd_index }
+            | ValidationError::UnsupportedCompression { ifd_index, .. }
+            | ValidationError::MissingTileTags { ifd_index, .. }
+            | ValidationError::InvalidTileDimensions { ifd_index, .. } => Some(*ifd_index),
+            ValidationError::NoPyramidLevels => None,
+        }
+    }
+
+    /// How to make the slide servable.
+    pub fn remediation(&self) -> &'static str {
+        match self {
+            ValidationError::StripOrganization { .. } | ValidationError::NoPyramidLevels => {
+                "Re-save the image as a tiled pyramidal TIFF, e.g. \
+                `vips tiffsave input output.tif --tile --pyramid --compression jpeg --bigtiff`"
+            }
+            ValidationError::UnsupportedCompression { compression, .. }
+                if Compression::from_u16(*compression) == Some(Compression::Jpeg2000) =>
+            {
+                "Rebuild wsi-streamer with the `jpeg2000` feature, or re-encode the tiles \
+                as JPEG, e.g. `vips tiffsave input output.tif --tile --pyramid --compression jpeg`"
+            }
+            ValidationError::UnsupportedCompression { .. } => {
+                "Re-encode the tiles as JPEG, JPEG 2000 or 8-bit LZW/Deflate, e.g. \
+                `vips tiffsave input output.tif --tile --pyramid --compression jpeg`"
+            }
+            ValidationError::MissingTag { .. } | ValidationError::MissingTileTags { .. } => {
+                "The file is truncated or not a tiled TIFF; re-export it from the scanner \
+                software or re-save it with `vips tiffsave --tile --pyramid`"
+            }
+            ValidationError::InvalidTileDimensions { .. } => {
+                "Re-save the image with a standard tile size, e.g. \
+                `vips tiffsave input output.tif --tile --tile-width 256 --tile-height 256`"
+            }
+        }
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationError::MissingTag { ifd_index, tag } => {
+                write!(f, "IFD {}: missing required tag {}", ifd_index, tag)
+            }
+            ValidationError::StripOrganization { ifd_index } => {
+                write!(f, "IFD {}: uses strips instead of tiles", ifd_index)
+            }
+            ValidationError::UnsupportedCompression {
+                ifd_index,
+                compression,
+                compression_name,
+            } => write!(
+                f,
+                "IFD {}: unsupported compression {} ({})",
+                ifd_index, compression, compression_name
+            ),
+            ValidationError::MissingTileTags {
+                ifd_index,
+                missing_tags,
+            } => write!(
+                f,
+                "IFD {}: missing tile tags {}",
+                ifd_index,
+                missing_tags.join(", ")
+            ),
+            ValidationError::NoPyramidLevels => write!(f, "no tiled pyramid levels found"),
+            ValidationError::InvalidTileDimensions {
+                ifd_index,
+                tile_width,
+                tile_height,
+                message,
+            } => write!(
+                f,
+                "IFD {}: invalid tile dimensions {}x{}: {}",
+                ifd_index, tile_width, tile_height, message
+            ),
+        }
+    }
+}
+
 impl From<ValidationError> for TiffError {
     fn from(error: ValidationError) -> Self {
         match error {
@@ -271,6 +373,15 @@ pub fn validate_level(level: &PyramidLevel, byte_order: ByteOrder) -> Validation
                     compression: compression_value,
                     compression_name: compression.name().to_string(),
                 });
+            } else if let Err(TiffError::UnsupportedCompression(name)) =
+                RawTileLayout::from_level(level, byte_order)
+            {
+                // LZW/Deflate with a pixel layout the codec can't decode
+                result.add_error(ValidationError::UnsupportedCompression {
+                    ifd_index: level.ifd_index,
+                    compression: compression_value,
+                    compression_name: name,
+                });
             }
         } else {
             result.add_error(ValidationError::UnsupportedCompression {
@@ -337,8 +448,16 @@ pub fn validate_pyramid(pyramid: &TiffPyramid) -> ValidationResult {
     let mut result = ValidationResult::ok();
     let byte_order = pyramid.header.byte_order;
 
-    // Must have at least one level
+    // Must have at least one level; report stripped images that were
+    // skipped so the cause is explicit
     if pyramid.levels.is_empty() {
+        for (ifd_index, ifd) in &pyramid.other_ifds {
+            if ifd.is_stripped() && !ifd.is_tiled() {
+                result.add_error(ValidationError::StripOrganization {
+                    ifd_index: *ifd_index,
+                });
+            }
+        }
         result.add_error(ValidationError::NoPyramidLevels);
         return result;
     }
@@ -363,7 +482,8 @@ pub fn validate_pyramid(pyramid: &TiffPyramid) -> ValidationResult {
 
 /// Check if an IFD uses supported compression.
 ///
-/// Returns Ok(()) if compression is JPEG or JPEG 2000, or an error otherwise.
+/// Returns Ok(()) if compression is JPEG, JPEG 2000, LZW or Deflate, or an
+/// error otherwise.
 pub fn check_compression(ifd: &Ifd, byte_order: ByteOrder) -> Result<(), TiffError> {
     if let Some(compression_value) = ifd.compression(byte_order) {
         if let Some(compression) = Compression::from_u16(compression_value) {
@@ -536,8 +656,8 @@ mod tests {
         }
     }
 
-    fn make_lzw_ifd() -> Ifd {
-        // Create a tiled IFD with LZW compression (unsupported)
+    fn make_old_jpeg_ifd() -> Ifd {
+        // Create a tiled IFD with old-style JPEG compression (unsupported)
         let entries = vec![
             make_entry(TiffTag::ImageWidth, 10000),
             make_entry(TiffTag::ImageLength, 8000),
@@ -564,7 +684,7 @@ mod tests {
                 field_type: Some(FieldType::Short),
                 field_type_raw: 3,
                 count: 1,
-                value_offset_bytes: vec![5, 0, 0, 0], // LZW = 5
+                value_offset_bytes: vec![6, 0, 0, 0], // Old JPEG = 6
                 is_inline: true,
             },
         ];
@@ -610,15 +730,15 @@ mod tests {
     }
 
     #[test]
-    fn test_validate_lzw_ifd() {
-        let ifd = make_lzw_ifd();
+    fn test_validate_old_jpeg_ifd() {
+        let ifd = make_old_jpeg_ifd();
         let header = make_header();
         let result = validate_ifd(&ifd, 0, header.byte_order);
 
         assert!(!result.is_valid);
         assert!(matches!(
             result.errors[0],
-            ValidationError::UnsupportedCompression { compression: 5, .. }
+            ValidationError::UnsupportedCompression { compression: 6, .. }
         ));
     }
 
@@ -673,8 +793,8 @@ mod tests {
     }
 
     #[test]
-    fn test_check_compression_lzw() {
-        let ifd = make_lzw_ifd();
+    fn test_check_compression_old_jpeg() {
+        let ifd = make_old_jpeg_ifd();
         let header = make_header();
         let result = check_compression(&ifd, header.byte_order);
         assert!(matches!(result, Err(TiffError::UnsupportedCompression(_))));
@@ -725,6 +845,45 @@ mod tests {
         assert!(result.into_result().is_err());
     }
 
+    #[test]
+    fn test_validation_result_reports_every_error() {
+        let mut result =
+            ValidationResult::error(ValidationError::StripOrganization { ifd_index: 0 });
+        result.add_error(ValidationError::UnsupportedCompression {
+            ifd_index: 2,
+            compression: 6,
+            compression_name: "Old JPEG".to_string(),
+        });
+
+        match result.into_result() {
+            Err(TiffError::Unsupported(errors)) => {
+                assert_eq!(errors.len(), 2);
+                assert_eq!(errors[0].constraint(), "strip_organization");
+                assert_eq!(errors[1].constraint(), "unsupported_compression");
+                assert_eq!(errors[1].ifd_index(), Some(2));
+                assert_eq!(
+                    errors[1].to_string(),
+                    "IFD 2: unsupported compression 6 (Old JPEG)"
+                );
+            }
+            other => panic!("Expected Unsupported error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_validation_error_remediation() {
+        let jpeg2000 = ValidationError::UnsupportedCompression {
+            ifd_index: 0,
+            compression: 33003,
+            compression_name: "JPEG 2000".to_string(),
+        };
+        assert!(jpeg2000.remediation().contains("jpeg2000"));
+
+        let strips = ValidationError::StripOrganization { ifd_index: 0 };
+        assert!(strips.remediation().contains("--tile"));
+        assert_eq!(ValidationError::NoPyramidLevels.ifd_index(), None);
+    }
+
     #[test]
     fn test_validation_error_to_tiff_error() {
         let strip_error = ValidationError::StripOrganization { ifd_index: 0 };
@@ -15,6 +15,7 @@ use bytes::Bytes;
 use crate::error::TiffError;
 use crate::io::RangeReader;
 
+use super::limits::TiffLimits;
 use super::parser::{ByteOrder, IfdEntry, TiffHeader};
 use super::tags::FieldType;
 
@@ -25,16 +26,29 @@ use super::tags::FieldType;
 /// Reads tag values from a TIFF file.
 ///
 /// This struct combines a RangeReader with TIFF header information to
-/// read values respecting the file's byte order and format.
+/// read values respecting the file's byte order and format. Values stored
+/// at an offset are checked against [`TiffLimits::max_array_bytes`] before
+/// they are fetched.
 pub struct ValueReader<'a, R: RangeReader> {
     reader: &'a R,
     header: &'a TiffHeader,
+    limits: TiffLimits,
 }
 
 impl<'a, R: RangeReader> ValueReader<'a, R> {
-    /// Create a new ValueReader.
+    /// Create a new ValueReader with the default limits.
     pub fn new(reader: &'a R, header: &'a TiffHeader) -> Self {
-        Self { reader, header }
+        Self {
+            reader,
+            header,
+            limits: TiffLimits::DEFAULT,
+        }
+    }
+
+    /// Use the given parsing limits.
+    pub fn with_limits(mut self, limits: TiffLimits) -> Self {
+        self.limits = limits;
+        self
     }
 
     /// Get the byte order from the header.
@@ -59,6 +73,7 @@ impl<'a, R: RangeReader> ValueReader<'a, R> {
             ))
         } else {
             // Value is at an offset - fetch from file
+            self.limits.check_array_bytes(size)?;
             let offset = entry.value_offset(self.header.byte_order);
             let bytes = self.reader.read_exact_at(offset, size as usize).await?;
             Ok(bytes)
@@ -139,7 +154,8 @@ impl<'a, R: RangeReader> ValueReader<'a, R> {
     /// This is the primary method for reading TileOffsets and TileByteCounts.
     /// The entire array is fetched in a single range request for efficiency.
     ///
-    /// Handles Short, Long, and Long8 field types, converting all to u64.
+    /// Handles Short, Long, and Long8 field types (and the IFD offset types
+    /// used by SubIFDs), converting all to u64.
     pub async fn read_u64_array(&self, entry: &IfdEntry) -> Result<Vec<u64>, TiffError> {
         let field_type = entry
             .field_type
@@ -162,13 +178,13 @@ impl<'a, R: RangeReader> ValueReader<'a, R> {
                     values.push(byte_order.read_u16(&bytes[offset..]) as u64);
                 }
             }
-            FieldType::Long => {
+            FieldType::Long | FieldType::Ifd => {
                 for i in 0..count {
                     let offset = i * 4;
                     values.push(byte_order.read_u32(&bytes[offset..]) as u64);
                 }
             }
-            FieldType::Long8 => {
+            FieldType::Long8 | FieldType::Ifd8 => {
                 for i in 0..count {
                     let offset = i * 8;
                     values.push(byte_order.read_u64(&bytes[offset..]));
@@ -256,6 +272,33 @@ impl<'a, R: RangeReader> ValueReader<'a, R> {
         Ok(s)
     }
 
+    /// Read a single Rational value from an entry as a float.
+    ///
+    /// A zero denominator is reported as an invalid value.
+    pub async fn read_rational(&self, entry: &IfdEntry) -> Result<f64, TiffError> {
+        if entry.field_type != Some(FieldType::Rational) || entry.count != 1 {
+            return Err(TiffError::InvalidTagValue {
+                tag: "unknown",
+                message: format!(
+                    "expected a single Rational, got {} of {:?}",
+                    entry.count, entry.field_type
+                ),
+            });
+        }
+
+        let bytes = self.read_bytes(entry).await?;
+        let byte_order = self.header.byte_order;
+        let numerator = byte_order.read_u32(&bytes[..4]);
+        let denominator = byte_order.read_u32(&bytes[4..8]);
+        if denominator == 0 {
+            return Err(TiffError::InvalidTagValue {
+                tag: "unknown",
+                message: "Rational with zero denominator".to_string(),
+            });
+        }
+        Ok(numerator as f64 / denominator as f64)
+    }
+
     /// Read raw bytes from an entry (for UNDEFINED or opaque data).
     ///
     /// This is used for JPEGTables and other binary data.
@@ -288,7 +331,7 @@ pub fn parse_u64_array(
                 }
             }
         }
-        FieldType::Long => {
+        FieldType::Long | FieldType::Ifd => {
             for i in 0..count {
                 let offset = i * 4;
                 if offset + 4 <= bytes.len() {
@@ -296,7 +339,7 @@ pub fn parse_u64_array(
                 }
             }
         }
-        FieldType::Long8 => {
+        FieldType::Long8 | FieldType::Ifd8 => {
             for i in 0..count {
                 let offset = i * 8;
                 if offset + 8 <= bytes.len() {
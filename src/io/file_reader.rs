@@ -0,0 +1,115 @@
+//! Local file range reader.
+//!
+//! Reads byte ranges from a file on disk, for commands that work on a local
+//! slide (`wsi-streamer inspect`) without loading it into memory.
+
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+
+use super::RangeReader;
+use crate::error::IoError;
+
+/// RangeReader over a local file.
+///
+/// Reads run on Tokio's blocking thread pool. Cloning is cheap: clones share
+/// the open file.
+#[derive(Clone)]
+pub struct FileRangeReader {
+    file: Arc<Mutex<File>>,
+    size: u64,
+    identifier: String,
+}
+
+impl FileRangeReader {
+    /// Open a file, identified as `file://{path}`.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, IoError> {
+        let path = path.as_ref();
+        let identifier = format!("file://{}", path.display());
+        let file = File::open(path).map_err(|e| io_error(&identifier, e))?;
+        let size = file.metadata().map_err(|e| io_error(&identifier, e))?.len();
+        Ok(Self {
+            file: Arc::new(Mutex::new(file)),
+            size,
+            identifier,
+        })
+    }
+}
+
+/// Map a file error, reporting anything but a missing file as a connection
+/// error like the remote readers do.
+fn io_error(identifier: &str, e: io::Error) -> IoError {
+    match e.kind() {
+        io::ErrorKind::NotFound => IoError::NotFound(identifier.to_string()),
+        _ => IoError::Connection(format!("{}: {}", identifier, e)),
+    }
+}
+
+#[async_trait]
+impl RangeReader for FileRangeReader {
+    async fn read_exact_at(&self, offset: u64, len: usize) -> Result<Bytes, IoError> {
+        let end = offset
+            .checked_add(len as u64)
+            .filter(|&end| end <= self.size);
+        if end.is_none() {
+            return Err(IoError::RangeOutOfBounds {
+                offset,
+                requested: len as u64,
+                size: self.size,
+            });
+        }
+
+        let file = Arc::clone(&self.file);
+        let result = tokio::task::spawn_blocking(move || {
+            let mut file = file.lock().unwrap_or_else(|e| e.into_inner());
+            let mut buffer = vec![0u8; len];
+            file.seek(SeekFrom::Start(offset))?;
+            file.read_exact(&mut buffer)?;
+            Ok::<_, io::Error>(Bytes::from(buffer))
+        })
+        .await
+        .map_err(|e| IoError::Connection(format!("{}: {}", self.identifier, e)))?;
+        result.map_err(|e| io_error(&self.identifier, e))
+    }
+
+    fn size(&self) -> u64 {
+        self.size
+    }
+
+    fn identifier(&self) -> &str {
+        &self.identifier
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_file_reader_ranges() {
+        let path = std::env::temp_dir().join(format!("wsi-file-reader-{}", std::process::id()));
+        std::fs::write(&path, b"0123456789").unwrap();
+
+        let reader = FileRangeReader::open(&path).unwrap();
+        assert_eq!(reader.size(), 10);
+        assert_eq!(&reader.read_exact_at(2, 3).await.unwrap()[..], b"234");
+        assert!(matches!(
+            reader.read_exact_at(8, 3).await,
+            Err(IoError::RangeOutOfBounds { size: 10, .. })
+        ));
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(
+            FileRangeReader::open(&path),
+            Err(IoError::NotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,188 @@
+//! Shared plumbing for object stores read over plain HTTP.
+//!
+//! GCS, Azure Blob Storage and static file servers all serve objects with
+//! standard HTTP semantics: `HEAD` reports the size, ETag and modification
+//! time, and `GET` with a `Range` header returns a byte range. Their range
+//! readers differ only in URLs and authorization, so the request handling
+//! lives here.
+
+use std::time::{Duration, SystemTime};
+
+use bytes::Bytes;
+use reqwest::{header, RequestBuilder, Response, StatusCode};
+use tracing::debug;
+
+use crate::error::IoError;
+use crate::server::parse_http_date;
+
+/// Size, ETag and modification time of an object, from a `HEAD` response.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ObjectInfo {
+    pub size: u64,
+    pub etag: Option<String>,
+    pub last_modified: Option<SystemTime>,
+}
+
+/// Delay before the first retry; doubled for each further attempt.
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(100);
+
+/// Send a `HEAD` request for an object and read its metadata.
+///
+/// Transient failures are retried up to `retries` times.
+pub(crate) async fn head_object(
+    request: RequestBuilder,
+    identifier: &str,
+    retries: u32,
+) -> Result<ObjectInfo, IoError> {
+    let response = send_with_retries(request, identifier, retries).await?;
+
+    let header_str = |name: header::HeaderName| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string)
+    };
+    let size = header_str(header::CONTENT_LENGTH)
+        .and_then(|len| len.parse().ok())
+        .unwrap_or(0);
+
+    Ok(ObjectInfo {
+        size,
+        etag: header_str(header::ETAG),
+        last_modified: header_str(header::LAST_MODIFIED).and_then(|d| parse_http_date(&d)),
+    })
+}
+
+/// Send a `GET` request for `len` bytes of an object starting at `offset`.
+///
+/// Transient failures are retried up to `retries` times.
+pub(crate) async fn get_range(
+    request: RequestBuilder,
+    identifier: &str,
+    offset: u64,
+    len: usize,
+    retries: u32,
+) -> Result<Bytes, IoError> {
+    // Range header: "bytes=start-end" (inclusive on both ends)
+    let range = format!("bytes={}-{}", offset, offset + len as u64 - 1);
+    let request = request.header(header::RANGE, range);
+    let response = send_with_retries(request, identifier, retries).await?;
+
+    let data = response
+        .bytes()
+        .await
+        .map_err(|e| IoError::Connection(e.to_string()))?;
+    if data.len() != len {
+        return Err(IoError::S3(format!(
+            "{}: expected {} bytes at offset {}, got {}",
+            identifier,
+            len,
+            offset,
+            data.len()
+        )));
+    }
+    Ok(data)
+}
+
+/// Send a request and fail unless it succeeds.
+pub(crate) async fn send_request(
+    request: RequestBuilder,
+    identifier: &str,
+) -> Result<Response, IoError> {
+    let response = request.send().await.map_err(request_error)?;
+    check_status(response, identifier)
+}
+
+/// Send a request, retrying connection failures, timeouts, throttling and
+/// server errors with exponential backoff.
+///
+/// Requests with streaming bodies can't be replayed and are sent once.
+pub(crate) async fn send_with_retries(
+    request: RequestBuilder,
+    identifier: &str,
+    retries: u32,
+) -> Result<Response, IoError> {
+    let mut attempt = 0;
+    loop {
+        let Some(next) = request.try_clone().filter(|_| attempt < retries) else {
+            return send_request(request, identifier).await;
+        };
+
+        let retryable = match next.send().await {
+            Ok(response) if is_retryable_status(response.status()) => {
+                format!("{} returned {}", identifier, response.status())
+            }
+            Ok(response) => return check_status(response, identifier),
+            Err(e) if e.is_connect() || e.is_timeout() => e.to_string(),
+            Err(e) => return Err(request_error(e)),
+        };
+
+        let delay = RETRY_BASE_DELAY * 2u32.pow(attempt);
+        debug!(
+            "Retrying {} in {:?} ({}/{}): {}",
+            identifier,
+            delay,
+            attempt + 1,
+            retries,
+            retryable
+        );
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+/// Whether a status indicates a transient failure worth retrying.
+fn is_retryable_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS
+        || status == StatusCode::REQUEST_TIMEOUT
+        || status.is_server_error()
+}
+
+/// Map an unsuccessful response to an error.
+fn check_status(response: Response, identifier: &str) -> Result<Response, IoError> {
+    match response.status() {
+        status if status.is_success() => Ok(response),
+        StatusCode::NOT_FOUND => Err(IoError::NotFound(identifier.to_string())),
+        status => Err(IoError::S3(format!("{} returned {}", identifier, status))),
+    }
+}
+
+/// Map a failed request (no response) to an error.
+pub(crate) fn request_error(e: reqwest::Error) -> IoError {
+    IoError::Connection(e.to_string())
+}
+
+/// Percent-encode an object key for a URL path, keeping `/` separators.
+pub(crate) fn encode_key(key: &str) -> String {
+    key.split('/')
+        .map(|segment| urlencoding::encode(segment).into_owned())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retryable_status() {
+        assert!(is_retryable_status(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(is_retryable_status(StatusCode::TOO_MANY_REQUESTS));
+        assert!(!is_retryable_status(StatusCode::NOT_FOUND));
+        assert!(!is_retryable_status(StatusCode::FORBIDDEN));
+    }
+
+    #[test]
+    fn test_encode_key() {
+        assert_eq!(encode_key("slides/a.svs"), "slides/a.svs");
+        assert_eq!(
+            encode_key("lab a/case#1/s?.svs"),
+            "lab%20a/case%231/s%3F.svs"
+        );
+    }
+}
@@ -0,0 +1,209 @@
+use std::time::{Duration, SystemTime};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use reqwest::{header, RequestBuilder};
+
+use super::http_object::{get_range, head_object};
+use super::RangeReader;
+use crate::error::IoError;
+
+/// Default number of retries for transient HTTP failures.
+pub const DEFAULT_HTTP_RETRIES: u32 = 3;
+
+/// Idle pooled connections are closed after this long.
+const POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// HTTP client shared by the readers of a slide source.
+///
+/// Connections are pooled per host and reused across readers, so tile
+/// reads from the same server don't pay for a new TLS handshake.
+#[derive(Clone)]
+pub struct HttpClient {
+    http: reqwest::Client,
+    retries: u32,
+}
+
+impl HttpClient {
+    /// Create a client retrying transient failures [`DEFAULT_HTTP_RETRIES`] times.
+    pub fn new() -> Result<Self, IoError> {
+        let http = reqwest::Client::builder()
+            .pool_idle_timeout(POOL_IDLE_TIMEOUT)
+            .build()
+            .map_err(|e| IoError::Connection(format!("Failed to create HTTP client: {}", e)))?;
+        Ok(Self {
+            http,
+            retries: DEFAULT_HTTP_RETRIES,
+        })
+    }
+
+    /// Set how many times connection failures, timeouts, throttling and
+    /// server errors are retried (0 disables retries).
+    pub fn with_retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Get the number of retries.
+    pub fn retries(&self) -> u32 {
+        self.retries
+    }
+
+    /// Start a request to `url`.
+    fn request(&self, method: reqwest::Method, url: &str) -> RequestBuilder {
+        self.http.request(method, url)
+    }
+}
+
+/// HTTP(S) implementation of RangeReader.
+///
+/// Reads byte ranges from any URL whose server supports range requests,
+/// such as static file servers and CDNs. The size, ETag and modification
+/// time are fetched once on creation via HEAD.
+#[derive(Clone)]
+pub struct HttpRangeReader {
+    client: HttpClient,
+    url: String,
+    size: u64,
+    etag: Option<String>,
+    last_modified: Option<SystemTime>,
+}
+
+impl HttpRangeReader {
+    /// Create a new HttpRangeReader for the given URL.
+    ///
+    /// This performs a HEAD request to determine the resource size.
+    /// Returns an error if the resource does not exist or is inaccessible.
+    pub async fn new(client: HttpClient, url: &str) -> Result<Self, IoError> {
+        let request = client.request(reqwest::Method::HEAD, url);
+        let info = head_object(request, url, client.retries).await?;
+
+        Ok(Self {
+            client,
+            url: url.to_string(),
+            size: info.size,
+            etag: info.etag,
+            last_modified: info.last_modified,
+        })
+    }
+}
+
+#[async_trait]
+impl RangeReader for HttpRangeReader {
+    async fn read_exact_at(&self, offset: u64, len: usize) -> Result<Bytes, IoError> {
+        // Validate range bounds
+        if offset + len as u64 > self.size {
+            return Err(IoError::RangeOutOfBounds {
+                offset,
+                requested: len as u64,
+                size: self.size,
+            });
+        }
+
+        // Handle zero-length reads
+        if len == 0 {
+            return Ok(Bytes::new());
+        }
+
+        let request = self
+            .client
+            .request(reqwest::Method::GET, &self.url)
+            // Byte ranges must address the stored bytes, not a compressed form
+            .header(header::ACCEPT_ENCODING, "identity");
+        get_range(request, &self.url, offset, len, self.client.retries).await
+    }
+
+    fn size(&self) -> u64 {
+        self.size
+    }
+
+    fn identifier(&self) -> &str {
+        &self.url
+    }
+
+    fn etag(&self) -> Option<&str> {
+        self.etag.as_deref()
+    }
+
+    fn last_modified(&self) -> Option<SystemTime> {
+        self.last_modified
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use axum::http::{HeaderMap, StatusCode};
+    use axum::response::{IntoResponse, Response};
+    use axum::routing::get;
+    use axum::Router;
+
+    use super::*;
+
+    const DATA: &[u8] = b"0123456789abcdef";
+
+    /// Serve `DATA` with range support, failing the first `failures` requests.
+    async fn serve(failures: usize) -> String {
+        let requests = Arc::new(AtomicUsize::new(0));
+        let handler = move |headers: HeaderMap| {
+            let requests = requests.clone();
+            async move {
+                if requests.fetch_add(1, Ordering::SeqCst) < failures {
+                    return StatusCode::SERVICE_UNAVAILABLE.into_response();
+                }
+                let range = headers
+                    .get(header::RANGE)
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|v| v.strip_prefix("bytes="))
+                    .and_then(|v| v.split_once('-'))
+                    .map(|(start, end)| (start.parse().unwrap(), end.parse::<usize>().unwrap()));
+                match range {
+                    Some((start, end)) => Response::builder()
+                        .status(StatusCode::PARTIAL_CONTENT)
+                        .body(DATA[start..=end].to_vec().into())
+                        .unwrap(),
+                    None => Response::builder()
+                        .header(header::ETAG, "\"v1\"")
+                        .body(DATA.to_vec().into())
+                        .unwrap(),
+                }
+            }
+        };
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let router = Router::new().route("/slides/a.svs", get(handler));
+        tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+        format!("http://{}/slides/a.svs", addr)
+    }
+
+    #[tokio::test]
+    async fn test_http_range_reads() {
+        let url = serve(0).await;
+        let reader = HttpRangeReader::new(HttpClient::new().unwrap(), &url)
+            .await
+            .unwrap();
+
+        assert_eq!(reader.size(), DATA.len() as u64);
+        assert_eq!(reader.etag(), Some("\"v1\""));
+        assert_eq!(reader.identifier(), url);
+        assert_eq!(&reader.read_exact_at(4, 6).await.unwrap()[..], b"456789");
+        assert!(matches!(
+            reader.read_exact_at(10, 10).await,
+            Err(IoError::RangeOutOfBounds { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_http_retries_transient_failures() {
+        let url = serve(2).await;
+        let reader = HttpRangeReader::new(HttpClient::new().unwrap(), &url).await;
+        assert_eq!(reader.unwrap().size(), DATA.len() as u64);
+
+        let url = serve(1).await;
+        let client = HttpClient::new().unwrap().with_retries(0);
+        assert!(HttpRangeReader::new(client, &url).await.is_err());
+    }
+}
@@ -0,0 +1,58 @@
+//! Object key validation.
+//!
+//! Slide IDs are used verbatim as object keys and, for the HTTP-based
+//! backends, joined into request URLs. URL normalization resolves `.` and
+//! `..` segments (even percent-encoded), so a slide ID like
+//! `../other-bucket/x.svs` would read from outside the configured bucket,
+//! container or base URL with the server's credentials. Every source and
+//! handler checks IDs here before using them.
+
+use crate::error::IoError;
+
+/// Check that an object key is a usable slide identifier.
+///
+/// Rejects empty keys and keys with an empty, `.` or `..` path segment
+/// (including leading, trailing and doubled `/`).
+pub fn validate_key(key: &str) -> Result<(), IoError> {
+    if key
+        .split('/')
+        .any(|segment| matches!(segment, "" | "." | ".."))
+    {
+        return Err(IoError::InvalidKey(key.to_string()));
+    }
+    Ok(())
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_key() {
+        assert!(validate_key("slide.svs").is_ok());
+        assert!(validate_key("lab-a/case 1/s1.svs").is_ok());
+        assert!(validate_key(".views/s1.svs/default.json").is_ok());
+        assert!(validate_key("a/..b/c..svs").is_ok());
+
+        for key in [
+            "",
+            "../other-bucket/x.svs",
+            "lab-a/../../x.svs",
+            "lab-a/./x.svs",
+            "lab-a/..",
+            "/x.svs",
+            "lab-a//x.svs",
+            "lab-a/",
+        ] {
+            assert!(
+                matches!(validate_key(key), Err(IoError::InvalidKey(_))),
+                "{:?} should be rejected",
+                key
+            );
+        }
+    }
+}
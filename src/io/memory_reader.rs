@@ -0,0 +1,79 @@
+//! In-memory range reader.
+//!
+//! Serves byte ranges from a buffer held in memory, for slides preloaded at
+//! startup (see [`MemorySlideSource`](crate::slide::MemorySlideSource)).
+
+use async_trait::async_trait;
+use bytes::Bytes;
+
+use super::RangeReader;
+use crate::error::IoError;
+
+/// RangeReader over a buffer held in memory.
+///
+/// Cloning is cheap: the buffer is reference-counted.
+#[derive(Clone)]
+pub struct MemoryRangeReader {
+    data: Bytes,
+    identifier: String,
+}
+
+impl MemoryRangeReader {
+    /// Create a reader over `data`, identified as `memory://{name}`.
+    pub fn new(name: &str, data: Bytes) -> Self {
+        Self {
+            data,
+            identifier: format!("memory://{}", name),
+        }
+    }
+}
+
+#[async_trait]
+impl RangeReader for MemoryRangeReader {
+    async fn read_exact_at(&self, offset: u64, len: usize) -> Result<Bytes, IoError> {
+        let size = self.data.len() as u64;
+        let end = offset.checked_add(len as u64).filter(|&end| end <= size);
+        match end {
+            Some(end) => Ok(self.data.slice(offset as usize..end as usize)),
+            None => Err(IoError::RangeOutOfBounds {
+                offset,
+                requested: len as u64,
+                size,
+            }),
+        }
+    }
+
+    fn size(&self) -> u64 {
+        self.data.len() as u64
+    }
+
+    fn identifier(&self) -> &str {
+        &self.identifier
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_memory_reader_ranges() {
+        let reader = MemoryRangeReader::new("a.svs", Bytes::from_static(b"0123456789"));
+        assert_eq!(reader.size(), 10);
+        assert_eq!(reader.identifier(), "memory://a.svs");
+
+        let bytes = reader.read_exact_at(2, 3).await.unwrap();
+        assert_eq!(&bytes[..], b"234");
+        assert_eq!(reader.read_exact_at(10, 0).await.unwrap().len(), 0);
+
+        assert!(matches!(
+            reader.read_exact_at(8, 3).await,
+            Err(IoError::RangeOutOfBounds { size: 10, .. })
+        ));
+        assert!(reader.read_exact_at(u64::MAX, 2).await.is_err());
+    }
+}
@@ -1,9 +1,38 @@
+#[cfg(feature = "azure")]
+mod azure_reader;
 mod block_cache;
+mod file_reader;
+#[cfg(feature = "gcs")]
+mod gcs_reader;
+#[cfg(any(feature = "gcs", feature = "azure", feature = "http"))]
+mod http_object;
+#[cfg(feature = "http")]
+mod http_reader;
+mod key;
+mod memory_reader;
 mod range_reader;
 mod s3_reader;
 
-pub use block_cache::{BlockCache, DEFAULT_BLOCK_SIZE};
+#[cfg(feature = "azure")]
+pub use azure_reader::{AzureBlobClient, AzureBlobRangeReader, AZURE_API_VERSION};
+pub use block_cache::{
+    BlockCache, BlockCacheStats, DEFAULT_BLOCK_CACHE_SHARDS, DEFAULT_BLOCK_SIZE,
+};
+pub use file_reader::FileRangeReader;
+#[cfg(feature = "gcs")]
+pub use gcs_reader::{GcsClient, GcsCredentials, GcsRangeReader, DEFAULT_GCS_ENDPOINT};
+#[cfg(feature = "http")]
+pub(crate) use http_object::encode_key;
+#[cfg(any(feature = "gcs", feature = "azure"))]
+pub(crate) use http_object::send_request;
+#[cfg(feature = "http")]
+pub use http_reader::{HttpClient, HttpRangeReader, DEFAULT_HTTP_RETRIES};
+pub use key::validate_key;
+pub use memory_reader::MemoryRangeReader;
 pub use range_reader::{
     read_u16_be, read_u16_le, read_u32_be, read_u32_le, read_u64_be, read_u64_le, RangeReader,
 };
-pub use s3_reader::{create_s3_client, S3RangeReader};
+pub use s3_reader::{
+    create_s3_client, s3_retry_stats, S3RangeReader, S3RetryPolicy, S3RetryStats,
+    DEFAULT_S3_MAX_ATTEMPTS, DEFAULT_S3_RETRY_BASE_DELAY, DEFAULT_S3_RETRY_MAX_DELAY,
+};
@@ -1,3 +1,5 @@
+use std::time::SystemTime;
+
 use async_trait::async_trait;
 use bytes::Bytes;
 
@@ -22,6 +24,22 @@ pub trait RangeReader: Send + Sync {
     ///
     /// For S3, this would typically be `s3://bucket/key`.
     fn identifier(&self) -> &str;
+
+    /// Get the storage backend's entity tag for the resource, if known.
+    ///
+    /// The ETag changes whenever the object is replaced, so it identifies
+    /// the exact content being read. The default implementation returns
+    /// `None`.
+    fn etag(&self) -> Option<&str> {
+        None
+    }
+
+    /// Get the time the resource was last modified, if known.
+    ///
+    /// The default implementation returns `None`.
+    fn last_modified(&self) -> Option<SystemTime> {
+        None
+    }
 }
 
 // =============================================================================
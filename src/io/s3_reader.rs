@@ -1,14 +1,155 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant, SystemTime};
+
 use async_trait::async_trait;
+use aws_sdk_s3::config::http::HttpResponse;
+use aws_sdk_s3::config::retry::RetryConfig;
+use aws_sdk_s3::error::SdkError;
 use aws_sdk_s3::Client;
 use bytes::Bytes;
+use tracing::{debug, info_span, Instrument};
 
 use super::RangeReader;
 use crate::error::IoError;
 
+/// Default number of attempts per S3 range read, including the first.
+pub const DEFAULT_S3_MAX_ATTEMPTS: u32 = 3;
+
+/// Default backoff before the first retry of an S3 range read.
+pub const DEFAULT_S3_RETRY_BASE_DELAY: Duration = Duration::from_millis(100);
+
+/// Default upper bound on the backoff between S3 read attempts.
+pub const DEFAULT_S3_RETRY_MAX_DELAY: Duration = Duration::from_secs(5);
+
+/// Range reads retried after a transient failure, since startup.
+static S3_READ_RETRIES: AtomicU64 = AtomicU64::new(0);
+
+/// Range reads that still failed after the last attempt, since startup.
+static S3_READ_RETRIES_EXHAUSTED: AtomicU64 = AtomicU64::new(0);
+
+// =============================================================================
+// Retry Policy
+// =============================================================================
+
+/// Retry policy for S3 range reads.
+///
+/// Throttling, server errors, timeouts and dropped connections are retried
+/// with exponential backoff and full jitter, so concurrent readers hit by
+/// the same outage don't retry in lockstep. Other errors (missing objects,
+/// denied access) fail immediately.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct S3RetryPolicy {
+    /// Attempts per read, including the first (1 disables retries)
+    pub max_attempts: u32,
+
+    /// Backoff cap before the first retry, doubled for each further retry
+    pub base_delay: Duration,
+
+    /// Upper bound on the backoff between attempts
+    pub max_delay: Duration,
+}
+
+impl Default for S3RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: DEFAULT_S3_MAX_ATTEMPTS,
+            base_delay: DEFAULT_S3_RETRY_BASE_DELAY,
+            max_delay: DEFAULT_S3_RETRY_MAX_DELAY,
+        }
+    }
+}
+
+impl S3RetryPolicy {
+    /// Create a policy with the given attempts and default delays.
+    pub fn new(max_attempts: u32) -> Self {
+        Self {
+            max_attempts: max_attempts.max(1),
+            ..Self::default()
+        }
+    }
+
+    /// A policy that never retries.
+    pub fn disabled() -> Self {
+        Self::new(1)
+    }
+
+    /// Set the backoff cap before the first retry.
+    pub fn with_base_delay(mut self, delay: Duration) -> Self {
+        self.base_delay = delay;
+        self
+    }
+
+    /// Set the upper bound on the backoff between attempts.
+    pub fn with_max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay = delay;
+        self
+    }
+
+    /// Backoff before retry number `retry` (starting at 1): uniformly
+    /// random up to `base_delay * 2^(retry - 1)`, capped at `max_delay`.
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let cap = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(retry.saturating_sub(1)))
+            .min(self.max_delay);
+
+        // A freshly keyed hasher yields a random value without a rand dependency
+        let random = RandomState::new().build_hasher().finish();
+        cap.mul_f64(random as f64 / u64::MAX as f64)
+    }
+}
+
+/// Counters for S3 range read retries since startup.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct S3RetryStats {
+    /// Reads retried after a transient failure
+    pub retries: u64,
+
+    /// Reads that failed on their last allowed attempt
+    pub exhausted: u64,
+}
+
+/// Get the S3 range read retry counters, across all readers.
+pub fn s3_retry_stats() -> S3RetryStats {
+    S3RetryStats {
+        retries: S3_READ_RETRIES.load(Ordering::Relaxed),
+        exhausted: S3_READ_RETRIES_EXHAUSTED.load(Ordering::Relaxed),
+    }
+}
+
+/// Status of a conditional read whose ETag no longer matches.
+const PRECONDITION_FAILED: u16 = 412;
+
+/// Whether an HTTP status indicates throttling or a transient server error.
+fn is_retryable_status(status: u16) -> bool {
+    status == 429 || status >= 500
+}
+
+/// Whether a failed S3 request is worth retrying.
+fn is_retryable<E>(err: &SdkError<E, HttpResponse>) -> bool {
+    match err {
+        SdkError::TimeoutError(_) | SdkError::DispatchFailure(_) | SdkError::ResponseError(_) => {
+            true
+        }
+        _ => err
+            .raw_response()
+            .is_some_and(|response| is_retryable_status(response.status().as_u16())),
+    }
+}
+
+/// A failed read attempt.
+struct ReadFailure {
+    error: IoError,
+    retryable: bool,
+}
+
 /// S3-backed implementation of RangeReader.
 ///
 /// Reads byte ranges from objects in S3 or S3-compatible storage (MinIO, GCS, etc.)
-/// using HTTP range requests. The object size is fetched once on creation via HEAD.
+/// using HTTP range requests. The object size, ETag and modification time are
+/// fetched once on creation via HEAD.
 #[derive(Clone)]
 pub struct S3RangeReader {
     client: Client,
@@ -16,6 +157,9 @@ pub struct S3RangeReader {
     key: String,
     size: u64,
     identifier: String,
+    etag: Option<String>,
+    last_modified: Option<SystemTime>,
+    retry: S3RetryPolicy,
 }
 
 impl S3RangeReader {
@@ -66,6 +210,10 @@ impl S3RangeReader {
 
         let size = head.content_length().unwrap_or(0) as u64;
         let identifier = format!("s3://{}/{}", bucket, key);
+        let etag = head.e_tag().map(str::to_string);
+        let last_modified = head
+            .last_modified()
+            .and_then(|t| SystemTime::try_from(*t).ok());
 
         Ok(Self {
             client,
@@ -73,9 +221,108 @@ impl S3RangeReader {
             key,
             size,
             identifier,
+            etag,
+            last_modified,
+            retry: S3RetryPolicy::default(),
         })
     }
 
+    /// Set the retry policy for range reads.
+    pub fn with_retry_policy(mut self, retry: S3RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Get the retry policy for range reads.
+    pub fn retry_policy(&self) -> S3RetryPolicy {
+        self.retry
+    }
+
+    /// Fetch a byte range with a single request.
+    ///
+    /// The request is conditional on the ETag seen when the reader was
+    /// created, so bytes of a replaced object are never mixed with metadata
+    /// parsed from the old one.
+    async fn fetch_range(&self, range: &str) -> Result<Bytes, ReadFailure> {
+        let resp = self
+            .client
+            .get_object()
+            .bucket(&self.bucket)
+            .key(&self.key)
+            .range(range)
+            .set_if_match(self.etag.clone())
+            .customize()
+            // Retries are handled by the reader's policy
+            .config_override(
+                aws_sdk_s3::config::Builder::new().retry_config(RetryConfig::disabled()),
+            )
+            .send()
+            .await
+            .map_err(|e| {
+                if e.raw_response()
+                    .is_some_and(|r| r.status().as_u16() == PRECONDITION_FAILED)
+                {
+                    return ReadFailure {
+                        error: IoError::ObjectChanged(self.identifier.clone()),
+                        retryable: false,
+                    };
+                }
+                ReadFailure {
+                    retryable: is_retryable(&e),
+                    error: IoError::S3(e.to_string()),
+                }
+            })?;
+
+        // A body cut short by a dropped connection is retried too
+        let data = resp
+            .body
+            .collect()
+            .await
+            .map_err(|e| ReadFailure {
+                error: IoError::Connection(e.to_string()),
+                retryable: true,
+            })?
+            .into_bytes();
+
+        Ok(data)
+    }
+
+    /// Read a byte range, retrying per the reader's policy.
+    ///
+    /// Returns the result along with the number of attempts made.
+    async fn read_range(&self, range: &str) -> (Result<Bytes, IoError>, u32) {
+        let mut attempt = 1;
+        loop {
+            let failure = match self.fetch_range(range).await {
+                Ok(data) => return (Ok(data), attempt),
+                Err(failure) => failure,
+            };
+            if !failure.retryable {
+                return (Err(failure.error), attempt);
+            }
+            if attempt >= self.retry.max_attempts {
+                if self.retry.max_attempts > 1 {
+                    S3_READ_RETRIES_EXHAUSTED.fetch_add(1, Ordering::Relaxed);
+                }
+                return (Err(failure.error), attempt);
+            }
+
+            let delay = self.retry.backoff(attempt);
+            debug!(
+                "Retrying read of {} ({}) in {:?}, attempt {}/{}: {}",
+                self.identifier,
+                range,
+                delay,
+                attempt + 1,
+                self.retry.max_attempts,
+                failure.error
+            );
+            S3_READ_RETRIES.fetch_add(1, Ordering::Relaxed);
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
     /// Get the bucket name.
     pub fn bucket(&self) -> &str {
         &self.bucket
@@ -107,24 +354,20 @@ impl RangeReader for S3RangeReader {
         // Build range header: "bytes=start-end" (inclusive on both ends)
         let range = format!("bytes={}-{}", offset, offset + len as u64 - 1);
 
-        let resp = self
-            .client
-            .get_object()
-            .bucket(&self.bucket)
-            .key(&self.key)
-            .range(range)
-            .send()
-            .await
-            .map_err(|e| IoError::S3(e.to_string()))?;
-
-        let data = resp
-            .body
-            .collect()
-            .await
-            .map_err(|e| IoError::Connection(e.to_string()))?
-            .into_bytes();
-
-        Ok(data)
+        let span = info_span!(
+            "s3_read",
+            bucket = %self.bucket,
+            key = %self.key,
+            offset,
+            len,
+            attempts = tracing::field::Empty,
+            latency_ms = tracing::field::Empty,
+        );
+        let started = Instant::now();
+        let (result, attempts) = self.read_range(&range).instrument(span.clone()).await;
+        span.record("attempts", attempts);
+        span.record("latency_ms", started.elapsed().as_millis() as u64);
+        result
     }
 
     fn size(&self) -> u64 {
@@ -134,6 +377,14 @@ impl RangeReader for S3RangeReader {
     fn identifier(&self) -> &str {
         &self.identifier
     }
+
+    fn etag(&self) -> Option<&str> {
+        self.etag.as_deref()
+    }
+
+    fn last_modified(&self) -> Option<SystemTime> {
+        self.last_modified
+    }
 }
 
 /// Create an S3 client with optional custom endpoint and region.
@@ -174,4 +425,43 @@ pub async fn create_s3_client(endpoint_url: Option<&str>, region: &str) -> Clien
 mod tests {
     // Integration tests require a running S3-compatible service (e.g., MinIO)
     // and are not included in unit tests. See tests/integration/ for E2E tests.
+
+    use super::*;
+
+    #[test]
+    fn test_retry_backoff_is_capped() {
+        let policy = S3RetryPolicy::new(5)
+            .with_base_delay(Duration::from_millis(100))
+            .with_max_delay(Duration::from_millis(300));
+
+        for _ in 0..100 {
+            assert!(policy.backoff(1) <= Duration::from_millis(100));
+            assert!(policy.backoff(2) <= Duration::from_millis(200));
+            assert!(policy.backoff(3) <= Duration::from_millis(300));
+            assert!(policy.backoff(30) <= Duration::from_millis(300));
+        }
+
+        // Jittered: not every backoff is the same
+        let delays: std::collections::HashSet<_> = (0..20).map(|_| policy.backoff(3)).collect();
+        assert!(delays.len() > 1);
+    }
+
+    #[test]
+    fn test_retry_policy() {
+        assert_eq!(
+            S3RetryPolicy::default().max_attempts,
+            DEFAULT_S3_MAX_ATTEMPTS
+        );
+        assert_eq!(S3RetryPolicy::disabled().max_attempts, 1);
+        assert_eq!(S3RetryPolicy::new(0).max_attempts, 1);
+    }
+
+    #[test]
+    fn test_retryable_status() {
+        assert!(is_retryable_status(503));
+        assert!(is_retryable_status(500));
+        assert!(is_retryable_status(429));
+        assert!(!is_retryable_status(404));
+        assert!(!is_retryable_status(403));
+    }
 }
@@ -23,8 +23,14 @@
 //! - [`mod@format`] - TIFF/SVS parsers and JPEG handling
 //! - [`slide`] - Slide abstraction and registry
 //! - [`tile`] - Tile service and encoding
+//! - [`overlay`] - GeoJSON annotations rendered onto tiles
 //! - [`server`] - Axum-based HTTP server and routes
 //! - [`config`] - CLI and configuration types
+//! - [`capabilities`] - Codecs and optional features compiled into this build
+//! - [`replay`] - Request log replay for reproducing bug reports
+//! - [`secrets`] - Secret references resolved from files and AWS at startup
+//! - [`telemetry`] - OpenTelemetry export of request, cache and storage spans
+//! - [`timing`] - Per-request phase timings for `Server-Timing` headers
 //!
 //! ## Example
 //!
@@ -49,45 +55,86 @@
 //!         wsi_streamer::Command::Check(config) => {
 //!             // Validate S3 connectivity
 //!         }
+//!         wsi_streamer::Command::Plan(config) => {
+//!             // Print a capacity plan
+//!         }
+//!         wsi_streamer::Command::Capabilities => {
+//!             // Print the build's codecs and features
+//!         }
+//!         wsi_streamer::Command::Manifest(config) => {
+//!             // Write a checksum manifest
+//!         }
+//!         wsi_streamer::Command::Inspect(config) => {
+//!             // Dump a slide's TIFF tags
+//!         }
+//!         wsi_streamer::Command::Replay(config) => {
+//!             // Replay captured requests
+//!         }
 //!     }
 //! }
 //! ```
 
+pub mod capabilities;
 pub mod config;
+mod date;
 pub mod error;
 pub mod format;
 pub mod io;
+pub mod overlay;
+pub mod plan;
+pub mod replay;
+pub mod secrets;
 pub mod server;
 pub mod slide;
+pub mod telemetry;
 pub mod tile;
+pub mod timing;
 
 // Re-export commonly used types
-pub use config::{CheckConfig, Cli, Command, Config, ServeConfig, SignConfig, SignOutputFormat};
-pub use error::{FormatError, IoError, TiffError, TileError};
+pub use capabilities::{capabilities, Capabilities, FeatureStatus};
+pub use config::{
+    CheckConfig, Cli, Command, Config, LogFormat, PlanConfig, PlanOutputFormat, ReplayConfig,
+    ServeConfig, SignConfig, SignOutputFormat,
+};
+pub use error::{
+    AnnotationError, ErrorCode, FormatError, IiifError, IoError, ReplayError, SecretError,
+    TiffError, TileError, TlsError, ViewError,
+};
 pub use format::tiff::{
     check_compression, check_tile_tags, check_tiled, parse_u32_array, parse_u64_array,
     validate_ifd, validate_ifd_strict, validate_level, validate_pyramid, ByteOrder, Compression,
-    FieldType, Ifd, IfdEntry, PyramidLevel, TiffHeader, TiffPyramid, TiffTag, TileData,
-    ValidationError, ValidationResult, ValueReader, BIGTIFF_HEADER_SIZE, TIFF_HEADER_SIZE,
+    FieldType, Ifd, IfdDescription, IfdEntry, PyramidLevel, TagDescription, TiffDescription,
+    TiffHeader, TiffMetadata, TiffPyramid, TiffTag, TileData, ValidationError, ValidationResult,
+    ValueReader, BIGTIFF_HEADER_SIZE, TIFF_HEADER_SIZE,
+};
+pub use format::{
+    detect_format, is_tiff_header, FormatPlugin, PluginSlide, SlideFiles, SlideFormat,
 };
-pub use format::{detect_format, is_tiff_header, SlideFormat};
 pub use format::{
     is_abbreviated_stream, is_complete_stream, merge_jpeg_tables, prepare_tile_jpeg,
     GenericTiffLevelData, GenericTiffReader, SvsLevelData, SvsMetadata, SvsReader,
 };
-pub use io::{create_s3_client, BlockCache, RangeReader, S3RangeReader};
+pub use io::{
+    create_s3_client, BlockCache, FileRangeReader, MemoryRangeReader, RangeReader, S3RangeReader,
+};
+pub use plan::{plan_capacity, CapacityInputs, CapacityPlan};
+pub use replay::{replay, ReplayEntry, ReplayLog, ReplayOptions, ReplayOutcome, ReplayTarget};
+pub use secrets::{resolve_secret, SecretRef};
 pub use server::{
-    auth_middleware, create_dev_router, create_production_router, create_router, health_handler,
-    slide_metadata_handler, slides_handler, tile_handler, AppState, AuthError, AuthQueryParams,
-    ErrorResponse, HealthResponse, LevelMetadataResponse, OptionalAuth, RouterConfig,
-    SignedUrlAuth, SlideMetadataResponse, SlidesQueryParams, SlidesResponse, TilePathParams,
-    TileQueryParams,
+    auth_middleware, create_dev_router, create_production_router, create_router,
+    create_split_routers, health_handler, slide_metadata_handler, slides_handler, tile_handler,
+    AppState, AuthError, AuthQueryParams, ErrorResponse, HealthResponse, LevelMetadataResponse,
+    OptionalAuth, RouterConfig, SignedUrlAuth, SlideMetadataResponse, SlidesQueryParams,
+    SlidesResponse, SplitRouters, TilePathParams, TileQueryParams,
 };
 pub use slide::{
-    CachedSlide, LevelInfo, S3SlideSource, SlideListResult, SlideReader, SlideRegistry, SlideSource,
+    CachedSlide, FailoverSlideSource, LevelInfo, MemorySlideSource, S3SlideSource, SlideListResult,
+    SlideReader, SlideRegistry, SlideSource, TileOrder, TileStream, TileStreamOptions,
 };
 pub use tile::{
-    clamp_quality, is_valid_quality, JpegTileEncoder, TileCache, TileCacheKey, TileRequest,
-    TileResponse, TileService, DEFAULT_JPEG_QUALITY, DEFAULT_TILE_CACHE_CAPACITY, MAX_JPEG_QUALITY,
+    buffer_pool_stats, clamp_quality, estimate_jpeg_quality, is_valid_quality, sample_tiles,
+    BufferPoolStats, JpegTileEncoder, OutputFormat, SampleOptions, SampledTile, Snapshot,
+    SnapshotRegion, TileCache, TileCacheKey, TileEncoder, TileRequest, TileResponse, TileService,
+    TissueMap, DEFAULT_JPEG_QUALITY, DEFAULT_TILE_CACHE_CAPACITY, MAX_JPEG_QUALITY,
     MIN_JPEG_QUALITY,
 };
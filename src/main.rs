@@ -2,28 +2,96 @@
 //!
 //! This binary starts the HTTP server and configures all components.
 
+use async_trait::async_trait;
 use clap::Parser;
+use std::future::IntoFuture;
+use std::path::PathBuf;
 use std::process::ExitCode;
-use std::time::Duration;
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant, SystemTime};
 use tracing::{error, info, warn};
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use tracing_subscriber::{
+    layer::SubscriberExt, reload, util::SubscriberInitExt, EnvFilter, Layer, Registry,
+};
 
 use wsi_streamer::{
-    config::{CheckConfig, Cli, Command, ServeConfig, SignConfig, SignOutputFormat},
+    capabilities::capabilities,
+    config::{
+        apply_config_file, apply_env_aliases, CheckConfig, Cli, Command, InspectConfig, LogFormat,
+        ManifestConfig, PlanConfig, PlanOutputFormat, ReplayConfig, ServeConfig, SignConfig,
+        SignOutputFormat, StorageBackend,
+    },
     create_s3_client,
-    server::{auth::SignedUrlAuth, create_router, RouterConfig},
-    slide::{S3SlideSource, SlideRegistry},
-    tile::TileService,
+    error::{ReplayError, TiffError},
+    format::tiff::{validate_pyramid, TiffPyramid},
+    io::FileRangeReader,
+    plan::{plan_capacity, CapacityPlan},
+    replay::{replay, ReplayLog, ReplayTarget},
+    server::{
+        auth::{AccessClaims, SignedUrlAuth},
+        create_router, create_split_routers, install_panic_hook, ConfigReloader,
+        ReloadableSettings, RouterConfig, SettingsSource, SloConfig, TrackedConnection,
+    },
+    slide::{
+        FailoverSlideSource, ManifestBuilder, MemorySlideSource, S3SlideSource, SlideRegistry,
+        SlideSource, TenantSlideSource, DEFAULT_REBALANCE_INTERVAL,
+    },
+    tile::{
+        EncodePool, FairScheduler, TenantQuota, TileCache, TileService,
+        DEFAULT_MEMORY_CHECK_INTERVAL, DEFAULT_TILE_CACHE_ENTRIES,
+    },
 };
 
-#[tokio::main]
-async fn main() -> ExitCode {
-    let cli = Cli::parse();
+fn main() -> ExitCode {
+    // Environment mutation is only sound before any other thread exists, so
+    // map WSI_STREAMER_* variables and structured lists, and apply the
+    // config file, before the async runtime starts its workers
+    if let Err(e) = apply_env_aliases() {
+        eprintln!("Configuration error: {}", e);
+        return ExitCode::FAILURE;
+    }
+    let mut cli = Cli::parse();
+
+    // Fill options not given as flags or variables from --config, then
+    // parse again so they are picked up
+    let mut config_file_vars = Vec::new();
+    if let Some(path) = cli.config_file() {
+        match apply_config_file(path) {
+            Ok(vars) => config_file_vars = vars,
+            Err(e) => {
+                eprintln!("Configuration error: {}", e);
+                return ExitCode::FAILURE;
+            }
+        }
+        cli = Cli::parse();
+    }
 
-    match cli.into_command() {
-        Command::Serve(config) => run_serve(config).await,
+    let runtime = match tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+    {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("Failed to start async runtime: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    runtime.block_on(run(cli.into_command(), config_file_vars))
+}
+
+async fn run(command: Command, config_file_vars: Vec<String>) -> ExitCode {
+    match command {
+        Command::Serve(mut config) => {
+            config.config_file_vars = config_file_vars;
+            run_serve(config).await
+        }
         Command::Sign(config) => run_sign(config),
         Command::Check(config) => run_check(config).await,
+        Command::Plan(config) => run_plan(config),
+        Command::Capabilities => run_capabilities(),
+        Command::Manifest(config) => run_manifest(config),
+        Command::Inspect(config) => run_inspect(config).await,
+        Command::Replay(config) => run_replay(config).await,
     }
 }
 
@@ -31,9 +99,22 @@ async fn main() -> ExitCode {
 // Serve Command
 // =============================================================================
 
-async fn run_serve(config: ServeConfig) -> ExitCode {
+async fn run_serve(mut config: ServeConfig) -> ExitCode {
+    // Export spans to an OTLP collector when configured; the guard flushes
+    // pending spans when the server exits
+    let (span_export, _telemetry) = match init_span_export(&config) {
+        Ok(export) => export,
+        Err(e) => {
+            eprintln!("Failed to set up span export: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
     // Initialize logging
-    init_logging(config.verbose);
+    init_logging(config.verbose, config.log_format, span_export);
+
+    // Capture backtraces of handler panics for the crash log
+    install_panic_hook();
 
     // Validate configuration
     if let Err(e) = config.validate() {
@@ -41,18 +122,326 @@ async fn run_serve(config: ServeConfig) -> ExitCode {
         return ExitCode::FAILURE;
     }
 
-    let bucket = config.bucket();
+    // Resolve secret files and references (values are never logged)
+    if let Err(e) = config.resolve_secrets().await {
+        error!("Configuration error: {}", e);
+        return ExitCode::FAILURE;
+    }
 
     // Print startup banner and info
     print_banner();
 
+    // Serve preloaded slides from memory, without a storage backend
+    if config.preloads_into_memory() {
+        info!("Configuration:");
+        log_common_config(&config);
+        info!("");
+        info!("Preloading slides into memory...");
+        let source = match preload_slides(&config).await {
+            Ok(source) => source,
+            Err(e) => {
+                error!("  Failed to preload slides: {}", e);
+                return ExitCode::FAILURE;
+            }
+        };
+        info!(
+            "  Loaded {} slide(s), {}MB; no storage backend is used from now on",
+            source.len(),
+            source.total_bytes() / (1024 * 1024)
+        );
+        return serve_source(config, source).await;
+    }
+
+    // Serve from GCS, Azure Blob Storage or a web server (validated to be
+    // compiled in)
+    match config.storage {
+        StorageBackend::S3 => {}
+        #[cfg(feature = "gcs")]
+        StorageBackend::Gcs => return serve_gcs(config).await,
+        #[cfg(feature = "azure")]
+        StorageBackend::Azure => return serve_azure(config).await,
+        #[cfg(feature = "http")]
+        StorageBackend::Http => return serve_http(config).await,
+        #[allow(unreachable_patterns)]
+        storage => {
+            error!("Storage backend {} is not supported by this build", storage);
+            return ExitCode::FAILURE;
+        }
+    }
+
+    let bucket = config.bucket();
+
     info!("Configuration:");
     info!("  S3 bucket: {}", bucket);
     if let Some(ref endpoint) = config.s3_endpoint {
         info!("  S3 endpoint: {}", endpoint);
     }
     info!("  S3 region: {}", config.s3_region);
+    info!("  S3 read attempts: {}", config.s3_max_attempts);
+    if config.has_failover() {
+        info!(
+            "  S3 failover: {} ({})",
+            config.failover_bucket(),
+            config.failover_region()
+        );
+        if let Some(ref endpoint) = config.s3_failover_endpoint {
+            info!("  S3 failover endpoint: {}", endpoint);
+        }
+    }
+
+    log_common_config(&config);
+
+    // Create S3 client
+    let s3_client = create_s3_client(config.s3_endpoint.as_deref(), &config.s3_region).await;
+
+    let failover_client = if config.has_failover() {
+        Some(
+            create_s3_client(
+                config.s3_failover_endpoint.as_deref(),
+                config.failover_region(),
+            )
+            .await,
+        )
+    } else {
+        None
+    };
 
+    // Test S3 connectivity
+    info!("");
+    info!("Connecting to S3...");
+    match test_s3_connection(&s3_client, &bucket).await {
+        Ok(slide_count) => {
+            info!("  Connected successfully");
+            info!("  Found {} slide(s) in bucket", slide_count);
+        }
+        Err(e) => {
+            // With a failover bucket, start degraded as long as it is reachable
+            let failover_ok = match failover_client {
+                Some(ref client) => {
+                    match test_s3_connection(client, &config.failover_bucket()).await {
+                        Ok(_) => true,
+                        Err(fe) => {
+                            error!("  Failover bucket is also unreachable: {}", fe);
+                            false
+                        }
+                    }
+                }
+                None => false,
+            };
+
+            if failover_ok {
+                warn!("  Primary bucket unreachable: {}", e);
+                warn!("  Serving from failover bucket until the primary recovers");
+            } else {
+                error!("  Failed to connect to S3: {}", e);
+                error!("");
+                error!("  Please check:");
+                error!("    - Your AWS credentials are configured correctly");
+                error!("    - The bucket '{}' exists and is accessible", bucket);
+                error!("    - The S3 endpoint is correct (if using MinIO/custom S3)");
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    // Create slide source
+    let retry = config.s3_retry_policy();
+    let mut source = FailoverSlideSource::new(
+        S3SlideSource::new(s3_client.clone(), bucket).with_retry_policy(retry),
+        &config.s3_region,
+    )
+    .with_cooldown(Duration::from_secs(config.s3_failover_cooldown_secs));
+    let tenant_buckets = config.parse_tenant_buckets().unwrap_or_default();
+    if !tenant_buckets.is_empty() {
+        // Tenant buckets share the main bucket's client; failover is rejected
+        // by validation, so each source only has a primary
+        let mut tenant_source = TenantSlideSource::new().with_default(source);
+        for (tenant, bucket) in tenant_buckets {
+            match test_s3_connection(&s3_client, &bucket).await {
+                Ok(slide_count) => info!(
+                    "  Tenant {}: bucket {} ({} slide(s))",
+                    tenant, bucket, slide_count
+                ),
+                Err(e) => warn!("  Tenant {}: bucket {} unreachable: {}", tenant, bucket, e),
+            }
+            tenant_source = tenant_source.with_tenant(
+                tenant,
+                FailoverSlideSource::new(
+                    S3SlideSource::new(s3_client.clone(), bucket).with_retry_policy(retry),
+                    &config.s3_region,
+                ),
+            );
+        }
+        return serve_source(config, tenant_source).await;
+    }
+    if let Some(client) = failover_client {
+        source = source.with_secondary(
+            S3SlideSource::new(client, config.failover_bucket()).with_retry_policy(retry),
+            config.failover_region(),
+        );
+
+        // Periodically probe endpoints so a recovered primary is used again
+        let probe_source = source.clone();
+        let interval = Duration::from_secs(config.s3_health_check_interval_secs);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                probe_source.probe().await;
+            }
+        });
+    }
+
+    serve_source(config, source).await
+}
+
+/// Serve slides from a Google Cloud Storage bucket.
+#[cfg(feature = "gcs")]
+async fn serve_gcs(config: ServeConfig) -> ExitCode {
+    use wsi_streamer::io::{GcsClient, GcsCredentials};
+    use wsi_streamer::slide::GcsSlideSource;
+
+    let bucket = config.bucket();
+    let credentials = match (&config.gcs_token, config.gcs_metadata_auth) {
+        (Some(token), _) => GcsCredentials::Token(token.clone()),
+        (None, true) => GcsCredentials::Metadata,
+        (None, false) => GcsCredentials::Anonymous,
+    };
+
+    info!("Configuration:");
+    info!("  GCS bucket: {}", bucket);
+    if let Some(ref endpoint) = config.gcs_endpoint {
+        info!("  GCS endpoint: {}", endpoint);
+    }
+    info!(
+        "  GCS auth: {}",
+        match credentials {
+            GcsCredentials::Anonymous => "anonymous",
+            GcsCredentials::Token(_) => "access token",
+            GcsCredentials::Metadata => "metadata server",
+        }
+    );
+    log_common_config(&config);
+
+    let client = match GcsClient::new(config.gcs_endpoint.as_deref(), credentials) {
+        Ok(client) => client,
+        Err(e) => {
+            error!("Failed to create GCS client: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let source = GcsSlideSource::new(client, bucket);
+
+    info!("");
+    info!("Connecting to GCS...");
+    if let Err(e) = test_source_connection(&source).await {
+        error!("  Failed to connect to GCS: {}", e);
+        error!("");
+        error!("  Please check:");
+        error!("    - The access token or instance service account can read the bucket");
+        error!("    - The bucket '{}' exists", source.bucket());
+        return ExitCode::FAILURE;
+    }
+
+    serve_source(config, source).await
+}
+
+/// Serve slides from an Azure Blob Storage container.
+#[cfg(feature = "azure")]
+async fn serve_azure(config: ServeConfig) -> ExitCode {
+    use wsi_streamer::io::AzureBlobClient;
+    use wsi_streamer::slide::AzureBlobSlideSource;
+
+    let container = config.bucket();
+    let account = config.azure_account.clone().unwrap_or_default();
+
+    let client = match AzureBlobClient::new(
+        &account,
+        config.azure_endpoint.as_deref(),
+        config.azure_sas_token.as_deref(),
+    ) {
+        Ok(client) => client,
+        Err(e) => {
+            error!("Failed to create Azure Blob client: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    info!("Configuration:");
+    info!("  Azure container: {}", container);
+    info!("  Azure endpoint: {}", client.endpoint());
+    info!(
+        "  Azure auth: {}",
+        if config.azure_sas_token.is_some() {
+            "SAS token"
+        } else {
+            "anonymous"
+        }
+    );
+    log_common_config(&config);
+
+    let source = AzureBlobSlideSource::new(client, container);
+
+    info!("");
+    info!("Connecting to Azure Blob Storage...");
+    if let Err(e) = test_source_connection(&source).await {
+        error!("  Failed to connect to Azure Blob Storage: {}", e);
+        error!("");
+        error!("  Please check:");
+        error!("    - The SAS token grants read and list permissions");
+        error!("    - The container '{}' exists", source.container());
+        return ExitCode::FAILURE;
+    }
+
+    serve_source(config, source).await
+}
+
+/// Serve slides from a web server supporting range requests.
+#[cfg(feature = "http")]
+async fn serve_http(config: ServeConfig) -> ExitCode {
+    use wsi_streamer::io::HttpClient;
+    use wsi_streamer::slide::HttpSlideSource;
+
+    let base_url = config
+        .resolve_http_base_url()
+        .expect("base URL should be validated before serving");
+
+    info!("Configuration:");
+    info!("  HTTP base URL: {}", base_url);
+    info!("  HTTP retries: {}", config.http_retries);
+    log_common_config(&config);
+
+    let client = match HttpClient::new() {
+        Ok(client) => client.with_retries(config.http_retries),
+        Err(e) => {
+            error!("Failed to create HTTP client: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let source = HttpSlideSource::new(client, base_url);
+
+    // Web servers can't be listed, so there is no connection test
+    info!("");
+    info!("  Slides are opened by ID; /slides listings are empty for HTTP sources");
+
+    serve_source(config, source).await
+}
+
+/// Check that a slide source can be listed, logging the slides found.
+#[cfg(any(feature = "gcs", feature = "azure"))]
+async fn test_source_connection<S: SlideSource>(source: &S) -> Result<(), String> {
+    let listing = source
+        .list_slides(1000, None, None, None)
+        .await
+        .map_err(|e| e.to_string())?;
+    info!("  Connected successfully");
+    info!("  Found {} slide(s) in bucket", listing.slides.len());
+    Ok(())
+}
+
+/// Log the auth and cache settings shared by every slide source.
+fn log_common_config(config: &ServeConfig) {
     // Auth status with warning if disabled
     if config.auth_enabled {
         info!("  Auth: enabled");
@@ -67,64 +456,311 @@ async fn run_serve(config: ServeConfig) -> ExitCode {
         config.cache_blocks,
         config.cache_tiles / (1024 * 1024)
     );
+    if config.adaptive_cache {
+        info!(
+            "  Adaptive cache: {} blocks hot, {} blocks cold",
+            config.cache_blocks_hot, config.cache_blocks_cold
+        );
+    }
+}
 
-    // Create S3 client
-    let s3_client = create_s3_client(config.s3_endpoint.as_deref(), &config.s3_region).await;
+/// Load the `--preload-into-memory` slides.
+///
+/// Local files are served under their file name; other entries are read
+/// from the S3 bucket under their key.
+async fn preload_slides(config: &ServeConfig) -> Result<MemorySlideSource, String> {
+    let mut source = MemorySlideSource::new();
+    let mut bucket_source = None;
+
+    for entry in config.preload_into_memory.iter().flatten() {
+        let path = std::path::Path::new(entry);
+        if path.is_file() {
+            let data = tokio::fs::read(path)
+                .await
+                .map_err(|e| format!("{}: {}", entry, e))?;
+            let slide_id = path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| entry.clone());
+            info!(
+                "  {} ({}MB, local file)",
+                slide_id,
+                data.len() / (1024 * 1024)
+            );
+            source.insert(slide_id, data);
+            continue;
+        }
 
-    // Test S3 connectivity
-    info!("");
-    info!("Connecting to S3...");
-    match test_s3_connection(&s3_client, &bucket).await {
-        Ok(slide_count) => {
-            info!("  Connected successfully");
-            info!("  Found {} slide(s) in bucket", slide_count);
+        if config.storage != StorageBackend::S3 {
+            return Err(format!(
+                "{} is not a local file; preloading from {} storage is not supported",
+                entry, config.storage
+            ));
         }
+        if bucket_source.is_none() {
+            let bucket = config.resolve_bucket().map_err(|_| {
+                format!(
+                    "{} is not a local file and no S3 bucket is configured",
+                    entry
+                )
+            })?;
+            let client = create_s3_client(config.s3_endpoint.as_deref(), &config.s3_region).await;
+            bucket_source = Some(
+                S3SlideSource::new(client, bucket).with_retry_policy(config.s3_retry_policy()),
+            );
+        }
+        if let Some(ref bucket_source) = bucket_source {
+            source = source
+                .preload_from(bucket_source, std::slice::from_ref(entry))
+                .await
+                .map_err(|e| format!("{}: {}", entry, e))?;
+            info!("  {} (from s3://{})", entry, bucket_source.bucket());
+        }
+    }
+
+    Ok(source)
+}
+
+/// Warm the `--warm-slides` slides.
+///
+/// Failures are logged; the server starts either way.
+async fn warm_slides<S: SlideSource + 'static>(
+    registry: &Arc<SlideRegistry<S>>,
+    entries: &[String],
+    concurrency: usize,
+) {
+    let started = Instant::now();
+    let slide_ids = match registry.resolve_slide_patterns(entries).await {
+        Ok(slide_ids) => slide_ids,
         Err(e) => {
-            error!("  Failed to connect to S3: {}", e);
-            error!("");
-            error!("  Please check:");
-            error!("    - Your AWS credentials are configured correctly");
-            error!("    - The bucket '{}' exists and is accessible", bucket);
-            error!("    - The S3 endpoint is correct (if using MinIO/custom S3)");
-            return ExitCode::FAILURE;
+            warn!("Failed to resolve slides to warm: {}", e);
+            return;
         }
+    };
+
+    info!("Warming {} slide(s)...", slide_ids.len());
+    let report = registry.warm(slide_ids, concurrency).await;
+    for (slide_id, error) in &report.failed {
+        warn!("  Failed to warm {}: {}", slide_id, error);
     }
+    info!(
+        "  Warmed {} slide(s), {} tile(s) in {:.1}s",
+        report.slides,
+        report.tiles,
+        started.elapsed().as_secs_f64()
+    );
+}
 
-    // Create slide source and registry
-    let source = S3SlideSource::new(s3_client, bucket);
+/// Build the tile service and routers over a slide source and serve them.
+async fn serve_source<S: SlideSource + 'static>(config: ServeConfig, source: S) -> ExitCode {
     let registry = SlideRegistry::with_capacity(
         source,
         config.cache_slides,
         config.block_size,
         config.cache_blocks,
-    );
+    )
+    .with_max_concurrent_opens(config.max_concurrent_opens)
+    .with_open_queue_timeout(Duration::from_millis(config.open_queue_timeout_ms))
+    .with_quarantine_threshold(config.quarantine_threshold)
+    .with_negative_cache_ttl(Duration::from_secs(config.negative_cache_ttl))
+    .with_checksum_verification(config.verify_checksums)
+    .with_read_verification(config.verify_reads)
+    .with_readahead(config.readahead_tiles)
+    .with_slide_headers(config.slide_headers)
+    .with_pyramid_detection(config.pyramid_detect_options())
+    .with_sparse_tiles(config.sparse_tiles())
+    .with_encode_pool(EncodePool::new(config.encode_workers));
+    let registry = config
+        .parse_tenant_cache_slides()
+        .unwrap_or_default()
+        .into_iter()
+        .fold(registry, |registry, (tenant, max_slides)| {
+            info!("  Tenant {} slide cache quota: {}", tenant, max_slides);
+            registry.with_tenant_slide_quota(tenant, max_slides)
+        });
+    let registry = match config.adaptive_cache_config() {
+        Some(adaptive) => registry.with_adaptive_caching(adaptive),
+        None => registry,
+    };
 
     // Create tile service
-    let tile_service = TileService::with_cache_capacity(registry, config.cache_tiles);
+    let mut tile_cache = TileCache::with_shards(
+        config.cache_tiles,
+        DEFAULT_TILE_CACHE_ENTRIES,
+        config.cache_tile_shards,
+    )
+    .with_admission(config.cache_tile_admission);
+    if config.cache_tile_ttl > 0 {
+        tile_cache = tile_cache.with_ttl(Duration::from_secs(config.cache_tile_ttl));
+    }
+    let watermark = match config.watermark() {
+        Ok(watermark) => watermark,
+        Err(e) => {
+            error!("Configuration error: watermark: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let tile_service = TileService::with_cache(registry, tile_cache)
+        .with_edge_cropping(config.crop_edge_tiles)
+        .with_retiling(config.retile_size)
+        .with_deterministic(config.deterministic)
+        .with_source_reuse(config.reuse_source_tiles)
+        .with_tissue_masks(config.tissue_masks)
+        .with_icc_profiles(config.embed_icc_profiles)
+        .with_resample_filter(config.resample_filter)
+        .with_stale_while_revalidate(config.stale_while_revalidate)
+        .with_tile_timeout(Duration::from_millis(config.tile_timeout_ms))
+        .with_memory_budget(config.max_memory)
+        .with_watermark_by_default(config.watermark_all);
+    let tile_service = match watermark {
+        Some(watermark) => {
+            info!(
+                "  Tile watermark: {} corner ({})",
+                watermark.corner(),
+                if config.watermark_all {
+                    "all tiles"
+                } else {
+                    "per access claim"
+                }
+            );
+            tile_service.with_watermark(watermark)
+        }
+        None => tile_service,
+    };
+    info!("  Encode workers: {}", tile_service.encode_pool().workers());
+    if config.deterministic {
+        warn!("Deterministic rendering enabled: source passthrough is disabled");
+    }
+
+    // Bound tile generation with fair queuing across tenants and slides
+    let tile_service = if config.max_concurrent_tiles > 0 {
+        let mut scheduler = FairScheduler::new(config.max_concurrent_tiles)
+            .with_queue_timeout(Duration::from_millis(config.tile_queue_timeout_ms))
+            .with_default_quota(TenantQuota::new(1, config.tenant_max_in_flight));
+        for (tenant, quota) in config.parse_tenant_quotas().unwrap_or_default() {
+            scheduler = scheduler.with_tenant_quota(tenant, quota);
+        }
+        info!(
+            "  Tile generation slots: {} (fair queuing)",
+            config.max_concurrent_tiles
+        );
+        tile_service.with_fair_scheduler(scheduler)
+    } else {
+        tile_service
+    };
+    if let Some(timeout) = tile_service.tile_timeout() {
+        info!("  Tile generation budget: {}ms", timeout.as_millis());
+    }
+
+    // Attach the inference sidecar if configured
+    #[cfg(feature = "inference")]
+    let tile_service = match config.inference_url {
+        Some(ref url) => {
+            let timeout = Duration::from_millis(config.inference_timeout_ms);
+            match wsi_streamer::tile::HttpTileTransformer::new(url.as_str(), timeout) {
+                Ok(transformer) => {
+                    info!("  Inference sidecar: {}", url);
+                    tile_service.with_transformer(Arc::new(transformer))
+                }
+                Err(e) => {
+                    error!("Failed to configure inference sidecar: {}", e);
+                    return ExitCode::FAILURE;
+                }
+            }
+        }
+        None => tile_service,
+    };
+
+    // Periodically evict across caches to stay within the memory budget
+    if let Some(budget) = tile_service.memory_budget() {
+        info!(
+            "  Memory budget: {}",
+            format_bytes(budget.max_bytes() as u64)
+        );
+        let budget = budget.clone();
+        let cache = tile_service.cache().clone();
+        let registry = tile_service.registry().clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(DEFAULT_MEMORY_CHECK_INTERVAL);
+            loop {
+                ticker.tick().await;
+                budget.enforce(&cache, &registry).await;
+            }
+        });
+    }
+
+    // Periodically reclassify slides and resize their block caches
+    if config.adaptive_cache {
+        let registry = tile_service.registry().clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(DEFAULT_REBALANCE_INTERVAL);
+            loop {
+                ticker.tick().await;
+                registry.classify_slides();
+            }
+        });
+    }
+
+    // Open and warm the configured slides before accepting traffic
+    if let Some(ref entries) = config.warm_slides {
+        warm_slides(tile_service.registry(), entries, config.warm_concurrency).await;
+    }
 
-    // Build router configuration
-    let router_config = build_router_config(&config);
+    // Periodically drop open slides whose file was replaced in storage
+    if config.slide_revalidate_interval > 0 {
+        let registry = tile_service.registry().clone();
+        let interval = Duration::from_secs(config.slide_revalidate_interval);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                registry.revalidate_slides().await;
+            }
+        });
+    }
 
-    // Create router
-    let router = create_router(tile_service, router_config);
+    // Build router configuration; the reloader updates the secret, CORS
+    // origins, cache max-age and log level while serving
+    let reloader = Arc::new(build_reloader(&config));
+    let router_config = build_router_config(&config).with_reloader(Arc::clone(&reloader));
+    spawn_config_watcher(&config, reloader);
+
+    // Create routers; admin surfaces move to their own listener if configured
+    let (router, admin_router) = match config.admin_bind_address() {
+        Some(_) => {
+            let routers = create_split_routers(tile_service, router_config);
+            (routers.public, Some(routers.admin))
+        }
+        None => (create_router(tile_service, router_config), None),
+    };
 
     // Bind and serve
     let addr = config.bind_address();
+    let scheme = if config.has_tls() { "https" } else { "http" };
 
     info!("");
     info!("────────────────────────────────────────────────────────────────");
-    info!("  Server listening on: http://{}", addr);
+    info!("  Server listening on: {}://{}", scheme, addr);
     info!("");
     info!("  Try these endpoints:");
-    info!("    curl http://{}/health", addr);
-    info!("    curl http://{}/slides", addr);
+    info!("    curl {}://{}/health", scheme, addr);
+    info!("    curl {}://{}/slides", scheme, addr);
     info!("");
     info!("  View slides in your browser:");
-    info!("    open http://{}/view/<slide_id>", addr);
+    info!("    open {}://{}/view/<slide_id>", scheme, addr);
     if !config.auth_enabled {
         info!("");
         info!("  Fetch a tile directly:");
-        info!("    curl http://{}/tiles/<slide_id>/0/0/0.jpg", addr);
+        info!("    curl {}://{}/tiles/<slide_id>/0/0/0.jpg", scheme, addr);
+    }
+    if let Some(admin_addr) = config.admin_bind_address() {
+        info!("");
+        info!("  Admin, debug and metrics on: http://{}", admin_addr);
+    }
+    if let Some(redirect_addr) = config.tls_redirect_address() {
+        info!("");
+        info!("  Redirecting http://{} to HTTPS", redirect_addr);
     }
     info!("────────────────────────────────────────────────────────────────");
     info!("");
@@ -137,9 +773,69 @@ async fn run_serve(config: ServeConfig) -> ExitCode {
         }
     };
 
-    if let Err(e) = axum::serve(listener, router).await {
-        error!("Server error: {}", e);
-        return ExitCode::FAILURE;
+    // Connect info registers each connection for /admin/connections and lets
+    // rate and bandwidth limits tell clients apart
+    let service = router.into_make_service_with_connect_info::<TrackedConnection>();
+    let mut servers = tokio::task::JoinSet::new();
+    match (&config.tls_cert, &config.tls_key) {
+        #[cfg(feature = "tls")]
+        (Some(cert), Some(key)) => {
+            let tls_config = match wsi_streamer::server::load_server_config(cert, key) {
+                Ok(tls_config) => tls_config,
+                Err(e) => {
+                    error!("{}", e);
+                    return ExitCode::FAILURE;
+                }
+            };
+            let listener = match wsi_streamer::server::TlsListener::new(listener, tls_config) {
+                Ok(listener) => listener,
+                Err(e) => {
+                    error!("Failed to listen on {}: {}", addr, e);
+                    return ExitCode::FAILURE;
+                }
+            };
+            servers.spawn(axum::serve(listener, service).into_future());
+        }
+        _ => {
+            servers.spawn(axum::serve(listener, service).into_future());
+        }
+    }
+
+    if let (Some(admin_router), Some(admin_addr)) = (admin_router, config.admin_bind_address()) {
+        let admin_listener = match tokio::net::TcpListener::bind(&admin_addr).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                error!("Failed to bind admin listener to {}: {}", admin_addr, e);
+                return ExitCode::FAILURE;
+            }
+        };
+        let admin_service = admin_router.into_make_service_with_connect_info::<TrackedConnection>();
+        servers.spawn(axum::serve(admin_listener, admin_service).into_future());
+    }
+
+    #[cfg(feature = "tls")]
+    if let Some(redirect_addr) = config.tls_redirect_address() {
+        let redirect_listener = match tokio::net::TcpListener::bind(&redirect_addr).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                error!(
+                    "Failed to bind redirect listener to {}: {}",
+                    redirect_addr, e
+                );
+                return ExitCode::FAILURE;
+            }
+        };
+        let redirect_router = wsi_streamer::server::https_redirect_router(config.port);
+        servers.spawn(axum::serve(redirect_listener, redirect_router).into_future());
+    }
+
+    // Any listener failing stops the server
+    while let Some(result) = servers.join_next().await {
+        let result = result.map_err(std::io::Error::other).and_then(|r| r);
+        if let Err(e) = result {
+            error!("Server error: {}", e);
+            return ExitCode::FAILURE;
+        }
     }
 
     ExitCode::SUCCESS
@@ -176,6 +872,7 @@ async fn test_s3_connection(client: &aws_sdk_s3::Client, bucket: &str) -> Result
                     k_lower.ends_with(".svs")
                         || k_lower.ends_with(".tif")
                         || k_lower.ends_with(".tiff")
+                        || k_lower.ends_with(".scn")
                 })
                 .unwrap_or(false)
         })
@@ -184,26 +881,194 @@ async fn test_s3_connection(client: &aws_sdk_s3::Client, bucket: &str) -> Result
     Ok(count)
 }
 
+/// Tracing layer exporting spans, added alongside the log output.
+type SpanExportLayer = Box<dyn Layer<Registry> + Send + Sync>;
+
+/// Keeps the span exporter running; dropping it flushes pending spans.
+#[cfg(feature = "otel")]
+type TelemetryGuard = Option<opentelemetry_sdk::trace::SdkTracerProvider>;
+#[cfg(not(feature = "otel"))]
+type TelemetryGuard = ();
+
+/// Set up OTLP span export if `--otel-endpoint` is configured.
+#[cfg(feature = "otel")]
+fn init_span_export(
+    config: &ServeConfig,
+) -> Result<(Option<SpanExportLayer>, TelemetryGuard), String> {
+    use opentelemetry::trace::TracerProvider as _;
+
+    let Some(endpoint) = config.otel_endpoint.as_deref() else {
+        return Ok((None, None));
+    };
+    let provider = wsi_streamer::telemetry::tracer_provider(endpoint, config.otel_sample_rate)
+        .map_err(|e| e.to_string())?;
+    let tracer = provider.tracer(wsi_streamer::telemetry::OTEL_SERVICE_NAME);
+    let layer = tracing_opentelemetry::layer().with_tracer(tracer);
+    Ok((Some(Box::new(layer)), Some(provider)))
+}
+
+/// Set up OTLP span export (not compiled in; rejected by validation).
+#[cfg(not(feature = "otel"))]
+fn init_span_export(
+    _config: &ServeConfig,
+) -> Result<(Option<SpanExportLayer>, TelemetryGuard), String> {
+    Ok((None, ()))
+}
+
 /// Initialize the tracing/logging subsystem.
-fn init_logging(verbose: bool) {
-    let env_filter = if verbose {
+fn init_logging(verbose: bool, format: LogFormat, span_export: Option<SpanExportLayer>) {
+    // A filter from RUST_LOG is left alone by reloads
+    let (env_filter, reloadable) = match EnvFilter::try_from_default_env() {
+        Ok(env_filter) => (env_filter, false),
+        Err(_) => (EnvFilter::new(log_filter(verbose)), true),
+    };
+    let (env_filter, filter_handle) = reload::Layer::new(env_filter);
+    if reloadable {
+        let _ = LOG_FILTER_RELOAD.set(Box::new(move |filter| {
+            let filter = EnvFilter::try_new(filter).map_err(|e| e.to_string())?;
+            filter_handle.reload(filter).map_err(|e| e.to_string())
+        }));
+    }
+
+    // The filter applies to exported spans as well as log lines
+    let registry = tracing_subscriber::registry()
+        .with(span_export)
+        .with(env_filter);
+    match format {
+        LogFormat::Text => registry
+            .with(
+                tracing_subscriber::fmt::layer()
+                    .compact()
+                    .with_target(false)
+                    .without_time(),
+            )
+            .init(),
+        // Timestamps and span fields (request ID, tile coordinates) are kept
+        // so each line stands on its own in a log aggregator
+        LogFormat::Json => registry
+            .with(
+                tracing_subscriber::fmt::layer()
+                    .json()
+                    .with_current_span(true)
+                    .with_span_list(true),
+            )
+            .init(),
+    }
+}
+
+/// Log filter used when `RUST_LOG` isn't set.
+fn log_filter(verbose: bool) -> &'static str {
+    if verbose {
         "wsi_streamer=debug,tower_http=debug"
     } else {
         "wsi_streamer=info,tower_http=info"
-    };
+    }
+}
 
-    tracing_subscriber::registry()
-        .with(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| env_filter.into()),
-        )
-        .with(
-            tracing_subscriber::fmt::layer()
-                .compact()
-                .with_target(false)
-                .without_time(),
-        )
-        .init();
+/// Replaces the log filter of the running subscriber.
+type LogFilterReload = Box<dyn Fn(&str) -> Result<(), String> + Send + Sync>;
+
+/// Set by [`init_logging`] unless the filter comes from `RUST_LOG`.
+static LOG_FILTER_RELOAD: OnceLock<LogFilterReload> = OnceLock::new();
+
+/// Reads the reloadable settings by parsing the configuration again.
+///
+/// Flags and variables parse as they did at startup, so only the
+/// configuration file and the secrets it points to can change.
+struct ServeSettings {
+    /// Reloadable options set by flags or variables
+    pinned: Vec<&'static str>,
+}
+
+#[async_trait]
+impl SettingsSource for ServeSettings {
+    async fn load(&self) -> Result<ReloadableSettings, String> {
+        let cli = Cli::try_parse().map_err(|e| e.to_string())?;
+        let Command::Serve(mut config) = cli.into_command() else {
+            return Err("not running the serve command".to_string());
+        };
+        config.reload_from_file(&self.pinned)?;
+        config.validate()?;
+        config.resolve_secrets().await.map_err(|e| e.to_string())?;
+
+        Ok(ReloadableSettings {
+            log_filter: Some(log_filter(config.verbose).to_string()),
+            auth_secret: config.auth_secret,
+            cors_origins: config.cors_origins,
+            cache_max_age: config.cache_max_age,
+        })
+    }
+}
+
+/// Create the reloader for the serve configuration.
+fn build_reloader(config: &ServeConfig) -> ConfigReloader {
+    let reloader = ConfigReloader::new(ServeSettings {
+        pinned: config.pinned_options(),
+    });
+    match LOG_FILTER_RELOAD.get() {
+        Some(reload) => reloader.with_log_filter(log_filter(config.verbose), reload),
+        None => reloader,
+    }
+}
+
+/// Reload settings on `SIGHUP` and when the configuration or secret file
+/// changes.
+fn spawn_config_watcher(config: &ServeConfig, reloader: Arc<ConfigReloader>) {
+    let files: Vec<PathBuf> = config
+        .config
+        .iter()
+        .chain(config.auth_secret_file.iter())
+        .cloned()
+        .collect();
+
+    if config.config_watch_interval_secs > 0 && !files.is_empty() {
+        let reloader = Arc::clone(&reloader);
+        let interval = Duration::from_secs(config.config_watch_interval_secs);
+        tokio::spawn(async move {
+            let modified_times = |files: &[PathBuf]| -> Vec<Option<SystemTime>> {
+                files
+                    .iter()
+                    .map(|file| std::fs::metadata(file).and_then(|m| m.modified()).ok())
+                    .collect()
+            };
+            let mut modified = modified_times(&files);
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                let current = modified_times(&files);
+                if current != modified {
+                    modified = current;
+                    reload_config(&reloader, "file change").await;
+                }
+            }
+        });
+    }
+
+    #[cfg(unix)]
+    tokio::spawn(async move {
+        use tokio::signal::unix::{signal, SignalKind};
+        let mut hangup = match signal(SignalKind::hangup()) {
+            Ok(hangup) => hangup,
+            Err(e) => {
+                warn!("Failed to listen for SIGHUP: {}", e);
+                return;
+            }
+        };
+        while hangup.recv().await.is_some() {
+            reload_config(&reloader, "SIGHUP").await;
+        }
+    });
+}
+
+async fn reload_config(reloader: &ConfigReloader, trigger: &str) {
+    match reloader.reload().await {
+        Ok(report) => info!(trigger, changed = ?report.changed, "Configuration reloaded"),
+        Err(e) => error!(
+            trigger,
+            "Configuration reload failed, keeping current settings: {}", e
+        ),
+    }
 }
 
 /// Build RouterConfig from the application ServeConfig.
@@ -214,6 +1079,9 @@ fn build_router_config(config: &ServeConfig) -> RouterConfig {
         RouterConfig::without_auth()
     };
 
+    router_config = router_config
+        .with_secret_rotation_grace(Duration::from_secs(config.auth_secret_grace_secs));
+
     // Apply cache max-age
     router_config = router_config.with_cache_max_age(config.cache_max_age);
 
@@ -222,8 +1090,48 @@ fn build_router_config(config: &ServeConfig) -> RouterConfig {
         router_config = router_config.with_cors_origins(origins.clone());
     }
 
-    // Apply tracing setting
+    // Apply tracing and compression settings
     router_config = router_config.with_tracing(!config.no_tracing);
+    router_config = router_config.with_compression(!config.no_compression);
+    if config.server_timing {
+        warn!("Server-Timing headers enabled: request phase timings are sent to clients");
+        router_config = router_config.with_server_timing(true);
+    }
+
+    // Apply SLO targets
+    router_config = router_config.with_slo(SloConfig {
+        availability_target: config.slo_availability,
+        latency_target: config.slo_latency,
+        latency_threshold: Duration::from_millis(config.slo_latency_threshold_ms),
+    });
+
+    // Apply tenant header for fair scheduling
+    if let Some(ref header) = config.tenant_header {
+        router_config = router_config.with_tenant_header(header.clone());
+    }
+
+    // Apply per-connection bandwidth limits (validated at startup)
+    let bandwidth = config.bandwidth_config().unwrap_or_default();
+    if bandwidth.is_enabled() {
+        info!(
+            "  Bandwidth limit: {} bytes/s per connection, {} key limit(s)",
+            bandwidth.default_rate,
+            bandwidth.key_rates.len()
+        );
+        router_config = router_config.with_bandwidth(bandwidth);
+    }
+
+    // Apply per-client request rate limits
+    let rate_limit = config.rate_limit_config();
+    if rate_limit.is_enabled() {
+        info!(
+            "  Rate limit: {} requests/s per {} (burst {})",
+            rate_limit.requests_per_sec,
+            rate_limit.key.name(),
+            rate_limit.effective_burst()
+        );
+        router_config = router_config.with_rate_limit(rate_limit);
+    }
 
     router_config
 }
@@ -252,12 +1160,25 @@ fn run_sign(config: SignConfig) -> ExitCode {
     let auth = SignedUrlAuth::new(&config.secret);
     let ttl = Duration::from_secs(config.ttl);
 
-    let params_ref: Vec<(&str, &str)> = params
-        .iter()
-        .map(|(k, v)| (k.as_str(), v.as_str()))
-        .collect();
+    let (signature, expiry, params) = if let Some(ref claims) = config.claims {
+        // Validated above; the signature covers the claims instead of the path
+        let claims = AccessClaims::parse(claims).expect("claims validated");
+        let (signature, expiry) = auth.sign_claims(&claims, ttl);
+        let mut claim_params = vec![(
+            "claims".to_string(),
+            urlencoding::encode(&claims.encode()).into_owned(),
+        )];
+        claim_params.extend(params);
+        (signature, expiry, claim_params)
+    } else {
+        let params_ref: Vec<(&str, &str)> = params
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+            .collect();
 
-    let (signature, expiry) = auth.sign_with_params(&config.path, ttl, &params_ref);
+        let (signature, expiry) = auth.sign_with_params(&config.path, ttl, &params_ref);
+        (signature, expiry, params)
+    };
 
     // Output based on format
     match config.format {
@@ -333,7 +1254,7 @@ fn build_query_string(params: &[(String, String)], expiry: u64, signature: &str)
 async fn run_check(config: CheckConfig) -> ExitCode {
     // Initialize minimal logging for check command
     if config.verbose {
-        init_logging(true);
+        init_logging(true, LogFormat::Text, None);
     }
 
     println!("WSI Streamer Configuration Check");
@@ -470,6 +1391,7 @@ async fn list_slides(client: &aws_sdk_s3::Client, bucket: &str) -> Result<Vec<St
                 if key_lower.ends_with(".svs")
                     || key_lower.ends_with(".tif")
                     || key_lower.ends_with(".tiff")
+                    || key_lower.ends_with(".scn")
                 {
                     slides.push(key.to_string());
                 }
@@ -485,3 +1407,284 @@ async fn list_slides(client: &aws_sdk_s3::Client, bucket: &str) -> Result<Vec<St
 
     Ok(slides)
 }
+
+// =============================================================================
+// Plan Command
+// =============================================================================
+
+fn run_plan(config: PlanConfig) -> ExitCode {
+    if let Err(e) = config.validate() {
+        eprintln!("Error: {}", e);
+        return ExitCode::FAILURE;
+    }
+
+    let plan = plan_capacity(&config.inputs());
+
+    match config.format {
+        PlanOutputFormat::Json => {
+            let json = serde_json::json!({
+                "plan": plan,
+                "serve_flags": plan.serve_flags(),
+            });
+            println!("{}", serde_json::to_string_pretty(&json).unwrap());
+        }
+        PlanOutputFormat::Text => print_plan(&config, &plan),
+    }
+
+    ExitCode::SUCCESS
+}
+
+/// Print a human-readable capacity plan.
+fn print_plan(config: &PlanConfig, plan: &CapacityPlan) {
+    println!("WSI Streamer Capacity Plan");
+    println!("==========================");
+    println!();
+    println!("Workload:");
+    println!("  Slides:             {}", config.slides);
+    println!(
+        "  Avg slide size:     {}",
+        format_bytes(config.avg_slide_size)
+    );
+    println!("  Concurrent viewers: {}", config.concurrency);
+    println!("  Tile requests:      {:.0}/s", plan.tile_requests_per_sec);
+    println!();
+    println!("Memory:");
+    println!(
+        "  Tile cache:         {}",
+        format_bytes(plan.tile_cache_bytes)
+    );
+    println!(
+        "  Block caches:       {} ({} slides x {} blocks x {})",
+        format_bytes(plan.block_cache_bytes),
+        plan.slide_cache_capacity,
+        plan.blocks_per_slide,
+        format_bytes(plan.block_size)
+    );
+    println!(
+        "  Slide metadata:     {}",
+        format_bytes(plan.metadata_bytes)
+    );
+    println!(
+        "  Total:              {} (with headroom)",
+        format_bytes(plan.total_memory_bytes)
+    );
+    println!();
+    println!("S3:");
+    println!("  GET requests:       {:.1}/s", plan.s3_gets_per_sec);
+    println!(
+        "  Egress:             {}/s",
+        format_bytes(plan.s3_egress_bytes_per_sec as u64)
+    );
+    println!();
+    println!("Suggested serve flags:");
+    for flag in plan.serve_flags() {
+        println!("  {}", flag);
+    }
+    println!();
+    println!("Estimates use baseline model constants; validate against production metrics.");
+}
+
+/// Format a byte count with a binary unit suffix.
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+// =============================================================================
+// Capabilities Command
+// =============================================================================
+
+fn run_capabilities() -> ExitCode {
+    println!("{}", serde_json::to_string_pretty(&capabilities()).unwrap());
+    ExitCode::SUCCESS
+}
+
+// =============================================================================
+// Manifest Command
+// =============================================================================
+
+fn run_manifest(config: ManifestConfig) -> ExitCode {
+    match write_manifest(&config) {
+        Ok(path) => {
+            println!("Wrote {}", path.display());
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Digest a local file and write its manifest next to it.
+fn write_manifest(config: &ManifestConfig) -> std::io::Result<std::path::PathBuf> {
+    use std::io::Read;
+
+    let mut file = std::fs::File::open(&config.file)?;
+    let mut builder = ManifestBuilder::new(config.block_size);
+    let mut buffer = vec![0u8; 8 * 1024 * 1024];
+    loop {
+        let n = file.read(&mut buffer)?;
+        if n == 0 {
+            break;
+        }
+        builder.update(&buffer[..n]);
+    }
+
+    let name = config
+        .file
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let path = config.output_path();
+    std::fs::write(&path, builder.finish().to_text(&name))?;
+    Ok(path)
+}
+
+// =============================================================================
+// Inspect Command
+// =============================================================================
+
+async fn run_inspect(config: InspectConfig) -> ExitCode {
+    match inspect(&config).await {
+        Ok(report) => {
+            println!("{}", serde_json::to_string_pretty(&report).unwrap());
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Dump a local slide's tags, with the validation result of its pyramid.
+async fn inspect(config: &InspectConfig) -> Result<serde_json::Value, TiffError> {
+    let reader = FileRangeReader::open(&config.file)?;
+    let pyramid = TiffPyramid::parse(&reader).await?;
+    let validation = validate_pyramid(&pyramid);
+
+    let mut report = serde_json::to_value(pyramid.describe(&reader).await).unwrap();
+    report["validation"] = serde_json::json!({
+        "valid": validation.is_valid,
+        "errors": validation.errors.iter().map(|e| e.to_string()).collect::<Vec<_>>(),
+        "warnings": validation.warnings,
+    });
+    Ok(report)
+}
+
+// =============================================================================
+// Replay Command
+// =============================================================================
+
+/// Run the replay command.
+async fn run_replay(config: ReplayConfig) -> ExitCode {
+    if let Err(e) = config.validate() {
+        eprintln!("Error: {}", e);
+        return ExitCode::FAILURE;
+    }
+
+    let target = match ReplayTarget::parse(&config.target) {
+        Ok(target) => target,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let log = match std::fs::read_to_string(&config.file)
+        .map_err(|e| ReplayError::Read(e.to_string()))
+        .and_then(|input| ReplayLog::parse(&input))
+    {
+        Ok(log) => log,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if log.entries.is_empty() {
+        eprintln!(
+            "Error: no GET or HEAD requests found in {}",
+            config.file.display()
+        );
+        return ExitCode::FAILURE;
+    }
+
+    println!(
+        "Replaying {} requests spanning {:.1}s against {}",
+        log.entries.len(),
+        log.duration().as_secs_f64(),
+        config.target
+    );
+    if log.skipped > 0 {
+        println!("Skipped {} unparseable or non-GET entries", log.skipped);
+    }
+    println!();
+
+    let outcomes = replay(&log, &target, &config.options()).await;
+
+    let mut failed = 0;
+    let mut mismatched = 0;
+    for outcome in &outcomes {
+        let entry = &outcome.entry;
+        let result = match (outcome.status, &outcome.error) {
+            (Some(status), _) => status.to_string(),
+            (None, Some(error)) => format!("error: {}", error),
+            (None, None) => "error".to_string(),
+        };
+        let expected = match entry.expected_status {
+            Some(expected) if outcome.is_mismatch() => format!(" (expected {})", expected),
+            _ => String::new(),
+        };
+
+        if outcome.status.is_none() {
+            failed += 1;
+        } else if outcome.is_mismatch() {
+            mismatched += 1;
+        }
+        if config.verbose || outcome.status.is_none() || outcome.is_mismatch() {
+            println!(
+                "  +{:>8.3}s  {} {} -> {}{}  {:.1}ms  {}B",
+                entry.offset.as_secs_f64(),
+                entry.method,
+                entry.path,
+                result,
+                expected,
+                outcome.latency.as_secs_f64() * 1000.0,
+                outcome.body_bytes
+            );
+        }
+    }
+
+    let mut latencies: Vec<_> = outcomes.iter().map(|o| o.latency).collect();
+    latencies.sort();
+    let p50 = latencies[latencies.len() / 2];
+    let p99 = latencies[(latencies.len() * 99 / 100).min(latencies.len() - 1)];
+
+    println!();
+    println!("Requests:         {}", outcomes.len());
+    println!("Failed:           {}", failed);
+    println!("Status mismatch:  {}", mismatched);
+    println!(
+        "Latency p50/p99:  {:.1}ms / {:.1}ms",
+        p50.as_secs_f64() * 1000.0,
+        p99.as_secs_f64() * 1000.0
+    );
+
+    if failed > 0 || mismatched > 0 {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
@@ -0,0 +1,487 @@
+//! GeoJSON annotation sets.
+//!
+//! Coordinates are level 0 pixels, x to the right and y down, as exported by
+//! QuPath and most slide annotation tools. A document is a
+//! `FeatureCollection`, a single `Feature`, a bare geometry or a JSON array
+//! of features. Supported geometries are `Point`, `LineString`, `Polygon`,
+//! their `Multi*` variants and `GeometryCollection`.
+//!
+//! A feature's color is read from `properties.color` or, as QuPath writes
+//! it, `properties.classification.color`; either a `"#rrggbb"` string or an
+//! `[r, g, b]` array. Features without a (valid) color use
+//! [`DEFAULT_ANNOTATION_COLOR`].
+
+use bytes::Bytes;
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+
+use crate::error::AnnotationError;
+
+// =============================================================================
+// Configuration
+// =============================================================================
+
+/// Maximum size of an uploaded annotation document in bytes.
+pub const MAX_ANNOTATION_BYTES: usize = 32 * 1024 * 1024;
+
+/// Maximum number of vertices in an annotation set.
+pub const MAX_ANNOTATION_VERTICES: usize = 1_000_000;
+
+/// Color of features without a color property.
+pub const DEFAULT_ANNOTATION_COLOR: [u8; 3] = [255, 204, 0];
+
+// =============================================================================
+// Shapes
+// =============================================================================
+
+/// A position in level 0 pixel coordinates.
+pub type Point = [f64; 2];
+
+/// A drawable geometry.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Shape {
+    /// Single position, drawn as a dot
+    Point(Point),
+
+    /// Open polyline
+    Line(Vec<Point>),
+
+    /// Exterior ring followed by its holes
+    Polygon(Vec<Vec<Point>>),
+}
+
+impl Shape {
+    /// All vertices of the shape.
+    fn vertices(&self) -> Box<dyn Iterator<Item = &Point> + '_> {
+        match self {
+            Shape::Point(point) => Box::new(std::iter::once(point)),
+            Shape::Line(points) => Box::new(points.iter()),
+            Shape::Polygon(rings) => Box::new(rings.iter().flatten()),
+        }
+    }
+
+    /// Bounding box of the shape.
+    pub fn bounds(&self) -> Bounds {
+        self.vertices()
+            .fold(Bounds::EMPTY, |bounds, &[x, y]| Bounds {
+                min_x: bounds.min_x.min(x),
+                min_y: bounds.min_y.min(y),
+                max_x: bounds.max_x.max(x),
+                max_y: bounds.max_y.max(y),
+            })
+    }
+}
+
+/// Axis-aligned bounding box in level 0 pixel coordinates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bounds {
+    pub min_x: f64,
+    pub min_y: f64,
+    pub max_x: f64,
+    pub max_y: f64,
+}
+
+impl Bounds {
+    /// Bounds containing nothing.
+    const EMPTY: Bounds = Bounds {
+        min_x: f64::INFINITY,
+        min_y: f64::INFINITY,
+        max_x: f64::NEG_INFINITY,
+        max_y: f64::NEG_INFINITY,
+    };
+}
+
+/// A shape with its drawing color.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Annotation {
+    /// Geometry in level 0 pixel coordinates
+    pub shape: Shape,
+
+    /// Stroke and fill color
+    pub color: [u8; 3],
+
+    /// Bounding box of the shape, used to skip tiles it doesn't touch
+    pub bounds: Bounds,
+}
+
+// =============================================================================
+// Annotation Set
+// =============================================================================
+
+/// Annotations of a slide, parsed from a GeoJSON document.
+#[derive(Debug, Clone)]
+pub struct AnnotationSet {
+    /// Shapes in document order; multi-geometries are split up
+    annotations: Vec<Annotation>,
+
+    /// Number of features in the document
+    feature_count: usize,
+
+    /// Content hash of the document
+    revision: u64,
+
+    /// The document as uploaded
+    geojson: Bytes,
+}
+
+impl AnnotationSet {
+    /// Parse a GeoJSON document.
+    ///
+    /// # Errors
+    ///
+    /// - [`AnnotationError::InvalidGeoJson`] if the document is not JSON or
+    ///   holds an unsupported or malformed geometry
+    /// - [`AnnotationError::TooLarge`] if it has more than
+    ///   [`MAX_ANNOTATION_VERTICES`] vertices
+    pub fn from_geojson(geojson: Bytes) -> Result<Self, AnnotationError> {
+        let value: Value = serde_json::from_slice(&geojson)
+            .map_err(|e| invalid(format!("not valid JSON: {}", e)))?;
+
+        let mut parser = Parser::default();
+        parser.document(&value)?;
+
+        let digest = Sha256::digest(&geojson);
+        let revision = u64::from_be_bytes(digest[..8].try_into().unwrap());
+
+        Ok(Self {
+            annotations: parser.annotations,
+            feature_count: parser.features,
+            revision,
+            geojson,
+        })
+    }
+
+    /// Shapes of the set.
+    pub fn annotations(&self) -> &[Annotation] {
+        &self.annotations
+    }
+
+    /// Number of shapes.
+    pub fn len(&self) -> usize {
+        self.annotations.len()
+    }
+
+    /// Whether the set has no shapes to draw.
+    pub fn is_empty(&self) -> bool {
+        self.annotations.is_empty()
+    }
+
+    /// Number of features in the uploaded document.
+    pub fn feature_count(&self) -> usize {
+        self.feature_count
+    }
+
+    /// Content hash of the uploaded document; changes whenever it does.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// The uploaded document.
+    pub fn geojson(&self) -> &Bytes {
+        &self.geojson
+    }
+}
+
+// =============================================================================
+// Parsing
+// =============================================================================
+
+fn invalid(message: impl Into<String>) -> AnnotationError {
+    AnnotationError::InvalidGeoJson {
+        message: message.into(),
+    }
+}
+
+/// The `type` member of a GeoJSON object.
+fn type_of(object: &Map<String, Value>) -> Result<&str, AnnotationError> {
+    object
+        .get("type")
+        .and_then(Value::as_str)
+        .ok_or_else(|| invalid("object without a type"))
+}
+
+fn array<'a>(value: &'a Value, what: &str) -> Result<&'a [Value], AnnotationError> {
+    value
+        .as_array()
+        .map(Vec::as_slice)
+        .ok_or_else(|| invalid(format!("{} must be an array", what)))
+}
+
+fn point(value: &Value) -> Result<Point, AnnotationError> {
+    match array(value, "position")? {
+        [x, y, ..] => match (x.as_f64(), y.as_f64()) {
+            (Some(x), Some(y)) => Ok([x, y]),
+            _ => Err(invalid("position coordinates must be numbers")),
+        },
+        _ => Err(invalid("position must have two coordinates")),
+    }
+}
+
+fn points(value: &Value, min: usize, what: &str) -> Result<Vec<Point>, AnnotationError> {
+    let points = array(value, what)?
+        .iter()
+        .map(point)
+        .collect::<Result<Vec<_>, _>>()?;
+    if points.len() < min {
+        return Err(invalid(format!(
+            "{} needs at least {} positions",
+            what, min
+        )));
+    }
+    Ok(points)
+}
+
+fn polygon(value: &Value) -> Result<Vec<Vec<Point>>, AnnotationError> {
+    let rings = array(value, "polygon")?
+        .iter()
+        .map(|ring| points(ring, 3, "polygon ring"))
+        .collect::<Result<Vec<_>, _>>()?;
+    if rings.is_empty() {
+        return Err(invalid("polygon needs an exterior ring"));
+    }
+    Ok(rings)
+}
+
+/// Parse a `"#rrggbb"` or `[r, g, b]` color.
+fn color(value: &Value) -> Option<[u8; 3]> {
+    match value {
+        Value::String(hex) => {
+            let hex = hex.strip_prefix('#')?;
+            if hex.len() != 6 {
+                return None;
+            }
+            let rgb = u32::from_str_radix(hex, 16).ok()?;
+            Some([(rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8])
+        }
+        Value::Array(channels) => match channels.as_slice() {
+            [r, g, b] => Some([
+                u8::try_from(r.as_u64()?).ok()?,
+                u8::try_from(g.as_u64()?).ok()?,
+                u8::try_from(b.as_u64()?).ok()?,
+            ]),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Color of a feature, from its properties.
+fn feature_color(properties: &Value) -> Option<[u8; 3]> {
+    properties.get("color").and_then(color).or_else(|| {
+        properties
+            .get("classification")
+            .and_then(|classification| classification.get("color"))
+            .and_then(color)
+    })
+}
+
+/// Collects the shapes of a document.
+#[derive(Default)]
+struct Parser {
+    annotations: Vec<Annotation>,
+    features: usize,
+    vertices: usize,
+}
+
+impl Parser {
+    fn document(&mut self, value: &Value) -> Result<(), AnnotationError> {
+        match value {
+            Value::Array(features) => features.iter().try_for_each(|f| self.feature(f)),
+            Value::Object(object) => match type_of(object)? {
+                "FeatureCollection" => {
+                    let features = object
+                        .get("features")
+                        .ok_or_else(|| invalid("FeatureCollection without features"))?;
+                    array(features, "features")?
+                        .iter()
+                        .try_for_each(|f| self.feature(f))
+                }
+                "Feature" => self.feature(value),
+                _ => {
+                    self.features += 1;
+                    self.geometry(value, DEFAULT_ANNOTATION_COLOR)
+                }
+            },
+            _ => Err(invalid("expected a GeoJSON object or an array of features")),
+        }
+    }
+
+    fn feature(&mut self, value: &Value) -> Result<(), AnnotationError> {
+        let object = value
+            .as_object()
+            .filter(|object| type_of(object).ok() == Some("Feature"))
+            .ok_or_else(|| invalid("expected a Feature"))?;
+        self.features += 1;
+
+        let color = object
+            .get("properties")
+            .and_then(feature_color)
+            .unwrap_or(DEFAULT_ANNOTATION_COLOR);
+        match object.get("geometry") {
+            None | Some(Value::Null) => Ok(()),
+            Some(geometry) => self.geometry(geometry, color),
+        }
+    }
+
+    fn geometry(&mut self, value: &Value, color: [u8; 3]) -> Result<(), AnnotationError> {
+        let object = value
+            .as_object()
+            .ok_or_else(|| invalid("geometry must be an object"))?;
+        let kind = type_of(object)?;
+
+        if kind == "GeometryCollection" {
+            let geometries = object
+                .get("geometries")
+                .ok_or_else(|| invalid("GeometryCollection without geometries"))?;
+            return array(geometries, "geometries")?
+                .iter()
+                .try_for_each(|geometry| self.geometry(geometry, color));
+        }
+
+        let coordinates = object
+            .get("coordinates")
+            .ok_or_else(|| invalid(format!("{} without coordinates", kind)))?;
+        match kind {
+            "Point" => self.push(Shape::Point(point(coordinates)?), color),
+            "MultiPoint" => array(coordinates, "MultiPoint")?
+                .iter()
+                .try_for_each(|c| self.push(Shape::Point(point(c)?), color)),
+            "LineString" => self.push(Shape::Line(points(coordinates, 2, "LineString")?), color),
+            "MultiLineString" => array(coordinates, "MultiLineString")?
+                .iter()
+                .try_for_each(|c| self.push(Shape::Line(points(c, 2, "LineString")?), color)),
+            "Polygon" => self.push(Shape::Polygon(polygon(coordinates)?), color),
+            "MultiPolygon" => array(coordinates, "MultiPolygon")?
+                .iter()
+                .try_for_each(|c| self.push(Shape::Polygon(polygon(c)?), color)),
+            other => Err(invalid(format!("unsupported geometry type {}", other))),
+        }
+    }
+
+    fn push(&mut self, shape: Shape, color: [u8; 3]) -> Result<(), AnnotationError> {
+        self.vertices += shape.vertices().count();
+        if self.vertices > MAX_ANNOTATION_VERTICES {
+            return Err(AnnotationError::TooLarge {
+                count: self.vertices,
+                max: MAX_ANNOTATION_VERTICES,
+            });
+        }
+
+        let bounds = shape.bounds();
+        self.annotations.push(Annotation {
+            shape,
+            color,
+            bounds,
+        });
+        Ok(())
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(json: &str) -> Result<AnnotationSet, AnnotationError> {
+        AnnotationSet::from_geojson(Bytes::copy_from_slice(json.as_bytes()))
+    }
+
+    #[test]
+    fn test_feature_collection() {
+        let set = parse(
+            r##"{
+                "type": "FeatureCollection",
+                "features": [
+                    {
+                        "type": "Feature",
+                        "properties": {"color": "#ff0000"},
+                        "geometry": {"type": "Polygon", "coordinates": [[[10, 10], [50, 10], [50, 40], [10, 10]]]}
+                    },
+                    {
+                        "type": "Feature",
+                        "properties": {"classification": {"name": "Tumor", "color": [0, 0, 255]}},
+                        "geometry": {"type": "MultiPoint", "coordinates": [[1, 2], [3, 4]]}
+                    },
+                    {"type": "Feature", "properties": null, "geometry": null}
+                ]
+            }"##,
+        )
+        .unwrap();
+
+        assert_eq!(set.feature_count(), 3);
+        assert_eq!(set.len(), 3);
+        let polygon = &set.annotations()[0];
+        assert_eq!(polygon.color, [255, 0, 0]);
+        assert_eq!(
+            polygon.bounds,
+            Bounds {
+                min_x: 10.0,
+                min_y: 10.0,
+                max_x: 50.0,
+                max_y: 40.0
+            }
+        );
+        assert_eq!(set.annotations()[1].shape, Shape::Point([1.0, 2.0]));
+        assert_eq!(set.annotations()[2].color, [0, 0, 255]);
+    }
+
+    #[test]
+    fn test_other_documents() {
+        // Bare geometry, default color
+        let set = parse(r#"{"type": "LineString", "coordinates": [[0, 0], [5, 5]]}"#).unwrap();
+        assert_eq!(set.feature_count(), 1);
+        assert_eq!(set.annotations()[0].color, DEFAULT_ANNOTATION_COLOR);
+
+        // Array of features with a geometry collection
+        let set = parse(
+            r#"[{"type": "Feature", "geometry": {"type": "GeometryCollection", "geometries": [
+                {"type": "Point", "coordinates": [1, 1]},
+                {"type": "MultiLineString", "coordinates": [[[0, 0], [1, 1]], [[2, 2], [3, 3]]]}
+            ]}}]"#,
+        )
+        .unwrap();
+        assert_eq!(set.len(), 3);
+    }
+
+    #[test]
+    fn test_invalid_documents() {
+        let invalid_documents = [
+            "not json",
+            "42",
+            r#"{"type": "FeatureCollection"}"#,
+            r#"{"type": "Point", "coordinates": [1]}"#,
+            r#"{"type": "Point", "coordinates": ["a", "b"]}"#,
+            r#"{"type": "LineString", "coordinates": [[0, 0]]}"#,
+            r#"{"type": "Polygon", "coordinates": []}"#,
+            r#"{"type": "Circle", "coordinates": [0, 0]}"#,
+            r#"[{"type": "Point", "coordinates": [0, 0]}]"#,
+        ];
+        for document in invalid_documents {
+            assert!(
+                matches!(parse(document), Err(AnnotationError::InvalidGeoJson { .. })),
+                "{}",
+                document
+            );
+        }
+    }
+
+    #[test]
+    fn test_revision_and_colors() {
+        let a = parse(r#"{"type": "Point", "coordinates": [1, 1]}"#).unwrap();
+        let b = parse(r#"{"type": "Point", "coordinates": [1, 2]}"#).unwrap();
+        assert_ne!(a.revision(), b.revision());
+        assert_eq!(
+            a.revision(),
+            parse(r#"{"type": "Point", "coordinates": [1, 1]}"#)
+                .unwrap()
+                .revision()
+        );
+
+        assert_eq!(color(&Value::from("#00ff80")), Some([0, 255, 128]));
+        assert_eq!(color(&Value::from("red")), None);
+        assert_eq!(color(&serde_json::json!([300, 0, 0])), None);
+    }
+}
@@ -0,0 +1,27 @@
+//! Annotation overlays.
+//!
+//! Clients upload GeoJSON annotations for a slide with
+//! `POST /slides/{slide_id}/annotations` and request tiles with
+//! `?overlay=annotations` to get them drawn onto the tile pixels, so viewers
+//! without a vector layer (and exported tiles) show the annotations too.
+//!
+//! # Components
+//!
+//! - [`AnnotationSet`]: Parsed GeoJSON geometries in level 0 pixel coordinates
+//! - [`AnnotationStore`]: Where annotation sets are kept; [`MemoryAnnotationStore`] by default
+//! - [`TileOverlay`]: Draws an annotation set onto one tile of a pyramid level
+//!
+//! Geometries are scaled to each level by the ratio of the level's
+//! dimensions to level 0, so strokes stay the same width in pixels at every
+//! zoom.
+
+mod annotations;
+mod render;
+mod store;
+
+pub use annotations::{
+    Annotation, AnnotationSet, Bounds, Point, Shape, DEFAULT_ANNOTATION_COLOR,
+    MAX_ANNOTATION_BYTES, MAX_ANNOTATION_VERTICES,
+};
+pub use render::{TileOverlay, FILL_OPACITY, POINT_RADIUS, STROKE_WIDTH};
+pub use store::{AnnotationStore, MemoryAnnotationStore};
@@ -0,0 +1,296 @@
+//! Drawing annotations onto tiles.
+//!
+//! A [`TileOverlay`] maps level 0 coordinates to the pixels of one decoded
+//! tile: scaled by the level's size relative to level 0, then shifted by the
+//! tile's position in the level. Polygons are filled translucently (even-odd
+//! rule, so holes stay clear) and outlined; lines are stroked and points
+//! drawn as dots. Stroke width and dot size are in tile pixels, the same at
+//! every level.
+
+use std::sync::Arc;
+
+use image::{DynamicImage, RgbImage};
+
+use super::annotations::{AnnotationSet, Point, Shape};
+
+/// Width of lines and outlines in tile pixels.
+pub const STROKE_WIDTH: f64 = 2.0;
+
+/// Radius of point annotations in tile pixels.
+pub const POINT_RADIUS: f64 = 4.0;
+
+/// Opacity of polygon fills (0.0-1.0).
+pub const FILL_OPACITY: f32 = 0.25;
+
+/// Position in tile pixel coordinates.
+type Pixel = (f64, f64);
+
+/// An annotation set placed on one tile.
+#[derive(Debug, Clone)]
+pub struct TileOverlay {
+    annotations: Arc<AnnotationSet>,
+
+    /// Level pixel coordinates of the tile's top-left pixel
+    origin: (f64, f64),
+
+    /// Level pixels per level 0 pixel, per axis
+    scale: (f64, f64),
+}
+
+impl TileOverlay {
+    /// Place annotations on the tile whose top-left pixel is at `origin` in
+    /// a level scaled by `scale` (level size / level 0 size) per axis.
+    pub fn new(annotations: Arc<AnnotationSet>, origin: (u32, u32), scale: (f64, f64)) -> Self {
+        Self {
+            annotations,
+            origin: (origin.0 as f64, origin.1 as f64),
+            scale,
+        }
+    }
+
+    /// Annotations drawn by the overlay.
+    pub fn annotations(&self) -> &AnnotationSet {
+        &self.annotations
+    }
+
+    /// Draw the annotations onto a tile, converting it to 8-bit RGB.
+    pub fn apply(&self, img: DynamicImage) -> DynamicImage {
+        let mut rgb = img.into_rgb8();
+        self.draw(&mut rgb);
+        DynamicImage::ImageRgb8(rgb)
+    }
+
+    /// Draw the annotations onto an RGB tile.
+    pub fn draw(&self, img: &mut RgbImage) {
+        let margin = STROKE_WIDTH.max(POINT_RADIUS) + 1.0;
+        let (width, height) = (img.width() as f64, img.height() as f64);
+
+        for annotation in self.annotations.annotations() {
+            let bounds = annotation.bounds;
+            let (min_x, min_y) = self.to_pixel(&[bounds.min_x, bounds.min_y]);
+            let (max_x, max_y) = self.to_pixel(&[bounds.max_x, bounds.max_y]);
+            if max_x < -margin
+                || max_y < -margin
+                || min_x > width + margin
+                || min_y > height + margin
+            {
+                continue;
+            }
+
+            let color = annotation.color;
+            match &annotation.shape {
+                Shape::Point(point) => fill_disc(img, self.to_pixel(point), POINT_RADIUS, color),
+                Shape::Line(points) => {
+                    let line: Vec<Pixel> = points.iter().map(|p| self.to_pixel(p)).collect();
+                    stroke(img, &line, false, color);
+                }
+                Shape::Polygon(rings) => {
+                    let rings: Vec<Vec<Pixel>> = rings
+                        .iter()
+                        .map(|ring| ring.iter().map(|p| self.to_pixel(p)).collect())
+                        .collect();
+                    fill_rings(img, &rings, color);
+                    for ring in &rings {
+                        stroke(img, ring, true, color);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Convert a level 0 position to tile pixel coordinates.
+    fn to_pixel(&self, point: &Point) -> Pixel {
+        (
+            point[0] * self.scale.0 - self.origin.0,
+            point[1] * self.scale.1 - self.origin.1,
+        )
+    }
+}
+
+/// Pixel index range `[start, end)` whose centers may lie within `min..=max`,
+/// clamped to `0..limit`.
+fn pixel_range(min: f64, max: f64, limit: u32) -> std::ops::Range<u32> {
+    let start = min.floor().clamp(0.0, limit as f64) as u32;
+    let end = max.ceil().clamp(0.0, limit as f64) as u32;
+    start..end
+}
+
+fn blend(img: &mut RgbImage, x: u32, y: u32, color: [u8; 3], opacity: f32) {
+    let pixel = img.get_pixel_mut(x, y);
+    for (dst, src) in pixel.0.iter_mut().zip(color) {
+        *dst = (*dst as f32 * (1.0 - opacity) + src as f32 * opacity).round() as u8;
+    }
+}
+
+/// Distance from `p` to the segment `a`-`b`.
+fn distance_to_segment(p: Pixel, a: Pixel, b: Pixel) -> f64 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length_sq = dx * dx + dy * dy;
+    let t = if length_sq > 0.0 {
+        (((p.0 - a.0) * dx + (p.1 - a.1) * dy) / length_sq).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    (p.0 - (a.0 + t * dx)).hypot(p.1 - (a.1 + t * dy))
+}
+
+fn draw_segment(img: &mut RgbImage, a: Pixel, b: Pixel, color: [u8; 3]) {
+    let half = STROKE_WIDTH / 2.0;
+    let xs = pixel_range(a.0.min(b.0) - half, a.0.max(b.0) + half, img.width());
+    let ys = pixel_range(a.1.min(b.1) - half, a.1.max(b.1) + half, img.height());
+    for y in ys {
+        for x in xs.clone() {
+            let center = (x as f64 + 0.5, y as f64 + 0.5);
+            if distance_to_segment(center, a, b) <= half {
+                img.put_pixel(x, y, image::Rgb(color));
+            }
+        }
+    }
+}
+
+/// Stroke a polyline, closing it back to its first point if `closed`.
+fn stroke(img: &mut RgbImage, points: &[Pixel], closed: bool, color: [u8; 3]) {
+    for pair in points.windows(2) {
+        draw_segment(img, pair[0], pair[1], color);
+    }
+    if let (true, Some(&first), Some(&last)) = (closed, points.first(), points.last()) {
+        draw_segment(img, last, first, color);
+    }
+}
+
+fn fill_disc(img: &mut RgbImage, center: Pixel, radius: f64, color: [u8; 3]) {
+    let xs = pixel_range(center.0 - radius, center.0 + radius, img.width());
+    let ys = pixel_range(center.1 - radius, center.1 + radius, img.height());
+    for y in ys {
+        for x in xs.clone() {
+            let dx = x as f64 + 0.5 - center.0;
+            let dy = y as f64 + 0.5 - center.1;
+            if dx.hypot(dy) <= radius {
+                img.put_pixel(x, y, image::Rgb(color));
+            }
+        }
+    }
+}
+
+/// Fill the interior of polygon rings with the even-odd rule.
+fn fill_rings(img: &mut RgbImage, rings: &[Vec<Pixel>], color: [u8; 3]) {
+    let (min_y, max_y) = rings
+        .iter()
+        .flatten()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), p| {
+            (min.min(p.1), max.max(p.1))
+        });
+    let width = img.width() as f64;
+
+    let mut crossings = Vec::new();
+    for y in pixel_range(min_y, max_y, img.height()) {
+        let scan_y = y as f64 + 0.5;
+        crossings.clear();
+        for ring in rings {
+            let edges = ring.iter().zip(ring.iter().cycle().skip(1));
+            for (a, b) in edges {
+                if (a.1 <= scan_y) != (b.1 <= scan_y) {
+                    crossings.push(a.0 + (scan_y - a.1) / (b.1 - a.1) * (b.0 - a.0));
+                }
+            }
+        }
+        crossings.sort_by(f64::total_cmp);
+
+        // Fill pixels whose centers lie between pairs of crossings
+        for span in crossings.chunks_exact(2) {
+            let start = (span[0] - 0.5).ceil().clamp(0.0, width) as u32;
+            let end = (span[1] - 0.5).ceil().clamp(0.0, width) as u32;
+            for x in start..end {
+                blend(img, x, y, color, FILL_OPACITY);
+            }
+        }
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::Bytes;
+    use image::Rgb;
+
+    const WHITE: [u8; 3] = [255, 255, 255];
+    const RED: [u8; 3] = [255, 0, 0];
+
+    fn annotations(geojson: &str) -> Arc<AnnotationSet> {
+        Arc::new(AnnotationSet::from_geojson(Bytes::copy_from_slice(geojson.as_bytes())).unwrap())
+    }
+
+    fn white_tile() -> RgbImage {
+        RgbImage::from_pixel(64, 64, Rgb(WHITE))
+    }
+
+    /// Square from (10, 10) to (50, 50) in level 0 pixels, with a hole.
+    fn square() -> Arc<AnnotationSet> {
+        annotations(
+            r##"{"type": "Feature", "properties": {"color": "#ff0000"}, "geometry": {
+                "type": "Polygon",
+                "coordinates": [
+                    [[10, 10], [50, 10], [50, 50], [10, 50], [10, 10]],
+                    [[26, 26], [34, 26], [34, 34], [26, 34], [26, 26]]
+                ]
+            }}"##,
+        )
+    }
+
+    #[test]
+    fn test_polygon_fill_and_outline() {
+        let mut tile = white_tile();
+        TileOverlay::new(square(), (0, 0), (1.0, 1.0)).draw(&mut tile);
+
+        // Outline is opaque, interior tinted, hole and outside untouched
+        assert_eq!(tile.get_pixel(10, 20).0, RED);
+        assert_eq!(tile.get_pixel(20, 20).0, [255, 191, 191]);
+        assert_eq!(tile.get_pixel(30, 30).0, WHITE);
+        assert_eq!(tile.get_pixel(5, 5).0, WHITE);
+        assert_eq!(tile.get_pixel(60, 60).0, WHITE);
+    }
+
+    #[test]
+    fn test_level_scaling_and_origin() {
+        // Half-resolution level: the square spans (5, 5)-(25, 25)
+        let mut tile = white_tile();
+        TileOverlay::new(square(), (0, 0), (0.5, 0.5)).draw(&mut tile);
+        assert_eq!(tile.get_pixel(5, 15).0, RED);
+        assert_eq!(tile.get_pixel(8, 8).0, [255, 191, 191]);
+        assert_eq!(tile.get_pixel(30, 30).0, WHITE);
+
+        // Tile starting at (20, 20) in level 0 pixels
+        let mut tile = white_tile();
+        TileOverlay::new(square(), (20, 20), (1.0, 1.0)).draw(&mut tile);
+        assert_eq!(tile.get_pixel(0, 0).0, [255, 191, 191]);
+        assert_eq!(tile.get_pixel(30, 10).0, RED);
+        assert_eq!(tile.get_pixel(40, 40).0, WHITE);
+
+        // Tile far from the annotations is unchanged
+        let mut tile = white_tile();
+        TileOverlay::new(square(), (1000, 1000), (1.0, 1.0)).draw(&mut tile);
+        assert!(tile.pixels().all(|p| p.0 == WHITE));
+    }
+
+    #[test]
+    fn test_lines_and_points() {
+        let set = annotations(
+            r#"{"type": "GeometryCollection", "geometries": [
+                {"type": "LineString", "coordinates": [[0, 5], [63, 5]]},
+                {"type": "Point", "coordinates": [32, 40]}
+            ]}"#,
+        );
+        let tile =
+            TileOverlay::new(set, (0, 0), (1.0, 1.0)).apply(DynamicImage::ImageRgb8(white_tile()));
+        let tile = tile.as_rgb8().unwrap();
+
+        assert_eq!(tile.get_pixel(30, 5).0, [255, 204, 0]);
+        assert_eq!(tile.get_pixel(30, 8).0, WHITE);
+        assert_eq!(tile.get_pixel(32, 40).0, [255, 204, 0]);
+        assert_eq!(tile.get_pixel(32, 46).0, WHITE);
+    }
+}
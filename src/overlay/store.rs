@@ -0,0 +1,87 @@
+//! Annotation storage.
+//!
+//! Annotation sets are kept behind the [`AnnotationStore`] trait so
+//! deployments can persist them (e.g. in a database shared by replicas).
+//! The server uses a [`MemoryAnnotationStore`] unless the router is given
+//! another store; its annotations last until the process exits.
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use async_trait::async_trait;
+
+use crate::error::IoError;
+
+use super::annotations::AnnotationSet;
+
+/// Where slide annotations are kept.
+#[async_trait]
+pub trait AnnotationStore: Send + Sync {
+    /// Load a slide's annotations, `None` if none are stored.
+    async fn load(&self, slide_id: &str) -> Result<Option<Arc<AnnotationSet>>, IoError>;
+
+    /// Store a slide's annotations, replacing any previous set.
+    async fn save(&self, slide_id: &str, annotations: Arc<AnnotationSet>) -> Result<(), IoError>;
+}
+
+/// Annotation store in process memory.
+#[derive(Debug, Default)]
+pub struct MemoryAnnotationStore {
+    sets: RwLock<HashMap<String, Arc<AnnotationSet>>>,
+}
+
+impl MemoryAnnotationStore {
+    /// Create an empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl AnnotationStore for MemoryAnnotationStore {
+    async fn load(&self, slide_id: &str) -> Result<Option<Arc<AnnotationSet>>, IoError> {
+        Ok(self.sets.read().unwrap().get(slide_id).cloned())
+    }
+
+    async fn save(&self, slide_id: &str, annotations: Arc<AnnotationSet>) -> Result<(), IoError> {
+        self.sets
+            .write()
+            .unwrap()
+            .insert(slide_id.to_string(), annotations);
+        Ok(())
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::Bytes;
+
+    #[tokio::test]
+    async fn test_memory_store_replaces_sets() {
+        let store = MemoryAnnotationStore::new();
+        assert!(store.load("slide.svs").await.unwrap().is_none());
+
+        let first = AnnotationSet::from_geojson(Bytes::from_static(
+            br#"{"type": "Point", "coordinates": [1, 1]}"#,
+        ))
+        .unwrap();
+        let second = AnnotationSet::from_geojson(Bytes::from_static(
+            br#"{"type": "Point", "coordinates": [2, 2]}"#,
+        ))
+        .unwrap();
+        store.save("slide.svs", Arc::new(first)).await.unwrap();
+        store
+            .save("slide.svs", Arc::new(second.clone()))
+            .await
+            .unwrap();
+
+        let loaded = store.load("slide.svs").await.unwrap().unwrap();
+        assert_eq!(loaded.revision(), second.revision());
+        assert!(store.load("other.svs").await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,358 @@
+//! Capacity planning estimates.
+//!
+//! Turns bucket statistics and a target load into recommended cache sizes,
+//! block size and expected S3 traffic. Used by the `wsi-streamer plan`
+//! command.
+//!
+//! # Model
+//!
+//! - Each active viewer keeps roughly a viewport of tiles (plus prefetch) hot
+//!   in the tile cache.
+//! - Tile cache misses read the source tile through the per-slide block
+//!   cache; neighboring tiles in the same block are served without another
+//!   GET.
+//! - Opening a slide reads the TIFF header and IFDs, costing a few block
+//!   GETs, and keeps the tile offset tables in memory.
+//!
+//! The constants below are either derived from how the server lays out data
+//! (tile geometry, metadata representation) or are assumptions about browser
+//! viewers. Each constant's documentation says which, and names the metric
+//! to check it against in production; the estimates are only as good as
+//! these inputs.
+//!
+//! Products of the inputs saturate at `u64::MAX` rather than overflowing, so
+//! absurd inputs give absurd but well-defined recommendations.
+
+use serde::Serialize;
+
+// =============================================================================
+// Model Constants
+// =============================================================================
+
+/// Tiles visible in a typical viewer viewport.
+///
+/// Derived: a 1920x1080 screen covers 8 x 5 tiles of 256px.
+pub const VIEWPORT_TILES: u64 = 40;
+
+/// Extra tiles kept per viewer for pan/zoom prefetch, as a multiple of the
+/// viewport.
+///
+/// Assumed: a ring of tiles around the viewport plus the next zoom level,
+/// as OpenSeadragon-style viewers request them. Check against the tile
+/// rate per viewer (`wsi_tile_requests_total` over active sessions).
+pub const PREFETCH_FACTOR: f64 = 1.5;
+
+/// Expected tile cache hit ratio for a cache sized to the working set.
+///
+/// Assumed, and deliberately conservative since viewers rarely share
+/// slides. Check against `wsi_tile_cache_hits_total` and
+/// `wsi_tile_cache_misses_total`.
+pub const TILE_CACHE_HIT_RATIO: f64 = 0.6;
+
+/// Block GETs needed to open a slide.
+///
+/// Derived from the open path: the header and first IFD share a block, and
+/// the remaining IFDs and the tile offset and byte count tables of the
+/// largest levels take about three more. Check against
+/// `wsi_slide_block_misses_total` on `/admin/slides/metrics` for a freshly
+/// opened slide.
+pub const GETS_PER_SLIDE_OPEN: u64 = 4;
+
+/// Bytes of parsed metadata kept per source tile.
+///
+/// Derived: each tile's offset and byte count are held as two `u64`s.
+pub const METADATA_BYTES_PER_TILE: u64 = 16;
+
+/// Blocks kept per slide beyond those needed for metadata.
+///
+/// Policy: enough for a viewport of tiles at the smallest block size.
+pub const MIN_BLOCKS_PER_SLIDE: u64 = 32;
+
+/// Memory headroom applied to cache totals.
+///
+/// Policy: allocator overhead and the bookkeeping of each cache entry.
+pub const MEMORY_HEADROOM: f64 = 1.25;
+
+/// Smallest recommended block size (64KB).
+///
+/// Policy: below this, S3 request overhead dominates the transfer time.
+pub const MIN_BLOCK_SIZE: u64 = 64 * 1024;
+
+/// Largest recommended block size (1MB).
+///
+/// Policy: above this, a cache miss fetches far more than one viewer needs.
+pub const MAX_BLOCK_SIZE: u64 = 1024 * 1024;
+
+// =============================================================================
+// Inputs and Plan
+// =============================================================================
+
+/// Inputs to the capacity model.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CapacityInputs {
+    /// Number of slides in the bucket
+    pub slides: u64,
+
+    /// Average slide size in bytes
+    pub avg_slide_bytes: u64,
+
+    /// Average compressed size of a source tile in bytes
+    pub avg_source_tile_bytes: u64,
+
+    /// Average size of an encoded (served) tile in bytes
+    pub avg_served_tile_bytes: u64,
+
+    /// Target number of concurrently active viewers
+    pub concurrency: u64,
+
+    /// Tile requests per second issued by one active viewer
+    pub tiles_per_viewer_per_sec: f64,
+
+    /// Distinct slides opened per minute across all viewers
+    pub slide_opens_per_minute: f64,
+
+    /// Typical S3 GET latency in milliseconds
+    pub s3_latency_ms: u64,
+}
+
+/// Recommended settings and expected load.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CapacityPlan {
+    /// Expected tile requests per second
+    pub tile_requests_per_sec: f64,
+
+    /// Recommended tile cache size in bytes (`--cache-tiles`)
+    pub tile_cache_bytes: u64,
+
+    /// Recommended number of cached slides (`--cache-slides`)
+    pub slide_cache_capacity: u64,
+
+    /// Recommended block size in bytes (`--block-size`)
+    pub block_size: u64,
+
+    /// Recommended blocks cached per slide (`--cache-blocks`)
+    pub blocks_per_slide: u64,
+
+    /// Memory used by block caches when the slide cache is full
+    pub block_cache_bytes: u64,
+
+    /// Memory used by parsed slide metadata when the slide cache is full
+    pub metadata_bytes: u64,
+
+    /// Total estimated cache memory, including headroom
+    pub total_memory_bytes: u64,
+
+    /// Recommended concurrent slide opens (`--max-concurrent-opens`)
+    pub max_concurrent_opens: u64,
+
+    /// Expected S3 GET requests per second
+    pub s3_gets_per_sec: f64,
+
+    /// Expected S3 egress in bytes per second
+    pub s3_egress_bytes_per_sec: f64,
+}
+
+impl CapacityPlan {
+    /// Serve flags applying the recommendations.
+    pub fn serve_flags(&self) -> Vec<String> {
+        vec![
+            format!("--cache-tiles {}", self.tile_cache_bytes),
+            format!("--cache-slides {}", self.slide_cache_capacity),
+            format!("--block-size {}", self.block_size),
+            format!("--cache-blocks {}", self.blocks_per_slide),
+            format!("--max-concurrent-opens {}", self.max_concurrent_opens),
+        ]
+    }
+}
+
+// =============================================================================
+// Planning
+// =============================================================================
+
+/// Estimate cache sizes and S3 load for the given inputs.
+pub fn plan_capacity(inputs: &CapacityInputs) -> CapacityPlan {
+    let concurrency = inputs.concurrency.max(1);
+    let source_tile = inputs.avg_source_tile_bytes.max(1);
+
+    let tile_requests_per_sec = concurrency as f64 * inputs.tiles_per_viewer_per_sec;
+
+    // Tile cache: every viewer's viewport plus prefetch
+    let tiles_per_viewer = (VIEWPORT_TILES as f64 * (1.0 + PREFETCH_FACTOR)).ceil() as u64;
+    let tile_cache_bytes = round_up_mib(
+        concurrency
+            .saturating_mul(tiles_per_viewer)
+            .saturating_mul(inputs.avg_served_tile_bytes) as f64
+            * MEMORY_HEADROOM,
+    );
+
+    // Slide cache: viewers rarely share slides, plus room for recently closed
+    let slide_cache_capacity = concurrency
+        .saturating_add(concurrency / 2)
+        .clamp(1, inputs.slides.max(1));
+
+    // Block size: a few tiles per block so neighbors share a GET, without
+    // fetching much more than a viewer needs
+    let block_size = source_tile
+        .saturating_mul(4)
+        .min(MAX_BLOCK_SIZE)
+        .next_power_of_two()
+        .max(MIN_BLOCK_SIZE);
+    let tiles_per_block = (block_size / source_tile).max(1);
+
+    // Block cache: metadata blocks plus a viewport of tiles per slide
+    let tiles_per_slide = inputs.avg_slide_bytes / source_tile;
+    let metadata_per_slide = tiles_per_slide.saturating_mul(METADATA_BYTES_PER_TILE);
+    let metadata_blocks = metadata_per_slide.div_ceil(block_size);
+    let viewport_blocks = tiles_per_viewer.div_ceil(tiles_per_block);
+    let blocks_per_slide = metadata_blocks
+        .saturating_add(viewport_blocks)
+        .max(MIN_BLOCKS_PER_SLIDE);
+
+    let block_cache_bytes = slide_cache_capacity
+        .saturating_mul(blocks_per_slide)
+        .saturating_mul(block_size);
+    let metadata_bytes = slide_cache_capacity.saturating_mul(metadata_per_slide);
+    let total_memory_bytes = round_up_mib(
+        tile_cache_bytes
+            .saturating_add(block_cache_bytes)
+            .saturating_add(metadata_bytes) as f64
+            * MEMORY_HEADROOM,
+    );
+
+    // Opens: enough slots to absorb the open rate at the expected open latency
+    let opens_per_sec = inputs.slide_opens_per_minute / 60.0;
+    let open_latency_secs =
+        GETS_PER_SLIDE_OPEN.saturating_mul(inputs.s3_latency_ms) as f64 / 1000.0;
+    let max_concurrent_opens = ((opens_per_sec * open_latency_secs * 2.0).ceil() as u64).max(4);
+
+    // S3 traffic: tile cache misses not served by an already-fetched block,
+    // plus slide opens
+    let tile_misses_per_sec = tile_requests_per_sec * (1.0 - TILE_CACHE_HIT_RATIO);
+    let tile_gets_per_sec = tile_misses_per_sec / tiles_per_block as f64;
+    let open_gets_per_sec = opens_per_sec * GETS_PER_SLIDE_OPEN as f64;
+    let s3_gets_per_sec = tile_gets_per_sec + open_gets_per_sec;
+    let s3_egress_bytes_per_sec = s3_gets_per_sec * block_size as f64;
+
+    CapacityPlan {
+        tile_requests_per_sec,
+        tile_cache_bytes,
+        slide_cache_capacity,
+        block_size,
+        blocks_per_slide,
+        block_cache_bytes,
+        metadata_bytes,
+        total_memory_bytes,
+        max_concurrent_opens,
+        s3_gets_per_sec,
+        s3_egress_bytes_per_sec,
+    }
+}
+
+/// Round a byte count up to a whole number of MiB.
+fn round_up_mib(bytes: f64) -> u64 {
+    const MIB: u64 = 1024 * 1024;
+    (bytes.max(0.0).ceil() as u64)
+        .div_ceil(MIB)
+        .saturating_mul(MIB)
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inputs() -> CapacityInputs {
+        CapacityInputs {
+            slides: 10_000,
+            avg_slide_bytes: 1024 * 1024 * 1024,
+            avg_source_tile_bytes: 40 * 1024,
+            avg_served_tile_bytes: 30 * 1024,
+            concurrency: 100,
+            tiles_per_viewer_per_sec: 10.0,
+            slide_opens_per_minute: 60.0,
+            s3_latency_ms: 50,
+        }
+    }
+
+    #[test]
+    fn test_plan_basic() {
+        let plan = plan_capacity(&inputs());
+
+        assert_eq!(plan.tile_requests_per_sec, 1000.0);
+        assert_eq!(plan.slide_cache_capacity, 150);
+        // 4 x 40KB rounded up to a power of two
+        assert_eq!(plan.block_size, 256 * 1024);
+        assert!(plan.blocks_per_slide >= MIN_BLOCKS_PER_SLIDE);
+        assert_eq!(plan.tile_cache_bytes % (1024 * 1024), 0);
+        assert!(
+            plan.total_memory_bytes
+                >= plan.tile_cache_bytes + plan.block_cache_bytes + plan.metadata_bytes
+        );
+        assert!(plan.s3_gets_per_sec > 0.0);
+        assert!(plan.max_concurrent_opens >= 4);
+    }
+
+    #[test]
+    fn test_plan_scales_with_concurrency() {
+        let small = plan_capacity(&inputs());
+        let large = plan_capacity(&CapacityInputs {
+            concurrency: 1000,
+            ..inputs()
+        });
+
+        assert!(large.tile_cache_bytes > small.tile_cache_bytes);
+        assert!(large.s3_gets_per_sec > small.s3_gets_per_sec);
+        assert!(large.total_memory_bytes > small.total_memory_bytes);
+    }
+
+    #[test]
+    fn test_slide_cache_bounded_by_bucket() {
+        let plan = plan_capacity(&CapacityInputs {
+            slides: 10,
+            ..inputs()
+        });
+        assert_eq!(plan.slide_cache_capacity, 10);
+    }
+
+    #[test]
+    fn test_block_size_clamped() {
+        let tiny = plan_capacity(&CapacityInputs {
+            avg_source_tile_bytes: 1024,
+            ..inputs()
+        });
+        assert_eq!(tiny.block_size, MIN_BLOCK_SIZE);
+
+        let huge = plan_capacity(&CapacityInputs {
+            avg_source_tile_bytes: 2 * 1024 * 1024,
+            ..inputs()
+        });
+        assert_eq!(huge.block_size, MAX_BLOCK_SIZE);
+    }
+
+    #[test]
+    fn test_huge_inputs_saturate() {
+        let plan = plan_capacity(&CapacityInputs {
+            slides: u64::MAX,
+            avg_slide_bytes: u64::MAX,
+            avg_source_tile_bytes: u64::MAX,
+            avg_served_tile_bytes: u64::MAX,
+            concurrency: u64::MAX,
+            s3_latency_ms: u64::MAX,
+            ..inputs()
+        });
+        assert_eq!(plan.tile_cache_bytes, u64::MAX);
+        assert_eq!(plan.block_size, MAX_BLOCK_SIZE);
+        assert_eq!(plan.total_memory_bytes, u64::MAX);
+    }
+
+    #[test]
+    fn test_serve_flags() {
+        let plan = plan_capacity(&inputs());
+        let flags = plan.serve_flags();
+        assert!(flags.contains(&"--block-size 262144".to_string()));
+        assert_eq!(flags.len(), 5);
+    }
+}
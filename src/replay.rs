@@ -0,0 +1,660 @@
+//! Request replay for reproducing viewer bug reports.
+//!
+//! Loads a captured sequence of requests and re-issues it against a running
+//! server with the original timing, so overlapping requests overlap again and
+//! the server sees the same ordering and concurrency as when the glitch was
+//! reported. Used by the `wsi-streamer replay` command.
+//!
+//! # Inputs
+//!
+//! - **HAR**: exported from browser developer tools. Request start times
+//!   have millisecond precision.
+//! - **Access logs** in Common or Combined Log Format, as written by most
+//!   reverse proxies and load balancers. Timestamps have one-second
+//!   precision, so requests logged within the same second are sent together
+//!   in log order.
+//!
+//! Only `GET` and `HEAD` requests are replayed; other methods are skipped
+//! since their bodies are not captured. Recorded response statuses are kept
+//! so replayed responses can be compared against them.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::sync::Semaphore;
+
+use crate::date::days_from_civil;
+use crate::error::ReplayError;
+
+// =============================================================================
+// Request Log
+// =============================================================================
+
+/// A captured request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplayEntry {
+    /// Start time relative to the first request
+    pub offset: Duration,
+
+    /// HTTP method (`GET` or `HEAD`)
+    pub method: String,
+
+    /// Path and query string
+    pub path: String,
+
+    /// Response status recorded with the request, if any
+    pub expected_status: Option<u16>,
+}
+
+/// A captured request sequence, ordered by start time.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReplayLog {
+    /// Requests to replay
+    pub entries: Vec<ReplayEntry>,
+
+    /// Lines or entries that could not be replayed
+    pub skipped: usize,
+}
+
+impl ReplayLog {
+    /// Parse a HAR document or an access log.
+    ///
+    /// Input starting with `{` is read as HAR; anything else as an access
+    /// log, one request per line.
+    pub fn parse(input: &str) -> Result<Self, ReplayError> {
+        if input.trim_start().starts_with('{') {
+            Self::parse_har(input)
+        } else {
+            Ok(Self::parse_access_log(input))
+        }
+    }
+
+    /// Parse a HAR document.
+    pub fn parse_har(input: &str) -> Result<Self, ReplayError> {
+        let har: Har =
+            serde_json::from_str(input).map_err(|e| ReplayError::Parse(e.to_string()))?;
+
+        let mut timed = Vec::new();
+        let mut skipped = 0;
+        for entry in har.log.entries {
+            let started = parse_iso8601_ms(&entry.started_date_time);
+            let path = url::Url::parse(&entry.request.url)
+                .ok()
+                .map(|url| match url.query() {
+                    Some(query) => format!("{}?{}", url.path(), query),
+                    None => url.path().to_string(),
+                });
+            match (started, path) {
+                (Some(started), Some(path)) if is_replayable(&entry.request.method) => {
+                    // HAR uses status 0 for requests without a response
+                    let status = entry.response.map(|r| r.status).filter(|&s| s != 0);
+                    timed.push((started, entry.request.method, path, status));
+                }
+                _ => skipped += 1,
+            }
+        }
+
+        Ok(Self::from_timed(timed, skipped))
+    }
+
+    /// Parse an access log in Common or Combined Log Format.
+    pub fn parse_access_log(input: &str) -> Self {
+        let mut timed = Vec::new();
+        let mut skipped = 0;
+        for line in input.lines().filter(|line| !line.trim().is_empty()) {
+            match parse_access_log_line(line) {
+                Some((started, method, path, status)) if is_replayable(&method) => {
+                    timed.push((started, method, path, status));
+                }
+                _ => skipped += 1,
+            }
+        }
+
+        Self::from_timed(timed, skipped)
+    }
+
+    /// Order requests by start time and make offsets relative to the first.
+    fn from_timed(mut timed: Vec<(i64, String, String, Option<u16>)>, skipped: usize) -> Self {
+        // Stable, so requests with equal timestamps keep their log order
+        timed.sort_by_key(|(started, ..)| *started);
+        let first = timed.first().map_or(0, |(started, ..)| *started);
+
+        let entries = timed
+            .into_iter()
+            .map(|(started, method, path, expected_status)| ReplayEntry {
+                offset: Duration::from_millis((started - first) as u64),
+                method,
+                path,
+                expected_status,
+            })
+            .collect();
+
+        Self { entries, skipped }
+    }
+
+    /// Time between the first and last request start.
+    pub fn duration(&self) -> Duration {
+        self.entries.last().map_or(Duration::ZERO, |e| e.offset)
+    }
+}
+
+fn is_replayable(method: &str) -> bool {
+    matches!(method, "GET" | "HEAD")
+}
+
+#[derive(Deserialize)]
+struct Har {
+    log: HarLog,
+}
+
+#[derive(Deserialize)]
+struct HarLog {
+    entries: Vec<HarEntry>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HarEntry {
+    started_date_time: String,
+    request: HarRequest,
+    response: Option<HarResponse>,
+}
+
+#[derive(Deserialize)]
+struct HarRequest {
+    method: String,
+    url: String,
+}
+
+#[derive(Deserialize)]
+struct HarResponse {
+    status: u16,
+}
+
+// =============================================================================
+// Timestamp Parsing
+// =============================================================================
+
+/// Milliseconds since the epoch for a date, time and UTC offset.
+fn epoch_ms(date: (i64, u32, u32), time: (i64, i64, i64), millis: i64, offset_secs: i64) -> i64 {
+    let (hour, minute, second) = time;
+    let secs =
+        days_from_civil(date.0, date.1, date.2) * 86_400 + hour * 3600 + minute * 60 + second
+            - offset_secs;
+    secs * 1000 + millis
+}
+
+/// Parse an ISO 8601 timestamp such as `2024-03-05T14:02:11.123+01:00`.
+fn parse_iso8601_ms(value: &str) -> Option<i64> {
+    let (date, rest) = value.split_once('T')?;
+    let mut date_parts = date.splitn(3, '-');
+    let year = date_parts.next()?.parse().ok()?;
+    let month = date_parts.next()?.parse().ok()?;
+    let day = date_parts.next()?.parse().ok()?;
+
+    let zone_start = rest.find(['Z', 'z', '+', '-'])?;
+    let (time, zone) = rest.split_at(zone_start);
+    let (clock, fraction) = time.split_once('.').unwrap_or((time, ""));
+    let mut clock_parts = clock.splitn(3, ':');
+    let hour = clock_parts.next()?.parse().ok()?;
+    let minute = clock_parts.next()?.parse().ok()?;
+    let second = clock_parts.next()?.parse().ok()?;
+
+    // Milliseconds from the first three fractional digits
+    let mut millis = 0;
+    for (i, digit) in fraction.chars().take(3).enumerate() {
+        millis += digit.to_digit(10)? as i64 * 10_i64.pow(2 - i as u32);
+    }
+
+    let offset_secs = match zone {
+        "Z" | "z" => 0,
+        _ => parse_utc_offset(&zone.replace(':', ""))?,
+    };
+
+    Some(epoch_ms(
+        (year, month, day),
+        (hour, minute, second),
+        millis,
+        offset_secs,
+    ))
+}
+
+/// Parse a `+hhmm` / `-hhmm` UTC offset into seconds.
+fn parse_utc_offset(zone: &str) -> Option<i64> {
+    let sign = match zone.get(..1)? {
+        "+" => 1,
+        "-" => -1,
+        _ => return None,
+    };
+    let hours: i64 = zone.get(1..3)?.parse().ok()?;
+    let minutes: i64 = zone.get(3..5)?.parse().ok()?;
+    if zone.len() != 5 {
+        return None;
+    }
+    Some(sign * (hours * 3600 + minutes * 60))
+}
+
+/// Parse a Common Log Format timestamp such as `10/Oct/2000:13:55:36 -0700`.
+fn parse_clf_timestamp_ms(value: &str) -> Option<i64> {
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+
+    let (datetime, zone) = value.split_once(' ')?;
+    let mut parts = datetime.splitn(4, [':', '/']);
+    let day = parts.next()?.parse().ok()?;
+    let month_name = parts.next()?;
+    let month = MONTHS.iter().position(|m| *m == month_name)? as u32 + 1;
+    let year = parts.next()?.parse().ok()?;
+    let mut clock_parts = parts.next()?.splitn(3, ':');
+    let hour = clock_parts.next()?.parse().ok()?;
+    let minute = clock_parts.next()?.parse().ok()?;
+    let second = clock_parts.next()?.parse().ok()?;
+
+    let offset_secs = parse_utc_offset(zone)?;
+
+    Some(epoch_ms(
+        (year, month, day),
+        (hour, minute, second),
+        0,
+        offset_secs,
+    ))
+}
+
+/// Parse an access log line into `(start_ms, method, path, status)`.
+///
+/// ```text
+/// 10.0.0.1 - - [10/Oct/2000:13:55:36 -0700] "GET /tiles/a.svs/0/1/2.jpg HTTP/1.1" 200 2326
+/// ```
+fn parse_access_log_line(line: &str) -> Option<(i64, String, String, Option<u16>)> {
+    let ts_start = line.find('[')? + 1;
+    let ts_end = ts_start + line[ts_start..].find(']')?;
+    let started = parse_clf_timestamp_ms(&line[ts_start..ts_end])?;
+
+    let rest = &line[ts_end..];
+    let req_start = rest.find('"')? + 1;
+    let req_end = req_start + rest[req_start..].find('"')?;
+    let mut request = rest[req_start..req_end].split_whitespace();
+    let method = request.next()?.to_string();
+    let path = request.next()?.to_string();
+    if !path.starts_with('/') {
+        return None;
+    }
+
+    let status = rest[req_end + 1..]
+        .split_whitespace()
+        .next()
+        .and_then(|s| s.parse().ok());
+
+    Some((started, method, path, status))
+}
+
+// =============================================================================
+// Replay
+// =============================================================================
+
+/// Server requests are replayed against.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplayTarget {
+    /// `host:port` to connect to
+    authority: String,
+
+    /// `Host` header value
+    host: String,
+
+    /// Path prefix prepended to every request (no trailing slash)
+    base_path: String,
+}
+
+impl ReplayTarget {
+    /// Parse a target URL such as `http://127.0.0.1:3000`.
+    ///
+    /// Only plain HTTP is supported; replay against the server directly
+    /// rather than through a TLS-terminating proxy.
+    pub fn parse(target: &str) -> Result<Self, ReplayError> {
+        let url = url::Url::parse(target)
+            .map_err(|e| ReplayError::InvalidTarget(format!("{}: {}", target, e)))?;
+        if url.scheme() != "http" {
+            return Err(ReplayError::InvalidTarget(format!(
+                "{}: only http:// targets are supported",
+                target
+            )));
+        }
+        let host = url
+            .host_str()
+            .ok_or_else(|| ReplayError::InvalidTarget(format!("{}: missing host", target)))?;
+        let port = url.port().unwrap_or(80);
+
+        Ok(Self {
+            authority: format!("{}:{}", host, port),
+            host: match url.port() {
+                Some(port) => format!("{}:{}", host, port),
+                None => host.to_string(),
+            },
+            base_path: url.path().trim_end_matches('/').to_string(),
+        })
+    }
+}
+
+/// Replay pacing options.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplayOptions {
+    /// Playback speed relative to the capture (2.0 = twice as fast, 0 = send
+    /// every request immediately, in order)
+    pub speed: f64,
+
+    /// Maximum requests in flight (0 = unlimited)
+    pub max_concurrency: usize,
+
+    /// Per-request timeout
+    pub timeout: Duration,
+}
+
+impl Default for ReplayOptions {
+    fn default() -> Self {
+        Self {
+            speed: 1.0,
+            max_concurrency: 0,
+            timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+/// Result of one replayed request.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplayOutcome {
+    /// The replayed request
+    pub entry: ReplayEntry,
+
+    /// Response status, or `None` if the request failed
+    pub status: Option<u16>,
+
+    /// Transport error, if the request failed
+    pub error: Option<String>,
+
+    /// Time from sending the request to reading the full response
+    pub latency: Duration,
+
+    /// Response bytes after the headers, as sent on the wire
+    pub body_bytes: u64,
+}
+
+impl ReplayOutcome {
+    /// Whether the response status differs from the recorded one.
+    pub fn is_mismatch(&self) -> bool {
+        match (self.status, self.entry.expected_status) {
+            (Some(status), Some(expected)) => status != expected,
+            _ => false,
+        }
+    }
+}
+
+/// Re-issue a request log against a server.
+///
+/// Each request is sent at its original offset (scaled by
+/// [`ReplayOptions::speed`]) on its own connection, without waiting for
+/// earlier requests to finish. Outcomes are returned in log order.
+pub async fn replay(
+    log: &ReplayLog,
+    target: &ReplayTarget,
+    options: &ReplayOptions,
+) -> Vec<ReplayOutcome> {
+    let target = Arc::new(target.clone());
+    let limit =
+        (options.max_concurrency > 0).then(|| Arc::new(Semaphore::new(options.max_concurrency)));
+    let start = tokio::time::Instant::now();
+
+    let mut tasks = Vec::with_capacity(log.entries.len());
+    for entry in &log.entries {
+        if options.speed > 0.0 {
+            let delay = entry.offset.div_f64(options.speed);
+            tokio::time::sleep_until(start + delay).await;
+        }
+        // Permits are acquired in log order, so a limit delays requests but
+        // never reorders them
+        let permit = match &limit {
+            Some(limit) => Some(Arc::clone(limit).acquire_owned().await.unwrap()),
+            None => None,
+        };
+
+        let target = Arc::clone(&target);
+        let entry = entry.clone();
+        let timeout = options.timeout;
+        tasks.push(tokio::spawn(async move {
+            let _permit = permit;
+            let sent = Instant::now();
+            let result = tokio::time::timeout(timeout, send(&target, &entry))
+                .await
+                .unwrap_or_else(|_| Err("timed out".to_string()));
+            let latency = sent.elapsed();
+            match result {
+                Ok((status, body_bytes)) => ReplayOutcome {
+                    entry,
+                    status: Some(status),
+                    error: None,
+                    latency,
+                    body_bytes,
+                },
+                Err(error) => ReplayOutcome {
+                    entry,
+                    status: None,
+                    error: Some(error),
+                    latency,
+                    body_bytes: 0,
+                },
+            }
+        }));
+    }
+
+    let mut outcomes = Vec::with_capacity(tasks.len());
+    for task in tasks {
+        outcomes.push(task.await.expect("replay task panicked"));
+    }
+    outcomes
+}
+
+/// Send one request and return its status and body size.
+async fn send(target: &ReplayTarget, entry: &ReplayEntry) -> Result<(u16, u64), String> {
+    let mut stream = TcpStream::connect(&target.authority)
+        .await
+        .map_err(|e| format!("connect to {}: {}", target.authority, e))?;
+
+    let request = format!(
+        "{} {}{} HTTP/1.1\r\nHost: {}\r\nUser-Agent: wsi-streamer-replay/{}\r\nConnection: close\r\n\r\n",
+        entry.method,
+        target.base_path,
+        entry.path,
+        target.host,
+        env!("CARGO_PKG_VERSION"),
+    );
+    stream
+        .write_all(request.as_bytes())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut response = Vec::new();
+    stream
+        .read_to_end(&mut response)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let header_end = response
+        .windows(4)
+        .position(|w| w == b"\r\n\r\n")
+        .ok_or_else(|| "incomplete response".to_string())?;
+    let status = std::str::from_utf8(&response[..header_end])
+        .ok()
+        .and_then(|head| head.split_whitespace().nth(1))
+        .and_then(|code| code.parse().ok())
+        .ok_or_else(|| "malformed status line".to_string())?;
+
+    Ok((status, (response.len() - header_end - 4) as u64))
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_iso8601() {
+        let utc = parse_iso8601_ms("2024-03-05T13:02:11.123Z").unwrap();
+        let offset = parse_iso8601_ms("2024-03-05T14:02:11.123+01:00").unwrap();
+        assert_eq!(utc, offset);
+        assert_eq!(parse_iso8601_ms("1970-01-01T00:00:01Z"), Some(1000));
+        assert_eq!(parse_iso8601_ms("1970-01-01T00:00:00.5Z"), Some(500));
+        assert_eq!(parse_iso8601_ms("not a date"), None);
+    }
+
+    #[test]
+    fn test_parse_clf_timestamp() {
+        let local = parse_clf_timestamp_ms("10/Oct/2000:13:55:36 -0700").unwrap();
+        let utc = parse_clf_timestamp_ms("10/Oct/2000:20:55:36 +0000").unwrap();
+        assert_eq!(local, utc);
+        assert_eq!(
+            parse_clf_timestamp_ms("01/Jan/1970:00:00:02 +0000"),
+            Some(2000)
+        );
+        assert_eq!(parse_clf_timestamp_ms("10/Foo/2000:13:55:36 -0700"), None);
+    }
+
+    #[test]
+    fn test_parse_har() {
+        let har = r#"{
+          "log": {
+            "entries": [
+              {
+                "startedDateTime": "2024-03-05T13:02:11.250Z",
+                "request": {"method": "GET", "url": "http://viewer.example/tiles/a.svs/0/1/0.jpg?quality=90"},
+                "response": {"status": 200}
+              },
+              {
+                "startedDateTime": "2024-03-05T13:02:11.000Z",
+                "request": {"method": "GET", "url": "http://viewer.example/slides/a.svs/info"},
+                "response": {"status": 0}
+              },
+              {
+                "startedDateTime": "2024-03-05T13:02:11.300Z",
+                "request": {"method": "POST", "url": "http://viewer.example/slides/a.svs/views"},
+                "response": {"status": 201}
+              }
+            ]
+          }
+        }"#;
+
+        let log = ReplayLog::parse(har).unwrap();
+        assert_eq!(log.skipped, 1);
+        assert_eq!(log.entries.len(), 2);
+
+        // Sorted by start time
+        assert_eq!(log.entries[0].path, "/slides/a.svs/info");
+        assert_eq!(log.entries[0].offset, Duration::ZERO);
+        assert_eq!(log.entries[0].expected_status, None);
+        assert_eq!(log.entries[1].path, "/tiles/a.svs/0/1/0.jpg?quality=90");
+        assert_eq!(log.entries[1].offset, Duration::from_millis(250));
+        assert_eq!(log.entries[1].expected_status, Some(200));
+        assert_eq!(log.duration(), Duration::from_millis(250));
+
+        assert!(matches!(
+            ReplayLog::parse("{ not json"),
+            Err(ReplayError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_access_log() {
+        let log = ReplayLog::parse_access_log(concat!(
+            "10.0.0.1 - - [05/Mar/2024:13:02:11 +0000] \"GET /tiles/a.svs/0/0/0.jpg HTTP/1.1\" 200 2326\n",
+            "10.0.0.1 - - [05/Mar/2024:13:02:11 +0000] \"GET /tiles/a.svs/0/1/0.jpg HTTP/1.1\" 404 87 \"-\" \"Mozilla/5.0\"\n",
+            "\n",
+            "garbage line\n",
+            "10.0.0.1 - - [05/Mar/2024:13:02:13 +0000] \"HEAD /slides/a.svs HTTP/1.1\" 200 0\n",
+        ));
+
+        assert_eq!(log.skipped, 1);
+        assert_eq!(log.entries.len(), 3);
+        // Same-second requests keep their log order
+        assert_eq!(log.entries[0].path, "/tiles/a.svs/0/0/0.jpg");
+        assert_eq!(log.entries[1].path, "/tiles/a.svs/0/1/0.jpg");
+        assert_eq!(log.entries[1].expected_status, Some(404));
+        assert_eq!(log.entries[2].method, "HEAD");
+        assert_eq!(log.entries[2].offset, Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_replay_target() {
+        let target = ReplayTarget::parse("http://localhost:3000/wsi/").unwrap();
+        assert_eq!(target.authority, "localhost:3000");
+        assert_eq!(target.host, "localhost:3000");
+        assert_eq!(target.base_path, "/wsi");
+
+        let target = ReplayTarget::parse("http://tiles.internal").unwrap();
+        assert_eq!(target.authority, "tiles.internal:80");
+        assert_eq!(target.base_path, "");
+
+        assert!(ReplayTarget::parse("https://tiles.example").is_err());
+        assert!(ReplayTarget::parse("not a url").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_replay_against_server() {
+        let app = axum::Router::new()
+            .route("/ok", axum::routing::get(|| async { "hello" }))
+            .route(
+                "/missing",
+                axum::routing::get(|| async { axum::http::StatusCode::NOT_FOUND }),
+            );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let entry = |offset_ms, path: &str, expected| ReplayEntry {
+            offset: Duration::from_millis(offset_ms),
+            method: "GET".to_string(),
+            path: path.to_string(),
+            expected_status: Some(expected),
+        };
+        let log = ReplayLog {
+            entries: vec![
+                entry(0, "/ok", 200),
+                entry(10, "/missing", 404),
+                entry(20, "/ok", 500),
+            ],
+            skipped: 0,
+        };
+
+        let target = ReplayTarget::parse(&format!("http://{}", addr)).unwrap();
+        let outcomes = replay(&log, &target, &ReplayOptions::default()).await;
+
+        assert_eq!(outcomes.len(), 3);
+        assert_eq!(outcomes[0].status, Some(200));
+        assert_eq!(outcomes[0].body_bytes, 5);
+        assert!(!outcomes[0].is_mismatch());
+        assert_eq!(outcomes[1].status, Some(404));
+        assert!(!outcomes[1].is_mismatch());
+        assert!(outcomes[2].is_mismatch());
+    }
+
+    #[tokio::test]
+    async fn test_replay_reports_connection_errors() {
+        // Bind then drop a listener to get a port nothing listens on
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+
+        let log = ReplayLog::parse_access_log(
+            "10.0.0.1 - - [05/Mar/2024:13:02:11 +0000] \"GET /health HTTP/1.1\" 200 15\n",
+        );
+        let target = ReplayTarget::parse(&format!("http://{}", addr)).unwrap();
+        let outcomes = replay(&log, &target, &ReplayOptions::default()).await;
+
+        assert_eq!(outcomes[0].status, None);
+        assert!(outcomes[0].error.is_some());
+    }
+}
@@ -0,0 +1,269 @@
+//! Secret references.
+//!
+//! Secret options (such as `--auth-secret`) accept either a literal value or
+//! a reference resolved at startup, so the secret itself never appears in
+//! process arguments or plain configuration files:
+//!
+//! | Reference | Source |
+//! |-----------|--------|
+//! | `file:///run/secrets/wsi` | Contents of a file (trailing newline trimmed) |
+//! | `secretsmanager://my-secret` | AWS Secrets Manager secret string |
+//! | `secretsmanager://my-secret#key` | Field `key` of a JSON secret string |
+//! | `ssm:///wsi/auth-secret` | AWS SSM parameter, decrypted |
+//!
+//! Anything else is used literally. AWS references require the
+//! `aws-secrets` feature and use the default AWS credential chain.
+
+use std::path::Path;
+
+use crate::error::SecretError;
+
+/// Where a secret comes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SecretRef {
+    /// The value itself
+    Literal(String),
+
+    /// Contents of a file
+    File(String),
+
+    /// AWS Secrets Manager secret, optionally a field of a JSON secret
+    SecretsManager {
+        secret_id: String,
+        key: Option<String>,
+    },
+
+    /// AWS Systems Manager Parameter Store parameter
+    Ssm { name: String },
+}
+
+impl SecretRef {
+    /// Parse a secret option value.
+    pub fn parse(value: &str) -> SecretRef {
+        if let Some(path) = value.strip_prefix("file://") {
+            SecretRef::File(path.to_string())
+        } else if let Some(rest) = value.strip_prefix("secretsmanager://") {
+            let (secret_id, key) = match rest.split_once('#') {
+                Some((id, key)) => (id.to_string(), Some(key.to_string())),
+                None => (rest.to_string(), None),
+            };
+            SecretRef::SecretsManager { secret_id, key }
+        } else if let Some(name) = value.strip_prefix("ssm://") {
+            SecretRef::Ssm {
+                name: name.to_string(),
+            }
+        } else {
+            SecretRef::Literal(value.to_string())
+        }
+    }
+
+    /// Description of the source, safe to log.
+    pub fn describe(&self) -> String {
+        match self {
+            SecretRef::Literal(_) => "literal value".to_string(),
+            SecretRef::File(path) => format!("file {}", path),
+            SecretRef::SecretsManager { secret_id, .. } => {
+                format!("Secrets Manager secret {}", secret_id)
+            }
+            SecretRef::Ssm { name } => format!("SSM parameter {}", name),
+        }
+    }
+
+    /// Resolve the secret value.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the source can't be read, the secret is empty,
+    /// or an AWS reference is used without the `aws-secrets` feature.
+    pub async fn resolve(&self) -> Result<String, SecretError> {
+        let value = match self {
+            SecretRef::Literal(value) => value.clone(),
+            SecretRef::File(path) => read_secret_file(Path::new(path))?,
+            SecretRef::SecretsManager { secret_id, key } => {
+                let secret = fetch_secrets_manager(secret_id).await?;
+                match key {
+                    Some(key) => {
+                        extract_json_key(&secret, key).ok_or_else(|| SecretError::MissingKey {
+                            origin: self.describe(),
+                            key: key.clone(),
+                        })?
+                    }
+                    None => secret,
+                }
+            }
+            SecretRef::Ssm { name } => fetch_ssm_parameter(name).await?,
+        };
+
+        if value.is_empty() {
+            return Err(SecretError::Empty {
+                origin: self.describe(),
+            });
+        }
+        Ok(value)
+    }
+}
+
+/// Resolve a secret option value (see [`SecretRef`]).
+pub async fn resolve_secret(value: &str) -> Result<String, SecretError> {
+    SecretRef::parse(value).resolve().await
+}
+
+/// Read a secret file, trimming the trailing newline editors and
+/// `echo` add.
+pub fn read_secret_file(path: &Path) -> Result<String, SecretError> {
+    let contents = std::fs::read_to_string(path).map_err(|e| SecretError::Read {
+        origin: format!("file {}", path.display()),
+        message: e.to_string(),
+    })?;
+    Ok(contents.trim_end_matches(['\r', '\n']).to_string())
+}
+
+/// Extract a string field from a JSON object secret.
+fn extract_json_key(secret: &str, key: &str) -> Option<String> {
+    let value: serde_json::Value = serde_json::from_str(secret).ok()?;
+    match value.get(key)? {
+        serde_json::Value::String(s) => Some(s.clone()),
+        other => Some(other.to_string()),
+    }
+}
+
+// =============================================================================
+// AWS Sources
+// =============================================================================
+
+#[cfg(feature = "aws-secrets")]
+async fn fetch_secrets_manager(secret_id: &str) -> Result<String, SecretError> {
+    let config = aws_config::load_defaults(aws_config::BehaviorVersion::latest()).await;
+    let client = aws_sdk_secretsmanager::Client::new(&config);
+    let output = client
+        .get_secret_value()
+        .secret_id(secret_id)
+        .send()
+        .await
+        .map_err(|e| SecretError::Read {
+            origin: format!("Secrets Manager secret {}", secret_id),
+            message: aws_sdk_secretsmanager::error::DisplayErrorContext(e).to_string(),
+        })?;
+
+    match (output.secret_string(), output.secret_binary()) {
+        (Some(s), _) => Ok(s.to_string()),
+        (None, Some(blob)) => {
+            String::from_utf8(blob.as_ref().to_vec()).map_err(|_| SecretError::Read {
+                origin: format!("Secrets Manager secret {}", secret_id),
+                message: "binary secret is not valid UTF-8".to_string(),
+            })
+        }
+        (None, None) => Ok(String::new()),
+    }
+}
+
+#[cfg(feature = "aws-secrets")]
+async fn fetch_ssm_parameter(name: &str) -> Result<String, SecretError> {
+    let config = aws_config::load_defaults(aws_config::BehaviorVersion::latest()).await;
+    let client = aws_sdk_ssm::Client::new(&config);
+    let output = client
+        .get_parameter()
+        .name(name)
+        .with_decryption(true)
+        .send()
+        .await
+        .map_err(|e| SecretError::Read {
+            origin: format!("SSM parameter {}", name),
+            message: aws_sdk_ssm::error::DisplayErrorContext(e).to_string(),
+        })?;
+
+    Ok(output
+        .parameter()
+        .and_then(|p| p.value())
+        .unwrap_or_default()
+        .to_string())
+}
+
+#[cfg(not(feature = "aws-secrets"))]
+async fn fetch_secrets_manager(_secret_id: &str) -> Result<String, SecretError> {
+    Err(SecretError::Unsupported {
+        scheme: "secretsmanager",
+    })
+}
+
+#[cfg(not(feature = "aws-secrets"))]
+async fn fetch_ssm_parameter(_name: &str) -> Result<String, SecretError> {
+    Err(SecretError::Unsupported { scheme: "ssm" })
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_secret_refs() {
+        assert_eq!(
+            SecretRef::parse("hunter2"),
+            SecretRef::Literal("hunter2".to_string())
+        );
+        assert_eq!(
+            SecretRef::parse("file:///run/secrets/wsi"),
+            SecretRef::File("/run/secrets/wsi".to_string())
+        );
+        assert_eq!(
+            SecretRef::parse("secretsmanager://prod/wsi#auth"),
+            SecretRef::SecretsManager {
+                secret_id: "prod/wsi".to_string(),
+                key: Some("auth".to_string()),
+            }
+        );
+        assert_eq!(
+            SecretRef::parse("ssm:///wsi/auth"),
+            SecretRef::Ssm {
+                name: "/wsi/auth".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_describe_does_not_leak_literals() {
+        assert_eq!(SecretRef::parse("hunter2").describe(), "literal value");
+    }
+
+    #[tokio::test]
+    async fn test_resolve_file_secret() {
+        let path = std::env::temp_dir().join(format!("wsi-secret-{}", std::process::id()));
+        std::fs::write(&path, "s3cret\n").unwrap();
+
+        let value = resolve_secret(&format!("file://{}", path.display()))
+            .await
+            .unwrap();
+        assert_eq!(value, "s3cret");
+
+        std::fs::write(&path, "\n").unwrap();
+        let result = resolve_secret(&format!("file://{}", path.display())).await;
+        assert!(matches!(result, Err(SecretError::Empty { .. })));
+
+        std::fs::remove_file(&path).unwrap();
+        let result = resolve_secret(&format!("file://{}", path.display())).await;
+        assert!(matches!(result, Err(SecretError::Read { .. })));
+    }
+
+    #[test]
+    fn test_extract_json_key() {
+        let secret = r#"{"auth": "abc", "port": 3000}"#;
+        assert_eq!(extract_json_key(secret, "auth"), Some("abc".to_string()));
+        assert_eq!(extract_json_key(secret, "port"), Some("3000".to_string()));
+        assert_eq!(extract_json_key(secret, "missing"), None);
+        assert_eq!(extract_json_key("not json", "auth"), None);
+    }
+
+    #[cfg(not(feature = "aws-secrets"))]
+    #[tokio::test]
+    async fn test_aws_refs_require_feature() {
+        let result = resolve_secret("ssm:///wsi/auth").await;
+        assert!(matches!(
+            result,
+            Err(SecretError::Unsupported { scheme: "ssm" })
+        ));
+    }
+}
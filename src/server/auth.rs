@@ -25,6 +25,33 @@
 //! - **Constant-time comparison**: Signature verification uses constant-time comparison
 //!   to prevent timing attacks
 //!
+//! # Share Links
+//!
+//! Share tokens (`st` query parameter) are self-contained signed policies:
+//! read-only access to one slide until an expiry, optionally capped to a
+//! number of tile requests. The cap is counted per server process.
+//!
+//! # Access Claims
+//!
+//! A `claims` query parameter turns a signature into a scoped grant instead
+//! of a single-path one. The signature covers the claims and expiry, and the
+//! claims say which requests it allows:
+//!
+//! ```text
+//! claims=slide=lab-a/*;min_level=1;endpoints=tiles,metadata&exp=1735689600&sig=abc123...
+//! ```
+//!
+//! - `slide`: slide ID pattern (`*` matches any run of characters, `?` one)
+//! - `min_level`: finest pyramid level allowed (0 = full resolution). Only
+//!   tiles, thumbnails and metadata can be checked against it, so other
+//!   pixel endpoints are refused when it is set.
+//! - `endpoints`: comma-separated endpoint families (see [`ClaimEndpoint`])
+//! - `watermark`: `on` or `off` to burn the configured watermark into tiles
+//!   or leave it out, overriding the server default. Handlers read it from
+//!   the verified claims, which are stored in the request extensions.
+//!
+//! Claims are read-only (GET/HEAD) and only grant slide-scoped endpoints.
+//!
 //! # Example
 //!
 //! ```rust
@@ -42,11 +69,13 @@
 //! assert!(auth.verify(path, &signature, expiry, &[]).is_ok());
 //! ```
 
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use axum::{
     extract::{FromRequestParts, OriginalUri, Request},
-    http::{request::Parts, StatusCode},
+    http::{request::Parts, Method, StatusCode},
     middleware::Next,
     response::{IntoResponse, Response},
     Json,
@@ -58,6 +87,9 @@ use subtle::ConstantTimeEq;
 use tracing::{debug, warn};
 use url::form_urlencoded;
 
+use crate::io::validate_key;
+use crate::slide::glob_matches;
+
 use super::handlers::ErrorResponse;
 
 // =============================================================================
@@ -67,6 +99,15 @@ use super::handlers::ErrorResponse;
 /// HMAC-SHA256 type alias
 type HmacSha256 = Hmac<Sha256>;
 
+/// Default lifetime of a share link
+pub const DEFAULT_SHARE_TTL: Duration = Duration::from_secs(24 * 3600);
+
+/// Maximum lifetime of a share link
+pub const MAX_SHARE_TTL: Duration = Duration::from_secs(30 * 24 * 3600);
+
+/// Default time a replaced signing secret keeps verifying after a rotation
+pub const DEFAULT_SECRET_ROTATION_GRACE: Duration = Duration::from_secs(3600);
+
 /// Authentication error types.
 #[derive(Debug, Clone)]
 pub enum AuthError {
@@ -92,6 +133,24 @@ pub enum AuthError {
 
     /// Expiry timestamp is not a valid integer
     InvalidExpiryFormat,
+
+    /// Viewer token used for anything but reading its slide
+    ViewerReadOnly,
+
+    /// Share token used for anything but reading its slide
+    ShareReadOnly,
+
+    /// Share token has been used for its maximum number of tiles
+    ShareLimitReached {
+        /// Tile requests allowed by the share link
+        max_tiles: u64,
+    },
+
+    /// Access claims could not be parsed
+    InvalidClaims(String),
+
+    /// Access claims don't allow the request
+    ClaimsDenied(String),
 }
 
 impl std::fmt::Display for AuthError {
@@ -110,6 +169,13 @@ impl std::fmt::Display for AuthError {
             AuthError::InvalidSignature => write!(f, "Invalid signature"),
             AuthError::InvalidSignatureFormat => write!(f, "Invalid signature format"),
             AuthError::InvalidExpiryFormat => write!(f, "Invalid expiry format"),
+            AuthError::ViewerReadOnly => write!(f, "Viewer tokens are read-only"),
+            AuthError::ShareReadOnly => write!(f, "Share links are read-only"),
+            AuthError::ShareLimitReached { max_tiles } => {
+                write!(f, "Share link tile limit reached ({} tiles)", max_tiles)
+            }
+            AuthError::InvalidClaims(reason) => write!(f, "Invalid access claims: {}", reason),
+            AuthError::ClaimsDenied(reason) => write!(f, "Access denied by claims: {}", reason),
         }
     }
 }
@@ -145,6 +211,23 @@ impl IntoResponse for AuthError {
                 "invalid_expiry_format",
                 self.to_string(),
             ),
+            AuthError::ViewerReadOnly => {
+                (StatusCode::FORBIDDEN, "viewer_read_only", self.to_string())
+            }
+            AuthError::ShareReadOnly => {
+                (StatusCode::FORBIDDEN, "share_read_only", self.to_string())
+            }
+            AuthError::ShareLimitReached { .. } => (
+                StatusCode::FORBIDDEN,
+                "share_limit_reached",
+                self.to_string(),
+            ),
+            AuthError::InvalidClaims(_) => {
+                (StatusCode::BAD_REQUEST, "invalid_claims", self.to_string())
+            }
+            AuthError::ClaimsDenied(_) => {
+                (StatusCode::FORBIDDEN, "access_denied", self.to_string())
+            }
         };
 
         // Log authentication errors
@@ -192,8 +275,39 @@ impl IntoResponse for AuthError {
 /// The signing scheme binds signatures to paths, query params, and expiry times.
 #[derive(Clone)]
 pub struct SignedUrlAuth {
-    /// Secret key for HMAC computation
-    secret_key: Vec<u8>,
+    /// Secret keys for HMAC computation, shared by clones so a rotation
+    /// reaches every copy
+    keys: Arc<RwLock<SigningKeys>>,
+
+    /// Tile requests served per share token signature, with its expiry
+    share_usage: Arc<Mutex<HashMap<String, ShareUsage>>>,
+}
+
+/// The signing secret, and the one it replaced until its grace period ends.
+struct SigningKeys {
+    current: Vec<u8>,
+    previous: Option<(Vec<u8>, Instant)>,
+    rotation_grace: Duration,
+}
+
+/// Tiles served under one share token.
+#[derive(Debug, Clone, Copy)]
+struct ShareUsage {
+    tiles: u64,
+    expires_at: u64,
+}
+
+/// Access granted by a verified share token.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SharePolicy {
+    /// The only slide the token can read
+    pub slide_id: String,
+
+    /// Unix timestamp after which the token is rejected
+    pub expires_at: u64,
+
+    /// Maximum number of tile requests (None = unlimited)
+    pub max_tiles: Option<u64>,
 }
 
 impl SignedUrlAuth {
@@ -205,10 +319,24 @@ impl SignedUrlAuth {
     ///   at least 32 bytes for security.
     pub fn new(secret_key: impl AsRef<[u8]>) -> Self {
         Self {
-            secret_key: secret_key.as_ref().to_vec(),
+            keys: Arc::new(RwLock::new(SigningKeys {
+                current: secret_key.as_ref().to_vec(),
+                previous: None,
+                rotation_grace: DEFAULT_SECRET_ROTATION_GRACE,
+            })),
+            share_usage: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
+    /// Set how long a replaced secret keeps verifying after a rotation
+    /// (default: [`DEFAULT_SECRET_ROTATION_GRACE`]).
+    ///
+    /// Zero revokes the replaced secret as soon as it is rotated out.
+    pub fn with_rotation_grace(self, grace: Duration) -> Self {
+        self.keys.write().unwrap().rotation_grace = grace;
+        self
+    }
+
     /// Sign a path with an expiry duration.
     ///
     /// Returns the hex-encoded signature and the expiry timestamp (Unix epoch seconds).
@@ -306,13 +434,8 @@ impl SignedUrlAuth {
         // Decode the provided signature
         let provided_sig = hex::decode(signature).map_err(|_| AuthError::InvalidSignatureFormat)?;
 
-        // Compute expected signature
-        let expected_sig_hex = self.compute_signature(path, expiry, params);
-        let expected_sig =
-            hex::decode(&expected_sig_hex).map_err(|_| AuthError::InvalidSignatureFormat)?;
-
         // Constant-time comparison
-        if provided_sig.ct_eq(&expected_sig).into() {
+        if self.verify_message(&signature_base(path, expiry, params), &provided_sig) {
             Ok(())
         } else {
             Err(AuthError::InvalidSignature)
@@ -321,16 +444,45 @@ impl SignedUrlAuth {
 
     /// Compute the HMAC-SHA256 signature for a path and expiry.
     fn compute_signature(&self, path: &str, expiry: u64, params: &[(&str, &str)]) -> String {
-        let message = signature_base(path, expiry, params);
+        hex::encode(self.sign_message(&signature_base(path, expiry, params)))
+    }
 
-        // Compute HMAC-SHA256
-        let mut mac =
-            HmacSha256::new_from_slice(&self.secret_key).expect("HMAC can take key of any size");
-        mac.update(message.as_bytes());
-        let result = mac.finalize();
+    /// Replace the secret key.
+    ///
+    /// Signatures made with the replaced key keep verifying for the rotation
+    /// grace period, so URLs and tokens handed out just before are not cut
+    /// off, and are rejected after it, so rotating a leaked secret revokes
+    /// it. Returns whether the key changed.
+    pub fn rotate_secret(&self, secret_key: impl AsRef<[u8]>) -> bool {
+        let secret_key = secret_key.as_ref();
+        let mut keys = self.keys.write().unwrap();
+        if keys.current == secret_key {
+            return false;
+        }
+        let previous = std::mem::replace(&mut keys.current, secret_key.to_vec());
+        let grace_ends = Instant::now() + keys.rotation_grace;
+        keys.previous = Some((previous, grace_ends));
+        true
+    }
+
+    /// Compute the raw HMAC-SHA256 of a message with the current key.
+    fn sign_message(&self, message: &str) -> Vec<u8> {
+        hmac_sha256(&self.keys.read().unwrap().current, message)
+    }
 
-        // Return hex-encoded signature
-        hex::encode(result.into_bytes())
+    /// Check a raw signature of a message against the current key, then the
+    /// one it replaced while in its grace period, in constant time.
+    fn verify_message(&self, message: &str, provided: &[u8]) -> bool {
+        let keys = self.keys.read().unwrap();
+        let now = Instant::now();
+        let previous = keys
+            .previous
+            .as_ref()
+            .filter(|(_, grace_ends)| now < *grace_ends)
+            .map(|(key, _)| key);
+        std::iter::once(&keys.current)
+            .chain(previous)
+            .any(|key| bool::from(provided.ct_eq(&hmac_sha256(key, message))))
     }
 
     /// Generate a complete signed URL.
@@ -392,12 +544,7 @@ impl SignedUrlAuth {
 
         let message = format!("viewer:{}:{}", slide_id, expiry);
 
-        let mut mac =
-            HmacSha256::new_from_slice(&self.secret_key).expect("HMAC can take key of any size");
-        mac.update(message.as_bytes());
-        let result = mac.finalize();
-
-        (hex::encode(result.into_bytes()), expiry)
+        (hex::encode(self.sign_message(&message)), expiry)
     }
 
     /// Verify a viewer token for a specific slide.
@@ -433,20 +580,205 @@ impl SignedUrlAuth {
         // Decode the provided token
         let provided_token = hex::decode(token).map_err(|_| AuthError::InvalidSignatureFormat)?;
 
-        // Compute expected token
-        let message = format!("viewer:{}:{}", slide_id, expiry);
-        let mut mac =
-            HmacSha256::new_from_slice(&self.secret_key).expect("HMAC can take key of any size");
-        mac.update(message.as_bytes());
-        let expected_token = mac.finalize().into_bytes();
-
         // Constant-time comparison
-        if provided_token.ct_eq(&expected_token).into() {
+        let message = format!("viewer:{}:{}", slide_id, expiry);
+        if self.verify_message(&message, &provided_token) {
             Ok(())
         } else {
             Err(AuthError::InvalidSignature)
         }
     }
+
+    /// Generate a share token for read-only access to one slide.
+    ///
+    /// The token is self-contained: it carries the slide, expiry and tile
+    /// limit, signed together so none of them can be altered.
+    ///
+    /// # Returns
+    ///
+    /// A tuple of (token, expiry_timestamp)
+    pub fn generate_share_token(
+        &self,
+        slide_id: &str,
+        ttl: Duration,
+        max_tiles: Option<u64>,
+    ) -> (String, u64) {
+        let expiry = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            + ttl.as_secs();
+        let max_tiles = max_tiles.unwrap_or(0);
+
+        let signature = self.sign_message(&share_message(slide_id, expiry, max_tiles));
+        let token = format!(
+            "{}.{}.{}.{}",
+            hex::encode(slide_id),
+            expiry,
+            max_tiles,
+            hex::encode(signature)
+        );
+
+        (token, expiry)
+    }
+
+    /// Verify a share token and return the policy it grants.
+    pub fn verify_share_token(&self, token: &str) -> Result<SharePolicy, AuthError> {
+        let mut parts = token.split('.');
+        let (Some(slide_hex), Some(expiry), Some(max_tiles), Some(signature), None) = (
+            parts.next(),
+            parts.next(),
+            parts.next(),
+            parts.next(),
+            parts.next(),
+        ) else {
+            return Err(AuthError::InvalidSignatureFormat);
+        };
+
+        let slide_id = hex::decode(slide_hex)
+            .ok()
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+            .ok_or(AuthError::InvalidSignatureFormat)?;
+        let expiry = expiry
+            .parse::<u64>()
+            .map_err(|_| AuthError::InvalidExpiryFormat)?;
+        let max_tiles = max_tiles
+            .parse::<u64>()
+            .map_err(|_| AuthError::InvalidSignatureFormat)?;
+
+        // Check expiry first
+        let current_time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        if current_time > expiry {
+            return Err(AuthError::Expired {
+                expired_at: expiry,
+                current_time,
+            });
+        }
+
+        let provided_sig = hex::decode(signature).map_err(|_| AuthError::InvalidSignatureFormat)?;
+        // Constant-time comparison
+        if !self.verify_message(&share_message(&slide_id, expiry, max_tiles), &provided_sig) {
+            return Err(AuthError::InvalidSignature);
+        }
+
+        Ok(SharePolicy {
+            slide_id,
+            expires_at: expiry,
+            max_tiles: (max_tiles > 0).then_some(max_tiles),
+        })
+    }
+
+    /// Count a tile request against a share token's limit.
+    ///
+    /// Returns [`AuthError::ShareLimitReached`] once the limit is used up.
+    /// Counts live in memory, so each server process enforces its own limit.
+    pub fn record_share_tile(&self, token: &str, policy: &SharePolicy) -> Result<(), AuthError> {
+        let Some(max_tiles) = policy.max_tiles else {
+            return Ok(());
+        };
+
+        // The signature part identifies the token
+        let key = token.rsplit('.').next().unwrap_or(token);
+
+        let mut usage = self.share_usage.lock().unwrap();
+        if !usage.contains_key(key) {
+            // Forget expired tokens before tracking a new one
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_secs();
+            usage.retain(|_, entry| entry.expires_at >= now);
+        }
+
+        let entry = usage.entry(key.to_string()).or_insert(ShareUsage {
+            tiles: 0,
+            expires_at: policy.expires_at,
+        });
+        if entry.tiles >= max_tiles {
+            return Err(AuthError::ShareLimitReached { max_tiles });
+        }
+        entry.tiles += 1;
+
+        Ok(())
+    }
+
+    /// Sign access claims with an expiry duration.
+    ///
+    /// Returns the hex-encoded signature and the expiry timestamp. The
+    /// signature is sent as `sig` alongside `claims` (the value of
+    /// [`AccessClaims::encode`]) and `exp` on any request the claims allow.
+    pub fn sign_claims(&self, claims: &AccessClaims, ttl: Duration) -> (String, u64) {
+        let expiry = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            + ttl.as_secs();
+
+        let signature = hex::encode(self.sign_message(&claims_message(&claims.encode(), expiry)));
+        (signature, expiry)
+    }
+
+    /// Build the query string (`claims`, `exp` and `sig`) granting access
+    /// claims, to append to any path they allow.
+    pub fn generate_claims_query(&self, claims: &AccessClaims, ttl: Duration) -> String {
+        let (signature, expiry) = self.sign_claims(claims, ttl);
+        form_urlencoded::Serializer::new(String::new())
+            .append_pair("claims", &claims.encode())
+            .append_pair("exp", &expiry.to_string())
+            .append_pair("sig", &signature)
+            .finish()
+    }
+
+    /// Verify signed access claims and parse them.
+    ///
+    /// The signature covers the claims string exactly as sent.
+    pub fn verify_claims(
+        &self,
+        claims: &str,
+        signature: &str,
+        expiry: u64,
+    ) -> Result<AccessClaims, AuthError> {
+        // Check expiry first
+        let current_time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        if current_time > expiry {
+            return Err(AuthError::Expired {
+                expired_at: expiry,
+                current_time,
+            });
+        }
+
+        let provided_sig = hex::decode(signature).map_err(|_| AuthError::InvalidSignatureFormat)?;
+        // Constant-time comparison
+        if !self.verify_message(&claims_message(claims, expiry), &provided_sig) {
+            return Err(AuthError::InvalidSignature);
+        }
+
+        AccessClaims::parse(claims)
+    }
+}
+
+fn hmac_sha256(key: &[u8], message: &str) -> Vec<u8> {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC can take key of any size");
+    mac.update(message.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Message signed for encoded access claims.
+fn claims_message(claims: &str, expiry: u64) -> String {
+    format!("claims:{}:{}", claims, expiry)
+}
+
+/// Message signed for a share policy.
+fn share_message(slide_id: &str, expiry: u64, max_tiles: u64) -> String {
+    format!("share:{}:{}:{}", slide_id, expiry, max_tiles)
 }
 
 fn signature_base(path: &str, expiry: u64, params: &[(&str, &str)]) -> String {
@@ -474,6 +806,255 @@ fn canonical_query(params: &[(String, String)]) -> String {
         .join("&")
 }
 
+// =============================================================================
+// Access Claims
+// =============================================================================
+
+/// Endpoint families that access claims can grant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClaimEndpoint {
+    /// `/tiles/...`
+    Tiles,
+    /// Slide metadata, info, levels, DZI descriptors and IIIF `info.json`
+    Metadata,
+    /// `/slides/{slide_id}/thumbnail` and `/slides/{slide_id}/thumbnail.jpg`
+    Thumbnail,
+    /// `/region/...` and `/slides/{slide_id}/snapshot`
+    Region,
+    /// IIIF image requests
+    Iiif,
+    /// Deep Zoom tiles under `/dzi/{slide_id}_files/`
+    Dzi,
+    /// `/slides/{slide_id}/raw`, only when listed explicitly
+    Raw,
+}
+
+impl ClaimEndpoint {
+    /// Name used in the `endpoints` claim.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ClaimEndpoint::Tiles => "tiles",
+            ClaimEndpoint::Metadata => "metadata",
+            ClaimEndpoint::Thumbnail => "thumbnail",
+            ClaimEndpoint::Region => "region",
+            ClaimEndpoint::Iiif => "iiif",
+            ClaimEndpoint::Dzi => "dzi",
+            ClaimEndpoint::Raw => "raw",
+        }
+    }
+
+    /// Parse an endpoint family name.
+    pub fn parse(name: &str) -> Option<Self> {
+        [
+            ClaimEndpoint::Tiles,
+            ClaimEndpoint::Metadata,
+            ClaimEndpoint::Thumbnail,
+            ClaimEndpoint::Region,
+            ClaimEndpoint::Iiif,
+            ClaimEndpoint::Dzi,
+            ClaimEndpoint::Raw,
+        ]
+        .into_iter()
+        .find(|endpoint| endpoint.name() == name)
+    }
+
+    /// Whether requests return slide pixels (counted against share limits).
+    pub fn returns_pixels(&self) -> bool {
+        !matches!(self, ClaimEndpoint::Metadata)
+    }
+
+    /// Classify a request path, or `None` if claims can't grant it.
+    pub fn from_path(path: &str) -> Option<Self> {
+        let parts: Vec<&str> = path.split('/').collect();
+
+        match (parts.get(1).copied(), parts.len()) {
+            (Some("tiles"), 6) => Some(ClaimEndpoint::Tiles),
+            (Some("region"), 3) => Some(ClaimEndpoint::Region),
+            (Some("slides"), 3) => Some(ClaimEndpoint::Metadata),
+            (Some("slides"), 4) => match parts[3] {
+                "info" | "levels" | "dzi" => Some(ClaimEndpoint::Metadata),
+                "thumbnail" | "thumbnail.jpg" => Some(ClaimEndpoint::Thumbnail),
+                "snapshot" => Some(ClaimEndpoint::Region),
+                "raw" => Some(ClaimEndpoint::Raw),
+                _ => None,
+            },
+            (Some("iiif"), 3) => Some(ClaimEndpoint::Metadata),
+            (Some("iiif"), 4) if parts[3] == "info.json" => Some(ClaimEndpoint::Metadata),
+            (Some("iiif"), 7) => Some(ClaimEndpoint::Iiif),
+            (Some("dzi"), 3) => Some(ClaimEndpoint::Metadata),
+            (Some("dzi"), 5) => Some(ClaimEndpoint::Dzi),
+            _ => None,
+        }
+    }
+}
+
+/// Who a verified request acts for: the share token, viewer token or access
+/// claims shared by every request a signature authorizes.
+///
+/// Stored in the request extensions by [`auth_middleware`] once the request
+/// is verified, so per-subject rate limits never trust an unverified token.
+/// Path-signed URLs have a different signature per tile and carry no
+/// subject.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AuthSubject(pub String);
+
+/// Scope granted by a signed `claims` parameter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccessClaims {
+    /// Slide ID pattern (`*` matches any run of characters, `?` one)
+    pub slide_pattern: String,
+
+    /// Finest pyramid level allowed (None = all levels)
+    pub min_level: Option<usize>,
+
+    /// Endpoint families allowed (None = all that claims can grant, except
+    /// raw slide bytes)
+    pub endpoints: Option<Vec<ClaimEndpoint>>,
+
+    /// Whether tiles are watermarked (None = server default)
+    pub watermark: Option<bool>,
+}
+
+impl AccessClaims {
+    /// Claims granting every endpoint for slides matching a pattern.
+    pub fn for_slides(pattern: impl Into<String>) -> Self {
+        Self {
+            slide_pattern: pattern.into(),
+            min_level: None,
+            endpoints: None,
+            watermark: None,
+        }
+    }
+
+    /// Only allow pyramid levels at or below this resolution.
+    pub fn with_min_level(mut self, level: usize) -> Self {
+        self.min_level = Some(level);
+        self
+    }
+
+    /// Only allow these endpoint families.
+    pub fn with_endpoints(mut self, endpoints: Vec<ClaimEndpoint>) -> Self {
+        self.endpoints = Some(endpoints);
+        self
+    }
+
+    /// Turn the tile watermark on or off for requests under these claims.
+    pub fn with_watermark(mut self, enabled: bool) -> Self {
+        self.watermark = Some(enabled);
+        self
+    }
+
+    /// Encode the claims as sent in the `claims` query parameter.
+    pub fn encode(&self) -> String {
+        let mut claims = format!("slide={}", self.slide_pattern);
+        if let Some(level) = self.min_level {
+            claims.push_str(&format!(";min_level={}", level));
+        }
+        if let Some(ref endpoints) = self.endpoints {
+            let names: Vec<_> = endpoints.iter().map(ClaimEndpoint::name).collect();
+            claims.push_str(&format!(";endpoints={}", names.join(",")));
+        }
+        if let Some(watermark) = self.watermark {
+            claims.push_str(if watermark {
+                ";watermark=on"
+            } else {
+                ";watermark=off"
+            });
+        }
+        claims
+    }
+
+    /// Parse claims from the `claims` query parameter.
+    pub fn parse(value: &str) -> Result<Self, AuthError> {
+        let invalid = |reason: String| AuthError::InvalidClaims(reason);
+        let mut slide_pattern = None;
+        let mut min_level = None;
+        let mut endpoints = None;
+        let mut watermark = None;
+
+        for claim in value.split(';') {
+            let (key, value) = claim
+                .split_once('=')
+                .ok_or_else(|| invalid(format!("expected key=value, got '{}'", claim)))?;
+            match key {
+                "slide" if !value.is_empty() => slide_pattern = Some(value.to_string()),
+                "min_level" => {
+                    let level = value
+                        .parse::<usize>()
+                        .map_err(|_| invalid(format!("invalid min_level '{}'", value)))?;
+                    min_level = Some(level);
+                }
+                "endpoints" => {
+                    let parsed = value
+                        .split(',')
+                        .map(|name| {
+                            ClaimEndpoint::parse(name)
+                                .ok_or_else(|| invalid(format!("unknown endpoint '{}'", name)))
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+                    endpoints = Some(parsed);
+                }
+                "watermark" => {
+                    watermark = match value {
+                        "on" => Some(true),
+                        "off" => Some(false),
+                        _ => return Err(invalid(format!("invalid watermark '{}'", value))),
+                    };
+                }
+                _ => return Err(invalid(format!("unknown claim '{}'", claim))),
+            }
+        }
+
+        Ok(Self {
+            slide_pattern: slide_pattern.ok_or_else(|| invalid("missing slide".to_string()))?,
+            min_level,
+            endpoints,
+            watermark,
+        })
+    }
+
+    /// Check that the claims allow a request.
+    pub fn check(&self, method: &Method, path: &str) -> Result<(), AuthError> {
+        let denied = |reason: &str| Err(AuthError::ClaimsDenied(reason.to_string()));
+
+        if method != Method::GET && method != Method::HEAD {
+            return denied("claims are read-only");
+        }
+        let Some(endpoint) = ClaimEndpoint::from_path(path) else {
+            return denied("endpoint is not slide-scoped");
+        };
+        let Some(slide_id) = extract_slide_id_from_path(path) else {
+            return denied("endpoint is not slide-scoped");
+        };
+        if !glob_matches(&self.slide_pattern, &slide_id) {
+            return denied("slide not covered");
+        }
+        let allowed = match self.endpoints {
+            Some(ref endpoints) => endpoints.contains(&endpoint),
+            // Downloading the whole file has to be granted explicitly
+            None => endpoint != ClaimEndpoint::Raw,
+        };
+        if !allowed {
+            return denied("endpoint not allowed");
+        }
+
+        if let Some(min_level) = self.min_level {
+            match endpoint {
+                ClaimEndpoint::Metadata | ClaimEndpoint::Thumbnail => {}
+                ClaimEndpoint::Tiles => {
+                    let level = path.split('/').nth(3).and_then(|l| l.parse::<usize>().ok());
+                    if level.map_or(true, |level| level < min_level) {
+                        return denied("pyramid level not allowed");
+                    }
+                }
+                _ => return denied("endpoint not allowed with a level restriction"),
+            }
+        }
+
+        Ok(())
+    }
+}
+
 // =============================================================================
 // Query Parameters for Auth
 // =============================================================================
@@ -503,6 +1084,12 @@ pub struct AuthQueryParams {
 ///    a signature for the exact request path.
 /// 2. **Viewer tokens**: Uses `vt` and `exp` query params to verify a token
 ///    that authorizes access to all tiles for a specific slide.
+/// 3. **Share tokens**: Uses the `st` query param, a self-contained token
+///    granting read-only (GET/HEAD) access to one slide, optionally limited
+///    to a number of tile requests.
+/// 4. **Access claims**: Uses `claims`, `exp` and `sig` query params; the
+///    signature covers the claims, which are checked against the request
+///    (see [`AccessClaims`]).
 ///
 /// # Example
 ///
@@ -518,12 +1105,14 @@ pub struct AuthQueryParams {
 pub async fn auth_middleware(
     axum::extract::State(auth): axum::extract::State<SignedUrlAuth>,
     OriginalUri(original_uri): OriginalUri,
-    request: Request,
+    mut request: Request,
     next: Next,
 ) -> Result<Response, AuthError> {
     let query = original_uri.query().unwrap_or("");
     let mut signature: Option<String> = None;
     let mut viewer_token: Option<String> = None;
+    let mut share_token: Option<String> = None;
+    let mut claims: Option<String> = None;
     let mut expiry: Option<u64> = None;
     let mut extra_params: Vec<(String, String)> = Vec::new();
 
@@ -542,6 +1131,20 @@ pub async fn auth_middleware(
             viewer_token = Some(value.into_owned());
             continue;
         }
+        if key == "st" {
+            if share_token.is_some() {
+                return Err(AuthError::InvalidSignatureFormat);
+            }
+            share_token = Some(value.into_owned());
+            continue;
+        }
+        if key == "claims" {
+            if claims.is_some() {
+                return Err(AuthError::InvalidSignatureFormat);
+            }
+            claims = Some(value.into_owned());
+            continue;
+        }
         if key == "exp" {
             if expiry.is_some() {
                 return Err(AuthError::InvalidExpiryFormat);
@@ -556,9 +1159,42 @@ pub async fn auth_middleware(
         extra_params.push((key.into_owned(), value.into_owned()));
     }
 
-    let expiry = expiry.ok_or(AuthError::MissingExpiry)?;
     let path = original_uri.path();
 
+    // Share tokens carry their own expiry
+    if let Some(token) = share_token {
+        let policy = auth.verify_share_token(&token)?;
+        if request.method() != Method::GET && request.method() != Method::HEAD {
+            return Err(AuthError::ShareReadOnly);
+        }
+        if extract_slide_id_from_path(path).as_deref() != Some(policy.slide_id.as_str()) {
+            return Err(AuthError::InvalidSignature);
+        }
+        // Every request for pixels counts against the limit
+        let endpoint = viewer_endpoint(path).ok_or(AuthError::ShareReadOnly)?;
+        if endpoint.returns_pixels() {
+            auth.record_share_tile(&token, &policy)?;
+        }
+        request
+            .extensions_mut()
+            .insert(AuthSubject(format!("st:{}", token)));
+        return Ok(next.run(request).await);
+    }
+
+    let expiry = expiry.ok_or(AuthError::MissingExpiry)?;
+
+    // Access claims are signed instead of the path
+    if let Some(raw_claims) = claims {
+        let signature = signature.ok_or(AuthError::MissingSignature)?;
+        let claims = auth.verify_claims(&raw_claims, &signature, expiry)?;
+        claims.check(request.method(), path)?;
+        request.extensions_mut().insert(claims);
+        request
+            .extensions_mut()
+            .insert(AuthSubject(format!("claims:{}:{}", raw_claims, expiry)));
+        return Ok(next.run(request).await);
+    }
+
     // Check for viewer token first (used by built-in viewer)
     if let Some(token) = viewer_token {
         // Extract slide_id from the path
@@ -566,6 +1202,17 @@ pub async fn auth_middleware(
         let slide_id = extract_slide_id_from_path(path);
         if let Some(slide_id) = slide_id {
             auth.verify_viewer_token(&slide_id, &token, expiry)?;
+            // The public viewer page hands these out, so they only read the
+            // slide's tiles and metadata, like access claims
+            if request.method() != Method::GET && request.method() != Method::HEAD {
+                return Err(AuthError::ViewerReadOnly);
+            }
+            if viewer_endpoint(path).is_none() {
+                return Err(AuthError::ViewerReadOnly);
+            }
+            request
+                .extensions_mut()
+                .insert(AuthSubject(format!("vt:{}", token)));
             return Ok(next.run(request).await);
         }
         // If we can't extract slide_id, fall through to require regular signature
@@ -585,6 +1232,12 @@ pub async fn auth_middleware(
     Ok(next.run(request).await)
 }
 
+/// Classify a path readable with a viewer or share token: the endpoint
+/// families access claims grant by default, so not the raw slide file.
+fn viewer_endpoint(path: &str) -> Option<ClaimEndpoint> {
+    ClaimEndpoint::from_path(path).filter(|endpoint| *endpoint != ClaimEndpoint::Raw)
+}
+
 /// Extract the slide_id from a tile or slides path.
 ///
 /// Handles paths like:
@@ -592,21 +1245,37 @@ pub async fn auth_middleware(
 /// - `/slides/{slide_id}`
 /// - `/slides/{slide_id}/dzi`
 /// - `/slides/{slide_id}/thumbnail`
+/// - `/slides/{slide_id}/thumbnail.jpg`
+/// - `/iiif/{slide_id}/info.json`
+/// - `/dzi/{slide_id}.dzi`
+/// - `/dzi/{slide_id}_files/{level}/{x}_{y}.jpg`
 fn extract_slide_id_from_path(path: &str) -> Option<String> {
     let parts: Vec<&str> = path.split('/').collect();
 
-    // Expected: ["", "tiles" or "slides", slide_id, ...]
+    // Expected: ["", "tiles", "slides", "region", "iiif" or "dzi", slide_id, ...]
     if parts.len() < 3 {
         return None;
     }
 
-    match parts[1] {
-        "tiles" | "slides" => {
+    let slide_id = match parts[1] {
+        "tiles" | "slides" | "region" | "iiif" => {
             // URL-decode the slide_id
             urlencoding::decode(parts[2]).ok().map(|s| s.into_owned())
         }
+        "dzi" => {
+            let name = urlencoding::decode(parts[2]).ok()?;
+            let slide_id = if parts.len() == 3 {
+                super::dzi::parse_dzi_descriptor_name(&name)
+            } else {
+                super::dzi::parse_dzi_files_name(&name)
+            };
+            slide_id.map(str::to_string)
+        }
         _ => None,
-    }
+    };
+
+    // IDs that could climb out of a prefix never match a claim or token
+    slide_id.filter(|slide_id| validate_key(slide_id).is_ok())
 }
 
 /// Axum extractor for optional authentication.
@@ -740,6 +1409,57 @@ mod tests {
         assert!(auth2.verify(path, &sig1, expiry, &[]).is_err());
     }
 
+    #[test]
+    fn test_rotate_secret_keeps_previous_key() {
+        let auth = SignedUrlAuth::new("key1");
+        let clone = auth.clone();
+        let path = "/tiles/slides/sample.svs/0/1/2.jpg";
+        let ttl = Duration::from_secs(3600);
+        let (old_sig, expiry) = auth.sign(path, ttl);
+
+        assert!(!auth.rotate_secret("key1"));
+        assert!(auth.rotate_secret("key2"));
+
+        // Clones sign with the new key and still accept the replaced one
+        let new_sig = clone.sign_with_expiry(path, expiry);
+        assert_eq!(
+            new_sig,
+            SignedUrlAuth::new("key2").sign_with_expiry(path, expiry)
+        );
+        assert!(clone.verify(path, &old_sig, expiry, &[]).is_ok());
+        assert!(clone.verify(path, &new_sig, expiry, &[]).is_ok());
+
+        // Until the next rotation
+        assert!(auth.rotate_secret("key3"));
+        assert!(clone.verify(path, &old_sig, expiry, &[]).is_err());
+        assert!(clone.verify(path, &new_sig, expiry, &[]).is_ok());
+    }
+
+    #[test]
+    fn test_rotated_secret_expires_after_grace() {
+        let path = "/tiles/slides/sample.svs/0/1/2.jpg";
+        let ttl = Duration::from_secs(3600);
+
+        let auth = SignedUrlAuth::new("key1").with_rotation_grace(Duration::from_millis(50));
+        let (old_sig, expiry) = auth.sign(path, ttl);
+        assert!(auth.rotate_secret("key2"));
+        assert!(auth.verify(path, &old_sig, expiry, &[]).is_ok());
+
+        std::thread::sleep(Duration::from_millis(80));
+        assert!(matches!(
+            auth.verify(path, &old_sig, expiry, &[]),
+            Err(AuthError::InvalidSignature)
+        ));
+        let (new_sig, expiry) = auth.sign(path, ttl);
+        assert!(auth.verify(path, &new_sig, expiry, &[]).is_ok());
+
+        // No grace revokes the old secret immediately
+        let auth = SignedUrlAuth::new("key1").with_rotation_grace(Duration::ZERO);
+        let (old_sig, expiry) = auth.sign(path, ttl);
+        assert!(auth.rotate_secret("key2"));
+        assert!(auth.verify(path, &old_sig, expiry, &[]).is_err());
+    }
+
     #[test]
     fn test_signature_is_deterministic() {
         let auth = SignedUrlAuth::new("test-secret-key");
@@ -927,6 +1647,251 @@ mod tests {
             extract_slide_id_from_path("/slides/sample.svs/thumbnail"),
             Some("sample.svs".to_string())
         );
+        assert_eq!(
+            extract_slide_id_from_path("/iiif/sample.svs/info.json"),
+            Some("sample.svs".to_string())
+        );
+        assert_eq!(
+            extract_slide_id_from_path("/region/sample.svs"),
+            Some("sample.svs".to_string())
+        );
+        assert_eq!(
+            extract_slide_id_from_path("/dzi/sample.svs.dzi"),
+            Some("sample.svs".to_string())
+        );
+        assert_eq!(
+            extract_slide_id_from_path("/dzi/sample.svs_files/12/3_4.jpg"),
+            Some("sample.svs".to_string())
+        );
+    }
+
+    #[test]
+    fn test_share_token_roundtrip() {
+        let auth = SignedUrlAuth::new("test-secret-key");
+        let (token, expiry) =
+            auth.generate_share_token("folder/sample.svs", Duration::from_secs(3600), Some(10));
+
+        let policy = auth.verify_share_token(&token).unwrap();
+        assert_eq!(policy.slide_id, "folder/sample.svs");
+        assert_eq!(policy.expires_at, expiry);
+        assert_eq!(policy.max_tiles, Some(10));
+
+        // No limit is encoded as zero
+        let (token, _) = auth.generate_share_token("sample.svs", Duration::from_secs(60), None);
+        assert_eq!(auth.verify_share_token(&token).unwrap().max_tiles, None);
+    }
+
+    #[test]
+    fn test_share_token_tampering() {
+        let auth = SignedUrlAuth::new("test-secret-key");
+        let (token, _) =
+            auth.generate_share_token("sample.svs", Duration::from_secs(3600), Some(10));
+        let parts: Vec<&str> = token.split('.').collect();
+
+        // Raising the tile limit invalidates the signature
+        let raised = format!("{}.{}.1000.{}", parts[0], parts[1], parts[3]);
+        assert!(matches!(
+            auth.verify_share_token(&raised),
+            Err(AuthError::InvalidSignature)
+        ));
+
+        // Pointing the token at another slide too
+        let other = format!(
+            "{}.{}.{}.{}",
+            hex::encode("other.svs"),
+            parts[1],
+            parts[2],
+            parts[3]
+        );
+        assert!(matches!(
+            auth.verify_share_token(&other),
+            Err(AuthError::InvalidSignature)
+        ));
+
+        // Tokens from another secret are rejected
+        let other_auth = SignedUrlAuth::new("other-secret");
+        assert!(other_auth.verify_share_token(&token).is_err());
+
+        // Malformed tokens
+        assert!(matches!(
+            auth.verify_share_token("not-a-token"),
+            Err(AuthError::InvalidSignatureFormat)
+        ));
+        assert!(matches!(
+            auth.verify_share_token(&format!("{}.extra", token)),
+            Err(AuthError::InvalidSignatureFormat)
+        ));
+    }
+
+    #[test]
+    fn test_share_token_expired() {
+        let auth = SignedUrlAuth::new("test-secret-key");
+        let signature = auth.sign_message(&share_message("sample.svs", 1000, 0));
+        let token = format!(
+            "{}.1000.0.{}",
+            hex::encode("sample.svs"),
+            hex::encode(signature)
+        );
+
+        assert!(matches!(
+            auth.verify_share_token(&token),
+            Err(AuthError::Expired { .. })
+        ));
+    }
+
+    #[test]
+    fn test_record_share_tile_limit() {
+        let auth = SignedUrlAuth::new("test-secret-key");
+        let (token, _) =
+            auth.generate_share_token("sample.svs", Duration::from_secs(3600), Some(2));
+        let policy = auth.verify_share_token(&token).unwrap();
+
+        assert!(auth.record_share_tile(&token, &policy).is_ok());
+        assert!(auth.record_share_tile(&token, &policy).is_ok());
+        assert!(matches!(
+            auth.record_share_tile(&token, &policy),
+            Err(AuthError::ShareLimitReached { max_tiles: 2 })
+        ));
+
+        // Clones share the counters
+        assert!(auth.clone().record_share_tile(&token, &policy).is_err());
+
+        // Unlimited tokens are not counted
+        let (token, _) = auth.generate_share_token("sample.svs", Duration::from_secs(3600), None);
+        let policy = auth.verify_share_token(&token).unwrap();
+        for _ in 0..5 {
+            assert!(auth.record_share_tile(&token, &policy).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_endpoints_returning_pixels() {
+        let returns_pixels =
+            |path: &str| ClaimEndpoint::from_path(path).map(|e| e.returns_pixels());
+        assert_eq!(returns_pixels("/tiles/sample.svs/0/1/2.jpg"), Some(true));
+        assert_eq!(
+            returns_pixels("/dzi/sample.svs_files/12/3_4.jpg"),
+            Some(true)
+        );
+        assert_eq!(
+            returns_pixels("/iiif/sample.svs/full/256,/0/default.jpg"),
+            Some(true)
+        );
+        assert_eq!(returns_pixels("/region/sample.svs"), Some(true));
+        assert_eq!(returns_pixels("/slides/sample.svs/snapshot"), Some(true));
+        assert_eq!(
+            returns_pixels("/slides/sample.svs/thumbnail.jpg"),
+            Some(true)
+        );
+        assert_eq!(returns_pixels("/dzi/sample.svs.dzi"), Some(false));
+        assert_eq!(returns_pixels("/iiif/sample.svs/info.json"), Some(false));
+        assert_eq!(returns_pixels("/slides/sample.svs"), Some(false));
+        assert_eq!(returns_pixels("/slides/sample.svs/sample"), None);
+    }
+
+    #[test]
+    fn test_claims_roundtrip() {
+        let auth = SignedUrlAuth::new("test-secret-key");
+        let claims = AccessClaims::for_slides("lab-a/*")
+            .with_min_level(1)
+            .with_endpoints(vec![ClaimEndpoint::Tiles, ClaimEndpoint::Metadata]);
+        assert_eq!(
+            claims.encode(),
+            "slide=lab-a/*;min_level=1;endpoints=tiles,metadata"
+        );
+        assert_eq!(AccessClaims::parse(&claims.encode()).unwrap(), claims);
+
+        let (signature, expiry) = auth.sign_claims(&claims, Duration::from_secs(3600));
+        assert_eq!(
+            auth.verify_claims(&claims.encode(), &signature, expiry)
+                .unwrap(),
+            claims
+        );
+
+        // Widening the claims invalidates the signature
+        assert!(matches!(
+            auth.verify_claims("slide=*", &signature, expiry),
+            Err(AuthError::InvalidSignature)
+        ));
+
+        let query = auth.generate_claims_query(&claims, Duration::from_secs(3600));
+        assert!(query.starts_with("claims=slide%3Dlab-a%2F*%3Bmin_level%3D1"));
+        assert!(query.contains("&sig="));
+
+        let watermarked = AccessClaims::for_slides("export/*").with_watermark(true);
+        assert_eq!(watermarked.encode(), "slide=export/*;watermark=on");
+        assert_eq!(
+            AccessClaims::parse("slide=export/*;watermark=off")
+                .unwrap()
+                .watermark,
+            Some(false)
+        );
+    }
+
+    #[test]
+    fn test_claims_parse_errors() {
+        for value in [
+            "",
+            "min_level=1",
+            "slide=",
+            "slide=a;min_level=x",
+            "slide=a;endpoints=tiles,nope",
+            "slide=a;owner=me",
+            "slide=a;watermark=yes",
+        ] {
+            assert!(
+                matches!(AccessClaims::parse(value), Err(AuthError::InvalidClaims(_))),
+                "{}",
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn test_claims_check() {
+        let get = Method::GET;
+        let claims = AccessClaims::for_slides("lab-a/*").with_min_level(1);
+
+        assert!(claims
+            .check(&get, "/tiles/lab-a%2Fs1.svs/1/0/0.jpg")
+            .is_ok());
+        assert!(claims
+            .check(&get, "/tiles/lab-a%2Fs1.svs/3/0/0.png")
+            .is_ok());
+        assert!(claims.check(&get, "/slides/lab-a%2Fs1.svs").is_ok());
+        assert!(claims
+            .check(&get, "/slides/lab-a%2Fs1.svs/thumbnail")
+            .is_ok());
+        assert!(claims
+            .check(&get, "/slides/lab-a%2Fs1.svs/thumbnail.jpg")
+            .is_ok());
+        assert!(claims.check(&get, "/iiif/lab-a%2Fs1.svs/info.json").is_ok());
+
+        let denied = [
+            (Method::GET, "/tiles/lab-a%2Fs1.svs/0/0/0.jpg"),
+            (Method::GET, "/tiles/lab-b%2Fs1.svs/1/0/0.jpg"),
+            (Method::GET, "/tiles/lab-a%2F..%2Fb.svs/1/0/0.jpg"),
+            (Method::GET, "/slides/lab-a%2Fs1.svs/raw"),
+            (Method::GET, "/region/lab-a%2Fs1.svs"),
+            (Method::GET, "/slides"),
+            (Method::POST, "/slides/lab-a%2Fs1.svs/views"),
+        ];
+        for (method, path) in denied {
+            assert!(
+                matches!(claims.check(&method, path), Err(AuthError::ClaimsDenied(_))),
+                "{} {}",
+                method,
+                path
+            );
+        }
+
+        // Raw bytes only when listed
+        let claims = AccessClaims::for_slides("s?.svs");
+        assert!(claims.check(&get, "/slides/s1.svs/raw").is_err());
+        let claims = AccessClaims::for_slides("s?.svs").with_endpoints(vec![ClaimEndpoint::Raw]);
+        assert!(claims.check(&get, "/slides/s1.svs/raw").is_ok());
+        assert!(claims.check(&get, "/slides/s1.svs").is_err());
+        assert!(claims.check(&get, "/slides/s10.svs/raw").is_err());
     }
 
     #[test]
@@ -935,5 +1900,12 @@ mod tests {
         assert_eq!(extract_slide_id_from_path("/view/sample.svs"), None);
         assert_eq!(extract_slide_id_from_path("/"), None);
         assert_eq!(extract_slide_id_from_path(""), None);
+
+        // Traversal segments are never treated as a slide
+        assert_eq!(
+            extract_slide_id_from_path("/slides/lab-a%2F..%2Fs1.svs"),
+            None
+        );
+        assert_eq!(extract_slide_id_from_path("/dzi/..%2Fs1.svs.dzi"), None);
     }
 }
@@ -0,0 +1,514 @@
+//! Per-connection bandwidth shaping.
+//!
+//! Response bodies are paced through a token bucket shared by every request
+//! on the same client connection, so a bulk export client can't saturate an
+//! uplink that interactive viewers also depend on. Buckets refill at the
+//! configured rate and hold up to one second of burst, which lets small
+//! responses such as viewer tiles go out immediately while long downloads
+//! settle at the limit.
+//!
+//! The rate comes from a global default, optionally overridden per API key
+//! named by a request header. A key limit of 0 exempts that key.
+//!
+//! Connections are identified by peer address, which requires the server
+//! to be run with connect info. Without it, each request is shaped on its
+//! own.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{ready, Context, Poll};
+use std::time::{Duration, Instant};
+
+use axum::{
+    body::Body,
+    extract::{ConnectInfo, Request, State},
+    http::HeaderMap,
+    middleware::Next,
+    response::Response,
+};
+use bytes::Bytes;
+use http_body::{Body as HttpBody, Frame, SizeHint};
+use tokio::time::Sleep;
+
+// =============================================================================
+// Configuration
+// =============================================================================
+
+/// Largest slice of a response body sent per token reservation.
+///
+/// Splitting large frames keeps pacing smooth instead of sending a whole
+/// chunk after one long pause.
+pub const THROTTLE_CHUNK_SIZE: usize = 16 * 1024;
+
+/// Time after which an unused connection bucket may be discarded.
+const BUCKET_IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Number of tracked connections above which idle buckets are pruned.
+const BUCKET_PRUNE_THRESHOLD: usize = 1024;
+
+/// Bandwidth limits for response bodies.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BandwidthConfig {
+    /// Bytes per second allowed per connection (0 = unlimited)
+    pub default_rate: u64,
+
+    /// Request header carrying the API key used to select a per-key limit
+    pub key_header: Option<String>,
+
+    /// Per-key limits in bytes per second (0 = unlimited)
+    pub key_rates: HashMap<String, u64>,
+}
+
+impl BandwidthConfig {
+    /// Create a configuration limiting every connection to `bytes_per_sec`.
+    pub fn new(bytes_per_sec: u64) -> Self {
+        Self {
+            default_rate: bytes_per_sec,
+            ..Self::default()
+        }
+    }
+
+    /// Select per-key limits by the value of a request header.
+    pub fn with_key_header(mut self, header: impl Into<String>) -> Self {
+        self.key_header = Some(header.into());
+        self
+    }
+
+    /// Set the limit for connections presenting the given API key.
+    pub fn with_key_rate(mut self, key: impl Into<String>, bytes_per_sec: u64) -> Self {
+        self.key_rates.insert(key.into(), bytes_per_sec);
+        self
+    }
+
+    /// Whether any response could be throttled.
+    pub fn is_enabled(&self) -> bool {
+        self.default_rate > 0
+            || (self.key_header.is_some() && self.key_rates.values().any(|&rate| rate > 0))
+    }
+
+    /// Parse a per-key limit of the form `key=bytes_per_sec`.
+    pub fn parse_key_rate(spec: &str) -> Result<(String, u64), String> {
+        let invalid = || {
+            format!(
+                "Invalid bandwidth limit '{}'. Expected key=bytes_per_sec",
+                spec
+            )
+        };
+        let (key, rate) = spec.split_once('=').ok_or_else(invalid)?;
+        let key = key.trim();
+        if key.is_empty() {
+            return Err(invalid());
+        }
+        let rate = rate.trim().parse().map_err(|_| invalid())?;
+        Ok((key.to_string(), rate))
+    }
+}
+
+// =============================================================================
+// Token Bucket
+// =============================================================================
+
+/// Token bucket pacing the bytes sent on one connection.
+///
+/// Reservations may overdraw the bucket; the caller waits until the debt
+/// would have been refilled. Concurrent responses on the same connection
+/// therefore share the rate rather than each getting their own.
+#[derive(Debug)]
+pub struct TokenBucket {
+    rate: u64,
+    burst: u64,
+    state: Mutex<BucketState>,
+}
+
+#[derive(Debug)]
+struct BucketState {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    /// Create a full bucket refilling at `bytes_per_sec`, holding one second
+    /// of burst.
+    pub fn new(bytes_per_sec: u64) -> Self {
+        Self::with_burst(bytes_per_sec, bytes_per_sec)
+    }
+
+    /// Create a full bucket refilling at `rate` tokens per second and
+    /// holding at most `burst` tokens.
+    pub fn with_burst(rate: u64, burst: u64) -> Self {
+        Self {
+            rate: rate.max(1),
+            burst: burst.max(1),
+            state: Mutex::new(BucketState {
+                tokens: burst.max(1) as f64,
+                updated: Instant::now(),
+            }),
+        }
+    }
+
+    /// Refill rate in bytes per second.
+    pub fn rate(&self) -> u64 {
+        self.rate
+    }
+
+    /// Reserve `bytes` and return how long to wait before sending them.
+    pub fn reserve(&self, bytes: usize) -> Duration {
+        let rate = self.rate as f64;
+        let mut state = self.refill();
+        state.tokens -= bytes as f64;
+
+        if state.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-state.tokens / rate)
+        }
+    }
+
+    /// Take `tokens` if the bucket holds them, or return how long until it
+    /// will. Unlike [`reserve`](Self::reserve), a refused request doesn't
+    /// overdraw the bucket.
+    pub fn try_acquire(&self, tokens: u64) -> Result<(), Duration> {
+        let mut state = self.refill();
+        let missing = tokens as f64 - state.tokens;
+        if missing <= 0.0 {
+            state.tokens -= tokens as f64;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64(missing / self.rate as f64))
+        }
+    }
+
+    /// Add the tokens accrued since the last update, up to the burst.
+    fn refill(&self) -> std::sync::MutexGuard<'_, BucketState> {
+        let mut state = self.state.lock().unwrap();
+        let now = Instant::now();
+        let refill = now.duration_since(state.updated).as_secs_f64() * self.rate as f64;
+        state.updated = now;
+        state.tokens = (state.tokens + refill).min(self.burst as f64);
+        state
+    }
+
+    pub(super) fn idle_for(&self, now: Instant) -> Duration {
+        now.saturating_duration_since(self.state.lock().unwrap().updated)
+    }
+}
+
+// =============================================================================
+// Limiter
+// =============================================================================
+
+/// Assigns token buckets to connections according to a [`BandwidthConfig`].
+#[derive(Debug)]
+pub struct BandwidthLimiter {
+    config: BandwidthConfig,
+    connections: Mutex<HashMap<SocketAddr, Arc<TokenBucket>>>,
+}
+
+impl BandwidthLimiter {
+    /// Create a limiter with the given configuration.
+    pub fn new(config: BandwidthConfig) -> Self {
+        Self {
+            config,
+            connections: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Bytes per second allowed for a request (0 = unlimited).
+    pub fn rate_for(&self, headers: &HeaderMap) -> u64 {
+        self.config
+            .key_header
+            .as_ref()
+            .and_then(|header| headers.get(header.as_str()))
+            .and_then(|value| value.to_str().ok())
+            .and_then(|key| self.config.key_rates.get(key.trim()))
+            .copied()
+            .unwrap_or(self.config.default_rate)
+    }
+
+    /// Bucket for a connection, created on first use.
+    ///
+    /// Requests without a peer address get a bucket of their own.
+    pub fn bucket(&self, peer: Option<SocketAddr>, rate: u64) -> Arc<TokenBucket> {
+        let Some(peer) = peer else {
+            return Arc::new(TokenBucket::new(rate));
+        };
+
+        let mut connections = self.connections.lock().unwrap();
+        if let Some(bucket) = connections.get(&peer) {
+            if bucket.rate() == rate.max(1) {
+                return bucket.clone();
+            }
+        }
+
+        if connections.len() >= BUCKET_PRUNE_THRESHOLD {
+            let now = Instant::now();
+            connections.retain(|_, bucket| bucket.idle_for(now) < BUCKET_IDLE_TIMEOUT);
+        }
+        let bucket = Arc::new(TokenBucket::new(rate));
+        connections.insert(peer, bucket.clone());
+        bucket
+    }
+
+    /// Number of connections currently tracked.
+    pub fn tracked_connections(&self) -> usize {
+        self.connections.lock().unwrap().len()
+    }
+}
+
+/// Middleware pacing response bodies through the connection's bucket.
+pub async fn bandwidth_middleware(
+    State(limiter): State<Arc<BandwidthLimiter>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let rate = limiter.rate_for(request.headers());
+    if rate == 0 {
+        return next.run(request).await;
+    }
+
+    let peer = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|info| info.0);
+    let bucket = limiter.bucket(peer, rate);
+
+    next.run(request)
+        .await
+        .map(|body| Body::new(ThrottledBody::new(body, bucket)))
+}
+
+// =============================================================================
+// Throttled Body
+// =============================================================================
+
+/// Response body whose data frames are released at the bucket's rate.
+pub struct ThrottledBody {
+    inner: Body,
+    bucket: Arc<TokenBucket>,
+    /// Slice held back until `sleep` elapses
+    ready: Option<Bytes>,
+    /// Rest of a split frame not yet reserved
+    remainder: Option<Bytes>,
+    sleep: Option<Pin<Box<Sleep>>>,
+}
+
+impl ThrottledBody {
+    /// Wrap a body so it is sent no faster than the bucket allows.
+    pub fn new(inner: Body, bucket: Arc<TokenBucket>) -> Self {
+        Self {
+            inner,
+            bucket,
+            ready: None,
+            remainder: None,
+            sleep: None,
+        }
+    }
+
+    fn buffered(&self) -> u64 {
+        let ready = self.ready.as_ref().map_or(0, Bytes::len);
+        let remainder = self.remainder.as_ref().map_or(0, Bytes::len);
+        (ready + remainder) as u64
+    }
+}
+
+impl HttpBody for ThrottledBody {
+    type Data = Bytes;
+    type Error = axum::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Bytes>, axum::Error>>> {
+        let this = self.get_mut();
+
+        if let Some(sleep) = this.sleep.as_mut() {
+            ready!(sleep.as_mut().poll(cx));
+            this.sleep = None;
+            if let Some(data) = this.ready.take() {
+                return Poll::Ready(Some(Ok(Frame::data(data))));
+            }
+        }
+
+        let mut data = match this.remainder.take() {
+            Some(data) => data,
+            None => match ready!(Pin::new(&mut this.inner).poll_frame(cx)) {
+                Some(Ok(frame)) => match frame.into_data() {
+                    Ok(data) => data,
+                    Err(frame) => return Poll::Ready(Some(Ok(frame))),
+                },
+                other => return Poll::Ready(other),
+            },
+        };
+        if data.len() > THROTTLE_CHUNK_SIZE {
+            this.remainder = Some(data.split_off(THROTTLE_CHUNK_SIZE));
+        }
+
+        let delay = this.bucket.reserve(data.len());
+        if delay.is_zero() {
+            return Poll::Ready(Some(Ok(Frame::data(data))));
+        }
+
+        let mut sleep = Box::pin(tokio::time::sleep(delay));
+        match sleep.as_mut().poll(cx) {
+            Poll::Ready(()) => Poll::Ready(Some(Ok(Frame::data(data)))),
+            Poll::Pending => {
+                this.ready = Some(data);
+                this.sleep = Some(sleep);
+                Poll::Pending
+            }
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.buffered() == 0 && self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        let buffered = self.buffered();
+        let inner = self.inner.size_hint();
+        match inner.exact() {
+            Some(exact) => SizeHint::with_exact(exact + buffered),
+            None => {
+                let mut hint = SizeHint::new();
+                hint.set_lower(inner.lower() + buffered);
+                if let Some(upper) = inner.upper() {
+                    hint.set_upper(upper + buffered);
+                }
+                hint
+            }
+        }
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+    use http_body_util::BodyExt;
+
+    fn peer(port: u16) -> Option<SocketAddr> {
+        Some(SocketAddr::from(([10, 0, 0, 1], port)))
+    }
+
+    #[test]
+    fn test_parse_key_rate() {
+        assert_eq!(
+            BandwidthConfig::parse_key_rate("export=1048576").unwrap(),
+            ("export".to_string(), 1_048_576)
+        );
+        assert_eq!(
+            BandwidthConfig::parse_key_rate(" viewer = 0 ").unwrap(),
+            ("viewer".to_string(), 0)
+        );
+        assert!(BandwidthConfig::parse_key_rate("export").is_err());
+        assert!(BandwidthConfig::parse_key_rate("=100").is_err());
+        assert!(BandwidthConfig::parse_key_rate("export=fast").is_err());
+    }
+
+    #[test]
+    fn test_is_enabled() {
+        assert!(!BandwidthConfig::default().is_enabled());
+        assert!(BandwidthConfig::new(1000).is_enabled());
+        assert!(!BandwidthConfig::default()
+            .with_key_rate("export", 1000)
+            .is_enabled());
+        assert!(BandwidthConfig::default()
+            .with_key_header("X-Api-Key")
+            .with_key_rate("export", 1000)
+            .is_enabled());
+    }
+
+    #[test]
+    fn test_bucket_allows_burst_then_waits() {
+        let bucket = TokenBucket::new(1000);
+        assert_eq!(bucket.reserve(1000), Duration::ZERO);
+
+        let wait = bucket.reserve(500);
+        assert!(wait > Duration::from_millis(400), "waited {:?}", wait);
+        assert!(wait <= Duration::from_millis(500));
+    }
+
+    #[test]
+    fn test_try_acquire_does_not_overdraw() {
+        let bucket = TokenBucket::with_burst(10, 2);
+        assert!(bucket.try_acquire(1).is_ok());
+        assert!(bucket.try_acquire(1).is_ok());
+
+        let wait = bucket.try_acquire(1).unwrap_err();
+        assert!(wait > Duration::from_millis(50), "waited {:?}", wait);
+        assert!(wait <= Duration::from_millis(100));
+
+        // Refused requests leave the bucket as it was
+        assert!(bucket.try_acquire(1).unwrap_err() <= wait);
+    }
+
+    #[test]
+    fn test_rate_for_key() {
+        let limiter = BandwidthLimiter::new(
+            BandwidthConfig::new(1000)
+                .with_key_header("X-Api-Key")
+                .with_key_rate("export", 200)
+                .with_key_rate("viewer", 0),
+        );
+
+        let mut headers = HeaderMap::new();
+        assert_eq!(limiter.rate_for(&headers), 1000);
+
+        headers.insert("X-Api-Key", HeaderValue::from_static("export"));
+        assert_eq!(limiter.rate_for(&headers), 200);
+
+        headers.insert("X-Api-Key", HeaderValue::from_static("viewer"));
+        assert_eq!(limiter.rate_for(&headers), 0);
+
+        headers.insert("X-Api-Key", HeaderValue::from_static("unknown"));
+        assert_eq!(limiter.rate_for(&headers), 1000);
+    }
+
+    #[test]
+    fn test_connection_shares_bucket() {
+        let limiter = BandwidthLimiter::new(BandwidthConfig::new(1000));
+
+        let a = limiter.bucket(peer(1), 1000);
+        let b = limiter.bucket(peer(1), 1000);
+        let c = limiter.bucket(peer(2), 1000);
+        assert!(Arc::ptr_eq(&a, &b));
+        assert!(!Arc::ptr_eq(&a, &c));
+
+        // A different rate on the same connection replaces the bucket
+        let d = limiter.bucket(peer(1), 500);
+        assert!(!Arc::ptr_eq(&a, &d));
+        assert_eq!(limiter.tracked_connections(), 2);
+
+        // Requests without connect info are not tracked
+        let e = limiter.bucket(None, 1000);
+        let f = limiter.bucket(None, 1000);
+        assert!(!Arc::ptr_eq(&e, &f));
+        assert_eq!(limiter.tracked_connections(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_throttled_body_paces_data() {
+        // One second of burst covers ten chunks; the last two must wait
+        let rate = 10 * THROTTLE_CHUNK_SIZE as u64;
+        let payload = vec![7u8; 12 * THROTTLE_CHUNK_SIZE];
+        let body = ThrottledBody::new(
+            Body::from(payload.clone()),
+            Arc::new(TokenBucket::new(rate)),
+        );
+        assert_eq!(body.size_hint().exact(), Some(payload.len() as u64));
+
+        let start = Instant::now();
+        let data = body.collect().await.unwrap().to_bytes();
+        assert_eq!(data.as_ref(), payload.as_slice());
+
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(150), "took {:?}", elapsed);
+    }
+}
@@ -0,0 +1,117 @@
+//! Response compression for JSON endpoints.
+//!
+//! Slide metadata, listings and error bodies are JSON and shrink several
+//! times under gzip, while tiles, thumbnails and raw slide bytes are already
+//! compressed (or served as byte ranges) and only cost CPU to compress
+//! again. [`compression_layer`] therefore compresses JSON responses only,
+//! for clients that send `Accept-Encoding: gzip`.
+
+use http::header::CONTENT_TYPE;
+use http::Response;
+use http_body::Body;
+use tower_http::compression::predicate::{And, Predicate, SizeAbove};
+use tower_http::compression::CompressionLayer;
+
+/// Smallest response body worth compressing, in bytes.
+pub const COMPRESSION_MIN_SIZE: u16 = 256;
+
+/// Predicate selecting JSON responses (`application/json` and `+json` types).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonResponses;
+
+impl Predicate for JsonResponses {
+    fn should_compress<B>(&self, response: &Response<B>) -> bool
+    where
+        B: Body,
+    {
+        response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(is_json)
+    }
+}
+
+/// Whether a content type is JSON, ignoring parameters.
+fn is_json(content_type: &str) -> bool {
+    let essence = content_type
+        .split(';')
+        .next()
+        .unwrap_or("")
+        .trim()
+        .to_ascii_lowercase();
+    essence == "application/json"
+        || (essence.starts_with("application/") && essence.ends_with("+json"))
+}
+
+/// Build the layer gzip-compressing JSON responses of at least
+/// [`COMPRESSION_MIN_SIZE`] bytes.
+pub fn compression_layer() -> CompressionLayer<And<JsonResponses, SizeAbove>> {
+    CompressionLayer::new()
+        .gzip(true)
+        .compress_when(JsonResponses.and(SizeAbove::new(COMPRESSION_MIN_SIZE)))
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::routing::get;
+    use axum::Router;
+    use http::header::{ACCEPT_ENCODING, CONTENT_ENCODING};
+    use http::Request;
+    use tower::ServiceExt;
+
+    fn app() -> Router {
+        let json = format!("{{\"slides\":[{}]}}", vec!["\"slide.svs\""; 100].join(","));
+        Router::new()
+            .route(
+                "/json",
+                get(move || async move { ([(CONTENT_TYPE, "application/json")], json) }),
+            )
+            .route(
+                "/small",
+                get(|| async { ([(CONTENT_TYPE, "application/json")], "{}") }),
+            )
+            .route(
+                "/tile",
+                get(|| async { ([(CONTENT_TYPE, "image/jpeg")], vec![0u8; 4096]) }),
+            )
+            .layer(compression_layer())
+    }
+
+    async fn content_encoding(uri: &str) -> Option<String> {
+        let request = Request::builder()
+            .uri(uri)
+            .header(ACCEPT_ENCODING, "gzip")
+            .body(Body::empty())
+            .unwrap();
+        let response = app().oneshot(request).await.unwrap();
+        response
+            .headers()
+            .get(CONTENT_ENCODING)
+            .map(|v| v.to_str().unwrap().to_string())
+    }
+
+    #[test]
+    fn test_is_json() {
+        assert!(is_json("application/json"));
+        assert!(is_json("application/json; charset=utf-8"));
+        assert!(is_json(
+            "application/ld+json;profile=\"http://iiif.io/api/image/3/context.json\""
+        ));
+        assert!(!is_json("image/jpeg"));
+        assert!(!is_json("text/html"));
+    }
+
+    #[tokio::test]
+    async fn test_only_json_is_compressed() {
+        assert_eq!(content_encoding("/json").await.as_deref(), Some("gzip"));
+        assert_eq!(content_encoding("/small").await, None);
+        assert_eq!(content_encoding("/tile").await, None);
+    }
+}
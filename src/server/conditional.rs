@@ -0,0 +1,304 @@
+//! Conditional requests for tiles.
+//!
+//! Tiles carry a strong ETag derived from everything that determines their
+//! bytes: the slide key, the storage ETag of the slide file, the tile
+//! coordinates and the encoding parameters. A viewer reloading a slide sends
+//! the ETags it already holds in `If-None-Match` and gets `304 Not Modified`
+//! without the tile being read or re-encoded.
+//!
+//! `If-Modified-Since` is honored against the slide file's modification time
+//! when the request has no `If-None-Match`, as RFC 9110 requires.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use axum::http::{header, HeaderMap};
+use sha2::{Digest, Sha256};
+
+use crate::date::{civil_from_days, days_from_civil};
+
+// =============================================================================
+// Tile ETags
+// =============================================================================
+
+/// Inputs identifying the exact bytes of a rendered tile.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TileValidator<'a> {
+    /// Slide key
+    pub slide_id: &'a str,
+
+    /// Storage ETag of the slide file, if the backend reports one
+    pub source_etag: Option<&'a str>,
+
+    /// Size of the slide file, used with the ETag (or alone without one)
+    pub source_size: u64,
+
+    /// Pyramid level
+    pub level: usize,
+
+    /// Tile X coordinate
+    pub x: u32,
+
+    /// Tile Y coordinate
+    pub y: u32,
+
+    /// Encoding quality
+    pub quality: u8,
+
+    /// Output format name
+    pub format: &'a str,
+
+    /// Server settings that change rendered tiles (edge cropping, retiling)
+    pub render_settings: &'a str,
+}
+
+impl TileValidator<'_> {
+    /// Strong ETag for the tile, quoted.
+    ///
+    /// The server version is included so tiles are revalidated after an
+    /// upgrade that may change encoding.
+    pub fn etag(&self) -> String {
+        let mut hasher = Sha256::new();
+        for part in [
+            env!("CARGO_PKG_VERSION"),
+            self.slide_id,
+            self.source_etag.unwrap_or(""),
+            &self.source_size.to_string(),
+            &self.level.to_string(),
+            &self.x.to_string(),
+            &self.y.to_string(),
+            &self.quality.to_string(),
+            self.format,
+            self.render_settings,
+        ] {
+            // Length-prefixed so adjacent fields can't run together
+            hasher.update((part.len() as u64).to_le_bytes());
+            hasher.update(part.as_bytes());
+        }
+        let digest = hasher.finalize();
+        format!("\"{}\"", hex::encode(&digest[..16]))
+    }
+}
+
+// =============================================================================
+// Request Evaluation
+// =============================================================================
+
+/// Whether a request's conditional headers allow a `304 Not Modified`.
+///
+/// `If-None-Match` takes precedence; `If-Modified-Since` is only used when
+/// it is absent and the resource has a known modification time.
+pub fn is_not_modified(headers: &HeaderMap, etag: &str, last_modified: Option<SystemTime>) -> bool {
+    if let Some(if_none_match) = headers.get(header::IF_NONE_MATCH) {
+        return if_none_match
+            .to_str()
+            .map(|value| etag_list_matches(value, etag))
+            .unwrap_or(false);
+    }
+
+    match (headers.get(header::IF_MODIFIED_SINCE), last_modified) {
+        (Some(since), Some(modified)) => since
+            .to_str()
+            .ok()
+            .and_then(parse_http_date)
+            .map(|since| truncate_to_secs(modified) <= since)
+            .unwrap_or(false),
+        _ => false,
+    }
+}
+
+/// Whether an `If-None-Match` value matches an ETag.
+///
+/// Uses weak comparison, so `W/"abc"` matches `"abc"`.
+fn etag_list_matches(value: &str, etag: &str) -> bool {
+    let etag = etag.trim_start_matches("W/");
+    value
+        .split(',')
+        .map(str::trim)
+        .any(|candidate| candidate == "*" || candidate.trim_start_matches("W/") == etag)
+}
+
+/// Drop sub-second precision, which HTTP dates can't carry.
+pub(super) fn truncate_to_secs(time: SystemTime) -> SystemTime {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    UNIX_EPOCH + Duration::from_secs(secs)
+}
+
+// =============================================================================
+// HTTP Dates
+// =============================================================================
+
+const WEEKDAYS: [&str; 7] = ["Thu", "Fri", "Sat", "Sun", "Mon", "Tue", "Wed"];
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// Format a time as an IMF-fixdate, e.g. `Sun, 06 Nov 1994 08:49:37 GMT`.
+pub fn format_http_date(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let days = (secs / 86_400) as i64;
+    let (year, month, day) = civil_from_days(days);
+    let rem = secs % 86_400;
+
+    format!(
+        "{}, {:02} {} {} {:02}:{:02}:{:02} GMT",
+        WEEKDAYS[(days % 7) as usize],
+        day,
+        MONTHS[(month - 1) as usize],
+        year,
+        rem / 3600,
+        (rem / 60) % 60,
+        rem % 60
+    )
+}
+
+/// Parse an IMF-fixdate, e.g. `Sun, 06 Nov 1994 08:49:37 GMT`.
+///
+/// The obsolete RFC 850 and asctime formats are not accepted; a date that
+/// can't be parsed makes `If-Modified-Since` ignored, as RFC 9110 requires.
+pub fn parse_http_date(value: &str) -> Option<SystemTime> {
+    let (_, rest) = value.split_once(", ")?;
+    let mut parts = rest.split(' ');
+    let day: u32 = parts.next()?.parse().ok()?;
+    let month_name = parts.next()?;
+    let month = MONTHS.iter().position(|m| *m == month_name)? as u32 + 1;
+    let year: i64 = parts.next()?.parse().ok()?;
+    let mut clock = parts.next()?.split(':');
+    let hour: u64 = clock.next()?.parse().ok()?;
+    let minute: u64 = clock.next()?.parse().ok()?;
+    let second: u64 = clock.next()?.parse().ok()?;
+    if parts.next()? != "GMT" || hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+
+    let days = days_from_civil(year, month, day);
+    if days < 0 {
+        return None;
+    }
+    let secs = days as u64 * 86_400 + hour * 3600 + minute * 60 + second;
+    Some(UNIX_EPOCH + Duration::from_secs(secs))
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn validator() -> TileValidator<'static> {
+        TileValidator {
+            slide_id: "slide.svs",
+            source_etag: Some("\"d41d8cd98f00b204e9800998ecf8427e\""),
+            source_size: 1024,
+            level: 0,
+            x: 1,
+            y: 2,
+            quality: 80,
+            format: "jpeg",
+            render_settings: "",
+        }
+    }
+
+    #[test]
+    fn test_etag_changes_with_inputs() {
+        let etag = validator().etag();
+        assert!(etag.starts_with('"') && etag.ends_with('"'));
+        assert_eq!(etag, validator().etag());
+
+        let variants = [
+            TileValidator {
+                source_etag: Some("\"other\""),
+                ..validator()
+            },
+            TileValidator {
+                x: 2,
+                ..validator()
+            },
+            TileValidator {
+                quality: 90,
+                ..validator()
+            },
+            TileValidator {
+                format: "png",
+                ..validator()
+            },
+            TileValidator {
+                render_settings: "retile=512",
+                ..validator()
+            },
+        ];
+        for variant in variants {
+            assert_ne!(variant.etag(), etag);
+        }
+    }
+
+    #[test]
+    fn test_if_none_match() {
+        let etag = validator().etag();
+        let mut headers = HeaderMap::new();
+        assert!(!is_not_modified(&headers, &etag, None));
+
+        headers.insert(header::IF_NONE_MATCH, HeaderValue::from_str(&etag).unwrap());
+        assert!(is_not_modified(&headers, &etag, None));
+
+        let list = format!("\"stale\", W/{}", etag);
+        headers.insert(header::IF_NONE_MATCH, HeaderValue::from_str(&list).unwrap());
+        assert!(is_not_modified(&headers, &etag, None));
+
+        headers.insert(header::IF_NONE_MATCH, HeaderValue::from_static("*"));
+        assert!(is_not_modified(&headers, &etag, None));
+
+        headers.insert(header::IF_NONE_MATCH, HeaderValue::from_static("\"stale\""));
+        assert!(!is_not_modified(&headers, &etag, None));
+    }
+
+    #[test]
+    fn test_if_modified_since() {
+        let modified = UNIX_EPOCH + Duration::from_millis(784_111_777_500);
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::IF_MODIFIED_SINCE,
+            HeaderValue::from_static("Sun, 06 Nov 1994 08:49:37 GMT"),
+        );
+        assert!(is_not_modified(&headers, "\"x\"", Some(modified)));
+        assert!(!is_not_modified(
+            &headers,
+            "\"x\"",
+            Some(modified + Duration::from_secs(1))
+        ));
+        // Unknown modification time
+        assert!(!is_not_modified(&headers, "\"x\"", None));
+
+        // If-None-Match takes precedence
+        headers.insert(header::IF_NONE_MATCH, HeaderValue::from_static("\"stale\""));
+        assert!(!is_not_modified(&headers, "\"x\"", Some(modified)));
+    }
+
+    #[test]
+    fn test_http_dates() {
+        let time = UNIX_EPOCH + Duration::from_secs(784_111_777);
+        assert_eq!(format_http_date(time), "Sun, 06 Nov 1994 08:49:37 GMT");
+        assert_eq!(parse_http_date("Sun, 06 Nov 1994 08:49:37 GMT"), Some(time));
+        assert_eq!(
+            format_http_date(UNIX_EPOCH),
+            "Thu, 01 Jan 1970 00:00:00 GMT"
+        );
+        assert_eq!(
+            format_http_date(UNIX_EPOCH + Duration::from_secs(951_782_400)),
+            "Tue, 29 Feb 2000 00:00:00 GMT"
+        );
+
+        assert_eq!(parse_http_date("Sunday, 06-Nov-94 08:49:37 GMT"), None);
+        assert_eq!(parse_http_date("Sun Nov  6 08:49:37 1994"), None);
+        assert_eq!(parse_http_date("Sun, 06 Nov 1994 08:49:37 PST"), None);
+    }
+}
@@ -0,0 +1,350 @@
+//! Client connection tracking.
+//!
+//! Every accepted TCP connection is registered with a tracker for as long as
+//! the server holds it open, along with the number of requests it has
+//! carried. This makes keep-alive behaviour visible from inside the process:
+//! a load balancer that opens a fresh connection per request, or one that
+//! pins all traffic to a handful of long-lived connections, shows up directly
+//! in `/admin/connections` and the connection gauges on `/metrics`.
+//!
+//! Connections are registered through [`TrackedConnection`], used as the
+//! server's connect info. [`connection_middleware`] counts requests against
+//! the connection and exposes the peer address as `ConnectInfo<SocketAddr>`
+//! for layers that only need the address, such as bandwidth shaping. On
+//! connections the server terminated TLS for, it also sets
+//! `X-Forwarded-Proto: https` so absolute URLs in responses use `https`.
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use axum::{
+    extract::{connect_info::Connected, ConnectInfo, Request},
+    http::{HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+    serve::IncomingStream,
+};
+use tokio::net::TcpListener;
+
+// =============================================================================
+// Tracker
+// =============================================================================
+
+/// Snapshot of one open connection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConnectionSnapshot {
+    /// Tracker-assigned connection ID
+    pub id: u64,
+
+    /// Peer address
+    pub peer: SocketAddr,
+
+    /// Time since the connection was accepted
+    pub age: Duration,
+
+    /// Time since the last request started (or since accept if none)
+    pub idle: Duration,
+
+    /// Requests received on this connection
+    pub requests: u64,
+
+    /// Requests currently being handled
+    pub in_flight: u64,
+}
+
+/// State of an open connection.
+#[derive(Debug)]
+struct ConnectionEntry {
+    peer: SocketAddr,
+    opened_at: Instant,
+    last_request_at: Instant,
+    requests: u64,
+    in_flight: u64,
+}
+
+#[derive(Debug, Default)]
+struct TrackerInner {
+    next_id: AtomicU64,
+    opened: AtomicU64,
+    connections: Mutex<HashMap<u64, ConnectionEntry>>,
+}
+
+/// Registry of open client connections.
+///
+/// Cloning shares the registry. The server registers connections with
+/// [`ConnectionTracker::global`], since connect info is created without
+/// access to application state.
+#[derive(Debug, Clone, Default)]
+pub struct ConnectionTracker {
+    inner: Arc<TrackerInner>,
+}
+
+impl ConnectionTracker {
+    /// Create an empty tracker.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The process-wide tracker used by the server.
+    pub fn global() -> &'static ConnectionTracker {
+        static GLOBAL: OnceLock<ConnectionTracker> = OnceLock::new();
+        GLOBAL.get_or_init(ConnectionTracker::new)
+    }
+
+    /// Register a newly accepted connection.
+    ///
+    /// The connection stays registered until every clone of the returned
+    /// handle is dropped.
+    pub fn open(&self, peer: SocketAddr) -> TrackedConnection {
+        let id = self.inner.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let now = Instant::now();
+        self.inner.connections.lock().unwrap().insert(
+            id,
+            ConnectionEntry {
+                peer,
+                opened_at: now,
+                last_request_at: now,
+                requests: 0,
+                in_flight: 0,
+            },
+        );
+        self.inner.opened.fetch_add(1, Ordering::Relaxed);
+
+        TrackedConnection {
+            peer,
+            tls: false,
+            handle: Arc::new(ConnectionHandle {
+                id,
+                tracker: Arc::clone(&self.inner),
+            }),
+        }
+    }
+
+    /// Number of connections currently open.
+    pub fn open_connections(&self) -> usize {
+        self.inner.connections.lock().unwrap().len()
+    }
+
+    /// Number of connections accepted since startup.
+    pub fn opened_total(&self) -> u64 {
+        self.inner.opened.load(Ordering::Relaxed)
+    }
+
+    /// Snapshot of every open connection, oldest first.
+    pub fn snapshot(&self) -> Vec<ConnectionSnapshot> {
+        let now = Instant::now();
+        let mut connections: Vec<ConnectionSnapshot> = self
+            .inner
+            .connections
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(&id, entry)| ConnectionSnapshot {
+                id,
+                peer: entry.peer,
+                age: now.duration_since(entry.opened_at),
+                idle: now.duration_since(entry.last_request_at),
+                requests: entry.requests,
+                in_flight: entry.in_flight,
+            })
+            .collect();
+        connections.sort_by_key(|c| c.id);
+        connections
+    }
+}
+
+// =============================================================================
+// Connection Handle
+// =============================================================================
+
+/// Registration of one connection, removed from the tracker on drop.
+#[derive(Debug)]
+struct ConnectionHandle {
+    id: u64,
+    tracker: Arc<TrackerInner>,
+}
+
+impl Drop for ConnectionHandle {
+    fn drop(&mut self) {
+        self.tracker.connections.lock().unwrap().remove(&self.id);
+    }
+}
+
+/// Connect info for a tracked connection.
+///
+/// The server keeps one clone for the lifetime of the connection and each
+/// request carries another, so the connection is unregistered once it is
+/// closed and its last request has finished.
+#[derive(Debug, Clone)]
+pub struct TrackedConnection {
+    peer: SocketAddr,
+    tls: bool,
+    handle: Arc<ConnectionHandle>,
+}
+
+impl TrackedConnection {
+    /// Tracker-assigned connection ID.
+    pub fn id(&self) -> u64 {
+        self.handle.id
+    }
+
+    /// Peer address.
+    pub fn peer(&self) -> SocketAddr {
+        self.peer
+    }
+
+    /// Mark the connection as terminated by the server's TLS listener.
+    pub fn with_tls(mut self) -> Self {
+        self.tls = true;
+        self
+    }
+
+    /// Whether the server terminated TLS for this connection.
+    pub fn is_tls(&self) -> bool {
+        self.tls
+    }
+
+    /// Record the start of a request; the request ends when the guard drops.
+    pub fn begin_request(&self) -> RequestGuard {
+        if let Some(entry) = self
+            .handle
+            .tracker
+            .connections
+            .lock()
+            .unwrap()
+            .get_mut(&self.handle.id)
+        {
+            entry.requests += 1;
+            entry.in_flight += 1;
+            entry.last_request_at = Instant::now();
+        }
+        RequestGuard {
+            handle: Arc::clone(&self.handle),
+        }
+    }
+}
+
+impl Connected<IncomingStream<'_, TcpListener>> for TrackedConnection {
+    fn connect_info(stream: IncomingStream<'_, TcpListener>) -> Self {
+        ConnectionTracker::global().open(*stream.remote_addr())
+    }
+}
+
+/// An in-flight request on a tracked connection.
+pub struct RequestGuard {
+    handle: Arc<ConnectionHandle>,
+}
+
+impl Drop for RequestGuard {
+    fn drop(&mut self) {
+        if let Some(entry) = self
+            .handle
+            .tracker
+            .connections
+            .lock()
+            .unwrap()
+            .get_mut(&self.handle.id)
+        {
+            entry.in_flight = entry.in_flight.saturating_sub(1);
+        }
+    }
+}
+
+// =============================================================================
+// Middleware
+// =============================================================================
+
+/// Middleware counting requests against their tracked connection.
+///
+/// Requests without [`TrackedConnection`] connect info (such as those sent
+/// to the router directly in tests) pass through untouched.
+pub async fn connection_middleware(mut request: Request, next: Next) -> Response {
+    let connection = request
+        .extensions()
+        .get::<ConnectInfo<TrackedConnection>>()
+        .map(|info| info.0.clone());
+
+    match connection {
+        Some(connection) => {
+            let _guard = connection.begin_request();
+            if connection.is_tls() {
+                request.headers_mut().insert(
+                    HeaderName::from_static("x-forwarded-proto"),
+                    HeaderValue::from_static("https"),
+                );
+            }
+            request
+                .extensions_mut()
+                .insert(ConnectInfo(connection.peer()));
+            next.run(request).await
+        }
+        None => next.run(request).await,
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn peer(port: u16) -> SocketAddr {
+        SocketAddr::from(([10, 0, 0, 1], port))
+    }
+
+    #[test]
+    fn test_connections_registered_until_dropped() {
+        let tracker = ConnectionTracker::new();
+        let a = tracker.open(peer(1000));
+        let b = tracker.open(peer(1001));
+        assert_eq!(tracker.open_connections(), 2);
+        assert_ne!(a.id(), b.id());
+
+        // Clones keep the connection open
+        let a_clone = a.clone();
+        drop(a);
+        assert_eq!(tracker.open_connections(), 2);
+
+        drop(a_clone);
+        assert_eq!(tracker.open_connections(), 1);
+        assert_eq!(tracker.snapshot()[0].peer, peer(1001));
+        assert_eq!(tracker.opened_total(), 2);
+    }
+
+    #[test]
+    fn test_request_counts() {
+        let tracker = ConnectionTracker::new();
+        let conn = tracker.open(peer(2000));
+
+        let first = conn.begin_request();
+        let second = conn.begin_request();
+        let snapshot = &tracker.snapshot()[0];
+        assert_eq!(snapshot.requests, 2);
+        assert_eq!(snapshot.in_flight, 2);
+
+        drop(first);
+        drop(second);
+        let snapshot = &tracker.snapshot()[0];
+        assert_eq!(snapshot.requests, 2);
+        assert_eq!(snapshot.in_flight, 0);
+    }
+
+    #[test]
+    fn test_in_flight_request_outlives_connection() {
+        let tracker = ConnectionTracker::new();
+        let conn = tracker.open(peer(3000));
+        let guard = conn.begin_request();
+
+        // The request still holds the registration
+        drop(conn);
+        assert_eq!(tracker.open_connections(), 1);
+
+        drop(guard);
+        assert_eq!(tracker.open_connections(), 0);
+    }
+}
@@ -14,6 +14,20 @@
 //! - Level N = lowest resolution
 //!
 //! This module handles the level mapping between these two conventions.
+//!
+//! # Endpoints
+//!
+//! ```text
+//! /dzi/{slide_id}.dzi                        - Descriptor
+//! /dzi/{slide_id}_files/{level}/{x}_{y}.jpg  - Tile
+//! ```
+//!
+//! Deep Zoom levels halve down to 1x1 pixel while WSI pyramids usually stop
+//! a few levels short and step by 4x. Each DZI tile is rendered from the
+//! closest pyramid level that doesn't need upsampling; levels below the
+//! smallest pyramid level are synthesized by downscaling it.
+
+use crate::tile::select_source_level;
 
 /// Generate DZI XML descriptor for a slide.
 ///
@@ -67,9 +81,10 @@ pub fn dzi_level_dimensions(
         return (0, 0);
     }
 
-    let scale = 1u32 << (max_dzi_level - dzi_level);
-    let level_width = width.div_ceil(scale);
-    let level_height = height.div_ceil(scale);
+    // Computed in u64: slides wider than 2^31 pixels have 32 levels to halve
+    let scale = 1u64 << (max_dzi_level - dzi_level).min(63);
+    let level_width = (width as u64).div_ceil(scale) as u32;
+    let level_height = (height as u64).div_ceil(scale) as u32;
 
     (level_width.max(1), level_height.max(1))
 }
@@ -81,7 +96,7 @@ pub fn dzi_level_downsample(dzi_level: usize, max_dzi_level: usize) -> f64 {
     if dzi_level > max_dzi_level {
         return 0.0;
     }
-    (1u64 << (max_dzi_level - dzi_level)) as f64
+    (1u64 << (max_dzi_level - dzi_level).min(63)) as f64
 }
 
 /// Find the best WSI level for a given DZI level.
@@ -100,17 +115,10 @@ pub fn find_best_wsi_level(
         return None;
     }
 
-    // Find the WSI level with the smallest downsample that is >= dzi_downsample
-    // (i.e., the highest resolution level that doesn't need upscaling)
-    let mut best_level = 0;
-    let mut best_downsample = wsi_level_downsamples[0];
-
-    for (level, &downsample) in wsi_level_downsamples.iter().enumerate() {
-        if downsample <= dzi_downsample && downsample >= best_downsample {
-            best_level = level;
-            best_downsample = downsample;
-        }
-    }
+    // Same selection as region rendering: the lowest-resolution level that
+    // doesn't need upscaling
+    let best_level = select_source_level(dzi_downsample, wsi_level_downsamples);
+    let best_downsample = wsi_level_downsamples[best_level];
 
     // Calculate additional scale factor needed
     let additional_scale = dzi_downsample / best_downsample;
@@ -147,6 +155,41 @@ pub fn dzi_tile_count(level_width: u32, level_height: u32, tile_size: u32) -> (u
     (tiles_x.max(1), tiles_y.max(1))
 }
 
+/// Pixel bounds of a DZI tile within its level, as `(x, y, width, height)`.
+///
+/// Edge tiles are cropped to the level, as Deep Zoom expects. Returns `None`
+/// for tiles outside the level.
+pub fn dzi_tile_bounds(
+    level_width: u32,
+    level_height: u32,
+    tile_size: u32,
+    x: u32,
+    y: u32,
+) -> Option<(u32, u32, u32, u32)> {
+    let left = x.checked_mul(tile_size)?;
+    let top = y.checked_mul(tile_size)?;
+    if left >= level_width || top >= level_height {
+        return None;
+    }
+
+    Some((
+        left,
+        top,
+        tile_size.min(level_width - left),
+        tile_size.min(level_height - top),
+    ))
+}
+
+/// Split a `{slide_id}.dzi` path segment into the slide ID.
+pub fn parse_dzi_descriptor_name(name: &str) -> Option<&str> {
+    name.strip_suffix(".dzi").filter(|id| !id.is_empty())
+}
+
+/// Split a `{slide_id}_files` path segment into the slide ID.
+pub fn parse_dzi_files_name(name: &str) -> Option<&str> {
+    name.strip_suffix("_files").filter(|id| !id.is_empty())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -230,6 +273,22 @@ mod tests {
         assert_eq!((w, h), (1, 1));
     }
 
+    #[test]
+    fn test_dzi_level_dimensions_deep_pyramid() {
+        // Wider than 2^31 pixels: 32 DZI levels below full resolution
+        let width = u32::MAX;
+        let max_level = calculate_max_dzi_level(width, 1000);
+        assert_eq!(max_level, 32);
+
+        assert_eq!(dzi_level_dimensions(width, 1000, 0, max_level), (1, 1));
+        assert_eq!(dzi_level_dimensions(width, 1000, 1, max_level), (2, 1));
+        assert_eq!(
+            dzi_level_dimensions(width, 1000, max_level, max_level),
+            (width, 1000)
+        );
+        assert_eq!(dzi_level_downsample(0, max_level), 4294967296.0);
+    }
+
     #[test]
     fn test_dzi_level_downsample() {
         let max_level = 10usize;
@@ -318,4 +377,34 @@ mod tests {
         // Downsample for invalid level
         assert_eq!(dzi_level_downsample(max_level + 1, max_level), 0.0);
     }
+
+    #[test]
+    fn test_dzi_tile_bounds() {
+        // 1000x600 level, 256 px tiles
+        assert_eq!(
+            dzi_tile_bounds(1000, 600, 256, 0, 0),
+            Some((0, 0, 256, 256))
+        );
+        assert_eq!(
+            dzi_tile_bounds(1000, 600, 256, 3, 2),
+            Some((768, 512, 232, 88))
+        );
+        assert_eq!(dzi_tile_bounds(1000, 600, 256, 4, 0), None);
+        assert_eq!(dzi_tile_bounds(1000, 600, 256, 0, 3), None);
+
+        // Synthesized small levels fit in a single tile
+        assert_eq!(dzi_tile_bounds(2, 1, 256, 0, 0), Some((0, 0, 2, 1)));
+    }
+
+    #[test]
+    fn test_parse_dzi_names() {
+        assert_eq!(
+            parse_dzi_descriptor_name("slide.svs.dzi"),
+            Some("slide.svs")
+        );
+        assert_eq!(parse_dzi_descriptor_name("slide.svs"), None);
+        assert_eq!(parse_dzi_descriptor_name(".dzi"), None);
+        assert_eq!(parse_dzi_files_name("slide.svs_files"), Some("slide.svs"));
+        assert_eq!(parse_dzi_files_name("slide.svs"), None);
+    }
 }
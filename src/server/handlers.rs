@@ -5,25 +5,79 @@
 //! # Endpoints
 //!
 //! - `GET /tiles/{slide_id}/{level}/{x}/{y}.jpg` - Serve a tile
-//! - `GET /health` - Health check endpoint
-
+//! - `GET /health`, `GET /livez` - Liveness check
+//! - `GET /readyz` - Readiness check with storage reachability
+//! - `GET /collections/{collection_id}/sprites` - Thumbnail sprite sheet for a collection
+//! - `POST /slides/{slide_id}/views`, `GET /slides/{slide_id}/views/{name}` - Saved viewer states
+//! - `POST /slides/{slide_id}/share`, `GET /share/{token}/view` - Share links
+//! - `GET /dzi/{slide_id}.dzi`, `GET /dzi/{slide_id}_files/{level}/{x}_{y}.jpg` - Deep Zoom
+//! - `GET /iiif/{slide_id}/info.json`, `GET /iiif/{slide_id}/{region}/{size}/{rotation}/{quality}.{format}` - IIIF Image API
+
+use std::collections::hash_map::RandomState;
+use std::collections::BTreeMap;
+use std::hash::{BuildHasher, Hasher};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use axum::{
-    extract::{Path, Query, State},
-    http::{header, HeaderMap, StatusCode},
+    extract::{
+        rejection::RawPathParamsRejection,
+        ws::{rejection::WebSocketUpgradeRejection, WebSocketUpgrade},
+        Path, Query, RawPathParams, Request, State,
+    },
+    http::{header, HeaderMap, Method, StatusCode},
+    middleware::Next,
     response::{Html, IntoResponse, Response},
-    Json,
+    Extension, Json,
 };
+use base64::prelude::{Engine as _, BASE64_STANDARD};
+use bytes::Bytes;
+use image::codecs::jpeg::JpegEncoder;
 use serde::{Deserialize, Serialize};
-use tracing::{debug, error, warn};
+use sha2::{Digest, Sha256};
+use tokio::sync::Semaphore;
+use tracing::{debug, error, info, warn};
 
-use crate::error::{FormatError, IoError, TiffError, TileError};
-use crate::slide::SlideSource;
-use crate::tile::{TileRequest, TileService, DEFAULT_JPEG_QUALITY};
+use crate::capabilities::{capabilities, Capabilities};
+use crate::error::{
+    AnnotationError, ErrorCode, FormatError, IiifError, IoError, TiffError, TileError, ViewError,
+};
+use crate::format::tiff::ValidationError;
+use crate::io::{s3_retry_stats, BlockCacheStats, RangeReader};
+use crate::overlay::{AnnotationSet, AnnotationStore, MemoryAnnotationStore};
+use crate::slide::{
+    load_view, save_view, AdaptiveCacheConfig, CachedSlide, IntegrityReport, IntegrityStatus,
+    QuarantineEntry, SlideCacheEntry, SlideConformance, SlideProperties, SlideSource,
+    SlideTemperature, StoredView, VerifyMode, ViewState,
+};
+use crate::tile::{
+    buffer_pool_stats, encode_snapshot, is_valid_quality, plan_region, region_output_size, Channel,
+    MemoryUsage, OutputFormat, RegionPlan, ResampleFilter, SampleOptions, SnapshotRegion,
+    SpriteEntry, TileCache, TileCacheStats, TileContext, TileRequest, TileService, ToneAdjustment,
+    BATCH_REGION_CONCURRENCY, DEFAULT_JPEG_QUALITY, DEFAULT_MIN_TISSUE, DEFAULT_PRINT_WIDTH_IN,
+    DEFAULT_SNAPSHOT_DPI, DEFAULT_SPRITE_SIZE, MAX_BATCH_REGIONS, MAX_BATCH_TILES,
+    MAX_REGION_PIXELS, MAX_SPRITE_SIZE, MAX_SPRITE_SLIDES, MIN_SPRITE_SIZE,
+};
 
-use super::auth::SignedUrlAuth;
+use super::auth::{AccessClaims, SignedUrlAuth, DEFAULT_SHARE_TTL, MAX_SHARE_TTL};
+use super::conditional::{format_http_date, is_not_modified, TileValidator};
+use super::connections::{ConnectionSnapshot, ConnectionTracker};
+use super::dzi::{
+    dzi_tile_bounds, parse_dzi_descriptor_name, parse_dzi_files_name, parse_dzi_tile_coords,
+};
+use super::iiif::{
+    apply_rotation_quality, build_info, parse_quality_format, parse_region, parse_rotation,
+    parse_size, IIIF_INFO_CONTENT_TYPE, IIIF_MAX_AREA,
+};
+use super::levels::LevelMap;
+use super::panic::panic_count;
+use super::raw::{range_request, RangeRequest, RAW_READ_CHUNK_SIZE};
+use super::readiness::{ReadinessProbe, StorageCheck};
+use super::reload::{ConfigReloader, LiveSettings};
+use super::slo::{SloSummary, SloTracker};
+use super::stream::{active_streams, blocking_body, spawned_body};
+use super::viewer::{generate_compare_html, ComparePane, MAX_COMPARE_SLIDES};
+use super::ws::{serve_events, EventFilter, MAX_CLIENT_MESSAGE_BYTES};
 
 // =============================================================================
 // Application State
@@ -36,11 +90,27 @@ pub struct AppState<S: SlideSource> {
     /// The tile service for processing tile requests
     pub tile_service: Arc<TileService<S>>,
 
-    /// Default cache control max-age in seconds (defaults to 1 hour)
-    pub cache_max_age: u32,
+    /// Settings a reload can change: cache control max-age (defaults to
+    /// 1 hour) and CORS origins
+    pub live: Arc<LiveSettings>,
 
     /// Authentication configuration for generating signed URLs in the viewer
     pub auth: Option<SignedUrlAuth>,
+
+    /// Tile request SLO tracking
+    pub slo: Arc<SloTracker>,
+
+    /// Request header naming the tenant tile requests are scheduled under
+    pub tenant_header: Option<String>,
+
+    /// Cached storage reachability for the readiness probe
+    pub readiness: Arc<ReadinessProbe>,
+
+    /// Reloader run by `POST /admin/reload`
+    pub reloader: Option<Arc<ConfigReloader>>,
+
+    /// Annotations drawn by `?overlay=annotations`
+    pub annotations: Arc<dyn AnnotationStore>,
 }
 
 impl<S: SlideSource> AppState<S> {
@@ -48,8 +118,13 @@ impl<S: SlideSource> AppState<S> {
     pub fn new(tile_service: TileService<S>) -> Self {
         Self {
             tile_service: Arc::new(tile_service),
-            cache_max_age: 3600, // 1 hour default
+            live: Arc::new(LiveSettings::new(3600, None)), // 1 hour default
             auth: None,
+            slo: Arc::new(SloTracker::default()),
+            tenant_header: None,
+            readiness: Arc::new(ReadinessProbe::default()),
+            reloader: None,
+            annotations: Arc::new(MemoryAnnotationStore::new()),
         }
     }
 
@@ -57,8 +132,13 @@ impl<S: SlideSource> AppState<S> {
     pub fn with_cache_max_age(tile_service: TileService<S>, cache_max_age: u32) -> Self {
         Self {
             tile_service: Arc::new(tile_service),
-            cache_max_age,
+            live: Arc::new(LiveSettings::new(cache_max_age, None)),
             auth: None,
+            slo: Arc::new(SloTracker::default()),
+            tenant_header: None,
+            readiness: Arc::new(ReadinessProbe::default()),
+            reloader: None,
+            annotations: Arc::new(MemoryAnnotationStore::new()),
         }
     }
 
@@ -67,14 +147,65 @@ impl<S: SlideSource> AppState<S> {
         self.auth = Some(auth);
         self
     }
+
+    /// Set the tracker recording tile request SLOs.
+    pub fn with_slo(mut self, slo: Arc<SloTracker>) -> Self {
+        self.slo = slo;
+        self
+    }
+
+    /// Set the request header naming the tenant of tile requests.
+    pub fn with_tenant_header(mut self, header: impl Into<String>) -> Self {
+        self.tenant_header = Some(header.into());
+        self
+    }
+
+    /// Set the settings a reload can change.
+    pub fn with_live_settings(mut self, live: Arc<LiveSettings>) -> Self {
+        self.live = live;
+        self
+    }
+
+    /// Set the reloader run by `POST /admin/reload`.
+    pub fn with_reloader(mut self, reloader: Arc<ConfigReloader>) -> Self {
+        self.reloader = Some(reloader);
+        self
+    }
+
+    /// Set the store annotations are saved to and drawn from.
+    pub fn with_annotation_store(mut self, store: Arc<dyn AnnotationStore>) -> Self {
+        self.annotations = store;
+        self
+    }
+
+    /// Get the cache control max-age in seconds.
+    pub fn cache_max_age(&self) -> u32 {
+        self.live.cache_max_age()
+    }
+
+    /// Tenant of a request, from the configured tenant header.
+    pub fn tenant(&self, headers: &HeaderMap) -> Option<String> {
+        let name = self.tenant_header.as_deref()?;
+        headers
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(str::to_string)
+    }
 }
 
 impl<S: SlideSource> Clone for AppState<S> {
     fn clone(&self) -> Self {
         Self {
             tile_service: Arc::clone(&self.tile_service),
-            cache_max_age: self.cache_max_age,
+            live: Arc::clone(&self.live),
             auth: self.auth.clone(),
+            slo: Arc::clone(&self.slo),
+            tenant_header: self.tenant_header.clone(),
+            readiness: Arc::clone(&self.readiness),
+            reloader: self.reloader.clone(),
+            annotations: Arc::clone(&self.annotations),
         }
     }
 }
@@ -98,15 +229,32 @@ pub struct TilePathParams {
     /// Tile X coordinate (0-indexed from left)
     pub x: u32,
 
-    /// Tile Y coordinate with optional .jpg extension (e.g., "0" or "0.jpg")
+    /// Tile Y coordinate with optional image extension (e.g., "0", "0.jpg"
+    /// or "0.webp")
     pub filename: String,
 }
 
 impl TilePathParams {
-    /// Parse the Y coordinate from the filename, stripping any .jpg extension.
+    /// Split the filename into the Y coordinate and a recognized extension.
+    fn split_filename(&self) -> (&str, Option<OutputFormat>) {
+        match self.filename.rsplit_once('.') {
+            Some((stem, extension)) => match OutputFormat::from_extension(extension) {
+                Some(format) => (stem, Some(format)),
+                None => (&self.filename, None),
+            },
+            None => (&self.filename, None),
+        }
+    }
+
+    /// Parse the Y coordinate from the filename, stripping any image
+    /// extension (`.jpg`, `.jpeg`, `.png` or `.webp`).
     pub fn y(&self) -> Result<u32, std::num::ParseIntError> {
-        let y_str = self.filename.strip_suffix(".jpg").unwrap_or(&self.filename);
-        y_str.parse()
+        self.split_filename().0.parse()
+    }
+
+    /// Output format named by the filename extension, if any.
+    pub fn format(&self) -> Option<OutputFormat> {
+        self.split_filename().1
     }
 }
 
@@ -117,6 +265,38 @@ pub struct TileQueryParams {
     #[serde(default = "default_quality")]
     pub quality: u8,
 
+    /// Output format (jpg, png or webp), overriding the path extension
+    #[serde(default)]
+    pub format: Option<OutputFormat>,
+
+    /// Forward the tile through the configured tile transformer (default: false)
+    #[serde(default)]
+    pub assist: bool,
+
+    /// Serve JPEG source tiles without re-encoding (default: false)
+    #[serde(default)]
+    pub passthrough: bool,
+
+    /// Brightness offset (-1.0 to 1.0, default 0.0)
+    #[serde(default)]
+    pub brightness: Option<f64>,
+
+    /// Contrast factor around mid-gray (0.0 to 4.0, default 1.0)
+    #[serde(default)]
+    pub contrast: Option<f64>,
+
+    /// Gamma, above 1.0 to lift midtones (0.1 to 10.0, default 1.0)
+    #[serde(default)]
+    pub gamma: Option<f64>,
+
+    /// Serve a single channel as grayscale (gray, r, g or b)
+    #[serde(default)]
+    pub channel: Option<Channel>,
+
+    /// Draw an overlay onto the tile (`annotations`)
+    #[serde(default)]
+    pub overlay: Option<OverlayKind>,
+
     /// Signature for authentication (handled by auth middleware)
     #[serde(default)]
     pub sig: Option<String>,
@@ -126,10 +306,51 @@ pub struct TileQueryParams {
     pub exp: Option<u64>,
 }
 
+/// Overlay drawn onto tiles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OverlayKind {
+    /// The slide's saved annotations
+    Annotations,
+}
+
+impl TileQueryParams {
+    /// Validate the requested tone adjustment.
+    ///
+    /// Returns `None` when no adjustment parameter is given.
+    pub fn adjustment(&self) -> Result<Option<ToneAdjustment>, TileError> {
+        if self.brightness.is_none() && self.contrast.is_none() && self.gamma.is_none() {
+            return Ok(None);
+        }
+        ToneAdjustment::new(self.brightness, self.contrast, self.gamma).map(Some)
+    }
+}
+
 fn default_quality() -> u8 {
     DEFAULT_JPEG_QUALITY
 }
 
+/// Query parameters for Deep Zoom tile requests.
+#[derive(Debug, Deserialize)]
+pub struct DziTileQueryParams {
+    /// JPEG quality (1-100, defaults to 80)
+    #[serde(default = "default_quality")]
+    pub quality: u8,
+
+    /// Resampling filter (`nearest`, `bilinear`, `lanczos3`; defaults to the
+    /// server setting)
+    #[serde(default)]
+    pub filter: Option<ResampleFilter>,
+
+    /// Signature for authentication (handled by auth middleware)
+    #[serde(default)]
+    pub sig: Option<String>,
+
+    /// Expiry timestamp for authentication (handled by auth middleware)
+    #[serde(default)]
+    pub exp: Option<u64>,
+}
+
 /// Query parameters for the slides list endpoint.
 #[derive(Debug, Deserialize)]
 pub struct SlidesQueryParams {
@@ -145,6 +366,10 @@ pub struct SlidesQueryParams {
     #[serde(default)]
     pub prefix: Option<String>,
 
+    /// Group keys below the prefix into folders at this separator (e.g., "/")
+    #[serde(default)]
+    pub delimiter: Option<String>,
+
     /// Search string to filter slide names (case-insensitive substring match)
     #[serde(default)]
     pub search: Option<String>,
@@ -165,14 +390,20 @@ fn default_limit() -> u32 {
 /// Query parameters for thumbnail requests.
 #[derive(Debug, Deserialize)]
 pub struct ThumbnailQueryParams {
-    /// Maximum width or height for the thumbnail (default: 512, max: 2048)
-    #[serde(default = "default_thumbnail_size")]
+    /// Maximum width or height for the thumbnail (default: 512, max: 2048),
+    /// also accepted as `max`
+    #[serde(default = "default_thumbnail_size", alias = "max")]
     pub max_size: u32,
 
     /// JPEG quality (1-100, defaults to 80)
     #[serde(default = "default_quality")]
     pub quality: u8,
 
+    /// Resampling filter (`nearest`, `bilinear`, `lanczos3`; defaults to the
+    /// server setting)
+    #[serde(default)]
+    pub filter: Option<ResampleFilter>,
+
     /// Signature for authentication (handled by auth middleware)
     #[serde(default)]
     pub sig: Option<String>,
@@ -186,6 +417,281 @@ fn default_thumbnail_size() -> u32 {
     512
 }
 
+/// Query parameters for print snapshot requests.
+#[derive(Debug, Deserialize)]
+pub struct SnapshotQueryParams {
+    /// Left edge of the region in microns
+    pub x_um: f64,
+
+    /// Top edge of the region in microns
+    pub y_um: f64,
+
+    /// Region width in microns
+    pub w_um: f64,
+
+    /// Region height in microns (defaults to the width)
+    #[serde(default)]
+    pub h_um: Option<f64>,
+
+    /// Output resolution in dots per inch (default: 300)
+    #[serde(default = "default_snapshot_dpi")]
+    pub dpi: u32,
+
+    /// Output width in inches (default: 6)
+    #[serde(default = "default_print_width")]
+    pub width_in: f64,
+
+    /// JPEG quality (1-100, defaults to 90)
+    #[serde(default = "default_snapshot_quality")]
+    pub quality: u8,
+
+    /// Resampling filter (`nearest`, `bilinear`, `lanczos3`; defaults to the
+    /// server setting)
+    #[serde(default)]
+    pub filter: Option<ResampleFilter>,
+
+    /// Signature for authentication (handled by auth middleware)
+    #[serde(default)]
+    pub sig: Option<String>,
+
+    /// Expiry timestamp for authentication (handled by auth middleware)
+    #[serde(default)]
+    pub exp: Option<u64>,
+}
+
+fn default_snapshot_dpi() -> u32 {
+    DEFAULT_SNAPSHOT_DPI
+}
+
+fn default_print_width() -> f64 {
+    DEFAULT_PRINT_WIDTH_IN
+}
+
+fn default_snapshot_quality() -> u8 {
+    90
+}
+
+/// Query parameters for region requests.
+#[derive(Debug, Deserialize)]
+pub struct RegionQueryParams {
+    /// Left edge of the region in level 0 pixels
+    pub x: u32,
+
+    /// Top edge of the region in level 0 pixels
+    pub y: u32,
+
+    /// Region width in pixels at the requested level or downsample
+    pub w: u32,
+
+    /// Region height in pixels at the requested level or downsample
+    pub h: u32,
+
+    /// Pyramid level the region size is given in (default: 0)
+    #[serde(default)]
+    pub level: Option<usize>,
+
+    /// Downsample the region size is given in, instead of a level
+    #[serde(default)]
+    pub downsample: Option<f64>,
+
+    /// Output width (default: `w`, or scaled with `out_h`)
+    #[serde(default)]
+    pub out_w: Option<u32>,
+
+    /// Output height (default: `h`, or scaled with `out_w`)
+    #[serde(default)]
+    pub out_h: Option<u32>,
+
+    /// Output format, `jpg` or `png` (default: `jpg`)
+    #[serde(default)]
+    pub format: OutputFormat,
+
+    /// JPEG quality (1-100, defaults to 80)
+    #[serde(default = "default_quality")]
+    pub quality: u8,
+
+    /// Resampling filter (`nearest`, `bilinear`, `lanczos3`; defaults to the
+    /// server setting)
+    #[serde(default)]
+    pub filter: Option<ResampleFilter>,
+
+    /// Signature for authentication (handled by auth middleware)
+    #[serde(default)]
+    pub sig: Option<String>,
+
+    /// Expiry timestamp for authentication (handled by auth middleware)
+    #[serde(default)]
+    pub exp: Option<u64>,
+}
+
+impl RegionQueryParams {
+    /// Position and size of the requested region.
+    pub fn geometry(&self) -> RegionGeometry {
+        RegionGeometry {
+            x: self.x,
+            y: self.y,
+            w: self.w,
+            h: self.h,
+            level: self.level,
+            downsample: self.downsample,
+            out_w: self.out_w,
+            out_h: self.out_h,
+        }
+    }
+}
+
+/// Position and size of a region, as given in region requests.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct RegionGeometry {
+    /// Left edge of the region in level 0 pixels
+    pub x: u32,
+
+    /// Top edge of the region in level 0 pixels
+    pub y: u32,
+
+    /// Region width in pixels at the requested level or downsample
+    pub w: u32,
+
+    /// Region height in pixels at the requested level or downsample
+    pub h: u32,
+
+    /// Pyramid level the region size is given in (default: 0)
+    #[serde(default)]
+    pub level: Option<usize>,
+
+    /// Downsample the region size is given in, instead of a level
+    #[serde(default)]
+    pub downsample: Option<f64>,
+
+    /// Output width (default: `w`, or scaled with `out_h`)
+    #[serde(default)]
+    pub out_w: Option<u32>,
+
+    /// Output height (default: `h`, or scaled with `out_w`)
+    #[serde(default)]
+    pub out_h: Option<u32>,
+}
+
+/// One region of a batch region request.
+#[derive(Debug, Clone, Deserialize)]
+pub struct BatchRegionRequest {
+    /// Slide the region is read from
+    pub slide_id: String,
+
+    /// Region position and size
+    #[serde(flatten)]
+    pub region: RegionGeometry,
+}
+
+/// Request body for batch region requests.
+#[derive(Debug, Deserialize)]
+pub struct BatchRegionsRequest {
+    /// Regions to render, in response order
+    pub regions: Vec<BatchRegionRequest>,
+
+    /// Output format of every region, `jpg` or `png` (default: `jpg`)
+    #[serde(default)]
+    pub format: OutputFormat,
+
+    /// JPEG quality (1-100, defaults to 80)
+    #[serde(default = "default_quality")]
+    pub quality: u8,
+
+    /// Resampling filter (defaults to the server setting)
+    #[serde(default)]
+    pub filter: Option<ResampleFilter>,
+}
+
+/// Query parameters for collection sprite sheet requests.
+#[derive(Debug, Deserialize)]
+pub struct SpriteQueryParams {
+    /// Cell size in pixels (default: 128, range: 32-512)
+    #[serde(default = "default_sprite_size")]
+    pub size: u32,
+
+    /// Maximum number of slides in the sheet (default and max: 256)
+    #[serde(default = "default_sprite_limit")]
+    pub limit: usize,
+
+    /// JPEG quality (1-100, defaults to 80)
+    #[serde(default = "default_quality")]
+    pub quality: u8,
+
+    /// Signature for authentication (handled by auth middleware)
+    #[serde(default)]
+    pub sig: Option<String>,
+
+    /// Expiry timestamp for authentication (handled by auth middleware)
+    #[serde(default)]
+    pub exp: Option<u64>,
+}
+
+fn default_sprite_size() -> u32 {
+    DEFAULT_SPRITE_SIZE
+}
+
+fn default_sprite_limit() -> usize {
+    MAX_SPRITE_SLIDES
+}
+
+/// Maximum number of tiles returned by a single sampling request.
+const MAX_SAMPLE_COUNT: usize = 1000;
+
+/// Maximum number of tiles returned by a sampling request that includes tile data.
+const MAX_SAMPLE_COUNT_WITH_TILES: usize = 100;
+
+/// Query parameters for tile sampling requests.
+#[derive(Debug, Deserialize)]
+pub struct SampleQueryParams {
+    /// Pyramid level to sample from (default: 0)
+    #[serde(default)]
+    pub level: usize,
+
+    /// Number of tiles to sample (default: 16)
+    #[serde(default = "default_sample_count")]
+    pub count: usize,
+
+    /// Random seed (default: 0)
+    #[serde(default)]
+    pub seed: u64,
+
+    /// Minimum tissue fraction for a tile to be eligible (default: 0.25)
+    #[serde(default = "default_min_tissue")]
+    pub min_tissue: f32,
+
+    /// Number of tissue-density strata (default: 1, no stratification)
+    #[serde(default = "default_strata")]
+    pub strata: usize,
+
+    /// Include base64-encoded tile data in the response (default: false)
+    #[serde(default)]
+    pub include_tiles: bool,
+
+    /// JPEG quality for included tiles (1-100, defaults to 80)
+    #[serde(default = "default_quality")]
+    pub quality: u8,
+
+    /// Signature for authentication (handled by auth middleware)
+    #[serde(default)]
+    pub sig: Option<String>,
+
+    /// Expiry timestamp for authentication (handled by auth middleware)
+    #[serde(default)]
+    pub exp: Option<u64>,
+}
+
+fn default_sample_count() -> usize {
+    16
+}
+
+fn default_min_tissue() -> f32 {
+    DEFAULT_MIN_TISSUE
+}
+
+fn default_strata() -> usize {
+    1
+}
+
 // =============================================================================
 // Response Types
 // =============================================================================
@@ -202,6 +708,14 @@ pub struct ErrorResponse {
     /// HTTP status code (included for convenience)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub status: Option<u16>,
+
+    /// Every failed support constraint, for rejected slides
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub violations: Vec<ViolationResponse>,
+
+    /// Whether the same request may succeed if retried (omitted when false)
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub retryable: bool,
 }
 
 impl ErrorResponse {
@@ -211,6 +725,8 @@ impl ErrorResponse {
             error: error.into(),
             message: message.into(),
             status: None,
+            violations: Vec::new(),
+            retryable: false,
         }
     }
 
@@ -224,10 +740,89 @@ impl ErrorResponse {
             error: error.into(),
             message: message.into(),
             status: Some(status.as_u16()),
+            violations: Vec::new(),
+            retryable: false,
+        }
+    }
+
+    /// Attach the failed support constraints of a rejected slide.
+    pub fn with_violations(mut self, errors: &[ValidationError]) -> Self {
+        self.violations = errors.iter().map(ViolationResponse::from).collect();
+        self
+    }
+
+    /// Mark whether retrying the request may succeed.
+    pub fn with_retryable(mut self, retryable: bool) -> Self {
+        self.retryable = retryable;
+        self
+    }
+}
+
+/// A failed support constraint in a 415 response.
+#[derive(Debug, Serialize)]
+pub struct ViolationResponse {
+    /// Constraint identifier (e.g. "unsupported_compression")
+    pub constraint: &'static str,
+
+    /// Index of the offending IFD, if the constraint concerns one IFD
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ifd: Option<usize>,
+
+    /// Human-readable description of the failure
+    pub message: String,
+
+    /// TIFF compression code, for compression failures
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compression: Option<u16>,
+
+    /// Compression name, for compression failures
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compression_name: Option<String>,
+
+    /// Missing tag names, for missing tag failures
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub missing_tags: Vec<&'static str>,
+
+    /// How to make the slide servable
+    pub remediation: &'static str,
+}
+
+impl From<&ValidationError> for ViolationResponse {
+    fn from(error: &ValidationError) -> Self {
+        let (compression, compression_name) = match error {
+            ValidationError::UnsupportedCompression {
+                compression,
+                compression_name,
+                ..
+            } => (Some(*compression), Some(compression_name.clone())),
+            _ => (None, None),
+        };
+        let missing_tags = match error {
+            ValidationError::MissingTag { tag, .. } => vec![*tag],
+            ValidationError::MissingTileTags { missing_tags, .. } => missing_tags.clone(),
+            _ => Vec::new(),
+        };
+
+        Self {
+            constraint: error.constraint(),
+            ifd: error.ifd_index(),
+            message: error.to_string(),
+            compression,
+            compression_name,
+            missing_tags,
+            remediation: error.remediation(),
         }
     }
 }
 
+/// Failed support constraints carried by a TIFF error, if any.
+fn tiff_violations(err: &TiffError) -> &[ValidationError] {
+    match err {
+        TiffError::Unsupported(errors) => errors,
+        _ => &[],
+    }
+}
+
 /// Health check response.
 #[derive(Debug, Serialize)]
 pub struct HealthResponse {
@@ -238,15 +833,61 @@ pub struct HealthResponse {
     pub version: String,
 }
 
-/// Response from the slides list endpoint.
+/// Response from the readiness endpoint.
 #[derive(Debug, Serialize)]
-pub struct SlidesResponse {
-    /// List of slide paths/IDs
-    pub slides: Vec<String>,
+pub struct ReadinessResponse {
+    /// `ready` or `not_ready`
+    pub status: String,
+
+    /// Service version
+    pub version: String,
+
+    /// Optional features compiled into this build
+    pub features: Vec<&'static str>,
+
+    /// Storage backend reachability
+    pub storage: StorageCheck,
+
+    /// Cache occupancy
+    pub cache: ReadinessCaches,
+}
+
+/// Cache occupancy reported by the readiness endpoint.
+#[derive(Debug, Serialize)]
+pub struct ReadinessCaches {
+    /// Slides open in the slide cache
+    pub slides: usize,
+
+    /// Tiles in the tile cache
+    pub tiles: usize,
+
+    /// Bytes held by the tile cache
+    pub tile_bytes: usize,
+
+    /// Tile cache capacity in bytes
+    pub tile_capacity_bytes: usize,
+}
+
+/// Response from the slides list endpoint.
+#[derive(Debug, Serialize)]
+pub struct SlidesResponse {
+    /// List of slide paths/IDs
+    pub slides: Vec<String>,
+
+    /// Folders directly under the prefix, ending in the delimiter (only
+    /// with `delimiter`)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub folders: Vec<String>,
 
     /// Continuation token for next page (None if no more pages)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub next_cursor: Option<String>,
+
+    /// Last validation result of listed slides, keyed by slide ID
+    ///
+    /// Only slides that have been opened have an entry.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub conformance: BTreeMap<String, SlideConformance>,
 }
 
 /// Metadata for a single pyramid level.
@@ -277,6 +918,117 @@ pub struct LevelMetadataResponse {
     pub downsample: f64,
 }
 
+/// A single sampled tile.
+#[derive(Debug, Serialize)]
+pub struct SampledTileResponse {
+    /// Tile X coordinate
+    pub x: u32,
+
+    /// Tile Y coordinate
+    pub y: u32,
+
+    /// Estimated tissue fraction (0.0-1.0)
+    pub tissue: f32,
+
+    /// Base64-encoded JPEG tile (only when `include_tiles=true`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<String>,
+}
+
+/// Response from the tile sampling endpoint.
+#[derive(Debug, Serialize)]
+pub struct SampleResponse {
+    /// Slide identifier
+    pub slide_id: String,
+
+    /// Pyramid level the tiles were sampled from
+    pub level: usize,
+
+    /// Seed used for sampling
+    pub seed: u64,
+
+    /// Sampled tiles, in sampling order
+    pub tiles: Vec<SampledTileResponse>,
+}
+
+/// Response from the collection sprite sheet endpoint.
+#[derive(Debug, Serialize)]
+pub struct SpriteSheetResponse {
+    /// Collection identifier (slide key prefix)
+    pub collection_id: String,
+
+    /// Sprite sheet as a `data:image/jpeg;base64,...` URI
+    pub image: String,
+
+    /// Sheet width in pixels
+    pub width: u32,
+
+    /// Sheet height in pixels
+    pub height: u32,
+
+    /// Cell size in pixels
+    pub size: u32,
+
+    /// Number of cells per row
+    pub columns: u32,
+
+    /// Thumbnail positions within the sheet
+    pub sprites: Vec<SpriteEntry>,
+
+    /// Slides whose thumbnails could not be rendered
+    pub failed: Vec<String>,
+
+    /// Whether the collection has more slides than were included
+    pub truncated: bool,
+}
+
+/// Request body for saving a view.
+#[derive(Debug, Deserialize)]
+pub struct SaveViewRequest {
+    /// View name, used in the view URL
+    pub name: String,
+
+    /// Viewport state to save
+    #[serde(flatten)]
+    pub state: ViewState,
+}
+
+/// Response from the view endpoints.
+#[derive(Debug, Serialize)]
+pub struct ViewResponse {
+    /// Path of the view (`/slides/{slide_id}/views/{name}`)
+    pub url: String,
+
+    /// The stored view
+    #[serde(flatten)]
+    pub view: StoredView,
+}
+
+impl ViewResponse {
+    fn new(view: StoredView) -> Self {
+        Self {
+            url: format!("/slides/{}/views/{}", view.slide_id, view.name),
+            view,
+        }
+    }
+}
+
+/// Response from saving annotations.
+#[derive(Debug, Serialize)]
+pub struct AnnotationsResponse {
+    /// Slide the annotations belong to
+    pub slide_id: String,
+
+    /// Number of features in the document
+    pub features: usize,
+
+    /// Number of shapes drawn (multi-geometries count once per part)
+    pub shapes: usize,
+
+    /// Content hash of the document, as hex; tile ETags change with it
+    pub revision: String,
+}
+
 /// Response from the slide metadata endpoint.
 #[derive(Debug, Serialize)]
 pub struct SlideMetadataResponse {
@@ -297,240 +1049,190 @@ pub struct SlideMetadataResponse {
 
     /// Metadata for each pyramid level
     pub levels: Vec<LevelMetadataResponse>,
+
+    /// Result of the last checksum verification (absent if never verified)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub integrity: Option<IntegrityReport>,
+}
+
+/// Viewer configuration for a slide.
+#[derive(Debug, Serialize)]
+pub struct SlideInfoResponse {
+    /// Slide identifier
+    pub slide_id: String,
+
+    /// Detected slide format (e.g., "Aperio SVS")
+    pub format: String,
+
+    /// Scanner vendor (absent if unknown)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vendor: Option<String>,
+
+    /// Level 0 resolution in microns per pixel (absent if uncalibrated)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mpp: Option<f64>,
+
+    /// Objective magnification (absent if unknown)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub magnification: Option<f64>,
+
+    /// Width of the full-resolution image in pixels
+    pub width: u32,
+
+    /// Height of the full-resolution image in pixels
+    pub height: u32,
+
+    /// Width of level 0 tiles in pixels
+    pub tile_width: u32,
+
+    /// Height of level 0 tiles in pixels
+    pub tile_height: u32,
+
+    /// Number of pyramid levels
+    pub level_count: usize,
+
+    /// Downsample factor of each level, in level order
+    pub downsamples: Vec<f64>,
+
+    /// Metadata for each pyramid level
+    pub levels: Vec<LevelMetadataResponse>,
+
+    /// OpenSlide-compatible properties (`openslide.*`, `tiff.*`, ...)
+    pub properties: SlideProperties,
 }
 
 // =============================================================================
 // Error Mapping
 // =============================================================================
 
-/// Convert TileError to HTTP response.
+/// Build the JSON response for an error.
 ///
-/// This implementation logs errors appropriately based on their severity:
-/// - 4xx errors are logged at WARN level (client errors)
+/// The status and `error` code come from [`ErrorCode`]; only the message is
+/// chosen by the caller. Errors are logged by severity:
 /// - 5xx errors are logged at ERROR level (server errors)
+/// - 404s are logged at DEBUG level (common and expected)
+/// - other 4xx errors are logged at WARN level (client errors)
+fn error_response(
+    err: &impl ErrorCode,
+    message: String,
+    violations: &[ValidationError],
+) -> Response {
+    let status = err.status();
+    let error_type = err.code();
+
+    if status.is_server_error() {
+        error!(
+            error_type = error_type,
+            status = status.as_u16(),
+            "Server error: {}",
+            message
+        );
+    } else if status == StatusCode::NOT_FOUND {
+        debug!(
+            error_type = error_type,
+            status = status.as_u16(),
+            "Resource not found: {}",
+            message
+        );
+    } else if status.is_client_error() {
+        warn!(
+            error_type = error_type,
+            status = status.as_u16(),
+            "Client error: {}",
+            message
+        );
+    }
+
+    let error_response = ErrorResponse::with_status(error_type, message, status)
+        .with_violations(violations)
+        .with_retryable(err.is_retryable());
+
+    (status, Json(error_response)).into_response()
+}
+
+/// Client-facing message for a storage error.
+fn io_error_message(err: &IoError) -> String {
+    match err {
+        IoError::NotFound(path) => format!("Resource not found: {}", path),
+        IoError::InvalidKey(_) => err.to_string(),
+        IoError::S3(msg) => format!("Storage error: {}", msg),
+        IoError::Connection(msg) => format!("Connection error: {}", msg),
+        IoError::RangeOutOfBounds { .. } => format!("I/O error: {}", err),
+        IoError::ObjectChanged(path) => format!("Slide changed while reading, retry: {}", path),
+        IoError::Corrupted { .. } => format!("Slide data is corrupted: {}", err),
+    }
+}
+
+/// Convert TileError to HTTP response.
 impl IntoResponse for TileError {
     fn into_response(self) -> Response {
-        let (status, error_type, message) = match &self {
-            // 404 Not Found
-            TileError::SlideNotFound { slide_id } => (
-                StatusCode::NOT_FOUND,
-                "not_found",
-                format!("Slide not found: {}", slide_id),
-            ),
-
-            // 400 Bad Request - Invalid parameters
-            TileError::InvalidLevel { level, max_levels } => (
-                StatusCode::BAD_REQUEST,
-                "invalid_level",
-                format!(
-                    "Invalid level: {} (slide has {} levels, valid range: 0-{})",
-                    level,
-                    max_levels,
-                    max_levels.saturating_sub(1)
-                ),
+        let message = match &self {
+            TileError::InvalidLevel { level, max_levels } => format!(
+                "Invalid level: {} (slide has {} levels, valid range: 0-{})",
+                level,
+                max_levels,
+                max_levels.saturating_sub(1)
             ),
-
             TileError::TileOutOfBounds {
                 level,
                 x,
                 y,
                 max_x,
                 max_y,
-            } => (
-                StatusCode::BAD_REQUEST,
-                "tile_out_of_bounds",
-                format!(
-                    "Tile coordinates ({}, {}) at level {} are out of bounds (max: {}, {})",
-                    x,
-                    y,
-                    level,
-                    max_x.saturating_sub(1),
-                    max_y.saturating_sub(1)
-                ),
-            ),
-
-            TileError::InvalidQuality { quality } => (
-                StatusCode::BAD_REQUEST,
-                "invalid_quality",
-                format!("Invalid quality: {} (must be 1-100)", quality),
-            ),
-
-            // TIFF structure errors map to 415 Unsupported Media Type
-            TileError::Slide(TiffError::Io(io_err)) => match io_err {
-                IoError::NotFound(path) => (
-                    StatusCode::NOT_FOUND,
-                    "not_found",
-                    format!("Resource not found: {}", path),
-                ),
-                _ => (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "io_error",
-                    format!("I/O error: {}", io_err),
-                ),
-            },
-            TileError::Slide(tiff_err) => (
-                StatusCode::UNSUPPORTED_MEDIA_TYPE,
-                "unsupported_format",
-                tiff_err.to_string(),
+            } => format!(
+                "Tile coordinates ({}, {}) at level {} are out of bounds (max: {}, {})",
+                x,
+                y,
+                level,
+                max_x.saturating_sub(1),
+                max_y.saturating_sub(1)
             ),
-
-            // 500 Internal Server Error - I/O and processing errors
-            TileError::Io(io_err) => {
-                // Map specific I/O errors
-                match io_err {
-                    IoError::NotFound(path) => (
-                        StatusCode::NOT_FOUND,
-                        "not_found",
-                        format!("Resource not found: {}", path),
-                    ),
-                    _ => (
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        "io_error",
-                        format!("I/O error: {}", io_err),
-                    ),
-                }
+            TileError::UnsupportedOutputFormat { format } => {
+                format!("Output format '{}' is not supported by this build", format)
             }
-
-            TileError::DecodeError { message } => (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "decode_error",
-                format!("Failed to decode tile: {}", message),
-            ),
-
-            TileError::EncodeError { message } => (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "encode_error",
-                format!("Failed to encode tile: {}", message),
-            ),
-        };
-
-        // Log errors based on severity
-        if status.is_server_error() {
-            error!(
-                error_type = error_type,
-                status = status.as_u16(),
-                "Server error: {}",
-                message
-            );
-        } else if status.is_client_error() {
-            // Log 404s at debug level (common and expected), others at warn
-            if status == StatusCode::NOT_FOUND {
-                debug!(
-                    error_type = error_type,
-                    status = status.as_u16(),
-                    "Resource not found: {}",
-                    message
-                );
-            } else {
-                warn!(
-                    error_type = error_type,
-                    status = status.as_u16(),
-                    "Client error: {}",
-                    message
-                );
+            TileError::Io(io_err) | TileError::Slide(TiffError::Io(io_err)) => {
+                io_error_message(io_err)
             }
-        }
-
-        let error_response = ErrorResponse::with_status(error_type, message, status);
+            TileError::Slide(tiff_err) => tiff_err.to_string(),
+            TileError::DecodeError { message } => format!("Failed to decode tile: {}", message),
+            TileError::EncodeError { message } => format!("Failed to encode tile: {}", message),
+            TileError::SlideQuarantined { slide_id } => {
+                format!("Slide {} is quarantined after repeated failures", slide_id)
+            }
+            _ => self.to_string(),
+        };
 
-        (status, Json(error_response)).into_response()
+        let violations = match &self {
+            TileError::Slide(tiff_err) => tiff_violations(tiff_err),
+            _ => &[],
+        };
+        error_response(&self, message, violations)
     }
 }
 
 /// Convert FormatError to HTTP response.
-///
-/// FormatError typically indicates an unsupported file format (HTTP 415)
-/// or an I/O error during format detection.
 impl IntoResponse for FormatError {
     fn into_response(self) -> Response {
-        let (status, error_type, message) = match &self {
-            FormatError::Io(io_err) => match io_err {
-                IoError::NotFound(path) => (
-                    StatusCode::NOT_FOUND,
-                    "not_found",
-                    format!("Slide not found: {}", path),
-                ),
-                IoError::S3(msg) => (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "storage_error",
-                    format!("Storage error: {}", msg),
-                ),
-                IoError::Connection(msg) => (
-                    StatusCode::BAD_GATEWAY,
-                    "connection_error",
-                    format!("Connection error: {}", msg),
-                ),
-                IoError::RangeOutOfBounds { .. } => (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "io_error",
-                    format!("I/O error: {}", io_err),
-                ),
-            },
-
-            FormatError::Tiff(tiff_err) => match tiff_err {
-                TiffError::Io(io_err) => match io_err {
-                    IoError::NotFound(path) => (
-                        StatusCode::NOT_FOUND,
-                        "not_found",
-                        format!("Slide not found: {}", path),
-                    ),
-                    IoError::S3(msg) => (
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        "storage_error",
-                        format!("Storage error: {}", msg),
-                    ),
-                    IoError::Connection(msg) => (
-                        StatusCode::BAD_GATEWAY,
-                        "connection_error",
-                        format!("Connection error: {}", msg),
-                    ),
-                    IoError::RangeOutOfBounds { .. } => (
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        "io_error",
-                        format!("I/O error: {}", io_err),
-                    ),
-                },
-                _ => (
-                    StatusCode::UNSUPPORTED_MEDIA_TYPE,
-                    "unsupported_format",
-                    tiff_err.to_string(),
-                ),
-            },
-
-            FormatError::UnsupportedFormat { reason } => (
-                StatusCode::UNSUPPORTED_MEDIA_TYPE,
-                "unsupported_format",
-                format!("Unsupported format: {}", reason),
-            ),
+        let message = match &self {
+            FormatError::Io(IoError::NotFound(path))
+            | FormatError::Tiff(TiffError::Io(IoError::NotFound(path))) => {
+                format!("Slide not found: {}", path)
+            }
+            FormatError::Io(io_err) | FormatError::Tiff(TiffError::Io(io_err)) => {
+                io_error_message(io_err)
+            }
+            FormatError::Tiff(tiff_err) => tiff_err.to_string(),
+            FormatError::Quarantined { slide_id } => {
+                format!("Slide {} is quarantined after repeated failures", slide_id)
+            }
+            _ => self.to_string(),
         };
 
-        // Log errors based on severity
-        if status.is_server_error() {
-            error!(
-                error_type = error_type,
-                status = status.as_u16(),
-                "Server error: {}",
-                message
-            );
-        } else if status == StatusCode::UNSUPPORTED_MEDIA_TYPE {
-            warn!(
-                error_type = error_type,
-                status = status.as_u16(),
-                "Unsupported format: {}",
-                message
-            );
-        } else if status == StatusCode::NOT_FOUND {
-            debug!(
-                error_type = error_type,
-                status = status.as_u16(),
-                "Resource not found: {}",
-                message
-            );
-        }
-
-        let error_response = ErrorResponse::with_status(error_type, message, status);
-
-        (status, Json(error_response)).into_response()
+        let violations = match &self {
+            FormatError::Tiff(tiff_err) => tiff_violations(tiff_err),
+            _ => &[],
+        };
+        error_response(&self, message, violations)
     }
 }
 
@@ -554,48 +1256,7 @@ pub struct SlidesError(pub IoError);
 
 impl IntoResponse for SlidesError {
     fn into_response(self) -> Response {
-        let (status, error_type, message) = match &self.0 {
-            IoError::NotFound(path) => (
-                StatusCode::NOT_FOUND,
-                "not_found",
-                format!("Resource not found: {}", path),
-            ),
-            IoError::S3(msg) => (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "storage_error",
-                format!("Storage error: {}", msg),
-            ),
-            IoError::Connection(msg) => (
-                StatusCode::BAD_GATEWAY,
-                "connection_error",
-                format!("Connection error: {}", msg),
-            ),
-            IoError::RangeOutOfBounds { .. } => (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "io_error",
-                format!("I/O error: {}", self.0),
-            ),
-        };
-
-        // Log based on severity
-        if status.is_server_error() {
-            error!(
-                error_type = error_type,
-                status = status.as_u16(),
-                "Server error: {}",
-                message
-            );
-        } else {
-            debug!(
-                error_type = error_type,
-                status = status.as_u16(),
-                "Client error: {}",
-                message
-            );
-        }
-
-        let error_response = ErrorResponse::with_status(error_type, message, status);
-        (status, Json(error_response)).into_response()
+        error_response(&self.0, io_error_message(&self.0), &[])
     }
 }
 
@@ -620,6 +1281,87 @@ impl From<FormatError> for SlideMetadataError {
     }
 }
 
+/// Convert ViewError to HTTP response.
+impl IntoResponse for ViewError {
+    fn into_response(self) -> Response {
+        let message = match self {
+            ViewError::Slide(err) => return err.into_response(),
+            ViewError::Io(ref io_err) => io_error_message(io_err),
+            _ => self.to_string(),
+        };
+        error_response(&self, message, &[])
+    }
+}
+
+/// Convert AnnotationError to HTTP response.
+impl IntoResponse for AnnotationError {
+    fn into_response(self) -> Response {
+        let message = match self {
+            AnnotationError::Slide(err) => return err.into_response(),
+            AnnotationError::Io(ref io_err) => io_error_message(io_err),
+            _ => self.to_string(),
+        };
+        error_response(&self, message, &[])
+    }
+}
+
+/// Convert IiifError to HTTP response.
+impl IntoResponse for IiifError {
+    fn into_response(self) -> Response {
+        if let IiifError::Tile(err) = self {
+            return err.into_response();
+        }
+        error_response(&self, self.to_string(), &[])
+    }
+}
+
+// =============================================================================
+// Slide Headers
+// =============================================================================
+
+/// Slide ID named by a route's path parameters.
+///
+/// Slide routes capture `{slide_id}` directly; Deep Zoom routes capture
+/// `{slide_id}.dzi` or `{slide_id}_files`.
+fn slide_id_from_params(params: &RawPathParams) -> Option<String> {
+    params.iter().find_map(|(key, value)| match key {
+        "slide_id" => Some(value.to_string()),
+        "name" => parse_dzi_descriptor_name(value).map(str::to_string),
+        "files" => parse_dzi_files_name(value).map(str::to_string),
+        _ => None,
+    })
+}
+
+/// Middleware adding a slide's sidecar headers to its responses.
+///
+/// Applied to slide-scoped routes when the registry loads headers sidecars
+/// (`--slide-headers`). Only successful and not-modified responses are
+/// labeled; the slide is already open by then, so the lookup is a cache hit.
+pub async fn slide_headers_middleware<S: SlideSource>(
+    State(state): State<AppState<S>>,
+    params: Result<RawPathParams, RawPathParamsRejection>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let registry = state.tile_service.registry();
+    let slide_id = match params {
+        Ok(params) if registry.slide_headers_enabled() => slide_id_from_params(&params),
+        _ => None,
+    };
+
+    let mut response = next.run(request).await;
+    let status = response.status();
+    if let (Some(slide_id), true) = (
+        slide_id,
+        status.is_success() || status == StatusCode::NOT_MODIFIED,
+    ) {
+        if let Ok(slide) = registry.get_slide(&slide_id).await {
+            slide.custom_headers().apply(response.headers_mut());
+        }
+    }
+    response
+}
+
 // =============================================================================
 // Handlers
 // =============================================================================
@@ -628,38 +1370,63 @@ impl From<FormatError> for SlideMetadataError {
 ///
 /// # Endpoint
 ///
-/// `GET /tiles/{slide_id}/{level}/{x}/{y}.jpg`
+/// `GET /tiles/{slide_id}/{level}/{x}/{y}.{jpg|png|webp}`
 ///
 /// # Path Parameters
 ///
 /// - `slide_id`: Slide identifier (URL-encoded if contains special characters)
 /// - `level`: Pyramid level (0 = highest resolution)
 /// - `x`: Tile X coordinate
-/// - `y`: Tile Y coordinate
+/// - `y`: Tile Y coordinate, optionally with an extension selecting the format
 ///
 /// # Query Parameters
 ///
-/// - `quality`: JPEG quality 1-100 (default: 80)
+/// - `quality`: JPEG or WebP quality 1-100 (default: 80); ignored for PNG
+/// - `format`: Output format (`jpg`, `png` or `webp`); overrides the extension
+/// - `assist`: Forward the tile through the configured tile transformer
+///   (e.g. an inference sidecar); ignored when no transformer is configured
 /// - `sig`: Authentication signature (optional, for signed URLs)
 /// - `exp`: Signature expiry timestamp (optional, for signed URLs)
 ///
 /// # Response
 ///
-/// - `200 OK`: JPEG tile image with `Content-Type: image/jpeg`
+/// - `200 OK`: Tile image
 /// - `400 Bad Request`: Invalid level or tile coordinates
 /// - `404 Not Found`: Slide not found
+/// - `406 Not Acceptable`: WebP requested from a build without the `webp` feature
 /// - `415 Unsupported Media Type`: Slide format not supported
 /// - `500 Internal Server Error`: Processing error
+/// - `502 Bad Gateway`: Tile transformer failed (`assist=true` only)
 ///
 /// # Headers
 ///
-/// - `Content-Type: image/jpeg`
-/// - `Cache-Control: public, max-age={cache_max_age}`
+/// - `Content-Type: image/jpeg`, `image/png` or `image/webp`
+/// - `Cache-Control: public, max-age={cache_max_age}`, or `private, no-store`
+///   for transformed tiles (`assist=true`), which carry no `ETag`
 /// - `X-Tile-Cache-Hit: true|false`
-pub async fn tile_handler<S: SlideSource>(
+/// - `X-Tile-Cache-Stale: true` when a stale tile is served while it is refreshed
+/// - `X-Tile-Transformed: true` and `X-Inference-*` (`assist=true` only)
+///
+/// Logs emitted while serving the tile are tagged with a `tile` span
+/// carrying the slide ID, coordinates, cache outcome and body size.
+#[tracing::instrument(
+    name = "tile",
+    skip_all,
+    fields(
+        slide_id = %params.slide_id,
+        level = params.level,
+        x = params.x,
+        y = tracing::field::Empty,
+        cache_hit = tracing::field::Empty,
+        bytes = tracing::field::Empty,
+    )
+)]
+pub async fn tile_handler<S: SlideSource + 'static>(
     State(state): State<AppState<S>>,
     Path(params): Path<TilePathParams>,
     Query(query): Query<TileQueryParams>,
+    claims: Option<Extension<AccessClaims>>,
+    headers: HeaderMap,
 ) -> Result<Response, HandlerError> {
     // Parse Y coordinate from filename (handles both "0" and "0.jpg")
     let y = params.y().map_err(|_| {
@@ -671,28 +1438,184 @@ pub async fn tile_handler<S: SlideSource>(
             max_y: 0,
         })
     })?;
+    let span = tracing::Span::current();
+    span.record("y", y);
+
+    // The format query parameter overrides the path extension, so clients
+    // that always request `.jpg` URLs can still ask for other formats
+    let format = query.format.or(params.format()).unwrap_or_default();
 
     // Build tile request
-    let request =
-        TileRequest::with_quality(&params.slide_id, params.level, params.x, y, query.quality);
+    let mut request =
+        TileRequest::with_quality(&params.slide_id, params.level, params.x, y, query.quality)
+            .with_format(format)
+            .with_passthrough(query.passthrough)
+            .with_adjustment(query.adjustment()?)
+            .with_channel(query.channel)
+            .with_watermark(wants_watermark(&state.tile_service, claims.as_deref()));
+    if query.overlay == Some(OverlayKind::Annotations) {
+        let annotations = state
+            .annotations
+            .load(&params.slide_id)
+            .await
+            .map_err(TileError::Io)?;
+        request = request.with_overlay(annotations);
+    }
+    if let Some(tenant) = state.tenant(&headers) {
+        request = request.with_tenant(tenant);
+    }
+
+    // Forward through the tile transformer when requested and configured
+    if query.assist {
+        if let Some(transformer) = state.tile_service.transformer() {
+            let context = TileContext {
+                slide_id: request.slide_id.clone(),
+                level: request.level,
+                tile_x: request.tile_x,
+                tile_y: request.tile_y,
+            };
+            let response = state.tile_service.get_tile_revalidating(request).await?;
+            let output = transformer
+                .transform(&context, response.data.clone())
+                .await?;
+
+            let mut builder = Response::builder()
+                .status(StatusCode::OK)
+                .header(
+                    header::CONTENT_TYPE,
+                    output
+                        .content_type
+                        .as_deref()
+                        .unwrap_or(format.content_type()),
+                )
+                // Transformer output can change without the slide changing,
+                // so there is no validator for it and shared caches must not
+                // keep it
+                .header(header::CACHE_CONTROL, "private, no-store")
+                .header("X-Tile-Cache-Hit", response.cache_hit.to_string())
+                .header("X-Tile-Quality", response.quality.to_string())
+                .header("X-Tile-Transformed", "true");
+
+            for (name, value) in &output.headers {
+                match (
+                    header::HeaderName::from_bytes(name.as_bytes()),
+                    header::HeaderValue::from_str(value),
+                ) {
+                    (Ok(name), Ok(value)) => builder = builder.header(name, value),
+                    _ => debug!("Skipping invalid transformer header: {}", name),
+                }
+            }
+
+            let body = output.data.unwrap_or(response.data);
+            span.record("cache_hit", response.cache_hit);
+            span.record("bytes", body.len());
+            return Ok(builder.body(axum::body::Body::from(body)).unwrap());
+        }
+    }
+
+    // Validators come from the slide file, so a conditional request is
+    // answered without reading or encoding the tile. A slide that can't be
+    // opened is reported here, so the failed open is only counted once.
+    let slide = state.tile_service.open_slide(&params.slide_id).await?;
+    let (etag, last_modified) = tile_validators(&state, &slide, &request);
+
+    if is_not_modified(&headers, &etag, last_modified) {
+        let mut builder = Response::builder()
+            .status(StatusCode::NOT_MODIFIED)
+            .header(header::ETAG, etag.as_str())
+            .header(
+                header::CACHE_CONTROL,
+                format!("public, max-age={}", state.cache_max_age()),
+            );
+        if let Some(modified) = last_modified {
+            builder = builder.header(header::LAST_MODIFIED, format_http_date(modified));
+        }
+        span.record("bytes", 0);
+        return Ok(builder.body(axum::body::Body::empty()).unwrap());
+    }
 
     // Get tile from service
-    let response = state.tile_service.get_tile(request).await?;
+    let response = state.tile_service.get_tile_revalidating(request).await?;
+    span.record("cache_hit", response.cache_hit);
+    span.record("bytes", response.data.len());
 
     // Build HTTP response with appropriate headers
-    let http_response = Response::builder()
+    let mut builder = Response::builder()
         .status(StatusCode::OK)
-        .header(header::CONTENT_TYPE, "image/jpeg")
+        .header(header::CONTENT_TYPE, format.content_type())
         .header(
             header::CACHE_CONTROL,
-            format!("public, max-age={}", state.cache_max_age),
+            format!("public, max-age={}", state.cache_max_age()),
         )
         .header("X-Tile-Cache-Hit", response.cache_hit.to_string())
         .header("X-Tile-Quality", response.quality.to_string())
-        .body(axum::body::Body::from(response.data))
-        .unwrap();
+        .header(header::ETAG, etag);
+    if response.stale {
+        builder = builder.header("X-Tile-Cache-Stale", "true");
+    }
+    if let Some(modified) = last_modified {
+        builder = builder.header(header::LAST_MODIFIED, format_http_date(modified));
+    }
 
-    Ok(http_response)
+    Ok(builder.body(axum::body::Body::from(response.data)).unwrap())
+}
+
+/// Whether a tile request is watermarked: as signed in its access claims,
+/// else the server default. Always false without a configured watermark.
+fn wants_watermark<S: SlideSource>(
+    tile_service: &TileService<S>,
+    claims: Option<&AccessClaims>,
+) -> bool {
+    tile_service.watermark().is_some()
+        && claims
+            .and_then(|claims| claims.watermark)
+            .unwrap_or(tile_service.watermark_by_default())
+}
+
+/// Compute the ETag and modification time for a tile request.
+fn tile_validators<S: SlideSource + 'static>(
+    state: &AppState<S>,
+    slide: &CachedSlide<S::Reader>,
+    request: &TileRequest,
+) -> (String, Option<SystemTime>) {
+    let mut render_settings = format!(
+        "retile={};crop={}",
+        state.tile_service.retile_size().unwrap_or(0),
+        state.tile_service.edge_cropping()
+    );
+    if request.passthrough {
+        render_settings.push_str(";passthrough");
+    }
+    if let Some(adjustment) = &request.adjustment {
+        render_settings.push_str(&format!(
+            ";brightness={};contrast={};gamma={}",
+            adjustment.brightness(),
+            adjustment.contrast(),
+            adjustment.gamma()
+        ));
+    }
+    if let Some(channel) = request.channel {
+        render_settings.push_str(&format!(";channel={}", channel.as_str()));
+    }
+    if let Some(overlay) = &request.overlay {
+        render_settings.push_str(&format!(";overlay={:016x}", overlay.revision()));
+    }
+    if request.watermark {
+        render_settings.push_str(";watermark");
+    }
+    let etag = TileValidator {
+        slide_id: &request.slide_id,
+        source_etag: slide.source_etag(),
+        source_size: slide.file_size(),
+        level: request.level,
+        x: request.tile_x,
+        y: request.tile_y,
+        quality: request.quality,
+        format: request.format.name(),
+        render_settings: &render_settings,
+    }
+    .etag();
+    (etag, slide.last_modified())
 }
 
 /// Handle health check requests.
@@ -717,361 +1640,3396 @@ pub async fn health_handler() -> Json<HealthResponse> {
     })
 }
 
-/// Handle slides list requests.
+/// Handle liveness probes.
 ///
 /// # Endpoint
 ///
-/// `GET /slides`
+/// `GET /livez`
 ///
-/// # Query Parameters
+/// # Response
 ///
-/// - `limit`: Maximum number of slides to return (default: 100, max: 1000)
-/// - `cursor`: Continuation token for pagination (from previous response)
-/// - `sig`: Authentication signature (for signed URLs)
-/// - `exp`: Signature expiry timestamp (for signed URLs)
+/// `200 OK` with the same body as `/health` whenever the server is
+/// answering requests. Storage is not checked, so a backend outage doesn't
+/// get the process restarted.
+pub async fn livez_handler() -> Json<HealthResponse> {
+    health_handler().await
+}
+
+/// Handle readiness probes.
+///
+/// # Endpoint
+///
+/// `GET /readyz`
 ///
 /// # Response
 ///
-/// `200 OK` with JSON body:
+/// - `200 OK`: Storage is reachable
+/// - `503 Service Unavailable`: Storage check failed or timed out
+///
+/// Both carry a JSON body with the storage check, cache occupancy and
+/// build info:
 /// ```json
 /// {
-///   "slides": ["path/to/slide1.svs", "path/to/slide2.tif"],
-///   "next_cursor": "continuation_token_or_null"
+///   "status": "ready",
+///   "version": "0.4.0",
+///   "features": ["jpeg2000", "webp"],
+///   "storage": { "reachable": true, "latency_ms": 12, "age_ms": 1830 },
+///   "cache": { "slides": 3, "tiles": 120, "tile_bytes": 2400000, "tile_capacity_bytes": 104857600 }
 /// }
 /// ```
 ///
-/// # Errors
-///
-/// - `401 Unauthorized`: Invalid or missing signature
-/// - `500 Internal Server Error`: Storage error
-pub async fn slides_handler<S: SlideSource>(
+/// The storage check is cached for a few seconds.
+pub async fn readyz_handler<S: SlideSource + 'static>(
+    State(state): State<AppState<S>>,
+) -> Response {
+    let registry = state.tile_service.registry();
+    let storage = state
+        .readiness
+        .check(|| registry.source().check_ready())
+        .await;
+    let (tile_bytes, tile_capacity_bytes, tiles) = state.tile_service.cache_stats().await;
+
+    let status = if storage.reachable {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    let body = ReadinessResponse {
+        status: if storage.reachable {
+            "ready"
+        } else {
+            "not_ready"
+        }
+        .to_string(),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        features: capabilities()
+            .features
+            .iter()
+            .filter(|feature| feature.enabled)
+            .map(|feature| feature.name)
+            .collect(),
+        storage,
+        cache: ReadinessCaches {
+            slides: registry.cached_count().await,
+            tiles,
+            tile_bytes,
+            tile_capacity_bytes,
+        },
+    };
+    (status, Json(body)).into_response()
+}
+
+/// Tile hash response for deterministic rendering.
+#[derive(Debug, Serialize)]
+pub struct TileHashResponse {
+    /// Slide identifier
+    pub slide_id: String,
+
+    /// Pyramid level
+    pub level: usize,
+
+    /// Tile X coordinate
+    pub x: u32,
+
+    /// Tile Y coordinate
+    pub y: u32,
+
+    /// JPEG quality the tile was rendered at
+    pub quality: u8,
+
+    /// Size of the rendered tile in bytes
+    pub bytes: usize,
+
+    /// SHA-256 of the rendered tile (hex)
+    pub sha256: String,
+}
+
+/// Handle rendered tile hash requests.
+///
+/// Only available when deterministic rendering is enabled
+/// (`--deterministic`), so hashes can be compared against golden values
+/// across releases.
+///
+/// # Endpoint
+///
+/// `GET /debug/tiles/{slide_id}/{level}/{x}/{y}/hash`
+///
+/// # Query Parameters
+///
+/// - `quality`: JPEG quality (1-100, default: 80)
+///
+/// # Response
+///
+/// `200 OK` with JSON body:
+/// ```json
+/// {
+///   "slide_id": "slide.svs",
+///   "level": 0,
+///   "x": 3,
+///   "y": 4,
+///   "quality": 80,
+///   "bytes": 18234,
+///   "sha256": "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08"
+/// }
+/// ```
+///
+/// # Errors
+///
+/// - `404 Not Found`: Deterministic rendering is disabled, or the slide
+///   does not exist
+/// - Tile errors as for the tile endpoint
+pub async fn tile_hash_handler<S: SlideSource>(
+    State(state): State<AppState<S>>,
+    Path((slide_id, level, x, y)): Path<(String, usize, u32, u32)>,
+    Query(query): Query<TileQueryParams>,
+) -> Result<Response, HandlerError> {
+    if !state.tile_service.is_deterministic() {
+        let body = ErrorResponse::with_status(
+            "not_found",
+            "Tile hashes require deterministic rendering (--deterministic)",
+            StatusCode::NOT_FOUND,
+        );
+        return Ok((StatusCode::NOT_FOUND, Json(body)).into_response());
+    }
+
+    let request = TileRequest::with_quality(&slide_id, level, x, y, query.quality);
+    let response = state.tile_service.get_tile(request).await?;
+
+    Ok(Json(TileHashResponse {
+        slide_id,
+        level,
+        x,
+        y,
+        quality: response.quality,
+        bytes: response.data.len(),
+        sha256: hex::encode(Sha256::digest(&response.data)),
+    })
+    .into_response())
+}
+
+/// Handle build capabilities requests.
+///
+/// # Endpoint
+///
+/// `GET /capabilities`
+///
+/// # Response
+///
+/// `200 OK` with JSON body:
+/// ```json
+/// {
+///   "version": "0.4.0",
+///   "features": [
+///     { "name": "jpeg2000", "enabled": true, "description": "JPEG 2000 source tiles" },
+///     { "name": "webp", "enabled": true, "description": "WebP tile output" }
+///   ],
+///   "formats": ["Aperio SVS", "Generic Pyramidal TIFF"],
+///   "compressions": ["LZW", "JPEG", "Deflate", "Adobe Deflate", "JPEG 2000"],
+///   "outputs": ["jpeg", "png", "webp"],
+///   "endpoints": [
+///     { "method": "GET", "path": "/health", "access": "public", "summary": "Health check" }
+///   ]
+/// }
+/// ```
+///
+/// `formats` also lists format plugins registered with the slide registry.
+pub async fn capabilities_handler<S: SlideSource>(
+    State(state): State<AppState<S>>,
+) -> Json<Capabilities> {
+    let mut capabilities = capabilities();
+    capabilities
+        .formats
+        .extend(state.tile_service.registry().format_plugins());
+    Json(capabilities)
+}
+
+/// Handle SLO summary requests.
+///
+/// # Endpoint
+///
+/// `GET /admin/slo`
+///
+/// # Response
+///
+/// `200 OK` with JSON body:
+/// ```json
+/// {
+///   "availability_target": 0.999,
+///   "latency_target": 0.99,
+///   "latency_threshold_ms": 500,
+///   "error_budget_remaining": 0.82,
+///   "page": false,
+///   "ticket": false,
+///   "windows": [
+///     {
+///       "window": "5m",
+///       "requests": 12034,
+///       "errors": 2,
+///       "slow": 31,
+///       "availability": 0.99983,
+///       "latency_compliance": 0.99742,
+///       "availability_burn_rate": 0.17,
+///       "latency_burn_rate": 0.26
+///     }
+///   ]
+/// }
+/// ```
+pub async fn slo_handler<S: SlideSource>(State(state): State<AppState<S>>) -> Json<SloSummary> {
+    Json(state.slo.summary())
+}
+
+/// Slide cache classification response.
+#[derive(Debug, Serialize)]
+pub struct CacheStatsResponse {
+    /// Adaptive budgets and thresholds, or `null` if adaptive caching is off
+    pub adaptive: Option<AdaptiveCacheConfig>,
+
+    /// Number of cached slides in each class
+    pub temperatures: BTreeMap<&'static str, usize>,
+
+    /// Cached slides, most recently used first
+    pub slides: Vec<SlideCacheEntry>,
+
+    /// Tile cache effectiveness
+    pub tiles: TileCacheSummary,
+
+    /// Memory held by caches
+    pub memory: MemorySummary,
+}
+
+/// Memory held by caches, against the budget.
+#[derive(Debug, Serialize)]
+pub struct MemorySummary {
+    /// Budget across caches in bytes, or `null` if unlimited
+    pub budget_bytes: Option<usize>,
+
+    /// Bytes held across caches
+    pub used_bytes: usize,
+
+    /// Bytes held by each cache
+    #[serde(flatten)]
+    pub usage: MemoryUsage,
+
+    /// Bytes evicted to stay within the budget since startup
+    pub evicted_bytes: u64,
+}
+
+impl MemorySummary {
+    async fn of<S: SlideSource>(tile_service: &TileService<S>) -> Self {
+        let usage = tile_service.memory_usage().await;
+        let budget = tile_service.memory_budget();
+        Self {
+            budget_bytes: budget.map(|budget| budget.max_bytes()),
+            used_bytes: usage.total(),
+            usage,
+            evicted_bytes: budget.map_or(0, |budget| budget.evicted_bytes()),
+        }
+    }
+}
+
+/// Tile cache admission policy and counters.
+#[derive(Debug, Serialize)]
+pub struct TileCacheSummary {
+    /// Admission policy (`lru` or `tinylfu`)
+    pub admission: &'static str,
+
+    /// Fraction of tile lookups served from the cache
+    pub hit_ratio: f64,
+
+    /// Counters since startup
+    #[serde(flatten)]
+    pub stats: TileCacheStats,
+}
+
+impl TileCacheSummary {
+    fn of(cache: &TileCache) -> Self {
+        let stats = cache.stats();
+        Self {
+            admission: cache.admission().as_str(),
+            hit_ratio: stats.hit_ratio(),
+            stats,
+        }
+    }
+}
+
+/// Quarantined slides response.
+#[derive(Debug, Serialize)]
+pub struct QuarantineResponse {
+    /// Failures within the window that quarantine a slide (0 = disabled)
+    pub threshold: u32,
+
+    /// Quarantined slides, most recent first
+    pub slides: Vec<QuarantineEntry>,
+}
+
+/// Quarantine release response.
+#[derive(Debug, Serialize)]
+pub struct QuarantineReleaseResponse {
+    /// Released slide
+    pub slide_id: String,
+
+    /// Always true; unknown slides return 404
+    pub released: bool,
+}
+
+/// Block cache I/O statistics of an open slide.
+#[derive(Debug, Serialize)]
+pub struct SlideIoStatsResponse {
+    /// Slide identifier
+    pub slide_id: String,
+
+    /// Block size, in bytes
+    pub block_size: usize,
+
+    /// Block cache capacity, in blocks
+    pub block_cache_capacity: usize,
+
+    /// Blocks currently cached
+    pub cached_blocks: usize,
+
+    /// Fraction of block lookups served from the cache
+    pub hit_rate: f64,
+
+    /// Counters since the slide was opened
+    #[serde(flatten)]
+    pub io: BlockCacheStats,
+}
+
+/// Slide invalidation response.
+#[derive(Debug, Serialize)]
+pub struct SlideInvalidateResponse {
+    /// Invalidated slide
+    pub slide_id: String,
+
+    /// Always true
+    pub invalidated: bool,
+}
+
+/// Open connections response.
+#[derive(Debug, Serialize)]
+pub struct ConnectionsResponse {
+    /// Client connections currently open
+    pub open: usize,
+
+    /// Client connections accepted since startup
+    pub opened_total: u64,
+
+    /// Tiles currently being generated
+    pub active_tile_generations: usize,
+
+    /// Streamed responses still being sent
+    pub active_streams: usize,
+
+    /// Open connections, oldest first
+    pub connections: Vec<ConnectionEntryResponse>,
+}
+
+/// A single open connection.
+#[derive(Debug, Serialize)]
+pub struct ConnectionEntryResponse {
+    /// Connection ID, unique for the process lifetime
+    pub id: u64,
+
+    /// Peer address
+    pub peer: String,
+
+    /// Seconds since the connection was accepted
+    pub age_secs: f64,
+
+    /// Seconds since the last request started
+    pub idle_secs: f64,
+
+    /// Requests received on the connection
+    pub requests: u64,
+
+    /// Requests currently being handled
+    pub in_flight: u64,
+}
+
+impl From<ConnectionSnapshot> for ConnectionEntryResponse {
+    fn from(snapshot: ConnectionSnapshot) -> Self {
+        Self {
+            id: snapshot.id,
+            peer: snapshot.peer.to_string(),
+            age_secs: snapshot.age.as_secs_f64(),
+            idle_secs: snapshot.idle.as_secs_f64(),
+            requests: snapshot.requests,
+            in_flight: snapshot.in_flight,
+        }
+    }
+}
+
+/// Handle slide cache classification requests.
+///
+/// # Endpoint
+///
+/// `GET /admin/cache`
+///
+/// Scores every cached slide and reports its class and block cache usage.
+/// With adaptive caching enabled this also applies the class budgets, as
+/// the periodic rebalance does.
+///
+/// # Response
+///
+/// `200 OK` with JSON body:
+/// ```json
+/// {
+///   "adaptive": {
+///     "hot_blocks": 400,
+///     "warm_blocks": 100,
+///     "cold_blocks": 16,
+///     "hot_score": 200.0,
+///     "cold_after": 600.0,
+///     "half_life": 300.0
+///   },
+///   "temperatures": { "cold": 1, "hot": 1, "warm": 0 },
+///   "slides": [
+///     {
+///       "slide_id": "busy.svs",
+///       "temperature": "hot",
+///       "score": 512.4,
+///       "accesses": 1840,
+///       "idle_secs": 0.2,
+///       "block_cache_capacity": 400,
+///       "cached_blocks": 312
+///     }
+///   ],
+///   "tiles": {
+///     "admission": "tinylfu",
+///     "hit_ratio": 0.82,
+///     "hits": 4100,
+///     "misses": 900,
+///     "admitted": 610,
+///     "rejected": 290,
+///     "evicted": 120
+///   },
+///   "memory": {
+///     "budget_bytes": 1073741824,
+///     "used_bytes": 412316860,
+///     "tile_cache_bytes": 104857600,
+///     "block_cache_bytes": 306184192,
+///     "metadata_bytes": 1275068,
+///     "evicted_bytes": 0
+///   }
+/// }
+/// ```
+pub async fn cache_stats_handler<S: SlideSource>(
+    State(state): State<AppState<S>>,
+) -> Json<CacheStatsResponse> {
+    let registry = state.tile_service.registry();
+    let slides = registry.classify_slides();
+    Json(CacheStatsResponse {
+        adaptive: registry.adaptive_caching().copied(),
+        temperatures: temperature_counts(&slides),
+        slides,
+        tiles: TileCacheSummary::of(state.tile_service.cache()),
+        memory: MemorySummary::of(&state.tile_service).await,
+    })
+}
+
+/// Render per-slide block cache counters of open slides.
+fn render_slide_io_metrics(slides: &[SlideCacheEntry]) -> String {
+    type SlideCounter = (&'static str, &'static str, fn(&BlockCacheStats) -> u64);
+    let counters: [SlideCounter; 6] = [
+        (
+            "wsi_slide_read_requests_total",
+            "Reads served by the slide's block cache.",
+            |io| io.requests,
+        ),
+        (
+            "wsi_slide_read_bytes_total",
+            "Bytes returned by the slide's block cache.",
+            |io| io.bytes_requested,
+        ),
+        (
+            "wsi_slide_block_hits_total",
+            "Block lookups served from the slide's block cache.",
+            |io| io.hits,
+        ),
+        (
+            "wsi_slide_block_misses_total",
+            "Block lookups that missed the slide's block cache.",
+            |io| io.misses,
+        ),
+        (
+            "wsi_slide_fetched_bytes_total",
+            "Bytes fetched from storage for the slide.",
+            |io| io.bytes_fetched,
+        ),
+        (
+            "wsi_slide_distinct_blocks",
+            "Distinct blocks of the slide fetched from storage.",
+            |io| io.distinct_blocks,
+        ),
+    ];
+
+    let mut out = String::new();
+    for (name, help, value) in counters {
+        let kind = if name.ends_with("_total") {
+            "counter"
+        } else {
+            "gauge"
+        };
+        out.push_str(&format!("# HELP {} {}\n", name, help));
+        out.push_str(&format!("# TYPE {} {}\n", name, kind));
+        for slide in slides {
+            out.push_str(&format!(
+                "{}{{slide=\"{}\"}} {}\n",
+                name,
+                escape_label_value(&slide.slide_id),
+                value(&slide.io)
+            ));
+        }
+    }
+    out
+}
+
+/// Escape a Prometheus label value.
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Count slides in each temperature class.
+fn temperature_counts(slides: &[SlideCacheEntry]) -> BTreeMap<&'static str, usize> {
+    SlideTemperature::ALL
+        .iter()
+        .map(|temperature| {
+            let count = slides
+                .iter()
+                .filter(|slide| slide.temperature == *temperature)
+                .count();
+            (temperature.name(), count)
+        })
+        .collect()
+}
+
+/// Handle quarantine listing requests.
+///
+/// # Endpoint
+///
+/// `GET /admin/quarantine`
+///
+/// # Response
+///
+/// `200 OK` with JSON body:
+/// ```json
+/// {
+///   "threshold": 5,
+///   "slides": [
+///     {
+///       "slide_id": "corrupt.svs",
+///       "reason": "Failed to decode JPEG: ...",
+///       "failures": 5,
+///       "quarantined_at": 1760000000
+///     }
+///   ]
+/// }
+/// ```
+pub async fn quarantine_handler<S: SlideSource>(
+    State(state): State<AppState<S>>,
+) -> Json<QuarantineResponse> {
+    let quarantine = state.tile_service.registry().quarantine();
+    Json(QuarantineResponse {
+        threshold: quarantine.threshold(),
+        slides: quarantine.entries(),
+    })
+}
+
+/// Handle quarantine release requests.
+///
+/// # Endpoint
+///
+/// `POST /admin/quarantine/{slide_id}/release`
+///
+/// # Errors
+///
+/// - `404 Not Found`: The slide is not quarantined
+pub async fn quarantine_release_handler<S: SlideSource>(
+    State(state): State<AppState<S>>,
+    Path(slide_id): Path<String>,
+) -> Response {
+    if !state
+        .tile_service
+        .registry()
+        .quarantine()
+        .release(&slide_id)
+    {
+        let body = ErrorResponse::with_status(
+            "not_found",
+            format!("Slide is not quarantined: {}", slide_id),
+            StatusCode::NOT_FOUND,
+        );
+        return (StatusCode::NOT_FOUND, Json(body)).into_response();
+    }
+
+    info!(slide_id = %slide_id, "Slide released from quarantine");
+    Json(QuarantineReleaseResponse {
+        slide_id,
+        released: true,
+    })
+    .into_response()
+}
+
+/// Handle open connection listing requests.
+///
+/// # Endpoint
+///
+/// `GET /admin/connections`
+///
+/// Lists the client connections the server currently holds open, with the
+/// number of requests each has carried. A load balancer reusing keep-alive
+/// connections shows a few long-lived connections with high request counts;
+/// one that reconnects per request shows many young connections with a
+/// single request each.
+///
+/// # Response
+///
+/// `200 OK` with JSON body:
+/// ```json
+/// {
+///   "open": 2,
+///   "opened_total": 118,
+///   "active_tile_generations": 1,
+///   "active_streams": 0,
+///   "connections": [
+///     {
+///       "id": 117,
+///       "peer": "10.0.3.12:51234",
+///       "age_secs": 84.2,
+///       "idle_secs": 0.4,
+///       "requests": 1520,
+///       "in_flight": 1
+///     }
+///   ]
+/// }
+/// ```
+pub async fn connections_handler<S: SlideSource>(
+    State(state): State<AppState<S>>,
+) -> Json<ConnectionsResponse> {
+    let tracker = ConnectionTracker::global();
+    Json(ConnectionsResponse {
+        open: tracker.open_connections(),
+        opened_total: tracker.opened_total(),
+        active_tile_generations: state.tile_service.active_generations(),
+        active_streams: active_streams(),
+        connections: tracker
+            .snapshot()
+            .into_iter()
+            .map(ConnectionEntryResponse::from)
+            .collect(),
+    })
+}
+
+/// Handle slide invalidation requests.
+///
+/// # Endpoint
+///
+/// `POST /admin/slides/{slide_id}/invalidate`
+///
+/// Drops the slide's cached metadata and marks its cached tiles stale, e.g.
+/// after the underlying object was replaced. With stale-while-revalidate
+/// enabled, stale tiles keep being served while they are regenerated.
+pub async fn slide_invalidate_handler<S: SlideSource>(
+    State(state): State<AppState<S>>,
+    Path(slide_id): Path<String>,
+) -> Json<SlideInvalidateResponse> {
+    state.tile_service.invalidate_slide(&slide_id).await;
+
+    info!(slide_id = %slide_id, "Slide invalidated");
+    Json(SlideInvalidateResponse {
+        slide_id,
+        invalidated: true,
+    })
+}
+
+/// Handle slide I/O statistics requests.
+///
+/// # Endpoint
+///
+/// `GET /admin/slides/{slide_id}/stats`
+///
+/// # Response
+///
+/// `200 OK` with the block cache counters of an open slide, or `404 Not
+/// Found` if the slide is not open. Counters start when the slide is opened
+/// and are lost when it is evicted from the slide cache.
+///
+/// ```json
+/// {
+///   "slide_id": "sample.svs",
+///   "block_size": 262144,
+///   "block_cache_capacity": 100,
+///   "cached_blocks": 42,
+///   "hit_rate": 0.93,
+///   "requests": 1520,
+///   "bytes_requested": 18350080,
+///   "hits": 1490,
+///   "misses": 112,
+///   "coalesced": 6,
+///   "fetches": 106,
+///   "bytes_fetched": 27787264,
+///   "distinct_blocks": 58,
+///   "corrupted": 0
+/// }
+/// ```
+pub async fn slide_io_stats_handler<S: SlideSource>(
+    State(state): State<AppState<S>>,
+    Path(slide_id): Path<String>,
+) -> Response {
+    let Some(slide) = state.tile_service.registry().cached_slide(&slide_id) else {
+        let body = ErrorResponse::with_status(
+            "not_found",
+            format!("Slide is not open: {}", slide_id),
+            StatusCode::NOT_FOUND,
+        );
+        return (StatusCode::NOT_FOUND, Json(body)).into_response();
+    };
+
+    let io = slide.io_stats();
+    Json(SlideIoStatsResponse {
+        slide_id,
+        block_size: slide.block_size(),
+        block_cache_capacity: slide.block_cache_capacity(),
+        cached_blocks: slide.cached_blocks(),
+        hit_rate: io.hit_rate(),
+        io,
+    })
+    .into_response()
+}
+
+/// Handle configuration reload requests.
+///
+/// # Endpoint
+///
+/// `POST /admin/reload`
+///
+/// Reloads the signing secret, CORS origins, cache max-age and log level
+/// without dropping connections or caches. Responds with the settings that
+/// changed, `500` if the new settings can't be loaded (the old ones stay in
+/// effect), or `404` if the server wasn't started with a reloader.
+pub async fn reload_handler<S: SlideSource>(State(state): State<AppState<S>>) -> Response {
+    let Some(reloader) = &state.reloader else {
+        let body = ErrorResponse::with_status(
+            "not_found",
+            "Configuration reload is not enabled",
+            StatusCode::NOT_FOUND,
+        );
+        return (StatusCode::NOT_FOUND, Json(body)).into_response();
+    };
+
+    match reloader.reload().await {
+        Ok(report) => {
+            info!(changed = ?report.changed, "Configuration reloaded");
+            Json(report).into_response()
+        }
+        Err(e) => {
+            warn!(error = %e, "Configuration reload failed");
+            let body =
+                ErrorResponse::with_status("reload_failed", e, StatusCode::INTERNAL_SERVER_ERROR);
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(body)).into_response()
+        }
+    }
+}
+
+/// Handle metrics requests.
+///
+/// # Endpoint
+///
+/// `GET /metrics`
+///
+/// # Response
+///
+/// `200 OK` with tile request counters, SLO burn rates, the recovered
+/// panic count and connection gauges in Prometheus text exposition format.
+pub async fn metrics_handler<S: SlideSource>(State(state): State<AppState<S>>) -> Response {
+    let mut body = state.slo.render_prometheus();
+    body.push_str("# HELP wsi_panics_total Request handler panics recovered as 500 responses.\n");
+    body.push_str("# TYPE wsi_panics_total counter\n");
+    body.push_str(&format!("wsi_panics_total {}\n", panic_count()));
+
+    let tracker = ConnectionTracker::global();
+    let buffers = buffer_pool_stats();
+    let opens = state.tile_service.registry().open_stats();
+    let gauges = [
+        (
+            "wsi_http_connections_open",
+            "gauge",
+            "Client connections currently open.",
+            tracker.open_connections() as u64,
+        ),
+        (
+            "wsi_http_connections_total",
+            "counter",
+            "Client connections accepted.",
+            tracker.opened_total(),
+        ),
+        (
+            "wsi_tile_generations_active",
+            "gauge",
+            "Tiles currently being generated.",
+            state.tile_service.active_generations() as u64,
+        ),
+        (
+            "wsi_tile_generations_coalesced_total",
+            "counter",
+            "Tile requests that shared a generation already in flight.",
+            state.tile_service.coalesced_generations(),
+        ),
+        (
+            "wsi_tile_encodes_active",
+            "gauge",
+            "Tiles being decoded and encoded on the blocking pool.",
+            state.tile_service.encode_pool().active() as u64,
+        ),
+        (
+            "wsi_streams_active",
+            "gauge",
+            "Streamed responses still being sent.",
+            active_streams() as u64,
+        ),
+        (
+            "wsi_slides_negative_cached",
+            "gauge",
+            "Slides remembered as missing or unparseable.",
+            state.tile_service.registry().negative_cache().len() as u64,
+        ),
+        (
+            "wsi_slide_opens_in_flight",
+            "gauge",
+            "Slides currently being opened.",
+            opens.in_flight as u64,
+        ),
+        (
+            "wsi_slide_opens_queued",
+            "gauge",
+            "Slide opens waiting for a free slot.",
+            opens.queued as u64,
+        ),
+        (
+            "wsi_s3_read_retries_total",
+            "counter",
+            "S3 range reads retried after a transient failure.",
+            s3_retry_stats().retries,
+        ),
+        (
+            "wsi_s3_read_retries_exhausted_total",
+            "counter",
+            "S3 range reads that failed after their last retry.",
+            s3_retry_stats().exhausted,
+        ),
+        (
+            "wsi_tile_buffers_acquired_total",
+            "counter",
+            "Decode and encode scratch buffers handed out.",
+            buffers.acquired,
+        ),
+        (
+            "wsi_tile_buffers_reused_total",
+            "counter",
+            "Scratch buffers served from a pool instead of a fresh allocation.",
+            buffers.reused,
+        ),
+        (
+            "wsi_tile_buffer_allocated_bytes_total",
+            "counter",
+            "Bytes of scratch buffer capacity allocated.",
+            buffers.allocated_bytes,
+        ),
+        (
+            "wsi_tile_buffers_recycled_total",
+            "counter",
+            "Scratch buffers returned to a pool after use.",
+            buffers.recycled,
+        ),
+        (
+            "wsi_tile_buffers_discarded_total",
+            "counter",
+            "Scratch buffers released because the pool was full or they were too large.",
+            buffers.discarded,
+        ),
+    ];
+    for (name, kind, help, value) in gauges {
+        body.push_str(&format!("# HELP {} {}\n", name, help));
+        body.push_str(&format!("# TYPE {} {}\n", name, kind));
+        body.push_str(&format!("{} {}\n", name, value));
+    }
+    if let Some(scheduler) = state.tile_service.scheduler() {
+        body.push_str(&scheduler.stats().render_prometheus());
+    }
+
+    let tiles = state.tile_service.cache().stats();
+    let contention = state.tile_service.cache().contention();
+    let tile_counters = [
+        (
+            "hits",
+            "Tile cache lookups that found the tile.",
+            tiles.hits,
+        ),
+        (
+            "misses",
+            "Tile cache lookups that found nothing.",
+            tiles.misses,
+        ),
+        (
+            "rejected",
+            "Tiles not cached by the admission policy.",
+            tiles.rejected,
+        ),
+        (
+            "evictions",
+            "Tiles evicted from the tile cache.",
+            tiles.evicted,
+        ),
+        (
+            "lock_acquisitions",
+            "Tile cache shard lock acquisitions.",
+            contention.acquisitions,
+        ),
+        (
+            "lock_contended",
+            "Tile cache shard lock acquisitions that waited for another holder.",
+            contention.contended,
+        ),
+    ];
+    for (name, help, value) in tile_counters {
+        body.push_str(&format!("# HELP wsi_tile_cache_{}_total {}\n", name, help));
+        body.push_str(&format!("# TYPE wsi_tile_cache_{}_total counter\n", name));
+        body.push_str(&format!("wsi_tile_cache_{}_total {}\n", name, value));
+    }
+    body.push_str("# HELP wsi_tile_cache_shards Lock shards of the tile cache.\n");
+    body.push_str("# TYPE wsi_tile_cache_shards gauge\n");
+    body.push_str(&format!("wsi_tile_cache_shards {}\n", contention.shards));
+
+    let memory = MemorySummary::of(&state.tile_service).await;
+    body.push_str("# HELP wsi_memory_bytes Bytes held by each cache.\n");
+    body.push_str("# TYPE wsi_memory_bytes gauge\n");
+    for (cache, bytes) in [
+        ("tiles", memory.usage.tile_cache_bytes),
+        ("blocks", memory.usage.block_cache_bytes),
+        ("metadata", memory.usage.metadata_bytes),
+    ] {
+        body.push_str(&format!(
+            "wsi_memory_bytes{{cache=\"{}\"}} {}\n",
+            cache, bytes
+        ));
+    }
+    if let Some(budget) = memory.budget_bytes {
+        body.push_str("# HELP wsi_memory_budget_bytes Memory budget across caches.\n");
+        body.push_str("# TYPE wsi_memory_budget_bytes gauge\n");
+        body.push_str(&format!("wsi_memory_budget_bytes {}\n", budget));
+    }
+    body.push_str(
+        "# HELP wsi_memory_evicted_bytes_total Bytes evicted to stay within the memory budget.\n",
+    );
+    body.push_str("# TYPE wsi_memory_evicted_bytes_total counter\n");
+    body.push_str(&format!(
+        "wsi_memory_evicted_bytes_total {}\n",
+        memory.evicted_bytes
+    ));
+
+    let slides = state.tile_service.registry().classify_slides();
+    let counts = temperature_counts(&slides);
+    body.push_str("# HELP wsi_slides_cached Open slides by access temperature.\n");
+    body.push_str("# TYPE wsi_slides_cached gauge\n");
+    for temperature in SlideTemperature::ALL {
+        body.push_str(&format!(
+            "wsi_slides_cached{{temperature=\"{}\"}} {}\n",
+            temperature.name(),
+            counts[temperature.name()]
+        ));
+    }
+    body.push_str("# HELP wsi_block_cache_blocks Blocks cached across open slides.\n");
+    body.push_str("# TYPE wsi_block_cache_blocks gauge\n");
+    body.push_str(&format!(
+        "wsi_block_cache_blocks {}\n",
+        slides
+            .iter()
+            .map(|slide| slide.cached_blocks)
+            .sum::<usize>()
+    ));
+
+    prometheus_response(body)
+}
+
+/// Handle per-slide I/O metrics requests.
+///
+/// # Endpoint
+///
+/// `GET /admin/slides/metrics`
+///
+/// # Response
+///
+/// `200 OK` with the block cache counters of every open slide, labelled by
+/// slide ID, in Prometheus text exposition format. Kept off the public
+/// `/metrics` endpoint so slide IDs are only visible to admins.
+pub async fn slide_io_metrics_handler<S: SlideSource>(
+    State(state): State<AppState<S>>,
+) -> Response {
+    let slides = state.tile_service.registry().classify_slides();
+    prometheus_response(render_slide_io_metrics(&slides))
+}
+
+/// Build a response in Prometheus text exposition format.
+fn prometheus_response(body: String) -> Response {
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "text/plain; version=0.0.4")
+        .body(axum::body::Body::from(body))
+        .unwrap()
+}
+
+/// Handle slides list requests.
+///
+/// # Endpoint
+///
+/// `GET /slides`
+///
+/// # Query Parameters
+///
+/// - `limit`: Maximum number of slides to return (default: 100, max: 1000)
+/// - `cursor`: Continuation token for pagination (from previous response)
+/// - `prefix`: Only list slides under this key prefix (e.g. `case-123/`)
+/// - `delimiter`: Roll keys below the prefix up into `folders` at this
+///   separator (e.g. `/`), for navigating nested buckets one level at a time
+/// - `search`: Case-insensitive substring filter on slide paths
+/// - `sig`: Authentication signature (for signed URLs)
+/// - `exp`: Signature expiry timestamp (for signed URLs)
+///
+/// # Response
+///
+/// `200 OK` with JSON body:
+/// ```json
+/// {
+///   "slides": ["path/to/slide1.svs", "path/to/slide2.tif"],
+///   "folders": ["path/to/case-1/"],
+///   "next_cursor": "continuation_token_or_null",
+///   "conformance": {
+///     "path/to/slide2.tif": {
+///       "status": "unsupported",
+///       "reason": "TIFF uses strips instead of tiles",
+///       "checked_at": 1735689600
+///     }
+///   }
+/// }
+/// ```
+///
+/// `conformance` carries the last validation result of listed slides that
+/// have been opened: `valid`, `warnings` (with the warnings) or
+/// `unsupported` (with the reason).
+///
+/// # Errors
+///
+/// - `401 Unauthorized`: Invalid or missing signature
+/// - `500 Internal Server Error`: Storage error
+pub async fn slides_handler<S: SlideSource>(
     State(state): State<AppState<S>>,
     Query(query): Query<SlidesQueryParams>,
 ) -> Result<Json<SlidesResponse>, SlidesError> {
     // Clamp limit to valid range (1-1000)
     let limit = query.limit.clamp(1, 1000);
 
-    // List slides from the source with optional prefix filter
-    let result = state
+    // List slides from the source with optional prefix filter
+    let result = state
+        .tile_service
+        .registry()
+        .source()
+        .list_slides(
+            limit,
+            query.cursor.as_deref(),
+            query.prefix.as_deref(),
+            query.delimiter.as_deref().filter(|d| !d.is_empty()),
+        )
+        .await?;
+
+    // Skip quarantined slides, then apply the search filter if provided
+    // (case-insensitive substring match)
+    let quarantine = state.tile_service.registry().quarantine();
+    let search_lower = query.search.as_ref().map(|s| s.to_lowercase());
+    let slides: Vec<String> = result
+        .slides
+        .into_iter()
+        .filter(|s| !quarantine.is_quarantined(s))
+        .filter(|s| match search_lower {
+            Some(ref search) => s.to_lowercase().contains(search),
+            None => true,
+        })
+        .collect();
+
+    // Attach the last validation result so clients can flag slides that
+    // won't open before they are clicked
+    let known = state.tile_service.registry().conformance();
+    let conformance = slides
+        .iter()
+        .filter_map(|slide| known.get(slide).map(|c| (slide.clone(), c)))
+        .collect();
+
+    Ok(Json(SlidesResponse {
+        slides,
+        folders: result.folders,
+        next_cursor: result.next_cursor,
+        conformance,
+    }))
+}
+
+/// Handle slide metadata requests.
+///
+/// # Endpoint
+///
+/// `GET /slides/{slide_id}`
+///
+/// # Path Parameters
+///
+/// - `slide_id`: Slide identifier (URL-encoded if contains special characters)
+///
+/// # Response
+///
+/// `200 OK` with JSON body containing slide metadata:
+/// ```json
+/// {
+///   "slide_id": "path/to/slide.svs",
+///   "format": "aperio_svs",
+///   "width": 46920,
+///   "height": 33600,
+///   "level_count": 4,
+///   "levels": [
+///     {
+///       "level": 0,
+///       "width": 46920,
+///       "height": 33600,
+///       "tile_width": 256,
+///       "tile_height": 256,
+///       "tiles_x": 184,
+///       "tiles_y": 132,
+///       "downsample": 1.0
+///     }
+///   ]
+/// }
+/// ```
+///
+/// Once the slide has been checked against its checksum manifest (at open
+/// with `--verify-checksums`, or via `POST /slides/{slide_id}/verify`), the
+/// response also carries an `integrity` report.
+///
+/// # Errors
+///
+/// - `401 Unauthorized`: Invalid or missing signature (when auth enabled)
+/// - `404 Not Found`: Slide not found
+/// - `415 Unsupported Media Type`: Slide format not supported
+/// - `500 Internal Server Error`: Storage or processing error
+pub async fn slide_metadata_handler<S: SlideSource>(
+    State(state): State<AppState<S>>,
+    Path(slide_id): Path<String>,
+) -> Result<Json<SlideMetadataResponse>, SlideMetadataError> {
+    // Get slide from registry (opens and caches if needed)
+    let slide = state.tile_service.registry().get_slide(&slide_id).await?;
+
+    // Get dimensions (should always be available for valid slides)
+    let (width, height) = slide.dimensions().unwrap_or((0, 0));
+
+    // Build level metadata for each pyramid level
+    let level_count = slide.level_count();
+    let levels = level_metadata(&state.tile_service, &slide);
+
+    Ok(Json(SlideMetadataResponse {
+        slide_id,
+        format: slide.format().name().to_string(),
+        width,
+        height,
+        level_count,
+        levels,
+        integrity: slide.integrity(),
+    }))
+}
+
+/// Handle slide info requests.
+///
+/// # Endpoint
+///
+/// `GET /slides/{slide_id}/info`
+///
+/// Returns what a viewer needs to configure itself, so it doesn't have to
+/// discover the tile grid by probing for 404s.
+///
+/// # Response
+///
+/// `200 OK` with JSON body:
+/// ```json
+/// {
+///   "slide_id": "path/to/slide.svs",
+///   "format": "Aperio SVS",
+///   "vendor": "Aperio",
+///   "mpp": 0.499,
+///   "magnification": 20.0,
+///   "width": 46920,
+///   "height": 33600,
+///   "tile_width": 256,
+///   "tile_height": 256,
+///   "level_count": 3,
+///   "downsamples": [1.0, 4.0, 16.0],
+///   "levels": [...],
+///   "properties": {
+///     "aperio.AppMag": "20",
+///     "openslide.level-count": "3",
+///     "openslide.mpp-x": "0.4990",
+///     "openslide.objective-power": "20",
+///     "openslide.vendor": "aperio",
+///     ...
+///   }
+/// }
+/// ```
+///
+/// `vendor`, `mpp` and `magnification` are omitted when the slide doesn't
+/// record them. `properties` uses OpenSlide's property names, so clients
+/// written against OpenSlide can read the same keys.
+///
+/// # Errors
+///
+/// - `401 Unauthorized`: Invalid or missing signature (when auth enabled)
+/// - `404 Not Found`: Slide not found
+/// - `415 Unsupported Media Type`: Slide format not supported
+/// - `500 Internal Server Error`: Storage or processing error
+pub async fn slide_info_handler<S: SlideSource>(
+    State(state): State<AppState<S>>,
+    Path(slide_id): Path<String>,
+) -> Result<Json<SlideInfoResponse>, SlideMetadataError> {
+    let slide = state.tile_service.registry().get_slide(&slide_id).await?;
+
+    let (width, height) = slide.dimensions().unwrap_or((0, 0));
+    let (tile_width, tile_height) = state
+        .tile_service
+        .level_info(&slide, 0)
+        .map(|info| (info.tile_width, info.tile_height))
+        .unwrap_or((0, 0));
+    let levels = level_metadata(&state.tile_service, &slide);
+
+    Ok(Json(SlideInfoResponse {
+        slide_id,
+        format: slide.format().name().to_string(),
+        vendor: slide.vendor(),
+        mpp: slide.mpp(),
+        magnification: slide.magnification(),
+        width,
+        height,
+        tile_width,
+        tile_height,
+        level_count: slide.level_count(),
+        downsamples: levels.iter().map(|level| level.downsample).collect(),
+        levels,
+        properties: slide.properties(),
+    }))
+}
+
+/// Build the metadata of every pyramid level of a slide, as exposed by the
+/// tile service (virtual grid when retiling).
+fn level_metadata<S: SlideSource>(
+    service: &TileService<S>,
+    slide: &CachedSlide<S::Reader>,
+) -> Vec<LevelMetadataResponse> {
+    (0..slide.level_count())
+        .filter_map(|level| {
+            service
+                .level_info(slide, level)
+                .map(|info| LevelMetadataResponse {
+                    level,
+                    width: info.width,
+                    height: info.height,
+                    tile_width: info.tile_width,
+                    tile_height: info.tile_height,
+                    tiles_x: info.tiles_x,
+                    tiles_y: info.tiles_y,
+                    downsample: info.downsample,
+                })
+        })
+        .collect()
+}
+
+/// Query parameters for checksum verification.
+#[derive(Debug, Deserialize)]
+pub struct VerifyQueryParams {
+    /// `sampled` (default) or `full`
+    #[serde(default)]
+    pub mode: VerifyMode,
+}
+
+/// Handle checksum verification requests.
+///
+/// # Endpoint
+///
+/// `POST /slides/{slide_id}/verify?mode=sampled|full`
+///
+/// Verifies the slide against its manifest (`{slide_id}.sha256`). Sampled
+/// verification checks the file size and the blocks covering the header and
+/// a few tiles; full verification reads the whole file.
+///
+/// # Response
+///
+/// `200 OK` with the integrity report, which is also reported by
+/// `GET /slides/{slide_id}`:
+/// ```json
+/// {
+///   "status": "failed",
+///   "mode": "sampled",
+///   "checked_at": 1735689600,
+///   "bytes_checked": 3145728,
+///   "blocks_checked": 3,
+///   "mismatches": ["block 12"]
+/// }
+/// ```
+///
+/// `status` is one of `verified`, `failed`, `unverified` (invalid manifest,
+/// or nothing the mode can check) and `no_manifest`.
+///
+/// # Errors
+///
+/// - `404 Not Found`: Slide not found
+/// - `415 Unsupported Media Type`: Slide format not supported
+/// - `500 Internal Server Error`: Storage error
+pub async fn verify_slide_handler<S: SlideSource>(
+    State(state): State<AppState<S>>,
+    Path(slide_id): Path<String>,
+    Query(params): Query<VerifyQueryParams>,
+) -> Result<Json<IntegrityReport>, SlideMetadataError> {
+    let report = state
+        .tile_service
+        .registry()
+        .verify_slide(&slide_id, params.mode)
+        .await?;
+
+    if report.status == IntegrityStatus::Failed {
+        warn!(
+            slide_id = %slide_id,
+            mismatches = ?report.mismatches,
+            "Slide failed checksum verification"
+        );
+    }
+
+    Ok(Json(report))
+}
+
+/// Level map of an open slide (see [`LevelMap`]), on the tile service's grid.
+fn level_map<S: SlideSource>(service: &TileService<S>, slide: &CachedSlide<S::Reader>) -> LevelMap {
+    LevelMap::new(&service.levels(slide))
+}
+
+/// Response for the level mapping endpoint.
+#[derive(Debug, Serialize)]
+pub struct SlideLevelsResponse {
+    /// Slide identifier
+    pub slide_id: String,
+
+    /// Native, Deep Zoom and IIIF levels
+    #[serde(flatten)]
+    pub levels: LevelMap,
+}
+
+/// Handle level mapping requests.
+///
+/// # Endpoint
+///
+/// `GET /slides/{slide_id}/levels`
+///
+/// # Response
+///
+/// `200 OK` with the mapping between native pyramid levels, Deep Zoom
+/// levels and IIIF scale factors:
+/// ```json
+/// {
+///   "slide_id": "path/to/slide.svs",
+///   "width": 46920,
+///   "height": 33600,
+///   "tile_width": 256,
+///   "tile_height": 256,
+///   "max_dzi_level": 16,
+///   "native_levels": [
+///     { "level": 0, "width": 46920, "height": 33600, "downsample": 1.0, "dzi_level": 16 }
+///   ],
+///   "levels": [
+///     {
+///       "dzi_level": 16,
+///       "iiif_scale_factor": 1,
+///       "downsample": 1.0,
+///       "width": 46920,
+///       "height": 33600,
+///       "tiles_x": 184,
+///       "tiles_y": 132,
+///       "source_level": 0,
+///       "source_downsample": 1.0,
+///       "synthesized": false
+///     }
+///   ]
+/// }
+/// ```
+///
+/// `levels` is indexed by Deep Zoom level; synthesized levels are rendered
+/// by downscaling `source_level`.
+///
+/// # Errors
+///
+/// - `404 Not Found`: Slide not found
+/// - `415 Unsupported Media Type`: Slide format not supported
+/// - `500 Internal Server Error`: Storage or processing error
+pub async fn slide_levels_handler<S: SlideSource>(
+    State(state): State<AppState<S>>,
+    Path(slide_id): Path<String>,
+) -> Result<Json<SlideLevelsResponse>, SlideMetadataError> {
+    let slide = state.tile_service.registry().get_slide(&slide_id).await?;
+    let levels = level_map(&state.tile_service, &slide);
+
+    Ok(Json(SlideLevelsResponse { slide_id, levels }))
+}
+
+/// Query parameters for tile grid requests.
+#[derive(Debug, Deserialize)]
+pub struct TileGridQueryParams {
+    /// Include the byte size of each tile (default: true)
+    #[serde(default = "default_grid_sizes")]
+    pub sizes: bool,
+}
+
+fn default_grid_sizes() -> bool {
+    true
+}
+
+/// Response for the tile grid endpoint.
+#[derive(Debug, Serialize)]
+pub struct TileGridResponse {
+    /// Slide identifier
+    pub slide_id: String,
+
+    /// Pyramid level
+    pub level: usize,
+
+    /// Number of tile columns
+    pub tiles_x: u32,
+
+    /// Number of tile rows
+    pub tiles_y: u32,
+
+    /// Tile width in pixels
+    pub tile_width: u32,
+
+    /// Tile height in pixels
+    pub tile_height: u32,
+
+    /// Number of tiles stored in the file
+    pub present: usize,
+
+    /// Number of tiles missing from the file (background only)
+    pub missing: usize,
+
+    /// Presence bitmap (hex): one bit per tile in row-major order, most
+    /// significant bit first
+    pub bitmap: String,
+
+    /// Stored size of each tile in row-major order (0 when missing)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub byte_counts: Option<Vec<u64>>,
+}
+
+/// Handle tile grid requests - reports which tiles of a level are stored.
+///
+/// Annotation tools use this to skip empty regions of sparse slides
+/// without fetching their tiles. The grid is read from the level's tile
+/// tables, so no tile data is fetched.
+///
+/// # Endpoint
+///
+/// `GET /slides/{slide_id}/levels/{level}/grid`
+///
+/// # Query Parameters
+///
+/// - `sizes`: Include the byte size of each tile (default: true)
+///
+/// # Response
+///
+/// `200 OK` with JSON body:
+/// ```json
+/// {
+///   "slide_id": "slide.tif",
+///   "level": 0,
+///   "tiles_x": 3,
+///   "tiles_y": 2,
+///   "tile_width": 256,
+///   "tile_height": 256,
+///   "present": 4,
+///   "missing": 2,
+///   "bitmap": "b8",
+///   "byte_counts": [18234, 0, 17020, 16544, 19002, 0]
+/// }
+/// ```
+///
+/// # Errors
+///
+/// - `400 Bad Request`: Level out of range
+/// - `404 Not Found`: Slide not found, or the level is stored in strips or
+///   read by a format plugin and has no tile tables
+pub async fn tile_grid_handler<S: SlideSource>(
+    State(state): State<AppState<S>>,
+    Path((slide_id, level)): Path<(String, usize)>,
+    Query(query): Query<TileGridQueryParams>,
+) -> Result<Response, HandlerError> {
+    let slide = state.tile_service.open_slide(&slide_id).await?;
+    let (tile_width, tile_height) = slide.tile_size(level).ok_or(TileError::InvalidLevel {
+        level,
+        max_levels: slide.level_count(),
+    })?;
+
+    let Some(grid) = slide.tile_grid(level) else {
+        let body = ErrorResponse::with_status(
+            "not_found",
+            format!("Level {} has no tile grid", level),
+            StatusCode::NOT_FOUND,
+        );
+        return Ok((StatusCode::NOT_FOUND, Json(body)).into_response());
+    };
+
+    Ok(Json(TileGridResponse {
+        slide_id,
+        level,
+        tiles_x: grid.tiles_x(),
+        tiles_y: grid.tiles_y(),
+        tile_width,
+        tile_height,
+        present: grid.present(),
+        missing: grid.missing(),
+        bitmap: hex::encode(grid.bitmap()),
+        byte_counts: query.sizes.then(|| grid.byte_counts().to_vec()),
+    })
+    .into_response())
+}
+
+/// Handle raw slide requests - serves bytes of the original slide file.
+///
+/// # Endpoint
+///
+/// `GET /slides/{slide_id}/raw`
+///
+/// # Request Headers
+///
+/// - `Range`: A single byte range, e.g. `bytes=0-65535`, `bytes=1024-` or
+///   `bytes=-8192`. Multiple ranges are not supported; the whole file is sent.
+/// - `If-Range`: Only honor `Range` if the file still has this ETag or
+///   modification date
+///
+/// # Response
+///
+/// `206 Partial Content` with the requested bytes and `Content-Range`, or
+/// `200 OK` with the whole file. The body is streamed from storage through
+/// the block cache. `Accept-Ranges: bytes` is always set, and `ETag` and
+/// `Last-Modified` when the storage backend reports them. `HEAD` returns
+/// the same headers without reading the file.
+///
+/// # Errors
+///
+/// - `401 Unauthorized`: Invalid or missing signature (when auth enabled)
+/// - `404 Not Found`: Slide not found
+/// - `415 Unsupported Media Type`: Slide format not supported
+/// - `416 Range Not Satisfiable`: The range starts past the end of the file
+/// - `500 Internal Server Error`: Storage or processing error
+pub async fn raw_slide_handler<S: SlideSource>(
+    State(state): State<AppState<S>>,
+    Path(slide_id): Path<String>,
+    method: Method,
+    headers: HeaderMap,
+) -> Result<Response, SlideMetadataError> {
+    let slide = state.tile_service.registry().get_slide(&slide_id).await?;
+    let size = slide.file_size();
+    let etag = slide.source_etag().map(str::to_string);
+    let last_modified = slide.last_modified();
+
+    let (status, range) = match range_request(&headers, size, etag.as_deref(), last_modified) {
+        RangeRequest::Unsatisfiable => {
+            return Ok(Response::builder()
+                .status(StatusCode::RANGE_NOT_SATISFIABLE)
+                .header(header::CONTENT_RANGE, format!("bytes */{}", size))
+                .body(axum::body::Body::empty())
+                .unwrap());
+        }
+        RangeRequest::Partial(range) => (StatusCode::PARTIAL_CONTENT, Some(range)),
+        RangeRequest::Full => (StatusCode::OK, None),
+    };
+    let (start, len) = match range {
+        Some(range) => (range.start, range.len()),
+        None => (0, size),
+    };
+
+    let mut builder = Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "application/octet-stream")
+        .header(header::CONTENT_LENGTH, len.to_string())
+        .header(header::ACCEPT_RANGES, "bytes");
+    if let Some(range) = range {
+        builder = builder.header(header::CONTENT_RANGE, range.content_range(size));
+    }
+    if let Some(etag) = &etag {
+        builder = builder.header(header::ETAG, etag);
+    }
+    if let Some(modified) = last_modified {
+        builder = builder.header(header::LAST_MODIFIED, format_http_date(modified));
+    }
+
+    // HEAD requests get the headers only; don't start reading storage
+    if method == Method::HEAD {
+        return Ok(builder.body(axum::body::Body::empty()).unwrap());
+    }
+
+    let body = spawned_body(move |tx| async move {
+        let end = start + len;
+        let mut offset = start;
+        while offset < end {
+            let chunk_len = (end - offset).min(RAW_READ_CHUNK_SIZE as u64) as usize;
+            let chunk = slide.read_raw(offset, chunk_len).await.map_err(|e| {
+                warn!(slide_id = %slide_id, offset, error = %e, "Raw slide read failed");
+                std::io::Error::other(e.to_string())
+            });
+            let failed = chunk.is_err();
+            if tx.send(chunk).await.is_err() || failed {
+                return;
+            }
+            offset += chunk_len as u64;
+        }
+    });
+
+    Ok(builder.body(body).unwrap())
+}
+
+/// Handle viewer requests - serves an HTML page with OpenSeadragon viewer.
+///
+/// # Endpoint
+///
+/// `GET /view/{slide_id}`
+///
+/// # Path Parameters
+///
+/// - `slide_id`: Slide identifier (URL-encoded if contains special characters)
+///
+/// # Response
+///
+/// `200 OK` with HTML page containing an embedded OpenSeadragon viewer.
+///
+/// # Errors
+///
+/// - `404 Not Found`: Slide not found
+/// - `415 Unsupported Media Type`: Slide format not supported
+/// - `500 Internal Server Error`: Storage or processing error
+pub async fn viewer_handler<S: SlideSource>(
+    State(state): State<AppState<S>>,
+    Path(slide_id): Path<String>,
+    headers: HeaderMap,
+) -> Result<Html<String>, SlideMetadataError> {
+    // Get slide from registry to retrieve metadata
+    let slide = state.tile_service.registry().get_slide(&slide_id).await?;
+    let auth_query = viewer_auth_query(&state, &slide_id);
+
+    Ok(render_viewer(
+        &state.tile_service,
+        &slide_id,
+        &slide,
+        &headers,
+        &auth_query,
+    ))
+}
+
+/// Render the viewer page for an open slide.
+fn render_viewer<S: SlideSource>(
+    service: &TileService<S>,
+    slide_id: &str,
+    slide: &CachedSlide<S::Reader>,
+    headers: &HeaderMap,
+    auth_query: &str,
+) -> Html<String> {
+    let metadata = viewer_metadata(service, slide_id, slide);
+    let base_url = request_base_url(headers);
+
+    // Generate the viewer HTML with auth info
+    let html = super::viewer::generate_viewer_html(slide_id, &metadata, &base_url, auth_query);
+
+    Html(html)
+}
+
+/// Slide metadata embedded in viewer pages.
+fn viewer_metadata<S: SlideSource>(
+    service: &TileService<S>,
+    slide_id: &str,
+    slide: &CachedSlide<S::Reader>,
+) -> SlideMetadataResponse {
+    let (width, height) = slide.dimensions().unwrap_or((0, 0));
+
+    SlideMetadataResponse {
+        slide_id: slide_id.to_string(),
+        format: slide.format().name().to_string(),
+        width,
+        height,
+        level_count: slide.level_count(),
+        levels: level_metadata(service, slide),
+        integrity: slide.integrity(),
+    }
+}
+
+/// Query string authorizing a viewer's tile requests for a slide.
+///
+/// With auth enabled this is a viewer token valid for 1 hour, covering all
+/// tiles of the slide; otherwise it is empty.
+fn viewer_auth_query<S: SlideSource>(state: &AppState<S>, slide_id: &str) -> String {
+    state
+        .auth
+        .as_ref()
+        .map(|auth| {
+            let ttl = Duration::from_secs(3600);
+            let (token, expiry) = auth.generate_viewer_token(slide_id, ttl);
+            format!("?vt={}&exp={}", token, expiry)
+        })
+        .unwrap_or_default()
+}
+
+/// Query parameters for the comparison viewer.
+#[derive(Debug, Deserialize)]
+pub struct CompareQueryParams {
+    /// Comma-separated slide IDs, shown left to right
+    #[serde(default)]
+    pub slides: String,
+}
+
+/// Handle comparison viewer requests - serves an HTML page showing slides
+/// side by side with synchronized pan and zoom.
+///
+/// # Endpoint
+///
+/// `GET /view/compare?slides={slide_id},{slide_id}`
+///
+/// # Query Parameters
+///
+/// - `slides`: 2 to 4 comma-separated slide identifiers (URL-encoded), shown
+///   left to right
+///
+/// # Response
+///
+/// `200 OK` with HTML page containing one OpenSeadragon viewer per slide.
+/// With auth enabled, each slide gets its own viewer token.
+///
+/// # Errors
+///
+/// - `400 Bad Request`: Fewer than 2 or more than 4 slides
+/// - `404 Not Found`: A slide was not found
+/// - `415 Unsupported Media Type`: A slide format is not supported
+/// - `500 Internal Server Error`: Storage or processing error
+pub async fn compare_viewer_handler<S: SlideSource>(
+    State(state): State<AppState<S>>,
+    Query(query): Query<CompareQueryParams>,
+    headers: HeaderMap,
+) -> Result<Response, SlideMetadataError> {
+    let slide_ids: Vec<&str> = query
+        .slides
+        .split(',')
+        .map(str::trim)
+        .filter(|slide_id| !slide_id.is_empty())
+        .collect();
+    if !(2..=MAX_COMPARE_SLIDES).contains(&slide_ids.len()) {
+        let body = ErrorResponse::with_status(
+            "invalid_compare",
+            format!(
+                "slides must list 2 to {} comma-separated slide IDs",
+                MAX_COMPARE_SLIDES
+            ),
+            StatusCode::BAD_REQUEST,
+        );
+        return Ok((StatusCode::BAD_REQUEST, Json(body)).into_response());
+    }
+
+    let mut panes = Vec::with_capacity(slide_ids.len());
+    for slide_id in slide_ids {
+        let slide = state.tile_service.registry().get_slide(slide_id).await?;
+        panes.push(ComparePane {
+            metadata: viewer_metadata(&state.tile_service, slide_id, &slide),
+            auth_query: viewer_auth_query(&state, slide_id),
+        });
+    }
+
+    let html = generate_compare_html(&panes, &request_base_url(&headers));
+    Ok(Html(html).into_response())
+}
+
+/// Query parameters for the event channel.
+#[derive(Debug, Deserialize)]
+pub struct EventsQueryParams {
+    /// Only events about these comma-separated slide IDs
+    pub slides: Option<String>,
+
+    /// Only these comma-separated event types
+    pub events: Option<String>,
+}
+
+/// Handle GET /ws - WebSocket channel of server events.
+///
+/// Upgrades the connection to a WebSocket and sends each server event
+/// (slide opens, warm-up progress, evictions, failed tiles) as a JSON text
+/// message with a `type` field.
+///
+/// # Query Parameters
+///
+/// - `slides`: Only events about these comma-separated slide IDs
+/// - `events`: Only these comma-separated event types
+///
+/// # Response
+///
+/// `101 Switching Protocols`, then one text message per event.
+///
+/// # Errors
+///
+/// - `400 Bad Request`: Unknown event type
+/// - `426 Upgrade Required`: Not a WebSocket upgrade request (or sent over
+///   HTTP/2)
+pub async fn events_handler<S: SlideSource>(
+    State(state): State<AppState<S>>,
+    Query(query): Query<EventsQueryParams>,
+    upgrade: Result<WebSocketUpgrade, WebSocketUpgradeRejection>,
+) -> Response {
+    let filter = match EventFilter::parse(query.slides.as_deref(), query.events.as_deref()) {
+        Ok(filter) => filter,
+        Err(message) => {
+            let body =
+                ErrorResponse::with_status("invalid_events", message, StatusCode::BAD_REQUEST);
+            return (StatusCode::BAD_REQUEST, Json(body)).into_response();
+        }
+    };
+
+    let Ok(upgrade) = upgrade else {
+        let body = ErrorResponse::with_status(
+            "upgrade_required",
+            "This endpoint requires a WebSocket upgrade over HTTP/1.1",
+            StatusCode::UPGRADE_REQUIRED,
+        );
+        let mut response = (StatusCode::UPGRADE_REQUIRED, Json(body)).into_response();
+        response.headers_mut().insert(
+            header::UPGRADE,
+            header::HeaderValue::from_static("websocket"),
+        );
+        return response;
+    };
+
+    // Subscribe before answering so no event published meanwhile is missed
+    let events = state.tile_service.events().subscribe();
+    upgrade
+        .max_message_size(MAX_CLIENT_MESSAGE_BYTES)
+        .on_failed_upgrade(|e| debug!(error = %e, "WebSocket upgrade failed"))
+        .on_upgrade(move |socket| serve_events(socket, events, filter))
+}
+
+/// Query parameters for creating a share link.
+#[derive(Debug, Deserialize)]
+pub struct ShareQueryParams {
+    /// Link lifetime in seconds (default 24 hours, at most 30 days)
+    pub ttl: Option<u64>,
+
+    /// Maximum number of tile requests served through the link
+    pub max_tiles: Option<u64>,
+}
+
+/// Response for a created share link.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShareLinkResponse {
+    /// Slide the link gives access to
+    pub slide_id: String,
+
+    /// Share token, accepted as the `st` query parameter
+    pub token: String,
+
+    /// Unix timestamp when the link expires
+    pub expires_at: u64,
+
+    /// Tile request limit, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_tiles: Option<u64>,
+
+    /// Viewer page for the link
+    pub url: String,
+}
+
+/// Handle share link creation.
+///
+/// # Endpoint
+///
+/// `POST /slides/{slide_id}/share?ttl={seconds}&max_tiles={n}`
+///
+/// # Response
+///
+/// `201 Created` with the link:
+/// ```json
+/// {
+///   "slide_id": "sample.svs",
+///   "token": "73616d706c652e737673.1735689600.500.9f2c...",
+///   "expires_at": 1735689600,
+///   "max_tiles": 500,
+///   "url": "https://example.com/share/73616d706c652e737673.1735689600.500.9f2c.../view"
+/// }
+/// ```
+///
+/// The token grants read-only access to this slide only, needs no account
+/// and cannot be extended or widened without invalidating its signature.
+///
+/// # Errors
+///
+/// - `400 Bad Request`: `ttl` is zero or longer than 30 days, or `max_tiles` is zero
+/// - `404 Not Found`: Slide not found, or authentication is disabled
+/// - `415 Unsupported Media Type`: Slide format not supported
+pub async fn share_handler<S: SlideSource>(
+    State(state): State<AppState<S>>,
+    Path(slide_id): Path<String>,
+    Query(params): Query<ShareQueryParams>,
+    headers: HeaderMap,
+) -> Result<Response, SlideMetadataError> {
+    let Some(auth) = state.auth.as_ref() else {
+        let body = ErrorResponse::with_status(
+            "not_found",
+            "Share links require authentication to be enabled",
+            StatusCode::NOT_FOUND,
+        );
+        return Ok((StatusCode::NOT_FOUND, Json(body)).into_response());
+    };
+
+    let ttl = params.ttl.map_or(DEFAULT_SHARE_TTL, Duration::from_secs);
+    if ttl.is_zero() || ttl > MAX_SHARE_TTL || params.max_tiles == Some(0) {
+        let body = ErrorResponse::with_status(
+            "invalid_share",
+            format!(
+                "ttl must be between 1 and {} seconds and max_tiles at least 1",
+                MAX_SHARE_TTL.as_secs()
+            ),
+            StatusCode::BAD_REQUEST,
+        );
+        return Ok((StatusCode::BAD_REQUEST, Json(body)).into_response());
+    }
+
+    // Only share slides that exist
+    state.tile_service.registry().get_slide(&slide_id).await?;
+
+    let (token, expires_at) = auth.generate_share_token(&slide_id, ttl, params.max_tiles);
+    let url = format!("{}/share/{}/view", request_base_url(&headers), token);
+
+    info!(
+        slide_id = %slide_id,
+        expires_at = expires_at,
+        max_tiles = ?params.max_tiles,
+        "Created share link"
+    );
+
+    let response = ShareLinkResponse {
+        slide_id,
+        token,
+        expires_at,
+        max_tiles: params.max_tiles,
+        url,
+    };
+    Ok((StatusCode::CREATED, Json(response)).into_response())
+}
+
+/// Handle share link viewer requests.
+///
+/// # Endpoint
+///
+/// `GET /share/{token}/view`
+///
+/// # Response
+///
+/// `200 OK` with the viewer page; its tile requests carry the share token.
+///
+/// # Errors
+///
+/// - `400 Bad Request`: Malformed token
+/// - `401 Unauthorized`: Invalid or expired token
+/// - `404 Not Found`: Slide not found, or authentication is disabled
+pub async fn share_viewer_handler<S: SlideSource>(
+    State(state): State<AppState<S>>,
+    Path(token): Path<String>,
+    headers: HeaderMap,
+) -> Result<Response, SlideMetadataError> {
+    let Some(auth) = state.auth.as_ref() else {
+        let body = ErrorResponse::with_status(
+            "not_found",
+            "Share links require authentication to be enabled",
+            StatusCode::NOT_FOUND,
+        );
+        return Ok((StatusCode::NOT_FOUND, Json(body)).into_response());
+    };
+
+    let policy = match auth.verify_share_token(&token) {
+        Ok(policy) => policy,
+        Err(err) => return Ok(err.into_response()),
+    };
+
+    let slide = state
+        .tile_service
+        .registry()
+        .get_slide(&policy.slide_id)
+        .await?;
+
+    // The token is URL-safe (hex, digits and dots)
+    let auth_query = format!("?st={}", token);
+    Ok(render_viewer(
+        &state.tile_service,
+        &policy.slide_id,
+        &slide,
+        &headers,
+        &auth_query,
+    )
+    .into_response())
+}
+
+/// Absolute base URL of the server as seen by the client.
+fn request_base_url(headers: &HeaderMap) -> String {
+    // Extract host from headers, defaulting to localhost:3000
+    let host = headers
+        .get(header::HOST)
+        .and_then(|h| h.to_str().ok())
+        .unwrap_or("localhost:3000");
+
+    // Detect protocol from X-Forwarded-Proto header (for reverse proxy support)
+    // or default to http for local development
+    let proto = headers
+        .get("x-forwarded-proto")
+        .and_then(|h| h.to_str().ok())
+        .unwrap_or("http");
+
+    format!("{}://{}", proto, host)
+}
+
+/// Handle DZI descriptor requests - returns XML descriptor for Deep Zoom viewers.
+///
+/// # Endpoint
+///
+/// `GET /slides/{slide_id}/dzi`
+///
+/// # Path Parameters
+///
+/// - `slide_id`: Slide identifier
+///
+/// # Response
+///
+/// `200 OK` with XML body containing DZI descriptor.
+///
+/// # Example Response
+///
+/// ```xml
+/// <?xml version="1.0" encoding="UTF-8"?>
+/// <Image xmlns="http://schemas.microsoft.com/deepzoom/2008"
+///        TileSize="256"
+///        Overlap="0"
+///        Format="jpg">
+///   <Size Width="46920" Height="33600" />
+/// </Image>
+/// ```
+///
+/// # Errors
+///
+/// - `404 Not Found`: Slide not found
+/// - `415 Unsupported Media Type`: Slide format not supported
+/// - `500 Internal Server Error`: Storage or processing error
+pub async fn dzi_descriptor_handler<S: SlideSource>(
+    State(state): State<AppState<S>>,
+    Path(slide_id): Path<String>,
+) -> Result<Response, SlideMetadataError> {
+    dzi_descriptor_response(&state, &slide_id).await
+}
+
+/// Build the DZI descriptor response for a slide.
+async fn dzi_descriptor_response<S: SlideSource>(
+    state: &AppState<S>,
+    slide_id: &str,
+) -> Result<Response, SlideMetadataError> {
+    // Get slide from registry
+    let slide = state.tile_service.registry().get_slide(slide_id).await?;
+
+    // Get dimensions
+    let (width, height) = slide.dimensions().unwrap_or((0, 0));
+
+    // Get tile size from level 0 (or default)
+    let tile_size = state
+        .tile_service
+        .level_info(&slide, 0)
+        .map(|info| info.tile_width)
+        .unwrap_or(256);
+
+    // Generate DZI XML
+    let xml = super::dzi::generate_dzi_xml(width, height, tile_size);
+
+    // Build response with XML content type
+    let response = Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "application/xml")
+        .header(
+            header::CACHE_CONTROL,
+            format!("public, max-age={}", state.cache_max_age()),
+        )
+        .body(axum::body::Body::from(xml))
+        .unwrap();
+
+    Ok(response)
+}
+
+/// Handle Deep Zoom descriptor requests at the standard DZI location.
+///
+/// # Endpoint
+///
+/// `GET /dzi/{slide_id}.dzi`
+///
+/// Same descriptor as `/slides/{slide_id}/dzi`, but at the URL layout
+/// Deep Zoom viewers derive tile URLs from, so OpenSeadragon can open it
+/// as a plain DZI tile source.
+///
+/// # Errors
+///
+/// - `404 Not Found`: Slide not found, or the path doesn't end in `.dzi`
+/// - `415 Unsupported Media Type`: Slide format not supported
+/// - `500 Internal Server Error`: Storage or processing error
+pub async fn dzi_file_handler<S: SlideSource>(
+    State(state): State<AppState<S>>,
+    Path(name): Path<String>,
+) -> Result<Response, SlideMetadataError> {
+    let slide_id = parse_dzi_descriptor_name(&name)
+        .ok_or_else(|| FormatError::Io(IoError::NotFound(name.clone())))?;
+    dzi_descriptor_response(&state, slide_id).await
+}
+
+/// Handle Deep Zoom tile requests.
+///
+/// # Endpoint
+///
+/// `GET /dzi/{slide_id}_files/{level}/{x}_{y}.jpg`
+///
+/// # Path Parameters
+///
+/// - `level`: DZI level (0 = 1x1 pixel, max = full resolution)
+/// - `x`, `y`: Tile coordinates at that level
+///
+/// # Query Parameters
+///
+/// - `quality`: JPEG quality 1-100 (default: 80)
+/// - `filter`: Resampling filter, `nearest`, `bilinear` or `lanczos3` (default: server setting)
+///
+/// Each tile is rendered from the pyramid level closest to the DZI level
+/// without upsampling; levels below the smallest pyramid level are
+/// synthesized by downscaling it. Edge tiles are cropped to the level.
+/// Rendered tiles are cached, and carry the same `ETag` and
+/// `Last-Modified` validators as pyramid tiles, so conditional requests
+/// are answered with `304 Not Modified` without rendering.
+///
+/// # Errors
+///
+/// - `400 Bad Request`: Invalid level, coordinates or quality
+/// - `404 Not Found`: Slide not found
+/// - `500 Internal Server Error`: Storage or processing error
+pub async fn dzi_tile_handler<S: SlideSource>(
+    State(state): State<AppState<S>>,
+    Path((files, level, filename)): Path<(String, usize, String)>,
+    Query(query): Query<DziTileQueryParams>,
+    headers: HeaderMap,
+) -> Result<Response, HandlerError> {
+    if !is_valid_quality(query.quality) {
+        return Err(TileError::InvalidQuality {
+            quality: query.quality,
+        }
+        .into());
+    }
+    let slide_id = parse_dzi_files_name(&files).ok_or_else(|| TileError::SlideNotFound {
+        slide_id: files.clone(),
+    })?;
+
+    let slide = state.tile_service.open_slide(slide_id).await?;
+    let levels = level_map(&state.tile_service, &slide);
+    let dzi = levels.dzi_level(level).ok_or(TileError::InvalidLevel {
+        level,
+        max_levels: levels.max_dzi_level + 1,
+    })?;
+
+    let out_of_bounds = |x: u32, y: u32| TileError::TileOutOfBounds {
+        level,
+        x,
+        y,
+        max_x: dzi.tiles_x,
+        max_y: dzi.tiles_y,
+    };
+    let (x, y) = parse_dzi_tile_coords(&filename).ok_or_else(|| out_of_bounds(0, 0))?;
+    let (left, top, tile_width, tile_height) =
+        dzi_tile_bounds(dzi.width, dzi.height, levels.tile_width, x, y)
+            .ok_or_else(|| out_of_bounds(x, y))?;
+
+    // Map the tile back to level 0, clipping the rounded-up level size
+    let region_x = left as f64 * dzi.downsample;
+    let region_y = top as f64 * dzi.downsample;
+    let mut plan = plan_region(
+        region_x,
+        region_y,
+        (tile_width as f64 * dzi.downsample).min(levels.width as f64 - region_x),
+        (tile_height as f64 * dzi.downsample).min(levels.height as f64 - region_y),
+        tile_width,
+        tile_height,
+        &levels.native_downsamples(),
+    );
+    // Clipped edge tiles scale slightly differently; read from the level
+    // the level map advertises so every tile of a DZI level agrees
+    plan.level = dzi.source_level;
+    plan.level_downsample = dzi.source_downsample;
+
+    // Validators come from the slide file, like pyramid tiles, with the
+    // resampling filter standing in for the render settings
+    let filter = query.filter.unwrap_or(state.tile_service.resample_filter());
+    let render_settings = format!("dzi;filter={}", filter);
+    let etag = TileValidator {
+        slide_id,
+        source_etag: slide.source_etag(),
+        source_size: slide.file_size(),
+        level,
+        x,
+        y,
+        quality: query.quality,
+        format: OutputFormat::Jpeg.name(),
+        render_settings: &render_settings,
+    }
+    .etag();
+    let last_modified = slide.last_modified();
+    let cache_control = format!("public, max-age={}", state.cache_max_age());
+
+    if is_not_modified(&headers, &etag, last_modified) {
+        let mut builder = Response::builder()
+            .status(StatusCode::NOT_MODIFIED)
+            .header(header::ETAG, etag.as_str())
+            .header(header::CACHE_CONTROL, cache_control);
+        if let Some(modified) = last_modified {
+            builder = builder.header(header::LAST_MODIFIED, format_http_date(modified));
+        }
+        return Ok(builder.body(axum::body::Body::empty()).unwrap());
+    }
+
+    let request = TileRequest::with_quality(slide_id, level, x, y, query.quality);
+    let response = state
+        .tile_service
+        .get_deep_zoom_tile(&request, &plan, filter)
+        .await?;
+
+    let mut builder = Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "image/jpeg")
+        .header(header::CACHE_CONTROL, cache_control)
+        .header("X-Tile-Cache-Hit", response.cache_hit.to_string())
+        .header("X-Tile-Source-Level", plan.level.to_string())
+        .header(header::ETAG, etag);
+    if let Some(modified) = last_modified {
+        builder = builder.header(header::LAST_MODIFIED, format_http_date(modified));
+    }
+
+    Ok(builder.body(axum::body::Body::from(response.data)).unwrap())
+}
+
+/// Path parameters for IIIF image requests.
+#[derive(Debug, Deserialize)]
+pub struct IiifImageParams {
+    /// Slide identifier
+    pub slide_id: String,
+
+    /// Region of the full image (`full`, `square`, `x,y,w,h`, `pct:x,y,w,h`)
+    pub region: String,
+
+    /// Output size (`max`, `w,`, `,h`, `pct:n`, `w,h`, `!w,h`, optionally `^`-prefixed)
+    pub size: String,
+
+    /// Rotation in degrees, `!`-prefixed to mirror
+    pub rotation: String,
+
+    /// `{quality}.{format}`, e.g. `default.jpg`
+    pub quality_format: String,
+}
+
+/// Query parameters for IIIF image requests.
+#[derive(Debug, Default, Deserialize)]
+pub struct IiifQueryParams {
+    /// Resampling filter (defaults to the server setting)
+    #[serde(default)]
+    pub filter: Option<ResampleFilter>,
+}
+
+/// Handle IIIF base URI requests by redirecting to the image information.
+///
+/// # Endpoint
+///
+/// `GET /iiif/{slide_id}`
+///
+/// # Response
+///
+/// `303 See Other` to `/iiif/{slide_id}/info.json`.
+pub async fn iiif_redirect_handler(Path(slide_id): Path<String>) -> Response {
+    let location = format!("{}/info.json", urlencoding::encode(&slide_id));
+    (StatusCode::SEE_OTHER, [(header::LOCATION, location)]).into_response()
+}
+
+/// Handle IIIF image information requests.
+///
+/// # Endpoint
+///
+/// `GET /iiif/{slide_id}/info.json`
+///
+/// # Response
+///
+/// `200 OK` with an IIIF Image API 3.0 `info.json` document. The `id` is
+/// derived from the `Host` and `X-Forwarded-Proto` headers.
+///
+/// # Errors
+///
+/// - `404 Not Found`: Slide not found
+/// - `415 Unsupported Media Type`: Slide format not supported
+/// - `500 Internal Server Error`: Storage or processing error
+pub async fn iiif_info_handler<S: SlideSource>(
+    State(state): State<AppState<S>>,
+    Path(slide_id): Path<String>,
+    headers: HeaderMap,
+) -> Result<Response, IiifError> {
+    let slide = state.tile_service.open_slide(&slide_id).await?;
+    let levels = level_map(&state.tile_service, &slide);
+
+    let id = format!(
+        "{}/iiif/{}",
+        request_base_url(&headers),
+        urlencoding::encode(&slide_id)
+    );
+    let info = build_info(id, &levels);
+
+    let response = Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, IIIF_INFO_CONTENT_TYPE)
+        .header(
+            header::CACHE_CONTROL,
+            format!("public, max-age={}", state.cache_max_age()),
+        )
+        .body(axum::body::Body::from(
+            serde_json::to_vec(&info).unwrap_or_default(),
+        ))
+        .unwrap();
+
+    Ok(response)
+}
+
+/// Handle IIIF image requests.
+///
+/// # Endpoint
+///
+/// `GET /iiif/{slide_id}/{region}/{size}/{rotation}/{quality}.{format}`
+///
+/// The region is stitched from the tiles of the lowest-resolution pyramid
+/// level that covers the output size, then mirrored, rotated and converted
+/// to the requested quality. The optional `filter` query parameter selects
+/// the resampling filter.
+///
+/// # Response
+///
+/// `200 OK` with a JPEG image.
+///
+/// # Errors
+///
+/// - `400 Bad Request`: Malformed parameter, region outside the image, or
+///   size above `maxArea` or upscaling without `^`
+/// - `404 Not Found`: Slide not found
+/// - `501 Not Implemented`: Arbitrary rotation angles or non-`jpg` formats
+/// - `500 Internal Server Error`: Storage or processing error
+pub async fn iiif_image_handler<S: SlideSource>(
+    State(state): State<AppState<S>>,
+    Path(params): Path<IiifImageParams>,
+    Query(query): Query<IiifQueryParams>,
+) -> Result<Response, IiifError> {
+    // Validate everything that doesn't need the slide first
+    let rotation = parse_rotation(&params.rotation)?;
+    let quality = parse_quality_format(&params.quality_format)?;
+
+    let slide = state.tile_service.open_slide(&params.slide_id).await?;
+    let levels = level_map(&state.tile_service, &slide);
+
+    let region = parse_region(&params.region, levels.width, levels.height)?;
+    let (out_width, out_height) = parse_size(&params.size, &region, IIIF_MAX_AREA)?;
+
+    let plan = plan_region(
+        region.x as f64,
+        region.y as f64,
+        region.width as f64,
+        region.height as f64,
+        out_width,
+        out_height,
+        &levels.native_downsamples(),
+    );
+    let canvas = state
+        .tile_service
+        .render_region_with_filter(
+            &params.slide_id,
+            &plan,
+            DEFAULT_JPEG_QUALITY,
+            query.filter.unwrap_or(state.tile_service.resample_filter()),
+        )
+        .await?;
+    let image = apply_rotation_quality(canvas, rotation, quality);
+
+    let body = blocking_body(move |writer| {
+        JpegEncoder::new_with_quality(writer, DEFAULT_JPEG_QUALITY)
+            .encode_image(&image)
+            .map_err(|e| std::io::Error::other(e.to_string()))
+    });
+
+    let response = Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "image/jpeg")
+        .header(
+            header::CACHE_CONTROL,
+            format!("public, max-age={}", state.cache_max_age()),
+        )
+        .header(
+            header::LINK,
+            format!(
+                "<{}>;rel=\"profile\"",
+                "http://iiif.io/api/image/3/level1.json"
+            ),
+        )
+        .body(body)
+        .unwrap();
+
+    Ok(response)
+}
+
+/// Handle tissue mask requests - returns a low-resolution map of where the
+/// slide holds tissue.
+///
+/// Only available when tissue masks are enabled (`--tissue-masks`). The
+/// mask is computed from an overview of the slide on first request and
+/// kept with the open slide; from then on, read-ahead skips background
+/// tiles of that slide.
+///
+/// # Endpoint
+///
+/// `GET /slides/{slide_id}/mask.png`
+///
+/// # Response
+///
+/// `200 OK` with a grayscale PNG covering the whole slide: white where
+/// there is tissue, black for background. `X-Tissue-Fraction` reports the
+/// fraction of the slide covered by tissue.
+///
+/// # Errors
+///
+/// - `404 Not Found`: Tissue masks are disabled, or the slide does not exist
+/// - `415 Unsupported Media Type`: Slide format not supported
+/// - `500 Internal Server Error`: Storage or processing error
+pub async fn tissue_mask_handler<S: SlideSource>(
+    State(state): State<AppState<S>>,
+    Path(slide_id): Path<String>,
+) -> Result<Response, HandlerError> {
+    if !state.tile_service.tissue_masks_enabled() {
+        let body = ErrorResponse::with_status(
+            "not_found",
+            "Tissue masks are disabled (--tissue-masks)",
+            StatusCode::NOT_FOUND,
+        );
+        return Ok((StatusCode::NOT_FOUND, Json(body)).into_response());
+    }
+
+    let mask = state.tile_service.tissue_mask(&slide_id).await?;
+    Ok(Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "image/png")
+        .header(
+            header::CACHE_CONTROL,
+            format!("public, max-age={}", state.cache_max_age()),
+        )
+        .header(
+            "X-Tissue-Fraction",
+            format!("{:.4}", mask.tissue_fraction()),
+        )
+        .body(axum::body::Body::from(mask.to_png()))
+        .unwrap())
+}
+
+/// Handle thumbnail requests - returns a low-resolution preview image.
+///
+/// # Endpoint
+///
+/// `GET /slides/{slide_id}/thumbnail` (or `/slides/{slide_id}/thumbnail.jpg`)
+///
+/// # Path Parameters
+///
+/// - `slide_id`: Slide identifier (URL-encoded if contains special characters)
+///
+/// # Query Parameters
+///
+/// - `max_size` (or `max`): Maximum width or height for the thumbnail (default: 512, max: 2048)
+/// - `quality`: JPEG quality 1-100 (default: 80)
+/// - `filter`: Resampling filter, `nearest`, `bilinear` or `lanczos3` (default: server setting)
+/// - `sig`: Authentication signature (for signed URLs)
+/// - `exp`: Signature expiry timestamp (for signed URLs)
+///
+/// # Response
+///
+/// `200 OK` with JPEG thumbnail image, with an `ETag` (and `Last-Modified`
+/// when the backend reports it). `304 Not Modified` when the client's
+/// validators match.
+///
+/// # Errors
+///
+/// - `400 Bad Request`: Invalid quality or max_size parameter
+/// - `404 Not Found`: Slide not found
+/// - `415 Unsupported Media Type`: Slide format not supported
+/// - `500 Internal Server Error`: Storage or processing error
+pub async fn thumbnail_handler<S: SlideSource>(
+    State(state): State<AppState<S>>,
+    Path(slide_id): Path<String>,
+    Query(query): Query<ThumbnailQueryParams>,
+    headers: HeaderMap,
+) -> Result<Response, HandlerError> {
+    // Clamp max_size to reasonable bounds (64 to 2048)
+    let requested_size = query.max_size;
+    let max_size = requested_size.clamp(64, 2048);
+    let was_clamped = max_size != requested_size;
+    let filter = query.filter.unwrap_or(state.tile_service.resample_filter());
+
+    // Validators come from the slide file, so a revalidating viewer is
+    // answered without rendering the thumbnail
+    let slide = state.tile_service.open_slide(&slide_id).await?;
+    let render_settings = format!("thumbnail;max_size={};filter={}", max_size, filter);
+    let etag = TileValidator {
+        slide_id: &slide_id,
+        source_etag: slide.source_etag(),
+        source_size: slide.file_size(),
+        level: 0,
+        x: 0,
+        y: 0,
+        quality: query.quality,
+        format: OutputFormat::Jpeg.name(),
+        render_settings: &render_settings,
+    }
+    .etag();
+    let last_modified = slide.last_modified();
+
+    let status = if is_not_modified(&headers, &etag, last_modified) {
+        StatusCode::NOT_MODIFIED
+    } else {
+        StatusCode::OK
+    };
+    let mut builder = Response::builder()
+        .status(status)
+        .header(
+            header::CACHE_CONTROL,
+            format!("public, max-age={}", state.cache_max_age()),
+        )
+        .header(header::ETAG, etag.as_str());
+    if let Some(modified) = last_modified {
+        builder = builder.header(header::LAST_MODIFIED, format_http_date(modified));
+    }
+    if status == StatusCode::NOT_MODIFIED {
+        return Ok(builder.body(axum::body::Body::empty()).unwrap());
+    }
+
+    // Generate thumbnail
+    let response = state
         .tile_service
-        .registry()
-        .source()
-        .list_slides(limit, query.cursor.as_deref(), query.prefix.as_deref())
+        .generate_thumbnail_with_filter(&slide_id, max_size, query.quality, filter)
         .await?;
 
-    // Apply search filter if provided (case-insensitive substring match)
-    let slides = if let Some(ref search) = query.search {
-        let search_lower = search.to_lowercase();
-        result
-            .slides
-            .into_iter()
-            .filter(|s| s.to_lowercase().contains(&search_lower))
-            .collect()
-    } else {
-        result.slides
-    };
+    // Build HTTP response with appropriate headers
+    builder = builder
+        .header(header::CONTENT_TYPE, "image/jpeg")
+        .header("X-Tile-Cache-Hit", response.cache_hit.to_string())
+        .header("X-Tile-Quality", response.quality.to_string());
 
-    Ok(Json(SlidesResponse {
-        slides,
-        next_cursor: result.next_cursor,
-    }))
+    // Add header indicating if max_size was clamped
+    if was_clamped {
+        builder = builder
+            .header("X-Thumbnail-Size-Clamped", "true")
+            .header("X-Thumbnail-Requested-Size", requested_size.to_string())
+            .header("X-Thumbnail-Actual-Size", max_size.to_string());
+    }
+
+    let http_response = builder.body(axum::body::Body::from(response.data)).unwrap();
+
+    Ok(http_response)
 }
 
-/// Handle slide metadata requests.
+/// Handle print snapshot requests - renders a region for figures.
 ///
 /// # Endpoint
 ///
-/// `GET /slides/{slide_id}`
+/// `GET /slides/{slide_id}/snapshot`
 ///
 /// # Path Parameters
 ///
 /// - `slide_id`: Slide identifier (URL-encoded if contains special characters)
 ///
+/// # Query Parameters
+///
+/// - `x_um`, `y_um`: Top-left corner of the region in microns
+/// - `w_um`: Region width in microns
+/// - `h_um`: Region height in microns (default: same as `w_um`)
+/// - `dpi`: Output resolution, 72-1200 (default: 300)
+/// - `width_in`: Output width in inches (default: 6)
+/// - `quality`: JPEG quality 1-100 (default: 90)
+/// - `filter`: Resampling filter, `nearest`, `bilinear` or `lanczos3` (default: server setting)
+/// - `sig`: Authentication signature (for signed URLs)
+/// - `exp`: Signature expiry timestamp (for signed URLs)
+///
+/// The output is `width_in * dpi` pixels wide, or the region's native
+/// resolution if that is lower. The DPI is recorded in the JPEG header.
+///
 /// # Response
 ///
-/// `200 OK` with JSON body containing slide metadata:
+/// `200 OK` with a JPEG image, streamed as it is encoded. A scale bar is
+/// burned into the bottom-left corner, and described by headers:
+/// - `X-Scale-Bar-Microns`: Length of the scale bar (absent if the image is too small)
+/// - `X-Snapshot-Mpp`: Microns per output pixel
+/// - `X-Snapshot-Level`: Pyramid level the region was rendered from
+///
+/// # Errors
+///
+/// - `400 Bad Request`: Slide has no MPP calibration, or the region is
+///   empty, outside the slide or larger than 50 megapixels
+/// - `404 Not Found`: Slide not found
+/// - `415 Unsupported Media Type`: Slide format not supported
+/// - `500 Internal Server Error`: Storage or processing error
+pub async fn snapshot_handler<S: SlideSource>(
+    State(state): State<AppState<S>>,
+    Path(slide_id): Path<String>,
+    Query(query): Query<SnapshotQueryParams>,
+) -> Result<Response, HandlerError> {
+    let region = SnapshotRegion::new(query.x_um, query.y_um, query.w_um)
+        .with_height(query.h_um.unwrap_or(query.w_um))
+        .with_dpi(query.dpi)
+        .with_print_width(query.width_in);
+
+    let snapshot = state
+        .tile_service
+        .render_snapshot_with_filter(
+            &slide_id,
+            &region,
+            query.quality,
+            query.filter.unwrap_or(state.tile_service.resample_filter()),
+        )
+        .await?;
+
+    let mut builder = Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "image/jpeg")
+        .header(
+            header::CACHE_CONTROL,
+            format!("public, max-age={}", state.cache_max_age()),
+        )
+        .header(
+            header::CONTENT_DISPOSITION,
+            "inline; filename=\"snapshot.jpg\"",
+        )
+        .header("X-Snapshot-Level", snapshot.plan.level.to_string())
+        .header("X-Snapshot-Mpp", snapshot.plan.output_mpp.to_string());
+    if let Some(microns) = snapshot.scale_bar_um {
+        builder = builder.header("X-Scale-Bar-Microns", microns.to_string());
+    }
+
+    let (dpi, quality) = (region.dpi, query.quality);
+    let body = blocking_body(move |writer| {
+        encode_snapshot(&snapshot.image, dpi, quality, writer)
+            .map_err(|e| std::io::Error::other(e.to_string()))
+    });
+
+    Ok(builder.body(body).unwrap())
+}
+
+/// Handle region requests - renders an arbitrary rectangle of a slide.
+///
+/// # Endpoint
+///
+/// `GET /region/{slide_id}`
+///
+/// # Query Parameters
+///
+/// - `x`, `y`: Top-left corner of the region in level 0 pixels
+/// - `w`, `h`: Region size in pixels at `level` (or `downsample`)
+/// - `level`: Pyramid level the size is given in (default: 0)
+/// - `downsample`: Arbitrary downsample instead of `level`
+/// - `out_w`, `out_h`: Resize the output; a single one keeps the aspect ratio
+/// - `format`: `jpg` (default) or `png`
+/// - `quality`: JPEG quality 1-100 (default: 80)
+/// - `filter`: Resampling filter, `nearest`, `bilinear` or `lanczos3` (default: server setting)
+/// - `sig`: Authentication signature (for signed URLs)
+/// - `exp`: Signature expiry timestamp (for signed URLs)
+///
+/// The intersecting tiles of the lowest-resolution level that covers the
+/// output are stitched, cropped to the region and resampled, so patches
+/// don't need to align with the tile grid. Parts of the region outside the
+/// slide are white.
+///
+/// # Response
+///
+/// `200 OK` with a JPEG or PNG image, streamed as it is encoded. The
+/// pyramid level read is reported in `X-Region-Source-Level`.
+///
+/// # Errors
+///
+/// - `400 Bad Request`: Empty region, region outside the slide, output
+///   above 25 megapixels, invalid level, downsample or quality
+/// - `404 Not Found`: Slide not found
+/// - `415 Unsupported Media Type`: Slide format not supported
+/// - `500 Internal Server Error`: Storage or processing error
+pub async fn region_handler<S: SlideSource>(
+    State(state): State<AppState<S>>,
+    Path(slide_id): Path<String>,
+    Query(query): Query<RegionQueryParams>,
+) -> Result<Response, HandlerError> {
+    if !is_valid_quality(query.quality) {
+        return Err(TileError::InvalidQuality {
+            quality: query.quality,
+        }
+        .into());
+    }
+
+    let slide = state.tile_service.open_slide(&slide_id).await?;
+    let plan = plan_slide_region(&slide, &query.geometry())?;
+
+    let image = state
+        .tile_service
+        .render_region_with_filter(
+            &slide_id,
+            &plan,
+            query.quality,
+            query.filter.unwrap_or(state.tile_service.resample_filter()),
+        )
+        .await?;
+
+    let (format, quality) = (query.format, query.quality);
+    let body = blocking_body(move |writer| {
+        format
+            .encode(&image, quality, writer)
+            .map_err(|e| std::io::Error::other(e.to_string()))
+    });
+
+    let response = Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, format.content_type())
+        .header(
+            header::CACHE_CONTROL,
+            format!("public, max-age={}", state.cache_max_age()),
+        )
+        .header("X-Region-Source-Level", plan.level.to_string())
+        .body(body)
+        .unwrap();
+
+    Ok(response)
+}
+
+/// Handle batch region requests - renders several regions in one response.
+///
+/// # Endpoint
+///
+/// `POST /regions/batch`
+///
+/// # Request Body
+///
 /// ```json
 /// {
-///   "slide_id": "path/to/slide.svs",
-///   "format": "aperio_svs",
-///   "width": 46920,
-///   "height": 33600,
-///   "level_count": 4,
-///   "levels": [
-///     {
-///       "level": 0,
-///       "width": 46920,
-///       "height": 33600,
-///       "tile_width": 256,
-///       "tile_height": 256,
-///       "tiles_x": 184,
-///       "tiles_y": 132,
-///       "downsample": 1.0
-///     }
-///   ]
+///   "regions": [
+///     { "slide_id": "a.svs", "x": 1000, "y": 2000, "w": 512, "h": 512, "level": 1 },
+///     { "slide_id": "b.svs", "x": 0, "y": 0, "w": 4096, "h": 4096, "out_w": 256 }
+///   ],
+///   "format": "jpg",
+///   "quality": 80
 /// }
 /// ```
 ///
+/// Each region takes the parameters of `GET /region/{slide_id}`; `format`,
+/// `quality` and `filter` apply to every region. A batch holds at most 64
+/// regions and may read at most 2048 source tiles in total, so one request
+/// can't cause an unbounded number of storage reads. Regions are rendered a
+/// few at a time, and their tiles go through the same cache and scheduler as
+/// tile requests.
+///
+/// # Response
+///
+/// `200 OK` with a `multipart/mixed` body holding one part per region, in
+/// request order. Each part carries `Content-ID: <region-{index}>`,
+/// `X-Slide-Id` (URL-encoded) and `X-Status`. Rendered regions have the
+/// image content type and `X-Region-Source-Level`; a region that fails has
+/// the JSON error body and status it would get from the region endpoint,
+/// without failing the rest of the batch.
+///
 /// # Errors
 ///
-/// - `401 Unauthorized`: Invalid or missing signature (when auth enabled)
+/// - `400 Bad Request`: No regions, more than 64 regions, tile budget
+///   exceeded or invalid quality
+/// - `406 Not Acceptable`: Output format not supported by this build
+pub async fn regions_batch_handler<S: SlideSource + 'static>(
+    State(state): State<AppState<S>>,
+    Json(request): Json<BatchRegionsRequest>,
+) -> Result<Response, HandlerError> {
+    if !is_valid_quality(request.quality) {
+        return Err(TileError::InvalidQuality {
+            quality: request.quality,
+        }
+        .into());
+    }
+    request.format.ensure_supported()?;
+    if request.regions.is_empty() || request.regions.len() > MAX_BATCH_REGIONS {
+        return Err(TileError::InvalidRegion {
+            message: format!("a batch holds 1 to {} regions", MAX_BATCH_REGIONS),
+        }
+        .into());
+    }
+
+    // Plan every region before reading any tile, so the batch is rejected
+    // as a whole when it would exceed its storage budget
+    let mut plans = Vec::with_capacity(request.regions.len());
+    let mut tiles = 0;
+    for region in &request.regions {
+        let plan = plan_batch_region(&state.tile_service, region).await;
+        if let Ok((_, count)) = &plan {
+            tiles += count;
+        }
+        plans.push(plan.map(|(plan, _)| plan));
+    }
+    if tiles > MAX_BATCH_TILES {
+        return Err(TileError::InvalidRegion {
+            message: format!(
+                "batch reads {} tiles, more than the limit of {}",
+                tiles, MAX_BATCH_TILES
+            ),
+        }
+        .into());
+    }
+
+    let format = request.format;
+    let quality = request.quality;
+    let filter = request
+        .filter
+        .unwrap_or(state.tile_service.resample_filter());
+    let permits = Arc::new(Semaphore::new(BATCH_REGION_CONCURRENCY));
+    let parts: Vec<_> = request
+        .regions
+        .into_iter()
+        .zip(plans)
+        .map(|(region, plan)| {
+            let plan = plan.map(|plan| {
+                let level = plan.level;
+                let service = state.tile_service.clone();
+                let permits = permits.clone();
+                let slide_id = region.slide_id.clone();
+                let task = tokio::spawn(async move {
+                    let _permit = permits.acquire_owned().await;
+                    let image = service
+                        .render_region_with_filter(&slide_id, &plan, quality, filter)
+                        .await?;
+                    tokio::task::spawn_blocking(move || {
+                        let mut data = Vec::new();
+                        format.encode(&image, quality, &mut data)?;
+                        Ok::<_, TileError>(Bytes::from(data))
+                    })
+                    .await
+                    .unwrap_or_else(|e| {
+                        Err(TileError::EncodeError {
+                            message: e.to_string(),
+                        })
+                    })
+                });
+                (level, task)
+            });
+            (region.slide_id, plan)
+        })
+        .collect();
+
+    let boundary = multipart_boundary();
+    let content_type = format!("multipart/mixed; boundary={}", boundary);
+    let body = spawned_body(move |tx| async move {
+        let mut parts = parts.into_iter().enumerate();
+        while let Some((index, (slide_id, plan))) = parts.next() {
+            let rendered = match plan {
+                Ok((level, task)) => match task.await {
+                    Ok(result) => result.map(|data| (level, data)),
+                    Err(e) => Err(TileError::EncodeError {
+                        message: e.to_string(),
+                    }),
+                },
+                Err(err) => Err(err),
+            };
+
+            let part = match rendered {
+                Ok((level, data)) => {
+                    let headers = format!(
+                        "Content-Type: {}\r\nX-Region-Source-Level: {}\r\n",
+                        format.content_type(),
+                        level
+                    );
+                    multipart_part(&boundary, index, &slide_id, StatusCode::OK, &headers, &data)
+                }
+                Err(err) => {
+                    let response = HandlerError(err).into_response();
+                    let status = response.status();
+                    let data = axum::body::to_bytes(response.into_body(), usize::MAX)
+                        .await
+                        .unwrap_or_default();
+                    let headers = "Content-Type: application/json\r\n";
+                    multipart_part(&boundary, index, &slide_id, status, headers, &data)
+                }
+            };
+
+            if tx.send(Ok(part)).await.is_err() {
+                // Client went away; stop rendering the rest of the batch
+                for (_, (_, plan)) in parts {
+                    if let Ok((_, task)) = plan {
+                        task.abort();
+                    }
+                }
+                return;
+            }
+        }
+        let _ = tx
+            .send(Ok(Bytes::from(format!("--{}--\r\n", boundary))))
+            .await;
+    });
+
+    let response = Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, content_type)
+        .header(header::CACHE_CONTROL, "no-store")
+        .body(body)
+        .unwrap();
+
+    Ok(response)
+}
+
+/// Open a batch region's slide and plan it, returning the plan and the
+/// number of tiles it reads.
+async fn plan_batch_region<S: SlideSource>(
+    service: &TileService<S>,
+    region: &BatchRegionRequest,
+) -> Result<(RegionPlan, u64), TileError> {
+    let slide = service.open_slide(&region.slide_id).await?;
+    let plan = plan_slide_region(&slide, &region.region)?;
+    let info = service
+        .level_info(&slide, plan.level)
+        .ok_or(TileError::InvalidLevel {
+            level: plan.level,
+            max_levels: slide.level_count(),
+        })?;
+    let (tiles_x, tiles_y) = plan.tile_range(&info);
+    let tiles = tiles_x.len() as u64 * tiles_y.len() as u64;
+    Ok((plan, tiles))
+}
+
+/// Generate a boundary for a multipart response.
+fn multipart_boundary() -> String {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u128(
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or(0),
+    );
+    format!("wsi-batch-{:016x}", hasher.finish())
+}
+
+/// Encode one part of a batch region response.
+///
+/// `headers` holds the part's content headers, each terminated by CRLF.
+fn multipart_part(
+    boundary: &str,
+    index: usize,
+    slide_id: &str,
+    status: StatusCode,
+    headers: &str,
+    data: &[u8],
+) -> Bytes {
+    let mut part = format!(
+        "--{}\r\n{}Content-ID: <region-{}>\r\nX-Slide-Id: {}\r\nX-Status: {}\r\n\r\n",
+        boundary,
+        headers,
+        index,
+        urlencoding::encode(slide_id),
+        status.as_u16()
+    )
+    .into_bytes();
+    part.extend_from_slice(data);
+    part.extend_from_slice(b"\r\n");
+    Bytes::from(part)
+}
+
+/// Plan a region of a slide, validating its position and size.
+fn plan_slide_region<R: RangeReader + 'static>(
+    slide: &CachedSlide<R>,
+    region: &RegionGeometry,
+) -> Result<RegionPlan, TileError> {
+    let invalid = |message: &str| TileError::InvalidRegion {
+        message: message.to_string(),
+    };
+    if region.w == 0 || region.h == 0 {
+        return Err(invalid("region is empty"));
+    }
+
+    let (width, height) = slide.dimensions().ok_or(TileError::InvalidLevel {
+        level: 0,
+        max_levels: 0,
+    })?;
+    if region.x >= width || region.y >= height {
+        return Err(invalid("region is outside the slide"));
+    }
+
+    let downsample = match (region.level, region.downsample) {
+        (Some(_), Some(_)) => {
+            return Err(invalid("give either level or downsample, not both"));
+        }
+        (Some(level), None) => slide
+            .level_downsample(level)
+            .ok_or(TileError::InvalidLevel {
+                level,
+                max_levels: slide.level_count(),
+            })?,
+        (None, Some(downsample)) if downsample.is_finite() && downsample > 0.0 => downsample,
+        (None, Some(_)) => return Err(invalid("downsample must be positive")),
+        (None, None) => 1.0,
+    };
+
+    let (out_width, out_height) =
+        region_output_size(region.w, region.h, region.out_w, region.out_h);
+    if out_width == 0 || out_height == 0 {
+        return Err(invalid("output size is empty"));
+    }
+    if out_width as u64 * out_height as u64 > MAX_REGION_PIXELS {
+        return Err(invalid("output is larger than 25 megapixels"));
+    }
+
+    let downsamples: Vec<f64> = (0..slide.level_count())
+        .map(|level| slide.level_downsample(level).unwrap_or(f64::INFINITY))
+        .collect();
+    Ok(plan_region(
+        region.x as f64,
+        region.y as f64,
+        region.w as f64 * downsample,
+        region.h as f64 * downsample,
+        out_width,
+        out_height,
+        &downsamples,
+    ))
+}
+
+/// Handle tile sampling requests - returns random tissue tile coordinates.
+///
+/// # Endpoint
+///
+/// `GET /slides/{slide_id}/sample`
+///
+/// # Path Parameters
+///
+/// - `slide_id`: Slide identifier (URL-encoded if contains special characters)
+///
+/// # Query Parameters
+///
+/// - `level`: Pyramid level to sample from (default: 0)
+/// - `count`: Number of tiles (default: 16, max: 1000, or 100 with `include_tiles`)
+/// - `seed`: Random seed (default: 0); the same seed always yields the same tiles
+/// - `min_tissue`: Minimum tissue fraction 0.0-1.0 (default: 0.25)
+/// - `strata`: Number of tissue-density strata (default: 1, max: 100)
+/// - `include_tiles`: Include base64-encoded JPEG tiles (default: false)
+/// - `quality`: JPEG quality for included tiles (default: 80)
+/// - `sig`: Authentication signature (for signed URLs)
+/// - `exp`: Signature expiry timestamp (for signed URLs)
+///
+/// # Response
+///
+/// `200 OK` with JSON body listing the sampled tiles.
+///
+/// # Errors
+///
+/// - `400 Bad Request`: Invalid level or quality
 /// - `404 Not Found`: Slide not found
 /// - `415 Unsupported Media Type`: Slide format not supported
 /// - `500 Internal Server Error`: Storage or processing error
-pub async fn slide_metadata_handler<S: SlideSource>(
+pub async fn sample_handler<S: SlideSource + 'static>(
     State(state): State<AppState<S>>,
     Path(slide_id): Path<String>,
-) -> Result<Json<SlideMetadataResponse>, SlideMetadataError> {
-    // Get slide from registry (opens and caches if needed)
-    let slide = state.tile_service.registry().get_slide(&slide_id).await?;
-
-    // Get dimensions (should always be available for valid slides)
-    let (width, height) = slide.dimensions().unwrap_or((0, 0));
-
-    // Build level metadata for each pyramid level
-    let level_count = slide.level_count();
-    let levels: Vec<LevelMetadataResponse> = (0..level_count)
-        .filter_map(|level| {
-            slide.level_info(level).map(|info| LevelMetadataResponse {
-                level,
-                width: info.width,
-                height: info.height,
-                tile_width: info.tile_width,
-                tile_height: info.tile_height,
-                tiles_x: info.tiles_x,
-                tiles_y: info.tiles_y,
-                downsample: info.downsample,
+    Query(query): Query<SampleQueryParams>,
+) -> Result<Json<SampleResponse>, HandlerError> {
+    let max_count = if query.include_tiles {
+        MAX_SAMPLE_COUNT_WITH_TILES
+    } else {
+        MAX_SAMPLE_COUNT
+    };
+    let options = SampleOptions::new(query.count.min(max_count))
+        .with_seed(query.seed)
+        .with_min_tissue(query.min_tissue)
+        .with_strata(query.strata);
+
+    let tiles = if query.include_tiles {
+        state
+            .tile_service
+            .sample_tiles_with_data(&slide_id, query.level, &options, query.quality)
+            .await?
+            .into_iter()
+            .map(|(sample, data)| SampledTileResponse {
+                x: sample.tile_x,
+                y: sample.tile_y,
+                tissue: sample.tissue,
+                data: Some(BASE64_STANDARD.encode(&data)),
+            })
+            .collect()
+    } else {
+        state
+            .tile_service
+            .sample_tiles(&slide_id, query.level, &options)
+            .await?
+            .into_iter()
+            .map(|sample| SampledTileResponse {
+                x: sample.tile_x,
+                y: sample.tile_y,
+                tissue: sample.tissue,
+                data: None,
             })
-        })
-        .collect();
+            .collect()
+    };
 
-    Ok(Json(SlideMetadataResponse {
+    Ok(Json(SampleResponse {
         slide_id,
-        format: slide.format().name().to_string(),
-        width,
-        height,
-        level_count,
-        levels,
+        level: query.level,
+        seed: query.seed,
+        tiles,
     }))
 }
 
-/// Handle viewer requests - serves an HTML page with OpenSeadragon viewer.
+/// Handle collection sprite sheet requests.
+///
+/// A collection is the set of slides whose keys start with `{collection_id}/`.
 ///
 /// # Endpoint
 ///
-/// `GET /view/{slide_id}`
+/// `GET /collections/{collection_id}/sprites`
 ///
 /// # Path Parameters
 ///
-/// - `slide_id`: Slide identifier (URL-encoded if contains special characters)
+/// - `collection_id`: Collection key prefix (URL-encoded if it contains `/`)
+///
+/// # Query Parameters
+///
+/// - `size`: Cell size in pixels (default: 128, clamped to 32-512)
+/// - `limit`: Maximum number of slides (default and max: 256)
+/// - `quality`: JPEG quality (default: 80)
+/// - `sig`: Authentication signature (for signed URLs)
+/// - `exp`: Signature expiry timestamp (for signed URLs)
 ///
 /// # Response
 ///
-/// `200 OK` with HTML page containing an embedded OpenSeadragon viewer.
+/// `200 OK` with a JSON body holding the sheet as a data URI and the
+/// position of each slide's thumbnail:
+/// ```json
+/// {
+///   "collection_id": "case-42",
+///   "image": "data:image/jpeg;base64,...",
+///   "width": 384,
+///   "height": 256,
+///   "size": 128,
+///   "columns": 3,
+///   "sprites": [
+///     { "slide_id": "case-42/a.svs", "x": 0, "y": 21, "width": 128, "height": 86 }
+///   ],
+///   "failed": [],
+///   "truncated": false
+/// }
+/// ```
 ///
 /// # Errors
 ///
-/// - `404 Not Found`: Slide not found
-/// - `415 Unsupported Media Type`: Slide format not supported
+/// - `400 Bad Request`: Invalid quality
+/// - `404 Not Found`: Collection contains no slides
 /// - `500 Internal Server Error`: Storage or processing error
-pub async fn viewer_handler<S: SlideSource>(
+/// - `503 Service Unavailable`: Too many slides are being opened
+pub async fn sprites_handler<S: SlideSource>(
     State(state): State<AppState<S>>,
-    Path(slide_id): Path<String>,
-    headers: HeaderMap,
-) -> Result<Html<String>, SlideMetadataError> {
-    // Get slide from registry to retrieve metadata
-    let slide = state.tile_service.registry().get_slide(&slide_id).await?;
-
-    // Get dimensions
-    let (width, height) = slide.dimensions().unwrap_or((0, 0));
-
-    // Build level metadata
-    let level_count = slide.level_count();
-    let levels: Vec<LevelMetadataResponse> = (0..level_count)
-        .filter_map(|level| {
-            slide.level_info(level).map(|info| LevelMetadataResponse {
-                level,
-                width: info.width,
-                height: info.height,
-                tile_width: info.tile_width,
-                tile_height: info.tile_height,
-                tiles_x: info.tiles_x,
-                tiles_y: info.tiles_y,
-                downsample: info.downsample,
-            })
-        })
-        .collect();
-
-    let metadata = SlideMetadataResponse {
-        slide_id: slide_id.clone(),
-        format: slide.format().name().to_string(),
-        width,
-        height,
-        level_count,
-        levels,
-    };
-
-    // Extract host from headers, defaulting to localhost:3000
-    let host = headers
-        .get(header::HOST)
-        .and_then(|h| h.to_str().ok())
-        .unwrap_or("localhost:3000");
-
-    // Detect protocol from X-Forwarded-Proto header (for reverse proxy support)
-    // or default to http for local development
-    let proto = headers
-        .get("x-forwarded-proto")
-        .and_then(|h| h.to_str().ok())
-        .unwrap_or("http");
-
-    // Generate the base URL from the host and protocol
-    let base_url = format!("{}://{}", proto, host);
-
-    // Generate viewer token if auth is enabled
-    // This token authorizes access to all tiles for this specific slide
-    let auth_query = state
-        .auth
-        .as_ref()
-        .map(|auth| {
-            // Generate viewer token valid for 1 hour
-            let ttl = Duration::from_secs(3600);
-            let (token, expiry) = auth.generate_viewer_token(&slide_id, ttl);
-            format!("?vt={}&exp={}", token, expiry)
-        })
-        .unwrap_or_default();
+    Path(collection_id): Path<String>,
+    Query(query): Query<SpriteQueryParams>,
+) -> Result<Json<SpriteSheetResponse>, HandlerError> {
+    let size = query.size.clamp(MIN_SPRITE_SIZE, MAX_SPRITE_SIZE);
+    let limit = query.limit.clamp(1, MAX_SPRITE_SLIDES);
+
+    let prefix = format!("{}/", collection_id.trim_end_matches('/'));
+    let listing = state
+        .tile_service
+        .registry()
+        .source()
+        .list_slides(limit as u32, None, Some(&prefix), None)
+        .await
+        .map_err(TileError::Io)?;
+
+    if listing.slides.is_empty() {
+        return Err(
+            TileError::Io(IoError::NotFound(format!("collection {}", collection_id))).into(),
+        );
+    }
 
-    // Generate the viewer HTML with auth info
-    let html = super::viewer::generate_viewer_html(&slide_id, &metadata, &base_url, &auth_query);
+    let sheet = state
+        .tile_service
+        .generate_sprite_sheet(&listing.slides, size, query.quality)
+        .await?;
 
-    Ok(Html(html))
+    Ok(Json(SpriteSheetResponse {
+        collection_id,
+        image: format!(
+            "data:image/jpeg;base64,{}",
+            BASE64_STANDARD.encode(&sheet.data)
+        ),
+        width: sheet.width,
+        height: sheet.height,
+        size: sheet.cell_size,
+        columns: sheet.columns,
+        sprites: sheet.sprites,
+        failed: sheet.failed,
+        truncated: listing.next_cursor.is_some(),
+    }))
 }
 
-/// Handle DZI descriptor requests - returns XML descriptor for Deep Zoom viewers.
+/// Handle requests to save a viewer state.
 ///
 /// # Endpoint
 ///
-/// `GET /slides/{slide_id}/dzi`
-///
-/// # Path Parameters
+/// `POST /slides/{slide_id}/views`
 ///
-/// - `slide_id`: Slide identifier
+/// # Request Body
 ///
-/// # Response
+/// ```json
+/// {
+///   "name": "tumor-margin",
+///   "level": 1,
+///   "center_x": 23000.0,
+///   "center_y": 16800.0,
+///   "zoom": 2.5,
+///   "filters": { "brightness": 1.1 }
+/// }
+/// ```
 ///
-/// `200 OK` with XML body containing DZI descriptor.
+/// The center is given in level 0 pixel coordinates. `filters` is optional
+/// and stored as-is. Saving a view with an existing name replaces it.
 ///
-/// # Example Response
+/// # Response
 ///
-/// ```xml
-/// <?xml version="1.0" encoding="UTF-8"?>
-/// <Image xmlns="http://schemas.microsoft.com/deepzoom/2008"
-///        TileSize="256"
-///        Overlap="0"
-///        Format="jpg">
-///   <Size Width="46920" Height="33600" />
-/// </Image>
-/// ```
+/// `201 Created` with the stored view and its URL.
 ///
 /// # Errors
 ///
+/// - `400 Bad Request`: Invalid view name, or state outside the slide
 /// - `404 Not Found`: Slide not found
-/// - `415 Unsupported Media Type`: Slide format not supported
-/// - `500 Internal Server Error`: Storage or processing error
-pub async fn dzi_descriptor_handler<S: SlideSource>(
+/// - `500 Internal Server Error`: Storage error
+pub async fn save_view_handler<S: SlideSource>(
     State(state): State<AppState<S>>,
     Path(slide_id): Path<String>,
-) -> Result<Response, SlideMetadataError> {
-    // Get slide from registry
+    Json(request): Json<SaveViewRequest>,
+) -> Result<(StatusCode, Json<ViewResponse>), ViewError> {
     let slide = state.tile_service.registry().get_slide(&slide_id).await?;
-
-    // Get dimensions
     let (width, height) = slide.dimensions().unwrap_or((0, 0));
+    request.state.validate(slide.level_count(), width, height)?;
 
-    // Get tile size from level 0 (or default)
-    let tile_size = slide.tile_size(0).map(|(w, _)| w).unwrap_or(256);
-
-    // Generate DZI XML
-    let xml = super::dzi::generate_dzi_xml(width, height, tile_size);
-
-    // Build response with XML content type
-    let response = Response::builder()
-        .status(StatusCode::OK)
-        .header(header::CONTENT_TYPE, "application/xml")
-        .header(
-            header::CACHE_CONTROL,
-            format!("public, max-age={}", state.cache_max_age),
-        )
-        .body(axum::body::Body::from(xml))
-        .unwrap();
+    let registry = state.tile_service.registry();
+    let view = save_view(registry.source(), &slide_id, &request.name, request.state).await?;
 
-    Ok(response)
+    Ok((StatusCode::CREATED, Json(ViewResponse::new(view))))
 }
 
-/// Handle thumbnail requests - returns a low-resolution preview image.
+/// Handle requests to load a saved viewer state.
 ///
 /// # Endpoint
 ///
-/// `GET /slides/{slide_id}/thumbnail`
+/// `GET /slides/{slide_id}/views/{name}`
 ///
-/// # Path Parameters
+/// # Response
 ///
-/// - `slide_id`: Slide identifier (URL-encoded if contains special characters)
+/// `200 OK` with the stored view (same shape as the save response).
 ///
-/// # Query Parameters
+/// # Errors
 ///
-/// - `max_size`: Maximum width or height for the thumbnail (default: 512, max: 2048)
-/// - `quality`: JPEG quality 1-100 (default: 80)
-/// - `sig`: Authentication signature (for signed URLs)
-/// - `exp`: Signature expiry timestamp (for signed URLs)
+/// - `400 Bad Request`: Invalid view name
+/// - `404 Not Found`: No view with this name for the slide
+/// - `500 Internal Server Error`: Storage error
+pub async fn get_view_handler<S: SlideSource>(
+    State(state): State<AppState<S>>,
+    Path((slide_id, name)): Path<(String, String)>,
+) -> Result<Json<ViewResponse>, ViewError> {
+    let registry = state.tile_service.registry();
+    let view = load_view(registry.source(), &slide_id, &name).await?;
+
+    Ok(Json(ViewResponse::new(view)))
+}
+
+/// Handle requests to save a slide's annotations.
+///
+/// # Endpoint
+///
+/// `POST /slides/{slide_id}/annotations`
+///
+/// # Request Body
+///
+/// A GeoJSON `FeatureCollection`, `Feature`, geometry or array of features,
+/// in level 0 pixel coordinates (up to 32 MiB). Feature colors are read
+/// from `properties.color` or `properties.classification.color`. Saving
+/// replaces the slide's previous annotations.
 ///
 /// # Response
 ///
-/// `200 OK` with JPEG thumbnail image.
+/// `201 Created` with the feature count and revision of the stored set.
 ///
 /// # Errors
 ///
-/// - `400 Bad Request`: Invalid quality or max_size parameter
+/// - `400 Bad Request`: Body is not supported GeoJSON
 /// - `404 Not Found`: Slide not found
-/// - `415 Unsupported Media Type`: Slide format not supported
-/// - `500 Internal Server Error`: Storage or processing error
-pub async fn thumbnail_handler<S: SlideSource>(
+/// - `413 Payload Too Large`: Too many vertices, or body over 32 MiB
+/// - `500 Internal Server Error`: Storage error
+pub async fn save_annotations_handler<S: SlideSource>(
     State(state): State<AppState<S>>,
     Path(slide_id): Path<String>,
-    Query(query): Query<ThumbnailQueryParams>,
-) -> Result<Response, HandlerError> {
-    // Clamp max_size to reasonable bounds (64 to 2048)
-    let requested_size = query.max_size;
-    let max_size = requested_size.clamp(64, 2048);
-    let was_clamped = max_size != requested_size;
-
-    // Generate thumbnail
-    let response = state
-        .tile_service
-        .generate_thumbnail(&slide_id, max_size, query.quality)
+    body: Bytes,
+) -> Result<(StatusCode, Json<AnnotationsResponse>), AnnotationError> {
+    state.tile_service.registry().get_slide(&slide_id).await?;
+
+    let annotations = Arc::new(AnnotationSet::from_geojson(body)?);
+    state
+        .annotations
+        .save(&slide_id, Arc::clone(&annotations))
         .await?;
 
-    // Build HTTP response with appropriate headers
-    let mut builder = Response::builder()
-        .status(StatusCode::OK)
-        .header(header::CONTENT_TYPE, "image/jpeg")
-        .header(
-            header::CACHE_CONTROL,
-            format!("public, max-age={}", state.cache_max_age),
-        )
-        .header("X-Tile-Cache-Hit", response.cache_hit.to_string())
-        .header("X-Tile-Quality", response.quality.to_string());
-
-    // Add header indicating if max_size was clamped
-    if was_clamped {
-        builder = builder
-            .header("X-Thumbnail-Size-Clamped", "true")
-            .header("X-Thumbnail-Requested-Size", requested_size.to_string())
-            .header("X-Thumbnail-Actual-Size", max_size.to_string());
-    }
-
-    let http_response = builder.body(axum::body::Body::from(response.data)).unwrap();
+    Ok((
+        StatusCode::CREATED,
+        Json(AnnotationsResponse {
+            slide_id,
+            features: annotations.feature_count(),
+            shapes: annotations.len(),
+            revision: format!("{:016x}", annotations.revision()),
+        }),
+    ))
+}
 
-    Ok(http_response)
+/// Handle requests to load a slide's annotations.
+///
+/// # Endpoint
+///
+/// `GET /slides/{slide_id}/annotations`
+///
+/// # Response
+///
+/// `200 OK` with the GeoJSON document as uploaded
+/// (`Content-Type: application/geo+json`).
+///
+/// # Errors
+///
+/// - `404 Not Found`: No annotations saved for the slide
+/// - `500 Internal Server Error`: Storage error
+pub async fn get_annotations_handler<S: SlideSource>(
+    State(state): State<AppState<S>>,
+    Path(slide_id): Path<String>,
+) -> Result<Response, AnnotationError> {
+    let annotations = state
+        .annotations
+        .load(&slide_id)
+        .await?
+        .ok_or(AnnotationError::NotFound { slide_id })?;
+
+    Ok((
+        [(header::CONTENT_TYPE, "application/geo+json")],
+        annotations.geojson().clone(),
+    )
+        .into_response())
 }
 
 // =============================================================================
@@ -1138,12 +5096,61 @@ mod tests {
         let response = err.into_response();
         assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
 
+        // Test SparseTile -> 404
+        let err = TileError::Slide(TiffError::SparseTile {
+            level: 0,
+            tile_x: 1,
+            tile_y: 1,
+        });
+        let response = err.into_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
         // Test DecodeError -> 500
         let err = TileError::DecodeError {
             message: "test".to_string(),
         };
         let response = err.into_response();
         assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+        // Test TransformError -> 502
+        let err = TileError::TransformError {
+            message: "sidecar down".to_string(),
+        };
+        let response = err.into_response();
+        assert_eq!(response.status(), StatusCode::BAD_GATEWAY);
+
+        // Test Overloaded -> 503
+        let err = TileError::Overloaded {
+            message: "too many opens".to_string(),
+        };
+        let response = err.into_response();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        // Test InvalidRegion -> 400
+        let err = TileError::InvalidRegion {
+            message: "slide has no microns-per-pixel calibration".to_string(),
+        };
+        let response = err.into_response();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        // Test SlideQuarantined -> 423
+        let err = TileError::SlideQuarantined {
+            slide_id: "bad.svs".to_string(),
+        };
+        let response = err.into_response();
+        assert_eq!(response.status(), StatusCode::LOCKED);
+
+        // Test Timeout -> 504
+        let err = TileError::Timeout { budget_ms: 2000 };
+        let response = err.into_response();
+        assert_eq!(response.status(), StatusCode::GATEWAY_TIMEOUT);
+
+        // Test UnsupportedOutputFormat -> 406
+        let err = TileError::UnsupportedOutputFormat {
+            format: "webp".to_string(),
+        };
+        let response = err.into_response();
+        assert_eq!(response.status(), StatusCode::NOT_ACCEPTABLE);
     }
 
     #[test]
@@ -1175,6 +5182,24 @@ mod tests {
         assert_eq!(params.exp, Some(1234567890));
     }
 
+    #[test]
+    fn test_tile_path_params_extension() {
+        let params = |filename: &str| TilePathParams {
+            slide_id: "slide.svs".to_string(),
+            level: 0,
+            x: 0,
+            filename: filename.to_string(),
+        };
+
+        assert_eq!(params("3").y(), Ok(3));
+        assert_eq!(params("3").format(), None);
+        assert_eq!(params("3.jpg").format(), Some(OutputFormat::Jpeg));
+        assert_eq!(params("3.png").y(), Ok(3));
+        assert_eq!(params("3.png").format(), Some(OutputFormat::Png));
+        assert_eq!(params("3.webp").format(), Some(OutputFormat::Webp));
+        assert!(params("3.gif").y().is_err());
+    }
+
     #[test]
     fn test_format_error_to_status_code() {
         // Test IoError::NotFound -> 404
@@ -1223,6 +5248,21 @@ mod tests {
         let err = FormatError::Tiff(TiffError::MissingTag("TileOffsets"));
         let response = err.into_response();
         assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+        // Test OpenQueueTimeout -> 503
+        let err = FormatError::OpenQueueTimeout {
+            slide_id: "test.svs".to_string(),
+            waited_ms: 100,
+        };
+        let response = err.into_response();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        // Test Quarantined -> 423
+        let err = FormatError::Quarantined {
+            slide_id: "bad.svs".to_string(),
+        };
+        let response = err.into_response();
+        assert_eq!(response.status(), StatusCode::LOCKED);
     }
 
     #[test]
@@ -1237,10 +5277,10 @@ mod tests {
         let response = err.into_response();
         assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
 
-        // Test Connection error -> 500
+        // Test Connection error -> 502, as for slide opens
         let err = TileError::Io(IoError::Connection("reset by peer".to_string()));
         let response = err.into_response();
-        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(response.status(), StatusCode::BAD_GATEWAY);
     }
 
     #[test]
@@ -1264,15 +5304,35 @@ mod tests {
         assert_eq!(params.exp, Some(1234567890));
     }
 
+    #[test]
+    fn test_sample_query_params_defaults() {
+        let params: SampleQueryParams = serde_json::from_str("{}").unwrap();
+        assert_eq!(params.level, 0);
+        assert_eq!(params.count, 16);
+        assert_eq!(params.seed, 0);
+        assert_eq!(params.min_tissue, DEFAULT_MIN_TISSUE);
+        assert_eq!(params.strata, 1);
+        assert!(!params.include_tiles);
+    }
+
+    #[test]
+    fn test_escape_label_value() {
+        assert_eq!(escape_label_value("lab/s1.svs"), "lab/s1.svs");
+        assert_eq!(escape_label_value("a\\b\"c\nd"), "a\\\\b\\\"c\\nd");
+    }
+
     #[test]
     fn test_slides_response_serialization() {
         let response = SlidesResponse {
             slides: vec!["slide1.svs".to_string(), "folder/slide2.tif".to_string()],
+            folders: vec!["case-1/".to_string()],
             next_cursor: Some("token123".to_string()),
+            conformance: BTreeMap::new(),
         };
         let json = serde_json::to_string(&response).unwrap();
         assert!(json.contains("slide1.svs"));
         assert!(json.contains("folder/slide2.tif"));
+        assert!(json.contains("\"folders\":[\"case-1/\"]"));
         assert!(json.contains("token123"));
     }
 
@@ -1280,10 +5340,14 @@ mod tests {
     fn test_slides_response_no_cursor() {
         let response = SlidesResponse {
             slides: vec!["slide.svs".to_string()],
+            folders: vec![],
             next_cursor: None,
+            conformance: BTreeMap::new(),
         };
         let json = serde_json::to_string(&response).unwrap();
         assert!(!json.contains("next_cursor"));
+        assert!(!json.contains("folders"));
+        assert!(!json.contains("conformance"));
     }
 
     #[test]
@@ -1304,6 +5368,32 @@ mod tests {
         assert_eq!(response.status(), StatusCode::BAD_GATEWAY);
     }
 
+    #[test]
+    fn test_error_response_violations_serialization() {
+        let plain = ErrorResponse::new("not_found", "Slide not found");
+        let json = serde_json::to_string(&plain).unwrap();
+        assert!(!json.contains("violations"));
+
+        let response = ErrorResponse::with_status(
+            "unsupported_format",
+            "Unsupported slide",
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+        )
+        .with_violations(&[
+            ValidationError::StripOrganization { ifd_index: 0 },
+            ValidationError::MissingTileTags {
+                ifd_index: 1,
+                missing_tags: vec!["TileOffsets"],
+            },
+        ]);
+        let json = serde_json::to_value(&response).unwrap();
+        assert_eq!(json["violations"][0]["constraint"], "strip_organization");
+        assert_eq!(json["violations"][0]["ifd"], 0);
+        assert!(json["violations"][0].get("missing_tags").is_none());
+        assert_eq!(json["violations"][1]["missing_tags"][0], "TileOffsets");
+        assert!(json["violations"][1]["remediation"].is_string());
+    }
+
     #[test]
     fn test_level_metadata_response_serialization() {
         let response = LevelMetadataResponse {
@@ -1357,6 +5447,7 @@ mod tests {
                     downsample: 2.0,
                 },
             ],
+            integrity: None,
         };
         let json = serde_json::to_string(&response).unwrap();
         assert!(json.contains("\"slide_id\":\"path/to/slide.svs\""));
@@ -1376,12 +5467,36 @@ mod tests {
             height: 0,
             level_count: 0,
             levels: vec![],
+            integrity: None,
         };
         let json = serde_json::to_string(&response).unwrap();
         assert!(json.contains("\"levels\":[]"));
         assert!(json.contains("\"level_count\":0"));
     }
 
+    #[test]
+    fn test_view_error_to_status_code() {
+        let err = ViewError::InvalidName {
+            name: "a/b".to_string(),
+        };
+        assert_eq!(err.into_response().status(), StatusCode::BAD_REQUEST);
+
+        let err = ViewError::NotFound {
+            slide_id: "slide.svs".to_string(),
+            name: "margin".to_string(),
+        };
+        assert_eq!(err.into_response().status(), StatusCode::NOT_FOUND);
+
+        let err = ViewError::Slide(FormatError::Io(IoError::NotFound("slide.svs".to_string())));
+        assert_eq!(err.into_response().status(), StatusCode::NOT_FOUND);
+
+        let err = ViewError::Io(IoError::S3("read-only".to_string()));
+        assert_eq!(
+            err.into_response().status(),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
     #[test]
     fn test_slide_metadata_error_to_status_code() {
         // Test NotFound -> 404
@@ -1403,4 +5518,35 @@ mod tests {
         let response = err.into_response();
         assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
     }
+
+    #[test]
+    fn test_batch_region_request_deserialization() {
+        let request: BatchRegionsRequest = serde_json::from_str(
+            r#"{"regions": [{"slide_id": "a b.svs", "x": 10, "y": 20, "w": 64, "h": 32, "out_w": 16}]}"#,
+        )
+        .unwrap();
+        assert_eq!(request.format, OutputFormat::Jpeg);
+        assert_eq!(request.quality, DEFAULT_JPEG_QUALITY);
+        assert_eq!(request.regions[0].slide_id, "a b.svs");
+        assert_eq!(request.regions[0].region.out_w, Some(16));
+        assert_eq!(request.regions[0].region.level, None);
+    }
+
+    #[test]
+    fn test_multipart_part() {
+        let part = multipart_part(
+            "b",
+            2,
+            "case/a b.svs",
+            StatusCode::NOT_FOUND,
+            "Content-Type: application/json\r\n",
+            b"{}",
+        );
+        assert_eq!(
+            &part[..],
+            b"--b\r\nContent-Type: application/json\r\nContent-ID: <region-2>\r\n\
+              X-Slide-Id: case%2Fa%20b.svs\r\nX-Status: 404\r\n\r\n{}\r\n"
+        );
+        assert_ne!(multipart_boundary(), multipart_boundary());
+    }
 }
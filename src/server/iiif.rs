@@ -0,0 +1,668 @@
+//! IIIF Image API 3.0 support.
+//!
+//! Exposes slides through the [IIIF Image API](https://iiif.io/api/image/3.0/)
+//! so IIIF viewers (Mirador, OpenSeadragon's IIIF tile source, ...) can
+//! display them without a custom tile source:
+//!
+//! ```text
+//! /iiif/{slide_id}/info.json
+//! /iiif/{slide_id}/{region}/{size}/{rotation}/{quality}.{format}
+//! ```
+//!
+//! This module parses the URL parameters and builds `info.json`; the
+//! requested region is rendered by [`crate::tile::TileService::render_region`].
+//!
+//! # Compliance
+//!
+//! Level 1, plus `sizeUpscaling`, `mirroring`, `rotationBy90s` and the
+//! `color`, `gray` and `bitonal` qualities. Arbitrary rotation angles and
+//! output formats other than `jpg` return `501 Not Implemented`.
+
+use image::{imageops, DynamicImage, GrayImage, Luma, RgbImage};
+use serde::Serialize;
+
+use crate::error::IiifError;
+
+use super::levels::LevelMap;
+
+// =============================================================================
+// Configuration
+// =============================================================================
+
+/// JSON-LD context of the Image API 3.0.
+pub const IIIF_CONTEXT: &str = "http://iiif.io/api/image/3/context.json";
+
+/// Content type of `info.json` responses.
+pub const IIIF_INFO_CONTENT_TYPE: &str =
+    "application/ld+json;profile=\"http://iiif.io/api/image/3/context.json\"";
+
+/// Maximum output area in pixels, advertised as `maxArea`.
+pub const IIIF_MAX_AREA: u64 = 4096 * 4096;
+
+/// Output formats defined by the Image API that this server doesn't produce.
+const UNSUPPORTED_FORMATS: [&str; 6] = ["png", "gif", "webp", "tif", "jp2", "pdf"];
+
+// =============================================================================
+// Request Parameters
+// =============================================================================
+
+/// A region of the full image in level 0 pixels, clipped to the image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IiifRegion {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Rotation applied after scaling.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IiifRotation {
+    /// Mirror horizontally before rotating
+    pub mirror: bool,
+
+    /// Clockwise rotation in degrees (0, 90, 180 or 270)
+    pub degrees: u32,
+}
+
+/// Output image quality.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IiifQuality {
+    Default,
+    Color,
+    Gray,
+    Bitonal,
+}
+
+fn invalid(parameter: &'static str, message: impl Into<String>) -> IiifError {
+    IiifError::InvalidParameter {
+        parameter,
+        message: message.into(),
+    }
+}
+
+fn unsupported(parameter: &'static str, message: impl Into<String>) -> IiifError {
+    IiifError::Unsupported {
+        parameter,
+        message: message.into(),
+    }
+}
+
+/// Parse comma-separated numbers, requiring exactly `N` of them.
+fn parse_numbers<T: std::str::FromStr, const N: usize>(
+    s: &str,
+    parameter: &'static str,
+) -> Result<[T; N], IiifError> {
+    let values: Vec<T> = s
+        .split(',')
+        .map(|v| v.parse::<T>())
+        .collect::<Result<_, _>>()
+        .map_err(|_| invalid(parameter, format!("malformed value '{}'", s)))?;
+    values
+        .try_into()
+        .map_err(|_| invalid(parameter, format!("expected {} values, got '{}'", N, s)))
+}
+
+/// Parse the region parameter against the full image size.
+///
+/// Supports `full`, `square`, `x,y,w,h` and `pct:x,y,w,h`. Regions
+/// extending past the image are clipped.
+pub fn parse_region(s: &str, width: u32, height: u32) -> Result<IiifRegion, IiifError> {
+    let (x, y, w, h) = match s {
+        "full" => (0.0, 0.0, width as f64, height as f64),
+        "square" => {
+            let side = width.min(height);
+            (
+                ((width - side) / 2) as f64,
+                ((height - side) / 2) as f64,
+                side as f64,
+                side as f64,
+            )
+        }
+        _ => match s.strip_prefix("pct:") {
+            Some(pct) => {
+                let [x, y, w, h] = parse_numbers::<f64, 4>(pct, "region")?;
+                if [x, y, w, h].iter().any(|v| !v.is_finite() || *v < 0.0) {
+                    return Err(invalid("region", "percentages must be non-negative"));
+                }
+                (
+                    (x / 100.0 * width as f64).round(),
+                    (y / 100.0 * height as f64).round(),
+                    (w / 100.0 * width as f64).round(),
+                    (h / 100.0 * height as f64).round(),
+                )
+            }
+            None => {
+                let [x, y, w, h] = parse_numbers::<u32, 4>(s, "region")?;
+                (x as f64, y as f64, w as f64, h as f64)
+            }
+        },
+    };
+
+    if w <= 0.0 || h <= 0.0 {
+        return Err(invalid("region", "width and height must be positive"));
+    }
+    if x >= width as f64 || y >= height as f64 {
+        return Err(invalid("region", format!("'{}' is outside the image", s)));
+    }
+
+    Ok(IiifRegion {
+        x: x as u32,
+        y: y as u32,
+        width: w.min(width as f64 - x) as u32,
+        height: h.min(height as f64 - y) as u32,
+    })
+}
+
+/// Parse the size parameter for a region, returning the output size.
+///
+/// Supports `max`, `w,`, `,h`, `pct:n`, `w,h` and `!w,h`, each optionally
+/// prefixed with `^` to allow upscaling. `max` is reduced to fit
+/// `max_area`; explicit sizes above it are rejected.
+pub fn parse_size(s: &str, region: &IiifRegion, max_area: u64) -> Result<(u32, u32), IiifError> {
+    let (upscale, spec) = match s.strip_prefix('^') {
+        Some(rest) => (true, rest),
+        None => (false, s),
+    };
+    let (rw, rh) = (region.width as f64, region.height as f64);
+    let scaled = |factor: f64| {
+        (
+            ((rw * factor).round() as u32).max(1),
+            ((rh * factor).round() as u32).max(1),
+        )
+    };
+
+    let (width, height) = if spec == "max" {
+        let area = rw * rh;
+        let factor = if area > max_area as f64 {
+            (max_area as f64 / area).sqrt()
+        } else {
+            1.0
+        };
+        let (w, h) = scaled(factor);
+        // Rounding must not push the area back over the limit
+        if w as u64 * h as u64 > max_area {
+            scaled(factor * 0.999)
+        } else {
+            (w, h)
+        }
+    } else if let Some(pct) = spec.strip_prefix("pct:") {
+        let n: f64 = pct
+            .parse()
+            .map_err(|_| invalid("size", format!("malformed percentage '{}'", pct)))?;
+        if !(n.is_finite() && n > 0.0) {
+            return Err(invalid("size", "percentage must be positive"));
+        }
+        scaled(n / 100.0)
+    } else {
+        let (confined, dims) = match spec.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, spec),
+        };
+        let (w, h) = dims
+            .split_once(',')
+            .ok_or_else(|| invalid("size", format!("malformed size '{}'", s)))?;
+        let parse = |v: &str| -> Result<Option<u32>, IiifError> {
+            if v.is_empty() {
+                return Ok(None);
+            }
+            match v.parse::<u32>() {
+                Ok(n) if n > 0 => Ok(Some(n)),
+                _ => Err(invalid("size", format!("malformed size '{}'", s))),
+            }
+        };
+        match (parse(w)?, parse(h)?) {
+            (Some(w), Some(h)) if confined => scaled((w as f64 / rw).min(h as f64 / rh)),
+            (Some(w), Some(h)) => (w, h),
+            (Some(w), None) if !confined => (w, ((rh * w as f64 / rw).round() as u32).max(1)),
+            (None, Some(h)) if !confined => (((rw * h as f64 / rh).round() as u32).max(1), h),
+            _ => return Err(invalid("size", format!("malformed size '{}'", s))),
+        }
+    };
+
+    if !upscale && (width > region.width || height > region.height) {
+        return Err(invalid(
+            "size",
+            format!(
+                "{}x{} is larger than the {}x{} region (prefix with ^ to upscale)",
+                width, height, region.width, region.height
+            ),
+        ));
+    }
+    if width as u64 * height as u64 > max_area {
+        return Err(invalid(
+            "size",
+            format!(
+                "{}x{} exceeds the maximum area of {} pixels",
+                width, height, max_area
+            ),
+        ));
+    }
+
+    Ok((width, height))
+}
+
+/// Parse the rotation parameter (`n` or `!n`).
+pub fn parse_rotation(s: &str) -> Result<IiifRotation, IiifError> {
+    let (mirror, angle) = match s.strip_prefix('!') {
+        Some(rest) => (true, rest),
+        None => (false, s),
+    };
+    let degrees: f64 = angle
+        .parse()
+        .map_err(|_| invalid("rotation", format!("malformed angle '{}'", s)))?;
+    if !(0.0..=360.0).contains(&degrees) {
+        return Err(invalid("rotation", "angle must be between 0 and 360"));
+    }
+    if degrees % 90.0 != 0.0 {
+        return Err(unsupported(
+            "rotation",
+            "only multiples of 90 degrees are supported",
+        ));
+    }
+
+    Ok(IiifRotation {
+        mirror,
+        degrees: degrees as u32 % 360,
+    })
+}
+
+/// Parse the `{quality}.{format}` path segment.
+pub fn parse_quality_format(s: &str) -> Result<IiifQuality, IiifError> {
+    let (quality, format) = s
+        .rsplit_once('.')
+        .ok_or_else(|| invalid("format", format!("missing format in '{}'", s)))?;
+
+    let quality = match quality {
+        "default" => IiifQuality::Default,
+        "color" => IiifQuality::Color,
+        "gray" => IiifQuality::Gray,
+        "bitonal" => IiifQuality::Bitonal,
+        other => return Err(invalid("quality", format!("unknown quality '{}'", other))),
+    };
+
+    match format {
+        "jpg" => Ok(quality),
+        f if UNSUPPORTED_FORMATS.contains(&f) => {
+            Err(unsupported("format", format!("'{}' is not supported", f)))
+        }
+        other => Err(invalid("format", format!("unknown format '{}'", other))),
+    }
+}
+
+// =============================================================================
+// Image Processing
+// =============================================================================
+
+/// Apply rotation and quality to a rendered region.
+pub fn apply_rotation_quality(
+    image: RgbImage,
+    rotation: IiifRotation,
+    quality: IiifQuality,
+) -> DynamicImage {
+    let mut image = image;
+    if rotation.mirror {
+        imageops::flip_horizontal_in_place(&mut image);
+    }
+    let image = match rotation.degrees {
+        90 => imageops::rotate90(&image),
+        180 => imageops::rotate180(&image),
+        270 => imageops::rotate270(&image),
+        _ => image,
+    };
+
+    match quality {
+        IiifQuality::Default | IiifQuality::Color => DynamicImage::ImageRgb8(image),
+        IiifQuality::Gray => DynamicImage::ImageLuma8(imageops::grayscale(&image)),
+        IiifQuality::Bitonal => {
+            let gray = imageops::grayscale(&image);
+            let bitonal = GrayImage::from_fn(gray.width(), gray.height(), |x, y| {
+                Luma([if gray.get_pixel(x, y)[0] >= 128 {
+                    255
+                } else {
+                    0
+                }])
+            });
+            DynamicImage::ImageLuma8(bitonal)
+        }
+    }
+}
+
+// =============================================================================
+// Image Information
+// =============================================================================
+
+/// A size clients can request for the full image.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct IiifSize {
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Tile size and the scale factors it is available at.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IiifTiles {
+    pub width: u32,
+    pub height: u32,
+    pub scale_factors: Vec<u64>,
+}
+
+/// The `info.json` image information document.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IiifInfo {
+    #[serde(rename = "@context")]
+    pub context: &'static str,
+
+    pub id: String,
+
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+
+    pub protocol: &'static str,
+
+    pub profile: &'static str,
+
+    pub width: u32,
+
+    pub height: u32,
+
+    pub max_area: u64,
+
+    pub sizes: Vec<IiifSize>,
+
+    pub tiles: Vec<IiifTiles>,
+
+    pub extra_qualities: Vec<&'static str>,
+
+    pub extra_features: Vec<&'static str>,
+}
+
+/// Build the image information for a slide.
+///
+/// # Arguments
+///
+/// * `id` - Absolute base URI of the image service (`.../iiif/{slide_id}`)
+/// * `levels` - Level map of the slide
+///
+/// Advertised sizes are the native pyramid levels (cheapest to render), and
+/// tiles use the level 0 tile size at the map's power-of-two scale factors,
+/// up to the one where the whole image fits in a single tile.
+pub fn build_info(id: String, levels: &LevelMap) -> IiifInfo {
+    let mut sizes: Vec<IiifSize> = levels
+        .native_levels
+        .iter()
+        .filter(|l| l.width as u64 * l.height as u64 <= IIIF_MAX_AREA)
+        .map(|l| IiifSize {
+            width: l.width,
+            height: l.height,
+        })
+        .collect();
+    sizes.sort_by_key(|s| (s.width, s.height));
+
+    IiifInfo {
+        context: IIIF_CONTEXT,
+        id,
+        kind: "ImageService3",
+        protocol: "http://iiif.io/api/image",
+        profile: "level1",
+        width: levels.width,
+        height: levels.height,
+        max_area: IIIF_MAX_AREA,
+        sizes,
+        tiles: vec![IiifTiles {
+            width: levels.tile_width,
+            height: levels.tile_height,
+            scale_factors: levels.iiif_scale_factors(),
+        }],
+        extra_qualities: vec!["color", "gray", "bitonal"],
+        extra_features: vec![
+            "baseUriRedirect",
+            "cors",
+            "jsonldMediaType",
+            "mirroring",
+            "regionByPct",
+            "regionByPx",
+            "regionSquare",
+            "rotationBy90s",
+            "sizeByConfinedWh",
+            "sizeByH",
+            "sizeByPct",
+            "sizeByW",
+            "sizeByWh",
+            "sizeUpscaling",
+        ],
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::slide::LevelInfo;
+
+    fn region(x: u32, y: u32, width: u32, height: u32) -> IiifRegion {
+        IiifRegion {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    #[test]
+    fn test_parse_region() {
+        assert_eq!(
+            parse_region("full", 1000, 500).unwrap(),
+            region(0, 0, 1000, 500)
+        );
+        assert_eq!(
+            parse_region("square", 1000, 500).unwrap(),
+            region(250, 0, 500, 500)
+        );
+        assert_eq!(
+            parse_region("10,20,30,40", 1000, 500).unwrap(),
+            region(10, 20, 30, 40)
+        );
+        // Clipped to the image
+        assert_eq!(
+            parse_region("900,400,500,500", 1000, 500).unwrap(),
+            region(900, 400, 100, 100)
+        );
+        assert_eq!(
+            parse_region("pct:10,10,50,50", 1000, 500).unwrap(),
+            region(100, 50, 500, 250)
+        );
+    }
+
+    #[test]
+    fn test_parse_region_rejects_invalid() {
+        for s in [
+            "",
+            "1,2,3",
+            "a,b,c,d",
+            "0,0,0,10",
+            "1000,0,10,10",
+            "pct:-1,0,5,5",
+        ] {
+            assert!(
+                matches!(
+                    parse_region(s, 1000, 500),
+                    Err(IiifError::InvalidParameter { .. })
+                ),
+                "{}",
+                s
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_size() {
+        let r = region(0, 0, 1000, 500);
+        assert_eq!(parse_size("max", &r, IIIF_MAX_AREA).unwrap(), (1000, 500));
+        assert_eq!(parse_size("500,", &r, IIIF_MAX_AREA).unwrap(), (500, 250));
+        assert_eq!(parse_size(",100", &r, IIIF_MAX_AREA).unwrap(), (200, 100));
+        assert_eq!(parse_size("pct:50", &r, IIIF_MAX_AREA).unwrap(), (500, 250));
+        assert_eq!(
+            parse_size("300,300", &r, IIIF_MAX_AREA).unwrap(),
+            (300, 300)
+        );
+        assert_eq!(
+            parse_size("!300,300", &r, IIIF_MAX_AREA).unwrap(),
+            (300, 150)
+        );
+        assert_eq!(
+            parse_size("^2000,", &r, IIIF_MAX_AREA).unwrap(),
+            (2000, 1000)
+        );
+    }
+
+    #[test]
+    fn test_parse_size_limits() {
+        let r = region(0, 0, 1000, 500);
+        // Upscaling requires ^
+        assert!(parse_size("2000,", &r, IIIF_MAX_AREA).is_err());
+        assert!(parse_size("pct:150", &r, IIIF_MAX_AREA).is_err());
+        // max shrinks to the area limit, explicit sizes are rejected
+        let (w, h) = parse_size("max", &r, 20_000).unwrap();
+        assert!(w as u64 * h as u64 <= 20_000);
+        assert_eq!(w, 2 * h);
+        assert!(parse_size("1000,500", &r, 20_000).is_err());
+        for s in ["", "abc", "0,", ",", "!100,", "pct:0"] {
+            assert!(parse_size(s, &r, IIIF_MAX_AREA).is_err(), "{}", s);
+        }
+    }
+
+    #[test]
+    fn test_parse_rotation() {
+        assert_eq!(
+            parse_rotation("0").unwrap(),
+            IiifRotation {
+                mirror: false,
+                degrees: 0
+            }
+        );
+        assert_eq!(
+            parse_rotation("!90").unwrap(),
+            IiifRotation {
+                mirror: true,
+                degrees: 90
+            }
+        );
+        assert_eq!(parse_rotation("360").unwrap().degrees, 0);
+        assert!(matches!(
+            parse_rotation("45"),
+            Err(IiifError::Unsupported { .. })
+        ));
+        assert!(matches!(
+            parse_rotation("400"),
+            Err(IiifError::InvalidParameter { .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_quality_format() {
+        assert_eq!(
+            parse_quality_format("default.jpg").unwrap(),
+            IiifQuality::Default
+        );
+        assert_eq!(
+            parse_quality_format("bitonal.jpg").unwrap(),
+            IiifQuality::Bitonal
+        );
+        assert!(matches!(
+            parse_quality_format("default.png"),
+            Err(IiifError::Unsupported { .. })
+        ));
+        assert!(matches!(
+            parse_quality_format("sepia.jpg"),
+            Err(IiifError::InvalidParameter { .. })
+        ));
+        assert!(parse_quality_format("default").is_err());
+    }
+
+    #[test]
+    fn test_apply_rotation_quality() {
+        let image = RgbImage::from_fn(4, 2, |x, _| image::Rgb([x as u8 * 60, 0, 0]));
+
+        let rotated = apply_rotation_quality(
+            image.clone(),
+            IiifRotation {
+                mirror: false,
+                degrees: 90,
+            },
+            IiifQuality::Default,
+        );
+        assert_eq!((rotated.width(), rotated.height()), (2, 4));
+
+        let mirrored = apply_rotation_quality(
+            image.clone(),
+            IiifRotation {
+                mirror: true,
+                degrees: 0,
+            },
+            IiifQuality::Color,
+        )
+        .to_rgb8();
+        assert_eq!(mirrored.get_pixel(0, 0)[0], 180);
+
+        let bitonal = apply_rotation_quality(
+            image,
+            IiifRotation {
+                mirror: false,
+                degrees: 0,
+            },
+            IiifQuality::Bitonal,
+        )
+        .to_luma8();
+        assert!(bitonal.pixels().all(|p| p[0] == 0 || p[0] == 255));
+    }
+
+    #[test]
+    fn test_build_info() {
+        let levels = [
+            LevelInfo {
+                width: 2000,
+                height: 1000,
+                tile_width: 256,
+                tile_height: 256,
+                tiles_x: 8,
+                tiles_y: 4,
+                downsample: 1.0,
+            },
+            LevelInfo {
+                width: 500,
+                height: 250,
+                tile_width: 256,
+                tile_height: 256,
+                tiles_x: 2,
+                tiles_y: 1,
+                downsample: 4.0,
+            },
+        ];
+        let info = build_info(
+            "http://localhost/iiif/slide.svs".to_string(),
+            &LevelMap::new(&levels),
+        );
+
+        assert_eq!((info.width, info.height), (2000, 1000));
+        assert_eq!(
+            info.sizes[0],
+            IiifSize {
+                width: 500,
+                height: 250
+            }
+        );
+        assert_eq!(info.tiles[0].scale_factors, vec![1, 2, 4, 8]);
+
+        let json = serde_json::to_value(&info).unwrap();
+        assert_eq!(json["@context"], IIIF_CONTEXT);
+        assert_eq!(json["type"], "ImageService3");
+        assert_eq!(json["maxArea"], IIIF_MAX_AREA);
+        assert_eq!(json["tiles"][0]["scaleFactors"][3], 8);
+    }
+}
@@ -0,0 +1,317 @@
+//! Level mapping between the native pyramid and protocol level numbering.
+//!
+//! The three ways of addressing a resolution disagree:
+//!
+//! - **Native pyramid**: level 0 is full resolution, levels usually step by
+//!   4x and stop well above 1 pixel
+//! - **Deep Zoom**: level 0 is 1x1 pixel, each level doubles up to full
+//!   resolution at `ceil(log2(max(width, height)))`
+//! - **IIIF**: power-of-two scale factors relative to full resolution
+//!
+//! [`LevelMap`] lines them up: for every Deep Zoom level it records the
+//! matching IIIF scale factor, the native level tiles are read from, and
+//! whether the level is native or synthesized by downscaling. DZI tiles, IIIF
+//! `info.json` and `GET /slides/{slide_id}/levels` are all derived from it,
+//! so clients never have to reproduce the level math.
+//!
+//! Dimensions and downsamples are computed in 64-bit arithmetic, so slides
+//! wider than 2^31 pixels (33 Deep Zoom levels) map cleanly.
+
+use serde::Serialize;
+
+use crate::slide::LevelInfo;
+use crate::tile::select_source_level;
+
+use super::dzi::{calculate_max_dzi_level, dzi_level_dimensions, dzi_tile_count};
+
+/// Relative difference under which two downsamples are considered equal.
+const DOWNSAMPLE_TOLERANCE: f64 = 0.001;
+
+/// A level stored in the slide's pyramid.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NativeLevel {
+    /// Native level index (0 = full resolution)
+    pub level: usize,
+
+    /// Width in pixels
+    pub width: u32,
+
+    /// Height in pixels
+    pub height: u32,
+
+    /// Downsample relative to level 0
+    pub downsample: f64,
+
+    /// Deep Zoom level with the same resolution, if the downsample is a
+    /// power of two
+    pub dzi_level: Option<usize>,
+}
+
+/// A Deep Zoom / IIIF level and how it is rendered.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProtocolLevel {
+    /// Deep Zoom level (0 = 1x1 pixel)
+    pub dzi_level: usize,
+
+    /// IIIF scale factor (power of two)
+    pub iiif_scale_factor: u64,
+
+    /// Downsample relative to level 0 (equal to the scale factor)
+    pub downsample: f64,
+
+    /// Width in pixels
+    pub width: u32,
+
+    /// Height in pixels
+    pub height: u32,
+
+    /// Number of Deep Zoom tiles horizontally
+    pub tiles_x: u32,
+
+    /// Number of Deep Zoom tiles vertically
+    pub tiles_y: u32,
+
+    /// Native level tiles are read from
+    pub source_level: usize,
+
+    /// Downsample of the source level
+    pub source_downsample: f64,
+
+    /// Whether the level is rendered by downscaling a finer native level
+    pub synthesized: bool,
+}
+
+/// Mapping between native pyramid levels and Deep Zoom / IIIF levels.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LevelMap {
+    /// Full resolution width in pixels
+    pub width: u32,
+
+    /// Full resolution height in pixels
+    pub height: u32,
+
+    /// Tile width used by Deep Zoom and IIIF (level 0 tile width)
+    pub tile_width: u32,
+
+    /// Tile height used by IIIF (level 0 tile height)
+    pub tile_height: u32,
+
+    /// Highest Deep Zoom level (full resolution)
+    pub max_dzi_level: usize,
+
+    /// Native pyramid levels, level 0 first
+    pub native_levels: Vec<NativeLevel>,
+
+    /// Deep Zoom levels, indexed by Deep Zoom level
+    pub levels: Vec<ProtocolLevel>,
+}
+
+impl LevelMap {
+    /// Build the level map of a slide.
+    ///
+    /// # Arguments
+    ///
+    /// * `levels` - Native pyramid levels, level 0 first
+    pub fn new(levels: &[LevelInfo]) -> Self {
+        let (width, height) = levels
+            .first()
+            .map(|l| (l.width, l.height))
+            .unwrap_or((0, 0));
+        let (tile_width, tile_height) = levels
+            .first()
+            .map(|l| (l.tile_width.max(1), l.tile_height.max(1)))
+            .unwrap_or((256, 256));
+        let max_dzi_level = calculate_max_dzi_level(width, height);
+        let downsamples: Vec<f64> = levels.iter().map(|l| l.downsample).collect();
+
+        let native_levels = levels
+            .iter()
+            .enumerate()
+            .map(|(level, info)| NativeLevel {
+                level,
+                width: info.width,
+                height: info.height,
+                downsample: info.downsample,
+                dzi_level: matching_dzi_level(info.downsample, max_dzi_level),
+            })
+            .collect();
+
+        let levels = (0..=max_dzi_level)
+            .map(|dzi_level| {
+                let shift = (max_dzi_level - dzi_level).min(63);
+                let scale_factor = 1u64 << shift;
+                let downsample = scale_factor as f64;
+                let (level_width, level_height) =
+                    dzi_level_dimensions(width, height, dzi_level, max_dzi_level);
+                let (tiles_x, tiles_y) = dzi_tile_count(level_width, level_height, tile_width);
+
+                let source_level = select_source_level(downsample, &downsamples);
+                let source_downsample = downsamples.get(source_level).copied().unwrap_or(1.0);
+
+                ProtocolLevel {
+                    dzi_level,
+                    iiif_scale_factor: scale_factor,
+                    downsample,
+                    width: level_width,
+                    height: level_height,
+                    tiles_x,
+                    tiles_y,
+                    source_level,
+                    source_downsample,
+                    synthesized: !same_downsample(source_downsample, downsample),
+                }
+            })
+            .collect();
+
+        LevelMap {
+            width,
+            height,
+            tile_width,
+            tile_height,
+            max_dzi_level,
+            native_levels,
+            levels,
+        }
+    }
+
+    /// Get a Deep Zoom level.
+    pub fn dzi_level(&self, dzi_level: usize) -> Option<&ProtocolLevel> {
+        self.levels.get(dzi_level)
+    }
+
+    /// Downsample factor of each native level, level 0 first.
+    pub fn native_downsamples(&self) -> Vec<f64> {
+        self.native_levels.iter().map(|l| l.downsample).collect()
+    }
+
+    /// IIIF tile scale factors, from full resolution up to the first scale
+    /// at which the whole image fits in a single tile.
+    pub fn iiif_scale_factors(&self) -> Vec<u64> {
+        let mut factors = Vec::new();
+        for level in self.levels.iter().rev() {
+            factors.push(level.iiif_scale_factor);
+            if level.width <= self.tile_width && level.height <= self.tile_height {
+                break;
+            }
+        }
+        factors
+    }
+}
+
+/// Whether two downsample factors are equal up to rounding.
+fn same_downsample(a: f64, b: f64) -> bool {
+    (a - b).abs() <= b.abs() * DOWNSAMPLE_TOLERANCE
+}
+
+/// Deep Zoom level whose downsample matches a native downsample, if any.
+fn matching_dzi_level(downsample: f64, max_dzi_level: usize) -> Option<usize> {
+    if !downsample.is_finite() || downsample < 1.0 {
+        return None;
+    }
+    let steps = downsample.log2().round() as usize;
+    if steps > max_dzi_level || !same_downsample(downsample, (1u64 << steps.min(63)) as f64) {
+        return None;
+    }
+    Some(max_dzi_level - steps)
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn level_info(width: u32, height: u32, downsample: f64) -> LevelInfo {
+        LevelInfo {
+            width,
+            height,
+            tile_width: 256,
+            tile_height: 256,
+            tiles_x: width.div_ceil(256),
+            tiles_y: height.div_ceil(256),
+            downsample,
+        }
+    }
+
+    #[test]
+    fn test_level_map_native_and_synthesized() {
+        let map = LevelMap::new(&[level_info(2000, 1000, 1.0), level_info(500, 250, 4.0)]);
+
+        // ceil(log2(2000)) = 11
+        assert_eq!(map.max_dzi_level, 11);
+        assert_eq!(map.levels.len(), 12);
+
+        let full = map.dzi_level(11).unwrap();
+        assert_eq!((full.width, full.height), (2000, 1000));
+        assert_eq!((full.tiles_x, full.tiles_y), (8, 4));
+        assert_eq!(full.source_level, 0);
+        assert!(!full.synthesized);
+
+        let half = map.dzi_level(10).unwrap();
+        assert_eq!(half.iiif_scale_factor, 2);
+        assert_eq!(half.source_level, 0);
+        assert!(half.synthesized);
+
+        let quarter = map.dzi_level(9).unwrap();
+        assert_eq!((quarter.width, quarter.height), (500, 250));
+        assert_eq!(quarter.source_level, 1);
+        assert!(!quarter.synthesized);
+
+        let smallest = map.dzi_level(0).unwrap();
+        assert_eq!((smallest.width, smallest.height), (1, 1));
+        assert_eq!(smallest.source_level, 1);
+        assert!(smallest.synthesized);
+
+        assert_eq!(map.native_levels[0].dzi_level, Some(11));
+        assert_eq!(map.native_levels[1].dzi_level, Some(9));
+    }
+
+    #[test]
+    fn test_native_level_off_power_of_two() {
+        // Aperio-style rounding still matches; odd factors don't
+        assert_eq!(matching_dzi_level(4.0002, 11), Some(9));
+        assert_eq!(matching_dzi_level(3.0, 11), None);
+        assert_eq!(matching_dzi_level(4096.0, 11), None);
+    }
+
+    #[test]
+    fn test_iiif_scale_factors() {
+        let map = LevelMap::new(&[level_info(2000, 1000, 1.0)]);
+        assert_eq!(map.iiif_scale_factors(), vec![1, 2, 4, 8]);
+
+        let map = LevelMap::new(&[level_info(100, 100, 1.0)]);
+        assert_eq!(map.iiif_scale_factors(), vec![1]);
+    }
+
+    #[test]
+    fn test_deep_pyramid() {
+        // 40 native levels stepping by 2x, on a slide wider than 2^31 pixels
+        let width = u32::MAX;
+        let levels: Vec<LevelInfo> = (0u32..40)
+            .map(|i| {
+                let ds = (1u64 << i.min(32)) as f64;
+                level_info(
+                    ((width as f64 / ds).ceil() as u32).max(1),
+                    1,
+                    ds * (1 + i.saturating_sub(32)) as f64,
+                )
+            })
+            .collect();
+        let map = LevelMap::new(&levels);
+
+        assert_eq!(map.max_dzi_level, 32);
+        assert_eq!(map.levels.len(), 33);
+        assert_eq!(map.dzi_level(0).unwrap().iiif_scale_factor, 1u64 << 32);
+        assert!(map.levels.iter().all(|l| !l.synthesized));
+        assert_eq!(map.iiif_scale_factors().len(), 25);
+    }
+
+    #[test]
+    fn test_empty_slide() {
+        let map = LevelMap::new(&[]);
+        assert_eq!(map.max_dzi_level, 0);
+        assert_eq!(map.iiif_scale_factors(), vec![1]);
+    }
+}
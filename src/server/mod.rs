@@ -17,16 +17,87 @@
 //! ```
 
 pub mod auth;
+pub mod bandwidth;
+pub mod compression;
+pub mod conditional;
+pub mod connections;
 pub mod dzi;
 pub mod handlers;
+pub mod iiif;
+pub mod levels;
+pub mod panic;
+pub mod rate_limit;
+pub mod raw;
+pub mod readiness;
+pub mod reload;
+pub mod request_id;
+pub mod route_table;
 pub mod routes;
+pub mod slo;
+pub mod stream;
+pub mod timing;
+#[cfg(feature = "tls")]
+pub mod tls;
 pub mod viewer;
+pub mod ws;
 
-pub use auth::{auth_middleware, AuthError, AuthQueryParams, OptionalAuth, SignedUrlAuth};
+pub use auth::{
+    auth_middleware, AccessClaims, AuthError, AuthQueryParams, AuthSubject, ClaimEndpoint,
+    OptionalAuth, SharePolicy, SignedUrlAuth, DEFAULT_SECRET_ROTATION_GRACE, DEFAULT_SHARE_TTL,
+    MAX_SHARE_TTL,
+};
+pub use bandwidth::{
+    bandwidth_middleware, BandwidthConfig, BandwidthLimiter, ThrottledBody, TokenBucket,
+    THROTTLE_CHUNK_SIZE,
+};
+pub use compression::{compression_layer, JsonResponses, COMPRESSION_MIN_SIZE};
+pub use conditional::{format_http_date, is_not_modified, parse_http_date, TileValidator};
+pub use connections::{
+    connection_middleware, ConnectionSnapshot, ConnectionTracker, RequestGuard, TrackedConnection,
+};
 pub use handlers::{
-    dzi_descriptor_handler, health_handler, slide_metadata_handler, slides_handler,
-    thumbnail_handler, tile_handler, viewer_handler, AppState, ErrorResponse, HealthResponse,
-    LevelMetadataResponse, SlideMetadataResponse, SlidesQueryParams, SlidesResponse,
-    ThumbnailQueryParams, TilePathParams, TileQueryParams,
+    cache_stats_handler, capabilities_handler, connections_handler, dzi_descriptor_handler,
+    dzi_file_handler, dzi_tile_handler, get_view_handler, health_handler, iiif_image_handler,
+    iiif_info_handler, iiif_redirect_handler, metrics_handler, quarantine_handler,
+    quarantine_release_handler, raw_slide_handler, region_handler, regions_batch_handler,
+    reload_handler, sample_handler, save_view_handler, share_handler, share_viewer_handler,
+    slide_headers_middleware, slide_info_handler, slide_invalidate_handler,
+    slide_io_metrics_handler, slide_io_stats_handler, slide_levels_handler, slide_metadata_handler,
+    slides_handler, slo_handler, snapshot_handler, sprites_handler, thumbnail_handler,
+    tile_grid_handler, tile_handler, tile_hash_handler, tissue_mask_handler, verify_slide_handler,
+    viewer_handler, AppState, BatchRegionRequest, BatchRegionsRequest, CacheStatsResponse,
+    ConnectionEntryResponse, ConnectionsResponse, ErrorResponse, HealthResponse, IiifImageParams,
+    IiifQueryParams, LevelMetadataResponse, MemorySummary, QuarantineReleaseResponse,
+    QuarantineResponse, RegionGeometry, RegionQueryParams, SampleQueryParams, SampleResponse,
+    SampledTileResponse, SaveViewRequest, ShareLinkResponse, ShareQueryParams, SlideInfoResponse,
+    SlideInvalidateResponse, SlideIoStatsResponse, SlideLevelsResponse, SlideMetadataResponse,
+    SlidesQueryParams, SlidesResponse, SnapshotQueryParams, SpriteQueryParams, SpriteSheetResponse,
+    ThumbnailQueryParams, TileCacheSummary, TileGridQueryParams, TileGridResponse,
+    TileHashResponse, TilePathParams, TileQueryParams, VerifyQueryParams, ViewResponse,
+};
+pub use levels::{LevelMap, NativeLevel, ProtocolLevel};
+pub use panic::{
+    catch_panic_layer, install_panic_hook, panic_count, PanicHandler, CRASH_ID_HEADER,
+};
+pub use rate_limit::{
+    rate_limit_middleware, RateLimitConfig, RateLimitKey, RateLimited, RateLimiter,
+};
+pub use raw::{parse_range, range_request, ByteRange, RangeRequest, RAW_READ_CHUNK_SIZE};
+pub use readiness::{ReadinessProbe, StorageCheck, DEFAULT_READINESS_TTL, READINESS_CHECK_TIMEOUT};
+pub use reload::{ConfigReloader, LiveSettings, ReloadReport, ReloadableSettings, SettingsSource};
+pub use request_id::{request_id_middleware, RequestId, MAX_REQUEST_ID_LEN, REQUEST_ID_HEADER};
+pub use route_table::{
+    cache_policy_middleware, endpoints, openapi_paths, Access, CachePolicy, EndpointSummary,
+    OpenApiOperation, RouteHandler, RouteMethod, RouteSpec, Surface, ROUTES,
+};
+pub use routes::{
+    create_dev_router, create_production_router, create_router, create_split_routers, RouterConfig,
+    SplitRouters,
+};
+pub use slo::{
+    slo_middleware, SloConfig, SloSummary, SloTracker, SloWindow, DEFAULT_AVAILABILITY_TARGET,
+    DEFAULT_LATENCY_TARGET, DEFAULT_LATENCY_THRESHOLD,
 };
-pub use routes::{create_dev_router, create_production_router, create_router, RouterConfig};
+pub use stream::{active_streams, blocking_body, spawned_body, ChunkWriter, STREAM_CHUNK_SIZE};
+#[cfg(feature = "tls")]
+pub use tls::{https_redirect_router, load_server_config, TlsListener, TLS_HANDSHAKE_TIMEOUT};
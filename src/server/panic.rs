@@ -0,0 +1,254 @@
+//! Panic recovery for request handlers.
+//!
+//! A panic while serving a request (for example in a codec hitting a
+//! malformed slide) would otherwise abort the connection with nothing in the
+//! logs. [`catch_panic_layer`] turns such panics into a structured `500`
+//! response carrying a crash ID, logs the panic message, location and
+//! backtrace under that ID, and counts it in `wsi_panics_total`.
+//!
+//! Backtraces can only be captured while the panic unwinds, so
+//! [`install_panic_hook`] should be called once at startup. Without it the
+//! response and log entry are still produced, just without a backtrace.
+//!
+//! The hook records the details on the panicking thread. Work moved to
+//! another thread (such as [`EncodePool`](crate::tile::EncodePool)) carries
+//! them back with [`CaughtPanic`] so the response still has them.
+
+use std::any::Any;
+use std::backtrace::Backtrace;
+use std::cell::RefCell;
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Once;
+use std::task::Poll;
+
+use axum::{
+    body::Body,
+    http::{header, HeaderValue, Response, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use tower_http::catch_panic::CatchPanicLayer;
+use tracing::error;
+
+use super::handlers::ErrorResponse;
+use super::request_id::new_id;
+
+/// Response header carrying the crash ID of a recovered panic.
+pub const CRASH_ID_HEADER: &str = "X-Crash-Id";
+
+static PANIC_COUNT: AtomicU64 = AtomicU64::new(0);
+static INSTALL_HOOK: Once = Once::new();
+
+/// Details of the last panic on this thread, recorded by the panic hook.
+struct PanicDetails {
+    location: String,
+    backtrace: String,
+}
+
+thread_local! {
+    static LAST_PANIC: RefCell<Option<PanicDetails>> = const { RefCell::new(None) };
+}
+
+/// A panic caught on one thread, to be resumed on another.
+///
+/// Holds the payload together with the location and backtrace the panic
+/// hook recorded on the thread that panicked.
+pub(crate) struct CaughtPanic {
+    payload: Box<dyn Any + Send + 'static>,
+    details: Option<PanicDetails>,
+}
+
+impl CaughtPanic {
+    /// Run `f`, catching a panic along with its recorded details.
+    pub(crate) fn catch<F: FnOnce() -> T, T>(f: F) -> Result<T, CaughtPanic> {
+        std::panic::catch_unwind(AssertUnwindSafe(f)).map_err(|payload| CaughtPanic {
+            payload,
+            details: LAST_PANIC.with(|last| last.borrow_mut().take()),
+        })
+    }
+
+    /// Drive `future` to completion, catching a panic raised while polling
+    /// it along with its recorded details.
+    ///
+    /// Use this for spawned tasks whose panics are resumed by the caller;
+    /// a `JoinError` only carries the payload.
+    pub(crate) async fn catch_async<F: Future>(future: F) -> Result<F::Output, CaughtPanic> {
+        let mut future = std::pin::pin!(future);
+        std::future::poll_fn(|cx| match Self::catch(|| future.as_mut().poll(cx)) {
+            Ok(Poll::Ready(output)) => Poll::Ready(Ok(output)),
+            Ok(Poll::Pending) => Poll::Pending,
+            Err(panic) => Poll::Ready(Err(panic)),
+        })
+        .await
+    }
+
+    /// Resume the panic on the current thread.
+    ///
+    /// The details are restored first, since resuming does not run the
+    /// panic hook again.
+    pub(crate) fn resume(self) -> ! {
+        if self.details.is_some() {
+            LAST_PANIC.with(|last| *last.borrow_mut() = self.details);
+        }
+        std::panic::resume_unwind(self.payload)
+    }
+}
+
+/// Take the location of the last panic recorded on this thread.
+#[cfg(test)]
+pub(crate) fn take_panic_location() -> Option<String> {
+    LAST_PANIC.with(|last| last.borrow_mut().take().map(|d| d.location))
+}
+
+/// Number of handler panics recovered since startup.
+pub fn panic_count() -> u64 {
+    PANIC_COUNT.load(Ordering::Relaxed)
+}
+
+/// Install a panic hook recording the location and backtrace of each panic
+/// for [`catch_panic_layer`] to log.
+///
+/// The previously installed hook still runs. Calling this more than once
+/// has no further effect.
+pub fn install_panic_hook() {
+    INSTALL_HOOK.call_once(|| {
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            let details = PanicDetails {
+                location: info
+                    .location()
+                    .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()))
+                    .unwrap_or_else(|| "unknown".to_string()),
+                backtrace: Backtrace::force_capture().to_string(),
+            };
+            LAST_PANIC.with(|last| *last.borrow_mut() = Some(details));
+            previous(info);
+        }));
+    });
+}
+
+/// Handler building the response for a recovered panic.
+pub type PanicHandler = fn(Box<dyn Any + Send + 'static>) -> Response<Body>;
+
+/// Layer converting handler panics into `500` responses.
+pub fn catch_panic_layer() -> CatchPanicLayer<PanicHandler> {
+    CatchPanicLayer::custom(panic_response as PanicHandler)
+}
+
+/// Build the response for a recovered panic and log it.
+fn panic_response(payload: Box<dyn Any + Send + 'static>) -> Response<Body> {
+    PANIC_COUNT.fetch_add(1, Ordering::Relaxed);
+
+    let crash_id = new_id();
+    let message = panic_message(payload.as_ref());
+    let details = LAST_PANIC.with(|last| last.borrow_mut().take());
+    let (location, backtrace) = match details {
+        Some(d) => (d.location, d.backtrace),
+        None => ("unknown".to_string(), "unavailable".to_string()),
+    };
+
+    error!(
+        crash_id = %crash_id,
+        location = %location,
+        backtrace = %backtrace,
+        "Request handler panicked: {}",
+        message
+    );
+
+    let body = ErrorResponse::with_status(
+        "internal_panic",
+        format!(
+            "Internal error while handling the request (crash ID {})",
+            crash_id
+        ),
+        StatusCode::INTERNAL_SERVER_ERROR,
+    );
+    let mut response = (StatusCode::INTERNAL_SERVER_ERROR, Json(body)).into_response();
+    if let Ok(value) = HeaderValue::from_str(&crash_id) {
+        response.headers_mut().insert(CRASH_ID_HEADER, value);
+    }
+    response
+        .headers_mut()
+        .insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+    response
+}
+
+/// Extract the message from a panic payload.
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "non-string panic payload".to_string()
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{routing::get, Router};
+    use http_body_util::BodyExt;
+    use tower::ServiceExt;
+
+    async fn boom() -> &'static str {
+        panic!("corrupt tile table");
+    }
+
+    #[tokio::test]
+    async fn test_panic_becomes_500_with_crash_id() {
+        install_panic_hook();
+        let before = panic_count();
+
+        let router = Router::new()
+            .route("/boom", get(boom))
+            .layer(catch_panic_layer());
+        let request = axum::http::Request::builder()
+            .uri("/boom")
+            .body(Body::empty())
+            .unwrap();
+        let response = router.oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        let crash_id = response.headers()[CRASH_ID_HEADER]
+            .to_str()
+            .unwrap()
+            .to_string();
+        assert_eq!(crash_id.len(), 16);
+
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["error"], "internal_panic");
+        assert!(json["message"].as_str().unwrap().contains(&crash_id));
+        assert!(panic_count() > before);
+    }
+
+    #[test]
+    fn test_panic_message() {
+        assert_eq!(panic_message(&"static"), "static");
+        assert_eq!(panic_message(&"owned".to_string()), "owned");
+        assert_eq!(panic_message(&42u32), "non-string panic payload");
+    }
+
+    #[test]
+    fn test_caught_panic_carries_details() {
+        install_panic_hook();
+
+        let caught =
+            std::thread::spawn(|| CaughtPanic::catch::<_, ()>(|| panic!("on another thread")))
+                .join()
+                .unwrap()
+                .err()
+                .unwrap();
+        let payload = std::panic::catch_unwind(AssertUnwindSafe(|| caught.resume())).unwrap_err();
+
+        assert_eq!(panic_message(payload.as_ref()), "on another thread");
+        assert!(take_panic_location().unwrap().contains("panic.rs"));
+    }
+}
@@ -0,0 +1,422 @@
+//! Per-client request rate limiting.
+//!
+//! Every request takes a token from its client's bucket; when the bucket is
+//! empty the request is answered with `429 Too Many Requests` and a
+//! `Retry-After` header instead of reaching storage. This keeps a single
+//! misbehaving viewer from exhausting the S3 request quota shared by
+//! everyone.
+//!
+//! Clients are identified either by IP address or by the subject of their
+//! signed URL: the share token, viewer token or access claims it carries.
+//! Subjects are only taken from requests the auth layer has verified (see
+//! [`AuthSubject`]), so a client can't get a fresh bucket by inventing a
+//! token; the limiter runs inside the auth layer for that reason. Path-signed
+//! URLs have a different signature per tile, and unverified requests have
+//! no subject, so both fall back to the IP address.
+//!
+//! Like bandwidth limits, IP addresses require the server to be run with
+//! connect info. Requests without a client identity are not limited.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::net::SocketAddr;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use tracing::warn;
+
+use super::auth::AuthSubject;
+use super::bandwidth::TokenBucket;
+use super::handlers::ErrorResponse;
+use crate::error::ErrorCode;
+
+// =============================================================================
+// Configuration
+// =============================================================================
+
+/// Time after which an unused client bucket may be discarded.
+const BUCKET_IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Minimum time between two sweeps for idle buckets.
+const BUCKET_PRUNE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Maximum number of clients tracked at once. Past it, new clients share
+/// one overflow bucket until idle buckets are pruned.
+const MAX_TRACKED_CLIENTS: usize = 65_536;
+
+/// How requests are grouped into clients.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RateLimitKey {
+    /// Client IP address
+    #[default]
+    Ip,
+
+    /// Signed-URL subject (share token, viewer token or access claims),
+    /// falling back to the IP address
+    Subject,
+}
+
+impl RateLimitKey {
+    /// Name used on the command line.
+    pub fn name(&self) -> &'static str {
+        match self {
+            RateLimitKey::Ip => "ip",
+            RateLimitKey::Subject => "subject",
+        }
+    }
+}
+
+impl FromStr for RateLimitKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "ip" => Ok(RateLimitKey::Ip),
+            "subject" => Ok(RateLimitKey::Subject),
+            _ => Err(format!(
+                "Invalid rate limit key '{}'. Expected ip or subject",
+                s
+            )),
+        }
+    }
+}
+
+/// Request rate limits per client.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RateLimitConfig {
+    /// Requests per second allowed per client (0 = unlimited)
+    pub requests_per_sec: u64,
+
+    /// Requests a client may make at once before being limited
+    /// (0 = one second's worth)
+    pub burst: u64,
+
+    /// How requests are grouped into clients
+    pub key: RateLimitKey,
+}
+
+impl RateLimitConfig {
+    /// Create a configuration limiting every client to `requests_per_sec`.
+    pub fn new(requests_per_sec: u64) -> Self {
+        Self {
+            requests_per_sec,
+            ..Self::default()
+        }
+    }
+
+    /// Set the number of requests a client may make at once.
+    pub fn with_burst(mut self, burst: u64) -> Self {
+        self.burst = burst;
+        self
+    }
+
+    /// Set how requests are grouped into clients.
+    pub fn with_key(mut self, key: RateLimitKey) -> Self {
+        self.key = key;
+        self
+    }
+
+    /// Whether any request could be limited.
+    pub fn is_enabled(&self) -> bool {
+        self.requests_per_sec > 0
+    }
+
+    /// Effective burst size.
+    pub fn effective_burst(&self) -> u64 {
+        if self.burst == 0 {
+            self.requests_per_sec
+        } else {
+            self.burst
+        }
+    }
+}
+
+// =============================================================================
+// Errors
+// =============================================================================
+
+/// A client has used up its request allowance.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RateLimited {
+    /// Time until the client may make another request
+    pub retry_after: Duration,
+}
+
+impl RateLimited {
+    /// Whole seconds to send in `Retry-After` (at least 1).
+    pub fn retry_after_secs(&self) -> u64 {
+        self.retry_after.as_secs_f64().ceil().max(1.0) as u64
+    }
+}
+
+impl fmt::Display for RateLimited {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Rate limit exceeded, retry after {} second(s)",
+            self.retry_after_secs()
+        )
+    }
+}
+
+impl std::error::Error for RateLimited {}
+
+impl ErrorCode for RateLimited {
+    fn code(&self) -> &'static str {
+        "rate_limited"
+    }
+
+    fn status(&self) -> StatusCode {
+        StatusCode::TOO_MANY_REQUESTS
+    }
+
+    fn is_retryable(&self) -> bool {
+        true
+    }
+}
+
+impl IntoResponse for RateLimited {
+    fn into_response(self) -> Response {
+        let body = ErrorResponse::with_status(self.code(), self.to_string(), self.status())
+            .with_retryable(self.is_retryable());
+
+        (
+            self.status(),
+            [(header::RETRY_AFTER, self.retry_after_secs().to_string())],
+            Json(body),
+        )
+            .into_response()
+    }
+}
+
+// =============================================================================
+// Limiter
+// =============================================================================
+
+/// Assigns request buckets to clients according to a [`RateLimitConfig`].
+#[derive(Debug)]
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    clients: Mutex<ClientBuckets>,
+    overflow: Arc<TokenBucket>,
+}
+
+/// Tracked client buckets and when idle ones were last pruned.
+#[derive(Debug)]
+struct ClientBuckets {
+    buckets: HashMap<String, Arc<TokenBucket>>,
+    pruned_at: Instant,
+}
+
+impl RateLimiter {
+    /// Create a limiter with the given configuration.
+    pub fn new(config: RateLimitConfig) -> Self {
+        let overflow = Arc::new(TokenBucket::with_burst(
+            config.requests_per_sec,
+            config.effective_burst(),
+        ));
+        Self {
+            config,
+            clients: Mutex::new(ClientBuckets {
+                buckets: HashMap::new(),
+                pruned_at: Instant::now(),
+            }),
+            overflow,
+        }
+    }
+
+    /// Identify the client making a request, if possible.
+    ///
+    /// `subject` must come from a verified request.
+    pub fn client_key(
+        &self,
+        peer: Option<SocketAddr>,
+        subject: Option<&AuthSubject>,
+    ) -> Option<String> {
+        if self.config.key == RateLimitKey::Subject {
+            if let Some(AuthSubject(subject)) = subject {
+                return Some(subject.clone());
+            }
+        }
+        peer.map(|peer| format!("ip:{}", peer.ip()))
+    }
+
+    /// Take one request from a client's bucket.
+    pub fn check(&self, client: &str) -> Result<(), RateLimited> {
+        self.bucket(client)
+            .try_acquire(1)
+            .map_err(|retry_after| RateLimited { retry_after })
+    }
+
+    /// Number of clients currently tracked.
+    pub fn tracked_clients(&self) -> usize {
+        self.clients.lock().unwrap().buckets.len()
+    }
+
+    fn bucket(&self, client: &str) -> Arc<TokenBucket> {
+        self.bucket_at(client, Instant::now(), MAX_TRACKED_CLIENTS)
+    }
+
+    fn bucket_at(&self, client: &str, now: Instant, max_clients: usize) -> Arc<TokenBucket> {
+        let mut clients = self.clients.lock().unwrap();
+        if let Some(bucket) = clients.buckets.get(client) {
+            return bucket.clone();
+        }
+
+        // Sweeps are rate limited themselves, so a flood of new clients
+        // doesn't turn every insert into a full scan
+        if now.saturating_duration_since(clients.pruned_at) >= BUCKET_PRUNE_INTERVAL {
+            clients
+                .buckets
+                .retain(|_, bucket| bucket.idle_for(now) < BUCKET_IDLE_TIMEOUT);
+            clients.pruned_at = now;
+        }
+        if clients.buckets.len() >= max_clients {
+            return self.overflow.clone();
+        }
+
+        let bucket = Arc::new(TokenBucket::with_burst(
+            self.config.requests_per_sec,
+            self.config.effective_burst(),
+        ));
+        clients.buckets.insert(client.to_string(), bucket.clone());
+        bucket
+    }
+}
+
+/// Middleware rejecting requests from clients over their rate limit.
+pub async fn rate_limit_middleware(
+    State(limiter): State<Arc<RateLimiter>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let peer = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|info| info.0);
+    let Some(client) = limiter.client_key(peer, request.extensions().get::<AuthSubject>()) else {
+        return next.run(request).await;
+    };
+
+    match limiter.check(&client) {
+        Ok(()) => next.run(request).await,
+        Err(limited) => {
+            warn!(
+                path = %request.uri().path(),
+                retry_after = limited.retry_after_secs(),
+                "Rate limit exceeded"
+            );
+            limited.into_response()
+        }
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn peer(port: u16) -> Option<SocketAddr> {
+        Some(SocketAddr::from(([10, 0, 0, 1], port)))
+    }
+
+    #[test]
+    fn test_parse_key() {
+        assert_eq!("ip".parse::<RateLimitKey>(), Ok(RateLimitKey::Ip));
+        assert_eq!("Subject".parse::<RateLimitKey>(), Ok(RateLimitKey::Subject));
+        assert!("user".parse::<RateLimitKey>().is_err());
+        assert_eq!(RateLimitKey::Subject.name(), "subject");
+    }
+
+    #[test]
+    fn test_effective_burst() {
+        assert!(!RateLimitConfig::default().is_enabled());
+        assert_eq!(RateLimitConfig::new(20).effective_burst(), 20);
+        assert_eq!(
+            RateLimitConfig::new(20).with_burst(50).effective_burst(),
+            50
+        );
+    }
+
+    #[test]
+    fn test_client_key() {
+        let subject = AuthSubject("vt:abc".to_string());
+
+        // Connections from the same IP share a client
+        let limiter = RateLimiter::new(RateLimitConfig::new(10));
+        assert_eq!(
+            limiter.client_key(peer(1000), Some(&subject)),
+            limiter.client_key(peer(1001), None)
+        );
+        assert_eq!(limiter.client_key(None, Some(&subject)), None);
+
+        let limiter = RateLimiter::new(RateLimitConfig::new(10).with_key(RateLimitKey::Subject));
+        assert_eq!(
+            limiter.client_key(peer(1000), Some(&subject)).as_deref(),
+            Some("vt:abc")
+        );
+
+        // Path-signed and unverified requests have no subject and fall back
+        // to the IP
+        assert_eq!(
+            limiter.client_key(peer(1000), None).as_deref(),
+            Some("ip:10.0.0.1")
+        );
+    }
+
+    #[test]
+    fn test_tracked_clients_are_capped() {
+        let limiter = RateLimiter::new(RateLimitConfig::new(1).with_burst(1));
+        let start = Instant::now();
+        limiter.bucket_at("a", start, 2);
+        limiter.bucket_at("b", start, 2);
+
+        // Past the cap, new clients share the overflow bucket
+        let c = limiter.bucket_at("c", start, 2);
+        let d = limiter.bucket_at("d", start, 2);
+        assert!(Arc::ptr_eq(&c, &d));
+        assert_eq!(limiter.tracked_clients(), 2);
+
+        // Idle buckets are pruned on the next sweep, making room again
+        let later = start + BUCKET_IDLE_TIMEOUT + BUCKET_PRUNE_INTERVAL;
+        let e = limiter.bucket_at("e", later, 2);
+        assert!(!Arc::ptr_eq(&e, &c));
+        assert_eq!(limiter.tracked_clients(), 1);
+    }
+
+    #[test]
+    fn test_check_limits_each_client() {
+        let limiter = RateLimiter::new(RateLimitConfig::new(1).with_burst(2));
+        assert!(limiter.check("a").is_ok());
+        assert!(limiter.check("a").is_ok());
+
+        let limited = limiter.check("a").unwrap_err();
+        assert_eq!(limited.retry_after_secs(), 1);
+        assert_eq!(limited.status(), StatusCode::TOO_MANY_REQUESTS);
+
+        // Other clients are unaffected
+        assert!(limiter.check("b").is_ok());
+        assert_eq!(limiter.tracked_clients(), 2);
+    }
+
+    #[test]
+    fn test_rate_limited_response() {
+        let response = RateLimited {
+            retry_after: Duration::from_millis(2500),
+        }
+        .into_response();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "3");
+    }
+}
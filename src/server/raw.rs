@@ -0,0 +1,225 @@
+//! Byte-range access to original slide files.
+//!
+//! `GET /slides/{slide_id}/raw` serves the slide file itself, so tools that
+//! read the original format (OpenSlide and friends over HTTP) can go through
+//! the same auth layer and block cache as the tile endpoints instead of
+//! needing separate presigned storage URLs.
+//!
+//! A single `Range: bytes=...` is honored with `206 Partial Content`.
+//! Multiple ranges are not supported; such requests get the whole file, which
+//! RFC 9110 allows. `If-Range` is honored against the storage ETag or the
+//! file's modification time.
+
+use std::time::SystemTime;
+
+use axum::http::{header, HeaderMap};
+
+use super::conditional::{parse_http_date, truncate_to_secs};
+
+/// Bytes read from storage per chunk of a raw response body.
+pub const RAW_READ_CHUNK_SIZE: usize = 1024 * 1024;
+
+// =============================================================================
+// Byte Ranges
+// =============================================================================
+
+/// An inclusive byte range within a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ByteRange {
+    /// First byte offset
+    pub start: u64,
+
+    /// Last byte offset (inclusive)
+    pub end: u64,
+}
+
+impl ByteRange {
+    /// Number of bytes in the range.
+    pub fn len(&self) -> u64 {
+        self.end - self.start + 1
+    }
+
+    /// Whether the range is empty (never true for a parsed range).
+    pub fn is_empty(&self) -> bool {
+        self.end < self.start
+    }
+
+    /// `Content-Range` value for this range of a file of `size` bytes.
+    pub fn content_range(&self, size: u64) -> String {
+        format!("bytes {}-{}/{}", self.start, self.end, size)
+    }
+}
+
+/// How a request's `Range` header applies to a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RangeRequest {
+    /// Serve the whole file (no range, an ignored range, or several ranges)
+    Full,
+
+    /// Serve one range with `206 Partial Content`
+    Partial(ByteRange),
+
+    /// No requested byte lies within the file; answer `416`
+    Unsatisfiable,
+}
+
+/// Resolve a `Range` header value against a file of `size` bytes.
+///
+/// Accepts `bytes=start-end`, `bytes=start-` and `bytes=-suffix`. Values in
+/// another unit or that don't parse are ignored, as RFC 9110 requires.
+pub fn parse_range(value: &str, size: u64) -> RangeRequest {
+    let Some(spec) = value.trim().strip_prefix("bytes=") else {
+        return RangeRequest::Full;
+    };
+    if spec.contains(',') {
+        return RangeRequest::Full;
+    }
+    let Some((first, last)) = spec.trim().split_once('-') else {
+        return RangeRequest::Full;
+    };
+
+    if first.is_empty() {
+        // Suffix range: the last N bytes
+        let Ok(suffix) = last.parse::<u64>() else {
+            return RangeRequest::Full;
+        };
+        if suffix == 0 || size == 0 {
+            return RangeRequest::Unsatisfiable;
+        }
+        return RangeRequest::Partial(ByteRange {
+            start: size.saturating_sub(suffix),
+            end: size - 1,
+        });
+    }
+
+    let Ok(start) = first.parse::<u64>() else {
+        return RangeRequest::Full;
+    };
+    let end = if last.is_empty() {
+        u64::MAX
+    } else {
+        match last.parse::<u64>() {
+            Ok(end) if end >= start => end,
+            _ => return RangeRequest::Full,
+        }
+    };
+    if start >= size {
+        return RangeRequest::Unsatisfiable;
+    }
+
+    RangeRequest::Partial(ByteRange {
+        start,
+        end: end.min(size - 1),
+    })
+}
+
+/// Resolve a request's range headers against a file.
+///
+/// A `Range` is only honored if `If-Range` is absent or still matches the
+/// file: an ETag must match strongly, and a date must equal the
+/// modification time exactly.
+pub fn range_request(
+    headers: &HeaderMap,
+    size: u64,
+    etag: Option<&str>,
+    last_modified: Option<SystemTime>,
+) -> RangeRequest {
+    let Some(range) = headers.get(header::RANGE).and_then(|v| v.to_str().ok()) else {
+        return RangeRequest::Full;
+    };
+
+    if let Some(if_range) = headers.get(header::IF_RANGE) {
+        let Ok(if_range) = if_range.to_str() else {
+            return RangeRequest::Full;
+        };
+        let current = if if_range.starts_with('"') {
+            etag.is_some_and(|etag| !etag.starts_with("W/") && etag == if_range)
+        } else {
+            match (parse_http_date(if_range), last_modified) {
+                (Some(date), Some(modified)) => date == truncate_to_secs(modified),
+                _ => false,
+            }
+        };
+        if !current {
+            return RangeRequest::Full;
+        }
+    }
+
+    parse_range(range, size)
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn partial(start: u64, end: u64) -> RangeRequest {
+        RangeRequest::Partial(ByteRange { start, end })
+    }
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range("bytes=0-99", 1000), partial(0, 99));
+        assert_eq!(parse_range("bytes=900-", 1000), partial(900, 999));
+        assert_eq!(parse_range("bytes=900-5000", 1000), partial(900, 999));
+        assert_eq!(parse_range("bytes=-100", 1000), partial(900, 999));
+        assert_eq!(parse_range("bytes=-5000", 1000), partial(0, 999));
+
+        assert_eq!(
+            parse_range("bytes=1000-", 1000),
+            RangeRequest::Unsatisfiable
+        );
+        assert_eq!(parse_range("bytes=-0", 1000), RangeRequest::Unsatisfiable);
+
+        assert_eq!(parse_range("bytes=0-1,5-6", 1000), RangeRequest::Full);
+        assert_eq!(parse_range("bytes=9-1", 1000), RangeRequest::Full);
+        assert_eq!(parse_range("items=0-1", 1000), RangeRequest::Full);
+        assert_eq!(parse_range("bytes=abc", 1000), RangeRequest::Full);
+
+        let range = ByteRange { start: 10, end: 19 };
+        assert_eq!(range.len(), 10);
+        assert_eq!(range.content_range(1000), "bytes 10-19/1000");
+    }
+
+    #[test]
+    fn test_if_range() {
+        let modified = UNIX_EPOCH + Duration::from_secs(784_111_777);
+        let mut headers = HeaderMap::new();
+        headers.insert(header::RANGE, HeaderValue::from_static("bytes=0-9"));
+        assert_eq!(range_request(&headers, 100, None, None), partial(0, 9));
+
+        headers.insert(header::IF_RANGE, HeaderValue::from_static("\"abc\""));
+        assert_eq!(
+            range_request(&headers, 100, Some("\"abc\""), None),
+            partial(0, 9)
+        );
+        assert_eq!(
+            range_request(&headers, 100, Some("\"def\""), None),
+            RangeRequest::Full
+        );
+        assert_eq!(range_request(&headers, 100, None, None), RangeRequest::Full);
+
+        headers.insert(
+            header::IF_RANGE,
+            HeaderValue::from_static("Sun, 06 Nov 1994 08:49:37 GMT"),
+        );
+        assert_eq!(
+            range_request(&headers, 100, None, Some(modified)),
+            partial(0, 9)
+        );
+        assert_eq!(
+            range_request(
+                &headers,
+                100,
+                None,
+                Some(modified + Duration::from_secs(60))
+            ),
+            RangeRequest::Full
+        );
+    }
+}
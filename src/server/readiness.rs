@@ -0,0 +1,158 @@
+//! Readiness probing of the storage backend.
+//!
+//! `/livez` only says the process is serving requests. `/readyz` also
+//! checks that the storage backend answers with the configured credentials,
+//! so an orchestrator stops routing traffic to an instance whose S3
+//! credentials expired or whose bucket became unreachable, instead of
+//! letting it fail every tile.
+//!
+//! The check is one cheap request
+//! ([`SlideSource::check_ready`](crate::slide::SlideSource::check_ready)), cached
+//! for a few seconds so frequent probes from several kubelets and load
+//! balancers don't add storage load. Concurrent probes share one check.
+
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tokio::sync::Mutex;
+
+use crate::error::IoError;
+
+/// Default time a storage check result is reused.
+pub const DEFAULT_READINESS_TTL: Duration = Duration::from_secs(5);
+
+/// Time a storage check may take before the backend counts as unreachable.
+pub const READINESS_CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Outcome of a storage reachability check.
+#[derive(Debug, Clone, Serialize)]
+pub struct StorageCheck {
+    /// Whether the backend answered successfully
+    pub reachable: bool,
+
+    /// Error of a failed check
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+
+    /// Time the check took, in milliseconds
+    pub latency_ms: u64,
+
+    /// Time since the check ran, in milliseconds
+    pub age_ms: u64,
+
+    #[serde(skip)]
+    checked_at: Instant,
+}
+
+impl StorageCheck {
+    fn new(result: Result<(), IoError>, latency: Duration, checked_at: Instant) -> Self {
+        Self {
+            reachable: result.is_ok(),
+            error: result.err().map(|e| e.to_string()),
+            latency_ms: latency.as_millis() as u64,
+            age_ms: 0,
+            checked_at,
+        }
+    }
+}
+
+/// Caches the result of storage reachability checks.
+#[derive(Debug)]
+pub struct ReadinessProbe {
+    ttl: Duration,
+    last: Mutex<Option<StorageCheck>>,
+}
+
+impl ReadinessProbe {
+    /// Create a probe reusing check results for `ttl`.
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            last: Mutex::new(None),
+        }
+    }
+
+    /// Get the time a check result is reused.
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Return the cached check result, running `check` if it expired.
+    ///
+    /// A check that doesn't finish within [`READINESS_CHECK_TIMEOUT`]
+    /// counts as a failure.
+    pub async fn check<F, Fut>(&self, check: F) -> StorageCheck
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<(), IoError>>,
+    {
+        // Held across the check, so concurrent probes wait for one result
+        let mut last = self.last.lock().await;
+        if let Some(cached) = last.as_ref() {
+            if cached.checked_at.elapsed() < self.ttl {
+                let mut cached = cached.clone();
+                cached.age_ms = cached.checked_at.elapsed().as_millis() as u64;
+                return cached;
+            }
+        }
+
+        let started = Instant::now();
+        let result = match tokio::time::timeout(READINESS_CHECK_TIMEOUT, check()).await {
+            Ok(result) => result,
+            Err(_) => Err(IoError::Connection(format!(
+                "storage check timed out after {}ms",
+                READINESS_CHECK_TIMEOUT.as_millis()
+            ))),
+        };
+        let result = StorageCheck::new(result, started.elapsed(), started);
+        *last = Some(result.clone());
+        result
+    }
+}
+
+impl Default for ReadinessProbe {
+    fn default() -> Self {
+        Self::new(DEFAULT_READINESS_TTL)
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn test_results_are_cached() {
+        let probe = ReadinessProbe::default();
+        let calls = AtomicUsize::new(0);
+        let check = || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err(IoError::S3("ExpiredToken".to_string()))
+        };
+
+        let first = probe.check(check).await;
+        assert!(!first.reachable);
+        assert!(first.error.unwrap().contains("ExpiredToken"));
+
+        let second = probe.check(check).await;
+        assert!(!second.reachable);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_expired_results_are_rechecked() {
+        let probe = ReadinessProbe::new(Duration::ZERO);
+        assert!(
+            !probe
+                .check(|| async { Err(IoError::S3("down".to_string())) })
+                .await
+                .reachable
+        );
+        assert!(probe.check(|| async { Ok(()) }).await.reachable);
+    }
+}
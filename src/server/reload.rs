@@ -0,0 +1,307 @@
+//! Runtime reload of selected settings.
+//!
+//! A few settings can change without a restart, which would drop open
+//! connections and empty the slide and tile caches:
+//!
+//! - the signing secret; URLs and tokens signed with the replaced secret
+//!   keep verifying until the next rotation
+//! - the allowed CORS origins
+//! - the `Cache-Control` max-age
+//! - the log filter
+//!
+//! A [`ConfigReloader`] fetches fresh values from a [`SettingsSource`] and
+//! applies them to every router it is attached to. It runs on
+//! `POST /admin/reload`; the binary also runs it on `SIGHUP` and when its
+//! configuration or secret file changes.
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+
+use async_trait::async_trait;
+use axum::http::HeaderValue;
+use serde::Serialize;
+
+use super::auth::SignedUrlAuth;
+
+/// Values of the settings a reload can change.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReloadableSettings {
+    /// Secret key for signed URLs (ignored when authentication is disabled)
+    pub auth_secret: Option<String>,
+
+    /// Allowed CORS origins (None = any origin)
+    pub cors_origins: Option<Vec<String>>,
+
+    /// `Cache-Control` max-age in seconds
+    pub cache_max_age: u32,
+
+    /// Log filter directives (None = leave the filter alone)
+    pub log_filter: Option<String>,
+}
+
+/// Where a reload reads settings from.
+#[async_trait]
+pub trait SettingsSource: Send + Sync {
+    /// Read the current settings.
+    async fn load(&self) -> Result<ReloadableSettings, String>;
+}
+
+// =============================================================================
+// Live Settings
+// =============================================================================
+
+/// Settings read on every request, so a reload takes effect immediately.
+#[derive(Debug)]
+pub struct LiveSettings {
+    cache_max_age: AtomicU32,
+
+    /// Allowed CORS origins (None = any origin)
+    cors_origins: RwLock<Option<Vec<HeaderValue>>>,
+}
+
+impl LiveSettings {
+    /// Create live settings with their startup values.
+    pub fn new(cache_max_age: u32, cors_origins: Option<&[String]>) -> Self {
+        Self {
+            cache_max_age: AtomicU32::new(cache_max_age),
+            cors_origins: RwLock::new(parse_origins(cors_origins)),
+        }
+    }
+
+    /// Get the `Cache-Control` max-age in seconds.
+    pub fn cache_max_age(&self) -> u32 {
+        self.cache_max_age.load(Ordering::Relaxed)
+    }
+
+    /// Set the `Cache-Control` max-age, returning whether it changed.
+    pub fn set_cache_max_age(&self, seconds: u32) -> bool {
+        self.cache_max_age.swap(seconds, Ordering::Relaxed) != seconds
+    }
+
+    /// Whether CORS requests from an origin are allowed.
+    pub fn allows_origin(&self, origin: &HeaderValue) -> bool {
+        match &*self.cors_origins.read().unwrap() {
+            None => true,
+            Some(origins) => origins.contains(origin),
+        }
+    }
+
+    /// Set the allowed CORS origins, returning whether they changed.
+    ///
+    /// `None` allows any origin and an empty list none; origins that
+    /// aren't valid header values are ignored.
+    pub fn set_cors_origins(&self, origins: Option<&[String]>) -> bool {
+        let origins = parse_origins(origins);
+        let mut current = self.cors_origins.write().unwrap();
+        if *current == origins {
+            return false;
+        }
+        *current = origins;
+        true
+    }
+}
+
+fn parse_origins(origins: Option<&[String]>) -> Option<Vec<HeaderValue>> {
+    origins.map(|origins| origins.iter().filter_map(|o| o.parse().ok()).collect())
+}
+
+// =============================================================================
+// Reloader
+// =============================================================================
+
+/// Settings changed by a reload.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ReloadReport {
+    /// Names of the settings whose value changed
+    pub changed: Vec<&'static str>,
+}
+
+impl ReloadReport {
+    fn record(&mut self, setting: &'static str, changed: bool) {
+        if changed && !self.changed.contains(&setting) {
+            self.changed.push(setting);
+        }
+    }
+}
+
+/// Settings of one router that a reload updates.
+struct ReloadTarget {
+    live: Arc<LiveSettings>,
+    auth: Option<SignedUrlAuth>,
+}
+
+type LogFilterHook = Box<dyn Fn(&str) -> Result<(), String> + Send + Sync>;
+
+/// Reloads settings from a [`SettingsSource`] into running routers.
+pub struct ConfigReloader {
+    source: Box<dyn SettingsSource>,
+    log_filter: Option<LogFilterHook>,
+    current_log_filter: Mutex<Option<String>>,
+    targets: Mutex<Vec<ReloadTarget>>,
+
+    /// Held while reloading, so concurrent reloads apply in order
+    running: tokio::sync::Mutex<()>,
+}
+
+impl ConfigReloader {
+    /// Create a reloader reading settings from `source`.
+    pub fn new(source: impl SettingsSource + 'static) -> Self {
+        Self {
+            source: Box::new(source),
+            log_filter: None,
+            current_log_filter: Mutex::new(None),
+            targets: Mutex::new(Vec::new()),
+            running: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    /// Apply reloaded log filters with `hook`, starting from `initial`.
+    pub fn with_log_filter(
+        mut self,
+        initial: impl Into<String>,
+        hook: impl Fn(&str) -> Result<(), String> + Send + Sync + 'static,
+    ) -> Self {
+        self.log_filter = Some(Box::new(hook));
+        self.current_log_filter = Mutex::new(Some(initial.into()));
+        self
+    }
+
+    /// Have reloads update a router's live settings and authenticator.
+    ///
+    /// Called when a router is built with this reloader.
+    pub fn attach(&self, live: Arc<LiveSettings>, auth: Option<SignedUrlAuth>) {
+        self.targets
+            .lock()
+            .unwrap()
+            .push(ReloadTarget { live, auth });
+    }
+
+    /// Read the settings again and apply them.
+    ///
+    /// Nothing is applied if the source fails.
+    pub async fn reload(&self) -> Result<ReloadReport, String> {
+        let _running = self.running.lock().await;
+        let settings = self.source.load().await?;
+        let mut report = ReloadReport::default();
+
+        if let (Some(hook), Some(filter)) = (&self.log_filter, &settings.log_filter) {
+            let mut current = self.current_log_filter.lock().unwrap();
+            if current.as_ref() != Some(filter) {
+                hook(filter)?;
+                *current = Some(filter.clone());
+                report.record("log_filter", true);
+            }
+        }
+
+        for target in self.targets.lock().unwrap().iter() {
+            if let (Some(auth), Some(secret)) = (&target.auth, &settings.auth_secret) {
+                report.record("auth_secret", auth.rotate_secret(secret));
+            }
+            report.record(
+                "cors_origins",
+                target
+                    .live
+                    .set_cors_origins(settings.cors_origins.as_deref()),
+            );
+            report.record(
+                "cache_max_age",
+                target.live.set_cache_max_age(settings.cache_max_age),
+            );
+        }
+
+        Ok(report)
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    struct FixedSource(Arc<Mutex<Result<ReloadableSettings, String>>>);
+
+    #[async_trait]
+    impl SettingsSource for FixedSource {
+        async fn load(&self) -> Result<ReloadableSettings, String> {
+            self.0.lock().unwrap().clone()
+        }
+    }
+
+    fn settings() -> ReloadableSettings {
+        ReloadableSettings {
+            auth_secret: Some("old-secret".to_string()),
+            cors_origins: None,
+            cache_max_age: 3600,
+            log_filter: Some("info".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_live_cors_origins() {
+        let live = LiveSettings::new(60, Some(&["https://a.example".to_string()]));
+        assert!(live.allows_origin(&HeaderValue::from_static("https://a.example")));
+        assert!(!live.allows_origin(&HeaderValue::from_static("https://b.example")));
+
+        assert!(live.set_cors_origins(None));
+        assert!(live.allows_origin(&HeaderValue::from_static("https://b.example")));
+        assert!(!live.set_cors_origins(None));
+
+        assert!(live.set_cors_origins(Some(&[])));
+        assert!(!live.allows_origin(&HeaderValue::from_static("https://a.example")));
+    }
+
+    #[tokio::test]
+    async fn test_reload_applies_changes() {
+        let loaded = Arc::new(Mutex::new(Ok(settings())));
+        let source = FixedSource(Arc::clone(&loaded));
+        let filters = Arc::new(Mutex::new(Vec::new()));
+        let applied = Arc::clone(&filters);
+        let reloader = ConfigReloader::new(source).with_log_filter("info", move |filter| {
+            applied.lock().unwrap().push(filter.to_string());
+            Ok(())
+        });
+        let live = Arc::new(LiveSettings::new(3600, None));
+        let auth = SignedUrlAuth::new("old-secret");
+        reloader.attach(Arc::clone(&live), Some(auth.clone()));
+
+        // Nothing changed yet
+        assert!(reloader.reload().await.unwrap().changed.is_empty());
+
+        *loaded.lock().unwrap() = Ok(ReloadableSettings {
+            auth_secret: Some("new-secret".to_string()),
+            cors_origins: Some(vec!["https://a.example".to_string()]),
+            cache_max_age: 60,
+            log_filter: Some("debug".to_string()),
+        });
+        let report = reloader.reload().await.unwrap();
+        assert_eq!(
+            report.changed,
+            ["log_filter", "auth_secret", "cors_origins", "cache_max_age"]
+        );
+        assert_eq!(live.cache_max_age(), 60);
+        assert_eq!(*filters.lock().unwrap(), ["debug"]);
+
+        let path = "/tiles/slides/a.svs/0/0/0.jpg";
+        let (sig, expiry) = auth.sign(path, Duration::from_secs(60));
+        assert_eq!(
+            sig,
+            SignedUrlAuth::new("new-secret").sign_with_expiry(path, expiry)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_failed_reload_applies_nothing() {
+        let reloader = ConfigReloader::new(FixedSource(Arc::new(Mutex::new(Err(
+            "bad file".to_string()
+        )))));
+        let live = Arc::new(LiveSettings::new(3600, None));
+        reloader.attach(Arc::clone(&live), None);
+
+        assert_eq!(reloader.reload().await.unwrap_err(), "bad file");
+        assert_eq!(live.cache_max_age(), 3600);
+    }
+}
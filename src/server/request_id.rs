@@ -0,0 +1,169 @@
+//! Request IDs for correlating logs across services.
+//!
+//! Every request is assigned an ID, taken from the client's `X-Request-Id`
+//! header when it sends a usable one (so a proxy or viewer backend can
+//! thread its own ID through) and generated otherwise. The ID is echoed on
+//! the response, stored in the request extensions as [`RequestId`], and
+//! recorded on a `request` span wrapping the rest of the stack, so every
+//! log line emitted while handling the request carries it.
+
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use axum::{
+    extract::Request,
+    http::{HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use tracing::Instrument;
+
+/// Header carrying the request ID in both directions.
+pub const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Longest client-supplied request ID that is honored.
+pub const MAX_REQUEST_ID_LEN: usize = 128;
+
+static ID_SEQUENCE: AtomicU64 = AtomicU64::new(0);
+
+/// Generate a short ID, unique within and across processes, for
+/// correlating a response with its log entries.
+///
+/// Used for request IDs and the crash IDs of recovered panics.
+pub(crate) fn new_id() -> String {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(ID_SEQUENCE.fetch_add(1, Ordering::Relaxed));
+    hasher.write_u128(
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or(0),
+    );
+    format!("{:016x}", hasher.finish())
+}
+
+/// ID of the request being handled, available as a request extension.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestId(Arc<str>);
+
+impl RequestId {
+    /// Use a client-supplied ID if it is non-empty, at most
+    /// [`MAX_REQUEST_ID_LEN`] bytes, and printable ASCII without spaces.
+    pub fn parse(value: &str) -> Option<Self> {
+        let valid = !value.is_empty()
+            && value.len() <= MAX_REQUEST_ID_LEN
+            && value.bytes().all(|b| b.is_ascii_graphic());
+        valid.then(|| Self(Arc::from(value)))
+    }
+
+    /// Generate a new ID.
+    pub fn generate() -> Self {
+        Self(Arc::from(new_id()))
+    }
+
+    /// Get the ID as a string.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for RequestId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Middleware assigning each request an ID and echoing it on the response.
+pub async fn request_id_middleware(mut request: Request, next: Next) -> Response {
+    let request_id = request
+        .headers()
+        .get(&REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(RequestId::parse)
+        .unwrap_or_else(RequestId::generate);
+    request.extensions_mut().insert(request_id.clone());
+
+    let span = tracing::info_span!(
+        "request",
+        request_id = %request_id,
+        method = %request.method(),
+        path = request.uri().path(),
+    );
+    let mut response = next.run(request).instrument(span).await;
+
+    // IDs are printable ASCII, so the header value is always valid
+    if let Ok(value) = HeaderValue::from_str(request_id.as_str()) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    response
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, middleware, routing::get, Extension, Router};
+    use http_body_util::BodyExt;
+    use tower::ServiceExt;
+
+    async fn echo(Extension(request_id): Extension<RequestId>) -> String {
+        request_id.to_string()
+    }
+
+    fn app() -> Router {
+        Router::new()
+            .route("/", get(echo))
+            .layer(middleware::from_fn(request_id_middleware))
+    }
+
+    #[test]
+    fn test_parse_rejects_unusable_ids() {
+        assert_eq!(RequestId::parse("abc-123").unwrap().as_str(), "abc-123");
+        assert!(RequestId::parse("").is_none());
+        assert!(RequestId::parse("has space").is_none());
+        assert!(RequestId::parse(&"a".repeat(MAX_REQUEST_ID_LEN + 1)).is_none());
+    }
+
+    #[test]
+    fn test_generated_ids_differ() {
+        let first = RequestId::generate();
+        let second = RequestId::generate();
+        assert_eq!(first.as_str().len(), 16);
+        assert_ne!(first, second);
+    }
+
+    #[tokio::test]
+    async fn test_client_id_is_honored() {
+        let request = axum::http::Request::get("/")
+            .header("X-Request-Id", "trace-42")
+            .body(Body::empty())
+            .unwrap();
+        let response = app().oneshot(request).await.unwrap();
+        assert_eq!(response.headers()["x-request-id"], "trace-42");
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(&body[..], b"trace-42");
+    }
+
+    #[tokio::test]
+    async fn test_missing_id_is_generated() {
+        let request = axum::http::Request::get("/")
+            .header("X-Request-Id", "bad id")
+            .body(Body::empty())
+            .unwrap();
+        let response = app().oneshot(request).await.unwrap();
+        let echoed = response.headers()["x-request-id"]
+            .to_str()
+            .unwrap()
+            .to_string();
+        assert_ne!(echoed, "bad id");
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(&body[..], echoed.as_bytes());
+    }
+}
@@ -0,0 +1,723 @@
+//! Declarative route table.
+//!
+//! Every HTTP route is declared once in [`ROUTES`]: its method, path, access
+//! requirement, cache policy and handler. The table drives the routers built
+//! in [`super::routes`], the endpoint list reported by `/capabilities`, and
+//! [`openapi_paths`], which the client contract tests compare against
+//! `clients/openapi.json`. A route added here is routed, covered (or not) by
+//! the auth layer and checked against the documentation in one place.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderValue},
+    middleware::Next,
+    response::Response,
+    routing::MethodFilter,
+};
+use serde::Serialize;
+
+use super::reload::LiveSettings;
+
+// =============================================================================
+// Route Attributes
+// =============================================================================
+
+/// HTTP method of a route.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RouteMethod {
+    /// `GET` (also answers `HEAD`)
+    Get,
+    /// `POST`
+    Post,
+}
+
+impl RouteMethod {
+    /// Method name, e.g. `GET`.
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            RouteMethod::Get => "GET",
+            RouteMethod::Post => "POST",
+        }
+    }
+
+    /// Method filter for the router.
+    pub fn filter(&self) -> MethodFilter {
+        match self {
+            RouteMethod::Get => MethodFilter::GET,
+            RouteMethod::Post => MethodFilter::POST,
+        }
+    }
+}
+
+/// Who may call a route.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Access {
+    /// No credentials required
+    Public,
+    /// Signed URL, viewer token or share token, when authentication is enabled
+    Signed,
+    /// The handler verifies a share token in the path itself
+    ShareToken,
+}
+
+/// Which listener serves a route when admin surfaces are split off
+/// (see [`super::routes::create_split_routers`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Surface {
+    /// Tile API, slide metadata, Deep Zoom, IIIF and viewer
+    Api,
+    /// Admin, debug and metrics
+    Admin,
+    /// Served on both listeners
+    Both,
+}
+
+/// How responses may be cached by clients and proxies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CachePolicy {
+    /// No `Cache-Control` header
+    Default,
+    /// Cacheable for `--cache-max-age` seconds; successful responses get
+    /// `public, max-age=N` unless the handler set its own directive
+    Public,
+    /// Never cached; responses get `no-store` unless the handler set its own
+    /// directive
+    NoStore,
+}
+
+/// Handler serving a route.
+///
+/// Handlers are generic over the slide source, so the table names them and
+/// [`super::routes`] maps each name to its function.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RouteHandler {
+    Health,
+    Livez,
+    Readyz,
+    Metrics,
+    Capabilities,
+    Slo,
+    Connections,
+    CacheStats,
+    Quarantine,
+    QuarantineRelease,
+    SlideInvalidate,
+    SlideIoStats,
+    SlideIoMetrics,
+    Reload,
+    TileHash,
+    Tile,
+    Slides,
+    SlideMetadata,
+    DziDescriptor,
+    SlideInfo,
+    SlideLevels,
+    TileGrid,
+    TissueMask,
+    RawSlide,
+    VerifySlide,
+    Thumbnail,
+    Sample,
+    Snapshot,
+    SaveView,
+    GetView,
+    SaveAnnotations,
+    GetAnnotations,
+    Share,
+    Region,
+    RegionsBatch,
+    Sprites,
+    DziFile,
+    DziTile,
+    IiifRedirect,
+    IiifInfo,
+    IiifImage,
+    Viewer,
+    CompareViewer,
+    ShareViewer,
+    Events,
+}
+
+/// OpenAPI operation for a route documented in `clients/openapi.json`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OpenApiOperation {
+    /// Stable operation ID, used as the generated client method name
+    pub operation_id: &'static str,
+
+    /// Tag grouping the operation
+    pub tag: &'static str,
+}
+
+// =============================================================================
+// Route Spec
+// =============================================================================
+
+/// Declaration of one route.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RouteSpec {
+    /// HTTP method
+    pub method: RouteMethod,
+
+    /// Router path pattern, e.g. `/tiles/{slide_id}/{level}/{x}/{filename}`
+    pub path: &'static str,
+
+    /// Path as documented, e.g. `/tiles/{slide_id}/{level}/{x}/{y}.jpg`
+    pub doc_path: &'static str,
+
+    /// Handler serving the route
+    pub handler: RouteHandler,
+
+    /// Who may call the route
+    pub access: Access,
+
+    /// Which listener serves the route
+    pub surface: Surface,
+
+    /// Caching of responses
+    pub cache: CachePolicy,
+
+    /// Whether the route names a slide, so its sidecar headers apply
+    pub slide_scoped: bool,
+
+    /// Whether requests count toward the tile SLO
+    pub tracks_slo: bool,
+
+    /// One-line description
+    pub summary: &'static str,
+
+    /// OpenAPI operation, for routes in the client contract
+    pub openapi: Option<OpenApiOperation>,
+}
+
+impl RouteSpec {
+    /// Declare a signed API route with default caching.
+    pub const fn new(
+        method: RouteMethod,
+        path: &'static str,
+        handler: RouteHandler,
+        summary: &'static str,
+    ) -> Self {
+        Self {
+            method,
+            path,
+            doc_path: path,
+            handler,
+            access: Access::Signed,
+            surface: Surface::Api,
+            cache: CachePolicy::Default,
+            slide_scoped: false,
+            tracks_slo: false,
+            summary,
+            openapi: None,
+        }
+    }
+
+    /// Declare a `GET` route.
+    pub const fn get(path: &'static str, handler: RouteHandler, summary: &'static str) -> Self {
+        Self::new(RouteMethod::Get, path, handler, summary)
+    }
+
+    /// Declare a `POST` route.
+    pub const fn post(path: &'static str, handler: RouteHandler, summary: &'static str) -> Self {
+        Self::new(RouteMethod::Post, path, handler, summary)
+    }
+
+    /// Document the route under a different path template.
+    pub const fn with_doc_path(mut self, doc_path: &'static str) -> Self {
+        self.doc_path = doc_path;
+        self
+    }
+
+    /// Set who may call the route.
+    pub const fn with_access(mut self, access: Access) -> Self {
+        self.access = access;
+        self
+    }
+
+    /// Set which listener serves the route.
+    pub const fn with_surface(mut self, surface: Surface) -> Self {
+        self.surface = surface;
+        self
+    }
+
+    /// Set the cache policy.
+    pub const fn with_cache(mut self, cache: CachePolicy) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// Mark the route as naming a slide.
+    pub const fn scoped_to_slide(mut self) -> Self {
+        self.slide_scoped = true;
+        self
+    }
+
+    /// Count requests toward the tile SLO.
+    pub const fn with_slo(mut self) -> Self {
+        self.tracks_slo = true;
+        self
+    }
+
+    /// Include the route in the client contract.
+    pub const fn with_openapi(mut self, operation_id: &'static str, tag: &'static str) -> Self {
+        self.openapi = Some(OpenApiOperation { operation_id, tag });
+        self
+    }
+}
+
+// =============================================================================
+// Route Table
+// =============================================================================
+
+use RouteHandler as H;
+
+/// Every route the server can serve.
+pub const ROUTES: &[RouteSpec] = &[
+    // Service
+    RouteSpec::get("/health", H::Health, "Health check")
+        .with_access(Access::Public)
+        .with_surface(Surface::Both)
+        .with_cache(CachePolicy::NoStore)
+        .with_openapi("health", "service"),
+    RouteSpec::get("/livez", H::Livez, "Liveness probe")
+        .with_access(Access::Public)
+        .with_surface(Surface::Both)
+        .with_cache(CachePolicy::NoStore),
+    RouteSpec::get("/readyz", H::Readyz, "Readiness probe with storage check")
+        .with_access(Access::Public)
+        .with_surface(Surface::Both)
+        .with_cache(CachePolicy::NoStore),
+    RouteSpec::get("/metrics", H::Metrics, "Prometheus metrics")
+        .with_access(Access::Public)
+        .with_surface(Surface::Admin)
+        .with_cache(CachePolicy::NoStore),
+    RouteSpec::get(
+        "/capabilities",
+        H::Capabilities,
+        "Codecs, features and endpoints of this build",
+    )
+    .with_access(Access::Public),
+    // Admin and debug
+    RouteSpec::get("/admin/slo", H::Slo, "Tile request SLO summary")
+        .with_surface(Surface::Admin)
+        .with_cache(CachePolicy::NoStore),
+    RouteSpec::get(
+        "/admin/connections",
+        H::Connections,
+        "Open client connections",
+    )
+    .with_surface(Surface::Admin)
+    .with_cache(CachePolicy::NoStore),
+    RouteSpec::get(
+        "/admin/cache",
+        H::CacheStats,
+        "Slide temperatures and block cache budgets",
+    )
+    .with_surface(Surface::Admin)
+    .with_cache(CachePolicy::NoStore),
+    RouteSpec::get("/admin/quarantine", H::Quarantine, "Quarantined slides")
+        .with_surface(Surface::Admin)
+        .with_cache(CachePolicy::NoStore),
+    RouteSpec::post(
+        "/admin/quarantine/{slide_id}/release",
+        H::QuarantineRelease,
+        "Release a quarantined slide",
+    )
+    .with_surface(Surface::Admin)
+    .with_cache(CachePolicy::NoStore),
+    RouteSpec::get(
+        "/admin/slides/metrics",
+        H::SlideIoMetrics,
+        "Block cache I/O metrics of open slides",
+    )
+    .with_surface(Surface::Admin)
+    .with_cache(CachePolicy::NoStore),
+    RouteSpec::get(
+        "/admin/slides/{slide_id}/stats",
+        H::SlideIoStats,
+        "Block cache I/O statistics of an open slide",
+    )
+    .with_surface(Surface::Admin)
+    .with_cache(CachePolicy::NoStore),
+    RouteSpec::post(
+        "/admin/slides/{slide_id}/invalidate",
+        H::SlideInvalidate,
+        "Reload slide metadata and mark its cached tiles stale",
+    )
+    .with_surface(Surface::Admin)
+    .with_cache(CachePolicy::NoStore),
+    RouteSpec::post(
+        "/admin/reload",
+        H::Reload,
+        "Reload the signing secret, CORS origins, cache max-age and log level",
+    )
+    .with_surface(Surface::Admin)
+    .with_cache(CachePolicy::NoStore),
+    RouteSpec::get(
+        "/debug/tiles/{slide_id}/{level}/{x}/{y}/hash",
+        H::TileHash,
+        "Rendered tile hash (deterministic mode)",
+    )
+    .with_surface(Surface::Admin)
+    .with_cache(CachePolicy::NoStore),
+    // Tiles
+    RouteSpec::get(
+        "/tiles/{slide_id}/{level}/{x}/{filename}",
+        H::Tile,
+        "Fetch tile",
+    )
+    .with_doc_path("/tiles/{slide_id}/{level}/{x}/{y}.jpg")
+    .with_cache(CachePolicy::Public)
+    .scoped_to_slide()
+    .with_slo()
+    .with_openapi("getTile", "tiles"),
+    // Slides
+    RouteSpec::get("/slides", H::Slides, "List slides").with_openapi("listSlides", "slides"),
+    RouteSpec::get("/slides/{slide_id}", H::SlideMetadata, "Slide metadata")
+        .scoped_to_slide()
+        .with_openapi("getSlide", "slides"),
+    RouteSpec::get("/slides/{slide_id}/dzi", H::DziDescriptor, "DZI descriptor")
+        .with_cache(CachePolicy::Public)
+        .scoped_to_slide()
+        .with_openapi("getDziDescriptor", "slides"),
+    RouteSpec::get(
+        "/slides/{slide_id}/info",
+        H::SlideInfo,
+        "Viewer configuration",
+    )
+    .scoped_to_slide()
+    .with_openapi("getSlideInfo", "slides"),
+    RouteSpec::get(
+        "/slides/{slide_id}/levels",
+        H::SlideLevels,
+        "Native, Deep Zoom and IIIF level mapping",
+    )
+    .scoped_to_slide()
+    .with_openapi("getSlideLevels", "slides"),
+    RouteSpec::get(
+        "/slides/{slide_id}/levels/{level}/grid",
+        H::TileGrid,
+        "Tiles stored in a level, with their sizes",
+    )
+    .scoped_to_slide(),
+    RouteSpec::get(
+        "/slides/{slide_id}/raw",
+        H::RawSlide,
+        "Original slide file bytes (Range supported)",
+    )
+    .scoped_to_slide(),
+    RouteSpec::post(
+        "/slides/{slide_id}/verify",
+        H::VerifySlide,
+        "Verify slide checksums",
+    )
+    .scoped_to_slide(),
+    RouteSpec::get("/slides/{slide_id}/thumbnail", H::Thumbnail, "Thumbnail")
+        .with_cache(CachePolicy::Public)
+        .scoped_to_slide()
+        .with_openapi("getThumbnail", "slides"),
+    RouteSpec::get(
+        "/slides/{slide_id}/thumbnail.jpg",
+        H::Thumbnail,
+        "Thumbnail (image URL form)",
+    )
+    .with_cache(CachePolicy::Public)
+    .scoped_to_slide(),
+    RouteSpec::get(
+        "/slides/{slide_id}/mask.png",
+        H::TissueMask,
+        "Tissue mask (requires --tissue-masks)",
+    )
+    .with_cache(CachePolicy::Public)
+    .scoped_to_slide(),
+    RouteSpec::get(
+        "/slides/{slide_id}/sample",
+        H::Sample,
+        "Tissue tile sampling",
+    )
+    .scoped_to_slide(),
+    RouteSpec::get(
+        "/slides/{slide_id}/snapshot",
+        H::Snapshot,
+        "Print snapshot with scale bar",
+    )
+    .with_cache(CachePolicy::Public)
+    .scoped_to_slide(),
+    RouteSpec::post(
+        "/slides/{slide_id}/views",
+        H::SaveView,
+        "Save a viewer state",
+    )
+    .scoped_to_slide(),
+    RouteSpec::get(
+        "/slides/{slide_id}/views/{name}",
+        H::GetView,
+        "Saved viewer state",
+    )
+    .scoped_to_slide(),
+    RouteSpec::post(
+        "/slides/{slide_id}/annotations",
+        H::SaveAnnotations,
+        "Save GeoJSON annotations",
+    )
+    .scoped_to_slide(),
+    RouteSpec::get(
+        "/slides/{slide_id}/annotations",
+        H::GetAnnotations,
+        "Saved GeoJSON annotations",
+    )
+    .scoped_to_slide(),
+    RouteSpec::post("/slides/{slide_id}/share", H::Share, "Create a share link")
+        .with_cache(CachePolicy::NoStore)
+        .scoped_to_slide(),
+    // Regions and collections
+    RouteSpec::get(
+        "/region/{slide_id}",
+        H::Region,
+        "Arbitrary region crop and scale",
+    )
+    .with_cache(CachePolicy::Public)
+    .scoped_to_slide(),
+    RouteSpec::post(
+        "/regions/batch",
+        H::RegionsBatch,
+        "Render several regions as a multipart response",
+    )
+    .with_cache(CachePolicy::NoStore),
+    RouteSpec::get(
+        "/collections/{collection_id}/sprites",
+        H::Sprites,
+        "Thumbnail sprite sheet",
+    ),
+    // Deep Zoom
+    RouteSpec::get("/dzi/{name}", H::DziFile, "Deep Zoom descriptor")
+        .with_doc_path("/dzi/{slide_id}.dzi")
+        .with_cache(CachePolicy::Public)
+        .scoped_to_slide(),
+    RouteSpec::get(
+        "/dzi/{files}/{level}/{filename}",
+        H::DziTile,
+        "Deep Zoom tile",
+    )
+    .with_doc_path("/dzi/{slide_id}_files/{level}/{x}_{y}.jpg")
+    .with_cache(CachePolicy::Public)
+    .scoped_to_slide(),
+    // IIIF
+    RouteSpec::get(
+        "/iiif/{slide_id}",
+        H::IiifRedirect,
+        "Redirect to IIIF info.json",
+    )
+    .scoped_to_slide(),
+    RouteSpec::get(
+        "/iiif/{slide_id}/info.json",
+        H::IiifInfo,
+        "IIIF Image API 3.0 image information",
+    )
+    .with_cache(CachePolicy::Public)
+    .scoped_to_slide()
+    .with_openapi("getIiifInfo", "iiif"),
+    RouteSpec::get(
+        "/iiif/{slide_id}/{region}/{size}/{rotation}/{quality_format}",
+        H::IiifImage,
+        "IIIF image",
+    )
+    .with_doc_path("/iiif/{slide_id}/{region}/{size}/{rotation}/{quality}.{format}")
+    .with_cache(CachePolicy::Public)
+    .scoped_to_slide(),
+    // Viewer
+    RouteSpec::get("/view/{slide_id}", H::Viewer, "Web viewer").with_access(Access::Public),
+    RouteSpec::get(
+        "/view/compare",
+        H::CompareViewer,
+        "Side-by-side comparison viewer",
+    )
+    .with_access(Access::Public),
+    RouteSpec::get("/share/{token}/view", H::ShareViewer, "Share link viewer")
+        .with_access(Access::ShareToken),
+    // Events
+    RouteSpec::get("/ws", H::Events, "WebSocket channel of server events")
+        .with_cache(CachePolicy::NoStore),
+];
+
+// =============================================================================
+// Cache Policy
+// =============================================================================
+
+/// Middleware applying a route's cache policy.
+///
+/// Handlers that set `Cache-Control` themselves keep their directive.
+pub async fn cache_policy_middleware(
+    State((policy, live)): State<(CachePolicy, Arc<LiveSettings>)>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let mut response = next.run(request).await;
+    if response.headers().contains_key(header::CACHE_CONTROL) {
+        return response;
+    }
+
+    let value = match policy {
+        CachePolicy::Default => return response,
+        CachePolicy::Public if response.status().is_success() => {
+            HeaderValue::from_str(&format!("public, max-age={}", live.cache_max_age())).unwrap()
+        }
+        CachePolicy::Public => return response,
+        CachePolicy::NoStore => HeaderValue::from_static("no-store"),
+    };
+    response.headers_mut().insert(header::CACHE_CONTROL, value);
+    response
+}
+
+// =============================================================================
+// Documentation
+// =============================================================================
+
+/// An endpoint as reported by `/capabilities`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EndpointSummary {
+    /// HTTP method
+    pub method: &'static str,
+
+    /// Documented path template
+    pub path: &'static str,
+
+    /// Who may call the endpoint
+    pub access: Access,
+
+    /// One-line description
+    pub summary: &'static str,
+}
+
+impl From<&RouteSpec> for EndpointSummary {
+    fn from(route: &RouteSpec) -> Self {
+        Self {
+            method: route.method.as_str(),
+            path: route.doc_path,
+            access: route.access,
+            summary: route.summary,
+        }
+    }
+}
+
+/// Every endpoint in the route table.
+pub fn endpoints() -> Vec<EndpointSummary> {
+    ROUTES.iter().map(EndpointSummary::from).collect()
+}
+
+/// OpenAPI `paths` skeleton for the routes in the client contract.
+///
+/// Each operation carries its ID, tag and summary; public operations
+/// declare `"security": []`. Parameters and responses are maintained in
+/// `clients/openapi.json`, whose operations must match this skeleton.
+pub fn openapi_paths() -> serde_json::Value {
+    let mut paths = serde_json::Map::new();
+    for route in ROUTES {
+        let Some(operation) = route.openapi else {
+            continue;
+        };
+
+        let mut entry = serde_json::json!({
+            "operationId": operation.operation_id,
+            "tags": [operation.tag],
+            "summary": route.summary,
+        });
+        if route.access == Access::Public {
+            entry["security"] = serde_json::json!([]);
+        }
+
+        let methods = paths
+            .entry(route.doc_path)
+            .or_insert_with(|| serde_json::json!({}));
+        methods[route.method.as_str().to_ascii_lowercase()] = entry;
+    }
+    serde_json::Value::Object(paths)
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_routes_unique() {
+        let mut seen = HashSet::new();
+        for route in ROUTES {
+            assert!(
+                seen.insert((route.method, route.path)),
+                "duplicate route {} {}",
+                route.method.as_str(),
+                route.path
+            );
+        }
+
+        let operation_ids: Vec<_> = ROUTES
+            .iter()
+            .filter_map(|r| r.openapi.map(|o| o.operation_id))
+            .collect();
+        let unique: HashSet<_> = operation_ids.iter().collect();
+        assert_eq!(unique.len(), operation_ids.len());
+    }
+
+    #[test]
+    fn test_admin_routes_on_admin_surface() {
+        for route in ROUTES {
+            let admin_path = route.path.starts_with("/admin/")
+                || route.path.starts_with("/debug/")
+                || route.path == "/metrics";
+            assert_eq!(
+                admin_path,
+                route.surface == Surface::Admin,
+                "{}",
+                route.path
+            );
+            if route.path.starts_with("/admin/") || route.path.starts_with("/debug/") {
+                assert_eq!(route.access, Access::Signed, "{}", route.path);
+            }
+        }
+    }
+
+    #[test]
+    fn test_slide_scoped_routes_name_a_slide() {
+        for route in ROUTES.iter().filter(|r| r.slide_scoped) {
+            assert!(
+                ["{slide_id}", "{name}", "{files}"]
+                    .iter()
+                    .any(|param| route.path.contains(param)),
+                "{}",
+                route.path
+            );
+        }
+    }
+
+    #[test]
+    fn test_openapi_paths() {
+        let paths = openapi_paths();
+        let health = &paths["/health"]["get"];
+        assert_eq!(health["operationId"], "health");
+        assert_eq!(health["security"], serde_json::json!([]));
+
+        let tile = &paths["/tiles/{slide_id}/{level}/{x}/{y}.jpg"]["get"];
+        assert_eq!(tile["operationId"], "getTile");
+        assert!(tile.get("security").is_none());
+
+        // Undocumented routes are left out
+        assert!(paths.get("/admin/slo").is_none());
+    }
+
+    #[test]
+    fn test_endpoints() {
+        let endpoints = endpoints();
+        assert_eq!(endpoints.len(), ROUTES.len());
+        assert!(endpoints.iter().any(|e| e.method == "POST"
+            && e.path == "/slides/{slide_id}/share"
+            && e.access == Access::Signed));
+    }
+}
@@ -5,11 +5,20 @@
 //!
 //! # Route Structure
 //!
-//! ```text
-//! /health                                    - Health check (public)
-//! /tiles/{slide_id}/{level}/{x}/{y}.jpg      - Tile endpoint (protected)
-//! /slides                                    - List slides (protected)
-//! ```
+//! Routes are declared in [`ROUTES`](super::route_table::ROUTES), with their
+//! method, access requirement, cache policy and handler. The builders here
+//! register every route from that table, so the routers, the endpoint list
+//! in `/capabilities` and the OpenAPI contract cannot drift apart. Routes
+//! marked [`Access::Signed`] sit behind the auth layer when authentication
+//! is enabled; the rest are public.
+//!
+//! # Separate Admin Listener
+//!
+//! [`create_split_routers`] builds two routers over shared state: the public
+//! router serves the tile API and viewer, and the admin router serves
+//! `/admin/*`, `/debug/*` and `/metrics` (plus `/health`). Serving them on
+//! different listeners keeps admin surfaces on an internal network without
+//! an external proxy.
 //!
 //! # Example
 //!
@@ -33,19 +42,47 @@
 //! axum::serve(listener, router).await?;
 //! ```
 
+use std::sync::Arc;
 use std::time::Duration;
 
-use axum::{middleware, routing::get, Router};
+use axum::{
+    extract::DefaultBodyLimit,
+    middleware,
+    routing::{on, MethodRouter},
+    Router,
+};
 use http::header::{AUTHORIZATION, CONTENT_TYPE};
 use http::Method;
-use tower_http::cors::{Any, CorsLayer};
+use tower_http::cors::{AllowOrigin, CorsLayer};
 use tower_http::trace::TraceLayer;
 
-use super::auth::SignedUrlAuth;
+use super::auth::{SignedUrlAuth, DEFAULT_SECRET_ROTATION_GRACE};
+use super::bandwidth::{bandwidth_middleware, BandwidthConfig, BandwidthLimiter};
+use super::compression::compression_layer;
+use super::connections::connection_middleware;
 use super::handlers::{
-    dzi_descriptor_handler, health_handler, slide_metadata_handler, slides_handler,
-    thumbnail_handler, tile_handler, viewer_handler, AppState,
+    cache_stats_handler, capabilities_handler, compare_viewer_handler, connections_handler,
+    dzi_descriptor_handler, dzi_file_handler, dzi_tile_handler, events_handler,
+    get_annotations_handler, get_view_handler, health_handler, iiif_image_handler,
+    iiif_info_handler, iiif_redirect_handler, livez_handler, metrics_handler, quarantine_handler,
+    quarantine_release_handler, raw_slide_handler, readyz_handler, region_handler,
+    regions_batch_handler, reload_handler, sample_handler, save_annotations_handler,
+    save_view_handler, share_handler, share_viewer_handler, slide_headers_middleware,
+    slide_info_handler, slide_invalidate_handler, slide_io_metrics_handler, slide_io_stats_handler,
+    slide_levels_handler, slide_metadata_handler, slides_handler, slo_handler, snapshot_handler,
+    sprites_handler, thumbnail_handler, tile_grid_handler, tile_handler, tile_hash_handler,
+    tissue_mask_handler, verify_slide_handler, viewer_handler, AppState,
+};
+use super::panic::catch_panic_layer;
+use super::rate_limit::{rate_limit_middleware, RateLimitConfig, RateLimiter};
+use super::reload::{ConfigReloader, LiveSettings};
+use super::request_id::request_id_middleware;
+use super::route_table::{
+    cache_policy_middleware, Access, CachePolicy, RouteHandler, RouteSpec, Surface, ROUTES,
 };
+use super::slo::{slo_middleware, SloConfig, SloTracker};
+use super::timing::server_timing_middleware;
+use crate::overlay::{AnnotationStore, MAX_ANNOTATION_BYTES};
 use crate::slide::SlideSource;
 use crate::tile::TileService;
 
@@ -62,6 +99,9 @@ pub struct RouterConfig {
     /// Whether authentication is enabled for tile requests
     pub auth_enabled: bool,
 
+    /// Time a replaced secret keeps verifying after a rotation
+    pub secret_rotation_grace: Duration,
+
     /// Allowed CORS origins (None = allow any origin)
     pub cors_origins: Option<Vec<String>>,
 
@@ -70,6 +110,31 @@ pub struct RouterConfig {
 
     /// Whether to enable request tracing
     pub enable_tracing: bool,
+
+    /// Whether to gzip JSON responses for clients accepting it
+    pub enable_compression: bool,
+
+    /// Tile request SLO targets
+    pub slo: SloConfig,
+
+    /// Request header naming the tenant tile requests are scheduled under
+    pub tenant_header: Option<String>,
+
+    /// Per-connection bandwidth limits for response bodies
+    pub bandwidth: BandwidthConfig,
+
+    /// Per-client request rate limits
+    pub rate_limit: RateLimitConfig,
+
+    /// Reloader updating the secret, CORS origins and cache max-age at
+    /// runtime
+    pub reloader: Option<Arc<ConfigReloader>>,
+
+    /// Store for slide annotations (None = in memory)
+    pub annotation_store: Option<Arc<dyn AnnotationStore>>,
+
+    /// Whether to report phase timings in `Server-Timing` headers
+    pub server_timing: bool,
 }
 
 impl RouterConfig {
@@ -80,13 +145,23 @@ impl RouterConfig {
     /// - CORS allows any origin
     /// - Cache max-age is 1 hour (3600 seconds)
     /// - Tracing is enabled
+    /// - JSON responses are compressed
     pub fn new(auth_secret: impl Into<String>) -> Self {
         Self {
             auth_secret: auth_secret.into(),
             auth_enabled: true,
+            secret_rotation_grace: DEFAULT_SECRET_ROTATION_GRACE,
             cors_origins: None, // Allow any origin by default
             cache_max_age: 3600,
             enable_tracing: true,
+            enable_compression: true,
+            slo: SloConfig::default(),
+            tenant_header: None,
+            bandwidth: BandwidthConfig::default(),
+            rate_limit: RateLimitConfig::default(),
+            reloader: None,
+            annotation_store: None,
+            server_timing: false,
         }
     }
 
@@ -97,9 +172,18 @@ impl RouterConfig {
         Self {
             auth_secret: String::new(),
             auth_enabled: false,
+            secret_rotation_grace: DEFAULT_SECRET_ROTATION_GRACE,
             cors_origins: None,
             cache_max_age: 3600,
             enable_tracing: true,
+            enable_compression: true,
+            slo: SloConfig::default(),
+            tenant_header: None,
+            bandwidth: BandwidthConfig::default(),
+            rate_limit: RateLimitConfig::default(),
+            reloader: None,
+            annotation_store: None,
+            server_timing: false,
         }
     }
 
@@ -130,11 +214,77 @@ impl RouterConfig {
         self
     }
 
+    /// Set how long a replaced secret keeps verifying after a rotation.
+    pub fn with_secret_rotation_grace(mut self, grace: Duration) -> Self {
+        self.secret_rotation_grace = grace;
+        self
+    }
+
     /// Enable or disable request tracing.
     pub fn with_tracing(mut self, enabled: bool) -> Self {
         self.enable_tracing = enabled;
         self
     }
+
+    /// Enable or disable gzip compression of JSON responses.
+    pub fn with_compression(mut self, enabled: bool) -> Self {
+        self.enable_compression = enabled;
+        self
+    }
+
+    /// Set the tile request SLO targets.
+    pub fn with_slo(mut self, slo: SloConfig) -> Self {
+        self.slo = slo;
+        self
+    }
+
+    /// Schedule tile requests under the tenant named by a request header.
+    ///
+    /// The header must be set by a trusted gateway; it only takes effect
+    /// when the tile service has a fair scheduler.
+    pub fn with_tenant_header(mut self, header: impl Into<String>) -> Self {
+        self.tenant_header = Some(header.into());
+        self
+    }
+
+    /// Set per-connection bandwidth limits for response bodies.
+    ///
+    /// Connections are told apart by peer address, so the router must be
+    /// served with connect info for requests to share a limit.
+    pub fn with_bandwidth(mut self, bandwidth: BandwidthConfig) -> Self {
+        self.bandwidth = bandwidth;
+        self
+    }
+
+    /// Set per-client request rate limits.
+    ///
+    /// Clients over their limit get `429 Too Many Requests`. Limiting by IP
+    /// requires the router to be served with connect info.
+    pub fn with_rate_limit(mut self, rate_limit: RateLimitConfig) -> Self {
+        self.rate_limit = rate_limit;
+        self
+    }
+
+    /// Let a reloader change the secret, CORS origins and cache max-age of
+    /// the routers built from this configuration, and serve it on
+    /// `POST /admin/reload`.
+    pub fn with_reloader(mut self, reloader: Arc<ConfigReloader>) -> Self {
+        self.reloader = Some(reloader);
+        self
+    }
+
+    /// Keep annotations in a store instead of process memory.
+    pub fn with_annotation_store(mut self, store: Arc<dyn AnnotationStore>) -> Self {
+        self.annotation_store = Some(store);
+        self
+    }
+
+    /// Enable or disable `Server-Timing` headers with the time spent in
+    /// each phase of a request (storage, parsing, decoding, encoding).
+    pub fn with_server_timing(mut self, enabled: bool) -> Self {
+        self.server_timing = enabled;
+        self
+    }
 }
 
 // =============================================================================
@@ -148,6 +298,9 @@ impl RouterConfig {
 /// - Protected routes (tile API with optional auth)
 /// - CORS configuration
 /// - Request tracing (optional)
+/// - Gzip compression of JSON responses (optional)
+/// - Per-client request rate limits (optional)
+/// - Per-connection bandwidth limits (optional)
 ///
 /// # Arguments
 ///
@@ -161,119 +314,331 @@ pub fn create_router<S>(tile_service: TileService<S>, config: RouterConfig) -> R
 where
     S: SlideSource + 'static,
 {
-    // Create application state with auth info for viewer token generation
-    let app_state = if config.auth_enabled {
-        let auth = SignedUrlAuth::new(&config.auth_secret);
-        AppState::with_cache_max_age(tile_service, config.cache_max_age).with_auth(auth.clone())
-    } else {
-        AppState::with_cache_max_age(tile_service, config.cache_max_age)
-    };
-
-    // Create the auth layer if enabled
-    let auth = SignedUrlAuth::new(&config.auth_secret);
+    let (app_state, auth) = build_app_state(tile_service, &config);
 
     // Build CORS layer
-    let cors = build_cors_layer(&config);
+    let cors = build_cors_layer(&app_state.live);
 
     // Build the router
-    let router = if config.auth_enabled {
-        build_protected_router(app_state, auth, cors)
+    let auth = config.auth_enabled.then_some(auth);
+    let limiter = rate_limiter(&config);
+    let router = build_router(app_state, auth, limiter, cors, Surfaces::All);
+
+    apply_layers(router, &config)
+}
+
+/// Routers for serving the public API and the admin surfaces on separate
+/// listeners (see [`create_split_routers`]).
+pub struct SplitRouters {
+    /// Tile API, slide metadata, Deep Zoom, IIIF and viewer routes
+    pub public: Router,
+
+    /// `/admin/*`, `/debug/*`, `/metrics` and `/health`
+    pub admin: Router,
+}
+
+/// Create a public router and an admin router sharing the same state.
+///
+/// Both routers use the same tile service, SLO tracker and authentication
+/// settings; the public router leaves out the admin, debug and metrics
+/// routes, which only the admin router serves.
+pub fn create_split_routers<S>(tile_service: TileService<S>, config: RouterConfig) -> SplitRouters
+where
+    S: SlideSource + 'static,
+{
+    let (app_state, auth) = build_app_state(tile_service, &config);
+    let cors = build_cors_layer(&app_state.live);
+
+    let auth = config.auth_enabled.then_some(auth);
+    let limiter = rate_limiter(&config);
+    let public = build_router(
+        app_state.clone(),
+        auth.clone(),
+        limiter.clone(),
+        cors.clone(),
+        Surfaces::Public,
+    );
+    let admin = build_router(app_state, auth, limiter, cors, Surfaces::Admin);
+
+    SplitRouters {
+        public: apply_layers(public, &config),
+        admin: apply_layers(admin, &config),
+    }
+}
+
+/// Which routes a router serves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Surfaces {
+    /// Every route
+    All,
+    /// Everything except admin, debug and metrics routes
+    Public,
+    /// Admin, debug and metrics routes, plus the health check
+    Admin,
+}
+
+impl Surfaces {
+    fn public(self) -> bool {
+        self != Surfaces::Admin
+    }
+
+    fn admin(self) -> bool {
+        self != Surfaces::Public
+    }
+
+    /// Whether a route on the given surface is served.
+    fn serves(self, surface: Surface) -> bool {
+        match surface {
+            Surface::Api => self.public(),
+            Surface::Admin => self.admin(),
+            Surface::Both => true,
+        }
+    }
+}
+
+/// Create the application state shared by all routers, and the auth
+/// verifier used by the auth layer.
+fn build_app_state<S>(
+    tile_service: TileService<S>,
+    config: &RouterConfig,
+) -> (AppState<S>, SignedUrlAuth)
+where
+    S: SlideSource + 'static,
+{
+    // Create application state with auth info for viewer token generation
+    let live = Arc::new(LiveSettings::new(
+        config.cache_max_age,
+        config.cors_origins.as_deref(),
+    ));
+    let mut app_state = AppState::new(tile_service)
+        .with_live_settings(Arc::clone(&live))
+        .with_slo(Arc::new(SloTracker::new(config.slo.clone())));
+    if let Some(header) = &config.tenant_header {
+        app_state = app_state.with_tenant_header(header);
+    }
+    // Shared by the state and the auth layer so share link counts and
+    // secret rotations agree
+    let auth =
+        SignedUrlAuth::new(&config.auth_secret).with_rotation_grace(config.secret_rotation_grace);
+    if config.auth_enabled {
+        app_state = app_state.with_auth(auth.clone());
+    }
+    if let Some(reloader) = &config.reloader {
+        reloader.attach(live, config.auth_enabled.then(|| auth.clone()));
+        app_state = app_state.with_reloader(Arc::clone(reloader));
+    }
+    if let Some(store) = &config.annotation_store {
+        app_state = app_state.with_annotation_store(Arc::clone(store));
+    }
+
+    (app_state, auth)
+}
+
+/// Apply the layers shared by every router: panic recovery, compression,
+/// bandwidth limits, tracing and request IDs.
+fn apply_layers(router: Router, config: &RouterConfig) -> Router {
+    // Recover from handler panics with a 500 instead of a dropped connection
+    let router = router.layer(catch_panic_layer());
+
+    // Compress JSON bodies inside the bandwidth limit, so paced bytes are
+    // the ones sent
+    let router = if config.enable_compression {
+        router.layer(compression_layer())
+    } else {
+        router
+    };
+
+    // Pace response bodies per connection
+    let router = if config.bandwidth.is_enabled() {
+        let limiter = Arc::new(BandwidthLimiter::new(config.bandwidth.clone()));
+        router.layer(middleware::from_fn_with_state(
+            limiter,
+            bandwidth_middleware,
+        ))
     } else {
-        build_public_router(app_state, cors)
+        router
     };
 
+    // Count requests per client connection; runs before rate and bandwidth
+    // limits, which read the peer address it exposes
+    let router = router.layer(middleware::from_fn(connection_middleware));
+
     // Add tracing if enabled
-    if config.enable_tracing {
+    let router = if config.enable_tracing {
         router.layer(TraceLayer::new_for_http())
     } else {
         router
-    }
+    };
+
+    // Time request phases for debugging, including time spent queued in
+    // the limits above
+    let router = if config.server_timing {
+        router.layer(middleware::from_fn(server_timing_middleware))
+    } else {
+        router
+    };
+
+    // Outermost, so every span and log line of the request carries its ID
+    router.layer(middleware::from_fn(request_id_middleware))
 }
 
-/// Build router with authentication on tile and slides routes.
-fn build_protected_router<S>(app_state: AppState<S>, auth: SignedUrlAuth, cors: CorsLayer) -> Router
+/// Create the request rate limiter, if rate limits are enabled.
+fn rate_limiter(config: &RouterConfig) -> Option<Arc<RateLimiter>> {
+    config
+        .rate_limit
+        .is_enabled()
+        .then(|| Arc::new(RateLimiter::new(config.rate_limit.clone())))
+}
+
+/// Build a router serving every route in the table on the given surfaces.
+///
+/// With `auth`, signed routes sit behind the auth layer; without it (for
+/// development and testing) every route is public. With `limiter`, every
+/// route is rate limited; on signed routes the limit applies after the auth
+/// layer, so clients are only keyed by subjects it verified.
+fn build_router<S>(
+    app_state: AppState<S>,
+    auth: Option<SignedUrlAuth>,
+    limiter: Option<Arc<RateLimiter>>,
+    cors: CorsLayer,
+    surfaces: Surfaces,
+) -> Router
 where
     S: SlideSource + 'static,
 {
-    // Protected tile routes (require authentication)
-    // Uses {filename} to capture both "{y}" and "{y}.jpg" formats
-    // Auth middleware is applied to the nested router AFTER nesting so it sees the full /tiles/... path
-    let tile_routes = Router::new()
-        .route("/{slide_id}/{level}/{x}/{filename}", get(tile_handler::<S>))
-        .with_state(app_state.clone());
-
-    // Protected slides routes (require authentication)
-    let slides_routes = Router::new()
-        .route("/", get(slides_handler::<S>))
-        .route("/{slide_id}", get(slide_metadata_handler::<S>))
-        .route("/{slide_id}/dzi", get(dzi_descriptor_handler::<S>))
-        .route("/{slide_id}/thumbnail", get(thumbnail_handler::<S>))
-        .with_state(app_state.clone());
-
-    // Create nested routes with auth applied AFTER nesting
-    let protected_routes = Router::new()
-        .nest("/tiles", tile_routes)
-        .nest("/slides", slides_routes)
-        .layer(middleware::from_fn_with_state(
+    let mut signed_routes = Router::new();
+    let mut public_routes = Router::new();
+    for route in ROUTES.iter().filter(|route| surfaces.serves(route.surface)) {
+        let method_router = route_method_router(route, &app_state);
+        if route.access == Access::Signed {
+            signed_routes = signed_routes.route(route.path, method_router);
+        } else {
+            // The viewer is just HTML (its tile requests are still signed)
+            // and the share viewer verifies its token itself
+            public_routes = public_routes.route(route.path, method_router);
+        }
+    }
+
+    // Reject clients over their request rate before any work is done
+    if let Some(limiter) = limiter {
+        let layer = middleware::from_fn_with_state(limiter, rate_limit_middleware);
+        signed_routes = signed_routes.layer(layer.clone());
+        public_routes = public_routes.layer(layer);
+    }
+
+    // Applied to the complete paths, which signatures cover
+    if let Some(auth) = auth {
+        signed_routes = signed_routes.layer(middleware::from_fn_with_state(
             auth,
             super::auth::auth_middleware,
         ));
+    }
 
-    // Public routes (no auth required)
-    // The viewer is public because it's just HTML - tile requests are still protected
-    let public_routes = Router::new()
-        .route("/health", get(health_handler))
-        .route("/view/{slide_id}", get(viewer_handler::<S>))
-        .with_state(app_state);
-
-    // Combine routes
     Router::new()
-        .merge(protected_routes)
+        .merge(signed_routes)
         .merge(public_routes)
+        .with_state(app_state)
         .layer(cors)
 }
 
-/// Build router without authentication (for development/testing).
-fn build_public_router<S>(app_state: AppState<S>, cors: CorsLayer) -> Router
+/// Build the method router for a route, with the route's layers applied.
+fn route_method_router<S>(route: &RouteSpec, app_state: &AppState<S>) -> MethodRouter<AppState<S>>
 where
     S: SlideSource + 'static,
 {
-    // All routes are public
-    // Uses {filename} to capture both "{y}" and "{y}.jpg" formats
-    Router::new()
-        .route("/health", get(health_handler))
-        .route(
-            "/tiles/{slide_id}/{level}/{x}/{filename}",
-            get(tile_handler::<S>),
-        )
-        .route("/slides", get(slides_handler::<S>))
-        .route("/slides/{slide_id}", get(slide_metadata_handler::<S>))
-        .route("/slides/{slide_id}/dzi", get(dzi_descriptor_handler::<S>))
-        .route("/slides/{slide_id}/thumbnail", get(thumbnail_handler::<S>))
-        .route("/view/{slide_id}", get(viewer_handler::<S>))
-        .with_state(app_state)
-        .layer(cors)
-}
+    let filter = route.method.filter();
+    let mut method_router = match route.handler {
+        RouteHandler::Health => on(filter, health_handler),
+        RouteHandler::Livez => on(filter, livez_handler),
+        RouteHandler::Readyz => on(filter, readyz_handler::<S>),
+        RouteHandler::Metrics => on(filter, metrics_handler::<S>),
+        RouteHandler::Capabilities => on(filter, capabilities_handler::<S>),
+        RouteHandler::Slo => on(filter, slo_handler::<S>),
+        RouteHandler::Connections => on(filter, connections_handler::<S>),
+        RouteHandler::CacheStats => on(filter, cache_stats_handler::<S>),
+        RouteHandler::Quarantine => on(filter, quarantine_handler::<S>),
+        RouteHandler::QuarantineRelease => on(filter, quarantine_release_handler::<S>),
+        RouteHandler::SlideInvalidate => on(filter, slide_invalidate_handler::<S>),
+        RouteHandler::SlideIoStats => on(filter, slide_io_stats_handler::<S>),
+        RouteHandler::SlideIoMetrics => on(filter, slide_io_metrics_handler::<S>),
+        RouteHandler::Reload => on(filter, reload_handler::<S>),
+        RouteHandler::TileHash => on(filter, tile_hash_handler::<S>),
+        RouteHandler::Tile => on(filter, tile_handler::<S>),
+        RouteHandler::Slides => on(filter, slides_handler::<S>),
+        RouteHandler::SlideMetadata => on(filter, slide_metadata_handler::<S>),
+        RouteHandler::DziDescriptor => on(filter, dzi_descriptor_handler::<S>),
+        RouteHandler::SlideInfo => on(filter, slide_info_handler::<S>),
+        RouteHandler::SlideLevels => on(filter, slide_levels_handler::<S>),
+        RouteHandler::TileGrid => on(filter, tile_grid_handler::<S>),
+        RouteHandler::TissueMask => on(filter, tissue_mask_handler::<S>),
+        RouteHandler::RawSlide => on(filter, raw_slide_handler::<S>),
+        RouteHandler::VerifySlide => on(filter, verify_slide_handler::<S>),
+        RouteHandler::Thumbnail => on(filter, thumbnail_handler::<S>),
+        RouteHandler::Sample => on(filter, sample_handler::<S>),
+        RouteHandler::Snapshot => on(filter, snapshot_handler::<S>),
+        RouteHandler::SaveView => on(filter, save_view_handler::<S>),
+        RouteHandler::GetView => on(filter, get_view_handler::<S>),
+        RouteHandler::SaveAnnotations => on(filter, save_annotations_handler::<S>)
+            .layer(DefaultBodyLimit::max(MAX_ANNOTATION_BYTES)),
+        RouteHandler::GetAnnotations => on(filter, get_annotations_handler::<S>),
+        RouteHandler::Share => on(filter, share_handler::<S>),
+        RouteHandler::Region => on(filter, region_handler::<S>),
+        RouteHandler::RegionsBatch => on(filter, regions_batch_handler::<S>),
+        RouteHandler::Sprites => on(filter, sprites_handler::<S>),
+        RouteHandler::DziFile => on(filter, dzi_file_handler::<S>),
+        RouteHandler::DziTile => on(filter, dzi_tile_handler::<S>),
+        RouteHandler::IiifRedirect => on(filter, iiif_redirect_handler),
+        RouteHandler::IiifInfo => on(filter, iiif_info_handler::<S>),
+        RouteHandler::IiifImage => on(filter, iiif_image_handler::<S>),
+        RouteHandler::Viewer => on(filter, viewer_handler::<S>),
+        RouteHandler::CompareViewer => on(filter, compare_viewer_handler::<S>),
+        RouteHandler::ShareViewer => on(filter, share_viewer_handler::<S>),
+        RouteHandler::Events => on(filter, events_handler::<S>),
+    };
 
-/// Build the CORS layer based on configuration.
-fn build_cors_layer(config: &RouterConfig) -> CorsLayer {
-    let cors = CorsLayer::new()
-        .allow_methods([Method::GET, Method::HEAD, Method::OPTIONS])
-        .allow_headers([AUTHORIZATION, CONTENT_TYPE])
-        .max_age(Duration::from_secs(86400)); // 24 hours
+    if route.tracks_slo {
+        // Panics are caught inside the SLO layer so they count as errors
+        method_router =
+            method_router
+                .layer(catch_panic_layer())
+                .route_layer(middleware::from_fn_with_state(
+                    app_state.slo.clone(),
+                    slo_middleware,
+                ));
+    }
 
-    match &config.cors_origins {
-        None => cors.allow_origin(Any),
-        Some(origins) if origins.is_empty() => {
-            // No origins allowed - this effectively disables CORS
-            cors
-        }
-        Some(origins) => {
-            // Parse origins into HeaderValues
-            let parsed_origins: Vec<_> = origins.iter().filter_map(|o| o.parse().ok()).collect();
-            cors.allow_origin(parsed_origins)
-        }
+    // Slide-scoped routes carry the slide's sidecar headers
+    if route.slide_scoped {
+        method_router = method_router.route_layer(middleware::from_fn_with_state(
+            app_state.clone(),
+            slide_headers_middleware::<S>,
+        ));
+    }
+
+    if route.cache != CachePolicy::Default {
+        method_router = method_router.route_layer(middleware::from_fn_with_state(
+            (route.cache, Arc::clone(&app_state.live)),
+            cache_policy_middleware,
+        ));
     }
+
+    method_router
+}
+
+/// Build the CORS layer, checking origins against the live settings.
+///
+/// Origins are checked per request so a reload takes effect immediately;
+/// an allowed origin is echoed back, and an empty list effectively disables
+/// CORS.
+fn build_cors_layer(live: &Arc<LiveSettings>) -> CorsLayer {
+    let live = Arc::clone(live);
+    CorsLayer::new()
+        .allow_methods([Method::GET, Method::HEAD, Method::POST, Method::OPTIONS])
+        .allow_headers([AUTHORIZATION, CONTENT_TYPE])
+        .max_age(Duration::from_secs(86400)) // 24 hours
+        .allow_origin(AllowOrigin::predicate(move |origin, _| {
+            live.allows_origin(origin)
+        }))
 }
 
 // =============================================================================
@@ -324,6 +689,7 @@ mod tests {
         assert!(config.cors_origins.is_none());
         assert_eq!(config.cache_max_age, 3600);
         assert!(config.enable_tracing);
+        assert!(config.enable_compression);
     }
 
     #[test]
@@ -360,10 +726,17 @@ mod tests {
         assert!(config.cors_origins.is_none());
     }
 
+    fn live_settings(config: &RouterConfig) -> Arc<LiveSettings> {
+        Arc::new(LiveSettings::new(
+            config.cache_max_age,
+            config.cors_origins.as_deref(),
+        ))
+    }
+
     #[test]
     fn test_build_cors_layer_any_origin() {
         let config = RouterConfig::new("secret");
-        let _cors = build_cors_layer(&config);
+        let _cors = build_cors_layer(&live_settings(&config));
         // Just verify it doesn't panic
     }
 
@@ -373,14 +746,14 @@ mod tests {
             "https://example.com".to_string(),
             "https://other.com".to_string(),
         ]);
-        let _cors = build_cors_layer(&config);
+        let _cors = build_cors_layer(&live_settings(&config));
         // Just verify it doesn't panic
     }
 
     #[test]
     fn test_build_cors_layer_empty_origins() {
         let config = RouterConfig::new("secret").with_cors_origins(vec![]);
-        let _cors = build_cors_layer(&config);
+        let _cors = build_cors_layer(&live_settings(&config));
         // Just verify it doesn't panic
     }
 }
@@ -0,0 +1,542 @@
+//! Service level objectives for tile requests.
+//!
+//! Tile responses are classified as they are served and counted into a
+//! ring of 10-second buckets covering the last six hours. Two SLOs are
+//! tracked:
+//!
+//! - **Availability**: the fraction of requests not failing with a 5xx status
+//! - **Latency**: the fraction of requests served within a threshold
+//!
+//! For each SLO, the burn rate over a window is the observed bad fraction
+//! divided by the error budget (`1 - target`). A burn rate of 1 spends the
+//! budget exactly over the SLO period; the multi-window alert conditions
+//! follow the usual fast-burn (page) and slow-burn (ticket) thresholds:
+//!
+//! | Alert  | Long window | Short window | Burn rate |
+//! |--------|-------------|--------------|-----------|
+//! | page   | 1h          | 5m           | > 14.4    |
+//! | ticket | 6h          | 30m          | > 6       |
+//!
+//! The summary is served at `GET /admin/slo`, and the burn rates are
+//! exported in Prometheus text format at `GET /metrics`.
+
+use std::fmt::Write as _;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+use serde::Serialize;
+
+// =============================================================================
+// Configuration
+// =============================================================================
+
+/// Default availability target (99.9% of tile requests succeed).
+pub const DEFAULT_AVAILABILITY_TARGET: f64 = 0.999;
+
+/// Default latency target (99% of tile requests are fast).
+pub const DEFAULT_LATENCY_TARGET: f64 = 0.99;
+
+/// Default latency threshold for a request to count as fast.
+pub const DEFAULT_LATENCY_THRESHOLD: Duration = Duration::from_millis(500);
+
+/// Width of a counting bucket in seconds.
+const BUCKET_SECS: u64 = 10;
+
+/// Windows burn rates are reported over, shortest first.
+pub const SLO_WINDOWS: [(&str, Duration); 4] = [
+    ("5m", Duration::from_secs(5 * 60)),
+    ("30m", Duration::from_secs(30 * 60)),
+    ("1h", Duration::from_secs(60 * 60)),
+    ("6h", Duration::from_secs(6 * 60 * 60)),
+];
+
+/// Burn rate above which the fast-burn (page) alert fires.
+pub const PAGE_BURN_RATE: f64 = 14.4;
+
+/// Burn rate above which the slow-burn (ticket) alert fires.
+pub const TICKET_BURN_RATE: f64 = 6.0;
+
+/// SLO targets for tile requests.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SloConfig {
+    /// Fraction of requests that must not fail with a server error
+    pub availability_target: f64,
+
+    /// Fraction of requests that must complete within `latency_threshold`
+    pub latency_target: f64,
+
+    /// Latency above which a request counts against the latency SLO
+    pub latency_threshold: Duration,
+}
+
+impl Default for SloConfig {
+    fn default() -> Self {
+        Self {
+            availability_target: DEFAULT_AVAILABILITY_TARGET,
+            latency_target: DEFAULT_LATENCY_TARGET,
+            latency_threshold: DEFAULT_LATENCY_THRESHOLD,
+        }
+    }
+}
+
+// =============================================================================
+// Tracker
+// =============================================================================
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Bucket {
+    /// Bucket index since the tracker started (`secs / BUCKET_SECS`)
+    index: u64,
+    requests: u64,
+    errors: u64,
+    slow: u64,
+}
+
+/// Counts tile request outcomes for SLO reporting.
+pub struct SloTracker {
+    config: SloConfig,
+    started: Instant,
+    buckets: Mutex<Vec<Bucket>>,
+    requests: AtomicU64,
+    errors: AtomicU64,
+    slow: AtomicU64,
+}
+
+impl SloTracker {
+    /// Create a tracker with the given targets.
+    pub fn new(config: SloConfig) -> Self {
+        let longest = SLO_WINDOWS[SLO_WINDOWS.len() - 1].1.as_secs();
+        let bucket_count = (longest / BUCKET_SECS) as usize + 1;
+        Self {
+            config,
+            started: Instant::now(),
+            buckets: Mutex::new(vec![Bucket::default(); bucket_count]),
+            requests: AtomicU64::new(0),
+            errors: AtomicU64::new(0),
+            slow: AtomicU64::new(0),
+        }
+    }
+
+    /// Get the SLO targets.
+    pub fn config(&self) -> &SloConfig {
+        &self.config
+    }
+
+    /// Record a served request.
+    ///
+    /// Only 5xx statuses count against availability; client errors are the
+    /// caller's problem, not the service's.
+    pub fn record(&self, status: u16, latency: Duration) {
+        self.record_at(self.started.elapsed().as_secs(), status, latency);
+    }
+
+    fn record_at(&self, now_secs: u64, status: u16, latency: Duration) {
+        let error = status >= 500;
+        let slow = latency > self.config.latency_threshold;
+
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        if error {
+            self.errors.fetch_add(1, Ordering::Relaxed);
+        }
+        if slow {
+            self.slow.fetch_add(1, Ordering::Relaxed);
+        }
+
+        let index = now_secs / BUCKET_SECS;
+        let mut buckets = self.buckets.lock().unwrap();
+        let len = buckets.len() as u64;
+        let bucket = &mut buckets[(index % len) as usize];
+        if bucket.index != index {
+            *bucket = Bucket {
+                index,
+                ..Bucket::default()
+            };
+        }
+        bucket.requests += 1;
+        bucket.errors += error as u64;
+        bucket.slow += slow as u64;
+    }
+
+    /// Summarize SLO compliance over each window.
+    pub fn summary(&self) -> SloSummary {
+        self.summary_at(self.started.elapsed().as_secs())
+    }
+
+    fn summary_at(&self, now_secs: u64) -> SloSummary {
+        let current = now_secs / BUCKET_SECS;
+        let buckets = self.buckets.lock().unwrap().clone();
+
+        let windows: Vec<SloWindow> = SLO_WINDOWS
+            .iter()
+            .map(|&(name, duration)| {
+                let span = duration.as_secs() / BUCKET_SECS;
+                let (requests, errors, slow) = buckets
+                    .iter()
+                    .filter(|b| b.requests > 0 && b.index <= current && current - b.index < span)
+                    .fold((0, 0, 0), |(r, e, s), b| {
+                        (r + b.requests, e + b.errors, s + b.slow)
+                    });
+                SloWindow::new(name, requests, errors, slow, &self.config)
+            })
+            .collect();
+
+        let burn = |name: &str, pick: fn(&SloWindow) -> f64| {
+            windows
+                .iter()
+                .find(|w| w.window == name)
+                .map(pick)
+                .unwrap_or(0.0)
+        };
+        let both_over = |long: &str, short: &str, threshold: f64| {
+            let picks: [fn(&SloWindow) -> f64; 2] =
+                [|w| w.availability_burn_rate, |w| w.latency_burn_rate];
+            picks
+                .iter()
+                .any(|&pick| burn(long, pick) > threshold && burn(short, pick) > threshold)
+        };
+
+        let error_budget_remaining = windows
+            .last()
+            .map(|w| w.budget_remaining(&self.config))
+            .unwrap_or(1.0);
+        SloSummary {
+            availability_target: self.config.availability_target,
+            latency_target: self.config.latency_target,
+            latency_threshold_ms: self.config.latency_threshold.as_millis() as u64,
+            error_budget_remaining,
+            page: both_over("1h", "5m", PAGE_BURN_RATE),
+            ticket: both_over("6h", "30m", TICKET_BURN_RATE),
+            windows,
+        }
+    }
+
+    /// Render request counters and burn rates in Prometheus text format.
+    pub fn render_prometheus(&self) -> String {
+        let summary = self.summary();
+        let mut out = String::new();
+
+        let counters = [
+            (
+                "wsi_tile_requests_total",
+                "Tile requests served.",
+                &self.requests,
+            ),
+            (
+                "wsi_tile_request_errors_total",
+                "Tile requests that failed with a server error.",
+                &self.errors,
+            ),
+            (
+                "wsi_tile_slow_requests_total",
+                "Tile requests slower than the latency SLO threshold.",
+                &self.slow,
+            ),
+        ];
+        for (name, help, value) in counters {
+            let _ = writeln!(out, "# HELP {} {}", name, help);
+            let _ = writeln!(out, "# TYPE {} counter", name);
+            let _ = writeln!(out, "{} {}", name, value.load(Ordering::Relaxed));
+        }
+
+        let _ = writeln!(out, "# HELP wsi_slo_target SLO target ratio.");
+        let _ = writeln!(out, "# TYPE wsi_slo_target gauge");
+        let _ = writeln!(
+            out,
+            "wsi_slo_target{{slo=\"availability\"}} {}",
+            summary.availability_target
+        );
+        let _ = writeln!(
+            out,
+            "wsi_slo_target{{slo=\"latency\"}} {}",
+            summary.latency_target
+        );
+
+        let _ = writeln!(
+            out,
+            "# HELP wsi_slo_burn_rate Error budget burn rate over a trailing window."
+        );
+        let _ = writeln!(out, "# TYPE wsi_slo_burn_rate gauge");
+        for window in &summary.windows {
+            let _ = writeln!(
+                out,
+                "wsi_slo_burn_rate{{slo=\"availability\",window=\"{}\"}} {}",
+                window.window, window.availability_burn_rate
+            );
+            let _ = writeln!(
+                out,
+                "wsi_slo_burn_rate{{slo=\"latency\",window=\"{}\"}} {}",
+                window.window, window.latency_burn_rate
+            );
+        }
+
+        out
+    }
+}
+
+impl Default for SloTracker {
+    fn default() -> Self {
+        Self::new(SloConfig::default())
+    }
+}
+
+// =============================================================================
+// Summary
+// =============================================================================
+
+/// SLO compliance over one trailing window.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SloWindow {
+    /// Window name (e.g. `5m`)
+    pub window: &'static str,
+
+    /// Requests served in the window
+    pub requests: u64,
+
+    /// Requests that failed with a server error
+    pub errors: u64,
+
+    /// Requests slower than the latency threshold
+    pub slow: u64,
+
+    /// Fraction of requests without a server error (1 if idle)
+    pub availability: f64,
+
+    /// Fraction of requests within the latency threshold (1 if idle)
+    pub latency_compliance: f64,
+
+    /// Availability error budget burn rate
+    pub availability_burn_rate: f64,
+
+    /// Latency error budget burn rate
+    pub latency_burn_rate: f64,
+}
+
+impl SloWindow {
+    fn new(name: &'static str, requests: u64, errors: u64, slow: u64, config: &SloConfig) -> Self {
+        let bad_fraction = |bad: u64| {
+            if requests == 0 {
+                0.0
+            } else {
+                bad as f64 / requests as f64
+            }
+        };
+        let burn_rate = |bad: f64, target: f64| {
+            let budget = 1.0 - target;
+            if budget > 0.0 {
+                bad / budget
+            } else {
+                0.0
+            }
+        };
+
+        let error_fraction = bad_fraction(errors);
+        let slow_fraction = bad_fraction(slow);
+        Self {
+            window: name,
+            requests,
+            errors,
+            slow,
+            availability: 1.0 - error_fraction,
+            latency_compliance: 1.0 - slow_fraction,
+            availability_burn_rate: burn_rate(error_fraction, config.availability_target),
+            latency_burn_rate: burn_rate(slow_fraction, config.latency_target),
+        }
+    }
+
+    /// Share of the error budget left in this window, for whichever
+    /// objective has spent more of its budget.
+    ///
+    /// The budget of an objective is the number of requests it may miss in
+    /// the window (`1 - target` of those served).
+    fn budget_remaining(&self, config: &SloConfig) -> f64 {
+        let remaining = |bad: u64, target: f64| {
+            let allowed = (1.0 - target) * self.requests as f64;
+            if bad == 0 {
+                1.0
+            } else if allowed <= 0.0 {
+                0.0
+            } else {
+                (1.0 - bad as f64 / allowed).max(0.0)
+            }
+        };
+        remaining(self.errors, config.availability_target)
+            .min(remaining(self.slow, config.latency_target))
+    }
+}
+
+/// SLO summary returned by `GET /admin/slo`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SloSummary {
+    /// Availability target
+    pub availability_target: f64,
+
+    /// Latency target
+    pub latency_target: f64,
+
+    /// Latency threshold in milliseconds
+    pub latency_threshold_ms: u64,
+
+    /// Share of the error budget left over the longest window (6h), for the
+    /// objective (availability or latency) that has spent more of its
+    /// budget; 1 when nothing is spent, 0 once either budget is exhausted
+    pub error_budget_remaining: f64,
+
+    /// Whether the fast-burn condition holds for either SLO
+    pub page: bool,
+
+    /// Whether the slow-burn condition holds for either SLO
+    pub ticket: bool,
+
+    /// Per-window compliance, shortest window first
+    pub windows: Vec<SloWindow>,
+}
+
+// =============================================================================
+// Middleware
+// =============================================================================
+
+/// Record the status and latency of each request into an [`SloTracker`].
+pub async fn slo_middleware(
+    State(tracker): State<Arc<SloTracker>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let start = Instant::now();
+    let response = next.run(request).await;
+    tracker.record(response.status().as_u16(), start.elapsed());
+    response
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FAST: Duration = Duration::from_millis(20);
+    const SLOW: Duration = Duration::from_secs(2);
+
+    fn window<'a>(summary: &'a SloSummary, name: &str) -> &'a SloWindow {
+        summary.windows.iter().find(|w| w.window == name).unwrap()
+    }
+
+    #[test]
+    fn test_idle_tracker_is_compliant() {
+        let summary = SloTracker::default().summary_at(0);
+        assert_eq!(summary.windows.len(), SLO_WINDOWS.len());
+        assert!(summary.windows.iter().all(|w| w.availability == 1.0));
+        assert_eq!(summary.error_budget_remaining, 1.0);
+        assert!(!summary.page && !summary.ticket);
+    }
+
+    #[test]
+    fn test_burn_rates() {
+        let tracker = SloTracker::default();
+        for i in 0..1000 {
+            let status = if i < 10 { 503 } else { 200 };
+            let latency = if i < 20 { SLOW } else { FAST };
+            tracker.record_at(100, status, latency);
+        }
+
+        let summary = tracker.summary_at(100);
+        let w = window(&summary, "5m");
+        assert_eq!((w.requests, w.errors, w.slow), (1000, 10, 20));
+        // 1% errors against a 0.1% budget
+        assert!((w.availability_burn_rate - 10.0).abs() < 1e-9);
+        // 2% slow against a 1% budget
+        assert!((w.latency_burn_rate - 2.0).abs() < 1e-9);
+        assert!(!summary.page);
+        assert!(summary.ticket);
+    }
+
+    #[test]
+    fn test_error_budget_remaining() {
+        let tracker = SloTracker::default();
+        // 1000 requests allow 1 error and 10 slow requests
+        for i in 0..1000 {
+            let latency = if i < 4 { SLOW } else { FAST };
+            tracker.record_at(100, 200, latency);
+        }
+        let summary = tracker.summary_at(100);
+        assert!((summary.error_budget_remaining - 0.6).abs() < 1e-9);
+
+        // Latency is the tighter objective until an error spends nearly
+        // all of the availability budget
+        tracker.record_at(100, 500, FAST);
+        let summary = tracker.summary_at(100);
+        assert!(summary.error_budget_remaining < 0.01);
+
+        // Overspending doesn't go negative
+        for _ in 0..10 {
+            tracker.record_at(100, 500, FAST);
+        }
+        assert_eq!(tracker.summary_at(100).error_budget_remaining, 0.0);
+    }
+
+    #[test]
+    fn test_client_errors_do_not_burn_budget() {
+        let tracker = SloTracker::default();
+        tracker.record_at(0, 404, FAST);
+        tracker.record_at(0, 401, FAST);
+
+        let summary = tracker.summary_at(0);
+        assert_eq!(window(&summary, "5m").errors, 0);
+    }
+
+    #[test]
+    fn test_old_requests_leave_short_windows() {
+        let tracker = SloTracker::default();
+        tracker.record_at(0, 500, FAST);
+
+        // Ten minutes later the error is outside 5m but inside 30m
+        let summary = tracker.summary_at(600);
+        assert_eq!(window(&summary, "5m").requests, 0);
+        assert_eq!(window(&summary, "30m").errors, 1);
+
+        // Seven hours later it is gone entirely, even after the ring wraps
+        tracker.record_at(7 * 3600, 200, FAST);
+        let summary = tracker.summary_at(7 * 3600);
+        assert_eq!(window(&summary, "6h").errors, 0);
+        assert_eq!(window(&summary, "6h").requests, 1);
+    }
+
+    #[test]
+    fn test_page_requires_both_windows() {
+        let tracker = SloTracker::default();
+        // A burst of errors 20 minutes ago: the 1h window burns fast, the
+        // 5m window is clean, so no page
+        for _ in 0..100 {
+            tracker.record_at(0, 500, FAST);
+        }
+        tracker.record_at(1200, 200, FAST);
+        assert!(!tracker.summary_at(1200).page);
+
+        // Errors still happening now: page
+        for _ in 0..100 {
+            tracker.record_at(1200, 500, FAST);
+        }
+        assert!(tracker.summary_at(1200).page);
+    }
+
+    #[test]
+    fn test_render_prometheus() {
+        let tracker = SloTracker::default();
+        tracker.record(200, FAST);
+        tracker.record(500, SLOW);
+
+        let text = tracker.render_prometheus();
+        assert!(text.contains("wsi_tile_requests_total 2"));
+        assert!(text.contains("wsi_tile_request_errors_total 1"));
+        assert!(text.contains("wsi_tile_slow_requests_total 1"));
+        assert!(text.contains("wsi_slo_target{slo=\"availability\"} 0.999"));
+        assert!(text.contains("wsi_slo_burn_rate{slo=\"latency\",window=\"1h\"} "));
+    }
+}
@@ -0,0 +1,183 @@
+//! Streaming response bodies.
+//!
+//! Large generated responses (such as print snapshots) are encoded on a
+//! blocking thread and sent to the client in chunks as they are produced,
+//! instead of being buffered whole before the first byte goes out. Large
+//! reads from storage (raw slide bytes) are streamed the same way from an
+//! async task.
+
+use std::future::Future;
+use std::io::{self, Write};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::task::{Context, Poll};
+
+use axum::body::Body;
+use bytes::Bytes;
+use http_body::{Body as HttpBody, Frame};
+use tokio::sync::mpsc;
+
+/// Size of each chunk sent to the client.
+pub const STREAM_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Chunks buffered ahead of a slow client before the producer blocks.
+const STREAM_BUFFERED_CHUNKS: usize = 4;
+
+static ACTIVE_STREAMS: AtomicUsize = AtomicUsize::new(0);
+
+/// Number of streamed response bodies still being sent.
+pub fn active_streams() -> usize {
+    ACTIVE_STREAMS.load(Ordering::Relaxed)
+}
+
+/// Run a blocking producer and stream what it writes as a response body.
+///
+/// The producer runs on the blocking thread pool. If it fails, or the client
+/// goes away, the body ends with an error so the connection is aborted
+/// rather than completed with a truncated payload.
+pub fn blocking_body<F>(produce: F) -> Body
+where
+    F: FnOnce(&mut ChunkWriter) -> io::Result<()> + Send + 'static,
+{
+    let (tx, rx) = mpsc::channel(STREAM_BUFFERED_CHUNKS);
+
+    tokio::task::spawn_blocking(move || {
+        let mut writer = ChunkWriter {
+            tx,
+            buf: Vec::with_capacity(STREAM_CHUNK_SIZE),
+        };
+        if let Err(e) = produce(&mut writer).and_then(|()| writer.flush()) {
+            let _ = writer.tx.blocking_send(Err(e));
+        }
+    });
+
+    Body::new(ChunkBody::new(rx))
+}
+
+/// Run an async producer and stream the chunks it sends as a response body.
+///
+/// The producer gets the sending half of the body; a send fails once the
+/// client has disconnected. Sending an error aborts the connection.
+pub fn spawned_body<F, Fut>(produce: F) -> Body
+where
+    F: FnOnce(mpsc::Sender<io::Result<Bytes>>) -> Fut,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let (tx, rx) = mpsc::channel(STREAM_BUFFERED_CHUNKS);
+    tokio::spawn(produce(tx));
+    Body::new(ChunkBody::new(rx))
+}
+
+/// Writer handed to [`blocking_body`] producers.
+///
+/// Writes are batched into [`STREAM_CHUNK_SIZE`] chunks. A write blocks
+/// while the client is behind, and fails once the client has disconnected.
+pub struct ChunkWriter {
+    tx: mpsc::Sender<io::Result<Bytes>>,
+    buf: Vec<u8>,
+}
+
+impl ChunkWriter {
+    fn send_buffered(&mut self) -> io::Result<()> {
+        let chunk = std::mem::replace(&mut self.buf, Vec::with_capacity(STREAM_CHUNK_SIZE));
+        self.tx
+            .blocking_send(Ok(Bytes::from(chunk)))
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "client disconnected"))
+    }
+}
+
+impl Write for ChunkWriter {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.buf.extend_from_slice(data);
+        if self.buf.len() >= STREAM_CHUNK_SIZE {
+            self.send_buffered()?;
+        }
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.buf.is_empty() {
+            return Ok(());
+        }
+        self.send_buffered()
+    }
+}
+
+/// Response body fed by a [`ChunkWriter`].
+struct ChunkBody {
+    rx: mpsc::Receiver<io::Result<Bytes>>,
+}
+
+impl ChunkBody {
+    fn new(rx: mpsc::Receiver<io::Result<Bytes>>) -> Self {
+        ACTIVE_STREAMS.fetch_add(1, Ordering::Relaxed);
+        Self { rx }
+    }
+}
+
+impl Drop for ChunkBody {
+    fn drop(&mut self) {
+        ACTIVE_STREAMS.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl HttpBody for ChunkBody {
+    type Data = Bytes;
+    type Error = io::Error;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Bytes>, io::Error>>> {
+        self.rx
+            .poll_recv(cx)
+            .map(|chunk| chunk.map(|result| result.map(Frame::data)))
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http_body_util::BodyExt;
+
+    #[tokio::test]
+    async fn test_blocking_body_streams_all_bytes() {
+        let body = blocking_body(|writer| {
+            for i in 0..(3 * STREAM_CHUNK_SIZE / 1000) {
+                writer.write_all(&[(i % 256) as u8; 1000])?;
+            }
+            Ok(())
+        });
+
+        let data = body.collect().await.unwrap().to_bytes();
+        assert_eq!(data.len(), (3 * STREAM_CHUNK_SIZE / 1000) * 1000);
+        assert_eq!(data[1000], 1);
+    }
+
+    #[tokio::test]
+    async fn test_blocking_body_propagates_errors() {
+        let body = blocking_body(|writer| {
+            writer.write_all(b"partial")?;
+            Err(io::Error::other("encoder failed"))
+        });
+
+        assert!(body.collect().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_spawned_body_streams_chunks() {
+        let body = spawned_body(|tx| async move {
+            for chunk in [&b"abc"[..], b"def"] {
+                if tx.send(Ok(Bytes::from_static(chunk))).await.is_err() {
+                    return;
+                }
+            }
+        });
+
+        assert_eq!(&body.collect().await.unwrap().to_bytes()[..], b"abcdef");
+    }
+}
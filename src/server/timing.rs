@@ -0,0 +1,40 @@
+//! `Server-Timing` response headers.
+//!
+//! With `--server-timing`, [`server_timing_middleware`] runs each request
+//! in a [`PhaseTimings`] scope and reports the phases recorded while
+//! handling it (see [`crate::timing`]), plus the total, in a
+//! `Server-Timing` header. Browser dev tools show the breakdown next to the
+//! request, so slow tiles can be triaged from a customer's browser without
+//! a custom build.
+//!
+//! Phases finished after the response headers are sent, such as those of
+//! streamed bodies, are not included. The header reveals storage and parse
+//! latencies to every client, so the flag is meant for debugging.
+
+use std::sync::Arc;
+use std::time::Instant;
+
+use axum::{
+    extract::Request,
+    http::{HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::timing::PhaseTimings;
+
+/// Header carrying the phase timings.
+pub const SERVER_TIMING_HEADER: HeaderName = HeaderName::from_static("server-timing");
+
+/// Time the request's phases and report them in a `Server-Timing` header.
+pub async fn server_timing_middleware(request: Request, next: Next) -> Response {
+    let timings = Arc::new(PhaseTimings::new());
+    let started = Instant::now();
+    let mut response = Arc::clone(&timings).scope(next.run(request)).await;
+
+    let value = timings.header_value(Some(started.elapsed()));
+    if let Ok(value) = HeaderValue::from_str(&value) {
+        response.headers_mut().append(SERVER_TIMING_HEADER, value);
+    }
+    response
+}
@@ -0,0 +1,366 @@
+//! TLS termination for the main listener.
+//!
+//! Small deployments often run the binary directly on a VM, without a
+//! reverse proxy to terminate HTTPS. With `--tls-cert` and `--tls-key`, the
+//! server accepts TLS itself through [`TlsListener`], which plugs into
+//! `axum::serve` like a plain `TcpListener`. Handshakes run in their own
+//! tasks with a timeout, so a slow or stalled client can't hold up other
+//! connections.
+//!
+//! [`https_redirect_router`] answers plain HTTP on a second port (usually
+//! 80) with a permanent redirect to the HTTPS listener.
+
+use std::io;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::{
+    extract::{connect_info::Connected, Request},
+    http::{header, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    serve::{IncomingStream, Listener},
+    Router,
+};
+use rustls::pki_types::{pem::PemObject, CertificateDer, PrivateKeyDer};
+use rustls::ServerConfig;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use tokio_rustls::server::TlsStream;
+use tokio_rustls::TlsAcceptor;
+use tracing::{debug, error};
+
+use super::connections::{ConnectionTracker, TrackedConnection};
+use crate::error::TlsError;
+
+/// Time a client has to complete the TLS handshake.
+pub const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Handshaken connections waiting for the server to pick them up.
+const TLS_ACCEPT_BACKLOG: usize = 128;
+
+/// Load a PEM certificate chain and private key into a server configuration.
+pub fn load_server_config(cert: &Path, key: &Path) -> Result<Arc<ServerConfig>, TlsError> {
+    let cert_error = |message: String| TlsError::Certificate {
+        path: cert.display().to_string(),
+        message,
+    };
+    let certs = CertificateDer::pem_file_iter(cert)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| cert_error(e.to_string()))?;
+    if certs.is_empty() {
+        return Err(cert_error("no certificate found".to_string()));
+    }
+
+    let key = PrivateKeyDer::from_pem_file(key).map_err(|e| TlsError::PrivateKey {
+        path: key.display().to_string(),
+        message: e.to_string(),
+    })?;
+
+    // Both ring and aws-lc-rs may be compiled in through other dependencies,
+    // so name the provider rather than rely on a process default
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let mut config = ServerConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .map_err(|e| TlsError::Config(e.to_string()))?
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .map_err(|e| TlsError::Config(e.to_string()))?;
+    config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+    Ok(Arc::new(config))
+}
+
+// =============================================================================
+// Listener
+// =============================================================================
+
+/// Listener accepting TLS connections, for use with `axum::serve`.
+pub struct TlsListener {
+    local_addr: SocketAddr,
+    connections: mpsc::Receiver<(TlsStream<TcpStream>, SocketAddr)>,
+}
+
+impl TlsListener {
+    /// Accept TLS on a bound TCP listener.
+    ///
+    /// Must be called within a Tokio runtime; connections are accepted and
+    /// handshaken in the background until the listener is dropped.
+    pub fn new(listener: TcpListener, config: Arc<ServerConfig>) -> io::Result<Self> {
+        let local_addr = listener.local_addr()?;
+        let (sender, connections) = mpsc::channel(TLS_ACCEPT_BACKLOG);
+        tokio::spawn(accept_connections(
+            listener,
+            TlsAcceptor::from(config),
+            sender,
+        ));
+        Ok(Self {
+            local_addr,
+            connections,
+        })
+    }
+}
+
+impl Listener for TlsListener {
+    type Io = TlsStream<TcpStream>;
+    type Addr = SocketAddr;
+
+    async fn accept(&mut self) -> (Self::Io, Self::Addr) {
+        match self.connections.recv().await {
+            Some(connection) => connection,
+            // The accept task only stops once the listener is dropped
+            None => std::future::pending().await,
+        }
+    }
+
+    fn local_addr(&self) -> io::Result<Self::Addr> {
+        Ok(self.local_addr)
+    }
+}
+
+impl Connected<IncomingStream<'_, TlsListener>> for TrackedConnection {
+    fn connect_info(stream: IncomingStream<'_, TlsListener>) -> Self {
+        ConnectionTracker::global()
+            .open(*stream.remote_addr())
+            .with_tls()
+    }
+}
+
+/// Accept TCP connections and hand them over once their handshake completes.
+async fn accept_connections(
+    listener: TcpListener,
+    acceptor: TlsAcceptor,
+    sender: mpsc::Sender<(TlsStream<TcpStream>, SocketAddr)>,
+) {
+    while !sender.is_closed() {
+        let (stream, peer) = match listener.accept().await {
+            Ok(connection) => connection,
+            Err(e) if is_connection_error(&e) => continue,
+            Err(e) => {
+                // Typically out of file descriptors; give others time to close
+                error!("Failed to accept connection: {}", e);
+                tokio::time::sleep(Duration::from_secs(1)).await;
+                continue;
+            }
+        };
+
+        let acceptor = acceptor.clone();
+        let sender = sender.clone();
+        tokio::spawn(async move {
+            match tokio::time::timeout(TLS_HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await {
+                Ok(Ok(stream)) => {
+                    let _ = sender.send((stream, peer)).await;
+                }
+                Ok(Err(e)) => debug!(%peer, "TLS handshake failed: {}", e),
+                Err(_) => debug!(%peer, "TLS handshake timed out"),
+            }
+        });
+    }
+}
+
+/// Errors affecting only the connection being accepted.
+fn is_connection_error(e: &io::Error) -> bool {
+    matches!(
+        e.kind(),
+        io::ErrorKind::ConnectionRefused
+            | io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::ConnectionReset
+    )
+}
+
+// =============================================================================
+// HTTP Redirect
+// =============================================================================
+
+/// Router redirecting every plain HTTP request to HTTPS on `https_port`.
+pub fn https_redirect_router(https_port: u16) -> Router {
+    Router::new()
+        .fallback(move |request: Request| async move { https_redirect(&request, https_port) })
+}
+
+/// Build the `308 Permanent Redirect` to the HTTPS URL of a request.
+fn https_redirect(request: &Request, https_port: u16) -> Response {
+    let Some(host) = request
+        .headers()
+        .get(header::HOST)
+        .and_then(|h| h.to_str().ok())
+        .map(strip_port)
+        .filter(|host| !host.is_empty())
+    else {
+        return (StatusCode::BAD_REQUEST, "Missing Host header").into_response();
+    };
+
+    let path = request
+        .uri()
+        .path_and_query()
+        .map_or("/", |path| path.as_str());
+    let location = if https_port == 443 {
+        format!("https://{}{}", host, path)
+    } else {
+        format!("https://{}:{}{}", host, https_port, path)
+    };
+
+    match HeaderValue::try_from(location) {
+        Ok(location) => (
+            StatusCode::PERMANENT_REDIRECT,
+            [(header::LOCATION, location)],
+        )
+            .into_response(),
+        Err(_) => (StatusCode::BAD_REQUEST, "Invalid Host header").into_response(),
+    }
+}
+
+/// Remove the port from a `Host` header value, keeping IPv6 brackets.
+fn strip_port(host: &str) -> &str {
+    if host.starts_with('[') {
+        return host.find(']').map_or(host, |end| &host[..=end]);
+    }
+    host.split(':').next().unwrap_or(host)
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::routing::get;
+    use std::path::PathBuf;
+    use tower::ServiceExt;
+
+    /// Write a self-signed certificate for `localhost` and its key.
+    fn write_cert(name: &str) -> (PathBuf, PathBuf, String) {
+        let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        let dir = std::env::temp_dir();
+        let cert_path = dir.join(format!("wsi-tls-{}-{}.crt", name, std::process::id()));
+        let key_path = dir.join(format!("wsi-tls-{}-{}.key", name, std::process::id()));
+        std::fs::write(&cert_path, cert.cert.pem()).unwrap();
+        std::fs::write(&key_path, cert.key_pair.serialize_pem()).unwrap();
+        (cert_path, key_path, cert.cert.pem())
+    }
+
+    async fn redirect(host: Option<&str>, uri: &str, https_port: u16) -> Response {
+        let mut request = Request::builder().uri(uri);
+        if let Some(host) = host {
+            request = request.header(header::HOST, host);
+        }
+        https_redirect_router(https_port)
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+    }
+
+    #[test]
+    fn test_load_server_config_errors() {
+        let (cert, key, _) = write_cert("errors");
+        assert!(load_server_config(&cert, &key).is_ok());
+
+        let missing = std::env::temp_dir().join("wsi-tls-missing.pem");
+        assert!(matches!(
+            load_server_config(&missing, &key),
+            Err(TlsError::Certificate { .. })
+        ));
+        assert!(matches!(
+            load_server_config(&key, &key),
+            Err(TlsError::Certificate { .. })
+        ));
+        assert!(matches!(
+            load_server_config(&cert, &cert),
+            Err(TlsError::PrivateKey { .. })
+        ));
+
+        std::fs::remove_file(cert).ok();
+        std::fs::remove_file(key).ok();
+    }
+
+    #[test]
+    fn test_strip_port() {
+        assert_eq!(strip_port("example.org:80"), "example.org");
+        assert_eq!(strip_port("example.org"), "example.org");
+        assert_eq!(strip_port("[::1]:8080"), "[::1]");
+    }
+
+    #[tokio::test]
+    async fn test_https_redirect() {
+        let response = redirect(
+            Some("slides.example.org"),
+            "/tiles/a.svs/0/0/0.jpg?q=1",
+            443,
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+        assert_eq!(
+            response.headers()[header::LOCATION],
+            "https://slides.example.org/tiles/a.svs/0/0/0.jpg?q=1"
+        );
+
+        let response = redirect(Some("127.0.0.1:8080"), "/health", 8443).await;
+        assert_eq!(
+            response.headers()[header::LOCATION],
+            "https://127.0.0.1:8443/health"
+        );
+
+        let response = redirect(None, "/health", 443).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_tls_listener_serves_https() {
+        let (cert, key, cert_pem) = write_cert("serve");
+        let config = load_server_config(&cert, &key).unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let listener = TlsListener::new(listener, config).unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let app = Router::new()
+            .route(
+                "/proto",
+                get(|request: Request| async move {
+                    request
+                        .headers()
+                        .get("x-forwarded-proto")
+                        .and_then(|h| h.to_str().ok())
+                        .unwrap_or("http")
+                        .to_string()
+                }),
+            )
+            .layer(axum::middleware::from_fn(
+                super::super::connections::connection_middleware,
+            ));
+        tokio::spawn(async move {
+            axum::serve(
+                listener,
+                app.into_make_service_with_connect_info::<TrackedConnection>(),
+            )
+            .await
+            .unwrap();
+        });
+
+        let client = reqwest::Client::builder()
+            .add_root_certificate(reqwest::Certificate::from_pem(cert_pem.as_bytes()).unwrap())
+            .resolve("localhost", addr)
+            .build()
+            .unwrap();
+        let body = client
+            .get(format!("https://localhost:{}/proto", addr.port()))
+            .send()
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+        assert_eq!(body, "https");
+
+        // Plain HTTP on the TLS port fails the handshake instead of being served
+        let plain = reqwest::Client::new()
+            .get(format!("http://127.0.0.1:{}/proto", addr.port()))
+            .send()
+            .await;
+        assert!(plain.is_err());
+
+        std::fs::remove_file(cert).ok();
+        std::fs::remove_file(key).ok();
+    }
+}
@@ -2,6 +2,19 @@
 
 use crate::server::handlers::SlideMetadataResponse;
 
+/// Maximum number of slides shown side by side in the comparison viewer.
+pub const MAX_COMPARE_SLIDES: usize = 4;
+
+/// A slide shown in the comparison viewer.
+#[derive(Debug)]
+pub struct ComparePane {
+    /// Slide metadata containing dimensions and level info
+    pub metadata: SlideMetadataResponse,
+
+    /// Query string authorizing the slide's tile requests (e.g. "?vt=...&exp=...")
+    pub auth_query: String,
+}
+
 /// Escape HTML special characters to prevent XSS attacks.
 fn html_escape(s: &str) -> String {
     let mut result = String::with_capacity(s.len());
@@ -349,6 +362,312 @@ pub fn generate_viewer_html(
     )
 }
 
+/// Tile source configuration of a comparison pane, read by the page script.
+fn compare_pane_json(pane: &ComparePane, base_url: &str) -> serde_json::Value {
+    let metadata = &pane.metadata;
+    let levels: Vec<_> = metadata
+        .levels
+        .iter()
+        .map(|l| serde_json::json!({ "level": l.level, "width": l.width, "height": l.height }))
+        .collect();
+
+    serde_json::json!({
+        "tileUrl": format!("{}/tiles/{}/", base_url, urlencoding::encode(&metadata.slide_id)),
+        "authQuery": pane.auth_query,
+        "width": metadata.width,
+        "height": metadata.height,
+        "tileSize": metadata.levels.first().map(|l| l.tile_width).unwrap_or(256),
+        "levels": levels,
+    })
+}
+
+/// Generate an HTML page showing slides side by side, with pan and zoom
+/// synchronized across their OpenSeadragon viewers.
+///
+/// Viewports are synchronized in OpenSeadragon's normalized coordinates, so
+/// slides of different sizes (e.g. serial sections) stay aligned by relative
+/// position.
+///
+/// # Arguments
+///
+/// * `panes` - Slides to show, left to right
+/// * `base_url` - Base URL for tile requests (e.g., "http://localhost:3000")
+pub fn generate_compare_html(panes: &[ComparePane], base_url: &str) -> String {
+    let base_url = base_url.trim_end_matches('/');
+
+    let pane_configs: Vec<_> = panes
+        .iter()
+        .map(|pane| compare_pane_json(pane, base_url))
+        .collect();
+    // Keep slide IDs from closing the script element
+    let pane_configs = serde_json::Value::from(pane_configs)
+        .to_string()
+        .replace("</", "<\\/");
+
+    let title = panes
+        .iter()
+        .map(|pane| html_escape(&pane.metadata.slide_id))
+        .collect::<Vec<_>>()
+        .join(" | ");
+
+    let pane_divs: String = panes
+        .iter()
+        .enumerate()
+        .map(|(i, pane)| {
+            let metadata = &pane.metadata;
+            format!(
+                r#"
+        <div class="pane">
+            <div id="viewer-{i}" class="viewer"></div>
+            <div class="info-panel">
+                <h2>{slide_id}</h2>
+                <div class="meta"><span>{width}</span> x <span>{height}</span> px</div>
+                <div class="format-badge">{format}</div>
+            </div>
+        </div>"#,
+                i = i,
+                slide_id = html_escape(&metadata.slide_id),
+                width = metadata.width,
+                height = metadata.height,
+                format = html_escape(&metadata.format),
+            )
+        })
+        .collect();
+
+    format!(
+        r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+    <meta charset="UTF-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1.0">
+    <title>WSI Compare - {title}</title>
+    <script src="https://cdn.jsdelivr.net/npm/openseadragon@4.1/build/openseadragon/openseadragon.min.js"></script>
+    <style>
+        * {{
+            margin: 0;
+            padding: 0;
+            box-sizing: border-box;
+        }}
+        body {{
+            background: #0f0f0f;
+            font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, Oxygen, Ubuntu, sans-serif;
+            overflow: hidden;
+        }}
+        .panes {{
+            display: flex;
+            width: 100vw;
+            height: 100vh;
+        }}
+        .pane {{
+            position: relative;
+            flex: 1;
+            min-width: 0;
+            border-right: 1px solid rgba(255, 255, 255, 0.15);
+        }}
+        .pane:last-child {{
+            border-right: none;
+        }}
+        .viewer {{
+            width: 100%;
+            height: 100%;
+        }}
+        .info-panel {{
+            position: absolute;
+            top: 16px;
+            left: 16px;
+            background: rgba(0, 0, 0, 0.85);
+            color: #fff;
+            padding: 12px 16px;
+            border-radius: 8px;
+            font-size: 12px;
+            line-height: 1.5;
+            border: 1px solid rgba(255, 255, 255, 0.1);
+            max-width: calc(100% - 32px);
+            z-index: 1000;
+        }}
+        .info-panel h2 {{
+            font-size: 13px;
+            font-weight: 600;
+            margin-bottom: 4px;
+            word-break: break-all;
+        }}
+        .info-panel .meta {{
+            color: rgba(255, 255, 255, 0.7);
+        }}
+        .info-panel .meta span {{
+            color: rgba(255, 255, 255, 0.9);
+        }}
+        .info-panel .format-badge {{
+            display: inline-block;
+            background: rgba(99, 102, 241, 0.2);
+            color: #818cf8;
+            padding: 2px 8px;
+            border-radius: 4px;
+            font-size: 11px;
+            font-weight: 500;
+            margin-top: 6px;
+        }}
+        .controls-hint {{
+            position: absolute;
+            bottom: 16px;
+            left: 16px;
+            background: rgba(0, 0, 0, 0.7);
+            color: rgba(255, 255, 255, 0.6);
+            padding: 8px 12px;
+            border-radius: 6px;
+            font-size: 11px;
+            z-index: 1000;
+        }}
+        .controls-hint kbd {{
+            background: rgba(255, 255, 255, 0.15);
+            padding: 2px 6px;
+            border-radius: 3px;
+            margin: 0 2px;
+        }}
+        .controls-hint label {{
+            margin-left: 8px;
+            cursor: pointer;
+        }}
+        .error-banner {{
+            position: absolute;
+            top: 0;
+            left: 0;
+            right: 0;
+            background: rgba(220, 38, 38, 0.95);
+            color: white;
+            padding: 12px 20px;
+            font-size: 14px;
+            z-index: 2000;
+            display: none;
+        }}
+        .error-banner.visible {{
+            display: block;
+        }}
+    </style>
+</head>
+<body>
+    <div id="error-banner" class="error-banner"></div>
+
+    <div class="panes">{pane_divs}
+    </div>
+
+    <div class="controls-hint">
+        <kbd>+</kbd>/<kbd>-</kbd> Zoom &nbsp; <kbd>Home</kbd> Reset &nbsp; <kbd>S</kbd> Sync
+        <label><input type="checkbox" id="sync" checked> Synchronized</label>
+    </div>
+
+    <script>
+        // Tile source configuration of each pane, left to right
+        const panes = {pane_configs};
+
+        if (typeof OpenSeadragon === 'undefined') {{
+            document.body.textContent = 'Error: Viewer library failed to load.';
+            throw new Error('OpenSeadragon library not loaded');
+        }}
+
+        // Same tile source as the single-slide viewer: OSD level 0 is the
+        // lowest resolution, our level 0 the highest
+        function tileSourceFor(pane) {{
+            const maxLevel = pane.levels.length - 1;
+            return {{
+                width: pane.width,
+                height: pane.height,
+                tileSize: pane.tileSize,
+                minLevel: 0,
+                maxLevel: maxLevel,
+                getLevelScale: function(level) {{
+                    const ourLevel = maxLevel - level;
+                    if (ourLevel < 0 || ourLevel > maxLevel) return 0;
+                    return pane.levels[ourLevel].width / pane.width;
+                }},
+                getNumTiles: function(level) {{
+                    const ourLevel = maxLevel - level;
+                    if (ourLevel < 0 || ourLevel > maxLevel) return {{ x: 0, y: 0 }};
+                    const dims = pane.levels[ourLevel];
+                    return {{
+                        x: Math.ceil(dims.width / pane.tileSize),
+                        y: Math.ceil(dims.height / pane.tileSize)
+                    }};
+                }},
+                getTileUrl: function(level, x, y) {{
+                    const originalLevel = pane.levels[maxLevel - level].level;
+                    return pane.tileUrl + originalLevel + "/" + x + "/" + y + ".jpg" + pane.authQuery;
+                }}
+            }};
+        }}
+
+        const viewers = panes.map(function(pane, i) {{
+            return OpenSeadragon({{
+                id: "viewer-" + i,
+                prefixUrl: "https://cdn.jsdelivr.net/npm/openseadragon@4.1/build/openseadragon/images/",
+                tileSources: tileSourceFor(pane),
+                showNavigator: true,
+                navigatorPosition: "BOTTOM_RIGHT",
+                navigatorSizeRatio: 0.2,
+                showZoomControl: true,
+                showHomeControl: true,
+                showFullPageControl: false,
+                animationTime: 0.3,
+                blendTime: 0.1,
+                maxZoomPixelRatio: 2,
+                visibilityRatio: 0.5,
+                constrainDuringPan: true,
+                crossOriginPolicy: "Anonymous"
+            }});
+        }});
+
+        // Mirror pan and zoom of the viewer being moved onto the others
+        const syncToggle = document.getElementById('sync');
+        let syncing = false;
+        function syncFrom(source) {{
+            if (!syncToggle.checked || syncing) return;
+            syncing = true;
+            const center = source.viewport.getCenter();
+            const zoom = source.viewport.getZoom();
+            viewers.forEach(function(viewer) {{
+                if (viewer !== source) {{
+                    viewer.viewport.zoomTo(zoom, null, true);
+                    viewer.viewport.panTo(center, true);
+                }}
+            }});
+            syncing = false;
+        }}
+        viewers.forEach(function(viewer) {{
+            viewer.addHandler('zoom', function() {{ syncFrom(viewer); }});
+            viewer.addHandler('pan', function() {{ syncFrom(viewer); }});
+        }});
+        syncToggle.addEventListener('change', function() {{
+            if (syncToggle.checked) syncFrom(viewers[0]);
+        }});
+
+        // Report the first tile failure of any pane
+        let errorShown = false;
+        viewers.forEach(function(viewer) {{
+            viewer.addHandler('tile-load-failed', function(event) {{
+                if (errorShown) return;
+                errorShown = true;
+                const banner = document.getElementById('error-banner');
+                banner.textContent = 'Failed to load tiles: ' +
+                    (event.message || 'check your network connection and try refreshing the page.');
+                banner.classList.add('visible');
+            }});
+        }});
+
+        document.addEventListener('keydown', function(e) {{
+            if (e.key === 's' || e.key === 'S') {{
+                syncToggle.checked = !syncToggle.checked;
+                syncToggle.dispatchEvent(new Event('change'));
+            }}
+        }});
+    </script>
+</body>
+</html>"##,
+        title = title,
+        pane_divs = pane_divs,
+        pane_configs = pane_configs,
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -393,6 +712,7 @@ mod tests {
                     downsample: 16.0,
                 },
             ],
+            integrity: None,
         }
     }
 
@@ -514,4 +834,47 @@ mod tests {
         // The escaped version should appear in the format badge
         assert!(html.contains("&lt;img onerror=alert(1)&gt;"));
     }
+
+    fn compare_panes() -> Vec<ComparePane> {
+        let mut ihc = test_metadata();
+        ihc.slide_id = "case 1/ihc.svs".to_string();
+        ihc.width = 48000;
+        vec![
+            ComparePane {
+                metadata: test_metadata(),
+                auth_query: "?vt=abc&exp=1".to_string(),
+            },
+            ComparePane {
+                metadata: ihc,
+                auth_query: "?vt=def&exp=1".to_string(),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_generate_compare_html_panes() {
+        let html = generate_compare_html(&compare_panes(), "http://localhost:3000/");
+
+        // One viewer per slide, each with its own tile URL and token
+        assert!(html.contains("id=\"viewer-0\""));
+        assert!(html.contains("id=\"viewer-1\""));
+        assert!(!html.contains("viewer-2"));
+        assert!(html.contains("http://localhost:3000/tiles/test.svs/"));
+        assert!(html.contains("http://localhost:3000/tiles/case%201%2Fihc.svs/"));
+        assert!(html.contains("?vt=abc&exp=1"));
+        assert!(html.contains("?vt=def&exp=1"));
+        assert!(html.contains("\"width\":48000"));
+        assert!(html.contains("test.svs | case 1/ihc.svs"));
+        assert!(html.contains("syncFrom"));
+    }
+
+    #[test]
+    fn test_generate_compare_html_escapes_slide_ids() {
+        let mut panes = compare_panes();
+        panes[1].metadata.slide_id = "</script><script>alert(1)</script>".to_string();
+        let html = generate_compare_html(&panes, "http://localhost:3000");
+
+        assert!(!html.contains("<script>alert(1)"));
+        assert!(html.contains("&lt;/script&gt;&lt;script&gt;alert(1)"));
+    }
 }
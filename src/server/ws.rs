@@ -0,0 +1,152 @@
+//! WebSocket channel for server events.
+//!
+//! `GET /ws` upgrades the connection to a WebSocket and pushes every
+//! [`ServerEvent`] published on the tile service's event bus as a JSON text
+//! message, so viewers can show "opening slide…" and warm-up progress
+//! instead of waiting on pending tile requests.
+//!
+//! The handshake and framing are axum's; this module decides what is sent.
+//! Messages sent by the client are read and ignored, and pings are answered
+//! automatically. A client that falls too far behind gets a
+//! `{"type": "lagged", "missed": N}` message in place of the events it
+//! missed.
+
+use std::time::Duration;
+
+use axum::extract::ws::{Message, WebSocket};
+use bytes::Bytes;
+use tokio::sync::broadcast::{self, error::RecvError};
+
+use crate::slide::{ServerEvent, EVENT_KINDS};
+
+/// Idle time after which the server pings the client.
+pub const PING_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Largest message accepted from a client.
+pub const MAX_CLIENT_MESSAGE_BYTES: usize = 64 * 1024;
+
+/// Which events a client receives.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EventFilter {
+    /// Only events about these slides (events about no slide are dropped)
+    slides: Option<Vec<String>>,
+
+    /// Only events of these types
+    kinds: Option<Vec<String>>,
+}
+
+impl EventFilter {
+    /// Build a filter from comma-separated slide IDs and event types.
+    ///
+    /// # Errors
+    ///
+    /// Returns a message naming the first unknown event type.
+    pub fn parse(slides: Option<&str>, events: Option<&str>) -> Result<Self, String> {
+        let list = |value: &str| -> Vec<String> {
+            value
+                .split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(str::to_string)
+                .collect()
+        };
+        let kinds = events.map(list);
+        if let Some(unknown) = kinds
+            .iter()
+            .flatten()
+            .find(|kind| !EVENT_KINDS.contains(&kind.as_str()))
+        {
+            return Err(format!(
+                "Unknown event type '{}'. Expected one of: {}",
+                unknown,
+                EVENT_KINDS.join(", ")
+            ));
+        }
+        Ok(Self {
+            slides: slides.map(list),
+            kinds,
+        })
+    }
+
+    /// Whether a client with this filter receives an event.
+    pub fn matches(&self, event: &ServerEvent) -> bool {
+        let slide_matches = self.slides.as_ref().map_or(true, |slides| {
+            event
+                .slide_id()
+                .is_some_and(|slide_id| slides.iter().any(|s| s == slide_id))
+        });
+        let kind_matches = self
+            .kinds
+            .as_ref()
+            .map_or(true, |kinds| kinds.iter().any(|k| k == event.kind()));
+        slide_matches && kind_matches
+    }
+}
+
+/// Forward events to an upgraded connection until either side closes it.
+pub async fn serve_events(
+    mut socket: WebSocket,
+    mut events: broadcast::Receiver<ServerEvent>,
+    filter: EventFilter,
+) {
+    let start = tokio::time::Instant::now() + PING_INTERVAL;
+    let mut ping = tokio::time::interval_at(start, PING_INTERVAL);
+    loop {
+        let message = tokio::select! {
+            event = events.recv() => match event {
+                Ok(event) if filter.matches(&event) => match serde_json::to_string(&event) {
+                    Ok(json) => Message::Text(json.into()),
+                    Err(_) => continue,
+                },
+                Ok(_) => continue,
+                Err(RecvError::Lagged(missed)) => Message::Text(
+                    serde_json::json!({ "type": "lagged", "missed": missed })
+                        .to_string()
+                        .into(),
+                ),
+                Err(RecvError::Closed) => Message::Close(None),
+            },
+            received = socket.recv() => match received {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => continue,
+            },
+            _ = ping.tick() => Message::Ping(Bytes::new()),
+        };
+
+        let close = matches!(message, Message::Close(_));
+        if socket.send(message).await.is_err() || close {
+            break;
+        }
+        ping.reset();
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_filter() {
+        let opened = ServerEvent::SlideOpening {
+            slide_id: "a.svs".to_string(),
+        };
+        let evicted = ServerEvent::TileCacheEvicted { tiles: 2 };
+
+        let all = EventFilter::default();
+        assert!(all.matches(&opened) && all.matches(&evicted));
+
+        let slides = EventFilter::parse(Some("a.svs, b.svs"), None).unwrap();
+        assert!(slides.matches(&opened));
+        assert!(!slides.matches(&evicted));
+
+        let kinds = EventFilter::parse(None, Some("tile_cache_evicted")).unwrap();
+        assert!(!kinds.matches(&opened));
+        assert!(kinds.matches(&evicted));
+
+        assert!(EventFilter::parse(None, Some("slide_opened,bogus")).is_err());
+    }
+}
@@ -0,0 +1,197 @@
+//! Azure Blob Storage slide source implementation.
+//!
+//! This module provides an implementation of `SlideSource` that creates
+//! `AzureBlobRangeReader` instances for slides stored in an Azure Storage
+//! container.
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use reqwest::header;
+
+use crate::error::IoError;
+use crate::io::{send_request, AzureBlobClient, AzureBlobRangeReader};
+
+use super::s3_source::is_slide_file;
+use super::{SlideListResult, SlideSource};
+
+/// Azure Blob Storage implementation of `SlideSource`.
+///
+/// Creates `AzureBlobRangeReader` instances for slides stored in a
+/// container. The slide ID is used as the blob name within the container.
+///
+/// # Example
+///
+/// ```ignore
+/// use wsi_streamer::io::AzureBlobClient;
+/// use wsi_streamer::slide::AzureBlobSlideSource;
+///
+/// let client = AzureBlobClient::new("myaccount", None, Some(&sas_token))?;
+/// let source = AzureBlobSlideSource::new(client, "slides".to_string());
+///
+/// // The slide ID "cases/example.svs" becomes the blob name
+/// let reader = source.create_reader("cases/example.svs").await?;
+/// ```
+#[derive(Clone)]
+pub struct AzureBlobSlideSource {
+    client: AzureBlobClient,
+    container: String,
+}
+
+impl AzureBlobSlideSource {
+    /// Create a new AzureBlobSlideSource for the given container.
+    pub fn new(client: AzureBlobClient, container: String) -> Self {
+        Self { client, container }
+    }
+
+    /// Get the container name.
+    pub fn container(&self) -> &str {
+        &self.container
+    }
+}
+
+#[async_trait]
+impl SlideSource for AzureBlobSlideSource {
+    type Reader = AzureBlobRangeReader;
+
+    async fn create_reader(&self, slide_id: &str) -> Result<Self::Reader, IoError> {
+        AzureBlobRangeReader::new(self.client.clone(), &self.container, slide_id).await
+    }
+
+    async fn list_slides(
+        &self,
+        limit: u32,
+        cursor: Option<&str>,
+        prefix: Option<&str>,
+        delimiter: Option<&str>,
+    ) -> Result<SlideListResult, IoError> {
+        let mut query = format!("restype=container&comp=list&maxresults={}", limit);
+        if let Some(marker) = cursor {
+            query.push_str(&format!("&marker={}", urlencoding::encode(marker)));
+        }
+        if let Some(prefix) = prefix {
+            query.push_str(&format!("&prefix={}", urlencoding::encode(prefix)));
+        }
+        if let Some(delimiter) = delimiter {
+            query.push_str(&format!("&delimiter={}", urlencoding::encode(delimiter)));
+        }
+
+        let url = self.client.container_url(&self.container, &query);
+        let request = self.client.request(reqwest::Method::GET, &url);
+        let identifier = format!("az://{}", self.container);
+        let response = send_request(request, &identifier).await?;
+        let body = response
+            .text()
+            .await
+            .map_err(|e| IoError::Connection(e.to_string()))?;
+        Ok(parse_listing(&body))
+    }
+
+    async fn put_object(&self, key: &str, data: Bytes, content_type: &str) -> Result<(), IoError> {
+        let url = self.client.blob_url(&self.container, key)?;
+        let request = self
+            .client
+            .request(reqwest::Method::PUT, &url)
+            .header("x-ms-blob-type", "BlockBlob")
+            .header(header::CONTENT_TYPE, content_type)
+            .body(data);
+        send_request(request, &format!("az://{}/{}", self.container, key)).await?;
+
+        Ok(())
+    }
+}
+
+// =============================================================================
+// Listing
+// =============================================================================
+
+/// Parse a List Blobs response.
+///
+/// The response is a flat XML document; blob names are the `<Name>`
+/// elements, folders (with a delimiter) the names inside `<BlobPrefix>`, and
+/// the continuation marker is `<NextMarker>`, empty on the last page.
+fn parse_listing(body: &str) -> SlideListResult {
+    let slides = xml_elements(body, "Name")
+        .into_iter()
+        .filter(|name| is_slide_file(name))
+        .collect();
+    let folders = xml_elements(body, "BlobPrefix")
+        .iter()
+        .flat_map(|prefix| xml_elements(prefix, "Name"))
+        .collect();
+    let next_cursor = xml_elements(body, "NextMarker")
+        .into_iter()
+        .next()
+        .filter(|marker| !marker.is_empty());
+
+    SlideListResult {
+        slides,
+        folders,
+        next_cursor,
+    }
+}
+
+/// Text of every `<tag>...</tag>` element in a document.
+fn xml_elements(body: &str, tag: &str) -> Vec<String> {
+    let open = format!("<{}>", tag);
+    let close = format!("</{}>", tag);
+
+    let mut elements = Vec::new();
+    let mut rest = body;
+    while let Some(start) = rest.find(&open) {
+        rest = &rest[start + open.len()..];
+        let Some(end) = rest.find(&close) else {
+            break;
+        };
+        elements.push(xml_unescape(&rest[..end]));
+        rest = &rest[end + close.len()..];
+    }
+    elements
+}
+
+/// Replace the predefined XML entities.
+fn xml_unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_listing() {
+        let body = r#"<?xml version="1.0" encoding="utf-8"?>
+<EnumerationResults ServiceEndpoint="https://lab.blob.core.windows.net/" ContainerName="slides">
+  <MaxResults>3</MaxResults>
+  <Blobs>
+    <Blob><Name>a/s1.svs</Name><Properties><Content-Length>10</Content-Length></Properties></Blob>
+    <Blob><Name>a/notes.txt</Name></Blob>
+    <Blob><Name>R&amp;D/s2.tif</Name></Blob>
+  </Blobs>
+  <NextMarker>2!72!MDAwMDE0</NextMarker>
+</EnumerationResults>"#;
+        let listing = parse_listing(body);
+        assert_eq!(listing.slides, vec!["a/s1.svs", "R&D/s2.tif"]);
+        assert!(listing.folders.is_empty());
+        assert_eq!(listing.next_cursor.as_deref(), Some("2!72!MDAwMDE0"));
+
+        // Delimited listings report folders as blob prefixes
+        let body = r#"<EnumerationResults><Blobs>
+    <BlobPrefix><Name>case-1/</Name></BlobPrefix>
+    <Blob><Name>s3.svs</Name></Blob>
+    <BlobPrefix><Name>case-2.svs/</Name></BlobPrefix>
+  </Blobs><NextMarker /></EnumerationResults>"#;
+        let listing = parse_listing(body);
+        assert_eq!(listing.slides, vec!["s3.svs"]);
+        assert_eq!(listing.folders, vec!["case-1/", "case-2.svs/"]);
+
+        // The last page has an empty marker
+        let listing =
+            parse_listing("<EnumerationResults><Blobs /><NextMarker /></EnumerationResults>");
+        assert!(listing.slides.is_empty());
+        assert!(listing.next_cursor.is_none());
+    }
+}
@@ -0,0 +1,197 @@
+//! Format conformance results for slide listings.
+//!
+//! Every slide open validates the file against the supported subset. The
+//! registry keeps the outcome of the last validation per slide, so listings
+//! can mark slides that will fail (or that open with warnings) before a user
+//! clicks them. Slides that were never opened have no entry; the outcome is
+//! dropped when a slide is invalidated, since the file may have changed.
+//!
+//! Only properties of the file are recorded. Storage errors and open
+//! timeouts say nothing about conformance and leave the last result alone.
+
+use std::collections::HashMap;
+use std::sync::RwLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+
+use crate::error::{FormatError, TiffError};
+
+/// Maximum number of slides with a remembered result.
+///
+/// Beyond this, results for new slides are not recorded, which bounds memory
+/// on buckets with very large inventories.
+pub const MAX_CONFORMANCE_ENTRIES: usize = 100_000;
+
+/// Outcome of validating a slide.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConformanceStatus {
+    /// The slide opened without issues
+    Valid,
+
+    /// The slide opened, but validation reported non-fatal issues
+    Warnings,
+
+    /// The slide is outside the supported subset and can't be served
+    Unsupported,
+}
+
+/// Result of the last validation of a slide.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SlideConformance {
+    /// Validation outcome
+    pub status: ConformanceStatus,
+
+    /// Non-fatal issues reported by validation
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+
+    /// Why the slide can't be served
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+
+    /// Validation time as a Unix timestamp (seconds)
+    pub checked_at: u64,
+}
+
+impl SlideConformance {
+    /// Result for a slide that opened, with any validation warnings.
+    pub fn opened(warnings: Vec<String>) -> Self {
+        let status = if warnings.is_empty() {
+            ConformanceStatus::Valid
+        } else {
+            ConformanceStatus::Warnings
+        };
+        Self {
+            status,
+            warnings,
+            reason: None,
+            checked_at: unix_now(),
+        }
+    }
+
+    /// Result for a slide that failed validation.
+    pub fn unsupported(reason: impl Into<String>) -> Self {
+        Self {
+            status: ConformanceStatus::Unsupported,
+            warnings: Vec::new(),
+            reason: Some(reason.into()),
+            checked_at: unix_now(),
+        }
+    }
+
+    /// Result for a failed open, if the failure is a property of the file.
+    pub fn from_open_error(err: &FormatError) -> Option<Self> {
+        match err {
+            FormatError::UnsupportedFormat { reason } => Some(Self::unsupported(reason.clone())),
+            FormatError::Tiff(TiffError::Io(_)) => None,
+            FormatError::Tiff(err) => Some(Self::unsupported(err.to_string())),
+            FormatError::Io(_)
+            | FormatError::OpenQueueTimeout { .. }
+            | FormatError::Quarantined { .. } => None,
+        }
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Last validation result of each slide.
+#[derive(Debug, Default)]
+pub struct ConformanceCache {
+    entries: RwLock<HashMap<String, SlideConformance>>,
+}
+
+impl ConformanceCache {
+    /// Create an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the result of validating a slide.
+    pub fn record(&self, slide_id: &str, conformance: SlideConformance) {
+        let mut entries = self.entries.write().unwrap();
+        if entries.len() >= MAX_CONFORMANCE_ENTRIES && !entries.contains_key(slide_id) {
+            return;
+        }
+        entries.insert(slide_id.to_string(), conformance);
+    }
+
+    /// Get the last validation result of a slide.
+    pub fn get(&self, slide_id: &str) -> Option<SlideConformance> {
+        self.entries.read().unwrap().get(slide_id).cloned()
+    }
+
+    /// Forget a slide's result.
+    pub fn forget(&self, slide_id: &str) {
+        self.entries.write().unwrap().remove(slide_id);
+    }
+
+    /// Number of slides with a result.
+    pub fn len(&self) -> usize {
+        self.entries.read().unwrap().len()
+    }
+
+    /// Whether no slide has a result.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::IoError;
+
+    #[test]
+    fn test_opened_status() {
+        assert_eq!(
+            SlideConformance::opened(Vec::new()).status,
+            ConformanceStatus::Valid
+        );
+        let conformance = SlideConformance::opened(vec!["odd tile size".to_string()]);
+        assert_eq!(conformance.status, ConformanceStatus::Warnings);
+        assert_eq!(conformance.warnings.len(), 1);
+    }
+
+    #[test]
+    fn test_from_open_error() {
+        let unsupported = FormatError::UnsupportedFormat {
+            reason: "not a TIFF".to_string(),
+        };
+        let conformance = SlideConformance::from_open_error(&unsupported).unwrap();
+        assert_eq!(conformance.status, ConformanceStatus::Unsupported);
+        assert_eq!(conformance.reason.as_deref(), Some("not a TIFF"));
+
+        let parse = FormatError::Tiff(TiffError::InvalidMagic(0x1234));
+        assert!(SlideConformance::from_open_error(&parse).is_some());
+
+        // Storage failures say nothing about the file
+        let io = IoError::Connection("reset".to_string());
+        assert!(SlideConformance::from_open_error(&FormatError::Io(io.clone())).is_none());
+        assert!(SlideConformance::from_open_error(&FormatError::Tiff(TiffError::Io(io))).is_none());
+    }
+
+    #[test]
+    fn test_cache_record_and_forget() {
+        let cache = ConformanceCache::new();
+        assert!(cache.is_empty());
+
+        cache.record("a.svs", SlideConformance::unsupported("strips"));
+        cache.record("a.svs", SlideConformance::opened(Vec::new()));
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.get("a.svs").unwrap().status, ConformanceStatus::Valid);
+
+        cache.forget("a.svs");
+        assert!(cache.get("a.svs").is_none());
+    }
+}
@@ -0,0 +1,205 @@
+//! Server events for live status updates.
+//!
+//! Opening a large slide from object storage can take seconds, and a viewer
+//! that only sees pending tile requests can't tell a slow open from a hung
+//! one. The [`SlideRegistry`](super::SlideRegistry) and the tile service
+//! publish [`ServerEvent`]s (slide opens, warm-up progress, evictions,
+//! failed tiles) on an [`EventBus`], which the `/ws` endpoint forwards to
+//! connected clients.
+//!
+//! Publishing is fire-and-forget: events are built only while someone is
+//! subscribed, and subscribers that fall more than [`EVENT_BUS_CAPACITY`]
+//! events behind skip the oldest ones rather than slowing the server.
+
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+/// Events buffered per subscriber before the oldest are dropped.
+pub const EVENT_BUS_CAPACITY: usize = 1024;
+
+/// Every event type, as serialized in the `type` field.
+pub const EVENT_KINDS: [&str; 7] = [
+    "slide_opening",
+    "slide_opened",
+    "slide_failed",
+    "slide_evicted",
+    "prefetch_progress",
+    "tile_cache_evicted",
+    "tile_failed",
+];
+
+/// Why a slide was closed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EvictionReason {
+    /// Least recently used slide, to make room in the slide cache
+    Capacity,
+
+    /// Least recently used slide of a tenant at its slide quota
+    TenantQuota,
+
+    /// Closed to stay within the memory budget
+    Memory,
+
+    /// Removed through the API or a cache reload
+    Invalidated,
+
+    /// The file changed in storage and will be reopened
+    Replaced,
+}
+
+/// Event published by the server.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerEvent {
+    /// A slide started opening
+    SlideOpening { slide_id: String },
+
+    /// A slide finished opening and is cached
+    SlideOpened {
+        slide_id: String,
+        format: String,
+        levels: usize,
+        elapsed_ms: u64,
+    },
+
+    /// A slide failed to open
+    SlideFailed { slide_id: String, error: String },
+
+    /// A slide was closed and its cached data dropped
+    SlideEvicted {
+        slide_id: String,
+        reason: EvictionReason,
+    },
+
+    /// Tiles of a slide read into the block cache while warming it
+    PrefetchProgress {
+        slide_id: String,
+        completed: usize,
+        total: usize,
+    },
+
+    /// Tiles dropped from the tile cache to make room for new ones
+    TileCacheEvicted { tiles: usize },
+
+    /// A tile failed to render
+    TileFailed {
+        slide_id: String,
+        level: usize,
+        x: u32,
+        y: u32,
+        error: String,
+    },
+}
+
+impl ServerEvent {
+    /// Slide the event is about, if any.
+    pub fn slide_id(&self) -> Option<&str> {
+        match self {
+            ServerEvent::SlideOpening { slide_id }
+            | ServerEvent::SlideOpened { slide_id, .. }
+            | ServerEvent::SlideFailed { slide_id, .. }
+            | ServerEvent::SlideEvicted { slide_id, .. }
+            | ServerEvent::PrefetchProgress { slide_id, .. }
+            | ServerEvent::TileFailed { slide_id, .. } => Some(slide_id),
+            ServerEvent::TileCacheEvicted { .. } => None,
+        }
+    }
+
+    /// Event type, as serialized in the `type` field.
+    pub const fn kind(&self) -> &'static str {
+        match self {
+            ServerEvent::SlideOpening { .. } => "slide_opening",
+            ServerEvent::SlideOpened { .. } => "slide_opened",
+            ServerEvent::SlideFailed { .. } => "slide_failed",
+            ServerEvent::SlideEvicted { .. } => "slide_evicted",
+            ServerEvent::PrefetchProgress { .. } => "prefetch_progress",
+            ServerEvent::TileCacheEvicted { .. } => "tile_cache_evicted",
+            ServerEvent::TileFailed { .. } => "tile_failed",
+        }
+    }
+}
+
+/// Broadcast channel for [`ServerEvent`]s.
+///
+/// Clones share the same channel.
+#[derive(Debug, Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<ServerEvent>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventBus {
+    /// Create a bus buffering [`EVENT_BUS_CAPACITY`] events per subscriber.
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(EVENT_BUS_CAPACITY);
+        Self { sender }
+    }
+
+    /// Publish an event to current subscribers.
+    ///
+    /// The event is only built if there are subscribers.
+    pub fn publish(&self, event: impl FnOnce() -> ServerEvent) {
+        if self.sender.receiver_count() > 0 {
+            let _ = self.sender.send(event());
+        }
+    }
+
+    /// Receive events published from now on.
+    pub fn subscribe(&self) -> broadcast::Receiver<ServerEvent> {
+        self.sender.subscribe()
+    }
+
+    /// Number of current subscribers.
+    pub fn subscriber_count(&self) -> usize {
+        self.sender.receiver_count()
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_publish_reaches_subscribers() {
+        let bus = EventBus::new();
+
+        // Without subscribers the event isn't built
+        bus.publish(|| unreachable!());
+
+        let mut events = bus.subscribe();
+        bus.clone().publish(|| ServerEvent::SlideOpening {
+            slide_id: "a.svs".to_string(),
+        });
+        let event = events.recv().await.unwrap();
+        assert_eq!(event.slide_id(), Some("a.svs"));
+        assert_eq!(event.kind(), "slide_opening");
+        assert_eq!(bus.subscriber_count(), 1);
+    }
+
+    #[test]
+    fn test_event_json() {
+        let event = ServerEvent::SlideEvicted {
+            slide_id: "a.svs".to_string(),
+            reason: EvictionReason::TenantQuota,
+        };
+        assert_eq!(
+            serde_json::to_string(&event).unwrap(),
+            r#"{"type":"slide_evicted","slide_id":"a.svs","reason":"tenant_quota"}"#
+        );
+
+        let event = ServerEvent::TileCacheEvicted { tiles: 3 };
+        assert_eq!(event.slide_id(), None);
+        assert_eq!(serde_json::to_value(&event).unwrap()["type"], event.kind());
+        assert!(EVENT_KINDS.contains(&event.kind()));
+    }
+}
@@ -0,0 +1,672 @@
+//! Multi-endpoint failover for slide sources.
+//!
+//! [`FailoverSlideSource`] wraps a primary source and any number of secondary
+//! sources holding the same objects, typically cross-region replicated S3
+//! buckets. Reads go to the first healthy endpoint in priority order; when an
+//! endpoint keeps failing it is marked down and traffic moves to the next one,
+//! so a regional outage costs latency rather than availability.
+//!
+//! # Health Tracking
+//!
+//! Endpoints are checked passively and actively:
+//!
+//! - Every read records success or failure. After
+//!   [`DEFAULT_FAILURE_THRESHOLD`] consecutive transient failures (S3 or
+//!   connection errors) the endpoint is marked down for the cooldown period.
+//! - [`FailoverSlideSource::probe`] issues a cheap listing request against
+//!   every endpoint. The server runs it periodically so a recovered primary is
+//!   picked up again without waiting for live traffic.
+//!
+//! Once the cooldown expires, a down endpoint is tried again; a single
+//! success marks it healthy. If every endpoint is down, all are tried in
+//! priority order rather than failing outright.
+//!
+//! `NotFound` and range errors are not failures of the endpoint and are
+//! returned as-is without trying other endpoints.
+//!
+//! Writes are not failed over: they always go to the primary and rely on
+//! bucket replication.
+
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use tokio::sync::OnceCell;
+use tracing::warn;
+
+use crate::error::{ErrorCode, IoError};
+use crate::io::RangeReader;
+
+use super::{SlideListResult, SlideSource};
+
+// =============================================================================
+// Configuration
+// =============================================================================
+
+/// Consecutive failures before an endpoint is marked down.
+pub const DEFAULT_FAILURE_THRESHOLD: u32 = 3;
+
+/// How long a down endpoint is skipped before it is tried again.
+pub const DEFAULT_FAILOVER_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// Whether an error indicates an unhealthy endpoint rather than a bad request.
+fn is_transient(err: &IoError) -> bool {
+    err.is_retryable() && !matches!(err, IoError::ObjectChanged(_))
+}
+
+// =============================================================================
+// Endpoint Health
+// =============================================================================
+
+/// Health state of a single endpoint.
+#[derive(Debug)]
+struct EndpointHealth {
+    /// Human-readable label for logs and stats
+    label: String,
+
+    /// Consecutive transient failures
+    consecutive_failures: AtomicU32,
+
+    /// Time (ms since `FailoverInner::epoch`) until which the endpoint is down
+    down_until_ms: AtomicU64,
+
+    /// Total failed requests
+    failures: AtomicU64,
+}
+
+impl EndpointHealth {
+    fn new(label: String) -> Self {
+        Self {
+            label,
+            consecutive_failures: AtomicU32::new(0),
+            down_until_ms: AtomicU64::new(0),
+            failures: AtomicU64::new(0),
+        }
+    }
+}
+
+/// Snapshot of an endpoint's health.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EndpointStatus {
+    /// Endpoint label (e.g. "primary", "us-west-2")
+    pub label: String,
+
+    /// Whether the endpoint is currently used for reads
+    pub healthy: bool,
+
+    /// Consecutive transient failures
+    pub consecutive_failures: u32,
+
+    /// Total failed requests
+    pub failures: u64,
+}
+
+// =============================================================================
+// FailoverSlideSource
+// =============================================================================
+
+struct FailoverInner<S> {
+    /// Sources in priority order; index 0 is the primary
+    sources: Vec<S>,
+    health: Vec<EndpointHealth>,
+    failure_threshold: u32,
+    cooldown: Duration,
+    epoch: Instant,
+}
+
+impl<S> FailoverInner<S> {
+    fn now_ms(&self) -> u64 {
+        self.epoch.elapsed().as_millis() as u64
+    }
+
+    fn is_healthy(&self, index: usize) -> bool {
+        self.health[index].down_until_ms.load(Ordering::Relaxed) <= self.now_ms()
+    }
+
+    /// Endpoint indices in the order they should be tried.
+    ///
+    /// Healthy endpoints come first in priority order, followed by down
+    /// endpoints as a last resort.
+    fn order(&self) -> Vec<usize> {
+        let (mut healthy, down): (Vec<usize>, Vec<usize>) =
+            (0..self.sources.len()).partition(|&i| self.is_healthy(i));
+        healthy.extend(down);
+        healthy
+    }
+
+    fn record_success(&self, index: usize) {
+        let health = &self.health[index];
+        if health.consecutive_failures.swap(0, Ordering::Relaxed) > 0 {
+            health.down_until_ms.store(0, Ordering::Relaxed);
+        }
+    }
+
+    fn record_failure(&self, index: usize, err: &IoError) {
+        let health = &self.health[index];
+        health.failures.fetch_add(1, Ordering::Relaxed);
+        let failures = health.consecutive_failures.fetch_add(1, Ordering::Relaxed) + 1;
+
+        if failures >= self.failure_threshold {
+            let until = self.now_ms() + self.cooldown.as_millis() as u64;
+            let previous = health.down_until_ms.swap(until, Ordering::Relaxed);
+            if previous <= self.now_ms() {
+                warn!(
+                    endpoint = %health.label,
+                    failures,
+                    error = %err,
+                    "Storage endpoint marked down"
+                );
+            }
+        }
+    }
+}
+
+/// A slide source that fails over between replicated endpoints.
+///
+/// # Example
+///
+/// ```ignore
+/// use wsi_streamer::slide::{FailoverSlideSource, S3SlideSource};
+///
+/// let primary = S3SlideSource::new(us_east_client, "slides".to_string());
+/// let secondary = S3SlideSource::new(us_west_client, "slides-replica".to_string());
+///
+/// let source = FailoverSlideSource::new(primary, "us-east-1")
+///     .with_secondary(secondary, "us-west-2");
+/// ```
+pub struct FailoverSlideSource<S> {
+    inner: Arc<FailoverInner<S>>,
+}
+
+impl<S> Clone for FailoverSlideSource<S> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl<S: SlideSource> FailoverSlideSource<S> {
+    /// Create a failover source with only a primary endpoint.
+    pub fn new(primary: S, label: impl Into<String>) -> Self {
+        Self {
+            inner: Arc::new(FailoverInner {
+                sources: vec![primary],
+                health: vec![EndpointHealth::new(label.into())],
+                failure_threshold: DEFAULT_FAILURE_THRESHOLD,
+                cooldown: DEFAULT_FAILOVER_COOLDOWN,
+                epoch: Instant::now(),
+            }),
+        }
+    }
+
+    /// Add a secondary endpoint, tried after all previously added endpoints.
+    ///
+    /// Must be called before the source is cloned or shared.
+    pub fn with_secondary(mut self, source: S, label: impl Into<String>) -> Self {
+        let inner = Arc::get_mut(&mut self.inner)
+            .expect("with_secondary must be called before the source is shared");
+        inner.sources.push(source);
+        inner.health.push(EndpointHealth::new(label.into()));
+        self
+    }
+
+    /// Set the number of consecutive failures before an endpoint is marked down.
+    ///
+    /// Must be called before the source is cloned or shared.
+    pub fn with_failure_threshold(mut self, threshold: u32) -> Self {
+        let inner = Arc::get_mut(&mut self.inner)
+            .expect("with_failure_threshold must be called before the source is shared");
+        inner.failure_threshold = threshold.max(1);
+        self
+    }
+
+    /// Set how long a down endpoint is skipped.
+    ///
+    /// Must be called before the source is cloned or shared.
+    pub fn with_cooldown(mut self, cooldown: Duration) -> Self {
+        let inner = Arc::get_mut(&mut self.inner)
+            .expect("with_cooldown must be called before the source is shared");
+        inner.cooldown = cooldown;
+        self
+    }
+
+    /// Number of configured endpoints, including the primary.
+    pub fn endpoint_count(&self) -> usize {
+        self.inner.sources.len()
+    }
+
+    /// Health of every endpoint, in priority order.
+    pub fn endpoint_status(&self) -> Vec<EndpointStatus> {
+        self.inner
+            .health
+            .iter()
+            .enumerate()
+            .map(|(i, health)| EndpointStatus {
+                label: health.label.clone(),
+                healthy: self.inner.is_healthy(i),
+                consecutive_failures: health.consecutive_failures.load(Ordering::Relaxed),
+                failures: health.failures.load(Ordering::Relaxed),
+            })
+            .collect()
+    }
+
+    /// Actively check every endpoint with a minimal listing request.
+    ///
+    /// Results feed the same health state as live reads.
+    pub async fn probe(&self) {
+        for (index, source) in self.inner.sources.iter().enumerate() {
+            match source.list_slides(1, None, None, None).await {
+                Ok(_) => self.inner.record_success(index),
+                Err(e) if is_transient(&e) => self.inner.record_failure(index, &e),
+                Err(_) => {}
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl<S: SlideSource + 'static> SlideSource for FailoverSlideSource<S> {
+    type Reader = FailoverReader<S>;
+
+    async fn create_reader(&self, slide_id: &str) -> Result<Self::Reader, IoError> {
+        let readers: Arc<[OnceCell<S::Reader>]> = (0..self.inner.sources.len())
+            .map(|_| OnceCell::new())
+            .collect();
+
+        let mut last_err = None;
+        for index in self.inner.order() {
+            match self.inner.sources[index].create_reader(slide_id).await {
+                Ok(reader) => {
+                    self.inner.record_success(index);
+                    let size = reader.size();
+                    let identifier = reader.identifier().to_string();
+                    let _ = readers[index].set(reader);
+                    return Ok(FailoverReader {
+                        inner: Arc::clone(&self.inner),
+                        slide_id: slide_id.to_string(),
+                        readers,
+                        size,
+                        identifier,
+                    });
+                }
+                Err(e) if is_transient(&e) => {
+                    self.inner.record_failure(index, &e);
+                    last_err = Some(e);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        Err(last_err.unwrap_or_else(|| IoError::Connection("no storage endpoints".to_string())))
+    }
+
+    async fn list_slides(
+        &self,
+        limit: u32,
+        cursor: Option<&str>,
+        prefix: Option<&str>,
+        delimiter: Option<&str>,
+    ) -> Result<SlideListResult, IoError> {
+        let mut last_err = None;
+        for index in self.inner.order() {
+            match self.inner.sources[index]
+                .list_slides(limit, cursor, prefix, delimiter)
+                .await
+            {
+                Ok(result) => {
+                    self.inner.record_success(index);
+                    return Ok(result);
+                }
+                Err(e) if is_transient(&e) => {
+                    self.inner.record_failure(index, &e);
+                    last_err = Some(e);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        Err(last_err.unwrap_or_else(|| IoError::Connection("no storage endpoints".to_string())))
+    }
+
+    /// Ready while any endpoint is reachable, since reads fail over to it.
+    async fn check_ready(&self) -> Result<(), IoError> {
+        let mut last_err = None;
+        for index in self.inner.order() {
+            match self.inner.sources[index].check_ready().await {
+                Ok(()) => {
+                    self.inner.record_success(index);
+                    return Ok(());
+                }
+                Err(e) => {
+                    if is_transient(&e) {
+                        self.inner.record_failure(index, &e);
+                    }
+                    last_err = Some(e);
+                }
+            }
+        }
+
+        Err(last_err.unwrap_or_else(|| IoError::Connection("no storage endpoints".to_string())))
+    }
+
+    /// Writes always go to the primary; replication carries them to the
+    /// secondaries.
+    async fn put_object(&self, key: &str, data: Bytes, content_type: &str) -> Result<(), IoError> {
+        self.inner.sources[0]
+            .put_object(key, data, content_type)
+            .await
+    }
+}
+
+// =============================================================================
+// FailoverReader
+// =============================================================================
+
+/// Range reader that retries failed reads against other endpoints.
+///
+/// Readers for secondary endpoints are created lazily, on the first read
+/// that needs them.
+pub struct FailoverReader<S: SlideSource> {
+    inner: Arc<FailoverInner<S>>,
+    slide_id: String,
+    readers: Arc<[OnceCell<S::Reader>]>,
+    size: u64,
+    identifier: String,
+}
+
+impl<S: SlideSource> FailoverReader<S> {
+    /// Get or create the reader for an endpoint.
+    async fn reader(&self, index: usize) -> Result<&S::Reader, IoError> {
+        let reader = self.readers[index]
+            .get_or_try_init(|| self.inner.sources[index].create_reader(&self.slide_id))
+            .await?;
+
+        if reader.size() != self.size {
+            // A replica that disagrees on size is out of sync; don't read from it
+            return Err(IoError::S3(format!(
+                "{} has size {} on endpoint {}, expected {}",
+                self.slide_id,
+                reader.size(),
+                self.inner.health[index].label,
+                self.size
+            )));
+        }
+
+        Ok(reader)
+    }
+}
+
+#[async_trait]
+impl<S: SlideSource + 'static> RangeReader for FailoverReader<S> {
+    async fn read_exact_at(&self, offset: u64, len: usize) -> Result<Bytes, IoError> {
+        let mut last_err = None;
+        for index in self.inner.order() {
+            let result = match self.reader(index).await {
+                Ok(reader) => reader.read_exact_at(offset, len).await,
+                Err(e) => Err(e),
+            };
+
+            match result {
+                Ok(data) => {
+                    self.inner.record_success(index);
+                    return Ok(data);
+                }
+                Err(e) if is_transient(&e) => {
+                    self.inner.record_failure(index, &e);
+                    last_err = Some(e);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        Err(last_err.unwrap_or_else(|| IoError::Connection("no storage endpoints".to_string())))
+    }
+
+    fn size(&self) -> u64 {
+        self.size
+    }
+
+    fn identifier(&self) -> &str {
+        &self.identifier
+    }
+
+    /// Taken from the first endpoint reader created; replicas of an object
+    /// normally share its ETag.
+    fn etag(&self) -> Option<&str> {
+        self.readers
+            .iter()
+            .find_map(|reader| reader.get())
+            .and_then(|reader| reader.etag())
+    }
+
+    fn last_modified(&self) -> Option<std::time::SystemTime> {
+        self.readers
+            .iter()
+            .find_map(|reader| reader.get())
+            .and_then(|reader| reader.last_modified())
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, AtomicUsize};
+
+    /// Source whose reads fail while `down` is set.
+    #[derive(Clone)]
+    struct FlakySource {
+        name: &'static str,
+        data: Bytes,
+        down: Arc<AtomicBool>,
+        reads: Arc<AtomicUsize>,
+    }
+
+    impl FlakySource {
+        fn new(name: &'static str, data: &[u8]) -> Self {
+            Self {
+                name,
+                data: Bytes::copy_from_slice(data),
+                down: Arc::new(AtomicBool::new(false)),
+                reads: Arc::new(AtomicUsize::new(0)),
+            }
+        }
+
+        fn set_down(&self, down: bool) {
+            self.down.store(down, Ordering::SeqCst);
+        }
+
+        fn reads(&self) -> usize {
+            self.reads.load(Ordering::SeqCst)
+        }
+    }
+
+    struct FlakyReader {
+        source: FlakySource,
+        identifier: String,
+    }
+
+    #[async_trait]
+    impl RangeReader for FlakyReader {
+        async fn read_exact_at(&self, offset: u64, len: usize) -> Result<Bytes, IoError> {
+            self.source.reads.fetch_add(1, Ordering::SeqCst);
+            if self.source.down.load(Ordering::SeqCst) {
+                return Err(IoError::Connection(format!(
+                    "{} unreachable",
+                    self.source.name
+                )));
+            }
+            let start = offset as usize;
+            Ok(self.source.data.slice(start..start + len))
+        }
+
+        fn size(&self) -> u64 {
+            self.source.data.len() as u64
+        }
+
+        fn identifier(&self) -> &str {
+            &self.identifier
+        }
+    }
+
+    #[async_trait]
+    impl SlideSource for FlakySource {
+        type Reader = FlakyReader;
+
+        async fn create_reader(&self, slide_id: &str) -> Result<Self::Reader, IoError> {
+            if self.down.load(Ordering::SeqCst) {
+                return Err(IoError::S3(format!("{} unavailable", self.name)));
+            }
+            if slide_id == "missing.svs" {
+                return Err(IoError::NotFound(slide_id.to_string()));
+            }
+            Ok(FlakyReader {
+                source: self.clone(),
+                identifier: format!("{}://{}", self.name, slide_id),
+            })
+        }
+
+        async fn list_slides(
+            &self,
+            _limit: u32,
+            _cursor: Option<&str>,
+            _prefix: Option<&str>,
+            _delimiter: Option<&str>,
+        ) -> Result<SlideListResult, IoError> {
+            if self.down.load(Ordering::SeqCst) {
+                return Err(IoError::S3(format!("{} unavailable", self.name)));
+            }
+            Ok(SlideListResult {
+                slides: vec![format!("{}.svs", self.name)],
+                folders: vec![],
+                next_cursor: None,
+            })
+        }
+    }
+
+    fn sources() -> (FlakySource, FlakySource, FailoverSlideSource<FlakySource>) {
+        let primary = FlakySource::new("primary", b"0123456789");
+        let secondary = FlakySource::new("secondary", b"0123456789");
+        let source = FailoverSlideSource::new(primary.clone(), "primary")
+            .with_secondary(secondary.clone(), "secondary")
+            .with_failure_threshold(2)
+            .with_cooldown(Duration::from_secs(60));
+        (primary, secondary, source)
+    }
+
+    #[tokio::test]
+    async fn test_reads_use_primary_when_healthy() {
+        let (primary, secondary, source) = sources();
+        let reader = source.create_reader("slide.svs").await.unwrap();
+
+        assert_eq!(&reader.read_exact_at(2, 3).await.unwrap()[..], b"234");
+        assert_eq!(reader.identifier(), "primary://slide.svs");
+        assert_eq!(primary.reads(), 1);
+        assert_eq!(secondary.reads(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_read_fails_over_to_secondary() {
+        let (primary, secondary, source) = sources();
+        let reader = source.create_reader("slide.svs").await.unwrap();
+
+        primary.set_down(true);
+        assert_eq!(&reader.read_exact_at(0, 4).await.unwrap()[..], b"0123");
+        assert_eq!(secondary.reads(), 1);
+        assert_eq!(source.endpoint_status()[0].consecutive_failures, 1);
+    }
+
+    #[tokio::test]
+    async fn test_endpoint_marked_down_after_threshold() {
+        let (primary, secondary, source) = sources();
+        let reader = source.create_reader("slide.svs").await.unwrap();
+
+        primary.set_down(true);
+        reader.read_exact_at(0, 1).await.unwrap();
+        reader.read_exact_at(0, 1).await.unwrap();
+        assert!(!source.endpoint_status()[0].healthy);
+
+        // Primary is skipped while down, even once it recovers
+        primary.set_down(false);
+        let primary_reads = primary.reads();
+        reader.read_exact_at(0, 1).await.unwrap();
+        assert_eq!(primary.reads(), primary_reads);
+        assert_eq!(secondary.reads(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_create_reader_fails_over() {
+        let (primary, _secondary, source) = sources();
+        primary.set_down(true);
+
+        let reader = source.create_reader("slide.svs").await.unwrap();
+        assert_eq!(reader.identifier(), "secondary://slide.svs");
+    }
+
+    #[tokio::test]
+    async fn test_not_found_is_not_failed_over() {
+        let (_primary, _secondary, source) = sources();
+        let result = source.create_reader("missing.svs").await;
+
+        assert!(matches!(result, Err(IoError::NotFound(_))));
+        assert_eq!(source.endpoint_status()[0].failures, 0);
+    }
+
+    #[tokio::test]
+    async fn test_all_endpoints_down() {
+        let (primary, secondary, source) = sources();
+        let reader = source.create_reader("slide.svs").await.unwrap();
+
+        primary.set_down(true);
+        secondary.set_down(true);
+        assert!(matches!(
+            reader.read_exact_at(0, 1).await,
+            Err(IoError::Connection(_)) | Err(IoError::S3(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_probe_restores_recovered_endpoint() {
+        let (primary, _secondary, source) = sources();
+
+        primary.set_down(true);
+        source.probe().await;
+        source.probe().await;
+        assert!(!source.endpoint_status()[0].healthy);
+
+        primary.set_down(false);
+        source.probe().await;
+        let status = source.endpoint_status();
+        assert!(status[0].healthy);
+        assert_eq!(status[0].consecutive_failures, 0);
+        assert_eq!(status[0].failures, 2);
+    }
+
+    #[tokio::test]
+    async fn test_list_slides_fails_over() {
+        let (primary, _secondary, source) = sources();
+        primary.set_down(true);
+
+        let result = source.list_slides(10, None, None, None).await.unwrap();
+        assert_eq!(result.slides, vec!["secondary.svs".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_size_mismatch_is_not_served() {
+        let primary = FlakySource::new("primary", b"0123456789");
+        let stale = FlakySource::new("stale", b"01234");
+        let source = FailoverSlideSource::new(primary.clone(), "primary")
+            .with_secondary(stale.clone(), "stale");
+        let reader = source.create_reader("slide.svs").await.unwrap();
+
+        primary.set_down(true);
+        assert!(reader.read_exact_at(0, 4).await.is_err());
+        assert_eq!(stale.reads(), 0);
+    }
+}
@@ -0,0 +1,170 @@
+//! Google Cloud Storage slide source implementation.
+//!
+//! This module provides an implementation of `SlideSource` that creates
+//! `GcsRangeReader` instances for slides stored in a GCS bucket.
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use reqwest::header;
+
+use crate::error::IoError;
+use crate::io::{send_request, validate_key, GcsClient, GcsRangeReader};
+
+use super::s3_source::is_slide_file;
+use super::{SlideListResult, SlideSource};
+
+/// GCS-backed implementation of `SlideSource`.
+///
+/// Creates `GcsRangeReader` instances for slides stored in a Google Cloud
+/// Storage bucket. The slide ID is used as the object name within the bucket.
+///
+/// # Example
+///
+/// ```ignore
+/// use wsi_streamer::io::{GcsClient, GcsCredentials};
+/// use wsi_streamer::slide::GcsSlideSource;
+///
+/// let client = GcsClient::new(None, GcsCredentials::Metadata)?;
+/// let source = GcsSlideSource::new(client, "my-bucket".to_string());
+///
+/// // The slide ID "slides/example.svs" becomes the object name
+/// let reader = source.create_reader("slides/example.svs").await?;
+/// ```
+#[derive(Clone)]
+pub struct GcsSlideSource {
+    client: GcsClient,
+    bucket: String,
+}
+
+impl GcsSlideSource {
+    /// Create a new GcsSlideSource for the given bucket.
+    pub fn new(client: GcsClient, bucket: String) -> Self {
+        Self { client, bucket }
+    }
+
+    /// Get the bucket name.
+    pub fn bucket(&self) -> &str {
+        &self.bucket
+    }
+}
+
+#[async_trait]
+impl SlideSource for GcsSlideSource {
+    type Reader = GcsRangeReader;
+
+    async fn create_reader(&self, slide_id: &str) -> Result<Self::Reader, IoError> {
+        GcsRangeReader::new(self.client.clone(), &self.bucket, slide_id).await
+    }
+
+    async fn list_slides(
+        &self,
+        limit: u32,
+        cursor: Option<&str>,
+        prefix: Option<&str>,
+        delimiter: Option<&str>,
+    ) -> Result<SlideListResult, IoError> {
+        let mut query = vec![
+            ("maxResults", limit.to_string()),
+            ("fields", "items(name),prefixes,nextPageToken".to_string()),
+        ];
+        if let Some(token) = cursor {
+            query.push(("pageToken", token.to_string()));
+        }
+        if let Some(prefix) = prefix {
+            query.push(("prefix", prefix.to_string()));
+        }
+        if let Some(delimiter) = delimiter {
+            query.push(("delimiter", delimiter.to_string()));
+        }
+
+        let url = self.client.list_url(&self.bucket);
+        let request = self
+            .client
+            .authorize(
+                self.client
+                    .request(reqwest::Method::GET, &url)
+                    .query(&query),
+            )
+            .await?;
+        let identifier = format!("gs://{}", self.bucket);
+        let response = send_request(request, &identifier).await?;
+        let body = response
+            .bytes()
+            .await
+            .map_err(|e| IoError::Connection(e.to_string()))?;
+        parse_listing(&body)
+    }
+
+    async fn put_object(&self, key: &str, data: Bytes, content_type: &str) -> Result<(), IoError> {
+        validate_key(key)?;
+        let url = self.client.upload_url(&self.bucket);
+        let request = self
+            .client
+            .request(reqwest::Method::POST, &url)
+            .query(&[("uploadType", "media"), ("name", key)])
+            .header(header::CONTENT_TYPE, content_type)
+            .body(data);
+        let request = self.client.authorize(request).await?;
+        send_request(request, &format!("gs://{}/{}", self.bucket, key)).await?;
+
+        Ok(())
+    }
+}
+
+/// Parse a JSON API object listing.
+fn parse_listing(body: &[u8]) -> Result<SlideListResult, IoError> {
+    let listing: serde_json::Value = serde_json::from_slice(body)
+        .map_err(|e| IoError::S3(format!("Invalid GCS listing: {}", e)))?;
+
+    let slides = listing["items"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|item| item["name"].as_str())
+        .filter(|name| is_slide_file(name))
+        .map(str::to_string)
+        .collect();
+    let folders = listing["prefixes"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|prefix| prefix.as_str())
+        .map(str::to_string)
+        .collect();
+
+    Ok(SlideListResult {
+        slides,
+        folders,
+        next_cursor: listing["nextPageToken"].as_str().map(str::to_string),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_listing() {
+        let body = br#"{
+            "items": [{"name": "a/s1.svs"}, {"name": "a/notes.txt"}, {"name": "s2.tiff"}],
+            "nextPageToken": "CgVzMi50aWZm"
+        }"#;
+        let listing = parse_listing(body).unwrap();
+        assert_eq!(listing.slides, vec!["a/s1.svs", "s2.tiff"]);
+        assert!(listing.folders.is_empty());
+        assert_eq!(listing.next_cursor.as_deref(), Some("CgVzMi50aWZm"));
+
+        // Delimited listings report folders as prefixes
+        let body = br#"{"items": [{"name": "s2.tiff"}], "prefixes": ["a/", "b/"]}"#;
+        let listing = parse_listing(body).unwrap();
+        assert_eq!(listing.slides, vec!["s2.tiff"]);
+        assert_eq!(listing.folders, vec!["a/", "b/"]);
+
+        // Empty buckets have no items
+        let listing = parse_listing(b"{}").unwrap();
+        assert!(listing.slides.is_empty());
+        assert!(listing.next_cursor.is_none());
+
+        assert!(parse_listing(b"<html>").is_err());
+    }
+}
@@ -0,0 +1,182 @@
+//! Per-slide custom response headers.
+//!
+//! A slide `path/to/slide.svs` may have a sidecar
+//! `path/to/slide.svs.headers.json` stored next to it, holding a flat JSON
+//! object of header names to values:
+//!
+//! ```json
+//! {
+//!   "X-Data-Classification": "restricted",
+//!   "X-Consent-Id": "c-20931"
+//! }
+//! ```
+//!
+//! The headers are added to every tile and metadata response for the slide,
+//! so downstream governance tooling can label traffic without knowing the
+//! slide inventory.
+//!
+//! Headers the server manages itself (framing, caching, CORS and cookies)
+//! can't be set from a sidecar.
+
+use http::header::{HeaderMap, HeaderName, HeaderValue};
+
+// =============================================================================
+// Configuration
+// =============================================================================
+
+/// Suffix appended to a slide ID to find its headers sidecar.
+pub const HEADERS_SUFFIX: &str = ".headers.json";
+
+/// Maximum number of headers a sidecar may set.
+pub const MAX_SLIDE_HEADERS: usize = 32;
+
+/// Headers the server sets itself, which a sidecar may not override.
+const RESERVED_HEADERS: &[&str] = &[
+    "cache-control",
+    "connection",
+    "content-encoding",
+    "content-length",
+    "content-range",
+    "content-type",
+    "etag",
+    "keep-alive",
+    "last-modified",
+    "set-cookie",
+    "trailer",
+    "transfer-encoding",
+    "upgrade",
+    "vary",
+];
+
+/// Get the headers sidecar key for a slide.
+pub fn headers_key(slide_id: &str) -> String {
+    format!("{}{}", slide_id, HEADERS_SUFFIX)
+}
+
+// =============================================================================
+// Slide Headers
+// =============================================================================
+
+/// Custom response headers for a slide.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SlideHeaders {
+    headers: HeaderMap,
+}
+
+impl SlideHeaders {
+    /// Parse a headers sidecar.
+    ///
+    /// # Errors
+    /// Returns a description of the problem if the sidecar is not a JSON
+    /// object of strings, names an invalid or reserved header, or sets more
+    /// than [`MAX_SLIDE_HEADERS`] headers.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let entries: serde_json::Map<String, serde_json::Value> =
+            serde_json::from_str(text).map_err(|e| e.to_string())?;
+        if entries.len() > MAX_SLIDE_HEADERS {
+            return Err(format!(
+                "{} headers exceed the limit of {}",
+                entries.len(),
+                MAX_SLIDE_HEADERS
+            ));
+        }
+
+        let mut headers = HeaderMap::new();
+        for (name, value) in entries {
+            let header_name = HeaderName::from_bytes(name.as_bytes())
+                .map_err(|_| format!("invalid header name {:?}", name))?;
+            if is_reserved(&header_name) {
+                return Err(format!("header {} is managed by the server", header_name));
+            }
+            let value = value
+                .as_str()
+                .ok_or_else(|| format!("value of {} is not a string", name))?;
+            let header_value = HeaderValue::from_str(value)
+                .map_err(|_| format!("invalid value for header {}", name))?;
+            headers.insert(header_name, header_value);
+        }
+
+        Ok(Self { headers })
+    }
+
+    /// Whether the sidecar sets no headers.
+    pub fn is_empty(&self) -> bool {
+        self.headers.is_empty()
+    }
+
+    /// Number of headers set.
+    pub fn len(&self) -> usize {
+        self.headers.len()
+    }
+
+    /// Iterate over the headers.
+    pub fn iter(&self) -> impl Iterator<Item = (&HeaderName, &HeaderValue)> {
+        self.headers.iter()
+    }
+
+    /// Add the headers to a response's headers, replacing existing values.
+    pub fn apply(&self, headers: &mut HeaderMap) {
+        for (name, value) in &self.headers {
+            headers.insert(name.clone(), value.clone());
+        }
+    }
+}
+
+fn is_reserved(name: &HeaderName) -> bool {
+    let name = name.as_str();
+    RESERVED_HEADERS.contains(&name) || name.starts_with("access-control-")
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_headers_key() {
+        assert_eq!(
+            headers_key("path/to/slide.svs"),
+            "path/to/slide.svs.headers.json"
+        );
+    }
+
+    #[test]
+    fn test_parse_headers() {
+        let headers = SlideHeaders::parse(
+            r#"{"X-Data-Classification": "restricted", "X-Consent-Id": "c-20931"}"#,
+        )
+        .unwrap();
+        assert_eq!(headers.len(), 2);
+
+        let mut response = HeaderMap::new();
+        response.insert("x-data-classification", HeaderValue::from_static("public"));
+        headers.apply(&mut response);
+        assert_eq!(response["x-data-classification"], "restricted");
+        assert_eq!(response["x-consent-id"], "c-20931");
+
+        assert!(SlideHeaders::parse("{}").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_headers() {
+        assert!(SlideHeaders::parse("not json").is_err());
+        assert!(SlideHeaders::parse(r#"["X-A", "b"]"#).is_err());
+        assert!(SlideHeaders::parse(r#"{"X-Count": 3}"#).is_err());
+        assert!(SlideHeaders::parse(r#"{"bad name": "x"}"#).is_err());
+        assert!(SlideHeaders::parse(r#"{"X-Label": "line\nbreak"}"#).is_err());
+
+        // Server-managed headers
+        let err = SlideHeaders::parse(r#"{"Content-Type": "text/html"}"#).unwrap_err();
+        assert!(err.contains("managed by the server"));
+        assert!(SlideHeaders::parse(r#"{"Access-Control-Allow-Origin": "*"}"#).is_err());
+
+        let many: serde_json::Map<String, serde_json::Value> = (0..=MAX_SLIDE_HEADERS)
+            .map(|i| (format!("X-H{}", i), serde_json::Value::from("v")))
+            .collect();
+        let text = serde_json::to_string(&many).unwrap();
+        assert!(SlideHeaders::parse(&text).is_err());
+    }
+}
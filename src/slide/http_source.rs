@@ -0,0 +1,95 @@
+//! HTTP(S) URL slide source implementation.
+//!
+//! This module provides an implementation of `SlideSource` that creates
+//! `HttpRangeReader` instances for slides served by an existing web server,
+//! so tiles can be federated from static file servers without copying the
+//! slides into a bucket.
+
+use async_trait::async_trait;
+
+use crate::error::IoError;
+use crate::io::{encode_key, validate_key, HttpClient, HttpRangeReader};
+
+use super::SlideSource;
+
+/// HTTP-backed implementation of `SlideSource`.
+///
+/// The slide ID is appended to a base URL, so `slides/example.svs` under
+/// `https://files.example.org/wsi` is read from
+/// `https://files.example.org/wsi/slides/example.svs`. The server must
+/// support range requests.
+///
+/// Web servers have no standard listing API, so `list_slides` returns an
+/// empty list; slides are opened by ID.
+///
+/// # Example
+///
+/// ```ignore
+/// use wsi_streamer::io::HttpClient;
+/// use wsi_streamer::slide::HttpSlideSource;
+///
+/// let source = HttpSlideSource::new(HttpClient::new()?, "https://files.example.org/wsi");
+/// let reader = source.create_reader("slides/example.svs").await?;
+/// ```
+#[derive(Clone)]
+pub struct HttpSlideSource {
+    client: HttpClient,
+    base_url: String,
+}
+
+impl HttpSlideSource {
+    /// Create a new HttpSlideSource serving slides under `base_url`.
+    pub fn new(client: HttpClient, base_url: impl Into<String>) -> Self {
+        let base_url = base_url.into().trim_end_matches('/').to_string();
+        Self { client, base_url }
+    }
+
+    /// Get the base URL.
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// URL of a slide.
+    ///
+    /// Fails for slide IDs that could resolve outside the base URL.
+    pub fn slide_url(&self, slide_id: &str) -> Result<String, IoError> {
+        validate_key(slide_id)?;
+        Ok(format!("{}/{}", self.base_url, encode_key(slide_id)))
+    }
+}
+
+#[async_trait]
+impl SlideSource for HttpSlideSource {
+    type Reader = HttpRangeReader;
+
+    async fn create_reader(&self, slide_id: &str) -> Result<Self::Reader, IoError> {
+        HttpRangeReader::new(self.client.clone(), &self.slide_url(slide_id)?).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slide_url() {
+        let source = HttpSlideSource::new(HttpClient::new().unwrap(), "https://files.test/wsi/");
+        assert_eq!(source.base_url(), "https://files.test/wsi");
+        assert_eq!(
+            source.slide_url("case 1/s1.svs").unwrap(),
+            "https://files.test/wsi/case%201/s1.svs"
+        );
+    }
+
+    #[test]
+    fn test_slide_url_rejects_traversal() {
+        let source = HttpSlideSource::new(HttpClient::new().unwrap(), "https://files.test/wsi");
+        for slide_id in ["../secret.svs", "case/../../secret.svs", "./s1.svs"] {
+            assert!(
+                matches!(source.slide_url(slide_id), Err(IoError::InvalidKey(_))),
+                "{}",
+                slide_id
+            );
+        }
+    }
+}
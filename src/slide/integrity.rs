@@ -0,0 +1,560 @@
+//! Checksum manifest verification.
+//!
+//! A slide `path/to/slide.svs` may have a manifest `path/to/slide.svs.sha256`
+//! stored next to it. The manifest is compatible with `sha256sum -c` and can
+//! carry per-block digests, so bit rot can be detected without reading the
+//! whole file:
+//!
+//! ```text
+//! 9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08  slide.svs
+//! size 1073741824
+//! block_size 1048576
+//! block 0 2c26b46b68ffc68ff99b453c1d30413413422d706483bfa0f98a5e886266e7ae
+//! block 1 fcde2b2edba56bf408601fb721fe9b5c338d10ee429ea04fae5511b68fbf8fb9
+//! ```
+//!
+//! Every line is optional; `wsi-streamer manifest` writes all of them.
+//!
+//! # Verification Modes
+//!
+//! - **Sampled**: checks the file size and the blocks covering the header
+//!   and a few tiles. Cheap enough to run when a slide is opened.
+//! - **Full**: reads the whole file and checks every digest.
+
+use std::collections::BTreeMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::error::IoError;
+use crate::io::RangeReader;
+
+// =============================================================================
+// Configuration
+// =============================================================================
+
+/// Suffix appended to a slide ID to find its manifest.
+pub const MANIFEST_SUFFIX: &str = ".sha256";
+
+/// Default block size for generated manifests (1 MiB).
+pub const DEFAULT_MANIFEST_BLOCK_SIZE: u64 = 1024 * 1024;
+
+/// Read size used by full verification.
+const FULL_VERIFY_CHUNK_SIZE: u64 = 8 * 1024 * 1024;
+
+/// Get the manifest key for a slide.
+pub fn manifest_key(slide_id: &str) -> String {
+    format!("{}{}", slide_id, MANIFEST_SUFFIX)
+}
+
+// =============================================================================
+// Manifest
+// =============================================================================
+
+/// SHA-256 digest.
+pub type Sha256Digest = [u8; 32];
+
+/// Parsed checksum manifest.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChecksumManifest {
+    /// Digest of the whole file
+    pub file_digest: Option<Sha256Digest>,
+
+    /// File size in bytes
+    pub size: Option<u64>,
+
+    /// Size of each digested block in bytes
+    pub block_size: Option<u64>,
+
+    /// Block digests by block index (may be sparse)
+    pub blocks: BTreeMap<u64, Sha256Digest>,
+}
+
+impl ChecksumManifest {
+    /// Parse a manifest.
+    ///
+    /// Blank lines and lines starting with `#` are ignored.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut manifest = ChecksumManifest::default();
+
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let invalid = |what: &str| format!("line {}: {}", number + 1, what);
+
+            match fields.as_slice() {
+                ["size", size] => {
+                    manifest.size = Some(size.parse().map_err(|_| invalid("invalid size"))?);
+                }
+                ["block_size", size] => {
+                    let size: u64 = size.parse().map_err(|_| invalid("invalid block size"))?;
+                    if size == 0 {
+                        return Err(invalid("block size must be positive"));
+                    }
+                    manifest.block_size = Some(size);
+                }
+                ["block", index, digest] => {
+                    let index = index.parse().map_err(|_| invalid("invalid block index"))?;
+                    let digest = parse_digest(digest).ok_or_else(|| invalid("invalid digest"))?;
+                    manifest.blocks.insert(index, digest);
+                }
+                // sha256sum output: "<digest>  <name>" or "<digest> *<name>"
+                [digest, ..] if digest.len() == 64 => {
+                    let digest = parse_digest(digest).ok_or_else(|| invalid("invalid digest"))?;
+                    manifest.file_digest = Some(digest);
+                }
+                _ => return Err(invalid("unrecognized entry")),
+            }
+        }
+
+        if !manifest.blocks.is_empty() && manifest.block_size.is_none() {
+            return Err("block digests require a block_size line".to_string());
+        }
+        Ok(manifest)
+    }
+
+    /// Render the manifest.
+    ///
+    /// # Arguments
+    /// * `file_name` - Name written next to the whole-file digest
+    pub fn to_text(&self, file_name: &str) -> String {
+        let mut text = String::new();
+        if let Some(digest) = self.file_digest {
+            text.push_str(&format!("{}  {}\n", hex::encode(digest), file_name));
+        }
+        if let Some(size) = self.size {
+            text.push_str(&format!("size {}\n", size));
+        }
+        if let Some(block_size) = self.block_size {
+            text.push_str(&format!("block_size {}\n", block_size));
+        }
+        for (index, digest) in &self.blocks {
+            text.push_str(&format!("block {} {}\n", index, hex::encode(digest)));
+        }
+        text
+    }
+
+    /// Indices of blocks overlapping `[offset, offset + len)`.
+    fn blocks_covering(&self, offset: u64, len: u64) -> Vec<u64> {
+        match self.block_size {
+            Some(block_size) if len > 0 => {
+                (offset / block_size..=(offset + len - 1) / block_size).collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+fn parse_digest(text: &str) -> Option<Sha256Digest> {
+    hex::decode(text).ok()?.try_into().ok()
+}
+
+/// Incrementally builds a manifest from file contents.
+pub struct ManifestBuilder {
+    block_size: u64,
+    file: Sha256,
+    block: Sha256,
+    block_filled: u64,
+    blocks: BTreeMap<u64, Sha256Digest>,
+    size: u64,
+}
+
+impl ManifestBuilder {
+    /// Create a builder digesting blocks of `block_size` bytes (at least 1).
+    pub fn new(block_size: u64) -> Self {
+        Self {
+            block_size: block_size.max(1),
+            file: Sha256::new(),
+            block: Sha256::new(),
+            block_filled: 0,
+            blocks: BTreeMap::new(),
+            size: 0,
+        }
+    }
+
+    /// Feed the next bytes of the file.
+    pub fn update(&mut self, mut data: &[u8]) {
+        self.file.update(data);
+        self.size += data.len() as u64;
+
+        while !data.is_empty() {
+            let take = ((self.block_size - self.block_filled) as usize).min(data.len());
+            self.block.update(&data[..take]);
+            self.block_filled += take as u64;
+            data = &data[take..];
+
+            if self.block_filled == self.block_size {
+                self.finish_block();
+            }
+        }
+    }
+
+    fn finish_block(&mut self) {
+        let index = self.blocks.len() as u64;
+        let digest = std::mem::take(&mut self.block).finalize();
+        self.blocks.insert(index, digest.into());
+        self.block_filled = 0;
+    }
+
+    /// Finish the manifest.
+    pub fn finish(mut self) -> ChecksumManifest {
+        if self.block_filled > 0 {
+            self.finish_block();
+        }
+        ChecksumManifest {
+            file_digest: Some(self.file.finalize().into()),
+            size: Some(self.size),
+            block_size: Some(self.block_size),
+            blocks: self.blocks,
+        }
+    }
+}
+
+// =============================================================================
+// Verification
+// =============================================================================
+
+/// How much of the file to verify.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VerifyMode {
+    /// File size plus the blocks covering the header and a few tiles
+    #[default]
+    Sampled,
+
+    /// Every byte of the file
+    Full,
+}
+
+/// Outcome of a verification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IntegrityStatus {
+    /// The slide has no manifest
+    NoManifest,
+
+    /// The manifest is invalid or has nothing this mode can check
+    Unverified,
+
+    /// Every checked digest matched
+    Verified,
+
+    /// At least one digest or the file size did not match
+    Failed,
+}
+
+/// Result of verifying a slide against its manifest.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IntegrityReport {
+    /// Outcome
+    pub status: IntegrityStatus,
+
+    /// Verification mode
+    pub mode: VerifyMode,
+
+    /// When the check ran (Unix epoch seconds)
+    pub checked_at: u64,
+
+    /// Bytes read and digested
+    pub bytes_checked: u64,
+
+    /// Block digests compared
+    pub blocks_checked: usize,
+
+    /// Digests that did not match (e.g. `block 12`, `file`, `size`)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub mismatches: Vec<String>,
+
+    /// Why the slide could not be verified
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+impl IntegrityReport {
+    fn new(status: IntegrityStatus, mode: VerifyMode) -> Self {
+        Self {
+            status,
+            mode,
+            checked_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            bytes_checked: 0,
+            blocks_checked: 0,
+            mismatches: Vec::new(),
+            message: None,
+        }
+    }
+
+    /// Report for a slide without a manifest.
+    pub fn no_manifest(mode: VerifyMode) -> Self {
+        Self::new(IntegrityStatus::NoManifest, mode)
+    }
+
+    /// Report for a slide that could not be verified.
+    pub fn unverified(mode: VerifyMode, message: impl Into<String>) -> Self {
+        let mut report = Self::new(IntegrityStatus::Unverified, mode);
+        report.message = Some(message.into());
+        report
+    }
+
+    /// Set the status from the mismatches found.
+    fn conclude(mut self) -> Self {
+        self.status = if self.mismatches.is_empty() {
+            IntegrityStatus::Verified
+        } else {
+            IntegrityStatus::Failed
+        };
+        self
+    }
+}
+
+/// Verify the blocks covering sampled byte ranges.
+///
+/// # Arguments
+/// * `reader` - Reader for the slide
+/// * `manifest` - The slide's manifest
+/// * `ranges` - `(offset, length)` ranges to check, e.g. the header and a
+///   few tiles; ranges without block digests are skipped
+pub async fn verify_sampled<R: RangeReader>(
+    reader: &R,
+    manifest: &ChecksumManifest,
+    ranges: &[(u64, u64)],
+) -> Result<IntegrityReport, IoError> {
+    let mode = VerifyMode::Sampled;
+    let size = reader.size();
+    let mut report = IntegrityReport::new(IntegrityStatus::Verified, mode);
+
+    if let Some(expected) = manifest.size {
+        if expected != size {
+            report.mismatches.push("size".to_string());
+            return Ok(report.conclude());
+        }
+    }
+
+    let mut indices: Vec<u64> = ranges
+        .iter()
+        .flat_map(|&(offset, len)| manifest.blocks_covering(offset, len))
+        .filter(|index| manifest.blocks.contains_key(index))
+        .collect();
+    indices.sort_unstable();
+    indices.dedup();
+
+    if indices.is_empty() && manifest.size.is_none() {
+        return Ok(IntegrityReport::unverified(
+            mode,
+            "manifest has no size or block digests; use full verification",
+        ));
+    }
+
+    // blocks_covering only returns indices when block_size is set
+    let block_size = manifest.block_size.unwrap_or(1);
+    for index in indices {
+        let start = index * block_size;
+        if start >= size {
+            report.mismatches.push(format!("block {}", index));
+            continue;
+        }
+        let len = block_size.min(size - start) as usize;
+        let data = reader.read_exact_at(start, len).await?;
+        let digest: Sha256Digest = Sha256::digest(&data).into();
+
+        report.bytes_checked += len as u64;
+        report.blocks_checked += 1;
+        if manifest.blocks.get(&index) != Some(&digest) {
+            report.mismatches.push(format!("block {}", index));
+        }
+    }
+
+    Ok(report.conclude())
+}
+
+/// Verify every byte of a slide.
+pub async fn verify_full<R: RangeReader>(
+    reader: &R,
+    manifest: &ChecksumManifest,
+) -> Result<IntegrityReport, IoError> {
+    let mode = VerifyMode::Full;
+    if manifest.file_digest.is_none() && manifest.blocks.is_empty() && manifest.size.is_none() {
+        return Ok(IntegrityReport::unverified(mode, "manifest is empty"));
+    }
+
+    let size = reader.size();
+    let mut builder = ManifestBuilder::new(manifest.block_size.unwrap_or(FULL_VERIFY_CHUNK_SIZE));
+    let mut offset = 0;
+    while offset < size {
+        let len = FULL_VERIFY_CHUNK_SIZE.min(size - offset) as usize;
+        builder.update(&reader.read_exact_at(offset, len).await?);
+        offset += len as u64;
+    }
+    let actual = builder.finish();
+
+    let mut report = IntegrityReport::new(IntegrityStatus::Verified, mode);
+    report.bytes_checked = size;
+
+    if manifest.size.is_some_and(|expected| expected != size) {
+        report.mismatches.push("size".to_string());
+    }
+    if manifest
+        .file_digest
+        .is_some_and(|expected| Some(expected) != actual.file_digest)
+    {
+        report.mismatches.push("file".to_string());
+    }
+    for (index, expected) in &manifest.blocks {
+        report.blocks_checked += 1;
+        if actual.blocks.get(index) != Some(expected) {
+            report.mismatches.push(format!("block {}", index));
+        }
+    }
+
+    Ok(report.conclude())
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use bytes::Bytes;
+
+    struct MemoryReader(Bytes);
+
+    #[async_trait]
+    impl RangeReader for MemoryReader {
+        async fn read_exact_at(&self, offset: u64, len: usize) -> Result<Bytes, IoError> {
+            let start = offset as usize;
+            Ok(self.0.slice(start..start + len))
+        }
+
+        fn size(&self) -> u64 {
+            self.0.len() as u64
+        }
+
+        fn identifier(&self) -> &str {
+            "memory://slide"
+        }
+    }
+
+    fn data() -> Vec<u8> {
+        (0..10_000u32).map(|i| (i % 251) as u8).collect()
+    }
+
+    fn manifest_for(data: &[u8], block_size: u64) -> ChecksumManifest {
+        let mut builder = ManifestBuilder::new(block_size);
+        // Uneven chunks exercise block boundaries
+        for chunk in data.chunks(777) {
+            builder.update(chunk);
+        }
+        builder.finish()
+    }
+
+    #[test]
+    fn test_builder_matches_direct_digests() {
+        let data = data();
+        let manifest = manifest_for(&data, 4096);
+
+        assert_eq!(manifest.size, Some(10_000));
+        assert_eq!(manifest.blocks.len(), 3);
+        assert_eq!(manifest.file_digest, Some(Sha256::digest(&data).into()));
+        let last: Sha256Digest = Sha256::digest(&data[8192..]).into();
+        assert_eq!(manifest.blocks[&2], last);
+    }
+
+    #[test]
+    fn test_manifest_round_trip() {
+        let manifest = manifest_for(&data(), 4096);
+        let text = manifest.to_text("slide.svs");
+        assert!(text.lines().next().unwrap().ends_with("  slide.svs"));
+        assert_eq!(ChecksumManifest::parse(&text).unwrap(), manifest);
+    }
+
+    #[test]
+    fn test_parse_sha256sum_output() {
+        let digest = "ab".repeat(32);
+        let manifest =
+            ChecksumManifest::parse(&format!("# comment\n{} *slide.svs\n", digest)).unwrap();
+        assert_eq!(manifest.file_digest, Some([0xab; 32]));
+        assert!(manifest.blocks.is_empty());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(ChecksumManifest::parse("size lots").is_err());
+        assert!(ChecksumManifest::parse("block_size 0").is_err());
+        assert!(ChecksumManifest::parse(&format!("block 0 {}", "ab".repeat(32))).is_err());
+        assert!(ChecksumManifest::parse("hello world").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_verify_sampled() {
+        let data = data();
+        let manifest = manifest_for(&data, 1024);
+        let reader = MemoryReader(Bytes::from(data.clone()));
+
+        let report = verify_sampled(&reader, &manifest, &[(0, 1), (5000, 100)])
+            .await
+            .unwrap();
+        assert_eq!(report.status, IntegrityStatus::Verified);
+        assert_eq!(report.blocks_checked, 2);
+
+        // Flip a byte inside a sampled block
+        let mut corrupted = data.clone();
+        corrupted[5050] ^= 0xff;
+        let reader = MemoryReader(Bytes::from(corrupted));
+        let report = verify_sampled(&reader, &manifest, &[(0, 1), (5000, 100)])
+            .await
+            .unwrap();
+        assert_eq!(report.status, IntegrityStatus::Failed);
+        assert_eq!(report.mismatches, vec!["block 4".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_verify_sampled_detects_truncation() {
+        let data = data();
+        let manifest = manifest_for(&data, 1024);
+        let reader = MemoryReader(Bytes::from(data[..9000].to_vec()));
+
+        let report = verify_sampled(&reader, &manifest, &[(0, 1)]).await.unwrap();
+        assert_eq!(report.status, IntegrityStatus::Failed);
+        assert_eq!(report.mismatches, vec!["size".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_verify_sampled_without_blocks() {
+        let digest = hex::encode(Sha256::digest(data()));
+        let manifest = ChecksumManifest::parse(&format!("{}  slide.svs", digest)).unwrap();
+        let reader = MemoryReader(Bytes::from(data()));
+
+        let report = verify_sampled(&reader, &manifest, &[(0, 1)]).await.unwrap();
+        assert_eq!(report.status, IntegrityStatus::Unverified);
+
+        let report = verify_full(&reader, &manifest).await.unwrap();
+        assert_eq!(report.status, IntegrityStatus::Verified);
+        assert_eq!(report.bytes_checked, 10_000);
+    }
+
+    #[tokio::test]
+    async fn test_verify_full_detects_corruption() {
+        let data = data();
+        let manifest = manifest_for(&data, 1024);
+        let mut corrupted = data;
+        corrupted[9999] ^= 0x01;
+        let reader = MemoryReader(Bytes::from(corrupted));
+
+        let report = verify_full(&reader, &manifest).await.unwrap();
+        assert_eq!(report.status, IntegrityStatus::Failed);
+        assert_eq!(
+            report.mismatches,
+            vec!["file".to_string(), "block 9".to_string()]
+        );
+        assert_eq!(report.blocks_checked, 10);
+    }
+}
@@ -0,0 +1,300 @@
+//! Tissue masks.
+//!
+//! Most of a whole slide image is bare glass. A [`TissueMask`] marks which
+//! parts of a slide hold tissue at low resolution, so clients and the tile
+//! pipeline can skip background regions without reading them: read-ahead
+//! (see [`CachedSlide::read_tile`]) leaves background tiles out of a scan
+//! once a slide's mask is known.
+//!
+//! The mask is computed from an overview image of the whole slide.
+//! Background on brightfield slides is bright and unsaturated while stained
+//! tissue is colored, so pixels are split on their saturation with an Otsu
+//! threshold, which adapts to the staining of each slide. Dense tissue that
+//! stays below background brightness counts too, so unstained and grayscale
+//! slides still get a mask. Near-black scanner padding is never tissue.
+//!
+//! The mask is the slide's only tissue detector: per-tile tissue fractions
+//! for sampling (see [`TissueMap`]) are measured on it too.
+//!
+//! [`TissueMap`]: crate::tile::TissueMap
+//! [`CachedSlide::read_tile`]: super::CachedSlide::read_tile
+
+use std::io::Cursor;
+
+use image::{GrayImage, ImageFormat, Luma, RgbImage};
+
+use super::reader::LevelInfo;
+
+/// Lowest saturation threshold, so faint scanner noise on an all-background
+/// overview is not split into tissue and background.
+const MIN_SATURATION_THRESHOLD: u8 = 15;
+
+/// Pixels darker than this on every channel are treated as scanner padding.
+const PADDING_BRIGHTNESS: u8 = 15;
+
+/// Pixels with a channel darker than this are tissue, whatever their
+/// saturation.
+const BACKGROUND_BRIGHTNESS: u8 = 220;
+
+/// Low-resolution tissue mask of a slide.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TissueMask {
+    /// 255 where the overview shows tissue, 0 elsewhere
+    mask: GrayImage,
+
+    /// Saturation above which pixels count as tissue
+    threshold: u8,
+}
+
+impl TissueMask {
+    /// Compute the mask of a slide from an overview image covering it.
+    pub fn from_image(overview: &RgbImage) -> Self {
+        let mut histogram = [0u64; 256];
+        for pixel in overview.pixels() {
+            if let Some(saturation) = saturation(pixel.0) {
+                histogram[saturation as usize] += 1;
+            }
+        }
+        let threshold = otsu_threshold(&histogram).max(MIN_SATURATION_THRESHOLD);
+
+        let mask = GrayImage::from_fn(overview.width(), overview.height(), |x, y| {
+            let tissue = is_tissue(overview.get_pixel(x, y).0, threshold);
+            Luma([if tissue { 255 } else { 0 }])
+        });
+        Self { mask, threshold }
+    }
+
+    /// Mask width in pixels.
+    pub fn width(&self) -> u32 {
+        self.mask.width()
+    }
+
+    /// Mask height in pixels.
+    pub fn height(&self) -> u32 {
+        self.mask.height()
+    }
+
+    /// Saturation above which pixels count as tissue.
+    pub fn threshold(&self) -> u8 {
+        self.threshold
+    }
+
+    /// Fraction of the slide covered by tissue (0.0-1.0).
+    pub fn tissue_fraction(&self) -> f64 {
+        let total = self.mask.as_raw().len();
+        if total == 0 {
+            return 0.0;
+        }
+        let tissue = self.mask.as_raw().iter().filter(|&&v| v > 0).count();
+        tissue as f64 / total as f64
+    }
+
+    /// Bytes held by the mask.
+    pub fn size_bytes(&self) -> usize {
+        self.mask.as_raw().len()
+    }
+
+    /// Whether any tissue lies under a tile of a level.
+    ///
+    /// The tile is projected onto the mask; tiles smaller than one mask
+    /// pixel use the pixel they fall in.
+    pub fn tile_has_tissue(&self, info: &LevelInfo, tile_x: u32, tile_y: u32) -> bool {
+        if tile_x >= info.tiles_x || tile_y >= info.tiles_y {
+            return false;
+        }
+        let (x0, x1) = project_span(tile_x, info.tile_width, info.width, self.width());
+        let (y0, y1) = project_span(tile_y, info.tile_height, info.height, self.height());
+        (y0..y1).any(|y| (x0..x1).any(|x| self.mask.get_pixel(x, y).0[0] > 0))
+    }
+
+    /// Fraction of a tile of a level covered by tissue (0.0-1.0).
+    ///
+    /// The tile is projected onto the mask like in
+    /// [`tile_has_tissue`](Self::tile_has_tissue); out-of-bounds tiles have
+    /// no tissue.
+    pub fn tile_fraction(&self, info: &LevelInfo, tile_x: u32, tile_y: u32) -> f32 {
+        if tile_x >= info.tiles_x || tile_y >= info.tiles_y {
+            return 0.0;
+        }
+        let (x0, x1) = project_span(tile_x, info.tile_width, info.width, self.width());
+        let (y0, y1) = project_span(tile_y, info.tile_height, info.height, self.height());
+        let total = (x1 - x0) as u64 * (y1 - y0) as u64;
+        if total == 0 {
+            return 0.0;
+        }
+        let tissue = (y0..y1)
+            .flat_map(|y| (x0..x1).map(move |x| (x, y)))
+            .filter(|&(x, y)| self.mask.get_pixel(x, y).0[0] > 0)
+            .count();
+        tissue as f32 / total as f32
+    }
+
+    /// Encode the mask as a grayscale PNG: white for tissue, black for
+    /// background.
+    pub fn to_png(&self) -> Vec<u8> {
+        let mut png = Cursor::new(Vec::new());
+        self.mask
+            .write_to(&mut png, ImageFormat::Png)
+            .expect("PNG encoding to memory cannot fail");
+        png.into_inner()
+    }
+}
+
+/// Saturation (channel spread) of a pixel, or `None` for scanner padding.
+fn saturation([r, g, b]: [u8; 3]) -> Option<u8> {
+    let max = r.max(g).max(b);
+    if max < PADDING_BRIGHTNESS {
+        return None;
+    }
+    Some(max - r.min(g).min(b))
+}
+
+/// Classify a pixel as tissue given the slide's saturation threshold.
+fn is_tissue([r, g, b]: [u8; 3], threshold: u8) -> bool {
+    match saturation([r, g, b]) {
+        Some(saturation) => saturation > threshold || r.min(g).min(b) < BACKGROUND_BRIGHTNESS,
+        None => false,
+    }
+}
+
+/// Otsu's threshold of a histogram: the value splitting it into two classes
+/// with the largest between-class variance.
+fn otsu_threshold(histogram: &[u64; 256]) -> u8 {
+    let total: u64 = histogram.iter().sum();
+    if total == 0 {
+        return 0;
+    }
+    let sum: f64 = histogram
+        .iter()
+        .enumerate()
+        .map(|(value, &count)| value as f64 * count as f64)
+        .sum();
+
+    let (mut best, mut best_variance) = (0u8, 0.0f64);
+    let (mut below, mut below_sum) = (0u64, 0.0f64);
+    for (value, &count) in histogram.iter().enumerate() {
+        below += count;
+        below_sum += value as f64 * count as f64;
+        let above = total - below;
+        if below == 0 || above == 0 {
+            continue;
+        }
+        let mean_below = below_sum / below as f64;
+        let mean_above = (sum - below_sum) / above as f64;
+        let variance = below as f64 * above as f64 * (mean_below - mean_above).powi(2);
+        if variance > best_variance {
+            best = value as u8;
+            best_variance = variance;
+        }
+    }
+    best
+}
+
+/// Project a tile's pixel span onto the mask.
+///
+/// Always returns at least one pixel so tiny tiles still get a reading.
+fn project_span(index: u32, tile_size: u32, level_size: u32, max: u32) -> (u32, u32) {
+    if max == 0 {
+        return (0, 0);
+    }
+    let scale = max as f64 / level_size.max(1) as f64;
+    let start = index as u64 * tile_size as u64;
+    let end = (start + tile_size as u64).min(level_size as u64);
+
+    let p0 = ((start as f64 * scale).floor() as u32).min(max - 1);
+    let p1 = ((end as f64 * scale).ceil() as u32).clamp(p0 + 1, max);
+    (p0, p1)
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgb;
+
+    fn level_info(width: u32, height: u32, tile: u32) -> LevelInfo {
+        LevelInfo {
+            width,
+            height,
+            tile_width: tile,
+            tile_height: tile,
+            tiles_x: width.div_ceil(tile),
+            tiles_y: height.div_ceil(tile),
+            downsample: 1.0,
+        }
+    }
+
+    /// Tissue in the left half, off-white glass on the right and a black
+    /// padding strip along the bottom.
+    fn overview() -> RgbImage {
+        RgbImage::from_fn(100, 50, |x, y| {
+            if y >= 45 {
+                Rgb([0, 0, 0])
+            } else if x < 50 {
+                Rgb([200, 120 + (x % 7) as u8, 190])
+            } else {
+                Rgb([240, 238 + (x % 3) as u8, 236])
+            }
+        })
+    }
+
+    #[test]
+    fn test_otsu_threshold() {
+        let mut histogram = [0u64; 256];
+        histogram[4] = 500;
+        histogram[5] = 300;
+        histogram[70] = 200;
+        histogram[80] = 100;
+        let threshold = otsu_threshold(&histogram);
+        assert!((5..70).contains(&threshold), "threshold {}", threshold);
+        assert_eq!(otsu_threshold(&[0; 256]), 0);
+    }
+
+    #[test]
+    fn test_mask_separates_tissue() {
+        let mask = TissueMask::from_image(&overview());
+        assert_eq!((mask.width(), mask.height()), (100, 50));
+        assert!(mask.threshold() >= MIN_SATURATION_THRESHOLD);
+        assert!((mask.tissue_fraction() - 0.45).abs() < 1e-9);
+
+        let info = level_info(1000, 500, 250);
+        assert!(mask.tile_has_tissue(&info, 0, 0));
+        assert!(mask.tile_has_tissue(&info, 1, 1));
+        assert!(!mask.tile_has_tissue(&info, 2, 0));
+        assert!(!mask.tile_has_tissue(&info, 3, 1));
+        assert!(!mask.tile_has_tissue(&info, 4, 0));
+    }
+
+    #[test]
+    fn test_blank_slide_has_no_tissue() {
+        let blank = RgbImage::from_fn(64, 64, |x, y| Rgb([245, 245 - ((x + y) % 4) as u8, 243]));
+        let mask = TissueMask::from_image(&blank);
+        assert_eq!(mask.tissue_fraction(), 0.0);
+    }
+
+    #[test]
+    fn test_unstained_tissue_below_background_brightness() {
+        let gray = RgbImage::from_fn(64, 64, |x, _| {
+            let value = if x < 32 { 120 } else { 245 };
+            Rgb([value, value, value])
+        });
+        let mask = TissueMask::from_image(&gray);
+        assert!((mask.tissue_fraction() - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_mask_png() {
+        let mask = TissueMask::from_image(&overview());
+        let png = mask.to_png();
+        let decoded = image::load_from_memory_with_format(&png, ImageFormat::Png)
+            .unwrap()
+            .into_luma8();
+        assert_eq!(decoded.dimensions(), (100, 50));
+        assert_eq!(decoded.get_pixel(10, 10).0, [255]);
+        assert_eq!(decoded.get_pixel(90, 10).0, [0]);
+        assert_eq!(decoded.get_pixel(10, 48).0, [0]);
+    }
+}
@@ -0,0 +1,287 @@
+//! In-memory slide source.
+//!
+//! Holds a fixed set of slides fully in memory and serves them without a
+//! storage backend, for demos, workshops and air-gapped review stations.
+//! Slides are loaded once at startup, from local files or from another
+//! [`SlideSource`] (see [`MemorySlideSource::preload_from`]); afterwards the
+//! source is read-only.
+
+use std::collections::BTreeMap;
+use std::ops::Bound;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+
+use crate::error::IoError;
+use crate::io::MemoryRangeReader;
+
+use super::{SlideListResult, SlideSource};
+
+/// Slide source serving slides held in memory.
+///
+/// # Example
+///
+/// ```ignore
+/// use wsi_streamer::slide::{MemorySlideSource, SlideRegistry};
+///
+/// let source = MemorySlideSource::new()
+///     .with_slide("demo.svs", std::fs::read("demo.svs")?);
+/// let registry = SlideRegistry::new(source);
+/// ```
+#[derive(Clone, Default)]
+pub struct MemorySlideSource {
+    slides: BTreeMap<String, Bytes>,
+}
+
+impl MemorySlideSource {
+    /// Create an empty source.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a slide.
+    pub fn with_slide(mut self, slide_id: impl Into<String>, data: impl Into<Bytes>) -> Self {
+        self.insert(slide_id, data);
+        self
+    }
+
+    /// Add or replace a slide.
+    pub fn insert(&mut self, slide_id: impl Into<String>, data: impl Into<Bytes>) {
+        self.slides.insert(slide_id.into(), data.into());
+    }
+
+    /// Copy slides from another source into memory.
+    ///
+    /// Each slide is read in full; the other source is not used afterwards.
+    pub async fn preload_from<S: SlideSource>(
+        mut self,
+        source: &S,
+        slide_ids: &[String],
+    ) -> Result<Self, IoError> {
+        for slide_id in slide_ids {
+            let data = source.get_object(slide_id).await?;
+            self.insert(slide_id.clone(), data);
+        }
+        Ok(self)
+    }
+
+    /// Number of slides held.
+    pub fn len(&self) -> usize {
+        self.slides.len()
+    }
+
+    /// Whether no slides are held.
+    pub fn is_empty(&self) -> bool {
+        self.slides.is_empty()
+    }
+
+    /// Total size of the slides held, in bytes.
+    pub fn total_bytes(&self) -> u64 {
+        self.slides.values().map(|data| data.len() as u64).sum()
+    }
+}
+
+#[async_trait]
+impl SlideSource for MemorySlideSource {
+    type Reader = MemoryRangeReader;
+
+    async fn create_reader(&self, slide_id: &str) -> Result<Self::Reader, IoError> {
+        self.slides
+            .get(slide_id)
+            .map(|data| MemoryRangeReader::new(slide_id, data.clone()))
+            .ok_or_else(|| IoError::NotFound(format!("memory://{}", slide_id)))
+    }
+
+    async fn list_slides(
+        &self,
+        limit: u32,
+        cursor: Option<&str>,
+        prefix: Option<&str>,
+        delimiter: Option<&str>,
+    ) -> Result<SlideListResult, IoError> {
+        let prefix = prefix.unwrap_or("");
+
+        // The cursor is the last slide ID or folder of the previous page;
+        // slides inside a folder returned as the cursor are skipped
+        let start = match cursor {
+            Some(cursor) => Bound::Excluded(cursor),
+            None => Bound::Unbounded,
+        };
+        let mut entries = self
+            .slides
+            .range::<str, _>((start, Bound::Unbounded))
+            .map(|(slide_id, _)| slide_id.as_str())
+            .filter(|slide_id| slide_id.starts_with(prefix))
+            .filter(|slide_id| match (cursor, delimiter) {
+                (Some(cursor), Some(delimiter)) if cursor.ends_with(delimiter) => {
+                    !slide_id.starts_with(cursor)
+                }
+                _ => true,
+            })
+            .map(|slide_id| {
+                // Roll keys with a delimiter past the prefix up into a folder
+                let folder = delimiter.and_then(|delimiter| {
+                    slide_id[prefix.len()..]
+                        .find(delimiter)
+                        .map(|end| &slide_id[..prefix.len() + end + delimiter.len()])
+                });
+                match folder {
+                    Some(folder) => (folder, true),
+                    None => (slide_id, false),
+                }
+            });
+
+        let mut slides = Vec::new();
+        let mut folders: Vec<String> = Vec::new();
+        let mut last = None;
+        while slides.len() + folders.len() < limit as usize {
+            let Some((entry, is_folder)) = entries.next() else {
+                break;
+            };
+            if is_folder {
+                if folders.last().map(String::as_str) == Some(entry) {
+                    continue;
+                }
+                folders.push(entry.to_string());
+            } else {
+                slides.push(entry.to_string());
+            }
+            last = Some(entry.to_string());
+        }
+
+        // Entries of the last folder don't start a new page
+        let next_cursor = match entries.find(|&(entry, _)| Some(entry) != last.as_deref()) {
+            Some(_) => last,
+            None => None,
+        };
+
+        Ok(SlideListResult {
+            slides,
+            folders,
+            next_cursor,
+        })
+    }
+
+    async fn get_object(&self, key: &str) -> Result<Bytes, IoError> {
+        self.slides
+            .get(key)
+            .cloned()
+            .ok_or_else(|| IoError::NotFound(format!("memory://{}", key)))
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::RangeReader;
+
+    #[tokio::test]
+    async fn test_memory_source_reads_slides() {
+        let source = MemorySlideSource::new().with_slide("a.svs", vec![1u8, 2, 3]);
+        assert_eq!(source.len(), 1);
+        assert_eq!(source.total_bytes(), 3);
+
+        let reader = source.create_reader("a.svs").await.unwrap();
+        assert_eq!(reader.size(), 3);
+        assert!(matches!(
+            source.create_reader("missing.svs").await,
+            Err(IoError::NotFound(_))
+        ));
+
+        // Writes are rejected: the source is read-only
+        assert!(source
+            .put_object("a.svs", Bytes::new(), "application/octet-stream")
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_memory_source_list_pagination() {
+        let source = MemorySlideSource::new()
+            .with_slide("b/2.svs", vec![0u8])
+            .with_slide("a.svs", vec![0u8])
+            .with_slide("b/1.svs", vec![0u8]);
+
+        let page = source.list_slides(2, None, None, None).await.unwrap();
+        assert_eq!(page.slides, vec!["a.svs", "b/1.svs"]);
+        assert_eq!(page.next_cursor.as_deref(), Some("b/1.svs"));
+
+        let page = source
+            .list_slides(2, page.next_cursor.as_deref(), None, None)
+            .await
+            .unwrap();
+        assert_eq!(page.slides, vec!["b/2.svs"]);
+        assert_eq!(page.next_cursor, None);
+
+        let page = source
+            .list_slides(10, None, Some("b/"), None)
+            .await
+            .unwrap();
+        assert_eq!(page.slides, vec!["b/1.svs", "b/2.svs"]);
+    }
+
+    #[tokio::test]
+    async fn test_memory_source_list_folders() {
+        let source = MemorySlideSource::new()
+            .with_slide("a/1.svs", vec![0u8])
+            .with_slide("a/x/2.svs", vec![0u8])
+            .with_slide("b/1.svs", vec![0u8])
+            .with_slide("c.svs", vec![0u8])
+            .with_slide("d/1.svs", vec![0u8]);
+
+        let page = source.list_slides(10, None, None, Some("/")).await.unwrap();
+        assert_eq!(page.folders, vec!["a/", "b/", "d/"]);
+        assert_eq!(page.slides, vec!["c.svs"]);
+        assert_eq!(page.next_cursor, None);
+
+        let page = source
+            .list_slides(10, None, Some("a/"), Some("/"))
+            .await
+            .unwrap();
+        assert_eq!(page.folders, vec!["a/x/"]);
+        assert_eq!(page.slides, vec!["a/1.svs"]);
+
+        // Folders count toward the page size and can end a page
+        let page = source.list_slides(1, None, None, Some("/")).await.unwrap();
+        assert_eq!(page.folders, vec!["a/"]);
+        assert_eq!(page.next_cursor.as_deref(), Some("a/"));
+        let page = source
+            .list_slides(2, page.next_cursor.as_deref(), None, Some("/"))
+            .await
+            .unwrap();
+        assert_eq!(page.folders, vec!["b/"]);
+        assert_eq!(page.slides, vec!["c.svs"]);
+        let page = source
+            .list_slides(2, page.next_cursor.as_deref(), None, Some("/"))
+            .await
+            .unwrap();
+        assert_eq!(page.folders, vec!["d/"]);
+        assert_eq!(page.next_cursor, None);
+    }
+
+    #[tokio::test]
+    async fn test_preload_from_other_source() {
+        let origin = MemorySlideSource::new()
+            .with_slide("a.svs", vec![1u8])
+            .with_slide("b.svs", vec![2u8]);
+
+        let source = MemorySlideSource::new()
+            .preload_from(&origin, &["b.svs".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(
+            source.get_object("b.svs").await.unwrap(),
+            Bytes::from(vec![2u8])
+        );
+        assert!(source.get_object("a.svs").await.is_err());
+
+        assert!(MemorySlideSource::new()
+            .preload_from(&origin, &["missing.svs".to_string()])
+            .await
+            .is_err());
+    }
+}
@@ -53,10 +53,83 @@
 //! let tile = slide.read_tile(0, 0, 0).await?;
 //! ```
 
+#[cfg(feature = "azure")]
+mod azure_source;
+mod conformance;
+mod events;
+mod failover;
+#[cfg(feature = "gcs")]
+mod gcs_source;
+mod headers;
+#[cfg(feature = "http")]
+mod http_source;
+mod integrity;
+mod mask;
+mod memory_source;
+mod negative;
+mod properties;
+mod quarantine;
+mod readahead;
 mod reader;
 mod registry;
 mod s3_source;
+mod sparse;
+mod temperature;
+mod tenants;
+mod tiles;
+mod views;
+mod warm;
 
+#[cfg(feature = "azure")]
+pub use azure_source::AzureBlobSlideSource;
+pub use conformance::{
+    ConformanceCache, ConformanceStatus, SlideConformance, MAX_CONFORMANCE_ENTRIES,
+};
+pub use events::{EventBus, EvictionReason, ServerEvent, EVENT_BUS_CAPACITY, EVENT_KINDS};
+pub use failover::{
+    EndpointStatus, FailoverReader, FailoverSlideSource, DEFAULT_FAILOVER_COOLDOWN,
+    DEFAULT_FAILURE_THRESHOLD,
+};
+#[cfg(feature = "gcs")]
+pub use gcs_source::GcsSlideSource;
+pub use headers::{headers_key, SlideHeaders, HEADERS_SUFFIX, MAX_SLIDE_HEADERS};
+#[cfg(feature = "http")]
+pub use http_source::HttpSlideSource;
+pub use integrity::{
+    manifest_key, verify_full, verify_sampled, ChecksumManifest, IntegrityReport, IntegrityStatus,
+    ManifestBuilder, Sha256Digest, VerifyMode, DEFAULT_MANIFEST_BLOCK_SIZE, MANIFEST_SUFFIX,
+};
+pub use mask::TissueMask;
+pub use memory_source::MemorySlideSource;
+pub use negative::{NegativeCache, DEFAULT_NEGATIVE_CACHE_TTL, MAX_NEGATIVE_CACHE_ENTRIES};
+pub use properties::{
+    level_property, slide_properties, PropertySources, SlideProperties, PROPERTY_COMMENT,
+    PROPERTY_LEVEL_COUNT, PROPERTY_MPP_X, PROPERTY_MPP_Y, PROPERTY_OBJECTIVE_POWER,
+    PROPERTY_VENDOR,
+};
+pub(crate) use quarantine::CatchUnwind;
+pub use quarantine::{
+    QuarantineEntry, SlideQuarantine, DEFAULT_QUARANTINE_THRESHOLD, DEFAULT_QUARANTINE_WINDOW,
+};
+pub use readahead::{ReadAhead, DEFAULT_READAHEAD_TILES, SEQUENTIAL_RUN};
 pub use reader::{LevelInfo, SlideReader};
-pub use registry::{CachedSlide, SlideListResult, SlideRegistry, SlideSource};
+pub use registry::{
+    CachedSlide, SlideCacheEntry, SlideListResult, SlideMemoryUsage, SlideOpenStats, SlideRegistry,
+    SlideSource, DEFAULT_MAX_CONCURRENT_OPENS, DEFAULT_OPEN_QUEUE_TIMEOUT,
+};
 pub use s3_source::S3SlideSource;
+pub use sparse::{background_tile, parse_color, SparseTiles, TileGrid, DEFAULT_SPARSE_TILE_COLOR};
+pub use temperature::{
+    AdaptiveCacheConfig, HeatSnapshot, SlideHeat, SlideTemperature, DEFAULT_COLD_AFTER,
+    DEFAULT_COLD_BLOCK_CACHE_CAPACITY, DEFAULT_HOT_BLOCK_CACHE_CAPACITY, DEFAULT_HOT_SCORE,
+    DEFAULT_REBALANCE_INTERVAL, DEFAULT_SCORE_HALF_LIFE,
+};
+pub use tenants::{slide_tenant, TenantSlideSource, TENANT_SEPARATOR};
+pub use tiles::{
+    TileOrder, TileStream, TileStreamItem, TileStreamOptions, DEFAULT_TILE_STREAM_CONCURRENCY,
+};
+pub use views::{
+    load_view, save_view, validate_view_name, view_key, StoredView, ViewState, MAX_VIEW_BYTES,
+    MAX_VIEW_NAME_LEN, VIEWS_PREFIX,
+};
+pub use warm::{glob_matches, WarmReport, DEFAULT_WARM_CONCURRENCY, MAX_WARM_TILES};
@@ -0,0 +1,208 @@
+//! Negative caching of slides that failed to open.
+//!
+//! A viewer left open on a deleted or corrupt slide keeps requesting tiles,
+//! and without a record of the failure every request re-runs the open: a
+//! storage GET (or several, for header parsing) that fails the same way.
+//! [`NegativeCache`] remembers slides that failed with a permanent error —
+//! not found, or a file that doesn't parse — for a short TTL and fails
+//! further opens immediately with the same error.
+//!
+//! Transient failures (timeouts, throttling, storage outages) are never
+//! cached, so a recovering backend is used again on the next request.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::error::{FormatError, IoError, TiffError};
+
+/// Default time a failed open is remembered.
+pub const DEFAULT_NEGATIVE_CACHE_TTL: Duration = Duration::from_secs(30);
+
+/// Maximum number of failed slides remembered at once.
+pub const MAX_NEGATIVE_CACHE_ENTRIES: usize = 10_000;
+
+#[derive(Debug)]
+struct NegativeEntry {
+    expires_at: Instant,
+    error: FormatError,
+}
+
+/// Remembers slides whose open recently failed permanently.
+#[derive(Debug)]
+pub struct NegativeCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<String, NegativeEntry>>,
+}
+
+impl NegativeCache {
+    /// Create a cache remembering failures for `ttl` (zero disables it).
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Get the TTL (zero = disabled).
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Whether an open error is permanent enough to cache.
+    ///
+    /// Missing objects and files that fail to parse are; storage and
+    /// queueing errors are not.
+    pub fn is_cacheable(error: &FormatError) -> bool {
+        match error {
+            FormatError::Io(IoError::NotFound(_))
+            | FormatError::Tiff(TiffError::Io(IoError::NotFound(_))) => true,
+            FormatError::Io(_) | FormatError::Tiff(TiffError::Io(_)) => false,
+            FormatError::Tiff(_) | FormatError::UnsupportedFormat { .. } => true,
+            FormatError::OpenQueueTimeout { .. } | FormatError::Quarantined { .. } => false,
+        }
+    }
+
+    /// Get the remembered error for a slide, if it hasn't expired.
+    pub fn get(&self, slide_id: &str) -> Option<FormatError> {
+        self.get_at(slide_id, Instant::now())
+    }
+
+    fn get_at(&self, slide_id: &str, now: Instant) -> Option<FormatError> {
+        if self.ttl.is_zero() {
+            return None;
+        }
+        let mut entries = self.entries.lock().unwrap();
+        match entries.get(slide_id) {
+            Some(entry) if entry.expires_at > now => Some(entry.error.clone()),
+            Some(_) => {
+                entries.remove(slide_id);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Remember a failed open if the error is cacheable.
+    ///
+    /// Returns `true` if the failure was recorded.
+    pub fn record(&self, slide_id: &str, error: &FormatError) -> bool {
+        self.record_at(slide_id, error, Instant::now())
+    }
+
+    fn record_at(&self, slide_id: &str, error: &FormatError, now: Instant) -> bool {
+        if self.ttl.is_zero() || !Self::is_cacheable(error) {
+            return false;
+        }
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= MAX_NEGATIVE_CACHE_ENTRIES {
+            entries.retain(|_, entry| entry.expires_at > now);
+            if entries.len() >= MAX_NEGATIVE_CACHE_ENTRIES {
+                return false;
+            }
+        }
+        entries.insert(
+            slide_id.to_string(),
+            NegativeEntry {
+                expires_at: now + self.ttl,
+                error: error.clone(),
+            },
+        );
+        true
+    }
+
+    /// Forget a slide's failure, e.g. after it was re-uploaded.
+    pub fn forget(&self, slide_id: &str) {
+        self.entries.lock().unwrap().remove(slide_id);
+    }
+
+    /// Forget every failure.
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+
+    /// Number of remembered failures, including expired ones not yet
+    /// removed.
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    /// Whether no failures are remembered.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Default for NegativeCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_NEGATIVE_CACHE_TTL)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn not_found() -> FormatError {
+        FormatError::Io(IoError::NotFound("gone.svs".to_string()))
+    }
+
+    #[test]
+    fn test_cacheable_errors() {
+        assert!(NegativeCache::is_cacheable(&not_found()));
+        assert!(NegativeCache::is_cacheable(&FormatError::Tiff(
+            TiffError::InvalidMagic(0)
+        )));
+        assert!(NegativeCache::is_cacheable(
+            &FormatError::UnsupportedFormat {
+                reason: "strips".to_string()
+            }
+        ));
+        assert!(!NegativeCache::is_cacheable(&FormatError::Io(
+            IoError::Connection("reset".to_string())
+        )));
+        assert!(!NegativeCache::is_cacheable(&FormatError::Tiff(
+            TiffError::Io(IoError::S3("503".to_string()))
+        )));
+        assert!(!NegativeCache::is_cacheable(
+            &FormatError::OpenQueueTimeout {
+                slide_id: "a.svs".to_string(),
+                waited_ms: 10
+            }
+        ));
+    }
+
+    #[test]
+    fn test_entries_expire() {
+        let cache = NegativeCache::new(Duration::from_secs(30));
+        let now = Instant::now();
+        assert!(cache.record_at("gone.svs", &not_found(), now));
+        assert!(matches!(
+            cache.get_at("gone.svs", now + Duration::from_secs(29)),
+            Some(FormatError::Io(IoError::NotFound(_)))
+        ));
+        assert!(cache
+            .get_at("gone.svs", now + Duration::from_secs(30))
+            .is_none());
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_transient_errors_not_recorded() {
+        let cache = NegativeCache::default();
+        let error = FormatError::Io(IoError::S3("throttled".to_string()));
+        assert!(!cache.record("a.svs", &error));
+        assert!(cache.get("a.svs").is_none());
+    }
+
+    #[test]
+    fn test_disabled_and_forget() {
+        let disabled = NegativeCache::new(Duration::ZERO);
+        assert!(!disabled.record("gone.svs", &not_found()));
+
+        let cache = NegativeCache::default();
+        cache.record("gone.svs", &not_found());
+        cache.forget("gone.svs");
+        assert!(cache.get("gone.svs").is_none());
+    }
+}
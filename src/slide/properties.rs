@@ -0,0 +1,222 @@
+//! OpenSlide-compatible slide properties.
+//!
+//! Viewers and analysis pipelines written against OpenSlide read slide
+//! metadata from a flat string map with well-known `openslide.*` keys.
+//! [`slide_properties`] builds the same map from what the format readers
+//! know: the standard keys, the descriptive TIFF tags under `tiff.*`, and
+//! the raw Aperio ImageDescription fields under `aperio.*`.
+
+use std::collections::BTreeMap;
+
+use crate::format::tiff::TiffMetadata;
+use crate::format::SvsMetadata;
+
+use super::LevelInfo;
+
+/// Slide properties, keyed by property name.
+pub type SlideProperties = BTreeMap<String, String>;
+
+/// Vendor identifying the slide format (`aperio`, `generic-tiff`, ...).
+pub const PROPERTY_VENDOR: &str = "openslide.vendor";
+
+/// Level 0 microns per pixel in the X dimension.
+pub const PROPERTY_MPP_X: &str = "openslide.mpp-x";
+
+/// Level 0 microns per pixel in the Y dimension.
+pub const PROPERTY_MPP_Y: &str = "openslide.mpp-y";
+
+/// Magnification of the objective the slide was scanned with.
+pub const PROPERTY_OBJECTIVE_POWER: &str = "openslide.objective-power";
+
+/// Free-form description of the slide.
+pub const PROPERTY_COMMENT: &str = "openslide.comment";
+
+/// Number of pyramid levels.
+pub const PROPERTY_LEVEL_COUNT: &str = "openslide.level-count";
+
+/// Name of a per-level property, such as `openslide.level[0].width`.
+pub fn level_property(level: usize, name: &str) -> String {
+    format!("openslide.level[{}].{}", level, name)
+}
+
+/// What a format reader knows about a slide.
+#[derive(Debug, Clone, Copy)]
+pub struct PropertySources<'a> {
+    /// Value of `openslide.vendor`
+    pub vendor: &'a str,
+
+    /// Pyramid levels, level 0 first
+    pub levels: &'a [LevelInfo],
+
+    /// Level 0 microns per pixel `(x, y)`
+    pub mpp: Option<(f64, f64)>,
+
+    /// Descriptive TIFF tags, for TIFF-based formats
+    pub tiff: Option<&'a TiffMetadata>,
+
+    /// Parsed Aperio ImageDescription, for SVS slides
+    pub svs: Option<&'a SvsMetadata>,
+}
+
+/// Build the OpenSlide-compatible property map of a slide.
+pub fn slide_properties(sources: PropertySources<'_>) -> SlideProperties {
+    let mut properties = SlideProperties::new();
+    let mut set = |key: &str, value: String| {
+        properties.insert(key.to_string(), value);
+    };
+
+    set(PROPERTY_VENDOR, sources.vendor.to_string());
+
+    if let Some((mpp_x, mpp_y)) = sources.mpp {
+        set(PROPERTY_MPP_X, mpp_x.to_string());
+        set(PROPERTY_MPP_Y, mpp_y.to_string());
+    }
+
+    if let Some(svs) = sources.svs {
+        // Keep the scanner's own spelling of the values, as OpenSlide does
+        if let Some(mpp) = svs.properties.get("MPP") {
+            set(PROPERTY_MPP_X, mpp.clone());
+            set(PROPERTY_MPP_Y, mpp.clone());
+        }
+        if let Some(magnification) = svs.properties.get("AppMag") {
+            set(PROPERTY_OBJECTIVE_POWER, magnification.clone());
+        }
+        for (key, value) in &svs.properties {
+            set(&format!("aperio.{}", key), value.clone());
+        }
+    }
+
+    if let Some(tiff) = sources.tiff {
+        let strings = [
+            ("tiff.ImageDescription", &tiff.image_description),
+            ("tiff.Make", &tiff.make),
+            ("tiff.Model", &tiff.model),
+            ("tiff.Software", &tiff.software),
+            ("tiff.DateTime", &tiff.date_time),
+        ];
+        for (key, value) in strings {
+            if let Some(value) = value {
+                set(key, value.clone());
+            }
+        }
+        if let Some(resolution) = tiff.x_resolution {
+            set("tiff.XResolution", resolution.to_string());
+        }
+        if let Some(resolution) = tiff.y_resolution {
+            set("tiff.YResolution", resolution.to_string());
+        }
+        if let Some(unit) = tiff.resolution_unit_name() {
+            set("tiff.ResolutionUnit", unit.to_string());
+        }
+        if let Some(description) = &tiff.image_description {
+            set(PROPERTY_COMMENT, description.clone());
+        }
+    }
+
+    // The whole level 0 image holds tissue as far as the readers know
+    if let Some(level0) = sources.levels.first() {
+        set("openslide.bounds-x", "0".to_string());
+        set("openslide.bounds-y", "0".to_string());
+        set("openslide.bounds-width", level0.width.to_string());
+        set("openslide.bounds-height", level0.height.to_string());
+    }
+
+    set(PROPERTY_LEVEL_COUNT, sources.levels.len().to_string());
+    for (index, level) in sources.levels.iter().enumerate() {
+        set(&level_property(index, "width"), level.width.to_string());
+        set(&level_property(index, "height"), level.height.to_string());
+        set(
+            &level_property(index, "downsample"),
+            level.downsample.to_string(),
+        );
+        set(
+            &level_property(index, "tile-width"),
+            level.tile_width.to_string(),
+        );
+        set(
+            &level_property(index, "tile-height"),
+            level.tile_height.to_string(),
+        );
+    }
+
+    properties
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::tiff::RESOLUTION_UNIT_CENTIMETER;
+
+    fn level(width: u32, height: u32, downsample: f64) -> LevelInfo {
+        LevelInfo {
+            width,
+            height,
+            tile_width: 256,
+            tile_height: 256,
+            tiles_x: width.div_ceil(256),
+            tiles_y: height.div_ceil(256),
+            downsample,
+        }
+    }
+
+    #[test]
+    fn test_svs_properties() {
+        let description = "Aperio Image Library v10.0.51\n46920x33014 [0,100 46000x32914] (256x256) JPEG/RGB Q=30|AppMag = 20|MPP = 0.4990";
+        let svs = SvsMetadata::parse(description);
+        let tiff = TiffMetadata {
+            image_description: Some(description.to_string()),
+            ..Default::default()
+        };
+        let levels = [level(46920, 33014, 1.0), level(11730, 8253, 4.0)];
+        let properties = slide_properties(PropertySources {
+            vendor: "aperio",
+            levels: &levels,
+            mpp: svs.mpp.map(|mpp| (mpp, mpp)),
+            tiff: Some(&tiff),
+            svs: Some(&svs),
+        });
+
+        assert_eq!(properties[PROPERTY_VENDOR], "aperio");
+        assert_eq!(properties[PROPERTY_MPP_X], "0.4990");
+        assert_eq!(properties[PROPERTY_MPP_Y], "0.4990");
+        assert_eq!(properties[PROPERTY_OBJECTIVE_POWER], "20");
+        assert_eq!(properties["aperio.AppMag"], "20");
+        assert_eq!(properties[PROPERTY_COMMENT], description);
+        assert_eq!(properties["openslide.bounds-width"], "46920");
+        assert_eq!(properties[PROPERTY_LEVEL_COUNT], "2");
+        assert_eq!(properties[&level_property(1, "width")], "11730");
+        assert_eq!(properties[&level_property(1, "downsample")], "4");
+        assert_eq!(properties[&level_property(1, "tile-height")], "256");
+    }
+
+    #[test]
+    fn test_generic_tiff_properties() {
+        let tiff = TiffMetadata {
+            software: Some("vips".to_string()),
+            x_resolution: Some(40_000.0),
+            y_resolution: Some(40_000.0),
+            resolution_unit: Some(RESOLUTION_UNIT_CENTIMETER),
+            ..Default::default()
+        };
+        let levels = [level(1024, 768, 1.0)];
+        let properties = slide_properties(PropertySources {
+            vendor: "generic-tiff",
+            levels: &levels,
+            mpp: tiff.mpp(),
+            tiff: Some(&tiff),
+            svs: None,
+        });
+
+        assert_eq!(properties[PROPERTY_VENDOR], "generic-tiff");
+        assert_eq!(properties[PROPERTY_MPP_X], "0.25");
+        assert_eq!(properties["tiff.Software"], "vips");
+        assert_eq!(properties["tiff.ResolutionUnit"], "centimeter");
+        assert!(!properties.contains_key(PROPERTY_OBJECTIVE_POWER));
+        assert!(!properties.contains_key(PROPERTY_COMMENT));
+        assert_eq!(properties["openslide.bounds-height"], "768");
+    }
+}
@@ -0,0 +1,287 @@
+//! Quarantine for slides that repeatedly fail.
+//!
+//! A corrupt upload can fail the same way on every request: a tile that
+//! doesn't decode, a header that doesn't parse, or a codec panic. Each
+//! attempt costs a storage read and CPU, and keeps alarming users. Once a
+//! slide fails [`DEFAULT_QUARANTINE_THRESHOLD`] times within
+//! [`DEFAULT_QUARANTINE_WINDOW`], it is quarantined: the registry refuses to
+//! open it (served as `423 Locked`) and it is left out of slide listings
+//! until an admin releases it.
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::pin::Pin;
+use std::sync::Mutex;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+use tracing::warn;
+
+// =============================================================================
+// Configuration
+// =============================================================================
+
+/// Default number of failures that quarantines a slide.
+pub const DEFAULT_QUARANTINE_THRESHOLD: u32 = 5;
+
+/// Default window in which failures are counted.
+pub const DEFAULT_QUARANTINE_WINDOW: Duration = Duration::from_secs(10 * 60);
+
+// =============================================================================
+// Quarantine
+// =============================================================================
+
+/// A quarantined slide.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct QuarantineEntry {
+    /// Slide identifier
+    pub slide_id: String,
+
+    /// The failure that tipped the slide into quarantine
+    pub reason: String,
+
+    /// Failures counted in the window before quarantine
+    pub failures: u32,
+
+    /// Quarantine time as a Unix timestamp (seconds)
+    pub quarantined_at: u64,
+}
+
+#[derive(Debug)]
+struct FailureWindow {
+    first_at: Instant,
+    count: u32,
+}
+
+#[derive(Debug, Default)]
+struct QuarantineState {
+    failures: HashMap<String, FailureWindow>,
+    quarantined: HashMap<String, QuarantineEntry>,
+}
+
+/// Tracks slide failures and quarantined slides.
+#[derive(Debug)]
+pub struct SlideQuarantine {
+    threshold: u32,
+    window: Duration,
+    state: Mutex<QuarantineState>,
+}
+
+impl SlideQuarantine {
+    /// Create a quarantine that triggers after `threshold` failures within
+    /// `window`. A threshold of 0 disables quarantining.
+    pub fn new(threshold: u32, window: Duration) -> Self {
+        Self {
+            threshold,
+            window,
+            state: Mutex::new(QuarantineState::default()),
+        }
+    }
+
+    /// Get the failure threshold (0 = disabled).
+    pub fn threshold(&self) -> u32 {
+        self.threshold
+    }
+
+    /// Check whether a slide is quarantined.
+    pub fn is_quarantined(&self, slide_id: &str) -> bool {
+        self.state
+            .lock()
+            .unwrap()
+            .quarantined
+            .contains_key(slide_id)
+    }
+
+    /// Record a failure for a slide.
+    ///
+    /// Returns `true` if this failure quarantined the slide.
+    pub fn record_failure(&self, slide_id: &str, reason: &str) -> bool {
+        self.record_failure_at(slide_id, reason, Instant::now())
+    }
+
+    fn record_failure_at(&self, slide_id: &str, reason: &str, now: Instant) -> bool {
+        if self.threshold == 0 {
+            return false;
+        }
+
+        let mut state = self.state.lock().unwrap();
+        if state.quarantined.contains_key(slide_id) {
+            return false;
+        }
+
+        let window = state
+            .failures
+            .entry(slide_id.to_string())
+            .or_insert(FailureWindow {
+                first_at: now,
+                count: 0,
+            });
+        if now.duration_since(window.first_at) > self.window {
+            *window = FailureWindow {
+                first_at: now,
+                count: 0,
+            };
+        }
+        window.count += 1;
+
+        if window.count < self.threshold {
+            return false;
+        }
+
+        let failures = window.count;
+        state.failures.remove(slide_id);
+        state.quarantined.insert(
+            slide_id.to_string(),
+            QuarantineEntry {
+                slide_id: slide_id.to_string(),
+                reason: reason.to_string(),
+                failures,
+                quarantined_at: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_secs())
+                    .unwrap_or(0),
+            },
+        );
+        warn!(
+            slide_id = slide_id,
+            failures = failures,
+            "Slide quarantined after repeated failures: {}",
+            reason
+        );
+        true
+    }
+
+    /// Release a slide from quarantine, clearing its failure count.
+    ///
+    /// Returns `false` if the slide was not quarantined.
+    pub fn release(&self, slide_id: &str) -> bool {
+        let mut state = self.state.lock().unwrap();
+        state.failures.remove(slide_id);
+        state.quarantined.remove(slide_id).is_some()
+    }
+
+    /// List quarantined slides, most recently quarantined first.
+    pub fn entries(&self) -> Vec<QuarantineEntry> {
+        let mut entries: Vec<_> = self
+            .state
+            .lock()
+            .unwrap()
+            .quarantined
+            .values()
+            .cloned()
+            .collect();
+        entries.sort_by(|a, b| {
+            b.quarantined_at
+                .cmp(&a.quarantined_at)
+                .then_with(|| a.slide_id.cmp(&b.slide_id))
+        });
+        entries
+    }
+}
+
+impl Default for SlideQuarantine {
+    fn default() -> Self {
+        Self::new(DEFAULT_QUARANTINE_THRESHOLD, DEFAULT_QUARANTINE_WINDOW)
+    }
+}
+
+// =============================================================================
+// Panic Detection
+// =============================================================================
+
+/// Future adapter catching panics raised while polling the inner future.
+///
+/// Used to attribute a panic to the slide being served before letting it
+/// continue to the HTTP layer.
+pub(crate) struct CatchUnwind<F>(Pin<Box<F>>);
+
+impl<F: Future> CatchUnwind<F> {
+    pub(crate) fn new(future: F) -> Self {
+        Self(Box::pin(future))
+    }
+}
+
+impl<F: Future> Future for CatchUnwind<F> {
+    type Output = Result<F::Output, Box<dyn Any + Send>>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let inner = self.0.as_mut();
+        match std::panic::catch_unwind(AssertUnwindSafe(|| inner.poll(cx))) {
+            Ok(Poll::Pending) => Poll::Pending,
+            Ok(Poll::Ready(output)) => Poll::Ready(Ok(output)),
+            Err(payload) => Poll::Ready(Err(payload)),
+        }
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quarantine_after_threshold() {
+        let quarantine = SlideQuarantine::new(3, Duration::from_secs(60));
+
+        assert!(!quarantine.record_failure("bad.svs", "decode error"));
+        assert!(!quarantine.record_failure("bad.svs", "decode error"));
+        assert!(!quarantine.is_quarantined("bad.svs"));
+        assert!(quarantine.record_failure("bad.svs", "decode error"));
+        assert!(quarantine.is_quarantined("bad.svs"));
+        assert!(!quarantine.is_quarantined("good.svs"));
+
+        let entries = quarantine.entries();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].slide_id, "bad.svs");
+        assert_eq!(entries[0].failures, 3);
+    }
+
+    #[test]
+    fn test_failures_expire_with_window() {
+        let quarantine = SlideQuarantine::new(2, Duration::from_secs(60));
+        let start = Instant::now();
+
+        quarantine.record_failure_at("slow.svs", "panic", start);
+        // The second failure lands after the window; counting restarts
+        let later = start + Duration::from_secs(120);
+        assert!(!quarantine.record_failure_at("slow.svs", "panic", later));
+        assert!(quarantine.record_failure_at("slow.svs", "panic", later));
+    }
+
+    #[test]
+    fn test_release() {
+        let quarantine = SlideQuarantine::new(1, Duration::from_secs(60));
+        quarantine.record_failure("bad.svs", "panic");
+
+        assert!(quarantine.release("bad.svs"));
+        assert!(!quarantine.is_quarantined("bad.svs"));
+        assert!(!quarantine.release("bad.svs"));
+    }
+
+    #[test]
+    fn test_zero_threshold_disables() {
+        let quarantine = SlideQuarantine::new(0, Duration::from_secs(60));
+        for _ in 0..100 {
+            assert!(!quarantine.record_failure("bad.svs", "panic"));
+        }
+        assert!(!quarantine.is_quarantined("bad.svs"));
+    }
+
+    #[tokio::test]
+    async fn test_catch_unwind() {
+        let ok = CatchUnwind::new(async { 7 }).await;
+        assert_eq!(ok.unwrap(), 7);
+
+        let panicked = CatchUnwind::new(async {
+            panic!("codec blew up");
+        })
+        .await;
+        assert!(panicked.is_err());
+    }
+}
@@ -0,0 +1,194 @@
+//! Read-ahead for sequential tile scans.
+//!
+//! Bulk jobs such as patch extraction read a level tile by tile in
+//! row-major order. Each tile read misses the block cache and waits for a
+//! storage round trip, so a scan is bound by latency rather than bandwidth.
+//! [`ReadAhead`] notices such scans and names the tiles that will be read
+//! next, so [`CachedSlide::read_tile`] can fetch their bytes into the block
+//! cache in the background.
+//!
+//! A read is part of a scan if its row-major tile index is slightly ahead
+//! of the previous read on the same level; small steps back are tolerated,
+//! since concurrent readers finish out of order. Once a run of
+//! [`SEQUENTIAL_RUN`] such reads is seen, the next tiles are requested, and
+//! requested again whenever the scan gets within half a window of the last
+//! tile already requested.
+//!
+//! [`CachedSlide::read_tile`]: super::CachedSlide::read_tile
+
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::Mutex;
+
+/// Default number of tiles read ahead of a sequential scan.
+pub const DEFAULT_READAHEAD_TILES: usize = 8;
+
+/// Consecutive forward reads that make a scan.
+///
+/// Longer than a viewer viewport row, so panning doesn't trigger read-ahead.
+pub const SEQUENTIAL_RUN: u32 = 16;
+
+/// Largest step between two reads that still counts as sequential, in
+/// tiles, in either direction.
+const SEQUENTIAL_GAP: u64 = 16;
+
+/// Scan state of one level.
+#[derive(Debug, Clone, Copy)]
+struct Scan {
+    /// Furthest tile index read
+    last: u64,
+
+    /// Forward reads in a row
+    run: u32,
+
+    /// End (exclusive) of the tiles already read ahead
+    ahead_until: u64,
+}
+
+/// Sequential scan detector of one slide.
+#[derive(Debug)]
+pub struct ReadAhead {
+    tiles: usize,
+    scans: Mutex<HashMap<usize, Scan>>,
+}
+
+impl ReadAhead {
+    /// Create a detector reading `tiles` tiles ahead (0 disables read-ahead).
+    pub fn new(tiles: usize) -> Self {
+        Self {
+            tiles,
+            scans: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Number of tiles read ahead (0 when disabled).
+    pub fn tiles(&self) -> usize {
+        self.tiles
+    }
+
+    /// Record a tile read and return the row-major indices of the tiles to
+    /// read ahead, if any.
+    ///
+    /// # Arguments
+    /// * `level` - Pyramid level of the read
+    /// * `index` - Row-major index of the tile read
+    /// * `tile_count` - Number of tiles in the level
+    pub fn record(&self, level: usize, index: u64, tile_count: u64) -> Option<Range<u64>> {
+        if self.tiles == 0 {
+            return None;
+        }
+
+        let mut scans = self.scans.lock().unwrap();
+        let scan = scans.entry(level).or_insert(Scan {
+            last: index,
+            run: 0,
+            ahead_until: 0,
+        });
+
+        if index > scan.last && index - scan.last <= SEQUENTIAL_GAP {
+            scan.last = index;
+            scan.run += 1;
+        } else if index <= scan.last && scan.last - index <= SEQUENTIAL_GAP {
+            // A read finishing out of order; neither breaks nor extends the scan
+        } else {
+            *scan = Scan {
+                last: index,
+                run: 0,
+                ahead_until: 0,
+            };
+            return None;
+        }
+
+        let window = self.tiles as u64;
+        if scan.run < SEQUENTIAL_RUN || scan.last + window / 2 < scan.ahead_until {
+            return None;
+        }
+
+        let start = scan.ahead_until.max(scan.last + 1);
+        let end = (scan.last + 1 + window).min(tile_count);
+        if start >= end {
+            return None;
+        }
+        scan.ahead_until = end;
+        Some(start..end)
+    }
+}
+
+impl Default for ReadAhead {
+    fn default() -> Self {
+        Self::new(DEFAULT_READAHEAD_TILES)
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sequential_scan_reads_ahead() {
+        let readahead = ReadAhead::new(8);
+
+        // The run starts after SEQUENTIAL_RUN forward reads
+        let run = SEQUENTIAL_RUN as u64;
+        for index in 0..run {
+            assert_eq!(readahead.record(0, index, 100), None);
+        }
+        assert_eq!(readahead.record(0, run, 100), Some(run + 1..run + 9));
+
+        // Nothing more until the scan nears the end of the window
+        for index in run + 1..run + 5 {
+            assert_eq!(readahead.record(0, index, 100), None);
+        }
+        assert_eq!(readahead.record(0, run + 5, 100), Some(run + 9..run + 14));
+
+        // The window stops at the end of the level
+        for index in 9..95 {
+            readahead.record(0, index, 100);
+        }
+        assert_eq!(readahead.record(0, 95, 100), None);
+    }
+
+    #[test]
+    fn test_out_of_order_reads_keep_the_scan() {
+        let readahead = ReadAhead::new(8);
+        // Pairs of tiles finishing in swapped order
+        let run = SEQUENTIAL_RUN as u64;
+        for index in 0..2 * run {
+            assert_eq!(readahead.record(1, index ^ 1, 100), None);
+        }
+        let next = 2 * run + 1;
+        assert_eq!(readahead.record(1, next, 100), Some(next + 1..next + 9));
+        assert_eq!(readahead.record(1, next - 1, 100), None);
+    }
+
+    #[test]
+    fn test_random_access_does_not_read_ahead() {
+        let readahead = ReadAhead::new(8);
+        // Viewport rows of 8 tiles in a level 100 tiles wide
+        for y in 0..10 {
+            for x in 20..28 {
+                assert_eq!(readahead.record(0, y * 100 + x, 10_000), None);
+            }
+        }
+
+        // Levels are tracked separately
+        let run = SEQUENTIAL_RUN as u64;
+        for index in 0..run {
+            readahead.record(0, index, 1000);
+            readahead.record(1, 500 + index, 1000);
+        }
+        assert_eq!(readahead.record(0, run, 1000), Some(run + 1..run + 9));
+    }
+
+    #[test]
+    fn test_disabled() {
+        let readahead = ReadAhead::new(0);
+        for index in 0..10 {
+            assert_eq!(readahead.record(0, index, 100), None);
+        }
+    }
+}
@@ -3,8 +3,11 @@
 //! The registry provides:
 //! - LRU caching of opened slide readers to avoid re-parsing metadata
 //! - Singleflight pattern to prevent duplicate opens for the same slide
-//! - Format auto-detection when opening slides
+//! - Format auto-detection when opening slides, including registered
+//!   [`FormatPlugin`]s
 //! - Block caching for efficient I/O
+//! - Optional checksum manifest verification at open, and of every block
+//!   read from storage
 //!
 //! # Example
 //!
@@ -26,19 +29,51 @@
 //! let tile = slide.read_tile(0, 0, 0).await?;
 //! ```
 
-use std::collections::HashMap;
-use std::sync::Arc;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex as SyncMutex, RwLock as SyncRwLock};
+use std::time::{Duration, Instant};
 
 use async_trait::async_trait;
 use bytes::Bytes;
+use image::{imageops, DynamicImage, Rgb, RgbImage};
 use lru::LruCache;
-use tokio::sync::{Mutex, Notify, RwLock};
-
-use crate::error::{FormatError, IoError, TiffError};
-use crate::format::{detect_format, GenericTiffReader, SlideFormat, SvsReader};
-use crate::io::{BlockCache, RangeReader, DEFAULT_BLOCK_SIZE};
-
+use serde::Serialize;
+use tokio::sync::{Mutex, Notify, Semaphore};
+use tokio::task::JoinSet;
+use tracing::{info, warn};
+
+use crate::error::{FormatError, IoError, TiffError, TileError};
+use crate::format::tiff::{Orientation, PyramidDetectOptions};
+use crate::format::RawTileLayout;
+use crate::format::{
+    detect_format, FormatPlugin, GenericTiffReader, PluginSlide, SlideFiles, SlideFormat, SvsReader,
+};
+use crate::io::{
+    validate_key, BlockCache, BlockCacheStats, RangeReader, DEFAULT_BLOCK_CACHE_SHARDS,
+    DEFAULT_BLOCK_SIZE,
+};
+use crate::plan::METADATA_BYTES_PER_TILE;
+use crate::tile::{decode_tile, EncodePool};
+use crate::timing::{self, Phase};
+
+use super::conformance::{ConformanceCache, SlideConformance};
+use super::events::{EventBus, EvictionReason, ServerEvent};
+use super::headers::{headers_key, SlideHeaders};
+use super::integrity::{
+    manifest_key, verify_full, verify_sampled, ChecksumManifest, IntegrityReport, IntegrityStatus,
+    Sha256Digest, VerifyMode,
+};
+use super::mask::TissueMask;
+use super::negative::NegativeCache;
+use super::properties::{slide_properties, PropertySources, SlideProperties};
+use super::quarantine::{SlideQuarantine, DEFAULT_QUARANTINE_WINDOW};
+use super::readahead::{ReadAhead, DEFAULT_READAHEAD_TILES};
 use super::reader::{LevelInfo, SlideReader};
+use super::sparse::{SparseTiles, TileGrid};
+use super::temperature::{AdaptiveCacheConfig, HeatSnapshot, SlideHeat, SlideTemperature};
+use super::tenants::slide_tenant;
+use super::tiles::{TileStream, TileStreamOptions};
 
 // =============================================================================
 // Configuration
@@ -50,6 +85,12 @@ const DEFAULT_SLIDE_CACHE_CAPACITY: usize = 100;
 /// Default capacity for block cache per slide (number of blocks).
 const DEFAULT_BLOCK_CACHE_CAPACITY: usize = 100;
 
+/// Largest manifest block size used for read verification (16 MiB).
+///
+/// Verified slides cache blocks of the manifest's block size, so larger
+/// blocks would make every cache miss fetch too much.
+const MAX_VERIFIED_BLOCK_SIZE: u64 = 16 * 1024 * 1024;
+
 // =============================================================================
 // SlideSource Trait
 // =============================================================================
@@ -59,6 +100,9 @@ const DEFAULT_BLOCK_CACHE_CAPACITY: usize = 100;
 pub struct SlideListResult {
     /// List of slide paths/keys.
     pub slides: Vec<String>,
+    /// Folders (common prefixes ending in the delimiter) directly under the
+    /// listed prefix; empty unless a delimiter was given.
+    pub folders: Vec<String>,
     /// Continuation token for pagination (None if no more results).
     pub next_cursor: Option<String>,
 }
@@ -90,20 +134,59 @@ pub trait SlideSource: Send + Sync {
     /// * `limit` - Maximum number of slides to return
     /// * `cursor` - Continuation token for pagination (from previous response)
     /// * `prefix` - Optional path prefix to filter results (e.g., "folder/")
+    /// * `delimiter` - Optional separator (e.g., "/"); keys containing it
+    ///   after the prefix are rolled up into `folders` instead of being listed
     ///
     /// # Returns
-    /// A list of slide paths and optional continuation token.
+    /// A list of slide paths, folders and optional continuation token.
+    /// Folders count toward `limit`.
     async fn list_slides(
         &self,
         _limit: u32,
         _cursor: Option<&str>,
         _prefix: Option<&str>,
+        _delimiter: Option<&str>,
     ) -> Result<SlideListResult, IoError> {
         Ok(SlideListResult {
             slides: vec![],
+            folders: vec![],
             next_cursor: None,
         })
     }
+
+    /// Check that the storage backend is reachable with the configured
+    /// credentials.
+    ///
+    /// Backs the readiness probe, so it should be a single cheap request.
+    /// The default implementation lists one slide.
+    async fn check_ready(&self) -> Result<(), IoError> {
+        self.list_slides(1, None, None, None).await.map(|_| ())
+    }
+
+    /// Read a whole object from the storage backend.
+    ///
+    /// Used for small auxiliary objects such as saved viewer states. The
+    /// default implementation reads the object through a range reader.
+    async fn get_object(&self, key: &str) -> Result<Bytes, IoError> {
+        let reader = self.create_reader(key).await?;
+        reader.read_exact_at(0, reader.size() as usize).await
+    }
+
+    /// Write a whole object to the storage backend.
+    ///
+    /// The default implementation fails; read-only sources don't need to
+    /// override it.
+    async fn put_object(
+        &self,
+        key: &str,
+        _data: Bytes,
+        _content_type: &str,
+    ) -> Result<(), IoError> {
+        Err(IoError::S3(format!(
+            "cannot write {}: storage backend is read-only",
+            key
+        )))
+    }
 }
 
 // =============================================================================
@@ -121,17 +204,47 @@ pub struct CachedSlide<R: RangeReader + 'static> {
     /// The underlying reader with block caching
     reader: Arc<BlockCache<R>>,
 
-    /// The slide reader (either SVS or generic TIFF)
+    /// The slide reader (SVS, generic TIFF or plugin-provided)
     inner: SlideReaderInner,
+
+    /// Result of the last checksum verification
+    integrity: SyncRwLock<Option<IntegrityReport>>,
+
+    /// Custom response headers from the slide's sidecar
+    headers: SyncRwLock<SlideHeaders>,
+
+    /// Access counters for hot/cold classification
+    heat: SlideHeat,
+
+    /// Storage ETag of the file when it was opened
+    etag: Option<Arc<str>>,
+
+    /// Set when a read found the object replaced; the registry then
+    /// reopens the slide
+    replaced: AtomicBool,
+
+    /// What to serve for tiles missing from a sparse TIFF
+    sparse_tiles: SparseTiles,
+
+    /// Sequential scan detection for tile read-ahead
+    readahead: ReadAhead,
+
+    /// Tissue mask, once computed
+    tissue_mask: SyncRwLock<Option<Arc<TissueMask>>>,
+
+    /// Blocking pool that tiles of rotated slides are composed on
+    encode_pool: EncodePool,
 }
 
 /// Internal enum to hold format-specific readers.
 ///
 /// We use an enum instead of trait objects because `SlideReader::read_tile`
 /// is generic over the reader type, making the trait not object-safe.
+/// Plugin formats implement the object-safe [`PluginSlide`] instead.
 enum SlideReaderInner {
-    Svs(SvsReader),
-    GenericTiff(GenericTiffReader),
+    Svs(Box<SvsReader>),
+    GenericTiff(Box<GenericTiffReader>),
+    Plugin(Box<dyn PluginSlide>),
 }
 
 impl<R: RangeReader + 'static> CachedSlide<R> {
@@ -140,28 +253,63 @@ impl<R: RangeReader + 'static> CachedSlide<R> {
         self.format
     }
 
+    /// Get the validation warnings reported when the slide was opened.
+    pub fn warnings(&self) -> &[String] {
+        match &self.inner {
+            SlideReaderInner::Svs(r) => r.warnings(),
+            SlideReaderInner::GenericTiff(r) => r.warnings(),
+            SlideReaderInner::Plugin(_) => &[],
+        }
+    }
+
     /// Get the number of pyramid levels.
     pub fn level_count(&self) -> usize {
         match &self.inner {
             SlideReaderInner::Svs(r) => r.level_count(),
             SlideReaderInner::GenericTiff(r) => r.level_count(),
+            SlideReaderInner::Plugin(r) => r.level_count(),
+        }
+    }
+
+    /// Get how the slide's stored pixels map onto the displayed image.
+    ///
+    /// Slides stored rotated or mirrored are presented upright: geometry
+    /// is reported and tiles are served in display orientation.
+    pub fn orientation(&self) -> Orientation {
+        match &self.inner {
+            SlideReaderInner::Svs(r) => r.tiff_metadata().orientation,
+            SlideReaderInner::GenericTiff(r) => r.tiff_metadata().orientation,
+            SlideReaderInner::Plugin(_) => Orientation::Normal,
+        }
+    }
+
+    /// Get the ICC color profile embedded in the slide, if any.
+    pub fn icc_profile(&self) -> Option<Bytes> {
+        match &self.inner {
+            SlideReaderInner::Svs(r) => r.tiff_metadata().icc_profile.clone(),
+            SlideReaderInner::GenericTiff(r) => r.tiff_metadata().icc_profile.clone(),
+            SlideReaderInner::Plugin(_) => None,
         }
     }
 
     /// Get dimensions of the full-resolution (level 0) image.
     pub fn dimensions(&self) -> Option<(u32, u32)> {
-        match &self.inner {
+        let (width, height) = match &self.inner {
             SlideReaderInner::Svs(r) => r.dimensions(),
             SlideReaderInner::GenericTiff(r) => r.dimensions(),
-        }
+            SlideReaderInner::Plugin(r) => r.level_info(0).map(|l| (l.width, l.height)),
+        }?;
+        Some(self.orientation().display_size(width, height))
     }
 
     /// Get dimensions of a specific level.
     pub fn level_dimensions(&self, level: usize) -> Option<(u32, u32)> {
-        match &self.inner {
+        let (width, height) = match &self.inner {
             SlideReaderInner::Svs(r) => r.level_dimensions(level),
             SlideReaderInner::GenericTiff(r) => r.level_dimensions(level),
-        }
+            SlideReaderInner::Plugin(r) => r.level_info(level).map(|l| (l.width, l.height)),
+        }?;
+        Some(self.orientation().display_size(width, height))
     }
 
     /// Get the downsample factor for a level.
@@ -169,59 +317,387 @@ impl<R: RangeReader + 'static> CachedSlide<R> {
         match &self.inner {
             SlideReaderInner::Svs(r) => r.level_downsample(level),
             SlideReaderInner::GenericTiff(r) => r.level_downsample(level),
+            SlideReaderInner::Plugin(r) => r.level_info(level).map(|l| l.downsample),
         }
     }
 
     /// Get tile size for a level.
     pub fn tile_size(&self, level: usize) -> Option<(u32, u32)> {
-        match &self.inner {
+        let (width, height) = match &self.inner {
             SlideReaderInner::Svs(r) => r.tile_size(level),
             SlideReaderInner::GenericTiff(r) => r.tile_size(level),
-        }
+            SlideReaderInner::Plugin(r) => {
+                r.level_info(level).map(|l| (l.tile_width, l.tile_height))
+            }
+        }?;
+        Some(self.orientation().display_size(width, height))
     }
 
     /// Get the number of tiles in X and Y directions for a level.
     pub fn tile_count(&self, level: usize) -> Option<(u32, u32)> {
-        match &self.inner {
+        let (tiles_x, tiles_y) = match &self.inner {
             SlideReaderInner::Svs(r) => r.tile_count(level),
             SlideReaderInner::GenericTiff(r) => r.tile_count(level),
-        }
+            SlideReaderInner::Plugin(r) => r.level_info(level).map(|l| (l.tiles_x, l.tiles_y)),
+        }?;
+        Some(self.orientation().display_size(tiles_x, tiles_y))
     }
 
     /// Get complete information about a level.
     pub fn level_info(&self, level: usize) -> Option<LevelInfo> {
+        let info = self.stored_level_info(level)?;
+        if !self.orientation().swaps_dimensions() {
+            return Some(info);
+        }
+        Some(LevelInfo {
+            width: info.height,
+            height: info.width,
+            tile_width: info.tile_height,
+            tile_height: info.tile_width,
+            tiles_x: info.tiles_y,
+            tiles_y: info.tiles_x,
+            downsample: info.downsample,
+        })
+    }
+
+    /// Get information about a level as stored, before orientation.
+    fn stored_level_info(&self, level: usize) -> Option<LevelInfo> {
         match &self.inner {
             SlideReaderInner::Svs(r) => r.level_info(level),
             SlideReaderInner::GenericTiff(r) => r.level_info(level),
+            SlideReaderInner::Plugin(r) => r.level_info(level),
+        }
+    }
+
+    /// Get the level 0 resolution in microns per pixel, if the slide is
+    /// calibrated.
+    pub fn mpp(&self) -> Option<f64> {
+        match &self.inner {
+            SlideReaderInner::Svs(r) => r.metadata().mpp,
+            SlideReaderInner::GenericTiff(r) => r.tiff_metadata().mpp().map(|(mpp_x, _)| mpp_x),
+            SlideReaderInner::Plugin(r) => r.mpp(),
+        }
+    }
+
+    /// Get the objective magnification the slide was scanned at, if known.
+    pub fn magnification(&self) -> Option<f64> {
+        match &self.inner {
+            SlideReaderInner::Svs(r) => r.metadata().magnification,
+            SlideReaderInner::GenericTiff(_) | SlideReaderInner::Plugin(_) => None,
+        }
+    }
+
+    /// Get the scanner vendor, if known.
+    pub fn vendor(&self) -> Option<String> {
+        match &self.inner {
+            SlideReaderInner::Svs(r) => r.metadata().vendor.clone(),
+            SlideReaderInner::GenericTiff(_) | SlideReaderInner::Plugin(_) => None,
+        }
+    }
+
+    /// Get the OpenSlide-compatible properties of the slide.
+    ///
+    /// Includes the `openslide.*` standard properties (vendor, resolution,
+    /// objective power, bounds and level geometry), the descriptive TIFF
+    /// tags and, for SVS slides, the Aperio ImageDescription fields.
+    pub fn properties(&self) -> SlideProperties {
+        let levels: Vec<LevelInfo> = (0..self.level_count())
+            .filter_map(|level| self.level_info(level))
+            .collect();
+        match &self.inner {
+            SlideReaderInner::Svs(r) => slide_properties(PropertySources {
+                vendor: "aperio",
+                levels: &levels,
+                mpp: r.metadata().mpp.map(|mpp| (mpp, mpp)),
+                tiff: Some(r.tiff_metadata()),
+                svs: Some(r.metadata()),
+            }),
+            SlideReaderInner::GenericTiff(r) => slide_properties(PropertySources {
+                vendor: "generic-tiff",
+                levels: &levels,
+                mpp: r.tiff_metadata().mpp(),
+                tiff: Some(r.tiff_metadata()),
+                svs: None,
+            }),
+            SlideReaderInner::Plugin(r) => {
+                let mut properties = slide_properties(PropertySources {
+                    vendor: self.format.name(),
+                    levels: &levels,
+                    mpp: r.mpp().map(|mpp| (mpp, mpp)),
+                    tiff: None,
+                    svs: None,
+                });
+                properties.extend(r.properties());
+                properties
+            }
+        }
+    }
+
+    /// Get the byte range `(offset, length)` of a tile in the file.
+    ///
+    /// Returns `None` if the tile is out of range, the slide is not stored
+    /// upright (its tiles are then composed from several stored tiles) or
+    /// the slide comes from a format plugin.
+    pub fn tile_location(&self, level: usize, tile_x: u32, tile_y: u32) -> Option<(u64, u64)> {
+        if !self.orientation().is_upright() {
+            return None;
+        }
+        match &self.inner {
+            SlideReaderInner::Svs(r) => r.get_level(level)?.get_tile_location(tile_x, tile_y),
+            SlideReaderInner::GenericTiff(r) => {
+                r.get_level(level)?.get_tile_location(tile_x, tile_y)
+            }
+            SlideReaderInner::Plugin(_) => None,
+        }
+    }
+
+    /// Get which tiles of a level are stored in the file.
+    ///
+    /// Returns `None` if the level is out of range or stored in strips, the
+    /// slide is not stored upright or comes from a format plugin.
+    pub fn tile_grid(&self, level: usize) -> Option<TileGrid> {
+        if !self.orientation().is_upright() {
+            return None;
+        }
+        let (level, tile_data) = match &self.inner {
+            SlideReaderInner::Svs(r) => {
+                let data = r.get_level(level)?;
+                (&data.level, &data.tile_data)
+            }
+            SlideReaderInner::GenericTiff(r) => {
+                let data = r.get_level(level)?;
+                (&data.level, &data.tile_data)
+            }
+            SlideReaderInner::Plugin(_) => return None,
+        };
+        if level.is_stripped() {
+            return None;
+        }
+        Some(TileGrid::from_tile_data(
+            level.tiles_x,
+            level.tiles_y,
+            tile_data,
+        ))
+    }
+
+    /// Get the size of the slide file in bytes.
+    pub fn file_size(&self) -> u64 {
+        self.reader.size()
+    }
+
+    /// Get the storage backend's ETag for the slide file, if known.
+    ///
+    /// Recorded when the slide was opened; the slide is reopened when the
+    /// object is replaced.
+    pub fn source_etag(&self) -> Option<&str> {
+        self.etag.as_deref()
+    }
+
+    /// Get the ETag as a shared string, for cache keys.
+    pub(crate) fn shared_etag(&self) -> Option<Arc<str>> {
+        self.etag.clone()
+    }
+
+    /// Get the time the slide file was last modified, if known.
+    pub fn last_modified(&self) -> Option<std::time::SystemTime> {
+        self.reader.last_modified()
+    }
+
+    /// Get the slide's access counters.
+    pub fn heat(&self) -> &SlideHeat {
+        &self.heat
+    }
+
+    /// Get the slide's block cache capacity, in blocks.
+    pub fn block_cache_capacity(&self) -> usize {
+        self.reader.capacity()
+    }
+
+    /// Get the number of blocks in the slide's block cache.
+    pub fn cached_blocks(&self) -> usize {
+        self.reader.cached_blocks()
+    }
+
+    /// Get the slide's block cache block size, in bytes.
+    pub fn block_size(&self) -> usize {
+        self.reader.block_size()
+    }
+
+    /// Get the slide's I/O counters since it was opened.
+    pub fn io_stats(&self) -> BlockCacheStats {
+        self.reader.stats()
+    }
+
+    /// Get the bytes held by the slide's block cache.
+    pub fn block_cache_bytes(&self) -> usize {
+        self.reader.cached_bytes()
+    }
+
+    /// Estimate the bytes of parsed metadata kept for the slide.
+    ///
+    /// Dominated by the tile offset and byte count tables, so this counts
+    /// [`METADATA_BYTES_PER_TILE`] per source tile.
+    pub fn metadata_bytes(&self) -> usize {
+        let tiles: u64 = (0..self.level_count())
+            .filter_map(|level| self.tile_count(level))
+            .map(|(x, y)| x as u64 * y as u64)
+            .sum();
+        let mask = self.tissue_mask().map_or(0, |mask| mask.size_bytes());
+        (tiles.saturating_mul(METADATA_BYTES_PER_TILE) as usize).saturating_add(mask)
+    }
+
+    /// Get the slide's tissue mask, if it has been computed.
+    pub fn tissue_mask(&self) -> Option<Arc<TissueMask>> {
+        self.tissue_mask.read().unwrap().clone()
+    }
+
+    /// Record the slide's tissue mask.
+    ///
+    /// Once set, read-ahead skips tiles without tissue.
+    pub fn set_tissue_mask(&self, mask: Arc<TissueMask>) {
+        *self.tissue_mask.write().unwrap() = Some(mask);
+    }
+
+    /// Whether a tile is known to hold only background.
+    ///
+    /// Returns `false` until the slide's tissue mask has been computed.
+    pub fn is_background_tile(&self, level: usize, tile_x: u32, tile_y: u32) -> bool {
+        let Some(mask) = self.tissue_mask() else {
+            return false;
+        };
+        self.level_info(level)
+            .is_some_and(|info| !mask.tile_has_tissue(&info, tile_x, tile_y))
+    }
+
+    /// Read bytes of the original slide file through the block cache.
+    pub async fn read_raw(&self, offset: u64, len: usize) -> Result<Bytes, IoError> {
+        let result = self.reader.read_exact_at(offset, len).await;
+        if let Err(IoError::ObjectChanged(_)) = result {
+            self.replaced.store(true, Ordering::Relaxed);
         }
+        result
+    }
+
+    /// Whether a read found the slide file replaced since it was opened.
+    pub fn is_replaced(&self) -> bool {
+        self.replaced.load(Ordering::Relaxed)
+    }
+
+    /// Get the result of the last checksum verification, if any.
+    pub fn integrity(&self) -> Option<IntegrityReport> {
+        self.integrity.read().unwrap().clone()
+    }
+
+    /// Verify the slide against its checksum manifest and record the result.
+    ///
+    /// Sampled verification checks the blocks covering the header and the
+    /// first and center tiles of level 0 and the first tile of the lowest
+    /// resolution level.
+    pub async fn verify(
+        &self,
+        manifest: &ChecksumManifest,
+        mode: VerifyMode,
+    ) -> Result<IntegrityReport, IoError> {
+        let report = match mode {
+            VerifyMode::Sampled => {
+                verify_sampled(self.reader.as_ref(), manifest, &self.sample_ranges()).await?
+            }
+            VerifyMode::Full => verify_full(self.reader.as_ref(), manifest).await?,
+        };
+        self.set_integrity(report.clone());
+        Ok(report)
+    }
+
+    /// Get the custom response headers loaded from the slide's sidecar.
+    ///
+    /// Empty unless the registry loads headers sidecars (see
+    /// [`SlideRegistry::with_slide_headers`]).
+    pub fn custom_headers(&self) -> SlideHeaders {
+        self.headers.read().unwrap().clone()
+    }
+
+    fn set_custom_headers(&self, headers: SlideHeaders) {
+        *self.headers.write().unwrap() = headers;
+    }
+
+    /// Record a verification result.
+    fn set_integrity(&self, report: IntegrityReport) {
+        *self.integrity.write().unwrap() = Some(report);
+    }
+
+    /// Byte ranges checked by sampled verification.
+    fn sample_ranges(&self) -> Vec<(u64, u64)> {
+        let mut ranges = vec![(0, 1)];
+        let last_level = self.level_count().saturating_sub(1);
+        let (tiles_x, tiles_y) = self.tile_count(0).unwrap_or((1, 1));
+        let samples = [(0, 0, 0), (0, tiles_x / 2, tiles_y / 2), (last_level, 0, 0)];
+        ranges.extend(
+            samples
+                .iter()
+                .filter_map(|&(level, x, y)| self.tile_location(level, x, y)),
+        );
+        ranges
     }
 
     /// Find the best level for a given downsample factor.
     pub fn best_level_for_downsample(&self, downsample: f64) -> Option<usize> {
         match &self.inner {
-            SlideReaderInner::Svs(r) => SlideReader::best_level_for_downsample(r, downsample),
+            SlideReaderInner::Svs(r) => {
+                SlideReader::best_level_for_downsample(r.as_ref(), downsample)
+            }
             SlideReaderInner::GenericTiff(r) => {
-                SlideReader::best_level_for_downsample(r, downsample)
+                SlideReader::best_level_for_downsample(r.as_ref(), downsample)
             }
+            SlideReaderInner::Plugin(r) => r.best_level_for_downsample(downsample),
         }
     }
 
     /// Read a tile and prepare it for JPEG decoding.
     ///
+    /// Tiles missing from a sparse TIFF are served as background tiles, or
+    /// fail with [`TiffError::SparseTile`], depending on the registry's
+    /// [`SparseTiles`] policy. Stored tiles that can't be decoded while
+    /// composing the tile of a rotated slide fail with
+    /// [`TileError::DecodeError`].
+    ///
     /// # Arguments
     /// * `level` - Pyramid level index (0 = highest resolution)
     /// * `tile_x` - Tile X coordinate (0-indexed from left)
     /// * `tile_y` - Tile Y coordinate (0-indexed from top)
     ///
     /// # Returns
-    /// Complete JPEG data ready for decoding.
+    /// Complete JPEG data ready for decoding. Tiles of slides not stored
+    /// upright are composed from the stored tiles under them and returned
+    /// as uncompressed RGB.
     pub async fn read_tile(
         &self,
         level: usize,
         tile_x: u32,
         tile_y: u32,
+    ) -> Result<Bytes, TileError> {
+        let orientation = self.orientation();
+        if !orientation.is_upright() {
+            return self
+                .read_oriented_tile(orientation, level, tile_x, tile_y)
+                .await;
+        }
+
+        let result = self.read_stored_tile(level, tile_x, tile_y).await;
+        if result.is_ok() {
+            self.read_ahead(level, tile_x, tile_y);
+        }
+        let (tile_width, tile_height) = self.tile_size(level).unwrap_or((0, 0));
+        Ok(self.sparse_tiles.resolve(result, tile_width, tile_height)?)
+    }
+
+    /// Read a tile as stored in the file.
+    async fn read_stored_tile(
+        &self,
+        level: usize,
+        tile_x: u32,
+        tile_y: u32,
     ) -> Result<Bytes, TiffError> {
-        match &self.inner {
+        let result = match &self.inner {
             SlideReaderInner::Svs(r) => {
                 r.read_tile(self.reader.as_ref(), level, tile_x, tile_y)
                     .await
@@ -230,7 +706,179 @@ impl<R: RangeReader + 'static> CachedSlide<R> {
                 r.read_tile(self.reader.as_ref(), level, tile_x, tile_y)
                     .await
             }
+            SlideReaderInner::Plugin(r) => {
+                r.read_tile(self.reader.as_ref(), level, tile_x, tile_y)
+                    .await
+            }
+        };
+        if let Err(TiffError::Io(IoError::ObjectChanged(_))) = result {
+            self.replaced.store(true, Ordering::Relaxed);
+        }
+        result
+    }
+
+    /// Read a displayed tile of a slide stored rotated or mirrored.
+    ///
+    /// The stored tiles under the displayed tile are decoded, cropped to
+    /// the area it shows and turned upright. Edge tiles are padded with
+    /// white to the full tile size.
+    async fn read_oriented_tile(
+        &self,
+        orientation: Orientation,
+        level: usize,
+        tile_x: u32,
+        tile_y: u32,
+    ) -> Result<Bytes, TileError> {
+        let (Some(stored), Some(info)) = (self.stored_level_info(level), self.level_info(level))
+        else {
+            return Err(TiffError::InvalidTagValue {
+                tag: "level",
+                message: format!("level {} out of range", level),
+            }
+            .into());
+        };
+        if tile_x >= info.tiles_x || tile_y >= info.tiles_y {
+            return Err(TiffError::InvalidTagValue {
+                tag: "tile",
+                message: format!(
+                    "tile ({}, {}) out of range for level {}",
+                    tile_x, tile_y, level
+                ),
+            }
+            .into());
+        }
+
+        // Displayed area of the tile, then the stored area under it
+        let x = tile_x * info.tile_width;
+        let y = tile_y * info.tile_height;
+        let area = (
+            x,
+            y,
+            info.tile_width.min(info.width - x),
+            info.tile_height.min(info.height - y),
+        );
+        let (sx, sy, sw, sh) = orientation.to_stored_rect(area, info.width, info.height);
+
+        let mut sources = Vec::new();
+        for ty in sy / stored.tile_height..=(sy + sh - 1) / stored.tile_height {
+            for tx in sx / stored.tile_width..=(sx + sw - 1) / stored.tile_width {
+                let data = self.read_stored_tile(level, tx, ty).await;
+                let data =
+                    self.sparse_tiles
+                        .resolve(data, stored.tile_width, stored.tile_height)?;
+                let left = (tx * stored.tile_width) as i64 - sx as i64;
+                let top = (ty * stored.tile_height) as i64 - sy as i64;
+                sources.push((left, top, data));
+            }
         }
+
+        let (tile_width, tile_height) = (info.tile_width, info.tile_height);
+        let composed = self
+            .encode_pool
+            .run(move || {
+                let mut region = RgbImage::from_pixel(sw, sh, Rgb([255; 3]));
+                for (left, top, data) in sources {
+                    let pixels = decode_tile(&data)?;
+                    imageops::replace(&mut region, &pixels.into_rgb8(), left, top);
+                }
+                let upright = orientation.apply(DynamicImage::ImageRgb8(region));
+
+                let mut tile = RgbImage::from_pixel(tile_width, tile_height, Rgb([255; 3]));
+                imageops::replace(&mut tile, &upright.into_rgb8(), 0, 0);
+                Ok(tile)
+            })
+            .await
+            .map_err(|err| match err {
+                TileError::DecodeError { message } => TileError::DecodeError {
+                    message: format!(
+                        "tile ({}, {}) of level {}: {}",
+                        tile_x, tile_y, level, message
+                    ),
+                },
+                other => other,
+            })?;
+
+        let layout = RawTileLayout::uncompressed_rgb(tile_width, tile_height);
+        Ok(layout.wrap(composed.as_raw()))
+    }
+
+    /// Fetch the next tiles of a sequential scan into the block cache in
+    /// the background; see [`ReadAhead`].
+    fn read_ahead(&self, level: usize, tile_x: u32, tile_y: u32) {
+        let Some((tiles_x, tiles_y)) = self.tile_count(level) else {
+            return;
+        };
+        let tiles_x = tiles_x as u64;
+        let index = tile_y as u64 * tiles_x + tile_x as u64;
+        let Some(ahead) = self
+            .readahead
+            .record(level, index, tiles_x * tiles_y as u64)
+        else {
+            return;
+        };
+
+        let mask = self.tissue_mask();
+        let info = self.level_info(level);
+        let ranges: Vec<(u64, u64)> = ahead
+            .map(|i| ((i % tiles_x) as u32, (i / tiles_x) as u32))
+            .filter(|&(x, y)| match (&mask, &info) {
+                (Some(mask), Some(info)) => mask.tile_has_tissue(info, x, y),
+                _ => true,
+            })
+            .filter_map(|(x, y)| self.tile_location(level, x, y))
+            .filter(|&(_, len)| len > 0)
+            .collect();
+        if ranges.is_empty() {
+            return;
+        }
+
+        let reader = self.reader.clone();
+        tokio::spawn(async move {
+            let mut reads = JoinSet::new();
+            for (offset, len) in ranges {
+                let reader = reader.clone();
+                reads.spawn(async move {
+                    // Failures surface when the tile itself is read
+                    let _ = reader.read_exact_at(offset, len as usize).await;
+                });
+            }
+            while reads.join_next().await.is_some() {}
+        });
+    }
+
+    /// Stream every tile of a level using the default options.
+    ///
+    /// Tiles are yielded in row-major order with up to
+    /// [`DEFAULT_TILE_STREAM_CONCURRENCY`](super::DEFAULT_TILE_STREAM_CONCURRENCY)
+    /// reads in flight. Must be called from within a Tokio runtime.
+    ///
+    /// # Errors
+    /// Returns an error if `level` is out of range.
+    pub fn tiles(self: &Arc<Self>, level: usize) -> Result<TileStream, TiffError> {
+        self.tiles_with_options(level, TileStreamOptions::default())
+    }
+
+    /// Stream every tile of a level with custom concurrency and ordering.
+    ///
+    /// # Errors
+    /// Returns an error if `level` is out of range.
+    pub fn tiles_with_options(
+        self: &Arc<Self>,
+        level: usize,
+        options: TileStreamOptions,
+    ) -> Result<TileStream, TiffError> {
+        let (tiles_x, tiles_y) = self.tile_count(level).ok_or(TiffError::InvalidTagValue {
+            tag: "level",
+            message: format!("level {} out of range", level),
+        })?;
+
+        Ok(TileStream::spawn(
+            Arc::clone(self),
+            level,
+            tiles_x,
+            tiles_y,
+            options,
+        ))
     }
 }
 
@@ -238,6 +886,116 @@ impl<R: RangeReader + 'static> CachedSlide<R> {
 // SlideRegistry
 // =============================================================================
 
+/// Default maximum number of slides opened concurrently.
+///
+/// Each open costs several storage round trips and some CPU for parsing, so
+/// an unbounded burst of opens (e.g. a crawler touching thousands of new
+/// slides) can starve tile requests for already-open slides.
+pub const DEFAULT_MAX_CONCURRENT_OPENS: usize = 32;
+
+/// Default time an open may wait for a free slot before failing.
+pub const DEFAULT_OPEN_QUEUE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Snapshot of slide open activity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SlideOpenStats {
+    /// Opens currently in progress
+    pub in_flight: usize,
+
+    /// Opens waiting for a free slot
+    pub queued: usize,
+
+    /// Maximum number of concurrent opens
+    pub max_concurrent: usize,
+}
+
+/// Memory held by open slides.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct SlideMemoryUsage {
+    /// Open slides
+    pub slides: usize,
+
+    /// Bytes of cached blocks, across slides
+    pub block_cache_bytes: usize,
+
+    /// Estimated bytes of parsed metadata, across slides
+    pub metadata_bytes: usize,
+}
+
+/// Cache state of one open slide.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SlideCacheEntry {
+    /// Slide identifier
+    pub slide_id: String,
+
+    /// Current classification
+    pub temperature: SlideTemperature,
+
+    /// Exponentially decayed access count
+    pub score: f64,
+
+    /// Accesses since the slide was opened
+    pub accesses: u64,
+
+    /// Seconds since the last access
+    pub idle_secs: f64,
+
+    /// Block cache capacity, in blocks
+    pub block_cache_capacity: usize,
+
+    /// Blocks currently cached
+    pub cached_blocks: usize,
+
+    /// Block size, in bytes
+    pub block_size: usize,
+
+    /// I/O counters since the slide was opened
+    pub io: BlockCacheStats,
+}
+
+impl SlideCacheEntry {
+    fn new<R: RangeReader + 'static>(
+        slide_id: String,
+        slide: &CachedSlide<R>,
+        heat: &HeatSnapshot,
+    ) -> Self {
+        Self {
+            slide_id,
+            temperature: heat.temperature,
+            score: heat.score,
+            accesses: heat.accesses,
+            idle_secs: heat.idle.as_secs_f64(),
+            block_cache_capacity: slide.block_cache_capacity(),
+            cached_blocks: slide.cached_blocks(),
+            block_size: slide.block_size(),
+            io: slide.io_stats(),
+        }
+    }
+}
+
+/// Increments a gauge for the lifetime of the guard.
+struct GaugeGuard<'a>(&'a AtomicUsize);
+
+impl<'a> GaugeGuard<'a> {
+    fn new(gauge: &'a AtomicUsize) -> Self {
+        gauge.fetch_add(1, Ordering::Relaxed);
+        Self(gauge)
+    }
+}
+
+impl Drop for GaugeGuard<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Number of buffered cache hits that triggers an opportunistic LRU update.
+const TOUCH_DRAIN_THRESHOLD: usize = 64;
+
+/// Maximum number of buffered cache hits; further hits are not recorded
+/// until the buffer is drained.
+const TOUCH_BUFFER_CAPACITY: usize = 256;
+
 /// Registry for managing slide lifecycle and caching.
 ///
 /// The registry:
@@ -245,12 +1003,30 @@ impl<R: RangeReader + 'static> CachedSlide<R> {
 /// - Creates readers on-demand with format auto-detection
 /// - Wraps readers in BlockCache for efficient I/O
 /// - Uses singleflight to prevent duplicate opens for the same slide
+/// - Bounds the number of slides opened concurrently; further opens queue
+///   for a slot and fail with [`FormatError::OpenQueueTimeout`] if none frees
+///   up in time
+/// - Quarantines slides that repeatedly fail to parse or decode; they fail
+///   with [`FormatError::Quarantined`] until released
+/// - Remembers slides that failed to open because they are missing or don't
+///   parse, failing further opens with the same error until the TTL expires
+///
+/// # Lookup Fast Path
+///
+/// Cache hits only take a shared lock and peek, so concurrent lookups of hot
+/// slides don't serialize. The LRU "touch" for a hit is recorded in a small
+/// buffer and applied later under the write lock (before inserts, or once
+/// enough hits accumulate). The buffer is lossy: when it is busy or full,
+/// hits are not recorded, which only makes eviction order approximate.
 pub struct SlideRegistry<S: SlideSource> {
     /// The source for creating range readers
     source: S,
 
     /// Cached slides indexed by slide ID
-    cache: RwLock<LruCache<String, Arc<CachedSlide<S::Reader>>>>,
+    cache: SyncRwLock<LruCache<String, Arc<CachedSlide<S::Reader>>>>,
+
+    /// Cache hits not yet applied to the LRU order
+    touches: SyncMutex<Vec<String>>,
 
     /// In-flight opens for singleflight pattern
     in_flight: Mutex<HashMap<String, Arc<InFlightState<S::Reader>>>>,
@@ -260,6 +1036,63 @@ pub struct SlideRegistry<S: SlideSource> {
 
     /// Block cache capacity per slide
     block_cache_capacity: usize,
+
+    /// Slots for concurrent slide opens
+    open_permits: Semaphore,
+
+    /// Maximum number of concurrent opens
+    max_concurrent_opens: usize,
+
+    /// How long an open may wait for a slot
+    open_queue_timeout: Duration,
+
+    /// Opens currently in progress
+    opens_in_flight: AtomicUsize,
+
+    /// Opens waiting for a slot
+    opens_queued: AtomicUsize,
+
+    /// Slides quarantined after repeated failures
+    quarantine: SlideQuarantine,
+
+    /// Slides that recently failed to open with a permanent error
+    negative: NegativeCache,
+
+    /// Last validation result of each slide opened
+    conformance: ConformanceCache,
+
+    /// Additional formats, tried in order before built-in detection
+    format_plugins: Vec<Arc<dyn FormatPlugin>>,
+
+    /// Whether to verify checksum manifests when slides are opened
+    verify_checksums: bool,
+
+    /// Whether to verify every block read against the checksum manifest
+    verify_reads: bool,
+
+    /// Tiles read ahead of sequential scans (0 = off)
+    readahead_tiles: usize,
+
+    /// Whether to load headers sidecars when slides are opened
+    slide_headers: bool,
+
+    /// Hot/cold block cache budgets, if adaptive caching is enabled
+    adaptive: Option<AdaptiveCacheConfig>,
+
+    /// Maximum number of cached slides per tenant (slide ID prefix)
+    tenant_slide_quotas: HashMap<String, usize>,
+
+    /// Heuristics identifying the pyramid levels of TIFF-based slides
+    pyramid_detection: PyramidDetectOptions,
+
+    /// What to serve for tiles missing from sparse TIFFs
+    sparse_tiles: SparseTiles,
+
+    /// Blocking pool shared with the tile service
+    encode_pool: EncodePool,
+
+    /// Where slide open and eviction events are published
+    events: EventBus,
 }
 
 /// State for an in-flight slide open operation.
@@ -300,12 +1133,321 @@ impl<S: SlideSource> SlideRegistry<S> {
     ) -> Self {
         Self {
             source,
-            cache: RwLock::new(LruCache::new(
+            cache: SyncRwLock::new(LruCache::new(
                 std::num::NonZeroUsize::new(slide_cache_capacity).unwrap(),
             )),
+            touches: SyncMutex::new(Vec::new()),
             in_flight: Mutex::new(HashMap::new()),
             block_size,
             block_cache_capacity,
+            open_permits: Semaphore::new(DEFAULT_MAX_CONCURRENT_OPENS),
+            max_concurrent_opens: DEFAULT_MAX_CONCURRENT_OPENS,
+            open_queue_timeout: DEFAULT_OPEN_QUEUE_TIMEOUT,
+            opens_in_flight: AtomicUsize::new(0),
+            opens_queued: AtomicUsize::new(0),
+            quarantine: SlideQuarantine::default(),
+            negative: NegativeCache::default(),
+            conformance: ConformanceCache::new(),
+            format_plugins: Vec::new(),
+            pyramid_detection: PyramidDetectOptions::default(),
+            sparse_tiles: SparseTiles::default(),
+            encode_pool: EncodePool::default(),
+            verify_checksums: false,
+            verify_reads: false,
+            readahead_tiles: DEFAULT_READAHEAD_TILES,
+            slide_headers: false,
+            adaptive: None,
+            tenant_slide_quotas: HashMap::new(),
+            events: EventBus::new(),
+        }
+    }
+
+    /// Set the maximum number of slides opened concurrently (at least 1).
+    pub fn with_max_concurrent_opens(mut self, max_concurrent_opens: usize) -> Self {
+        let max_concurrent_opens = max_concurrent_opens.max(1);
+        self.open_permits = Semaphore::new(max_concurrent_opens);
+        self.max_concurrent_opens = max_concurrent_opens;
+        self
+    }
+
+    /// Set how long an open may wait for a free slot before failing.
+    pub fn with_open_queue_timeout(mut self, timeout: Duration) -> Self {
+        self.open_queue_timeout = timeout;
+        self
+    }
+
+    /// Set how many failures quarantine a slide (0 disables quarantining).
+    pub fn with_quarantine_threshold(mut self, threshold: u32) -> Self {
+        self.quarantine = SlideQuarantine::new(threshold, DEFAULT_QUARANTINE_WINDOW);
+        self
+    }
+
+    /// Set how long slides that failed to open are remembered (zero
+    /// disables negative caching).
+    ///
+    /// While remembered, opening the slide fails immediately with the same
+    /// error instead of reaching storage; see [`NegativeCache`].
+    pub fn with_negative_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.negative = NegativeCache::new(ttl);
+        self
+    }
+
+    /// Register an additional slide format.
+    ///
+    /// Plugins are tried in registration order before the built-in SVS and
+    /// generic TIFF detection.
+    pub fn with_format_plugin(mut self, plugin: Arc<dyn FormatPlugin>) -> Self {
+        self.format_plugins.push(plugin);
+        self
+    }
+
+    /// Set the heuristics identifying the pyramid levels of SVS and generic
+    /// TIFF slides.
+    ///
+    /// Applies to slides opened afterwards; see [`PyramidDetectOptions`].
+    pub fn with_pyramid_detection(mut self, options: PyramidDetectOptions) -> Self {
+        self.pyramid_detection = options;
+        self
+    }
+
+    /// Set what to serve for tiles missing from sparse TIFFs (a white
+    /// background tile by default).
+    ///
+    /// Applies to slides opened afterwards; see [`SparseTiles`].
+    pub fn with_sparse_tiles(mut self, policy: SparseTiles) -> Self {
+        self.sparse_tiles = policy;
+        self
+    }
+
+    /// Verify slides against their checksum manifests when they are opened.
+    ///
+    /// Verification is sampled; see [`CachedSlide::verify`]. Failures are
+    /// logged and reported by [`CachedSlide::integrity`] but don't prevent
+    /// serving.
+    pub fn with_checksum_verification(mut self, enabled: bool) -> Self {
+        self.verify_checksums = enabled;
+        self
+    }
+
+    /// Verify every block read from storage against the slide's checksum
+    /// manifest.
+    ///
+    /// Slides whose manifest has block digests get a block cache with the
+    /// manifest's block size (and the same byte budget), and each block is
+    /// checked when it is fetched: a mismatch fails the read with
+    /// [`IoError::Corrupted`] instead of serving the bytes. Slides without
+    /// block digests are served unverified.
+    pub fn with_read_verification(mut self, enabled: bool) -> Self {
+        self.verify_reads = enabled;
+        self
+    }
+
+    /// Compose tiles of rotated slides on `pool`, sharing its worker limit.
+    ///
+    /// A [`TileService`](crate::tile::TileService) built from this registry
+    /// decodes and encodes on the same pool.
+    pub fn with_encode_pool(mut self, pool: EncodePool) -> Self {
+        self.encode_pool = pool;
+        self
+    }
+
+    /// Get the blocking pool tiles are decoded on.
+    pub fn encode_pool(&self) -> &EncodePool {
+        &self.encode_pool
+    }
+
+    /// Set how many tiles are fetched into the block cache ahead of a
+    /// sequential (row-major) tile scan; 0 disables read-ahead.
+    ///
+    /// Applies to slides opened afterwards; see [`ReadAhead`].
+    pub fn with_readahead(mut self, tiles: usize) -> Self {
+        self.readahead_tiles = tiles;
+        self
+    }
+
+    /// Load each slide's `{slide_id}.headers.json` sidecar when it is
+    /// opened; see [`CachedSlide::custom_headers`].
+    ///
+    /// A missing sidecar sets no headers and an invalid one is logged and
+    /// ignored. Failing to read the sidecar fails the open.
+    pub fn with_slide_headers(mut self, enabled: bool) -> Self {
+        self.slide_headers = enabled;
+        self
+    }
+
+    /// Size block caches by how heavily each slide is accessed.
+    ///
+    /// Slides are classified hot, warm or cold by [`classify_slides`]
+    /// (run it periodically), and their block caches resized to the budget
+    /// of their class. Hot slides are also kept in the slide cache in
+    /// preference to others.
+    ///
+    /// [`classify_slides`]: Self::classify_slides
+    pub fn with_adaptive_caching(mut self, config: AdaptiveCacheConfig) -> Self {
+        self.adaptive = Some(config);
+        self
+    }
+
+    /// Cache at most `max_slides` slides of a tenant at once.
+    ///
+    /// A tenant's slides are those whose ID starts with `{tenant}/` (see
+    /// [`TenantSlideSource`](super::TenantSlideSource)). When the tenant is
+    /// at its quota, opening another of its slides evicts its least recently
+    /// used one rather than another tenant's, so one lab can't push every
+    /// other lab's slides out of a shared cache.
+    pub fn with_tenant_slide_quota(mut self, tenant: impl Into<String>, max_slides: usize) -> Self {
+        self.tenant_slide_quotas
+            .insert(tenant.into(), max_slides.max(1));
+        self
+    }
+
+    /// Get the bus slide open, warm-up and eviction events are published on.
+    pub fn events(&self) -> &EventBus {
+        &self.events
+    }
+
+    /// Get the adaptive caching configuration, if enabled.
+    pub fn adaptive_caching(&self) -> Option<&AdaptiveCacheConfig> {
+        self.adaptive.as_ref()
+    }
+
+    /// Whether headers sidecars are loaded when slides are opened.
+    pub fn slide_headers_enabled(&self) -> bool {
+        self.slide_headers
+    }
+
+    /// Get the names of registered format plugins.
+    pub fn format_plugins(&self) -> Vec<&'static str> {
+        self.format_plugins.iter().map(|p| p.name()).collect()
+    }
+
+    /// Get the slide quarantine.
+    pub fn quarantine(&self) -> &SlideQuarantine {
+        &self.quarantine
+    }
+
+    /// Get the cache of slides that recently failed to open.
+    pub fn negative_cache(&self) -> &NegativeCache {
+        &self.negative
+    }
+
+    /// Get the last validation result of each slide opened.
+    pub fn conformance(&self) -> &ConformanceCache {
+        &self.conformance
+    }
+
+    /// Record a failure attributed to a slide.
+    ///
+    /// If the failure quarantines the slide, it is also dropped from the
+    /// cache. Returns `true` if the slide was quarantined.
+    pub fn record_failure(&self, slide_id: &str, reason: &str) -> bool {
+        let quarantined = self.quarantine.record_failure(slide_id, reason);
+        if quarantined {
+            self.cache.write().unwrap().pop(slide_id);
+        }
+        quarantined
+    }
+
+    /// Score and classify every cached slide.
+    ///
+    /// With adaptive caching enabled, each slide's block cache is resized to
+    /// the budget of its class. Without it, slides are classified with the
+    /// default thresholds for reporting only. Entries are in LRU order, most
+    /// recently used first.
+    pub fn classify_slides(&self) -> Vec<SlideCacheEntry> {
+        let config = self
+            .adaptive
+            .unwrap_or_else(|| AdaptiveCacheConfig::new(self.block_cache_capacity));
+        let slides: Vec<_> = self
+            .cache
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(id, slide)| (id.clone(), slide.clone()))
+            .collect();
+
+        let now = Instant::now();
+        slides
+            .into_iter()
+            .map(|(slide_id, slide)| {
+                let heat = slide.heat.score(&config, now);
+                if self.adaptive.is_some() {
+                    let budget = config.budget(heat.temperature);
+                    if slide.reader.capacity() != budget {
+                        slide.reader.resize(budget);
+                    }
+                }
+                SlideCacheEntry::new(slide_id, &slide, &heat)
+            })
+            .collect()
+    }
+
+    /// Measure the memory held by open slides.
+    pub fn memory_usage(&self) -> SlideMemoryUsage {
+        let cache = self.cache.read().unwrap();
+        cache.iter().fold(
+            SlideMemoryUsage {
+                slides: cache.len(),
+                ..SlideMemoryUsage::default()
+            },
+            |mut usage, (_, slide)| {
+                usage.block_cache_bytes += slide.block_cache_bytes();
+                usage.metadata_bytes += slide.metadata_bytes();
+                usage
+            },
+        )
+    }
+
+    /// Free memory held by open slides.
+    ///
+    /// Every slide's block cache gives up a share of `block_bytes`
+    /// proportional to its size; capacities are unchanged. Then least
+    /// recently used slides are closed until `metadata_bytes` of metadata
+    /// are freed. Returns the bytes freed, including the blocks of closed
+    /// slides.
+    pub fn shed_memory(&self, block_bytes: usize, metadata_bytes: usize) -> usize {
+        let mut freed = 0;
+
+        if block_bytes > 0 {
+            let slides: Vec<_> = self
+                .cache
+                .read()
+                .unwrap()
+                .iter()
+                .map(|(_, slide)| (slide.clone(), slide.block_cache_bytes()))
+                .collect();
+            let total = slides.iter().map(|(_, bytes)| bytes).sum::<usize>().max(1);
+            for (slide, bytes) in slides {
+                let share = (block_bytes as u128 * bytes as u128).div_ceil(total as u128);
+                freed += slide.reader.evict_bytes(share as usize);
+            }
+        }
+
+        if metadata_bytes > 0 {
+            let mut cache = self.cache.write().unwrap();
+            self.apply_touches(&mut cache);
+            let mut metadata_freed = 0;
+            while metadata_freed < metadata_bytes {
+                let Some((slide_id, slide)) = cache.pop_lru() else {
+                    break;
+                };
+                info!(slide_id = %slide_id, "Closing slide to stay within the memory budget");
+                self.publish_eviction(&slide_id, EvictionReason::Memory);
+                metadata_freed += slide.metadata_bytes();
+                freed += slide.block_cache_bytes();
+            }
+            freed += metadata_freed;
+        }
+
+        freed
+    }
+
+    /// Get current slide open activity.
+    pub fn open_stats(&self) -> SlideOpenStats {
+        SlideOpenStats {
+            in_flight: self.opens_in_flight.load(Ordering::Relaxed),
+            queued: self.opens_queued.load(Ordering::Relaxed),
+            max_concurrent: self.max_concurrent_opens,
         }
     }
 
@@ -325,12 +1467,34 @@ impl<S: SlideSource> SlideRegistry<S> {
         &self,
         slide_id: &str,
     ) -> Result<Arc<CachedSlide<S::Reader>>, FormatError> {
+        let slide = self.lookup_or_open(slide_id).await?;
+        slide.heat.record();
+        Ok(slide)
+    }
+
+    /// Get a slide from the cache, or open it with singleflight.
+    async fn lookup_or_open(
+        &self,
+        slide_id: &str,
+    ) -> Result<Arc<CachedSlide<S::Reader>>, FormatError> {
+        validate_key(slide_id)?;
+
+        if self.quarantine.is_quarantined(slide_id) {
+            return Err(FormatError::Quarantined {
+                slide_id: slide_id.to_string(),
+            });
+        }
+
         // Fast path: check cache
-        {
-            let mut cache = self.cache.write().await;
-            if let Some(slide) = cache.get(slide_id) {
-                return Ok(slide.clone());
-            }
+        if let Some(slide) = self.cached(slide_id) {
+            return Ok(slide);
+        }
+
+        // Recently missing or unparseable slides fail without reaching
+        // storage, but still count toward quarantine
+        if let Some(err) = self.negative.get(slide_id) {
+            self.record_open_failure(slide_id, &err);
+            return Err(err);
         }
 
         // Slow path: check in_flight or become leader
@@ -351,7 +1515,33 @@ impl<S: SlideSource> SlideRegistry<S> {
                     drop(in_flight);
 
                     // Perform the open
-                    let result = self.open_slide_internal(slide_id).await;
+                    self.events.publish(|| ServerEvent::SlideOpening {
+                        slide_id: slide_id.to_string(),
+                    });
+                    let started = Instant::now();
+                    let mut result = self.open_slide_bounded(slide_id).await;
+
+                    // Headers are loaded before the slide is shared, so no
+                    // response is served without them
+                    if let (true, Ok(slide)) = (self.slide_headers, &result) {
+                        if let Err(e) = self.load_headers(slide_id, slide).await {
+                            result = Err(FormatError::Io(e));
+                        }
+                    }
+
+                    if let Err(ref err) = result {
+                        self.record_open_failure(slide_id, err);
+                        self.negative.record(slide_id, err);
+                    }
+
+                    // Remember the validation outcome for slide listings
+                    let conformance = match result {
+                        Ok(ref slide) => Some(SlideConformance::opened(slide.warnings().to_vec())),
+                        Err(ref err) => SlideConformance::from_open_error(err),
+                    };
+                    if let Some(conformance) = conformance {
+                        self.conformance.record(slide_id, conformance);
+                    }
 
                     // Store result and update cache
                     {
@@ -359,9 +1549,47 @@ impl<S: SlideSource> SlideRegistry<S> {
                         *result_guard = Some(result.clone());
                     }
 
+                    if let (true, Ok(slide)) = (self.verify_checksums, &result) {
+                        self.verify_on_open(slide_id, slide).await;
+                    }
+
                     if let Ok(ref slide) = result {
-                        let mut cache = self.cache.write().await;
-                        cache.put(slide_id.to_string(), slide.clone());
+                        let mut evicted = Vec::new();
+                        {
+                            let mut cache = self.cache.write().unwrap();
+                            self.apply_touches(&mut cache);
+                            evicted.extend(
+                                self.evict_over_tenant_quota(&mut cache, slide_id)
+                                    .map(|id| (id, EvictionReason::TenantQuota)),
+                            );
+                            if self.adaptive.is_some() {
+                                evicted.extend(
+                                    Self::evict_unpinned(&mut cache, slide_id)
+                                        .map(|id| (id, EvictionReason::Capacity)),
+                                );
+                            }
+                            if let Some((id, _)) = cache.push(slide_id.to_string(), slide.clone()) {
+                                if id != slide_id {
+                                    evicted.push((id, EvictionReason::Capacity));
+                                }
+                            }
+                        }
+                        for (id, reason) in evicted {
+                            self.publish_eviction(&id, reason);
+                        }
+                    }
+
+                    match result {
+                        Ok(ref slide) => self.events.publish(|| ServerEvent::SlideOpened {
+                            slide_id: slide_id.to_string(),
+                            format: slide.format().name().to_string(),
+                            levels: slide.level_count(),
+                            elapsed_ms: started.elapsed().as_millis() as u64,
+                        }),
+                        Err(ref err) => self.events.publish(|| ServerEvent::SlideFailed {
+                            slide_id: slide_id.to_string(),
+                            error: err.to_string(),
+                        }),
                     }
 
                     // Clean up in_flight and notify waiters
@@ -378,70 +1606,492 @@ impl<S: SlideSource> SlideRegistry<S> {
             // Wait for the leader to finish
             state.notify.notified().await;
 
-            // Check if result is available
-            let result_guard = state.result.lock().await;
-            if let Some(ref result) = *result_guard {
-                return result.clone();
+            // Check if result is available
+            let result_guard = state.result.lock().await;
+            if let Some(ref result) = *result_guard {
+                return result.clone();
+            }
+
+            // Result not yet available, loop back (shouldn't normally happen)
+        }
+    }
+
+    /// Publish that a slide was closed.
+    fn publish_eviction(&self, slide_id: &str, reason: EvictionReason) {
+        self.events.publish(|| ServerEvent::SlideEvicted {
+            slide_id: slide_id.to_string(),
+            reason,
+        });
+    }
+
+    /// Count a failed open toward quarantine.
+    ///
+    /// Parse failures are properties of the file and count; storage errors
+    /// don't.
+    fn record_open_failure(&self, slide_id: &str, err: &FormatError) {
+        if let FormatError::Tiff(err) = err {
+            if !matches!(err, TiffError::Io(_)) {
+                self.record_failure(slide_id, &err.to_string());
+            }
+        }
+    }
+
+    /// Get a slide if it is already open, without opening it or counting
+    /// an access.
+    ///
+    /// Slides found replaced in storage are dropped and not returned.
+    pub fn cached_slide(&self, slide_id: &str) -> Option<Arc<CachedSlide<S::Reader>>> {
+        let slide = self.cache.read().unwrap().peek(slide_id).cloned()?;
+        if slide.is_replaced() {
+            self.drop_replaced(slide_id, &slide);
+            return None;
+        }
+        Some(slide)
+    }
+
+    /// Look up a cached slide under the shared lock, recording the hit.
+    fn cached(&self, slide_id: &str) -> Option<Arc<CachedSlide<S::Reader>>> {
+        let slide = self.cached_slide(slide_id)?;
+        self.record_touch(slide_id);
+        Some(slide)
+    }
+
+    /// Drop a slide whose file was replaced, unless it was reopened already.
+    fn drop_replaced(&self, slide_id: &str, slide: &Arc<CachedSlide<S::Reader>>) {
+        let mut cache = self.cache.write().unwrap();
+        if cache
+            .peek(slide_id)
+            .is_some_and(|cached| Arc::ptr_eq(cached, slide))
+        {
+            cache.pop(slide_id);
+            self.conformance.forget(slide_id);
+            info!(slide_id, "Slide replaced in storage; reopening");
+            self.publish_eviction(slide_id, EvictionReason::Replaced);
+        }
+    }
+
+    /// Buffer an LRU touch, draining the buffer if it has grown large.
+    fn record_touch(&self, slide_id: &str) {
+        let pending = match self.touches.try_lock() {
+            Ok(mut touches) => {
+                if touches.len() < TOUCH_BUFFER_CAPACITY {
+                    touches.push(slide_id.to_string());
+                }
+                touches.len()
+            }
+            // Another task is recording; dropping this touch is fine
+            Err(_) => return,
+        };
+
+        if pending >= TOUCH_DRAIN_THRESHOLD {
+            if let Ok(mut cache) = self.cache.try_write() {
+                self.apply_touches(&mut cache);
+            }
+        }
+    }
+
+    /// Apply buffered touches to the LRU order.
+    ///
+    /// Must be called with the cache write lock held.
+    fn apply_touches(&self, cache: &mut LruCache<String, Arc<CachedSlide<S::Reader>>>) {
+        let touches = std::mem::take(&mut *self.touches.lock().unwrap());
+        for slide_id in &touches {
+            cache.promote(slide_id.as_str());
+        }
+    }
+
+    /// Make room for a new slide by evicting the least recently used slide
+    /// that isn't hot.
+    ///
+    /// Hot slides keep their parsed metadata while they stay hot. If every
+    /// cached slide is hot, the insert evicts the least recently used one as
+    /// usual. Returns the evicted slide's ID.
+    fn evict_unpinned(
+        cache: &mut LruCache<String, Arc<CachedSlide<S::Reader>>>,
+        slide_id: &str,
+    ) -> Option<String> {
+        if cache.len() < cache.cap().get() || cache.contains(slide_id) {
+            return None;
+        }
+        let victim = cache
+            .iter()
+            .rev()
+            .find(|(_, slide)| slide.heat.temperature() != SlideTemperature::Hot)
+            .map(|(id, _)| id.clone())?;
+        cache.pop(&victim);
+        Some(victim)
+    }
+
+    /// Make room for a slide of a tenant at its quota by evicting the
+    /// tenant's least recently used slide. Returns the evicted slide's ID.
+    fn evict_over_tenant_quota(
+        &self,
+        cache: &mut LruCache<String, Arc<CachedSlide<S::Reader>>>,
+        slide_id: &str,
+    ) -> Option<String> {
+        let (tenant, quota) = slide_tenant(slide_id)
+            .and_then(|tenant| Some((tenant, *self.tenant_slide_quotas.get(tenant)?)))?;
+        if cache.contains(slide_id) {
+            return None;
+        }
+        // Iteration runs from most to least recently used
+        let cached: Vec<&String> = cache
+            .iter()
+            .filter(|(id, _)| slide_tenant(id) == Some(tenant))
+            .map(|(id, _)| id)
+            .collect();
+        if cached.len() < quota {
+            return None;
+        }
+        let victim = cached.last().map(|id| id.to_string())?;
+        cache.pop(&victim);
+        Some(victim)
+    }
+
+    /// Open a slide once a concurrent-open slot is available.
+    async fn open_slide_bounded(
+        &self,
+        slide_id: &str,
+    ) -> Result<Arc<CachedSlide<S::Reader>>, FormatError> {
+        let _permit = match self.open_permits.try_acquire() {
+            Ok(permit) => permit,
+            Err(_) => {
+                let _queued = GaugeGuard::new(&self.opens_queued);
+                let started = Instant::now();
+                match tokio::time::timeout(self.open_queue_timeout, self.open_permits.acquire())
+                    .await
+                {
+                    Ok(Ok(permit)) => permit,
+                    // Timed out (the semaphore is never closed)
+                    _ => {
+                        return Err(FormatError::OpenQueueTimeout {
+                            slide_id: slide_id.to_string(),
+                            waited_ms: started.elapsed().as_millis() as u64,
+                        });
+                    }
+                }
+            }
+        };
+
+        let _in_flight = GaugeGuard::new(&self.opens_in_flight);
+        self.open_slide_internal(slide_id).await
+    }
+
+    /// Open a slide without caching (internal implementation).
+    async fn open_slide_internal(
+        &self,
+        slide_id: &str,
+    ) -> Result<Arc<CachedSlide<S::Reader>>, FormatError> {
+        // Create the underlying reader
+        let reader =
+            timing::measure(Phase::StorageHead, self.source.create_reader(slide_id)).await?;
+
+        // Wrap in block cache
+        let cached_reader = Arc::new(self.block_cache(slide_id, reader).await?);
+
+        timing::measure(Phase::IfdParse, self.parse_slide(slide_id, cached_reader)).await
+    }
+
+    /// Detect a slide's format and parse its directories.
+    async fn parse_slide(
+        &self,
+        slide_id: &str,
+        cached_reader: Arc<BlockCache<S::Reader>>,
+    ) -> Result<Arc<CachedSlide<S::Reader>>, FormatError> {
+        // Registered plugins take precedence over built-in detection
+        for plugin in &self.format_plugins {
+            if plugin.detect(slide_id, cached_reader.as_ref()).await? {
+                let files = SourceFiles(&self.source);
+                let inner = plugin
+                    .open(slide_id, cached_reader.as_ref(), &files)
+                    .await?;
+                return Ok(Arc::new(CachedSlide {
+                    format: SlideFormat::Plugin(plugin.name()),
+                    etag: cached_reader.etag().map(Arc::from),
+                    reader: cached_reader,
+                    inner: SlideReaderInner::Plugin(inner),
+                    integrity: SyncRwLock::new(None),
+                    headers: SyncRwLock::new(SlideHeaders::default()),
+                    heat: SlideHeat::new(),
+                    replaced: AtomicBool::new(false),
+                    sparse_tiles: self.sparse_tiles,
+                    readahead: ReadAhead::new(self.readahead_tiles),
+                    tissue_mask: SyncRwLock::new(None),
+                    encode_pool: self.encode_pool.clone(),
+                }));
+            }
+        }
+
+        // Detect format
+        let format = detect_format(cached_reader.as_ref()).await?;
+
+        // Open the appropriate reader
+        let inner = match format {
+            SlideFormat::AperioSvs => {
+                let svs =
+                    SvsReader::open_with_options(cached_reader.as_ref(), &self.pyramid_detection)
+                        .await?;
+                SlideReaderInner::Svs(Box::new(svs))
+            }
+            SlideFormat::GenericTiff => {
+                let tiff = GenericTiffReader::open_with_options(
+                    cached_reader.as_ref(),
+                    &self.pyramid_detection,
+                )
+                .await?;
+                SlideReaderInner::GenericTiff(Box::new(tiff))
+            }
+            // detect_format only returns built-in formats
+            SlideFormat::Plugin(name) => {
+                return Err(FormatError::UnsupportedFormat {
+                    reason: format!("no plugin registered for {}", name),
+                });
+            }
+        };
+
+        Ok(Arc::new(CachedSlide {
+            format,
+            etag: cached_reader.etag().map(Arc::from),
+            reader: cached_reader,
+            inner,
+            integrity: SyncRwLock::new(None),
+            headers: SyncRwLock::new(SlideHeaders::default()),
+            heat: SlideHeat::new(),
+            replaced: AtomicBool::new(false),
+            sparse_tiles: self.sparse_tiles,
+            readahead: ReadAhead::new(self.readahead_tiles),
+            tissue_mask: SyncRwLock::new(None),
+            encode_pool: self.encode_pool.clone(),
+        }))
+    }
+
+    /// Verify a slide against its checksum manifest.
+    ///
+    /// The manifest is read from `{slide_id}.sha256` in the slide source.
+    /// A missing or invalid manifest is reported, not returned as an error.
+    ///
+    /// # Errors
+    /// Returns an error if the slide can't be opened or read.
+    pub async fn verify_slide(
+        &self,
+        slide_id: &str,
+        mode: VerifyMode,
+    ) -> Result<IntegrityReport, FormatError> {
+        let slide = self.get_slide(slide_id).await?;
+        Ok(self.verify_cached(slide_id, &slide, mode).await?)
+    }
+
+    /// Wrap a slide's reader in a block cache, verifying blocks against
+    /// the slide's manifest if read verification is enabled.
+    async fn block_cache(
+        &self,
+        slide_id: &str,
+        reader: S::Reader,
+    ) -> Result<BlockCache<S::Reader>, IoError> {
+        let digests = if self.verify_reads {
+            self.block_digests(slide_id).await?
+        } else {
+            None
+        };
+
+        Ok(match digests {
+            Some((block_size, digests)) => {
+                let budget = self.block_size * self.block_cache_capacity;
+                BlockCache::with_shards(
+                    reader,
+                    block_size,
+                    budget.div_ceil(block_size).max(1),
+                    DEFAULT_BLOCK_CACHE_SHARDS,
+                )
+                .with_block_digests(digests)
+            }
+            None => BlockCache::with_shards(
+                reader,
+                self.block_size,
+                self.block_cache_capacity,
+                DEFAULT_BLOCK_CACHE_SHARDS,
+            ),
+        })
+    }
+
+    /// Block size and digests of a slide's manifest, if it has usable ones.
+    async fn block_digests(
+        &self,
+        slide_id: &str,
+    ) -> Result<Option<(usize, BTreeMap<u64, Sha256Digest>)>, IoError> {
+        let manifest = match self.load_manifest(slide_id).await? {
+            Some(Ok(manifest)) => manifest,
+            Some(Err(message)) => {
+                warn!(slide_id = slide_id, error = %message, "Invalid checksum manifest; reads are not verified");
+                return Ok(None);
+            }
+            None => return Ok(None),
+        };
+
+        match manifest.block_size {
+            Some(block_size)
+                if block_size <= MAX_VERIFIED_BLOCK_SIZE && !manifest.blocks.is_empty() =>
+            {
+                Ok(Some((block_size as usize, manifest.blocks)))
+            }
+            _ => {
+                warn!(
+                    slide_id = slide_id,
+                    "Checksum manifest has no usable block digests; reads are not verified"
+                );
+                Ok(None)
+            }
+        }
+    }
+
+    /// Read and parse a slide's checksum manifest.
+    ///
+    /// Returns `None` if the slide has no manifest, and the parse error if
+    /// the manifest is invalid.
+    async fn load_manifest(
+        &self,
+        slide_id: &str,
+    ) -> Result<Option<Result<ChecksumManifest, String>>, IoError> {
+        match self.source.get_object(&manifest_key(slide_id)).await {
+            Ok(bytes) => Ok(Some(
+                String::from_utf8(bytes.to_vec())
+                    .map_err(|_| "not valid UTF-8".to_string())
+                    .and_then(|text| ChecksumManifest::parse(&text)),
+            )),
+            Err(IoError::NotFound(_)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn verify_cached(
+        &self,
+        slide_id: &str,
+        slide: &CachedSlide<S::Reader>,
+        mode: VerifyMode,
+    ) -> Result<IntegrityReport, IoError> {
+        let Some(manifest) = self.load_manifest(slide_id).await? else {
+            let report = IntegrityReport::no_manifest(mode);
+            slide.set_integrity(report.clone());
+            return Ok(report);
+        };
+
+        match manifest {
+            Ok(manifest) => slide.verify(&manifest, mode).await,
+            Err(message) => {
+                let report =
+                    IntegrityReport::unverified(mode, format!("invalid manifest: {}", message));
+                slide.set_integrity(report.clone());
+                Ok(report)
             }
-
-            // Result not yet available, loop back (shouldn't normally happen)
         }
     }
 
-    /// Open a slide without caching (internal implementation).
-    async fn open_slide_internal(
+    /// Load a newly opened slide's headers sidecar.
+    ///
+    /// An invalid sidecar is logged and ignored. Storage errors fail the
+    /// open rather than caching the slide without its headers.
+    async fn load_headers(
         &self,
         slide_id: &str,
-    ) -> Result<Arc<CachedSlide<S::Reader>>, FormatError> {
-        // Create the underlying reader
-        let reader = self.source.create_reader(slide_id).await?;
-
-        // Wrap in block cache
-        let cached_reader = Arc::new(BlockCache::with_capacity(
-            reader,
-            self.block_size,
-            self.block_cache_capacity,
-        ));
+        slide: &CachedSlide<S::Reader>,
+    ) -> Result<(), IoError> {
+        let headers = match self.source.get_object(&headers_key(slide_id)).await {
+            Ok(bytes) => String::from_utf8(bytes.to_vec())
+                .map_err(|_| "not valid UTF-8".to_string())
+                .and_then(|text| SlideHeaders::parse(&text)),
+            Err(IoError::NotFound(_)) => return Ok(()),
+            Err(e) => return Err(e),
+        };
 
-        // Detect format
-        let format = detect_format(cached_reader.as_ref()).await?;
+        match headers {
+            Ok(headers) => slide.set_custom_headers(headers),
+            Err(message) => {
+                warn!(slide_id = slide_id, error = %message, "Ignoring invalid slide headers sidecar");
+            }
+        }
+        Ok(())
+    }
 
-        // Open the appropriate reader
-        let inner = match format {
-            SlideFormat::AperioSvs => {
-                let svs = SvsReader::open(cached_reader.as_ref()).await?;
-                SlideReaderInner::Svs(svs)
+    /// Sampled verification of a newly opened slide; problems are logged.
+    async fn verify_on_open(&self, slide_id: &str, slide: &CachedSlide<S::Reader>) {
+        match self
+            .verify_cached(slide_id, slide, VerifyMode::Sampled)
+            .await
+        {
+            Ok(report) if report.status == IntegrityStatus::Failed => {
+                warn!(
+                    slide_id = slide_id,
+                    mismatches = ?report.mismatches,
+                    "Slide failed checksum verification"
+                );
             }
-            SlideFormat::GenericTiff => {
-                let tiff = GenericTiffReader::open(cached_reader.as_ref()).await?;
-                SlideReaderInner::GenericTiff(tiff)
+            Ok(_) => {}
+            Err(e) => {
+                warn!(slide_id = slide_id, error = %e, "Could not verify slide checksums");
             }
-        };
+        }
+    }
 
-        Ok(Arc::new(CachedSlide {
-            format,
-            reader: cached_reader,
-            inner,
-        }))
+    /// Check cached slides against storage and drop those whose file was
+    /// replaced or deleted.
+    ///
+    /// Each slide with a known ETag costs one metadata request (a HEAD for
+    /// S3). Slides whose check fails transiently are kept. Returns the IDs
+    /// of the slides dropped.
+    pub async fn revalidate_slides(&self) -> Vec<String> {
+        let slides: Vec<_> = self
+            .cache
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|(_, slide)| slide.etag.is_some())
+            .map(|(id, slide)| (id.clone(), slide.clone()))
+            .collect();
+
+        let mut dropped = Vec::new();
+        for (slide_id, slide) in slides {
+            let changed = match self.source.create_reader(&slide_id).await {
+                Ok(reader) => reader.etag() != slide.source_etag(),
+                Err(IoError::NotFound(_)) => true,
+                Err(e) => {
+                    warn!(slide_id = slide_id.as_str(), error = %e, "Could not revalidate slide");
+                    false
+                }
+            };
+            if changed {
+                slide.replaced.store(true, Ordering::Relaxed);
+                self.drop_replaced(&slide_id, &slide);
+                dropped.push(slide_id);
+            }
+        }
+        dropped
     }
 
     /// Remove a slide from the cache.
     ///
-    /// This can be useful for forcing a reload of a slide's metadata.
+    /// This can be useful for forcing a reload of a slide's metadata. The
+    /// slide's conformance result is dropped too, since the file may have
+    /// changed.
     pub async fn invalidate(&self, slide_id: &str) {
-        let mut cache = self.cache.write().await;
-        cache.pop(slide_id);
+        let removed = self.cache.write().unwrap().pop(slide_id).is_some();
+        self.conformance.forget(slide_id);
+        self.negative.forget(slide_id);
+        if removed {
+            self.publish_eviction(slide_id, EvictionReason::Invalidated);
+        }
     }
 
     /// Clear all cached slides.
     pub async fn clear(&self) {
-        let mut cache = self.cache.write().await;
+        let mut cache = self.cache.write().unwrap();
         cache.clear();
+        self.touches.lock().unwrap().clear();
+        self.negative.clear();
     }
 
     /// Get the number of cached slides.
     pub async fn cached_count(&self) -> usize {
-        let cache = self.cache.read().await;
+        let cache = self.cache.read().unwrap();
         cache.len()
     }
 
@@ -453,6 +2103,17 @@ impl<S: SlideSource> SlideRegistry<S> {
     }
 }
 
+/// [`SlideFiles`] backed by the registry's slide source.
+struct SourceFiles<'a, S: SlideSource>(&'a S);
+
+#[async_trait]
+impl<S: SlideSource> SlideFiles for SourceFiles<'_, S> {
+    async fn open(&self, key: &str) -> Result<Arc<dyn RangeReader>, IoError> {
+        let reader = self.0.create_reader(key).await?;
+        Ok(Arc::new(reader))
+    }
+}
+
 // =============================================================================
 // Tests
 // =============================================================================
@@ -660,6 +2321,183 @@ mod tests {
         assert_eq!(registry.source.create_count(), 4);
     }
 
+    #[tokio::test]
+    async fn test_registry_rejects_traversal_ids() {
+        let source = MockSlideSource::new(create_minimal_tiff());
+        let registry = SlideRegistry::with_capacity(source, 10, 256, 10);
+
+        for slide_id in ["../other-bucket/x.svs", "lab-a/../../x.svs", "lab-a//x.svs"] {
+            let err = registry.get_slide(slide_id).await.err().unwrap();
+            assert!(
+                matches!(err, FormatError::Io(IoError::InvalidKey(_))),
+                "{}: {:?}",
+                slide_id,
+                err
+            );
+        }
+        // Rejected before reaching storage
+        assert_eq!(registry.source.create_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_registry_publishes_open_and_eviction_events() {
+        let source = MockSlideSource::new(create_minimal_tiff());
+        let registry = SlideRegistry::with_capacity(source, 1, 256, 10);
+        let mut events = registry.events().subscribe();
+
+        registry.get_slide("slide1.tif").await.unwrap();
+        registry.get_slide("slide2.tif").await.unwrap();
+        registry.invalidate("slide2.tif").await;
+
+        let mut published = Vec::new();
+        let mut reasons = Vec::new();
+        while let Ok(event) = events.try_recv() {
+            if let ServerEvent::SlideEvicted { reason, .. } = event {
+                reasons.push(reason);
+            }
+            published.push(format!("{} {}", event.kind(), event.slide_id().unwrap()));
+        }
+        assert_eq!(
+            published,
+            vec![
+                "slide_opening slide1.tif",
+                "slide_opened slide1.tif",
+                "slide_opening slide2.tif",
+                "slide_evicted slide1.tif",
+                "slide_opened slide2.tif",
+                "slide_evicted slide2.tif",
+            ]
+        );
+        assert_eq!(
+            reasons,
+            vec![EvictionReason::Capacity, EvictionReason::Invalidated]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_registry_hits_keep_slides_recent() {
+        let tiff_data = create_minimal_tiff();
+        let source = MockSlideSource::new(tiff_data);
+        let registry = SlideRegistry::with_capacity(source, 2, 256, 10);
+
+        registry.get_slide("slide1.tif").await.unwrap();
+        registry.get_slide("slide2.tif").await.unwrap();
+
+        // Hit slide1 so slide2 becomes least recently used
+        registry.get_slide("slide1.tif").await.unwrap();
+        registry.get_slide("slide3.tif").await.unwrap();
+        assert_eq!(registry.source.create_count(), 3);
+
+        // slide1 survived the eviction; slide2 did not
+        registry.get_slide("slide1.tif").await.unwrap();
+        assert_eq!(registry.source.create_count(), 3);
+        registry.get_slide("slide2.tif").await.unwrap();
+        assert_eq!(registry.source.create_count(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_tenant_slide_quota() {
+        let tiff_data = create_minimal_tiff();
+        let source = MockSlideSource::new(tiff_data);
+        let registry =
+            SlideRegistry::with_capacity(source, 10, 256, 10).with_tenant_slide_quota("lab-a", 2);
+
+        registry.get_slide("lab-b/slide1.tif").await.unwrap();
+        registry.get_slide("lab-a/slide1.tif").await.unwrap();
+        registry.get_slide("lab-a/slide2.tif").await.unwrap();
+        registry.get_slide("lab-a/slide3.tif").await.unwrap();
+        assert_eq!(registry.cached_count().await, 3);
+
+        // Lab A's oldest slide made room; lab B's was left alone
+        registry.get_slide("lab-b/slide1.tif").await.unwrap();
+        assert_eq!(registry.source.create_count(), 4);
+        registry.get_slide("lab-a/slide1.tif").await.unwrap();
+        assert_eq!(registry.source.create_count(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_adaptive_caching_budgets_and_pinning() {
+        let tiff_data = create_minimal_tiff();
+        let source = MockSlideSource::new(tiff_data);
+        let config = AdaptiveCacheConfig::new(10)
+            .with_hot_blocks(20)
+            .with_hot_score(3.0);
+        let registry =
+            SlideRegistry::with_capacity(source, 2, 256, 10).with_adaptive_caching(config);
+
+        for _ in 0..5 {
+            registry.get_slide("slide1.tif").await.unwrap();
+        }
+        registry.get_slide("slide2.tif").await.unwrap();
+
+        let entries = registry.classify_slides();
+        let entry = |id: &str| entries.iter().find(|e| e.slide_id == id).unwrap().clone();
+        assert_eq!(entry("slide1.tif").temperature, SlideTemperature::Hot);
+        assert_eq!(entry("slide1.tif").accesses, 5);
+        assert_eq!(entry("slide1.tif").block_cache_capacity, 20);
+        assert_eq!(entry("slide2.tif").temperature, SlideTemperature::Warm);
+        assert_eq!(entry("slide2.tif").block_cache_capacity, 10);
+
+        // slide1 is least recently used but hot, so slide2 is evicted instead
+        registry.get_slide("slide2.tif").await.unwrap();
+        registry.get_slide("slide3.tif").await.unwrap();
+        assert_eq!(registry.source.create_count(), 3);
+        registry.get_slide("slide1.tif").await.unwrap();
+        assert_eq!(registry.source.create_count(), 3);
+        registry.get_slide("slide2.tif").await.unwrap();
+        assert_eq!(registry.source.create_count(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_adaptive_caching_shrinks_cold_slides() {
+        let tiff_data = create_minimal_tiff();
+        let source = MockSlideSource::new(tiff_data);
+        let config = AdaptiveCacheConfig::new(10)
+            .with_cold_blocks(2)
+            .with_cold_after(Duration::ZERO);
+        let registry =
+            SlideRegistry::with_capacity(source, 2, 256, 10).with_adaptive_caching(config);
+
+        registry.get_slide("slide1.tif").await.unwrap();
+        let entries = registry.classify_slides();
+        assert_eq!(entries[0].temperature, SlideTemperature::Cold);
+        assert_eq!(entries[0].block_cache_capacity, 2);
+        // Each shard keeps at least one block
+        assert!(entries[0].cached_blocks <= DEFAULT_BLOCK_CACHE_SHARDS);
+
+        // Without adaptive caching, slides are classified but not resized
+        let source = MockSlideSource::new(create_minimal_tiff());
+        let registry = SlideRegistry::with_capacity(source, 2, 256, 10);
+        registry.get_slide("slide1.tif").await.unwrap();
+        let entries = registry.classify_slides();
+        assert_eq!(entries[0].temperature, SlideTemperature::Warm);
+        assert_eq!(entries[0].block_cache_capacity, 10);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_cache_hits() {
+        let tiff_data = create_minimal_tiff();
+        let source = MockSlideSource::new(tiff_data);
+        let registry = Arc::new(SlideRegistry::with_capacity(source, 10, 256, 10));
+        registry.get_slide("slide.tif").await.unwrap();
+
+        let mut handles = Vec::new();
+        for _ in 0..8 {
+            let registry = registry.clone();
+            handles.push(tokio::spawn(async move {
+                for _ in 0..200 {
+                    registry.get_slide("slide.tif").await.unwrap();
+                }
+            }));
+        }
+        for handle in handles {
+            handle.await.unwrap();
+        }
+
+        assert_eq!(registry.source.create_count(), 1);
+        assert!(registry.touches.lock().unwrap().len() <= TOUCH_BUFFER_CAPACITY);
+    }
+
     #[tokio::test]
     async fn test_registry_invalidate() {
         let tiff_data = create_minimal_tiff();
@@ -711,6 +2549,95 @@ mod tests {
         assert_eq!(slide.tile_count(0), Some((8, 6)));
     }
 
+    #[tokio::test]
+    async fn test_tissue_mask_marks_background_tiles() {
+        let tiff_data = create_minimal_tiff();
+        let source = MockSlideSource::new(tiff_data);
+        let registry = SlideRegistry::new(source);
+        let slide = registry.get_slide("test.tif").await.unwrap();
+        let metadata_bytes = slide.metadata_bytes();
+
+        // Nothing is background until the mask is known
+        assert!(!slide.is_background_tile(0, 0, 0));
+
+        // Tissue in the left quarter of the slide only
+        let overview = image::RgbImage::from_fn(32, 24, |x, _| {
+            if x < 8 {
+                image::Rgb([180, 90, 160])
+            } else {
+                image::Rgb([245, 245, 245])
+            }
+        });
+        let mask = Arc::new(TissueMask::from_image(&overview));
+        slide.set_tissue_mask(mask.clone());
+
+        assert!(!slide.is_background_tile(0, 1, 0));
+        assert!(slide.is_background_tile(0, 2, 0));
+        assert!(slide.is_background_tile(0, 7, 5));
+        assert_eq!(slide.metadata_bytes(), metadata_bytes + mask.size_bytes());
+    }
+
+    #[tokio::test]
+    async fn test_tiles_stream_row_major() {
+        let source = MockSlideSource::new(create_minimal_tiff());
+        let registry = SlideRegistry::new(source);
+        let slide = registry.get_slide("test.tif").await.unwrap();
+
+        let mut tiles = slide.tiles(0).unwrap();
+        assert_eq!(tiles.tile_count(), 48);
+
+        let mut coords = Vec::new();
+        while let Some(tile) = tiles.next().await {
+            let (x, y, data) = tile.unwrap();
+            assert_eq!(&data[..2], &[0xFF, 0xD8]);
+            coords.push((x, y));
+        }
+
+        let expected: Vec<(u32, u32)> = (0..6).flat_map(|y| (0..8).map(move |x| (x, y))).collect();
+        assert_eq!(coords, expected);
+    }
+
+    #[tokio::test]
+    async fn test_tiles_stream_column_major_and_unordered() {
+        use crate::slide::{TileOrder, TileStreamOptions};
+
+        let source = MockSlideSource::new(create_minimal_tiff());
+        let registry = SlideRegistry::new(source);
+        let slide = registry.get_slide("test.tif").await.unwrap();
+
+        let options = TileStreamOptions::new()
+            .with_concurrency(3)
+            .with_order(TileOrder::ColumnMajor);
+        let mut tiles = slide.tiles_with_options(0, options).unwrap();
+        let first: Vec<(u32, u32)> = {
+            let mut v = Vec::new();
+            for _ in 0..3 {
+                let (x, y, _) = tiles.next().await.unwrap().unwrap();
+                v.push((x, y));
+            }
+            v
+        };
+        assert_eq!(first, vec![(0, 0), (0, 1), (0, 2)]);
+
+        let options = TileStreamOptions::new().with_order(TileOrder::Unordered);
+        let mut tiles = slide.tiles_with_options(0, options).unwrap();
+        let mut seen = std::collections::HashSet::new();
+        while let Some(tile) = tiles.next().await {
+            let (x, y, _) = tile.unwrap();
+            assert!(seen.insert((x, y)));
+        }
+        assert_eq!(seen.len(), 48);
+    }
+
+    #[tokio::test]
+    async fn test_tiles_invalid_level() {
+        let source = MockSlideSource::new(create_minimal_tiff());
+        let registry = SlideRegistry::new(source);
+        let slide = registry.get_slide("test.tif").await.unwrap();
+
+        assert!(slide.tiles(5).is_err());
+    }
+
     #[tokio::test]
     async fn test_concurrent_opens_singleflight() {
         use std::sync::atomic::AtomicBool;
@@ -779,4 +2706,283 @@ mod tests {
         // Should have only created one reader due to singleflight
         assert_eq!(registry.source.create_count.load(Ordering::SeqCst), 1);
     }
+
+    /// Source that takes a fixed time to create readers and tracks how many
+    /// creates run at once.
+    struct ConcurrencyTrackingSource {
+        data: Bytes,
+        delay: Duration,
+        active: AtomicUsize,
+        max_active: AtomicUsize,
+    }
+
+    impl ConcurrencyTrackingSource {
+        fn new(data: Vec<u8>, delay: Duration) -> Self {
+            Self {
+                data: Bytes::from(data),
+                delay,
+                active: AtomicUsize::new(0),
+                max_active: AtomicUsize::new(0),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl SlideSource for ConcurrencyTrackingSource {
+        type Reader = MockReader;
+
+        async fn create_reader(&self, slide_id: &str) -> Result<Self::Reader, IoError> {
+            let active = self.active.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_active.fetch_max(active, Ordering::SeqCst);
+            tokio::time::sleep(self.delay).await;
+            self.active.fetch_sub(1, Ordering::SeqCst);
+
+            Ok(MockReader {
+                data: self.data.clone(),
+                identifier: format!("mock://{}", slide_id),
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_opens_are_bounded() {
+        let source =
+            ConcurrencyTrackingSource::new(create_minimal_tiff(), Duration::from_millis(20));
+        let registry = Arc::new(SlideRegistry::new(source).with_max_concurrent_opens(2));
+
+        let mut handles = Vec::new();
+        for i in 0..6 {
+            let registry = registry.clone();
+            handles.push(tokio::spawn(async move {
+                registry.get_slide(&format!("slide{}.tif", i)).await
+            }));
+        }
+        for handle in handles {
+            assert!(handle.await.unwrap().is_ok());
+        }
+
+        assert_eq!(registry.source.max_active.load(Ordering::SeqCst), 2);
+
+        let stats = registry.open_stats();
+        assert_eq!(stats.in_flight, 0);
+        assert_eq!(stats.queued, 0);
+        assert_eq!(stats.max_concurrent, 2);
+    }
+
+    #[tokio::test]
+    async fn test_open_queue_timeout() {
+        let source =
+            ConcurrencyTrackingSource::new(create_minimal_tiff(), Duration::from_millis(200));
+        let registry = Arc::new(
+            SlideRegistry::new(source)
+                .with_max_concurrent_opens(1)
+                .with_open_queue_timeout(Duration::from_millis(10)),
+        );
+
+        let first = {
+            let registry = registry.clone();
+            tokio::spawn(async move { registry.get_slide("slow.tif").await })
+        };
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        assert_eq!(registry.open_stats().in_flight, 1);
+
+        let result = registry.get_slide("queued.tif").await;
+        assert!(matches!(
+            result,
+            Err(FormatError::OpenQueueTimeout { ref slide_id, .. }) if slide_id == "queued.tif"
+        ));
+        assert_eq!(registry.open_stats().queued, 0);
+
+        assert!(first.await.unwrap().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_repeated_parse_failures_quarantine_slide() {
+        // Not a TIFF: every open fails with InvalidMagic
+        let source = MockSlideSource::new(vec![b'X'; 64]);
+        let registry = SlideRegistry::new(source).with_quarantine_threshold(2);
+
+        assert!(matches!(
+            registry.get_slide("corrupt.tif").await,
+            Err(FormatError::Tiff(_))
+        ));
+        assert!(matches!(
+            registry.get_slide("corrupt.tif").await,
+            Err(FormatError::Tiff(_))
+        ));
+
+        // Quarantined: rejected without touching storage
+        let result = registry.get_slide("corrupt.tif").await;
+        assert!(matches!(
+            result,
+            Err(FormatError::Quarantined { ref slide_id }) if slide_id == "corrupt.tif"
+        ));
+        // The second failure came from the negative cache
+        assert_eq!(registry.source.create_count(), 1);
+
+        assert!(registry.quarantine().release("corrupt.tif"));
+        assert!(matches!(
+            registry.get_slide("corrupt.tif").await,
+            Err(FormatError::Tiff(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_failed_opens_are_negatively_cached() {
+        let source = MockSlideSource::new(vec![b'X'; 64]);
+        let registry = SlideRegistry::new(source).with_quarantine_threshold(0);
+
+        for _ in 0..3 {
+            assert!(matches!(
+                registry.get_slide("corrupt.tif").await,
+                Err(FormatError::Tiff(TiffError::InvalidMagic(_)))
+            ));
+        }
+        assert_eq!(registry.source.create_count(), 1);
+        assert_eq!(registry.negative_cache().len(), 1);
+
+        // Invalidating (e.g. after a re-upload) retries the open
+        registry.invalidate("corrupt.tif").await;
+        assert!(registry.get_slide("corrupt.tif").await.is_err());
+        assert_eq!(registry.source.create_count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_record_failure_evicts_quarantined_slide() {
+        let source = MockSlideSource::new(create_minimal_tiff());
+        let registry = SlideRegistry::new(source).with_quarantine_threshold(1);
+
+        registry.get_slide("panicky.tif").await.unwrap();
+        assert_eq!(registry.cached_count().await, 1);
+
+        assert!(registry.record_failure("panicky.tif", "decoder panicked"));
+        assert_eq!(registry.cached_count().await, 0);
+        assert!(registry.get_slide("panicky.tif").await.is_err());
+    }
+
+    /// Plugin for a fake two-file format: `{id}.fake` names the slide and
+    /// `{id}.fake.dat` holds the tile bytes.
+    struct FakePlugin;
+
+    struct FakeSlide {
+        data: Arc<dyn RangeReader>,
+    }
+
+    #[async_trait]
+    impl PluginSlide for FakeSlide {
+        fn level_count(&self) -> usize {
+            1
+        }
+
+        fn level_info(&self, level: usize) -> Option<LevelInfo> {
+            (level == 0).then_some(LevelInfo {
+                width: 512,
+                height: 256,
+                tile_width: 256,
+                tile_height: 256,
+                tiles_x: 2,
+                tiles_y: 1,
+                downsample: 1.0,
+            })
+        }
+
+        async fn read_tile(
+            &self,
+            _reader: &dyn RangeReader,
+            _level: usize,
+            tile_x: u32,
+            _tile_y: u32,
+        ) -> Result<Bytes, TiffError> {
+            Ok(self.data.read_exact_at(tile_x as u64 * 4, 4).await?)
+        }
+    }
+
+    #[async_trait]
+    impl FormatPlugin for FakePlugin {
+        fn name(&self) -> &'static str {
+            "Fake"
+        }
+
+        async fn detect(
+            &self,
+            slide_id: &str,
+            _reader: &dyn RangeReader,
+        ) -> Result<bool, FormatError> {
+            Ok(slide_id.ends_with(".fake"))
+        }
+
+        async fn open(
+            &self,
+            slide_id: &str,
+            _reader: &dyn RangeReader,
+            files: &dyn SlideFiles,
+        ) -> Result<Box<dyn PluginSlide>, FormatError> {
+            let data = files.open(&format!("{}.dat", slide_id)).await?;
+            Ok(Box::new(FakeSlide { data }))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_format_plugin() {
+        let source = MockSlideSource::new(create_minimal_tiff());
+        let registry = SlideRegistry::new(source).with_format_plugin(Arc::new(FakePlugin));
+        assert_eq!(registry.format_plugins(), vec!["Fake"]);
+
+        let slide = registry.get_slide("slide.fake").await.unwrap();
+        assert_eq!(slide.format(), SlideFormat::Plugin("Fake"));
+        assert_eq!(slide.format().name(), "Fake");
+        assert_eq!(slide.dimensions(), Some((512, 256)));
+        assert_eq!(slide.tile_count(0), Some((2, 1)));
+        assert_eq!(slide.best_level_for_downsample(4.0), Some(0));
+
+        // Tile bytes come from the companion file
+        let tile = slide.read_tile(0, 1, 0).await.unwrap();
+        assert_eq!(tile.as_ref(), &create_minimal_tiff()[4..8]);
+        assert_eq!(registry.source().create_count(), 2);
+
+        // Slides the plugin declines use built-in detection
+        let slide = registry.get_slide("slide.tif").await.unwrap();
+        assert_eq!(slide.format(), SlideFormat::GenericTiff);
+    }
+
+    #[tokio::test]
+    async fn test_slide_headers_loaded_on_open() {
+        use crate::slide::MemorySlideSource;
+
+        let source = MemorySlideSource::new()
+            .with_slide("labeled.tif", create_minimal_tiff())
+            .with_slide(
+                "labeled.tif.headers.json",
+                r#"{"X-Data-Classification": "restricted"}"#.as_bytes().to_vec(),
+            )
+            .with_slide("plain.tif", create_minimal_tiff())
+            .with_slide("broken.tif", create_minimal_tiff())
+            .with_slide("broken.tif.headers.json", b"{not json".to_vec());
+
+        // Sidecars are ignored unless enabled
+        let registry = SlideRegistry::new(source.clone());
+        let slide = registry.get_slide("labeled.tif").await.unwrap();
+        assert!(slide.custom_headers().is_empty());
+
+        let registry = SlideRegistry::new(source).with_slide_headers(true);
+        let slide = registry.get_slide("labeled.tif").await.unwrap();
+        let headers: Vec<_> = slide
+            .custom_headers()
+            .iter()
+            .map(|(n, v)| (n.to_string(), v.clone()))
+            .collect();
+        assert_eq!(
+            headers,
+            vec![(
+                "x-data-classification".to_string(),
+                "restricted".parse().unwrap()
+            )]
+        );
+
+        // Missing and invalid sidecars don't prevent serving
+        let slide = registry.get_slide("plain.tif").await.unwrap();
+        assert!(slide.custom_headers().is_empty());
+        let slide = registry.get_slide("broken.tif").await.unwrap();
+        assert!(slide.custom_headers().is_empty());
+    }
 }
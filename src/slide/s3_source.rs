@@ -4,10 +4,12 @@
 //! `S3RangeReader` instances for slides stored in S3 or S3-compatible storage.
 
 use async_trait::async_trait;
+use aws_sdk_s3::primitives::ByteStream;
 use aws_sdk_s3::Client;
+use bytes::Bytes;
 
 use crate::error::IoError;
-use crate::io::S3RangeReader;
+use crate::io::{S3RangeReader, S3RetryPolicy};
 
 use super::{SlideListResult, SlideSource};
 
@@ -16,10 +18,10 @@ use super::{SlideListResult, SlideSource};
 // =============================================================================
 
 /// Supported slide file extensions (case-insensitive).
-const SLIDE_EXTENSIONS: &[&str] = &[".svs", ".tif", ".tiff"];
+const SLIDE_EXTENSIONS: &[&str] = &[".svs", ".tif", ".tiff", ".scn"];
 
 /// Check if a file path has a supported slide extension.
-fn is_slide_file(path: &str) -> bool {
+pub(super) fn is_slide_file(path: &str) -> bool {
     let path_lower = path.to_lowercase();
     SLIDE_EXTENSIONS.iter().any(|ext| path_lower.ends_with(ext))
 }
@@ -45,6 +47,7 @@ fn is_slide_file(path: &str) -> bool {
 pub struct S3SlideSource {
     client: Client,
     bucket: String,
+    retry: S3RetryPolicy,
 }
 
 impl S3SlideSource {
@@ -54,7 +57,17 @@ impl S3SlideSource {
     /// * `client` - AWS S3 client to use for requests
     /// * `bucket` - S3 bucket name containing the slides
     pub fn new(client: Client, bucket: String) -> Self {
-        Self { client, bucket }
+        Self {
+            client,
+            bucket,
+            retry: S3RetryPolicy::default(),
+        }
+    }
+
+    /// Set the retry policy for range reads of the slides.
+    pub fn with_retry_policy(mut self, retry: S3RetryPolicy) -> Self {
+        self.retry = retry;
+        self
     }
 
     /// Get the bucket name.
@@ -68,12 +81,13 @@ impl SlideSource for S3SlideSource {
     type Reader = S3RangeReader;
 
     async fn create_reader(&self, slide_id: &str) -> Result<Self::Reader, IoError> {
-        S3RangeReader::new(
+        let reader = S3RangeReader::new(
             self.client.clone(),
             self.bucket.clone(),
             slide_id.to_string(),
         )
-        .await
+        .await?;
+        Ok(reader.with_retry_policy(self.retry))
     }
 
     async fn list_slides(
@@ -81,6 +95,7 @@ impl SlideSource for S3SlideSource {
         limit: u32,
         cursor: Option<&str>,
         prefix: Option<&str>,
+        delimiter: Option<&str>,
     ) -> Result<SlideListResult, IoError> {
         let mut request = self
             .client
@@ -96,6 +111,10 @@ impl SlideSource for S3SlideSource {
             request = request.prefix(prefix);
         }
 
+        if let Some(delimiter) = delimiter {
+            request = request.delimiter(delimiter);
+        }
+
         let response = request
             .send()
             .await
@@ -109,11 +128,43 @@ impl SlideSource for S3SlideSource {
             .map(|s| s.to_string())
             .collect();
 
+        let folders: Vec<String> = response
+            .common_prefixes()
+            .iter()
+            .filter_map(|p| p.prefix())
+            .map(|s| s.to_string())
+            .collect();
+
         Ok(SlideListResult {
             slides,
+            folders,
             next_cursor: response.next_continuation_token().map(|s| s.to_string()),
         })
     }
+    async fn check_ready(&self) -> Result<(), IoError> {
+        self.client
+            .head_bucket()
+            .bucket(&self.bucket)
+            .send()
+            .await
+            .map_err(|e| IoError::S3(e.to_string()))?;
+
+        Ok(())
+    }
+
+    async fn put_object(&self, key: &str, data: Bytes, content_type: &str) -> Result<(), IoError> {
+        self.client
+            .put_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .content_type(content_type)
+            .body(ByteStream::from(data))
+            .send()
+            .await
+            .map_err(|e| IoError::S3(e.to_string()))?;
+
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -164,6 +215,13 @@ mod tests {
         assert!(is_slide_file("SLIDE.TIFF"));
     }
 
+    #[test]
+    fn test_is_slide_file_scn() {
+        assert!(is_slide_file("slide.scn"));
+        assert!(is_slide_file("path/to/SLIDE.SCN"));
+        assert!(is_slide_file("slide.ome.tif"));
+    }
+
     #[test]
     fn test_is_slide_file_non_slide() {
         assert!(!is_slide_file("image.jpg"));
@@ -0,0 +1,229 @@
+//! Tiles missing from sparse TIFFs.
+//!
+//! Some converters don't write tiles that hold only background: their
+//! TileOffsets or TileByteCounts entry is 0. Readers report such tiles as
+//! [`TiffError::SparseTile`]. By default [`CachedSlide::read_tile`] serves
+//! them as a solid background tile instead, so viewers show blank areas
+//! rather than scattered errors.
+//!
+//! A [`TileGrid`] maps which tiles of a level are stored, so clients can
+//! skip empty regions without requesting their tiles.
+//!
+//! [`CachedSlide::read_tile`]: super::CachedSlide::read_tile
+
+use bytes::Bytes;
+
+use crate::error::TiffError;
+use crate::format::tiff::TileData;
+use crate::format::RawTileLayout;
+
+/// Default color of background tiles (white, like brightfield glass).
+pub const DEFAULT_SPARSE_TILE_COLOR: [u8; 3] = [255, 255, 255];
+
+/// What to serve for tiles missing from a sparse TIFF.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SparseTiles {
+    /// Fail the read with [`TiffError::SparseTile`] (HTTP 404)
+    Error,
+
+    /// Serve a solid tile of this RGB color
+    Background([u8; 3]),
+}
+
+impl Default for SparseTiles {
+    fn default() -> Self {
+        SparseTiles::Background(DEFAULT_SPARSE_TILE_COLOR)
+    }
+}
+
+impl SparseTiles {
+    /// Apply the policy to a tile read.
+    ///
+    /// A [`TiffError::SparseTile`] becomes a `width` x `height` background
+    /// tile when serving backgrounds; any other result is returned as is.
+    pub fn resolve(
+        self,
+        result: Result<Bytes, TiffError>,
+        width: u32,
+        height: u32,
+    ) -> Result<Bytes, TiffError> {
+        match (result, self) {
+            (Err(TiffError::SparseTile { .. }), SparseTiles::Background(color)) => {
+                Ok(background_tile(color, width, height))
+            }
+            (result, _) => result,
+        }
+    }
+}
+
+/// Build a solid tile of `color`, wrapped as uncompressed RGB pixels for
+/// the tile encoder (see [`crate::format::codec`]).
+pub fn background_tile(color: [u8; 3], width: u32, height: u32) -> Bytes {
+    let pixels = color.repeat(width as usize * height as usize);
+    RawTileLayout::uncompressed_rgb(width, height).wrap(&pixels)
+}
+
+/// Which tiles of a level are stored in the file, and their sizes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TileGrid {
+    tiles_x: u32,
+    tiles_y: u32,
+
+    /// Stored size of each tile in row-major order (0 when missing)
+    byte_counts: Vec<u64>,
+}
+
+impl TileGrid {
+    /// Build the grid of a `tiles_x` x `tiles_y` level from its tile
+    /// tables.
+    ///
+    /// A tile is missing when its offset or byte count is 0, or when the
+    /// tables are too short to hold it.
+    pub fn from_tile_data(tiles_x: u32, tiles_y: u32, tile_data: &TileData) -> Self {
+        let count = tiles_x as usize * tiles_y as usize;
+        let byte_counts = (0..count)
+            .map(|index| match tile_data.get_tile_location(index as u32) {
+                Some((offset, bytes)) if offset != 0 => bytes,
+                _ => 0,
+            })
+            .collect();
+        Self {
+            tiles_x,
+            tiles_y,
+            byte_counts,
+        }
+    }
+
+    /// Number of tile columns.
+    pub fn tiles_x(&self) -> u32 {
+        self.tiles_x
+    }
+
+    /// Number of tile rows.
+    pub fn tiles_y(&self) -> u32 {
+        self.tiles_y
+    }
+
+    /// Stored size of each tile in row-major order (0 when missing).
+    pub fn byte_counts(&self) -> &[u64] {
+        &self.byte_counts
+    }
+
+    /// Whether the tile at `(tile_x, tile_y)` is stored.
+    pub fn is_present(&self, tile_x: u32, tile_y: u32) -> bool {
+        tile_x < self.tiles_x
+            && tile_y < self.tiles_y
+            && self.byte_counts[(tile_y * self.tiles_x + tile_x) as usize] > 0
+    }
+
+    /// Number of stored tiles.
+    pub fn present(&self) -> usize {
+        self.byte_counts.iter().filter(|&&bytes| bytes > 0).count()
+    }
+
+    /// Number of missing tiles.
+    pub fn missing(&self) -> usize {
+        self.byte_counts.len() - self.present()
+    }
+
+    /// Presence of each tile as a bitmap: one bit per tile in row-major
+    /// order, most significant bit first, set when the tile is stored.
+    pub fn bitmap(&self) -> Vec<u8> {
+        let mut bitmap = vec![0u8; self.byte_counts.len().div_ceil(8)];
+        for (index, &bytes) in self.byte_counts.iter().enumerate() {
+            if bytes > 0 {
+                bitmap[index / 8] |= 0x80 >> (index % 8);
+            }
+        }
+        bitmap
+    }
+}
+
+/// Parse a `#RRGGBB` (or `RRGGBB`) hex color.
+pub fn parse_color(value: &str) -> Result<[u8; 3], String> {
+    let hex = value.strip_prefix('#').unwrap_or(value);
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("invalid color {:?}: expected #RRGGBB", value));
+    }
+    let channel = |at: usize| u8::from_str_radix(&hex[at..at + 2], 16).unwrap();
+    Ok([channel(0), channel(2), channel(4)])
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::decode_raw_tile;
+
+    fn sparse() -> Result<Bytes, TiffError> {
+        Err(TiffError::SparseTile {
+            level: 0,
+            tile_x: 1,
+            tile_y: 2,
+        })
+    }
+
+    #[test]
+    fn test_tile_grid() {
+        // A 3x3 level whose corners hold background only
+        let tile_data = TileData {
+            offsets: vec![0, 100, 0, 200, 300, 400, 0, 500, 600],
+            byte_counts: vec![0, 10, 0, 20, 30, 40, 0, 50, 0],
+            jpeg_tables: None,
+        };
+        let grid = TileGrid::from_tile_data(3, 3, &tile_data);
+        assert_eq!(grid.byte_counts(), &[0, 10, 0, 20, 30, 40, 0, 50, 0]);
+        assert_eq!(grid.present(), 5);
+        assert_eq!(grid.missing(), 4);
+        assert!(grid.is_present(1, 0));
+        assert!(!grid.is_present(2, 2));
+        assert!(!grid.is_present(3, 0));
+        assert_eq!(grid.bitmap(), vec![0b0101_1101, 0b0000_0000]);
+    }
+
+    #[test]
+    fn test_tile_grid_short_tables() {
+        let tile_data = TileData {
+            offsets: vec![100],
+            byte_counts: vec![10],
+            jpeg_tables: None,
+        };
+        let grid = TileGrid::from_tile_data(2, 1, &tile_data);
+        assert_eq!(grid.byte_counts(), &[10, 0]);
+        assert_eq!(grid.bitmap(), vec![0b1000_0000]);
+    }
+
+    #[test]
+    fn test_parse_color() {
+        assert_eq!(parse_color("#ffffff"), Ok([255, 255, 255]));
+        assert_eq!(parse_color("F0e0D0"), Ok([0xf0, 0xe0, 0xd0]));
+        assert!(parse_color("#fff").is_err());
+        assert!(parse_color("#gggggg").is_err());
+        assert!(parse_color("").is_err());
+    }
+
+    #[test]
+    fn test_background_replaces_sparse_tile() {
+        let tile = SparseTiles::Background([10, 20, 30])
+            .resolve(sparse(), 4, 2)
+            .unwrap();
+        let image = decode_raw_tile(&tile).unwrap().into_rgb8();
+        assert_eq!(image.dimensions(), (4, 2));
+        assert!(image.pixels().all(|p| p.0 == [10, 20, 30]));
+    }
+
+    #[test]
+    fn test_error_policy_keeps_error() {
+        assert!(matches!(
+            SparseTiles::Error.resolve(sparse(), 4, 2),
+            Err(TiffError::SparseTile { tile_y: 2, .. })
+        ));
+
+        // Other errors are never replaced
+        let result = SparseTiles::default().resolve(Err(TiffError::StripOrganization), 4, 2);
+        assert!(matches!(result, Err(TiffError::StripOrganization)));
+    }
+}
@@ -0,0 +1,345 @@
+//! Hot/warm/cold slide classification for adaptive caching.
+//!
+//! A deployment with hundreds of slides usually has a handful being viewed
+//! at any time and a long tail that was opened once. Giving every open slide
+//! the same block cache budget either starves the busy slides or wastes
+//! memory on idle ones. Each cached slide tracks its accesses; the registry
+//! periodically scores them with an exponentially decaying access count and
+//! resizes block caches to the budget of their class:
+//!
+//! - **Hot**: decayed score at or above the hot threshold. Gets the large
+//!   budget, and its metadata is kept when the slide cache evicts.
+//! - **Warm**: everything in between. Gets the configured per-slide budget.
+//! - **Cold**: not accessed for the idle window. Gets a minimal budget.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+// =============================================================================
+// Configuration
+// =============================================================================
+
+/// Default block cache budget for hot slides (100MB with 256KB blocks).
+pub const DEFAULT_HOT_BLOCK_CACHE_CAPACITY: usize = 400;
+
+/// Default block cache budget for cold slides (4MB with 256KB blocks).
+pub const DEFAULT_COLD_BLOCK_CACHE_CAPACITY: usize = 16;
+
+/// Default decayed access score that makes a slide hot.
+///
+/// A viewer panning a slide fetches tens of tiles per second, so this is
+/// reached within seconds of active viewing and not by occasional lookups.
+pub const DEFAULT_HOT_SCORE: f64 = 200.0;
+
+/// Default idle time after which a slide is cold.
+pub const DEFAULT_COLD_AFTER: Duration = Duration::from_secs(10 * 60);
+
+/// Default half-life of the access score.
+pub const DEFAULT_SCORE_HALF_LIFE: Duration = Duration::from_secs(5 * 60);
+
+/// Default interval between cache rebalances.
+pub const DEFAULT_REBALANCE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Access temperature of a slide.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SlideTemperature {
+    /// Heavily accessed
+    Hot,
+    /// Accessed recently
+    Warm,
+    /// Idle
+    Cold,
+}
+
+impl SlideTemperature {
+    /// All temperatures, hottest first.
+    pub const ALL: [SlideTemperature; 3] = [Self::Hot, Self::Warm, Self::Cold];
+
+    /// Lowercase name, as used in JSON and metric labels.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Hot => "hot",
+            Self::Warm => "warm",
+            Self::Cold => "cold",
+        }
+    }
+}
+
+/// Classification thresholds and per-class block cache budgets.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct AdaptiveCacheConfig {
+    /// Block cache capacity for hot slides, in blocks
+    pub hot_blocks: usize,
+
+    /// Block cache capacity for warm slides, in blocks
+    pub warm_blocks: usize,
+
+    /// Block cache capacity for cold slides, in blocks
+    pub cold_blocks: usize,
+
+    /// Decayed access score at which a slide becomes hot
+    pub hot_score: f64,
+
+    /// Idle time after which a slide becomes cold
+    #[serde(serialize_with = "serialize_secs")]
+    pub cold_after: Duration,
+
+    /// Time for the access score to halve without accesses
+    #[serde(serialize_with = "serialize_secs")]
+    pub half_life: Duration,
+}
+
+fn serialize_secs<S: serde::Serializer>(
+    value: &Duration,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(value.as_secs_f64())
+}
+
+impl AdaptiveCacheConfig {
+    /// Create a configuration with the given warm budget and default hot
+    /// and cold budgets and thresholds.
+    pub fn new(warm_blocks: usize) -> Self {
+        Self {
+            hot_blocks: DEFAULT_HOT_BLOCK_CACHE_CAPACITY.max(warm_blocks),
+            warm_blocks,
+            cold_blocks: DEFAULT_COLD_BLOCK_CACHE_CAPACITY.min(warm_blocks),
+            hot_score: DEFAULT_HOT_SCORE,
+            cold_after: DEFAULT_COLD_AFTER,
+            half_life: DEFAULT_SCORE_HALF_LIFE,
+        }
+    }
+
+    /// Set the block cache budget for hot slides.
+    pub fn with_hot_blocks(mut self, blocks: usize) -> Self {
+        self.hot_blocks = blocks;
+        self
+    }
+
+    /// Set the block cache budget for cold slides.
+    pub fn with_cold_blocks(mut self, blocks: usize) -> Self {
+        self.cold_blocks = blocks;
+        self
+    }
+
+    /// Set the decayed access score at which a slide becomes hot.
+    pub fn with_hot_score(mut self, score: f64) -> Self {
+        self.hot_score = score;
+        self
+    }
+
+    /// Set the idle time after which a slide becomes cold.
+    pub fn with_cold_after(mut self, idle: Duration) -> Self {
+        self.cold_after = idle;
+        self
+    }
+
+    /// Set the half-life of the access score.
+    pub fn with_half_life(mut self, half_life: Duration) -> Self {
+        self.half_life = half_life;
+        self
+    }
+
+    /// Block cache budget for a temperature, in blocks (at least 1).
+    pub fn budget(&self, temperature: SlideTemperature) -> usize {
+        let blocks = match temperature {
+            SlideTemperature::Hot => self.hot_blocks,
+            SlideTemperature::Warm => self.warm_blocks,
+            SlideTemperature::Cold => self.cold_blocks,
+        };
+        blocks.max(1)
+    }
+
+    /// Classify a slide from its decayed score and idle time.
+    ///
+    /// Idleness wins: a slide that was hot but has not been touched for the
+    /// idle window is cold, whatever its remaining score.
+    pub fn classify(&self, score: f64, idle: Duration) -> SlideTemperature {
+        if idle >= self.cold_after {
+            SlideTemperature::Cold
+        } else if score >= self.hot_score {
+            SlideTemperature::Hot
+        } else {
+            SlideTemperature::Warm
+        }
+    }
+}
+
+// =============================================================================
+// Access Tracking
+// =============================================================================
+
+/// Result of scoring a slide's accesses.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HeatSnapshot {
+    /// Current classification
+    pub temperature: SlideTemperature,
+
+    /// Exponentially decayed access count
+    pub score: f64,
+
+    /// Accesses since the slide was opened
+    pub accesses: u64,
+
+    /// Time since the last access
+    pub idle: Duration,
+}
+
+#[derive(Debug)]
+struct HeatScore {
+    score: f64,
+    scored_accesses: u64,
+    scored_at: Instant,
+    temperature: SlideTemperature,
+}
+
+/// Access counters for one slide.
+///
+/// Recording an access is two relaxed atomic updates, so it can sit on the
+/// lookup fast path. Scoring takes a lock and is done by the periodic
+/// rebalance.
+#[derive(Debug)]
+pub struct SlideHeat {
+    opened_at: Instant,
+    accesses: AtomicU64,
+    last_access_ms: AtomicU64,
+    score: Mutex<HeatScore>,
+}
+
+impl SlideHeat {
+    /// Create counters for a slide opened now. New slides start warm.
+    pub fn new() -> Self {
+        let now = Instant::now();
+        Self {
+            opened_at: now,
+            accesses: AtomicU64::new(0),
+            last_access_ms: AtomicU64::new(0),
+            score: Mutex::new(HeatScore {
+                score: 0.0,
+                scored_accesses: 0,
+                scored_at: now,
+                temperature: SlideTemperature::Warm,
+            }),
+        }
+    }
+
+    /// Record an access.
+    pub fn record(&self) {
+        self.accesses.fetch_add(1, Ordering::Relaxed);
+        let elapsed = self.opened_at.elapsed().as_millis() as u64;
+        self.last_access_ms.fetch_max(elapsed, Ordering::Relaxed);
+    }
+
+    /// Get the temperature assigned by the last [`score`](Self::score).
+    pub fn temperature(&self) -> SlideTemperature {
+        self.score.lock().unwrap().temperature
+    }
+
+    /// Decay the score to `now`, add accesses since the last scoring, and
+    /// reclassify the slide.
+    pub fn score(&self, config: &AdaptiveCacheConfig, now: Instant) -> HeatSnapshot {
+        let accesses = self.accesses.load(Ordering::Relaxed);
+        let last_access =
+            self.opened_at + Duration::from_millis(self.last_access_ms.load(Ordering::Relaxed));
+        let idle = now.saturating_duration_since(last_access);
+
+        let mut state = self.score.lock().unwrap();
+        let elapsed = now.saturating_duration_since(state.scored_at);
+        let decay = if config.half_life.is_zero() {
+            0.0
+        } else {
+            0.5f64.powf(elapsed.as_secs_f64() / config.half_life.as_secs_f64())
+        };
+        state.score = state.score * decay + (accesses - state.scored_accesses) as f64;
+        state.scored_accesses = accesses;
+        state.scored_at = now;
+        state.temperature = config.classify(state.score, idle);
+
+        HeatSnapshot {
+            temperature: state.temperature,
+            score: state.score,
+            accesses,
+            idle,
+        }
+    }
+}
+
+impl Default for SlideHeat {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> AdaptiveCacheConfig {
+        AdaptiveCacheConfig::new(100)
+            .with_hot_score(10.0)
+            .with_cold_after(Duration::from_secs(60))
+            .with_half_life(Duration::from_secs(10))
+    }
+
+    #[test]
+    fn test_budgets() {
+        let config = AdaptiveCacheConfig::new(100);
+        assert_eq!(config.budget(SlideTemperature::Hot), 400);
+        assert_eq!(config.budget(SlideTemperature::Warm), 100);
+        assert_eq!(config.budget(SlideTemperature::Cold), 16);
+
+        // Defaults never invert the warm budget
+        let config = AdaptiveCacheConfig::new(1000);
+        assert_eq!(config.budget(SlideTemperature::Hot), 1000);
+        let config = AdaptiveCacheConfig::new(4).with_cold_blocks(0);
+        assert_eq!(config.budget(SlideTemperature::Cold), 1);
+    }
+
+    #[test]
+    fn test_classify() {
+        let config = config();
+        let recent = Duration::from_secs(1);
+        assert_eq!(config.classify(0.0, recent), SlideTemperature::Warm);
+        assert_eq!(config.classify(10.0, recent), SlideTemperature::Hot);
+        assert_eq!(
+            config.classify(1000.0, Duration::from_secs(60)),
+            SlideTemperature::Cold
+        );
+    }
+
+    #[test]
+    fn test_score_decays() {
+        let config = config();
+        let heat = SlideHeat::new();
+        for _ in 0..20 {
+            heat.record();
+        }
+
+        let now = Instant::now();
+        let snapshot = heat.score(&config, now);
+        assert_eq!(snapshot.accesses, 20);
+        assert_eq!(snapshot.score, 20.0);
+        assert_eq!(snapshot.temperature, SlideTemperature::Hot);
+        assert_eq!(heat.temperature(), SlideTemperature::Hot);
+
+        // One half-life later, with no new accesses
+        let snapshot = heat.score(&config, now + Duration::from_secs(10));
+        assert!((snapshot.score - 10.0).abs() < 1e-9);
+        assert_eq!(snapshot.temperature, SlideTemperature::Hot);
+
+        let snapshot = heat.score(&config, now + Duration::from_secs(20));
+        assert_eq!(snapshot.temperature, SlideTemperature::Warm);
+
+        // Idle past the cold window
+        let snapshot = heat.score(&config, now + Duration::from_secs(120));
+        assert_eq!(snapshot.temperature, SlideTemperature::Cold);
+    }
+}
@@ -0,0 +1,345 @@
+//! Routing slides to per-tenant storage.
+//!
+//! [`TenantSlideSource`] lets one server host several labs, each with its
+//! own bucket. A slide ID whose first path segment names a tenant
+//! (`lab-a/cases/slide.svs`) is read from that tenant's source with the
+//! segment removed (`cases/slide.svs` in lab A's bucket); any other ID goes
+//! to the default source, if there is one.
+//!
+//! Listings follow the same layout: tenants appear as folders at the root,
+//! and listing under `lab-a/` lists lab A's bucket. A listing spanning
+//! several sources returns them one after another, the cursor recording
+//! which source it stopped in.
+//!
+//! Because tenant slides keep their prefixed IDs everywhere else, the slide
+//! registry's per-tenant cache quotas
+//! ([`SlideRegistry::with_tenant_slide_quota`](super::SlideRegistry::with_tenant_slide_quota))
+//! apply to them directly.
+
+use std::collections::BTreeMap;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+
+use crate::error::IoError;
+
+use super::{SlideListResult, SlideSource};
+
+/// Separator between the tenant and the rest of a slide ID.
+pub const TENANT_SEPARATOR: char = '/';
+
+/// Tenant named by the first segment of a slide ID, if it has several.
+pub fn slide_tenant(slide_id: &str) -> Option<&str> {
+    slide_id
+        .split_once(TENANT_SEPARATOR)
+        .map(|(tenant, _)| tenant)
+}
+
+/// Slide source dispatching slide IDs to per-tenant sources by prefix.
+pub struct TenantSlideSource<S> {
+    tenants: BTreeMap<String, S>,
+    default: Option<S>,
+}
+
+/// One source (or tenant folder) of a listing.
+enum ListSegment<'a, S> {
+    /// Listed with the given prefix, results prefixed with `root`
+    Source {
+        source: &'a S,
+        root: String,
+        prefix: String,
+    },
+    /// A tenant rolled up into a folder
+    Folder(String),
+}
+
+impl<S: SlideSource> TenantSlideSource<S> {
+    /// Create a source without tenants or default source.
+    pub fn new() -> Self {
+        Self {
+            tenants: BTreeMap::new(),
+            default: None,
+        }
+    }
+
+    /// Serve slide IDs starting with `{tenant}/` from `source`.
+    pub fn with_tenant(mut self, tenant: impl Into<String>, source: S) -> Self {
+        self.tenants.insert(tenant.into(), source);
+        self
+    }
+
+    /// Serve slide IDs without a tenant prefix from `source`.
+    pub fn with_default(mut self, source: S) -> Self {
+        self.default = Some(source);
+        self
+    }
+
+    /// Get the tenant names, in order.
+    pub fn tenants(&self) -> impl Iterator<Item = &str> {
+        self.tenants.keys().map(String::as_str)
+    }
+
+    /// Get a tenant's source.
+    pub fn tenant(&self, tenant: &str) -> Option<&S> {
+        self.tenants.get(tenant)
+    }
+
+    /// Find the source of a slide ID and the key within it.
+    fn route<'a>(&self, slide_id: &'a str) -> Result<(&S, &'a str), IoError> {
+        if let Some((tenant, key)) = slide_id.split_once(TENANT_SEPARATOR) {
+            if let Some(source) = self.tenants.get(tenant) {
+                return Ok((source, key));
+            }
+        }
+        self.default
+            .as_ref()
+            .map(|source| (source, slide_id))
+            .ok_or_else(|| IoError::NotFound(slide_id.to_string()))
+    }
+
+    /// Sources a listing of `prefix` walks, in order.
+    fn list_segments(&self, prefix: &str, delimiter: Option<&str>) -> Vec<ListSegment<'_, S>> {
+        let mut segments = Vec::new();
+        if let Some(source) = &self.default {
+            segments.push(ListSegment::Source {
+                source,
+                root: String::new(),
+                prefix: prefix.to_string(),
+            });
+        }
+
+        for (tenant, source) in &self.tenants {
+            let root = format!("{}{}", tenant, TENANT_SEPARATOR);
+            if let Some(inner) = prefix.strip_prefix(&root) {
+                segments.push(ListSegment::Source {
+                    source,
+                    root,
+                    prefix: inner.to_string(),
+                });
+            } else if let Some(rest) = root.strip_prefix(prefix) {
+                // The prefix stops inside the tenant name
+                match delimiter.and_then(|delimiter| {
+                    rest.find(delimiter)
+                        .map(|end| root[..prefix.len() + end + delimiter.len()].to_string())
+                }) {
+                    Some(folder) => segments.push(ListSegment::Folder(folder)),
+                    None => segments.push(ListSegment::Source {
+                        source,
+                        root,
+                        prefix: String::new(),
+                    }),
+                }
+            }
+        }
+        segments
+    }
+}
+
+impl<S: SlideSource> Default for TenantSlideSource<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Split a listing cursor into the segment index and the source's cursor.
+fn parse_cursor(cursor: &str) -> Option<(usize, Option<&str>)> {
+    let (index, inner) = cursor.split_once(':')?;
+    let index = index.parse().ok()?;
+    Some((index, (!inner.is_empty()).then_some(inner)))
+}
+
+#[async_trait]
+impl<S: SlideSource> SlideSource for TenantSlideSource<S> {
+    type Reader = S::Reader;
+
+    async fn create_reader(&self, slide_id: &str) -> Result<Self::Reader, IoError> {
+        let (source, key) = self.route(slide_id)?;
+        source.create_reader(key).await
+    }
+
+    async fn list_slides(
+        &self,
+        limit: u32,
+        cursor: Option<&str>,
+        prefix: Option<&str>,
+        delimiter: Option<&str>,
+    ) -> Result<SlideListResult, IoError> {
+        let mut result = SlideListResult {
+            slides: Vec::new(),
+            folders: Vec::new(),
+            next_cursor: None,
+        };
+        let (start, mut inner_cursor) = match cursor {
+            Some(cursor) => match parse_cursor(cursor) {
+                Some(position) => position,
+                None => return Ok(result),
+            },
+            None => (0, None),
+        };
+
+        let segments = self.list_segments(prefix.unwrap_or(""), delimiter);
+        for (index, segment) in segments.iter().enumerate().skip(start) {
+            let listed = (result.slides.len() + result.folders.len()) as u32;
+            if listed >= limit {
+                result.next_cursor = Some(format!("{}:", index));
+                break;
+            }
+
+            match segment {
+                ListSegment::Folder(folder) => result.folders.push(folder.clone()),
+                ListSegment::Source {
+                    source,
+                    root,
+                    prefix,
+                } => {
+                    let page = source
+                        .list_slides(
+                            limit - listed,
+                            inner_cursor.take(),
+                            (!prefix.is_empty()).then_some(prefix.as_str()),
+                            delimiter,
+                        )
+                        .await?;
+                    result
+                        .slides
+                        .extend(page.slides.into_iter().map(|s| format!("{}{}", root, s)));
+                    result
+                        .folders
+                        .extend(page.folders.into_iter().map(|f| format!("{}{}", root, f)));
+                    if let Some(next) = page.next_cursor {
+                        result.next_cursor = Some(format!("{}:{}", index, next));
+                        break;
+                    }
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Ready while any source is reachable, so one lab's storage outage
+    /// doesn't take the server out of rotation for every lab.
+    async fn check_ready(&self) -> Result<(), IoError> {
+        let mut last_err = None;
+        for source in self.default.iter().chain(self.tenants.values()) {
+            match source.check_ready().await {
+                Ok(()) => return Ok(()),
+                Err(e) => last_err = Some(e),
+            }
+        }
+        Err(last_err.unwrap_or_else(|| IoError::Connection("no storage configured".to_string())))
+    }
+
+    async fn get_object(&self, key: &str) -> Result<Bytes, IoError> {
+        let (source, key) = self.route(key)?;
+        source.get_object(key).await
+    }
+
+    async fn put_object(&self, key: &str, data: Bytes, content_type: &str) -> Result<(), IoError> {
+        let (source, key) = self.route(key)?;
+        source.put_object(key, data, content_type).await
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::RangeReader;
+    use crate::slide::MemorySlideSource;
+
+    fn source() -> TenantSlideSource<MemorySlideSource> {
+        TenantSlideSource::new()
+            .with_default(MemorySlideSource::new().with_slide("shared.svs", &b"shared"[..]))
+            .with_tenant(
+                "lab-a",
+                MemorySlideSource::new()
+                    .with_slide("cases/1.svs", &b"a1"[..])
+                    .with_slide("cases/2.svs", &b"a2"[..]),
+            )
+            .with_tenant(
+                "lab-b",
+                MemorySlideSource::new().with_slide("1.svs", &b"b1"[..]),
+            )
+    }
+
+    async fn read(source: &TenantSlideSource<MemorySlideSource>, slide_id: &str) -> Bytes {
+        let reader = source.create_reader(slide_id).await.unwrap();
+        reader
+            .read_exact_at(0, reader.size() as usize)
+            .await
+            .unwrap()
+    }
+
+    #[test]
+    fn test_slide_tenant() {
+        assert_eq!(slide_tenant("lab-a/cases/1.svs"), Some("lab-a"));
+        assert_eq!(slide_tenant("slide.svs"), None);
+    }
+
+    #[tokio::test]
+    async fn test_reads_are_routed_by_prefix() {
+        let source = source();
+        assert_eq!(&read(&source, "lab-a/cases/1.svs").await[..], b"a1");
+        assert_eq!(&read(&source, "lab-b/1.svs").await[..], b"b1");
+        assert_eq!(&read(&source, "shared.svs").await[..], b"shared");
+
+        // Tenants don't see each other's slides
+        assert!(matches!(
+            source.create_reader("lab-b/cases/1.svs").await,
+            Err(IoError::NotFound(_))
+        ));
+        let without_default =
+            TenantSlideSource::new().with_tenant("lab-b", MemorySlideSource::new());
+        assert!(matches!(
+            without_default.create_reader("shared.svs").await,
+            Err(IoError::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_listing_shows_tenants_as_folders() {
+        let source = source();
+        let root = source.list_slides(10, None, None, Some("/")).await.unwrap();
+        assert_eq!(root.slides, ["shared.svs"]);
+        assert_eq!(root.folders, ["lab-a/", "lab-b/"]);
+
+        let lab_a = source
+            .list_slides(10, None, Some("lab-a/"), Some("/"))
+            .await
+            .unwrap();
+        assert!(lab_a.slides.is_empty());
+        assert_eq!(lab_a.folders, ["lab-a/cases/"]);
+    }
+
+    #[tokio::test]
+    async fn test_listing_pages_across_sources() {
+        let source = source();
+        let mut slides = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = source
+                .list_slides(2, cursor.as_deref(), None, None)
+                .await
+                .unwrap();
+            assert!(page.slides.len() <= 2);
+            slides.extend(page.slides);
+            match page.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+        assert_eq!(
+            slides,
+            [
+                "shared.svs",
+                "lab-a/cases/1.svs",
+                "lab-a/cases/2.svs",
+                "lab-b/1.svs"
+            ]
+        );
+    }
+}
@@ -0,0 +1,282 @@
+//! Async iteration over all tiles of a pyramid level.
+//!
+//! Library consumers such as exporters and dataset builders frequently need to
+//! visit every tile of a level. [`TileStream`] does this with a bounded number
+//! of concurrent reads and a configurable output order, so callers don't have
+//! to reimplement tile enumeration and parallel fetching.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use wsi_streamer::slide::{TileOrder, TileStreamOptions};
+//!
+//! let slide = registry.get_slide("path/to/slide.svs").await?;
+//! let options = TileStreamOptions::new()
+//!     .with_concurrency(16)
+//!     .with_order(TileOrder::Unordered);
+//!
+//! let mut tiles = slide.tiles_with_options(0, options)?;
+//! while let Some(tile) = tiles.next().await {
+//!     let (x, y, data) = tile?;
+//!     // ...
+//! }
+//! ```
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use bytes::Bytes;
+use tokio::sync::mpsc;
+use tokio::task::{JoinHandle, JoinSet};
+
+use crate::error::TileError;
+use crate::io::RangeReader;
+
+use super::registry::CachedSlide;
+
+// =============================================================================
+// Configuration
+// =============================================================================
+
+/// Default number of tiles fetched concurrently by a [`TileStream`].
+pub const DEFAULT_TILE_STREAM_CONCURRENCY: usize = 8;
+
+/// Order in which a [`TileStream`] yields tiles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TileOrder {
+    /// Left to right, then top to bottom.
+    #[default]
+    RowMajor,
+
+    /// Top to bottom, then left to right.
+    ColumnMajor,
+
+    /// Tiles are yielded as soon as they are read.
+    ///
+    /// This gives the best throughput since a slow tile never holds back
+    /// the tiles behind it.
+    Unordered,
+}
+
+/// Options controlling how a [`TileStream`] fetches tiles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TileStreamOptions {
+    /// Maximum number of tiles read concurrently (at least 1).
+    pub concurrency: usize,
+
+    /// Order in which tiles are yielded.
+    pub order: TileOrder,
+}
+
+impl Default for TileStreamOptions {
+    fn default() -> Self {
+        Self {
+            concurrency: DEFAULT_TILE_STREAM_CONCURRENCY,
+            order: TileOrder::default(),
+        }
+    }
+}
+
+impl TileStreamOptions {
+    /// Create options with the default concurrency and row-major ordering.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the maximum number of concurrent tile reads.
+    ///
+    /// Values below 1 are treated as 1.
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Set the order in which tiles are yielded.
+    pub fn with_order(mut self, order: TileOrder) -> Self {
+        self.order = order;
+        self
+    }
+}
+
+// =============================================================================
+// TileStream
+// =============================================================================
+
+/// A tile yielded by a [`TileStream`]: `(tile_x, tile_y, data)`.
+pub type TileStreamItem = Result<(u32, u32, Bytes), TileError>;
+
+/// Async stream over every tile of a pyramid level.
+///
+/// Created by [`CachedSlide::tiles`] or [`CachedSlide::tiles_with_options`].
+/// Tiles are read in a background task; dropping the stream cancels any
+/// outstanding reads.
+pub struct TileStream {
+    /// Receiver for tiles produced by the driver task
+    rx: mpsc::Receiver<TileStreamItem>,
+
+    /// Background task issuing the tile reads
+    driver: JoinHandle<()>,
+
+    /// Total number of tiles this stream will yield
+    total: usize,
+}
+
+impl TileStream {
+    /// Spawn the driver task for a level and return the stream.
+    ///
+    /// Must be called from within a Tokio runtime.
+    pub(crate) fn spawn<R: RangeReader + 'static>(
+        slide: Arc<CachedSlide<R>>,
+        level: usize,
+        tiles_x: u32,
+        tiles_y: u32,
+        options: TileStreamOptions,
+    ) -> Self {
+        let coords = tile_coordinates(tiles_x, tiles_y, options.order);
+        let total = coords.len();
+        let (tx, rx) = mpsc::channel(options.concurrency.max(1));
+        let driver = tokio::spawn(drive(slide, level, coords, options, tx));
+
+        Self { rx, driver, total }
+    }
+
+    /// Get the next tile, or `None` once every tile has been yielded.
+    ///
+    /// A failed read is yielded as an error; the stream continues with the
+    /// remaining tiles so callers can decide whether to skip or abort.
+    pub async fn next(&mut self) -> Option<TileStreamItem> {
+        self.rx.recv().await
+    }
+
+    /// Total number of tiles the stream yields.
+    pub fn tile_count(&self) -> usize {
+        self.total
+    }
+}
+
+impl Drop for TileStream {
+    fn drop(&mut self) {
+        self.driver.abort();
+    }
+}
+
+/// Enumerate tile coordinates of a `tiles_x` x `tiles_y` grid.
+///
+/// Unordered streams enumerate in row-major order; only the output order
+/// differs.
+fn tile_coordinates(tiles_x: u32, tiles_y: u32, order: TileOrder) -> Vec<(u32, u32)> {
+    let mut coords = Vec::with_capacity(tiles_x as usize * tiles_y as usize);
+    match order {
+        TileOrder::RowMajor | TileOrder::Unordered => {
+            for y in 0..tiles_y {
+                for x in 0..tiles_x {
+                    coords.push((x, y));
+                }
+            }
+        }
+        TileOrder::ColumnMajor => {
+            for x in 0..tiles_x {
+                for y in 0..tiles_y {
+                    coords.push((x, y));
+                }
+            }
+        }
+    }
+    coords
+}
+
+/// Driver task: keeps up to `concurrency` reads in flight and forwards
+/// results to the stream.
+///
+/// For ordered streams, completed tiles are held back until every tile
+/// before them has been sent. Held-back tiles count against the concurrency
+/// budget so memory stays bounded even when one tile is slow.
+async fn drive<R: RangeReader + 'static>(
+    slide: Arc<CachedSlide<R>>,
+    level: usize,
+    coords: Vec<(u32, u32)>,
+    options: TileStreamOptions,
+    tx: mpsc::Sender<TileStreamItem>,
+) {
+    let concurrency = options.concurrency.max(1);
+    let ordered = options.order != TileOrder::Unordered;
+
+    let mut pending = coords.into_iter().enumerate();
+    let mut join_set = JoinSet::new();
+    let mut held: BTreeMap<usize, TileStreamItem> = BTreeMap::new();
+    let mut next_index = 0usize;
+
+    loop {
+        while join_set.len() + held.len() < concurrency {
+            let Some((index, (x, y))) = pending.next() else {
+                break;
+            };
+            let slide = slide.clone();
+            join_set.spawn(async move {
+                let result = slide.read_tile(level, x, y).await.map(|data| (x, y, data));
+                (index, result)
+            });
+        }
+
+        let Some(joined) = join_set.join_next().await else {
+            break;
+        };
+
+        let (index, result) = match joined {
+            Ok(output) => output,
+            Err(err) if err.is_panic() => std::panic::resume_unwind(err.into_panic()),
+            Err(_) => return,
+        };
+
+        if ordered {
+            held.insert(index, result);
+            while let Some(result) = held.remove(&next_index) {
+                if tx.send(result).await.is_err() {
+                    return;
+                }
+                next_index += 1;
+            }
+        } else if tx.send(result).await.is_err() {
+            return;
+        }
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_row_major_coordinates() {
+        let coords = tile_coordinates(3, 2, TileOrder::RowMajor);
+        assert_eq!(coords, vec![(0, 0), (1, 0), (2, 0), (0, 1), (1, 1), (2, 1)]);
+    }
+
+    #[test]
+    fn test_column_major_coordinates() {
+        let coords = tile_coordinates(3, 2, TileOrder::ColumnMajor);
+        assert_eq!(coords, vec![(0, 0), (0, 1), (1, 0), (1, 1), (2, 0), (2, 1)]);
+    }
+
+    #[test]
+    fn test_empty_grid() {
+        assert!(tile_coordinates(0, 5, TileOrder::RowMajor).is_empty());
+    }
+
+    #[test]
+    fn test_options_builder() {
+        let options = TileStreamOptions::new()
+            .with_concurrency(0)
+            .with_order(TileOrder::Unordered);
+        assert_eq!(options.concurrency, 1);
+        assert_eq!(options.order, TileOrder::Unordered);
+
+        let defaults = TileStreamOptions::default();
+        assert_eq!(defaults.concurrency, DEFAULT_TILE_STREAM_CONCURRENCY);
+        assert_eq!(defaults.order, TileOrder::RowMajor);
+    }
+}
@@ -0,0 +1,331 @@
+//! Saved viewer states.
+//!
+//! A view captures an exact field of view on a slide (level, center, zoom and
+//! display filters) under a name, so it can be shared as a stable URL. Views
+//! are stored as small JSON objects in the slide bucket, next to the slides:
+//!
+//! ```text
+//! .wsi-streamer/views/{slide_id}/{name}.json
+//! ```
+//!
+//! Keys under this prefix don't have a slide extension, so they never show up
+//! in slide listings.
+
+use std::collections::BTreeMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{IoError, ViewError};
+
+use super::SlideSource;
+
+// =============================================================================
+// Configuration
+// =============================================================================
+
+/// Key prefix under which views are stored.
+pub const VIEWS_PREFIX: &str = ".wsi-streamer/views";
+
+/// Maximum length of a view name.
+pub const MAX_VIEW_NAME_LEN: usize = 64;
+
+/// Maximum size of a stored view in bytes.
+pub const MAX_VIEW_BYTES: usize = 16 * 1024;
+
+// =============================================================================
+// View State
+// =============================================================================
+
+/// Viewport state of a viewer.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ViewState {
+    /// Pyramid level being displayed
+    pub level: usize,
+
+    /// Center of the viewport, in level 0 pixel coordinates
+    pub center_x: f64,
+
+    /// Center of the viewport, in level 0 pixel coordinates
+    pub center_y: f64,
+
+    /// Viewer zoom factor
+    pub zoom: f64,
+
+    /// Display filters (e.g. brightness, contrast), passed through as-is
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub filters: BTreeMap<String, serde_json::Value>,
+}
+
+impl ViewState {
+    /// Check the state against the slide's level count and dimensions.
+    pub fn validate(&self, level_count: usize, width: u32, height: u32) -> Result<(), ViewError> {
+        if self.level >= level_count {
+            return Err(ViewError::InvalidState {
+                message: format!(
+                    "level {} out of range (slide has {} levels)",
+                    self.level, level_count
+                ),
+            });
+        }
+
+        let in_bounds =
+            |value: f64, max: u32| value.is_finite() && (0.0..=max as f64).contains(&value);
+        if !in_bounds(self.center_x, width) || !in_bounds(self.center_y, height) {
+            return Err(ViewError::InvalidState {
+                message: format!(
+                    "center ({}, {}) outside the slide ({}x{})",
+                    self.center_x, self.center_y, width, height
+                ),
+            });
+        }
+
+        if !(self.zoom.is_finite() && self.zoom > 0.0) {
+            return Err(ViewError::InvalidState {
+                message: format!("zoom must be positive, got {}", self.zoom),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// A view as stored in the bucket.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StoredView {
+    /// Slide the view belongs to
+    pub slide_id: String,
+
+    /// View name
+    pub name: String,
+
+    /// Creation time as a Unix timestamp (seconds)
+    pub created_at: u64,
+
+    /// Viewport state
+    #[serde(flatten)]
+    pub state: ViewState,
+}
+
+// =============================================================================
+// Storage
+// =============================================================================
+
+/// Check that a view name is safe to use in an object key and a URL.
+///
+/// Names are 1-64 characters of ASCII letters, digits, `-`, `_` and `.`,
+/// and may not start with `.`.
+pub fn validate_view_name(name: &str) -> Result<(), ViewError> {
+    let valid = !name.is_empty()
+        && name.len() <= MAX_VIEW_NAME_LEN
+        && !name.starts_with('.')
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.'));
+
+    if valid {
+        Ok(())
+    } else {
+        Err(ViewError::InvalidName {
+            name: name.to_string(),
+        })
+    }
+}
+
+/// Object key of a saved view.
+pub fn view_key(slide_id: &str, name: &str) -> String {
+    format!("{}/{}/{}.json", VIEWS_PREFIX, slide_id, name)
+}
+
+/// Save a view, replacing any existing view with the same name.
+///
+/// The caller is responsible for validating the state against the slide.
+pub async fn save_view<S: SlideSource>(
+    source: &S,
+    slide_id: &str,
+    name: &str,
+    state: ViewState,
+) -> Result<StoredView, ViewError> {
+    validate_view_name(name)?;
+
+    let view = StoredView {
+        slide_id: slide_id.to_string(),
+        name: name.to_string(),
+        created_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+        state,
+    };
+
+    let body = serde_json::to_vec(&view).map_err(|e| ViewError::InvalidState {
+        message: e.to_string(),
+    })?;
+    if body.len() > MAX_VIEW_BYTES {
+        return Err(ViewError::InvalidState {
+            message: format!("view exceeds {} bytes", MAX_VIEW_BYTES),
+        });
+    }
+
+    source
+        .put_object(
+            &view_key(slide_id, name),
+            Bytes::from(body),
+            "application/json",
+        )
+        .await?;
+
+    Ok(view)
+}
+
+/// Load a saved view.
+pub async fn load_view<S: SlideSource>(
+    source: &S,
+    slide_id: &str,
+    name: &str,
+) -> Result<StoredView, ViewError> {
+    validate_view_name(name)?;
+
+    let data = source
+        .get_object(&view_key(slide_id, name))
+        .await
+        .map_err(|e| match e {
+            IoError::NotFound(_) => ViewError::NotFound {
+                slide_id: slide_id.to_string(),
+                name: name.to_string(),
+            },
+            other => ViewError::Io(other),
+        })?;
+
+    serde_json::from_slice(&data).map_err(|e| {
+        ViewError::Io(IoError::S3(format!(
+            "corrupt view {}: {}",
+            view_key(slide_id, name),
+            e
+        )))
+    })
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::RangeReader;
+    use async_trait::async_trait;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct MemorySource {
+        objects: Mutex<HashMap<String, Bytes>>,
+    }
+
+    struct MemoryReader {
+        data: Bytes,
+    }
+
+    #[async_trait]
+    impl RangeReader for MemoryReader {
+        async fn read_exact_at(&self, offset: u64, len: usize) -> Result<Bytes, IoError> {
+            let start = offset as usize;
+            Ok(self.data.slice(start..start + len))
+        }
+
+        fn size(&self) -> u64 {
+            self.data.len() as u64
+        }
+
+        fn identifier(&self) -> &str {
+            "memory"
+        }
+    }
+
+    #[async_trait]
+    impl SlideSource for MemorySource {
+        type Reader = MemoryReader;
+
+        async fn create_reader(&self, slide_id: &str) -> Result<Self::Reader, IoError> {
+            self.objects
+                .lock()
+                .unwrap()
+                .get(slide_id)
+                .cloned()
+                .map(|data| MemoryReader { data })
+                .ok_or_else(|| IoError::NotFound(slide_id.to_string()))
+        }
+
+        async fn put_object(
+            &self,
+            key: &str,
+            data: Bytes,
+            _content_type: &str,
+        ) -> Result<(), IoError> {
+            self.objects.lock().unwrap().insert(key.to_string(), data);
+            Ok(())
+        }
+    }
+
+    fn state() -> ViewState {
+        ViewState {
+            level: 1,
+            center_x: 1000.0,
+            center_y: 500.0,
+            zoom: 2.5,
+            filters: BTreeMap::from([("brightness".to_string(), serde_json::json!(1.2))]),
+        }
+    }
+
+    #[test]
+    fn test_validate_view_name() {
+        assert!(validate_view_name("tumor-margin_2").is_ok());
+        assert!(validate_view_name("v1.2").is_ok());
+        assert!(validate_view_name("").is_err());
+        assert!(validate_view_name(".hidden").is_err());
+        assert!(validate_view_name("a/b").is_err());
+        assert!(validate_view_name(&"x".repeat(MAX_VIEW_NAME_LEN + 1)).is_err());
+    }
+
+    #[test]
+    fn test_view_key() {
+        assert_eq!(
+            view_key("cases/a.svs", "margin"),
+            ".wsi-streamer/views/cases/a.svs/margin.json"
+        );
+    }
+
+    #[test]
+    fn test_validate_state() {
+        assert!(state().validate(3, 2000, 1000).is_ok());
+        assert!(state().validate(1, 2000, 1000).is_err());
+        assert!(state().validate(3, 500, 1000).is_err());
+
+        let mut bad_zoom = state();
+        bad_zoom.zoom = 0.0;
+        assert!(bad_zoom.validate(3, 2000, 1000).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_save_and_load_round_trip() {
+        let source = MemorySource::default();
+        let saved = save_view(&source, "a.svs", "margin", state())
+            .await
+            .unwrap();
+        let loaded = load_view(&source, "a.svs", "margin").await.unwrap();
+
+        assert_eq!(saved, loaded);
+        assert_eq!(loaded.state, state());
+    }
+
+    #[tokio::test]
+    async fn test_load_missing_view() {
+        let source = MemorySource::default();
+        assert!(matches!(
+            load_view(&source, "a.svs", "nope").await,
+            Err(ViewError::NotFound { .. })
+        ));
+    }
+}
@@ -0,0 +1,262 @@
+//! Startup warm-up of frequently viewed slides.
+//!
+//! Opening a slide parses its TIFF directories and loads the tile offset
+//! arrays of every level, which for a multi-gigabyte SVS on S3 takes several
+//! round trips. Warming runs that work before the first viewer arrives: each
+//! slide is opened through the [`SlideRegistry`] (so it stays cached) and the
+//! tiles of its lowest-resolution level, which every viewer requests first,
+//! are read into the block cache.
+//!
+//! Slides are named by ID or by glob pattern (`*` and `?`), resolved against
+//! the source's listing.
+
+use std::sync::Arc;
+
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use tracing::debug;
+
+use crate::error::{FormatError, IoError};
+
+use super::events::ServerEvent;
+use super::registry::{SlideRegistry, SlideSource};
+use super::tiles::{TileOrder, TileStreamOptions};
+
+/// Default number of slides warmed at once.
+pub const DEFAULT_WARM_CONCURRENCY: usize = 4;
+
+/// Most tiles read from a slide's lowest-resolution level while warming.
+///
+/// Bounds warm-up of slides whose smallest level is still large.
+pub const MAX_WARM_TILES: usize = 256;
+
+/// Tiles read between warm-up progress events.
+const PROGRESS_INTERVAL: usize = 16;
+
+/// Page size used when resolving glob patterns against a listing.
+const LIST_PAGE_SIZE: u32 = 1000;
+
+/// Match text against a pattern where `*` matches any run of characters
+/// (including `/`) and `?` matches one character.
+pub fn glob_matches(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // Position of the last `*` and the text position it was tried at
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star, matched)) = backtrack {
+            // Let the last `*` absorb one more character
+            p = star + 1;
+            t = matched + 1;
+            backtrack = Some((star, t));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Whether a warm-up entry is a glob pattern rather than a slide ID.
+fn is_pattern(entry: &str) -> bool {
+    entry.contains(['*', '?'])
+}
+
+/// Outcome of warming a set of slides.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WarmReport {
+    /// Slides opened successfully
+    pub slides: usize,
+
+    /// Lowest-level tiles read into the block cache
+    pub tiles: usize,
+
+    /// Slides that failed to open, with the error
+    pub failed: Vec<(String, String)>,
+}
+
+impl<S: SlideSource + 'static> SlideRegistry<S> {
+    /// Resolve warm-up entries to slide IDs.
+    ///
+    /// Entries without wildcards are taken as slide IDs. Patterns are
+    /// matched against the source's listing under their literal prefix.
+    /// The result keeps the order of the entries, without duplicates.
+    pub async fn resolve_slide_patterns(&self, entries: &[String]) -> Result<Vec<String>, IoError> {
+        let mut slide_ids: Vec<String> = Vec::new();
+        let mut push = |slide_id: String| {
+            if !slide_ids.contains(&slide_id) {
+                slide_ids.push(slide_id);
+            }
+        };
+
+        for entry in entries {
+            if !is_pattern(entry) {
+                push(entry.clone());
+                continue;
+            }
+
+            let prefix = &entry[..entry.find(['*', '?']).unwrap_or(entry.len())];
+            let prefix = (!prefix.is_empty()).then_some(prefix);
+            let mut cursor: Option<String> = None;
+            loop {
+                let page = self
+                    .source()
+                    .list_slides(LIST_PAGE_SIZE, cursor.as_deref(), prefix, None)
+                    .await?;
+                for slide_id in page.slides {
+                    if glob_matches(entry, &slide_id) {
+                        push(slide_id);
+                    }
+                }
+                match page.next_cursor {
+                    Some(next) => cursor = Some(next),
+                    None => break,
+                }
+            }
+        }
+
+        Ok(slide_ids)
+    }
+
+    /// Open a slide and read its lowest-resolution tiles.
+    ///
+    /// Returns the number of tiles read. Tiles that fail to read are
+    /// skipped; the slide is still cached. Progress is published as
+    /// [`ServerEvent::PrefetchProgress`] every few tiles.
+    pub async fn warm_slide(&self, slide_id: &str) -> Result<usize, FormatError> {
+        let slide = self.get_slide(slide_id).await?;
+        let Some(level) = slide.level_count().checked_sub(1) else {
+            return Ok(0);
+        };
+        let total = slide
+            .tile_count(level)
+            .map_or(0, |(x, y)| x as usize * y as usize)
+            .min(MAX_WARM_TILES);
+        let progress = |completed: usize| {
+            self.events().publish(|| ServerEvent::PrefetchProgress {
+                slide_id: slide_id.to_string(),
+                completed,
+                total,
+            })
+        };
+
+        let options = TileStreamOptions::new().with_order(TileOrder::Unordered);
+        let mut tiles = slide.tiles_with_options(level, options)?;
+        let (mut read, mut attempted) = (0, 0);
+        while read < MAX_WARM_TILES {
+            match tiles.next().await {
+                Some(Ok(_)) => read += 1,
+                Some(Err(e)) => debug!(slide_id, level, error = %e, "Skipped warm-up tile"),
+                None => break,
+            }
+            attempted += 1;
+            if attempted % PROGRESS_INTERVAL == 0 && attempted < total {
+                progress(attempted);
+            }
+        }
+        progress(total);
+        Ok(read)
+    }
+
+    /// Warm slides with at most `concurrency` slides in progress at once.
+    ///
+    /// A concurrency of 0 uses [`DEFAULT_WARM_CONCURRENCY`]. Opens still
+    /// count against the registry's own open limit.
+    pub async fn warm(self: &Arc<Self>, slide_ids: Vec<String>, concurrency: usize) -> WarmReport {
+        let concurrency = if concurrency == 0 {
+            DEFAULT_WARM_CONCURRENCY
+        } else {
+            concurrency
+        };
+        let permits = Arc::new(Semaphore::new(concurrency));
+        let mut join_set = JoinSet::new();
+        for slide_id in slide_ids {
+            let registry = Arc::clone(self);
+            let permits = permits.clone();
+            join_set.spawn(async move {
+                let _permit = permits.acquire_owned().await;
+                let result = registry.warm_slide(&slide_id).await;
+                (slide_id, result)
+            });
+        }
+
+        let mut report = WarmReport::default();
+        while let Some(joined) = join_set.join_next().await {
+            match joined {
+                Ok((_, Ok(tiles))) => {
+                    report.slides += 1;
+                    report.tiles += tiles;
+                }
+                Ok((slide_id, Err(e))) => report.failed.push((slide_id, e.to_string())),
+                Err(err) if err.is_panic() => std::panic::resume_unwind(err.into_panic()),
+                Err(_) => {}
+            }
+        }
+        report.failed.sort();
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::slide::MemorySlideSource;
+
+    #[test]
+    fn test_glob_matches() {
+        assert!(glob_matches("*", ""));
+        assert!(glob_matches("*", "a/b/c.svs"));
+        assert!(glob_matches("lab-a/*.svs", "lab-a/x/y.svs"));
+        assert!(glob_matches("a*b*c", "axxbyyc"));
+        assert!(glob_matches("a?c", "abc"));
+        assert!(glob_matches("exact.svs", "exact.svs"));
+        assert!(!glob_matches("exact.svs", "exact.svsx"));
+        assert!(!glob_matches("lab-a/*", "lab-b/x"));
+        assert!(!glob_matches("a*b", "acc"));
+    }
+
+    #[tokio::test]
+    async fn test_resolve_slide_patterns() {
+        let source = MemorySlideSource::new()
+            .with_slide("lab-a/1.svs", vec![0u8])
+            .with_slide("lab-a/2.tif", vec![0u8])
+            .with_slide("lab-b/1.svs", vec![0u8])
+            .with_slide("top.svs", vec![0u8]);
+        let registry = SlideRegistry::new(source);
+
+        let entries = vec![
+            "top.svs".to_string(),
+            "lab-a/*.svs".to_string(),
+            "*/1.svs".to_string(),
+            "missing.svs".to_string(),
+        ];
+        let slide_ids = registry.resolve_slide_patterns(&entries).await.unwrap();
+        assert_eq!(
+            slide_ids,
+            vec!["top.svs", "lab-a/1.svs", "lab-b/1.svs", "missing.svs"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_warm_reports_failures() {
+        let source = MemorySlideSource::new().with_slide("broken.svs", vec![0u8; 16]);
+        let registry = Arc::new(SlideRegistry::new(source));
+
+        let report = registry
+            .warm(vec!["broken.svs".to_string(), "missing.svs".to_string()], 0)
+            .await;
+        assert_eq!(report.slides, 0);
+        assert_eq!(report.tiles, 0);
+        let failed: Vec<&str> = report.failed.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(failed, vec!["broken.svs", "missing.svs"]);
+        assert_eq!(registry.cached_count().await, 0);
+    }
+}
@@ -0,0 +1,95 @@
+//! OpenTelemetry span export.
+//!
+//! With the `otel` feature and `--otel-endpoint`, the tracing spans emitted
+//! while serving a request are exported to an OTLP/HTTP collector, so a
+//! slow tile can be followed from the router through the tile service and
+//! block cache down to the individual storage reads:
+//!
+//! ```text
+//! request (request_id, method, path)
+//! └── tile (slide_id, level, x, y, cache_hit, bytes)
+//!     └── tile_service (cache_hit)
+//!         └── block_fetch (block, offset, len)
+//!             └── s3_read (bucket, key, offset, len, attempts, latency_ms)
+//! ```
+//!
+//! Traces are sampled at the root by `--otel-sample-rate`; child spans
+//! follow their root's decision.
+
+/// Default share of traces exported.
+pub const DEFAULT_OTEL_SAMPLE_RATE: f64 = 1.0;
+
+/// Service name reported on exported spans.
+pub const OTEL_SERVICE_NAME: &str = "wsi-streamer";
+
+/// Path of the OTLP/HTTP trace ingestion endpoint.
+const TRACES_PATH: &str = "/v1/traces";
+
+/// Resolve a collector endpoint to its trace ingestion URL.
+///
+/// A bare collector address (`http://collector:4318`) gets the standard
+/// `/v1/traces` path; a URL that already names a path is used as is.
+pub fn otlp_traces_endpoint(endpoint: &str) -> String {
+    let endpoint = endpoint.trim_end_matches('/');
+    let path = endpoint
+        .split_once("://")
+        .map_or(endpoint, |(_, rest)| rest)
+        .split_once('/')
+        .map(|(_, path)| path);
+    match path {
+        Some(path) if !path.is_empty() => endpoint.to_string(),
+        _ => format!("{endpoint}{TRACES_PATH}"),
+    }
+}
+
+/// Build a tracer provider batching spans to an OTLP/HTTP collector.
+///
+/// The provider must be kept alive (and shut down on exit, to flush the
+/// last batch) for as long as spans are exported.
+#[cfg(feature = "otel")]
+pub fn tracer_provider(
+    endpoint: &str,
+    sample_rate: f64,
+) -> Result<opentelemetry_sdk::trace::SdkTracerProvider, opentelemetry_otlp::ExporterBuildError> {
+    use opentelemetry_otlp::WithExportConfig;
+    use opentelemetry_sdk::trace::{Sampler, SdkTracerProvider};
+    use opentelemetry_sdk::Resource;
+
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_http()
+        .with_endpoint(otlp_traces_endpoint(endpoint))
+        .build()?;
+
+    Ok(SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_sampler(Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(
+            sample_rate,
+        ))))
+        .with_resource(
+            Resource::builder()
+                .with_service_name(OTEL_SERVICE_NAME)
+                .build(),
+        )
+        .build())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_otlp_traces_endpoint() {
+        assert_eq!(
+            otlp_traces_endpoint("http://collector:4318"),
+            "http://collector:4318/v1/traces"
+        );
+        assert_eq!(
+            otlp_traces_endpoint("http://collector:4318/"),
+            "http://collector:4318/v1/traces"
+        );
+        assert_eq!(
+            otlp_traces_endpoint("https://otel.example.com/ingest/traces"),
+            "https://otel.example.com/ingest/traces"
+        );
+    }
+}
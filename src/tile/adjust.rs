@@ -0,0 +1,299 @@
+//! Display adjustments of tiles.
+//!
+//! Pathologists often tweak brightness, contrast and gamma to bring out
+//! faint staining. Clients can't do this losslessly on compressed tiles, so
+//! a [`ToneAdjustment`] is applied to the decoded pixels before the tile is
+//! re-encoded. Fluorescence-adjacent workflows and QC tools that want a
+//! single channel select it with [`Channel`]; the channel is extracted
+//! first, then adjusted.
+//!
+//! Each channel value `v` (scaled to 0.0-1.0) becomes
+//! `((v - 0.5) × contrast + 0.5 + brightness) ^ (1 / gamma)`, clamped to
+//! 0.0-1.0: contrast stretches values around mid-gray, brightness shifts
+//! them and a gamma above 1 lifts the midtones. Alpha is left untouched.
+
+use std::hash::{Hash, Hasher};
+use std::ops::RangeInclusive;
+
+use image::{DynamicImage, GrayImage};
+use serde::Deserialize;
+
+use crate::error::TileError;
+
+/// Accepted brightness offsets (0.0 leaves brightness unchanged).
+pub const BRIGHTNESS_RANGE: RangeInclusive<f64> = -1.0..=1.0;
+
+/// Accepted contrast factors (1.0 leaves contrast unchanged).
+pub const CONTRAST_RANGE: RangeInclusive<f64> = 0.0..=4.0;
+
+/// Accepted gamma values (1.0 leaves midtones unchanged).
+pub const GAMMA_RANGE: RangeInclusive<f64> = 0.1..=10.0;
+
+/// Brightness, contrast and gamma applied to a tile's pixels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ToneAdjustment {
+    brightness: f64,
+    contrast: f64,
+    gamma: f64,
+}
+
+impl ToneAdjustment {
+    /// Create an adjustment, using the neutral value for missing parameters.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TileError::InvalidAdjustment`] if a value is outside
+    /// [`BRIGHTNESS_RANGE`], [`CONTRAST_RANGE`] or [`GAMMA_RANGE`].
+    pub fn new(
+        brightness: Option<f64>,
+        contrast: Option<f64>,
+        gamma: Option<f64>,
+    ) -> Result<Self, TileError> {
+        let check = |name: &'static str, value: Option<f64>, neutral: f64, range| {
+            let value = value.unwrap_or(neutral);
+            if !RangeInclusive::contains(range, &value) {
+                return Err(TileError::InvalidAdjustment {
+                    parameter: name,
+                    message: format!("{} is outside {}-{}", value, range.start(), range.end()),
+                });
+            }
+            // Adding 0.0 turns -0.0 into 0.0, so equal values hash alike
+            Ok(value + 0.0)
+        };
+        Ok(Self {
+            brightness: check("brightness", brightness, 0.0, &BRIGHTNESS_RANGE)?,
+            contrast: check("contrast", contrast, 1.0, &CONTRAST_RANGE)?,
+            gamma: check("gamma", gamma, 1.0, &GAMMA_RANGE)?,
+        })
+    }
+
+    /// Brightness offset.
+    pub fn brightness(&self) -> f64 {
+        self.brightness
+    }
+
+    /// Contrast factor.
+    pub fn contrast(&self) -> f64 {
+        self.contrast
+    }
+
+    /// Gamma.
+    pub fn gamma(&self) -> f64 {
+        self.gamma
+    }
+
+    /// Whether the adjustment leaves pixels unchanged.
+    pub fn is_identity(&self) -> bool {
+        self.brightness == 0.0 && self.contrast == 1.0 && self.gamma == 1.0
+    }
+
+    /// Output value of each 8-bit input value.
+    pub fn lookup_table(&self) -> [u8; 256] {
+        let mut table = [0u8; 256];
+        for (input, output) in table.iter_mut().enumerate() {
+            let value = input as f64 / 255.0;
+            let value = ((value - 0.5) * self.contrast + 0.5 + self.brightness).clamp(0.0, 1.0);
+            *output = (value.powf(1.0 / self.gamma) * 255.0).round() as u8;
+        }
+        table
+    }
+
+    /// Adjust the color channels of an image.
+    ///
+    /// Grayscale, RGB and RGBA images keep their layout; other layouts are
+    /// converted to 8-bit RGB.
+    pub fn apply(&self, img: DynamicImage) -> DynamicImage {
+        let table = self.lookup_table();
+        match img {
+            DynamicImage::ImageLuma8(mut gray) => {
+                gray.iter_mut().for_each(|v| *v = table[*v as usize]);
+                DynamicImage::ImageLuma8(gray)
+            }
+            DynamicImage::ImageRgba8(mut rgba) => {
+                for pixel in rgba.pixels_mut() {
+                    pixel.0[..3]
+                        .iter_mut()
+                        .for_each(|v| *v = table[*v as usize]);
+                }
+                DynamicImage::ImageRgba8(rgba)
+            }
+            other => {
+                let mut rgb = other.into_rgb8();
+                rgb.iter_mut().for_each(|v| *v = table[*v as usize]);
+                DynamicImage::ImageRgb8(rgb)
+            }
+        }
+    }
+}
+
+impl Default for ToneAdjustment {
+    fn default() -> Self {
+        Self {
+            brightness: 0.0,
+            contrast: 1.0,
+            gamma: 1.0,
+        }
+    }
+}
+
+// Values are validated finite numbers, so equality is total
+impl Eq for ToneAdjustment {}
+
+impl Hash for ToneAdjustment {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.brightness.to_bits().hash(state);
+        self.contrast.to_bits().hash(state);
+        self.gamma.to_bits().hash(state);
+    }
+}
+
+/// Single channel served instead of the full color tile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+pub enum Channel {
+    /// Luminance
+    #[serde(rename = "gray", alias = "grey")]
+    Gray,
+
+    /// Red channel
+    #[serde(rename = "r", alias = "red")]
+    Red,
+
+    /// Green channel
+    #[serde(rename = "g", alias = "green")]
+    Green,
+
+    /// Blue channel
+    #[serde(rename = "b", alias = "blue")]
+    Blue,
+}
+
+impl Channel {
+    /// Name used in query parameters.
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Channel::Gray => "gray",
+            Channel::Red => "r",
+            Channel::Green => "g",
+            Channel::Blue => "b",
+        }
+    }
+
+    /// Convert an image to a grayscale image of the channel.
+    ///
+    /// Grayscale sources have the same value in every channel.
+    pub fn apply(self, img: DynamicImage) -> DynamicImage {
+        let index = match self {
+            Channel::Gray => return DynamicImage::ImageLuma8(img.into_luma8()),
+            Channel::Red => 0,
+            Channel::Green => 1,
+            Channel::Blue => 2,
+        };
+        let rgb = img.into_rgb8();
+        let values = rgb.chunks_exact(3).map(|pixel| pixel[index]).collect();
+        let gray =
+            GrayImage::from_raw(rgb.width(), rgb.height(), values).expect("one value per pixel");
+        DynamicImage::ImageLuma8(gray)
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{GrayImage, Luma, Rgba, RgbaImage};
+
+    #[test]
+    fn test_adjustment_validation() {
+        let neutral = ToneAdjustment::new(None, None, None).unwrap();
+        assert!(neutral.is_identity());
+        assert_eq!(neutral, ToneAdjustment::default());
+        assert_eq!(
+            ToneAdjustment::new(Some(-0.0), None, None).unwrap(),
+            neutral
+        );
+
+        assert!(ToneAdjustment::new(Some(1.5), None, None).is_err());
+        assert!(ToneAdjustment::new(None, Some(-1.0), None).is_err());
+        assert!(ToneAdjustment::new(None, None, Some(0.0)).is_err());
+        assert!(ToneAdjustment::new(None, None, Some(f64::NAN)).is_err());
+        let err = ToneAdjustment::new(None, None, Some(f64::INFINITY)).unwrap_err();
+        assert!(matches!(
+            err,
+            TileError::InvalidAdjustment {
+                parameter: "gamma",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_lookup_table() {
+        let identity = ToneAdjustment::default().lookup_table();
+        assert!(identity.iter().enumerate().all(|(i, &v)| v as usize == i));
+
+        let brighter = ToneAdjustment::new(Some(0.2), None, None)
+            .unwrap()
+            .lookup_table();
+        assert_eq!(brighter[0], 51);
+        assert_eq!(brighter[255], 255);
+
+        // No contrast flattens everything to mid-gray
+        let flat = ToneAdjustment::new(None, Some(0.0), None)
+            .unwrap()
+            .lookup_table();
+        assert!(flat.iter().all(|&v| v == 128));
+
+        // Gamma above 1 lifts midtones and keeps the end points
+        let lifted = ToneAdjustment::new(None, None, Some(2.0))
+            .unwrap()
+            .lookup_table();
+        assert_eq!((lifted[0], lifted[255]), (0, 255));
+        assert_eq!(lifted[64], 128);
+    }
+
+    #[test]
+    fn test_apply_keeps_alpha() {
+        let adjustment = ToneAdjustment::new(Some(-1.0), None, None).unwrap();
+
+        let gray = GrayImage::from_pixel(2, 2, Luma([200]));
+        let adjusted = adjustment.apply(DynamicImage::ImageLuma8(gray));
+        assert_eq!(adjusted.as_luma8().unwrap().get_pixel(0, 0).0, [0]);
+
+        let rgba = RgbaImage::from_pixel(2, 2, Rgba([200, 100, 50, 77]));
+        let adjusted = adjustment.apply(DynamicImage::ImageRgba8(rgba));
+        assert_eq!(
+            adjusted.as_rgba8().unwrap().get_pixel(1, 1).0,
+            [0, 0, 0, 77]
+        );
+    }
+
+    #[test]
+    fn test_channel_selection() {
+        let rgb = image::RgbImage::from_pixel(3, 2, image::Rgb([200, 100, 50]));
+        let img = DynamicImage::ImageRgb8(rgb);
+        for (channel, expected) in [
+            (Channel::Red, 200),
+            (Channel::Green, 100),
+            (Channel::Blue, 50),
+        ] {
+            let gray = channel.apply(img.clone());
+            let gray = gray.as_luma8().unwrap();
+            assert_eq!(gray.dimensions(), (3, 2));
+            assert!(gray.pixels().all(|p| p.0 == [expected]));
+        }
+
+        let luma = Channel::Gray.apply(img).into_luma8();
+        assert!((100..140).contains(&luma.get_pixel(0, 0).0[0]));
+
+        let parse = |value: &str| serde_json::from_value::<Channel>(value.into()).ok();
+        assert_eq!(parse("gray"), Some(Channel::Gray));
+        assert_eq!(parse("grey"), Some(Channel::Gray));
+        assert_eq!(parse("g"), Some(Channel::Green));
+        assert_eq!(parse("blue"), Some(Channel::Blue));
+        assert_eq!(parse("alpha"), None);
+        assert_eq!(Channel::Red.as_str(), "r");
+    }
+}
@@ -0,0 +1,223 @@
+//! Admission policy for the tile cache.
+//!
+//! A plain LRU cache admits every tile it is given. One client scanning a
+//! whole slide at full resolution (a bulk export, a crawler) then pushes out
+//! the low-resolution tiles every viewer session starts with, even though
+//! each scanned tile is requested once and never again.
+//!
+//! With [`CacheAdmission::TinyLfu`], the cache estimates how often each tile
+//! has been requested recently with a [`FrequencySketch`]. When a tile would
+//! evict another, it is only admitted if it has been requested more often
+//! than the tile it would replace. One-off tiles of a scan lose against
+//! popular ones, while tiles that become popular get in on their second
+//! request. Counts are halved periodically, so tiles that stop being
+//! requested eventually make room.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::Deserialize;
+
+/// Largest value of a frequency counter.
+const MAX_COUNT: u8 = 15;
+
+/// Accesses recorded per counter between two halvings of the sketch.
+const SAMPLE_FACTOR: usize = 10;
+
+/// Multipliers deriving the sketch rows' indices from a key hash.
+const ROW_SEEDS: [u64; 4] = [
+    0x9E37_79B9_7F4A_7C15,
+    0xC2B2_AE3D_27D4_EB4F,
+    0x1656_67B1_9E37_79F9,
+    0xD6E8_FEB8_6659_FD93,
+];
+
+/// How the tile cache decides whether to admit a new tile.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CacheAdmission {
+    /// Admit every tile, evicting the least recently used (default)
+    #[default]
+    Lru,
+
+    /// Admit a tile over the least recently used one only if it has been
+    /// requested more often recently
+    TinyLfu,
+}
+
+impl CacheAdmission {
+    /// All policies.
+    pub const ALL: [CacheAdmission; 2] = [CacheAdmission::Lru, CacheAdmission::TinyLfu];
+
+    /// Name used in configuration.
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            CacheAdmission::Lru => "lru",
+            CacheAdmission::TinyLfu => "tinylfu",
+        }
+    }
+}
+
+impl fmt::Display for CacheAdmission {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for CacheAdmission {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        CacheAdmission::ALL
+            .into_iter()
+            .find(|policy| policy.as_str().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| {
+                format!(
+                    "Invalid cache admission policy '{}'. Expected lru or tinylfu",
+                    s
+                )
+            })
+    }
+}
+
+/// Approximate recent access counts of keys, in constant memory.
+///
+/// A count-min sketch of 4-bit counters: each key maps to one counter in
+/// each of four rows and its estimate is the smallest of them, so
+/// collisions can only overestimate. After every `10 × capacity` accesses
+/// all counters are halved.
+#[derive(Debug, Clone)]
+pub struct FrequencySketch {
+    /// Four rows of `width` counters
+    counters: Box<[u8]>,
+
+    /// Counters per row (a power of two)
+    width: usize,
+
+    /// Accesses recorded since the last halving
+    additions: usize,
+
+    /// Accesses between two halvings
+    sample_size: usize,
+}
+
+impl FrequencySketch {
+    /// Create a sketch sized for a cache of `capacity` entries.
+    pub fn new(capacity: usize) -> Self {
+        // Four counters per entry in each row keep collisions rare
+        let width = capacity.saturating_mul(4).max(16).next_power_of_two();
+        Self {
+            counters: vec![0; width * ROW_SEEDS.len()].into_boxed_slice(),
+            width,
+            additions: 0,
+            sample_size: capacity.max(1).saturating_mul(SAMPLE_FACTOR),
+        }
+    }
+
+    /// Estimated recent accesses of a key, from its hash.
+    pub fn frequency(&self, hash: u64) -> u8 {
+        (0..ROW_SEEDS.len())
+            .map(|row| self.counters[self.index(row, hash)])
+            .min()
+            .unwrap_or(0)
+    }
+
+    /// Record an access of a key, from its hash.
+    pub fn increment(&mut self, hash: u64) {
+        let mut incremented = false;
+        for row in 0..ROW_SEEDS.len() {
+            let index = self.index(row, hash);
+            if self.counters[index] < MAX_COUNT {
+                self.counters[index] += 1;
+                incremented = true;
+            }
+        }
+
+        if incremented {
+            self.additions += 1;
+            if self.additions >= self.sample_size {
+                self.halve();
+            }
+        }
+    }
+
+    /// Halve every counter, so past popularity fades.
+    fn halve(&mut self) {
+        for counter in self.counters.iter_mut() {
+            *counter /= 2;
+        }
+        self.additions /= 2;
+    }
+
+    /// Position of a key's counter in a row.
+    fn index(&self, row: usize, hash: u64) -> usize {
+        let mixed = (hash ^ (hash >> 32)).wrapping_mul(ROW_SEEDS[row]);
+        let bits = self.width.trailing_zeros();
+        row * self.width + (mixed >> (64 - bits)) as usize
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::hash_map::RandomState;
+    use std::hash::BuildHasher;
+
+    #[test]
+    fn test_admission_parse() {
+        assert_eq!("lru".parse::<CacheAdmission>(), Ok(CacheAdmission::Lru));
+        assert_eq!(
+            " TinyLFU ".parse::<CacheAdmission>(),
+            Ok(CacheAdmission::TinyLfu)
+        );
+        assert!("lfu".parse::<CacheAdmission>().is_err());
+        for policy in CacheAdmission::ALL {
+            assert_eq!(policy.to_string().parse::<CacheAdmission>(), Ok(policy));
+        }
+    }
+
+    #[test]
+    fn test_sketch_counts_accesses() {
+        let hasher = RandomState::new();
+        let mut sketch = FrequencySketch::new(1000);
+        let hot = hasher.hash_one("hot");
+        let cold = hasher.hash_one("cold");
+
+        for _ in 0..5 {
+            sketch.increment(hot);
+        }
+        sketch.increment(cold);
+        assert_eq!(sketch.frequency(hot), 5);
+        assert_eq!(sketch.frequency(cold), 1);
+        assert_eq!(sketch.frequency(hasher.hash_one("unseen")), 0);
+
+        // Counters saturate
+        for _ in 0..100 {
+            sketch.increment(hot);
+        }
+        assert_eq!(sketch.frequency(hot), MAX_COUNT);
+    }
+
+    #[test]
+    fn test_sketch_ages() {
+        let hasher = RandomState::new();
+        let mut sketch = FrequencySketch::new(16);
+        let hot = hasher.hash_one(0u64);
+        for _ in 0..20 {
+            sketch.increment(hot);
+        }
+        assert_eq!(sketch.frequency(hot), MAX_COUNT);
+
+        // A scan of many distinct keys halves the counters
+        let additions = sketch.additions;
+        for key in 1..=(sketch.sample_size - additions) as u64 {
+            sketch.increment(hasher.hash_one(key));
+        }
+        assert_eq!(sketch.additions, sketch.sample_size / 2);
+        assert!(sketch.frequency(hot) <= MAX_COUNT / 2);
+    }
+}
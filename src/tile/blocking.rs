@@ -0,0 +1,193 @@
+//! Bounded offloading of CPU-heavy tile work.
+//!
+//! Decoding and re-encoding a tile takes milliseconds of CPU. Done inline on
+//! the async runtime, a burst of cache misses stalls the reactor and every
+//! other endpoint's latency with it. [`EncodePool`] runs that work on
+//! tokio's blocking thread pool instead, behind a semaphore that bounds how
+//! many encodes run at once so the blocking pool doesn't spawn a thread per
+//! request.
+//!
+//! Panics inside an encode are resumed on the calling task, so callers that
+//! catch panics (such as slide quarantining) still see them. The location
+//! and backtrace recorded by the panic hook on the blocking thread travel
+//! with the panic, so the crash log points at the encode that failed.
+
+use std::sync::Arc;
+
+use tokio::sync::Semaphore;
+
+use crate::error::TileError;
+use crate::server::panic::CaughtPanic;
+use crate::timing;
+
+/// Default number of concurrent encodes: one per available CPU.
+pub fn default_encode_workers() -> usize {
+    std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+}
+
+/// Runs tile decoding and encoding on the blocking thread pool.
+///
+/// Cloning shares the same worker limit.
+#[derive(Debug, Clone)]
+pub struct EncodePool {
+    permits: Arc<Semaphore>,
+    workers: usize,
+}
+
+impl Default for EncodePool {
+    fn default() -> Self {
+        Self::new(default_encode_workers())
+    }
+}
+
+impl EncodePool {
+    /// Create a pool running at most `workers` encodes at once (0 uses
+    /// [`default_encode_workers`]).
+    pub fn new(workers: usize) -> Self {
+        let workers = if workers == 0 {
+            default_encode_workers()
+        } else {
+            workers
+        };
+        Self {
+            permits: Arc::new(Semaphore::new(workers)),
+            workers,
+        }
+    }
+
+    /// Maximum number of concurrent encodes.
+    pub fn workers(&self) -> usize {
+        self.workers
+    }
+
+    /// Number of encodes currently running.
+    pub fn active(&self) -> usize {
+        self.workers - self.permits.available_permits()
+    }
+
+    /// Run `work` on the blocking thread pool once a worker is free.
+    ///
+    /// Waiting for a worker is cancellable; once started, the work runs to
+    /// completion even if the caller is dropped.
+    pub async fn run<F, T>(&self, work: F) -> Result<T, TileError>
+    where
+        F: FnOnce() -> Result<T, TileError> + Send + 'static,
+        T: Send + 'static,
+    {
+        let permit =
+            self.permits
+                .clone()
+                .acquire_owned()
+                .await
+                .map_err(|_| TileError::Overloaded {
+                    message: "encode pool closed".to_string(),
+                })?;
+
+        // Decode and encode times count toward the caller's request
+        let timings = timing::current();
+        let result = tokio::task::spawn_blocking(move || {
+            let _permit = permit;
+            CaughtPanic::catch(|| timing::sync_scope(timings, work))
+        })
+        .await;
+
+        match result {
+            Ok(Ok(output)) => output,
+            Ok(Err(panic)) => panic.resume(),
+            Err(err) if err.is_panic() => std::panic::resume_unwind(err.into_panic()),
+            Err(err) => Err(TileError::EncodeError {
+                message: format!("encode task failed: {}", err),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    #[test]
+    fn test_default_workers() {
+        assert!(default_encode_workers() >= 1);
+        assert_eq!(EncodePool::new(0).workers(), default_encode_workers());
+        assert_eq!(EncodePool::new(3).workers(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_run_returns_result() {
+        let pool = EncodePool::new(2);
+        assert_eq!(pool.run(|| Ok(42)).await.unwrap(), 42);
+
+        let err = pool
+            .run(|| -> Result<(), TileError> {
+                Err(TileError::EncodeError {
+                    message: "bad".to_string(),
+                })
+            })
+            .await;
+        assert!(matches!(err, Err(TileError::EncodeError { .. })));
+        assert_eq!(pool.active(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_run_bounds_concurrency() {
+        let pool = EncodePool::new(2);
+        let running = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+
+        let tasks: Vec<_> = (0..8)
+            .map(|_| {
+                let pool = pool.clone();
+                let running = running.clone();
+                let peak = peak.clone();
+                tokio::spawn(async move {
+                    pool.run(move || {
+                        let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                        peak.fetch_max(now, Ordering::SeqCst);
+                        std::thread::sleep(Duration::from_millis(20));
+                        running.fetch_sub(1, Ordering::SeqCst);
+                        Ok(())
+                    })
+                    .await
+                })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap().unwrap();
+        }
+
+        assert!(peak.load(Ordering::SeqCst) <= 2);
+    }
+
+    #[tokio::test]
+    async fn test_run_resumes_panics() {
+        let pool = EncodePool::new(1);
+        let task = tokio::spawn(async move {
+            pool.run(|| -> Result<(), TileError> { panic!("boom") })
+                .await
+        });
+        assert!(task.await.unwrap_err().is_panic());
+    }
+
+    #[test]
+    fn test_run_carries_panic_location() {
+        crate::server::install_panic_hook();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let pool = EncodePool::new(1);
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            runtime.block_on(pool.run(|| -> Result<(), TileError> { panic!("boom") }))
+        }));
+
+        assert!(result.is_err());
+        let location = crate::server::panic::take_panic_location().unwrap();
+        assert!(location.contains("blocking.rs"), "{}", location);
+    }
+}
@@ -11,23 +11,63 @@
 //! - Tile X coordinate
 //! - Tile Y coordinate
 //! - JPEG quality setting
+//! - Output format
 //!
 //! # Size-Based Eviction
 //!
 //! The cache tracks the total size of cached tiles in bytes and evicts
 //! least-recently-used entries when the capacity is exceeded.
-
-use std::sync::Arc;
+//!
+//! # Sharding
+//!
+//! The cache can be split into independently locked shards to avoid a single
+//! lock becoming the bottleneck at high request rates. Each shard holds an
+//! even share of the capacity and evicts in LRU order on its own.
+//!
+//! # Admission
+//!
+//! By default every tile is admitted. With [`CacheAdmission::TinyLfu`] a
+//! new tile only replaces the least recently used one if it has been
+//! requested more often recently, so a bulk scan of a slide can't flush
+//! the tiles everyone else uses (see [`super::admission`]). Hits, misses
+//! and rejected tiles are counted in [`TileCacheStats`].
+//!
+//! # Staleness
+//!
+//! Entries become stale when their slide is invalidated or, with a TTL
+//! configured, once they outlive it. Stale entries are kept rather than
+//! evicted so callers can serve them immediately while a fresh tile is
+//! generated in the background (stale-while-revalidate).
+
+use std::collections::hash_map::RandomState;
+use std::collections::{HashMap, HashSet};
+use std::hash::BuildHasher;
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use bytes::Bytes;
 use lru::LruCache;
-use tokio::sync::RwLock;
+use serde::Serialize;
+use tokio::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use super::adjust::{Channel, ToneAdjustment};
+use super::admission::{CacheAdmission, FrequencySketch};
+use super::encoder::OutputFormat;
+use super::resample::ResampleFilter;
 
 /// Default cache capacity: 100MB
 pub const DEFAULT_TILE_CACHE_CAPACITY: usize = 100 * 1024 * 1024;
 
 /// Default maximum number of entries (to bound LRU overhead)
-const DEFAULT_MAX_ENTRIES: usize = 10_000;
+pub const DEFAULT_TILE_CACHE_ENTRIES: usize = 10_000;
+
+/// Default shard count for high-throughput deployments.
+///
+/// Library constructors default to a single shard (exact LRU); the server
+/// uses this value unless configured otherwise.
+pub const DEFAULT_TILE_CACHE_SHARDS: usize = 16;
 
 // =============================================================================
 // Cache Key
@@ -35,7 +75,8 @@ const DEFAULT_MAX_ENTRIES: usize = 10_000;
 
 /// Cache key for encoded tiles.
 ///
-/// This key uniquely identifies a tile at a specific quality level.
+/// This key uniquely identifies a tile at a specific quality level and
+/// output format.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct TileCacheKey {
     /// Slide identifier (typically the S3 path or slide ID)
@@ -52,10 +93,35 @@ pub struct TileCacheKey {
 
     /// JPEG quality (1-100)
     pub quality: u8,
+
+    /// Output format
+    pub format: OutputFormat,
+
+    /// Whether the source tile was requested without re-encoding
+    pub passthrough: bool,
+
+    /// Brightness, contrast and gamma applied before encoding
+    pub adjustment: Option<ToneAdjustment>,
+
+    /// Single channel served instead of the full color tile
+    pub channel: Option<Channel>,
+
+    /// Revision of the annotation set drawn onto the tile
+    pub overlay: Option<u64>,
+
+    /// Whether the service's watermark is burned into the tile
+    pub watermark: bool,
+
+    /// Storage ETag of the slide file the tile was rendered from, if known
+    pub source_etag: Option<Arc<str>>,
+
+    /// Resampling filter of a Deep Zoom tile, whose level and coordinates
+    /// are on the Deep Zoom grid; `None` for pyramid tiles
+    pub deep_zoom: Option<ResampleFilter>,
 }
 
 impl TileCacheKey {
-    /// Create a new cache key.
+    /// Create a new cache key for a JPEG tile.
     pub fn new(
         slide_id: impl Into<Arc<str>>,
         level: u32,
@@ -69,19 +135,211 @@ impl TileCacheKey {
             tile_x,
             tile_y,
             quality,
+            format: OutputFormat::Jpeg,
+            passthrough: false,
+            adjustment: None,
+            channel: None,
+            overlay: None,
+            watermark: false,
+            source_etag: None,
+            deep_zoom: None,
         }
     }
+
+    /// Set the output format.
+    pub fn with_format(mut self, format: OutputFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Mark the key as addressing the unmodified source tile.
+    pub fn with_passthrough(mut self, passthrough: bool) -> Self {
+        self.passthrough = passthrough;
+        self
+    }
+
+    /// Set the tone adjustment the tile is rendered with.
+    pub fn with_adjustment(mut self, adjustment: Option<ToneAdjustment>) -> Self {
+        self.adjustment = adjustment;
+        self
+    }
+
+    /// Set the channel the tile is rendered from.
+    pub fn with_channel(mut self, channel: Option<Channel>) -> Self {
+        self.channel = channel;
+        self
+    }
+
+    /// Set the revision of the annotations drawn onto the tile.
+    pub fn with_overlay(mut self, revision: Option<u64>) -> Self {
+        self.overlay = revision;
+        self
+    }
+
+    /// Mark the key as addressing a watermarked tile.
+    pub fn with_watermark(mut self, watermark: bool) -> Self {
+        self.watermark = watermark;
+        self
+    }
+
+    /// Set the ETag of the slide file version the tile belongs to.
+    pub fn with_source_etag(mut self, source_etag: Option<Arc<str>>) -> Self {
+        self.source_etag = source_etag;
+        self
+    }
+
+    /// Mark the key as addressing a Deep Zoom tile resampled with `filter`.
+    pub fn with_deep_zoom(mut self, filter: ResampleFilter) -> Self {
+        self.deep_zoom = Some(filter);
+        self
+    }
 }
 
 // =============================================================================
 // Tile Cache
 // =============================================================================
 
+/// Snapshot of tile cache lock contention.
+///
+/// A lock acquisition is contended when the shard lock was already held and
+/// the caller had to wait. A high contended ratio at high request rates
+/// indicates the cache needs more shards.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TileCacheContention {
+    /// Number of shards
+    pub shards: usize,
+
+    /// Total shard lock acquisitions
+    pub acquisitions: u64,
+
+    /// Acquisitions that had to wait for another holder
+    pub contended: u64,
+}
+
+impl TileCacheContention {
+    /// Fraction of lock acquisitions that were contended (0.0 - 1.0).
+    pub fn contended_ratio(&self) -> f64 {
+        if self.acquisitions == 0 {
+            0.0
+        } else {
+            self.contended as f64 / self.acquisitions as f64
+        }
+    }
+}
+
+/// Snapshot of tile cache effectiveness counters.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct TileCacheStats {
+    /// Lookups that found the tile, stale or not
+    pub hits: u64,
+
+    /// Lookups that found nothing
+    pub misses: u64,
+
+    /// Tiles stored
+    pub admitted: u64,
+
+    /// Tiles not stored because they were requested less often than the
+    /// tile they would have evicted
+    pub rejected: u64,
+
+    /// Tiles evicted to make room
+    pub evicted: u64,
+}
+
+impl TileCacheStats {
+    /// Fraction of lookups that found the tile (0.0 - 1.0).
+    pub fn hit_ratio(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.hits as f64 / lookups as f64
+        }
+    }
+}
+
+/// A cached tile and the state needed to decide whether it is stale.
+struct CachedTile {
+    /// Encoded tile data
+    data: Bytes,
+
+    /// When the tile was stored
+    stored_at: Instant,
+
+    /// Slide invalidation generation at the time the tile was stored
+    generation: u64,
+}
+
+/// Result of a cache lookup that distinguishes fresh from stale entries.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheLookup {
+    /// Cached tile data
+    pub data: Bytes,
+
+    /// Whether the entry was invalidated or outlived the TTL
+    pub stale: bool,
+}
+
+/// One independently locked partition of the cache.
+struct Shard {
+    /// The underlying LRU cache
+    entries: LruCache<TileCacheKey, CachedTile>,
+
+    /// Total size of this shard's entries in bytes
+    size: usize,
+
+    /// Maximum total size of this shard in bytes
+    max_size: usize,
+
+    /// Recent access frequencies, with TinyLFU admission
+    sketch: Option<FrequencySketch>,
+}
+
+impl Shard {
+    /// Record an access of a key.
+    fn record_access(&mut self, hash: u64) {
+        if let Some(sketch) = &mut self.sketch {
+            sketch.increment(hash);
+        }
+    }
+
+    /// Whether a new entry may be stored, possibly evicting the least
+    /// recently used one.
+    fn admits(&self, key: &TileCacheKey, hash: u64, size: usize, hasher: &RandomState) -> bool {
+        let Some(sketch) = &self.sketch else {
+            return true;
+        };
+        if self.entries.contains(key) {
+            return true;
+        }
+        let full =
+            self.entries.len() >= self.entries.cap().get() || self.size + size > self.max_size;
+        match self.entries.peek_lru() {
+            Some((victim, _)) if full => {
+                sketch.frequency(hash) > sketch.frequency(hasher.hash_one(victim))
+            }
+            _ => true,
+        }
+    }
+}
+
 /// LRU cache for encoded JPEG tiles with size-based capacity.
 ///
 /// This cache stores encoded tile data and evicts least-recently-used entries
 /// when the total cached size exceeds capacity.
 ///
+/// # Sharding
+///
+/// By default the cache is a single LRU behind one lock. Under very high
+/// request rates that lock becomes a bottleneck; [`with_shards`] partitions
+/// the cache into independently locked shards selected by key hash. Capacity
+/// and entry limits are split evenly between shards, and LRU order is
+/// maintained per shard. Lock contention is reported by [`contention`].
+///
+/// [`with_shards`]: TileCache::with_shards
+/// [`contention`]: TileCache::contention
+///
 /// # Thread Safety
 ///
 /// The cache is thread-safe and can be shared across async tasks via `Arc`.
@@ -109,14 +367,51 @@ impl TileCacheKey {
 /// }
 /// ```
 pub struct TileCache {
-    /// The underlying LRU cache
-    cache: RwLock<LruCache<TileCacheKey, Bytes>>,
+    /// Independently locked partitions of the cache
+    shards: Box<[RwLock<Shard>]>,
+
+    /// Hasher used to select a shard for a key
+    hasher: RandomState,
 
     /// Maximum total size in bytes
     max_size: usize,
 
-    /// Current total size in bytes
-    current_size: RwLock<usize>,
+    /// Current total size in bytes, across all shards
+    current_size: AtomicUsize,
+
+    /// Total shard lock acquisitions
+    acquisitions: AtomicU64,
+
+    /// Shard lock acquisitions that had to wait
+    contended: AtomicU64,
+
+    /// Optional age after which entries are stale
+    ttl: Option<Duration>,
+
+    /// Invalidation generation per slide; entries stored under an older
+    /// generation are stale
+    generations: Mutex<HashMap<Arc<str>, u64>>,
+
+    /// Keys with a background refresh in flight
+    refreshing: Mutex<HashSet<TileCacheKey>>,
+
+    /// Admission policy
+    admission: CacheAdmission,
+
+    /// Lookups that found the tile
+    hits: AtomicU64,
+
+    /// Lookups that found nothing
+    misses: AtomicU64,
+
+    /// Tiles stored
+    admitted: AtomicU64,
+
+    /// Tiles turned away by the admission policy
+    rejected: AtomicU64,
+
+    /// Tiles evicted to make room
+    evicted: AtomicU64,
 }
 
 impl TileCache {
@@ -131,13 +426,7 @@ impl TileCache {
     ///
     /// * `max_size` - Maximum total size of cached tiles in bytes
     pub fn with_capacity(max_size: usize) -> Self {
-        Self {
-            cache: RwLock::new(LruCache::new(
-                std::num::NonZeroUsize::new(DEFAULT_MAX_ENTRIES).unwrap(),
-            )),
-            max_size,
-            current_size: RwLock::new(0),
-        }
+        Self::with_capacity_and_entries(max_size, DEFAULT_TILE_CACHE_ENTRIES)
     }
 
     /// Create a new tile cache with specified capacity and maximum entries.
@@ -147,12 +436,140 @@ impl TileCache {
     /// * `max_size` - Maximum total size of cached tiles in bytes
     /// * `max_entries` - Maximum number of entries in the cache
     pub fn with_capacity_and_entries(max_size: usize, max_entries: usize) -> Self {
+        Self::with_shards(max_size, max_entries, 1)
+    }
+
+    /// Create a sharded tile cache.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_size` - Maximum total size of cached tiles in bytes
+    /// * `max_entries` - Maximum number of entries in the cache
+    /// * `shards` - Number of independently locked shards (at least 1)
+    pub fn with_shards(max_size: usize, max_entries: usize, shards: usize) -> Self {
+        let shard_count = shards.max(1);
+        let shard_entries = max_entries.div_ceil(shard_count).max(1);
+        let shard_size = max_size.div_ceil(shard_count);
+
+        let shards = (0..shard_count)
+            .map(|_| {
+                RwLock::new(Shard {
+                    entries: LruCache::new(NonZeroUsize::new(shard_entries).unwrap()),
+                    size: 0,
+                    max_size: shard_size,
+                    sketch: None,
+                })
+            })
+            .collect();
+
         Self {
-            cache: RwLock::new(LruCache::new(
-                std::num::NonZeroUsize::new(max_entries).unwrap(),
-            )),
+            shards,
+            hasher: RandomState::new(),
             max_size,
-            current_size: RwLock::new(0),
+            current_size: AtomicUsize::new(0),
+            acquisitions: AtomicU64::new(0),
+            contended: AtomicU64::new(0),
+            ttl: None,
+            generations: Mutex::new(HashMap::new()),
+            refreshing: Mutex::new(HashSet::new()),
+            admission: CacheAdmission::Lru,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            admitted: AtomicU64::new(0),
+            rejected: AtomicU64::new(0),
+            evicted: AtomicU64::new(0),
+        }
+    }
+
+    /// Set the admission policy.
+    ///
+    /// Call before the cache is shared; frequency history starts empty.
+    pub fn with_admission(mut self, admission: CacheAdmission) -> Self {
+        self.admission = admission;
+        for shard in self.shards.iter_mut() {
+            let shard = shard.get_mut();
+            shard.sketch = match admission {
+                CacheAdmission::Lru => None,
+                CacheAdmission::TinyLfu => Some(FrequencySketch::new(shard.entries.cap().get())),
+            };
+        }
+        self
+    }
+
+    /// Get the admission policy.
+    pub fn admission(&self) -> CacheAdmission {
+        self.admission
+    }
+
+    /// Mark entries stale once they are older than `ttl`.
+    ///
+    /// Stale entries are still returned by [`get`](Self::get); use
+    /// [`lookup`](Self::lookup) to tell them apart.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Get the configured TTL, if any.
+    pub fn ttl(&self) -> Option<Duration> {
+        self.ttl
+    }
+
+    /// Current invalidation generation of a slide.
+    fn generation(&self, slide_id: &str) -> u64 {
+        self.generations
+            .lock()
+            .unwrap()
+            .get(slide_id)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Check whether an entry is stale.
+    fn is_stale(&self, key: &TileCacheKey, tile: &CachedTile) -> bool {
+        if tile.generation < self.generation(&key.slide_id) {
+            return true;
+        }
+        self.ttl.is_some_and(|ttl| tile.stored_at.elapsed() >= ttl)
+    }
+
+    /// Hash a key, for shard selection and frequency estimates.
+    fn hash(&self, key: &TileCacheKey) -> u64 {
+        self.hasher.hash_one(key)
+    }
+
+    /// Select the shard for a key hash.
+    fn shard_for(&self, hash: u64) -> &RwLock<Shard> {
+        &self.shards[hash as usize % self.shards.len()]
+    }
+
+    /// Count a lookup as a hit or a miss.
+    fn record_lookup(&self, hit: bool) {
+        let counter = if hit { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Acquire a shard's write lock, recording contention.
+    async fn write_shard<'a>(&self, shard: &'a RwLock<Shard>) -> RwLockWriteGuard<'a, Shard> {
+        self.acquisitions.fetch_add(1, Ordering::Relaxed);
+        match shard.try_write() {
+            Ok(guard) => guard,
+            Err(_) => {
+                self.contended.fetch_add(1, Ordering::Relaxed);
+                shard.write().await
+            }
+        }
+    }
+
+    /// Acquire a shard's read lock, recording contention.
+    async fn read_shard<'a>(&self, shard: &'a RwLock<Shard>) -> RwLockReadGuard<'a, Shard> {
+        self.acquisitions.fetch_add(1, Ordering::Relaxed);
+        match shard.try_read() {
+            Ok(guard) => guard,
+            Err(_) => {
+                self.contended.fetch_add(1, Ordering::Relaxed);
+                shard.read().await
+            }
         }
     }
 
@@ -160,17 +577,62 @@ impl TileCache {
     ///
     /// Returns `Some(data)` if the tile is cached, `None` otherwise.
     /// This operation marks the entry as recently used.
+    /// Stale entries are returned as well.
     pub async fn get(&self, key: &TileCacheKey) -> Option<Bytes> {
-        let mut cache = self.cache.write().await;
-        cache.get(key).cloned()
+        let hash = self.hash(key);
+        let mut shard = self.write_shard(self.shard_for(hash)).await;
+        shard.record_access(hash);
+        let data = shard.entries.get(key).map(|tile| tile.data.clone());
+        self.record_lookup(data.is_some());
+        data
+    }
+
+    /// Get a tile from the cache along with its staleness.
+    ///
+    /// This operation marks the entry as recently used.
+    pub async fn lookup(&self, key: &TileCacheKey) -> Option<CacheLookup> {
+        let hash = self.hash(key);
+        let mut shard = self.write_shard(self.shard_for(hash)).await;
+        shard.record_access(hash);
+        let tile = shard.entries.get(key);
+        self.record_lookup(tile.is_some());
+        let tile = tile?;
+        Some(CacheLookup {
+            data: tile.data.clone(),
+            stale: self.is_stale(key, tile),
+        })
+    }
+
+    /// Mark every cached tile of a slide as stale.
+    ///
+    /// Entries are kept so they can still be served while being refreshed;
+    /// a subsequent [`put`](Self::put) stores a fresh tile. This is O(1)
+    /// regardless of how many tiles are cached.
+    pub fn invalidate_slide(&self, slide_id: &str) {
+        let mut generations = self.generations.lock().unwrap();
+        *generations.entry(Arc::from(slide_id)).or_insert(0) += 1;
+    }
+
+    /// Claim the background refresh of a key.
+    ///
+    /// Returns `false` if a refresh is already in flight, so concurrent
+    /// requests for the same stale tile trigger a single regeneration.
+    /// Release the claim with [`end_refresh`](Self::end_refresh).
+    pub fn begin_refresh(&self, key: &TileCacheKey) -> bool {
+        self.refreshing.lock().unwrap().insert(key.clone())
+    }
+
+    /// Release a refresh claimed with [`begin_refresh`](Self::begin_refresh).
+    pub fn end_refresh(&self, key: &TileCacheKey) {
+        self.refreshing.lock().unwrap().remove(key);
     }
 
     /// Check if a tile is in the cache without updating LRU order.
     ///
     /// Returns `true` if the tile is cached, `false` otherwise.
     pub async fn contains(&self, key: &TileCacheKey) -> bool {
-        let cache = self.cache.read().await;
-        cache.contains(key)
+        let shard = self.read_shard(self.shard_for(self.hash(key))).await;
+        shard.entries.contains(key)
     }
 
     /// Store a tile in the cache.
@@ -179,76 +641,153 @@ impl TileCache {
     /// entries are evicted until the cache is within capacity.
     ///
     /// If the tile already exists, it is updated and marked as recently used.
-    pub async fn put(&self, key: TileCacheKey, data: Bytes) {
+    ///
+    /// With [`CacheAdmission::TinyLfu`], a new tile that would evict a more
+    /// frequently requested one is not stored.
+    ///
+    /// Returns the number of tiles evicted to make room.
+    pub async fn put(&self, key: TileCacheKey, data: Bytes) -> usize {
         let data_size = data.len();
-        let mut cache = self.cache.write().await;
-        let mut current_size = self.current_size.write().await;
-
-        // If key exists, subtract old size first
-        if let Some(old_data) = cache.peek(&key) {
-            *current_size = current_size.saturating_sub(old_data.len());
+        let hash = self.hash(&key);
+        let tile = CachedTile {
+            data,
+            stored_at: Instant::now(),
+            generation: self.generation(&key.slide_id),
+        };
+        let mut guard = self.write_shard(self.shard_for(hash)).await;
+        let shard = &mut *guard;
+        if !shard.admits(&key, hash, data_size, &self.hasher) {
+            self.rejected.fetch_add(1, Ordering::Relaxed);
+            return 0;
         }
-
-        // Insert the new data
-        cache.put(key, data);
-        *current_size += data_size;
+        self.admitted.fetch_add(1, Ordering::Relaxed);
+        let mut removed = 0;
+        let mut evicted = 0;
+
+        // Insert the new data. This returns the previous value for an
+        // existing key, or the LRU entry dropped when at the entry limit.
+        if let Some((old_key, old_tile)) = shard.entries.push(key.clone(), tile) {
+            removed += old_tile.data.len();
+            if old_key != key {
+                evicted += 1;
+            }
+        }
+        shard.size = shard.size.saturating_sub(removed) + data_size;
 
         // Evict entries until we're under capacity
-        while *current_size > self.max_size {
-            if let Some((_, evicted_data)) = cache.pop_lru() {
-                *current_size = current_size.saturating_sub(evicted_data.len());
+        while shard.size > shard.max_size {
+            if let Some((_, tile)) = shard.entries.pop_lru() {
+                shard.size = shard.size.saturating_sub(tile.data.len());
+                removed += tile.data.len();
+                evicted += 1;
             } else {
-                // Cache is empty, nothing more to evict
+                // Shard is empty, nothing more to evict
                 break;
             }
         }
+
+        self.current_size.fetch_add(data_size, Ordering::Relaxed);
+        self.current_size.fetch_sub(removed, Ordering::Relaxed);
+        self.evicted.fetch_add(evicted, Ordering::Relaxed);
+        evicted as usize
     }
 
     /// Remove a tile from the cache.
     ///
     /// Returns the cached data if it existed, `None` otherwise.
     pub async fn remove(&self, key: &TileCacheKey) -> Option<Bytes> {
-        let mut cache = self.cache.write().await;
-        let mut current_size = self.current_size.write().await;
+        let mut shard = self.write_shard(self.shard_for(self.hash(key))).await;
 
-        if let Some(data) = cache.pop(key) {
-            *current_size = current_size.saturating_sub(data.len());
-            Some(data)
-        } else {
-            None
+        let data = shard.entries.pop(key)?.data;
+        shard.size = shard.size.saturating_sub(data.len());
+        self.current_size.fetch_sub(data.len(), Ordering::Relaxed);
+        Some(data)
+    }
+
+    /// Evict least recently used tiles until at least `bytes` are freed or
+    /// the cache is empty, without changing the capacity.
+    ///
+    /// Each shard gives up a share of `bytes` proportional to its size.
+    /// Returns the bytes freed.
+    pub async fn evict_bytes(&self, bytes: usize) -> usize {
+        let total = self.current_size.load(Ordering::Relaxed).max(1);
+        let mut freed = 0;
+        for shard in self.shards.iter() {
+            let mut shard = self.write_shard(shard).await;
+            let target = (bytes as u128 * shard.size as u128).div_ceil(total as u128) as usize;
+            let mut shard_freed = 0;
+            while shard_freed < target {
+                let Some((_, tile)) = shard.entries.pop_lru() else {
+                    break;
+                };
+                shard_freed += tile.data.len();
+                self.evicted.fetch_add(1, Ordering::Relaxed);
+            }
+            shard.size = shard.size.saturating_sub(shard_freed);
+            self.current_size.fetch_sub(shard_freed, Ordering::Relaxed);
+            freed += shard_freed;
         }
+        freed
     }
 
     /// Clear all entries from the cache.
     pub async fn clear(&self) {
-        let mut cache = self.cache.write().await;
-        let mut current_size = self.current_size.write().await;
-        cache.clear();
-        *current_size = 0;
+        for shard in self.shards.iter() {
+            let mut shard = self.write_shard(shard).await;
+            shard.entries.clear();
+            self.current_size.fetch_sub(shard.size, Ordering::Relaxed);
+            shard.size = 0;
+        }
     }
 
     /// Get the current number of cached tiles.
     pub async fn len(&self) -> usize {
-        let cache = self.cache.read().await;
-        cache.len()
+        let mut len = 0;
+        for shard in self.shards.iter() {
+            len += self.read_shard(shard).await.entries.len();
+        }
+        len
     }
 
     /// Check if the cache is empty.
     pub async fn is_empty(&self) -> bool {
-        let cache = self.cache.read().await;
-        cache.is_empty()
+        self.len().await == 0
     }
 
     /// Get the current total size of cached tiles in bytes.
     pub async fn size(&self) -> usize {
-        let current_size = self.current_size.read().await;
-        *current_size
+        self.current_size.load(Ordering::Relaxed)
     }
 
     /// Get the maximum capacity in bytes.
     pub fn capacity(&self) -> usize {
         self.max_size
     }
+
+    /// Get the number of shards.
+    pub fn shard_count(&self) -> usize {
+        self.shards.len()
+    }
+
+    /// Get lock contention counters since the cache was created.
+    pub fn contention(&self) -> TileCacheContention {
+        TileCacheContention {
+            shards: self.shards.len(),
+            acquisitions: self.acquisitions.load(Ordering::Relaxed),
+            contended: self.contended.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Get hit, miss and admission counters since the cache was created.
+    pub fn stats(&self) -> TileCacheStats {
+        TileCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            admitted: self.admitted.load(Ordering::Relaxed),
+            rejected: self.rejected.load(Ordering::Relaxed),
+            evicted: self.evicted.load(Ordering::Relaxed),
+        }
+    }
 }
 
 impl Default for TileCache {
@@ -444,6 +983,243 @@ mod tests {
         assert_eq!(cache.capacity(), 50_000);
     }
 
+    #[tokio::test]
+    async fn test_sharded_cache() {
+        let cache = TileCache::with_shards(1_000_000, 1000, 8);
+        assert_eq!(cache.shard_count(), 8);
+
+        for x in 0..64 {
+            cache
+                .put(make_key("slide.svs", 0, x, 0, 80), make_tile(100))
+                .await;
+        }
+        assert_eq!(cache.len().await, 64);
+        assert_eq!(cache.size().await, 6400);
+
+        for x in 0..64 {
+            assert!(cache
+                .get(&make_key("slide.svs", 0, x, 0, 80))
+                .await
+                .is_some());
+        }
+
+        cache.remove(&make_key("slide.svs", 0, 0, 0, 80)).await;
+        assert_eq!(cache.size().await, 6300);
+
+        cache.clear().await;
+        assert!(cache.is_empty().await);
+        assert_eq!(cache.size().await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_sharded_cache_respects_capacity() {
+        let cache = TileCache::with_shards(4000, 1000, 4);
+
+        for x in 0..100 {
+            cache
+                .put(make_key("slide.svs", 0, x, 0, 80), make_tile(100))
+                .await;
+        }
+        assert!(cache.size().await <= 4000);
+        assert!(cache.len().await <= 40);
+    }
+
+    #[tokio::test]
+    async fn test_entry_limit_tracks_size() {
+        let cache = TileCache::with_capacity_and_entries(10_000, 2);
+
+        cache.put(make_key("a", 0, 0, 0, 80), make_tile(100)).await;
+        cache.put(make_key("b", 0, 0, 0, 80), make_tile(100)).await;
+        cache.put(make_key("c", 0, 0, 0, 80), make_tile(100)).await;
+
+        assert_eq!(cache.len().await, 2);
+        assert_eq!(cache.size().await, 200);
+    }
+
+    #[tokio::test]
+    async fn test_contention_stats() {
+        let cache = TileCache::with_shards(10_000, 100, 4);
+        let key = make_key("slide.svs", 0, 0, 0, 80);
+
+        cache.put(key.clone(), make_tile(100)).await;
+        cache.get(&key).await;
+
+        let contention = cache.contention();
+        assert_eq!(contention.shards, 4);
+        assert_eq!(contention.acquisitions, 2);
+        assert_eq!(contention.contended, 0);
+        assert_eq!(contention.contended_ratio(), 0.0);
+    }
+
+    /// Look a tile up, storing it on a miss as the tile service does.
+    async fn request(cache: &TileCache, key: TileCacheKey) -> bool {
+        if cache.lookup(&key).await.is_some() {
+            return true;
+        }
+        cache.put(key, make_tile(100)).await;
+        false
+    }
+
+    /// Serve viewers requesting the same overview tiles while a bulk job
+    /// scans level 0, returning the viewers' hits.
+    async fn scan_while_viewing(cache: &TileCache) -> usize {
+        let mut hits = 0;
+        for x in 0..200 {
+            if x % 10 == 0 {
+                for hot in 0..5 {
+                    hits += request(cache, make_key("slide.svs", 6, hot, 0, 80)).await as usize;
+                }
+            }
+            request(cache, make_key("slide.svs", 0, x, 0, 80)).await;
+        }
+        hits
+    }
+
+    #[tokio::test]
+    async fn test_tinylfu_resists_scans() {
+        // With plain LRU the scan flushes the overview tiles every time
+        let lru = TileCache::with_capacity_and_entries(1_000_000, 10);
+        assert_eq!(lru.admission(), CacheAdmission::Lru);
+        assert_eq!(scan_while_viewing(&lru).await, 0);
+        assert_eq!(lru.stats().rejected, 0);
+
+        // TinyLFU keeps the overview tiles; after their first round nearly
+        // every viewer request is a hit (frequency estimates are
+        // approximate, so a scanned tile occasionally gets in)
+        let tinylfu = TileCache::with_capacity_and_entries(1_000_000, 10)
+            .with_admission(CacheAdmission::TinyLfu);
+        let hits = scan_while_viewing(&tinylfu).await;
+        assert!(hits >= 17 * 5, "{hits} viewer hits");
+        assert!(tinylfu.stats().rejected > 150);
+        for hot in 0..5 {
+            assert!(
+                tinylfu
+                    .contains(&make_key("slide.svs", 6, hot, 0, 80))
+                    .await
+            );
+        }
+        assert!(tinylfu.stats().hit_ratio() > lru.stats().hit_ratio());
+    }
+
+    #[tokio::test]
+    async fn test_tinylfu_admits_when_not_full() {
+        let cache =
+            TileCache::with_capacity_and_entries(1_000, 10).with_admission(CacheAdmission::TinyLfu);
+
+        // Tiles never looked up are admitted while there is room
+        for x in 0..10 {
+            cache
+                .put(make_key("slide.svs", 0, x, 0, 80), make_tile(100))
+                .await;
+        }
+        assert_eq!(cache.len().await, 10);
+
+        // Replacing a cached tile is always admitted
+        cache
+            .put(make_key("slide.svs", 0, 0, 0, 80), make_tile(50))
+            .await;
+        assert_eq!(cache.size().await, 950);
+
+        // A tile requested more often than the LRU one evicts it
+        let popular = make_key("slide.svs", 1, 0, 0, 80);
+        for _ in 0..3 {
+            cache.lookup(&popular).await;
+        }
+        cache.put(popular.clone(), make_tile(100)).await;
+        assert!(cache.contains(&popular).await);
+        assert!(!cache.contains(&make_key("slide.svs", 0, 1, 0, 80)).await);
+        assert_eq!(cache.stats().evicted, 1);
+    }
+
+    #[tokio::test]
+    async fn test_hit_ratio_stats() {
+        let cache = TileCache::new();
+        assert_eq!(cache.stats().hit_ratio(), 0.0);
+
+        let key = make_key("slide.svs", 0, 0, 0, 80);
+        assert!(!request(&cache, key.clone()).await);
+        for _ in 0..3 {
+            assert!(request(&cache, key.clone()).await);
+        }
+        cache.get(&key).await;
+
+        let stats = cache.stats();
+        assert_eq!(stats.hits, 4);
+        assert_eq!(stats.misses, 1);
+        assert_eq!(stats.admitted, 1);
+        assert_eq!(stats.hit_ratio(), 0.8);
+    }
+
+    #[tokio::test]
+    async fn test_evict_bytes() {
+        let cache = TileCache::with_shards(10_000, 100, 4);
+        for x in 0..40 {
+            cache
+                .put(make_key("slide.svs", 0, x, 0, 80), make_tile(100))
+                .await;
+        }
+        assert_eq!(cache.size().await, 4000);
+
+        // Every shard gives up its share, rounded up to whole tiles
+        let freed = cache.evict_bytes(1000).await;
+        assert!((1000..1400).contains(&freed), "freed {freed}");
+        assert_eq!(cache.size().await, 4000 - freed);
+        assert_eq!(cache.capacity(), 10_000);
+        assert_eq!(cache.stats().evicted as usize, freed / 100);
+
+        assert_eq!(cache.evict_bytes(usize::MAX).await, 4000 - freed);
+        assert!(cache.is_empty().await);
+    }
+
+    #[tokio::test]
+    async fn test_invalidate_slide_marks_entries_stale() {
+        let cache = TileCache::new();
+        let key = make_key("a.svs", 0, 0, 0, 80);
+        let other = make_key("b.svs", 0, 0, 0, 80);
+        cache.put(key.clone(), make_tile(100)).await;
+        cache.put(other.clone(), make_tile(100)).await;
+
+        cache.invalidate_slide("a.svs");
+
+        // The stale entry is kept and still served by get()
+        let lookup = cache.lookup(&key).await.unwrap();
+        assert!(lookup.stale);
+        assert_eq!(cache.get(&key).await, Some(make_tile(100)));
+        assert!(!cache.lookup(&other).await.unwrap().stale);
+
+        // Storing a new tile makes it fresh again
+        cache.put(key.clone(), make_tile(50)).await;
+        let lookup = cache.lookup(&key).await.unwrap();
+        assert!(!lookup.stale);
+        assert_eq!(lookup.data.len(), 50);
+    }
+
+    #[tokio::test]
+    async fn test_ttl_marks_entries_stale() {
+        let cache = TileCache::new().with_ttl(Duration::from_millis(20));
+        let key = make_key("a.svs", 0, 0, 0, 80);
+        cache.put(key.clone(), make_tile(100)).await;
+        assert!(!cache.lookup(&key).await.unwrap().stale);
+
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert!(cache.lookup(&key).await.unwrap().stale);
+        assert!(cache
+            .lookup(&make_key("a.svs", 0, 1, 0, 80))
+            .await
+            .is_none());
+    }
+
+    #[test]
+    fn test_refresh_claims_are_exclusive() {
+        let cache = TileCache::new();
+        let key = make_key("a.svs", 0, 0, 0, 80);
+
+        assert!(cache.begin_refresh(&key));
+        assert!(!cache.begin_refresh(&key));
+        cache.end_refresh(&key);
+        assert!(cache.begin_refresh(&key));
+    }
+
     #[test]
     fn test_cache_key_equality() {
         let key1 = make_key("slide.svs", 0, 1, 2, 80);
@@ -452,6 +1228,7 @@ mod tests {
 
         assert_eq!(key1, key2);
         assert_ne!(key1, key3);
+        assert_ne!(key1, key2.with_format(OutputFormat::Webp));
     }
 
     #[test]
@@ -1,12 +1,17 @@
-//! Tile encoder with JPEG and JPEG 2000 support.
+//! Tile encoder with JPEG, JPEG 2000, LZW and Deflate support.
 //!
-//! This module handles decoding source tiles (JPEG or JPEG 2000) and
-//! re-encoding them as JPEG at a specified quality level.
+//! This module handles decoding source tiles (JPEG, JPEG 2000, or LZW/Deflate
+//! raw pixels) and re-encoding them as JPEG at a specified quality level, or
+//! as PNG or WebP (see [`OutputFormat`]).
 //!
 //! # Design Decisions
 //!
-//! - **Always decode/encode**: For simplicity and correctness, tiles are always
-//!   decoded from source format and re-encoded as JPEG. No passthrough optimization.
+//! - **Decode/encode by default**: Tiles are decoded from source format and
+//!   re-encoded as JPEG with IJG tables scaled by the requested quality.
+//!   Opting in with [`TileEncoder::with_source_reuse`] returns a JPEG source
+//!   unchanged when its quantization tables are exactly the tables the
+//!   re-encode would use, since re-quantizing with identical tables only
+//!   adds generation loss and CPU.
 //!
 //! - **No resizing**: Tiles are served at their native size. The tile coordinates
 //!   specify tile indices, not pixel coordinates.
@@ -14,16 +19,44 @@
 //! - **Quality control**: JPEG quality is configurable per request, allowing
 //!   clients to trade off file size vs image quality.
 //!
+//! - **Deterministic mode**: For golden-image regression tests, the encoder
+//!   can be pinned to a single reproducible path: no source passthrough (which
+//!   depends on the quality estimation heuristic), pixels normalized to 8-bit
+//!   RGB, and the baseline IJG quantization tables scaled by quality. Decoding
+//!   and encoding are single-threaded, so output bytes depend only on the
+//!   source tile, the quality and the codec versions.
+//!
 //! - **Format detection**: Source format is auto-detected from magic bytes,
 //!   supporting both JPEG (FFD8) and JPEG 2000 (FF4F or JP2 container).
+//!   JPEG 2000 decoding requires the `jpeg2000` feature; without it, JPEG 2000
+//!   tiles are rejected as an unsupported compression. LZW and Deflate tiles
+//!   arrive wrapped by the slide readers and are decoded by
+//!   [`crate::format::codec`].
+//!
+//! - **Output formats**: JPEG is the default. PNG is lossless, for mask
+//!   overlays that must not pick up compression artifacts. Lossy WebP is
+//!   typically around 30% smaller than JPEG at equivalent quality and
+//!   requires the `webp` feature (libwebp).
 
 use bytes::Bytes;
-use image::codecs::jpeg::JpegEncoder;
-use image::{DynamicImage, ImageReader};
+use image::codecs::jpeg::{JpegDecoder, JpegEncoder};
+use image::codecs::png::PngEncoder;
+use image::{DynamicImage, ExtendedColorType, ImageDecoder, ImageReader, Limits, RgbImage};
+#[cfg(feature = "jpeg2000")]
 use jpeg2k::Image as J2kImage;
-use std::io::Cursor;
+use serde::Deserialize;
+use std::io::{Cursor, Write};
+use std::sync::Arc;
 
 use crate::error::TileError;
+use crate::format::codec::{decode_raw_tile, is_raw_tile, RawTileLayout};
+use crate::overlay::TileOverlay;
+use crate::timing::{self, Phase};
+
+use super::adjust::{Channel, ToneAdjustment};
+use super::jpeg_crop::crop_jpeg;
+use super::pool::PooledBuffer;
+use super::watermark::Watermark;
 
 // =============================================================================
 // Format Detection
@@ -36,6 +69,8 @@ enum TileFormat {
     Jpeg,
     /// JPEG 2000 codestream or JP2 container
     Jpeg2000,
+    /// LZW/Deflate pixels wrapped with their layout by the slide reader
+    Raw,
     /// Unknown format
     Unknown,
 }
@@ -68,6 +103,10 @@ fn detect_tile_format(data: &[u8]) -> TileFormat {
         return TileFormat::Jpeg2000;
     }
 
+    if is_raw_tile(data) {
+        return TileFormat::Raw;
+    }
+
     TileFormat::Unknown
 }
 
@@ -78,6 +117,7 @@ fn detect_tile_format(data: &[u8]) -> TileFormat {
 ///
 /// # Returns
 /// Decoded image, or error if decoding fails.
+#[cfg(feature = "jpeg2000")]
 fn decode_jpeg2000(data: &[u8]) -> Result<DynamicImage, TileError> {
     let j2k_image = J2kImage::from_bytes(data).map_err(|e| TileError::DecodeError {
         message: format!("JPEG 2000 decode error: {}", e),
@@ -106,6 +146,7 @@ fn decode_jpeg2000(data: &[u8]) -> Result<DynamicImage, TileError> {
 ///
 /// This handles YCbCr 4:2:0 subsampled images by manually upsampling
 /// and converting to RGB.
+#[cfg(feature = "jpeg2000")]
 fn decode_jpeg2000_manual(j2k_image: &J2kImage) -> Result<DynamicImage, TileError> {
     let num_components = j2k_image.num_components();
     let components = j2k_image.components();
@@ -221,6 +262,386 @@ fn decode_jpeg2000_manual(j2k_image: &J2kImage) -> Result<DynamicImage, TileErro
     }
 }
 
+/// JPEG 2000 placeholder for builds without the `jpeg2000` feature.
+#[cfg(not(feature = "jpeg2000"))]
+fn decode_jpeg2000(_data: &[u8]) -> Result<DynamicImage, TileError> {
+    Err(jpeg2000_unavailable())
+}
+
+/// Read the dimensions of a JPEG 2000 tile.
+#[cfg(feature = "jpeg2000")]
+fn jpeg2000_dimensions(data: &[u8]) -> Result<(u32, u32), TileError> {
+    // jpeg2k requires full decode to get dimensions
+    let j2k = J2kImage::from_bytes(data).map_err(|e| TileError::DecodeError {
+        message: format!("JPEG 2000 decode error: {}", e),
+    })?;
+    Ok((j2k.width(), j2k.height()))
+}
+
+/// JPEG 2000 placeholder for builds without the `jpeg2000` feature.
+#[cfg(not(feature = "jpeg2000"))]
+fn jpeg2000_dimensions(_data: &[u8]) -> Result<(u32, u32), TileError> {
+    Err(jpeg2000_unavailable())
+}
+
+/// Error for JPEG 2000 tiles in builds without the `jpeg2000` feature.
+#[cfg(not(feature = "jpeg2000"))]
+fn jpeg2000_unavailable() -> TileError {
+    TileError::Slide(crate::error::TiffError::UnsupportedCompression(
+        "JPEG 2000 (this build lacks the jpeg2000 feature)".to_string(),
+    ))
+}
+
+/// Largest width or height accepted when decoding a JPEG tile.
+///
+/// Tiles are at most a few thousand pixels wide; the SOF header is
+/// untrusted, so a larger frame is rejected before any pixel buffer is
+/// allocated for it.
+pub const MAX_DECODE_DIMENSION: u32 = 16_384;
+
+/// Decoder limits applied to untrusted JPEG tiles.
+fn decode_limits() -> Limits {
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_DECODE_DIMENSION);
+    limits.max_image_height = Some(MAX_DECODE_DIMENSION);
+    limits
+}
+
+/// Decode a JPEG, JPEG 2000 or wrapped raw tile.
+pub(crate) fn decode_tile(source: &[u8]) -> Result<DynamicImage, TileError> {
+    timing::measure_sync(Phase::Decode, || match detect_tile_format(source) {
+        TileFormat::Jpeg => {
+            let cursor = Cursor::new(source);
+            let mut reader = ImageReader::with_format(cursor, image::ImageFormat::Jpeg);
+            reader.limits(decode_limits());
+            reader.decode().map_err(|e| TileError::DecodeError {
+                message: format!("JPEG decode error: {}", e),
+            })
+        }
+        TileFormat::Jpeg2000 => decode_jpeg2000(source),
+        TileFormat::Raw => decode_raw_tile(source),
+        TileFormat::Unknown => Err(TileError::DecodeError {
+            message: "Unknown tile format: expected JPEG or JPEG 2000".to_string(),
+        }),
+    })
+}
+
+/// Encode an image as JPEG at the given quality.
+pub(crate) fn encode_image(img: &DynamicImage, quality: u8) -> Result<Bytes, TileError> {
+    let mut output = PooledBuffer::acquire();
+    let mut encoder = JpegEncoder::new_with_quality(&mut *output, quality);
+
+    // A `DynamicImage` is viewed as RGBA, so grayscale images are passed
+    // as-is to keep a single-component JPEG
+    let result = timing::measure_sync(Phase::Encode, || match img {
+        DynamicImage::ImageLuma8(gray) => encoder.encode_image(gray),
+        _ => encoder.encode_image(img),
+    });
+    result.map_err(|e| TileError::EncodeError {
+        message: e.to_string(),
+    })?;
+
+    Ok(output.to_bytes())
+}
+
+/// Re-encode a JPEG tile using pooled buffers for both the decoded pixels
+/// and the encoder output.
+fn reencode_jpeg(source: &[u8], quality: u8) -> Result<Bytes, TileError> {
+    let to_decode_error = |e: image::ImageError| TileError::DecodeError {
+        message: format!("JPEG decode error: {}", e),
+    };
+
+    let mut decoder = JpegDecoder::new(Cursor::new(source)).map_err(to_decode_error)?;
+    let mut limits = decode_limits();
+    decoder
+        .set_limits(limits.clone())
+        .map_err(to_decode_error)?;
+    limits
+        .reserve(decoder.total_bytes())
+        .map_err(to_decode_error)?;
+    let (width, height) = decoder.dimensions();
+    let color_type = ExtendedColorType::from(decoder.color_type());
+
+    let mut pixels = PooledBuffer::acquire();
+    pixels.resize(decoder.total_bytes() as usize, 0);
+    timing::measure_sync(Phase::Decode, || decoder.read_image(&mut pixels))
+        .map_err(to_decode_error)?;
+
+    timing::measure_sync(Phase::Encode, || {
+        let mut output = PooledBuffer::acquire();
+        let mut encoder = JpegEncoder::new_with_quality(&mut *output, quality);
+
+        encoder
+            .encode(&pixels, width, height, color_type)
+            .map_err(|e| TileError::EncodeError {
+                message: e.to_string(),
+            })?;
+
+        Ok(output.to_bytes())
+    })
+}
+
+// =============================================================================
+// Source Quality Detection
+// =============================================================================
+
+/// IJG standard luminance quantization table (Annex K of the JPEG spec), in
+/// natural (row-major) order.
+const STD_LUMINANCE_QTABLE: [u16; 64] = [
+    16, 11, 10, 16, 24, 40, 51, 61, 12, 12, 14, 19, 26, 58, 60, 55, 14, 13, 16, 24, 40, 57, 69, 56,
+    14, 17, 22, 29, 51, 87, 80, 62, 18, 22, 37, 56, 68, 109, 103, 77, 24, 35, 55, 64, 81, 104, 113,
+    92, 49, 64, 78, 87, 103, 121, 120, 101, 72, 92, 95, 98, 112, 100, 103, 99,
+];
+
+/// IJG standard chrominance quantization table (Annex K of the JPEG spec), in
+/// natural (row-major) order.
+const STD_CHROMINANCE_QTABLE: [u16; 64] = [
+    17, 18, 24, 47, 99, 99, 99, 99, 18, 21, 26, 66, 99, 99, 99, 99, 24, 26, 56, 99, 99, 99, 99, 99,
+    47, 66, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99,
+    99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99,
+];
+
+/// Natural index of each coefficient in zigzag order, the order DQT
+/// segments store tables in.
+const ZIGZAG: [usize; 64] = [
+    0, 1, 8, 16, 9, 2, 3, 10, 17, 24, 32, 25, 18, 11, 4, 5, 12, 19, 26, 33, 40, 48, 41, 34, 27, 20,
+    13, 6, 7, 14, 21, 28, 35, 42, 49, 56, 57, 50, 43, 36, 29, 22, 15, 23, 30, 37, 44, 51, 58, 59,
+    52, 45, 38, 31, 39, 46, 53, 60, 61, 54, 47, 55, 62, 63,
+];
+
+/// Maximum difference between [`estimate_jpeg_quality`] and the quality a
+/// stream was actually encoded at, for streams using scaled IJG tables.
+pub const SOURCE_QUALITY_TOLERANCE: u8 = 1;
+
+/// Standard table a quantization table slot is scaled from: luminance for
+/// table 0, chrominance for the others.
+fn standard_table(table_id: u8) -> &'static [u16; 64] {
+    if table_id == 0 {
+        &STD_LUMINANCE_QTABLE
+    } else {
+        &STD_CHROMINANCE_QTABLE
+    }
+}
+
+/// Scale a standard table to `quality` the way libjpeg (and our encoder)
+/// does, clamped to baseline 8-bit values.
+fn scaled_table(standard: &[u16; 64], quality: u8) -> [u16; 64] {
+    let quality = quality.clamp(MIN_JPEG_QUALITY, MAX_JPEG_QUALITY) as u32;
+    let scale = if quality < 50 {
+        5000 / quality
+    } else {
+        200 - quality * 2
+    };
+    standard.map(|v| ((v as u32 * scale + 50) / 100).clamp(1, 255) as u16)
+}
+
+/// Header information relevant to the passthrough fast path.
+#[derive(Debug, Default)]
+struct JpegHeaderInfo {
+    /// Quantization tables by id, in natural order
+    tables: Vec<(u8, [u16; 64])>,
+
+    /// Component identifiers from the frame header
+    component_ids: Vec<u8>,
+
+    /// Quantization table id used by each frame component
+    component_tables: Vec<u8>,
+
+    /// Adobe APP14 color transform flag, if present
+    adobe_transform: Option<u8>,
+
+    /// Whether the frame is baseline or extended sequential
+    sequential: bool,
+}
+
+/// Scan JPEG header segments up to the first SOS marker.
+///
+/// Returns `None` if the data is not a well-formed JPEG header.
+fn scan_jpeg_header(data: &[u8]) -> Option<JpegHeaderInfo> {
+    if data.len() < 4 || data[0] != 0xFF || data[1] != 0xD8 {
+        return None;
+    }
+
+    let mut info = JpegHeaderInfo::default();
+    let mut pos = 2;
+
+    while pos + 4 <= data.len() {
+        if data[pos] != 0xFF {
+            return None;
+        }
+        let marker = data[pos + 1];
+
+        // Fill bytes and standalone markers have no length
+        if marker == 0xFF {
+            pos += 1;
+            continue;
+        }
+        if marker == 0x01 || (0xD0..=0xD7).contains(&marker) {
+            pos += 2;
+            continue;
+        }
+        if marker == 0xDA || marker == 0xD9 {
+            break;
+        }
+
+        let length = u16::from_be_bytes([data[pos + 2], data[pos + 3]]) as usize;
+        if length < 2 || pos + 2 + length > data.len() {
+            return None;
+        }
+        let payload = &data[pos + 4..pos + 2 + length];
+
+        match marker {
+            // DQT: one or more tables
+            0xDB => {
+                let mut p = 0;
+                while p < payload.len() {
+                    let precision = payload[p] >> 4;
+                    let table_id = payload[p] & 0x0F;
+                    let entry_size = if precision == 0 { 1 } else { 2 };
+                    let table_len = 64 * entry_size;
+                    if p + 1 + table_len > payload.len() {
+                        return None;
+                    }
+                    let mut table = [0u16; 64];
+                    for (i, &natural) in ZIGZAG.iter().enumerate() {
+                        let at = p + 1 + i * entry_size;
+                        table[natural] = if entry_size == 1 {
+                            payload[at] as u16
+                        } else {
+                            u16::from_be_bytes([payload[at], payload[at + 1]])
+                        };
+                    }
+                    // A later definition of the same id replaces the earlier one
+                    info.tables.retain(|&(id, _)| id != table_id);
+                    info.tables.push((table_id, table));
+                    p += 1 + table_len;
+                }
+            }
+            // SOF markers (excluding DHT, JPG and DAC)
+            0xC0..=0xCF if marker != 0xC4 && marker != 0xC8 && marker != 0xCC => {
+                info.sequential = marker == 0xC0 || marker == 0xC1;
+                if payload.len() >= 6 {
+                    let count = payload[5] as usize;
+                    info.component_ids = (0..count)
+                        .filter_map(|i| payload.get(6 + i * 3).copied())
+                        .collect();
+                    info.component_tables = (0..count)
+                        .filter_map(|i| payload.get(6 + i * 3 + 2).copied())
+                        .collect();
+                }
+            }
+            // APP14 "Adobe" segment carries the color transform flag
+            0xEE if payload.len() >= 12 && &payload[0..5] == b"Adobe" => {
+                info.adobe_transform = Some(payload[11]);
+            }
+            _ => {}
+        }
+
+        pos += 2 + length;
+    }
+
+    Some(info)
+}
+
+/// Estimate the IJG quality (1-100) a JPEG stream was encoded with.
+///
+/// The estimate compares every quantization table in the stream against the
+/// IJG standard table it would be scaled from (luminance for table 0,
+/// chrominance for the others) and inverts the libjpeg quality scaling.
+/// Streams using custom (non-IJG) tables still get an estimate of
+/// equivalent strength.
+///
+/// Returns `None` if the data is not a JPEG or has no quantization tables.
+pub fn estimate_jpeg_quality(data: &[u8]) -> Option<u8> {
+    estimate_table_quality(&scan_jpeg_header(data)?.tables)
+}
+
+/// Estimate the IJG quality of a set of quantization tables.
+fn estimate_table_quality(tables: &[(u8, [u16; 64])]) -> Option<u8> {
+    if tables.is_empty() {
+        return None;
+    }
+
+    let sum: f64 = tables
+        .iter()
+        .flat_map(|(_, table)| table.iter())
+        .map(|&v| v as f64)
+        .sum();
+    let std_sum: f64 = tables
+        .iter()
+        .flat_map(|&(id, _)| standard_table(id).iter())
+        .map(|&v| v as f64)
+        .sum();
+    let scale = sum * 100.0 / std_sum;
+
+    let quality = if scale <= 100.0 {
+        (200.0 - scale) / 2.0
+    } else {
+        5000.0 / scale
+    };
+
+    Some(
+        quality
+            .round()
+            .clamp(MIN_JPEG_QUALITY as f64, MAX_JPEG_QUALITY as f64) as u8,
+    )
+}
+
+/// Source quality of a JPEG stream that is safe to serve unchanged.
+///
+/// Streams are excluded when browsers might render them differently from
+/// the decoded-and-re-encoded output: progressive/lossless frames, and RGB
+/// (non-YCbCr) color encodings that rely on out-of-band TIFF metadata.
+fn passthrough_quality(data: &[u8]) -> Option<u8> {
+    let info = passthrough_header(data)?;
+    estimate_table_quality(&info.tables)
+}
+
+/// Header of a JPEG stream that is safe to serve unchanged.
+fn passthrough_header(data: &[u8]) -> Option<JpegHeaderInfo> {
+    let info = scan_jpeg_header(data)?;
+    if !info.sequential || info.tables.is_empty() {
+        return None;
+    }
+    if info.adobe_transform == Some(0) && info.component_ids.len() == 3 {
+        return None;
+    }
+    if info.component_ids == b"RGB" {
+        return None;
+    }
+    Some(info)
+}
+
+/// Whether a JPEG stream is quantized exactly as a re-encode at `quality`
+/// would quantize it: luminance on IJG table 0, chrominance on IJG table 1,
+/// both scaled to `quality`.
+///
+/// Serving such a stream unchanged is indistinguishable from re-encoding
+/// it, minus the generation loss.
+fn matches_encoder_tables(data: &[u8], quality: u8) -> bool {
+    let Some(info) = passthrough_header(data) else {
+        return false;
+    };
+    if info.component_tables.is_empty() {
+        return false;
+    }
+
+    info.component_tables
+        .iter()
+        .enumerate()
+        .all(|(component, &table_id)| {
+            let expected_id = u8::from(component > 0);
+            table_id == expected_id
+                && info
+                    .tables
+                    .iter()
+                    .find(|&&(id, _)| id == table_id)
+                    .is_some_and(|(_, table)| {
+                        *table == scaled_table(standard_table(table_id), quality)
+                    })
+        })
+}
+
 /// Default JPEG quality (1-100).
 pub const DEFAULT_JPEG_QUALITY: u8 = 80;
 
@@ -230,49 +651,204 @@ pub const MIN_JPEG_QUALITY: u8 = 1;
 /// Maximum allowed JPEG quality.
 pub const MAX_JPEG_QUALITY: u8 = 100;
 
+/// Encode an image in the given output format.
+fn encode_image_as(
+    img: &DynamicImage,
+    quality: u8,
+    format: OutputFormat,
+) -> Result<Bytes, TileError> {
+    if format == OutputFormat::Jpeg {
+        return encode_image(img, quality);
+    }
+
+    timing::measure_sync(Phase::Encode, || {
+        let mut output = PooledBuffer::acquire();
+        format.encode(&img.to_rgb8(), quality, &mut *output)?;
+        Ok(output.to_bytes())
+    })
+}
+
+/// Encode crops `(x, y, width, height)` of an image, clamped to its bounds,
+/// stamping each crop with a watermark if given.
+fn encode_image_crops(
+    img: &DynamicImage,
+    quality: u8,
+    format: OutputFormat,
+    watermark: Option<&Watermark>,
+    crops: &[(u32, u32, u32, u32)],
+) -> Result<Vec<Bytes>, TileError> {
+    crops
+        .iter()
+        .map(|&(x, y, width, height)| {
+            let x = x.min(img.width().saturating_sub(1));
+            let y = y.min(img.height().saturating_sub(1));
+            let width = width.min(img.width() - x).max(1);
+            let height = height.min(img.height() - y).max(1);
+            let crop = img.crop_imm(x, y, width, height);
+            let crop = match watermark {
+                Some(watermark) => watermark.apply(crop),
+                None => crop,
+            };
+            encode_image_as(&crop, quality, format)
+        })
+        .collect()
+}
+
+/// Pixel changes made to tiles between decoding and encoding.
+#[derive(Debug, Clone, Default)]
+pub struct TileRendering {
+    /// Brightness, contrast and gamma
+    pub adjustment: Option<ToneAdjustment>,
+
+    /// Single channel served as a grayscale tile
+    pub channel: Option<Channel>,
+
+    /// Annotations drawn onto the decoded source tile
+    pub overlay: Option<TileOverlay>,
+
+    /// Stamp burned into each output tile
+    pub watermark: Option<Arc<Watermark>>,
+}
+
+impl TileRendering {
+    /// Whether tiles are encoded from unchanged pixels.
+    pub fn is_identity(&self) -> bool {
+        self.adjustment.is_none()
+            && self.channel.is_none()
+            && self.overlay.is_none()
+            && self.watermark.is_none()
+    }
+}
+
 // =============================================================================
-// JPEG Encoder
+// Tile Encoder
 // =============================================================================
 
-/// JPEG tile encoder for decoding and re-encoding tiles.
+/// Tile encoder for decoding and re-encoding tiles.
 ///
-/// This encoder takes raw JPEG data from slide tiles, decodes it to pixels,
-/// and re-encodes it at the requested quality level.
+/// This encoder takes raw tile data from slides, decodes it to pixels, and
+/// re-encodes it at the requested quality level. [`encode`](Self::encode)
+/// produces JPEG; [`encode_as`](Self::encode_as) produces any
+/// [`OutputFormat`].
 ///
 /// # Example
 ///
 /// ```ignore
-/// use wsi_streamer::tile::JpegTileEncoder;
+/// use wsi_streamer::tile::{OutputFormat, TileEncoder};
 /// use bytes::Bytes;
 ///
-/// let encoder = JpegTileEncoder::new();
+/// let encoder = TileEncoder::new();
 ///
 /// // Source JPEG data from slide
 /// let source_jpeg: Bytes = /* ... */;
 ///
 /// // Re-encode at quality 85
 /// let output = encoder.encode(&source_jpeg, 85)?;
+///
+/// // Or as WebP
+/// let output = encoder.encode_as(&source_jpeg, 85, OutputFormat::Webp)?;
 /// ```
-#[derive(Debug, Clone, Default)]
-pub struct JpegTileEncoder {
-    // Currently stateless, but struct allows future extension
-    // (e.g., shared thread pool, encoder settings)
+#[derive(Debug, Clone)]
+pub struct TileEncoder {
+    /// Serve JPEG sources unchanged when their tables match the request
+    reuse_source: bool,
+
+    /// Always decode to RGB and re-encode, for reproducible output
+    deterministic: bool,
 }
 
-impl JpegTileEncoder {
-    /// Create a new JPEG tile encoder.
+/// Former name of [`TileEncoder`], from when it only produced JPEG.
+pub type JpegTileEncoder = TileEncoder;
+
+impl Default for TileEncoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TileEncoder {
+    /// Create a new tile encoder.
+    ///
+    /// Source reuse is disabled: every tile is re-encoded at the requested
+    /// quality.
     pub fn new() -> Self {
-        Self {}
+        Self {
+            reuse_source: false,
+            deterministic: false,
+        }
+    }
+
+    /// Enable or disable deterministic encoding.
+    ///
+    /// When enabled, every tile is decoded, normalized to 8-bit RGB and
+    /// re-encoded, so identical inputs always produce identical bytes.
+    /// Source reuse is disabled.
+    pub fn with_deterministic(mut self, enabled: bool) -> Self {
+        self.deterministic = enabled;
+        if enabled {
+            self.reuse_source = false;
+        }
+        self
+    }
+
+    /// Check whether deterministic encoding is enabled.
+    pub fn is_deterministic(&self) -> bool {
+        self.deterministic
+    }
+
+    /// Enable or disable serving JPEG sources unchanged when every component
+    /// is quantized with the IJG table scaled to the requested quality, i.e.
+    /// the tables a re-encode would use.
+    ///
+    /// Sources with custom tables, or IJG tables at a different quality, are
+    /// always re-encoded. Ignored in deterministic mode.
+    pub fn with_source_reuse(mut self, enabled: bool) -> Self {
+        self.reuse_source = enabled;
+        self
+    }
+
+    /// Get the quality a source tile can be served at without re-encoding.
+    ///
+    /// Returns `None` for JPEG 2000 sources and JPEG streams that are not
+    /// eligible for passthrough.
+    pub fn source_quality(&self, source: &[u8]) -> Option<u8> {
+        passthrough_quality(source)
+    }
+
+    /// Check whether a source tile can be served unchanged, at any quality.
+    ///
+    /// Always false in deterministic mode.
+    pub fn can_pass_through(&self, source: &[u8]) -> bool {
+        !self.deterministic && passthrough_quality(source).is_some()
+    }
+
+    /// Serve a tile at its source quality, avoiding re-encoding when possible.
+    ///
+    /// Eligible JPEG sources are returned unchanged; other sources are
+    /// re-encoded at [`DEFAULT_JPEG_QUALITY`].
+    ///
+    /// # Returns
+    ///
+    /// The tile data and the quality it is encoded at.
+    pub fn encode_at_source_quality(&self, source: &[u8]) -> Result<(Bytes, u8), TileError> {
+        match passthrough_quality(source).filter(|_| !self.deterministic) {
+            Some(quality) => Ok((Bytes::copy_from_slice(source), quality)),
+            None => Ok((
+                self.encode(source, DEFAULT_JPEG_QUALITY)?,
+                DEFAULT_JPEG_QUALITY,
+            )),
+        }
     }
 
     /// Decode source tile and re-encode at the specified quality.
     ///
-    /// This method auto-detects the source format (JPEG or JPEG 2000) and
-    /// decodes accordingly. Output is always JPEG.
+    /// This method auto-detects the source format (JPEG, JPEG 2000 or a
+    /// wrapped LZW/Deflate tile) and decodes accordingly. Output is always
+    /// JPEG.
     ///
     /// # Arguments
     ///
-    /// * `source` - Raw tile data (JPEG or JPEG 2000)
+    /// * `source` - Tile data as returned by the slide reader
     /// * `quality` - Output JPEG quality (1-100)
     ///
     /// # Returns
@@ -289,36 +865,162 @@ impl JpegTileEncoder {
         // Clamp quality to valid range
         let quality = quality.clamp(MIN_JPEG_QUALITY, MAX_JPEG_QUALITY);
 
-        // Detect source format and decode
-        let format = detect_tile_format(source);
+        // Fast path: source already quantized with the requested tables
+        if self.reuse_source && matches_encoder_tables(source, quality) {
+            return Ok(Bytes::copy_from_slice(source));
+        }
 
-        let img = match format {
-            TileFormat::Jpeg => {
-                let cursor = Cursor::new(source);
-                let reader = ImageReader::with_format(cursor, image::ImageFormat::Jpeg);
-                reader.decode().map_err(|e| TileError::DecodeError {
-                    message: format!("JPEG decode error: {}", e),
-                })?
-            }
-            TileFormat::Jpeg2000 => decode_jpeg2000(source)?,
-            TileFormat::Unknown => {
-                return Err(TileError::DecodeError {
-                    message: "Unknown tile format: expected JPEG or JPEG 2000".to_string(),
-                });
+        if self.deterministic {
+            let img = DynamicImage::ImageRgb8(decode_tile(source)?.into_rgb8());
+            return encode_image(&img, quality);
+        }
+
+        if detect_tile_format(source) == TileFormat::Jpeg {
+            return reencode_jpeg(source, quality);
+        }
+
+        let img = decode_tile(source)?;
+        encode_image(&img, quality)
+    }
+
+    /// Encode a tile cropped to `width` x `height` (anchored at the top-left).
+    ///
+    /// Used to trim padded edge tiles to their true dimensions. With source
+    /// reuse enabled and a JPEG source already quantized with the requested
+    /// tables, the crop is done losslessly in the DCT domain without
+    /// decoding; otherwise the tile is
+    /// decoded, cropped and re-encoded. Crops larger than the tile are
+    /// clamped to the tile size.
+    pub fn encode_cropped(
+        &self,
+        source: &[u8],
+        quality: u8,
+        width: u32,
+        height: u32,
+    ) -> Result<Bytes, TileError> {
+        let quality = quality.clamp(MIN_JPEG_QUALITY, MAX_JPEG_QUALITY);
+
+        // Fast path: lossless crop of a source already quantized with the
+        // requested tables
+        if self.reuse_source && matches_encoder_tables(source, quality) {
+            if let Some(cropped) = crop_jpeg(source, width, height) {
+                return Ok(cropped);
             }
-        };
+        }
 
-        // Encode to JPEG at requested quality
-        let mut output = Vec::new();
-        let mut encoder = JpegEncoder::new_with_quality(&mut output, quality);
+        let mut img = decode_tile(source)?;
+        if self.deterministic {
+            img = DynamicImage::ImageRgb8(img.into_rgb8());
+        }
+        let width = width.min(img.width());
+        let height = height.min(img.height());
+        if width == img.width() && height == img.height() {
+            return encode_image(&img, quality);
+        }
+        encode_image(&img.crop_imm(0, 0, width, height), quality)
+    }
 
-        encoder
-            .encode_image(&img)
-            .map_err(|e| TileError::EncodeError {
-                message: e.to_string(),
-            })?;
+    /// Decode a source tile and encode it in the given output format.
+    ///
+    /// JPEG output goes through [`encode`](Self::encode), including its
+    /// source passthrough. PNG and WebP always decode and re-encode; PNG
+    /// ignores `quality`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TileError::UnsupportedOutputFormat`] if the format was not
+    /// compiled into this build, or a decode or encode error.
+    pub fn encode_as(
+        &self,
+        source: &[u8],
+        quality: u8,
+        format: OutputFormat,
+    ) -> Result<Bytes, TileError> {
+        if format == OutputFormat::Jpeg {
+            return self.encode(source, quality);
+        }
+        format.ensure_supported()?;
+
+        let quality = quality.clamp(MIN_JPEG_QUALITY, MAX_JPEG_QUALITY);
+        encode_image_as(&decode_tile(source)?, quality, format)
+    }
+
+    /// Encode a tile cropped to `width` x `height` in the given output format.
+    ///
+    /// See [`encode_cropped`](Self::encode_cropped) and
+    /// [`encode_as`](Self::encode_as).
+    pub fn encode_cropped_as(
+        &self,
+        source: &[u8],
+        quality: u8,
+        format: OutputFormat,
+        width: u32,
+        height: u32,
+    ) -> Result<Bytes, TileError> {
+        if format == OutputFormat::Jpeg {
+            return self.encode_cropped(source, quality, width, height);
+        }
+        format.ensure_supported()?;
 
-        Ok(Bytes::from(output))
+        let quality = quality.clamp(MIN_JPEG_QUALITY, MAX_JPEG_QUALITY);
+        let img = decode_tile(source)?;
+        let width = width.min(img.width());
+        let height = height.min(img.height());
+        encode_image_as(&img.crop_imm(0, 0, width, height), quality, format)
+    }
+
+    /// Decode a source tile once and encode several crops of it.
+    ///
+    /// Each crop is `(x, y, width, height)` in source pixels, clamped to the
+    /// tile bounds. Used to cut giant native tiles into a virtual grid.
+    pub fn encode_crops(
+        &self,
+        source: &[u8],
+        quality: u8,
+        format: OutputFormat,
+        crops: &[(u32, u32, u32, u32)],
+    ) -> Result<Vec<Bytes>, TileError> {
+        format.ensure_supported()?;
+        let quality = quality.clamp(MIN_JPEG_QUALITY, MAX_JPEG_QUALITY);
+
+        let mut img = decode_tile(source)?;
+        if self.deterministic {
+            img = DynamicImage::ImageRgb8(img.into_rgb8());
+        }
+        encode_image_crops(&img, quality, format, None, crops)
+    }
+
+    /// Decode a source tile once, render it (select a channel, apply a
+    /// tone adjustment, draw annotations) and encode several crops of it,
+    /// each stamped with the rendering's watermark.
+    ///
+    /// Crops are clamped as in [`encode_crops`](Self::encode_crops).
+    /// Rendered tiles are always re-encoded, never passed through. A
+    /// selected channel is encoded as grayscale JPEG, unless annotations
+    /// are drawn in color over it.
+    pub fn encode_rendered(
+        &self,
+        source: &[u8],
+        quality: u8,
+        format: OutputFormat,
+        rendering: &TileRendering,
+        crops: &[(u32, u32, u32, u32)],
+    ) -> Result<Vec<Bytes>, TileError> {
+        format.ensure_supported()?;
+        let quality = quality.clamp(MIN_JPEG_QUALITY, MAX_JPEG_QUALITY);
+
+        let mut img = decode_tile(source)?;
+        if let Some(channel) = rendering.channel {
+            img = channel.apply(img);
+        }
+        if let Some(adjustment) = &rendering.adjustment {
+            img = adjustment.apply(img);
+        }
+        if let Some(overlay) = &rendering.overlay {
+            img = overlay.apply(img);
+        }
+        let watermark = rendering.watermark.as_deref();
+        encode_image_crops(&img, quality, format, watermark, crops)
     }
 
     /// Decode source JPEG and re-encode at the default quality.
@@ -350,13 +1052,12 @@ impl JpegTileEncoder {
                         message: format!("JPEG dimensions error: {}", e),
                     })
             }
-            TileFormat::Jpeg2000 => {
-                // jpeg2k requires full decode to get dimensions
-                let j2k = J2kImage::from_bytes(source).map_err(|e| TileError::DecodeError {
-                    message: format!("JPEG 2000 decode error: {}", e),
-                })?;
-                Ok((j2k.width(), j2k.height()))
-            }
+            TileFormat::Jpeg2000 => jpeg2000_dimensions(source),
+            TileFormat::Raw => RawTileLayout::from_wrapped(source)
+                .map(|(layout, _)| (layout.width, layout.height))
+                .ok_or_else(|| TileError::DecodeError {
+                    message: "Invalid raw tile header".to_string(),
+                }),
             TileFormat::Unknown => Err(TileError::DecodeError {
                 message: "Unknown tile format: expected JPEG or JPEG 2000".to_string(),
             }),
@@ -364,6 +1065,202 @@ impl JpegTileEncoder {
     }
 }
 
+// =============================================================================
+// Output Formats
+// =============================================================================
+
+/// Image format of rendered tiles and regions.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// Baseline JPEG at the requested quality
+    #[default]
+    #[serde(rename = "jpg", alias = "jpeg")]
+    Jpeg,
+
+    /// Lossless PNG; quality is ignored
+    Png,
+
+    /// Lossy WebP at the requested quality (requires the `webp` feature)
+    Webp,
+}
+
+impl OutputFormat {
+    /// All output formats, including those not compiled into this build.
+    pub const ALL: [OutputFormat; 3] = [OutputFormat::Jpeg, OutputFormat::Png, OutputFormat::Webp];
+
+    /// Format name, as reported by `/capabilities`.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            OutputFormat::Jpeg => "jpeg",
+            OutputFormat::Png => "png",
+            OutputFormat::Webp => "webp",
+        }
+    }
+
+    /// File extension, without the dot.
+    pub const fn extension(&self) -> &'static str {
+        match self {
+            OutputFormat::Jpeg => "jpg",
+            OutputFormat::Png => "png",
+            OutputFormat::Webp => "webp",
+        }
+    }
+
+    /// Look up a format by file extension (without the dot, case-insensitive).
+    ///
+    /// Accepts `jpeg` as well as `jpg`.
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        if extension.eq_ignore_ascii_case("jpeg") {
+            return Some(OutputFormat::Jpeg);
+        }
+        Self::ALL
+            .into_iter()
+            .find(|format| format.extension().eq_ignore_ascii_case(extension))
+    }
+
+    /// MIME type for the `Content-Type` header.
+    pub const fn content_type(&self) -> &'static str {
+        match self {
+            OutputFormat::Jpeg => "image/jpeg",
+            OutputFormat::Png => "image/png",
+            OutputFormat::Webp => "image/webp",
+        }
+    }
+
+    /// Whether this build can encode the format.
+    pub const fn is_supported(&self) -> bool {
+        match self {
+            OutputFormat::Jpeg | OutputFormat::Png => true,
+            OutputFormat::Webp => cfg!(feature = "webp"),
+        }
+    }
+
+    /// Return an error if this build cannot encode the format.
+    pub fn ensure_supported(&self) -> Result<(), TileError> {
+        if self.is_supported() {
+            Ok(())
+        } else {
+            Err(TileError::UnsupportedOutputFormat {
+                format: self.name().to_string(),
+            })
+        }
+    }
+
+    /// Whether encoding discards detail (and so depends on quality).
+    pub const fn is_lossy(&self) -> bool {
+        !matches!(self, OutputFormat::Png)
+    }
+
+    /// Encode an RGB image, writing it to `writer`.
+    pub fn encode<W: Write>(
+        &self,
+        image: &RgbImage,
+        quality: u8,
+        mut writer: W,
+    ) -> Result<(), TileError> {
+        let to_encode_error = |e: image::ImageError| TileError::EncodeError {
+            message: e.to_string(),
+        };
+        match self {
+            OutputFormat::Jpeg => image
+                .write_with_encoder(JpegEncoder::new_with_quality(writer, quality))
+                .map_err(to_encode_error),
+            OutputFormat::Png => image
+                .write_with_encoder(PngEncoder::new(writer))
+                .map_err(to_encode_error),
+            OutputFormat::Webp => {
+                let encoded = encode_webp(image, quality)?;
+                writer
+                    .write_all(&encoded)
+                    .map_err(|e| TileError::EncodeError {
+                        message: e.to_string(),
+                    })
+            }
+        }
+    }
+}
+
+/// Encode an RGB image as lossy WebP.
+#[cfg(feature = "webp")]
+fn encode_webp(image: &RgbImage, quality: u8) -> Result<Vec<u8>, TileError> {
+    let encoder = webp::Encoder::from_rgb(image.as_raw(), image.width(), image.height());
+    Ok(encoder.encode(quality as f32).to_vec())
+}
+
+/// WebP output is unavailable without the `webp` feature.
+#[cfg(not(feature = "webp"))]
+fn encode_webp(_image: &RgbImage, _quality: u8) -> Result<Vec<u8>, TileError> {
+    Err(TileError::UnsupportedOutputFormat {
+        format: OutputFormat::Webp.name().to_string(),
+    })
+}
+
+// =============================================================================
+// Color Profiles
+// =============================================================================
+
+/// Identifier opening each APP2 segment of an embedded ICC profile.
+const ICC_MARKER_ID: &[u8] = b"ICC_PROFILE\0";
+
+/// Largest ICC profile chunk per APP2 segment: the 65533-byte segment
+/// payload minus the identifier and the two sequence bytes.
+const MAX_ICC_CHUNK: usize = 65_519;
+
+/// Embed an ICC color profile in a JPEG stream.
+///
+/// The profile is split across APP2 `ICC_PROFILE` segments placed after
+/// the SOI marker and any JFIF segment, so viewers that honor color
+/// profiles render the tile in the scanner's color space. Streams that
+/// already carry a profile, non-JPEG data and profiles too large for the
+/// 255 segments a JPEG allows are returned unchanged.
+pub fn embed_icc_profile(jpeg: Bytes, profile: &[u8]) -> Bytes {
+    if profile.is_empty()
+        || profile.len() > MAX_ICC_CHUNK * 255
+        || detect_tile_format(&jpeg) != TileFormat::Jpeg
+        || has_icc_profile(&jpeg)
+    {
+        return jpeg;
+    }
+
+    // Keep the JFIF segment first, as decoders expect
+    let mut insert_at = 2;
+    if jpeg.get(2..4) == Some(&[0xFF, 0xE0]) && jpeg.len() >= 6 {
+        insert_at = (4 + u16::from_be_bytes([jpeg[4], jpeg[5]]) as usize).min(jpeg.len());
+    }
+
+    let count = profile.len().div_ceil(MAX_ICC_CHUNK);
+    let mut output = Vec::with_capacity(jpeg.len() + profile.len() + count * 18);
+    output.extend_from_slice(&jpeg[..insert_at]);
+    for (index, chunk) in profile.chunks(MAX_ICC_CHUNK).enumerate() {
+        let length = 2 + ICC_MARKER_ID.len() + 2 + chunk.len();
+        output.extend_from_slice(&[0xFF, 0xE2]);
+        output.extend_from_slice(&(length as u16).to_be_bytes());
+        output.extend_from_slice(ICC_MARKER_ID);
+        output.extend_from_slice(&[index as u8 + 1, count as u8]);
+        output.extend_from_slice(chunk);
+    }
+    output.extend_from_slice(&jpeg[insert_at..]);
+    Bytes::from(output)
+}
+
+/// Whether a JPEG stream carries an ICC profile before its first scan.
+fn has_icc_profile(data: &[u8]) -> bool {
+    let mut pos = 2;
+    while pos + 4 <= data.len() && data[pos] == 0xFF {
+        let marker = data[pos + 1];
+        if marker == 0xDA || marker == 0xD9 {
+            break;
+        }
+        let length = u16::from_be_bytes([data[pos + 2], data[pos + 3]]) as usize;
+        if marker == 0xE2 && data[pos + 4..].starts_with(ICC_MARKER_ID) {
+            return true;
+        }
+        pos += 2 + length;
+    }
+    false
+}
+
 // =============================================================================
 // Utility Functions
 // =============================================================================
@@ -409,14 +1306,14 @@ mod tests {
 
     #[test]
     fn test_encoder_creation() {
-        let encoder = JpegTileEncoder::new();
+        let encoder = TileEncoder::new();
         // Just verify the encoder can be created without panicking
         let _ = &encoder;
     }
 
     #[test]
     fn test_encode_valid_jpeg() {
-        let encoder = JpegTileEncoder::new();
+        let encoder = TileEncoder::new();
         let source = create_test_jpeg();
 
         let result = encoder.encode(&source, 80);
@@ -431,7 +1328,7 @@ mod tests {
 
     #[test]
     fn test_encode_different_qualities() {
-        let encoder = JpegTileEncoder::new();
+        let encoder = TileEncoder::new();
         let source = create_test_jpeg();
 
         let low_quality = encoder.encode(&source, 10).unwrap();
@@ -445,7 +1342,7 @@ mod tests {
 
     #[test]
     fn test_encode_default() {
-        let encoder = JpegTileEncoder::new();
+        let encoder = TileEncoder::new();
         let source = create_test_jpeg();
 
         let result = encoder.encode_default(&source);
@@ -454,7 +1351,7 @@ mod tests {
 
     #[test]
     fn test_encode_invalid_data() {
-        let encoder = JpegTileEncoder::new();
+        let encoder = TileEncoder::new();
         let invalid = vec![0x00, 0x01, 0x02, 0x03];
 
         let result = encoder.encode(&invalid, 80);
@@ -466,9 +1363,27 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_encode_rejects_oversized_frame() {
+        let encoder = TileEncoder::new();
+        let mut source = create_test_jpeg();
+
+        // Claim a 65535x65535 frame in the SOF0 header
+        let sof = source.windows(2).position(|w| w == [0xFF, 0xC0]).unwrap();
+        source[sof + 5..sof + 9].copy_from_slice(&[0xFF, 0xFF, 0xFF, 0xFF]);
+
+        for quality in [10, 50] {
+            match encoder.encode(&source, quality) {
+                Err(TileError::DecodeError { .. }) => {}
+                other => panic!("Expected DecodeError, got {:?}", other.map(|b| b.len())),
+            }
+        }
+        assert!(decode_tile(&source).is_err());
+    }
+
     #[test]
     fn test_encode_empty_data() {
-        let encoder = JpegTileEncoder::new();
+        let encoder = TileEncoder::new();
 
         let result = encoder.encode(&[], 80);
         assert!(result.is_err());
@@ -476,7 +1391,7 @@ mod tests {
 
     #[test]
     fn test_quality_clamping() {
-        let encoder = JpegTileEncoder::new();
+        let encoder = TileEncoder::new();
         let source = create_test_jpeg();
 
         // Quality 0 should be clamped to 1
@@ -490,7 +1405,7 @@ mod tests {
 
     #[test]
     fn test_dimensions() {
-        let encoder = JpegTileEncoder::new();
+        let encoder = TileEncoder::new();
         let source = create_test_jpeg();
 
         let (width, height) = encoder.dimensions(&source).unwrap();
@@ -500,7 +1415,7 @@ mod tests {
 
     #[test]
     fn test_dimensions_invalid() {
-        let encoder = JpegTileEncoder::new();
+        let encoder = TileEncoder::new();
         let invalid = vec![0x00, 0x01, 0x02];
 
         let result = encoder.dimensions(&invalid);
@@ -528,7 +1443,7 @@ mod tests {
 
     #[test]
     fn test_output_is_valid_jpeg() {
-        let encoder = JpegTileEncoder::new();
+        let encoder = TileEncoder::new();
         let source = create_test_jpeg();
 
         let output = encoder.encode(&source, 80).unwrap();
@@ -544,6 +1459,225 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    // -------------------------------------------------------------------------
+    // Source Quality Tests
+    // -------------------------------------------------------------------------
+
+    fn create_rgb_jpeg(quality: u8) -> Vec<u8> {
+        use image::{Rgb, RgbImage};
+
+        let img = RgbImage::from_fn(64, 64, |x, y| Rgb([(x * 4) as u8, (y * 4) as u8, 128]));
+        let mut buf = Vec::new();
+        let mut encoder = JpegEncoder::new_with_quality(&mut buf, quality);
+        encoder.encode_image(&img).unwrap();
+        buf
+    }
+
+    /// An RGB JPEG carrying a comment segment, which re-encoding drops.
+    ///
+    /// Re-encoding an image crate JPEG at its own quality can reproduce it
+    /// byte for byte, so the comment tells re-encoded output apart.
+    fn create_commented_jpeg(quality: u8) -> Vec<u8> {
+        let mut jpeg = create_rgb_jpeg(quality);
+        jpeg.splice(2..2, [0xFF, 0xFE, 0x00, 0x06, b't', b'e', b's', b't']);
+        jpeg
+    }
+
+    #[test]
+    fn test_estimate_jpeg_quality() {
+        for quality in [30u8, 50, 75, 90] {
+            let jpeg = create_rgb_jpeg(quality);
+            let estimate = estimate_jpeg_quality(&jpeg).unwrap();
+            assert!(
+                estimate.abs_diff(quality) <= SOURCE_QUALITY_TOLERANCE,
+                "quality {} estimated as {}",
+                quality,
+                estimate
+            );
+        }
+    }
+
+    /// Overwrite an 8-bit quantization table in place with `table`, given in
+    /// natural order.
+    fn replace_table(jpeg: &mut [u8], table_id: u8, table: &[u16; 64]) {
+        let dqt = jpeg
+            .windows(5)
+            .position(|w| w[..2] == [0xFF, 0xDB] && w[4] == table_id)
+            .unwrap();
+        for (i, &natural) in ZIGZAG.iter().enumerate() {
+            jpeg[dqt + 5 + i] = table[natural] as u8;
+        }
+    }
+
+    #[test]
+    fn test_estimate_jpeg_quality_uses_all_tables() {
+        let mut jpeg = create_rgb_jpeg(75);
+        replace_table(&mut jpeg, 1, &scaled_table(&STD_CHROMINANCE_QTABLE, 30));
+
+        // Coarser chrominance pulls the estimate well below the luminance's 75
+        let estimate = estimate_jpeg_quality(&jpeg).unwrap();
+        assert!(estimate < 60, "estimated {}", estimate);
+    }
+
+    #[test]
+    fn test_scaled_table_matches_encoder() {
+        let jpeg = create_rgb_jpeg(75);
+        let info = scan_jpeg_header(&jpeg).unwrap();
+        assert_eq!(info.component_tables, vec![0, 1, 1]);
+        for (id, table) in &info.tables {
+            assert_eq!(*table, scaled_table(standard_table(*id), 75));
+        }
+        assert!(matches_encoder_tables(&jpeg, 75));
+        assert!(!matches_encoder_tables(&jpeg, 76));
+    }
+
+    #[test]
+    fn test_estimate_jpeg_quality_invalid() {
+        assert_eq!(estimate_jpeg_quality(&[]), None);
+        assert_eq!(estimate_jpeg_quality(&[0x00, 0x01, 0x02, 0x03]), None);
+        // Abbreviated stream without tables
+        assert_eq!(
+            estimate_jpeg_quality(&[0xFF, 0xD8, 0xFF, 0xDA, 0x00, 0x08]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_encode_reuses_source_at_matching_quality() {
+        let encoder = TileEncoder::new().with_source_reuse(true);
+        let source = create_commented_jpeg(75);
+
+        let output = encoder.encode(&source, 75).unwrap();
+        assert_eq!(&output[..], &source[..]);
+
+        // Close enough for the estimate, but not the same tables
+        let reencoded = encoder.encode(&source, 76).unwrap();
+        assert_ne!(&reencoded[..], &source[..]);
+
+        let reencoded = encoder.encode(&source, 40).unwrap();
+        assert_ne!(&reencoded[..], &source[..]);
+    }
+
+    #[test]
+    fn test_source_reuse_is_opt_in() {
+        let encoder = TileEncoder::new();
+        let source = create_commented_jpeg(75);
+
+        let output = encoder.encode(&source, 75).unwrap();
+        assert_ne!(&output[..], &source[..]);
+        assert!(encoder.can_pass_through(&source));
+    }
+
+    #[test]
+    fn test_custom_tables_not_reused() {
+        let encoder = TileEncoder::new().with_source_reuse(true);
+        let mut source = create_commented_jpeg(75);
+        let mut chroma = scaled_table(&STD_CHROMINANCE_QTABLE, 75);
+        chroma[0] += 1;
+        replace_table(&mut source, 1, &chroma);
+
+        let estimate = estimate_jpeg_quality(&source).unwrap();
+        assert!(estimate.abs_diff(75) <= SOURCE_QUALITY_TOLERANCE);
+        let output = encoder.encode(&source, 75).unwrap();
+        assert_ne!(&output[..], &source[..]);
+    }
+
+    #[test]
+    fn test_encode_without_source_reuse() {
+        let encoder = TileEncoder::new().with_source_reuse(false);
+        let source = create_commented_jpeg(75);
+
+        let output = encoder.encode(&source, 75).unwrap();
+        assert_ne!(&output[..], &source[..]);
+    }
+
+    #[test]
+    fn test_deterministic_encoding() {
+        let encoder = TileEncoder::new().with_deterministic(true);
+        assert!(encoder.is_deterministic());
+        let source = create_commented_jpeg(75);
+
+        // Always re-encoded, and byte-identical across runs
+        let first = encoder.encode(&source, 75).unwrap();
+        let second = encoder.encode(&source, 75).unwrap();
+        assert_ne!(&first[..], &source[..]);
+        assert_eq!(first, second);
+
+        // Grayscale sources are normalized to RGB
+        let gray = encoder.encode(&create_test_jpeg(), 80).unwrap();
+        let decoded = image::load_from_memory(&gray).unwrap();
+        assert_eq!(decoded.color(), image::ColorType::Rgb8);
+
+        let (_, quality) = encoder.encode_at_source_quality(&source).unwrap();
+        assert_eq!(quality, DEFAULT_JPEG_QUALITY);
+    }
+
+    #[test]
+    fn test_encode_at_source_quality() {
+        let encoder = TileEncoder::new();
+        let source = create_rgb_jpeg(60);
+
+        let (output, quality) = encoder.encode_at_source_quality(&source).unwrap();
+        assert_eq!(&output[..], &source[..]);
+        assert!(quality.abs_diff(60) <= SOURCE_QUALITY_TOLERANCE);
+    }
+
+    #[test]
+    fn test_rgb_component_ids_not_passed_through() {
+        let mut source = create_rgb_jpeg(75);
+        let info = scan_jpeg_header(&source).unwrap();
+        assert!(info.sequential);
+        assert_eq!(info.component_ids.len(), 3);
+
+        // Rewrite component IDs to 'R', 'G', 'B'
+        let sof = source.windows(2).position(|w| w == [0xFF, 0xC0]).unwrap();
+        for (i, id) in b"RGB".iter().enumerate() {
+            source[sof + 10 + i * 3] = *id;
+        }
+        assert_eq!(passthrough_quality(&source), None);
+    }
+
+    #[test]
+    fn test_encode_cropped_lossless_fast_path() {
+        let encoder = TileEncoder::new().with_source_reuse(true);
+        let source = create_rgb_jpeg(75);
+
+        let output = encoder.encode_cropped(&source, 75, 40, 24).unwrap();
+        assert_eq!(encoder.dimensions(&output).unwrap(), (40, 24));
+        // Same tables, fewer MCUs
+        assert_eq!(
+            estimate_jpeg_quality(&output),
+            estimate_jpeg_quality(&source)
+        );
+        assert!(output.len() < source.len());
+    }
+
+    #[test]
+    fn test_encode_cropped_reencode_path() {
+        let encoder = TileEncoder::new();
+        let source = create_rgb_jpeg(75);
+
+        let output = encoder.encode_cropped(&source, 30, 40, 24).unwrap();
+        assert_eq!(encoder.dimensions(&output).unwrap(), (40, 24));
+
+        // Crops larger than the tile are clamped
+        let output = encoder.encode_cropped(&source, 30, 500, 500).unwrap();
+        assert_eq!(encoder.dimensions(&output).unwrap(), (64, 64));
+    }
+
+    #[test]
+    fn test_reencode_uses_buffer_pool() {
+        let encoder = TileEncoder::new();
+        let source = create_rgb_jpeg(90);
+
+        let before = crate::tile::buffer_pool_stats();
+        let output = encoder.encode(&source, 50).unwrap();
+        let after = crate::tile::buffer_pool_stats();
+
+        assert_eq!(encoder.dimensions(&output).unwrap(), (64, 64));
+        assert!(after.acquired >= before.acquired + 2);
+    }
+
     // -------------------------------------------------------------------------
     // Format Detection Tests
     // -------------------------------------------------------------------------
@@ -572,6 +1706,45 @@ mod tests {
         assert_eq!(detect_tile_format(&jp2), TileFormat::Jpeg2000);
     }
 
+    #[test]
+    fn test_detect_raw_format() {
+        let layout = RawTileLayout {
+            compression: crate::format::tiff::Compression::Deflate,
+            width: 8,
+            height: 8,
+            samples_per_pixel: 3,
+            predictor: 1,
+            photometric: 2,
+        };
+        assert_eq!(detect_tile_format(&layout.wrap(&[])), TileFormat::Raw);
+    }
+
+    #[test]
+    fn test_encode_deflate_tile() {
+        use std::io::Write;
+
+        let pixels = vec![128u8; 16 * 8 * 3];
+        let mut zlib = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        zlib.write_all(&pixels).unwrap();
+        let layout = RawTileLayout {
+            compression: crate::format::tiff::Compression::Deflate,
+            width: 16,
+            height: 8,
+            samples_per_pixel: 3,
+            predictor: 1,
+            photometric: 2,
+        };
+        let source = layout.wrap(&zlib.finish().unwrap());
+
+        let encoder = TileEncoder::new();
+        assert_eq!(encoder.dimensions(&source).unwrap(), (16, 8));
+        assert_eq!(encoder.source_quality(&source), None);
+
+        let output = encoder.encode(&source, 80).unwrap();
+        assert_eq!(detect_tile_format(&output), TileFormat::Jpeg);
+        assert_eq!(encoder.dimensions(&output).unwrap(), (16, 8));
+    }
+
     #[test]
     fn test_detect_unknown_format() {
         let unknown = [0x00, 0x00, 0x00, 0x00];
@@ -590,7 +1763,7 @@ mod tests {
 
     #[test]
     fn test_encode_unknown_format_returns_error() {
-        let encoder = JpegTileEncoder::new();
+        let encoder = TileEncoder::new();
         let unknown = vec![0x00, 0x01, 0x02, 0x03];
 
         let result = encoder.encode(&unknown, 80);
@@ -603,4 +1776,153 @@ mod tests {
             _ => panic!("Expected DecodeError with format message"),
         }
     }
+
+    #[test]
+    fn test_output_format_encode() {
+        let img = RgbImage::from_pixel(16, 8, image::Rgb([200, 40, 90]));
+
+        let mut jpeg = Vec::new();
+        OutputFormat::Jpeg.encode(&img, 80, &mut jpeg).unwrap();
+        assert_eq!(&jpeg[..2], &[0xFF, 0xD8]);
+
+        let mut png = Vec::new();
+        OutputFormat::Png.encode(&img, 80, &mut png).unwrap();
+        assert_eq!(&png[..4], b"\x89PNG");
+        let decoded = image::load_from_memory(&png).unwrap().to_rgb8();
+        assert_eq!(decoded, img);
+    }
+
+    #[test]
+    fn test_output_format_deserialize() {
+        let parse = |s: &str| serde_json::from_str::<OutputFormat>(&format!("\"{}\"", s));
+        assert_eq!(parse("jpg").unwrap(), OutputFormat::Jpeg);
+        assert_eq!(parse("jpeg").unwrap(), OutputFormat::Jpeg);
+        assert_eq!(parse("png").unwrap(), OutputFormat::Png);
+        assert_eq!(parse("webp").unwrap(), OutputFormat::Webp);
+        assert!(parse("gif").is_err());
+        assert_eq!(OutputFormat::Png.content_type(), "image/png");
+        assert_eq!(OutputFormat::Webp.content_type(), "image/webp");
+    }
+
+    #[test]
+    fn test_output_format_from_extension() {
+        assert_eq!(
+            OutputFormat::from_extension("jpg"),
+            Some(OutputFormat::Jpeg)
+        );
+        assert_eq!(
+            OutputFormat::from_extension("JPEG"),
+            Some(OutputFormat::Jpeg)
+        );
+        assert_eq!(OutputFormat::from_extension("png"), Some(OutputFormat::Png));
+        assert_eq!(
+            OutputFormat::from_extension("webp"),
+            Some(OutputFormat::Webp)
+        );
+        assert_eq!(OutputFormat::from_extension("gif"), None);
+    }
+
+    #[test]
+    fn test_encode_as_png_is_lossless() {
+        let encoder = TileEncoder::new();
+        let source = create_test_jpeg();
+
+        let png = encoder.encode_as(&source, 80, OutputFormat::Png).unwrap();
+        assert_eq!(&png[..4], b"\x89PNG");
+        let decoded = image::load_from_memory(&png).unwrap().to_rgb8();
+        assert_eq!(decoded, decode_tile(&source).unwrap().to_rgb8());
+
+        let cropped = encoder
+            .encode_cropped_as(&source, 80, OutputFormat::Png, 6, 5)
+            .unwrap();
+        let decoded = image::load_from_memory(&cropped).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (6, 5));
+    }
+
+    #[test]
+    fn test_encode_crops() {
+        let encoder = TileEncoder::new();
+        let source = create_test_jpeg();
+
+        let crops = encoder
+            .encode_crops(
+                &source,
+                80,
+                OutputFormat::Png,
+                &[(0, 0, 4, 4), (4, 4, 4, 4), (6, 0, 4, 4)],
+            )
+            .unwrap();
+        assert_eq!(crops.len(), 3);
+
+        let full = decode_tile(&source).unwrap().to_rgb8();
+        let second = image::load_from_memory(&crops[1]).unwrap().to_rgb8();
+        assert_eq!(
+            second,
+            image::imageops::crop_imm(&full, 4, 4, 4, 4).to_image()
+        );
+
+        // Crops past the edge are clamped to the tile
+        let third = image::load_from_memory(&crops[2]).unwrap();
+        assert_eq!((third.width(), third.height()), (2, 4));
+    }
+
+    #[test]
+    fn test_encode_channel_as_grayscale() {
+        let encoder = TileEncoder::new();
+        let source = create_test_jpeg();
+
+        let rendering = TileRendering {
+            channel: Some(Channel::Red),
+            ..Default::default()
+        };
+        let crops = encoder
+            .encode_rendered(&source, 80, OutputFormat::Jpeg, &rendering, &[(0, 0, 8, 8)])
+            .unwrap();
+        let decoded = image::load_from_memory(&crops[0]).unwrap();
+        assert_eq!(decoded.color(), image::ColorType::L8);
+        assert_eq!((decoded.width(), decoded.height()), (8, 8));
+    }
+
+    #[test]
+    fn test_encode_as_webp() {
+        let encoder = TileEncoder::new();
+        let source = create_test_jpeg();
+
+        let result = encoder.encode_as(&source, 80, OutputFormat::Webp);
+        if cfg!(feature = "webp") {
+            let webp = result.unwrap();
+            assert_eq!(&webp[..4], b"RIFF");
+            assert_eq!(&webp[8..12], b"WEBP");
+        } else {
+            assert!(matches!(
+                result,
+                Err(TileError::UnsupportedOutputFormat { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_embed_icc_profile() {
+        let source = Bytes::from(create_test_jpeg());
+        // Large enough to span two APP2 segments
+        let profile: Vec<u8> = (0..70_000u32).map(|i| (i % 251) as u8).collect();
+
+        let tagged = embed_icc_profile(source.clone(), &profile);
+        assert_eq!(&tagged[..2], &[0xFF, 0xD8]);
+        assert!(has_icc_profile(&tagged));
+        let mut decoder = JpegDecoder::new(Cursor::new(&tagged[..])).unwrap();
+        assert_eq!(decoder.icc_profile().unwrap(), Some(profile.clone()));
+        assert_eq!(
+            decode_tile(&tagged).unwrap().to_luma8(),
+            decode_tile(&source).unwrap().to_luma8()
+        );
+
+        // Embedding twice keeps the first profile
+        assert_eq!(embed_icc_profile(tagged.clone(), b"other"), tagged);
+
+        // Other formats pass through
+        let png = Bytes::from_static(b"\x89PNG\r\n\x1a\n");
+        assert_eq!(embed_icc_profile(png.clone(), &profile), png);
+        assert_eq!(embed_icc_profile(source.clone(), &[]), source);
+    }
 }
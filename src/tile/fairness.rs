@@ -0,0 +1,626 @@
+//! Weighted fair queuing of tile generation.
+//!
+//! Generating a tile (fetch, decode, encode) takes one of a fixed number of
+//! slots. When none is free, requests wait in a queue per tenant, or per
+//! slide for requests without a tenant. Freed slots go to the waiting queue
+//! with the lowest virtual start time (start-time fair queuing), so queues
+//! share the slots in proportion to their weights however many requests
+//! each has outstanding: one client hammering a single slide only delays
+//! its own queue.
+//!
+//! Per-tenant quotas also cap how many slots a queue may hold at once, even
+//! when the others are idle.
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Write as _;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tokio::sync::oneshot;
+
+use crate::error::TileError;
+
+/// Default time a tile request may wait for a generation slot.
+pub const DEFAULT_TILE_QUEUE_TIMEOUT: Duration = Duration::from_secs(30);
+
+// =============================================================================
+// Quotas
+// =============================================================================
+
+/// Scheduling share and in-flight limit of a queue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TenantQuota {
+    /// Relative share of the slots when queues compete (at least 1)
+    pub weight: u32,
+
+    /// Maximum slots held at once (0 = limited only by the pool)
+    pub max_in_flight: usize,
+}
+
+impl Default for TenantQuota {
+    fn default() -> Self {
+        Self {
+            weight: 1,
+            max_in_flight: 0,
+        }
+    }
+}
+
+impl TenantQuota {
+    /// Create a quota. A weight of 0 is raised to 1.
+    pub fn new(weight: u32, max_in_flight: usize) -> Self {
+        Self {
+            weight: weight.max(1),
+            max_in_flight,
+        }
+    }
+
+    /// Parse a `name=weight[:max_in_flight]` quota specification.
+    ///
+    /// # Example
+    ///
+    /// `acme=4:16` gives tenant `acme` four times the default share and
+    /// at most 16 tiles in flight.
+    pub fn parse_spec(spec: &str) -> Result<(String, TenantQuota), String> {
+        let invalid = || {
+            format!(
+                "Invalid tenant quota '{}'. Expected name=weight[:max_in_flight]",
+                spec
+            )
+        };
+        let (name, quota) = spec.split_once('=').ok_or_else(invalid)?;
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(invalid());
+        }
+
+        let (weight, max_in_flight) = match quota.split_once(':') {
+            Some((weight, max)) => (weight, Some(max)),
+            None => (quota, None),
+        };
+        let weight: u32 = weight.trim().parse().map_err(|_| invalid())?;
+        if weight == 0 {
+            return Err(format!("Tenant '{}' weight must be greater than 0", name));
+        }
+        let max_in_flight = match max_in_flight {
+            Some(max) => max.trim().parse().map_err(|_| invalid())?,
+            None => 0,
+        };
+
+        Ok((name.to_string(), TenantQuota::new(weight, max_in_flight)))
+    }
+
+    fn has_room(&self, in_flight: usize) -> bool {
+        self.max_in_flight == 0 || in_flight < self.max_in_flight
+    }
+}
+
+/// Name of the queue a request waits in.
+pub fn queue_key(tenant: Option<&str>, slide_id: &str) -> String {
+    match tenant {
+        Some(tenant) => format!("tenant:{}", tenant),
+        None => format!("slide:{}", slide_id),
+    }
+}
+
+// =============================================================================
+// Statistics
+// =============================================================================
+
+/// Activity of one queue.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct QueueStats {
+    /// Queue name (`tenant:{name}` or `slide:{slide_id}`)
+    pub queue: String,
+
+    /// Scheduling weight
+    pub weight: u32,
+
+    /// In-flight limit (0 = none)
+    pub max_in_flight: usize,
+
+    /// Slots currently held
+    pub in_flight: usize,
+
+    /// Requests waiting for a slot
+    pub waiting: usize,
+}
+
+/// Activity of the scheduler. Idle queues are not listed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SchedulerStats {
+    /// Total generation slots
+    pub capacity: usize,
+
+    /// Slots currently held
+    pub in_flight: usize,
+
+    /// Requests waiting for a slot
+    pub waiting: usize,
+
+    /// Slots granted since startup
+    pub granted: u64,
+
+    /// Requests that gave up waiting since startup
+    pub timeouts: u64,
+
+    /// Active queues
+    pub queues: Vec<QueueStats>,
+}
+
+impl SchedulerStats {
+    /// Render the statistics in Prometheus text exposition format.
+    pub fn render_prometheus(&self) -> String {
+        let mut out = String::new();
+
+        let totals = [
+            (
+                "wsi_tile_slots",
+                "gauge",
+                "Tile generation slots.",
+                self.capacity as u64,
+            ),
+            (
+                "wsi_tile_slots_in_use",
+                "gauge",
+                "Tile generation slots in use.",
+                self.in_flight as u64,
+            ),
+            (
+                "wsi_tile_queue_waiting",
+                "gauge",
+                "Tile requests waiting for a generation slot.",
+                self.waiting as u64,
+            ),
+            (
+                "wsi_tile_slots_granted_total",
+                "counter",
+                "Tile generation slots granted.",
+                self.granted,
+            ),
+            (
+                "wsi_tile_queue_timeouts_total",
+                "counter",
+                "Tile requests that timed out waiting for a generation slot.",
+                self.timeouts,
+            ),
+        ];
+        for (name, kind, help, value) in totals {
+            let _ = writeln!(out, "# HELP {} {}", name, help);
+            let _ = writeln!(out, "# TYPE {} {}", name, kind);
+            let _ = writeln!(out, "{} {}", name, value);
+        }
+
+        type QueueGauge = (&'static str, &'static str, fn(&QueueStats) -> usize);
+        let per_queue: [QueueGauge; 2] = [
+            (
+                "wsi_tile_queue_in_flight",
+                "Tile generation slots held per active queue.",
+                |q| q.in_flight,
+            ),
+            (
+                "wsi_tile_queue_depth",
+                "Tile requests waiting per active queue.",
+                |q| q.waiting,
+            ),
+        ];
+        for (name, help, value) in per_queue {
+            let _ = writeln!(out, "# HELP {} {}", name, help);
+            let _ = writeln!(out, "# TYPE {} gauge", name);
+            for queue in &self.queues {
+                let label = queue.queue.replace('\\', "\\\\").replace('"', "\\\"");
+                let _ = writeln!(out, "{}{{queue=\"{}\"}} {}", name, label, value(queue));
+            }
+        }
+
+        out
+    }
+}
+
+// =============================================================================
+// Scheduler
+// =============================================================================
+
+struct Waiter {
+    id: u64,
+    start: f64,
+    grant: oneshot::Sender<()>,
+}
+
+struct Queue {
+    quota: TenantQuota,
+    in_flight: usize,
+    last_finish: f64,
+    waiting: VecDeque<Waiter>,
+}
+
+#[derive(Default)]
+struct State {
+    in_flight: usize,
+    virtual_time: f64,
+    next_id: u64,
+    queues: HashMap<String, Queue>,
+}
+
+impl State {
+    /// Hand free slots to waiters, lowest start tag first.
+    fn dispatch(&mut self, capacity: usize) {
+        while self.in_flight < capacity {
+            let next = self
+                .queues
+                .iter()
+                .filter(|(_, q)| q.quota.has_room(q.in_flight))
+                .filter_map(|(key, q)| q.waiting.front().map(|w| (key, w.start)))
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(key, _)| key.clone());
+            let Some(key) = next else {
+                break;
+            };
+
+            let queue = self.queues.get_mut(&key).unwrap();
+            let waiter = queue.waiting.pop_front().unwrap();
+            // A closed receiver means the waiter gave up; its slot stays free
+            if waiter.grant.send(()).is_ok() {
+                queue.in_flight += 1;
+                self.in_flight += 1;
+                self.virtual_time = self.virtual_time.max(waiter.start);
+            }
+        }
+    }
+
+    /// Return a slot held by `key`.
+    fn release(&mut self, key: &str) {
+        if let Some(queue) = self.queues.get_mut(key) {
+            queue.in_flight -= 1;
+            self.in_flight -= 1;
+        }
+        self.remove_if_idle(key);
+    }
+
+    /// Drop idle queues so they don't accumulate per slide.
+    fn remove_if_idle(&mut self, key: &str) {
+        if self
+            .queues
+            .get(key)
+            .is_some_and(|q| q.in_flight == 0 && q.waiting.is_empty())
+        {
+            self.queues.remove(key);
+        }
+    }
+}
+
+/// Fair scheduler for tile generation slots.
+pub struct FairScheduler {
+    /// Total generation slots
+    capacity: usize,
+
+    /// How long a request may wait for a slot
+    queue_timeout: Duration,
+
+    /// Quota for tenants without an explicit one, and for per-slide queues
+    default_quota: TenantQuota,
+
+    /// Per-tenant quotas
+    quotas: HashMap<String, TenantQuota>,
+
+    /// Queues and slot accounting
+    state: Mutex<State>,
+
+    /// Slots granted since startup
+    granted: AtomicU64,
+
+    /// Requests that gave up waiting since startup
+    timeouts: AtomicU64,
+}
+
+impl FairScheduler {
+    /// Create a scheduler with `capacity` generation slots (at least 1).
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            queue_timeout: DEFAULT_TILE_QUEUE_TIMEOUT,
+            default_quota: TenantQuota::default(),
+            quotas: HashMap::new(),
+            state: Mutex::new(State::default()),
+            granted: AtomicU64::new(0),
+            timeouts: AtomicU64::new(0),
+        }
+    }
+
+    /// Set how long a request may wait for a slot before failing.
+    pub fn with_queue_timeout(mut self, timeout: Duration) -> Self {
+        self.queue_timeout = timeout;
+        self
+    }
+
+    /// Set the quota for tenants without an explicit one and for per-slide
+    /// queues.
+    pub fn with_default_quota(mut self, quota: TenantQuota) -> Self {
+        self.default_quota = quota;
+        self
+    }
+
+    /// Set the quota of a tenant.
+    pub fn with_tenant_quota(mut self, tenant: impl Into<String>, quota: TenantQuota) -> Self {
+        self.quotas.insert(tenant.into(), quota);
+        self
+    }
+
+    /// Total generation slots.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    fn quota(&self, tenant: Option<&str>) -> TenantQuota {
+        tenant
+            .and_then(|t| self.quotas.get(t))
+            .copied()
+            .unwrap_or(self.default_quota)
+    }
+
+    /// Wait for a generation slot.
+    ///
+    /// The slot is held until the returned permit is dropped.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TileError::Overloaded`] if no slot frees up for this
+    /// queue within the queue timeout.
+    pub async fn acquire(
+        &self,
+        tenant: Option<&str>,
+        slide_id: &str,
+    ) -> Result<FairPermit<'_>, TileError> {
+        let key = queue_key(tenant, slide_id);
+        let quota = self.quota(tenant);
+
+        let (id, rx) = {
+            let mut guard = self.state.lock().unwrap();
+            let state = &mut *guard;
+            let queue = state.queues.entry(key.clone()).or_insert_with(|| Queue {
+                quota,
+                in_flight: 0,
+                last_finish: 0.0,
+                waiting: VecDeque::new(),
+            });
+
+            let start = state.virtual_time.max(queue.last_finish);
+            queue.last_finish = start + 1.0 / quota.weight as f64;
+
+            if state.in_flight < self.capacity
+                && queue.waiting.is_empty()
+                && quota.has_room(queue.in_flight)
+            {
+                queue.in_flight += 1;
+                state.in_flight += 1;
+                state.virtual_time = state.virtual_time.max(start);
+                self.granted.fetch_add(1, Ordering::Relaxed);
+                return Ok(FairPermit {
+                    scheduler: self,
+                    key,
+                });
+            }
+
+            let id = state.next_id;
+            state.next_id += 1;
+            let (tx, rx) = oneshot::channel();
+            queue.waiting.push_back(Waiter {
+                id,
+                start,
+                grant: tx,
+            });
+            (id, rx)
+        };
+
+        let started = Instant::now();
+        let mut pending = PendingWaiter {
+            scheduler: self,
+            key: &key,
+            id,
+            rx,
+            granted: false,
+        };
+        match tokio::time::timeout(self.queue_timeout, &mut pending.rx).await {
+            Ok(Ok(())) => {
+                pending.granted = true;
+                drop(pending);
+                self.granted.fetch_add(1, Ordering::Relaxed);
+                Ok(FairPermit {
+                    scheduler: self,
+                    key,
+                })
+            }
+            _ => {
+                drop(pending);
+                self.timeouts.fetch_add(1, Ordering::Relaxed);
+                Err(TileError::Overloaded {
+                    message: format!(
+                        "no tile generation slot for queue '{}' after {} ms",
+                        key,
+                        started.elapsed().as_millis()
+                    ),
+                })
+            }
+        }
+    }
+
+    /// Get current scheduler activity.
+    pub fn stats(&self) -> SchedulerStats {
+        let state = self.state.lock().unwrap();
+        let mut queues: Vec<QueueStats> = state
+            .queues
+            .iter()
+            .map(|(key, q)| QueueStats {
+                queue: key.clone(),
+                weight: q.quota.weight,
+                max_in_flight: q.quota.max_in_flight,
+                in_flight: q.in_flight,
+                waiting: q.waiting.len(),
+            })
+            .collect();
+        queues.sort_by(|a, b| a.queue.cmp(&b.queue));
+
+        SchedulerStats {
+            capacity: self.capacity,
+            in_flight: state.in_flight,
+            waiting: queues.iter().map(|q| q.waiting).sum(),
+            granted: self.granted.load(Ordering::Relaxed),
+            timeouts: self.timeouts.load(Ordering::Relaxed),
+            queues,
+        }
+    }
+}
+
+/// A held generation slot, returned when dropped.
+pub struct FairPermit<'a> {
+    scheduler: &'a FairScheduler,
+    key: String,
+}
+
+impl Drop for FairPermit<'_> {
+    fn drop(&mut self) {
+        let mut state = self.scheduler.state.lock().unwrap();
+        state.release(&self.key);
+        state.dispatch(self.scheduler.capacity);
+    }
+}
+
+/// A queued request, withdrawn on timeout or cancellation.
+///
+/// Grants are sent under the state lock, so checking the receiver under the
+/// same lock tells reliably whether a slot was handed over and must be
+/// returned.
+struct PendingWaiter<'a> {
+    scheduler: &'a FairScheduler,
+    key: &'a str,
+    id: u64,
+    rx: oneshot::Receiver<()>,
+    granted: bool,
+}
+
+impl Drop for PendingWaiter<'_> {
+    fn drop(&mut self) {
+        if self.granted {
+            return;
+        }
+
+        let mut state = self.scheduler.state.lock().unwrap();
+        if self.rx.try_recv().is_ok() {
+            state.release(self.key);
+            state.dispatch(self.scheduler.capacity);
+        } else if let Some(queue) = state.queues.get_mut(self.key) {
+            queue.waiting.retain(|w| w.id != self.id);
+            state.remove_if_idle(self.key);
+            // A waiter at the head may have been blocking others' turn
+            state.dispatch(self.scheduler.capacity);
+        }
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_parse_quota_spec() {
+        assert_eq!(
+            TenantQuota::parse_spec("acme=4:16").unwrap(),
+            ("acme".to_string(), TenantQuota::new(4, 16))
+        );
+        assert_eq!(
+            TenantQuota::parse_spec("beta=2").unwrap(),
+            ("beta".to_string(), TenantQuota::new(2, 0))
+        );
+        for spec in ["acme", "=2", "acme=0", "acme=x", "acme=1:y"] {
+            assert!(TenantQuota::parse_spec(spec).is_err(), "{}", spec);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_render_prometheus() {
+        let scheduler = FairScheduler::new(3);
+        let _permit = scheduler.acquire(Some("acme"), "a").await.unwrap();
+
+        let text = scheduler.stats().render_prometheus();
+        assert!(text.contains("wsi_tile_slots 3"));
+        assert!(text.contains("wsi_tile_slots_in_use 1"));
+        assert!(text.contains("wsi_tile_queue_in_flight{queue=\"tenant:acme\"} 1"));
+    }
+
+    #[tokio::test]
+    async fn test_permits_bound_in_flight() {
+        let scheduler = FairScheduler::new(2);
+        let a = scheduler.acquire(None, "a").await.unwrap();
+        let _b = scheduler.acquire(None, "b").await.unwrap();
+        assert_eq!(scheduler.stats().in_flight, 2);
+
+        drop(a);
+        assert_eq!(scheduler.stats().in_flight, 1);
+        // Idle queues are dropped
+        assert_eq!(scheduler.stats().queues.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_wait_times_out() {
+        let scheduler = FairScheduler::new(1).with_queue_timeout(Duration::from_millis(20));
+        let _held = scheduler.acquire(None, "a").await.unwrap();
+
+        let result = scheduler.acquire(None, "b").await;
+        assert!(matches!(result, Err(TileError::Overloaded { .. })));
+
+        let stats = scheduler.stats();
+        assert_eq!(stats.timeouts, 1);
+        assert_eq!(stats.waiting, 0);
+    }
+
+    #[tokio::test]
+    async fn test_tenant_in_flight_quota() {
+        let scheduler = FairScheduler::new(4)
+            .with_queue_timeout(Duration::from_millis(20))
+            .with_tenant_quota("acme", TenantQuota::new(1, 1));
+
+        let _held = scheduler.acquire(Some("acme"), "a").await.unwrap();
+        // Free slots remain, but acme is at its quota
+        assert!(scheduler.acquire(Some("acme"), "b").await.is_err());
+        assert!(scheduler.acquire(Some("other"), "b").await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_waiters_are_served_fairly() {
+        let scheduler = Arc::new(FairScheduler::new(1));
+        let held = scheduler.acquire(None, "busy").await.unwrap();
+
+        // A backlog on one slide, then a single request for another
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let mut tasks = Vec::new();
+        for slide in ["busy", "busy", "busy", "quiet"] {
+            let task_scheduler = scheduler.clone();
+            let order = order.clone();
+            tasks.push(tokio::spawn(async move {
+                let _permit = task_scheduler.acquire(None, slide).await.unwrap();
+                order.lock().unwrap().push(slide);
+            }));
+            // Let each task enqueue before the next
+            while scheduler.stats().waiting < tasks.len() {
+                tokio::task::yield_now().await;
+            }
+        }
+
+        drop(held);
+        for task in tasks {
+            task.await.unwrap();
+        }
+
+        // The quiet slide doesn't wait behind the whole backlog
+        let order = order.lock().unwrap();
+        let quiet = order.iter().position(|s| *s == "quiet").unwrap();
+        assert!(quiet <= 1, "{:?}", order);
+    }
+}
@@ -0,0 +1,669 @@
+//! Lossless JPEG cropping in the DCT domain.
+//!
+//! Tiles on the right and bottom borders of a level are stored padded to the
+//! full tile size. Trimming them to their true dimensions by decoding and
+//! re-encoding costs CPU and adds generation loss. Instead, this module
+//! entropy-decodes the quantized DCT coefficients, drops the MCUs outside the
+//! crop, and entropy-encodes the remaining ones with the original Huffman
+//! tables — the same approach as `jpegtran -crop`.
+//!
+//! Since the crop is anchored at the top-left corner, no coefficients are
+//! changed: partial MCUs are kept whole and the frame header declares the
+//! true dimensions, so decoders clip the padding themselves.
+//!
+//! # Limitations
+//!
+//! Only baseline and extended sequential Huffman-coded streams without
+//! restart intervals are supported. [`crop_jpeg`] returns `None` for anything
+//! else, and callers fall back to decode/crop/encode.
+
+use bytes::Bytes;
+
+// =============================================================================
+// Public API
+// =============================================================================
+
+/// Losslessly crop a JPEG stream to `width` x `height`, anchored at the
+/// top-left corner.
+///
+/// Returns `None` if the stream is not eligible for lossless cropping (see
+/// the module documentation), if the crop is empty, or if the crop does not
+/// fit inside the image. A crop equal to the image size returns the stream
+/// unchanged.
+pub fn crop_jpeg(data: &[u8], width: u32, height: u32) -> Option<Bytes> {
+    if width == 0 || height == 0 {
+        return None;
+    }
+
+    let stream = parse_stream(data)?;
+    let frame = &stream.frame;
+
+    if width > frame.width as u32 || height > frame.height as u32 {
+        return None;
+    }
+    if width == frame.width as u32 && height == frame.height as u32 {
+        return Some(Bytes::copy_from_slice(data));
+    }
+
+    let scan = &stream.scan;
+    let (src_mcus_x, _) = frame.mcu_grid(scan, frame.width as u32, frame.height as u32);
+    let (dst_mcus_x, dst_mcus_y) = frame.mcu_grid(scan, width, height);
+
+    let mut reader = BitReader::new(&data[stream.entropy_start..]);
+    let mut writer = BitWriter::with_capacity(data.len());
+    let mut src_pred = vec![0i32; scan.components.len()];
+    let mut dst_pred = vec![0i32; scan.components.len()];
+    let mut block = [0i32; 64];
+
+    for _ in 0..dst_mcus_y {
+        for mcu_x in 0..src_mcus_x {
+            let keep = mcu_x < dst_mcus_x;
+            for (i, component) in scan.components.iter().enumerate() {
+                let blocks = if scan.components.len() == 1 {
+                    1
+                } else {
+                    component.h as u32 * component.v as u32
+                };
+                let dc = stream.dc_tables[component.dc_table].as_ref()?;
+                let ac = stream.ac_tables[component.ac_table].as_ref()?;
+
+                for _ in 0..blocks {
+                    decode_block(&mut reader, dc, ac, &mut src_pred[i], &mut block)?;
+                    if keep {
+                        encode_block(&mut writer, dc, ac, &mut dst_pred[i], &block)?;
+                    }
+                }
+            }
+        }
+    }
+
+    // Header (with patched dimensions), SOS, new entropy data, EOI
+    let mut output = Vec::with_capacity(stream.entropy_start + writer.len() + 2);
+    output.extend_from_slice(&data[..stream.entropy_start]);
+    let sof = stream.frame.dims_offset;
+    output[sof..sof + 2].copy_from_slice(&(height as u16).to_be_bytes());
+    output[sof + 2..sof + 4].copy_from_slice(&(width as u16).to_be_bytes());
+    output.extend_from_slice(&writer.finish());
+    output.extend_from_slice(&[0xFF, 0xD9]);
+
+    Some(Bytes::from(output))
+}
+
+// =============================================================================
+// Stream Parsing
+// =============================================================================
+
+/// Frame component from the SOF header.
+#[derive(Debug, Clone, Copy)]
+struct FrameComponent {
+    id: u8,
+    h: u8,
+    v: u8,
+}
+
+/// Frame header information.
+#[derive(Debug)]
+struct Frame {
+    width: u16,
+    height: u16,
+    components: Vec<FrameComponent>,
+
+    /// Byte offset of the height field (followed by width)
+    dims_offset: usize,
+}
+
+/// Component referenced by the scan header.
+#[derive(Debug)]
+struct ScanComponent {
+    h: u8,
+    v: u8,
+    dc_table: usize,
+    ac_table: usize,
+}
+
+/// Scan header information.
+#[derive(Debug)]
+struct Scan {
+    components: Vec<ScanComponent>,
+}
+
+/// Parsed stream headers.
+struct Stream {
+    frame: Frame,
+    scan: Scan,
+    dc_tables: [Option<HuffmanTable>; 4],
+    ac_tables: [Option<HuffmanTable>; 4],
+
+    /// Byte offset of the first entropy-coded byte
+    entropy_start: usize,
+}
+
+impl Frame {
+    fn max_sampling(&self) -> (u32, u32) {
+        let h = self
+            .components
+            .iter()
+            .map(|c| c.h as u32)
+            .max()
+            .unwrap_or(1);
+        let v = self
+            .components
+            .iter()
+            .map(|c| c.v as u32)
+            .max()
+            .unwrap_or(1);
+        (h, v)
+    }
+
+    /// Number of MCUs covering a `width` x `height` image for this scan.
+    fn mcu_grid(&self, scan: &Scan, width: u32, height: u32) -> (u32, u32) {
+        let (h_max, v_max) = self.max_sampling();
+        if scan.components.len() == 1 {
+            // Non-interleaved: one block per MCU, sized by the component
+            let c = &scan.components[0];
+            let comp_w = (width * c.h as u32).div_ceil(h_max);
+            let comp_h = (height * c.v as u32).div_ceil(v_max);
+            (comp_w.div_ceil(8), comp_h.div_ceil(8))
+        } else {
+            (width.div_ceil(8 * h_max), height.div_ceil(8 * v_max))
+        }
+    }
+}
+
+/// Parse headers up to and including the first SOS segment.
+fn parse_stream(data: &[u8]) -> Option<Stream> {
+    if data.len() < 4 || data[0] != 0xFF || data[1] != 0xD8 {
+        return None;
+    }
+
+    let mut frame: Option<Frame> = None;
+    let mut dc_tables: [Option<HuffmanTable>; 4] = Default::default();
+    let mut ac_tables: [Option<HuffmanTable>; 4] = Default::default();
+    let mut pos = 2;
+
+    while pos + 4 <= data.len() {
+        if data[pos] != 0xFF {
+            return None;
+        }
+        let marker = data[pos + 1];
+        if marker == 0xFF {
+            pos += 1;
+            continue;
+        }
+
+        let length = u16::from_be_bytes([data[pos + 2], data[pos + 3]]) as usize;
+        if length < 2 || pos + 2 + length > data.len() {
+            return None;
+        }
+        let payload_start = pos + 4;
+        let payload = &data[payload_start..pos + 2 + length];
+
+        match marker {
+            // Baseline and extended sequential Huffman
+            0xC0 | 0xC1 => {
+                if payload.len() < 6 {
+                    return None;
+                }
+                let count = payload[5] as usize;
+                if payload.len() < 6 + count * 3 {
+                    return None;
+                }
+                let components = (0..count)
+                    .map(|i| {
+                        let sampling = payload[6 + i * 3 + 1];
+                        FrameComponent {
+                            id: payload[6 + i * 3],
+                            h: (sampling >> 4).max(1),
+                            v: (sampling & 0x0F).max(1),
+                        }
+                    })
+                    .collect();
+                frame = Some(Frame {
+                    height: u16::from_be_bytes([payload[1], payload[2]]),
+                    width: u16::from_be_bytes([payload[3], payload[4]]),
+                    components,
+                    dims_offset: payload_start + 1,
+                });
+            }
+            // Progressive, lossless or arithmetic-coded frames
+            0xC2 | 0xC3 | 0xC5..=0xC7 | 0xC9..=0xCB | 0xCD..=0xCF => return None,
+            // DHT
+            0xC4 => {
+                let mut p = 0;
+                while p < payload.len() {
+                    let class = payload[p] >> 4;
+                    let id = (payload[p] & 0x0F) as usize;
+                    if id > 3 || p + 17 > payload.len() {
+                        return None;
+                    }
+                    let counts: [u8; 16] = payload[p + 1..p + 17].try_into().ok()?;
+                    let total: usize = counts.iter().map(|&c| c as usize).sum();
+                    if p + 17 + total > payload.len() {
+                        return None;
+                    }
+                    let table = HuffmanTable::new(&counts, &payload[p + 17..p + 17 + total])?;
+                    match class {
+                        0 => dc_tables[id] = Some(table),
+                        1 => ac_tables[id] = Some(table),
+                        _ => return None,
+                    }
+                    p += 17 + total;
+                }
+            }
+            // DRI: restart intervals are not supported
+            0xDD if payload.len() < 2 || u16::from_be_bytes([payload[0], payload[1]]) != 0 => {
+                return None;
+            }
+            // SOS
+            0xDA => {
+                let frame = frame?;
+                let count = *payload.first()? as usize;
+                if payload.len() < 1 + count * 2 {
+                    return None;
+                }
+                let mut components = Vec::with_capacity(count);
+                for i in 0..count {
+                    let id = payload[1 + i * 2];
+                    let tables = payload[2 + i * 2];
+                    let fc = frame.components.iter().find(|c| c.id == id)?;
+                    components.push(ScanComponent {
+                        h: fc.h,
+                        v: fc.v,
+                        dc_table: (tables >> 4) as usize & 0x03,
+                        ac_table: (tables & 0x0F) as usize & 0x03,
+                    });
+                }
+
+                // A single scan must carry every component
+                if components.len() != frame.components.len() {
+                    return None;
+                }
+
+                return Some(Stream {
+                    frame,
+                    scan: Scan { components },
+                    dc_tables,
+                    ac_tables,
+                    entropy_start: pos + 2 + length,
+                });
+            }
+            _ => {}
+        }
+
+        pos += 2 + length;
+    }
+
+    None
+}
+
+// =============================================================================
+// Huffman Coding
+// =============================================================================
+
+/// Huffman table usable for both decoding and encoding.
+#[derive(Debug, Clone)]
+struct HuffmanTable {
+    /// Largest code of each length (-1 if none), indexed by length 1..=16
+    max_code: [i32; 17],
+
+    /// Index into `values` of the first code of each length
+    val_ptr: [i32; 17],
+
+    /// Smallest code of each length
+    min_code: [i32; 17],
+
+    /// Symbols in code order
+    values: Vec<u8>,
+
+    /// Code and length for each symbol (length 0 = not present)
+    codes: [(u16, u8); 256],
+}
+
+impl HuffmanTable {
+    fn new(counts: &[u8; 16], values: &[u8]) -> Option<Self> {
+        let mut max_code = [-1i32; 17];
+        let mut val_ptr = [0i32; 17];
+        let mut min_code = [0i32; 17];
+        let mut codes = [(0u16, 0u8); 256];
+
+        let mut code = 0i32;
+        let mut k = 0usize;
+        for len in 1..=16 {
+            let count = counts[len - 1] as usize;
+            if count > 0 {
+                val_ptr[len] = k as i32;
+                min_code[len] = code;
+                for _ in 0..count {
+                    codes[values[k] as usize] = (code as u16, len as u8);
+                    code += 1;
+                    k += 1;
+                }
+                max_code[len] = code - 1;
+                if code > (1 << len) {
+                    return None;
+                }
+            }
+            code <<= 1;
+        }
+
+        Some(Self {
+            max_code,
+            val_ptr,
+            min_code,
+            values: values.to_vec(),
+            codes,
+        })
+    }
+
+    fn decode(&self, reader: &mut BitReader<'_>) -> Option<u8> {
+        let mut code = reader.bit()? as i32;
+        for len in 1..=16 {
+            if code <= self.max_code[len] {
+                let index = self.val_ptr[len] + code - self.min_code[len];
+                return self.values.get(index as usize).copied();
+            }
+            code = (code << 1) | reader.bit()? as i32;
+        }
+        None
+    }
+
+    fn encode(&self, writer: &mut BitWriter, symbol: u8) -> Option<()> {
+        let (code, len) = self.codes[symbol as usize];
+        if len == 0 {
+            // Symbol absent from the source table (e.g. optimized tables)
+            return None;
+        }
+        writer.write(code as u32, len);
+        Some(())
+    }
+}
+
+/// Sign-extend an `s`-bit magnitude value (JPEG `EXTEND` procedure).
+fn extend(value: u32, s: u8) -> i32 {
+    if s == 0 {
+        return 0;
+    }
+    let value = value as i32;
+    if value < (1 << (s - 1)) {
+        value - (1 << s) + 1
+    } else {
+        value
+    }
+}
+
+/// Magnitude category and raw bits for a coefficient value.
+fn category(value: i32) -> (u8, u32) {
+    let magnitude = value.unsigned_abs();
+    let s = (32 - magnitude.leading_zeros()) as u8;
+    let bits = if value < 0 {
+        (value - 1) as u32 & ((1u32 << s) - 1)
+    } else {
+        value as u32
+    };
+    (s, bits)
+}
+
+/// Entropy-decode one 8x8 block of quantized coefficients (zigzag order).
+fn decode_block(
+    reader: &mut BitReader<'_>,
+    dc: &HuffmanTable,
+    ac: &HuffmanTable,
+    pred: &mut i32,
+    block: &mut [i32; 64],
+) -> Option<()> {
+    block.fill(0);
+
+    let s = dc.decode(reader)?;
+    if s > 15 {
+        return None;
+    }
+    *pred += extend(reader.bits(s)?, s);
+    block[0] = *pred;
+
+    let mut k = 1;
+    while k < 64 {
+        let rs = ac.decode(reader)?;
+        let (r, s) = (rs >> 4, rs & 0x0F);
+        if s == 0 {
+            if r == 15 {
+                k += 16;
+                continue;
+            }
+            break;
+        }
+        k += r as usize;
+        if k > 63 {
+            return None;
+        }
+        block[k] = extend(reader.bits(s)?, s);
+        k += 1;
+    }
+
+    Some(())
+}
+
+/// Entropy-encode one 8x8 block of quantized coefficients (zigzag order).
+fn encode_block(
+    writer: &mut BitWriter,
+    dc: &HuffmanTable,
+    ac: &HuffmanTable,
+    pred: &mut i32,
+    block: &[i32; 64],
+) -> Option<()> {
+    let (s, bits) = category(block[0] - *pred);
+    *pred = block[0];
+    dc.encode(writer, s)?;
+    writer.write(bits, s);
+
+    let mut run = 0u8;
+    for &coef in &block[1..] {
+        if coef == 0 {
+            run += 1;
+            continue;
+        }
+        while run > 15 {
+            ac.encode(writer, 0xF0)?;
+            run -= 16;
+        }
+        let (s, bits) = category(coef);
+        ac.encode(writer, (run << 4) | s)?;
+        writer.write(bits, s);
+        run = 0;
+    }
+    if run > 0 {
+        ac.encode(writer, 0x00)?;
+    }
+
+    Some(())
+}
+
+// =============================================================================
+// Bit I/O
+// =============================================================================
+
+/// Reads bits from entropy-coded data, removing byte stuffing.
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    buffer: u32,
+    count: u8,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            pos: 0,
+            buffer: 0,
+            count: 0,
+        }
+    }
+
+    fn bit(&mut self) -> Option<u32> {
+        if self.count == 0 {
+            let byte = *self.data.get(self.pos)?;
+            self.pos += 1;
+            if byte == 0xFF {
+                // Stuffed zero byte; anything else is a marker
+                if self.data.get(self.pos) != Some(&0x00) {
+                    return None;
+                }
+                self.pos += 1;
+            }
+            self.buffer = byte as u32;
+            self.count = 8;
+        }
+        self.count -= 1;
+        Some((self.buffer >> self.count) & 1)
+    }
+
+    fn bits(&mut self, n: u8) -> Option<u32> {
+        let mut value = 0;
+        for _ in 0..n {
+            value = (value << 1) | self.bit()?;
+        }
+        Some(value)
+    }
+}
+
+/// Writes bits as entropy-coded data, inserting byte stuffing.
+struct BitWriter {
+    output: Vec<u8>,
+    buffer: u32,
+    count: u8,
+}
+
+impl BitWriter {
+    fn with_capacity(capacity: usize) -> Self {
+        Self {
+            output: Vec::with_capacity(capacity),
+            buffer: 0,
+            count: 0,
+        }
+    }
+
+    fn write(&mut self, value: u32, len: u8) {
+        for i in (0..len).rev() {
+            self.buffer = (self.buffer << 1) | ((value >> i) & 1);
+            self.count += 1;
+            if self.count == 8 {
+                self.push_byte(self.buffer as u8);
+                self.buffer = 0;
+                self.count = 0;
+            }
+        }
+    }
+
+    fn push_byte(&mut self, byte: u8) {
+        self.output.push(byte);
+        if byte == 0xFF {
+            self.output.push(0x00);
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.output.len()
+    }
+
+    /// Pad the final byte with 1-bits and return the data.
+    fn finish(mut self) -> Vec<u8> {
+        if self.count > 0 {
+            let pad = 8 - self.count;
+            self.write((1 << pad) - 1, pad);
+        }
+        self.output
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::codecs::jpeg::JpegEncoder;
+    use image::{DynamicImage, GrayImage, Luma, Rgb, RgbImage};
+
+    fn encode(img: DynamicImage) -> Vec<u8> {
+        let mut buf = Vec::new();
+        JpegEncoder::new_with_quality(&mut buf, 85)
+            .encode_image(&img)
+            .unwrap();
+        buf
+    }
+
+    fn decode(data: &[u8]) -> DynamicImage {
+        image::load_from_memory_with_format(data, image::ImageFormat::Jpeg).unwrap()
+    }
+
+    fn rgb_tile() -> Vec<u8> {
+        let img = RgbImage::from_fn(64, 48, |x, y| {
+            Rgb([(x * 4) as u8, (y * 5) as u8, ((x + y) * 2) as u8])
+        });
+        encode(DynamicImage::ImageRgb8(img))
+    }
+
+    #[test]
+    fn test_crop_rgb_is_lossless() {
+        let source = rgb_tile();
+        let cropped = crop_jpeg(&source, 40, 20).unwrap();
+
+        let original = decode(&source).to_rgb8();
+        let result = decode(&cropped).to_rgb8();
+        assert_eq!(result.dimensions(), (40, 20));
+
+        // Pixels inside whole kept MCUs decode identically
+        for y in 0..16 {
+            for x in 0..32 {
+                assert_eq!(result.get_pixel(x, y), original.get_pixel(x, y));
+            }
+        }
+    }
+
+    #[test]
+    fn test_crop_grayscale() {
+        let img = GrayImage::from_fn(48, 40, |x, y| Luma([((x * 3) ^ (y * 5)) as u8]));
+        let source = encode(DynamicImage::ImageLuma8(img));
+
+        let cropped = crop_jpeg(&source, 17, 9).unwrap();
+        let original = decode(&source).to_luma8();
+        let result = decode(&cropped).to_luma8();
+
+        assert_eq!(result.dimensions(), (17, 9));
+        for y in 0..9 {
+            for x in 0..17 {
+                assert_eq!(result.get_pixel(x, y), original.get_pixel(x, y));
+            }
+        }
+    }
+
+    #[test]
+    fn test_crop_full_size_is_unchanged() {
+        let source = rgb_tile();
+        let cropped = crop_jpeg(&source, 64, 48).unwrap();
+        assert_eq!(&cropped[..], &source[..]);
+    }
+
+    #[test]
+    fn test_crop_rejects_invalid_dimensions() {
+        let source = rgb_tile();
+        assert!(crop_jpeg(&source, 0, 10).is_none());
+        assert!(crop_jpeg(&source, 65, 10).is_none());
+        assert!(crop_jpeg(&source, 10, 49).is_none());
+    }
+
+    #[test]
+    fn test_crop_rejects_non_jpeg() {
+        assert!(crop_jpeg(&[0x00, 0x01, 0x02, 0x03], 1, 1).is_none());
+        assert!(crop_jpeg(&[], 1, 1).is_none());
+    }
+
+    #[test]
+    fn test_category_and_extend_roundtrip() {
+        for value in [-1023, -255, -3, -1, 0, 1, 2, 7, 8, 1024] {
+            let (s, bits) = category(value);
+            assert_eq!(extend(bits, s), value);
+        }
+    }
+}
@@ -0,0 +1,166 @@
+//! Memory budget across caches.
+//!
+//! The tile cache has a fixed byte capacity, but every open slide brings
+//! its own block cache and parsed metadata, so memory grows with the
+//! number of open slides until the process is killed. A [`MemoryBudget`]
+//! caps the sum: when the tile cache, the block caches and slide metadata
+//! together exceed it, each gives up a share of the excess proportional to
+//! its size (see [`MemoryBudget::enforce`]).
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use serde::Serialize;
+use tracing::info;
+
+use crate::slide::{SlideRegistry, SlideSource};
+
+use super::cache::TileCache;
+
+/// Default interval between memory budget checks.
+pub const DEFAULT_MEMORY_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Bytes held by each cache.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct MemoryUsage {
+    /// Encoded tiles
+    pub tile_cache_bytes: usize,
+
+    /// Cached slide blocks, across open slides
+    pub block_cache_bytes: usize,
+
+    /// Estimated parsed slide metadata, across open slides
+    pub metadata_bytes: usize,
+}
+
+impl MemoryUsage {
+    /// Measure the memory held by a tile cache and the slides open in a
+    /// registry.
+    pub async fn measure<S: SlideSource>(cache: &TileCache, registry: &SlideRegistry<S>) -> Self {
+        let slides = registry.memory_usage();
+        Self {
+            tile_cache_bytes: cache.size().await,
+            block_cache_bytes: slides.block_cache_bytes,
+            metadata_bytes: slides.metadata_bytes,
+        }
+    }
+
+    /// Total bytes across caches.
+    pub fn total(&self) -> usize {
+        self.tile_cache_bytes + self.block_cache_bytes + self.metadata_bytes
+    }
+
+    /// Bytes each cache must give up to bring the total within `budget`,
+    /// in proportion to its size.
+    ///
+    /// Shares are rounded up, so they add up to at least the excess.
+    pub fn excess_shares(&self, budget: usize) -> MemoryUsage {
+        let total = self.total();
+        if total <= budget {
+            return MemoryUsage::default();
+        }
+        let excess = (total - budget) as u128;
+        let share = |bytes: usize| (excess * bytes as u128).div_ceil(total as u128) as usize;
+        MemoryUsage {
+            tile_cache_bytes: share(self.tile_cache_bytes),
+            block_cache_bytes: share(self.block_cache_bytes),
+            metadata_bytes: share(self.metadata_bytes),
+        }
+    }
+}
+
+/// Upper bound on the memory held by caches.
+#[derive(Debug)]
+pub struct MemoryBudget {
+    /// Maximum bytes across caches
+    max_bytes: usize,
+
+    /// Bytes evicted to stay within the budget
+    evicted: AtomicU64,
+}
+
+impl MemoryBudget {
+    /// Create a budget of `max_bytes` across caches.
+    pub fn new(max_bytes: usize) -> Self {
+        Self {
+            max_bytes,
+            evicted: AtomicU64::new(0),
+        }
+    }
+
+    /// Maximum bytes across caches.
+    pub fn max_bytes(&self) -> usize {
+        self.max_bytes
+    }
+
+    /// Bytes evicted to stay within the budget since startup.
+    pub fn evicted_bytes(&self) -> u64 {
+        self.evicted.load(Ordering::Relaxed)
+    }
+
+    /// Evict from a tile cache and the slides open in a registry to bring
+    /// their memory within the budget.
+    ///
+    /// Each cache gives up a share of the excess proportional to its size:
+    /// the tile cache and block caches drop least recently used entries,
+    /// and least recently used slides are closed to free metadata. Returns
+    /// the bytes freed (0 when within the budget).
+    pub async fn enforce<S: SlideSource>(
+        &self,
+        cache: &TileCache,
+        registry: &SlideRegistry<S>,
+    ) -> usize {
+        let usage = MemoryUsage::measure(cache, registry).await;
+        let shares = usage.excess_shares(self.max_bytes);
+        if shares.total() == 0 {
+            return 0;
+        }
+
+        let mut freed = cache.evict_bytes(shares.tile_cache_bytes).await;
+        freed += registry.shed_memory(shares.block_cache_bytes, shares.metadata_bytes);
+        self.evicted.fetch_add(freed as u64, Ordering::Relaxed);
+        info!(
+            used = usage.total(),
+            budget = self.max_bytes,
+            freed,
+            "Memory budget exceeded; evicted from caches"
+        );
+        freed
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_excess_shares_are_proportional() {
+        let usage = MemoryUsage {
+            tile_cache_bytes: 600,
+            block_cache_bytes: 300,
+            metadata_bytes: 100,
+        };
+        assert_eq!(usage.total(), 1000);
+        assert_eq!(usage.excess_shares(1000), MemoryUsage::default());
+        assert_eq!(usage.excess_shares(2000), MemoryUsage::default());
+
+        let shares = usage.excess_shares(800);
+        assert_eq!(
+            shares,
+            MemoryUsage {
+                tile_cache_bytes: 120,
+                block_cache_bytes: 60,
+                metadata_bytes: 20,
+            }
+        );
+
+        // Rounding never leaves the total over budget
+        let shares = usage.excess_shares(999);
+        assert!(shares.total() >= 1);
+        assert_eq!(shares.metadata_bytes, 1);
+    }
+}
@@ -32,10 +32,26 @@
 //!
 //! - [`TileService`]: Main entry point for tile requests, orchestrates the full pipeline
 //! - [`TileCache`]: LRU cache for encoded JPEG tiles with size-based eviction
+//! - [`CacheAdmission`]: LRU or TinyLFU admission of new tiles into the cache
 //! - [`TileCacheKey`]: Composite key for tile identification (slide, level, coords, quality)
-//! - [`JpegTileEncoder`]: Decodes source JPEG and re-encodes at requested quality
+//! - [`TileFlights`]: Singleflight sharing one generation among concurrent requests for a tile
+//! - [`TileEncoder`]: Decodes source tiles and re-encodes them as JPEG, PNG or WebP
+//! - [`ToneAdjustment`] / [`Channel`]: Brightness, contrast, gamma and channel selection applied before re-encoding
+//! - [`TileRendering`]: Pixel changes (adjustment, channel, overlay, watermark) made between decoding and encoding
+//! - [`virtual_level`]: Virtual grid exposed when giant native tiles are retiled
+//! - [`FairScheduler`]: Weighted fair queuing of tile generation across tenants and slides
+//! - [`crop_jpeg`]: Lossless DCT-domain cropping of padded edge tiles
+//! - [`MemoryBudget`]: Cap on memory across the tile, block and metadata caches
+//! - [`Orientation`]: EXIF / TIFF orientation of slides, label and macro images
+//! - [`PooledBuffer`]: Thread-local scratch buffers for decode/encode, with [`buffer_pool_stats`]
+//! - [`ResampleFilter`]: Nearest, bilinear or Lanczos3 scaling for regions and thumbnails
+//! - [`SpriteSheet`]: Collection thumbnails packed into one image
+//! - [`Snapshot`] / [`SnapshotRegion`]: Print-resolution region renders with a burned-in scale bar
 //! - [`TileRequest`]: Parameters for a tile request
 //! - [`TileResponse`]: Response containing tile data and metadata
+//! - [`TissueMap`] / [`sample_tiles`]: Seeded tissue-aware tile sampling for dataset creation
+//! - [`TileTransformer`]: Hook for forwarding tiles to an external inference service
+//! - [`Watermark`]: Text or logo stamp burned into a corner of outgoing tiles
 //!
 //! # Example
 //!
@@ -63,13 +79,73 @@
 //! }
 //! ```
 
+mod adjust;
+mod admission;
+mod blocking;
 mod cache;
 mod encoder;
+mod fairness;
+mod jpeg_crop;
+mod memory;
+mod orientation;
+mod pool;
+mod region;
+mod resample;
+mod retile;
+mod sampling;
 mod service;
+mod singleflight;
+mod snapshot;
+mod sprite;
+mod transform;
+mod watermark;
 
-pub use cache::{TileCache, TileCacheKey, DEFAULT_TILE_CACHE_CAPACITY};
+pub use adjust::{Channel, ToneAdjustment, BRIGHTNESS_RANGE, CONTRAST_RANGE, GAMMA_RANGE};
+pub use admission::{CacheAdmission, FrequencySketch};
+pub use blocking::{default_encode_workers, EncodePool};
+pub use cache::{
+    CacheLookup, TileCache, TileCacheContention, TileCacheKey, TileCacheStats,
+    DEFAULT_TILE_CACHE_CAPACITY, DEFAULT_TILE_CACHE_ENTRIES, DEFAULT_TILE_CACHE_SHARDS,
+};
+pub(crate) use encoder::decode_tile;
 pub use encoder::{
-    clamp_quality, is_valid_quality, JpegTileEncoder, DEFAULT_JPEG_QUALITY, MAX_JPEG_QUALITY,
-    MIN_JPEG_QUALITY,
+    clamp_quality, embed_icc_profile, estimate_jpeg_quality, is_valid_quality, JpegTileEncoder,
+    OutputFormat, TileEncoder, TileRendering, DEFAULT_JPEG_QUALITY, MAX_JPEG_QUALITY,
+    MIN_JPEG_QUALITY, SOURCE_QUALITY_TOLERANCE,
+};
+pub use fairness::{
+    queue_key, FairPermit, FairScheduler, QueueStats, SchedulerStats, TenantQuota,
+    DEFAULT_TILE_QUEUE_TIMEOUT,
+};
+pub use jpeg_crop::crop_jpeg;
+pub use memory::{MemoryBudget, MemoryUsage, DEFAULT_MEMORY_CHECK_INTERVAL};
+pub use orientation::Orientation;
+pub use pool::{
+    buffer_pool_stats, BufferPoolStats, PooledBuffer, MAX_POOLED_BUFFERS, MAX_POOLED_CAPACITY,
+};
+pub use region::{
+    plan_region, region_output_size, select_source_level, RegionPlan, BATCH_REGION_CONCURRENCY,
+    MAX_BATCH_REGIONS, MAX_BATCH_TILES, MAX_REGION_PIXELS,
+};
+pub use resample::ResampleFilter;
+pub use retile::{native_tile, retile_factor, sub_tiles, virtual_level};
+pub use sampling::{
+    sample_tiles, SampleOptions, SampledTile, TissueMap, DEFAULT_MIN_TISSUE, MAX_SAMPLE_STRATA,
+};
+pub use service::{Snapshot, TileRequest, TileResponse, TileService};
+pub use singleflight::{Passenger, TileFlights};
+pub use snapshot::{
+    draw_scale_bar, encode_snapshot, plan_snapshot, scale_bar_label, scale_bar_microns,
+    SnapshotPlan, SnapshotRegion, DEFAULT_PRINT_WIDTH_IN, DEFAULT_SNAPSHOT_DPI, MAX_PRINT_WIDTH_IN,
+    MAX_SNAPSHOT_DPI, MAX_SNAPSHOT_PIXELS, MIN_SNAPSHOT_DPI,
+};
+pub use sprite::{
+    compose_sprite_sheet, sprite_columns, SpriteEntry, SpriteSheet, DEFAULT_SPRITE_SIZE,
+    MAX_SPRITE_SIZE, MAX_SPRITE_SLIDES, MIN_SPRITE_SIZE,
+};
+#[cfg(feature = "inference")]
+pub use transform::HttpTileTransformer;
+pub use transform::{
+    inference_headers, TileContext, TileTransformer, TransformOutput, INFERENCE_HEADER_PREFIX,
 };
-pub use service::{TileRequest, TileResponse, TileService};
+pub use watermark::{Watermark, WatermarkCorner, DEFAULT_WATERMARK_OPACITY};
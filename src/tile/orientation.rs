@@ -0,0 +1,204 @@
+//! Image orientation.
+//!
+//! Label and macro images written by slide scanners often carry an EXIF
+//! orientation instead of storing pixels upright, so they appear sideways
+//! or mirrored unless the transform is applied before serving. This module
+//! reads the orientation from a JPEG's EXIF segment and applies it to a
+//! decoded image.
+//!
+//! Orientation values follow the EXIF / TIFF `Orientation` tag (1-8).
+
+pub use crate::format::tiff::Orientation;
+
+/// EXIF / TIFF `Orientation` tag number.
+const ORIENTATION_TAG: u16 = 0x0112;
+
+impl Orientation {
+    /// Read the EXIF orientation of a JPEG stream.
+    ///
+    /// Returns `None` if the stream has no EXIF segment or no valid
+    /// orientation tag.
+    pub fn from_jpeg(data: &[u8]) -> Option<Self> {
+        exif_orientation(exif_segment(data)?)
+    }
+}
+
+// =============================================================================
+// EXIF Parsing
+// =============================================================================
+
+/// Find the TIFF structure inside a JPEG's APP1 `Exif` segment.
+fn exif_segment(data: &[u8]) -> Option<&[u8]> {
+    if data.get(..2)? != [0xFF, 0xD8] {
+        return None;
+    }
+
+    let mut pos = 2;
+    while pos + 4 <= data.len() {
+        if data[pos] != 0xFF {
+            return None;
+        }
+        let marker = data[pos + 1];
+        // Metadata segments precede the scan
+        if marker == 0xDA || marker == 0xD9 {
+            return None;
+        }
+        let len = u16::from_be_bytes([data[pos + 2], data[pos + 3]]) as usize;
+        let segment = data.get(pos + 4..pos + 2 + len)?;
+        if marker == 0xE1 && segment.starts_with(b"Exif\0\0") {
+            return Some(&segment[6..]);
+        }
+        pos += 2 + len;
+    }
+    None
+}
+
+/// Read the orientation tag from IFD0 of an EXIF TIFF structure.
+fn exif_orientation(tiff: &[u8]) -> Option<Orientation> {
+    let little_endian = match tiff.get(..2)? {
+        b"II" => true,
+        b"MM" => false,
+        _ => return None,
+    };
+    let u16_at = |offset: usize| {
+        let bytes = [*tiff.get(offset)?, *tiff.get(offset + 1)?];
+        Some(if little_endian {
+            u16::from_le_bytes(bytes)
+        } else {
+            u16::from_be_bytes(bytes)
+        })
+    };
+    let u32_at = |offset: usize| {
+        let bytes: [u8; 4] = tiff.get(offset..offset + 4)?.try_into().ok()?;
+        Some(if little_endian {
+            u32::from_le_bytes(bytes)
+        } else {
+            u32::from_be_bytes(bytes)
+        })
+    };
+
+    let ifd = u32_at(4)? as usize;
+    let count = u16_at(ifd)? as usize;
+    for index in 0..count {
+        let entry = ifd + 2 + index * 12;
+        if u16_at(entry)? == ORIENTATION_TAG {
+            // SHORT value stored inline in the first two bytes
+            return Orientation::from_value(u16_at(entry + 8)?);
+        }
+    }
+    None
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{DynamicImage, GenericImageView, Rgb, RgbImage};
+
+    /// A JPEG prefix with an EXIF orientation tag (no image data needed).
+    fn jpeg_with_orientation(value: u16, little_endian: bool) -> Vec<u8> {
+        let mut tiff = Vec::new();
+        type Encoders = (fn(u16) -> [u8; 2], fn(u32) -> [u8; 4]);
+        let (u16b, u32b): Encoders = if little_endian {
+            tiff.extend_from_slice(b"II");
+            (u16::to_le_bytes, u32::to_le_bytes)
+        } else {
+            tiff.extend_from_slice(b"MM");
+            (u16::to_be_bytes, u32::to_be_bytes)
+        };
+        tiff.extend_from_slice(&u16b(42));
+        tiff.extend_from_slice(&u32b(8));
+        tiff.extend_from_slice(&u16b(2));
+        // ImageDescription entry before the orientation
+        tiff.extend_from_slice(&u16b(0x010E));
+        tiff.extend_from_slice(&u16b(2));
+        tiff.extend_from_slice(&u32b(4));
+        tiff.extend_from_slice(b"abc\0");
+        tiff.extend_from_slice(&u16b(ORIENTATION_TAG));
+        tiff.extend_from_slice(&u16b(3));
+        tiff.extend_from_slice(&u32b(1));
+        tiff.extend_from_slice(&u16b(value));
+        tiff.extend_from_slice(&[0, 0]);
+        tiff.extend_from_slice(&u32b(0));
+
+        let mut segment = b"Exif\0\0".to_vec();
+        segment.extend_from_slice(&tiff);
+
+        let mut jpeg = vec![0xFF, 0xD8];
+        // A JFIF segment first, as most encoders write one
+        jpeg.extend_from_slice(&[0xFF, 0xE0, 0x00, 0x04, 0x00, 0x00]);
+        jpeg.extend_from_slice(&[0xFF, 0xE1]);
+        jpeg.extend_from_slice(&((segment.len() + 2) as u16).to_be_bytes());
+        jpeg.extend_from_slice(&segment);
+        jpeg.extend_from_slice(&[0xFF, 0xDA, 0x00, 0x02]);
+        jpeg
+    }
+
+    #[test]
+    fn test_orientation_from_jpeg() {
+        for value in 1..=8 {
+            let expected = Orientation::from_value(value);
+            assert_eq!(
+                Orientation::from_jpeg(&jpeg_with_orientation(value, true)),
+                expected
+            );
+            assert_eq!(
+                Orientation::from_jpeg(&jpeg_with_orientation(value, false)),
+                expected
+            );
+        }
+        assert_eq!(
+            Orientation::from_jpeg(&jpeg_with_orientation(9, true)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_orientation_missing_or_malformed() {
+        assert_eq!(Orientation::from_jpeg(&[]), None);
+        assert_eq!(Orientation::from_jpeg(b"not a jpeg"), None);
+        assert_eq!(
+            Orientation::from_jpeg(&[0xFF, 0xD8, 0xFF, 0xDA, 0x00, 0x02]),
+            None
+        );
+
+        // Truncated EXIF segment
+        let jpeg = jpeg_with_orientation(6, true);
+        assert_eq!(Orientation::from_jpeg(&jpeg[..20]), None);
+    }
+
+    #[test]
+    fn test_apply_orientation() {
+        // 2x1 image: red on the left, blue on the right
+        let mut img = RgbImage::new(2, 1);
+        img.put_pixel(0, 0, Rgb([255, 0, 0]));
+        img.put_pixel(1, 0, Rgb([0, 0, 255]));
+        let img = DynamicImage::ImageRgb8(img);
+        let red = image::Rgba([255, 0, 0, 255]);
+
+        let upright = Orientation::Normal.apply(img.clone());
+        assert_eq!(upright.get_pixel(0, 0), red);
+
+        let mirrored = Orientation::FlipHorizontal.apply(img.clone());
+        assert_eq!(mirrored.get_pixel(1, 0), red);
+
+        // 90° clockwise: the left column becomes the top row
+        let rotated = Orientation::Rotate90.apply(img.clone());
+        assert_eq!(rotated.dimensions(), (1, 2));
+        assert_eq!(rotated.get_pixel(0, 0), red);
+
+        let rotated = Orientation::Rotate270.apply(img.clone());
+        assert_eq!(rotated.get_pixel(0, 1), red);
+
+        // Transpose keeps the top-left pixel in place
+        let transposed = Orientation::Transpose.apply(img);
+        assert_eq!(transposed.dimensions(), (1, 2));
+        assert_eq!(transposed.get_pixel(0, 0), red);
+
+        assert!(Orientation::Transverse.swaps_dimensions());
+        assert!(!Orientation::Rotate180.swaps_dimensions());
+    }
+}
@@ -0,0 +1,259 @@
+//! Rendering arbitrary slide regions.
+//!
+//! A region is a rectangle in level 0 pixels rendered to an output size. It
+//! is stitched from the tiles of the lowest-resolution level that still
+//! covers the output resolution, each tile resampled straight into the
+//! output canvas, so memory is bounded by the output size rather than the
+//! source region. Used by print snapshots, the IIIF Image API and the
+//! region endpoint.
+
+use std::ops::Range;
+
+use crate::slide::LevelInfo;
+
+/// Maximum number of output pixels of a region request (25 megapixels).
+pub const MAX_REGION_PIXELS: u64 = 25_000_000;
+
+/// Maximum number of regions in one batch request.
+pub const MAX_BATCH_REGIONS: usize = 64;
+
+/// Maximum number of source tiles one batch request may read.
+///
+/// This is the storage budget of a batch: it bounds the S3 reads a single
+/// request can cause, however its regions are sized.
+pub const MAX_BATCH_TILES: u64 = 2048;
+
+/// Number of regions of a batch rendered at once.
+pub const BATCH_REGION_CONCURRENCY: usize = 4;
+
+/// How a region maps onto the pyramid.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegionPlan {
+    /// Pyramid level the tiles are read from
+    pub level: usize,
+
+    /// Downsample of that level relative to level 0
+    pub level_downsample: f64,
+
+    /// Left edge of the region in level 0 pixels
+    pub x: f64,
+
+    /// Top edge of the region in level 0 pixels
+    pub y: f64,
+
+    /// Region width in level 0 pixels
+    pub width: f64,
+
+    /// Region height in level 0 pixels
+    pub height: f64,
+
+    /// Horizontal output pixels per level 0 pixel
+    pub scale_x: f64,
+
+    /// Vertical output pixels per level 0 pixel
+    pub scale_y: f64,
+
+    /// Output width in pixels
+    pub out_width: u32,
+
+    /// Output height in pixels
+    pub out_height: u32,
+}
+
+impl RegionPlan {
+    /// Tiles of the planned level that intersect the region.
+    pub fn tile_range(&self, info: &LevelInfo) -> (Range<u32>, Range<u32>) {
+        let span = |start: f64, len: f64, tile: u32, count: u32| {
+            let first = (start / self.level_downsample / tile as f64).floor() as u32;
+            let last = ((start + len) / self.level_downsample / tile as f64).ceil() as u32;
+            first.min(count)..last.min(count)
+        };
+        (
+            span(self.x, self.width, info.tile_width, info.tiles_x),
+            span(self.y, self.height, info.tile_height, info.tiles_y),
+        )
+    }
+
+    /// Where a decoded tile lands in the output, as `(x, y, width, height)`.
+    ///
+    /// Edges are rounded independently so neighboring tiles share their
+    /// boundary exactly and no seams appear. `tile_width` and `tile_height`
+    /// are the decoded tile's dimensions, which are smaller than the nominal
+    /// tile size for cropped edge tiles.
+    pub fn tile_destination(
+        &self,
+        info: &LevelInfo,
+        tile_x: u32,
+        tile_y: u32,
+        tile_width: u32,
+        tile_height: u32,
+    ) -> (i64, i64, u32, u32) {
+        let edge = |level_px: f64, origin: f64, scale: f64| {
+            ((level_px * self.level_downsample - origin) * scale).round() as i64
+        };
+        let left_px = (tile_x * info.tile_width) as f64;
+        let top_px = (tile_y * info.tile_height) as f64;
+
+        let left = edge(left_px, self.x, self.scale_x);
+        let top = edge(top_px, self.y, self.scale_y);
+        let right = edge(left_px + tile_width as f64, self.x, self.scale_x);
+        let bottom = edge(top_px + tile_height as f64, self.y, self.scale_y);
+
+        (
+            left,
+            top,
+            (right - left).max(0) as u32,
+            (bottom - top).max(0) as u32,
+        )
+    }
+}
+
+/// Pick the pyramid level to render a target downsample from.
+///
+/// Returns the lowest-resolution level whose downsample doesn't exceed
+/// `downsample`, so the source never needs upsampling; level 0 when every
+/// level is coarser. The small tolerance absorbs rounding in stored
+/// downsample factors.
+pub fn select_source_level(downsample: f64, downsamples: &[f64]) -> usize {
+    downsamples
+        .iter()
+        .enumerate()
+        .filter(|(_, ds)| **ds <= downsample * 1.001)
+        .max_by(|a, b| a.1.total_cmp(b.1))
+        .map(|(level, _)| level)
+        .unwrap_or(0)
+}
+
+/// Plan rendering a region of level 0 pixels at an output size.
+///
+/// # Arguments
+///
+/// * `x`, `y`, `width`, `height` - Region in level 0 pixels, already clipped
+///   to the slide and non-empty
+/// * `out_width`, `out_height` - Output size in pixels (at least 1)
+/// * `downsamples` - Downsample factor of each level, level 0 first
+///
+/// Picks the lowest-resolution level that doesn't need upsampling along
+/// either axis; outputs larger than the region are upsampled from level 0.
+pub fn plan_region(
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+    out_width: u32,
+    out_height: u32,
+    downsamples: &[f64],
+) -> RegionPlan {
+    let scale_x = out_width as f64 / width;
+    let scale_y = out_height as f64 / height;
+
+    let level = select_source_level(1.0 / scale_x.max(scale_y), downsamples);
+
+    RegionPlan {
+        level,
+        level_downsample: downsamples.get(level).copied().unwrap_or(1.0),
+        x,
+        y,
+        width,
+        height,
+        scale_x,
+        scale_y,
+        out_width,
+        out_height,
+    }
+}
+
+/// Output size of a `width` x `height` region resized to the requested
+/// dimensions.
+///
+/// A missing dimension follows the region's aspect ratio; with neither, the
+/// region is returned at its own size.
+pub fn region_output_size(
+    width: u32,
+    height: u32,
+    out_width: Option<u32>,
+    out_height: Option<u32>,
+) -> (u32, u32) {
+    let scaled = |len: u32, target: u32, other: u32| {
+        ((len as f64 * target as f64 / other as f64).round() as u32).max(1)
+    };
+    match (out_width, out_height) {
+        (Some(w), Some(h)) => (w, h),
+        (Some(w), None) => (w, scaled(height, w, width)),
+        (None, Some(h)) => (scaled(width, h, height), h),
+        (None, None) => (width, height),
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn level_info(width: u32, height: u32, downsample: f64) -> LevelInfo {
+        LevelInfo {
+            width,
+            height,
+            tile_width: 256,
+            tile_height: 256,
+            tiles_x: width.div_ceil(256),
+            tiles_y: height.div_ceil(256),
+            downsample,
+        }
+    }
+
+    #[test]
+    fn test_plan_region_picks_coarsest_sufficient_level() {
+        let downsamples = [1.0, 4.0, 16.0];
+
+        // 4096 px rendered at 1024 px: level 1 exactly
+        let plan = plan_region(0.0, 0.0, 4096.0, 4096.0, 1024, 1024, &downsamples);
+        assert_eq!(plan.level, 1);
+        assert_eq!(plan.scale_x, 0.25);
+
+        // Anisotropic output: the finer axis decides
+        let plan = plan_region(0.0, 0.0, 4096.0, 4096.0, 2048, 256, &downsamples);
+        assert_eq!(plan.level, 0);
+        assert_eq!(plan.scale_y, 1.0 / 16.0);
+
+        // Upsampling reads level 0
+        let plan = plan_region(0.0, 0.0, 100.0, 100.0, 400, 400, &downsamples);
+        assert_eq!(plan.level, 0);
+        assert_eq!(plan.scale_x, 4.0);
+    }
+
+    #[test]
+    fn test_region_output_size() {
+        assert_eq!(region_output_size(400, 300, None, None), (400, 300));
+        assert_eq!(region_output_size(400, 300, Some(200), None), (200, 150));
+        assert_eq!(region_output_size(400, 300, None, Some(60)), (80, 60));
+        assert_eq!(region_output_size(400, 300, Some(10), Some(10)), (10, 10));
+        assert_eq!(region_output_size(1000, 1, Some(10), None), (10, 1));
+    }
+
+    #[test]
+    fn test_region_tile_range() {
+        let info = level_info(1000, 1000, 1.0);
+        let plan = plan_region(300.0, 10.0, 300.0, 200.0, 300, 200, &[1.0]);
+
+        let (xs, ys) = plan.tile_range(&info);
+        assert_eq!(xs, 1..3);
+        assert_eq!(ys, 0..1);
+    }
+
+    #[test]
+    fn test_region_tile_destinations_are_seamless() {
+        let info = level_info(1024, 1024, 1.0);
+        let plan = plan_region(100.0, 0.0, 700.0, 512.0, 233, 170, &[1.0]);
+
+        let (x0, _, w0, _) = plan.tile_destination(&info, 0, 0, 256, 256);
+        let (x1, _, _, _) = plan.tile_destination(&info, 1, 0, 256, 256);
+        assert_eq!(x0 + w0 as i64, x1);
+        let (_, y0, _, h0) = plan.tile_destination(&info, 0, 0, 256, 256);
+        let (_, y1, _, _) = plan.tile_destination(&info, 0, 1, 256, 256);
+        assert_eq!(y0 + h0 as i64, y1);
+    }
+}
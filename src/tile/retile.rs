@@ -0,0 +1,140 @@
+//! Retiling of giant native tiles.
+//!
+//! Some scanners write 1024x1024 or 2048x2048 native tiles, which are slow
+//! to transfer to browsers. With retiling enabled, such levels are exposed as
+//! a virtual grid of smaller tiles. A native tile is decoded once and cropped
+//! into all of its sub-tiles, which are encoded and cached together, so the
+//! neighbours a viewer requests next are already warm.
+//!
+//! Only levels whose native tile size is an exact multiple of the virtual
+//! tile size (in both directions) are retiled; other levels keep their
+//! native grid.
+
+use crate::slide::LevelInfo;
+
+/// Number of virtual tiles per native tile along each axis, if the level is
+/// retiled at `tile_size`.
+pub fn retile_factor(info: &LevelInfo, tile_size: u32) -> Option<(u32, u32)> {
+    if tile_size == 0 || info.tile_width % tile_size != 0 || info.tile_height % tile_size != 0 {
+        return None;
+    }
+
+    match (info.tile_width / tile_size, info.tile_height / tile_size) {
+        (1, 1) | (0, _) | (_, 0) => None,
+        factor => Some(factor),
+    }
+}
+
+/// The level as exposed to clients when retiling at `tile_size`.
+///
+/// Levels that are not retiled are returned unchanged.
+pub fn virtual_level(info: &LevelInfo, tile_size: u32) -> LevelInfo {
+    if retile_factor(info, tile_size).is_none() {
+        return *info;
+    }
+
+    LevelInfo {
+        tile_width: tile_size,
+        tile_height: tile_size,
+        tiles_x: info.width.div_ceil(tile_size).max(1),
+        tiles_y: info.height.div_ceil(tile_size).max(1),
+        ..*info
+    }
+}
+
+/// Native tile containing a virtual tile.
+pub fn native_tile(tile_x: u32, tile_y: u32, factor: (u32, u32)) -> (u32, u32) {
+    (tile_x / factor.0, tile_y / factor.1)
+}
+
+/// Virtual tiles cut from a native tile, clipped to the virtual grid.
+///
+/// Returns `(tile_x, tile_y, offset_x, offset_y)` for each sub-tile, where
+/// the offset is the sub-tile's top-left corner within the native tile.
+pub fn sub_tiles(
+    info: &LevelInfo,
+    tile_size: u32,
+    native_x: u32,
+    native_y: u32,
+) -> Vec<(u32, u32, u32, u32)> {
+    let Some((factor_x, factor_y)) = retile_factor(info, tile_size) else {
+        return Vec::new();
+    };
+    let grid = virtual_level(info, tile_size);
+
+    let mut tiles = Vec::with_capacity((factor_x * factor_y) as usize);
+    for sub_y in 0..factor_y {
+        let tile_y = native_y * factor_y + sub_y;
+        if tile_y >= grid.tiles_y {
+            break;
+        }
+        for sub_x in 0..factor_x {
+            let tile_x = native_x * factor_x + sub_x;
+            if tile_x >= grid.tiles_x {
+                break;
+            }
+            tiles.push((tile_x, tile_y, sub_x * tile_size, sub_y * tile_size));
+        }
+    }
+    tiles
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn level(width: u32, height: u32, tile: u32) -> LevelInfo {
+        LevelInfo {
+            width,
+            height,
+            tile_width: tile,
+            tile_height: tile,
+            tiles_x: width.div_ceil(tile),
+            tiles_y: height.div_ceil(tile),
+            downsample: 1.0,
+        }
+    }
+
+    #[test]
+    fn test_retile_factor() {
+        assert_eq!(retile_factor(&level(4096, 4096, 1024), 256), Some((4, 4)));
+        assert_eq!(retile_factor(&level(4096, 4096, 2048), 512), Some((4, 4)));
+        assert_eq!(retile_factor(&level(4096, 4096, 256), 256), None);
+        assert_eq!(retile_factor(&level(4096, 4096, 240), 256), None);
+        assert_eq!(retile_factor(&level(4096, 4096, 1000), 256), None);
+        assert_eq!(retile_factor(&level(4096, 4096, 1024), 0), None);
+    }
+
+    #[test]
+    fn test_virtual_level() {
+        let native = level(3000, 1500, 1024);
+        let grid = virtual_level(&native, 256);
+        assert_eq!((grid.tile_width, grid.tile_height), (256, 256));
+        assert_eq!((grid.tiles_x, grid.tiles_y), (12, 6));
+        assert_eq!((grid.width, grid.height), (3000, 1500));
+
+        let small = level(3000, 1500, 256);
+        assert_eq!(virtual_level(&small, 256), small);
+    }
+
+    #[test]
+    fn test_sub_tiles_clipped_to_grid() {
+        let native = level(3000, 1500, 1024);
+        assert_eq!(native_tile(9, 5, (4, 4)), (2, 1));
+
+        // Interior native tile: all 16 sub-tiles
+        let tiles = sub_tiles(&native, 256, 0, 0);
+        assert_eq!(tiles.len(), 16);
+        assert_eq!(tiles[5], (1, 1, 256, 256));
+
+        // Bottom-right native tile covers columns 8-11 and rows 4-5 only
+        let tiles = sub_tiles(&native, 256, 2, 1);
+        assert_eq!(tiles.len(), 8);
+        assert_eq!(tiles[0], (8, 4, 0, 0));
+        assert_eq!(tiles[7], (11, 5, 768, 256));
+    }
+}
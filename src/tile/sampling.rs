@@ -0,0 +1,394 @@
+//! Deterministic tissue-aware tile sampling.
+//!
+//! Building a training set from a slide usually starts with picking a random
+//! subset of tiles that actually contain tissue. This module provides:
+//!
+//! - [`TissueMap`]: per-tile tissue fraction for a pyramid level, measured
+//!   on the slide's [`TissueMask`]
+//! - [`sample_tiles`]: seeded sampling of tissue tiles, optionally stratified
+//!   by tissue density so sparse and dense regions are both represented
+//!
+//! Sampling is fully deterministic: the same slide, level and options always
+//! produce the same coordinates, on every platform.
+
+use image::RgbImage;
+
+use crate::slide::{LevelInfo, TissueMask};
+
+// =============================================================================
+// Configuration
+// =============================================================================
+
+/// Default minimum tissue fraction for a tile to be eligible for sampling.
+pub const DEFAULT_MIN_TISSUE: f32 = 0.25;
+
+/// Maximum number of tissue-density strata.
+pub const MAX_SAMPLE_STRATA: usize = 100;
+
+// =============================================================================
+// Tissue Map
+// =============================================================================
+
+/// Per-tile tissue fraction for a single pyramid level.
+#[derive(Debug, Clone)]
+pub struct TissueMap {
+    /// Number of tiles in X direction
+    tiles_x: u32,
+
+    /// Number of tiles in Y direction
+    tiles_y: u32,
+
+    /// Tissue fraction (0.0-1.0) of each tile, in row-major order
+    fractions: Vec<f32>,
+}
+
+impl TissueMap {
+    /// Build a tissue map for a level from an overview image of the slide.
+    ///
+    /// The overview must cover the whole slide. Tissue is detected with a
+    /// [`TissueMask`], then measured per tile with [`from_mask`](Self::from_mask).
+    pub fn from_image(overview: &RgbImage, info: &LevelInfo) -> Self {
+        Self::from_mask(&TissueMask::from_image(overview), info)
+    }
+
+    /// Build a tissue map for a level from the slide's tissue mask.
+    ///
+    /// Each tile of the level is projected onto the mask and the fraction
+    /// of tissue pixels underneath is recorded. Tiles smaller than one mask
+    /// pixel use the pixel they fall in.
+    pub fn from_mask(mask: &TissueMask, info: &LevelInfo) -> Self {
+        let fractions = (0..info.tiles_y)
+            .flat_map(|tile_y| (0..info.tiles_x).map(move |tile_x| (tile_x, tile_y)))
+            .map(|(tile_x, tile_y)| mask.tile_fraction(info, tile_x, tile_y))
+            .collect();
+
+        Self {
+            tiles_x: info.tiles_x,
+            tiles_y: info.tiles_y,
+            fractions,
+        }
+    }
+
+    /// Build a tissue map from precomputed fractions (row-major).
+    ///
+    /// Returns `None` if the number of fractions doesn't match the grid.
+    pub fn from_fractions(tiles_x: u32, tiles_y: u32, fractions: Vec<f32>) -> Option<Self> {
+        if fractions.len() != tiles_x as usize * tiles_y as usize {
+            return None;
+        }
+        Some(Self {
+            tiles_x,
+            tiles_y,
+            fractions,
+        })
+    }
+
+    /// Number of tiles in X direction.
+    pub fn tiles_x(&self) -> u32 {
+        self.tiles_x
+    }
+
+    /// Number of tiles in Y direction.
+    pub fn tiles_y(&self) -> u32 {
+        self.tiles_y
+    }
+
+    /// Tissue fraction of a tile, or `None` if out of bounds.
+    pub fn fraction(&self, tile_x: u32, tile_y: u32) -> Option<f32> {
+        if tile_x >= self.tiles_x || tile_y >= self.tiles_y {
+            return None;
+        }
+        self.fractions
+            .get((tile_y * self.tiles_x + tile_x) as usize)
+            .copied()
+    }
+}
+
+// =============================================================================
+// Sampling
+// =============================================================================
+
+/// Options for tile sampling.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SampleOptions {
+    /// Number of tiles to sample
+    pub count: usize,
+
+    /// Seed for the random generator
+    pub seed: u64,
+
+    /// Minimum tissue fraction for a tile to be eligible (0.0-1.0)
+    pub min_tissue: f32,
+
+    /// Number of tissue-density strata (1 = no stratification)
+    pub strata: usize,
+}
+
+impl SampleOptions {
+    /// Create options sampling `count` tiles with seed 0 and no stratification.
+    pub fn new(count: usize) -> Self {
+        Self {
+            count,
+            seed: 0,
+            min_tissue: DEFAULT_MIN_TISSUE,
+            strata: 1,
+        }
+    }
+
+    /// Set the random seed.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Set the minimum tissue fraction (clamped to 0.0-1.0).
+    pub fn with_min_tissue(mut self, min_tissue: f32) -> Self {
+        self.min_tissue = min_tissue.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Stratify by tissue density into `strata` equal-width bins (clamped
+    /// to 1-[`MAX_SAMPLE_STRATA`]).
+    ///
+    /// Samples are drawn round-robin from each non-empty bin, so tiles at
+    /// the tissue boundary are represented as well as dense tissue.
+    pub fn with_strata(mut self, strata: usize) -> Self {
+        self.strata = strata.clamp(1, MAX_SAMPLE_STRATA);
+        self
+    }
+}
+
+/// A sampled tile coordinate.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SampledTile {
+    /// Tile X coordinate
+    pub tile_x: u32,
+
+    /// Tile Y coordinate
+    pub tile_y: u32,
+
+    /// Tissue fraction of the tile (0.0-1.0)
+    pub tissue: f32,
+}
+
+/// Sample tissue-containing tiles from a tissue map.
+///
+/// Returns at most `options.count` tiles; fewer if the slide doesn't have
+/// enough eligible tiles. The result is deterministic for a given map and
+/// options.
+pub fn sample_tiles(map: &TissueMap, options: &SampleOptions) -> Vec<SampledTile> {
+    let strata = options.strata.clamp(1, MAX_SAMPLE_STRATA);
+    let mut bins: Vec<Vec<SampledTile>> = vec![Vec::new(); strata];
+
+    for tile_y in 0..map.tiles_y {
+        for tile_x in 0..map.tiles_x {
+            let tissue = map.fraction(tile_x, tile_y).unwrap_or(0.0);
+            if tissue <= 0.0 || tissue < options.min_tissue {
+                continue;
+            }
+            let bin = stratum(tissue, options.min_tissue, strata);
+            bins[bin].push(SampledTile {
+                tile_x,
+                tile_y,
+                tissue,
+            });
+        }
+    }
+
+    let mut rng = SplitMix64::new(options.seed);
+    for bin in &mut bins {
+        rng.shuffle(bin);
+    }
+
+    // Round-robin across bins so each stratum contributes evenly
+    let mut result = Vec::with_capacity(options.count);
+    let mut cursors = vec![0usize; strata];
+    while result.len() < options.count {
+        let mut progressed = false;
+        for (bin, cursor) in bins.iter().zip(cursors.iter_mut()) {
+            if result.len() >= options.count {
+                break;
+            }
+            if let Some(tile) = bin.get(*cursor) {
+                result.push(*tile);
+                *cursor += 1;
+                progressed = true;
+            }
+        }
+        if !progressed {
+            break;
+        }
+    }
+
+    result
+}
+
+/// Map a tissue fraction to its stratum index.
+fn stratum(tissue: f32, min_tissue: f32, strata: usize) -> usize {
+    let span = (1.0 - min_tissue).max(f32::EPSILON);
+    let position = ((tissue - min_tissue) / span).clamp(0.0, 1.0);
+    ((position * strata as f32) as usize).min(strata - 1)
+}
+
+/// Small, portable PRNG so sampling results don't depend on a platform RNG.
+struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Fisher-Yates shuffle.
+    fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = (self.next_u64() % (i as u64 + 1)) as usize;
+            items.swap(i, j);
+        }
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgb;
+
+    fn level_info(width: u32, height: u32, tile: u32) -> LevelInfo {
+        LevelInfo {
+            width,
+            height,
+            tile_width: tile,
+            tile_height: tile,
+            tiles_x: width.div_ceil(tile),
+            tiles_y: height.div_ceil(tile),
+            downsample: 1.0,
+        }
+    }
+
+    #[test]
+    fn test_padding_is_background() {
+        // Tissue on the left, glass in the middle, black and near-black
+        // scanner padding on the right
+        let overview = RgbImage::from_fn(100, 50, |x, _| match x {
+            0..25 => Rgb([180, 90, 160]),
+            25..50 => Rgb([245, 245, 245]),
+            50..75 => Rgb([0, 0, 0]),
+            _ => Rgb([14, 0, 10]),
+        });
+        let info = level_info(1000, 500, 250);
+
+        // Mask and map share one detector, so they agree on padding
+        let mask = TissueMask::from_image(&overview);
+        let map = TissueMap::from_mask(&mask, &info);
+        assert_eq!(map.fraction(0, 0), Some(1.0));
+        for tile_x in 1..4 {
+            assert_eq!(map.fraction(tile_x, 0), Some(0.0));
+            assert!(!mask.tile_has_tissue(&info, tile_x, 0));
+        }
+        assert!((mask.tissue_fraction() - 0.25).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_tissue_map_from_image() {
+        // Left half tissue, right half background
+        let overview = RgbImage::from_fn(100, 50, |x, _| {
+            if x < 50 {
+                Rgb([150, 80, 140])
+            } else {
+                Rgb([250, 250, 250])
+            }
+        });
+        let info = level_info(1000, 500, 250);
+        let map = TissueMap::from_image(&overview, &info);
+
+        assert_eq!(map.tiles_x(), 4);
+        assert_eq!(map.tiles_y(), 2);
+        assert_eq!(map.fraction(0, 0), Some(1.0));
+        assert_eq!(map.fraction(3, 1), Some(0.0));
+        assert_eq!(map.fraction(4, 0), None);
+    }
+
+    #[test]
+    fn test_from_fractions_validates_length() {
+        assert!(TissueMap::from_fractions(2, 2, vec![0.0; 3]).is_none());
+        assert!(TissueMap::from_fractions(2, 2, vec![0.0; 4]).is_some());
+    }
+
+    #[test]
+    fn test_sampling_is_deterministic() {
+        let fractions = (0..100).map(|i| (i % 10) as f32 / 10.0).collect();
+        let map = TissueMap::from_fractions(10, 10, fractions).unwrap();
+
+        let options = SampleOptions::new(20).with_seed(42);
+        let a = sample_tiles(&map, &options);
+        let b = sample_tiles(&map, &options);
+        assert_eq!(a, b);
+        assert_eq!(a.len(), 20);
+
+        let c = sample_tiles(&map, &options.with_seed(43));
+        assert_ne!(a, c);
+    }
+
+    #[test]
+    fn test_sampling_respects_min_tissue() {
+        let fractions = (0..100).map(|i| (i % 10) as f32 / 10.0).collect();
+        let map = TissueMap::from_fractions(10, 10, fractions).unwrap();
+
+        let options = SampleOptions::new(1000).with_min_tissue(0.5);
+        let samples = sample_tiles(&map, &options);
+
+        // 5 of every 10 tiles have tissue >= 0.5
+        assert_eq!(samples.len(), 50);
+        assert!(samples.iter().all(|s| s.tissue >= 0.5));
+    }
+
+    #[test]
+    fn test_sampling_stratified() {
+        // 90 dense tiles, 10 sparse tiles
+        let fractions = (0..100).map(|i| if i < 10 { 0.3 } else { 1.0 }).collect();
+        let map = TissueMap::from_fractions(10, 10, fractions).unwrap();
+
+        let options = SampleOptions::new(10).with_min_tissue(0.25).with_strata(2);
+        let samples = sample_tiles(&map, &options);
+
+        let sparse = samples.iter().filter(|s| s.tissue < 0.5).count();
+        assert_eq!(samples.len(), 10);
+        assert_eq!(sparse, 5);
+    }
+
+    #[test]
+    fn test_strata_clamped() {
+        assert_eq!(SampleOptions::new(1).with_strata(0).strata, 1);
+        assert_eq!(
+            SampleOptions::new(1).with_strata(usize::MAX).strata,
+            MAX_SAMPLE_STRATA
+        );
+
+        let map = TissueMap::from_fractions(2, 2, vec![1.0; 4]).unwrap();
+        let options = SampleOptions {
+            strata: usize::MAX,
+            ..SampleOptions::new(4)
+        };
+        assert_eq!(sample_tiles(&map, &options).len(), 4);
+    }
+
+    #[test]
+    fn test_sampling_empty_map() {
+        let map = TissueMap::from_fractions(4, 4, vec![0.0; 16]).unwrap();
+        let samples = sample_tiles(&map, &SampleOptions::new(5).with_min_tissue(0.0));
+        assert!(samples.is_empty());
+    }
+}
@@ -21,23 +21,53 @@
 //! │           │                    │                    │            │
 //! │           ▼                    ▼                    ▼            │
 //! │    ┌───────────┐      ┌──────────────┐    ┌──────────────────┐  │
-//! │    │ TileCache │      │ SlideRegistry│    │ JpegTileEncoder  │  │
+//! │    │ TileCache │      │ SlideRegistry│    │   TileEncoder    │  │
 //! │    └───────────┘      └──────────────┘    └──────────────────┘  │
 //! └─────────────────────────────────────────────────────────────────┘
 //! ```
 
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 
 use bytes::Bytes;
 use image::codecs::jpeg::JpegEncoder;
-use image::{DynamicImage, ImageReader, RgbImage};
+use image::{imageops, DynamicImage, ImageReader, Rgb, RgbImage};
 use std::io::Cursor;
-
-use crate::error::TileError;
-use crate::slide::{SlideRegistry, SlideSource};
-
+use tokio::task::JoinSet;
+use tracing::warn;
+
+use crate::error::{IoError, TiffError, TileError};
+use crate::overlay::{AnnotationSet, TileOverlay};
+use crate::server::panic::CaughtPanic;
+use crate::slide::{
+    CachedSlide, CatchUnwind, EventBus, LevelInfo, ServerEvent, SlideRegistry, SlideSource,
+    TissueMask, DEFAULT_TILE_STREAM_CONCURRENCY,
+};
+use crate::timing::{self, Phase};
+
+use super::adjust::{Channel, ToneAdjustment};
+use super::blocking::EncodePool;
 use super::cache::{TileCache, TileCacheKey};
-use super::encoder::{is_valid_quality, JpegTileEncoder, DEFAULT_JPEG_QUALITY};
+use super::encoder::{
+    decode_tile, embed_icc_profile, encode_image, estimate_jpeg_quality, is_valid_quality,
+    OutputFormat, TileEncoder, TileRendering, DEFAULT_JPEG_QUALITY,
+};
+use super::fairness::FairScheduler;
+use super::memory::{MemoryBudget, MemoryUsage};
+use super::region::{plan_region, RegionPlan};
+use super::resample::ResampleFilter;
+use super::retile::{native_tile, retile_factor, sub_tiles, virtual_level};
+use super::sampling::{sample_tiles, SampleOptions, SampledTile, TissueMap};
+use super::singleflight::TileFlights;
+use super::snapshot::{draw_scale_bar, plan_snapshot, SnapshotPlan, SnapshotRegion};
+use super::sprite::{compose_sprite_sheet, SpriteSheet};
+use super::transform::TileTransformer;
+use super::watermark::Watermark;
+
+/// Maximum dimension of the overview image used to estimate tissue.
+const TISSUE_OVERVIEW_SIZE: u32 = 1024;
 
 // =============================================================================
 // Tile Request
@@ -62,6 +92,28 @@ pub struct TileRequest {
 
     /// JPEG quality (1-100, defaults to 80)
     pub quality: u8,
+
+    /// Output format (defaults to JPEG)
+    pub format: OutputFormat,
+
+    /// Tenant the request is scheduled under (see [`FairScheduler`])
+    pub tenant: Option<String>,
+
+    /// Serve JPEG source tiles unchanged instead of re-encoding them at
+    /// `quality`
+    pub passthrough: bool,
+
+    /// Brightness, contrast and gamma applied before encoding
+    pub adjustment: Option<ToneAdjustment>,
+
+    /// Single channel served as a grayscale tile
+    pub channel: Option<Channel>,
+
+    /// Annotations drawn onto the tile
+    pub overlay: Option<Arc<AnnotationSet>>,
+
+    /// Burn the service's watermark into the tile, if one is configured
+    pub watermark: bool,
 }
 
 impl TileRequest {
@@ -73,6 +125,13 @@ impl TileRequest {
             tile_x,
             tile_y,
             quality: DEFAULT_JPEG_QUALITY,
+            format: OutputFormat::Jpeg,
+            tenant: None,
+            passthrough: false,
+            adjustment: None,
+            channel: None,
+            overlay: None,
+            watermark: false,
         }
     }
 
@@ -90,8 +149,71 @@ impl TileRequest {
             tile_x,
             tile_y,
             quality,
+            format: OutputFormat::Jpeg,
+            tenant: None,
+            passthrough: false,
+            adjustment: None,
+            channel: None,
+            overlay: None,
+            watermark: false,
         }
     }
+
+    /// Encode the tile in the given output format.
+    pub fn with_format(mut self, format: OutputFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Schedule the request under a tenant instead of its slide.
+    pub fn with_tenant(mut self, tenant: impl Into<String>) -> Self {
+        self.tenant = Some(tenant.into());
+        self
+    }
+
+    /// Serve the source tile without decoding and re-encoding it.
+    ///
+    /// Applies to JPEG output from complete JPEG source tiles that are safe
+    /// to serve as-is; other tiles are encoded at [`DEFAULT_JPEG_QUALITY`].
+    /// `quality` is ignored for JPEG output of unchanged pixels.
+    pub fn with_passthrough(mut self, enabled: bool) -> Self {
+        self.passthrough = enabled;
+        self
+    }
+
+    /// Adjust brightness, contrast and gamma before encoding.
+    ///
+    /// Adjusted tiles are always re-encoded; an identity adjustment is
+    /// dropped, so it shares cached tiles with unadjusted requests.
+    pub fn with_adjustment(mut self, adjustment: Option<ToneAdjustment>) -> Self {
+        self.adjustment = adjustment.filter(|adjustment| !adjustment.is_identity());
+        self
+    }
+
+    /// Serve a single channel (or luminance) as a grayscale tile.
+    ///
+    /// Like adjusted tiles, channel tiles are always re-encoded.
+    pub fn with_channel(mut self, channel: Option<Channel>) -> Self {
+        self.channel = channel;
+        self
+    }
+
+    /// Draw annotations onto the tile, scaled to its level.
+    ///
+    /// Like adjusted tiles, annotated tiles are always re-encoded; an empty
+    /// set is dropped.
+    pub fn with_overlay(mut self, annotations: Option<Arc<AnnotationSet>>) -> Self {
+        self.overlay = annotations.filter(|annotations| !annotations.is_empty());
+        self
+    }
+
+    /// Stamp the tile with the service's watermark.
+    ///
+    /// Ignored when the service has no watermark configured.
+    pub fn with_watermark(mut self, enabled: bool) -> Self {
+        self.watermark = enabled;
+        self
+    }
 }
 
 // =============================================================================
@@ -107,10 +229,27 @@ pub struct TileResponse {
     /// Whether this tile was served from cache
     pub cache_hit: bool,
 
-    /// The JPEG quality used for encoding
+    /// Whether the cached tile was stale and is being refreshed
+    pub stale: bool,
+
+    /// The JPEG quality used for encoding (estimated from the source for
+    /// passthrough tiles)
     pub quality: u8,
 }
 
+/// A rendered snapshot, ready to be encoded.
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    /// Rendered region with the scale bar burned in
+    pub image: RgbImage,
+
+    /// How the region was mapped onto the pyramid
+    pub plan: SnapshotPlan,
+
+    /// Scale bar length in microns, if the image was large enough to hold one
+    pub scale_bar_um: Option<f64>,
+}
+
 // =============================================================================
 // Tile Service
 // =============================================================================
@@ -150,10 +289,55 @@ pub struct TileService<S: SlideSource> {
     registry: Arc<SlideRegistry<S>>,
 
     /// Cache for encoded tiles
-    cache: TileCache,
+    cache: Arc<TileCache>,
 
     /// JPEG encoder
-    encoder: JpegTileEncoder,
+    encoder: TileEncoder,
+
+    /// Blocking pool that tiles are decoded and encoded on
+    encode_pool: EncodePool,
+
+    /// Optional transformer for forwarding tiles to an external service
+    transformer: Option<Arc<dyn TileTransformer>>,
+
+    /// Whether edge tiles are trimmed to the true level dimensions
+    crop_edge_tiles: bool,
+
+    /// Optional fair scheduler bounding concurrent tile generation
+    scheduler: Option<FairScheduler>,
+
+    /// Default filter for regions, thumbnails and sprite sheets
+    resample_filter: ResampleFilter,
+
+    /// Whether stale cached tiles are served while being refreshed
+    stale_while_revalidate: bool,
+
+    /// Virtual tile size for levels with giant native tiles
+    retile_size: Option<u32>,
+
+    /// Time budget for generating a tile on a cache miss
+    tile_timeout: Option<Duration>,
+
+    /// Tiles currently being generated
+    active_generations: AtomicUsize,
+
+    /// Generations shared by concurrent requests for the same tile
+    flights: TileFlights,
+
+    /// Optional cap on memory across the tile, block and metadata caches
+    memory_budget: Option<Arc<MemoryBudget>>,
+
+    /// Whether tissue masks are served
+    tissue_masks: bool,
+
+    /// Whether slide ICC profiles are embedded in JPEG tiles
+    icc_profiles: bool,
+
+    /// Stamp burned into watermarked tiles
+    watermark: Option<Arc<Watermark>>,
+
+    /// Whether tiles are watermarked unless a request opts out
+    watermark_by_default: bool,
 }
 
 impl<S: SlideSource> TileService<S> {
@@ -161,10 +345,26 @@ impl<S: SlideSource> TileService<S> {
     ///
     /// Uses default tile cache capacity (100MB).
     pub fn new(registry: SlideRegistry<S>) -> Self {
+        let encode_pool = registry.encode_pool().clone();
         Self {
             registry: Arc::new(registry),
-            cache: TileCache::new(),
-            encoder: JpegTileEncoder::new(),
+            cache: Arc::new(TileCache::new()),
+            encoder: TileEncoder::new(),
+            encode_pool,
+            transformer: None,
+            crop_edge_tiles: false,
+            scheduler: None,
+            resample_filter: ResampleFilter::default(),
+            stale_while_revalidate: false,
+            retile_size: None,
+            tile_timeout: None,
+            active_generations: AtomicUsize::new(0),
+            flights: TileFlights::new(),
+            memory_budget: None,
+            tissue_masks: false,
+            icc_profiles: false,
+            watermark: None,
+            watermark_by_default: false,
         }
     }
 
@@ -172,10 +372,26 @@ impl<S: SlideSource> TileService<S> {
     ///
     /// This allows multiple services or components to share the same registry.
     pub fn with_shared_registry(registry: Arc<SlideRegistry<S>>) -> Self {
+        let encode_pool = registry.encode_pool().clone();
         Self {
             registry,
-            cache: TileCache::new(),
-            encoder: JpegTileEncoder::new(),
+            cache: Arc::new(TileCache::new()),
+            encoder: TileEncoder::new(),
+            encode_pool,
+            transformer: None,
+            crop_edge_tiles: false,
+            scheduler: None,
+            resample_filter: ResampleFilter::default(),
+            stale_while_revalidate: false,
+            retile_size: None,
+            tile_timeout: None,
+            active_generations: AtomicUsize::new(0),
+            flights: TileFlights::new(),
+            memory_budget: None,
+            tissue_masks: false,
+            icc_profiles: false,
+            watermark: None,
+            watermark_by_default: false,
         }
     }
 
@@ -186,13 +402,327 @@ impl<S: SlideSource> TileService<S> {
     /// * `registry` - The slide registry
     /// * `cache_capacity` - Maximum tile cache size in bytes
     pub fn with_cache_capacity(registry: SlideRegistry<S>, cache_capacity: usize) -> Self {
+        let encode_pool = registry.encode_pool().clone();
         Self {
             registry: Arc::new(registry),
-            cache: TileCache::with_capacity(cache_capacity),
-            encoder: JpegTileEncoder::new(),
+            cache: Arc::new(TileCache::with_capacity(cache_capacity)),
+            encoder: TileEncoder::new(),
+            encode_pool,
+            transformer: None,
+            crop_edge_tiles: false,
+            scheduler: None,
+            resample_filter: ResampleFilter::default(),
+            stale_while_revalidate: false,
+            retile_size: None,
+            tile_timeout: None,
+            active_generations: AtomicUsize::new(0),
+            flights: TileFlights::new(),
+            memory_budget: None,
+            tissue_masks: false,
+            icc_profiles: false,
+            watermark: None,
+            watermark_by_default: false,
         }
     }
 
+    /// Create a new tile service with a preconfigured tile cache.
+    ///
+    /// Use this to supply a sharded cache (see [`TileCache::with_shards`]).
+    pub fn with_cache(registry: SlideRegistry<S>, cache: TileCache) -> Self {
+        let encode_pool = registry.encode_pool().clone();
+        Self {
+            registry: Arc::new(registry),
+            cache: Arc::new(cache),
+            encoder: TileEncoder::new(),
+            encode_pool,
+            transformer: None,
+            crop_edge_tiles: false,
+            scheduler: None,
+            resample_filter: ResampleFilter::default(),
+            stale_while_revalidate: false,
+            retile_size: None,
+            tile_timeout: None,
+            active_generations: AtomicUsize::new(0),
+            flights: TileFlights::new(),
+            memory_budget: None,
+            tissue_masks: false,
+            icc_profiles: false,
+            watermark: None,
+            watermark_by_default: false,
+        }
+    }
+
+    /// Get the tile cache.
+    pub fn cache(&self) -> &Arc<TileCache> {
+        &self.cache
+    }
+
+    /// Attach a tile transformer (e.g. an inference sidecar).
+    ///
+    /// The transformer is applied on demand by the HTTP layer; cached tiles
+    /// are always the untransformed originals.
+    pub fn with_transformer(mut self, transformer: Arc<dyn TileTransformer>) -> Self {
+        self.transformer = Some(transformer);
+        self
+    }
+
+    /// Trim padded edge tiles to the true level dimensions.
+    ///
+    /// Tiles on the right and bottom borders are stored padded to the full
+    /// tile size. When enabled, they are cropped before being served; with
+    /// source reuse, JPEG sources already at the requested quality are
+    /// cropped losslessly.
+    pub fn with_edge_cropping(mut self, enabled: bool) -> Self {
+        self.crop_edge_tiles = enabled;
+        self
+    }
+
+    /// Render tiles deterministically, for golden-image regression tests.
+    ///
+    /// Every tile is decoded and re-encoded through a single reproducible
+    /// path (see [`TileEncoder::with_deterministic`]), so a tile's bytes
+    /// only change when the source, quality or codec versions do.
+    pub fn with_deterministic(mut self, enabled: bool) -> Self {
+        self.encoder = self.encoder.with_deterministic(enabled);
+        self
+    }
+
+    /// Serve JPEG tiles unchanged when they are already quantized with the
+    /// tables a re-encode at the requested quality would use.
+    ///
+    /// See [`TileEncoder::with_source_reuse`].
+    pub fn with_source_reuse(mut self, enabled: bool) -> Self {
+        self.encoder = self.encoder.with_source_reuse(enabled);
+        self
+    }
+
+    /// Check whether deterministic rendering is enabled.
+    pub fn is_deterministic(&self) -> bool {
+        self.encoder.is_deterministic()
+    }
+
+    /// Serve tissue masks (see [`TileService::tissue_mask`]).
+    ///
+    /// Each slide's mask costs one overview render the first time it is
+    /// requested.
+    pub fn with_tissue_masks(mut self, enabled: bool) -> Self {
+        self.tissue_masks = enabled;
+        self
+    }
+
+    /// Check whether tissue masks are served.
+    pub fn tissue_masks_enabled(&self) -> bool {
+        self.tissue_masks
+    }
+
+    /// Embed each slide's ICC color profile in its JPEG tiles (default off).
+    ///
+    /// Without the profile, viewers assume sRGB and slides scanned in a
+    /// wider color space render with shifted colors. Profiles add their
+    /// size to every tile, so this is only worth enabling for viewers that
+    /// apply them.
+    pub fn with_icc_profiles(mut self, enabled: bool) -> Self {
+        self.icc_profiles = enabled;
+        self
+    }
+
+    /// Burn a watermark into the tiles of requests that ask for it (see
+    /// [`TileRequest::with_watermark`]).
+    pub fn with_watermark(mut self, watermark: Watermark) -> Self {
+        self.watermark = Some(Arc::new(watermark));
+        self
+    }
+
+    /// Watermark tiles unless a request opts out (default off).
+    ///
+    /// Requests only carry their choice through signed access claims, so
+    /// the default decides for every other request.
+    pub fn with_watermark_by_default(mut self, enabled: bool) -> Self {
+        self.watermark_by_default = enabled;
+        self
+    }
+
+    /// The configured watermark, if any.
+    pub fn watermark(&self) -> Option<&Watermark> {
+        self.watermark.as_deref()
+    }
+
+    /// Check whether tiles are watermarked unless a request opts out.
+    pub fn watermark_by_default(&self) -> bool {
+        self.watermark_by_default
+    }
+
+    /// Decode and encode tiles on the blocking thread pool, at most
+    /// `workers` at once (0 = one per CPU).
+    ///
+    /// This replaces the pool shared with the registry; to bound both with
+    /// one limit, use [`SlideRegistry::with_encode_pool`] instead.
+    pub fn with_encode_workers(mut self, workers: usize) -> Self {
+        self.encode_pool = EncodePool::new(workers);
+        self
+    }
+
+    /// Get the pool tiles are encoded on.
+    pub fn encode_pool(&self) -> &EncodePool {
+        &self.encode_pool
+    }
+
+    /// Bound concurrent tile generation with a fair scheduler.
+    ///
+    /// Cache misses wait for a generation slot, queued per tenant (or per
+    /// slide without one) so no single client can monopolize encoding.
+    pub fn with_fair_scheduler(mut self, scheduler: FairScheduler) -> Self {
+        self.scheduler = Some(scheduler);
+        self
+    }
+
+    /// Get the fair scheduler, if configured.
+    pub fn scheduler(&self) -> Option<&FairScheduler> {
+        self.scheduler.as_ref()
+    }
+
+    /// Set the default filter used to scale regions, thumbnails and sprite
+    /// sheets.
+    ///
+    /// Requests can override it (see [`Self::render_region_with_filter`]).
+    pub fn with_resample_filter(mut self, filter: ResampleFilter) -> Self {
+        self.resample_filter = filter;
+        self
+    }
+
+    /// Get the default resampling filter.
+    pub fn resample_filter(&self) -> ResampleFilter {
+        self.resample_filter
+    }
+
+    /// Serve stale cached tiles while refreshing them in the background.
+    ///
+    /// Tiles become stale when their slide is invalidated or they outlive
+    /// the cache TTL. Without this, a stale tile is regenerated before it is
+    /// returned, so an invalidation storm turns every hit into a miss. Only
+    /// [`Self::get_tile_revalidating`] serves stale tiles.
+    pub fn with_stale_while_revalidate(mut self, enabled: bool) -> Self {
+        self.stale_while_revalidate = enabled;
+        self
+    }
+
+    /// Check whether stale-while-revalidate is enabled.
+    pub fn stale_while_revalidate(&self) -> bool {
+        self.stale_while_revalidate
+    }
+
+    /// Expose levels with giant native tiles as a virtual grid of
+    /// `tile_size` tiles (0 disables retiling).
+    ///
+    /// A level is retiled when its native tile size is an exact multiple of
+    /// `tile_size`, e.g. 1024x1024 or 2048x2048 native tiles with a 256
+    /// virtual size. Each native tile is decoded once and all of its
+    /// sub-tiles are cached together. Tile coordinates, slide metadata and
+    /// Deep Zoom and IIIF tiling all use the virtual grid.
+    pub fn with_retiling(mut self, tile_size: u32) -> Self {
+        self.retile_size = (tile_size > 0).then_some(tile_size);
+        self
+    }
+
+    /// Get the virtual tile size, if retiling is enabled.
+    pub fn retile_size(&self) -> Option<u32> {
+        self.retile_size
+    }
+
+    /// Fail tile requests that are not generated within `timeout` (zero
+    /// disables the budget).
+    ///
+    /// The deadline covers waiting for a generation slot, slide reads and
+    /// decoding. Such requests fail with [`TileError::Timeout`]; pending
+    /// reads are cancelled when it passes, unless another request is waiting
+    /// for the same tile, which then takes the generation over.
+    pub fn with_tile_timeout(mut self, timeout: Duration) -> Self {
+        self.tile_timeout = (!timeout.is_zero()).then_some(timeout);
+        self
+    }
+
+    /// Get the tile generation budget, if configured.
+    pub fn tile_timeout(&self) -> Option<Duration> {
+        self.tile_timeout
+    }
+
+    /// Whether padded edge tiles are cropped to the level dimensions.
+    pub fn edge_cropping(&self) -> bool {
+        self.crop_edge_tiles
+    }
+
+    /// Number of tiles currently being generated.
+    ///
+    /// Counts renders that hold a generation slot, not requests served
+    /// from the cache or waiting in the scheduler queue.
+    pub fn active_generations(&self) -> usize {
+        self.active_generations.load(Ordering::Relaxed)
+    }
+
+    /// Cap the memory held by the tile cache, slide block caches and
+    /// parsed slide metadata together (0 disables the cap).
+    ///
+    /// The cap is enforced by [`Self::enforce_memory_budget`], which the
+    /// caller runs periodically.
+    pub fn with_memory_budget(mut self, max_bytes: usize) -> Self {
+        self.memory_budget = (max_bytes > 0).then(|| Arc::new(MemoryBudget::new(max_bytes)));
+        self
+    }
+
+    /// Get the memory budget, if any.
+    pub fn memory_budget(&self) -> Option<&Arc<MemoryBudget>> {
+        self.memory_budget.as_ref()
+    }
+
+    /// Measure the memory held by the tile cache and open slides.
+    pub async fn memory_usage(&self) -> MemoryUsage {
+        MemoryUsage::measure(&self.cache, &self.registry).await
+    }
+
+    /// Evict from every cache to bring memory within the budget.
+    ///
+    /// See [`MemoryBudget::enforce`]. Returns the bytes freed (0 without a
+    /// budget or when within it).
+    pub async fn enforce_memory_budget(&self) -> usize {
+        match &self.memory_budget {
+            Some(budget) => budget.enforce(&self.cache, &self.registry).await,
+            None => 0,
+        }
+    }
+
+    /// Number of tile requests that waited for a generation another
+    /// request had already started, instead of generating the tile again.
+    pub fn coalesced_generations(&self) -> u64 {
+        self.flights.coalesced()
+    }
+
+    /// A native level as exposed to clients (see [`Self::with_retiling`]).
+    pub fn virtual_level(&self, native: &LevelInfo) -> LevelInfo {
+        match self.retile_size {
+            Some(size) => virtual_level(native, size),
+            None => *native,
+        }
+    }
+
+    /// Get a level of a slide as exposed to clients.
+    pub fn level_info(&self, slide: &CachedSlide<S::Reader>, level: usize) -> Option<LevelInfo> {
+        slide
+            .level_info(level)
+            .map(|native| self.virtual_level(&native))
+    }
+
+    /// Get every level of a slide as exposed to clients, level 0 first.
+    pub fn levels(&self, slide: &CachedSlide<S::Reader>) -> Vec<LevelInfo> {
+        (0..slide.level_count())
+            .filter_map(|level| self.level_info(slide, level))
+            .collect()
+    }
+
+    /// Get the configured tile transformer, if any.
+    pub fn transformer(&self) -> Option<&Arc<dyn TileTransformer>> {
+        self.transformer.as_ref()
+    }
+
     /// Get a tile, using cache when available.
     ///
     /// This is the main entry point for tile requests. It:
@@ -209,45 +739,263 @@ impl<S: SlideSource> TileService<S> {
     /// - The tile coordinates are out of bounds
     /// - The tile data cannot be decoded or encoded
     pub async fn get_tile(&self, request: TileRequest) -> Result<TileResponse, TileError> {
+        self.lookup_or_generate(request, false).await
+    }
+
+    /// Look up a tile, generating it on a miss or a disallowed stale hit.
+    async fn lookup_or_generate(
+        &self,
+        request: TileRequest,
+        allow_stale: bool,
+    ) -> Result<TileResponse, TileError> {
         // Validate quality
         if !is_valid_quality(request.quality) {
             return Err(TileError::InvalidQuality {
                 quality: request.quality,
             });
         }
-        let quality = request.quality;
+        request.format.ensure_supported()?;
 
-        // Create cache key
-        let cache_key = TileCacheKey::new(
-            request.slide_id.as_str(),
-            request.level as u32,
-            request.tile_x,
-            request.tile_y,
-            quality,
-        );
-
-        // Check cache first
-        if let Some(cached_data) = self.cache.get(&cache_key).await {
-            return Ok(TileResponse {
-                data: cached_data,
-                cache_hit: true,
-                quality,
+        if self.registry.quarantine().is_quarantined(&request.slide_id) {
+            return Err(TileError::SlideQuarantined {
+                slide_id: request.slide_id.clone(),
             });
         }
 
-        // Cache miss - need to generate tile
-        let tile_data = self.generate_tile(&request, quality).await?;
+        // Wait within this request's budget; dropping the wait on timeout
+        // withdraws it from the slot queue and, if no other request shares
+        // the generation, cancels pending reads
+        let deadline = self.tile_timeout.map(Deadline::after);
+        let mut replaced = false;
+        loop {
+            let lookup = self.lookup_or_generate_once(&request, allow_stale);
+            let result = match deadline {
+                Some(deadline) => deadline.run(lookup).await?,
+                None => lookup.await,
+            };
+            match result {
+                // The object was replaced since the slide was opened; the
+                // registry reopens it, so try once more against the new content
+                Err(err) if !replaced && is_object_changed(&err) => replaced = true,
+                result => return result,
+            }
+        }
+    }
 
-        // Cache the result
-        self.cache.put(cache_key, tile_data.clone()).await;
+    /// Look up a tile in the cache under the slide's current ETag,
+    /// generating it on a miss.
+    #[tracing::instrument(
+        name = "tile_service",
+        skip_all,
+        fields(
+            slide_id = %request.slide_id,
+            level = request.level,
+            x = request.tile_x,
+            y = request.tile_y,
+            cache_hit = tracing::field::Empty,
+        )
+    )]
+    async fn lookup_or_generate_once(
+        &self,
+        request: &TileRequest,
+        allow_stale: bool,
+    ) -> Result<TileResponse, TileError> {
+        let cache_key = self.cache_key(request).await?;
+        let quality = self.effective_quality(request);
+        let passthrough = cache_key.passthrough;
+
+        // Passthrough tiles are at whatever quality the scanner wrote
+        let quality_of = |data: &Bytes| {
+            if passthrough {
+                estimate_jpeg_quality(data).unwrap_or(quality)
+            } else {
+                quality
+            }
+        };
+
+        // Check cache first
+        if let Some(cached) = self.cache.lookup(&cache_key).await {
+            if !cached.stale || allow_stale {
+                tracing::Span::current().record("cache_hit", true);
+                return Ok(TileResponse {
+                    quality: quality_of(&cached.data),
+                    data: cached.data,
+                    cache_hit: true,
+                    stale: cached.stale,
+                });
+            }
+        }
+
+        tracing::Span::current().record("cache_hit", false);
+        let tile_data = self.generate_and_cache(request, cache_key).await?;
 
         Ok(TileResponse {
+            quality: quality_of(&tile_data),
             data: tile_data,
             cache_hit: false,
-            quality,
+            stale: false,
         })
     }
 
+    /// Build the cache key for a request.
+    ///
+    /// Keys carry the ETag of the slide file, so tiles rendered from a
+    /// replaced object are never served for the new one. The slide is
+    /// opened if it isn't cached yet.
+    async fn cache_key(&self, request: &TileRequest) -> Result<TileCacheKey, TileError> {
+        let source_etag = match self.registry.cached_slide(&request.slide_id) {
+            Some(slide) => slide.shared_etag(),
+            None => self.open_slide(&request.slide_id).await?.shared_etag(),
+        };
+        Ok(self.cache_key_for(request, source_etag))
+    }
+
+    /// Pixel changes made to a request's tile between decoding and encoding.
+    ///
+    /// The overlay depends on where the decoded tile sits in its level, so
+    /// it is placed by the caller.
+    fn rendering_for(&self, request: &TileRequest) -> TileRendering {
+        TileRendering {
+            adjustment: request.adjustment,
+            channel: request.channel,
+            overlay: None,
+            watermark: self.watermark.clone().filter(|_| request.watermark),
+        }
+    }
+
+    /// Whether passthrough applies to a request: JPEG output of unchanged
+    /// pixels.
+    fn is_passthrough(&self, request: &TileRequest) -> bool {
+        request.passthrough
+            && request.format == OutputFormat::Jpeg
+            && request.overlay.is_none()
+            && self.rendering_for(request).is_identity()
+    }
+
+    /// Quality a request's tile is encoded and cached at.
+    ///
+    /// Passthrough tiles are at whatever quality the scanner wrote, so the
+    /// requested quality is ignored: every passthrough request for a tile
+    /// shares one cache entry, and sources that can't be passed through are
+    /// encoded at [`DEFAULT_JPEG_QUALITY`], like
+    /// [`TileEncoder::encode_at_source_quality`].
+    fn effective_quality(&self, request: &TileRequest) -> u8 {
+        if self.is_passthrough(request) {
+            DEFAULT_JPEG_QUALITY
+        } else {
+            request.quality
+        }
+    }
+
+    /// Build the cache key for a request against a slide file version.
+    fn cache_key_for(&self, request: &TileRequest, source_etag: Option<Arc<str>>) -> TileCacheKey {
+        TileCacheKey::new(
+            request.slide_id.as_str(),
+            request.level as u32,
+            request.tile_x,
+            request.tile_y,
+            self.effective_quality(request),
+        )
+        .with_format(request.format)
+        .with_passthrough(self.is_passthrough(request))
+        .with_adjustment(request.adjustment)
+        .with_channel(request.channel)
+        .with_overlay(request.overlay.as_ref().map(|overlay| overlay.revision()))
+        .with_watermark(self.rendering_for(request).watermark.is_some())
+        .with_source_etag(source_etag)
+    }
+
+    /// Generate a tile and store it in the cache.
+    ///
+    /// Concurrent calls for the same tile share one generation, which has
+    /// no deadline of its own: each caller bounds only its own wait, so a
+    /// caller timing out doesn't fail the others. Every caller also waits
+    /// for a generation slot in its own queue, so requests joining a
+    /// generation are scheduled against their own tenant rather than the
+    /// one that started it.
+    async fn generate_and_cache(
+        &self,
+        request: &TileRequest,
+        cache_key: TileCacheKey,
+    ) -> Result<Bytes, TileError> {
+        let passenger = self.flights.join(&cache_key);
+        let _permit = match &self.scheduler {
+            Some(scheduler) => Some(
+                scheduler
+                    .acquire(request.tenant.as_deref(), &request.slide_id)
+                    .await?,
+            ),
+            None => None,
+        };
+        passenger
+            .run(|| self.generate_and_cache_once(request, cache_key))
+            .await
+    }
+
+    /// Generate a tile and cache it, along with any sibling tiles rendered
+    /// with it.
+    async fn generate_and_cache_once(
+        &self,
+        request: &TileRequest,
+        cache_key: TileCacheKey,
+    ) -> Result<Bytes, TileError> {
+        let _generating = GenerationGuard::new(&self.active_generations);
+
+        // Generate the tile (and, for retiled levels, its siblings cut
+        // from the same native tile). Decode errors and panics are
+        // counted toward quarantining the slide; the panic is then resumed
+        // so it is still reported as a crash.
+        let tiles = match CatchUnwind::new(self.render_tiles(
+            request,
+            self.effective_quality(request),
+            true,
+        ))
+        .await
+        {
+            Ok(Ok(tiles)) => tiles,
+            Ok(Err(err)) => {
+                if let TileError::DecodeError { ref message } = err {
+                    self.registry.record_failure(&request.slide_id, message);
+                }
+                self.events().publish(|| ServerEvent::TileFailed {
+                    slide_id: request.slide_id.clone(),
+                    level: request.level,
+                    x: request.tile_x,
+                    y: request.tile_y,
+                    error: err.to_string(),
+                });
+                return Err(err);
+            }
+            Err(payload) => {
+                self.registry
+                    .record_failure(&request.slide_id, "panic while generating tile");
+                std::panic::resume_unwind(payload);
+            }
+        };
+
+        // Cache the result; the requested tile comes first
+        let mut tiles = tiles.into_iter();
+        let (_, tile_data) = tiles.next().ok_or_else(|| TileError::EncodeError {
+            message: "no tile rendered".to_string(),
+        })?;
+        let mut evicted = 0;
+        for ((tile_x, tile_y), data) in tiles {
+            let key = TileCacheKey {
+                tile_x,
+                tile_y,
+                ..cache_key.clone()
+            };
+            evicted += self.cache.put(key, data).await;
+        }
+        evicted += self.cache.put(cache_key, tile_data.clone()).await;
+        if evicted > 0 {
+            self.events()
+                .publish(|| ServerEvent::TileCacheEvicted { tiles: evicted });
+        }
+
+        Ok(tile_data)
+    }
+
     /// Generate a tile without caching.
     ///
     /// This is useful for one-off requests or when you want to bypass the cache.
@@ -256,66 +1004,179 @@ impl<S: SlideSource> TileService<S> {
         request: &TileRequest,
         quality: u8,
     ) -> Result<Bytes, TileError> {
+        let mut tiles = self.render_tiles(request, quality, false).await?;
+        Ok(tiles.swap_remove(0).1)
+    }
+
+    /// Render a tile, optionally along with the other tiles cut from the same
+    /// native tile of a retiled level.
+    ///
+    /// Returns `((tile_x, tile_y), data)` pairs with the requested tile first.
+    async fn render_tiles(
+        &self,
+        request: &TileRequest,
+        quality: u8,
+        siblings: bool,
+    ) -> Result<Vec<((u32, u32), Bytes)>, TileError> {
         // Get the slide from registry
-        let slide = self
-            .registry
-            .get_slide(&request.slide_id)
-            .await
-            .map_err(|e| match e {
-                crate::error::FormatError::Io(io_err) => {
-                    if matches!(io_err, crate::error::IoError::NotFound(_)) {
-                        TileError::SlideNotFound {
-                            slide_id: request.slide_id.clone(),
-                        }
-                    } else {
-                        TileError::Io(io_err)
-                    }
-                }
-                crate::error::FormatError::Tiff(tiff_err) => TileError::Slide(tiff_err),
-                crate::error::FormatError::UnsupportedFormat { reason } => {
-                    TileError::Slide(crate::error::TiffError::InvalidTagValue {
-                        tag: "Format",
-                        message: reason,
-                    })
-                }
-            })?;
+        let slide = self.open_slide(&request.slide_id).await?;
+        let icc_profile = slide.icc_profile().filter(|_| {
+            // Slide profiles describe RGB data, not single-channel tiles
+            self.icc_profiles && request.format == OutputFormat::Jpeg && request.channel.is_none()
+        });
+        let with_profile = |tile: Bytes| match &icc_profile {
+            Some(profile) => embed_icc_profile(tile, profile),
+            None => tile,
+        };
 
         // Validate level
         let level_count = slide.level_count();
-        if request.level >= level_count {
-            return Err(TileError::InvalidLevel {
-                level: request.level,
-                max_levels: level_count,
-            });
-        }
-
-        // Validate tile coordinates
-        let (max_x, max_y) = slide
-            .tile_count(request.level)
+        let native = slide
+            .level_info(request.level)
             .ok_or(TileError::InvalidLevel {
                 level: request.level,
                 max_levels: level_count,
             })?;
+        let grid = self.virtual_level(&native);
+
+        // Annotations are drawn onto the decoded native tile, scaled from
+        // level 0 to this level
+        let (base_width, base_height) = slide.dimensions().unwrap_or((native.width, native.height));
+        let scale = (
+            native.width as f64 / base_width.max(1) as f64,
+            native.height as f64 / base_height.max(1) as f64,
+        );
+        let rendering_at = |native_x: u32, native_y: u32| {
+            let mut rendering = self.rendering_for(request);
+            rendering.overlay = request.overlay.clone().map(|annotations| {
+                let origin = (native_x * native.tile_width, native_y * native.tile_height);
+                TileOverlay::new(annotations, origin, scale)
+            });
+            rendering
+        };
 
-        if request.tile_x >= max_x || request.tile_y >= max_y {
+        // Validate tile coordinates against the grid exposed to clients
+        if request.tile_x >= grid.tiles_x || request.tile_y >= grid.tiles_y {
             return Err(TileError::TileOutOfBounds {
                 level: request.level,
                 x: request.tile_x,
                 y: request.tile_y,
-                max_x,
-                max_y,
+                max_x: grid.tiles_x,
+                max_y: grid.tiles_y,
             });
         }
 
-        // Read the raw tile data from the slide
-        let raw_tile = slide
-            .read_tile(request.level, request.tile_x, request.tile_y)
+        // Size of a tile trimmed to the level bounds, when edge cropping is enabled
+        let cropped_size = |tile_x: u32, tile_y: u32| {
+            let width = grid
+                .width
+                .saturating_sub(tile_x * grid.tile_width)
+                .min(grid.tile_width);
+            let height = grid
+                .height
+                .saturating_sub(tile_y * grid.tile_height)
+                .min(grid.tile_height);
+            if self.crop_edge_tiles {
+                (width, height)
+            } else {
+                (grid.tile_width, grid.tile_height)
+            }
+        };
+
+        // Retiled level: decode the native tile once and cut it up
+        if let Some((size, factor)) = self
+            .retile_size
+            .and_then(|size| Some((size, retile_factor(&native, size)?)))
+        {
+            let (native_x, native_y) = native_tile(request.tile_x, request.tile_y, factor);
+            let raw_tile = timing::measure(
+                Phase::TileFetch,
+                slide.read_tile(request.level, native_x, native_y),
+            )
             .await?;
 
-        // Decode and re-encode at the requested quality
-        let encoded_tile = self.encoder.encode(&raw_tile, quality)?;
+            let mut tiles = if siblings {
+                sub_tiles(&native, size, native_x, native_y)
+            } else {
+                Vec::new()
+            };
+            tiles.retain(|&(x, y, _, _)| (x, y) != (request.tile_x, request.tile_y));
+            tiles.insert(
+                0,
+                (
+                    request.tile_x,
+                    request.tile_y,
+                    (request.tile_x % factor.0) * size,
+                    (request.tile_y % factor.1) * size,
+                ),
+            );
 
-        Ok(encoded_tile)
+            let crops: Vec<_> = tiles
+                .iter()
+                .map(|&(tile_x, tile_y, offset_x, offset_y)| {
+                    let (width, height) = cropped_size(tile_x, tile_y);
+                    (offset_x, offset_y, width, height)
+                })
+                .collect();
+            let encoder = self.encoder.clone();
+            let format = request.format;
+            let rendering = rendering_at(native_x, native_y);
+            let encoded = self
+                .encode_pool
+                .run(move || {
+                    if !rendering.is_identity() {
+                        encoder.encode_rendered(&raw_tile, quality, format, &rendering, &crops)
+                    } else {
+                        encoder.encode_crops(&raw_tile, quality, format, &crops)
+                    }
+                })
+                .await?;
+
+            return Ok(tiles
+                .iter()
+                .map(|&(tile_x, tile_y, _, _)| (tile_x, tile_y))
+                .zip(encoded.into_iter().map(with_profile))
+                .collect());
+        }
+
+        // Read the raw tile data from the slide
+        let raw_tile = timing::measure(
+            Phase::TileFetch,
+            slide.read_tile(request.level, request.tile_x, request.tile_y),
+        )
+        .await?;
+        let coords = (request.tile_x, request.tile_y);
+
+        // Trim edge tiles to the level bounds when enabled, then decode and
+        // re-encode at the requested quality and format
+        let (width, height) = cropped_size(request.tile_x, request.tile_y);
+        let cropped = width < native.tile_width || height < native.tile_height;
+
+        // Serve eligible JPEG sources byte-for-byte when passthrough is
+        // requested, skipping the blocking pool entirely
+        if self.is_passthrough(request) && !cropped && self.encoder.can_pass_through(&raw_tile) {
+            return Ok(vec![(coords, with_profile(raw_tile))]);
+        }
+        let encoder = self.encoder.clone();
+        let format = request.format;
+        let rendering = rendering_at(request.tile_x, request.tile_y);
+        let encoded_tile = self
+            .encode_pool
+            .run(move || {
+                if !rendering.is_identity() {
+                    let crop = [(0, 0, width, height)];
+                    let mut encoded =
+                        encoder.encode_rendered(&raw_tile, quality, format, &rendering, &crop)?;
+                    Ok(encoded.remove(0))
+                } else if cropped {
+                    encoder.encode_cropped_as(&raw_tile, quality, format, width, height)
+                } else {
+                    encoder.encode_as(&raw_tile, quality, format)
+                }
+            })
+            .await?;
+
+        Ok(vec![(coords, with_profile(encoded_tile))])
     }
 
     /// Get tile cache statistics.
@@ -333,20 +1194,426 @@ impl<S: SlideSource> TileService<S> {
         self.cache.clear().await;
     }
 
-    /// Invalidate cached tiles for a specific slide.
-    ///
-    /// This removes all cached tiles for the given slide from the tile cache.
-    /// Note: This is O(n) where n is the number of cached tiles.
-    pub async fn invalidate_slide(&self, _slide_id: &str) {
-        // TODO: Implement efficient per-slide invalidation
-        // For now, this would require iterating the cache which isn't supported
-        // by the LRU cache. A production implementation might use a different
-        // data structure or maintain a secondary index.
-    }
+    /// Invalidate cached tiles for a specific slide.
+    ///
+    /// The slide is reopened on its next request and its cached tiles are
+    /// marked stale: they are regenerated before being served, or served
+    /// while being refreshed when stale-while-revalidate is enabled.
+    pub async fn invalidate_slide(&self, slide_id: &str) {
+        self.registry.invalidate(slide_id).await;
+        self.cache.invalidate_slide(slide_id);
+    }
+
+    /// Get a reference to the underlying registry.
+    pub fn registry(&self) -> &Arc<SlideRegistry<S>> {
+        &self.registry
+    }
+
+    /// Get the bus server events are published on (shared with the
+    /// registry).
+    pub fn events(&self) -> &EventBus {
+        self.registry.events()
+    }
+
+    /// Open a slide through the registry, mapping open failures to tile errors.
+    pub async fn open_slide(
+        &self,
+        slide_id: &str,
+    ) -> Result<Arc<CachedSlide<S::Reader>>, TileError> {
+        self.registry
+            .get_slide(slide_id)
+            .await
+            .map_err(|e| TileError::from_open(slide_id, e))
+    }
+
+    /// Compute the tissue map for a level.
+    ///
+    /// Tissue is measured on the slide's [`TissueMask`]: the mask kept with
+    /// the open slide if there is one, otherwise one estimated from a
+    /// low-resolution overview, which costs roughly one thumbnail render
+    /// regardless of level.
+    pub async fn tissue_map(&self, slide_id: &str, level: usize) -> Result<TissueMap, TileError> {
+        let slide = self.open_slide(slide_id).await?;
+        let info = self
+            .level_info(&slide, level)
+            .ok_or(TileError::InvalidLevel {
+                level,
+                max_levels: slide.level_count(),
+            })?;
+
+        let mask = match slide.tissue_mask() {
+            Some(mask) => mask,
+            None => Arc::new(TissueMask::from_image(
+                &self.tissue_overview(slide_id).await?,
+            )),
+        };
+        Ok(TissueMap::from_mask(&mask, &info))
+    }
+
+    /// Get a slide's tissue mask, computing it on first use.
+    ///
+    /// The mask is estimated from the same overview as tissue maps and kept
+    /// with the open slide, which then skips background tiles when reading
+    /// ahead (see [`CachedSlide::is_background_tile`]).
+    pub async fn tissue_mask(&self, slide_id: &str) -> Result<Arc<TissueMask>, TileError> {
+        let slide = self.open_slide(slide_id).await?;
+        if let Some(mask) = slide.tissue_mask() {
+            return Ok(mask);
+        }
+
+        let overview = self.tissue_overview(slide_id).await?;
+        let mask = Arc::new(TissueMask::from_image(&overview));
+        slide.set_tissue_mask(mask.clone());
+        Ok(mask)
+    }
+
+    /// Render the low-resolution overview tissue is estimated from.
+    ///
+    /// The whole slide is rendered straight into an RGB image at most
+    /// [`TISSUE_OVERVIEW_SIZE`] on a side, from the lowest-resolution level
+    /// that doesn't need upsampling.
+    async fn tissue_overview(&self, slide_id: &str) -> Result<RgbImage, TileError> {
+        let slide = self.open_slide(slide_id).await?;
+        let (width, height) = slide
+            .dimensions()
+            .filter(|&(width, height)| width > 0 && height > 0)
+            .ok_or(TileError::InvalidLevel {
+                level: 0,
+                max_levels: 0,
+            })?;
+
+        let scale = (TISSUE_OVERVIEW_SIZE as f64 / width.max(height) as f64).min(1.0);
+        let out_width = ((width as f64 * scale).round() as u32).max(1);
+        let out_height = ((height as f64 * scale).round() as u32).max(1);
+        let downsamples: Vec<f64> = self
+            .levels(&slide)
+            .iter()
+            .map(|info| info.downsample)
+            .collect();
+        let plan = plan_region(
+            0.0,
+            0.0,
+            width as f64,
+            height as f64,
+            out_width,
+            out_height,
+            &downsamples,
+        );
+
+        self.render_region(slide_id, &plan, DEFAULT_JPEG_QUALITY)
+            .await
+    }
+
+    /// Sample tissue-containing tile coordinates from a level.
+    ///
+    /// The result is deterministic for a given slide, level and options,
+    /// which makes it suitable for reproducible dataset construction.
+    pub async fn sample_tiles(
+        &self,
+        slide_id: &str,
+        level: usize,
+        options: &SampleOptions,
+    ) -> Result<Vec<SampledTile>, TileError> {
+        let map = self.tissue_map(slide_id, level).await?;
+        Ok(sample_tiles(&map, options))
+    }
+
+    /// Render thumbnails for several slides into a single sprite sheet.
+    ///
+    /// Slides whose thumbnail cannot be produced (missing, unsupported or
+    /// corrupt) are listed in [`SpriteSheet::failed`] rather than failing the
+    /// sheet; only an overloaded service aborts the whole request.
+    pub async fn generate_sprite_sheet(
+        &self,
+        slide_ids: &[String],
+        cell_size: u32,
+        quality: u8,
+    ) -> Result<SpriteSheet, TileError> {
+        if !is_valid_quality(quality) {
+            return Err(TileError::InvalidQuality { quality });
+        }
+
+        let mut thumbnails = Vec::with_capacity(slide_ids.len());
+        let mut failed = Vec::new();
+        for slide_id in slide_ids {
+            match self.generate_thumbnail(slide_id, cell_size, quality).await {
+                Ok(response) => thumbnails.push((slide_id.clone(), response.data)),
+                Err(e @ TileError::Overloaded { .. }) => return Err(e),
+                Err(_) => failed.push(slide_id.clone()),
+            }
+        }
+
+        compose_sprite_sheet(
+            &thumbnails,
+            failed,
+            cell_size,
+            quality,
+            self.resample_filter,
+        )
+    }
+
+    /// Render a region of a slide at print resolution.
+    ///
+    /// The stored tiles of the planned level are read concurrently, then
+    /// decoded, resampled straight into the output canvas and stamped with a
+    /// scale bar on the encode pool. They bypass the tile cache, and are
+    /// decoded from the source rather than from a re-encoded tile. `quality`
+    /// is validated here and applies when the snapshot is encoded.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TileError::InvalidRegion`] if the slide has no
+    /// microns-per-pixel calibration, or the region is empty, outside the
+    /// slide or too large.
+    pub async fn render_snapshot(
+        &self,
+        slide_id: &str,
+        region: &SnapshotRegion,
+        quality: u8,
+    ) -> Result<Snapshot, TileError> {
+        self.render_snapshot_with_filter(slide_id, region, quality, self.resample_filter)
+            .await
+    }
+
+    /// Render a region of a slide at print resolution with a specific
+    /// resampling filter.
+    pub async fn render_snapshot_with_filter(
+        &self,
+        slide_id: &str,
+        region: &SnapshotRegion,
+        quality: u8,
+        filter: ResampleFilter,
+    ) -> Result<Snapshot, TileError> {
+        if !is_valid_quality(quality) {
+            return Err(TileError::InvalidQuality { quality });
+        }
+
+        let slide = self.open_slide(slide_id).await?;
+        let mpp = slide.mpp().ok_or_else(|| TileError::InvalidRegion {
+            message: "slide has no microns-per-pixel calibration".to_string(),
+        })?;
+        let dimensions = slide.dimensions().ok_or(TileError::InvalidLevel {
+            level: 0,
+            max_levels: 0,
+        })?;
+        let downsamples: Vec<f64> = (0..slide.level_count())
+            .map(|level| slide.level_downsample(level).unwrap_or(f64::INFINITY))
+            .collect();
+
+        let plan = plan_snapshot(region, mpp, dimensions, &downsamples)?;
+        let region = plan.region();
+        let info = slide
+            .level_info(region.level)
+            .ok_or(TileError::InvalidLevel {
+                level: region.level,
+                max_levels: slide.level_count(),
+            })?;
+        let tiles = timing::measure(
+            Phase::TileFetch,
+            self.read_region_tiles(&slide, &region, &info),
+        )
+        .await?;
+
+        let output_mpp = plan.output_mpp;
+        let (image, scale_bar_um) = self
+            .encode_pool
+            .run(move || {
+                let mut canvas = stitch_tiles(&region, &info, tiles, filter)?;
+                let scale_bar_um = draw_scale_bar(&mut canvas, output_mpp);
+                Ok((canvas, scale_bar_um))
+            })
+            .await?;
+
+        Ok(Snapshot {
+            image,
+            plan,
+            scale_bar_um,
+        })
+    }
+
+    /// Read the stored tiles of a level under a region, with up to
+    /// [`DEFAULT_TILE_STREAM_CONCURRENCY`] reads in flight.
+    ///
+    /// Tiles are read from the slide rather than the tile pipeline, so they
+    /// are neither re-encoded nor cached.
+    async fn read_region_tiles(
+        &self,
+        slide: &Arc<CachedSlide<S::Reader>>,
+        plan: &RegionPlan,
+        info: &LevelInfo,
+    ) -> Result<Vec<(u32, u32, Bytes)>, TileError> {
+        let level = plan.level;
+        let (tiles_x, tiles_y) = plan.tile_range(info);
+        let mut pending = tiles_y.flat_map(|y| tiles_x.clone().map(move |x| (x, y)));
+        let mut reads = JoinSet::new();
+        let mut tiles = Vec::new();
+
+        loop {
+            while reads.len() < DEFAULT_TILE_STREAM_CONCURRENCY {
+                let Some((x, y)) = pending.next() else {
+                    break;
+                };
+                let slide = slide.clone();
+                reads.spawn(CaughtPanic::catch_async(async move {
+                    slide.read_tile(level, x, y).await.map(|data| (x, y, data))
+                }));
+            }
+
+            let Some(joined) = reads.join_next().await else {
+                break;
+            };
+            match joined {
+                Ok(Ok(tile)) => tiles.push(tile?),
+                Ok(Err(panic)) => panic.resume(),
+                Err(err) if err.is_panic() => std::panic::resume_unwind(err.into_panic()),
+                Err(err) => {
+                    return Err(TileError::Overloaded {
+                        message: format!("tile read cancelled: {}", err),
+                    })
+                }
+            }
+        }
+
+        Ok(tiles)
+    }
+
+    /// Render a planned region of a slide.
+    ///
+    /// Tiles of the planned level are fetched through the tile cache at
+    /// `quality` and resampled into an RGB canvas of the output size. Parts
+    /// of the canvas not covered by the slide are white.
+    pub async fn render_region(
+        &self,
+        slide_id: &str,
+        plan: &RegionPlan,
+        quality: u8,
+    ) -> Result<RgbImage, TileError> {
+        self.render_region_with_filter(slide_id, plan, quality, self.resample_filter)
+            .await
+    }
+
+    /// Render a planned region of a slide with a specific resampling filter.
+    pub async fn render_region_with_filter(
+        &self,
+        slide_id: &str,
+        plan: &RegionPlan,
+        quality: u8,
+        filter: ResampleFilter,
+    ) -> Result<RgbImage, TileError> {
+        let slide = self.open_slide(slide_id).await?;
+        let info = self
+            .level_info(&slide, plan.level)
+            .ok_or(TileError::InvalidLevel {
+                level: plan.level,
+                max_levels: slide.level_count(),
+            })?;
+
+        let mut canvas =
+            RgbImage::from_pixel(plan.out_width, plan.out_height, Rgb([255, 255, 255]));
+        let (tiles_x, tiles_y) = plan.tile_range(&info);
+        for tile_y in tiles_y {
+            for tile_x in tiles_x.clone() {
+                let request =
+                    TileRequest::with_quality(slide_id, plan.level, tile_x, tile_y, quality);
+                let tile_response = self.get_tile(request).await?;
+
+                let cursor = Cursor::new(&tile_response.data[..]);
+                let reader = ImageReader::with_format(cursor, image::ImageFormat::Jpeg);
+                let tile_img = reader
+                    .decode()
+                    .map_err(|e| TileError::DecodeError {
+                        message: format!("Failed to decode tile ({}, {}): {}", tile_x, tile_y, e),
+                    })?
+                    .to_rgb8();
+
+                let (x, y, width, height) = plan.tile_destination(
+                    &info,
+                    tile_x,
+                    tile_y,
+                    tile_img.width(),
+                    tile_img.height(),
+                );
+                if width == 0 || height == 0 {
+                    continue;
+                }
+
+                let scaled = if (width, height) == tile_img.dimensions() {
+                    tile_img
+                } else {
+                    imageops::resize(&tile_img, width, height, filter.filter_type())
+                };
+                imageops::replace(&mut canvas, &scaled, x, y);
+            }
+        }
+
+        Ok(canvas)
+    }
+
+    /// Get a Deep Zoom tile, using cache when available.
+    ///
+    /// Deep Zoom levels don't line up with the pyramid, so the tile is the
+    /// region of the slide given by `plan`, rendered with `filter` and
+    /// encoded as JPEG on the encode pool. `request` addresses the tile on
+    /// the Deep Zoom grid; it is cached under those coordinates, apart from
+    /// pyramid tiles, and concurrent requests share one render.
+    pub async fn get_deep_zoom_tile(
+        &self,
+        request: &TileRequest,
+        plan: &RegionPlan,
+        filter: ResampleFilter,
+    ) -> Result<TileResponse, TileError> {
+        if !is_valid_quality(request.quality) {
+            return Err(TileError::InvalidQuality {
+                quality: request.quality,
+            });
+        }
+
+        let slide = self.open_slide(&request.slide_id).await?;
+        let cache_key = TileCacheKey::new(
+            request.slide_id.as_str(),
+            request.level as u32,
+            request.tile_x,
+            request.tile_y,
+            request.quality,
+        )
+        .with_deep_zoom(filter)
+        .with_source_etag(slide.shared_etag());
+
+        if let Some(data) = self.cache.get(&cache_key).await {
+            return Ok(TileResponse {
+                data,
+                cache_hit: true,
+                stale: false,
+                quality: request.quality,
+            });
+        }
 
-    /// Get a reference to the underlying registry.
-    pub fn registry(&self) -> &Arc<SlideRegistry<S>> {
-        &self.registry
+        let quality = request.quality;
+        let data = self
+            .flights
+            .run(&cache_key, || async {
+                let image = self
+                    .render_region_with_filter(&request.slide_id, plan, quality, filter)
+                    .await?;
+                let data = self
+                    .encode_pool
+                    .run(move || encode_image(&DynamicImage::ImageRgb8(image), quality))
+                    .await?;
+                let evicted = self.cache.put(cache_key.clone(), data.clone()).await;
+                if evicted > 0 {
+                    self.events()
+                        .publish(|| ServerEvent::TileCacheEvicted { tiles: evicted });
+                }
+                Ok(data)
+            })
+            .await?;
+
+        Ok(TileResponse {
+            data,
+            cache_hit: false,
+            stale: false,
+            quality,
+        })
     }
 
     /// Generate a thumbnail for a slide.
@@ -368,6 +1635,18 @@ impl<S: SlideSource> TileService<S> {
         slide_id: &str,
         max_dimension: u32,
         quality: u8,
+    ) -> Result<TileResponse, TileError> {
+        self.generate_thumbnail_with_filter(slide_id, max_dimension, quality, self.resample_filter)
+            .await
+    }
+
+    /// Generate a thumbnail for a slide with a specific resampling filter.
+    pub async fn generate_thumbnail_with_filter(
+        &self,
+        slide_id: &str,
+        max_dimension: u32,
+        quality: u8,
+        filter: ResampleFilter,
     ) -> Result<TileResponse, TileError> {
         // Validate quality
         if !is_valid_quality(quality) {
@@ -375,28 +1654,7 @@ impl<S: SlideSource> TileService<S> {
         }
 
         // Get the slide from registry
-        let slide = self
-            .registry
-            .get_slide(slide_id)
-            .await
-            .map_err(|e| match e {
-                crate::error::FormatError::Io(io_err) => {
-                    if matches!(io_err, crate::error::IoError::NotFound(_)) {
-                        TileError::SlideNotFound {
-                            slide_id: slide_id.to_string(),
-                        }
-                    } else {
-                        TileError::Io(io_err)
-                    }
-                }
-                crate::error::FormatError::Tiff(tiff_err) => TileError::Slide(tiff_err),
-                crate::error::FormatError::UnsupportedFormat { reason } => {
-                    TileError::Slide(crate::error::TiffError::InvalidTagValue {
-                        tag: "Format",
-                        message: reason,
-                    })
-                }
-            })?;
+        let slide = self.open_slide(slide_id).await?;
 
         let (full_width, full_height) = slide.dimensions().ok_or(TileError::InvalidLevel {
             level: 0,
@@ -412,10 +1670,12 @@ impl<S: SlideSource> TileService<S> {
             .best_level_for_downsample(downsample)
             .unwrap_or(slide.level_count().saturating_sub(1));
 
-        let info = slide.level_info(level).ok_or(TileError::InvalidLevel {
-            level,
-            max_levels: slide.level_count(),
-        })?;
+        let info = self
+            .level_info(&slide, level)
+            .ok_or(TileError::InvalidLevel {
+                level,
+                max_levels: slide.level_count(),
+            })?;
 
         // If single tile covers the entire level, just return that tile
         // (resize if needed to fit max_dimension)
@@ -425,10 +1685,12 @@ impl<S: SlideSource> TileService<S> {
 
             // Resize if the tile is larger than max_dimension
             if info.width > max_dimension || info.height > max_dimension {
-                let resized = self.resize_image(&tile_response.data, max_dimension, quality)?;
+                let resized =
+                    self.resize_image(&tile_response.data, max_dimension, quality, filter)?;
                 return Ok(TileResponse {
                     data: resized,
                     cache_hit: false,
+                    stale: false,
                     quality,
                 });
             }
@@ -442,11 +1704,12 @@ impl<S: SlideSource> TileService<S> {
             .await?;
 
         // Resize the composite to fit within max_dimension
-        let resized = self.resize_image(&composite, max_dimension, quality)?;
+        let resized = self.resize_image(&composite, max_dimension, quality, filter)?;
 
         Ok(TileResponse {
             data: resized,
             cache_hit: false,
+            stale: false,
             quality,
         })
     }
@@ -456,7 +1719,7 @@ impl<S: SlideSource> TileService<S> {
         &self,
         slide_id: &str,
         level: usize,
-        info: &crate::slide::LevelInfo,
+        info: &LevelInfo,
         quality: u8,
     ) -> Result<Bytes, TileError> {
         // Create a canvas for the full level
@@ -513,6 +1776,7 @@ impl<S: SlideSource> TileService<S> {
         jpeg_data: &[u8],
         max_dimension: u32,
         quality: u8,
+        filter: ResampleFilter,
     ) -> Result<Bytes, TileError> {
         // Decode the source image
         let cursor = Cursor::new(jpeg_data);
@@ -528,9 +1792,7 @@ impl<S: SlideSource> TileService<S> {
         let new_width = (width as f64 * scale).round() as u32;
         let new_height = (height as f64 * scale).round() as u32;
 
-        // Resize using high-quality Lanczos3 filter
-        let resized =
-            img.resize_exact(new_width, new_height, image::imageops::FilterType::Lanczos3);
+        let resized = img.resize_exact(new_width, new_height, filter.filter_type());
 
         // Encode as JPEG
         let mut output = Vec::new();
@@ -545,6 +1807,235 @@ impl<S: SlideSource> TileService<S> {
     }
 }
 
+/// Counts a tile generation for as long as it is held (including through
+/// panics).
+struct GenerationGuard<'a>(&'a AtomicUsize);
+
+impl<'a> GenerationGuard<'a> {
+    fn new(counter: &'a AtomicUsize) -> Self {
+        counter.fetch_add(1, Ordering::Relaxed);
+        Self(counter)
+    }
+}
+
+impl Drop for GenerationGuard<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Decode tiles of a level and resample them into a region's canvas.
+///
+/// Edge tiles are trimmed to the level bounds first. Parts of the canvas not
+/// covered by a tile are white.
+fn stitch_tiles(
+    plan: &RegionPlan,
+    info: &LevelInfo,
+    tiles: Vec<(u32, u32, Bytes)>,
+    filter: ResampleFilter,
+) -> Result<RgbImage, TileError> {
+    let mut canvas = RgbImage::from_pixel(plan.out_width, plan.out_height, Rgb([255, 255, 255]));
+    for (tile_x, tile_y, data) in tiles {
+        let mut tile = decode_tile(&data)
+            .map_err(|e| match e {
+                TileError::DecodeError { message } => TileError::DecodeError {
+                    message: format!(
+                        "Failed to decode tile ({}, {}): {}",
+                        tile_x, tile_y, message
+                    ),
+                },
+                other => other,
+            })?
+            .into_rgb8();
+
+        let width = info
+            .width
+            .saturating_sub(tile_x * info.tile_width)
+            .min(tile.width());
+        let height = info
+            .height
+            .saturating_sub(tile_y * info.tile_height)
+            .min(tile.height());
+        if (width, height) != tile.dimensions() {
+            tile = imageops::crop_imm(&tile, 0, 0, width, height).to_image();
+        }
+
+        let (x, y, width, height) = plan.tile_destination(info, tile_x, tile_y, width, height);
+        if width == 0 || height == 0 {
+            continue;
+        }
+        let scaled = if (width, height) == tile.dimensions() {
+            tile
+        } else {
+            imageops::resize(&tile, width, height, filter.filter_type())
+        };
+        imageops::replace(&mut canvas, &scaled, x, y);
+    }
+    Ok(canvas)
+}
+
+/// Whether a tile failed because the slide file was replaced mid-read.
+fn is_object_changed(err: &TileError) -> bool {
+    matches!(
+        err,
+        TileError::Io(IoError::ObjectChanged(_))
+            | TileError::Slide(TiffError::Io(IoError::ObjectChanged(_)))
+    )
+}
+
+/// Point in time by which a tile must be generated.
+#[derive(Debug, Clone, Copy)]
+struct Deadline {
+    at: tokio::time::Instant,
+    budget: Duration,
+}
+
+impl Deadline {
+    /// A deadline `budget` from now.
+    fn after(budget: Duration) -> Self {
+        Self {
+            at: tokio::time::Instant::now() + budget,
+            budget,
+        }
+    }
+
+    fn timeout(&self) -> TileError {
+        TileError::Timeout {
+            budget_ms: self.budget.as_millis() as u64,
+        }
+    }
+
+    /// Run `future`, dropping it if it hasn't completed by the deadline.
+    async fn run<F: Future>(self, future: F) -> Result<F::Output, TileError> {
+        tokio::time::timeout_at(self.at, future)
+            .await
+            .map_err(|_| self.timeout())
+    }
+}
+
+// =============================================================================
+// Stale-While-Revalidate
+// =============================================================================
+
+impl<S: SlideSource + 'static> TileService<S> {
+    /// Get a tile, serving stale cached tiles while they are refreshed.
+    ///
+    /// Behaves like [`Self::get_tile`], except that when
+    /// stale-while-revalidate is enabled a stale cached tile is returned
+    /// immediately (with [`TileResponse::stale`] set) and regenerated on a
+    /// background task. Concurrent requests for the same stale tile share a
+    /// single refresh.
+    pub async fn get_tile_revalidating(
+        self: &Arc<Self>,
+        request: TileRequest,
+    ) -> Result<TileResponse, TileError> {
+        let response = self
+            .lookup_or_generate(request.clone(), self.stale_while_revalidate)
+            .await?;
+
+        if response.stale {
+            let cache_key = self.cache_key(&request).await?;
+            if self.cache.begin_refresh(&cache_key) {
+                let claim = RefreshClaim {
+                    service: Arc::clone(self),
+                    key: cache_key,
+                };
+                tokio::spawn(async move {
+                    if let Err(e) = claim
+                        .service
+                        .generate_and_cache(&request, claim.key.clone())
+                        .await
+                    {
+                        warn!(
+                            slide_id = %request.slide_id,
+                            level = request.level,
+                            x = request.tile_x,
+                            y = request.tile_y,
+                            error = %e,
+                            "Failed to refresh stale tile"
+                        );
+                    }
+                });
+            }
+        }
+
+        Ok(response)
+    }
+
+    /// Sample tissue-containing tiles from a level along with their data.
+    ///
+    /// Tiles are fetched through the regular tile pipeline (and cache) at
+    /// the given JPEG quality, up to [`DEFAULT_TILE_STREAM_CONCURRENCY`] at
+    /// a time, and returned in sample order.
+    pub async fn sample_tiles_with_data(
+        self: &Arc<Self>,
+        slide_id: &str,
+        level: usize,
+        options: &SampleOptions,
+        quality: u8,
+    ) -> Result<Vec<(SampledTile, Bytes)>, TileError> {
+        let samples = self.sample_tiles(slide_id, level, options).await?;
+
+        let mut pending = samples.iter().copied().enumerate();
+        let mut fetches = JoinSet::new();
+        let mut tiles = vec![None; samples.len()];
+
+        loop {
+            while fetches.len() < DEFAULT_TILE_STREAM_CONCURRENCY {
+                let Some((index, sample)) = pending.next() else {
+                    break;
+                };
+                let service = Arc::clone(self);
+                let request = TileRequest::with_quality(
+                    slide_id,
+                    level,
+                    sample.tile_x,
+                    sample.tile_y,
+                    quality,
+                );
+                fetches.spawn(CaughtPanic::catch_async(async move {
+                    service
+                        .get_tile(request)
+                        .await
+                        .map(|response| (index, response.data))
+                }));
+            }
+
+            let Some(joined) = fetches.join_next().await else {
+                break;
+            };
+            match joined {
+                Ok(Ok(tile)) => {
+                    let (index, data) = tile?;
+                    tiles[index] = Some((samples[index], data));
+                }
+                Ok(Err(panic)) => panic.resume(),
+                Err(err) if err.is_panic() => std::panic::resume_unwind(err.into_panic()),
+                Err(err) => {
+                    return Err(TileError::Overloaded {
+                        message: format!("tile fetch cancelled: {}", err),
+                    })
+                }
+            }
+        }
+
+        Ok(tiles.into_iter().flatten().collect())
+    }
+}
+
+/// A claimed background refresh, released when the refresh task ends
+/// (including by panic) so the tile can be refreshed again.
+struct RefreshClaim<S: SlideSource> {
+    service: Arc<TileService<S>>,
+    key: TileCacheKey,
+}
+
+impl<S: SlideSource> Drop for RefreshClaim<S> {
+    fn drop(&mut self) {
+        self.service.cache.end_refresh(&self.key);
+    }
+}
+
 // =============================================================================
 // Tests
 // =============================================================================
@@ -665,6 +2156,9 @@ mod tests {
     #[async_trait]
     impl RangeReader for MockReader {
         async fn read_exact_at(&self, offset: u64, len: usize) -> Result<Bytes, IoError> {
+            if self.identifier.contains("slow") {
+                tokio::time::sleep(Duration::from_millis(200)).await;
+            }
             let start = offset as usize;
             let end = start + len;
             if end > self.data.len() {
@@ -750,6 +2244,48 @@ mod tests {
         assert_eq!(response.data[1], 0xD8);
     }
 
+    #[tokio::test]
+    async fn test_passthrough_serves_source_jpeg() {
+        let tiff_data = create_tiff_with_jpeg_tile();
+        let registry = SlideRegistry::new(MockSlideSource::new(tiff_data));
+        let service = TileService::new(registry);
+
+        let request = TileRequest::with_quality("test.tif", 0, 0, 0, 50).with_passthrough(true);
+        let response = service.get_tile(request.clone()).await.unwrap();
+        assert_eq!(&response.data[..], &create_test_jpeg()[..]);
+        assert!(response.quality.abs_diff(90) <= crate::tile::SOURCE_QUALITY_TOLERANCE);
+
+        // Passthrough tiles are cached apart from re-encoded ones
+        let cached = service.get_tile(request).await.unwrap();
+        assert!(cached.cache_hit);
+        assert_eq!(cached.data, response.data);
+        let encoded = service
+            .get_tile(TileRequest::with_quality("test.tif", 0, 0, 0, 50))
+            .await
+            .unwrap();
+        assert!(!encoded.cache_hit);
+        assert_ne!(encoded.data, response.data);
+        assert_eq!(encoded.quality, 50);
+    }
+
+    #[tokio::test]
+    async fn test_passthrough_qualities_share_cache_entry() {
+        let tiff_data = create_tiff_with_jpeg_tile();
+        let registry = SlideRegistry::new(MockSlideSource::new(tiff_data));
+        let service = TileService::new(registry);
+
+        let request = TileRequest::with_quality("test.tif", 0, 0, 0, 50).with_passthrough(true);
+        let first = service.get_tile(request).await.unwrap();
+        assert!(!first.cache_hit);
+
+        let request = TileRequest::with_quality("test.tif", 0, 0, 0, 95).with_passthrough(true);
+        let second = service.get_tile(request).await.unwrap();
+        assert!(second.cache_hit);
+        assert_eq!(second.data, first.data);
+        assert_eq!(second.quality, first.quality);
+        assert_eq!(service.cache().len().await, 1);
+    }
+
     #[tokio::test]
     async fn test_get_tile_cache_hit() {
         let tiff_data = create_tiff_with_jpeg_tile();
@@ -769,6 +2305,176 @@ mod tests {
         assert_eq!(response1.data, response2.data);
     }
 
+    #[tokio::test]
+    async fn test_invalidated_tile_is_regenerated() {
+        let tiff_data = create_tiff_with_jpeg_tile();
+        let source = MockSlideSource::new(tiff_data);
+        let registry = SlideRegistry::new(source);
+        let service = TileService::new(registry);
+
+        let request = TileRequest::new("test.tif", 0, 0, 0);
+        service.get_tile(request.clone()).await.unwrap();
+        service.invalidate_slide("test.tif").await;
+
+        // Without stale-while-revalidate the stale tile is not served
+        let response = service.get_tile(request.clone()).await.unwrap();
+        assert!(!response.cache_hit);
+        assert!(!response.stale);
+        assert!(service.get_tile(request).await.unwrap().cache_hit);
+    }
+
+    #[tokio::test]
+    async fn test_stale_while_revalidate() {
+        let tiff_data = create_tiff_with_jpeg_tile();
+        let source = MockSlideSource::new(tiff_data);
+        let registry = SlideRegistry::new(source);
+        let service = Arc::new(TileService::new(registry).with_stale_while_revalidate(true));
+
+        let request = TileRequest::new("test.tif", 0, 0, 0);
+        let original = service.get_tile(request.clone()).await.unwrap();
+        service.invalidate_slide("test.tif").await;
+
+        // The stale tile is served immediately
+        let response = service.get_tile_revalidating(request).await.unwrap();
+        assert!(response.cache_hit);
+        assert!(response.stale);
+        assert_eq!(response.data, original.data);
+
+        // ...and refreshed in the background
+        let key = TileCacheKey::new("test.tif", 0, 0, 0, DEFAULT_JPEG_QUALITY);
+        for _ in 0..100 {
+            if !service.cache().lookup(&key).await.unwrap().stale {
+                return;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        panic!("stale tile was not refreshed");
+    }
+
+    #[tokio::test]
+    async fn test_tile_timeout() {
+        let tiff_data = create_tiff_with_jpeg_tile();
+        let registry = SlideRegistry::new(MockSlideSource::new(tiff_data));
+        let service = TileService::new(registry).with_tile_timeout(Duration::from_millis(50));
+        assert_eq!(service.tile_timeout(), Some(Duration::from_millis(50)));
+
+        // Reads slower than the budget are cancelled
+        let err = service
+            .get_tile(TileRequest::new("slow.tif", 0, 0, 0))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, TileError::Timeout { budget_ms: 50 }));
+        assert_eq!(service.active_generations(), 0);
+
+        // Fast slides are unaffected
+        let response = service
+            .get_tile(TileRequest::new("test.tif", 0, 0, 0))
+            .await;
+        assert!(response.is_ok());
+
+        // A zero budget disables the timeout
+        let service = service.with_tile_timeout(Duration::ZERO);
+        assert_eq!(service.tile_timeout(), None);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_requests_share_generation() {
+        let tiff_data = create_tiff_with_jpeg_tile();
+        let registry = SlideRegistry::new(MockSlideSource::new(tiff_data));
+        let service = TileService::new(registry);
+
+        // Slow reads keep the first generation in flight while the others
+        // arrive
+        let request = || service.get_tile(TileRequest::new("slow.tif", 0, 0, 0));
+        let (a, b, c, d) = tokio::join!(request(), request(), request(), request());
+        let tiles = [a.unwrap(), b.unwrap(), c.unwrap(), d.unwrap()];
+        assert!(tiles.iter().all(|tile| tile.data == tiles[0].data));
+        assert_eq!(service.coalesced_generations(), 3);
+
+        // Once cached, the tile is a plain hit
+        let response = service
+            .get_tile(TileRequest::new("slow.tif", 0, 0, 0))
+            .await
+            .unwrap();
+        assert!(response.cache_hit);
+        assert_eq!(service.coalesced_generations(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_joined_generation_uses_own_tenant_slot() {
+        let registry = SlideRegistry::new(MockSlideSource::new(create_tiff_with_jpeg_tile()));
+        let scheduler =
+            FairScheduler::new(4).with_tenant_quota("a", crate::tile::TenantQuota::new(1, 1));
+        let service = TileService::new(registry).with_fair_scheduler(scheduler);
+
+        // Tenant "a" is at its in-flight limit, so its request queues
+        let held = service
+            .scheduler()
+            .unwrap()
+            .acquire(Some("a"), "other.tif")
+            .await
+            .unwrap();
+        let queued = service.get_tile(TileRequest::new("slow.tif", 0, 0, 0).with_tenant("a"));
+
+        // Tenant "b" isn't held up behind tenant "a"'s queue
+        let joined = async {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            let response = service
+                .get_tile(TileRequest::new("slow.tif", 0, 0, 0).with_tenant("b"))
+                .await;
+            drop(held);
+            response
+        };
+        let (a, b) = tokio::time::timeout(Duration::from_secs(5), async {
+            tokio::join!(queued, joined)
+        })
+        .await
+        .expect("tenant b waited on tenant a's slot");
+
+        // Tenant "a" still picks up the tile generated meanwhile
+        assert_eq!(a.unwrap().data, b.unwrap().data);
+        assert_eq!(service.coalesced_generations(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_memory_budget() {
+        let tiff_data = create_tiff_with_jpeg_tile();
+        let registry = SlideRegistry::new(MockSlideSource::new(tiff_data.clone()));
+        let service = TileService::new(registry);
+        service
+            .get_tile(TileRequest::new("test.tif", 0, 0, 0))
+            .await
+            .unwrap();
+
+        // Without a budget nothing is evicted
+        let usage = service.memory_usage().await;
+        assert!(usage.tile_cache_bytes > 0);
+        assert!(usage.block_cache_bytes > 0);
+        assert!(usage.metadata_bytes > 0);
+        assert_eq!(service.enforce_memory_budget().await, 0);
+        assert_eq!(service.memory_usage().await, usage);
+
+        // A generous budget is left alone
+        let registry = SlideRegistry::new(MockSlideSource::new(tiff_data));
+        let service = TileService::new(registry).with_memory_budget(usage.total() * 2);
+        service
+            .get_tile(TileRequest::new("test.tif", 0, 0, 0))
+            .await
+            .unwrap();
+        assert_eq!(service.enforce_memory_budget().await, 0);
+
+        // Over budget, every cache gives up its share
+        let service = service.with_memory_budget(1);
+        let freed = service.enforce_memory_budget().await;
+        assert!(freed >= usage.total() - 1);
+        assert_eq!(
+            service.memory_budget().unwrap().evicted_bytes(),
+            freed as u64
+        );
+        assert_eq!(service.memory_usage().await, MemoryUsage::default());
+        assert_eq!(service.registry().cached_count().await, 0);
+    }
+
     #[tokio::test]
     async fn test_different_quality_different_cache() {
         let tiff_data = create_tiff_with_jpeg_tile();
@@ -843,6 +2549,48 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_retiled_level_uses_virtual_grid() {
+        let tiff_data = create_tiff_with_jpeg_tile();
+        let source = MockSlideSource::new(tiff_data);
+        let registry = SlideRegistry::new(source);
+        let service = TileService::new(registry).with_retiling(128);
+
+        // 2048x1536 with 256px native tiles becomes a 16x12 grid of 128px tiles
+        let slide = service.open_slide("test.tif").await.unwrap();
+        let info = service.level_info(&slide, 0).unwrap();
+        assert_eq!((info.tile_width, info.tile_height), (128, 128));
+        assert_eq!((info.tiles_x, info.tiles_y), (16, 12));
+
+        let response = service
+            .get_tile(TileRequest::new("test.tif", 0, 3, 1))
+            .await
+            .unwrap();
+        assert!(!response.cache_hit);
+        let img = image::load_from_memory(&response.data).unwrap();
+        assert_eq!((img.width(), img.height()), (128, 128));
+
+        // The native tile was cut into all four sub-tiles at once
+        let (_, _, count) = service.cache_stats().await;
+        assert_eq!(count, 4);
+        for (x, y) in [(2, 0), (3, 0), (2, 1)] {
+            let sibling = TileRequest::new("test.tif", 0, x, y);
+            assert!(service.get_tile(sibling).await.unwrap().cache_hit);
+        }
+
+        // Coordinates are validated against the virtual grid
+        assert!(service
+            .get_tile(TileRequest::new("test.tif", 0, 15, 11))
+            .await
+            .is_ok());
+        assert!(matches!(
+            service
+                .get_tile(TileRequest::new("test.tif", 0, 16, 0))
+                .await,
+            Err(TileError::TileOutOfBounds { max_x: 16, .. })
+        ));
+    }
+
     #[tokio::test]
     async fn test_slide_not_found() {
         let tiff_data = create_tiff_with_jpeg_tile();
@@ -939,6 +2687,70 @@ mod tests {
     // Thumbnail Tests
     // =========================================================================
 
+    #[tokio::test]
+    async fn test_sample_tiles_deterministic() {
+        let tiff_data = create_tiff_with_jpeg_tile();
+        let source = MockSlideSource::new(tiff_data);
+        let registry = SlideRegistry::new(source);
+        let service = TileService::new(registry);
+
+        let options = SampleOptions::new(8).with_seed(7).with_min_tissue(0.1);
+        let first = service.sample_tiles("test.tif", 0, &options).await.unwrap();
+        let second = service.sample_tiles("test.tif", 0, &options).await.unwrap();
+
+        assert_eq!(first.len(), 8);
+        assert_eq!(first, second);
+        assert!(first.iter().all(|s| s.tile_x < 8 && s.tile_y < 6));
+    }
+
+    #[tokio::test]
+    async fn test_sample_tiles_with_data() {
+        let tiff_data = create_tiff_with_jpeg_tile();
+        let source = MockSlideSource::new(tiff_data);
+        let registry = SlideRegistry::new(source);
+        let service = Arc::new(TileService::new(registry));
+
+        let options = SampleOptions::new(3).with_min_tissue(0.1);
+        let tiles = service
+            .sample_tiles_with_data("test.tif", 0, &options, 80)
+            .await
+            .unwrap();
+
+        assert_eq!(tiles.len(), 3);
+        for (_, data) in &tiles {
+            assert_eq!(&data[..2], &[0xFF, 0xD8]);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tissue_mask_is_kept_with_slide() {
+        let tiff_data = create_tiff_with_jpeg_tile();
+        let source = MockSlideSource::new(tiff_data);
+        let registry = SlideRegistry::new(source);
+        let service = TileService::new(registry);
+
+        let mask = service.tissue_mask("test.tif").await.unwrap();
+        assert!(mask.width() > 0 && mask.height() > 0);
+
+        let slide = service.open_slide("test.tif").await.unwrap();
+        assert!(Arc::ptr_eq(&slide.tissue_mask().unwrap(), &mask));
+        let again = service.tissue_mask("test.tif").await.unwrap();
+        assert!(Arc::ptr_eq(&again, &mask));
+    }
+
+    #[tokio::test]
+    async fn test_sample_tiles_invalid_level() {
+        let tiff_data = create_tiff_with_jpeg_tile();
+        let source = MockSlideSource::new(tiff_data);
+        let registry = SlideRegistry::new(source);
+        let service = TileService::new(registry);
+
+        let result = service
+            .sample_tiles("test.tif", 3, &SampleOptions::new(1))
+            .await;
+        assert!(matches!(result, Err(TileError::InvalidLevel { .. })));
+    }
+
     #[tokio::test]
     async fn test_generate_thumbnail_returns_valid_jpeg() {
         let tiff_data = create_tiff_with_jpeg_tile();
@@ -1048,6 +2860,23 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_generate_thumbnail_with_each_filter() {
+        let source = MockSlideSource::new(create_tiff_with_jpeg_tile());
+        let service = TileService::new(SlideRegistry::new(source))
+            .with_resample_filter(ResampleFilter::Lanczos3);
+        assert_eq!(service.resample_filter(), ResampleFilter::Lanczos3);
+
+        for filter in ResampleFilter::ALL {
+            let response = service
+                .generate_thumbnail_with_filter("test.tif", 200, 80, filter)
+                .await
+                .unwrap();
+            let img = image::load_from_memory(&response.data).unwrap();
+            assert_eq!(img.width().max(img.height()), 200, "{}", filter);
+        }
+    }
+
     #[tokio::test]
     async fn test_generate_thumbnail_preserves_aspect_ratio() {
         let tiff_data = create_tiff_with_jpeg_tile();
@@ -1114,4 +2943,17 @@ mod tests {
             e => panic!("Expected SlideNotFound error, got {:?}", e),
         }
     }
+
+    #[tokio::test]
+    async fn test_render_snapshot_requires_calibration() {
+        let tiff_data = create_tiff_with_jpeg_tile();
+        let source = MockSlideSource::new(tiff_data);
+        let registry = SlideRegistry::new(source);
+        let service = TileService::new(registry);
+
+        // Generic TIFFs carry no microns-per-pixel metadata
+        let region = SnapshotRegion::new(0.0, 0.0, 100.0);
+        let result = service.render_snapshot("test.tif", &region, 90).await;
+        assert!(matches!(result, Err(TileError::InvalidRegion { .. })));
+    }
 }
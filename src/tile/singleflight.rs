@@ -0,0 +1,298 @@
+//! Deduplication of concurrent tile generations.
+//!
+//! Viewers opened on the same region (several tabs, or a shared link
+//! opened by a room full of people) request the same tiles at the same
+//! moment. Without coordination each request misses the cache and decodes
+//! and encodes the tile on its own. [`TileFlights`] lets the first request
+//! for a [`TileCacheKey`] generate the tile while later requests for the
+//! same key wait for its result.
+//!
+//! A flight is not tied to the request that started it: if that request
+//! is cancelled (client gone, tile timeout), one of the waiters takes over
+//! the generation, so waiters never hang on an abandoned flight. Likewise,
+//! errors that describe the request rather than the tile (its timeout, or
+//! no generation slot for its queue) are not handed to the waiters.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use bytes::Bytes;
+use tokio::sync::OnceCell;
+
+use crate::error::TileError;
+
+use super::cache::TileCacheKey;
+
+/// Shared outcome of one tile generation.
+type Flight = Arc<OnceCell<Result<Bytes, TileError>>>;
+
+/// In-flight tile generations, keyed by tile.
+#[derive(Debug, Default)]
+pub struct TileFlights {
+    flights: Mutex<HashMap<TileCacheKey, Flight>>,
+
+    /// Requests that joined a generation already in flight
+    coalesced: AtomicU64,
+}
+
+impl TileFlights {
+    /// Create an empty set of flights.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Generate a tile, or wait for the generation of the same tile that is
+    /// already in flight.
+    ///
+    /// `generate` only runs if no other request is generating `key`, or if
+    /// the request that was has been cancelled or failed with an error of
+    /// its own. Other errors are shared with the waiters like tiles are.
+    pub async fn run<F, Fut>(&self, key: &TileCacheKey, generate: F) -> Result<Bytes, TileError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<Bytes, TileError>>,
+    {
+        self.join(key).run(generate).await
+    }
+
+    /// Number of requests served by a generation another request started.
+    pub fn coalesced(&self) -> u64 {
+        self.coalesced.load(Ordering::Relaxed)
+    }
+
+    /// Number of distinct tiles being generated.
+    pub fn len(&self) -> usize {
+        self.flights.lock().unwrap().len()
+    }
+
+    /// Whether no tile is being generated.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Join the flight for `key`, starting one if none is in the air.
+    ///
+    /// The flight stays in the air while the returned passenger is held, so
+    /// a request can do its own preparation (such as waiting for a
+    /// generation slot) and still pick up a tile generated meanwhile.
+    pub fn join(&self, key: &TileCacheKey) -> Passenger<'_> {
+        let mut flights = self.flights.lock().unwrap();
+        let flight = match flights.get(key) {
+            Some(flight) => {
+                self.coalesced.fetch_add(1, Ordering::Relaxed);
+                flight.clone()
+            }
+            None => {
+                let flight = Flight::default();
+                flights.insert(key.clone(), flight.clone());
+                flight
+            }
+        };
+        Passenger {
+            flights: self,
+            key: key.clone(),
+            flight: Some(flight),
+        }
+    }
+}
+
+/// A request's hold on a flight; the last one to leave lands it.
+pub struct Passenger<'a> {
+    flights: &'a TileFlights,
+    key: TileCacheKey,
+    flight: Option<Flight>,
+}
+
+impl Passenger<'_> {
+    /// Wait for the flight's tile, running `generate` if no other request
+    /// is generating it.
+    ///
+    /// See [`TileFlights::run`].
+    pub async fn run<F, Fut>(&self, generate: F) -> Result<Bytes, TileError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<Bytes, TileError>>,
+    {
+        let flight = self.flight.as_ref().expect("flight is held");
+        let landed = flight
+            .get_or_try_init(|| async {
+                match generate().await {
+                    Err(err) if !is_shared(&err) => Err(err),
+                    result => Ok(result),
+                }
+            })
+            .await;
+        match landed {
+            Ok(result) => result.clone(),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+/// Whether an error describes the tile, rather than the request that hit
+/// it, and can be handed to the other requests waiting for the tile.
+fn is_shared(err: &TileError) -> bool {
+    !matches!(
+        err,
+        TileError::Timeout { .. } | TileError::Overloaded { .. }
+    )
+}
+
+impl Drop for Passenger<'_> {
+    fn drop(&mut self) {
+        // Holds are taken and released under the lock, so the count is
+        // exact: one for the map and one for this passenger
+        let mut flights = self.flights.flights.lock().unwrap();
+        let Some(flight) = self.flight.take() else {
+            return;
+        };
+        let last = Arc::strong_count(&flight) == 2;
+        if last
+            && flights
+                .get(&self.key)
+                .is_some_and(|current| Arc::ptr_eq(current, &flight))
+        {
+            flights.remove(&self.key);
+        }
+        drop(flight);
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+    use std::time::Duration;
+
+    fn key(tile_x: u32) -> TileCacheKey {
+        TileCacheKey::new("slide.svs", 0, tile_x, 0, 80)
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_requests_share_one_generation() {
+        let flights = Arc::new(TileFlights::new());
+        let generations = Arc::new(AtomicUsize::new(0));
+
+        let mut tasks = Vec::new();
+        for _ in 0..8 {
+            let flights = flights.clone();
+            let generations = generations.clone();
+            tasks.push(tokio::spawn(async move {
+                flights
+                    .run(&key(0), || async {
+                        generations.fetch_add(1, Ordering::SeqCst);
+                        tokio::time::sleep(Duration::from_millis(50)).await;
+                        Ok(Bytes::from_static(b"tile"))
+                    })
+                    .await
+            }));
+        }
+        for task in tasks {
+            assert_eq!(task.await.unwrap().unwrap(), Bytes::from_static(b"tile"));
+        }
+
+        assert_eq!(generations.load(Ordering::SeqCst), 1);
+        assert_eq!(flights.coalesced(), 7);
+        assert!(flights.is_empty());
+
+        // Landed flights aren't reused
+        flights
+            .run(&key(0), || async { Ok(Bytes::new()) })
+            .await
+            .unwrap();
+        assert_eq!(flights.coalesced(), 7);
+    }
+
+    #[tokio::test]
+    async fn test_different_tiles_generate_separately() {
+        let flights = TileFlights::new();
+        let (first, second) = (key(0), key(1));
+        let (a, b) = tokio::join!(
+            flights.run(&first, || async { Ok(Bytes::from_static(b"a")) }),
+            flights.run(&second, || async { Ok(Bytes::from_static(b"b")) }),
+        );
+        assert_eq!(a.unwrap(), Bytes::from_static(b"a"));
+        assert_eq!(b.unwrap(), Bytes::from_static(b"b"));
+        assert_eq!(flights.coalesced(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_errors_are_shared() {
+        let flights = TileFlights::new();
+        let failing = || async {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            Err(TileError::EncodeError {
+                message: "boom".to_string(),
+            })
+        };
+        let tile = key(0);
+        let (a, b) = tokio::join!(
+            flights.run(&tile, failing),
+            flights.run(&tile, || async { Ok(Bytes::new()) }),
+        );
+        assert!(matches!(a, Err(TileError::EncodeError { .. })));
+        assert!(matches!(b, Err(TileError::EncodeError { .. })));
+        assert!(flights.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_request_errors_are_not_shared() {
+        let flights = TileFlights::new();
+        let timed_out = || async {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            Err(TileError::Timeout { budget_ms: 20 })
+        };
+        let tile = key(0);
+        let (a, b) = tokio::join!(
+            flights.run(&tile, timed_out),
+            flights.run(&tile, || async { Ok(Bytes::from_static(b"tile")) }),
+        );
+        assert!(matches!(a, Err(TileError::Timeout { .. })));
+        assert_eq!(b.unwrap(), Bytes::from_static(b"tile"));
+        assert!(flights.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_waiter_takes_over_cancelled_generation() {
+        let flights = Arc::new(TileFlights::new());
+
+        // The leader is cancelled while generating
+        let leader = {
+            let flights = flights.clone();
+            tokio::spawn(async move {
+                flights
+                    .run(&key(0), || async {
+                        tokio::time::sleep(Duration::from_secs(60)).await;
+                        Ok(Bytes::from_static(b"leader"))
+                    })
+                    .await
+            })
+        };
+        tokio::time::sleep(Duration::from_millis(20)).await;
+
+        let waiter = {
+            let flights = flights.clone();
+            tokio::spawn(async move {
+                flights
+                    .run(&key(0), || async { Ok(Bytes::from_static(b"waiter")) })
+                    .await
+            })
+        };
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        leader.abort();
+
+        let tile = tokio::time::timeout(Duration::from_secs(5), waiter)
+            .await
+            .expect("waiter hung on a cancelled flight")
+            .unwrap()
+            .unwrap();
+        assert_eq!(tile, Bytes::from_static(b"waiter"));
+        assert!(flights.is_empty());
+    }
+}
@@ -0,0 +1,620 @@
+//! Print-resolution snapshots of a slide region.
+//!
+//! A snapshot renders a region given in microns at a print width and DPI, for
+//! figures in papers and reports. It is rendered like any other region (see
+//! [`RegionPlan`]): stitched from the tiles of the lowest-resolution level
+//! that still covers the output resolution.
+//!
+//! A scale bar computed from the slide's microns-per-pixel calibration is
+//! burned into the bottom-left corner.
+
+use std::io::Write;
+use std::ops::Range;
+
+use image::codecs::jpeg::{JpegEncoder, PixelDensity};
+use image::{DynamicImage, Rgb, RgbImage};
+
+use crate::error::TileError;
+use crate::slide::LevelInfo;
+
+use super::region::RegionPlan;
+
+// =============================================================================
+// Configuration
+// =============================================================================
+
+/// Default output resolution in dots per inch.
+pub const DEFAULT_SNAPSHOT_DPI: u32 = 300;
+
+/// Lowest accepted output resolution.
+pub const MIN_SNAPSHOT_DPI: u32 = 72;
+
+/// Highest accepted output resolution.
+pub const MAX_SNAPSHOT_DPI: u32 = 1200;
+
+/// Default print width in inches (a single journal column is ~3.5", a full
+/// page width ~7").
+pub const DEFAULT_PRINT_WIDTH_IN: f64 = 6.0;
+
+/// Largest accepted print width in inches.
+pub const MAX_PRINT_WIDTH_IN: f64 = 40.0;
+
+/// Maximum number of output pixels (width x height).
+pub const MAX_SNAPSHOT_PIXELS: u64 = 50_000_000;
+
+/// Fraction of the image width the scale bar aims for.
+const SCALE_BAR_FRACTION: f64 = 0.2;
+
+const BLACK: Rgb<u8> = Rgb([0, 0, 0]);
+const WHITE: Rgb<u8> = Rgb([255, 255, 255]);
+
+// =============================================================================
+// Region and Plan
+// =============================================================================
+
+/// A region to snapshot, in microns from the top-left corner of the slide.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnapshotRegion {
+    /// Left edge in microns
+    pub x_um: f64,
+
+    /// Top edge in microns
+    pub y_um: f64,
+
+    /// Region width in microns
+    pub width_um: f64,
+
+    /// Region height in microns
+    pub height_um: f64,
+
+    /// Output resolution in dots per inch
+    pub dpi: u32,
+
+    /// Output width in inches
+    pub print_width_in: f64,
+}
+
+impl SnapshotRegion {
+    /// Create a square region at the default DPI and print width.
+    pub fn new(x_um: f64, y_um: f64, width_um: f64) -> Self {
+        Self {
+            x_um,
+            y_um,
+            width_um,
+            height_um: width_um,
+            dpi: DEFAULT_SNAPSHOT_DPI,
+            print_width_in: DEFAULT_PRINT_WIDTH_IN,
+        }
+    }
+
+    /// Set the region height in microns.
+    pub fn with_height(mut self, height_um: f64) -> Self {
+        self.height_um = height_um;
+        self
+    }
+
+    /// Set the output resolution.
+    pub fn with_dpi(mut self, dpi: u32) -> Self {
+        self.dpi = dpi;
+        self
+    }
+
+    /// Set the output width in inches.
+    pub fn with_print_width(mut self, inches: f64) -> Self {
+        self.print_width_in = inches;
+        self
+    }
+}
+
+/// How a snapshot maps onto the slide pyramid.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnapshotPlan {
+    /// Pyramid level the tiles are read from
+    pub level: usize,
+
+    /// Downsample of that level relative to level 0
+    pub level_downsample: f64,
+
+    /// Left edge of the region in level 0 pixels (clipped to the slide)
+    pub x: f64,
+
+    /// Top edge of the region in level 0 pixels (clipped to the slide)
+    pub y: f64,
+
+    /// Region width in level 0 pixels (clipped to the slide)
+    pub width: f64,
+
+    /// Region height in level 0 pixels (clipped to the slide)
+    pub height: f64,
+
+    /// Output pixels per level 0 pixel (at most 1, snapshots never upsample)
+    pub scale: f64,
+
+    /// Output width in pixels
+    pub out_width: u32,
+
+    /// Output height in pixels
+    pub out_height: u32,
+
+    /// Microns per output pixel
+    pub output_mpp: f64,
+}
+
+impl SnapshotPlan {
+    /// The region this snapshot renders.
+    pub fn region(&self) -> RegionPlan {
+        RegionPlan {
+            level: self.level,
+            level_downsample: self.level_downsample,
+            x: self.x,
+            y: self.y,
+            width: self.width,
+            height: self.height,
+            scale_x: self.scale,
+            scale_y: self.scale,
+            out_width: self.out_width,
+            out_height: self.out_height,
+        }
+    }
+
+    /// Tiles of the planned level that intersect the region.
+    pub fn tile_range(&self, info: &LevelInfo) -> (Range<u32>, Range<u32>) {
+        self.region().tile_range(info)
+    }
+
+    /// Where a decoded tile lands in the output, as `(x, y, width, height)`.
+    ///
+    /// See [`RegionPlan::tile_destination`].
+    pub fn tile_destination(
+        &self,
+        info: &LevelInfo,
+        tile_x: u32,
+        tile_y: u32,
+        tile_width: u32,
+        tile_height: u32,
+    ) -> (i64, i64, u32, u32) {
+        self.region()
+            .tile_destination(info, tile_x, tile_y, tile_width, tile_height)
+    }
+}
+
+/// Plan a snapshot.
+///
+/// # Arguments
+///
+/// * `region` - Requested region and print size
+/// * `mpp` - Microns per level 0 pixel
+/// * `dimensions` - Level 0 dimensions of the slide
+/// * `downsamples` - Downsample factor of each level, level 0 first
+///
+/// The region is clipped to the slide. The output keeps the requested
+/// microns-per-pixel, so a clipped region yields a smaller image rather than
+/// a stretched one.
+pub fn plan_snapshot(
+    region: &SnapshotRegion,
+    mpp: f64,
+    dimensions: (u32, u32),
+    downsamples: &[f64],
+) -> Result<SnapshotPlan, TileError> {
+    let invalid = |message: String| TileError::InvalidRegion { message };
+
+    if !(mpp.is_finite() && mpp > 0.0) {
+        return Err(invalid(format!("invalid slide calibration: {} um/px", mpp)));
+    }
+    if ![region.x_um, region.y_um].iter().all(|v| v.is_finite()) {
+        return Err(invalid("region origin must be finite".to_string()));
+    }
+    if !(region.width_um.is_finite()
+        && region.width_um > 0.0
+        && region.height_um.is_finite()
+        && region.height_um > 0.0)
+    {
+        return Err(invalid(format!(
+            "region size must be positive, got {}x{} um",
+            region.width_um, region.height_um
+        )));
+    }
+    if !(MIN_SNAPSHOT_DPI..=MAX_SNAPSHOT_DPI).contains(&region.dpi) {
+        return Err(invalid(format!(
+            "dpi must be {}-{}, got {}",
+            MIN_SNAPSHOT_DPI, MAX_SNAPSHOT_DPI, region.dpi
+        )));
+    }
+    if !(region.print_width_in > 0.0 && region.print_width_in <= MAX_PRINT_WIDTH_IN) {
+        return Err(invalid(format!(
+            "print width must be in (0, {}] inches, got {}",
+            MAX_PRINT_WIDTH_IN, region.print_width_in
+        )));
+    }
+
+    // Requested region in level 0 pixels, clipped to the slide
+    let (slide_width, slide_height) = (dimensions.0 as f64, dimensions.1 as f64);
+    let x0 = (region.x_um / mpp).max(0.0);
+    let y0 = (region.y_um / mpp).max(0.0);
+    let x1 = ((region.x_um + region.width_um) / mpp).min(slide_width);
+    let y1 = ((region.y_um + region.height_um) / mpp).min(slide_height);
+    if x1 <= x0 || y1 <= y0 {
+        return Err(invalid(format!(
+            "region ({}, {}) {}x{} um is outside the slide",
+            region.x_um, region.y_um, region.width_um, region.height_um
+        )));
+    }
+
+    let target_width = region.print_width_in * region.dpi as f64;
+    let scale = (target_width / (region.width_um / mpp)).min(1.0);
+    let out_width = (((x1 - x0) * scale).round() as u32).max(1);
+    let out_height = (((y1 - y0) * scale).round() as u32).max(1);
+    if out_width as u64 * out_height as u64 > MAX_SNAPSHOT_PIXELS {
+        return Err(invalid(format!(
+            "snapshot of {}x{} pixels exceeds the {} pixel limit",
+            out_width, out_height, MAX_SNAPSHOT_PIXELS
+        )));
+    }
+
+    // Lowest-resolution level that doesn't need upsampling; the small
+    // tolerance absorbs rounding in stored downsample factors
+    let needed = 1.0 / scale;
+    let level = downsamples
+        .iter()
+        .enumerate()
+        .filter(|(_, ds)| **ds <= needed * 1.001)
+        .max_by(|a, b| a.1.total_cmp(b.1))
+        .map(|(level, _)| level)
+        .unwrap_or(0);
+
+    Ok(SnapshotPlan {
+        level,
+        level_downsample: downsamples.get(level).copied().unwrap_or(1.0),
+        x: x0,
+        y: y0,
+        width: x1 - x0,
+        height: y1 - y0,
+        scale,
+        out_width,
+        out_height,
+        output_mpp: mpp / scale,
+    })
+}
+
+// =============================================================================
+// Scale Bar
+// =============================================================================
+
+/// Pick a round scale bar length (1, 2 or 5 x 10^n microns) for an image.
+///
+/// Returns the largest such length that spans at most a fifth of the width.
+pub fn scale_bar_microns(output_mpp: f64, image_width: u32) -> Option<f64> {
+    let target = image_width as f64 * SCALE_BAR_FRACTION * output_mpp;
+    if !(target.is_finite() && target > 0.0) {
+        return None;
+    }
+
+    let magnitude = 10f64.powi(target.log10().floor() as i32);
+    [5.0, 2.0, 1.0]
+        .into_iter()
+        .map(|m| m * magnitude)
+        .find(|&length| length <= target)
+}
+
+/// Label for a scale bar length, e.g. `500 µm` or `2 mm`.
+pub fn scale_bar_label(microns: f64) -> String {
+    let trim = |v: f64| (v * 1000.0).round() / 1000.0;
+    if microns >= 1000.0 {
+        format!("{} mm", trim(microns / 1000.0))
+    } else {
+        format!("{} µm", trim(microns))
+    }
+}
+
+/// Burn a scale bar into the bottom-left corner of an image.
+///
+/// The bar and its label are drawn in black on a white box so they stay
+/// legible over any tissue. Returns the bar length in microns, or `None` if
+/// the image is too small to hold it.
+pub fn draw_scale_bar(img: &mut RgbImage, output_mpp: f64) -> Option<f64> {
+    let (width, height) = img.dimensions();
+    let microns = scale_bar_microns(output_mpp, width)?;
+    let bar_width = (microns / output_mpp).round() as u32;
+    if bar_width == 0 {
+        return None;
+    }
+
+    let label = scale_bar_label(microns);
+    let unit = (width / 500).max(1);
+    let padding = 2 * unit;
+    let margin = (width.min(height) / 40).max(2);
+    let label_width = (label.chars().count() as u32 * 6).saturating_sub(1) * unit;
+    let label_height = 7 * unit;
+    let bar_height = 3 * unit;
+
+    let box_width = bar_width.max(label_width) + 2 * padding;
+    let box_height = label_height + 2 * unit + bar_height + 2 * padding;
+    if box_width + margin > width || box_height + margin > height {
+        return None;
+    }
+
+    let box_x = margin;
+    let box_y = height - margin - box_height;
+    fill_rect(img, box_x, box_y, box_width, box_height, WHITE);
+
+    let mut cursor = box_x + padding;
+    for c in label.chars() {
+        draw_glyph(img, cursor, box_y + padding, unit, glyph(c));
+        cursor += 6 * unit;
+    }
+
+    let bar_y = box_y + padding + label_height + 2 * unit;
+    fill_rect(img, box_x + padding, bar_y, bar_width, bar_height, BLACK);
+
+    Some(microns)
+}
+
+fn fill_rect(img: &mut RgbImage, x: u32, y: u32, width: u32, height: u32, color: Rgb<u8>) {
+    for py in y..(y + height).min(img.height()) {
+        for px in x..(x + width).min(img.width()) {
+            img.put_pixel(px, py, color);
+        }
+    }
+}
+
+fn draw_glyph(img: &mut RgbImage, x: u32, y: u32, unit: u32, rows: [u8; 7]) {
+    for (row, bits) in rows.iter().enumerate() {
+        for col in 0..5 {
+            if bits & (0x10 >> col) != 0 {
+                fill_rect(
+                    img,
+                    x + col * unit,
+                    y + row as u32 * unit,
+                    unit,
+                    unit,
+                    BLACK,
+                );
+            }
+        }
+    }
+}
+
+/// 5x7 bitmap glyphs for scale bar labels and watermarks, one byte per row
+/// (low 5 bits).
+///
+/// Lowercase letters other than `m` (needed for units) use the uppercase
+/// glyph; characters without a glyph are blank.
+pub(super) fn glyph(c: char) -> [u8; 7] {
+    match c {
+        '0' => [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E],
+        '1' => [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        '2' => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F],
+        '3' => [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E],
+        '4' => [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02],
+        '5' => [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E],
+        '6' => [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E],
+        '7' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08],
+        '8' => [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E],
+        '9' => [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C],
+        '.' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C],
+        'µ' => [0x00, 0x00, 0x11, 0x11, 0x13, 0x1D, 0x10],
+        'm' => [0x00, 0x00, 0x1A, 0x15, 0x15, 0x11, 0x11],
+        'A' => [0x0E, 0x11, 0x11, 0x11, 0x1F, 0x11, 0x11],
+        'B' => [0x1E, 0x11, 0x11, 0x1E, 0x11, 0x11, 0x1E],
+        'C' => [0x0E, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0E],
+        'D' => [0x1C, 0x12, 0x11, 0x11, 0x11, 0x12, 0x1C],
+        'E' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x1F],
+        'F' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x10],
+        'G' => [0x0E, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0F],
+        'H' => [0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11],
+        'I' => [0x0E, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        'J' => [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0C],
+        'K' => [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11],
+        'L' => [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1F],
+        'M' => [0x11, 0x1B, 0x15, 0x15, 0x11, 0x11, 0x11],
+        'N' => [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11],
+        'O' => [0x0E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+        'P' => [0x1E, 0x11, 0x11, 0x1E, 0x10, 0x10, 0x10],
+        'Q' => [0x0E, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0D],
+        'R' => [0x1E, 0x11, 0x11, 0x1E, 0x14, 0x12, 0x11],
+        'S' => [0x0F, 0x10, 0x10, 0x0E, 0x01, 0x01, 0x1E],
+        'T' => [0x1F, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04],
+        'U' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+        'V' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x0A, 0x04],
+        'W' => [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0A],
+        'X' => [0x11, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x11],
+        'Y' => [0x11, 0x11, 0x11, 0x0A, 0x04, 0x04, 0x04],
+        'Z' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1F],
+        ',' => [0x00, 0x00, 0x00, 0x00, 0x0C, 0x04, 0x08],
+        ':' => [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x0C, 0x00],
+        '-' => [0x00, 0x00, 0x00, 0x1F, 0x00, 0x00, 0x00],
+        '_' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x1F],
+        '/' => [0x00, 0x01, 0x02, 0x04, 0x08, 0x10, 0x00],
+        '(' => [0x02, 0x04, 0x08, 0x08, 0x08, 0x04, 0x02],
+        ')' => [0x08, 0x04, 0x02, 0x02, 0x02, 0x04, 0x08],
+        '#' => [0x0A, 0x0A, 0x1F, 0x0A, 0x1F, 0x0A, 0x0A],
+        '@' => [0x0E, 0x11, 0x01, 0x0D, 0x15, 0x15, 0x0E],
+        '+' => [0x00, 0x04, 0x04, 0x1F, 0x04, 0x04, 0x00],
+        '=' => [0x00, 0x00, 0x1F, 0x00, 0x1F, 0x00, 0x00],
+        '\'' => [0x0C, 0x04, 0x08, 0x00, 0x00, 0x00, 0x00],
+        '!' => [0x04, 0x04, 0x04, 0x04, 0x00, 0x00, 0x04],
+        '?' => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x00, 0x04],
+        c if c.is_ascii_lowercase() => glyph(c.to_ascii_uppercase()),
+        _ => [0x00; 7],
+    }
+}
+
+// =============================================================================
+// Encoding
+// =============================================================================
+
+/// Encode a snapshot as JPEG, recording the DPI in the JFIF header so image
+/// editors and layout tools place it at the intended print size.
+pub fn encode_snapshot<W: Write>(
+    img: &RgbImage,
+    dpi: u32,
+    quality: u8,
+    writer: W,
+) -> Result<(), TileError> {
+    let mut encoder = JpegEncoder::new_with_quality(writer, quality);
+    encoder.set_pixel_density(PixelDensity::dpi(dpi.min(u16::MAX as u32) as u16));
+    encoder
+        .encode_image(&DynamicImage::ImageRgb8(img.clone()))
+        .map_err(|e| TileError::EncodeError {
+            message: format!("Failed to encode snapshot: {}", e),
+        })
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOWNSAMPLES: [f64; 3] = [1.0, 4.0, 16.0];
+
+    fn level_info(downsample: f64) -> LevelInfo {
+        let width = (40_000.0 / downsample) as u32;
+        let height = (30_000.0 / downsample) as u32;
+        LevelInfo {
+            width,
+            height,
+            tile_width: 256,
+            tile_height: 256,
+            tiles_x: width.div_ceil(256),
+            tiles_y: height.div_ceil(256),
+            downsample,
+        }
+    }
+
+    #[test]
+    fn test_plan_picks_level_without_upsampling() {
+        // 2mm at 0.25 um/px = 8000 px, printed 6" at 300 dpi = 1800 px
+        let region = SnapshotRegion::new(1000.0, 1000.0, 2000.0);
+        let plan = plan_snapshot(&region, 0.25, (40_000, 30_000), &DOWNSAMPLES).unwrap();
+
+        assert_eq!(plan.out_width, 1800);
+        assert_eq!(plan.out_height, 1800);
+        // Needed downsample is ~4.44, so level 1 (4x) is read and reduced
+        assert_eq!(plan.level, 1);
+        assert!((plan.output_mpp - 2000.0 / 1800.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_plan_never_upsamples() {
+        // 100 um region is only 400 native pixels wide
+        let region = SnapshotRegion::new(0.0, 0.0, 100.0).with_dpi(600);
+        let plan = plan_snapshot(&region, 0.25, (40_000, 30_000), &DOWNSAMPLES).unwrap();
+
+        assert_eq!(plan.level, 0);
+        assert_eq!(plan.scale, 1.0);
+        assert_eq!((plan.out_width, plan.out_height), (400, 400));
+        assert_eq!(plan.output_mpp, 0.25);
+    }
+
+    #[test]
+    fn test_plan_clips_to_slide() {
+        let region = SnapshotRegion::new(9000.0, 0.0, 2000.0).with_height(1000.0);
+        let plan = plan_snapshot(&region, 0.25, (40_000, 30_000), &DOWNSAMPLES).unwrap();
+
+        // Only the first 1000 um of the width lie on the slide
+        assert_eq!(plan.width, 4000.0);
+        assert_eq!(plan.out_width, 900);
+        assert_eq!(plan.out_height, 900);
+    }
+
+    #[test]
+    fn test_plan_rejects_invalid_regions() {
+        let dims = (40_000, 30_000);
+        let outside = SnapshotRegion::new(20_000.0, 0.0, 100.0);
+        assert!(plan_snapshot(&outside, 0.25, dims, &DOWNSAMPLES).is_err());
+
+        let empty = SnapshotRegion::new(0.0, 0.0, 0.0);
+        assert!(plan_snapshot(&empty, 0.25, dims, &DOWNSAMPLES).is_err());
+
+        let bad_dpi = SnapshotRegion::new(0.0, 0.0, 100.0).with_dpi(5000);
+        assert!(plan_snapshot(&bad_dpi, 0.25, dims, &DOWNSAMPLES).is_err());
+
+        let huge = SnapshotRegion::new(0.0, 0.0, 10_000.0)
+            .with_dpi(1200)
+            .with_print_width(MAX_PRINT_WIDTH_IN);
+        assert!(matches!(
+            plan_snapshot(&huge, 0.25, dims, &DOWNSAMPLES),
+            Err(TileError::InvalidRegion { .. })
+        ));
+    }
+
+    #[test]
+    fn test_tile_destinations_are_seamless() {
+        let region = SnapshotRegion::new(1000.0, 1000.0, 2000.0);
+        let plan = plan_snapshot(&region, 0.25, (40_000, 30_000), &DOWNSAMPLES).unwrap();
+        let info = level_info(plan.level_downsample);
+
+        let (xs, ys) = plan.tile_range(&info);
+        assert!(!xs.is_empty() && !ys.is_empty());
+
+        let mut expected_left = None;
+        for tile_x in xs {
+            let (left, _, width, _) = plan.tile_destination(&info, tile_x, ys.start, 256, 256);
+            if let Some(expected) = expected_left {
+                assert_eq!(left, expected);
+            }
+            expected_left = Some(left + width as i64);
+        }
+        // The last tile reaches the right edge of the output
+        assert!(expected_left.unwrap() >= plan.out_width as i64);
+    }
+
+    #[test]
+    fn test_scale_bar_microns() {
+        // 1000 px at 1 um/px: a fifth is 200 um
+        assert_eq!(scale_bar_microns(1.0, 1000), Some(200.0));
+        // A fifth is 300 um, rounded down to 200
+        assert_eq!(scale_bar_microns(1.5, 1000), Some(200.0));
+        // A fifth is 700 um, rounded down to 500
+        assert_eq!(scale_bar_microns(3.5, 1000), Some(500.0));
+        assert_eq!(scale_bar_microns(0.0, 1000), None);
+    }
+
+    #[test]
+    fn test_scale_bar_label() {
+        assert_eq!(scale_bar_label(200.0), "200 µm");
+        assert_eq!(scale_bar_label(0.5), "0.5 µm");
+        assert_eq!(scale_bar_label(2000.0), "2 mm");
+    }
+
+    #[test]
+    fn test_draw_scale_bar() {
+        let mut img = RgbImage::from_pixel(1000, 800, Rgb([200, 120, 160]));
+        let microns = draw_scale_bar(&mut img, 1.0).unwrap();
+        assert_eq!(microns, 200.0);
+
+        // The bar is black, 200 px long, inside the bottom-left box
+        let margin = 800 / 40;
+        let bar_bottom = 800 - margin - 2 * 2 - 1;
+        let bar_left = margin + 2 * 2;
+        assert_eq!(*img.get_pixel(bar_left, bar_bottom), BLACK);
+        assert_eq!(*img.get_pixel(bar_left + 199, bar_bottom), BLACK);
+        assert_eq!(*img.get_pixel(bar_left + 200, bar_bottom), WHITE);
+        // The rest of the image is untouched
+        assert_eq!(*img.get_pixel(900, 10), Rgb([200, 120, 160]));
+    }
+
+    #[test]
+    fn test_draw_scale_bar_skips_tiny_images() {
+        let mut img = RgbImage::new(20, 8);
+        assert_eq!(draw_scale_bar(&mut img, 1.0), None);
+    }
+
+    #[test]
+    fn test_encode_snapshot_records_dpi() {
+        let img = RgbImage::from_pixel(64, 32, WHITE);
+        let mut data = Vec::new();
+        encode_snapshot(&img, 300, 90, &mut data).unwrap();
+
+        // JFIF APP0: units = 1 (dots per inch), then X and Y density
+        let jfif = data.windows(5).position(|w| w == b"JFIF\0").unwrap();
+        assert_eq!(data[jfif + 7], 1);
+        assert_eq!(&data[jfif + 8..jfif + 12], &[0x01, 0x2C, 0x01, 0x2C]);
+
+        let decoded = image::load_from_memory(&data).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (64, 32));
+    }
+}
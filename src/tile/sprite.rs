@@ -0,0 +1,254 @@
+//! Sprite sheets of slide thumbnails.
+//!
+//! Gallery pages show a thumbnail for every slide in a collection. Rather
+//! than issuing one thumbnail request per slide, a client can fetch a single
+//! sprite sheet: thumbnails packed into a grid of square cells, along with the
+//! position of each slide's thumbnail within the sheet.
+//!
+//! Each thumbnail is centered in its cell and keeps its aspect ratio; unused
+//! cell area is filled with white.
+
+use bytes::Bytes;
+use image::codecs::jpeg::JpegEncoder;
+use image::{imageops, DynamicImage, ImageReader, Rgb, RgbImage};
+use serde::Serialize;
+use std::io::Cursor;
+
+use crate::error::TileError;
+
+use super::resample::ResampleFilter;
+
+// =============================================================================
+// Configuration
+// =============================================================================
+
+/// Default sprite cell size in pixels.
+pub const DEFAULT_SPRITE_SIZE: u32 = 128;
+
+/// Smallest allowed sprite cell size in pixels.
+pub const MIN_SPRITE_SIZE: u32 = 32;
+
+/// Largest allowed sprite cell size in pixels.
+pub const MAX_SPRITE_SIZE: u32 = 512;
+
+/// Maximum number of slides in a single sprite sheet.
+pub const MAX_SPRITE_SLIDES: usize = 256;
+
+/// Background color for empty cell area.
+const BACKGROUND: Rgb<u8> = Rgb([255, 255, 255]);
+
+// =============================================================================
+// Sprite Sheet
+// =============================================================================
+
+/// Position of one slide's thumbnail within a sprite sheet.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SpriteEntry {
+    /// Slide identifier
+    pub slide_id: String,
+
+    /// Left edge of the thumbnail in pixels
+    pub x: u32,
+
+    /// Top edge of the thumbnail in pixels
+    pub y: u32,
+
+    /// Thumbnail width in pixels
+    pub width: u32,
+
+    /// Thumbnail height in pixels
+    pub height: u32,
+}
+
+/// A rendered sprite sheet.
+#[derive(Debug, Clone)]
+pub struct SpriteSheet {
+    /// JPEG-encoded sheet
+    pub data: Bytes,
+
+    /// Sheet width in pixels
+    pub width: u32,
+
+    /// Sheet height in pixels
+    pub height: u32,
+
+    /// Cell size in pixels
+    pub cell_size: u32,
+
+    /// Number of cells per row
+    pub columns: u32,
+
+    /// Thumbnail positions, in input order
+    pub sprites: Vec<SpriteEntry>,
+
+    /// Slides whose thumbnails could not be rendered
+    pub failed: Vec<String>,
+}
+
+/// Number of columns for a near-square grid of `count` cells.
+pub fn sprite_columns(count: usize) -> u32 {
+    if count == 0 {
+        return 1;
+    }
+    (count as f64).sqrt().ceil() as u32
+}
+
+/// Pack JPEG thumbnails into a sprite sheet.
+///
+/// Thumbnails larger than `cell_size` are scaled down with `filter` to fit
+/// their cell.
+/// Thumbnails that fail to decode are listed in [`SpriteSheet::failed`]
+/// instead of failing the whole sheet.
+pub fn compose_sprite_sheet(
+    thumbnails: &[(String, Bytes)],
+    failed: Vec<String>,
+    cell_size: u32,
+    quality: u8,
+    filter: ResampleFilter,
+) -> Result<SpriteSheet, TileError> {
+    // Decode first so undecodable thumbnails don't leave holes in the grid
+    let mut images = Vec::with_capacity(thumbnails.len());
+    let mut failed = failed;
+    for (slide_id, data) in thumbnails {
+        let decoded = ImageReader::with_format(Cursor::new(&data[..]), image::ImageFormat::Jpeg)
+            .decode()
+            .map(|img| img.to_rgb8());
+        match decoded {
+            Ok(img) => images.push((slide_id, fit_to_cell(img, cell_size, filter))),
+            Err(_) => failed.push(slide_id.clone()),
+        }
+    }
+
+    let columns = sprite_columns(images.len());
+    let rows = (images.len() as u32).div_ceil(columns).max(1);
+    let width = columns * cell_size;
+    let height = rows * cell_size;
+
+    let mut canvas = RgbImage::from_pixel(width, height, BACKGROUND);
+    let mut sprites = Vec::with_capacity(images.len());
+
+    for (index, (slide_id, img)) in images.into_iter().enumerate() {
+        let index = index as u32;
+        let cell_x = (index % columns) * cell_size;
+        let cell_y = (index / columns) * cell_size;
+        let x = cell_x + (cell_size - img.width()) / 2;
+        let y = cell_y + (cell_size - img.height()) / 2;
+
+        imageops::replace(&mut canvas, &img, x as i64, y as i64);
+        sprites.push(SpriteEntry {
+            slide_id: slide_id.clone(),
+            x,
+            y,
+            width: img.width(),
+            height: img.height(),
+        });
+    }
+
+    let mut output = Vec::new();
+    JpegEncoder::new_with_quality(&mut output, quality)
+        .encode_image(&DynamicImage::ImageRgb8(canvas))
+        .map_err(|e| TileError::EncodeError {
+            message: format!("Failed to encode sprite sheet: {}", e),
+        })?;
+
+    Ok(SpriteSheet {
+        data: Bytes::from(output),
+        width,
+        height,
+        cell_size,
+        columns,
+        sprites,
+        failed,
+    })
+}
+
+/// Scale an image down to fit within a square cell, preserving aspect ratio.
+fn fit_to_cell(img: RgbImage, cell_size: u32, filter: ResampleFilter) -> RgbImage {
+    let (width, height) = img.dimensions();
+    if width <= cell_size && height <= cell_size {
+        return img;
+    }
+
+    let scale = cell_size as f64 / width.max(height) as f64;
+    let new_width = ((width as f64 * scale).round() as u32).clamp(1, cell_size);
+    let new_height = ((height as f64 * scale).round() as u32).clamp(1, cell_size);
+    imageops::resize(&img, new_width, new_height, filter.filter_type())
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn jpeg(width: u32, height: u32) -> Bytes {
+        let img = RgbImage::from_pixel(width, height, Rgb([200, 40, 90]));
+        let mut buf = Vec::new();
+        JpegEncoder::new_with_quality(&mut buf, 90)
+            .encode_image(&img)
+            .unwrap();
+        Bytes::from(buf)
+    }
+
+    #[test]
+    fn test_sprite_columns() {
+        assert_eq!(sprite_columns(0), 1);
+        assert_eq!(sprite_columns(1), 1);
+        assert_eq!(sprite_columns(4), 2);
+        assert_eq!(sprite_columns(5), 3);
+        assert_eq!(sprite_columns(10), 4);
+    }
+
+    #[test]
+    fn test_compose_grid_layout() {
+        let thumbs: Vec<_> = (0..5)
+            .map(|i| (format!("case/{}.svs", i), jpeg(64, 64)))
+            .collect();
+        let sheet =
+            compose_sprite_sheet(&thumbs, vec![], 64, 80, ResampleFilter::default()).unwrap();
+
+        assert_eq!(sheet.columns, 3);
+        assert_eq!((sheet.width, sheet.height), (192, 128));
+        assert_eq!(sheet.sprites.len(), 5);
+        assert_eq!(sheet.sprites[4].slide_id, "case/4.svs");
+        assert_eq!((sheet.sprites[4].x, sheet.sprites[4].y), (64, 64));
+
+        let decoded = image::load_from_memory(&sheet.data).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (192, 128));
+    }
+
+    #[test]
+    fn test_thumbnails_centered_and_scaled() {
+        let thumbs = vec![("wide.svs".to_string(), jpeg(256, 128))];
+        let sheet =
+            compose_sprite_sheet(&thumbs, vec![], 128, 80, ResampleFilter::default()).unwrap();
+
+        let sprite = &sheet.sprites[0];
+        assert_eq!((sprite.width, sprite.height), (128, 64));
+        assert_eq!((sprite.x, sprite.y), (0, 32));
+    }
+
+    #[test]
+    fn test_undecodable_thumbnail_reported() {
+        let thumbs = vec![
+            ("good.svs".to_string(), jpeg(32, 32)),
+            ("bad.svs".to_string(), Bytes::from_static(b"not a jpeg")),
+        ];
+        let sheet = compose_sprite_sheet(
+            &thumbs,
+            vec!["missing.svs".to_string()],
+            32,
+            80,
+            ResampleFilter::default(),
+        )
+        .unwrap();
+
+        assert_eq!(sheet.sprites.len(), 1);
+        assert_eq!(
+            sheet.failed,
+            vec!["missing.svs".to_string(), "bad.svs".to_string()]
+        );
+    }
+}
@@ -0,0 +1,311 @@
+//! Tile transformer hook for external inference services.
+//!
+//! A [`TileTransformer`] receives an encoded tile after it leaves the tile
+//! pipeline and may replace it (e.g. with a composited heatmap overlay) and/or
+//! attach results as response headers (e.g. a tumor probability score). This
+//! enables "AI-assist" viewing modes without running a second proxy in front
+//! of the server.
+//!
+//! Transformed tiles are never stored in the tile cache; the inference service
+//! is responsible for caching its own results if needed.
+//!
+//! # HTTP Sidecar
+//!
+//! With the `inference` feature enabled, [`HttpTileTransformer`] forwards each
+//! tile to an HTTP endpoint:
+//!
+//! - Request: `POST {endpoint}` with the JPEG tile as body and the tile
+//!   coordinates in `X-Slide-Id`, `X-Tile-Level`, `X-Tile-X`, `X-Tile-Y`
+//! - Response `image/*`: replaces the tile (composited overlay)
+//! - Response `application/json`: top-level scalar fields become
+//!   `X-Inference-{Field}` response headers
+//! - Response headers starting with `X-Inference-` are always forwarded
+//!
+//! Other transports (e.g. gRPC) can be integrated by implementing the trait.
+
+use async_trait::async_trait;
+use bytes::Bytes;
+
+use crate::error::TileError;
+
+// =============================================================================
+// Transformer Trait
+// =============================================================================
+
+/// Identifies the tile being transformed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TileContext {
+    /// Slide identifier
+    pub slide_id: String,
+
+    /// Pyramid level
+    pub level: usize,
+
+    /// Tile X coordinate
+    pub tile_x: u32,
+
+    /// Tile Y coordinate
+    pub tile_y: u32,
+}
+
+/// Result of a tile transformation.
+#[derive(Debug, Clone, Default)]
+pub struct TransformOutput {
+    /// Replacement tile data (None keeps the original tile)
+    pub data: Option<Bytes>,
+
+    /// Content type of the replacement data (defaults to `image/jpeg`)
+    pub content_type: Option<String>,
+
+    /// Extra response headers, e.g. inference scores
+    pub headers: Vec<(String, String)>,
+}
+
+/// Integration point for forwarding tiles to an external service.
+#[async_trait]
+pub trait TileTransformer: Send + Sync {
+    /// Transform an encoded tile.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TileError::TransformError`] if the external service fails;
+    /// this is surfaced to clients as `502 Bad Gateway`.
+    async fn transform(
+        &self,
+        context: &TileContext,
+        tile: Bytes,
+    ) -> Result<TransformOutput, TileError>;
+}
+
+// =============================================================================
+// Score Headers
+// =============================================================================
+
+/// Prefix for headers carrying inference results.
+pub const INFERENCE_HEADER_PREFIX: &str = "X-Inference-";
+
+/// Convert a JSON object of inference results into response headers.
+///
+/// Only top-level strings, numbers and booleans are converted; nested values
+/// are skipped. Field names are normalized to header-safe `Title-Case`, so
+/// `tumor_score` becomes `X-Inference-Tumor-Score`.
+pub fn inference_headers(value: &serde_json::Value) -> Vec<(String, String)> {
+    let Some(object) = value.as_object() else {
+        return Vec::new();
+    };
+
+    object
+        .iter()
+        .filter_map(|(key, value)| {
+            let value = match value {
+                serde_json::Value::String(s) => s.clone(),
+                serde_json::Value::Number(n) => n.to_string(),
+                serde_json::Value::Bool(b) => b.to_string(),
+                _ => return None,
+            };
+            let name = header_name_for(key)?;
+            Some((format!("{}{}", INFERENCE_HEADER_PREFIX, name), value))
+        })
+        .collect()
+}
+
+/// Normalize a field name into a header name segment.
+fn header_name_for(key: &str) -> Option<String> {
+    let parts: Vec<String> = key
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            let first = chars.next().unwrap().to_ascii_uppercase();
+            std::iter::once(first)
+                .chain(chars.map(|c| c.to_ascii_lowercase()))
+                .collect()
+        })
+        .collect();
+
+    if parts.is_empty() {
+        None
+    } else {
+        Some(parts.join("-"))
+    }
+}
+
+// =============================================================================
+// HTTP Transformer
+// =============================================================================
+
+/// Tile transformer that forwards tiles to an HTTP inference sidecar.
+#[cfg(feature = "inference")]
+pub struct HttpTileTransformer {
+    /// HTTP client (connection pooled)
+    client: reqwest::Client,
+
+    /// Endpoint receiving tiles
+    endpoint: String,
+}
+
+#[cfg(feature = "inference")]
+impl HttpTileTransformer {
+    /// Create a transformer posting tiles to `endpoint`.
+    ///
+    /// # Arguments
+    ///
+    /// * `endpoint` - URL of the inference service
+    /// * `timeout` - Per-request timeout
+    pub fn new(
+        endpoint: impl Into<String>,
+        timeout: std::time::Duration,
+    ) -> Result<Self, TileError> {
+        let client = reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .map_err(|e| TileError::TransformError {
+                message: format!("Failed to create HTTP client: {}", e),
+            })?;
+
+        Ok(Self {
+            client,
+            endpoint: endpoint.into(),
+        })
+    }
+
+    /// Get the inference endpoint URL.
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+}
+
+#[cfg(feature = "inference")]
+#[async_trait]
+impl TileTransformer for HttpTileTransformer {
+    async fn transform(
+        &self,
+        context: &TileContext,
+        tile: Bytes,
+    ) -> Result<TransformOutput, TileError> {
+        let to_error = |e: reqwest::Error| TileError::TransformError {
+            message: e.to_string(),
+        };
+
+        let response = self
+            .client
+            .post(&self.endpoint)
+            .header(reqwest::header::CONTENT_TYPE, "image/jpeg")
+            .header(
+                "X-Slide-Id",
+                urlencoding::encode(&context.slide_id).into_owned(),
+            )
+            .header("X-Tile-Level", context.level.to_string())
+            .header("X-Tile-X", context.tile_x.to_string())
+            .header("X-Tile-Y", context.tile_y.to_string())
+            .body(tile)
+            .send()
+            .await
+            .map_err(to_error)?;
+
+        let status = response.status();
+        if !status.is_success() {
+            return Err(TileError::TransformError {
+                message: format!("Inference service returned {}", status),
+            });
+        }
+
+        let prefix = INFERENCE_HEADER_PREFIX.to_ascii_lowercase();
+        let mut headers: Vec<(String, String)> = response
+            .headers()
+            .iter()
+            .filter(|(name, _)| name.as_str().starts_with(prefix.as_str()))
+            .filter_map(|(name, value)| {
+                value
+                    .to_str()
+                    .ok()
+                    .map(|v| (name.as_str().to_string(), v.to_string()))
+            })
+            .collect();
+
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.to_string());
+
+        let body = response.bytes().await.map_err(to_error)?;
+
+        match content_type.as_deref() {
+            Some(ct) if ct.starts_with("image/") => Ok(TransformOutput {
+                data: Some(body),
+                content_type,
+                headers,
+            }),
+            Some(ct) if ct.starts_with("application/json") => {
+                let value: serde_json::Value =
+                    serde_json::from_slice(&body).map_err(|e| TileError::TransformError {
+                        message: format!("Invalid JSON from inference service: {}", e),
+                    })?;
+                headers.extend(inference_headers(&value));
+                Ok(TransformOutput {
+                    data: None,
+                    content_type: None,
+                    headers,
+                })
+            }
+            _ => Ok(TransformOutput {
+                data: None,
+                content_type: None,
+                headers,
+            }),
+        }
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inference_headers_from_json() {
+        let value = serde_json::json!({
+            "tumor_score": 0.93,
+            "label": "tumor",
+            "flagged": true,
+            "nested": {"ignored": 1},
+            "list": [1, 2, 3]
+        });
+
+        let mut headers = inference_headers(&value);
+        headers.sort();
+
+        assert_eq!(
+            headers,
+            vec![
+                ("X-Inference-Flagged".to_string(), "true".to_string()),
+                ("X-Inference-Label".to_string(), "tumor".to_string()),
+                ("X-Inference-Tumor-Score".to_string(), "0.93".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_inference_headers_non_object() {
+        assert!(inference_headers(&serde_json::json!([1, 2])).is_empty());
+        assert!(inference_headers(&serde_json::json!("score")).is_empty());
+    }
+
+    #[test]
+    fn test_header_name_normalization() {
+        assert_eq!(
+            header_name_for("tumor_score"),
+            Some("Tumor-Score".to_string())
+        );
+        assert_eq!(
+            header_name_for("ModelVersion"),
+            Some("Modelversion".to_string())
+        );
+        assert_eq!(header_name_for("a.b-c"), Some("A-B-C".to_string()));
+        assert_eq!(header_name_for("__"), None);
+    }
+}
@@ -0,0 +1,329 @@
+//! Burned-in tile watermarks.
+//!
+//! De-identified research exports need visible provenance marks that survive
+//! screenshots and re-hosting. A [`Watermark`] is a small stamp (a line of
+//! text or a PNG logo) blended into a corner of every outgoing tile at a
+//! configured opacity. Text is drawn with the 5x7 bitmap font of the
+//! snapshot scale bar, in white on a translucent black box so it stays
+//! legible over both glass and dense tissue.
+//!
+//! The stamp is rendered once when the watermark is created; applying it to
+//! a tile is a single alpha blend over the stamp's pixels.
+
+use std::fmt;
+use std::str::FromStr;
+
+use image::{DynamicImage, ImageFormat, Pixel, Rgba, RgbaImage};
+
+use crate::error::TileError;
+
+use super::snapshot::glyph;
+
+/// Default opacity of watermarks (0.0-1.0).
+pub const DEFAULT_WATERMARK_OPACITY: f32 = 0.5;
+
+/// Distance in pixels between the stamp and the tile edges.
+const MARGIN: u32 = 8;
+
+/// Size in pixels of one font pixel in text stamps.
+const TEXT_UNIT: u32 = 2;
+
+/// Alpha of the box behind text stamps.
+const TEXT_BACKGROUND_ALPHA: u8 = 128;
+
+/// Corner of a tile a watermark is placed in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WatermarkCorner {
+    /// Top-left corner
+    TopLeft,
+
+    /// Top-right corner
+    TopRight,
+
+    /// Bottom-left corner
+    BottomLeft,
+
+    /// Bottom-right corner (default)
+    #[default]
+    BottomRight,
+}
+
+impl WatermarkCorner {
+    /// All corners.
+    pub const ALL: [WatermarkCorner; 4] = [
+        WatermarkCorner::TopLeft,
+        WatermarkCorner::TopRight,
+        WatermarkCorner::BottomLeft,
+        WatermarkCorner::BottomRight,
+    ];
+
+    /// Name used in configuration.
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            WatermarkCorner::TopLeft => "top-left",
+            WatermarkCorner::TopRight => "top-right",
+            WatermarkCorner::BottomLeft => "bottom-left",
+            WatermarkCorner::BottomRight => "bottom-right",
+        }
+    }
+}
+
+impl fmt::Display for WatermarkCorner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for WatermarkCorner {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        WatermarkCorner::ALL
+            .into_iter()
+            .find(|corner| corner.as_str().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| {
+                format!(
+                    "Invalid watermark corner '{}'. Expected top-left, top-right, bottom-left or bottom-right",
+                    s
+                )
+            })
+    }
+}
+
+/// Stamp blended into a corner of outgoing tiles.
+#[derive(Debug, Clone)]
+pub struct Watermark {
+    /// Pre-rendered stamp; its alpha is scaled by `opacity` when blended
+    stamp: RgbaImage,
+
+    /// Opacity of the stamp (0.0-1.0)
+    opacity: f32,
+
+    /// Corner the stamp is placed in
+    corner: WatermarkCorner,
+}
+
+impl Watermark {
+    /// Create a watermark from a line of text.
+    ///
+    /// Lowercase letters are drawn in uppercase; characters outside the
+    /// bitmap font are left blank.
+    pub fn from_text(text: &str) -> Self {
+        let chars: Vec<char> = text.chars().collect();
+        let padding = 2 * TEXT_UNIT;
+        let text_width = (chars.len() as u32 * 6).saturating_sub(1) * TEXT_UNIT;
+        let width = text_width + 2 * padding;
+        let height = 7 * TEXT_UNIT + 2 * padding;
+
+        let mut stamp =
+            RgbaImage::from_pixel(width, height, Rgba([0, 0, 0, TEXT_BACKGROUND_ALPHA]));
+        for (i, &c) in chars.iter().enumerate() {
+            let x = padding + i as u32 * 6 * TEXT_UNIT;
+            for (row, bits) in glyph(c).iter().enumerate() {
+                for col in 0..5 {
+                    if bits & (0x10 >> col) == 0 {
+                        continue;
+                    }
+                    for dy in 0..TEXT_UNIT {
+                        for dx in 0..TEXT_UNIT {
+                            let px = x + col * TEXT_UNIT + dx;
+                            let py = padding + row as u32 * TEXT_UNIT + dy;
+                            stamp.put_pixel(px, py, Rgba([255, 255, 255, 255]));
+                        }
+                    }
+                }
+            }
+        }
+        Self::from_image(stamp)
+    }
+
+    /// Create a watermark from a PNG logo, keeping its transparency.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TileError::DecodeError`] if the data is not a valid PNG.
+    pub fn from_png(data: &[u8]) -> Result<Self, TileError> {
+        let logo = image::load_from_memory_with_format(data, ImageFormat::Png).map_err(|e| {
+            TileError::DecodeError {
+                message: format!("Invalid watermark PNG: {}", e),
+            }
+        })?;
+        Ok(Self::from_image(logo.into_rgba8()))
+    }
+
+    /// Create a watermark from a stamp image.
+    pub fn from_image(stamp: RgbaImage) -> Self {
+        Self {
+            stamp,
+            opacity: DEFAULT_WATERMARK_OPACITY,
+            corner: WatermarkCorner::default(),
+        }
+    }
+
+    /// Set the opacity, clamped to 0.0-1.0.
+    pub fn with_opacity(mut self, opacity: f32) -> Self {
+        self.opacity = opacity.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Set the corner the stamp is placed in.
+    pub fn with_corner(mut self, corner: WatermarkCorner) -> Self {
+        self.corner = corner;
+        self
+    }
+
+    /// Stamp size in pixels.
+    pub fn dimensions(&self) -> (u32, u32) {
+        self.stamp.dimensions()
+    }
+
+    /// Opacity of the stamp.
+    pub fn opacity(&self) -> f32 {
+        self.opacity
+    }
+
+    /// Corner the stamp is placed in.
+    pub fn corner(&self) -> WatermarkCorner {
+        self.corner
+    }
+
+    /// Position of the stamp's top-left pixel on a `width` x `height` tile.
+    ///
+    /// Stamps larger than the tile are clipped on the side away from their
+    /// corner.
+    fn origin(&self, width: u32, height: u32) -> (u32, u32) {
+        let (stamp_width, stamp_height) = self.stamp.dimensions();
+        let left = MARGIN.min(width.saturating_sub(stamp_width));
+        let top = MARGIN.min(height.saturating_sub(stamp_height));
+        let right = width.saturating_sub(stamp_width + MARGIN);
+        let bottom = height.saturating_sub(stamp_height + MARGIN);
+        match self.corner {
+            WatermarkCorner::TopLeft => (left, top),
+            WatermarkCorner::TopRight => (right, top),
+            WatermarkCorner::BottomLeft => (left, bottom),
+            WatermarkCorner::BottomRight => (right, bottom),
+        }
+    }
+
+    /// Blend the stamp into a tile.
+    ///
+    /// Grayscale tiles get the stamp's luminance and stay grayscale; other
+    /// layouts are converted to 8-bit RGB.
+    pub fn apply(&self, img: DynamicImage) -> DynamicImage {
+        let (x0, y0) = self.origin(img.width(), img.height());
+        let stamp_pixels = self.stamp.enumerate_pixels().filter_map(|(x, y, pixel)| {
+            let weight = pixel.0[3] as f32 / 255.0 * self.opacity;
+            (weight > 0.0).then_some((x0 + x, y0 + y, pixel, weight))
+        });
+        let blend = |dst: u8, src: u8, weight: f32| {
+            (dst as f32 * (1.0 - weight) + src as f32 * weight).round() as u8
+        };
+
+        match img {
+            DynamicImage::ImageLuma8(mut gray) => {
+                for (x, y, pixel, weight) in stamp_pixels {
+                    if x < gray.width() && y < gray.height() {
+                        let luma = pixel.to_luma().0[0];
+                        let value = &mut gray.get_pixel_mut(x, y).0[0];
+                        *value = blend(*value, luma, weight);
+                    }
+                }
+                DynamicImage::ImageLuma8(gray)
+            }
+            other => {
+                let mut rgb = other.into_rgb8();
+                for (x, y, pixel, weight) in stamp_pixels {
+                    if x < rgb.width() && y < rgb.height() {
+                        let target = rgb.get_pixel_mut(x, y);
+                        for (dst, &src) in target.0.iter_mut().zip(&pixel.0[..3]) {
+                            *dst = blend(*dst, src, weight);
+                        }
+                    }
+                }
+                DynamicImage::ImageRgb8(rgb)
+            }
+        }
+    }
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{GrayImage, Luma, Rgb, RgbImage};
+    use std::io::Cursor;
+
+    fn white_tile() -> DynamicImage {
+        DynamicImage::ImageRgb8(RgbImage::from_pixel(64, 64, Rgb([255, 255, 255])))
+    }
+
+    #[test]
+    fn test_corner_names() {
+        for corner in WatermarkCorner::ALL {
+            assert_eq!(corner.as_str().parse::<WatermarkCorner>(), Ok(corner));
+        }
+        assert_eq!(
+            "Top-Right".parse::<WatermarkCorner>(),
+            Ok(WatermarkCorner::TopRight)
+        );
+        assert!("center".parse::<WatermarkCorner>().is_err());
+    }
+
+    #[test]
+    fn test_text_stamp_in_corner() {
+        let watermark = Watermark::from_text("ab");
+        // Two glyphs of 5 pixels with a 1 pixel gap, plus padding
+        assert_eq!(watermark.dimensions(), (11 * 2 + 8, 7 * 2 + 8));
+
+        let tile = watermark.clone().with_opacity(1.0).apply(white_tile());
+        let tile = tile.as_rgb8().unwrap();
+        // Bottom-right by default: the far corner is untouched, the box is dark
+        assert_eq!(tile.get_pixel(0, 0).0, [255, 255, 255]);
+        assert_eq!(tile.get_pixel(63 - MARGIN, 63 - MARGIN).0, [127, 127, 127]);
+
+        let top_left = watermark
+            .with_corner(WatermarkCorner::TopLeft)
+            .with_opacity(0.0)
+            .apply(white_tile());
+        assert!(top_left
+            .as_rgb8()
+            .unwrap()
+            .pixels()
+            .all(|p| p.0 == [255; 3]));
+    }
+
+    #[test]
+    fn test_png_logo_keeps_transparency() {
+        let mut logo = RgbaImage::from_pixel(4, 4, Rgba([255, 0, 0, 0]));
+        logo.put_pixel(0, 0, Rgba([255, 0, 0, 255]));
+        let mut png = Cursor::new(Vec::new());
+        logo.write_to(&mut png, ImageFormat::Png).unwrap();
+
+        let watermark = Watermark::from_png(png.get_ref())
+            .unwrap()
+            .with_opacity(1.0)
+            .with_corner(WatermarkCorner::TopLeft);
+        let tile = watermark.apply(white_tile()).into_rgb8();
+        assert_eq!(tile.get_pixel(MARGIN, MARGIN).0, [255, 0, 0]);
+        assert_eq!(tile.get_pixel(MARGIN + 1, MARGIN).0, [255, 255, 255]);
+
+        assert!(Watermark::from_png(b"not a png").is_err());
+    }
+
+    #[test]
+    fn test_grayscale_and_small_tiles() {
+        let watermark = Watermark::from_text("WSI").with_opacity(1.0);
+        let gray = DynamicImage::ImageLuma8(GrayImage::from_pixel(64, 64, Luma([255])));
+        let stamped = watermark.apply(gray);
+        assert!(stamped.as_luma8().is_some());
+
+        // Stamps larger than the tile are clipped, not panicking
+        let tiny = DynamicImage::ImageRgb8(RgbImage::from_pixel(10, 6, Rgb([255, 255, 255])));
+        let stamped = watermark.apply(tiny).into_rgb8();
+        assert_eq!(stamped.dimensions(), (10, 6));
+        assert_eq!(stamped.get_pixel(0, 0).0, [127, 127, 127]);
+    }
+}
@@ -0,0 +1,221 @@
+//! Per-request phase timings.
+//!
+//! With `--server-timing`, each request runs inside a [`PhaseTimings`]
+//! scope, and the time spent in each phase of serving it (storage reads,
+//! IFD parsing, decoding, encoding) is returned in a `Server-Timing` header
+//! that browser dev tools display next to the request:
+//!
+//! ```text
+//! Server-Timing: storage_head;dur=41.2;desc="Storage metadata",
+//!     ifd_parse;dur=118.0;desc="IFD parse", storage_read;dur=97.5;desc="Storage reads",
+//!     tile_fetch;dur=12.3;desc="Tile fetch", decode;dur=3.1;desc="Decode",
+//!     encode;dur=2.4;desc="Encode", total;dur=180.6;desc="Total"
+//! ```
+//!
+//! Phases are recorded with [`measure`] and [`measure_sync`], which only
+//! time their work when a scope is active. The scope is task-local: work
+//! run on other tasks is only counted if the scope is carried over, as the
+//! encode pool does with [`current`] and [`sync_scope`].
+//!
+//! Phases that run more than once (a region reading several tiles) are
+//! summed, and `storage_read` overlaps the phases that triggered the reads,
+//! so the durations don't add up to the total.
+
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+tokio::task_local! {
+    static CURRENT: Arc<PhaseTimings>;
+}
+
+/// A timed phase of serving a request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    /// Storage metadata request (size and ETag) when opening a slide
+    StorageHead,
+
+    /// Format detection and TIFF directory parsing when opening a slide
+    IfdParse,
+
+    /// Range reads from storage (block cache misses)
+    StorageRead,
+
+    /// Reading compressed tile data
+    TileFetch,
+
+    /// Decoding source tiles
+    Decode,
+
+    /// Encoding output images
+    Encode,
+}
+
+impl Phase {
+    /// Metric name in the `Server-Timing` header.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Phase::StorageHead => "storage_head",
+            Phase::IfdParse => "ifd_parse",
+            Phase::StorageRead => "storage_read",
+            Phase::TileFetch => "tile_fetch",
+            Phase::Decode => "decode",
+            Phase::Encode => "encode",
+        }
+    }
+
+    /// Human-readable description.
+    pub const fn description(&self) -> &'static str {
+        match self {
+            Phase::StorageHead => "Storage metadata",
+            Phase::IfdParse => "IFD parse",
+            Phase::StorageRead => "Storage reads",
+            Phase::TileFetch => "Tile fetch",
+            Phase::Decode => "Decode",
+            Phase::Encode => "Encode",
+        }
+    }
+}
+
+/// Time spent in each phase of one request.
+#[derive(Debug, Default)]
+pub struct PhaseTimings {
+    /// Total time per phase, in order of first occurrence
+    phases: Mutex<Vec<(Phase, Duration)>>,
+}
+
+impl PhaseTimings {
+    /// Create an empty set of timings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add time spent in a phase.
+    pub fn record(&self, phase: Phase, elapsed: Duration) {
+        let mut phases = self.phases.lock().unwrap();
+        match phases.iter_mut().find(|(p, _)| *p == phase) {
+            Some((_, total)) => *total += elapsed,
+            None => phases.push((phase, elapsed)),
+        }
+    }
+
+    /// Total time recorded for a phase.
+    pub fn get(&self, phase: Phase) -> Option<Duration> {
+        let phases = self.phases.lock().unwrap();
+        phases.iter().find(|(p, _)| *p == phase).map(|(_, d)| *d)
+    }
+
+    /// Format the timings as a `Server-Timing` header value, ending with
+    /// `total` if given.
+    pub fn header_value(&self, total: Option<Duration>) -> String {
+        let metric = |name: &str, elapsed: Duration, description: &str| {
+            format!(
+                "{};dur={:.1};desc=\"{}\"",
+                name,
+                elapsed.as_secs_f64() * 1000.0,
+                description
+            )
+        };
+        let phases = self.phases.lock().unwrap();
+        phases
+            .iter()
+            .map(|(phase, elapsed)| metric(phase.name(), *elapsed, phase.description()))
+            .chain(total.map(|total| metric("total", total, "Total")))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Run a future with these timings as the current scope.
+    pub async fn scope<F: Future>(self: Arc<Self>, future: F) -> F::Output {
+        CURRENT.scope(self, future).await
+    }
+}
+
+/// Timings of the current scope, if any.
+pub fn current() -> Option<Arc<PhaseTimings>> {
+    CURRENT.try_with(Arc::clone).ok()
+}
+
+/// Run a closure in a scope carried over from another task.
+pub fn sync_scope<R>(timings: Option<Arc<PhaseTimings>>, f: impl FnOnce() -> R) -> R {
+    match timings {
+        Some(timings) => CURRENT.sync_scope(timings, f),
+        None => f(),
+    }
+}
+
+/// Add time spent in a phase to the current scope, if any.
+pub fn record(phase: Phase, elapsed: Duration) {
+    let _ = CURRENT.try_with(|timings| timings.record(phase, elapsed));
+}
+
+/// Await a future, timing it as `phase` if a scope is active.
+pub async fn measure<F: Future>(phase: Phase, future: F) -> F::Output {
+    if CURRENT.try_with(|_| ()).is_err() {
+        return future.await;
+    }
+    let started = Instant::now();
+    let output = future.await;
+    record(phase, started.elapsed());
+    output
+}
+
+/// Run a closure, timing it as `phase` if a scope is active.
+pub fn measure_sync<R>(phase: Phase, f: impl FnOnce() -> R) -> R {
+    if CURRENT.try_with(|_| ()).is_err() {
+        return f();
+    }
+    let started = Instant::now();
+    let output = f();
+    record(phase, started.elapsed());
+    output
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_phases_recorded_in_scope() {
+        // Outside a scope nothing is recorded
+        assert!(current().is_none());
+        measure_sync(Phase::Decode, || ());
+
+        let timings = Arc::new(PhaseTimings::new());
+        Arc::clone(&timings)
+            .scope(async {
+                record(Phase::Decode, Duration::from_millis(2));
+                measure(Phase::TileFetch, async {}).await;
+
+                // Carried over to a blocking thread
+                let scope = current();
+                tokio::task::spawn_blocking(move || {
+                    sync_scope(scope, || record(Phase::Decode, Duration::from_millis(3)))
+                })
+                .await
+                .unwrap();
+            })
+            .await;
+
+        assert_eq!(timings.get(Phase::Decode), Some(Duration::from_millis(5)));
+        assert!(timings.get(Phase::TileFetch).is_some());
+        assert_eq!(timings.get(Phase::Encode), None);
+    }
+
+    #[test]
+    fn test_header_value() {
+        let timings = PhaseTimings::new();
+        timings.record(Phase::StorageHead, Duration::from_micros(41_300));
+        timings.record(Phase::Encode, Duration::from_millis(2));
+        assert_eq!(
+            timings.header_value(Some(Duration::from_millis(50))),
+            "storage_head;dur=41.3;desc=\"Storage metadata\", \
+             encode;dur=2.0;desc=\"Encode\", total;dur=50.0;desc=\"Total\""
+        );
+        assert_eq!(PhaseTimings::new().header_value(None), "");
+    }
+}
@@ -8,6 +8,7 @@
 //! - TIFF parser edge cases (endianness, BigTIFF)
 //! - SVS JPEGTables handling
 //! - Block cache effectiveness
+//! - OpenAPI spec and client signing helpers
 
 mod integration {
     pub mod test_utils;
@@ -15,6 +16,7 @@ mod integration {
     pub mod api_tests;
     pub mod auth_tests;
     pub mod cache_tests;
+    pub mod clients_tests;
     pub mod format_tests;
     pub mod slides_tests;
 }
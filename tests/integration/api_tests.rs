@@ -6,16 +6,23 @@
 //! - HTTP response codes and headers
 
 use axum::body::Body;
+use axum::extract::ConnectInfo;
 use axum::http::{Request, StatusCode};
 use http_body_util::BodyExt;
 use tower::ServiceExt;
 
-use wsi_streamer::slide::SlideRegistry;
-use wsi_streamer::tile::TileService;
-use wsi_streamer::{create_router, RouterConfig};
+use wsi_streamer::server::{ConnectionTracker, RateLimitConfig, RateLimitKey, SignedUrlAuth};
+use wsi_streamer::slide::{
+    AdaptiveCacheConfig, ManifestBuilder, MemorySlideSource, SlideRegistry, SparseTiles,
+};
+use wsi_streamer::tile::{
+    FairScheduler, TenantQuota, TileContext, TileService, TileTransformer, TransformOutput,
+};
+use wsi_streamer::{create_router, create_split_routers, RouterConfig};
 
 use super::test_utils::{
-    create_strip_tiff, create_tiff_with_jpeg_tile, create_tiff_with_lzw_compression, is_valid_jpeg,
+    create_calibrated_tiff, create_strip_tiff, create_tiff_with_deflate_tile,
+    create_tiff_with_jpeg_tile, create_tiff_with_unsupported_compression, is_valid_jpeg,
     MockSlideSource,
 };
 
@@ -126,6 +133,49 @@ async fn test_tile_retrieval_without_jpg_extension() {
     );
 }
 
+#[tokio::test]
+async fn test_tile_retrieval_png_and_webp() {
+    let tiff_data = create_tiff_with_jpeg_tile();
+    let source = MockSlideSource::new().with_slide("test.tif", tiff_data);
+    let registry = SlideRegistry::new(source);
+    let tile_service = TileService::new(registry);
+    let router = create_router(tile_service, RouterConfig::without_auth());
+
+    let get = |uri: &str| Request::builder().uri(uri).body(Body::empty()).unwrap();
+
+    let response = router
+        .clone()
+        .oneshot(get("/tiles/test.tif/0/0/0.png"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "image/png");
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(&body[..4], b"\x89PNG");
+
+    // The format query parameter overrides the extension, and each format
+    // is cached separately
+    let response = router
+        .clone()
+        .oneshot(get("/tiles/test.tif/0/0/0.jpg?format=png"))
+        .await
+        .unwrap();
+    assert_eq!(response.headers()["content-type"], "image/png");
+    assert_eq!(response.headers()["x-tile-cache-hit"], "true");
+
+    let response = router
+        .oneshot(get("/tiles/test.tif/0/0/0.webp"))
+        .await
+        .unwrap();
+    if cfg!(feature = "webp") {
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["content-type"], "image/webp");
+        assert_eq!(response.headers()["x-tile-cache-hit"], "false");
+    } else {
+        assert_eq!(response.status(), StatusCode::NOT_ACCEPTABLE);
+    }
+}
+
 #[tokio::test]
 async fn test_cache_hit_header() {
     let tiff_data = create_tiff_with_jpeg_tile();
@@ -158,6 +208,263 @@ async fn test_cache_hit_header() {
     assert_eq!(response2.headers().get("x-tile-cache-hit").unwrap(), "true");
 }
 
+#[tokio::test]
+async fn test_request_id_header() {
+    let tiff_data = create_tiff_with_jpeg_tile();
+    let source = MockSlideSource::new().with_slide("test.tif", tiff_data);
+    let registry = SlideRegistry::new(source);
+    let tile_service = TileService::new(registry);
+    let router = create_router(tile_service, RouterConfig::without_auth());
+
+    // A client-supplied ID is echoed back
+    let request = Request::builder()
+        .uri("/tiles/test.tif/0/0/0.jpg")
+        .header("X-Request-Id", "viewer-7f3a")
+        .body(Body::empty())
+        .unwrap();
+    let response = router.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["x-request-id"], "viewer-7f3a");
+
+    // Error responses get a generated one
+    let request = Request::builder()
+        .uri("/tiles/missing.tif/0/0/0.jpg")
+        .body(Body::empty())
+        .unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert!(!response.headers()["x-request-id"].is_empty());
+}
+
+#[tokio::test]
+async fn test_server_timing_header() {
+    let source = MockSlideSource::new().with_slide("deflate.tif", create_tiff_with_deflate_tile());
+    let registry = SlideRegistry::new(source);
+    let tile_service = TileService::new(registry);
+    let router = create_router(
+        tile_service,
+        RouterConfig::without_auth().with_server_timing(true),
+    );
+
+    let request = Request::builder()
+        .uri("/tiles/deflate.tif/0/0/0.jpg")
+        .body(Body::empty())
+        .unwrap();
+    let response = router.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // The first request opens the slide and re-encodes the tile
+    let timing = response.headers()["server-timing"].to_str().unwrap();
+    for phase in [
+        "storage_head",
+        "ifd_parse",
+        "storage_read",
+        "tile_fetch",
+        "decode",
+        "encode",
+        "total",
+    ] {
+        assert!(timing.contains(&format!("{};dur=", phase)), "{}", timing);
+    }
+
+    // A cached tile only reports the total
+    let request = Request::builder()
+        .uri("/tiles/deflate.tif/0/0/0.jpg")
+        .body(Body::empty())
+        .unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    let timing = response.headers()["server-timing"].to_str().unwrap();
+    assert!(timing.starts_with("total;dur="), "{}", timing);
+
+    // Off by default
+    let source = MockSlideSource::new().with_slide("deflate.tif", create_tiff_with_deflate_tile());
+    let tile_service = TileService::new(SlideRegistry::new(source));
+    let router = create_router(tile_service, RouterConfig::without_auth());
+    let request = Request::builder()
+        .uri("/tiles/deflate.tif/0/0/0.jpg")
+        .body(Body::empty())
+        .unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    assert!(!response.headers().contains_key("server-timing"));
+}
+
+// =============================================================================
+// Conditional Requests
+// =============================================================================
+
+#[tokio::test]
+async fn test_tile_etag_and_not_modified() {
+    let source = MockSlideSource::new().with_slide("test.tif", create_tiff_with_jpeg_tile());
+    let registry = SlideRegistry::new(source);
+    let router = create_router(TileService::new(registry), RouterConfig::without_auth());
+
+    let get = |uri: &str| Request::builder().uri(uri).body(Body::empty()).unwrap();
+
+    let response = router
+        .clone()
+        .oneshot(get("/tiles/test.tif/0/0/0.jpg"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let etag = response.headers()["etag"].to_str().unwrap().to_string();
+    assert!(etag.starts_with('"') && etag.ends_with('"'));
+
+    // Revalidating with the ETag skips the tile
+    let request = Request::builder()
+        .uri("/tiles/test.tif/0/0/0.jpg")
+        .header("if-none-match", &etag)
+        .body(Body::empty())
+        .unwrap();
+    let response = router.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+    assert_eq!(response.headers()["etag"], etag.as_str());
+    assert!(response.headers().contains_key("cache-control"));
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    assert!(body.is_empty());
+
+    // A stale ETag gets the tile
+    let request = Request::builder()
+        .uri("/tiles/test.tif/0/0/0.jpg")
+        .header("if-none-match", "\"stale\"")
+        .body(Body::empty())
+        .unwrap();
+    let response = router.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // Encoding parameters and coordinates are part of the ETag
+    for uri in [
+        "/tiles/test.tif/0/0/0.jpg?quality=50",
+        "/tiles/test.tif/0/0/0.png",
+        "/tiles/test.tif/0/1/0.jpg",
+    ] {
+        let response = router.clone().oneshot(get(uri)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK, "{}", uri);
+        assert_ne!(response.headers()["etag"], etag.as_str(), "{}", uri);
+    }
+}
+
+#[tokio::test]
+async fn test_thumbnail_jpg_etag_and_not_modified() {
+    let source = MockSlideSource::new().with_slide("test.tif", create_tiff_with_jpeg_tile());
+    let registry = SlideRegistry::new(source);
+    let router = create_router(TileService::new(registry), RouterConfig::without_auth());
+
+    let get = |uri: &str| Request::builder().uri(uri).body(Body::empty()).unwrap();
+
+    let response = router
+        .clone()
+        .oneshot(get("/slides/test.tif/thumbnail.jpg?max=128&quality=70"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "image/jpeg");
+    assert_eq!(response.headers()["x-tile-quality"], "70");
+    let etag = response.headers()["etag"].to_str().unwrap().to_string();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let image = image::load_from_memory(&body).unwrap();
+    assert_eq!(image.width().max(image.height()), 128);
+
+    // `max` is an alias of `max_size` on both paths
+    let response = router
+        .clone()
+        .oneshot(get("/slides/test.tif/thumbnail?max_size=128&quality=70"))
+        .await
+        .unwrap();
+    assert_eq!(response.headers()["etag"], etag.as_str());
+
+    let request = Request::builder()
+        .uri("/slides/test.tif/thumbnail.jpg?max=128&quality=70")
+        .header("if-none-match", &etag)
+        .body(Body::empty())
+        .unwrap();
+    let response = router.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+    assert!(response.headers().contains_key("cache-control"));
+
+    // Size and quality are part of the ETag
+    for uri in [
+        "/slides/test.tif/thumbnail.jpg?max=256&quality=70",
+        "/slides/test.tif/thumbnail.jpg?max=128",
+    ] {
+        let response = router.clone().oneshot(get(uri)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK, "{}", uri);
+        assert_ne!(response.headers()["etag"], etag.as_str(), "{}", uri);
+    }
+}
+
+// =============================================================================
+// Raw Slide Bytes
+// =============================================================================
+
+#[tokio::test]
+async fn test_raw_slide_ranges() {
+    let data = create_tiff_with_jpeg_tile();
+    let size = data.len();
+    let source = MockSlideSource::new().with_slide("test.tif", data.clone());
+    let registry = SlideRegistry::new(source);
+    let router = create_router(TileService::new(registry), RouterConfig::without_auth());
+
+    let get = |range: Option<&str>| {
+        let mut builder = Request::builder().uri("/slides/test.tif/raw");
+        if let Some(range) = range {
+            builder = builder.header("range", range);
+        }
+        builder.body(Body::empty()).unwrap()
+    };
+
+    // Whole file
+    let response = router.clone().oneshot(get(None)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["accept-ranges"], "bytes");
+    assert_eq!(
+        response.headers()["content-length"],
+        size.to_string().as_str()
+    );
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(&body[..], &data[..]);
+
+    // A single range
+    let response = router
+        .clone()
+        .oneshot(get(Some("bytes=4-11")))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+    assert_eq!(
+        response.headers()["content-range"],
+        format!("bytes 4-11/{}", size).as_str()
+    );
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(&body[..], &data[4..12]);
+
+    // Suffix range
+    let response = router
+        .clone()
+        .oneshot(get(Some("bytes=-16")))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(&body[..], &data[size - 16..]);
+
+    // Past the end of the file
+    let range = format!("bytes={}-", size);
+    let response = router.clone().oneshot(get(Some(&range))).await.unwrap();
+    assert_eq!(response.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+    assert_eq!(
+        response.headers()["content-range"],
+        format!("bytes */{}", size).as_str()
+    );
+
+    // Missing slide
+    let request = Request::builder()
+        .uri("/slides/missing.tif/raw")
+        .body(Body::empty())
+        .unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
 // =============================================================================
 // Error Cases - Missing Slide
 // =============================================================================
@@ -231,20 +538,46 @@ async fn test_tile_out_of_bounds() {
     assert_eq!(error["error"], "tile_out_of_bounds");
 }
 
+#[tokio::test]
+async fn test_deflate_tile_reencoded_as_jpeg() {
+    let tiff_data = create_tiff_with_deflate_tile();
+    let source = MockSlideSource::new().with_slide("deflate.tif", tiff_data);
+    let registry = SlideRegistry::new(source);
+    let tile_service = TileService::new(registry);
+    let router = create_router(tile_service, RouterConfig::without_auth());
+
+    let request = Request::builder()
+        .uri("/tiles/deflate.tif/0/0/0.jpg")
+        .body(Body::empty())
+        .unwrap();
+
+    let response = router.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    assert!(is_valid_jpeg(&body));
+
+    let image = image::load_from_memory(&body).unwrap().into_luma8();
+    assert_eq!(image.dimensions(), (256, 256));
+    // Gradient survives decoding (allowing for JPEG loss)
+    assert!(image.get_pixel(10, 0).0[0] < 40);
+    assert!(image.get_pixel(240, 0).0[0] > 200);
+}
+
 // =============================================================================
 // Error Cases - Unsupported Format
 // =============================================================================
 
 #[tokio::test]
-async fn test_unsupported_compression_lzw() {
-    let tiff_data = create_tiff_with_lzw_compression();
-    let source = MockSlideSource::new().with_slide("lzw.tif", tiff_data);
+async fn test_unsupported_compression_old_jpeg() {
+    let tiff_data = create_tiff_with_unsupported_compression();
+    let source = MockSlideSource::new().with_slide("ojpeg.tif", tiff_data);
     let registry = SlideRegistry::new(source);
     let tile_service = TileService::new(registry);
     let router = create_router(tile_service, RouterConfig::without_auth());
 
     let request = Request::builder()
-        .uri("/tiles/lzw.tif/0/0/0.jpg")
+        .uri("/tiles/ojpeg.tif/0/0/0.jpg")
         .body(Body::empty())
         .unwrap();
 
@@ -255,6 +588,16 @@ async fn test_unsupported_compression_lzw() {
     let error: serde_json::Value = serde_json::from_slice(&body).unwrap();
     assert_eq!(error["error"], "unsupported_format");
     assert!(error["message"].as_str().unwrap().contains("compression"));
+
+    // Every failed constraint is listed with a remediation hint
+    let violation = &error["violations"][0];
+    assert_eq!(violation["constraint"], "unsupported_compression");
+    assert_eq!(violation["compression"], 6);
+    assert_eq!(violation["compression_name"], "Old JPEG");
+    assert!(violation["remediation"]
+        .as_str()
+        .unwrap()
+        .contains("--compression jpeg"));
 }
 
 #[tokio::test]
@@ -297,6 +640,268 @@ async fn test_unsupported_strip_organization() {
     );
 }
 
+#[tokio::test]
+async fn test_sparse_tile_policies() {
+    // Tile (1, 0) was never written: its byte count is 0
+    let mut tiff_data = create_tiff_with_jpeg_tile();
+    tiff_data[404..408].copy_from_slice(&0u32.to_le_bytes());
+
+    let get = |policy: SparseTiles| {
+        let source = MockSlideSource::new().with_slide("sparse.tif", tiff_data.clone());
+        let registry = SlideRegistry::new(source).with_sparse_tiles(policy);
+        let router = create_router(TileService::new(registry), RouterConfig::without_auth());
+        let request = Request::builder()
+            .uri("/tiles/sparse.tif/0/1/0.jpg")
+            .body(Body::empty())
+            .unwrap();
+        async move { router.oneshot(request).await.unwrap() }
+    };
+
+    // Served as a background tile by default
+    let response = get(SparseTiles::default()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let tile = image::load_from_memory(&body).unwrap().into_rgb8();
+    assert!(tile.pixels().all(|p| p.0.iter().all(|&c| c >= 250)));
+
+    let response = get(SparseTiles::Error).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let error: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(error["error"], "missing_tile");
+}
+
+#[tokio::test]
+async fn test_tile_grid_endpoint() {
+    // Tile (1, 0) was never written: its byte count is 0
+    let mut tiff_data = create_tiff_with_jpeg_tile();
+    tiff_data[404..408].copy_from_slice(&0u32.to_le_bytes());
+
+    let source = MockSlideSource::new().with_slide("sparse.tif", tiff_data);
+    let router = create_router(
+        TileService::new(SlideRegistry::new(source)),
+        RouterConfig::without_auth(),
+    );
+    let get = |uri: &str| {
+        let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+        let router = router.clone();
+        async move { router.oneshot(request).await.unwrap() }
+    };
+
+    let response = get("/slides/sparse.tif/levels/0/grid").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let grid: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(grid["tiles_x"], 8);
+    assert_eq!(grid["tiles_y"], 6);
+    assert_eq!(grid["present"], 47);
+    assert_eq!(grid["missing"], 1);
+    assert_eq!(grid["bitmap"], "bfffffffffff");
+    let byte_counts = grid["byte_counts"].as_array().unwrap();
+    assert_eq!(byte_counts.len(), 48);
+    assert_eq!(byte_counts[1], 0);
+    assert!(byte_counts[0].as_u64().unwrap() > 0);
+
+    let response = get("/slides/sparse.tif/levels/0/grid?sizes=false").await;
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let grid: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert!(grid.get("byte_counts").is_none());
+
+    let response = get("/slides/sparse.tif/levels/9/grid").await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_tissue_mask_endpoint() {
+    let get = |enabled: bool| {
+        let source = MockSlideSource::new().with_slide("test.tif", create_tiff_with_jpeg_tile());
+        let tile_service = TileService::new(SlideRegistry::new(source)).with_tissue_masks(enabled);
+        let router = create_router(tile_service, RouterConfig::without_auth());
+        let request = Request::builder()
+            .uri("/slides/test.tif/mask.png")
+            .body(Body::empty())
+            .unwrap();
+        async move { router.oneshot(request).await.unwrap() }
+    };
+
+    // Disabled by default
+    let response = get(false).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let response = get(true).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "image/png");
+    let fraction: f64 = response.headers()["x-tissue-fraction"]
+        .to_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert!((0.0..=1.0).contains(&fraction));
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let mask = image::load_from_memory(&body).unwrap();
+    assert!(mask.width() > 0 && mask.height() > 0);
+}
+
+#[tokio::test]
+async fn test_tile_tone_adjustment() {
+    let source = MockSlideSource::new().with_slide("test.tif", create_tiff_with_jpeg_tile());
+    let router = create_router(
+        TileService::new(SlideRegistry::new(source)),
+        RouterConfig::without_auth(),
+    );
+    let get = |uri: &str| {
+        let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+        let router = router.clone();
+        async move { router.oneshot(request).await.unwrap() }
+    };
+    let mean = |body: &[u8]| {
+        let tile = image::load_from_memory(body).unwrap().into_luma8();
+        tile.pixels().map(|p| p.0[0] as f64).sum::<f64>() / (tile.len() as f64)
+    };
+
+    let plain = get("/tiles/test.tif/0/0/0.jpg").await;
+    let plain_etag = plain.headers()["etag"].clone();
+    let plain = plain.into_body().collect().await.unwrap().to_bytes();
+
+    let brighter = get("/tiles/test.tif/0/0/0.jpg?brightness=0.3&gamma=1.5").await;
+    assert_eq!(brighter.status(), StatusCode::OK);
+    assert_eq!(brighter.headers()["x-tile-cache-hit"], "false");
+    assert_ne!(brighter.headers()["etag"], plain_etag);
+    let brighter = brighter.into_body().collect().await.unwrap().to_bytes();
+    assert!(mean(&brighter) > mean(&plain) + 40.0);
+
+    // Cached under its own key
+    let again = get("/tiles/test.tif/0/0/0.jpg?gamma=1.5&brightness=0.3").await;
+    assert_eq!(again.headers()["x-tile-cache-hit"], "true");
+
+    // Neutral values share the unadjusted tile
+    let neutral = get("/tiles/test.tif/0/0/0.jpg?contrast=1&gamma=1").await;
+    assert_eq!(neutral.headers()["etag"], plain_etag);
+    assert_eq!(neutral.headers()["x-tile-cache-hit"], "true");
+
+    for uri in [
+        "/tiles/test.tif/0/0/0.jpg?gamma=0",
+        "/tiles/test.tif/0/0/0.jpg?brightness=-2",
+        "/tiles/test.tif/0/0/0.jpg?contrast=5",
+    ] {
+        let response = get(uri).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{}", uri);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let error: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(error["error"], "invalid_adjustment");
+    }
+}
+
+#[tokio::test]
+async fn test_tile_channel_selection() {
+    let source = MockSlideSource::new().with_slide("test.tif", create_tiff_with_jpeg_tile());
+    let router = create_router(
+        TileService::new(SlideRegistry::new(source)),
+        RouterConfig::without_auth(),
+    );
+    let get = |uri: &str| {
+        let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+        let router = router.clone();
+        async move { router.oneshot(request).await.unwrap() }
+    };
+
+    let plain = get("/tiles/test.tif/0/0/0.jpg").await;
+    let plain_etag = plain.headers()["etag"].clone();
+
+    let mut etags = Vec::new();
+    for channel in ["gray", "r", "g", "b"] {
+        let response = get(&format!("/tiles/test.tif/0/0/0.jpg?channel={}", channel)).await;
+        assert_eq!(response.status(), StatusCode::OK, "{}", channel);
+        assert_eq!(response.headers()["x-tile-cache-hit"], "false");
+        etags.push(response.headers()["etag"].clone());
+
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let tile = image::load_from_memory(&body).unwrap();
+        assert_eq!(tile.color(), image::ColorType::L8, "{}", channel);
+    }
+    assert!(!etags.contains(&plain_etag));
+    etags.dedup();
+    assert_eq!(etags.len(), 4);
+
+    // Combines with tone adjustment and is cached under its own key
+    let adjusted = get("/tiles/test.tif/0/0/0.jpg?channel=g&gamma=2").await;
+    assert_eq!(adjusted.status(), StatusCode::OK);
+    assert_eq!(adjusted.headers()["x-tile-cache-hit"], "false");
+    let again = get("/tiles/test.tif/0/0/0.jpg?channel=g").await;
+    assert_eq!(again.headers()["x-tile-cache-hit"], "true");
+
+    let response = get("/tiles/test.tif/0/0/0.jpg?channel=alpha").await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_annotation_overlay() {
+    let source = MockSlideSource::new().with_slide("test.tif", create_tiff_with_jpeg_tile());
+    let router = create_router(
+        TileService::new(SlideRegistry::new(source)),
+        RouterConfig::without_auth(),
+    );
+    let send = |method: &str, uri: &str, body: &'static str| {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("content-type", "application/geo+json")
+            .body(Body::from(body))
+            .unwrap();
+        let router = router.clone();
+        async move { router.oneshot(request).await.unwrap() }
+    };
+    let red_point = r##"{"type": "Feature", "properties": {"color": "#ff0000"},
+        "geometry": {"type": "Point", "coordinates": [300, 100]}}"##;
+
+    let response = send("GET", "/slides/test.tif/annotations", "").await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let response = send("POST", "/slides/test.tif/annotations", "{\"type\": 1}").await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let response = send("POST", "/slides/missing.tif/annotations", red_point).await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let response = send("POST", "/slides/test.tif/annotations", red_point).await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["features"], 1);
+    assert_eq!(json["shapes"], 1);
+
+    let response = send("GET", "/slides/test.tif/annotations", "").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "application/geo+json");
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(&body[..], red_point.as_bytes());
+
+    // The point at (300, 100) lands at (44, 100) in tile (1, 0)
+    let plain = send("GET", "/tiles/test.tif/0/1/0.jpg", "").await;
+    let plain_etag = plain.headers()["etag"].clone();
+    let overlaid = send("GET", "/tiles/test.tif/0/1/0.jpg?overlay=annotations", "").await;
+    assert_eq!(overlaid.status(), StatusCode::OK);
+    let overlay_etag = overlaid.headers()["etag"].clone();
+    assert_ne!(overlay_etag, plain_etag);
+    let body = overlaid.into_body().collect().await.unwrap().to_bytes();
+    let tile = image::load_from_memory(&body).unwrap().into_rgb8();
+    let [r, g, b] = tile.get_pixel(44, 100).0;
+    assert!(r > 200 && g < 60 && b < 60, "{:?}", [r, g, b]);
+
+    // Replacing the annotations changes the tile's ETag and cache entry
+    let response = send(
+        "POST",
+        "/slides/test.tif/annotations",
+        r#"{"type": "Point", "coordinates": [10, 10]}"#,
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let response = send("GET", "/tiles/test.tif/0/1/0.jpg?overlay=annotations", "").await;
+    assert_ne!(response.headers()["etag"], overlay_etag);
+    assert_eq!(response.headers()["x-tile-cache-hit"], "false");
+
+    let response = send("GET", "/tiles/test.tif/0/1/0.jpg?overlay=heatmap", "").await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
 // =============================================================================
 // Health Endpoint
 // =============================================================================
@@ -322,39 +927,809 @@ async fn test_health_endpoint() {
     assert!(health["version"].is_string());
 }
 
-// =============================================================================
-// Multiple Tiles from Same Slide
-// =============================================================================
-
 #[tokio::test]
-async fn test_multiple_tiles_same_slide() {
-    let tiff_data = create_tiff_with_jpeg_tile();
-    let source = MockSlideSource::new().with_slide("test.tif", tiff_data);
+async fn test_livez_endpoint() {
+    let source = MockSlideSource::new().unreachable();
     let registry = SlideRegistry::new(source);
     let tile_service = TileService::new(registry);
     let router = create_router(tile_service, RouterConfig::without_auth());
 
-    // Request multiple different tiles
-    let tiles = [(0, 0), (1, 0), (0, 1), (1, 1)];
+    // Liveness doesn't depend on storage
+    let request = Request::builder()
+        .uri("/livez")
+        .body(Body::empty())
+        .unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["cache-control"], "no-store");
+}
 
-    for (x, y) in tiles {
-        let request = Request::builder()
-            .uri(format!("/tiles/test.tif/0/{}/{}.jpg", x, y))
-            .body(Body::empty())
-            .unwrap();
+#[tokio::test]
+async fn test_readyz_endpoint() {
+    let source = MockSlideSource::new().with_slide("test.tif", create_tiff_with_jpeg_tile());
+    let registry = SlideRegistry::new(source);
+    let tile_service = TileService::new(registry);
+    let router = create_router(tile_service, RouterConfig::without_auth());
 
-        let response = router.clone().oneshot(request).await.unwrap();
-        assert_eq!(
-            response.status(),
-            StatusCode::OK,
-            "Tile ({}, {}) should succeed",
-            x,
-            y
-        );
+    let request = Request::builder()
+        .uri("/tiles/test.tif/0/0/0.jpg")
+        .body(Body::empty())
+        .unwrap();
+    let response = router.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
 
-        let body = response.into_body().collect().await.unwrap().to_bytes();
-        assert!(
-            is_valid_jpeg(&body),
+    let request = Request::builder()
+        .uri("/readyz")
+        .body(Body::empty())
+        .unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let ready: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(ready["status"], "ready");
+    assert_eq!(ready["storage"]["reachable"], true);
+    assert_eq!(ready["cache"]["slides"], 1);
+    assert_eq!(ready["cache"]["tiles"], 1);
+    assert!(ready["version"].is_string());
+    assert!(ready["features"].is_array());
+}
+
+#[tokio::test]
+async fn test_readyz_reports_unreachable_storage() {
+    let source = MockSlideSource::new().unreachable();
+    let registry = SlideRegistry::new(source);
+    let tile_service = TileService::new(registry);
+    let router = create_router(tile_service, RouterConfig::without_auth());
+
+    let request = Request::builder()
+        .uri("/readyz")
+        .body(Body::empty())
+        .unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let ready: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(ready["status"], "not_ready");
+    assert_eq!(ready["storage"]["reachable"], false);
+    assert!(ready["storage"]["error"]
+        .as_str()
+        .unwrap()
+        .contains("ExpiredToken"));
+}
+
+#[tokio::test]
+async fn test_capabilities_endpoint() {
+    let source = MockSlideSource::new();
+    let registry = SlideRegistry::new(source);
+    let tile_service = TileService::new(registry);
+    let router = create_router(tile_service, RouterConfig::without_auth());
+
+    let request = Request::builder()
+        .uri("/capabilities")
+        .body(Body::empty())
+        .unwrap();
+
+    let response = router.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let caps: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let mut outputs = vec!["jpeg", "png"];
+    if cfg!(feature = "webp") {
+        outputs.push("webp");
+    }
+    assert_eq!(caps["outputs"], serde_json::json!(outputs));
+    let jpeg2000 = caps["features"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|f| f["name"] == "jpeg2000")
+        .unwrap();
+    assert_eq!(jpeg2000["enabled"], cfg!(feature = "jpeg2000"));
+}
+
+// =============================================================================
+// SLO Endpoints
+// =============================================================================
+
+#[tokio::test]
+async fn test_slo_counts_tile_requests() {
+    let tiff_data = create_tiff_with_jpeg_tile();
+    let source = MockSlideSource::new().with_slide("test.tif", tiff_data);
+    let registry = SlideRegistry::new(source);
+    let tile_service = TileService::new(registry);
+    let router = create_router(tile_service, RouterConfig::without_auth());
+
+    for uri in ["/tiles/test.tif/0/0/0.jpg", "/tiles/missing.tif/0/0/0.jpg"] {
+        let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+        router.clone().oneshot(request).await.unwrap();
+    }
+
+    let request = Request::builder()
+        .uri("/admin/slo")
+        .body(Body::empty())
+        .unwrap();
+    let response = router.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let summary: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(summary["availability_target"], 0.999);
+    assert_eq!(summary["windows"][0]["window"], "5m");
+    assert_eq!(summary["windows"][0]["requests"], 2);
+    // A missing slide is a client error and does not burn the budget
+    assert_eq!(summary["windows"][0]["errors"], 0);
+
+    let request = Request::builder()
+        .uri("/metrics")
+        .body(Body::empty())
+        .unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let text = String::from_utf8(body.to_vec()).unwrap();
+    assert!(text.contains("wsi_tile_requests_total 2"));
+    assert!(text.contains("wsi_panics_total "));
+    assert!(text.contains("wsi_slo_burn_rate{slo=\"availability\",window=\"5m\"} 0"));
+}
+
+#[tokio::test]
+async fn test_fair_scheduler_metrics() {
+    let source = MockSlideSource::new().with_slide("test.tif", create_tiff_with_jpeg_tile());
+    let tile_service = TileService::new(SlideRegistry::new(source)).with_fair_scheduler(
+        FairScheduler::new(2).with_tenant_quota("acme", TenantQuota::new(2, 4)),
+    );
+    let router = create_router(
+        tile_service,
+        RouterConfig::without_auth().with_tenant_header("x-tenant-id"),
+    );
+
+    let request = Request::builder()
+        .uri("/tiles/test.tif/0/0/0.jpg")
+        .header("x-tenant-id", "acme")
+        .body(Body::empty())
+        .unwrap();
+    let response = router.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let request = Request::builder()
+        .uri("/metrics")
+        .body(Body::empty())
+        .unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let text = String::from_utf8(body.to_vec()).unwrap();
+    assert!(text.contains("wsi_tile_slots 2"));
+    assert!(text.contains("wsi_tile_slots_granted_total 1"));
+    // The permit is returned once the tile is generated
+    assert!(text.contains("wsi_tile_slots_in_use 0"));
+}
+
+#[tokio::test]
+async fn test_connection_tracking() {
+    let source = MockSlideSource::new().with_slide("test.tif", create_tiff_with_jpeg_tile());
+    let tile_service = TileService::new(SlideRegistry::new(source));
+    let router = create_router(tile_service, RouterConfig::without_auth());
+
+    // Requests carrying the connect info the server attaches to each request
+    let tracker = ConnectionTracker::new();
+    let connection = tracker.open("10.0.0.7:40000".parse().unwrap());
+    for _ in 0..3 {
+        let mut request = Request::builder()
+            .uri("/tiles/test.tif/0/0/0.jpg")
+            .body(Body::empty())
+            .unwrap();
+        request
+            .extensions_mut()
+            .insert(ConnectInfo(connection.clone()));
+        let response = router.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    let snapshot = tracker.snapshot();
+    assert_eq!(snapshot.len(), 1);
+    assert_eq!(snapshot[0].requests, 3);
+    assert_eq!(snapshot[0].in_flight, 0);
+
+    let request = Request::builder()
+        .uri("/admin/connections")
+        .body(Body::empty())
+        .unwrap();
+    let response = router.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let listing: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(listing["active_tile_generations"], 0);
+    assert!(listing["connections"].is_array());
+
+    let request = Request::builder()
+        .uri("/metrics")
+        .body(Body::empty())
+        .unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let text = String::from_utf8(body.to_vec()).unwrap();
+    assert!(text.contains("wsi_http_connections_open "));
+    assert!(text.contains("wsi_tile_generations_active 0"));
+    assert!(text.contains("wsi_tile_encodes_active 0"));
+    assert!(text.contains("wsi_tile_cache_hits_total "));
+    assert!(text.contains("wsi_tile_cache_rejected_total 0"));
+    assert!(text.contains("wsi_tile_cache_lock_acquisitions_total "));
+    assert!(text.contains("wsi_tile_cache_lock_contended_total "));
+    assert!(text.contains("wsi_tile_cache_shards "));
+    assert!(text.contains("wsi_memory_bytes{cache=\"tiles\"} "));
+    assert!(text.contains("wsi_memory_evicted_bytes_total 0"));
+    assert!(!text.contains("wsi_memory_budget_bytes"));
+    assert!(text.contains("wsi_slides_negative_cached 0"));
+    assert!(text.contains("wsi_slide_opens_in_flight 0"));
+    assert!(text.contains("wsi_slide_opens_queued 0"));
+    assert!(text.contains("wsi_streams_active "));
+    assert!(text.contains("wsi_tile_buffers_acquired_total "));
+    assert!(text.contains("wsi_tile_buffers_reused_total "));
+
+    // The connection is unregistered once the server drops it
+    drop(connection);
+    assert!(tracker.snapshot().is_empty());
+}
+
+// =============================================================================
+// Rate Limiting
+// =============================================================================
+
+#[tokio::test]
+async fn test_rate_limit_per_client() {
+    let source = MockSlideSource::new().with_slide("test.tif", create_tiff_with_jpeg_tile());
+    let tile_service = TileService::new(SlideRegistry::new(source));
+    let config =
+        RouterConfig::without_auth().with_rate_limit(RateLimitConfig::new(1).with_burst(2));
+    let router = create_router(tile_service, config);
+
+    let tracker = ConnectionTracker::new();
+    let viewer = tracker.open("10.0.0.7:40000".parse().unwrap());
+    let other = tracker.open("10.0.0.8:40000".parse().unwrap());
+    let get = |connection| {
+        let mut request = Request::builder()
+            .uri("/tiles/test.tif/0/0/0.jpg")
+            .body(Body::empty())
+            .unwrap();
+        request.extensions_mut().insert(ConnectInfo(connection));
+        router.clone().oneshot(request)
+    };
+
+    for _ in 0..2 {
+        let response = get(viewer.clone()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    let response = get(viewer.clone()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(response.headers()["retry-after"], "1");
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let error: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(error["error"], "rate_limited");
+    assert_eq!(error["retryable"], true);
+
+    // Other clients keep their own allowance
+    let response = get(other.clone()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_rate_limit_keys_by_verified_subject() {
+    let source = MockSlideSource::new().with_slide("test.tif", create_tiff_with_jpeg_tile());
+    let tile_service = TileService::new(SlideRegistry::new(source));
+    let config = RouterConfig::new("rate-secret").with_rate_limit(
+        RateLimitConfig::new(1)
+            .with_burst(2)
+            .with_key(RateLimitKey::Subject),
+    );
+    let router = create_router(tile_service, config);
+    let auth = SignedUrlAuth::new("rate-secret");
+    let (token, expiry) =
+        auth.generate_viewer_token("test.tif", std::time::Duration::from_secs(3600));
+
+    let tracker = ConnectionTracker::new();
+    let first = tracker.open("10.0.0.7:40000".parse().unwrap());
+    let second = tracker.open("10.0.0.8:40000".parse().unwrap());
+    let get = |connection, query: String| {
+        let mut request = Request::builder()
+            .uri(format!("/tiles/test.tif/0/0/0.jpg?{}", query))
+            .body(Body::empty())
+            .unwrap();
+        request.extensions_mut().insert(ConnectInfo(connection));
+        router.clone().oneshot(request)
+    };
+    let viewer = format!("vt={}&exp={}", token, expiry);
+
+    // The verified token is one client, whichever address it comes from
+    let response = get(first.clone(), viewer.clone()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = get(second.clone(), viewer.clone()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = get(second.clone(), viewer.clone()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+
+    // Invented tokens are rejected by auth instead of getting fresh buckets
+    for n in 0..3 {
+        let forged = format!("vt={:064x}&exp={}", n, expiry);
+        let response = get(second.clone(), forged).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+}
+
+// =============================================================================
+// Separate Admin Listener
+// =============================================================================
+
+#[tokio::test]
+async fn test_split_routers_share_state() {
+    let source = MockSlideSource::new().with_slide("test.tif", create_tiff_with_jpeg_tile());
+    let tile_service = TileService::new(SlideRegistry::new(source));
+    let routers = create_split_routers(tile_service, RouterConfig::without_auth());
+
+    let get = |uri: &str| Request::builder().uri(uri).body(Body::empty()).unwrap();
+
+    // Tiles are served publicly; admin surfaces are not
+    let response = routers
+        .public
+        .clone()
+        .oneshot(get("/tiles/test.tif/0/0/0.jpg"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    for uri in ["/admin/slo", "/admin/quarantine", "/metrics"] {
+        let response = routers.public.clone().oneshot(get(uri)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND, "{}", uri);
+    }
+
+    // The admin router sees requests served by the public router
+    let response = routers
+        .admin
+        .clone()
+        .oneshot(get("/metrics"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let text = String::from_utf8(body.to_vec()).unwrap();
+    assert!(text.contains("wsi_tile_requests_total 1"));
+
+    let response = routers
+        .admin
+        .clone()
+        .oneshot(get("/tiles/test.tif/0/0/0.jpg"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    // Both listeners answer health checks
+    for router in [routers.public, routers.admin] {
+        let response = router.oneshot(get("/health")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
+
+// =============================================================================
+// Deterministic Rendering
+// =============================================================================
+
+#[tokio::test]
+async fn test_tile_hash_requires_deterministic_mode() {
+    let source = MockSlideSource::new().with_slide("test.tif", create_tiff_with_jpeg_tile());
+    let tile_service = TileService::new(SlideRegistry::new(source));
+    let router = create_router(tile_service, RouterConfig::without_auth());
+
+    let request = Request::builder()
+        .uri("/debug/tiles/test.tif/0/0/0/hash")
+        .body(Body::empty())
+        .unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_tile_hash_is_stable() {
+    let mut hashes = Vec::new();
+    for _ in 0..2 {
+        // Fresh service each time so the tile is rendered, not cached
+        let source = MockSlideSource::new().with_slide("test.tif", create_tiff_with_jpeg_tile());
+        let tile_service = TileService::new(SlideRegistry::new(source)).with_deterministic(true);
+        let router = create_router(tile_service, RouterConfig::without_auth());
+
+        let request = Request::builder()
+            .uri("/debug/tiles/test.tif/0/0/0/hash?quality=85")
+            .body(Body::empty())
+            .unwrap();
+        let response = router.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["quality"], 85);
+        assert_eq!(json["sha256"].as_str().unwrap().len(), 64);
+        hashes.push(json["sha256"].clone());
+    }
+    assert_eq!(hashes[0], hashes[1]);
+}
+
+// =============================================================================
+// Adaptive Caching
+// =============================================================================
+
+#[tokio::test]
+async fn test_admin_cache_reports_temperatures() {
+    let source = MockSlideSource::new()
+        .with_slide("busy.tif", create_tiff_with_jpeg_tile())
+        .with_slide("quiet.tif", create_tiff_with_jpeg_tile());
+    let adaptive = AdaptiveCacheConfig::new(10)
+        .with_hot_blocks(40)
+        .with_hot_score(3.0);
+    let registry = SlideRegistry::new(source).with_adaptive_caching(adaptive);
+    let router = create_router(TileService::new(registry), RouterConfig::without_auth());
+
+    let get = |uri: &str| Request::builder().uri(uri).body(Body::empty()).unwrap();
+
+    for _ in 0..4 {
+        let response = router
+            .clone()
+            .oneshot(get("/tiles/busy.tif/0/0/0.jpg"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+    router
+        .clone()
+        .oneshot(get("/slides/quiet.tif"))
+        .await
+        .unwrap();
+
+    let response = router.clone().oneshot(get("/admin/cache")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["cache-control"], "no-store");
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["adaptive"]["hot_blocks"], 40);
+    assert_eq!(json["temperatures"]["hot"], 1);
+    assert_eq!(json["temperatures"]["warm"], 1);
+    assert_eq!(json["tiles"]["admission"], "lru");
+    assert_eq!(json["tiles"]["misses"], 1);
+    assert_eq!(json["tiles"]["hits"], 3);
+    assert_eq!(json["tiles"]["hit_ratio"], 0.75);
+    assert!(json["memory"]["budget_bytes"].is_null());
+    assert!(json["memory"]["tile_cache_bytes"].as_u64().unwrap() > 0);
+    assert!(json["memory"]["block_cache_bytes"].as_u64().unwrap() > 0);
+    assert_eq!(json["memory"]["evicted_bytes"], 0);
+
+    let slides = json["slides"].as_array().unwrap();
+    let busy = slides.iter().find(|s| s["slide_id"] == "busy.tif").unwrap();
+    assert_eq!(busy["temperature"], "hot");
+    assert_eq!(busy["block_cache_capacity"], 40);
+    let quiet = slides
+        .iter()
+        .find(|s| s["slide_id"] == "quiet.tif")
+        .unwrap();
+    assert_eq!(quiet["temperature"], "warm");
+    assert_eq!(quiet["block_cache_capacity"], 10);
+
+    let response = router.oneshot(get("/metrics")).await.unwrap();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let metrics = String::from_utf8(body.to_vec()).unwrap();
+    assert!(metrics.contains("wsi_slides_cached{temperature=\"hot\"} 1"));
+}
+
+#[tokio::test]
+async fn test_admin_slide_io_stats() {
+    let source = MockSlideSource::new().with_slide("test.tif", create_tiff_with_jpeg_tile());
+    let router = create_router(
+        TileService::new(SlideRegistry::new(source)),
+        RouterConfig::without_auth(),
+    );
+    let get = |uri: &str| Request::builder().uri(uri).body(Body::empty()).unwrap();
+
+    // Slides that aren't open have no statistics
+    let response = router
+        .clone()
+        .oneshot(get("/admin/slides/test.tif/stats"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    for _ in 0..2 {
+        let response = router
+            .clone()
+            .oneshot(get("/tiles/test.tif/0/0/0.jpg"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    let response = router
+        .clone()
+        .oneshot(get("/admin/slides/test.tif/stats"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["cache-control"], "no-store");
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let stats: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(stats["slide_id"], "test.tif");
+    assert_eq!(stats["block_size"], 256 * 1024);
+    assert!(stats["requests"].as_u64().unwrap() > 0);
+    assert!(stats["hits"].as_u64().unwrap() > 0);
+    // The whole file fits in one block, fetched once
+    assert_eq!(stats["fetches"], 1);
+    assert_eq!(stats["distinct_blocks"], 1);
+    assert!(stats["hit_rate"].as_f64().unwrap() > 0.5);
+
+    let response = router
+        .clone()
+        .oneshot(get("/admin/slides/metrics"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let metrics = String::from_utf8(body.to_vec()).unwrap();
+    assert!(metrics.contains("wsi_slide_distinct_blocks{slide=\"test.tif\"} 1"));
+    assert!(metrics.contains("# TYPE wsi_slide_fetched_bytes_total counter"));
+
+    // Slide IDs stay off the public metrics endpoint
+    let response = router.oneshot(get("/metrics")).await.unwrap();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let metrics = String::from_utf8(body.to_vec()).unwrap();
+    assert!(!metrics.contains("test.tif"));
+}
+
+// =============================================================================
+// Slide Quarantine
+// =============================================================================
+
+#[tokio::test]
+async fn test_corrupt_slide_is_quarantined_until_released() {
+    let source = MockSlideSource::new()
+        .with_slide("good.tif", create_tiff_with_jpeg_tile())
+        .with_slide("corrupt.tif", vec![b'X'; 64]);
+    let registry = SlideRegistry::new(source).with_quarantine_threshold(2);
+    let tile_service = TileService::new(registry);
+    let router = create_router(tile_service, RouterConfig::without_auth());
+
+    let get = |uri: &str| Request::builder().uri(uri).body(Body::empty()).unwrap();
+
+    for _ in 0..2 {
+        let response = router
+            .clone()
+            .oneshot(get("/tiles/corrupt.tif/0/0/0.jpg"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    }
+
+    let response = router
+        .clone()
+        .oneshot(get("/tiles/corrupt.tif/0/0/0.jpg"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::LOCKED);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["error"], "slide_quarantined");
+
+    // Quarantined slides are left out of listings
+    let response = router.clone().oneshot(get("/slides")).await.unwrap();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["slides"], serde_json::json!(["good.tif"]));
+
+    let response = router
+        .clone()
+        .oneshot(get("/admin/quarantine"))
+        .await
+        .unwrap();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["threshold"], 2);
+    assert_eq!(json["slides"][0]["slide_id"], "corrupt.tif");
+    assert_eq!(json["slides"][0]["failures"], 2);
+
+    let release = || {
+        Request::builder()
+            .method("POST")
+            .uri("/admin/quarantine/corrupt.tif/release")
+            .body(Body::empty())
+            .unwrap()
+    };
+    let response = router.clone().oneshot(release()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = router.clone().oneshot(release()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let response = router
+        .oneshot(get("/tiles/corrupt.tif/0/0/0.jpg"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+}
+
+// =============================================================================
+// Slide Invalidation
+// =============================================================================
+
+#[tokio::test]
+async fn test_invalidated_slide_serves_stale_tiles_while_revalidating() {
+    let source = MockSlideSource::new().with_slide("test.tif", create_tiff_with_jpeg_tile());
+    let registry = SlideRegistry::new(source);
+    let tile_service = TileService::new(registry).with_stale_while_revalidate(true);
+    let router = create_router(tile_service, RouterConfig::without_auth());
+
+    let get = || {
+        Request::builder()
+            .uri("/tiles/test.tif/0/0/0.jpg")
+            .body(Body::empty())
+            .unwrap()
+    };
+
+    let response = router.clone().oneshot(get()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers().get("X-Tile-Cache-Stale").is_none());
+
+    let response = router
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/admin/slides/test.tif/invalidate")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["invalidated"], true);
+
+    let response = router.clone().oneshot(get()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["X-Tile-Cache-Hit"], "true");
+    assert_eq!(response.headers()["X-Tile-Cache-Stale"], "true");
+}
+
+// =============================================================================
+// Slide Headers
+// =============================================================================
+
+#[tokio::test]
+async fn test_slide_headers_sidecar() {
+    let source = MockSlideSource::new()
+        .with_slide("labeled.tif", create_tiff_with_jpeg_tile())
+        .with_slide(
+            "labeled.tif.headers.json",
+            br#"{"X-Data-Classification": "restricted", "X-Consent-Id": "c-20931"}"#.to_vec(),
+        )
+        .with_slide("plain.tif", create_tiff_with_jpeg_tile());
+    let registry = SlideRegistry::new(source).with_slide_headers(true);
+    let router = create_router(TileService::new(registry), RouterConfig::without_auth());
+
+    let get = |uri: &str| Request::builder().uri(uri).body(Body::empty()).unwrap();
+
+    for uri in [
+        "/tiles/labeled.tif/0/0/0.jpg",
+        "/slides/labeled.tif",
+        "/slides/labeled.tif/info",
+        "/dzi/labeled.tif.dzi",
+        "/dzi/labeled.tif_files/11/0_0.jpg",
+        "/iiif/labeled.tif/info.json",
+    ] {
+        let response = router.clone().oneshot(get(uri)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK, "{}", uri);
+        assert_eq!(
+            response.headers()["x-data-classification"],
+            "restricted",
+            "{}",
+            uri
+        );
+        assert_eq!(response.headers()["x-consent-id"], "c-20931", "{}", uri);
+    }
+
+    // Other slides and non-slide routes are unlabeled
+    let response = router
+        .clone()
+        .oneshot(get("/tiles/plain.tif/0/0/0.jpg"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers().get("x-data-classification").is_none());
+
+    let response = router.clone().oneshot(get("/slides")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers().get("x-data-classification").is_none());
+
+    // Errors are unlabeled
+    let response = router
+        .oneshot(get("/tiles/labeled.tif/9/0/0.jpg"))
+        .await
+        .unwrap();
+    assert_ne!(response.status(), StatusCode::OK);
+    assert!(response.headers().get("x-data-classification").is_none());
+}
+
+// =============================================================================
+// Preloaded Slides
+// =============================================================================
+
+#[tokio::test]
+async fn test_preloaded_slides_served_from_memory() {
+    let origin = MockSlideSource::new()
+        .with_slide("demo.tif", create_tiff_with_jpeg_tile())
+        .with_slide("other.tif", create_tiff_with_jpeg_tile());
+    let source = MemorySlideSource::new()
+        .preload_from(&origin, &["demo.tif".to_string()])
+        .await
+        .unwrap();
+    drop(origin);
+
+    let tile_service = TileService::new(SlideRegistry::new(source));
+    let router = create_router(tile_service, RouterConfig::without_auth());
+
+    let get = |uri: &str| Request::builder().uri(uri).body(Body::empty()).unwrap();
+
+    let response = router
+        .clone()
+        .oneshot(get("/tiles/demo.tif/0/1/1.jpg"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    assert!(is_valid_jpeg(&body));
+
+    // Only preloaded slides are listed and served
+    let response = router.clone().oneshot(get("/slides")).await.unwrap();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["slides"], serde_json::json!(["demo.tif"]));
+
+    let response = router
+        .oneshot(get("/tiles/other.tif/0/0/0.jpg"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+// =============================================================================
+// Multiple Tiles from Same Slide
+// =============================================================================
+
+#[tokio::test]
+async fn test_multiple_tiles_same_slide() {
+    let tiff_data = create_tiff_with_jpeg_tile();
+    let source = MockSlideSource::new().with_slide("test.tif", tiff_data);
+    let registry = SlideRegistry::new(source);
+    let tile_service = TileService::new(registry);
+    let router = create_router(tile_service, RouterConfig::without_auth());
+
+    // Request multiple different tiles
+    let tiles = [(0, 0), (1, 0), (0, 1), (1, 1)];
+
+    for (x, y) in tiles {
+        let request = Request::builder()
+            .uri(format!("/tiles/test.tif/0/{}/{}.jpg", x, y))
+            .body(Body::empty())
+            .unwrap();
+
+        let response = router.clone().oneshot(request).await.unwrap();
+        assert_eq!(
+            response.status(),
+            StatusCode::OK,
+            "Tile ({}, {}) should succeed",
+            x,
+            y
+        );
+
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert!(
+            is_valid_jpeg(&body),
             "Tile ({}, {}) should be valid JPEG",
             x,
             y
@@ -413,12 +1788,260 @@ async fn test_slide_id_with_special_chars() {
     let router = create_router(tile_service, RouterConfig::without_auth());
 
     let request = Request::builder()
-        .uri("/tiles/my_slide-2024.tif/0/0/0.jpg")
+        .uri("/tiles/my_slide-2024.tif/0/0/0.jpg")
+        .body(Body::empty())
+        .unwrap();
+
+    let response = router.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+// =============================================================================
+// Deep Zoom
+// =============================================================================
+
+#[tokio::test]
+async fn test_dzi_descriptor_at_standard_path() {
+    let source = MockSlideSource::new().with_slide("test.tif", create_tiff_with_jpeg_tile());
+    let tile_service = TileService::new(SlideRegistry::new(source));
+    let router = create_router(tile_service, RouterConfig::without_auth());
+
+    let request = Request::builder()
+        .uri("/dzi/test.tif.dzi")
+        .body(Body::empty())
+        .unwrap();
+    let response = router.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    assert!(String::from_utf8_lossy(&body).contains("<Image"));
+
+    let request = Request::builder()
+        .uri("/dzi/test.tif")
+        .body(Body::empty())
+        .unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_dzi_tiles_full_and_synthesized_levels() {
+    let source = MockSlideSource::new().with_slide("test.tif", create_tiff_with_jpeg_tile());
+    let tile_service = TileService::new(SlideRegistry::new(source));
+    let router = create_router(tile_service, RouterConfig::without_auth());
+
+    // Level 0 is 1x1 and always synthesized; level 11 is full resolution
+    // for the 2048x1536 test slide
+    for uri in [
+        "/dzi/test.tif_files/0/0_0.jpg",
+        "/dzi/test.tif_files/11/7_5.jpg",
+    ] {
+        let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+        let response = router.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK, "{}", uri);
+
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert!(is_valid_jpeg(&body));
+    }
+
+    let request = Request::builder()
+        .uri("/dzi/test.tif_files/0/1_0.jpg")
+        .body(Body::empty())
+        .unwrap();
+    let response = router.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let request = Request::builder()
+        .uri("/dzi/test.tif_files/30/0_0.jpg")
+        .body(Body::empty())
+        .unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_dzi_tiles_cached_and_revalidated() {
+    let source = MockSlideSource::new().with_slide("test.tif", create_tiff_with_jpeg_tile());
+    let tile_service = TileService::new(SlideRegistry::new(source));
+    let router = create_router(tile_service, RouterConfig::without_auth());
+
+    let get = |uri: &str| Request::builder().uri(uri).body(Body::empty()).unwrap();
+    let uri = "/dzi/test.tif_files/10/1_1.jpg";
+
+    let response = router.clone().oneshot(get(uri)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["x-tile-cache-hit"], "false");
+    let etag = response.headers()["etag"].to_str().unwrap().to_string();
+    let first = response.into_body().collect().await.unwrap().to_bytes();
+
+    // The rendered tile is served from the cache
+    let response = router.clone().oneshot(get(uri)).await.unwrap();
+    assert_eq!(response.headers()["x-tile-cache-hit"], "true");
+    assert_eq!(response.headers()["etag"], etag.as_str());
+    let second = response.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(first, second);
+
+    // Revalidating with the ETag skips the tile
+    let request = Request::builder()
+        .uri(uri)
+        .header("if-none-match", &etag)
+        .body(Body::empty())
+        .unwrap();
+    let response = router.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+    assert_eq!(response.headers()["etag"], etag.as_str());
+
+    // The filter and quality change the tile, so they change the ETag
+    for other in [
+        "/dzi/test.tif_files/10/1_1.jpg?quality=50",
+        "/dzi/test.tif_files/10/1_1.jpg?filter=lanczos3",
+    ] {
+        let response = router.clone().oneshot(get(other)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["x-tile-cache-hit"], "false", "{}", other);
+        assert_ne!(response.headers()["etag"], etag.as_str(), "{}", other);
+    }
+}
+
+#[tokio::test]
+async fn test_slide_levels_mapping() {
+    let source = MockSlideSource::new().with_slide("test.tif", create_tiff_with_jpeg_tile());
+    let tile_service = TileService::new(SlideRegistry::new(source));
+    let router = create_router(tile_service, RouterConfig::without_auth());
+
+    let request = Request::builder()
+        .uri("/slides/test.tif/levels")
+        .body(Body::empty())
+        .unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let map: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+    // Single native level, 2048x1536: Deep Zoom levels 0..=11
+    assert_eq!(map["slide_id"], "test.tif");
+    assert_eq!(map["max_dzi_level"], 11);
+    assert_eq!(map["native_levels"][0]["dzi_level"], 11);
+
+    let levels = map["levels"].as_array().unwrap();
+    assert_eq!(levels.len(), 12);
+    assert_eq!(levels[11]["iiif_scale_factor"], 1);
+    assert_eq!(levels[11]["tiles_x"], 8);
+    assert_eq!(levels[11]["tiles_y"], 6);
+    assert_eq!(levels[11]["synthesized"], false);
+    assert_eq!(levels[0]["iiif_scale_factor"], 2048);
+    assert_eq!(levels[0]["source_level"], 0);
+    assert_eq!(levels[0]["synthesized"], true);
+}
+
+// =============================================================================
+// IIIF Image API
+// =============================================================================
+
+#[tokio::test]
+async fn test_iiif_info_json() {
+    let source = MockSlideSource::new().with_slide("test.tif", create_tiff_with_jpeg_tile());
+    let tile_service = TileService::new(SlideRegistry::new(source));
+    let router = create_router(tile_service, RouterConfig::without_auth());
+
+    let request = Request::builder()
+        .uri("/iiif/test.tif/info.json")
+        .header("host", "example.com")
+        .body(Body::empty())
+        .unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response
+        .headers()
+        .get("content-type")
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .starts_with("application/ld+json"));
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let info: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(info["id"], "http://example.com/iiif/test.tif");
+    assert_eq!(info["type"], "ImageService3");
+    assert_eq!(info["profile"], "level1");
+    assert!(info["width"].as_u64().unwrap() > 0);
+    assert!(!info["tiles"][0]["scaleFactors"]
+        .as_array()
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn test_iiif_image_request() {
+    let source = MockSlideSource::new().with_slide("test.tif", create_tiff_with_jpeg_tile());
+    let tile_service = TileService::new(SlideRegistry::new(source));
+    let router = create_router(tile_service, RouterConfig::without_auth());
+
+    let request = Request::builder()
+        .uri("/iiif/test.tif/full/!64,64/90/gray.jpg")
+        .body(Body::empty())
+        .unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers().get("content-type").unwrap(),
+        "image/jpeg"
+    );
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    assert!(is_valid_jpeg(&body));
+}
+
+#[tokio::test]
+async fn test_iiif_invalid_and_unsupported_parameters() {
+    let source = MockSlideSource::new().with_slide("test.tif", create_tiff_with_jpeg_tile());
+    let tile_service = TileService::new(SlideRegistry::new(source));
+    let router = create_router(tile_service, RouterConfig::without_auth());
+
+    for (uri, status) in [
+        (
+            "/iiif/test.tif/full/max/0/default.png",
+            StatusCode::NOT_IMPLEMENTED,
+        ),
+        (
+            "/iiif/test.tif/full/max/45/default.jpg",
+            StatusCode::NOT_IMPLEMENTED,
+        ),
+        (
+            "/iiif/test.tif/full/max/0/sepia.jpg",
+            StatusCode::BAD_REQUEST,
+        ),
+        (
+            "/iiif/test.tif/bogus/max/0/default.jpg",
+            StatusCode::BAD_REQUEST,
+        ),
+        (
+            "/iiif/missing.tif/full/max/0/default.jpg",
+            StatusCode::NOT_FOUND,
+        ),
+    ] {
+        let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+        let response = router.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), status, "{}", uri);
+    }
+}
+
+#[tokio::test]
+async fn test_iiif_base_uri_redirects_to_info() {
+    let source = MockSlideSource::new().with_slide("test.tif", create_tiff_with_jpeg_tile());
+    let tile_service = TileService::new(SlideRegistry::new(source));
+    let router = create_router(tile_service, RouterConfig::without_auth());
+
+    let request = Request::builder()
+        .uri("/iiif/test.tif")
         .body(Body::empty())
         .unwrap();
-
     let response = router.oneshot(request).await.unwrap();
-    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.status(), StatusCode::SEE_OTHER);
+    assert_eq!(
+        response.headers().get("location").unwrap(),
+        "test.tif/info.json"
+    );
 }
 
 // =============================================================================
@@ -489,3 +2112,622 @@ async fn test_slide_metadata_not_found() {
     let error: serde_json::Value = serde_json::from_slice(&body).unwrap();
     assert_eq!(error["error"], "not_found");
 }
+
+#[tokio::test]
+async fn test_slide_info_endpoint() {
+    let source = MockSlideSource::new().with_slide("test.tif", create_tiff_with_jpeg_tile());
+    let tile_service = TileService::new(SlideRegistry::new(source));
+    let router = create_router(tile_service, RouterConfig::without_auth());
+
+    let request = Request::builder()
+        .uri("/slides/test.tif/info")
+        .body(Body::empty())
+        .unwrap();
+
+    let response = router.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let info: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+    assert_eq!(info["slide_id"], "test.tif");
+    assert_eq!(info["format"], "Generic Pyramidal TIFF");
+    assert_eq!(info["width"], 2048);
+    assert_eq!(info["height"], 1536);
+    assert_eq!(info["tile_width"], 256);
+    assert_eq!(info["tile_height"], 256);
+
+    let level_count = info["level_count"].as_u64().unwrap() as usize;
+    let downsamples = info["downsamples"].as_array().unwrap();
+    assert_eq!(downsamples.len(), level_count);
+    assert_eq!(downsamples[0], 1.0);
+    assert_eq!(info["levels"].as_array().unwrap().len(), level_count);
+    assert_eq!(info["levels"][0]["tiles_x"], 8);
+    assert_eq!(info["levels"][0]["tiles_y"], 6);
+
+    // Generic TIFFs carry no calibration or scanner details
+    assert!(info.get("mpp").is_none());
+    assert!(info.get("magnification").is_none());
+    assert!(info.get("vendor").is_none());
+
+    let properties = &info["properties"];
+    assert_eq!(properties["openslide.vendor"], "generic-tiff");
+    assert_eq!(properties["openslide.bounds-width"], "2048");
+    assert_eq!(properties["openslide.level-count"], level_count.to_string());
+    assert_eq!(properties["openslide.level[0].tile-width"], "256");
+    assert!(properties.get("openslide.mpp-x").is_none());
+}
+
+#[tokio::test]
+async fn test_slide_info_not_found() {
+    let tile_service = TileService::new(SlideRegistry::new(MockSlideSource::new()));
+    let router = create_router(tile_service, RouterConfig::without_auth());
+
+    let request = Request::builder()
+        .uri("/slides/nonexistent.tif/info")
+        .body(Body::empty())
+        .unwrap();
+
+    let response = router.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+// =============================================================================
+// Region Endpoint
+// =============================================================================
+
+fn region_router() -> axum::Router {
+    let source = MockSlideSource::new().with_slide("test.tif", create_tiff_with_jpeg_tile());
+    let tile_service = TileService::new(SlideRegistry::new(source));
+    create_router(tile_service, RouterConfig::without_auth())
+}
+
+async fn get_region(query: &str) -> axum::response::Response {
+    let request = Request::builder()
+        .uri(format!("/region/test.tif?{}", query))
+        .body(Body::empty())
+        .unwrap();
+    region_router().oneshot(request).await.unwrap()
+}
+
+#[tokio::test]
+async fn test_region_crops_unaligned_rectangle() {
+    let response = get_region("x=100&y=50&w=300&h=200").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers().get("content-type").unwrap(),
+        "image/jpeg"
+    );
+    assert_eq!(
+        response.headers().get("x-region-source-level").unwrap(),
+        "0"
+    );
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    assert!(is_valid_jpeg(&body));
+    let image = image::load_from_memory(&body).unwrap();
+    assert_eq!((image.width(), image.height()), (300, 200));
+}
+
+#[tokio::test]
+async fn test_region_resized_png() {
+    let response = get_region("x=0&y=0&w=400&h=200&out_w=100&format=png&filter=nearest").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers().get("content-type").unwrap(), "image/png");
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let image = image::load_from_memory(&body).unwrap();
+    assert_eq!((image.width(), image.height()), (100, 50));
+}
+
+#[tokio::test]
+async fn test_region_invalid_requests() {
+    for query in [
+        "x=0&y=0&w=0&h=10",
+        "x=5000&y=0&w=10&h=10",
+        "x=0&y=0&w=10&h=10&level=0&downsample=2",
+        "x=0&y=0&w=10&h=10&downsample=0",
+        "x=0&y=0&w=10&h=10&level=7",
+        "x=0&y=0&w=10000&h=10000",
+        "x=0&y=0&w=10&h=10&quality=0",
+    ] {
+        let response = get_region(query).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{}", query);
+    }
+}
+
+// =============================================================================
+// Snapshots
+// =============================================================================
+
+#[tokio::test]
+async fn test_snapshot_renders_from_source_tiles() {
+    let source = MockSlideSource::new().with_slide("test.tif", create_calibrated_tiff(0.5));
+    let tile_service = TileService::new(SlideRegistry::new(source));
+    let router = create_router(tile_service, RouterConfig::without_auth());
+    let get = |uri: &str| Request::builder().uri(uri).body(Body::empty()).unwrap();
+
+    // 300 µm at 0.5 mpp spans 600 level 0 pixels over three tiles
+    let response = router
+        .clone()
+        .oneshot(get(
+            "/slides/test.tif/snapshot?x_um=100&y_um=100&w_um=300&h_um=150&dpi=100&width_in=2",
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "image/jpeg");
+    assert_eq!(response.headers()["x-snapshot-level"], "0");
+    assert_eq!(response.headers()["x-snapshot-mpp"], "1.5");
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    assert!(is_valid_jpeg(&body));
+    let image = image::load_from_memory(&body).unwrap();
+    assert_eq!((image.width(), image.height()), (200, 100));
+
+    // Source tiles are read directly, without filling the tile cache
+    let response = router.clone().oneshot(get("/admin/cache")).await.unwrap();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["tiles"]["misses"], 0);
+    assert_eq!(json["memory"]["tile_cache_bytes"], 0);
+
+    // An uncalibrated slide can't be rendered at a physical size
+    let source = MockSlideSource::new().with_slide("test.tif", create_tiff_with_jpeg_tile());
+    let router = create_router(
+        TileService::new(SlideRegistry::new(source)),
+        RouterConfig::without_auth(),
+    );
+    let response = router
+        .oneshot(get("/slides/test.tif/snapshot?x_um=0&y_um=0&w_um=100"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+// =============================================================================
+// Batch Regions
+// =============================================================================
+
+async fn post_regions_batch(body: serde_json::Value) -> axum::response::Response {
+    let request = Request::builder()
+        .method("POST")
+        .uri("/regions/batch")
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap();
+    region_router().oneshot(request).await.unwrap()
+}
+
+/// Split a multipart body into (headers, data) parts.
+fn multipart_parts(body: &[u8], boundary: &str) -> Vec<(String, Vec<u8>)> {
+    let delimiter = format!("--{}", boundary).into_bytes();
+    let starts: Vec<usize> = (0..body.len())
+        .filter(|&i| body[i..].starts_with(&delimiter))
+        .collect();
+    starts
+        .windows(2)
+        .map(|bounds| {
+            let part = &body[bounds[0] + delimiter.len() + 2..bounds[1] - 2];
+            let split = part.windows(4).position(|w| w == b"\r\n\r\n").unwrap();
+            (
+                String::from_utf8(part[..split].to_vec()).unwrap(),
+                part[split + 4..].to_vec(),
+            )
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_regions_batch_multipart() {
+    let response = post_regions_batch(serde_json::json!({
+        "regions": [
+            { "slide_id": "test.tif", "x": 100, "y": 50, "w": 300, "h": 200 },
+            { "slide_id": "test.tif", "x": 0, "y": 0, "w": 400, "h": 200, "out_w": 100 },
+            { "slide_id": "missing.tif", "x": 0, "y": 0, "w": 10, "h": 10 }
+        ]
+    }))
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let content_type = response.headers()["content-type"].to_str().unwrap();
+    let boundary = content_type
+        .strip_prefix("multipart/mixed; boundary=")
+        .unwrap()
+        .to_string();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    assert!(body.ends_with(format!("--{}--\r\n", boundary).as_bytes()));
+
+    let parts = multipart_parts(&body, &boundary);
+    assert_eq!(parts.len(), 3);
+
+    let (headers, data) = &parts[0];
+    assert!(headers.contains("Content-Type: image/jpeg"));
+    assert!(headers.contains("Content-ID: <region-0>"));
+    assert!(headers.contains("X-Slide-Id: test.tif"));
+    assert!(headers.contains("X-Status: 200"));
+    assert!(headers.contains("X-Region-Source-Level: 0"));
+    let image = image::load_from_memory(data).unwrap();
+    assert_eq!((image.width(), image.height()), (300, 200));
+
+    let image = image::load_from_memory(&parts[1].1).unwrap();
+    assert_eq!((image.width(), image.height()), (100, 50));
+
+    // A failed region is reported in its part without failing the batch
+    let (headers, data) = &parts[2];
+    assert!(headers.contains("Content-Type: application/json"));
+    assert!(headers.contains("X-Status: 404"));
+    let error: serde_json::Value = serde_json::from_slice(data).unwrap();
+    assert_eq!(error["error"], "not_found");
+}
+
+#[tokio::test]
+async fn test_regions_batch_limits() {
+    let empty = post_regions_batch(serde_json::json!({ "regions": [] })).await;
+    assert_eq!(empty.status(), StatusCode::BAD_REQUEST);
+
+    let region = serde_json::json!({ "slide_id": "test.tif", "x": 0, "y": 0, "w": 10, "h": 10 });
+    let too_many = post_regions_batch(serde_json::json!({ "regions": vec![region; 65] })).await;
+    assert_eq!(too_many.status(), StatusCode::BAD_REQUEST);
+
+    // 64 full-slide regions read more tiles than a batch may
+    let full = serde_json::json!({ "slide_id": "test.tif", "x": 0, "y": 0, "w": 2048, "h": 1536 });
+    let over_budget = post_regions_batch(serde_json::json!({ "regions": vec![full; 64] })).await;
+    assert_eq!(over_budget.status(), StatusCode::BAD_REQUEST);
+
+    let bad_quality = post_regions_batch(serde_json::json!({
+        "regions": [{ "slide_id": "test.tif", "x": 0, "y": 0, "w": 10, "h": 10 }],
+        "quality": 0
+    }))
+    .await;
+    assert_eq!(bad_quality.status(), StatusCode::BAD_REQUEST);
+}
+
+// =============================================================================
+// Tile Sampling Endpoint
+// =============================================================================
+
+#[tokio::test]
+async fn test_sample_endpoint_is_deterministic() {
+    let tiff_data = create_tiff_with_jpeg_tile();
+    let source = MockSlideSource::new().with_slide("test.tif", tiff_data);
+    let registry = SlideRegistry::new(source);
+    let tile_service = TileService::new(registry);
+    let router = create_router(tile_service, RouterConfig::without_auth());
+
+    let uri = "/slides/test.tif/sample?count=5&seed=11&min_tissue=0.1";
+
+    let mut bodies = Vec::new();
+    for _ in 0..2 {
+        let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+        let response = router.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        bodies.push(serde_json::from_slice::<serde_json::Value>(&body).unwrap());
+    }
+
+    assert_eq!(bodies[0], bodies[1]);
+    assert_eq!(bodies[0]["slide_id"], "test.tif");
+    assert_eq!(bodies[0]["seed"], 11);
+
+    let tiles = bodies[0]["tiles"].as_array().unwrap();
+    assert_eq!(tiles.len(), 5);
+    assert!(tiles[0]["x"].as_u64().is_some());
+    assert!(tiles[0].get("data").is_none());
+}
+
+#[tokio::test]
+async fn test_sample_endpoint_with_tiles() {
+    let tiff_data = create_tiff_with_jpeg_tile();
+    let source = MockSlideSource::new().with_slide("test.tif", tiff_data);
+    let registry = SlideRegistry::new(source);
+    let tile_service = TileService::new(registry);
+    let router = create_router(tile_service, RouterConfig::without_auth());
+
+    let request = Request::builder()
+        .uri("/slides/test.tif/sample?count=2&min_tissue=0.1&include_tiles=true")
+        .body(Body::empty())
+        .unwrap();
+
+    let response = router.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let sample: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let tiles = sample["tiles"].as_array().unwrap();
+    assert_eq!(tiles.len(), 2);
+
+    // Base64 of a JPEG SOI marker (FF D8 FF) starts with "/9j/"
+    assert!(tiles[0]["data"].as_str().unwrap().starts_with("/9j/"));
+}
+
+#[tokio::test]
+async fn test_sample_endpoint_invalid_level() {
+    let tiff_data = create_tiff_with_jpeg_tile();
+    let source = MockSlideSource::new().with_slide("test.tif", tiff_data);
+    let registry = SlideRegistry::new(source);
+    let tile_service = TileService::new(registry);
+    let router = create_router(tile_service, RouterConfig::without_auth());
+
+    let request = Request::builder()
+        .uri("/slides/test.tif/sample?level=9")
+        .body(Body::empty())
+        .unwrap();
+
+    let response = router.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+// =============================================================================
+// Tile Transformer
+// =============================================================================
+
+/// Transformer that attaches a fixed score and keeps the original tile.
+struct ScoringTransformer;
+
+#[async_trait::async_trait]
+impl TileTransformer for ScoringTransformer {
+    async fn transform(
+        &self,
+        context: &TileContext,
+        _tile: bytes::Bytes,
+    ) -> Result<TransformOutput, wsi_streamer::TileError> {
+        Ok(TransformOutput {
+            data: None,
+            content_type: None,
+            headers: vec![(
+                "X-Inference-Score".to_string(),
+                format!("{}-{}", context.tile_x, context.tile_y),
+            )],
+        })
+    }
+}
+
+#[tokio::test]
+async fn test_tile_assist_applies_transformer() {
+    let tiff_data = create_tiff_with_jpeg_tile();
+    let source = MockSlideSource::new().with_slide("test.tif", tiff_data);
+    let registry = SlideRegistry::new(source);
+    let tile_service =
+        TileService::new(registry).with_transformer(std::sync::Arc::new(ScoringTransformer));
+    let router = create_router(tile_service, RouterConfig::without_auth());
+
+    let request = Request::builder()
+        .uri("/tiles/test.tif/0/1/2.jpg?assist=true")
+        .body(Body::empty())
+        .unwrap();
+    let response = router.clone().oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers().get("x-tile-transformed").unwrap(),
+        "true"
+    );
+    assert_eq!(response.headers().get("x-inference-score").unwrap(), "1-2");
+    // Transformer output isn't validated, so it is never cached or revalidated
+    assert_eq!(response.headers()["cache-control"], "private, no-store");
+    assert!(response.headers().get("etag").is_none());
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    assert!(is_valid_jpeg(&body));
+
+    // Without assist the transformer is not applied
+    let request = Request::builder()
+        .uri("/tiles/test.tif/0/1/2.jpg")
+        .body(Body::empty())
+        .unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    assert!(response.headers().get("x-inference-score").is_none());
+}
+
+// =============================================================================
+// Checksum Verification
+// =============================================================================
+
+/// Manifest for `data` with 512-byte blocks.
+fn manifest_for(data: &[u8]) -> Vec<u8> {
+    let mut builder = ManifestBuilder::new(512);
+    builder.update(data);
+    builder.finish().to_text("test.tif").into_bytes()
+}
+
+async fn post_verify(router: &axum::Router, mode: &str) -> serde_json::Value {
+    let request = Request::builder()
+        .method("POST")
+        .uri(format!("/slides/test.tif/verify?mode={}", mode))
+        .body(Body::empty())
+        .unwrap();
+    let response = router.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    serde_json::from_slice(&body).unwrap()
+}
+
+#[tokio::test]
+async fn test_verify_slide_without_manifest() {
+    let source = MockSlideSource::new().with_slide("test.tif", create_tiff_with_jpeg_tile());
+    let tile_service = TileService::new(SlideRegistry::new(source));
+    let router = create_router(tile_service, RouterConfig::without_auth());
+
+    let report = post_verify(&router, "sampled").await;
+    assert_eq!(report["status"], "no_manifest");
+}
+
+#[tokio::test]
+async fn test_verify_slide_sampled_and_full() {
+    let tiff_data = create_tiff_with_jpeg_tile();
+    let manifest = manifest_for(&tiff_data);
+    let source = MockSlideSource::new()
+        .with_slide("test.tif", tiff_data)
+        .with_slide("test.tif.sha256", manifest);
+    let tile_service = TileService::new(SlideRegistry::new(source));
+    let router = create_router(tile_service, RouterConfig::without_auth());
+
+    let report = post_verify(&router, "sampled").await;
+    assert_eq!(report["status"], "verified");
+    assert!(report["blocks_checked"].as_u64().unwrap() >= 2);
+
+    let report = post_verify(&router, "full").await;
+    assert_eq!(report["status"], "verified");
+    assert_eq!(report["mode"], "full");
+
+    // The last result is reported with the slide metadata
+    let request = Request::builder()
+        .uri("/slides/test.tif")
+        .body(Body::empty())
+        .unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let metadata: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(metadata["integrity"]["status"], "verified");
+    assert_eq!(metadata["integrity"]["mode"], "full");
+}
+
+#[tokio::test]
+async fn test_verify_slide_detects_corrupted_tile() {
+    let tiff_data = create_tiff_with_jpeg_tile();
+    let manifest = manifest_for(&tiff_data);
+
+    // Flip a byte in the first tile (tile data starts at offset 1000)
+    let mut corrupted = tiff_data;
+    corrupted[1010] ^= 0xff;
+
+    let source = MockSlideSource::new()
+        .with_slide("test.tif", corrupted)
+        .with_slide("test.tif.sha256", manifest);
+    let registry = SlideRegistry::new(source).with_checksum_verification(true);
+    let router = create_router(TileService::new(registry), RouterConfig::without_auth());
+
+    // Verified at open, before any explicit request
+    let request = Request::builder()
+        .uri("/slides/test.tif")
+        .body(Body::empty())
+        .unwrap();
+    let response = router.clone().oneshot(request).await.unwrap();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let metadata: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(metadata["integrity"]["status"], "failed");
+    assert_eq!(metadata["integrity"]["mismatches"][0], "block 1");
+
+    let report = post_verify(&router, "full").await;
+    assert_eq!(report["status"], "failed");
+    assert_eq!(report["mismatches"][0], "file");
+}
+
+#[tokio::test]
+async fn test_read_verification_rejects_corrupted_tile() {
+    let tiff_data = create_tiff_with_jpeg_tile();
+    let manifest = manifest_for(&tiff_data);
+
+    let tile_status = |data: Vec<u8>| {
+        let source = MockSlideSource::new()
+            .with_slide("test.tif", data)
+            .with_slide("test.tif.sha256", manifest.clone());
+        let registry = SlideRegistry::new(source).with_read_verification(true);
+        let router = create_router(TileService::new(registry), RouterConfig::without_auth());
+        async move {
+            let request = Request::builder()
+                .uri("/tiles/test.tif/0/0/0.jpg")
+                .body(Body::empty())
+                .unwrap();
+            let response = router.oneshot(request).await.unwrap();
+            let status = response.status();
+            let body = response.into_body().collect().await.unwrap().to_bytes();
+            (status, body)
+        }
+    };
+
+    let (status, _) = tile_status(tiff_data.clone()).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Flip a byte in the first tile (tile data starts at offset 1000)
+    let mut corrupted = tiff_data;
+    corrupted[1010] ^= 0xff;
+    let (status, body) = tile_status(corrupted).await;
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    let error: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(error["error"], "data_corrupted");
+}
+
+// =============================================================================
+// Event Channel
+// =============================================================================
+
+#[tokio::test]
+async fn test_event_websocket() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let source = MockSlideSource::new().with_slide("test.tif", create_tiff_with_jpeg_tile());
+    let router = create_router(
+        TileService::new(SlideRegistry::new(source)),
+        RouterConfig::without_auth(),
+    );
+
+    // Plain requests are refused
+    let request = Request::builder().uri("/ws").body(Body::empty()).unwrap();
+    let response = router.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::UPGRADE_REQUIRED);
+    let request = Request::builder()
+        .uri("/ws?events=bogus")
+        .body(Body::empty())
+        .unwrap();
+    let response = router.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = router.clone();
+    tokio::spawn(async move { axum::serve(listener, server).await });
+
+    let mut socket = tokio::net::TcpStream::connect(addr).await.unwrap();
+    socket
+        .write_all(
+            b"GET /ws?slides=test.tif&events=slide_opening,slide_opened HTTP/1.1\r\n\
+              Host: localhost\r\n\
+              Upgrade: websocket\r\n\
+              Connection: Upgrade\r\n\
+              Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\
+              Sec-WebSocket-Version: 13\r\n\r\n",
+        )
+        .await
+        .unwrap();
+
+    // Read the handshake response
+    let mut head = Vec::new();
+    while !head.ends_with(b"\r\n\r\n") {
+        head.push(socket.read_u8().await.unwrap());
+    }
+    let head = String::from_utf8(head).unwrap();
+    assert!(head.starts_with("HTTP/1.1 101"), "{}", head);
+    assert!(head
+        .to_ascii_lowercase()
+        .contains("sec-websocket-accept: s3pplmbitxaq9kygzzhzrbk+xoo="));
+
+    // Opening the slide publishes its events, in order
+    let request = Request::builder()
+        .uri("/tiles/test.tif/0/0/0.jpg")
+        .body(Body::empty())
+        .unwrap();
+    assert_eq!(
+        router.oneshot(request).await.unwrap().status(),
+        StatusCode::OK
+    );
+
+    async fn read_event(socket: &mut tokio::net::TcpStream) -> serde_json::Value {
+        let opcode = socket.read_u8().await.unwrap();
+        assert_eq!(opcode, 0x81, "expected a text frame");
+        let len = socket.read_u8().await.unwrap() as usize;
+        assert!(len < 126);
+        let mut payload = vec![0; len];
+        socket.read_exact(&mut payload).await.unwrap();
+        serde_json::from_slice(&payload).unwrap()
+    }
+    let opening = tokio::time::timeout(std::time::Duration::from_secs(5), read_event(&mut socket))
+        .await
+        .unwrap();
+    assert_eq!(opening["type"], "slide_opening");
+    assert_eq!(opening["slide_id"], "test.tif");
+    let opened = read_event(&mut socket).await;
+    assert_eq!(opened["type"], "slide_opened");
+    assert_eq!(opened["levels"], 1);
+}
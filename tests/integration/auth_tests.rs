@@ -5,7 +5,9 @@
 //! - Expired signatures are rejected
 //! - Invalid signatures are rejected
 //! - Missing auth parameters are handled
+//! - Reloading rotates the secret without a restart
 
+use std::sync::Arc;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use axum::body::Body;
@@ -13,8 +15,11 @@ use axum::http::{Request, StatusCode};
 use http_body_util::BodyExt;
 use tower::ServiceExt;
 
+use wsi_streamer::server::{
+    AccessClaims, ClaimEndpoint, ConfigReloader, ReloadableSettings, SettingsSource,
+};
 use wsi_streamer::slide::SlideRegistry;
-use wsi_streamer::tile::TileService;
+use wsi_streamer::tile::{TileService, Watermark};
 use wsi_streamer::{create_router, RouterConfig, SignedUrlAuth};
 
 use super::test_utils::{create_tiff_with_jpeg_tile, is_valid_jpeg, MockSlideSource};
@@ -461,3 +466,496 @@ async fn test_signature_binds_to_level() {
     let response = router.oneshot(request).await.unwrap();
     assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
 }
+
+// =============================================================================
+// Share Links
+// =============================================================================
+
+/// Create a share link through the signed `POST /slides/{id}/share` endpoint.
+async fn create_share_link(router: &axum::Router, params: &[(&str, &str)]) -> serde_json::Value {
+    let auth = SignedUrlAuth::new(TEST_SECRET);
+    let path = "/slides/test.tif/share";
+    let (signature, expiry) = auth.sign_with_params(path, Duration::from_secs(3600), params);
+
+    let query: String = params
+        .iter()
+        .map(|(key, value)| format!("{}={}&", key, value))
+        .collect();
+    let request = Request::builder()
+        .method("POST")
+        .uri(format!(
+            "{}?{}sig={}&exp={}",
+            path, query, signature, expiry
+        ))
+        .body(Body::empty())
+        .unwrap();
+
+    let response = router.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    serde_json::from_slice(&body).unwrap()
+}
+
+async fn get_status(router: &axum::Router, method: &str, uri: &str) -> StatusCode {
+    let request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .body(Body::empty())
+        .unwrap();
+    router.clone().oneshot(request).await.unwrap().status()
+}
+
+fn share_router() -> axum::Router {
+    let source = MockSlideSource::new()
+        .with_slide("test.tif", create_tiff_with_jpeg_tile())
+        .with_slide("other.tif", create_tiff_with_jpeg_tile());
+    let registry = SlideRegistry::new(source);
+    let tile_service = TileService::new(registry);
+    create_router(tile_service, RouterConfig::new(TEST_SECRET))
+}
+
+#[tokio::test]
+async fn test_share_link_grants_read_only_slide_access() {
+    let router = share_router();
+    let link = create_share_link(&router, &[("ttl", "600")]).await;
+
+    let token = link["token"].as_str().unwrap();
+    assert_eq!(link["slide_id"], "test.tif");
+    assert!(link["max_tiles"].is_null());
+    assert!(link["url"]
+        .as_str()
+        .unwrap()
+        .ends_with(&format!("/share/{}/view", token)));
+
+    // Tiles and metadata of the shared slide
+    let tile = format!("/tiles/test.tif/0/0/0.jpg?st={}", token);
+    assert_eq!(get_status(&router, "GET", &tile).await, StatusCode::OK);
+    let metadata = format!("/slides/test.tif?st={}", token);
+    assert_eq!(get_status(&router, "GET", &metadata).await, StatusCode::OK);
+
+    // Nothing else
+    let other = format!("/tiles/other.tif/0/0/0.jpg?st={}", token);
+    assert_eq!(
+        get_status(&router, "GET", &other).await,
+        StatusCode::UNAUTHORIZED
+    );
+    let list = format!("/slides?st={}", token);
+    assert_eq!(
+        get_status(&router, "GET", &list).await,
+        StatusCode::UNAUTHORIZED
+    );
+    let reshare = format!("/slides/test.tif/share?st={}", token);
+    assert_eq!(
+        get_status(&router, "POST", &reshare).await,
+        StatusCode::FORBIDDEN
+    );
+}
+
+#[tokio::test]
+async fn test_viewer_token_is_read_only() {
+    let router = share_router();
+    let auth = SignedUrlAuth::new(TEST_SECRET);
+    let (token, expiry) = auth.generate_viewer_token("test.tif", Duration::from_secs(3600));
+    let vt = |path: &str| format!("{}?vt={}&exp={}", path, token, expiry);
+
+    let tile = vt("/tiles/test.tif/0/0/0.jpg");
+    assert_eq!(get_status(&router, "GET", &tile).await, StatusCode::OK);
+    let metadata = vt("/slides/test.tif");
+    assert_eq!(get_status(&router, "GET", &metadata).await, StatusCode::OK);
+
+    // The public viewer page mints these, so they can't write to the bucket
+    for path in [
+        "/slides/test.tif/views",
+        "/slides/test.tif/annotations",
+        "/slides/test.tif/share",
+        "/slides/test.tif/verify",
+    ] {
+        assert_eq!(
+            get_status(&router, "POST", &vt(path)).await,
+            StatusCode::FORBIDDEN,
+            "{}",
+            path
+        );
+    }
+    let views = vt("/slides/test.tif/views");
+    assert_eq!(
+        get_status(&router, "GET", &views).await,
+        StatusCode::FORBIDDEN
+    );
+}
+
+#[tokio::test]
+async fn test_share_link_tile_limit() {
+    let router = share_router();
+    let link = create_share_link(&router, &[("max_tiles", "2")]).await;
+    let token = link["token"].as_str().unwrap();
+    assert_eq!(link["max_tiles"], 2);
+
+    let tile = format!("/tiles/test.tif/0/0/0.jpg?st={}", token);
+    assert_eq!(get_status(&router, "GET", &tile).await, StatusCode::OK);
+    assert_eq!(get_status(&router, "GET", &tile).await, StatusCode::OK);
+    assert_eq!(
+        get_status(&router, "GET", &tile).await,
+        StatusCode::FORBIDDEN
+    );
+
+    // Metadata requests do not count against the limit
+    let metadata = format!("/slides/test.tif?st={}", token);
+    assert_eq!(get_status(&router, "GET", &metadata).await, StatusCode::OK);
+
+    // Other pixel endpoints count too
+    let link = create_share_link(&router, &[("max_tiles", "1")]).await;
+    let token = link["token"].as_str().unwrap();
+    let thumbnail = format!("/slides/test.tif/thumbnail.jpg?st={}", token);
+    assert_eq!(get_status(&router, "GET", &thumbnail).await, StatusCode::OK);
+    let region = format!("/region/test.tif?x=0&y=0&width=64&height=64&st={}", token);
+    assert_eq!(
+        get_status(&router, "GET", &region).await,
+        StatusCode::FORBIDDEN
+    );
+
+    // Endpoints outside the viewer's read scope are refused
+    let sample = format!("/slides/test.tif/sample?include_tiles=true&st={}", token);
+    assert_eq!(
+        get_status(&router, "GET", &sample).await,
+        StatusCode::FORBIDDEN
+    );
+}
+
+#[tokio::test]
+async fn test_share_viewer_page() {
+    let router = share_router();
+    let link = create_share_link(&router, &[]).await;
+    let token = link["token"].as_str().unwrap();
+
+    let request = Request::builder()
+        .uri(format!("/share/{}/view", token))
+        .body(Body::empty())
+        .unwrap();
+    let response = router.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let html = String::from_utf8(body.to_vec()).unwrap();
+    assert!(html.contains(&format!("?st={}", token)));
+
+    // Tampered tokens are rejected
+    let tampered = token.replace(".0.", ".100.");
+    assert_eq!(
+        get_status(&router, "GET", &format!("/share/{}/view", tampered)).await,
+        StatusCode::UNAUTHORIZED
+    );
+}
+
+#[tokio::test]
+async fn test_compare_viewer_page() {
+    let router = share_router();
+
+    let request = Request::builder()
+        .uri("/view/compare?slides=test.tif,other.tif")
+        .body(Body::empty())
+        .unwrap();
+    let response = router.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let html = String::from_utf8(body.to_vec()).unwrap();
+
+    // Each pane gets a viewer token for its own slide only
+    let auth_queries: Vec<&str> = html
+        .split("\"authQuery\":\"")
+        .skip(1)
+        .map(|rest| &rest[..rest.find('"').unwrap()])
+        .collect();
+    assert_eq!(auth_queries.len(), 2);
+    let other_tile = |query: &str| format!("/tiles/other.tif/0/0/0.jpg{}", query);
+    assert_eq!(
+        get_status(&router, "GET", &other_tile(auth_queries[1])).await,
+        StatusCode::OK
+    );
+    assert_eq!(
+        get_status(&router, "GET", &other_tile(auth_queries[0])).await,
+        StatusCode::UNAUTHORIZED
+    );
+
+    assert_eq!(
+        get_status(&router, "GET", "/view/compare?slides=test.tif").await,
+        StatusCode::BAD_REQUEST
+    );
+    assert_eq!(
+        get_status(&router, "GET", "/view/compare?slides=test.tif,missing.tif").await,
+        StatusCode::NOT_FOUND
+    );
+}
+
+#[tokio::test]
+async fn test_share_link_rejects_invalid_ttl() {
+    let router = share_router();
+    let auth = SignedUrlAuth::new(TEST_SECRET);
+    let path = "/slides/test.tif/share";
+    let (signature, expiry) =
+        auth.sign_with_params(path, Duration::from_secs(3600), &[("ttl", "0")]);
+
+    let uri = format!("{}?ttl=0&sig={}&exp={}", path, signature, expiry);
+    assert_eq!(
+        get_status(&router, "POST", &uri).await,
+        StatusCode::BAD_REQUEST
+    );
+}
+
+// =============================================================================
+// Access Claims
+// =============================================================================
+
+fn claims_uri(path: &str, claims: &AccessClaims) -> String {
+    let auth = SignedUrlAuth::new(TEST_SECRET);
+    format!(
+        "{}?{}",
+        path,
+        auth.generate_claims_query(claims, Duration::from_secs(3600))
+    )
+}
+
+#[tokio::test]
+async fn test_claims_grant_matching_slides() {
+    let router = share_router();
+    let claims = AccessClaims::for_slides("test.*")
+        .with_endpoints(vec![ClaimEndpoint::Tiles, ClaimEndpoint::Metadata]);
+
+    // One signature covers every tile and the metadata of the slide
+    let tile = claims_uri("/tiles/test.tif/0/0/0.jpg", &claims);
+    assert_eq!(get_status(&router, "GET", &tile).await, StatusCode::OK);
+    let metadata = claims_uri("/slides/test.tif", &claims);
+    assert_eq!(get_status(&router, "GET", &metadata).await, StatusCode::OK);
+
+    // Other slides and endpoints are denied
+    let other = claims_uri("/tiles/other.tif/0/0/0.jpg", &claims);
+    assert_eq!(
+        get_status(&router, "GET", &other).await,
+        StatusCode::FORBIDDEN
+    );
+    for path in [
+        "/slides/test.tif/thumbnail",
+        "/slides/test.tif/thumbnail.jpg",
+    ] {
+        let thumbnail = claims_uri(path, &claims);
+        assert_eq!(
+            get_status(&router, "GET", &thumbnail).await,
+            StatusCode::FORBIDDEN
+        );
+    }
+    let list = claims_uri("/slides", &claims);
+    assert_eq!(
+        get_status(&router, "GET", &list).await,
+        StatusCode::FORBIDDEN
+    );
+}
+
+#[tokio::test]
+async fn test_claims_min_level() {
+    let router = share_router();
+    let claims = AccessClaims::for_slides("test.tif").with_min_level(1);
+
+    let tile = claims_uri("/tiles/test.tif/0/0/0.jpg", &claims);
+    assert_eq!(
+        get_status(&router, "GET", &tile).await,
+        StatusCode::FORBIDDEN
+    );
+    let metadata = claims_uri("/slides/test.tif", &claims);
+    assert_eq!(get_status(&router, "GET", &metadata).await, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_raw_slide_requires_explicit_grant() {
+    let router = share_router();
+    let auth = SignedUrlAuth::new(TEST_SECRET);
+    let path = "/slides/test.tif/raw";
+
+    // Viewer and share links can't download the file
+    let (token, expiry) = auth.generate_viewer_token("test.tif", Duration::from_secs(3600));
+    let viewer = format!("{}?vt={}&exp={}", path, token, expiry);
+    assert_eq!(
+        get_status(&router, "GET", &viewer).await,
+        StatusCode::FORBIDDEN
+    );
+    let link = create_share_link(&router, &[]).await;
+    let share = format!("{}?st={}", path, link["token"].as_str().unwrap());
+    assert_eq!(
+        get_status(&router, "GET", &share).await,
+        StatusCode::FORBIDDEN
+    );
+
+    // Nor claims that don't list raw
+    let claims = AccessClaims::for_slides("test.tif");
+    assert_eq!(
+        get_status(&router, "GET", &claims_uri(path, &claims)).await,
+        StatusCode::FORBIDDEN
+    );
+
+    // A path signature or explicit raw claims can
+    let claims = claims.with_endpoints(vec![ClaimEndpoint::Raw]);
+    assert_eq!(
+        get_status(&router, "GET", &claims_uri(path, &claims)).await,
+        StatusCode::OK
+    );
+    let (signature, expiry) = auth.sign(path, Duration::from_secs(3600));
+    let signed = format!("{}?sig={}&exp={}", path, signature, expiry);
+    assert_eq!(get_status(&router, "GET", &signed).await, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_claims_require_signature() {
+    let router = share_router();
+    let claims = AccessClaims::for_slides("test.tif").with_min_level(1);
+    let uri = claims_uri("/tiles/test.tif/0/0/0.jpg", &claims);
+
+    // Dropping the level restriction breaks the signature
+    let tampered = uri.replace("%3Bmin_level%3D1", "");
+    assert_eq!(
+        get_status(&router, "GET", &tampered).await,
+        StatusCode::UNAUTHORIZED
+    );
+
+    // Claims still need their signature
+    let auth = SignedUrlAuth::new(TEST_SECRET);
+    let claims = AccessClaims::for_slides("test.tif");
+    let (signature, expiry) = auth.sign_claims(&claims, Duration::from_secs(3600));
+    let uri = format!(
+        "/slides/test.tif?claims=slide%3Dtest.tif&exp={}&sig={}",
+        expiry, signature
+    );
+    assert_eq!(get_status(&router, "GET", &uri).await, StatusCode::OK);
+    let missing_sig = format!("/slides/test.tif?claims=slide%3Dtest.tif&exp={}", expiry);
+    assert_eq!(
+        get_status(&router, "GET", &missing_sig).await,
+        StatusCode::UNAUTHORIZED
+    );
+}
+
+#[tokio::test]
+async fn test_claims_toggle_watermark() {
+    let source = MockSlideSource::new().with_slide("test.tif", create_tiff_with_jpeg_tile());
+    let tile_service = TileService::new(SlideRegistry::new(source))
+        .with_watermark(Watermark::from_text("EXPORT").with_opacity(1.0));
+    let router = create_router(tile_service, RouterConfig::new(TEST_SECRET));
+    let fetch = |claims: AccessClaims| {
+        let uri = claims_uri("/tiles/test.tif/0/0/0.jpg", &claims);
+        let router = router.clone();
+        async move {
+            let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+            let response = router.oneshot(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let etag = response.headers()["etag"].clone();
+            let body = response.into_body().collect().await.unwrap().to_bytes();
+            (etag, image::load_from_memory(&body).unwrap().into_rgb8())
+        }
+    };
+
+    // Off unless the claims ask for it
+    let (plain_etag, plain) = fetch(AccessClaims::for_slides("test.tif")).await;
+    let (off_etag, _) = fetch(AccessClaims::for_slides("test.tif").with_watermark(false)).await;
+    assert_eq!(off_etag, plain_etag);
+
+    let (marked_etag, marked) =
+        fetch(AccessClaims::for_slides("test.tif").with_watermark(true)).await;
+    assert_ne!(marked_etag, plain_etag);
+    assert_eq!(marked.dimensions(), plain.dimensions());
+
+    // The stamp darkens the bottom-right corner and leaves the rest alone
+    let (width, height) = marked.dimensions();
+    let luma = |tile: &image::RgbImage, x: u32, y: u32| {
+        tile.get_pixel(x, y)
+            .0
+            .iter()
+            .map(|&v| v as i32)
+            .sum::<i32>()
+    };
+    let corner = (width - 12, height - 12);
+    assert!(luma(&plain, corner.0, corner.1) - luma(&marked, corner.0, corner.1) > 100);
+    assert!((luma(&plain, 4, 4) - luma(&marked, 4, 4)).abs() < 30);
+}
+
+// =============================================================================
+// Reload
+// =============================================================================
+
+struct RotatedSettings;
+
+#[async_trait::async_trait]
+impl SettingsSource for RotatedSettings {
+    async fn load(&self) -> Result<ReloadableSettings, String> {
+        Ok(ReloadableSettings {
+            auth_secret: Some("rotated-secret".to_string()),
+            cors_origins: Some(vec!["https://viewer.example".to_string()]),
+            cache_max_age: 60,
+            log_filter: None,
+        })
+    }
+}
+
+fn signed_uri(auth: &SignedUrlAuth, path: &str) -> String {
+    let (signature, expiry) = auth.sign(path, Duration::from_secs(3600));
+    format!("{}?sig={}&exp={}", path, signature, expiry)
+}
+
+#[tokio::test]
+async fn test_reload_rotates_secret_without_restart() {
+    let source = MockSlideSource::new().with_slide("test.tif", create_tiff_with_jpeg_tile());
+    let tile_service = TileService::new(SlideRegistry::new(source));
+    let reloader = Arc::new(ConfigReloader::new(RotatedSettings));
+    let router = create_router(
+        tile_service,
+        RouterConfig::new(TEST_SECRET).with_reloader(reloader),
+    );
+    let old_auth = SignedUrlAuth::new(TEST_SECRET);
+    let new_auth = SignedUrlAuth::new("rotated-secret");
+    let tile = "/tiles/test.tif/0/0/0.jpg";
+    let old_tile_uri = signed_uri(&old_auth, tile);
+
+    let request = Request::post(signed_uri(&old_auth, "/admin/reload"))
+        .body(Body::empty())
+        .unwrap();
+    let response = router.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let report: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(
+        report["changed"],
+        serde_json::json!(["auth_secret", "cors_origins", "cache_max_age"])
+    );
+
+    // The new secret signs, and URLs signed just before still verify
+    let request = Request::get(signed_uri(&new_auth, tile))
+        .header("Origin", "https://viewer.example")
+        .body(Body::empty())
+        .unwrap();
+    let response = router.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["cache-control"], "public, max-age=60");
+    assert_eq!(
+        response.headers()["access-control-allow-origin"],
+        "https://viewer.example"
+    );
+    assert_eq!(
+        get_status(&router, "GET", &old_tile_uri).await,
+        StatusCode::OK
+    );
+
+    // Other origins are no longer allowed
+    let request = Request::get(signed_uri(&new_auth, tile))
+        .header("Origin", "https://other.example")
+        .body(Body::empty())
+        .unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    assert!(!response
+        .headers()
+        .contains_key("access-control-allow-origin"));
+}
+
+#[tokio::test]
+async fn test_reload_not_configured() {
+    let router = share_router();
+    let uri = signed_uri(&SignedUrlAuth::new(TEST_SECRET), "/admin/reload");
+    assert_eq!(
+        get_status(&router, "POST", &uri).await,
+        StatusCode::NOT_FOUND
+    );
+}
@@ -14,10 +14,12 @@ use http_body_util::BodyExt;
 use tower::ServiceExt;
 
 use wsi_streamer::slide::SlideRegistry;
-use wsi_streamer::tile::TileService;
+use wsi_streamer::tile::{TileRequest, TileService};
 use wsi_streamer::{create_router, RouterConfig};
 
-use super::test_utils::{create_tiff_with_jpeg_tile, is_valid_jpeg, MockSlideSource};
+use super::test_utils::{
+    create_tiff_with_jpeg_tile, is_valid_jpeg, MockSlideSource, VersionedSlideSource,
+};
 
 // =============================================================================
 // Tile Cache Effectiveness
@@ -437,6 +439,79 @@ async fn test_slide_metadata_cached_between_tile_requests() {
     // (Not strictly asserting timing as it can vary)
 }
 
+#[tokio::test]
+async fn test_warm_slides_opens_matching_slides_once() {
+    let source = MockSlideSource::new()
+        .with_slide("lab/a.tif", create_tiff_with_jpeg_tile())
+        .with_slide("lab/b.tif", create_tiff_with_jpeg_tile())
+        .with_slide("other/c.tif", create_tiff_with_jpeg_tile());
+    let registry = Arc::new(SlideRegistry::new(source));
+
+    let slide_ids = registry
+        .resolve_slide_patterns(&["lab/*.tif".to_string()])
+        .await
+        .unwrap();
+    assert_eq!(slide_ids, vec!["lab/a.tif", "lab/b.tif"]);
+
+    let report = registry.warm(slide_ids, 2).await;
+    assert_eq!(report.slides, 2);
+    assert!(report.tiles >= 2);
+    assert!(report.failed.is_empty());
+    assert_eq!(registry.cached_count().await, 2);
+
+    // Viewers reuse the warmed slide instead of reopening it
+    registry.get_slide("lab/a.tif").await.unwrap();
+    assert_eq!(registry.source().get_request_count("lab/a.tif").await, 1);
+    assert_eq!(registry.source().get_request_count("other/c.tif").await, 0);
+}
+
+#[tokio::test]
+async fn test_replaced_slide_is_reopened_and_recached() {
+    // A one-block cache so tile reads reach storage
+    let source = VersionedSlideSource::new(create_tiff_with_jpeg_tile());
+    let tile_service = TileService::new(SlideRegistry::with_capacity(source, 10, 256, 1));
+    let registry = tile_service.registry().clone();
+
+    let first = tile_service
+        .get_tile(TileRequest::new("test.tif", 0, 0, 0))
+        .await
+        .unwrap();
+    assert!(!first.cache_hit);
+    assert_eq!(registry.source().create_count(), 1);
+
+    // A read against the old version reopens the slide and retries
+    registry.source().replace();
+    let response = tile_service
+        .get_tile(TileRequest::new("test.tif", 0, 1, 0))
+        .await
+        .unwrap();
+    assert!(!response.cache_hit);
+    assert_eq!(registry.source().create_count(), 2);
+    let slide = registry.cached_slide("test.tif").unwrap();
+    assert_eq!(slide.source_etag(), Some("\"v2\""));
+
+    // Tiles cached under the old ETag are not served for the new version
+    let response = tile_service
+        .get_tile(TileRequest::new("test.tif", 0, 0, 0))
+        .await
+        .unwrap();
+    assert!(!response.cache_hit);
+}
+
+#[tokio::test]
+async fn test_revalidate_drops_replaced_slides() {
+    let source = VersionedSlideSource::new(create_tiff_with_jpeg_tile());
+    let registry = SlideRegistry::new(source);
+    registry.get_slide("test.tif").await.unwrap();
+
+    assert!(registry.revalidate_slides().await.is_empty());
+    assert_eq!(registry.cached_count().await, 1);
+
+    registry.source().replace();
+    assert_eq!(registry.revalidate_slides().await, vec!["test.tif"]);
+    assert_eq!(registry.cached_count().await, 0);
+}
+
 // =============================================================================
 // Default Quality Caching
 // =============================================================================
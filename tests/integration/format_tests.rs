@@ -5,10 +5,15 @@
 //! - BigTIFF files are parsed correctly
 //! - SVS JPEGTables handling works correctly
 //! - Decoded tiles are valid images
+//! - Orientation and ICC profile tags are honored
+
+use std::io::Cursor;
 
 use axum::body::Body;
 use axum::http::{Request, StatusCode};
 use http_body_util::BodyExt;
+use image::codecs::jpeg::JpegDecoder;
+use image::ImageDecoder;
 use tower::ServiceExt;
 
 use wsi_streamer::slide::SlideRegistry;
@@ -16,9 +21,9 @@ use wsi_streamer::tile::TileService;
 use wsi_streamer::{create_router, RouterConfig};
 
 use super::test_utils::{
-    create_bigtiff_with_jpeg_tile, create_svs_with_jpeg_tables, create_tiff_with_jpeg_tile,
-    create_tiff_with_jpeg_tile_endian, is_bigtiff_magic, is_tiff_magic, is_valid_jpeg,
-    ByteOrderType, MockSlideSource,
+    create_bigtiff_with_jpeg_tile, create_oriented_tiff, create_svs_with_jpeg_tables,
+    create_tiff_with_jpeg_tile, create_tiff_with_jpeg_tile_endian, is_bigtiff_magic, is_tiff_magic,
+    is_valid_jpeg, ByteOrderType, MockSlideSource,
 };
 
 // =============================================================================
@@ -460,3 +465,109 @@ async fn test_corner_tiles() {
         );
     }
 }
+
+// =============================================================================
+// Orientation and Color Profile Tests
+// =============================================================================
+
+#[tokio::test]
+async fn test_rotated_slide_is_served_upright() {
+    let profile: Vec<u8> = (0..3000u32).map(|i| (i % 251) as u8).collect();
+    let get = |icc_profiles: bool, uri: &'static str| {
+        // Orientation 6: stored 512x256, displayed 256x512
+        let source =
+            MockSlideSource::new().with_slide("rotated.tif", create_oriented_tiff(6, &profile));
+        let tile_service =
+            TileService::new(SlideRegistry::new(source)).with_icc_profiles(icc_profiles);
+        let router = create_router(tile_service, RouterConfig::without_auth());
+        let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+        async move { router.oneshot(request).await.unwrap() }
+    };
+
+    let response = get(true, "/slides/rotated.tif/info").await;
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let info: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(info["width"], 256);
+    assert_eq!(info["height"], 512);
+    assert_eq!(info["levels"][0]["tiles_x"], 1);
+    assert_eq!(info["levels"][0]["tiles_y"], 2);
+
+    // Display pixel (x, y) shows stored pixel (y, 255 - x); stored red
+    // follows x within each stored tile and green follows y
+    let near = |actual: u8, expected: u8| actual.abs_diff(expected) <= 8;
+    for (uri, top) in [
+        ("/tiles/rotated.tif/0/0/0.jpg", 0u32),
+        ("/tiles/rotated.tif/0/0/1.jpg", 256),
+    ] {
+        let response = get(true, uri).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+
+        let mut decoder = JpegDecoder::new(Cursor::new(&body[..])).unwrap();
+        assert_eq!(decoder.icc_profile().unwrap(), Some(profile.clone()));
+
+        let tile = image::load_from_memory(&body).unwrap().into_rgb8();
+        assert_eq!(tile.dimensions(), (256, 256));
+        for (x, y) in [(10, 200), (200, 40), (128, 128)] {
+            let pixel = tile.get_pixel(x, y).0;
+            let stored_x = (top + y) % 256;
+            assert!(near(pixel[0], stored_x as u8), "{} ({}, {})", uri, x, y);
+            assert!(near(pixel[1], (255 - x) as u8), "{} ({}, {})", uri, x, y);
+        }
+    }
+
+    // The stored grid is 2x1 but the displayed one is 1x2
+    let response = get(true, "/tiles/rotated.tif/0/1/0.jpg").await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    // Tiles are composed, so the stored grid is not exposed
+    let response = get(true, "/slides/rotated.tif/levels/0/grid").await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let response = get(false, "/tiles/rotated.tif/0/0/0.jpg").await;
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let mut decoder = JpegDecoder::new(Cursor::new(&body[..])).unwrap();
+    assert_eq!(decoder.icc_profile().unwrap(), None);
+
+    // Embedding is opt-in
+    let source =
+        MockSlideSource::new().with_slide("rotated.tif", create_oriented_tiff(6, &profile));
+    let router = create_router(
+        TileService::new(SlideRegistry::new(source)),
+        RouterConfig::without_auth(),
+    );
+    let request = Request::builder()
+        .uri("/tiles/rotated.tif/0/0/0.jpg")
+        .body(Body::empty())
+        .unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let mut decoder = JpegDecoder::new(Cursor::new(&body[..])).unwrap();
+    assert_eq!(decoder.icc_profile().unwrap(), None);
+}
+
+#[tokio::test]
+async fn test_rotated_slide_decode_failure_counts_toward_quarantine() {
+    // Drop the frame header of the stored tiles so they can't be decoded
+    let mut tiff = create_oriented_tiff(6, &[]);
+    let sof = tiff.windows(2).position(|w| w == [0xFF, 0xC0]).unwrap();
+    tiff[sof + 1] = 0xFE;
+
+    let source = MockSlideSource::new().with_slide("rotated.tif", tiff);
+    let registry = SlideRegistry::new(source).with_quarantine_threshold(1);
+    let router = create_router(TileService::new(registry), RouterConfig::without_auth());
+    let get = |uri: &'static str| {
+        let router = router.clone();
+        let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+        async move { router.oneshot(request).await.unwrap() }
+    };
+
+    let response = get("/tiles/rotated.tif/0/0/0.jpg").await;
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let error: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(error["error"], "decode_error");
+
+    let response = get("/tiles/rotated.tif/0/0/1.jpg").await;
+    assert_eq!(response.status(), StatusCode::LOCKED);
+}
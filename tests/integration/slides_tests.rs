@@ -4,19 +4,21 @@
 //! - Slides listing returns correct results
 //! - Extension filtering (.svs, .tif, .tiff)
 //! - Pagination with limit parameter
+//! - Folder navigation with prefix and delimiter
 //! - Authentication requirements
 //! - Empty bucket handling
+//! - Conformance status of opened slides
 
 use axum::body::Body;
 use axum::http::{Request, StatusCode};
 use http_body_util::BodyExt;
 use tower::ServiceExt;
 
-use wsi_streamer::slide::SlideRegistry;
+use wsi_streamer::slide::{MemorySlideSource, SlideRegistry};
 use wsi_streamer::tile::TileService;
 use wsi_streamer::{create_router, RouterConfig, SignedUrlAuth};
 
-use super::test_utils::{create_tiff_with_jpeg_tile, MockSlideSource};
+use super::test_utils::{create_strip_tiff, create_tiff_with_jpeg_tile, MockSlideSource};
 
 // =============================================================================
 // Basic Functionality Tests
@@ -364,3 +366,251 @@ async fn test_slides_response_no_cursor_when_all_returned() {
     // next_cursor should not be present when all results are returned
     assert!(result.get("next_cursor").is_none());
 }
+
+#[tokio::test]
+async fn test_slides_list_folders_with_delimiter() {
+    let tiff_data = create_tiff_with_jpeg_tile();
+
+    let source = MemorySlideSource::new()
+        .with_slide("case-123/a.svs", tiff_data.clone())
+        .with_slide("case-123/stains/b.svs", tiff_data.clone())
+        .with_slide("case-124/c.svs", tiff_data.clone())
+        .with_slide("d.svs", tiff_data);
+
+    let registry = SlideRegistry::new(source);
+    let tile_service = TileService::new(registry);
+    let router = create_router(tile_service, RouterConfig::without_auth());
+
+    let list = |uri: &'static str| {
+        let router = router.clone();
+        async move {
+            let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+            let response = router.oneshot(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = response.into_body().collect().await.unwrap().to_bytes();
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+        }
+    };
+
+    // The bucket root: top-level folders and slides
+    let result = list("/slides?delimiter=/").await;
+    assert_eq!(
+        result["folders"],
+        serde_json::json!(["case-123/", "case-124/"])
+    );
+    assert_eq!(result["slides"], serde_json::json!(["d.svs"]));
+
+    // One folder down
+    let result = list("/slides?prefix=case-123/&delimiter=/").await;
+    assert_eq!(result["folders"], serde_json::json!(["case-123/stains/"]));
+    assert_eq!(result["slides"], serde_json::json!(["case-123/a.svs"]));
+
+    // Without a delimiter the listing stays flat
+    let result = list("/slides").await;
+    assert_eq!(result["slides"].as_array().unwrap().len(), 4);
+    assert!(result.get("folders").is_none());
+}
+
+// =============================================================================
+// Conformance Tests
+// =============================================================================
+
+#[tokio::test]
+async fn test_slides_list_reports_conformance() {
+    let source = MockSlideSource::new()
+        .with_slide("good.tif", create_tiff_with_jpeg_tile())
+        .with_slide("strips.tif", create_strip_tiff())
+        .with_slide("unopened.tif", create_tiff_with_jpeg_tile());
+    let registry = SlideRegistry::new(source);
+    let tile_service = TileService::new(registry);
+    let router = create_router(tile_service, RouterConfig::without_auth());
+
+    // Open two of the slides
+    for slide in ["good.tif", "strips.tif"] {
+        let request = Request::builder()
+            .uri(format!("/slides/{}", slide))
+            .body(Body::empty())
+            .unwrap();
+        router.clone().oneshot(request).await.unwrap();
+    }
+
+    let request = Request::builder()
+        .uri("/slides")
+        .body(Body::empty())
+        .unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let result: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+    let conformance = &result["conformance"];
+    // The fixture's inline JPEG tables are accepted with a warning
+    assert_eq!(conformance["good.tif"]["status"], "warnings");
+    assert!(conformance["good.tif"]["reason"].is_null());
+    assert_eq!(conformance["strips.tif"]["status"], "unsupported");
+    assert!(conformance["strips.tif"]["reason"].is_string());
+    assert!(conformance.get("unopened.tif").is_none());
+}
+
+// =============================================================================
+// Collection Sprite Sheet Tests
+// =============================================================================
+
+#[tokio::test]
+async fn test_collection_sprites() {
+    let tiff_data = create_tiff_with_jpeg_tile();
+
+    let source = MockSlideSource::new()
+        .with_slide("case-1/a.svs", tiff_data.clone())
+        .with_slide("case-1/b.svs", tiff_data.clone())
+        .with_slide("case-2/c.svs", tiff_data);
+
+    let registry = SlideRegistry::new(source);
+    let tile_service = TileService::new(registry);
+    let router = create_router(tile_service, RouterConfig::without_auth());
+
+    let request = Request::builder()
+        .uri("/collections/case-1/sprites?size=64")
+        .body(Body::empty())
+        .unwrap();
+
+    let response = router.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let result: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+    assert_eq!(result["collection_id"], "case-1");
+    assert_eq!(result["size"], 64);
+    assert_eq!(result["truncated"], false);
+    assert!(result["image"]
+        .as_str()
+        .unwrap()
+        .starts_with("data:image/jpeg;base64,"));
+
+    // Only slides under the collection prefix are included
+    let sprites = result["sprites"].as_array().unwrap();
+    let failed = result["failed"].as_array().unwrap();
+    assert_eq!(sprites.len() + failed.len(), 2);
+    for sprite in sprites {
+        assert!(sprite["slide_id"].as_str().unwrap().starts_with("case-1/"));
+        assert!(sprite["width"].as_u64().unwrap() <= 64);
+    }
+}
+
+#[tokio::test]
+async fn test_collection_sprites_empty_collection() {
+    let source = MockSlideSource::new().with_slide("case-1/a.svs", create_tiff_with_jpeg_tile());
+
+    let registry = SlideRegistry::new(source);
+    let tile_service = TileService::new(registry);
+    let router = create_router(tile_service, RouterConfig::without_auth());
+
+    let request = Request::builder()
+        .uri("/collections/unknown/sprites")
+        .body(Body::empty())
+        .unwrap();
+
+    let response = router.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+// =============================================================================
+// Saved View Tests
+// =============================================================================
+
+fn view_router() -> axum::Router {
+    let source = MockSlideSource::new().with_slide("slide.svs", create_tiff_with_jpeg_tile());
+    let registry = SlideRegistry::new(source);
+    let tile_service = TileService::new(registry);
+    create_router(tile_service, RouterConfig::without_auth())
+}
+
+fn save_view_request(slide_id: &str, body: serde_json::Value) -> Request<Body> {
+    Request::builder()
+        .method("POST")
+        .uri(format!("/slides/{}/views", slide_id))
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_save_and_get_view() {
+    let router = view_router();
+
+    let body = serde_json::json!({
+        "name": "margin",
+        "level": 0,
+        "center_x": 1000.0,
+        "center_y": 500.0,
+        "zoom": 2.0,
+        "filters": { "brightness": 1.1 }
+    });
+    let response = router
+        .clone()
+        .oneshot(save_view_request("slide.svs", body))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let saved: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(saved["url"], "/slides/slide.svs/views/margin");
+
+    let request = Request::builder()
+        .uri("/slides/slide.svs/views/margin")
+        .body(Body::empty())
+        .unwrap();
+    let response = router.oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let loaded: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(loaded["slide_id"], "slide.svs");
+    assert_eq!(loaded["center_x"], 1000.0);
+    assert_eq!(loaded["zoom"], 2.0);
+    assert_eq!(loaded["filters"]["brightness"], 1.1);
+}
+
+#[tokio::test]
+async fn test_save_view_outside_slide_rejected() {
+    let body = serde_json::json!({
+        "name": "margin",
+        "level": 0,
+        "center_x": 1.0e9,
+        "center_y": 500.0,
+        "zoom": 2.0
+    });
+    let response = view_router()
+        .oneshot(save_view_request("slide.svs", body))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_save_view_invalid_name_rejected() {
+    let body = serde_json::json!({
+        "name": "../escape",
+        "level": 0,
+        "center_x": 10.0,
+        "center_y": 10.0,
+        "zoom": 1.0
+    });
+    let response = view_router()
+        .oneshot(save_view_request("slide.svs", body))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_get_missing_view() {
+    let request = Request::builder()
+        .uri("/slides/slide.svs/views/none")
+        .body(Body::empty())
+        .unwrap();
+    let response = view_router().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
@@ -8,6 +8,7 @@ use bytes::Bytes;
 use image::codecs::jpeg::JpegEncoder;
 use image::{GrayImage, Luma, Rgb, RgbImage};
 use std::collections::HashMap;
+use std::io::Write;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -102,6 +103,10 @@ impl RangeReader for TrackingMockReader {
 pub struct MockSlideSource {
     slides: HashMap<String, Bytes>,
     request_counts: Arc<RwLock<HashMap<String, usize>>>,
+    /// Objects written through `put_object`
+    objects: Arc<RwLock<HashMap<String, Bytes>>>,
+    /// Fail readiness checks, as with expired credentials
+    unreachable: bool,
 }
 
 impl MockSlideSource {
@@ -109,9 +114,18 @@ impl MockSlideSource {
         Self {
             slides: HashMap::new(),
             request_counts: Arc::new(RwLock::new(HashMap::new())),
+            objects: Arc::new(RwLock::new(HashMap::new())),
+            unreachable: false,
         }
     }
 
+    /// Fail readiness checks.
+    #[allow(dead_code)]
+    pub fn unreachable(mut self) -> Self {
+        self.unreachable = true;
+        self
+    }
+
     pub fn with_slide(mut self, slide_id: impl Into<String>, data: Vec<u8>) -> Self {
         self.slides.insert(slide_id.into(), Bytes::from(data));
         self
@@ -154,7 +168,12 @@ impl SlideSource for MockSlideSource {
             *counts.entry(slide_id.to_string()).or_insert(0) += 1;
         }
 
-        match self.slides.get(slide_id) {
+        let data = match self.slides.get(slide_id) {
+            Some(data) => Some(data.clone()),
+            None => self.objects.read().await.get(slide_id).cloned(),
+        };
+
+        match data {
             Some(data) => Ok(TrackingMockReader::new(
                 data.to_vec(),
                 format!("mock://{}", slide_id),
@@ -163,11 +182,26 @@ impl SlideSource for MockSlideSource {
         }
     }
 
+    async fn check_ready(&self) -> Result<(), IoError> {
+        if self.unreachable {
+            return Err(IoError::S3(
+                "ExpiredToken: the security token has expired".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    async fn put_object(&self, key: &str, data: Bytes, _content_type: &str) -> Result<(), IoError> {
+        self.objects.write().await.insert(key.to_string(), data);
+        Ok(())
+    }
+
     async fn list_slides(
         &self,
         limit: u32,
         _cursor: Option<&str>,
         prefix: Option<&str>,
+        _delimiter: Option<&str>,
     ) -> Result<SlideListResult, IoError> {
         // Get all slide keys that have supported extensions
         let mut slides: Vec<String> = self
@@ -195,11 +229,103 @@ impl SlideSource for MockSlideSource {
 
         Ok(SlideListResult {
             slides,
+            folders: vec![],
             next_cursor,
         })
     }
 }
 
+// =============================================================================
+// Replaceable Slide Source
+// =============================================================================
+
+/// A slide source whose objects can be overwritten in place.
+///
+/// Each overwrite gets a new ETag. Like conditional S3 reads, readers opened
+/// on an older version fail with `IoError::ObjectChanged`.
+pub struct VersionedSlideSource {
+    data: Bytes,
+    version: Arc<AtomicUsize>,
+    create_count: Arc<AtomicUsize>,
+}
+
+impl VersionedSlideSource {
+    pub fn new(data: Vec<u8>) -> Self {
+        Self {
+            data: Bytes::from(data),
+            version: Arc::new(AtomicUsize::new(1)),
+            create_count: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Overwrite every object with identical content under a new ETag.
+    pub fn replace(&self) {
+        self.version.fetch_add(1, Ordering::SeqCst);
+    }
+
+    /// Number of readers created (one per slide open or revalidation).
+    pub fn create_count(&self) -> usize {
+        self.create_count.load(Ordering::SeqCst)
+    }
+}
+
+/// Reader pinned to the version current when it was created.
+pub struct VersionedReader {
+    data: Bytes,
+    identifier: String,
+    version: usize,
+    current: Arc<AtomicUsize>,
+    etag: String,
+}
+
+#[async_trait]
+impl RangeReader for VersionedReader {
+    async fn read_exact_at(&self, offset: u64, len: usize) -> Result<Bytes, IoError> {
+        if self.current.load(Ordering::SeqCst) != self.version {
+            return Err(IoError::ObjectChanged(self.identifier.clone()));
+        }
+        let start = offset as usize;
+        let end = start + len;
+        if end > self.data.len() {
+            return Err(IoError::RangeOutOfBounds {
+                offset,
+                requested: len as u64,
+                size: self.data.len() as u64,
+            });
+        }
+        Ok(self.data.slice(start..end))
+    }
+
+    fn size(&self) -> u64 {
+        self.data.len() as u64
+    }
+
+    fn identifier(&self) -> &str {
+        &self.identifier
+    }
+
+    fn etag(&self) -> Option<&str> {
+        Some(&self.etag)
+    }
+}
+
+#[async_trait]
+impl SlideSource for VersionedSlideSource {
+    type Reader = VersionedReader;
+
+    async fn create_reader(&self, slide_id: &str) -> Result<Self::Reader, IoError> {
+        self.create_count.fetch_add(1, Ordering::SeqCst);
+        let version = self.version.load(Ordering::SeqCst);
+        Ok(VersionedReader {
+            data: self.data.clone(),
+            identifier: format!("mock://{}", slide_id),
+            version,
+            current: self.version.clone(),
+            etag: format!("\"v{}\"", version),
+        })
+    }
+}
+
 // =============================================================================
 // Test JPEG Creation
 // =============================================================================
@@ -792,6 +918,50 @@ pub fn create_tiff_with_jpeg_tile_endian(byte_order: ByteOrderType) -> Vec<u8> {
     data
 }
 
+/// Create a minimal TIFF like [`create_tiff_with_jpeg_tile`], calibrated
+/// at `mpp` microns per pixel.
+pub fn create_calibrated_tiff(mpp: f64) -> Vec<u8> {
+    let mut data = create_tiff_with_jpeg_tile();
+
+    // XResolution and YResolution rationals (pixels per centimeter), placed
+    // after the TileByteCounts array
+    let resolution_offset = 600u32;
+    let pixels_per_cm = (10_000.0 / mpp).round() as u32;
+    for offset in [resolution_offset, resolution_offset + 8] {
+        let offset = offset as usize;
+        data[offset..offset + 4].copy_from_slice(&pixels_per_cm.to_le_bytes());
+        data[offset + 4..offset + 8].copy_from_slice(&1u32.to_le_bytes());
+    }
+
+    // Rewrite the IFD with the resolution tags, sorted by tag number
+    let entries: [(u16, u16, u32, u32); 12] = [
+        (256, 4, 1, 2048),                  // ImageWidth
+        (257, 4, 1, 1536),                  // ImageLength
+        (258, 3, 1, 8),                     // BitsPerSample
+        (259, 3, 1, 7),                     // Compression = JPEG
+        (277, 3, 1, 1),                     // SamplesPerPixel
+        (282, 5, 1, resolution_offset),     // XResolution
+        (283, 5, 1, resolution_offset + 8), // YResolution
+        (296, 3, 1, 3),                     // ResolutionUnit = centimeter
+        (322, 4, 1, 256),                   // TileWidth
+        (323, 4, 1, 256),                   // TileLength
+        (324, 4, 48, 200),                  // TileOffsets
+        (325, 4, 48, 400),                  // TileByteCounts
+    ];
+    data[8..10].copy_from_slice(&(entries.len() as u16).to_le_bytes());
+    let mut offset = 10;
+    for (tag, typ, count, value) in entries {
+        data[offset..offset + 2].copy_from_slice(&tag.to_le_bytes());
+        data[offset + 2..offset + 4].copy_from_slice(&typ.to_le_bytes());
+        data[offset + 4..offset + 8].copy_from_slice(&count.to_le_bytes());
+        data[offset + 8..offset + 12].copy_from_slice(&value.to_le_bytes());
+        offset += 12;
+    }
+    data[offset..offset + 4].copy_from_slice(&0u32.to_le_bytes());
+
+    data
+}
+
 /// Create a BigTIFF file with JPEG tile data.
 pub fn create_bigtiff_with_jpeg_tile() -> Vec<u8> {
     let jpeg_data = create_test_jpeg(256, 256, 90);
@@ -869,11 +1039,11 @@ pub fn create_bigtiff_with_jpeg_tile() -> Vec<u8> {
     data
 }
 
-/// Create a TIFF file with unsupported LZW compression.
-pub fn create_tiff_with_lzw_compression() -> Vec<u8> {
+/// Create a TIFF file with unsupported old-style JPEG compression.
+pub fn create_tiff_with_unsupported_compression() -> Vec<u8> {
     let mut data = create_tiff_with_jpeg_tile();
 
-    // Change compression tag value from 7 (JPEG) to 5 (LZW)
+    // Change compression tag value from 7 (JPEG) to 6 (Old JPEG)
     // The compression entry is at offset 10 + 3*12 + 8 = 10 + 36 + 8 = 54
     // Actually, let's find it more carefully...
     // Entry format: tag(2) + type(2) + count(4) + value(4) = 12 bytes
@@ -890,7 +1060,31 @@ pub fn create_tiff_with_lzw_compression() -> Vec<u8> {
     // Entry 3: Compression (tag 259) at offset 10 + 3*12 = 46
     // Value/offset field at 46 + 8 = 54
 
-    data[54] = 5; // LZW compression
+    data[54] = 6; // Old JPEG compression
+
+    data
+}
+
+/// Create a TIFF file whose tiles are Deflate-compressed 8-bit grayscale.
+pub fn create_tiff_with_deflate_tile() -> Vec<u8> {
+    let mut data = create_tiff_with_jpeg_tile();
+
+    // Compression value field (see create_tiff_with_unsupported_compression)
+    data[54] = 8; // Deflate compression
+
+    // Horizontal gradient, one 256x256 tile
+    let pixels: Vec<u8> = (0..256 * 256).map(|i| (i % 256) as u8).collect();
+    let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(&pixels).unwrap();
+    let tile = encoder.finish().unwrap();
+
+    // Replace the JPEG tile data at offset 1000 and its 48 byte counts at 400
+    assert!(1000 + tile.len() <= data.len());
+    data[1000..1000 + tile.len()].copy_from_slice(&tile);
+    for i in 0..48 {
+        let at = 400 + i * 4;
+        data[at..at + 4].copy_from_slice(&(tile.len() as u32).to_le_bytes());
+    }
 
     data
 }
@@ -952,6 +1146,81 @@ pub fn create_strip_tiff() -> Vec<u8> {
     data
 }
 
+/// Create a 512x256 RGB TIFF of two identical 256x256 JPEG tiles with an
+/// Orientation tag and, when non-empty, an ICCProfile tag.
+///
+/// Each tile is [`create_test_rgb_jpeg`]: red follows x and green follows y.
+#[allow(dead_code)]
+pub fn create_oriented_tiff(orientation: u16, icc_profile: &[u8]) -> Vec<u8> {
+    let jpeg_data = create_test_rgb_jpeg(256, 256, 95);
+    let entry_count: u16 = if icc_profile.is_empty() { 10 } else { 11 };
+
+    let tile_offsets_offset = 200u32;
+    let tile_byte_counts_offset = 208u32;
+    let icc_offset = 216u32;
+    let tile_data_offset = icc_offset + icc_profile.len() as u32;
+
+    let mut data = vec![0u8; tile_data_offset as usize];
+    data.extend_from_slice(&jpeg_data);
+
+    // Header
+    data[0] = b'I';
+    data[1] = b'I';
+    data[2..4].copy_from_slice(&42u16.to_le_bytes());
+    data[4..8].copy_from_slice(&8u32.to_le_bytes());
+
+    // IFD at offset 8
+    data[8..10].copy_from_slice(&entry_count.to_le_bytes());
+
+    let mut offset = 10;
+
+    let write_entry =
+        |data: &mut [u8], offset: &mut usize, tag: u16, typ: u16, count: u32, value: u32| {
+            data[*offset..*offset + 2].copy_from_slice(&tag.to_le_bytes());
+            data[*offset + 2..*offset + 4].copy_from_slice(&typ.to_le_bytes());
+            data[*offset + 4..*offset + 8].copy_from_slice(&count.to_le_bytes());
+            data[*offset + 8..*offset + 12].copy_from_slice(&value.to_le_bytes());
+            *offset += 12;
+        };
+
+    // Entries sorted by tag
+    write_entry(&mut data, &mut offset, 256, 4, 1, 512); // ImageWidth
+    write_entry(&mut data, &mut offset, 257, 4, 1, 256); // ImageLength
+    write_entry(&mut data, &mut offset, 258, 3, 1, 8); // BitsPerSample
+    write_entry(&mut data, &mut offset, 259, 3, 1, 7); // Compression = JPEG
+    write_entry(&mut data, &mut offset, 274, 3, 1, orientation as u32); // Orientation
+    write_entry(&mut data, &mut offset, 277, 3, 1, 3); // SamplesPerPixel
+    write_entry(&mut data, &mut offset, 322, 4, 1, 256); // TileWidth
+    write_entry(&mut data, &mut offset, 323, 4, 1, 256); // TileLength
+    write_entry(&mut data, &mut offset, 324, 4, 2, tile_offsets_offset); // TileOffsets
+    write_entry(&mut data, &mut offset, 325, 4, 2, tile_byte_counts_offset); // TileByteCounts
+    if !icc_profile.is_empty() {
+        // ICCProfile (UNDEFINED bytes)
+        write_entry(
+            &mut data,
+            &mut offset,
+            34675,
+            7,
+            icc_profile.len() as u32,
+            icc_offset,
+        );
+    }
+
+    // Next IFD offset
+    data[offset..offset + 4].copy_from_slice(&0u32.to_le_bytes());
+
+    // Both tiles point to the same JPEG data
+    for i in 0..2 {
+        let at = tile_offsets_offset as usize + i * 4;
+        data[at..at + 4].copy_from_slice(&tile_data_offset.to_le_bytes());
+        let at = tile_byte_counts_offset as usize + i * 4;
+        data[at..at + 4].copy_from_slice(&(jpeg_data.len() as u32).to_le_bytes());
+    }
+    data[icc_offset as usize..tile_data_offset as usize].copy_from_slice(icc_profile);
+
+    data
+}
+
 // =============================================================================
 // SVS-specific Test Data
 // =============================================================================